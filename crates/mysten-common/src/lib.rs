@@ -1,4 +1,6 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod lifecycle;
+pub mod memory_budget;
 pub mod sync;
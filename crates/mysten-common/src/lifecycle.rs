@@ -0,0 +1,182 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use parking_lot::Mutex;
+use std::fmt;
+use std::sync::Arc;
+
+/// The states of a component of a node, from its start to its shutdown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComponentState {
+    Starting,
+    Ready,
+    Draining,
+    Stopped,
+}
+
+impl fmt::Display for ComponentState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ComponentState::Starting => "starting",
+            ComponentState::Ready => "ready",
+            ComponentState::Draining => "draining",
+            ComponentState::Stopped => "stopped",
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Component {
+    name: &'static str,
+    dependencies: Vec<&'static str>,
+    state: ComponentState,
+}
+
+/// Tracks the states of the components of a node, and the components each one depends on: a
+/// component is stopped before the components it depends on, so that it never runs without them.
+///
+/// A component can only depend on components registered before it, which rules out cycles and
+/// makes the reverse order of registration a valid order of shutdown.
+#[derive(Clone, Debug, Default)]
+pub struct Lifecycle {
+    components: Arc<Mutex<Vec<Component>>>,
+}
+
+impl Lifecycle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a component in the `Starting` state. Registering a component again, e.g. when
+    /// it is restarted at a new epoch, keeps its place in the order of shutdown.
+    pub fn register(&self, name: &'static str, dependencies: &[&'static str]) {
+        let mut components = self.components.lock();
+        let position = components
+            .iter()
+            .position(|c| c.name == name)
+            .unwrap_or(components.len());
+        for dependency in dependencies {
+            assert!(
+                components[..position].iter().any(|c| c.name == *dependency),
+                "Component {name} depends on {dependency}, which must be registered before it"
+            );
+        }
+        let component = Component {
+            name,
+            dependencies: dependencies.to_vec(),
+            state: ComponentState::Starting,
+        };
+        if position == components.len() {
+            components.push(component);
+        } else {
+            components[position] = component;
+        }
+    }
+
+    /// Removes a stopped component which is not restarted, e.g. when the node leaves the
+    /// committee. The components depending on it must be removed first.
+    pub fn deregister(&self, name: &str) {
+        let mut components = self.components.lock();
+        assert!(
+            components.iter().all(|c| !c.dependencies.contains(&name)),
+            "Components still depend on {name}"
+        );
+        components.retain(|c| c.name != name);
+    }
+
+    /// Sets the state of a registered component. Unknown components are ignored.
+    pub fn set_state(&self, name: &str, state: ComponentState) {
+        if let Some(component) = self.components.lock().iter_mut().find(|c| c.name == name) {
+            component.state = state;
+        }
+    }
+
+    pub fn state(&self, name: &str) -> Option<ComponentState> {
+        self.components
+            .lock()
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.state)
+    }
+
+    /// The states of the components and their dependencies, in the order of registration.
+    pub fn states(&self) -> Vec<(&'static str, ComponentState, Vec<&'static str>)> {
+        self.components
+            .lock()
+            .iter()
+            .map(|c| (c.name, c.state, c.dependencies.clone()))
+            .collect()
+    }
+
+    /// Whether every component registered is ready.
+    pub fn is_ready(&self) -> bool {
+        self.components
+            .lock()
+            .iter()
+            .all(|c| c.state == ComponentState::Ready)
+    }
+
+    /// The given components in the order they must be stopped in: each one before the components
+    /// it depends on, directly or not. Components never registered are left out.
+    pub fn shutdown_order(&self, names: &[&'static str]) -> Vec<&'static str> {
+        self.components
+            .lock()
+            .iter()
+            .rev()
+            .map(|c| c.name)
+            .filter(|name| names.contains(name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComponentState, Lifecycle};
+
+    #[test]
+    fn test_states_and_shutdown_order() {
+        let lifecycle = Lifecycle::new();
+        lifecycle.register("network", &[]);
+        lifecycle.register("consensus", &["network"]);
+        lifecycle.register("checkpoints", &["consensus"]);
+        assert_eq!(lifecycle.state("consensus"), Some(ComponentState::Starting));
+        assert!(!lifecycle.is_ready());
+
+        for name in ["network", "consensus", "checkpoints"] {
+            lifecycle.set_state(name, ComponentState::Ready);
+        }
+        assert!(lifecycle.is_ready());
+        assert_eq!(
+            lifecycle.shutdown_order(&["network", "checkpoints", "consensus", "unknown"]),
+            vec!["checkpoints", "consensus", "network"]
+        );
+
+        // A restarted component keeps its place.
+        lifecycle.set_state("consensus", ComponentState::Stopped);
+        lifecycle.register("consensus", &["network"]);
+        assert_eq!(lifecycle.state("consensus"), Some(ComponentState::Starting));
+        assert_eq!(
+            lifecycle.shutdown_order(&["consensus", "checkpoints"]),
+            vec!["checkpoints", "consensus"]
+        );
+
+        lifecycle.deregister("checkpoints");
+        assert_eq!(lifecycle.state("checkpoints"), None);
+        assert_eq!(lifecycle.states().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Components still depend on network")]
+    fn test_deregister_dependency() {
+        let lifecycle = Lifecycle::new();
+        lifecycle.register("network", &[]);
+        lifecycle.register("consensus", &["network"]);
+        lifecycle.deregister("network");
+    }
+
+    #[test]
+    #[should_panic(expected = "must be registered before it")]
+    fn test_unknown_dependency() {
+        Lifecycle::new().register("consensus", &["network"]);
+    }
+}
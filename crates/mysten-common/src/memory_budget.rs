@@ -0,0 +1,272 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Accounting of the memory held by the components of a node, against a soft budget shared by
+//! all of them.
+//!
+//! The components buffering data, e.g. the queues of the workers or the certificates suspended
+//! by the primary, register an account with the budget and reserve the memory they hold from it.
+//! The budget is soft: a reservation is never refused once under way, but while the budget is
+//! exceeded, the components holding back their inputs wait, or reject the inputs they can refuse,
+//! so that a single component under load cannot exhaust the memory of the whole process.
+
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+static GLOBAL: OnceCell<MemoryBudget> = OnceCell::new();
+
+/// Sets the soft limit of the budget of the process, in bytes, or no limit. The budget can only
+/// be set once, before the components of the node register with it: the budget already set is
+/// returned otherwise.
+pub fn init_global(soft_limit: Option<usize>) -> &'static MemoryBudget {
+    GLOBAL.get_or_init(|| MemoryBudget::new(soft_limit))
+}
+
+/// The budget of the process, without limit unless set with [init_global].
+pub fn global() -> &'static MemoryBudget {
+    GLOBAL.get_or_init(|| MemoryBudget::new(None))
+}
+
+#[derive(Clone)]
+pub struct MemoryBudget {
+    inner: Arc<BudgetInner>,
+}
+
+struct BudgetInner {
+    soft_limit: Option<usize>,
+    used: AtomicUsize,
+    accounts: Mutex<BTreeMap<&'static str, Arc<AtomicUsize>>>,
+    // Notified when memory is released while the budget is not exceeded.
+    released: Notify,
+}
+
+impl MemoryBudget {
+    pub fn new(soft_limit: Option<usize>) -> Self {
+        Self {
+            inner: Arc::new(BudgetInner {
+                soft_limit,
+                used: AtomicUsize::new(0),
+                accounts: Mutex::new(BTreeMap::new()),
+                released: Notify::new(),
+            }),
+        }
+    }
+
+    pub fn soft_limit(&self) -> Option<usize> {
+        self.inner.soft_limit
+    }
+
+    /// The memory reserved by all the components, in bytes.
+    pub fn used(&self) -> usize {
+        self.inner.used.load(Ordering::Acquire)
+    }
+
+    pub fn is_within_budget(&self) -> bool {
+        self.inner
+            .soft_limit
+            .map_or(true, |soft_limit| self.used() < soft_limit)
+    }
+
+    /// Waits until the budget is not exceeded.
+    pub async fn wait_for_budget(&self) {
+        loop {
+            let released = self.inner.released.notified();
+            tokio::pin!(released);
+            // Registered before checking the budget, not to miss a release in between.
+            released.as_mut().enable();
+            if self.is_within_budget() {
+                return;
+            }
+            released.await;
+        }
+    }
+
+    /// Returns the account of the component, shared by all the accounts registered under the
+    /// same name.
+    pub fn register(&self, component: &'static str) -> MemoryAccount {
+        let used = self
+            .inner
+            .accounts
+            .lock()
+            .entry(component)
+            .or_default()
+            .clone();
+        MemoryAccount {
+            budget: self.clone(),
+            used,
+        }
+    }
+
+    /// The memory reserved by each of the components, in bytes.
+    pub fn usage(&self) -> Vec<(&'static str, usize)> {
+        self.inner
+            .accounts
+            .lock()
+            .iter()
+            .map(|(component, used)| (*component, used.load(Ordering::Acquire)))
+            .collect()
+    }
+}
+
+impl fmt::Debug for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryBudget")
+            .field("soft_limit", &self.soft_limit())
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
+/// The account of a component with the budget.
+#[derive(Clone)]
+pub struct MemoryAccount {
+    budget: MemoryBudget,
+    used: Arc<AtomicUsize>,
+}
+
+impl MemoryAccount {
+    pub fn budget(&self) -> &MemoryBudget {
+        &self.budget
+    }
+
+    /// The memory reserved through the account, in bytes.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Acquire)
+    }
+
+    pub fn is_within_budget(&self) -> bool {
+        self.budget.is_within_budget()
+    }
+
+    /// Reserves the memory even if the budget is exceeded, for memory which cannot be refused.
+    pub fn reserve(&self, bytes: usize) -> MemoryReservation {
+        let mut reservation = MemoryReservation {
+            account: self.clone(),
+            bytes: 0,
+        };
+        reservation.grow(bytes);
+        reservation
+    }
+
+    /// Reserves the memory, unless the budget is exceeded.
+    pub fn try_reserve(&self, bytes: usize) -> Option<MemoryReservation> {
+        self.is_within_budget().then(|| self.reserve(bytes))
+    }
+
+    fn add(&self, bytes: usize) {
+        self.used.fetch_add(bytes, Ordering::AcqRel);
+        self.budget.inner.used.fetch_add(bytes, Ordering::AcqRel);
+    }
+
+    fn sub(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Ordering::AcqRel);
+        self.budget.inner.used.fetch_sub(bytes, Ordering::AcqRel);
+        if bytes > 0 && self.budget.is_within_budget() {
+            self.budget.inner.released.notify_waiters();
+        }
+    }
+}
+
+/// Memory reserved from the budget, released when dropped.
+pub struct MemoryReservation {
+    account: MemoryAccount,
+    bytes: usize,
+}
+
+impl MemoryReservation {
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn grow(&mut self, bytes: usize) {
+        self.account.add(bytes);
+        self.bytes += bytes;
+    }
+
+    pub fn shrink(&mut self, bytes: usize) {
+        let bytes = bytes.min(self.bytes);
+        self.account.sub(bytes);
+        self.bytes -= bytes;
+    }
+
+    pub fn resize(&mut self, bytes: usize) {
+        if bytes > self.bytes {
+            self.grow(bytes - self.bytes);
+        } else {
+            self.shrink(self.bytes - bytes);
+        }
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.account.sub(self.bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryBudget;
+    use std::time::Duration;
+
+    #[test]
+    fn test_reservations_are_accounted() {
+        let budget = MemoryBudget::new(Some(100));
+        let batches = budget.register("batches");
+        let certificates = budget.register("certificates");
+
+        let mut batch = batches.reserve(30);
+        let certificate = certificates.try_reserve(50).unwrap();
+        assert_eq!(budget.used(), 80);
+        assert!(budget.is_within_budget());
+
+        // The budget is soft: the reservation crossing the limit is admitted.
+        batch.grow(40);
+        assert_eq!(budget.used(), 120);
+        assert!(!budget.is_within_budget());
+        assert!(certificates.try_reserve(1).is_none());
+
+        // The accounts registered under the same name are shared.
+        assert_eq!(budget.register("batches").used(), 70);
+        assert_eq!(budget.usage(), vec![("batches", 70), ("certificates", 50)]);
+
+        drop(certificate);
+        batch.resize(10);
+        assert_eq!(budget.used(), 10);
+        assert!(budget.is_within_budget());
+        drop(batch);
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn test_no_limit() {
+        let budget = MemoryBudget::new(None);
+        let account = budget.register("responses");
+        let _reservation = account.reserve(usize::MAX / 2);
+        assert!(account.try_reserve(1).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_budget() {
+        let budget = MemoryBudget::new(Some(100));
+        let reservation = budget.register("batches").reserve(200);
+
+        let waiter = tokio::spawn({
+            let budget = budget.clone();
+            async move { budget.wait_for_budget().await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        drop(reservation);
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -38,6 +38,7 @@ use tower_http::trace::{DefaultMakeSpan, DefaultOnBodyChunk, DefaultOnEos, Trace
 pub struct ServerBuilder<M: MetricsCallbackProvider = DefaultMetricsCallbackProvider> {
     router: Router<WrapperService<M>>,
     health_reporter: tonic_health::server::HealthReporter,
+    file_descriptor_sets: Vec<&'static [u8]>,
 }
 
 type AddPathToHeaderFunction = fn(&Request<Body>) -> Option<HeaderValue>;
@@ -133,6 +134,7 @@ impl<M: MetricsCallbackProvider> ServerBuilder<M> {
         Self {
             router,
             health_reporter,
+            file_descriptor_sets: vec![tonic_health::proto::GRPC_HEALTH_V1_FILE_DESCRIPTOR_SET],
         }
     }
 
@@ -154,9 +156,29 @@ impl<M: MetricsCallbackProvider> ServerBuilder<M> {
         self
     }
 
+    /// Registers the encoded file descriptor set of a service, so that the service can be
+    /// discovered through the server reflection service.
+    pub fn register_file_descriptor_set(mut self, file_descriptor_set: &'static [u8]) -> Self {
+        self.file_descriptor_sets.push(file_descriptor_set);
+        self
+    }
+
     pub async fn bind(self, addr: &Multiaddr) -> Result<Server> {
         let mut iter = addr.iter();
 
+        let reflection_service = self
+            .file_descriptor_sets
+            .into_iter()
+            .fold(
+                tonic_reflection::server::Builder::configure(),
+                |builder, file_descriptor_set| {
+                    builder.register_encoded_file_descriptor_set(file_descriptor_set)
+                },
+            )
+            .build()
+            .map_err(|e| eyre!("invalid file descriptor set: {e}"))?;
+        let router = self.router.add_service(reflection_service);
+
         let (tx_cancellation, rx_cancellation) = tokio::sync::oneshot::channel();
         let rx_cancellation = rx_cancellation.map(|_| ());
         let (local_addr, server): (Multiaddr, BoxFuture<(), tonic::transport::Error>) =
@@ -166,30 +188,24 @@ impl<M: MetricsCallbackProvider> ServerBuilder<M> {
                     let (local_addr, incoming) =
                         tcp_listener_and_update_multiaddr(addr, (dns_name.as_ref(), tcp_port))
                             .await?;
-                    let server = Box::pin(
-                        self.router
-                            .serve_with_incoming_shutdown(incoming, rx_cancellation),
-                    );
+                    let server =
+                        Box::pin(router.serve_with_incoming_shutdown(incoming, rx_cancellation));
                     (local_addr, server)
                 }
                 Protocol::Ip4(_) => {
                     let (socket_addr, _http_or_https) = parse_ip4(addr)?;
                     let (local_addr, incoming) =
                         tcp_listener_and_update_multiaddr(addr, socket_addr).await?;
-                    let server = Box::pin(
-                        self.router
-                            .serve_with_incoming_shutdown(incoming, rx_cancellation),
-                    );
+                    let server =
+                        Box::pin(router.serve_with_incoming_shutdown(incoming, rx_cancellation));
                     (local_addr, server)
                 }
                 Protocol::Ip6(_) => {
                     let (socket_addr, _http_or_https) = parse_ip6(addr)?;
                     let (local_addr, incoming) =
                         tcp_listener_and_update_multiaddr(addr, socket_addr).await?;
-                    let server = Box::pin(
-                        self.router
-                            .serve_with_incoming_shutdown(incoming, rx_cancellation),
-                    );
+                    let server =
+                        Box::pin(router.serve_with_incoming_shutdown(incoming, rx_cancellation));
                     (local_addr, server)
                 }
                 // Protocol::Memory(_) => todo!(),
@@ -199,10 +215,8 @@ impl<M: MetricsCallbackProvider> ServerBuilder<M> {
                     let uds = tokio::net::UnixListener::bind(path.as_ref())?;
                     let uds_stream = tokio_stream::wrappers::UnixListenerStream::new(uds);
                     let local_addr = addr.to_owned();
-                    let server = Box::pin(
-                        self.router
-                            .serve_with_incoming_shutdown(uds_stream, rx_cancellation),
-                    );
+                    let server =
+                        Box::pin(router.serve_with_incoming_shutdown(uds_stream, rx_cancellation));
                     (local_addr, server)
                 }
                 unsupported => return Err(eyre!("unsupported protocol {unsupported}")),
@@ -418,6 +432,43 @@ mod test {
         assert!(metrics.metrics_called.lock().unwrap().deref());
     }
 
+    #[tokio::test]
+    async fn test_reflection_lists_services() {
+        use tonic_reflection::proto::{
+            server_reflection_client::ServerReflectionClient,
+            server_reflection_request::MessageRequest, server_reflection_response::MessageResponse,
+            ServerReflectionRequest,
+        };
+
+        let address: Multiaddr = "/ip4/127.0.0.1/tcp/0/http".parse().unwrap();
+        let config = Config::new();
+        let mut server = config.server_builder().bind(&address).await.unwrap();
+        let address = server.local_addr().to_owned();
+        let cancel_handle = server.take_cancel_handle().unwrap();
+        let server_handle = tokio::spawn(server.serve());
+        let channel = config.connect(&address).await.unwrap();
+        let mut client = ServerReflectionClient::new(channel);
+
+        let request = ServerReflectionRequest {
+            host: "".to_owned(),
+            message_request: Some(MessageRequest::ListServices("".to_owned())),
+        };
+        let mut responses = client
+            .server_reflection_info(tokio_stream::once(request))
+            .await
+            .unwrap()
+            .into_inner();
+        let response = responses.message().await.unwrap().unwrap();
+        let Some(MessageResponse::ListServicesResponse(list)) = response.message_response else {
+            panic!("unexpected reflection response");
+        };
+        let services: Vec<_> = list.service.into_iter().map(|s| s.name).collect();
+        assert!(services.contains(&"grpc.health.v1.Health".to_owned()));
+
+        cancel_handle.send(()).unwrap();
+        server_handle.await.unwrap().unwrap();
+    }
+
     async fn test_multiaddr(address: Multiaddr) {
         let config = Config::new();
         let mut server = config.server_builder().bind(&address).await.unwrap();
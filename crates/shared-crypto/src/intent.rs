@@ -70,6 +70,7 @@ pub enum IntentScope {
     SenderSignedTransaction = 4, // Used for an authority signature on a user signed transaction.
     ProofOfPossession = 5, // Used as a signature representing an authority's proof of possesion of its authority protocol key.
     HeaderDigest = 6,      // Used for narwhal authority signature on header digest.
+    ValidatorReport = 7,   // Used for an authority signature on its operational report.
 }
 
 impl TryFrom<u8> for IntentScope {
@@ -82,6 +83,7 @@ impl TryFrom<u8> for IntentScope {
             3 => Ok(Self::PersonalMessage),
             4 => Ok(Self::SenderSignedTransaction),
             5 => Ok(Self::ProofOfPossession),
+            7 => Ok(Self::ValidatorReport),
             _ => Err(eyre!("Invalid IntentScope")),
         }
     }
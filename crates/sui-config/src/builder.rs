@@ -413,6 +413,7 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
                     db_path: consensus_db_path,
                     internal_worker_address,
                     max_pending_transactions: None,
+                    narwhal_worker_standby: false,
                     narwhal_config: ConsensusParameters {
                         network_admin_server: match self.validator_ip_sel {
                             ValidatorIpSelection::Simulator => NetworkAdminServerParameters {
@@ -459,6 +460,8 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
                     protocol_key_pair: AuthorityKeyPairWithPath::new(
                         validator.genesis_info.key_pair,
                     ),
+                    protocol_key_signer: None,
+                    next_protocol_key_pair: None,
                     network_key_pair: KeyPairWithPath::new(SuiKeyPair::Ed25519(
                         validator.genesis_info.network_key_pair,
                     )),
@@ -475,6 +478,7 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
                         ValidatorIpSelection::Simulator => 8888,
                         _ => utils::get_available_port("127.0.0.1"),
                     },
+                    admin_interface_token: None,
                     json_rpc_address: utils::available_local_socket_address(),
                     consensus_config: Some(consensus_config),
                     enable_event_processing: false,
@@ -490,6 +494,11 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
                     supported_protocol_versions: Some(supported_protocol_versions),
                     db_checkpoint_config: self.db_checkpoint_config.clone(),
                     indirect_objects_threshold: usize::MAX,
+                    archive_mode: false,
+                    resubmit_transactions_across_epochs: true,
+                    quorum_driver_journal: false,
+                    memory_budget_bytes: None,
+                    validator_report: None,
                 }
             })
             .collect();
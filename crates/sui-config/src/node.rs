@@ -13,6 +13,7 @@ use serde_with::serde_as;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use std::usize;
 use sui_keys::keypair_file::{read_authority_keypair_from_file, read_keypair_from_file};
 use sui_protocol_config::SupportedProtocolVersions;
@@ -35,6 +36,20 @@ pub const DEFAULT_GRPC_CONCURRENCY_LIMIT: usize = 20000000000;
 pub struct NodeConfig {
     #[serde(default = "default_authority_key_pair")]
     pub protocol_key_pair: AuthorityKeyPairWithPath,
+    /// The external signer holding the protocol key, e.g. fronting a KMS or a PKCS#11 HSM, in
+    /// which case the protocol key pair is not used.
+    ///
+    /// If unspecified, the protocol messages are signed with the protocol key pair.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_key_signer: Option<RemoteSignerConfig>,
+    /// The protocol key pair the validator rotates to, once its new protocol public key, submitted
+    /// on chain with `update_validator_next_epoch_protocol_pubkey`, is in the committee of an
+    /// epoch. The key file is read at that epoch change, so it may be written while the node is
+    /// running.
+    ///
+    /// If unspecified, the protocol key of the validator is never rotated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_protocol_key_pair: Option<AuthorityKeyPairWithPath>,
     #[serde(default = "default_key_pair")]
     pub worker_key_pair: KeyPairWithPath,
     #[serde(default = "default_key_pair")]
@@ -52,6 +67,12 @@ pub struct NodeConfig {
     pub metrics_address: SocketAddr,
     #[serde(default = "default_admin_interface_port")]
     pub admin_interface_port: u16,
+    /// The bearer token authenticating the requests to the admin endpoints changing the node,
+    /// e.g. tuning its consensus parameters.
+    ///
+    /// If unspecified, these endpoints are disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_interface_token: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub consensus_config: Option<ConsensusConfig>,
@@ -96,6 +117,40 @@ pub struct NodeConfig {
 
     #[serde(default)]
     pub indirect_objects_threshold: usize,
+
+    /// Whether the node archives every version of the objects: the objects and transactions are
+    /// never pruned, and the versions of the objects are indexed by checkpoint, so that explorers
+    /// and audit tooling can read any object efficiently at any past version or checkpoint.
+    ///
+    /// The index covers the checkpoints executed since archive mode was enabled, so that an
+    /// archive node is best synced from genesis. The older checkpoints are read without it.
+    #[serde(default)]
+    pub archive_mode: bool,
+
+    /// Whether the transaction orchestrator of a fullnode resubmits the transactions dropped by
+    /// the validators at the end of an epoch once the next epoch starts, instead of failing them.
+    #[serde(default = "bool_true")]
+    pub resubmit_transactions_across_epochs: bool,
+
+    /// Whether the quorum driver of a fullnode journals the transactions it accepts from the
+    /// clients and their progress, to drive the ones in flight again after a restart.
+    #[serde(default)]
+    pub quorum_driver_journal: bool,
+
+    /// The soft limit of the memory held by the buffers of the node, in bytes: the queues of the
+    /// workers, the certificates suspended by the primary, the DAG of consensus, and the responses
+    /// of the JSON-RPC being built. While it is exceeded, the workers stop taking transactions,
+    /// the primary stops suspending certificates and the JSON-RPC refuses the larger requests.
+    ///
+    /// If unspecified, the memory is not limited.
+    #[serde(default)]
+    pub memory_budget_bytes: Option<usize>,
+
+    /// If set, the validator periodically signs a report of its operations with its protocol key,
+    /// serves the latest one through its gRPC interface, and publishes it to the configured
+    /// endpoint, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator_report: Option<ValidatorReportConfig>,
 }
 
 fn default_authority_store_pruning_config() -> AuthorityStorePruningConfig {
@@ -180,7 +235,10 @@ impl NodeConfig {
     }
 
     pub fn protocol_public_key(&self) -> AuthorityPublicKeyBytes {
-        self.protocol_key_pair().public().into()
+        match &self.protocol_key_signer {
+            Some(signer) => signer.public_key,
+            None => self.protocol_key_pair().public().into(),
+        }
     }
 
     pub fn sui_address(&self) -> SuiAddress {
@@ -224,6 +282,13 @@ pub struct ConsensusConfig {
     // Default to 100_000.
     pub max_pending_transactions: Option<usize>,
 
+    // Whether the node stands by for the Narwhal workers of the validator, run by another node
+    // with the same keys: it runs no primary, and its workers replicate the batches of theirs
+    // to start in their place once they stop responding. Requires the replication log of the
+    // workers replicated to be enabled.
+    #[serde(default)]
+    pub narwhal_worker_standby: bool,
+
     pub narwhal_config: ConsensusParameters,
 }
 
@@ -261,6 +326,14 @@ pub struct CheckpointExecutorConfig {
     /// If unspecified, this will default to `10`.
     #[serde(default = "default_local_execution_timeout_sec")]
     pub local_execution_timeout_sec: u64,
+
+    /// Upper bound on the number of transactions of a checkpoint that can be concurrently
+    /// executed. The transactions of a checkpoint are executed in parallel once the transactions
+    /// they depend on, which accessed the same objects before them, are executed.
+    ///
+    /// If unspecified, this will default to the number of CPUs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_execution_max_concurrency: Option<usize>,
 }
 
 fn default_checkpoint_execution_max_concurrency() -> usize {
@@ -276,6 +349,7 @@ impl Default for CheckpointExecutorConfig {
         Self {
             checkpoint_execution_max_concurrency: default_checkpoint_execution_max_concurrency(),
             local_execution_timeout_sec: default_local_execution_timeout_sec(),
+            transaction_execution_max_concurrency: None,
         }
     }
 }
@@ -285,10 +359,21 @@ impl Default for CheckpointExecutorConfig {
 pub struct AuthorityStorePruningConfig {
     pub num_latest_epoch_dbs_to_retain: usize,
     pub epoch_db_pruning_period_secs: u64,
+    /// The number of epochs of object versions to keep, 0 keeping only the latest versions.
     pub num_epochs_to_retain: u64,
     pub max_checkpoints_in_batch: usize,
     pub max_transactions_in_batch: usize,
     pub use_range_deletion: bool,
+    /// The number of epochs of transactions, effects and events to keep, all of them when None.
+    /// The transactions of a checkpoint are only pruned once the object versions they modified
+    /// are, as pruning the objects reads the effects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_epochs_to_retain_for_transactions: Option<u64>,
+    /// The period of the compaction of the pruned tables in the background, which reclaims the
+    /// space of the pruned entries sooner than the compactions scheduled by RocksDB. Unset or
+    /// zero disables them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compaction_period_secs: Option<u64>,
 }
 
 impl Default for AuthorityStorePruningConfig {
@@ -300,6 +385,8 @@ impl Default for AuthorityStorePruningConfig {
             max_checkpoints_in_batch: 200,
             max_transactions_in_batch: 1000,
             use_range_deletion: true,
+            num_epochs_to_retain_for_transactions: None,
+            compaction_period_secs: None,
         }
     }
 }
@@ -313,6 +400,16 @@ impl AuthorityStorePruningConfig {
             max_checkpoints_in_batch: 200,
             max_transactions_in_batch: 1000,
             use_range_deletion: true,
+            num_epochs_to_retain_for_transactions: None,
+            compaction_period_secs: None,
+        }
+    }
+    /// The configuration retaining every object version and transaction, for archive nodes.
+    pub fn for_archive_mode(self) -> Self {
+        Self {
+            num_epochs_to_retain: u64::MAX,
+            num_epochs_to_retain_for_transactions: None,
+            ..self
         }
     }
     pub fn fullnode_config() -> Self {
@@ -323,6 +420,8 @@ impl AuthorityStorePruningConfig {
             max_checkpoints_in_batch: 200,
             max_transactions_in_batch: 1000,
             use_range_deletion: true,
+            num_epochs_to_retain_for_transactions: None,
+            compaction_period_secs: None,
         }
     }
 }
@@ -336,6 +435,25 @@ pub struct MetricsConfig {
     pub push_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ValidatorReportConfig {
+    /// How often the report is signed, in seconds, at least every second.
+    ///
+    /// If unspecified, this will default to `60`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report_interval_seconds: Option<u64>,
+    /// The endpoint the signed reports are posted to, as JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_url: Option<String>,
+}
+
+impl ValidatorReportConfig {
+    pub fn report_interval(&self) -> Duration {
+        Duration::from_secs(self.report_interval_seconds.unwrap_or(60).max(1))
+    }
+}
+
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct DBCheckpointConfig {
@@ -471,6 +589,33 @@ enum GenesisLocation {
     },
 }
 
+/// An external signer holding the protocol key of a validator, e.g. a sidecar fronting a KMS or a
+/// PKCS#11 HSM, which the node asks to sign over HTTP: a `POST` to `<url>/sign` with the JSON body
+/// `{"key_id": ..., "message": <base64>}` is answered with `{"signature": <base64>}`.
+///
+/// Only the protocol key can be held by a signer: the network and worker keys are used by the TLS
+/// handshakes of the node, which need them in memory.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteSignerConfig {
+    pub url: String,
+    /// The id of the protocol key in the signer.
+    pub key_id: String,
+    /// The public key of the protocol key, against which the node checks the signer on startup.
+    pub public_key: AuthorityPublicKeyBytes,
+    /// The timeout of a signing request, in milliseconds.
+    ///
+    /// If unspecified, this will default to `2000`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout_ms: Option<u64>,
+}
+
+impl RemoteSignerConfig {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms.unwrap_or(2_000))
+    }
+}
+
 /// Wrapper struct for SuiKeyPair that can be deserialized from a file path. Used by network, worker, and account keypair.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct KeyPairWithPath {
@@ -597,6 +742,19 @@ impl AuthorityKeyPairWithPath {
             })
             .as_ref()
     }
+
+    /// Returns the key pair, or an error if its file cannot be read, e.g. for a key pair which is
+    /// loaded after the node started.
+    pub fn try_authority_keypair(&self) -> Result<&AuthorityKeyPair> {
+        self.keypair
+            .get_or_try_init(|| match &self.location {
+                AuthorityKeyPairLocation::InPlace { value } => Ok(value.clone()),
+                AuthorityKeyPairLocation::File { path } => read_authority_keypair_from_file(path)
+                    .map(Arc::new)
+                    .map_err(|e| anyhow::anyhow!("Invalid authority keypair file {path:?}: {e}")),
+            })
+            .map(|keypair| keypair.as_ref())
+    }
 }
 
 #[cfg(test)]
@@ -610,7 +768,7 @@ mod tests {
         get_key_pair_from_rng, AccountKeyPair, AuthorityKeyPair, NetworkKeyPair, SuiKeyPair,
     };
 
-    use super::Genesis;
+    use super::{Genesis, RemoteSignerConfig};
     use crate::NodeConfig;
 
     #[test]
@@ -673,6 +831,44 @@ mod tests {
         let _template: NodeConfig = serde_yaml::from_str(TEMPLATE).unwrap();
     }
 
+    #[test]
+    fn protocol_key_held_by_remote_signer() {
+        const TEMPLATE: &str = include_str!("../data/fullnode-template.yaml");
+        let mut config: NodeConfig = serde_yaml::from_str(TEMPLATE).unwrap();
+        let protocol_key_pair: AuthorityKeyPair =
+            get_key_pair_from_rng(&mut StdRng::from_seed([0; 32])).1;
+        let signer = RemoteSignerConfig {
+            url: "http://localhost:9100".to_string(),
+            key_id: "protocol-key".to_string(),
+            public_key: protocol_key_pair.public().into(),
+            request_timeout_ms: None,
+        };
+        assert_eq!(signer.request_timeout().as_millis(), 2_000);
+
+        // The public key is the one of the signer, whatever the protocol key pair.
+        let s = serde_yaml::to_string(&signer).unwrap();
+        config.protocol_key_signer = Some(serde_yaml::from_str(&s).unwrap());
+        assert_eq!(config.protocol_public_key(), signer.public_key);
+    }
+
+    #[test]
+    fn next_protocol_key_pair_written_after_start() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("next-protocol.key");
+        let next_key_pair: AuthorityKeyPairWithPath =
+            serde_yaml::from_str(&format!("path: {}", path.display())).unwrap();
+        assert!(next_key_pair.try_authority_keypair().is_err());
+
+        // The key file is read once it exists.
+        let protocol_key_pair: AuthorityKeyPair =
+            get_key_pair_from_rng(&mut StdRng::from_seed([0; 32])).1;
+        write_authority_keypair_to_file(&protocol_key_pair, &path).unwrap();
+        assert_eq!(
+            next_key_pair.try_authority_keypair().unwrap().public(),
+            protocol_key_pair.public()
+        );
+    }
+
     #[test]
     fn load_key_pairs_to_node_config() {
         let protocol_key_pair: AuthorityKeyPair =
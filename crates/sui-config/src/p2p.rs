@@ -1,9 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{net::SocketAddr, num::NonZeroU32, time::Duration};
+use std::{net::SocketAddr, num::NonZeroU32, path::PathBuf, time::Duration};
 
 use serde::{Deserialize, Serialize};
+use sui_storage::object_store::ObjectStoreConfig;
 use sui_types::multiaddr::Multiaddr;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -131,6 +132,34 @@ pub struct StateSyncConfig {
     /// If unspecified, this will default to no limit.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub get_checkpoint_contents_rate_limit: Option<NonZeroU32>,
+
+    /// Object store holding an archive of the checkpoints, which is used to sync the checkpoints
+    /// that peers are no longer able to serve.
+    ///
+    /// If unspecified, checkpoints are only synced from peers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_object_store: Option<ObjectStoreConfig>,
+
+    /// Follow the chain without joining the P2P network, for nodes in restricted network
+    /// environments. Checkpoints are then only synced from the RPC of a trusted fullnode and from
+    /// the archive, and are verified like the ones served by peers.
+    ///
+    /// If unspecified, checkpoints are synced from peers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follower: Option<FollowerConfig>,
+
+    /// Duration of the ban of the peers which served invalid or slow data, once their score
+    /// drops to the ban threshold.
+    ///
+    /// If unspecified, this will default to `3,600,000` milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_ban_duration_ms: Option<u64>,
+
+    /// File where the banned peers are persisted across restarts.
+    ///
+    /// If unspecified, nodes keep it in their database directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_ban_list_path: Option<PathBuf>,
 }
 
 impl StateSyncConfig {
@@ -182,6 +211,29 @@ impl StateSyncConfig {
             .map(Duration::from_millis)
             .unwrap_or(DEFAULT_TIMEOUT)
     }
+
+    pub fn peer_ban_duration(&self) -> Duration {
+        const DEFAULT_PEER_BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+        self.peer_ban_duration_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_PEER_BAN_DURATION)
+    }
+
+    pub fn is_follower(&self) -> bool {
+        self.follower.is_some()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FollowerConfig {
+    /// The url of the JSON-RPC of the fullnode to follow, polled every interval period for its
+    /// latest checkpoint.
+    ///
+    /// If unspecified, checkpoints are only synced from the archive, which must be configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
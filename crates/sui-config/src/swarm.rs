@@ -260,6 +260,8 @@ impl<'a> FullnodeConfigBuilder<'a> {
 
         Ok(NodeConfig {
             protocol_key_pair: AuthorityKeyPairWithPath::new(protocol_key_pair),
+            protocol_key_signer: None,
+            next_protocol_key_pair: None,
             account_key_pair: KeyPairWithPath::new(SuiKeyPair::Ed25519(account_key_pair)),
             worker_key_pair: KeyPairWithPath::new(SuiKeyPair::Ed25519(worker_key_pair)),
             network_key_pair: KeyPairWithPath::new(SuiKeyPair::Ed25519(network_key_pair)),
@@ -270,6 +272,7 @@ impl<'a> FullnodeConfigBuilder<'a> {
             // TODO: admin server is hard coded to start on 127.0.0.1 - we should probably
             // provide the entire socket address here to avoid confusion.
             admin_interface_port: self.admin_port.unwrap_or_else(|| get_available_port(8888)),
+            admin_interface_token: None,
             json_rpc_address,
             consensus_config: None,
             enable_event_processing: self.enable_event_store,
@@ -285,6 +288,11 @@ impl<'a> FullnodeConfigBuilder<'a> {
             supported_protocol_versions: Some(supported_protocol_versions),
             db_checkpoint_config: self.db_checkpoint_config,
             indirect_objects_threshold: usize::MAX,
+            archive_mode: false,
+            resubmit_transactions_across_epochs: true,
+            quorum_driver_journal: false,
+            memory_budget_bytes: None,
+            validator_report: None,
         })
     }
 }
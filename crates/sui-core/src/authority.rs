@@ -2,7 +2,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::{collections::HashMap, fs, pin::Pin, sync::Arc};
@@ -16,6 +16,7 @@ use fastcrypto::traits::KeyPair;
 use itertools::Itertools;
 use move_binary_format::compatibility::Compatibility;
 use move_binary_format::CompiledModule;
+use move_bytecode_utils::module_cache::GetModule;
 use move_core_types::language_storage::ModuleId;
 use parking_lot::Mutex;
 use prometheus::{
@@ -46,14 +47,16 @@ use sui_config::genesis::Genesis;
 use sui_config::node::{AuthorityStorePruningConfig, DBCheckpointConfig};
 use sui_framework::{MoveStdlib, SuiFramework, SuiSystem, SystemPackage};
 use sui_json_rpc_types::{
-    Checkpoint, DevInspectResults, DryRunTransactionBlockResponse, EventFilter, SuiEvent,
-    SuiMoveValue, SuiObjectDataFilter, SuiTransactionBlockEvents,
+    Checkpoint, DevInspectResults, DryRunTransactionBlockResponse, EventFilter, EventPage, Filter,
+    SuiEvent, SuiMoveValue, SuiObjectData, SuiObjectDataFilter, SuiObjectDataOptions,
+    SuiObjectVersionConflict, SuiObjectVersionStatus, SuiPackageUpgradeIncompatibility,
+    SuiPackageUpgradeReport, SuiTransactionBlockEvents,
 };
 use sui_macros::{fail_point, fail_point_async, nondeterministic};
 use sui_protocol_config::SupportedProtocolVersions;
 use sui_storage::indexes::ObjectIndexChanges;
 use sui_storage::IndexStore;
-use sui_types::committee::{EpochId, ProtocolVersion};
+use sui_types::committee::{EpochId, ProtocolVersion, StakeUnit};
 use sui_types::crypto::{
     default_hash, AuthorityKeyPair, AuthoritySignInfo, NetworkKeyPair, Signer,
 };
@@ -65,12 +68,16 @@ use sui_types::gas::{GasCostSummary, GasPrice, SuiCostTable, SuiGasStatus};
 use sui_types::message_envelope::Message;
 use sui_types::messages_checkpoint::{
     CheckpointCommitment, CheckpointContents, CheckpointContentsDigest, CheckpointDigest,
-    CheckpointSequenceNumber, CheckpointSummary, CheckpointTimestamp, VerifiedCheckpoint,
+    CheckpointSequenceNumber, CheckpointSummary, CheckpointTimestamp, FullCheckpointContents,
+    VerifiedCheckpoint,
 };
 use sui_types::messages_checkpoint::{CheckpointRequest, CheckpointResponse};
+use sui_types::move_package::{normalize_deserialized_modules, UpgradePolicy};
 use sui_types::object::{MoveObject, Owner, PastObjectRead, OBJECT_START_VERSION};
 use sui_types::query::TransactionFilter;
-use sui_types::storage::{ObjectKey, ObjectStore, WriteKind};
+use sui_types::storage::{
+    BackingPackageStore, ChildObjectResolver, ObjectKey, ObjectStore, ParentSync, WriteKind,
+};
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemStateTrait;
 use sui_types::sui_system_state::SuiSystemState;
 use sui_types::sui_system_state::SuiSystemStateTrait;
@@ -92,6 +99,7 @@ use crate::authority::authority_per_epoch_store::{AuthorityPerEpochStore, CertTx
 use crate::authority::authority_per_epoch_store_pruner::AuthorityPerEpochStorePruner;
 use crate::authority::authority_store::{ExecutionLockReadGuard, InputKey, ObjectLockStatus};
 use crate::authority::authority_store_pruner::AuthorityStorePruner;
+use crate::authority::checkpoint_state_store::CheckpointStateStore;
 use crate::authority::epoch_start_configuration::EpochStartConfigTrait;
 use crate::authority::epoch_start_configuration::EpochStartConfiguration;
 use crate::checkpoints::CheckpointStore;
@@ -99,9 +107,12 @@ use crate::epoch::committee_store::CommitteeStore;
 use crate::epoch::epoch_metrics::EpochMetrics;
 use crate::event_handler::EventHandler;
 use crate::execution_driver::execution_process;
+use crate::gas_price_oracle::RecentGasPrices;
 use crate::module_cache_metrics::ResolverMetrics;
 use crate::signature_verifier::VerifiedDigestCacheMetrics;
 use crate::stake_aggregator::StakeAggregator;
+use crate::storage::RocksDbStore;
+use crate::transaction_input_checker::InputObjectSource;
 use crate::{transaction_input_checker, transaction_manager::TransactionManager};
 
 #[cfg(test)]
@@ -130,6 +141,7 @@ pub mod authority_per_epoch_store_pruner;
 pub mod authority_store_pruner;
 pub mod authority_store_tables;
 pub mod authority_store_types;
+pub mod checkpoint_state_store;
 pub mod epoch_start_configuration;
 
 pub(crate) mod authority_notify_read;
@@ -150,6 +162,17 @@ pub type VerifiedTransactionBatch = Vec<(
     Option<(EpochId, CheckpointSequenceNumber)>,
 )>;
 
+/// The maximum number of events read by a query of the events, so that a filter matching few
+/// events of its index does not scan all of them at once.
+pub const MAX_EVENTS_SCANNED_PER_QUERY: usize = 10_000;
+
+/// The number of events read at once from an index when the events must be filtered.
+const EVENT_SCAN_BATCH_SIZE: usize = 500;
+
+/// The number of the latest transactions taking an object as input searched for the one which
+/// deleted or wrapped it.
+const MAX_TRANSACTIONS_SCANNED_FOR_CONSUMER: usize = 50;
+
 /// Prometheus metrics which can be displayed in Grafana, queried and alerted on
 pub struct AuthorityMetrics {
     tx_orders: IntCounter,
@@ -194,10 +217,16 @@ pub struct AuthorityMetrics {
     pub consensus_handler_processed_batches: IntCounter,
     pub consensus_handler_processed_bytes: IntCounter,
     pub consensus_handler_processed: IntCounterVec,
+    pub consensus_handler_deferred_transactions: IntCounter,
+    pub consensus_handler_prefetched_objects: IntCounter,
+    pub consensus_handler_prefetch_dropped: IntCounter,
     pub consensus_handler_num_low_scoring_authorities: IntGauge,
     pub consensus_handler_scores: IntGaugeVec,
     pub consensus_committed_subdags: IntCounterVec,
     pub consensus_committed_certificates: IntCounterVec,
+
+    highest_supported_protocol_version: IntGauge,
+    protocol_upgrade_unsupported: IntGauge,
 }
 
 // Override default Prom buckets for positive numbers in 0-50k range
@@ -411,6 +440,31 @@ impl AuthorityMetrics {
                 "Number of batches processed by consensus_handler",
                 registry
             ).unwrap(),
+            consensus_handler_deferred_transactions: register_int_counter_with_registry!(
+                "consensus_handler_deferred_transactions",
+                "Number of transactions deferred to a later commit by consensus_handler",
+                registry
+            ).unwrap(),
+            consensus_handler_prefetched_objects: register_int_counter_with_registry!(
+                "consensus_handler_prefetched_objects",
+                "Number of input objects of sequenced transactions read ahead of their execution",
+                registry
+            ).unwrap(),
+            highest_supported_protocol_version: register_int_gauge_with_registry!(
+                "highest_supported_protocol_version",
+                "The highest protocol version supported by the binary",
+                registry
+            ).unwrap(),
+            protocol_upgrade_unsupported: register_int_gauge_with_registry!(
+                "protocol_upgrade_unsupported",
+                "1 if the committee votes to upgrade to a protocol version not supported by the binary",
+                registry
+            ).unwrap(),
+            consensus_handler_prefetch_dropped: register_int_counter_with_registry!(
+                "consensus_handler_prefetch_dropped",
+                "Number of commits whose input objects were not prefetched, as the prefetcher was behind",
+                registry
+            ).unwrap(),
             consensus_handler_processed_bytes: register_int_counter_with_registry!(
                 "consensus_handler_processed_bytes",
                 "Number of bytes processed by consensus_handler",
@@ -456,12 +510,86 @@ impl AuthorityMetrics {
 ///
 pub type StableSyncAuthoritySigner = Pin<Arc<dyn Signer<AuthoritySignature> + Send + Sync>>;
 
-pub struct AuthorityState {
-    // Fixed size, static, identity of the authority
+/// The protocol key of the authority, i.e. its identity in the committee.
+pub struct ProtocolKey {
     /// The name of this authority.
     pub name: AuthorityName,
     /// The signature key of the authority.
     pub secret: StableSyncAuthoritySigner,
+}
+
+/// Whether the binary supports the protocol version the network upgrades to at the end of the
+/// epoch, if the epoch ended with the capabilities received so far from the committee.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolUpgradeReadiness {
+    pub current_version: ProtocolVersion,
+    pub supported_versions: SupportedProtocolVersions,
+    /// The protocol version of the next epoch, as voted by the committee so far.
+    pub next_version: ProtocolVersion,
+    /// The stake of the committee supporting the versions from the current one which are the
+    /// highest supported by a member of the committee or by the binary, and the next version.
+    pub stake_by_version: Vec<(ProtocolVersion, StakeUnit)>,
+    pub quorum_threshold: StakeUnit,
+    pub total_stake: StakeUnit,
+}
+
+impl ProtocolUpgradeReadiness {
+    pub fn new(
+        current_version: ProtocolVersion,
+        supported_versions: SupportedProtocolVersions,
+        committee: &Committee,
+        capabilities: Vec<AuthorityCapabilities>,
+        buffer_stake_bps: u64,
+    ) -> Self {
+        // The support of each member is a range of versions, so the stake only changes at the
+        // highest version of a member.
+        let versions: BTreeSet<_> = capabilities
+            .iter()
+            .map(|cap| cap.supported_protocol_versions.max)
+            .chain([current_version + 1, supported_versions.max])
+            .filter(|version| *version >= current_version)
+            .chain([current_version])
+            .collect();
+        let stake_by_version = versions
+            .into_iter()
+            .map(|version| {
+                let stake = capabilities
+                    .iter()
+                    .filter(|cap| {
+                        cap.supported_protocol_versions
+                            .is_version_supported(version)
+                    })
+                    .map(|cap| committee.weight(&cap.authority))
+                    .sum();
+                (version, stake)
+            })
+            .collect();
+        let (next_version, _) = AuthorityState::choose_protocol_version_and_system_packages(
+            current_version,
+            committee,
+            capabilities,
+            buffer_stake_bps,
+        );
+        Self {
+            current_version,
+            supported_versions,
+            next_version,
+            stake_by_version,
+            quorum_threshold: committee.quorum_threshold(),
+            total_stake: committee.total_votes(),
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.supported_versions
+            .is_version_supported(self.next_version)
+    }
+}
+
+pub struct AuthorityState {
+    // The identity of the authority, which only changes at an epoch change, when its protocol key
+    // is rotated.
+    protocol_key: ArcSwap<ProtocolKey>,
 
     /// The database
     pub database: Arc<AuthorityStore>, // TODO: remove pub
@@ -488,6 +616,9 @@ pub struct AuthorityState {
 
     /// Take db checkpoints af different dbs
     db_checkpoint_config: DBCheckpointConfig,
+
+    /// The protocol versions supported by the binary.
+    supported_protocol_versions: SupportedProtocolVersions,
 }
 
 /// The authority state encapsulates all state, drives execution, and ensures safety.
@@ -497,14 +628,63 @@ pub struct AuthorityState {
 ///
 /// Repeating valid commands should produce no changes and return no error.
 impl AuthorityState {
+    /// The name of this authority, i.e. its current protocol public key.
+    pub fn name(&self) -> AuthorityName {
+        self.protocol_key.load().name
+    }
+
+    /// The signature key of the authority.
+    pub fn secret(&self) -> StableSyncAuthoritySigner {
+        self.protocol_key.load().secret.clone()
+    }
+
+    /// Replaces the protocol key of the authority, once the committee of the next epoch has the
+    /// new key, i.e. before reconfiguring to that epoch.
+    pub fn rotate_protocol_key(&self, secret: StableSyncAuthoritySigner, name: AuthorityName) {
+        info!(old = ?self.name(), new = ?name, "Rotating the protocol key of the authority");
+        self.protocol_key
+            .store(Arc::new(ProtocolKey { name, secret }));
+    }
+
     pub fn is_validator(&self, epoch_store: &AuthorityPerEpochStore) -> bool {
-        epoch_store.committee().authority_exists(&self.name)
+        epoch_store.committee().authority_exists(&self.name())
     }
 
     pub fn is_fullnode(&self, epoch_store: &AuthorityPerEpochStore) -> bool {
         !self.is_validator(epoch_store)
     }
 
+    pub fn supported_protocol_versions(&self) -> SupportedProtocolVersions {
+        self.supported_protocol_versions
+    }
+
+    /// Checks whether the binary supports the protocol version the committee votes to upgrade
+    /// to, and reports it in the metrics and in the logs.
+    pub fn check_protocol_upgrade_readiness(
+        &self,
+        epoch_store: &AuthorityPerEpochStore,
+    ) -> ProtocolUpgradeReadiness {
+        let readiness = ProtocolUpgradeReadiness::new(
+            epoch_store.protocol_version(),
+            self.supported_protocol_versions,
+            epoch_store.committee(),
+            epoch_store.get_capabilities(),
+            epoch_store.get_effective_buffer_stake_bps(),
+        );
+        self.metrics
+            .protocol_upgrade_unsupported
+            .set(!readiness.is_ready() as i64);
+        if !readiness.is_ready() {
+            warn!(
+                next_version = ?readiness.next_version,
+                supported_versions = ?readiness.supported_versions,
+                "The committee votes to upgrade to a protocol version not supported by this \
+                binary. The binary must be upgraded before the end of the epoch!"
+            );
+        }
+        readiness
+    }
+
     pub fn committee_store(&self) -> &Arc<CommitteeStore> {
         &self.committee_store
     }
@@ -547,11 +727,12 @@ impl AuthorityState {
 
         let owned_objects = input_objects.filter_owned_objects();
 
+        let protocol_key = self.protocol_key.load();
         let signed_transaction = VerifiedSignedTransaction::new(
             epoch_store.epoch(),
             transaction,
-            self.name,
-            &*self.secret,
+            protocol_key.name,
+            &*protocol_key.secret,
         );
 
         // Check and write locks, to signed transaction, into the database
@@ -830,7 +1011,7 @@ impl AuthorityState {
             .await
         {
             Err(e) => {
-                info!(name = ?self.name, ?digest, "Error preparing transaction: {e}");
+                info!(name = ?self.name(), ?digest, "Error preparing transaction: {e}");
                 tx_guard.release();
                 return Err(e);
             }
@@ -994,6 +1175,55 @@ impl AuthorityState {
             .certificate_executed(digest, epoch_store)
     }
 
+    /// Checks the inputs of a transaction against the local state before it is submitted to the
+    /// validators, e.g. by a fullnode: that the objects exist and are not consumed yet, and that
+    /// the gas covers the budget. The objects of a given version never change, but this node may
+    /// not have synced the latest versions yet: the check only fails when the transaction is sure
+    /// to be rejected by the validators of the epoch of this node, and passes otherwise.
+    ///
+    /// The transactions already executed pass: the validators return their effects.
+    pub async fn pre_validate_transaction(&self, transaction: &VerifiedTransaction) -> SuiResult {
+        if self.is_tx_already_executed(transaction.digest())? {
+            return Ok(());
+        }
+        let epoch_store = self.load_epoch_store_one_call_per_task();
+        let transaction = transaction.data().transaction_data();
+
+        // An owned object consumed locally is consumed for the validators too.
+        for kind in transaction.input_objects()? {
+            let InputObjectKind::ImmOrOwnedMoveObject(object_ref) = kind else {
+                continue;
+            };
+            if let Some(latest) = self.database.get_object_or_tombstone(object_ref.0)? {
+                fp_ensure!(
+                    latest.1 <= object_ref.1,
+                    UserInputError::ObjectVersionUnavailableForConsumption {
+                        provided_obj_ref: object_ref,
+                        current_version: latest.1,
+                    }
+                    .into()
+                );
+            }
+        }
+
+        match transaction_input_checker::check_transaction_input(
+            &self.database,
+            &epoch_store,
+            transaction,
+        )
+        .await
+        {
+            Ok(_) => Ok(()),
+            // The objects may not be synced by this node yet.
+            Err(SuiError::UserInputError {
+                error:
+                    UserInputError::ObjectNotFound { .. }
+                    | UserInputError::DependentPackageNotFound { .. },
+            }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
     pub async fn dry_exec_transaction(
         &self,
         transaction: TransactionData,
@@ -1006,6 +1236,75 @@ impl AuthorityState {
         ),
         anyhow::Error,
     > {
+        self.dry_exec_transaction_with_store(self.database.clone(), transaction, transaction_digest)
+            .await
+    }
+
+    /// Executes the transaction against the state as of the checkpoint, as `dry_exec_transaction`
+    /// does against the latest state. Only archive nodes keep the past versions of the objects
+    /// needed. The transaction is executed under the protocol config of the current epoch.
+    pub async fn dry_exec_transaction_at_checkpoint(
+        &self,
+        transaction: TransactionData,
+        transaction_digest: TransactionDigest,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<
+        (
+            DryRunTransactionBlockResponse,
+            BTreeMap<ObjectID, (ObjectRef, Object, WriteKind)>,
+            TransactionEffects,
+        ),
+        anyhow::Error,
+    > {
+        self.check_executable_at_checkpoint(checkpoint)?;
+        self.dry_exec_transaction_with_store(
+            CheckpointStateStore::new(self, checkpoint),
+            transaction,
+            transaction_digest,
+        )
+        .await
+    }
+
+    fn check_executable_at_checkpoint(
+        &self,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<(), anyhow::Error> {
+        if !self.database.is_archive_mode() {
+            return Err(anyhow!(
+                "executing against a past checkpoint is only supported on archive nodes"
+            ));
+        }
+        let latest_checkpoint = self.get_latest_checkpoint_sequence_number()?;
+        if checkpoint > latest_checkpoint {
+            return Err(anyhow!(
+                "checkpoint {checkpoint} is not executed yet, the latest executed checkpoint is \
+                {latest_checkpoint}"
+            ));
+        }
+        Ok(())
+    }
+
+    async fn dry_exec_transaction_with_store<S>(
+        &self,
+        store: S,
+        transaction: TransactionData,
+        transaction_digest: TransactionDigest,
+    ) -> Result<
+        (
+            DryRunTransactionBlockResponse,
+            BTreeMap<ObjectID, (ObjectRef, Object, WriteKind)>,
+            TransactionEffects,
+        ),
+        anyhow::Error,
+    >
+    where
+        S: InputObjectSource
+            + BackingPackageStore
+            + ParentSync
+            + ChildObjectResolver
+            + ObjectStore
+            + GetModule<Error = SuiError, Item = CompiledModule>,
+    {
         let epoch_store = self.load_epoch_store_one_call_per_task();
         if !self.is_fullnode(&epoch_store) {
             return Err(anyhow!("dry-exec is only supported on fullnodes"));
@@ -1028,7 +1327,7 @@ impl AuthorityState {
             let gas_object_ref = gas_object.compute_object_reference();
             gas_object_refs = vec![gas_object_ref];
             transaction_input_checker::check_transaction_input_with_given_gas(
-                &self.database,
+                &store,
                 epoch_store.as_ref(),
                 &transaction,
                 gas_object,
@@ -1036,7 +1335,7 @@ impl AuthorityState {
             .await?
         } else {
             transaction_input_checker::check_transaction_input(
-                &self.database,
+                &store,
                 epoch_store.as_ref(),
                 &transaction,
             )
@@ -1047,7 +1346,7 @@ impl AuthorityState {
 
         let transaction_dependencies = input_objects.transaction_dependencies();
         let temporary_store = TemporaryStore::new(
-            self.database.clone(),
+            store,
             input_objects,
             transaction_digest,
             epoch_store.protocol_config(),
@@ -1085,6 +1384,20 @@ impl AuthorityState {
         // Returning empty vector here because we recalculate changes in the rpc layer.
         let balance_changes = Vec::new();
 
+        let written_objects = inner_temp_store
+            .written
+            .values()
+            .map(|(object_ref, object, _)| {
+                let layout = object.get_layout(ObjectFormatOptions::default(), &module_cache)?;
+                SuiObjectData::try_from((
+                    *object_ref,
+                    object.clone(),
+                    layout,
+                    SuiObjectDataOptions::full_content(),
+                ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
         Ok((
             DryRunTransactionBlockResponse {
                 effects: effects.clone().try_into()?,
@@ -1096,6 +1409,7 @@ impl AuthorityState {
                 )?,
                 object_changes,
                 balance_changes,
+                written_objects,
             },
             inner_temp_store.written,
             effects,
@@ -1109,6 +1423,49 @@ impl AuthorityState {
         transaction_kind: TransactionKind,
         gas_price: Option<u64>,
     ) -> Result<DevInspectResults, anyhow::Error> {
+        self.dev_inspect_transaction_block_with_store(
+            self.database.clone(),
+            sender,
+            transaction_kind,
+            gas_price,
+        )
+        .await
+    }
+
+    /// Runs the transaction in dev-inspect mode against the state as of the checkpoint, see
+    /// `dry_exec_transaction_at_checkpoint`.
+    pub async fn dev_inspect_transaction_block_at_checkpoint(
+        &self,
+        sender: SuiAddress,
+        transaction_kind: TransactionKind,
+        gas_price: Option<u64>,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<DevInspectResults, anyhow::Error> {
+        self.check_executable_at_checkpoint(checkpoint)?;
+        self.dev_inspect_transaction_block_with_store(
+            CheckpointStateStore::new(self, checkpoint),
+            sender,
+            transaction_kind,
+            gas_price,
+        )
+        .await
+    }
+
+    async fn dev_inspect_transaction_block_with_store<S>(
+        &self,
+        store: S,
+        sender: SuiAddress,
+        transaction_kind: TransactionKind,
+        gas_price: Option<u64>,
+    ) -> Result<DevInspectResults, anyhow::Error>
+    where
+        S: InputObjectSource
+            + BackingPackageStore
+            + ParentSync
+            + ChildObjectResolver
+            + ObjectStore
+            + GetModule<Error = SuiError, Item = CompiledModule>,
+    {
         let epoch_store = self.load_epoch_store_one_call_per_task();
         if !self.is_fullnode(&epoch_store) {
             return Err(anyhow!("dev-inspect is only supported on fullnodes"));
@@ -1131,7 +1488,7 @@ impl AuthorityState {
             TransactionDigest::genesis(),
         );
         let (gas_object_ref, input_objects) = transaction_input_checker::check_dev_inspect_input(
-            &self.database,
+            &store,
             protocol_config,
             &transaction_kind,
             gas_object,
@@ -1152,12 +1509,8 @@ impl AuthorityState {
         let transaction_digest = TransactionDigest::new(default_hash(&data));
         let transaction_kind = data.into_kind();
         let transaction_dependencies = input_objects.transaction_dependencies();
-        let temporary_store = TemporaryStore::new(
-            self.database.clone(),
-            input_objects,
-            transaction_digest,
-            protocol_config,
-        );
+        let temporary_store =
+            TemporaryStore::new(store, input_objects, transaction_digest, protocol_config);
         let gas_status = SuiGasStatus::new_with_budget(
             max_tx_gas,
             GasPrice::from(gas_price),
@@ -1197,6 +1550,139 @@ impl AuthorityState {
         )
     }
 
+    /// Checks that the modules can upgrade the package under the policy, running the checks the
+    /// validators run when executing the upgrade: the verification of the modules, their
+    /// compatibility with the existing package, and the linkage of the upgraded package with its
+    /// dependencies. All the incompatibilities found are reported, rather than the first one.
+    pub fn check_package_upgrade(
+        &self,
+        package_id: ObjectID,
+        module_bytes: Vec<Vec<u8>>,
+        dep_ids: Vec<ObjectID>,
+        policy: u8,
+    ) -> SuiResult<SuiPackageUpgradeReport> {
+        let epoch_store = self.load_epoch_store_one_call_per_task();
+        let protocol_config = epoch_store.protocol_config();
+        let current_package =
+            self.database
+                .get_package(&package_id)?
+                .ok_or(UserInputError::ObjectNotFound {
+                    object_id: package_id,
+                    version: None,
+                })?;
+
+        let mut incompatibilities = vec![];
+        let report = move |incompatibilities| SuiPackageUpgradeReport {
+            package_id,
+            policy,
+            incompatibilities,
+        };
+        let policy = UpgradePolicy::try_from(policy).ok();
+        if policy.is_none() {
+            incompatibilities.push(SuiPackageUpgradeIncompatibility::UnknownUpgradePolicy);
+        }
+
+        let modules: Result<Vec<_>, _> = module_bytes
+            .iter()
+            .map(|bytes| {
+                CompiledModule::deserialize_with_max_version(
+                    bytes,
+                    protocol_config.move_binary_format_version(),
+                )
+            })
+            .collect();
+        let mut modules = match modules {
+            Ok(modules) if !modules.is_empty() => modules,
+            Ok(_) => {
+                incompatibilities.push(SuiPackageUpgradeIncompatibility::InvalidModules {
+                    error: "The package has no modules".to_string(),
+                });
+                return Ok(report(incompatibilities));
+            }
+            Err(e) => {
+                incompatibilities.push(SuiPackageUpgradeIncompatibility::InvalidModules {
+                    error: e.to_string(),
+                });
+                return Ok(report(incompatibilities));
+            }
+        };
+        // As in execution, the modules are verified under the original id of the package.
+        if let Err(e) =
+            adapter::substitute_package_id(&mut modules, current_package.original_package_id())
+        {
+            incompatibilities.push(SuiPackageUpgradeIncompatibility::InvalidModules {
+                error: e.to_string(),
+            });
+            return Ok(report(incompatibilities));
+        }
+
+        let verifier_config = adapter::default_verifier_config(protocol_config, false);
+        for module in &modules {
+            let error =
+                match move_bytecode_verifier::verify_module_with_config(&verifier_config, module) {
+                    Ok(()) => sui_verifier::verifier::verify_module(module, &BTreeMap::new())
+                        .err()
+                        .map(|e| e.to_string()),
+                    Err(e) => Some(format!("{:?}", e.major_status())),
+                };
+            if let Some(error) = error {
+                incompatibilities.push(SuiPackageUpgradeIncompatibility::VerificationFailure {
+                    module: module.self_id().name().to_string(),
+                    error,
+                });
+            }
+        }
+
+        if let Some(policy) = policy {
+            let current_normalized =
+                current_package.normalize(protocol_config.move_binary_format_version())?;
+            let mut new_normalized = normalize_deserialized_modules(modules.iter());
+            for (name, current_module) in current_normalized {
+                let Some(new_module) = new_normalized.remove(&name) else {
+                    incompatibilities
+                        .push(SuiPackageUpgradeIncompatibility::MissingModule { module: name });
+                    continue;
+                };
+                if let Err(e) = policy.check_compatibility(&current_module, &new_module) {
+                    incompatibilities.push(SuiPackageUpgradeIncompatibility::IncompatibleModule {
+                        module: name,
+                        error: format!("{:?}", e.major_status()),
+                    });
+                }
+            }
+        }
+
+        let mut dependencies = vec![];
+        let mut missing_dependencies = false;
+        for dep_id in dep_ids {
+            match self.database.get_package(&dep_id) {
+                Ok(Some(package)) => dependencies.push(package),
+                Ok(None) | Err(SuiError::BadObjectType { .. }) => {
+                    missing_dependencies = true;
+                    incompatibilities.push(SuiPackageUpgradeIncompatibility::MissingDependency {
+                        package: dep_id,
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if !missing_dependencies {
+            // The id of the upgraded package does not matter to the linkage.
+            if let Err(e) = current_package.new_upgraded(
+                ObjectID::ZERO,
+                modules,
+                protocol_config.max_move_package_size(),
+                &dependencies,
+            ) {
+                incompatibilities.push(SuiPackageUpgradeIncompatibility::InvalidLinkage {
+                    error: e.to_string(),
+                });
+            }
+        }
+
+        Ok(report(incompatibilities))
+    }
+
     pub fn is_tx_already_executed(&self, digest: &TransactionDigest) -> SuiResult<bool> {
         self.database.is_tx_already_executed(digest)
     }
@@ -1612,6 +2098,9 @@ impl AuthorityState {
         Self::check_protocol_version(supported_protocol_versions, epoch_store.protocol_version());
 
         let metrics = Arc::new(AuthorityMetrics::new(prometheus_registry));
+        metrics
+            .highest_supported_protocol_version
+            .set(supported_protocol_versions.max.as_u64() as i64);
         let (tx_ready_certificates, rx_ready_certificates) = unbounded_channel();
         let transaction_manager = Arc::new(TransactionManager::new(
             store.clone(),
@@ -1631,8 +2120,7 @@ impl AuthorityState {
             epoch_store.epoch_start_state().epoch_duration_ms(),
         );
         let state = Arc::new(AuthorityState {
-            name,
-            secret,
+            protocol_key: ArcSwap::from_pointee(ProtocolKey { name, secret }),
             epoch_store: ArcSwap::new(epoch_store.clone()),
             database: store,
             indexes,
@@ -1645,6 +2133,7 @@ impl AuthorityState {
             _objects_pruner,
             _authority_per_epoch_pruner,
             db_checkpoint_config: db_checkpoint_config.clone(),
+            supported_protocol_versions,
         });
 
         // Start a task to execute ready certificates.
@@ -1952,6 +2441,13 @@ impl AuthorityState {
             .get_checkpoint_by_sequence_number(sequence_number)?)
     }
 
+    pub fn get_checkpoint_by_digest(
+        &self,
+        digest: &CheckpointDigest,
+    ) -> SuiResult<Option<VerifiedCheckpoint>> {
+        Ok(self.checkpoint_store.get_checkpoint_by_digest(digest)?)
+    }
+
     pub fn get_transaction_checkpoint(
         &self,
         digest: &TransactionDigest,
@@ -1964,6 +2460,93 @@ impl AuthorityState {
         Ok(checkpoint)
     }
 
+    /// Explains a conflict on the version of the object: whether the version is still the latest
+    /// one, or which transaction consumed it and in which checkpoint.
+    pub fn get_object_version_conflict(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> SuiResult<SuiObjectVersionConflict> {
+        let conflict = |status| SuiObjectVersionConflict {
+            object_id,
+            version,
+            status,
+        };
+        if self
+            .database
+            .get_object_by_key(&object_id, version)?
+            .is_none()
+        {
+            let latest_version = self
+                .database
+                .get_object_or_tombstone(object_id)?
+                .map(|(_, version, _)| version);
+            return Ok(conflict(SuiObjectVersionStatus::NotFound {
+                latest_version,
+            }));
+        }
+
+        let Some((_, next_version, digest)) = self
+            .database
+            .get_object_ref_after_key(&object_id, version)? else {
+            return Ok(conflict(SuiObjectVersionStatus::Live));
+        };
+        let deleted = !digest.is_alive();
+        let transaction = if deleted {
+            // Tombstones do not record the transaction which wrote them.
+            self.find_transaction_consuming(object_id, version)?
+        } else {
+            self.database
+                .get_object_by_key(&object_id, next_version)?
+                .map(|object| object.previous_transaction)
+        };
+        let checkpoint = match &transaction {
+            Some(digest) => self
+                .database
+                .get_transaction_checkpoint(digest)?
+                .map(|(_, checkpoint)| checkpoint),
+            None => None,
+        };
+        Ok(conflict(SuiObjectVersionStatus::Consumed {
+            next_version,
+            deleted,
+            transaction,
+            checkpoint,
+        }))
+    }
+
+    /// Finds the transaction which consumed the version of the object among the latest
+    /// transactions taking the object as input, in the indexes of a fullnode.
+    fn find_transaction_consuming(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> SuiResult<Option<TransactionDigest>> {
+        let Some(indexes) = &self.indexes else {
+            return Ok(None);
+        };
+        let digests = indexes.get_transactions_by_input_object(
+            object_id,
+            None,
+            Some(MAX_TRANSACTIONS_SCANNED_FOR_CONSUMER),
+            true,
+        )?;
+        for digest in digests {
+            let consumed = self
+                .database
+                .get_executed_effects(&digest)?
+                .map_or(false, |effects| {
+                    effects
+                        .modified_at_versions()
+                        .contains(&(object_id, version))
+                });
+            if consumed {
+                return Ok(Some(digest));
+            }
+        }
+        Ok(None)
+    }
+
     pub async fn get_object_read(&self, object_id: &ObjectID) -> Result<ObjectRead, SuiError> {
         match self.database.get_object_or_tombstone(*object_id)? {
             None => Ok(ObjectRead::NotExists(*object_id)),
@@ -2057,6 +2640,12 @@ impl AuthorityState {
                 if version < obj_ref.1 {
                     // Read past objects
                     return Ok(match self.database.get_object_by_key(object_id, version)? {
+                        None if self.database.is_object_version_pruned(object_id, version)? => {
+                            return Err(SuiError::ObjectVersionPruned {
+                                object_id: *object_id,
+                                version,
+                            });
+                        }
                         None => PastObjectRead::VersionNotFound(*object_id, version),
                         Some(object) => {
                             let layout = object.get_layout(
@@ -2104,6 +2693,130 @@ impl AuthorityState {
         }
     }
 
+    /// Reads the object as of the checkpoint: its version once the transactions of the checkpoint
+    /// and of the previous ones are executed, which makes the reads of several objects at the same
+    /// checkpoint consistent with each other. As for `get_past_object_read`, the past versions may
+    /// have been pruned, and finding when a deleted object was deleted requires the indexes.
+    /// Archive nodes find the version in a single lookup, otherwise the versions of the object are
+    /// walked back from the latest one.
+    pub fn get_object_read_at_checkpoint(
+        &self,
+        object_id: &ObjectID,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<PastObjectRead, anyhow::Error> {
+        // In archive mode, the version of the object is found in the index instead.
+        if let Some(object_ref) = self
+            .database
+            .find_object_ref_at_checkpoint(object_id, checkpoint)?
+        {
+            let Some(object_ref) = object_ref else {
+                return Ok(PastObjectRead::ObjectNotExists(*object_id));
+            };
+            if !object_ref.2.is_alive() {
+                return Ok(PastObjectRead::ObjectDeleted(object_ref));
+            }
+            let Some(object) = self.database.get_object_by_key(object_id, object_ref.1)? else {
+                return Ok(PastObjectRead::VersionNotFound(*object_id, object_ref.1));
+            };
+            let layout = object.get_layout(
+                ObjectFormatOptions::default(),
+                self.load_epoch_store_one_call_per_task()
+                    .module_cache()
+                    .as_ref(),
+            )?;
+            return Ok(PastObjectRead::VersionFound(object_ref, object, layout));
+        }
+
+        let Some(mut object_ref) = self.database.get_object_or_tombstone(*object_id)? else {
+            return Ok(PastObjectRead::ObjectNotExists(*object_id));
+        };
+        loop {
+            // The transaction which wrote this version of the object, or deleted it.
+            let (digest, object) = if object_ref.2.is_alive() {
+                let Some(object) = self.database.get_object_by_key(object_id, object_ref.1)? else {
+                    return Ok(PastObjectRead::VersionNotFound(*object_id, object_ref.1));
+                };
+                (object.previous_transaction, Some(object))
+            } else {
+                (self.find_deleting_transaction(&object_ref)?, None)
+            };
+
+            // The transactions not in a checkpoint yet are executed after it.
+            let executed_at = self.database.get_transaction_checkpoint(&digest)?;
+            if executed_at.map_or(false, |(_, executed_at)| executed_at <= checkpoint) {
+                let Some(object) = object else {
+                    return Ok(PastObjectRead::ObjectDeleted(object_ref));
+                };
+                let layout = object.get_layout(
+                    ObjectFormatOptions::default(),
+                    self.load_epoch_store_one_call_per_task()
+                        .module_cache()
+                        .as_ref(),
+                )?;
+                return Ok(PastObjectRead::VersionFound(object_ref, object, layout));
+            }
+
+            // The object was written after the checkpoint, it is read at its previous version.
+            let effects = self
+                .database
+                .get_executed_effects(&digest)?
+                .ok_or(SuiError::TransactionNotFound { digest })?;
+            let Some((_, version)) = effects
+                .modified_at_versions()
+                .iter()
+                .find(|(id, _)| id == object_id) else {
+                // The object was created after the checkpoint.
+                return Ok(PastObjectRead::ObjectNotExists(*object_id));
+            };
+            let Some(object) = self.database.get_object_by_key(object_id, *version)? else {
+                if self.database.is_object_version_pruned(object_id, *version)? {
+                    return Err(SuiError::ObjectVersionPruned {
+                        object_id: *object_id,
+                        version: *version,
+                    }
+                    .into());
+                }
+                return Ok(PastObjectRead::VersionNotFound(*object_id, *version));
+            };
+            object_ref = object.compute_object_reference();
+        }
+    }
+
+    /// Finds the transaction which deleted or wrapped the object: the last transaction taking it
+    /// as input.
+    fn find_deleting_transaction(
+        &self,
+        object_ref: &ObjectRef,
+    ) -> Result<TransactionDigest, anyhow::Error> {
+        let digests = self.get_transactions(
+            Some(TransactionFilter::InputObject(object_ref.0)),
+            None,
+            Some(1),
+            true,
+        )?;
+        digests
+            .into_iter()
+            .find(|digest| {
+                self.database
+                    .get_executed_effects(digest)
+                    .ok()
+                    .flatten()
+                    .map_or(false, |effects| {
+                        effects
+                            .all_deleted()
+                            .iter()
+                            .any(|(deleted, _)| **deleted == *object_ref)
+                    })
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "The transaction deleting object {} at version {} was not found",
+                    object_ref.0,
+                    object_ref.1
+                )
+            })
+    }
+
     fn get_owner_at_version(
         &self,
         object_id: &ObjectID,
@@ -2231,7 +2944,7 @@ impl AuthorityState {
         digest: TransactionDigest,
     ) -> Result<VerifiedTransaction, anyhow::Error> {
         let transaction = self.database.get_transaction_block(&digest)?;
-        transaction.ok_or_else(|| anyhow!(SuiError::TransactionNotFound { digest }))
+        transaction.ok_or_else(|| anyhow!(self.transaction_not_found_error(digest)))
     }
 
     pub async fn get_executed_effects(
@@ -2239,7 +2952,15 @@ impl AuthorityState {
         digest: TransactionDigest,
     ) -> Result<TransactionEffects, anyhow::Error> {
         let effects = self.database.get_executed_effects(&digest)?;
-        effects.ok_or_else(|| anyhow!(SuiError::TransactionNotFound { digest }))
+        effects.ok_or_else(|| anyhow!(self.transaction_not_found_error(digest)))
+    }
+
+    /// The error of a transaction not found, telling apart the transactions which were pruned.
+    fn transaction_not_found_error(&self, digest: TransactionDigest) -> SuiError {
+        match self.database.is_transaction_pruned(&digest) {
+            Ok(true) => SuiError::TransactionPruned { digest },
+            _ => SuiError::TransactionNotFound { digest },
+        }
     }
 
     pub async fn multi_get_executed_transactions(
@@ -2338,6 +3059,26 @@ impl AuthorityState {
             .ok_or_else(|| anyhow!("Latest checkpoint sequence number not found"))
     }
 
+    /// Waits until this node has executed the checkpoint of the given sequence number, so that
+    /// its reads reflect at least the state as of this checkpoint.
+    pub async fn notify_read_executed_checkpoint(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> SuiResult {
+        // We need to register the waiter _before_ reading the watermark to avoid race conditions
+        let registration = self
+            .database
+            .executed_checkpoint_notify_read
+            .register_one(&sequence_number);
+        let highest_executed = self
+            .get_checkpoint_store()
+            .get_highest_executed_checkpoint_seq_number()?;
+        if !matches!(highest_executed, Some(highest) if highest >= sequence_number) {
+            registration.await;
+        }
+        Ok(())
+    }
+
     pub fn get_checkpoint_summary_by_sequence_number(
         &self,
         sequence_number: CheckpointSequenceNumber,
@@ -2379,6 +3120,25 @@ impl AuthorityState {
             .ok_or_else(|| anyhow!("Checkpoint contents not found for digest: {:?}", digest))
     }
 
+    /// The contents of the checkpoint with the transactions and effects they refer to.
+    pub fn get_full_checkpoint_contents(
+        &self,
+        digest: CheckpointContentsDigest,
+    ) -> Result<FullCheckpointContents, anyhow::Error> {
+        let contents = self.get_checkpoint_contents(digest)?;
+        let store = RocksDbStore::new(
+            self.database.clone(),
+            self.committee_store().clone(),
+            self.get_checkpoint_store(),
+        );
+        FullCheckpointContents::from_checkpoint_contents(&store, contents)?.ok_or_else(|| {
+            anyhow!(
+                "Transactions of checkpoint contents not found for digest: {:?}",
+                digest
+            )
+        })
+    }
+
     pub fn get_checkpoint_contents_by_sequence_number(
         &self,
         sequence_number: CheckpointSequenceNumber,
@@ -2398,6 +3158,80 @@ impl AuthorityState {
         }
     }
 
+    /// Returns the objects changed by the transactions of the checkpoints after `from`, up to `to`
+    /// included: their references as of `to`, tombstones for the deleted and wrapped ones, and
+    /// whether each one existed as of `from`.
+    pub fn get_object_changes_between_checkpoints(
+        &self,
+        from: CheckpointSequenceNumber,
+        to: CheckpointSequenceNumber,
+    ) -> Result<BTreeMap<ObjectID, (ObjectRef, bool)>, anyhow::Error> {
+        let mut changes: BTreeMap<ObjectID, (ObjectRef, bool)> = BTreeMap::new();
+        for sequence_number in from + 1..=to {
+            let contents = self.get_checkpoint_contents_by_sequence_number(sequence_number)?;
+            let effects = self
+                .database
+                .multi_get_effects(contents.iter().map(|digests| &digests.effects))?;
+            // The transactions of a checkpoint are in causal order, the last change is kept.
+            for effects in effects {
+                let effects = effects.ok_or_else(|| {
+                    anyhow!("Transaction effects of checkpoint {sequence_number} are pruned")
+                })?;
+                let created = effects.created().iter().chain(effects.unwrapped());
+                let created = created.map(|(object_ref, _)| (object_ref, false));
+                let mutated = effects
+                    .mutated()
+                    .iter()
+                    .map(|(object_ref, _)| (object_ref, true));
+                let deleted = effects.deleted().iter().chain(effects.wrapped());
+                let deleted = deleted.map(|object_ref| (object_ref, true));
+                // These objects were wrapped as of `from` if it is their first change.
+                let unwrapped_then_deleted = effects
+                    .unwrapped_then_deleted()
+                    .iter()
+                    .map(|object_ref| (object_ref, false));
+                for (object_ref, existed) in created
+                    .chain(mutated)
+                    .chain(deleted)
+                    .chain(unwrapped_then_deleted)
+                {
+                    changes
+                        .entry(object_ref.0)
+                        .and_modify(|(latest, _)| *latest = *object_ref)
+                        .or_insert((*object_ref, existed));
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Returns the gas prices paid by the user transactions of the latest `num_checkpoints`
+    /// executed checkpoints. The transactions pruned from the store are skipped.
+    pub fn get_recent_gas_prices(
+        &self,
+        num_checkpoints: u64,
+    ) -> Result<RecentGasPrices, anyhow::Error> {
+        let last_checkpoint = self.get_latest_checkpoint_sequence_number()?;
+        let first_checkpoint = last_checkpoint.saturating_sub(num_checkpoints.saturating_sub(1));
+        let mut prices = Vec::new();
+        for sequence_number in first_checkpoint..=last_checkpoint {
+            let contents = self.get_checkpoint_contents_by_sequence_number(sequence_number)?;
+            let digests: Vec<_> = contents.iter().map(|digests| digests.transaction).collect();
+            let transactions = self.database.multi_get_transaction_blocks(&digests)?;
+            prices.extend(
+                transactions
+                    .into_iter()
+                    .flatten()
+                    .filter(|transaction| !transaction.is_system_tx())
+                    .map(|transaction| transaction.data().transaction_data().gas_price()),
+            );
+        }
+        Ok(RecentGasPrices::new(
+            first_checkpoint..=last_checkpoint,
+            prices,
+        ))
+    }
+
     pub fn get_checkpoints(
         &self,
         // If `Some`, the query will start from the next item after the specified cursor
@@ -2453,10 +3287,122 @@ impl AuthorityState {
         limit: usize,
         descending: bool,
     ) -> Result<Vec<SuiEvent>, anyhow::Error> {
+        Ok(self
+            .query_events_page(query, cursor, limit, descending)
+            .await?
+            .data)
+    }
+
+    /// Returns a page of the events matching the filter. The events are read from the index of
+    /// the most selective indexed filter the query is a conjunction of, or from all the events
+    /// otherwise, and the rest of the filter is evaluated on them. At most
+    /// [MAX_EVENTS_SCANNED_PER_QUERY] events are read: a page may hold less than `limit` events
+    /// and still have a next page, its cursor being the position the scan stopped at.
+    pub async fn query_events_page(
+        &self,
+        query: EventFilter,
+        // If `Some`, the query will start from the next item after the specified cursor
+        cursor: Option<EventID>,
+        limit: usize,
+        descending: bool,
+    ) -> Result<EventPage, anyhow::Error> {
         let index_store = self.get_indexes()?;
+        let index_filter = Self::event_index_filter(&query);
+        let needs_filtering = !matches!(index_filter, Some(filter) if std::ptr::eq(filter, &query));
+
+        let mut data = vec![];
+        let mut position = cursor;
+        let mut scanned = 0;
+        loop {
+            // Reads one more event than needed, to know whether there is a next page.
+            let batch_size = if needs_filtering {
+                EVENT_SCAN_BATCH_SIZE.max(limit + 1)
+            } else {
+                limit + 1 - data.len()
+            };
+            let event_keys = Self::get_event_keys(
+                &index_store,
+                index_filter,
+                position.as_ref(),
+                batch_size,
+                descending,
+            )?;
+            let exhausted = event_keys.len() < batch_size;
+            scanned += event_keys.len();
+
+            for event in self.get_events_by_keys(event_keys)? {
+                let event_id = event.id.clone();
+                if !needs_filtering || query.matches(&event) {
+                    if data.len() == limit {
+                        let next_cursor = data.last().map(|e: &SuiEvent| e.id.clone());
+                        return Ok(EventPage {
+                            data,
+                            next_cursor,
+                            has_next_page: true,
+                        });
+                    }
+                    data.push(event);
+                }
+                position = Some(event_id);
+            }
 
+            if exhausted {
+                let next_cursor = data.last().map(|e| e.id.clone()).or(position);
+                return Ok(EventPage {
+                    data,
+                    next_cursor,
+                    has_next_page: false,
+                });
+            }
+            if scanned >= MAX_EVENTS_SCANNED_PER_QUERY {
+                // The next page resumes the scan where it stopped.
+                return Ok(EventPage {
+                    data,
+                    next_cursor: position,
+                    has_next_page: true,
+                });
+            }
+        }
+    }
+
+    /// Returns the indexed filter to read the events of the query from, which the query implies,
+    /// or None if the events must be read from all the events.
+    fn event_index_filter(query: &EventFilter) -> Option<&EventFilter> {
+        // The lower the more selective.
+        fn selectivity(filter: &EventFilter) -> Option<u8> {
+            match filter {
+                EventFilter::Transaction(_) => Some(0),
+                EventFilter::MoveEventType(_) => Some(1),
+                EventFilter::MoveModule { .. } => Some(2),
+                EventFilter::Sender(_) => Some(3),
+                EventFilter::TimeRange { .. } => Some(4),
+                _ => None,
+            }
+        }
+        match query {
+            EventFilter::All(filters) => filters
+                .iter()
+                .filter_map(Self::event_index_filter)
+                .min_by_key(|filter| selectivity(filter)),
+            EventFilter::And(f1, f2) => [f1, f2]
+                .into_iter()
+                .filter_map(|filter| Self::event_index_filter(filter))
+                .min_by_key(|filter| selectivity(filter)),
+            filter => selectivity(filter).map(|_| filter),
+        }
+    }
+
+    /// Returns the keys of the events of the index of the filter, or of all the events if None,
+    /// following the cursor.
+    fn get_event_keys(
+        index_store: &IndexStore,
+        filter: Option<&EventFilter>,
+        cursor: Option<&EventID>,
+        limit: usize,
+        descending: bool,
+    ) -> Result<Vec<(TransactionEventsDigest, TransactionDigest, usize, u64)>, anyhow::Error> {
         //Get the tx_num from tx_digest
-        let (tx_num, event_num) = if let Some(cursor) = cursor.as_ref() {
+        let (tx_num, event_num) = if let Some(cursor) = cursor {
             let tx_seq = index_store.get_transaction_seq(&cursor.tx_digest)?.ok_or(
                 SuiError::TransactionNotFound {
                     digest: cursor.tx_digest,
@@ -2469,48 +3415,57 @@ impl AuthorityState {
             (0, 0)
         };
 
-        let limit = limit + 1;
-        let mut event_keys = match query {
-            EventFilter::All(..) => index_store.all_events(tx_num, event_num, limit, descending)?,
-            EventFilter::Transaction(digest) => {
-                index_store.events_by_transaction(&digest, tx_num, event_num, limit, descending)?
+        // The cursor is included by the indexes, one more event is read to skip it.
+        let limit = if cursor.is_some() { limit + 1 } else { limit };
+        let mut event_keys = match filter {
+            None => index_store.all_events(tx_num, event_num, limit, descending)?,
+            Some(EventFilter::Transaction(digest)) => {
+                index_store.events_by_transaction(digest, tx_num, event_num, limit, descending)?
             }
-            EventFilter::MoveModule { package, module } => {
-                let module_id = ModuleId::new(package.into(), module);
+            Some(EventFilter::MoveModule { package, module }) => {
+                let module_id = ModuleId::new((*package).into(), module.clone());
                 index_store.events_by_module_id(&module_id, tx_num, event_num, limit, descending)?
             }
-            EventFilter::MoveEventType(struct_name) => index_store
+            Some(EventFilter::MoveEventType(struct_name)) => index_store
                 .events_by_move_event_struct_name(
-                    &struct_name,
+                    struct_name,
                     tx_num,
                     event_num,
                     limit,
                     descending,
                 )?,
-            EventFilter::Sender(sender) => {
-                index_store.events_by_sender(&sender, tx_num, event_num, limit, descending)?
+            Some(EventFilter::Sender(sender)) => {
+                index_store.events_by_sender(sender, tx_num, event_num, limit, descending)?
             }
-            EventFilter::TimeRange {
+            Some(EventFilter::TimeRange {
                 start_time,
                 end_time,
-            } => index_store
-                .event_iterator(start_time, end_time, tx_num, event_num, limit, descending)?,
-            _ => {
-                return Err(anyhow!(
-                    "This query type is not supported by the full node."
-                ))
+            }) => index_store.event_iterator(
+                *start_time,
+                *end_time,
+                tx_num,
+                event_num,
+                limit,
+                descending,
+            )?,
+            Some(filter) => {
+                return Err(anyhow!("Events are not indexed by the filter {filter:?}"));
             }
         };
 
-        // skip one event if exclusive cursor is provided,
-        // otherwise truncate to the original limit.
-        if cursor.is_some() {
-            if !event_keys.is_empty() {
-                event_keys.remove(0);
-            }
-        } else {
+        if let Some(cursor) = cursor {
+            event_keys.retain(|(_, tx_digest, event_seq, _)| {
+                (*tx_digest, *event_seq as u64) != (cursor.tx_digest, cursor.event_seq)
+            });
             event_keys.truncate(limit - 1);
         }
+        Ok(event_keys)
+    }
+
+    fn get_events_by_keys(
+        &self,
+        event_keys: Vec<(TransactionEventsDigest, TransactionDigest, usize, u64)>,
+    ) -> Result<Vec<SuiEvent>, anyhow::Error> {
         let keys = event_keys.iter().map(|(digest, _, seq, _)| (*digest, *seq));
 
         let stored_events = self
@@ -2671,11 +3626,12 @@ impl AuthorityState {
                     epoch=?epoch_store.epoch(),
                     "Re-signing the effects with the current epoch"
                 );
+                let protocol_key = self.protocol_key.load();
                 SignedTransactionEffects::new(
                     epoch_store.epoch(),
                     effects,
-                    &*self.secret,
-                    self.name,
+                    &*protocol_key.secret,
+                    protocol_key.name,
                 )
             }
         };
@@ -2738,12 +3694,13 @@ impl AuthorityState {
         let tx_digest = certificate.digest();
         // Only need to sign effects if we are a validator.
         let effects_sig = if self.is_validator(epoch_store) {
+            let protocol_key = self.protocol_key.load();
             Some(AuthoritySignInfo::new(
                 epoch_store.epoch(),
                 effects,
                 Intent::default().with_scope(IntentScope::TransactionEffects),
-                self.name,
-                &*self.secret,
+                protocol_key.name,
+                &*protocol_key.secret,
             ))
         } else {
             None
@@ -2913,7 +3870,7 @@ impl AuthorityState {
 
         let Some(sui_system) = self.compare_system_package(
             SuiSystem::ID,
-            sui_system_injection::get_modules(self.name),
+            sui_system_injection::get_modules(self.name()),
             SuiSystem::transitive_dependencies(),
             max_binary_format_version,
         ).await else {
@@ -3055,7 +4012,7 @@ impl AuthorityState {
                     SuiFramework::transitive_dependencies(),
                 ),
                 SuiSystem::ID => (
-                    sui_system_injection::get_bytes(self.name),
+                    sui_system_injection::get_bytes(self.name()),
                     SuiSystem::transitive_dependencies(),
                 ),
                 _ => panic!("Unrecognised framework: {}", system_package.0),
@@ -3198,6 +4155,25 @@ impl AuthorityState {
                 buffer_stake_bps,
             );
 
+        // The last checkpoint of the epoch is not signed for a protocol version the binary cannot
+        // run: it is formed by the rest of the committee, and the node shuts down when it
+        // reconfigures to the next epoch.
+        if !self
+            .supported_protocol_versions
+            .is_version_supported(next_epoch_protocol_version)
+        {
+            self.metrics.protocol_upgrade_unsupported.set(1);
+            error!(
+                ?next_epoch_protocol_version,
+                supported_versions = ?self.supported_protocol_versions,
+                "upgraded protocol version is not supported, cannot create ChangeEpochTx. \
+                validator binary must be upgraded to the correct version!"
+            );
+            return Err(anyhow!(
+                "unsupported protocol version: cannot form ChangeEpochTx"
+            ));
+        }
+
         // since system packages are created during the current epoch, they should abide by the
         // rules of the current epoch, including the current epoch's max Move binary format version
         let Some(next_epoch_system_package_bytes) = self.get_system_package_bytes(
@@ -3353,7 +4329,7 @@ impl AuthorityState {
             .await?;
         fail_point!("before-open-new-epoch-store");
         let new_epoch_store = cur_epoch_store.new_at_next_epoch(
-            self.name,
+            self.name(),
             new_committee,
             epoch_start_configuration,
             self.db(),
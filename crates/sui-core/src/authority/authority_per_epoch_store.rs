@@ -231,6 +231,11 @@ pub struct AuthorityEpochTables {
     /// every message output by consensus (and in the right order).
     last_consensus_index: DBMap<u64, ExecutionIndicesWithHash>,
 
+    /// The user transactions deferred by the congestion control of the shared objects, keyed by
+    /// the index of the sub dag they are retried at, then by the position they were deferred at.
+    /// These transactions are not marked as processed, so that they are processed when retried.
+    deferred_transactions: DBMap<(u64, ExecutionIndices), (AuthorityName, ConsensusTransaction)>,
+
     /// This table lists all checkpoint boundaries in the consensus sequence
     ///
     /// The key in this table is incremental index and value is corresponding narwhal
@@ -604,6 +609,35 @@ impl AuthorityPerEpochStore {
             .map_err(SuiError::from)
     }
 
+    /// The transactions deferred to the given sub dag, in the order they were deferred in.
+    pub fn get_deferred_transactions(
+        &self,
+        sub_dag_index: u64,
+    ) -> SuiResult<Vec<(AuthorityName, ConsensusTransaction)>> {
+        Ok(self
+            .tables
+            .deferred_transactions
+            .iter()
+            .skip_to(&(sub_dag_index, ExecutionIndices::default()))?
+            .take_while(|((index, _), _)| *index == sub_dag_index)
+            .map(|(_, transaction)| transaction)
+            .collect())
+    }
+
+    /// Deletes the transactions deferred to the given sub dag, once it is fully processed.
+    pub fn delete_deferred_transactions(&self, sub_dag_index: u64) -> SuiResult {
+        self.tables
+            .deferred_transactions
+            .batch()
+            .delete_range(
+                &self.tables.deferred_transactions,
+                &(sub_dag_index, ExecutionIndices::default()),
+                &(sub_dag_index + 1, ExecutionIndices::default()),
+            )?
+            .write()?;
+        Ok(())
+    }
+
     pub fn get_accumulators_in_checkpoint_range(
         &self,
         from_checkpoint: CheckpointSequenceNumber,
@@ -1173,6 +1207,45 @@ impl AuthorityPerEpochStore {
         )
     }
 
+    /// Defers the user transaction to the given sub dag. Only the last consensus index is
+    /// updated, the transaction is not marked as processed.
+    pub fn defer_consensus_transaction(
+        &self,
+        transaction: VerifiedSequencedConsensusTransaction,
+        retry_at: u64,
+    ) -> SuiResult {
+        let VerifiedSequencedConsensusTransaction(SequencedConsensusTransaction {
+            certificate_author,
+            consensus_index,
+            transaction,
+            ..
+        }) = transaction;
+        let SequencedConsensusTransactionKind::External(transaction) = transaction else {
+            panic!("Only the user transactions can be deferred");
+        };
+        debug!(
+            tracking_id = ?transaction.get_tracking_id(),
+            ?retry_at,
+            "Deferring consensus transaction"
+        );
+        self.tables
+            .deferred_transactions
+            .batch()
+            .insert_batch(
+                &self.tables.deferred_transactions,
+                [(
+                    (retry_at, consensus_index.index),
+                    (certificate_author, transaction),
+                )],
+            )?
+            .insert_batch(
+                &self.tables.last_consensus_index,
+                [(LAST_CONSENSUS_INDEX_ADDR, consensus_index)],
+            )?
+            .write()?;
+        Ok(())
+    }
+
     pub fn record_consensus_transaction_processed(
         &self,
         transaction: &SequencedConsensusTransactionKind,
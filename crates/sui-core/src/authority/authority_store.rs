@@ -61,6 +61,9 @@ pub struct AuthorityStore {
     pub(crate) executed_effects_notify_read: NotifyRead<TransactionDigest, TransactionEffects>,
 
     pub(crate) root_state_notify_read: NotifyRead<EpochId, (CheckpointSequenceNumber, Accumulator)>,
+
+    // Notified by the checkpoint executor, to support notify_read_executed_checkpoint().
+    pub(crate) executed_checkpoint_notify_read: NotifyRead<CheckpointSequenceNumber, ()>,
     /// This lock denotes current 'execution epoch'.
     /// Execution acquires read lock, checks certificate epoch and holds it until all writes are complete.
     /// Reconfiguration acquires write lock, changes the epoch and revert all transactions
@@ -71,6 +74,10 @@ pub struct AuthorityStore {
     pub(crate) objects_lock_table: Arc<RwLockTable<ObjectContentDigest>>,
 
     indirect_objects_threshold: usize,
+
+    /// Whether the versions of the objects are indexed by checkpoint, see
+    /// `NodeConfig::archive_mode`.
+    archive_mode: bool,
 }
 
 pub type ExecutionLockReadGuard<'a> = RwLockReadGuard<'a, EpochId>;
@@ -85,6 +92,7 @@ impl AuthorityStore {
         genesis: &Genesis,
         committee_store: &Arc<CommitteeStore>,
         indirect_objects_threshold: usize,
+        archive_mode: bool,
     ) -> SuiResult<Self> {
         let perpetual_tables = Arc::new(AuthorityPerpetualTables::open(path, db_options.clone()));
         if perpetual_tables.database_is_empty()? {
@@ -105,6 +113,7 @@ impl AuthorityStore {
             perpetual_tables,
             &committee,
             indirect_objects_threshold,
+            archive_mode,
         )
         .await
     }
@@ -125,6 +134,7 @@ impl AuthorityStore {
             perpetual_tables,
             committee,
             indirect_objects_threshold,
+            false,
         )
         .await
     }
@@ -134,6 +144,7 @@ impl AuthorityStore {
         perpetual_tables: Arc<AuthorityPerpetualTables>,
         committee: &Committee,
         indirect_objects_threshold: usize,
+        archive_mode: bool,
     ) -> SuiResult<Self> {
         let epoch = committee.epoch;
 
@@ -143,9 +154,11 @@ impl AuthorityStore {
             executed_effects_notify_read: NotifyRead::new(),
             root_state_notify_read:
                 NotifyRead::<EpochId, (CheckpointSequenceNumber, Accumulator)>::new(),
+            executed_checkpoint_notify_read: NotifyRead::new(),
             execution_lock: RwLock::new(epoch),
             objects_lock_table: Arc::new(RwLockTable::new(NUM_SHARDS)),
             indirect_objects_threshold,
+            archive_mode,
         };
         // Only initialize an empty database.
         if store
@@ -187,6 +200,11 @@ impl AuthorityStore {
         Ok(store)
     }
 
+    /// Whether every version of the objects is kept and indexed by checkpoint.
+    pub fn is_archive_mode(&self) -> bool {
+        self.archive_mode
+    }
+
     pub fn get_root_state_hash(&self, epoch: EpochId) -> SuiResult<ECMHLiveObjectSetDigest> {
         let acc = self
             .perpetual_tables
@@ -304,9 +322,12 @@ impl AuthorityStore {
         Ok(result)
     }
 
+    /// Records the checkpoint of the transactions given with their effects and, in archive mode,
+    /// indexes the versions of the objects they changed.
     pub fn insert_finalized_transactions(
         &self,
         digests: &[TransactionDigest],
+        effects: &[TransactionEffects],
         epoch: EpochId,
         sequence: CheckpointSequenceNumber,
     ) -> SuiResult {
@@ -314,10 +335,15 @@ impl AuthorityStore {
             .perpetual_tables
             .executed_transactions_to_checkpoint
             .batch();
-        let batch = batch.insert_batch(
+        let mut batch = batch.insert_batch(
             &self.perpetual_tables.executed_transactions_to_checkpoint,
             digests.iter().map(|d| (*d, (epoch, sequence))),
         )?;
+        if self.archive_mode {
+            batch = self
+                .perpetual_tables
+                .insert_object_versions_at_checkpoint(batch, sequence, effects)?;
+        }
         batch.write()?;
         trace!("Transactions {digests:?} finalized at checkpoint {sequence} epoch {epoch}");
         Ok(())
@@ -343,6 +369,52 @@ impl AuthorityStore {
             .get(digest)?)
     }
 
+    /// Whether the transaction was executed in a checkpoint whose transactions were pruned.
+    pub fn is_transaction_pruned(&self, digest: &TransactionDigest) -> SuiResult<bool> {
+        let Some((_, checkpoint)) = self.get_transaction_checkpoint(digest)? else {
+            return Ok(false);
+        };
+        // The transactions of the genesis checkpoint are never pruned.
+        Ok(checkpoint > 0
+            && checkpoint
+                <= self
+                    .perpetual_tables
+                    .get_highest_pruned_transactions_checkpoint()?)
+    }
+
+    /// Whether the version of the object was pruned: only more recent versions of the object
+    /// remain.
+    /// Finds the reference of the object as of the checkpoint in the index kept in archive mode,
+    /// see `AuthorityPerpetualTables::find_object_ref_at_checkpoint`.
+    pub fn find_object_ref_at_checkpoint(
+        &self,
+        object_id: &ObjectID,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiResult<Option<Option<ObjectRef>>> {
+        self.perpetual_tables
+            .find_object_ref_at_checkpoint(*object_id, checkpoint)
+    }
+
+    pub fn is_object_version_pruned(
+        &self,
+        object_id: &ObjectID,
+        version: VersionNumber,
+    ) -> SuiResult<bool> {
+        if self.perpetual_tables.get_highest_pruned_checkpoint()? == 0 {
+            return Ok(false);
+        }
+        let lowest_version = self
+            .perpetual_tables
+            .objects
+            .iter()
+            .skip_to(&ObjectKey(*object_id, VersionNumber::MIN))?
+            .next();
+        Ok(matches!(
+            lowest_version,
+            Some((ObjectKey(id, lowest_version), _)) if id == *object_id && lowest_version > version
+        ))
+    }
+
     pub fn multi_get_transaction_checkpoint(
         &self,
         digests: &[TransactionDigest],
@@ -405,6 +477,31 @@ impl AuthorityStore {
         Ok(None)
     }
 
+    /// Returns the reference of the version of the object following `version`, or of the
+    /// tombstone left by the transaction which consumed `version`, if it was consumed.
+    pub fn get_object_ref_after_key(
+        &self,
+        object_id: &ObjectID,
+        version: VersionNumber,
+    ) -> Result<Option<ObjectRef>, SuiError> {
+        let iterator = self
+            .perpetual_tables
+            .objects
+            .iter()
+            .skip_to(&ObjectKey(*object_id, version))?;
+        for (object_key, value) in iterator {
+            if object_key.0 != *object_id {
+                break;
+            }
+            if object_key.1 > version {
+                return Ok(Some(
+                    self.perpetual_tables.object_reference(&object_key, value)?,
+                ));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn multi_get_object_by_key(
         &self,
         object_keys: &[ObjectKey],
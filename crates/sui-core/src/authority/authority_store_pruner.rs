@@ -9,6 +9,7 @@ use std::collections::HashMap;
 use std::{sync::Arc, time::Duration};
 use sui_config::node::AuthorityStorePruningConfig;
 use sui_storage::mutex_table::RwLockTable;
+use sui_types::message_envelope::Message;
 use sui_types::messages::{TransactionEffects, TransactionEffectsAPI};
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 use sui_types::{
@@ -18,6 +19,7 @@ use sui_types::{
 use tokio::sync::oneshot::{self, Sender};
 use tokio::time::Instant;
 use tracing::log::{debug, error};
+use typed_store::rocks::DBMap;
 use typed_store::Map;
 
 use super::authority_store_tables::AuthorityPerpetualTables;
@@ -102,6 +104,41 @@ impl AuthorityStorePruner {
         Ok(())
     }
 
+    /// prunes the transactions, effects and events of the given effects
+    fn prune_transactions(
+        transaction_effects: Vec<TransactionEffects>,
+        perpetual_db: &Arc<AuthorityPerpetualTables>,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> anyhow::Result<()> {
+        let _scope = monitored_scope("TransactionsPruner");
+        let mut wb = perpetual_db.transactions.batch();
+
+        let transactions: Vec<_> = transaction_effects
+            .iter()
+            .map(|effects| *effects.transaction_digest())
+            .collect();
+        wb = wb.delete_batch(&perpetual_db.transactions, transactions.iter())?;
+        wb = wb.delete_batch(&perpetual_db.executed_effects, transactions.iter())?;
+        wb = wb.delete_batch(
+            &perpetual_db.effects,
+            transaction_effects.iter().map(|effects| effects.digest()),
+        )?;
+        for events_digest in transaction_effects
+            .iter()
+            .flat_map(|effects| effects.events_digest())
+        {
+            wb = wb.delete_range(
+                &perpetual_db.events,
+                &(*events_digest, usize::MIN),
+                &(*events_digest, usize::MAX),
+            )?;
+        }
+        // The mapping to the checkpoints is kept, to tell apart the transactions pruned.
+        wb = perpetual_db.set_highest_pruned_transactions_checkpoint(wb, checkpoint_number)?;
+        wb.write()?;
+        Ok(())
+    }
+
     /// Prunes old object versions based on effects from all checkpoints from epochs eligible for pruning
     async fn prune_objects_for_eligible_epochs(
         perpetual_db: &Arc<AuthorityPerpetualTables>,
@@ -194,6 +231,96 @@ impl AuthorityStorePruner {
         Ok(())
     }
 
+    /// Prunes the transactions, effects and events of all checkpoints from epochs eligible for
+    /// pruning, whose object versions were already pruned
+    fn prune_transactions_for_eligible_epochs(
+        perpetual_db: &Arc<AuthorityPerpetualTables>,
+        checkpoint_store: &Arc<CheckpointStore>,
+        config: AuthorityStorePruningConfig,
+    ) -> anyhow::Result<()> {
+        let Some(num_epochs_to_retain) = config.num_epochs_to_retain_for_transactions else {
+            return Ok(());
+        };
+        let mut checkpoint_number = perpetual_db.get_highest_pruned_transactions_checkpoint()?;
+        // The objects pruner reads the effects of the checkpoints it prunes.
+        let highest_pruned_objects_checkpoint = perpetual_db.get_highest_pruned_checkpoint()?;
+        let current_epoch = checkpoint_store
+            .get_highest_executed_checkpoint()?
+            .map(|c| c.epoch())
+            .unwrap_or_default();
+        let mut checkpoints_in_batch = 0;
+        let mut batch_effects = vec![];
+        let mut network_total_transactions = 0;
+
+        debug!(
+            "Starting transactions pruning. Current epoch: {}. Latest pruned checkpoint: {}",
+            current_epoch, checkpoint_number
+        );
+        let iter = checkpoint_store
+            .certified_checkpoints
+            .iter()
+            .skip_to(&(checkpoint_number + 1))?
+            .map(|(k, ckpt)| (k, ckpt.into_inner()));
+
+        for (_, checkpoint) in iter {
+            if (current_epoch < checkpoint.epoch() + num_epochs_to_retain)
+                || (*checkpoint.sequence_number() > highest_pruned_objects_checkpoint)
+            {
+                break;
+            }
+            checkpoint_number = *checkpoint.sequence_number();
+            checkpoints_in_batch += 1;
+            if network_total_transactions == checkpoint.network_total_transactions {
+                continue;
+            }
+            network_total_transactions = checkpoint.network_total_transactions;
+
+            let content = checkpoint_store
+                .get_checkpoint_contents(&checkpoint.content_digest)?
+                .ok_or_else(|| anyhow::anyhow!("checkpoint content data is missing"))?;
+            let effects = perpetual_db
+                .effects
+                .multi_get(content.iter().map(|tx| tx.effects))?;
+
+            if effects.iter().any(|effect| effect.is_none()) {
+                return Err(anyhow::anyhow!("transaction effects data is missing"));
+            }
+            batch_effects.extend(effects.into_iter().flatten());
+
+            if batch_effects.len() >= config.max_transactions_in_batch
+                || checkpoints_in_batch >= config.max_checkpoints_in_batch
+            {
+                Self::prune_transactions(batch_effects, perpetual_db, checkpoint_number)?;
+                batch_effects = vec![];
+                checkpoints_in_batch = 0;
+            }
+        }
+        if checkpoints_in_batch > 0 {
+            Self::prune_transactions(batch_effects, perpetual_db, checkpoint_number)?;
+        }
+        debug!(
+            "Finished transactions pruner iteration. Latest pruned checkpoint: {}",
+            checkpoint_number
+        );
+        Ok(())
+    }
+
+    /// Compacts the pruned tables, so that the space of the pruned entries is reclaimed
+    fn compact(perpetual_db: &AuthorityPerpetualTables) {
+        let _scope = monitored_scope("PrunedTablesCompaction");
+        fn compact_table<K, V>(table: &DBMap<K, V>) {
+            table
+                .rocksdb
+                .compact_range_cf(&table.cf(), None::<Vec<u8>>, None::<Vec<u8>>);
+        }
+        compact_table(&perpetual_db.objects);
+        compact_table(&perpetual_db.indirect_move_objects);
+        compact_table(&perpetual_db.transactions);
+        compact_table(&perpetual_db.effects);
+        compact_table(&perpetual_db.executed_effects);
+        compact_table(&perpetual_db.events);
+    }
+
     fn setup_objects_pruning(
         config: AuthorityStorePruningConfig,
         epoch_duration_ms: u64,
@@ -216,6 +343,16 @@ impl AuthorityStorePruner {
         let mut prune_interval =
             tokio::time::interval_at(Instant::now() + pruning_initial_delay, tick_duration);
 
+        // A zero period disables the compactions, as `interval_at` panics on it.
+        let mut compaction_interval =
+            config
+                .compaction_period_secs
+                .filter(|secs| *secs > 0)
+                .map(|secs| {
+                    let period = Duration::from_secs(secs);
+                    tokio::time::interval_at(Instant::now() + period, period)
+                });
+
         tokio::task::spawn(async move {
             loop {
                 tokio::select! {
@@ -223,6 +360,15 @@ impl AuthorityStorePruner {
                         if let Err(err) = Self::prune_objects_for_eligible_epochs(&perpetual_db, &checkpoint_store, &objects_lock_table, config).await {
                             error!("Failed to prune objects: {:?}", err);
                         }
+                        if let Err(err) = Self::prune_transactions_for_eligible_epochs(&perpetual_db, &checkpoint_store, config) {
+                            error!("Failed to prune transactions: {:?}", err);
+                        }
+                    },
+                    _ = async { compaction_interval.as_mut().unwrap().tick().await }, if compaction_interval.is_some() => {
+                        let perpetual_db = perpetual_db.clone();
+                        if let Err(err) = tokio::task::spawn_blocking(move || Self::compact(&perpetual_db)).await {
+                            error!("Failed to compact the pruned tables: {:?}", err);
+                        }
                     },
                     _ = &mut recv => break,
                 }
@@ -267,7 +413,8 @@ mod tests {
     #[cfg(not(target_env = "msvc"))]
     use pprof::Symbol;
     use sui_storage::mutex_table::RwLockTable;
-    use sui_types::base_types::{ObjectDigest, VersionNumber};
+    use sui_types::base_types::{ObjectDigest, TransactionDigest, VersionNumber};
+    use sui_types::message_envelope::Message;
     use sui_types::messages::{TransactionEffects, TransactionEffectsAPI};
     use sui_types::{
         base_types::{ObjectID, SequenceNumber},
@@ -460,6 +607,38 @@ mod tests {
         .await;
     }
 
+    #[test]
+    fn test_transactions_pruning() {
+        let path = tempfile::tempdir().unwrap().into_path();
+        let db = Arc::new(AuthorityPerpetualTables::open(&path, None));
+        let mut all_effects = vec![];
+        for _ in 0..10 {
+            let mut effects = TransactionEffects::default();
+            *effects.transaction_digest_mut_for_testing() = TransactionDigest::random();
+            db.effects.insert(&effects.digest(), &effects).unwrap();
+            db.executed_effects
+                .insert(effects.transaction_digest(), &effects.digest())
+                .unwrap();
+            all_effects.push(effects);
+        }
+
+        let retained = all_effects.split_off(5);
+        AuthorityStorePruner::prune_transactions(all_effects.clone(), &db, 3).unwrap();
+        for effects in &all_effects {
+            assert!(db.effects.get(&effects.digest()).unwrap().is_none());
+            let digest = effects.transaction_digest();
+            assert!(db.executed_effects.get(digest).unwrap().is_none());
+        }
+        for effects in &retained {
+            assert!(db.effects.get(&effects.digest()).unwrap().is_some());
+            let digest = effects.transaction_digest();
+            assert!(db.executed_effects.get(digest).unwrap().is_some());
+        }
+        assert_eq!(db.get_highest_pruned_transactions_checkpoint().unwrap(), 3);
+        // The objects pruner progress is tracked separately.
+        assert_eq!(db.get_highest_pruned_checkpoint().unwrap(), 0);
+    }
+
     #[tokio::test]
     async fn test_ref_count_pruning() {
         let path = tempfile::tempdir().unwrap().into_path();
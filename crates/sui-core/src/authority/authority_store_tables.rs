@@ -4,6 +4,7 @@
 use super::*;
 use crate::authority::authority_store::LockDetailsWrapper;
 use rocksdb::Options;
+use std::collections::BTreeMap;
 use std::path::Path;
 use sui_types::accumulator::Accumulator;
 use sui_types::base_types::SequenceNumber;
@@ -97,6 +98,19 @@ pub struct AuthorityPerpetualTables {
 
     /// A singleton table that stores latest pruned checkpoint. Used to keep objects pruner progress
     pub(crate) pruned_checkpoint: DBMap<(), CheckpointSequenceNumber>,
+
+    /// A singleton table that stores the latest checkpoint whose transactions, effects and events
+    /// were pruned. Used to keep transactions pruner progress
+    pub(crate) pruned_transactions_checkpoint: DBMap<(), CheckpointSequenceNumber>,
+
+    /// The references of the objects changed by each checkpoint, at their last version written by
+    /// the transactions of the checkpoint, or their tombstones. Only written in archive mode.
+    pub(crate) object_versions_by_checkpoint:
+        DBMap<(ObjectID, CheckpointSequenceNumber), ObjectRef>,
+
+    /// A singleton table that stores the first checkpoint indexed in
+    /// `object_versions_by_checkpoint`, which covers the checkpoints from it on.
+    pub(crate) object_versions_index_start: DBMap<(), CheckpointSequenceNumber>,
 }
 
 impl AuthorityPerpetualTables {
@@ -226,6 +240,93 @@ impl AuthorityPerpetualTables {
         Ok(wb.insert_batch(&self.pruned_checkpoint, [((), checkpoint_number)])?)
     }
 
+    pub fn get_highest_pruned_transactions_checkpoint(
+        &self,
+    ) -> SuiResult<CheckpointSequenceNumber> {
+        Ok(self
+            .pruned_transactions_checkpoint
+            .get(&())?
+            .unwrap_or_default())
+    }
+
+    pub fn set_highest_pruned_transactions_checkpoint(
+        &self,
+        wb: DBBatch,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> SuiResult<DBBatch> {
+        Ok(wb.insert_batch(
+            &self.pruned_transactions_checkpoint,
+            [((), checkpoint_number)],
+        )?)
+    }
+
+    pub fn get_object_versions_index_start(&self) -> SuiResult<Option<CheckpointSequenceNumber>> {
+        Ok(self.object_versions_index_start.get(&())?)
+    }
+
+    /// Indexes the versions of the objects changed by the transactions of the checkpoint, given
+    /// their effects.
+    pub fn insert_object_versions_at_checkpoint(
+        &self,
+        wb: DBBatch,
+        checkpoint: CheckpointSequenceNumber,
+        effects: &[TransactionEffects],
+    ) -> SuiResult<DBBatch> {
+        // An object changed by several transactions of the checkpoint is at its highest version.
+        let mut object_refs: BTreeMap<ObjectID, ObjectRef> = BTreeMap::new();
+        for effects in effects {
+            let changed = effects.all_changed_objects().into_iter().map(|(r, _, _)| r);
+            let deleted = effects.all_deleted().into_iter().map(|(r, _)| r);
+            for object_ref in changed.chain(deleted) {
+                object_refs
+                    .entry(object_ref.0)
+                    .and_modify(|r| {
+                        if object_ref.1 > r.1 {
+                            *r = *object_ref;
+                        }
+                    })
+                    .or_insert(*object_ref);
+            }
+        }
+        let mut wb = wb.insert_batch(
+            &self.object_versions_by_checkpoint,
+            object_refs
+                .into_iter()
+                .map(|(id, object_ref)| ((id, checkpoint), object_ref)),
+        )?;
+        if self.get_object_versions_index_start()?.is_none() {
+            wb = wb.insert_batch(&self.object_versions_index_start, [((), checkpoint)])?;
+        }
+        Ok(wb)
+    }
+
+    /// Finds the reference of the object as of the checkpoint in the index of the versions of the
+    /// objects: `Some(None)` if the object did not exist yet, and `None` if the index does not
+    /// cover the checkpoint, or if the object was not changed since the index started.
+    pub fn find_object_ref_at_checkpoint(
+        &self,
+        object_id: ObjectID,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiResult<Option<Option<ObjectRef>>> {
+        let Some(start) = self.get_object_versions_index_start()? else {
+            return Ok(None);
+        };
+        if checkpoint < start {
+            return Ok(None);
+        }
+        let mut iter = self
+            .object_versions_by_checkpoint
+            .iter()
+            .skip_prior_to(&(object_id, checkpoint))?;
+        if let Some(((id, _), object_ref)) = iter.next() {
+            if id == object_id {
+                return Ok(Some(Some(object_ref)));
+            }
+        }
+        // Unless the index starts at genesis, the object may have been changed before it did.
+        Ok((start == 0).then_some(None))
+    }
+
     pub fn database_is_empty(&self) -> SuiResult<bool> {
         Ok(self
             .objects
@@ -320,6 +421,7 @@ fn objects_table_default_config() -> DBOptions {
         options: db_options.options,
         rw_options: ReadWriteOptions {
             ignore_range_deletions: true,
+            ..Default::default()
         },
     }
 }
@@ -0,0 +1,176 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::CompiledModule;
+use move_bytecode_utils::module_cache::GetModule;
+use move_core_types::language_storage::ModuleId;
+use move_core_types::resolver::ModuleResolver;
+use sui_protocol_config::ProtocolConfig;
+use sui_types::base_types::{ObjectID, ObjectRef};
+use sui_types::error::{SuiError, SuiResult, UserInputError};
+use sui_types::fp_ensure;
+use sui_types::messages::InputObjectKind;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use sui_types::object::{Object, Owner, PastObjectRead};
+use sui_types::storage::{
+    get_module_by_id, BackingPackageStore, ChildObjectResolver, ObjectStore, ParentSync,
+};
+
+use crate::authority::AuthorityState;
+use crate::transaction_input_checker::InputObjectSource;
+
+/// The objects as of a past checkpoint, to execute transactions against the state of the network
+/// once the transactions of the checkpoint and of the previous ones were executed. The objects
+/// are read with `AuthorityState::get_object_read_at_checkpoint`, so the versions of the objects
+/// pruned since cannot be read.
+#[derive(Clone, Copy)]
+pub struct CheckpointStateStore<'a> {
+    state: &'a AuthorityState,
+    checkpoint: CheckpointSequenceNumber,
+}
+
+impl<'a> CheckpointStateStore<'a> {
+    pub fn new(state: &'a AuthorityState, checkpoint: CheckpointSequenceNumber) -> Self {
+        Self { state, checkpoint }
+    }
+
+    pub fn checkpoint(&self) -> CheckpointSequenceNumber {
+        self.checkpoint
+    }
+
+    fn read(&self, object_id: &ObjectID) -> SuiResult<PastObjectRead> {
+        self.state
+            .get_object_read_at_checkpoint(object_id, self.checkpoint)
+            .map_err(|e| SuiError::GenericStorageError(e.to_string()))
+    }
+
+    fn object(&self, object_id: &ObjectID) -> SuiResult<Option<Object>> {
+        match self.read(object_id)? {
+            PastObjectRead::VersionFound(_, object, _) => Ok(Some(object)),
+            PastObjectRead::ObjectNotExists(_) | PastObjectRead::ObjectDeleted(_) => Ok(None),
+            PastObjectRead::VersionNotFound(object_id, version)
+            | PastObjectRead::VersionTooHigh {
+                object_id,
+                asked_version: version,
+                ..
+            } => Err(SuiError::GenericStorageError(format!(
+                "Version {version} of object {object_id}, as of checkpoint {}, is not available",
+                self.checkpoint
+            ))),
+        }
+    }
+}
+
+impl InputObjectSource for CheckpointStateStore<'_> {
+    fn check_input_objects(
+        &self,
+        objects: &[InputObjectKind],
+        protocol_config: &ProtocolConfig,
+    ) -> SuiResult<Vec<Object>> {
+        fp_ensure!(
+            objects.len() <= protocol_config.max_input_objects() as usize,
+            UserInputError::SizeLimitExceeded {
+                limit: "maximum input objects in a transaction".to_string(),
+                value: protocol_config.max_input_objects().to_string()
+            }
+            .into()
+        );
+
+        let mut result = Vec::new();
+        for kind in objects {
+            let id = kind.object_id();
+            let object = self
+                .object(&id)?
+                .ok_or_else(|| SuiError::from(kind.object_not_found_error()))?;
+            // The owned objects are used at the version they had as of the checkpoint.
+            if let InputObjectKind::ImmOrOwnedMoveObject(object_ref) = kind {
+                fp_ensure!(
+                    object.version() == object_ref.1,
+                    UserInputError::ObjectVersionUnavailableForConsumption {
+                        provided_obj_ref: *object_ref,
+                        current_version: object.version(),
+                    }
+                    .into()
+                );
+            }
+            result.push(object);
+        }
+        Ok(result)
+    }
+}
+
+impl BackingPackageStore for CheckpointStateStore<'_> {
+    fn get_package_object(&self, package_id: &ObjectID) -> SuiResult<Option<Object>> {
+        let package = self.object(package_id)?;
+        if let Some(object) = &package {
+            fp_ensure!(
+                object.is_package(),
+                SuiError::BadObjectType {
+                    error: format!("Package expected, Move object found: {package_id}"),
+                }
+            );
+        }
+        Ok(package)
+    }
+}
+
+impl ObjectStore for CheckpointStateStore<'_> {
+    fn get_object(&self, object_id: &ObjectID) -> Result<Option<Object>, SuiError> {
+        self.object(object_id)
+    }
+}
+
+impl ChildObjectResolver for CheckpointStateStore<'_> {
+    fn read_child_object(&self, parent: &ObjectID, child: &ObjectID) -> SuiResult<Option<Object>> {
+        let Some(child_object) = self.object(child)? else {
+            return Ok(None);
+        };
+        let parent = *parent;
+        if child_object.owner != Owner::ObjectOwner(parent.into()) {
+            return Err(SuiError::InvalidChildObjectAccess {
+                object: *child,
+                given_parent: parent,
+                actual_owner: child_object.owner,
+            });
+        }
+        Ok(Some(child_object))
+    }
+}
+
+impl ParentSync for CheckpointStateStore<'_> {
+    fn get_latest_parent_entry_ref(&self, object_id: ObjectID) -> SuiResult<Option<ObjectRef>> {
+        Ok(match self.read(&object_id)? {
+            PastObjectRead::VersionFound(object_ref, _, _)
+            | PastObjectRead::ObjectDeleted(object_ref) => Some(object_ref),
+            _ => None,
+        })
+    }
+}
+
+impl ModuleResolver for CheckpointStateStore<'_> {
+    type Error = SuiError;
+
+    fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self
+            .get_package_object(&ObjectID::from(*module_id.address()))?
+            .and_then(|package| {
+                // unwrap safe since get_package_object() ensures it's a package object.
+                package
+                    .data
+                    .try_as_package()
+                    .unwrap()
+                    .serialized_module_map()
+                    .get(module_id.name().as_str())
+                    .cloned()
+            }))
+    }
+}
+
+impl GetModule for CheckpointStateStore<'_> {
+    type Error = SuiError;
+    type Item = CompiledModule;
+
+    fn get_module_by_id(&self, id: &ModuleId) -> anyhow::Result<Option<Self::Item>, Self::Error> {
+        get_module_by_id(self, id)
+    }
+}
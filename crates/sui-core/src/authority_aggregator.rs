@@ -7,7 +7,8 @@ use crate::authority_client::{
     AuthorityAPI, NetworkAuthorityClient,
 };
 use crate::safe_client::{SafeClient, SafeClientMetrics, SafeClientMetricsBase};
-use futures::{future::BoxFuture, stream::FuturesUnordered, StreamExt};
+use crate::validator_scores::{ValidatorScore, ValidatorScores};
+use futures::{future::BoxFuture, stream::FuturesUnordered, Future, StreamExt};
 use mysten_metrics::monitored_future;
 use mysten_network::config::Config;
 use std::convert::AsRef;
@@ -32,13 +33,14 @@ use thiserror::Error;
 use tracing::{debug, error, info, trace, warn, Instrument};
 
 use prometheus::{
-    register_histogram_with_registry, register_int_counter_vec_with_registry,
-    register_int_counter_with_registry, Histogram, IntCounter, IntCounterVec, Registry,
+    register_gauge_vec_with_registry, register_histogram_with_registry,
+    register_int_counter_vec_with_registry, register_int_counter_with_registry, GaugeVec,
+    Histogram, IntCounter, IntCounterVec, Registry,
 };
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::string::ToString;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use sui_types::committee::{CommitteeWithNetworkMetadata, StakeUnit};
 use tokio::time::{sleep, timeout};
 
@@ -75,6 +77,11 @@ pub struct TimeoutConfig {
     // it is set to a value greater than serial_authority_request_timeout then it becomes
     // completely serial.
     pub serial_authority_request_interval: Duration,
+
+    // Delay before sending a transaction or a certificate to the validators deprioritized for
+    // being unresponsive, which are not sent it at all when the other validators reach a quorum
+    // in the meantime.
+    pub deprioritized_validator_delay: Duration,
 }
 
 impl Default for TimeoutConfig {
@@ -85,6 +92,7 @@ impl Default for TimeoutConfig {
             post_quorum_timeout: Duration::from_secs(30),
             serial_authority_request_timeout: Duration::from_secs(5),
             serial_authority_request_interval: Duration::from_millis(1000),
+            deprioritized_validator_delay: Duration::from_millis(1000),
         }
     }
 }
@@ -102,6 +110,9 @@ pub struct AuthAggMetrics {
     pub process_cert_errors: IntCounterVec,
     pub total_client_double_spend_attempts_detected: IntCounter,
     pub total_aggregated_err: IntCounterVec,
+    pub validator_success_rate: GaugeVec,
+    pub validator_latency_ms: GaugeVec,
+    pub total_deprioritized_validator_requests: IntCounterVec,
 }
 
 // Override default Prom buckets for positive numbers in 0-50k range
@@ -181,6 +192,27 @@ impl AuthAggMetrics {
                 registry,
             )
             .unwrap(),
+            validator_success_rate: register_gauge_vec_with_registry!(
+                "validator_success_rate",
+                "Moving average of the rate of transactions and certificates successfully handled by each validator",
+                &["name"],
+                registry,
+            )
+            .unwrap(),
+            validator_latency_ms: register_gauge_vec_with_registry!(
+                "validator_latency_ms",
+                "Moving average of the latency of the transactions and certificates handled by each validator",
+                &["name"],
+                registry,
+            )
+            .unwrap(),
+            total_deprioritized_validator_requests: register_int_counter_vec_with_registry!(
+                "total_deprioritized_validator_requests",
+                "Number of requests delayed for their validator being deprioritized as unresponsive",
+                &["name"],
+                registry,
+            )
+            .unwrap(),
         }
     }
 
@@ -351,6 +383,8 @@ pub struct AuthorityAggregator<A> {
     pub timeouts: TimeoutConfig,
     /// Store here for clone during re-config.
     pub committee_store: Arc<CommitteeStore>,
+    /// The scores of the validators, kept across re-configs.
+    pub validator_scores: Arc<ValidatorScores>,
 }
 
 impl<A> AuthorityAggregator<A> {
@@ -397,6 +431,7 @@ impl<A> AuthorityAggregator<A> {
             safe_client_metrics_base,
             timeouts,
             committee_store,
+            validator_scores: Default::default(),
         }
     }
 
@@ -427,6 +462,7 @@ impl<A> AuthorityAggregator<A> {
             safe_client_metrics_base,
             timeouts: Default::default(),
             committee_store,
+            validator_scores: Default::default(),
         }
     }
 
@@ -493,6 +529,7 @@ impl<A> AuthorityAggregator<A> {
             timeouts: self.timeouts.clone(),
             safe_client_metrics_base: self.safe_client_metrics_base.clone(),
             committee_store: self.committee_store.clone(),
+            validator_scores: self.validator_scores.clone(),
         })
     }
 
@@ -991,6 +1028,44 @@ where
         .map_err(|_| anyhow::anyhow!("Failed to get latest system state from the authorities"))
     }
 
+    /// Sends the request to the validator, after a delay if it is deprioritized, and records
+    /// the response in its score.
+    async fn scored_request<V>(
+        &self,
+        name: AuthorityName,
+        deprioritized: &BTreeSet<AuthorityName>,
+        request: impl Future<Output = SuiResult<V>>,
+    ) -> SuiResult<V> {
+        if deprioritized.contains(&name) {
+            self.metrics
+                .total_deprioritized_validator_requests
+                .with_label_values(&[&name.concise().to_string()])
+                .inc();
+            sleep(self.timeouts.deprioritized_validator_delay).await;
+        }
+        let start = Instant::now();
+        let response = request.await;
+        if let Some(score) = self
+            .validator_scores
+            .record(name, &response, start.elapsed())
+        {
+            self.record_validator_score_metrics(name, &score);
+        }
+        response
+    }
+
+    fn record_validator_score_metrics(&self, name: AuthorityName, score: &ValidatorScore) {
+        let name = name.concise().to_string();
+        self.metrics
+            .validator_success_rate
+            .with_label_values(&[&name])
+            .set(score.success_rate);
+        self.metrics
+            .validator_latency_ms
+            .with_label_values(&[&name])
+            .set(score.latency.as_secs_f64() * 1000.0);
+    }
+
     /// Submits the transaction to a quorum of validators to make a certificate.
     pub async fn process_transaction(
         &self,
@@ -1022,13 +1097,16 @@ where
         let transaction_ref = &transaction;
         let validity_threshold = committee.validity_threshold();
         let quorum_threshold = committee.quorum_threshold();
+        let deprioritized = &self.validator_scores.deprioritized_validators(&committee);
         let result = self
             .quorum_map_then_reduce_with_timeout(
                 state,
-                |_name, client| {
-                    Box::pin(
+                |name, client| {
+                    Box::pin(self.scored_request(
+                        name,
+                        deprioritized,
                         async move { client.handle_transaction(transaction_ref.clone()).await },
-                    )
+                    ))
                 },
                 |mut state, name, weight, response| {
                     Box::pin(async move {
@@ -1419,6 +1497,9 @@ where
         let cert_ref = &certificate;
         let threshold = self.committee.quorum_threshold();
         let validity = self.committee.validity_threshold();
+        let deprioritized = &self
+            .validator_scores
+            .deprioritized_validators(&self.committee);
         debug!(
             ?tx_digest,
             quorum_threshold = threshold,
@@ -1429,14 +1510,14 @@ where
         self.quorum_map_then_reduce_with_timeout(
             state,
             |name, client| {
-                Box::pin(async move {
+                Box::pin(self.scored_request(name, deprioritized, async move {
                     client
                         .handle_certificate(cert_ref.clone())
                         .instrument(
                             tracing::trace_span!("handle_certificate", authority =? name.concise()),
                         )
                         .await
-                })
+                }))
             },
             |mut state, name, weight, response| {
                 Box::pin(async move {
@@ -1613,8 +1694,9 @@ where
         validators: &BTreeSet<AuthorityName>,
         timeout_total: Option<Duration>,
     ) -> SuiResult<PlainTransactionInfoResponse> {
+        let responsive = self.validator_scores.responsive_validators(&self.committee);
         self.quorum_once_with_timeout(
-            None,
+            Some(&responsive),
             Some(validators),
             |_authority, client| {
                 Box::pin(async move {
@@ -13,6 +13,7 @@ use sui_types::committee::CommitteeWithNetworkMetadata;
 use sui_types::messages_checkpoint::{CheckpointRequest, CheckpointResponse};
 use sui_types::multiaddr::Multiaddr;
 use sui_types::sui_system_state::SuiSystemState;
+use sui_types::validator_report::{SignedValidatorReport, ValidatorReportRequest};
 use sui_types::{error::SuiError, messages::*};
 
 use sui_network::tonic::transport::Channel;
@@ -84,6 +85,16 @@ impl NetworkAuthorityClient {
     fn client(&self) -> ValidatorClient<Channel> {
         self.client.clone()
     }
+
+    /// The latest operational report signed by the validator, to be verified against the
+    /// committee with `SignedValidatorReport::verify_report`.
+    pub async fn validator_report(&self) -> Result<SignedValidatorReport, SuiError> {
+        self.client()
+            .validator_report(ValidatorReportRequest { _unused: false })
+            .await
+            .map(tonic::Response::into_inner)
+            .map_err(Into::into)
+    }
 }
 
 #[async_trait]
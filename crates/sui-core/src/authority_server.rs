@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
+use arc_swap::ArcSwapOption;
 use async_trait::async_trait;
 use mysten_metrics::spawn_monitored_task;
 use prometheus::{
@@ -16,6 +17,7 @@ use sui_network::{
 };
 use sui_types::multiaddr::Multiaddr;
 use sui_types::sui_system_state::SuiSystemState;
+use sui_types::validator_report::{SignedValidatorReport, ValidatorReportRequest};
 use sui_types::{error::*, messages::*};
 use sui_types::{
     fp_ensure,
@@ -39,6 +41,9 @@ pub(crate) const MAX_EXECUTION_QUEUE_LENGTH: usize = 20_000;
 // is above the threshold.
 pub(crate) const MAX_PER_OBJECT_EXECUTION_QUEUE_LENGTH: usize = 1000;
 
+/// The latest operational report signed by the validator, if it publishes reports.
+pub type LatestValidatorReport = Arc<ArcSwapOption<SignedValidatorReport>>;
+
 #[cfg(test)]
 #[path = "unit_tests/server_tests.rs"]
 mod server_tests;
@@ -88,7 +93,7 @@ impl AuthorityServer {
     ) -> Self {
         let consensus_adapter = Arc::new(ConsensusAdapter::new(
             Box::new(LazyNarwhalClient::new(consensus_address)),
-            state.name,
+            state.name(),
             Box::new(Arc::new(ConnectionMonitorStatusForTests {})),
             100_000,
             100_000,
@@ -119,6 +124,7 @@ impl AuthorityServer {
             .add_service(ValidatorServer::new(ValidatorService {
                 state: self.state,
                 consensus_adapter: self.consensus_adapter,
+                validator_report: Default::default(),
                 metrics: self.metrics.clone(),
             }))
             .bind(&address)
@@ -235,6 +241,7 @@ impl ValidatorServiceMetrics {
 pub struct ValidatorService {
     state: Arc<AuthorityState>,
     consensus_adapter: Arc<ConsensusAdapter>,
+    validator_report: LatestValidatorReport,
     metrics: Arc<ValidatorServiceMetrics>,
 }
 
@@ -242,11 +249,13 @@ impl ValidatorService {
     pub async fn new(
         state: Arc<AuthorityState>,
         consensus_adapter: Arc<ConsensusAdapter>,
+        validator_report: LatestValidatorReport,
         prometheus_registry: &Registry,
     ) -> Result<Self> {
         Ok(Self {
             state,
             consensus_adapter,
+            validator_report,
             metrics: Arc::new(ValidatorServiceMetrics::new(prometheus_registry)),
         })
     }
@@ -454,7 +463,7 @@ impl ValidatorService {
                     None
                 };
                 let transaction = ConsensusTransaction::new_certificate_message(
-                    &state.name,
+                    &state.name(),
                     certificate.clone().into(),
                 );
                 consensus_adapter.submit(transaction, Some(&reconfiguration_lock), &epoch_store)?;
@@ -601,4 +610,16 @@ impl Validator for ValidatorService {
 
         return Ok(tonic::Response::new(response));
     }
+
+    async fn validator_report(
+        &self,
+        _request: tonic::Request<ValidatorReportRequest>,
+    ) -> Result<tonic::Response<SignedValidatorReport>, tonic::Status> {
+        let Some(report) = self.validator_report.load_full() else {
+            return Err(tonic::Status::unavailable(
+                "The validator does not publish operational reports",
+            ));
+        };
+        Ok(tonic::Response::new(report.as_ref().clone()))
+    }
 }
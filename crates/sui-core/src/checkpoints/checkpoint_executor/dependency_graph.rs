@@ -0,0 +1,137 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dependencies between the transactions of a checkpoint, derived from the objects they access,
+//! so that the transactions accessing disjoint sets of objects are executed in parallel.
+//!
+//! The transactions of a checkpoint are causally ordered, so a transaction only depends on the
+//! previous transactions of the checkpoint: the ones which wrote an object it reads or writes,
+//! and the ones which read an object it writes since the object was last written. An object is
+//! read when it is an input of the transaction which is left unchanged by its effects (e.g. an
+//! immutable object or a package), and written when the effects create, mutate, wrap, unwrap or
+//! delete it.
+
+use std::collections::{HashMap, HashSet};
+
+use sui_types::{
+    base_types::{ObjectID, TransactionDigest},
+    messages::{
+        TransactionDataAPI, TransactionEffects, TransactionEffectsAPI,
+        VerifiedExecutableTransaction,
+    },
+};
+
+pub(crate) struct TransactionDependencyGraph {
+    /// The transactions without any pending dependency initially, in causal order.
+    roots: Vec<TransactionDigest>,
+    /// The number of dependencies of each transaction which are not executed yet.
+    pending_dependencies: HashMap<TransactionDigest, usize>,
+    /// The transactions depending on each transaction.
+    dependents: HashMap<TransactionDigest, Vec<TransactionDigest>>,
+}
+
+impl TransactionDependencyGraph {
+    /// Builds the graph of the transactions, given in causal order with their effects.
+    pub fn new<'a>(
+        transactions: impl IntoIterator<
+            Item = (&'a VerifiedExecutableTransaction, &'a TransactionEffects),
+        >,
+    ) -> Self {
+        Self::from_object_accesses(transactions.into_iter().map(|(transaction, effects)| {
+            let written: HashSet<ObjectID> = effects
+                .modified_at_versions()
+                .iter()
+                .map(|(id, _)| *id)
+                .chain(
+                    effects
+                        .all_changed_objects()
+                        .into_iter()
+                        .map(|(o, _, _)| o.0),
+                )
+                .chain(effects.all_deleted().into_iter().map(|(o, _)| o.0))
+                .chain(effects.shared_objects().iter().map(|o| o.0))
+                .collect();
+            // The inputs are checked when the transaction is certified, and all of the inputs it
+            // changes are in its effects anyway.
+            let read: HashSet<ObjectID> = transaction
+                .data()
+                .intent_message()
+                .value
+                .input_objects()
+                .unwrap_or_default()
+                .iter()
+                .map(|input| input.object_id())
+                .filter(|id| !written.contains(id))
+                .collect();
+            (*transaction.digest(), read, written)
+        }))
+    }
+
+    /// Builds the graph of the transactions, given in causal order with the objects they read
+    /// and the objects they write.
+    pub fn from_object_accesses(
+        transactions: impl IntoIterator<
+            Item = (TransactionDigest, HashSet<ObjectID>, HashSet<ObjectID>),
+        >,
+    ) -> Self {
+        let mut last_writer: HashMap<ObjectID, TransactionDigest> = HashMap::new();
+        let mut readers_since_write: HashMap<ObjectID, Vec<TransactionDigest>> = HashMap::new();
+        let mut graph = Self {
+            roots: Vec::new(),
+            pending_dependencies: HashMap::new(),
+            dependents: HashMap::new(),
+        };
+
+        for (digest, read, written) in transactions {
+            let mut dependencies = HashSet::new();
+            for id in &read {
+                dependencies.extend(last_writer.get(id).copied());
+            }
+            for id in &written {
+                dependencies.extend(last_writer.get(id).copied());
+                dependencies.extend(readers_since_write.get(id).into_iter().flatten().copied());
+            }
+            dependencies.remove(&digest);
+
+            for id in read {
+                readers_since_write.entry(id).or_default().push(digest);
+            }
+            for id in written {
+                last_writer.insert(id, digest);
+                readers_since_write.remove(&id);
+            }
+
+            if dependencies.is_empty() {
+                graph.roots.push(digest);
+            }
+            graph
+                .pending_dependencies
+                .insert(digest, dependencies.len());
+            for dependency in dependencies {
+                graph.dependents.entry(dependency).or_default().push(digest);
+            }
+        }
+        graph
+    }
+
+    /// Returns the transactions which can be executed right away.
+    pub fn roots(&self) -> &[TransactionDigest] {
+        &self.roots
+    }
+
+    /// Marks the transaction as executed, and returns the transactions which can now be executed.
+    pub fn mark_executed(&mut self, digest: &TransactionDigest) -> Vec<TransactionDigest> {
+        let mut ready = Vec::new();
+        for dependent in self.dependents.remove(digest).unwrap_or_default() {
+            let pending = self
+                .pending_dependencies
+                .get_mut(&dependent)
+                .expect("dependents must be in the graph");
+            *pending -= 1;
+            if *pending == 0 {
+                ready.push(dependent);
+            }
+        }
+        ready
+    }
+}
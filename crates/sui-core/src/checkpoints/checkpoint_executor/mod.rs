@@ -9,7 +9,9 @@
 //! as quickly as possible so that a newly joined, or recovering Node can
 //! participate in a timely manner. To that end, CheckpointExecutor attempts
 //! to saturate the CPU with executor tasks (one per checkpoint), each of which
-//! handle scheduling and awaiting checkpoint transaction execution.
+//! handle scheduling and awaiting checkpoint transaction execution. Within a checkpoint, the
+//! transactions are scheduled in parallel as well, following the dependencies between the
+//! transactions which access the same objects.
 //!
 //! CheckpointExecutor is made recoverable in the event of Node shutdown by way of a watermark,
 //! highest_executed_checkpoint, which is guaranteed to be updated sequentially in order,
@@ -19,12 +21,13 @@
 //! end of epoch. This allows us to use it as a signal for reconfig.
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     sync::Arc,
     time::{Duration, Instant, SystemTime},
 };
 
-use futures::stream::FuturesOrdered;
+use futures::stream::{FuturesOrdered, FuturesUnordered};
+use futures::FutureExt;
 use itertools::izip;
 use mysten_metrics::{spawn_monitored_task, MonitoredFutureExt};
 use prometheus::Registry;
@@ -53,8 +56,10 @@ use crate::state_accumulator::StateAccumulator;
 use crate::transaction_manager::TransactionManager;
 use crate::{authority::EffectsNotifyRead, checkpoints::CheckpointStore};
 
+use self::dependency_graph::TransactionDependencyGraph;
 use self::metrics::CheckpointExecutorMetrics;
 
+mod dependency_graph;
 mod metrics;
 #[cfg(test)]
 pub(crate) mod tests;
@@ -240,6 +245,9 @@ impl CheckpointExecutor {
         self.checkpoint_store
             .update_highest_executed_checkpoint(checkpoint)
             .unwrap();
+        self.authority_store
+            .executed_checkpoint_notify_read
+            .notify(&seq, &());
         self.metrics.last_executed_checkpoint.set(seq as i64);
     }
 
@@ -302,6 +310,10 @@ impl CheckpointExecutor {
 
         let metrics = self.metrics.clone();
         let local_execution_timeout_sec = self.config.local_execution_timeout_sec;
+        let transaction_execution_max_concurrency = self
+            .config
+            .transaction_execution_max_concurrency
+            .unwrap_or_else(num_cpus::get);
         let authority_store = self.authority_store.clone();
         let checkpoint_store = self.checkpoint_store.clone();
         let tx_manager = self.tx_manager.clone();
@@ -317,6 +329,7 @@ impl CheckpointExecutor {
                 tx_manager.clone(),
                 accumulator.clone(),
                 local_execution_timeout_sec,
+                transaction_execution_max_concurrency,
                 &metrics,
             )
             .await
@@ -368,6 +381,7 @@ impl CheckpointExecutor {
                         epoch_store.clone(),
                         self.tx_manager.clone(),
                         self.config.local_execution_timeout_sec,
+                        1,
                         checkpoint.clone(),
                     )
                     .await
@@ -438,6 +452,7 @@ async fn execute_checkpoint(
     transaction_manager: Arc<TransactionManager>,
     accumulator: Arc<StateAccumulator>,
     local_execution_timeout_sec: u64,
+    transaction_execution_max_concurrency: usize,
     metrics: &Arc<CheckpointExecutorMetrics>,
 ) -> SuiResult {
     let checkpoint_sequence = *checkpoint.sequence_number();
@@ -478,6 +493,7 @@ async fn execute_checkpoint(
         epoch_store.clone(),
         transaction_manager,
         local_execution_timeout_sec,
+        transaction_execution_max_concurrency,
         checkpoint,
     )
     .await?;
@@ -674,6 +690,7 @@ async fn execute_transactions(
     epoch_store: Arc<AuthorityPerEpochStore>,
     transaction_manager: Arc<TransactionManager>,
     log_timeout_sec: u64,
+    max_concurrency: usize,
     checkpoint: VerifiedCheckpoint,
 ) -> SuiResult<Vec<TransactionEffects>> {
     let effects_digests = execution_digests.iter().map(|digest| digest.effects);
@@ -704,80 +721,116 @@ async fn execute_transactions(
         }
     }
 
-    transaction_manager.enqueue(executable_txns, &epoch_store)?;
+    // Schedule the transactions of the checkpoint in parallel, as soon as the transactions which
+    // accessed the same objects before them are executed.
+    let mut dependency_graph = TransactionDependencyGraph::new(
+        executable_txns
+            .iter()
+            .map(|tx| (tx, digest_to_effects.get(tx.digest()).unwrap())),
+    );
+    let mut ready: VecDeque<_> = dependency_graph.roots().iter().copied().collect();
+    let mut unscheduled_txns: HashMap<_, _> = executable_txns
+        .into_iter()
+        .map(|tx| (*tx.digest(), tx))
+        .collect();
+    let mut executing = FuturesUnordered::new();
 
-    // Once synced_txns have been awaited, all txns should have effects committed.
     let mut periods = 1;
     let log_timeout_sec = Duration::from_secs(log_timeout_sec);
 
     loop {
-        let effects_future = authority_store.notify_read_executed_effects(all_tx_digests.clone());
+        while executing.len() < max_concurrency.max(1) {
+            let Some(digest) = ready.pop_front() else {
+                break;
+            };
+            let tx = unscheduled_txns.remove(&digest).unwrap();
+            transaction_manager.enqueue(vec![tx], &epoch_store)?;
+            executing.push(
+                authority_store
+                    .notify_read_executed_effects(vec![digest])
+                    .map(move |result| result.map(|_| digest)),
+            );
+        }
+        if executing.is_empty() {
+            break;
+        }
 
-        match timeout(log_timeout_sec, effects_future).await {
+        match timeout(log_timeout_sec, executing.next()).await {
             Err(_elapsed) => {
-                let missing_digests: Vec<TransactionDigest> = authority_store
-                    .multi_get_executed_effects(&all_tx_digests)?
-                    .iter()
-                    .zip(all_tx_digests.clone())
-                    .filter_map(
-                        |(fx, digest)| {
-                            if fx.is_none() {
-                                Some(digest)
-                            } else {
-                                None
-                            }
-                        },
-                    )
-                    .collect();
-
-                if missing_digests.is_empty() {
-                    // All effects just become available.
-                    continue;
-                }
-
-                warn!(
-                    "Transaction effects for checkpoint tx digests {:?} not present within {:?}. ",
-                    missing_digests,
+                warn_pending_transactions(
+                    &all_tx_digests,
+                    &authority_store,
+                    &transaction_manager,
                     log_timeout_sec * periods,
-                );
-
-                // Print out more information for the 1st pending transaction, which should have
-                // all of its input available.
-                let pending_digest = missing_digests.first().unwrap();
-                let missing_input = transaction_manager.get_missing_input(pending_digest);
-                let pending_transaction = authority_store
-                    .get_transaction_block(pending_digest)?
-                    .expect("state-sync should have ensured that the transaction exists");
-
-                warn!(
-                    "Transaction {pending_digest:?} has missing input objects {missing_input:?}\
-                    \nTransaction input: {:?}\nTransaction content: {:?}",
-                    pending_transaction
-                        .data()
-                        .intent_message()
-                        .value
-                        .input_objects(),
-                    pending_transaction,
-                );
+                )?;
                 periods += 1;
             }
-            Ok(Err(err)) => return Err(err),
-            Ok(Ok(effects)) => {
-                for (tx_digest, expected_digest, actual_effects) in
-                    izip!(&all_tx_digests, &execution_digests, &effects)
-                {
-                    let expected_effects_digest = &expected_digest.effects;
-                    assert_not_forked(
-                        &checkpoint,
-                        tx_digest,
-                        expected_effects_digest,
-                        actual_effects,
-                    );
-                }
-                return Ok(effects);
-            }
+            Ok(Some(Err(err))) => return Err(err),
+            Ok(Some(Ok(digest))) => ready.extend(dependency_graph.mark_executed(&digest)),
+            Ok(None) => unreachable!("executing is not empty"),
         }
     }
+    debug_assert!(unscheduled_txns.is_empty());
+
+    // Once all of the transactions have been executed, all txns should have effects committed.
+    let effects = authority_store
+        .notify_read_executed_effects(all_tx_digests.clone())
+        .await?;
+    for (tx_digest, expected_digest, actual_effects) in
+        izip!(&all_tx_digests, &execution_digests, &effects)
+    {
+        let expected_effects_digest = &expected_digest.effects;
+        assert_not_forked(
+            &checkpoint,
+            tx_digest,
+            expected_effects_digest,
+            actual_effects,
+        );
+    }
+    Ok(effects)
+}
+
+fn warn_pending_transactions(
+    all_tx_digests: &[TransactionDigest],
+    authority_store: &AuthorityStore,
+    transaction_manager: &TransactionManager,
+    elapsed: Duration,
+) -> SuiResult {
+    let missing_digests: Vec<TransactionDigest> = authority_store
+        .multi_get_executed_effects(all_tx_digests)?
+        .iter()
+        .zip(all_tx_digests)
+        .filter_map(|(fx, digest)| if fx.is_none() { Some(*digest) } else { None })
+        .collect();
+
+    // All effects may just have become available.
+    let Some(pending_digest) = missing_digests.first() else {
+        return Ok(());
+    };
+
+    warn!(
+        "Transaction effects for checkpoint tx digests {:?} not present within {:?}. ",
+        missing_digests, elapsed,
+    );
+
+    // Print out more information for the 1st pending transaction, which should have
+    // all of its input available.
+    let missing_input = transaction_manager.get_missing_input(pending_digest);
+    let pending_transaction = authority_store
+        .get_transaction_block(pending_digest)?
+        .expect("state-sync should have ensured that the transaction exists");
+
+    warn!(
+        "Transaction {pending_digest:?} has missing input objects {missing_input:?}\
+        \nTransaction input: {:?}\nTransaction content: {:?}",
+        pending_transaction
+            .data()
+            .intent_message()
+            .value
+            .input_objects(),
+        pending_transaction,
+    );
+    Ok(())
 }
 
 fn finalize_checkpoint(
@@ -790,6 +843,7 @@ fn finalize_checkpoint(
 ) -> SuiResult {
     authority_store.insert_finalized_transactions(
         tx_digests,
+        &effects,
         epoch_store.epoch(),
         checkpoint_sequence,
     )?;
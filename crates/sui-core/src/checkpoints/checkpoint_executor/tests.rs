@@ -99,6 +99,58 @@ pub async fn test_checkpoint_executor_crash_recovery() {
     executor_handle.abort();
 }
 
+/// Test that readers waiting for a checkpoint are notified once it is executed.
+#[tokio::test]
+pub async fn test_notify_read_executed_checkpoint() {
+    let buffer_size = num_cpus::get() * 2;
+    let tempdir = tempdir().unwrap();
+    let checkpoint_store = CheckpointStore::new(tempdir.path());
+
+    let (state, mut executor, _accumulator, checkpoint_sender, committee): (
+        Arc<AuthorityState>,
+        CheckpointExecutor,
+        Arc<StateAccumulator>,
+        Sender<VerifiedCheckpoint>,
+        CommitteeFixture,
+    ) = init_executor_test(buffer_size, checkpoint_store.clone()).await;
+
+    let state_clone = state.clone();
+    let waiter = tokio::spawn(async move {
+        state_clone
+            .notify_read_executed_checkpoint(buffer_size as u64)
+            .await
+    });
+    let _ = sync_new_checkpoints(
+        &checkpoint_store,
+        &checkpoint_sender,
+        buffer_size,
+        None,
+        &committee,
+    );
+    let epoch_store = state.epoch_store_for_testing().clone();
+    let executor_handle =
+        spawn_monitored_task!(async move { executor.run_epoch(epoch_store).await });
+
+    // The checkpoint waited for is not synced yet.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(!waiter.is_finished());
+
+    let _ = sync_new_checkpoints(
+        &checkpoint_store,
+        &checkpoint_sender,
+        1,
+        checkpoint_store.get_highest_synced_checkpoint().unwrap(),
+        &committee,
+    );
+    timeout(Duration::from_secs(5), waiter)
+        .await
+        .expect("Timed out waiting for the checkpoint to be executed")
+        .unwrap()
+        .unwrap();
+
+    executor_handle.abort();
+}
+
 /// Test that checkpoint execution correctly signals end of epoch after
 /// receiving last checkpoint of epoch, then resumes executing cehckpoints
 /// from the next epoch if called after reconfig
@@ -371,6 +423,43 @@ pub async fn test_reconfig_crash_recovery() {
     );
 }
 
+/// Test that the transactions of a checkpoint only wait for the previous transactions which
+/// accessed the same objects.
+#[test]
+pub fn test_transaction_dependency_graph() {
+    use super::dependency_graph::TransactionDependencyGraph;
+    use std::collections::HashSet;
+    use sui_types::base_types::ObjectID;
+
+    let (a, b, c, d, e) = (
+        TransactionDigest::random(),
+        TransactionDigest::random(),
+        TransactionDigest::random(),
+        TransactionDigest::random(),
+        TransactionDigest::random(),
+    );
+    let (object_1, object_2, package) =
+        (ObjectID::random(), ObjectID::random(), ObjectID::random());
+    let objects = |ids: &[ObjectID]| ids.iter().copied().collect::<HashSet<_>>();
+    let mut graph = TransactionDependencyGraph::from_object_accesses(vec![
+        // a and b write disjoint objects, and read the same package
+        (a, objects(&[package]), objects(&[object_1])),
+        (b, objects(&[package]), objects(&[object_2])),
+        // c writes the objects of both a and b
+        (c, objects(&[]), objects(&[object_1, object_2])),
+        // d reads the object written by c, and e writes it after d read it
+        (d, objects(&[object_1]), objects(&[])),
+        (e, objects(&[]), objects(&[object_1])),
+    ]);
+
+    assert_eq!(graph.roots(), &[a, b]);
+    assert!(graph.mark_executed(&a).is_empty());
+    assert_eq!(graph.mark_executed(&b), vec![c]);
+    assert_eq!(graph.mark_executed(&c), vec![d]);
+    assert_eq!(graph.mark_executed(&d), vec![e]);
+    assert!(graph.mark_executed(&e).is_empty());
+}
+
 async fn init_executor_test(
     buffer_size: usize,
     store: Arc<CheckpointStore>,
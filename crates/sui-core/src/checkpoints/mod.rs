@@ -48,6 +48,7 @@ use sui_types::signature::GenericSignature;
 use sui_types::sui_system_state::{SuiSystemState, SuiSystemStateTrait};
 use tokio::{
     sync::{watch, Notify},
+    task::JoinHandle,
     time::timeout,
 };
 use tracing::{debug, error, info, trace, warn};
@@ -1099,6 +1100,38 @@ pub trait CheckpointServiceNotify {
     ) -> SuiResult;
 }
 
+/// The name of the checkpoint service in the lifecycle of the node.
+pub const CHECKPOINT_SERVICE: &str = "checkpoint-service";
+
+/// How long the tasks of the checkpoint service are waited for at shutdown, before they are
+/// aborted.
+const CHECKPOINT_SERVICE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Stops the tasks of the checkpoint service. Dropping it signals the tasks to exit without
+/// waiting for them.
+pub struct CheckpointServiceExit {
+    exit: watch::Sender<()>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl CheckpointServiceExit {
+    /// Signals the tasks to exit and waits until they have, aborting the ones still running
+    /// after a timeout.
+    pub async fn shutdown(self) {
+        let Self { exit, handles } = self;
+        drop(exit);
+        for mut handle in handles {
+            if timeout(CHECKPOINT_SERVICE_SHUTDOWN_TIMEOUT, &mut handle)
+                .await
+                .is_err()
+            {
+                warn!("Checkpoint service task did not exit in time, aborting it");
+                handle.abort();
+            }
+        }
+    }
+}
+
 /// This is a service used to communicate with other pieces of sui(for ex. authority)
 pub struct CheckpointService {
     tables: Arc<CheckpointStore>,
@@ -1120,7 +1153,7 @@ impl CheckpointService {
         metrics: Arc<CheckpointMetrics>,
         max_transactions_per_checkpoint: usize,
         max_checkpoint_size_bytes: usize,
-    ) -> (Arc<Self>, watch::Sender<()> /* The exit sender */) {
+    ) -> (Arc<Self>, CheckpointServiceExit) {
         info!(
             "Starting checkpoint service with {max_transactions_per_checkpoint} max_transactions_per_checkpoint and {max_checkpoint_size_bytes} max_checkpoint_size_bytes"
         );
@@ -1144,7 +1177,7 @@ impl CheckpointService {
             max_checkpoint_size_bytes,
         );
 
-        spawn_monitored_task!(builder.run());
+        let builder_handle = spawn_monitored_task!(builder.run());
 
         let aggregator = CheckpointAggregator::new(
             checkpoint_store.clone(),
@@ -1155,7 +1188,7 @@ impl CheckpointService {
             metrics.clone(),
         );
 
-        spawn_monitored_task!(aggregator.run());
+        let aggregator_handle = spawn_monitored_task!(aggregator.run());
 
         let last_signature_index = epoch_store.get_last_checkpoint_signature_index();
         let last_signature_index = Mutex::new(last_signature_index);
@@ -1167,7 +1200,11 @@ impl CheckpointService {
             last_signature_index,
             metrics,
         });
-        (service, exit_snd)
+        let exit = CheckpointServiceExit {
+            exit: exit_snd,
+            handles: vec![builder_handle, aggregator_handle],
+        };
+        (service, exit)
     }
 }
 
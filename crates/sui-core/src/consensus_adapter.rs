@@ -9,7 +9,7 @@ use futures::future::{select, Either};
 use futures::FutureExt;
 use itertools::Itertools;
 use mysten_network::Multiaddr;
-use narwhal_types::{TransactionProto, TransactionsClient};
+use narwhal_types::{PriorityLane, TransactionPriorityProto, TransactionProto, TransactionsClient};
 use narwhal_worker::LocalNarwhalClient;
 use parking_lot::{Mutex, RwLockReadGuard};
 use prometheus::IntGauge;
@@ -21,10 +21,11 @@ use prometheus::{register_histogram_vec_with_registry, register_int_gauge_with_r
 use prometheus::{HistogramVec, IntCounter};
 use rand::rngs::StdRng;
 use rand::SeedableRng;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::future::Future;
 use std::ops::Deref;
 use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Instant;
@@ -46,7 +47,7 @@ use mysten_metrics::{spawn_monitored_task, GaugeGuard, GaugeGuardFutureExt};
 use sui_simulator::anemo::PeerId;
 use sui_simulator::narwhal_network::connectivity::ConnectionStatus;
 use sui_types::base_types::AuthorityName;
-use sui_types::messages::ConsensusTransactionKind;
+use sui_types::messages::{ConsensusTransactionKey, ConsensusTransactionKind};
 use tokio::time::Duration;
 use tracing::{debug, info, warn};
 
@@ -163,6 +164,16 @@ pub trait SubmitToConsensus: Sync + Send + 'static {
     ) -> SuiResult;
 }
 
+/// The Narwhal priority lane a consensus transaction is submitted to. Transactions issued by the
+/// validator itself are not queued behind user transactions.
+fn priority_lane(transaction: &ConsensusTransaction) -> PriorityLane {
+    if transaction.is_user_certificate() {
+        PriorityLane::Normal
+    } else {
+        PriorityLane::System
+    }
+}
+
 #[async_trait::async_trait]
 impl SubmitToConsensus for TransactionsClient<sui_network::tonic::transport::Channel> {
     async fn submit_to_consensus(
@@ -175,7 +186,10 @@ impl SubmitToConsensus for TransactionsClient<sui_network::tonic::transport::Cha
         let bytes = Bytes::from(serialized.clone());
 
         self.clone()
-            .submit_transaction(TransactionProto { transaction: bytes })
+            .submit_transaction(TransactionProto {
+                transaction: bytes,
+                priority: TransactionPriorityProto::from(priority_lane(transaction)).into(),
+            })
             .await
             .map_err(|e| SuiError::ConsensusConnectionBroken(format!("{:?}", e)))
             .tap_err(|r| {
@@ -232,6 +246,7 @@ impl SubmitToConsensus for LazyNarwhalClient {
         transaction: &ConsensusTransaction,
         _epoch_store: &Arc<AuthorityPerEpochStore>,
     ) -> SuiResult {
+        let lane = priority_lane(transaction);
         let transaction =
             bcs::to_bytes(transaction).expect("Serializing consensus transaction cannot fail");
         // The retrieved LocalNarwhalClient can be from the past epoch. Submit would fail after
@@ -247,7 +262,7 @@ impl SubmitToConsensus for LazyNarwhalClient {
         };
         let client = client.as_ref().unwrap().load();
         client
-            .submit_transaction(transaction)
+            .submit_transaction_in_lane(transaction, lane)
             .await
             .map_err(|e| SuiError::FailedToSubmitToConsensus(format!("{:?}", e)))
             .tap_err(|r| {
@@ -262,10 +277,11 @@ impl SubmitToConsensus for LazyNarwhalClient {
 pub struct ConsensusAdapter {
     /// The network client connecting to the consensus node of this authority.
     consensus_client: Box<dyn SubmitToConsensus>,
-    /// Authority pubkey.
-    authority: AuthorityName,
-    /// The limit to number of inflight transactions at this node.
-    max_pending_transactions: usize,
+    /// Authority pubkey, replaced at the start of the epochs following a rotation of the protocol
+    /// key.
+    authority: ArcSwap<AuthorityName>,
+    /// The limit to number of inflight transactions at this node, which can be changed at runtime.
+    max_pending_transactions: AtomicUsize,
     /// Number of submitted transactions still inflight at this node.
     num_inflight_transactions: AtomicU64,
     /// A structure to check the connection statuses populated by the Connection Monitor Listener
@@ -277,6 +293,21 @@ pub struct ConsensusAdapter {
     /// Semaphore limiting parallel submissions to narwhal
     submit_semaphore: Semaphore,
     latency_observer: LatencyObserver,
+    /// The transactions submitted by this node and not yet processed, and the id of the next one.
+    pending_transactions: Mutex<BTreeMap<u64, PendingConsensusTransaction>>,
+    next_pending_id: AtomicU64,
+}
+
+/// A transaction submitted to the consensus adapter, and not yet processed out of consensus.
+#[derive(Clone, Debug)]
+pub struct PendingConsensusTransaction {
+    /// Increases with the order the transactions were submitted in, to paginate over them.
+    pub id: u64,
+    pub key: ConsensusTransactionKey,
+    /// The size of the serialized transaction.
+    pub size: usize,
+    pub lane: PriorityLane,
+    pub submitted_at: Instant,
 }
 
 pub trait CheckConnection: Send + Sync {
@@ -312,17 +343,46 @@ impl ConsensusAdapter {
             ArcSwap::from_pointee(Arc::new(ArcSwap::from_pointee(HashMap::new())));
         Self {
             consensus_client,
-            authority,
-            max_pending_transactions,
+            authority: ArcSwap::from_pointee(authority),
+            max_pending_transactions: AtomicUsize::new(max_pending_transactions),
             num_inflight_transactions,
             connection_monitor_status,
             low_scoring_authorities,
             metrics,
             submit_semaphore: Semaphore::new(max_pending_local_submissions),
             latency_observer: LatencyObserver::new(),
+            pending_transactions: Mutex::new(BTreeMap::new()),
+            next_pending_id: AtomicU64::new(0),
         }
     }
 
+    /// Lists up to `limit` of the transactions waiting to be processed out of consensus, the
+    /// oldest first, starting after the transaction with id `cursor` if set.
+    pub fn pending_transactions(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+    ) -> Vec<PendingConsensusTransaction> {
+        let start = cursor.map_or(0, |cursor| cursor.saturating_add(1));
+        self.pending_transactions
+            .lock()
+            .range(start..)
+            .take(limit)
+            .map(|(_, pending)| pending.clone())
+            .collect()
+    }
+
+    pub fn max_pending_transactions(&self) -> usize {
+        self.max_pending_transactions.load(Ordering::Relaxed)
+    }
+
+    /// Changes the limit to number of inflight transactions, the transactions already inflight
+    /// being kept.
+    pub fn set_max_pending_transactions(&self, max_pending_transactions: usize) {
+        self.max_pending_transactions
+            .store(max_pending_transactions, Ordering::Relaxed);
+    }
+
     pub fn swap_low_scoring_authorities(
         &self,
         new_low_scoring: Arc<ArcSwap<HashMap<AuthorityName, u64>>>,
@@ -330,6 +390,18 @@ impl ConsensusAdapter {
         self.low_scoring_authorities.swap(Arc::new(new_low_scoring));
     }
 
+    /// The name this authority submits transactions under, i.e. its protocol key in the current
+    /// epoch.
+    pub fn authority(&self) -> AuthorityName {
+        **self.authority.load()
+    }
+
+    /// Replaces the name this authority submits transactions under, once its protocol key was
+    /// rotated at the start of an epoch.
+    pub fn swap_authority(&self, authority: AuthorityName) {
+        self.authority.store(Arc::new(authority));
+    }
+
     // todo - this probably need to hold some kind of lock to make sure epoch does not change while we are recovering
     pub fn submit_recovered(self: &Arc<Self>, epoch_store: &Arc<AuthorityPerEpochStore>) {
         // Currently narwhal worker might lose transactions on restart, so we need to resend them
@@ -353,7 +425,7 @@ impl ConsensusAdapter {
                 // re-introduce EndOfPublish message on restart
                 // (2) If node crashed inside ConsensusAdapter::close_epoch,
                 // after reconfig lock state was written to DB and before we persisted EndOfPublish message
-                recovered.push(ConsensusTransaction::new_end_of_publish(self.authority));
+                recovered.push(ConsensusTransaction::new_end_of_publish(self.authority()));
             }
         }
         debug!(
@@ -438,20 +510,21 @@ impl ConsensusAdapter {
         &self,
         positions: Vec<AuthorityName>,
     ) -> (usize, bool) {
-        if self.authority_is_low_scoring(&self.authority) {
+        let ourself = self.authority();
+        if self.authority_is_low_scoring(&ourself) {
             return (positions.len(), true);
         }
 
-        let initial_position = get_position_in_list(self.authority, positions.clone());
+        let initial_position = get_position_in_list(ourself, positions.clone());
 
         let filtered_positions = positions
             .into_iter()
             .filter(|authority| {
                 // Filter out any nodes that appear disconnected to us
-                self.authority == *authority
+                ourself == *authority
                     || self
                         .connection_monitor_status
-                        .check_connection(&self.authority, authority)
+                        .check_connection(&ourself, authority)
                         .unwrap_or(ConnectionStatus::Disconnected)
                         == ConnectionStatus::Connected
             })
@@ -461,7 +534,7 @@ impl ConsensusAdapter {
             })
             .collect();
 
-        let position = get_position_in_list(self.authority, filtered_positions);
+        let position = get_position_in_list(ourself, filtered_positions);
         (position, position < initial_position)
     }
 
@@ -495,7 +568,7 @@ impl ConsensusAdapter {
     pub fn check_limits(&self) -> bool {
         // First check total transactions (waiting and in submission)
         if self.num_inflight_transactions.load(Ordering::Relaxed) as usize
-            > self.max_pending_transactions
+            > self.max_pending_transactions.load(Ordering::Relaxed)
         {
             return false;
         }
@@ -557,7 +630,7 @@ impl ConsensusAdapter {
 
         let (await_submit, position, mapped_to_low_scoring) =
             self.await_submit_delay(epoch_store.committee(), &transaction);
-        let mut guard = InflightDropGuard::acquire(&self);
+        let mut guard = InflightDropGuard::acquire(&self, &transaction);
 
         // We need to wait for some delay until we submit transaction to the consensus
         // However, if transaction is received by consensus while we wait, we don't need to wait
@@ -679,7 +752,7 @@ impl ConsensusAdapter {
         if send_end_of_publish {
             // sending message outside of any locks scope
             if let Err(err) = self.submit(
-                ConsensusTransaction::new_end_of_publish(self.authority),
+                ConsensusTransaction::new_end_of_publish(self.authority()),
                 None,
                 epoch_store,
             ) {
@@ -788,7 +861,7 @@ impl ReconfigurationInitiator for Arc<ConsensusAdapter> {
         };
         if send_end_of_publish {
             if let Err(err) = self.submit(
-                ConsensusTransaction::new_end_of_publish(self.authority),
+                ConsensusTransaction::new_end_of_publish(self.authority()),
                 None,
                 epoch_store,
             ) {
@@ -817,13 +890,15 @@ impl<T> Drop for CancelOnDrop<T> {
 /// Tracks number of inflight consensus requests and relevant metrics
 struct InflightDropGuard<'a> {
     adapter: &'a ConsensusAdapter,
+    // The id of the transaction in the pending transactions of the adapter.
+    id: u64,
     start: Instant,
     position: Option<usize>,
     mapped_to_low_scoring: bool,
 }
 
 impl<'a> InflightDropGuard<'a> {
-    pub fn acquire(adapter: &'a ConsensusAdapter) -> Self {
+    pub fn acquire(adapter: &'a ConsensusAdapter, transaction: &ConsensusTransaction) -> Self {
         let inflight = adapter
             .num_inflight_transactions
             .fetch_add(1, Ordering::SeqCst);
@@ -832,9 +907,24 @@ impl<'a> InflightDropGuard<'a> {
             .metrics
             .sequencing_certificate_inflight
             .set(inflight as i64);
+        let id = adapter.next_pending_id.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+        let size = bcs::serialized_size(transaction)
+            .expect("Serializing consensus transaction cannot fail");
+        adapter.pending_transactions.lock().insert(
+            id,
+            PendingConsensusTransaction {
+                id,
+                key: transaction.key(),
+                size,
+                lane: priority_lane(transaction),
+                submitted_at: start,
+            },
+        );
         Self {
             adapter,
-            start: Instant::now(),
+            id,
+            start,
             position: None,
             mapped_to_low_scoring: false,
         }
@@ -843,6 +933,7 @@ impl<'a> InflightDropGuard<'a> {
 
 impl<'a> Drop for InflightDropGuard<'a> {
     fn drop(&mut self) {
+        self.adapter.pending_transactions.lock().remove(&self.id);
         let inflight = self
             .adapter
             .num_inflight_transactions
@@ -4,33 +4,38 @@
 use crate::authority::authority_per_epoch_store::{
     AuthorityPerEpochStore, ExecutionIndicesWithHash,
 };
-use crate::authority::AuthorityMetrics;
+use crate::authority::{AuthorityMetrics, AuthorityStore};
 use crate::checkpoints::CheckpointService;
 
 use crate::scoring_decision::update_low_scoring_authorities;
 use crate::transaction_manager::TransactionManager;
+use crate::transaction_prefetcher::TransactionPrefetcher;
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::{Blake2b256, Hash as _, HashFunction};
 use fastcrypto::traits::ToFromBytes;
 use lru::LruCache;
 use mysten_metrics::{monitored_scope, spawn_monitored_task};
 use narwhal_config::Committee;
 use narwhal_executor::{ExecutionIndices, ExecutionState};
 use narwhal_types::{BatchAPI, CertificateAPI, ConsensusOutput, HeaderAPI};
+use narwhal_worker::TransactionStatusTracker;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
 use std::sync::Arc;
-use sui_types::base_types::{AuthorityName, EpochId, TransactionDigest};
+use sui_types::base_types::{AuthorityName, EpochId, ObjectID, TransactionDigest};
 use sui_types::messages::{
     ConsensusTransaction, ConsensusTransactionKey, ConsensusTransactionKind,
     VerifiedExecutableTransaction, VerifiedTransaction,
 };
 
 use sui_types::storage::ParentSync;
+use telemetry_subscribers::CORRELATION_TARGET;
 
 use tracing::{debug, error, instrument};
 
@@ -54,6 +59,8 @@ pub struct ConsensusHandler<T> {
     /// Lru cache to quickly discard transactions processed by consensus
     processed_cache: Mutex<LruCache<SequencedConsensusTransactionKey, ()>>,
     transaction_scheduler: AsyncTransactionScheduler,
+    /// Reads the input objects of the sequenced transactions ahead of their execution.
+    transaction_prefetcher: TransactionPrefetcher,
 }
 
 const PROCESSED_CACHE_CAP: usize = 1024 * 1024;
@@ -64,6 +71,7 @@ impl<T> ConsensusHandler<T> {
         checkpoint_service: Arc<CheckpointService>,
         transaction_manager: Arc<TransactionManager>,
         parent_sync_store: T,
+        object_store: Arc<AuthorityStore>,
         low_scoring_authorities: Arc<ArcSwap<HashMap<AuthorityName, u64>>>,
         authority_names_to_hostnames: HashMap<AuthorityName, String>,
         committee: Committee,
@@ -72,6 +80,7 @@ impl<T> ConsensusHandler<T> {
         let last_seen = Mutex::new(Default::default());
         let transaction_scheduler =
             AsyncTransactionScheduler::start(transaction_manager, epoch_store.clone());
+        let transaction_prefetcher = TransactionPrefetcher::start(object_store, metrics.clone());
         Self {
             epoch_store,
             last_seen,
@@ -85,6 +94,7 @@ impl<T> ConsensusHandler<T> {
                 NonZeroUsize::new(PROCESSED_CACHE_CAP).unwrap(),
             )),
             transaction_scheduler,
+            transaction_prefetcher,
         }
     }
 }
@@ -128,19 +138,55 @@ impl<T: ParentSync + Send + Sync> ExecutionState for ConsensusHandler<T> {
 
         let mut bytes = 0usize;
         let round = consensus_output.sub_dag.leader_round();
+        let sub_dag_index = consensus_output.sub_dag.sub_dag_index;
+        let protocol_config = self.epoch_store.protocol_config();
+        let mut congestion_tracker = protocol_config
+            .check_shared_object_congestion_control_supported()
+            .then(|| {
+                SharedObjectCongestionTracker::new(
+                    protocol_config.max_transactions_per_shared_object_per_commit(),
+                )
+            });
 
-        /* (serialized, transaction, output_cert) */
+        /* (serialized, transaction, output_cert, certificate_author) */
         let mut transactions = vec![];
         // Narwhal enforces some invariants on the header.created_at, so we can use it as a timestamp
-        let timestamp = *consensus_output.sub_dag.leader.header().created_at();
+        let timestamp = if self
+            .epoch_store
+            .protocol_config()
+            .check_consensus_commit_timestamp_supported()
+        {
+            consensus_output.commit_timestamp()
+        } else {
+            *consensus_output.sub_dag.leader.header().created_at()
+        };
 
         let prologue_transaction = self.consensus_commit_prologue_transaction(round, timestamp);
+        let leader = Arc::new(consensus_output.sub_dag.leader.clone());
         transactions.push((
             vec![],
             SequencedConsensusTransactionKind::System(prologue_transaction),
-            Arc::new(consensus_output.sub_dag.leader.clone()),
+            leader.clone(),
+            None,
         ));
 
+        // The transactions deferred by the previous commits come first, oldest first, so that
+        // they are not deferred forever.
+        if congestion_tracker.is_some() {
+            for (author, transaction) in self
+                .epoch_store
+                .get_deferred_transactions(sub_dag_index)
+                .expect("Failed to load deferred transactions")
+            {
+                transactions.push((
+                    bcs::to_bytes(&transaction).expect("Serialization cannot fail"),
+                    SequencedConsensusTransactionKind::External(transaction),
+                    leader.clone(),
+                    Some(author),
+                ));
+            }
+        }
+
         // TODO: spawn a separate task for this as an optimization
         update_low_scoring_authorities(
             self.low_scoring_authorities.clone(),
@@ -159,6 +205,7 @@ impl<T: ParentSync + Send + Sync> ExecutionState for ConsensusHandler<T> {
                 .author()
                 .to_string()])
             .inc();
+        let first_committed = transactions.len();
         for (cert, batches) in consensus_output.batches {
             let author = cert.header().author();
             self.metrics
@@ -188,22 +235,69 @@ impl<T: ParentSync + Send + Sync> ExecutionState for ConsensusHandler<T> {
                         .consensus_handler_processed
                         .with_label_values(&[classify(&transaction)])
                         .inc();
+                    if tracing::enabled!(target: CORRELATION_TARGET, tracing::Level::DEBUG) {
+                        let digest = match &transaction.kind {
+                            ConsensusTransactionKind::UserTransaction(certificate) => {
+                                Some(*certificate.digest())
+                            }
+                            _ => None,
+                        };
+                        debug!(
+                            target: CORRELATION_TARGET,
+                            stage = "sequenced",
+                            transaction = Hex::encode(
+                                TransactionStatusTracker::digest(serialized_transaction).digest
+                            ),
+                            certificate = %output_cert.digest(),
+                            sub_dag = sub_dag_index,
+                            digest = ?digest,
+                        );
+                    }
                     let transaction = SequencedConsensusTransactionKind::External(transaction);
                     transactions.push((
                         serialized_transaction.clone(),
                         transaction,
                         output_cert.clone(),
+                        None,
                     ));
                 }
             }
         }
+        // The input objects are read while the transactions go through the ordering and the
+        // assignment of the shared object versions, and while they wait for their execution.
+        self.transaction_prefetcher
+            .prefetch(
+                transactions[first_committed..]
+                    .iter()
+                    .filter_map(|(_, transaction, ..)| match transaction {
+                        SequencedConsensusTransactionKind::External(ConsensusTransaction {
+                            kind: ConsensusTransactionKind::UserTransaction(certificate),
+                            ..
+                        }) => Some(certificate.as_ref()),
+                        _ => None,
+                    }),
+            );
+        if protocol_config.check_consensus_transaction_shuffling_supported() {
+            shuffle_transactions(
+                &mut transactions[first_committed..],
+                leader.digest().as_ref(),
+                |(serialized, ..)| serialized.as_slice(),
+            );
+        }
 
-        for (seq, (serialized, transaction, output_cert)) in transactions.into_iter().enumerate() {
+        for (seq, (serialized, transaction, output_cert, author)) in
+            transactions.into_iter().enumerate()
+        {
             let index = ExecutionIndices {
                 last_committed_round: round,
-                sub_dag_index: consensus_output.sub_dag.sub_dag_index,
+                sub_dag_index,
                 transaction_index: seq as u64,
             };
+            // Deferring is decided before skipping the transactions already processed, so that it
+            // does not change when a commit is processed again after a crash.
+            let defer = congestion_tracker
+                .as_mut()
+                .map_or(false, |tracker| tracker.should_defer(&transaction));
 
             let index_with_hash = match update_hash(&self.last_seen, index, &serialized) {
                 Some(i) => i,
@@ -216,21 +310,26 @@ impl<T: ParentSync + Send + Sync> ExecutionState for ConsensusHandler<T> {
                 }
             };
 
-            let certificate_author = AuthorityName::from_bytes(
-                self.committee
-                    .authority_safe(&output_cert.header().author())
-                    .protocol_key_bytes()
-                    .0
-                    .as_ref(),
-            )
-            .unwrap();
-
-            sequenced_transactions.push(SequencedConsensusTransaction {
-                certificate: output_cert.clone(),
-                certificate_author,
-                consensus_index: index_with_hash,
-                transaction,
+            let certificate_author = author.unwrap_or_else(|| {
+                AuthorityName::from_bytes(
+                    self.committee
+                        .authority_safe(&output_cert.header().author())
+                        .protocol_key_bytes()
+                        .0
+                        .as_ref(),
+                )
+                .unwrap()
             });
+
+            sequenced_transactions.push((
+                SequencedConsensusTransaction {
+                    certificate: output_cert.clone(),
+                    certificate_author,
+                    consensus_index: index_with_hash,
+                    transaction,
+                },
+                defer,
+            ));
         }
 
         self.metrics
@@ -238,7 +337,7 @@ impl<T: ParentSync + Send + Sync> ExecutionState for ConsensusHandler<T> {
             .inc_by(bytes as u64);
 
         let mut transactions_to_schedule = vec![];
-        for sequenced_transaction in sequenced_transactions {
+        for (sequenced_transaction, defer) in sequenced_transactions {
             // todo if we can make handle_consensus_transaction into sync function,
             // we could acquire mutex once for entire loop
             if self
@@ -258,6 +357,18 @@ impl<T: ParentSync + Send + Sync> ExecutionState for ConsensusHandler<T> {
                 Err(()) => continue,
             };
 
+            if defer {
+                // The transaction is processed when retried, not to be skipped by the cache then.
+                self.processed_cache
+                    .lock()
+                    .pop(&verified_transaction.0.key());
+                self.epoch_store
+                    .defer_consensus_transaction(verified_transaction, sub_dag_index + 1)
+                    .expect("Unrecoverable error in consensus handler when deferring");
+                self.metrics.consensus_handler_deferred_transactions.inc();
+                continue;
+            }
+
             if let Some(transaction) = self
                 .epoch_store
                 .process_consensus_transaction(
@@ -278,7 +389,13 @@ impl<T: ParentSync + Send + Sync> ExecutionState for ConsensusHandler<T> {
 
         self.epoch_store
             .handle_commit_boundary(round, timestamp, &self.checkpoint_service)
-            .expect("Unrecoverable error in consensus handler when processing commit boundary")
+            .expect("Unrecoverable error in consensus handler when processing commit boundary");
+
+        if congestion_tracker.is_some() {
+            self.epoch_store
+                .delete_deferred_transactions(sub_dag_index)
+                .expect("Failed to delete deferred transactions");
+        }
     }
 
     async fn last_executed_sub_dag_index(&self) -> u64 {
@@ -343,6 +460,62 @@ impl<T> ConsensusHandler<T> {
     }
 }
 
+/// Counts the transactions touching each shared object in a commit, so that the transactions
+/// touching a shared object past its budget are deferred to the next commit. The decisions only
+/// depend on the order of the transactions in the commit, hence are the same on all validators.
+struct SharedObjectCongestionTracker {
+    max_transactions_per_object: u64,
+    transactions_per_object: HashMap<ObjectID, u64>,
+}
+
+impl SharedObjectCongestionTracker {
+    fn new(max_transactions_per_object: u64) -> Self {
+        Self {
+            max_transactions_per_object,
+            transactions_per_object: HashMap::new(),
+        }
+    }
+
+    /// Whether the transaction must be deferred. Otherwise it is counted against each of its
+    /// shared objects.
+    fn should_defer(&mut self, transaction: &SequencedConsensusTransactionKind) -> bool {
+        let SequencedConsensusTransactionKind::External(ConsensusTransaction {
+            kind: ConsensusTransactionKind::UserTransaction(certificate),
+            ..
+        }) = transaction else {
+            return false;
+        };
+        let objects: BTreeSet<_> = certificate
+            .shared_input_objects()
+            .map(|object| object.id)
+            .collect();
+        if objects.iter().any(|object| {
+            self.transactions_per_object
+                .get(object)
+                .map_or(false, |count| *count >= self.max_transactions_per_object)
+        }) {
+            return true;
+        }
+        for object in objects {
+            *self.transactions_per_object.entry(object).or_default() += 1;
+        }
+        false
+    }
+}
+
+/// Reorders the transactions of a commit by their hashes salted with the seed, e.g. the digest of
+/// the leader of the commit, so that whoever batched or certified them first does not decide
+/// their order of execution. Identical transactions keep their relative order, so the result
+/// is the same on all validators.
+fn shuffle_transactions<T>(transactions: &mut [T], seed: &[u8], bytes: impl Fn(&T) -> &[u8]) {
+    transactions.sort_by_cached_key(|transaction| {
+        let mut hasher = Blake2b256::default();
+        hasher.update(seed);
+        hasher.update(bytes(transaction));
+        hasher.finalize().digest
+    });
+}
+
 fn classify(transaction: &ConsensusTransaction) -> &'static str {
     match &transaction.kind {
         ConsensusTransactionKind::UserTransaction(certificate) => {
@@ -463,3 +636,57 @@ pub fn test_update_hash() {
     assert!(update_hash(&last_seen, index1, tx).is_none());
     assert!(update_hash(&last_seen, index2, tx).is_some());
 }
+
+#[tokio::test]
+pub async fn test_shared_object_congestion_tracker() {
+    use crate::authority::authority_tests::init_state_with_objects;
+    use crate::consensus_adapter::consensus_tests::{test_certificates, test_gas_objects};
+    use sui_types::object::Object;
+
+    let mut objects = test_gas_objects();
+    objects.push(Object::shared_for_testing());
+    let state = init_state_with_objects(objects).await;
+    // All the certificates touch the same shared object.
+    let transactions: Vec<_> = test_certificates(&state)
+        .await
+        .into_iter()
+        .map(|certificate| {
+            SequencedConsensusTransactionKind::External(
+                ConsensusTransaction::new_certificate_message(&state.name(), certificate),
+            )
+        })
+        .collect();
+    assert_eq!(transactions.len(), 4);
+
+    let mut tracker = SharedObjectCongestionTracker::new(2);
+    let deferred: Vec<_> = transactions
+        .iter()
+        .map(|transaction| tracker.should_defer(transaction))
+        .collect();
+    assert_eq!(deferred, vec![false, false, true, true]);
+
+    // The decisions only depend on the order of the transactions.
+    let mut tracker = SharedObjectCongestionTracker::new(2);
+    assert!(!tracker.should_defer(&transactions[3]));
+    assert!(!tracker.should_defer(&transactions[2]));
+    assert!(tracker.should_defer(&transactions[1]));
+}
+
+#[test]
+pub fn test_shuffle_transactions() {
+    let transactions: Vec<Vec<u8>> = (0..20u8).map(|i| vec![i]).collect();
+    let shuffle = |seed: &[u8]| {
+        let mut shuffled = transactions.clone();
+        shuffle_transactions(&mut shuffled, seed, |transaction| transaction.as_slice());
+        shuffled
+    };
+
+    // The order only depends on the seed.
+    let shuffled = shuffle(&[1; 32]);
+    assert_ne!(shuffled, transactions);
+    assert_eq!(shuffled, shuffle(&[1; 32]));
+    assert_ne!(shuffled, shuffle(&[2; 32]));
+    let mut sorted = shuffled;
+    sorted.sort();
+    assert_eq!(sorted, transactions);
+}
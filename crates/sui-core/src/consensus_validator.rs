@@ -9,9 +9,14 @@ use std::sync::Arc;
 use crate::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use crate::transaction_manager::TransactionManager;
 use async_trait::async_trait;
+use narwhal_config::BatchLimits;
 use narwhal_types::BatchAPI;
 use narwhal_worker::TransactionValidator;
-use sui_types::messages::{ConsensusTransaction, ConsensusTransactionKind};
+use sui_types::base_types::EpochId;
+use sui_types::messages::{
+    CertifiedTransaction, ConsensusTransaction, ConsensusTransactionKind, TransactionDataAPI,
+    TransactionExpiration,
+};
 use tap::TapFallible;
 use tokio::runtime::Handle;
 use tracing::{info, warn};
@@ -21,6 +26,11 @@ use tracing::{info, warn};
 pub struct SuiTxValidator {
     epoch_store: Arc<AuthorityPerEpochStore>,
     _transaction_manager: Arc<TransactionManager>,
+    /// The minimum gas price of the user transactions, the reference gas price of the epoch, when
+    /// the protocol enforces it.
+    minimum_gas_price: Option<u64>,
+    /// The limits on the transactions and size of the batches, when the protocol enforces them.
+    batch_limits: Option<BatchLimits>,
     metrics: Arc<SuiTxValidatorMetrics>,
 }
 
@@ -30,16 +40,49 @@ impl SuiTxValidator {
         transaction_manager: Arc<TransactionManager>,
         metrics: Arc<SuiTxValidatorMetrics>,
     ) -> Self {
+        let minimum_gas_price = epoch_store
+            .protocol_config()
+            .check_consensus_minimum_gas_price_supported()
+            .then(|| epoch_store.reference_gas_price());
+        let protocol_config = epoch_store.protocol_config();
+        let batch_limits = protocol_config
+            .check_narwhal_batch_limits_supported()
+            .then(|| BatchLimits {
+                max_num_of_transactions: protocol_config.narwhal_max_batch_num_of_transactions()
+                    as usize,
+                max_size: protocol_config.narwhal_max_batch_size_bytes() as usize,
+            });
         info!(
-            "SuiTxValidator constructed for epoch {}",
-            epoch_store.epoch()
+            "SuiTxValidator constructed for epoch {} with minimum gas price {:?}",
+            epoch_store.epoch(),
+            minimum_gas_price
         );
         Self {
             epoch_store,
             _transaction_manager: transaction_manager,
+            minimum_gas_price,
+            batch_limits,
             metrics,
         }
     }
+
+    /// Rejects the certificate if its gas price is below the minimum, to keep the transactions
+    /// spamming consensus at a low price out of the batches.
+    fn check_gas_price(&self, certificate: &CertifiedTransaction) -> Result<(), eyre::Report> {
+        let Some(minimum_gas_price) = self.minimum_gas_price else {
+            return Ok(());
+        };
+        let gas_price = certificate.data().transaction_data().gas_price();
+        if gas_price < minimum_gas_price {
+            self.metrics.gas_price_rejected_transactions.inc();
+            eyre::bail!(
+                "Transaction {} has gas price {gas_price}, below the minimum gas price \
+                {minimum_gas_price}",
+                certificate.digest()
+            );
+        }
+        Ok(())
+    }
 }
 
 fn tx_from_bytes(tx: &[u8]) -> Result<ConsensusTransaction, eyre::Report> {
@@ -47,17 +90,38 @@ fn tx_from_bytes(tx: &[u8]) -> Result<ConsensusTransaction, eyre::Report> {
         .wrap_err("Malformed transaction (failed to deserialize)")
 }
 
+/// Whether the transaction of a certificate expired before the given epoch.
+fn is_expired_certificate(certificate: &CertifiedTransaction, epoch: EpochId) -> bool {
+    match certificate.data().transaction_data().expiration() {
+        TransactionExpiration::None => false,
+        TransactionExpiration::Epoch(expiration) => *expiration < epoch,
+    }
+}
+
 #[async_trait]
 impl TransactionValidator for SuiTxValidator {
     type Error = eyre::Report;
 
-    fn validate(&self, _tx: &[u8]) -> Result<(), Self::Error> {
-        // We only accept transactions from local sui instance so no need to re-verify it
-        Ok(())
+    async fn validate(&self, tx: &[u8]) -> Result<(), Self::Error> {
+        // We only accept transactions from local sui instance so no need to re-verify it, but the
+        // ones below the minimum gas price are rejected before they are batched.
+        if self.minimum_gas_price.is_none() {
+            return Ok(());
+        }
+        match tx_from_bytes(tx)?.kind {
+            ConsensusTransactionKind::UserTransaction(certificate) => {
+                self.check_gas_price(&certificate)
+            }
+            _ => Ok(()),
+        }
     }
 
     async fn validate_batch(&self, b: &narwhal_types::Batch) -> Result<(), Self::Error> {
         let _scope = monitored_scope("ValidateBatch");
+        if let Some(limits) = &self.batch_limits {
+            b.validate_limits(limits)
+                .wrap_err("Batch exceeds the protocol limits")?;
+        }
         let txs = b
             .transactions()
             .iter()
@@ -69,6 +133,10 @@ impl TransactionValidator for SuiTxValidator {
         for tx in txs.into_iter() {
             match tx.kind {
                 ConsensusTransactionKind::UserTransaction(certificate) => {
+                    if is_expired_certificate(&certificate, self.epoch_store.epoch()) {
+                        eyre::bail!("Expired transaction {} in batch", certificate.digest());
+                    }
+                    self.check_gas_price(&certificate)?;
                     cert_batch.push(*certificate);
 
                     // if !certificate.contains_shared_object() {
@@ -115,11 +183,21 @@ impl TransactionValidator for SuiTxValidator {
         //     .enqueue_certificates(owned_tx_certs, &self.epoch_store)
         //     .wrap_err("Failed to schedule certificates for execution")
     }
+
+    fn sender(&self, tx: &[u8]) -> Option<Vec<u8>> {
+        match tx_from_bytes(tx).ok()?.kind {
+            ConsensusTransactionKind::UserTransaction(certificate) => {
+                Some(certificate.sender_address().as_ref().to_vec())
+            }
+            _ => None,
+        }
+    }
 }
 
 pub struct SuiTxValidatorMetrics {
     certificate_signatures_verified: IntCounter,
     checkpoint_signatures_verified: IntCounter,
+    gas_price_rejected_transactions: IntCounter,
 }
 
 impl SuiTxValidatorMetrics {
@@ -137,6 +215,12 @@ impl SuiTxValidatorMetrics {
                 registry
             )
             .unwrap(),
+            gas_price_rejected_transactions: register_int_counter_with_registry!(
+                "gas_price_rejected_transactions",
+                "Number of transactions rejected in narwhal batch verifier for their gas price",
+                registry
+            )
+            .unwrap(),
         })
     }
 }
@@ -151,8 +235,16 @@ mod tests {
     use fastcrypto::traits::KeyPair;
     use narwhal_types::Batch;
     use narwhal_worker::TransactionValidator;
+    use sui_protocol_config::ProtocolConfig;
+    use sui_types::utils::to_sender_signed_transaction;
     use sui_types::{
-        base_types::AuthorityName, messages::ConsensusTransaction, signature::GenericSignature,
+        base_types::AuthorityName,
+        crypto::deterministic_random_account_key,
+        messages::{
+            CertifiedTransaction, ConsensusTransaction, GasData, TransactionData,
+            TransactionDataAPI,
+        },
+        signature::GenericSignature,
     };
 
     use sui_macros::sim_test;
@@ -191,7 +283,7 @@ mod tests {
             state.transaction_manager().clone(),
             metrics,
         );
-        let res = validator.validate(&first_transaction_bytes);
+        let res = validator.validate(&first_transaction_bytes).await;
         assert!(res.is_ok(), "{res:?}");
 
         let transaction_bytes: Vec<_> = certificates
@@ -222,4 +314,115 @@ mod tests {
         let res_batch = validator.validate_batch(&batch).await;
         assert!(res_batch.is_err());
     }
+
+    #[sim_test]
+    async fn reject_transaction_below_minimum_gas_price() {
+        let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut config| {
+            config.set_consensus_minimum_gas_price_for_testing(true);
+            config
+        });
+        let mut objects = test_gas_objects();
+        objects.push(Object::shared_for_testing());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let network_config = sui_config::builder::ConfigBuilder::new(&dir)
+            .with_objects(objects.clone())
+            .build();
+        let genesis = network_config.genesis;
+
+        let sec1 = network_config.validator_configs[0]
+            .protocol_key_pair()
+            .copy();
+        let name1: AuthorityName = sec1.public().into();
+
+        let state = init_state_with_objects_and_committee(objects, &genesis, &sec1).await;
+        let certificates = test_certificates(&state).await;
+        let epoch_store = state.epoch_store_for_testing().clone();
+        let reference_gas_price = epoch_store.reference_gas_price();
+
+        // A transaction below the reference gas price. Its certificate is not verified, as the
+        // gas price is checked first.
+        let certificate = &certificates[0];
+        let (sender, keypair) = deterministic_random_account_key();
+        let data = TransactionData::new_with_gas_data(
+            certificate.data().transaction_data().kind().clone(),
+            sender,
+            GasData {
+                price: reference_gas_price - 1,
+                ..certificate.data().transaction_data().gas_data().clone()
+            },
+        );
+        let cheap_certificate = CertifiedTransaction::new_from_data_and_sig(
+            to_sender_signed_transaction(data, &keypair).into_message(),
+            certificate.auth_sig().clone(),
+        );
+
+        let metrics = SuiTxValidatorMetrics::new(&Default::default());
+        let validator = SuiTxValidator::new(
+            epoch_store,
+            state.transaction_manager().clone(),
+            metrics.clone(),
+        );
+        let to_bytes = |certificate: &CertifiedTransaction| {
+            bcs::to_bytes(&ConsensusTransaction::new_certificate_message(
+                &name1,
+                certificate.clone(),
+            ))
+            .unwrap()
+        };
+        assert!(validator.validate(&to_bytes(certificate)).await.is_ok());
+        assert!(validator
+            .validate(&to_bytes(&cheap_certificate))
+            .await
+            .is_err());
+
+        let batch = Batch::new(vec![to_bytes(certificate), to_bytes(&cheap_certificate)]);
+        assert!(validator.validate_batch(&batch).await.is_err());
+        assert_eq!(metrics.gas_price_rejected_transactions.get(), 2);
+    }
+
+    #[sim_test]
+    async fn reject_batch_over_limits() {
+        let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut config| {
+            config.set_narwhal_batch_limits_for_testing(true);
+            config.set_narwhal_max_batch_num_of_transactions_for_testing(1);
+            config
+        });
+        let mut objects = test_gas_objects();
+        objects.push(Object::shared_for_testing());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let network_config = sui_config::builder::ConfigBuilder::new(&dir)
+            .with_objects(objects.clone())
+            .build();
+        let genesis = network_config.genesis;
+
+        let sec1 = network_config.validator_configs[0]
+            .protocol_key_pair()
+            .copy();
+        let name1: AuthorityName = sec1.public().into();
+
+        let state = init_state_with_objects_and_committee(objects, &genesis, &sec1).await;
+        let certificates = test_certificates(&state).await;
+        let transaction_bytes: Vec<_> = certificates
+            .into_iter()
+            .take(2)
+            .map(|cert| {
+                bcs::to_bytes(&ConsensusTransaction::new_certificate_message(&name1, cert)).unwrap()
+            })
+            .collect();
+
+        let metrics = SuiTxValidatorMetrics::new(&Default::default());
+        let validator = SuiTxValidator::new(
+            state.epoch_store_for_testing().clone(),
+            state.transaction_manager().clone(),
+            metrics,
+        );
+        let batch = Batch::new(transaction_bytes[..1].to_vec());
+        let res_batch = validator.validate_batch(&batch).await;
+        assert!(res_batch.is_ok(), "{res_batch:?}");
+
+        let batch = Batch::new(transaction_bytes);
+        assert!(validator.validate_batch(&batch).await.is_err());
+    }
 }
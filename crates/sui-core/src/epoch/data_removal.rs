@@ -7,21 +7,51 @@ pub mod epoch_data_tests;
 
 use mysten_metrics::spawn_monitored_task;
 use narwhal_config::Epoch;
+use prometheus::{register_int_gauge_vec_with_registry, IntGaugeVec, Registry};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// How often the disk usage of the epoch stores is reported.
+const STORE_SIZE_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct EpochDataMetrics {
+    /// The disk usage of the consensus store of each epoch kept
+    store_size_bytes: IntGaugeVec,
+}
+
+impl EpochDataMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            store_size_bytes: register_int_gauge_vec_with_registry!(
+                "narwhal_epoch_store_size_bytes",
+                "The disk usage of the consensus store of each epoch kept",
+                &["epoch"],
+                registry
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// Removes the consensus stores of the past epochs. Each epoch has its own store, in a directory
+/// of the base path named after the epoch, so removing the data of an epoch is a directory
+/// removal.
 pub struct EpochDataRemover {
     base_path: PathBuf,
     tx_remove: mpsc::Sender<Epoch>,
+    metrics: EpochDataMetrics,
 }
 
 impl EpochDataRemover {
-    pub fn new(base_path: PathBuf) -> Self {
+    pub fn new(base_path: PathBuf, metrics: EpochDataMetrics) -> Self {
         let (tx_remove, _rx_remove) = mpsc::channel(1);
         Self {
             base_path,
             tx_remove,
+            metrics,
         }
     }
 
@@ -29,16 +59,24 @@ impl EpochDataRemover {
         let (tx_remove, mut rx_remove) = mpsc::channel(1);
         self.tx_remove = tx_remove;
         let base_path = self.base_path.clone();
-        spawn_monitored_task!(async {
+        let metrics = self.metrics.clone();
+        spawn_monitored_task!(async move {
             tracing::info!("Starting Epoch Data Remover");
+            let mut report_interval = tokio::time::interval(STORE_SIZE_REPORT_INTERVAL);
             loop {
-                match rx_remove.recv().await {
-                    Some(epoch) => {
-                        remove_old_epoch_data(base_path.clone(), epoch);
-                    }
-                    None => {
-                        tracing::info!("Closing Epoch Data Remover");
-                        break;
+                tokio::select! {
+                    message = rx_remove.recv() => match message {
+                        Some(epoch) => {
+                            remove_old_epoch_data(base_path.clone(), epoch);
+                            report_epoch_store_sizes(&base_path, &metrics);
+                        }
+                        None => {
+                            tracing::info!("Closing Epoch Data Remover");
+                            break;
+                        }
+                    },
+                    _ = report_interval.tick() => {
+                        report_epoch_store_sizes(&base_path, &metrics);
                     }
                 }
             }
@@ -120,3 +158,44 @@ pub(crate) fn remove_old_epoch_data(storage_base_path: PathBuf, epoch: Epoch) {
         epoch
     );
 }
+
+/// The disk usage of the store of each epoch in the base path, in bytes.
+pub(crate) fn epoch_store_sizes(storage_base_path: &Path) -> Vec<(Epoch, u64)> {
+    let Ok(files) = fs::read_dir(storage_base_path) else {
+        return Vec::new();
+    };
+    let mut sizes: Vec<_> = files
+        .filter_map(|file| {
+            let file = file.ok()?;
+            let epoch = file.file_name().to_str()?.parse::<Epoch>().ok()?;
+            Some((epoch, directory_size(&file.path())))
+        })
+        .collect();
+    sizes.sort();
+    sizes
+}
+
+fn report_epoch_store_sizes(storage_base_path: &Path, metrics: &EpochDataMetrics) {
+    // Forget the epochs removed since the last report.
+    metrics.store_size_bytes.reset();
+    for (epoch, size) in epoch_store_sizes(storage_base_path) {
+        metrics
+            .store_size_bytes
+            .with_label_values(&[&epoch.to_string()])
+            .set(size as i64);
+    }
+}
+
+fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
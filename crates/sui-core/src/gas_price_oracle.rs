@@ -0,0 +1,46 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The gas prices paid by the transactions of the latest checkpoints, from which wallets can
+//! estimate the price making a transaction likely to be executed promptly.
+
+use std::ops::RangeInclusive;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+
+#[cfg(test)]
+#[path = "unit_tests/gas_price_oracle_tests.rs"]
+mod gas_price_oracle_tests;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecentGasPrices {
+    /// The checkpoints the gas prices were observed in.
+    pub checkpoints: RangeInclusive<CheckpointSequenceNumber>,
+    /// The gas prices of the user transactions, the system transactions not paying gas.
+    prices: Vec<u64>,
+}
+
+impl RecentGasPrices {
+    pub fn new(
+        checkpoints: RangeInclusive<CheckpointSequenceNumber>,
+        mut prices: Vec<u64>,
+    ) -> Self {
+        prices.sort_unstable();
+        Self {
+            checkpoints,
+            prices,
+        }
+    }
+
+    pub fn num_transactions(&self) -> usize {
+        self.prices.len()
+    }
+
+    /// Returns the lowest gas price which at least `percentile` percents of the transactions
+    /// paid, or None if no transaction was observed.
+    pub fn percentile(&self, percentile: u8) -> Option<u64> {
+        let percentile = percentile.min(100) as usize;
+        // The nearest-rank method: the rank is the smallest one including the percentile.
+        let rank = (percentile * self.prices.len() + 99) / 100;
+        self.prices.get(rank.saturating_sub(1)).copied()
+    }
+}
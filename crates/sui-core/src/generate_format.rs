@@ -13,12 +13,15 @@ use std::{fs::File, io::Write};
 use sui_types::crypto::Signer;
 use sui_types::{
     base_types::{
-        self, MoveObjectType, ObjectDigest, ObjectID, TransactionDigest, TransactionEffectsDigest,
+        self, ExecutionDigests, MoveObjectType, ObjectDigest, ObjectID, TransactionDigest,
+        TransactionEffectsDigest,
     },
     crypto::{
         get_key_pair, AccountKeyPair, AuthorityKeyPair, AuthorityPublicKeyBytes,
         AuthoritySignature, KeypairTraits, Signature,
     },
+    digests::{CheckpointContentsDigest, CheckpointDigest},
+    gas::GasCostSummary,
     messages::{
         Argument, CallArg, Command, CommandArgumentError, ExecutionFailureStatus, ExecutionStatus,
         ObjectArg, ObjectInfoRequestKind, PackageUpgradeError, TransactionKind, TypeArgumentError,
@@ -71,6 +74,11 @@ fn get_registry() -> Result<Registry> {
     let teff = TransactionEffectsDigest::random();
     tracer.trace_value(&mut samples, &teff)?;
 
+    let cd = CheckpointDigest::random();
+    let ccd = CheckpointContentsDigest::random();
+    tracer.trace_value(&mut samples, &cd)?;
+    tracer.trace_value(&mut samples, &ccd)?;
+
     // 2. Trace the main entry point(s) + every enum separately.
     tracer.trace_type::<Owner>(&samples)?;
     tracer.trace_type::<ExecutionStatus>(&samples)?;
@@ -93,6 +101,8 @@ fn get_registry() -> Result<Registry> {
     tracer.trace_type::<CommandArgumentError>(&samples)?;
     tracer.trace_type::<TypeArgumentError>(&samples)?;
     tracer.trace_type::<PackageUpgradeError>(&samples)?;
+    tracer.trace_type::<ExecutionDigests>(&samples)?;
+    tracer.trace_type::<GasCostSummary>(&samples)?;
 
     tracer.registry()
 }
@@ -114,25 +124,43 @@ struct Options {
     action: Action,
 }
 
-const FILE_PATH: &str = "sui-core/tests/staged/sui.yaml";
+/// The version of the formats. The formats of each version are recorded once and for all, for
+/// the clients implementing them: any change to the formats must bump the version.
+const FORMAT_VERSION: u64 = 1;
+
+fn file_path(version: u64) -> String {
+    format!("sui-core/tests/staged/sui_v{version}.yaml")
+}
 
 fn main() {
     let options = Options::parse();
     let registry = get_registry().unwrap();
+    let file_path = file_path(FORMAT_VERSION);
+    let content = serde_yaml::to_string(&registry).unwrap();
     match options.action {
         Action::Print => {
-            let content = serde_yaml::to_string(&registry).unwrap();
             println!("{content}");
         }
         Action::Record => {
-            let content = serde_yaml::to_string(&registry).unwrap();
-            let mut f = File::create(FILE_PATH).unwrap();
+            if let Ok(reference) = std::fs::read_to_string(&file_path) {
+                assert!(
+                    reference == content + "\n",
+                    "The formats of version {FORMAT_VERSION} are already recorded in {file_path}: \
+                     bump FORMAT_VERSION to record the new formats"
+                );
+            }
+            let mut f = File::create(&file_path).unwrap();
             writeln!(f, "{}", content).unwrap();
         }
         Action::Test => {
-            let reference = std::fs::read_to_string(FILE_PATH).unwrap();
-            let content = serde_yaml::to_string(&registry).unwrap() + "\n";
-            assert_str_eq!(&reference, &content);
+            let reference = std::fs::read_to_string(&file_path).unwrap_or_else(|_| {
+                panic!("The formats of version {FORMAT_VERSION} are not recorded in {file_path}")
+            });
+            assert_str_eq!(
+                &reference,
+                &(content + "\n"),
+                "The formats changed without bumping FORMAT_VERSION"
+            );
         }
     }
 }
@@ -15,6 +15,7 @@ pub mod consensus_validator;
 pub mod db_checkpoint_handler;
 pub mod epoch;
 pub mod event_handler;
+pub mod gas_price_oracle;
 mod execution_driver;
 mod math;
 pub mod metrics;
@@ -31,7 +32,9 @@ pub mod streamer;
 pub mod test_utils;
 pub mod transaction_input_checker;
 mod transaction_manager;
+mod transaction_prefetcher;
 pub mod transaction_orchestrator;
+pub mod validator_scores;
 
 #[cfg(test)]
 #[path = "unit_tests/move_package_publish_tests.rs"]
@@ -6,19 +6,33 @@
 pub mod narwhal_manager_tests;
 
 use fastcrypto::traits::KeyPair;
+use mysten_common::lifecycle::{ComponentState, Lifecycle};
 use mysten_metrics::RegistryService;
-use narwhal_config::{Committee, Epoch, Parameters, WorkerCache, WorkerId};
+use narwhal_config::{
+    BatchLimits, BatchVersion, Committee, Epoch, HeaderPayloadLimits, Parameters, WorkerCache,
+    WorkerId, WorkerSharding,
+};
+use narwhal_crypto::signer::AuthoritySigner;
 use narwhal_executor::ExecutionState;
 use narwhal_node::primary_node::PrimaryNode;
 use narwhal_node::worker_node::WorkerNodes;
-use narwhal_node::{CertificateStoreCacheMetrics, NodeStorage};
+use narwhal_node::{
+    CertificateStoreCacheMetrics, ConsensusRound, NodeStorage, PeerAddresses, StoreCipher,
+};
 use narwhal_worker::TransactionValidator;
 use prometheus::{register_int_gauge_with_registry, IntGauge, Registry};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use sui_types::crypto::{AuthorityKeyPair, NetworkKeyPair};
-use tokio::sync::Mutex;
+use sui_protocol_config::ProtocolConfig;
+use sui_types::crypto::NetworkKeyPair;
+use tokio::sync::{watch, Mutex};
+use typed_store::rocks::ValueCipher;
+
+/// The names of the Narwhal components in the lifecycle of the node. The primary is stopped
+/// before the workers, which are drained while it still runs.
+pub const NARWHAL_WORKERS: &str = "narwhal-workers";
+pub const NARWHAL_PRIMARY: &str = "narwhal-primary";
 
 #[derive(PartialEq)]
 enum Running {
@@ -27,13 +41,19 @@ enum Running {
 }
 
 pub struct NarwhalConfiguration {
-    pub primary_keypair: AuthorityKeyPair,
+    /// The signer of the protocol key, which may be held outside of the node, e.g. by a KMS.
+    pub primary_signer: Arc<dyn AuthoritySigner>,
     pub network_keypair: NetworkKeyPair,
     pub worker_ids_and_keypairs: Vec<(WorkerId, NetworkKeyPair)>,
 
     pub storage_base_path: PathBuf,
     pub parameters: Parameters,
     pub registry_service: RegistryService,
+    /// The lifecycle of the node, where the states of the Narwhal components are reported.
+    pub lifecycle: Lifecycle,
+    /// Whether the node runs no primary, its workers standing by for the workers of the node
+    /// running it with the same keys, to start in their place once they stop responding.
+    pub worker_standby: bool,
 }
 
 pub struct NarwhalManagerMetrics {
@@ -76,7 +96,7 @@ impl NarwhalManagerMetrics {
 
 pub struct NarwhalManager {
     storage_base_path: PathBuf,
-    primary_keypair: AuthorityKeyPair,
+    primary_signer: Arc<dyn AuthoritySigner>,
     network_keypair: NetworkKeyPair,
     worker_ids_and_keypairs: Vec<(WorkerId, NetworkKeyPair)>,
     primary_node: PrimaryNode,
@@ -84,6 +104,10 @@ pub struct NarwhalManager {
     running: Mutex<Running>,
     metrics: NarwhalManagerMetrics,
     store_cache_metrics: CertificateStoreCacheMetrics,
+    // Encrypts the batches and certificates stored, if set.
+    store_cipher: Option<Arc<dyn ValueCipher>>,
+    lifecycle: Lifecycle,
+    worker_standby: bool,
 }
 
 impl NarwhalManager {
@@ -101,20 +125,31 @@ impl NarwhalManager {
 
         let store_cache_metrics =
             CertificateStoreCacheMetrics::new(&config.registry_service.default_registry());
+        let store_cipher = StoreCipher::from_parameters(&config.parameters.storage_encryption)
+            .expect("Failed to load the Narwhal storage encryption key");
 
         Self {
             primary_node,
             worker_nodes,
-            primary_keypair: config.primary_keypair,
+            primary_signer: config.primary_signer,
             network_keypair: config.network_keypair,
             worker_ids_and_keypairs: config.worker_ids_and_keypairs,
             storage_base_path: config.storage_base_path,
             running: Mutex::new(Running::False),
             metrics,
             store_cache_metrics,
+            store_cipher,
+            lifecycle: config.lifecycle,
+            worker_standby: config.worker_standby,
         }
     }
 
+    // Replaces the signer of the primary, e.g. when the protocol key of the validator is rotated
+    // at an epoch change. Takes effect the next time Narwhal is started.
+    pub fn set_primary_signer(&mut self, primary_signer: Arc<dyn AuthoritySigner>) {
+        self.primary_signer = primary_signer;
+    }
+
     // Starts the Narwhal (primary & worker(s)) - if not already running.
     pub async fn start<State, TxValidator: TransactionValidator>(
         &self,
@@ -122,6 +157,7 @@ impl NarwhalManager {
         worker_cache: WorkerCache,
         execution_state: Arc<State>,
         tx_validator: TxValidator,
+        protocol_config: &ProtocolConfig,
     ) where
         State: ExecutionState + Send + Sync + 'static,
     {
@@ -139,29 +175,64 @@ impl NarwhalManager {
 
         // Create a new store
         let store_path = self.get_store_path(committee.epoch());
-        let store = NodeStorage::reopen(store_path, Some(self.store_cache_metrics.clone()));
+        let store = NodeStorage::reopen_with_cipher(
+            store_path,
+            Some(self.store_cache_metrics.clone()),
+            self.store_cipher.clone(),
+        );
 
-        let name = self.primary_keypair.public().clone();
+        let name = self.primary_signer.public_key();
 
         tracing::info!("Starting up Narwhal for epoch {}", committee.epoch());
+        self.lifecycle.register(NARWHAL_WORKERS, &[]);
+        if !self.worker_standby {
+            self.lifecycle.register(NARWHAL_PRIMARY, &[NARWHAL_WORKERS]);
+        }
 
-        // start primary
+        // The limits on the headers are gated by the protocol, so that every validator votes for
+        // the same headers. Until then, the limits of the parameters apply.
+        let header_payload_limits = protocol_config
+            .check_narwhal_header_payload_limits_supported()
+            .then(|| HeaderPayloadLimits {
+                max_num_of_batches: protocol_config.narwhal_max_header_num_of_batches() as usize,
+                max_num_of_batches_per_worker: protocol_config
+                    .narwhal_max_header_num_of_batches_per_worker()
+                    as usize,
+            });
+        // Swapping out the leaders with a low reputation changes the leaders elected, so it is
+        // only enabled once decided by the protocol, for every validator at the same epoch.
+        let leader_swaps = protocol_config.check_narwhal_leader_swaps_supported();
+        // The workers of every validator shard the transactions the same way, as decided by the
+        // protocol, so that the senders know which worker to submit their transactions to.
+        let mut worker_cache = worker_cache;
+        worker_cache.sharding =
+            if protocol_config.check_narwhal_worker_sharding_by_sender_supported() {
+                WorkerSharding::BySender
+            } else {
+                WorkerSharding::Any
+            };
+
+        // start primary, unless the node only stands by for the workers
         const MAX_PRIMARY_RETRIES: u32 = 2;
         let mut primary_retries = 0;
-        loop {
+        while !self.worker_standby {
             match self
                 .primary_node
                 .start(
-                    self.primary_keypair.copy(),
+                    self.primary_signer.clone(),
                     self.network_keypair.copy(),
                     committee.clone(),
                     worker_cache.clone(),
                     &store,
                     execution_state.clone(),
+                    header_payload_limits,
+                    leader_swaps,
                 )
                 .await
             {
                 Ok(_) => {
+                    self.lifecycle
+                        .set_state(NARWHAL_PRIMARY, ComponentState::Ready);
                     break;
                 }
                 Err(e) => {
@@ -175,6 +246,24 @@ impl NarwhalManager {
             }
         }
 
+        // The batch version is gated by the protocol, so that V2 and V3 batches are only created
+        // once every validator can decode them, and digest V3 batches the same way.
+        let batch_version = if protocol_config.check_narwhal_batch_v3_supported() {
+            BatchVersion::V3
+        } else if protocol_config.check_narwhal_batch_v2_supported() {
+            BatchVersion::V2
+        } else {
+            BatchVersion::V1
+        };
+        // Likewise for the limits on the batches, which are checked on receipt by the validator.
+        let batch_limits = protocol_config
+            .check_narwhal_batch_limits_supported()
+            .then(|| BatchLimits {
+                max_num_of_transactions: protocol_config.narwhal_max_batch_num_of_transactions()
+                    as usize,
+                max_size: protocol_config.narwhal_max_batch_size_bytes() as usize,
+            });
+
         // Start Narwhal Workers with configuration
         const MAX_WORKER_RETRIES: u32 = 2;
         let mut worker_retries = 0;
@@ -186,19 +275,37 @@ impl NarwhalManager {
                 .map(|(id, keypair)| (*id, keypair.copy()))
                 .collect();
 
-            match self
-                .worker_nodes
-                .start(
-                    name.clone(),
-                    id_keypair_copy,
-                    committee.clone(),
-                    worker_cache.clone(),
-                    &store,
-                    tx_validator.clone(),
-                )
-                .await
-            {
+            let started = if self.worker_standby {
+                self.worker_nodes
+                    .start_standby(
+                        name.clone(),
+                        id_keypair_copy,
+                        committee.clone(),
+                        worker_cache.clone(),
+                        &store,
+                        tx_validator.clone(),
+                        batch_version,
+                        batch_limits,
+                    )
+                    .await
+            } else {
+                self.worker_nodes
+                    .start(
+                        name.clone(),
+                        id_keypair_copy,
+                        committee.clone(),
+                        worker_cache.clone(),
+                        &store,
+                        tx_validator.clone(),
+                        batch_version,
+                        batch_limits,
+                    )
+                    .await
+            };
+            match started {
                 Ok(_) => {
+                    self.lifecycle
+                        .set_state(NARWHAL_WORKERS, ComponentState::Ready);
                     break;
                 }
                 Err(e) => {
@@ -240,8 +347,20 @@ impl NarwhalManager {
                 let now = Instant::now();
                 tracing::info!("Shutting down Narwhal epoch {:?}", epoch);
 
+                // Drain the workers while the primary is still running, so their pending batches
+                // can still be included. The rest is failed back to the consensus adapter once the
+                // workers of the next epoch start, for it to resubmit what is still valid.
+                self.lifecycle
+                    .set_state(NARWHAL_WORKERS, ComponentState::Draining);
+                self.worker_nodes.drain().await;
+                self.lifecycle
+                    .set_state(NARWHAL_PRIMARY, ComponentState::Draining);
                 self.primary_node.shutdown().await;
+                self.lifecycle
+                    .set_state(NARWHAL_PRIMARY, ComponentState::Stopped);
                 self.worker_nodes.shutdown().await;
+                self.lifecycle
+                    .set_state(NARWHAL_WORKERS, ComponentState::Stopped);
 
                 tracing::info!(
                     "Narwhal shutdown for epoch {:?} is complete - took {} seconds",
@@ -263,6 +382,36 @@ impl NarwhalManager {
         *running = Running::False;
     }
 
+    // Updates the addresses of the peers of the Narwhal nodes, if they are running the epoch of
+    // the addresses, e.g. when a validator moves to the address it staged for the next epoch.
+    // Returns whether they are.
+    pub async fn update_peer_addresses(&self, epoch: Epoch, peer_addresses: PeerAddresses) -> bool {
+        if *self.running.lock().await != Running::True(epoch) {
+            return false;
+        }
+        self.primary_node
+            .update_peer_addresses(&peer_addresses)
+            .await;
+        self.worker_nodes
+            .update_peer_addresses(&peer_addresses)
+            .await;
+        true
+    }
+
+    // Subscribes to the last committed round and GC round of Narwhal consensus, if the Narwhal
+    // nodes are running. The subscription ends with the epoch.
+    pub async fn subscribe_consensus_round(&self) -> Option<watch::Receiver<ConsensusRound>> {
+        self.primary_node.subscribe_consensus_round().await
+    }
+
+    // Sets the parameters of the Narwhal nodes, which apply the next time they are started, i.e.
+    // at the start of the next epoch if they are running. The ones tunable at runtime, e.g. the
+    // batch and header delays, also apply to the running nodes.
+    pub async fn set_parameters(&self, parameters: Parameters) {
+        self.primary_node.set_parameters(parameters.clone()).await;
+        self.worker_nodes.set_parameters(parameters).await;
+    }
+
     fn get_store_path(&self, epoch: Epoch) -> PathBuf {
         let mut store_path = self.storage_base_path.clone();
         store_path.push(format!("{}", epoch));
@@ -24,6 +24,8 @@ pub struct QuorumDriverMetrics {
     pub(crate) total_attempts_retrying_conflicting_transaction: IntCounter,
     pub(crate) total_successful_attempts_retrying_conflicting_transaction: IntCounter,
     pub(crate) total_times_conflicting_transaction_already_finalized_when_retrying: IntCounter,
+    pub(crate) total_resubmissions_across_epochs: IntCounter,
+    pub(crate) total_recovered_from_journal: IntCounter,
 }
 
 impl QuorumDriverMetrics {
@@ -89,6 +91,18 @@ impl QuorumDriverMetrics {
                 registry,
             )
             .unwrap(),
+            total_resubmissions_across_epochs: register_int_counter_with_registry!(
+                "quorum_driver_total_resubmissions_across_epochs",
+                "Total number of transactions dropped at the end of an epoch and resubmitted in the next one",
+                registry,
+            )
+            .unwrap(),
+            total_recovered_from_journal: register_int_counter_with_registry!(
+                "quorum_driver_total_recovered_from_journal",
+                "Total number of transactions in flight before a restart driven again from the journal",
+                registry,
+            )
+            .unwrap(),
         }
     }
 
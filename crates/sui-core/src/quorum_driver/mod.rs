@@ -17,12 +17,12 @@ use sui_types::quorum_driver_types::{
     QuorumDriverEffectsQueueResult, QuorumDriverError, QuorumDriverResult,
 };
 use tap::TapFallible;
-use tokio::time::{sleep_until, Instant};
+use tokio::time::{sleep, sleep_until, Instant};
 
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::task::JoinHandle;
 use tracing::Instrument;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::authority_aggregator::{
     AggregatorProcessCertificateError, AggregatorProcessTransactionError, AuthorityAggregator,
@@ -32,9 +32,11 @@ use crate::authority_client::AuthorityAPI;
 use mysten_common::sync::notify_read::{NotifyRead, Registration};
 use mysten_metrics::spawn_monitored_task;
 use std::fmt::Write;
+use sui_storage::quorum_driver_journal::QuorumDriverJournal;
 use sui_types::error::{SuiError, SuiResult};
 use sui_types::messages::{
-    PlainTransactionInfoResponse, QuorumDriverResponse, VerifiedCertificate, VerifiedTransaction,
+    PlainTransactionInfoResponse, QuorumDriverResponse, TransactionDataAPI, TransactionExpiration,
+    VerifiedCertificate, VerifiedTransaction,
 };
 
 use self::reconfig_observer::ReconfigObserver;
@@ -45,6 +47,11 @@ mod tests;
 const TASK_QUEUE_SIZE: usize = 10000;
 const EFFECTS_QUEUE_SIZE: usize = 10000;
 const TX_MAX_RETRY_TIMES: u8 = 10;
+const TX_MAX_EPOCH_RESUBMISSIONS: u8 = 2;
+/// How long a transaction dropped at the end of an epoch waits for the next epoch before being
+/// retried as usual.
+const EPOCH_CHANGE_TIMEOUT: Duration = Duration::from_secs(30);
+const EPOCH_CHANGE_POLL_INTERVAL: Duration = Duration::from_millis(200);
 
 #[derive(Clone)]
 pub struct QuorumDriverTask {
@@ -52,6 +59,21 @@ pub struct QuorumDriverTask {
     pub tx_cert: Option<VerifiedCertificate>,
     pub retry_times: u8,
     pub next_retry_after: Instant,
+    /// The number of times the transaction was resubmitted in a new epoch after being dropped
+    /// at the end of the previous one.
+    pub epoch_resubmissions: u8,
+}
+
+/// Why an attempt to drive a transaction to finality failed.
+#[derive(Debug)]
+pub(crate) enum AttemptError {
+    /// The transaction may reach finality if attempted again.
+    Retryable,
+    /// The validators closed `epoch`, the epoch of the attempt, so the transaction may only
+    /// reach finality once resubmitted in the next epoch.
+    EpochEnded { epoch: EpochId },
+    /// The transaction cannot reach finality.
+    Fatal(QuorumDriverError),
 }
 
 impl Debug for QuorumDriverTask {
@@ -61,6 +83,7 @@ impl Debug for QuorumDriverTask {
         write!(writer, "has_tx_cert={} ", self.tx_cert.is_some())?;
         write!(writer, "retry_times={} ", self.retry_times)?;
         write!(writer, "next_retry_after={:?} ", self.next_retry_after)?;
+        write!(writer, "epoch_resubmissions={} ", self.epoch_resubmissions)?;
         write!(f, "{}", writer)
     }
 }
@@ -72,6 +95,9 @@ pub struct QuorumDriver<A> {
     notifier: Arc<NotifyRead<TransactionDigest, QuorumDriverResult>>,
     metrics: Arc<QuorumDriverMetrics>,
     max_retry_times: u8,
+    resubmit_across_epochs: bool,
+    /// Records the accepted transactions and their progress, to drive them again after a restart.
+    journal: Option<Arc<QuorumDriverJournal>>,
 }
 
 impl<A> QuorumDriver<A> {
@@ -82,6 +108,8 @@ impl<A> QuorumDriver<A> {
         notifier: Arc<NotifyRead<TransactionDigest, QuorumDriverResult>>,
         metrics: Arc<QuorumDriverMetrics>,
         max_retry_times: u8,
+        resubmit_across_epochs: bool,
+        journal: Option<Arc<QuorumDriverJournal>>,
     ) -> Self {
         Self {
             validators,
@@ -90,6 +118,8 @@ impl<A> QuorumDriver<A> {
             notifier,
             metrics,
             max_retry_times,
+            resubmit_across_epochs,
+            journal,
         }
     }
 
@@ -105,6 +135,36 @@ impl<A> QuorumDriver<A> {
         self.validators.load().committee.epoch
     }
 
+    pub fn journal(&self) -> Option<&Arc<QuorumDriverJournal>> {
+        self.journal.as_ref()
+    }
+
+    // Failing to update the journal only loses the progress of the transaction on a restart, so
+    // it does not fail the transaction.
+    fn journal_accepted(&self, transaction: &VerifiedTransaction) {
+        if let Some(journal) = &self.journal {
+            if let Err(err) = journal.accept(transaction) {
+                error!(tx_digest = ?transaction.digest(), "Failed to journal transaction: {err}");
+            }
+        }
+    }
+
+    fn journal_certified(&self, certificate: &VerifiedCertificate) {
+        if let Some(journal) = &self.journal {
+            if let Err(err) = journal.certified(certificate) {
+                error!(tx_digest = ?certificate.digest(), "Failed to journal certificate: {err}");
+            }
+        }
+    }
+
+    fn journal_finished(&self, tx_digest: &TransactionDigest) {
+        if let Some(journal) = &self.journal {
+            if let Err(err) = journal.finish(tx_digest) {
+                error!(?tx_digest, "Failed to finish transaction in journal: {err}");
+            }
+        }
+    }
+
     async fn enqueue_task(&self, task: QuorumDriverTask) -> SuiResult<()> {
         self.task_sender
             .send(task.clone())
@@ -128,6 +188,7 @@ impl<A> QuorumDriver<A> {
         transaction: VerifiedTransaction,
         tx_cert: Option<VerifiedCertificate>,
         old_retry_times: u8,
+        epoch_resubmissions: u8,
     ) -> SuiResult<()> {
         if old_retry_times >= self.max_retry_times {
             // max out the retry times, notify failure
@@ -160,10 +221,99 @@ impl<A> QuorumDriver<A> {
             tx_cert,
             retry_times: old_retry_times + 1,
             next_retry_after,
+            epoch_resubmissions,
+        })
+        .await
+    }
+
+    /// Resubmits the transaction dropped by the validators at the end of `epoch` once the next
+    /// epoch is observed, if the transaction does not expire before it. The transaction gets a
+    /// fresh budget of retries in the new epoch. If the next epoch is not observed in time, the
+    /// transaction is retried as usual.
+    async fn resubmit_in_next_epoch(
+        &self,
+        transaction: VerifiedTransaction,
+        epoch: EpochId,
+        old_retry_times: u8,
+        epoch_resubmissions: u8,
+    ) -> SuiResult<()> {
+        if epoch_resubmissions >= TX_MAX_EPOCH_RESUBMISSIONS
+            || !self.wait_for_epoch_change(epoch).await
+        {
+            return self
+                .enqueue_again_maybe(transaction, None, old_retry_times, epoch_resubmissions)
+                .await;
+        }
+
+        let tx_digest = *transaction.digest();
+        let new_epoch = self.current_epoch();
+        if let TransactionExpiration::Epoch(expiration) =
+            transaction.data().transaction_data().expiration()
+        {
+            if *expiration < new_epoch {
+                info!(
+                    ?tx_digest,
+                    "Transaction dropped at the end of epoch {epoch} expired in epoch {new_epoch}"
+                );
+                self.notify(
+                    &transaction,
+                    &Err(QuorumDriverError::TransactionExpiredAtEpochEnd { epoch }),
+                    old_retry_times + 1,
+                );
+                return Ok(());
+            }
+        }
+
+        info!(
+            ?tx_digest,
+            "Resubmitting transaction dropped at the end of epoch {epoch} in epoch {new_epoch}"
+        );
+        self.metrics.total_resubmissions_across_epochs.inc();
+        self.enqueue_task(QuorumDriverTask {
+            transaction,
+            tx_cert: None,
+            retry_times: 0,
+            next_retry_after: Instant::now(),
+            epoch_resubmissions: epoch_resubmissions + 1,
         })
         .await
     }
 
+    /// Waits until the validators of an epoch after `epoch` are known, and returns whether they
+    /// were in time.
+    async fn wait_for_epoch_change(&self, epoch: EpochId) -> bool {
+        let deadline = Instant::now() + EPOCH_CHANGE_TIMEOUT;
+        while self.current_epoch() <= epoch {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            sleep(EPOCH_CHANGE_POLL_INTERVAL).await;
+        }
+        true
+    }
+
+    /// Classifies the errors of an attempt which may be retried: once at least f+1 validators by
+    /// stake reported having closed the epoch, the transaction can only wait for the next one.
+    fn retryable_attempt_error(
+        &self,
+        errors: &[(SuiError, Vec<AuthorityName>, StakeUnit)],
+    ) -> AttemptError {
+        let validators = self.validators.load();
+        let committee = &validators.committee;
+        let halted_stake: StakeUnit = errors
+            .iter()
+            .filter(|(err, _, _)| matches!(err, SuiError::ValidatorHaltedAtEpochEnd))
+            .map(|(_, _, stake)| stake)
+            .sum();
+        if halted_stake >= committee.validity_threshold() {
+            AttemptError::EpochEnded {
+                epoch: committee.epoch,
+            }
+        } else {
+            AttemptError::Retryable
+        }
+    }
+
     pub fn notify(
         &self,
         transaction: &VerifiedTransaction,
@@ -171,6 +321,7 @@ impl<A> QuorumDriver<A> {
         total_attempts: u8,
     ) {
         let tx_digest = transaction.digest();
+        self.journal_finished(tx_digest);
         let effects_queue_result = match &response {
             Ok(resp) => {
                 self.metrics.total_ok_responses.inc();
@@ -210,11 +361,13 @@ where
         self.metrics.total_requests.inc();
 
         let ticket = self.notifier.register_one(tx_digest);
+        self.journal_accepted(&transaction);
         self.enqueue_task(QuorumDriverTask {
             transaction,
             tx_cert: None,
             retry_times: 0,
             next_retry_after: Instant::now(),
+            epoch_resubmissions: 0,
         })
         .await?;
         Ok(ticket)
@@ -232,12 +385,14 @@ where
             "Received transaction execution request, no ticket."
         );
         self.metrics.total_requests.inc();
+        self.journal_accepted(&transaction);
 
         self.enqueue_task(QuorumDriverTask {
             transaction,
             tx_cert: None,
             retry_times: 0,
             next_retry_after: Instant::now(),
+            epoch_resubmissions: 0,
         })
         .await
     }
@@ -245,7 +400,7 @@ where
     pub(crate) async fn process_transaction(
         &self,
         transaction: VerifiedTransaction,
-    ) -> Result<ProcessTransactionResult, Option<QuorumDriverError>> {
+    ) -> Result<ProcessTransactionResult, AttemptError> {
         let tx_digest = *transaction.digest();
         let result = self
             .validators
@@ -261,7 +416,7 @@ where
         &self,
         result: Result<ProcessTransactionResult, AggregatorProcessTransactionError>,
         tx_digest: TransactionDigest,
-    ) -> Result<ProcessTransactionResult, Option<QuorumDriverError>> {
+    ) -> Result<ProcessTransactionResult, AttemptError> {
         match result {
             Ok(resp) => Ok(resp),
             Err(AggregatorProcessTransactionError::RetryableConflictingTransaction {
@@ -293,7 +448,7 @@ where
                         ?errors,
                         "Observed Tx {tx_digest:} is still in retryable state. Conflicting Txes: {conflicting_tx_digests:?}", 
                     );
-                    Err(None)
+                    Err(AttemptError::Retryable)
                 }
             }
 
@@ -305,7 +460,7 @@ where
                     ?errors,
                     "Observed Tx {tx_digest:} double spend attempted. Conflicting Txes: {conflicting_tx_digests:?}",
                 );
-                Err(Some(QuorumDriverError::ObjectsDoubleUsed {
+                Err(AttemptError::Fatal(QuorumDriverError::ObjectsDoubleUsed {
                     conflicting_txes: conflicting_tx_digests,
                     retried_tx: None,
                     retried_tx_success: None,
@@ -314,9 +469,9 @@ where
 
             Err(AggregatorProcessTransactionError::FatalTransaction { errors }) => {
                 debug!(?tx_digest, ?errors, "Nonretryable transaction error");
-                Err(Some(QuorumDriverError::NonRecoverableTransactionError {
-                    errors,
-                }))
+                Err(AttemptError::Fatal(
+                    QuorumDriverError::NonRecoverableTransactionError { errors },
+                ))
             }
 
             Err(AggregatorProcessTransactionError::SystemOverload {
@@ -324,7 +479,7 @@ where
                 errors,
             }) => {
                 debug!(?tx_digest, ?errors, "System overload");
-                Err(Some(QuorumDriverError::SystemOverload {
+                Err(AttemptError::Fatal(QuorumDriverError::SystemOverload {
                     overloaded_stake,
                     errors,
                 }))
@@ -332,7 +487,7 @@ where
 
             Err(AggregatorProcessTransactionError::RetryableTransaction { errors }) => {
                 debug!(?tx_digest, ?errors, "Retryable transaction error");
-                Err(None)
+                Err(self.retryable_attempt_error(&errors))
             }
         }
     }
@@ -345,7 +500,7 @@ where
             TransactionDigest,
             (Vec<(AuthorityName, ObjectRef)>, StakeUnit),
         >,
-    ) -> Result<ProcessTransactionResult, Option<QuorumDriverError>> {
+    ) -> Result<ProcessTransactionResult, AttemptError> {
         // Safe to unwrap because tx_digest_to_retry is generated from conflicting_tx_digests
         // in ProcessTransactionState::conflicting_tx_digest_with_most_stake()
         let (validators, _) = conflicting_tx_digests.get(&conflicting_tx_digest).unwrap();
@@ -366,7 +521,7 @@ where
                     ?tx_digest,
                     "Encountered error while attemptting conflicting transaction: {:?}", err
                 );
-                let err = Err(Some(QuorumDriverError::ObjectsDoubleUsed {
+                let err = Err(AttemptError::Fatal(QuorumDriverError::ObjectsDoubleUsed {
                     conflicting_txes: conflicting_tx_digests,
                     retried_tx: None,
                     retried_tx_success: None,
@@ -389,7 +544,7 @@ where
                         .total_successful_attempts_retrying_conflicting_transaction
                         .inc();
                 }
-                Err(Some(QuorumDriverError::ObjectsDoubleUsed {
+                Err(AttemptError::Fatal(QuorumDriverError::ObjectsDoubleUsed {
                     conflicting_txes: conflicting_tx_digests,
                     retried_tx: Some(conflicting_tx_digest),
                     retried_tx_success: Some(success),
@@ -401,7 +556,7 @@ where
     pub(crate) async fn process_certificate(
         &self,
         certificate: VerifiedCertificate,
-    ) -> Result<QuorumDriverResponse, Option<QuorumDriverError>> {
+    ) -> Result<QuorumDriverResponse, AttemptError> {
         let (effects, events) = self
             .validators
             .load()
@@ -415,7 +570,7 @@ where
                     non_retryable_errors,
                 } => {
                     debug!(?non_retryable_errors, "Nonretryable certificate");
-                    Some(QuorumDriverError::NonRecoverableTransactionError {
+                    AttemptError::Fatal(QuorumDriverError::NonRecoverableTransactionError {
                         errors: non_retryable_errors,
                     })
                 }
@@ -423,7 +578,7 @@ where
                     retryable_errors,
                 } => {
                     debug!(?retryable_errors, "Retryable certificate");
-                    None
+                    self.retryable_attempt_error(&retryable_errors)
                 }
             })?;
         let response = QuorumDriverResponse {
@@ -434,6 +589,40 @@ where
         Ok(response)
     }
 
+    /// Drives again the transactions which were in flight in the journal before a restart. The
+    /// certified ones only have their certificate executed, unless their epoch ended.
+    async fn recover_from_journal(&self) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        let epoch = self.current_epoch();
+        for entry in journal.load_in_flight() {
+            let transaction = entry.transaction();
+            let tx_digest = *transaction.digest();
+            let tx_cert = entry
+                .certificate()
+                .filter(|tx_cert| tx_cert.epoch() == epoch);
+            info!(
+                ?tx_digest,
+                has_tx_cert = tx_cert.is_some(),
+                "Recovering transaction from the quorum driver journal"
+            );
+            self.metrics.total_recovered_from_journal.inc();
+            if let Err(err) = self
+                .enqueue_task(QuorumDriverTask {
+                    transaction,
+                    tx_cert,
+                    retry_times: 0,
+                    next_retry_after: Instant::now(),
+                    epoch_resubmissions: 0,
+                })
+                .await
+            {
+                error!(?tx_digest, "Failed to enqueue journaled transaction: {err}");
+            }
+        }
+    }
+
     pub async fn update_validators(&self, new_validators: Arc<AuthorityAggregator<A>>) {
         info!(
             "Quorum Driver updating AuthorityAggregator with committee {}",
@@ -541,6 +730,8 @@ where
         reconfig_observer: Arc<dyn ReconfigObserver<A> + Sync + Send>,
         metrics: Arc<QuorumDriverMetrics>,
         max_retry_times: u8,
+        resubmit_across_epochs: bool,
+        journal: Option<Arc<QuorumDriverJournal>>,
     ) -> Self {
         let (task_tx, task_rx) = mpsc::channel::<QuorumDriverTask>(TASK_QUEUE_SIZE);
         let (subscriber_tx, subscriber_rx) =
@@ -552,6 +743,8 @@ where
             notifier,
             metrics.clone(),
             max_retry_times,
+            resubmit_across_epochs,
+            journal,
         ));
         let metrics_clone = metrics.clone();
         let processor_handle = {
@@ -572,6 +765,10 @@ where
                 }
             });
         };
+        {
+            let quorum_driver_clone = quorum_driver.clone();
+            spawn_monitored_task!(async move { quorum_driver_clone.recover_from_journal().await });
+        }
         Self {
             quorum_driver,
             effects_subscriber: subscriber_rx,
@@ -615,6 +812,10 @@ where
             notifier: Arc::new(NotifyRead::new()),
             metrics: self.quorum_driver_metrics.clone(),
             max_retry_times: self.quorum_driver.max_retry_times,
+            resubmit_across_epochs: self.quorum_driver.resubmit_across_epochs,
+            // The transactions of the clone are not journaled, as they would be driven again by
+            // both quorum drivers after a restart.
+            journal: None,
         });
         let metrics = self.quorum_driver_metrics.clone();
         let processor_handle = {
@@ -672,6 +873,7 @@ where
             transaction,
             tx_cert,
             retry_times: old_retry_times,
+            epoch_resubmissions,
             ..
         } = task;
         let tx_digest = *transaction.digest();
@@ -680,6 +882,7 @@ where
             None => match quorum_driver.process_transaction(transaction.clone()).await {
                 Ok(ProcessTransactionResult::Certified(tx_cert)) => {
                     debug!(?tx_digest, "Transaction processing succeeded");
+                    quorum_driver.journal_certified(&tx_cert);
                     tx_cert
                 }
                 Ok(ProcessTransactionResult::Executed(effects_cert, events)) => {
@@ -701,6 +904,7 @@ where
                         err,
                         None,
                         old_retry_times,
+                        epoch_resubmissions,
                         "get tx cert",
                     );
                     return;
@@ -729,6 +933,7 @@ where
                     err,
                     Some(tx_cert),
                     old_retry_times,
+                    epoch_resubmissions,
                     "get effects cert",
                 );
                 return;
@@ -741,23 +946,40 @@ where
     fn handle_error(
         quorum_driver: Arc<QuorumDriver<A>>,
         transaction: VerifiedTransaction,
-        err: Option<QuorumDriverError>,
+        err: AttemptError,
         tx_cert: Option<VerifiedCertificate>,
         old_retry_times: u8,
+        epoch_resubmissions: u8,
         action: &'static str,
     ) {
         let tx_digest = *transaction.digest();
-        if let Some(qd_error) = err {
-            debug!(?tx_digest, "Failed to {action}: {}", qd_error);
-            // non-retryable failure, this task reaches terminal state for now, notify waiter.
-            quorum_driver.notify(&transaction, &Err(qd_error), old_retry_times + 1);
-        } else {
-            debug!(?tx_digest, "Failed to {action} - Retrying");
-            spawn_monitored_task!(quorum_driver.enqueue_again_maybe(
-                transaction.clone(),
-                tx_cert,
-                old_retry_times
-            ));
+        match err {
+            AttemptError::Fatal(qd_error) => {
+                debug!(?tx_digest, "Failed to {action}: {}", qd_error);
+                // non-retryable failure, this task reaches terminal state for now, notify waiter.
+                quorum_driver.notify(&transaction, &Err(qd_error), old_retry_times + 1);
+            }
+            AttemptError::EpochEnded { epoch } if quorum_driver.resubmit_across_epochs => {
+                debug!(
+                    ?tx_digest,
+                    "Failed to {action} as epoch {epoch} ended - Resubmitting in the next epoch"
+                );
+                spawn_monitored_task!(quorum_driver.resubmit_in_next_epoch(
+                    transaction,
+                    epoch,
+                    old_retry_times,
+                    epoch_resubmissions
+                ));
+            }
+            AttemptError::Retryable | AttemptError::EpochEnded { .. } => {
+                debug!(?tx_digest, "Failed to {action} - Retrying");
+                spawn_monitored_task!(quorum_driver.enqueue_again_maybe(
+                    transaction.clone(),
+                    tx_cert,
+                    old_retry_times,
+                    epoch_resubmissions
+                ));
+            }
         }
     }
 
@@ -791,6 +1013,8 @@ pub struct QuorumDriverHandlerBuilder<A> {
     notifier: Option<Arc<NotifyRead<TransactionDigest, QuorumDriverResult>>>,
     reconfig_observer: Option<Arc<dyn ReconfigObserver<A> + Sync + Send>>,
     max_retry_times: u8,
+    resubmit_across_epochs: bool,
+    journal: Option<Arc<QuorumDriverJournal>>,
 }
 
 impl<A> QuorumDriverHandlerBuilder<A>
@@ -804,6 +1028,8 @@ where
             notifier: None,
             reconfig_observer: None,
             max_retry_times: TX_MAX_RETRY_TIMES,
+            resubmit_across_epochs: true,
+            journal: None,
         }
     }
 
@@ -829,6 +1055,20 @@ where
         self
     }
 
+    /// Whether the transactions dropped by the validators at the end of an epoch are resubmitted
+    /// in the next epoch, instead of being retried in the ended one until they fail.
+    pub fn with_resubmit_across_epochs(mut self, resubmit_across_epochs: bool) -> Self {
+        self.resubmit_across_epochs = resubmit_across_epochs;
+        self
+    }
+
+    /// Journals the accepted transactions and their progress, and drives again the ones which
+    /// were in flight in the journal once started.
+    pub fn with_journal(mut self, journal: Arc<QuorumDriverJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
     pub fn start(self) -> QuorumDriverHandler<A> {
         QuorumDriverHandler::new(
             self.validators,
@@ -839,6 +1079,8 @@ where
                 .expect("Reconfig observer is missing"),
             self.metrics,
             self.max_retry_times,
+            self.resubmit_across_epochs,
+            self.journal,
         )
     }
 }
@@ -4,16 +4,21 @@
 use crate::quorum_driver::reconfig_observer::DummyReconfigObserver;
 use crate::quorum_driver::{AuthorityAggregator, QuorumDriverHandlerBuilder};
 use crate::test_authority_clients::LocalAuthorityClient;
-use crate::test_utils::make_transfer_sui_transaction;
+use crate::test_utils::{make_transfer_sui_transaction, MAX_GAS};
 use crate::{quorum_driver::QuorumDriverMetrics, test_utils::init_local_authorities};
 use mysten_common::sync::notify_read::{NotifyRead, Registration};
 use std::sync::Arc;
 use std::time::Duration;
+use sui_storage::quorum_driver_journal::QuorumDriverJournal;
 use sui_types::base_types::SuiAddress;
 use sui_types::crypto::{deterministic_random_account_key, get_key_pair, AccountKeyPair};
-use sui_types::messages::{TransactionEffectsAPI, VerifiedTransaction};
+use sui_types::messages::{
+    TransactionData, TransactionDataAPI, TransactionEffectsAPI, TransactionExpiration,
+    VerifiedTransaction, DUMMY_GAS_PRICE,
+};
 use sui_types::object::{generate_test_gas_objects, Object};
 use sui_types::quorum_driver_types::{QuorumDriverError, QuorumDriverResult};
+use sui_types::utils::to_sender_signed_transaction;
 use sui_types::{base_types::TransactionDigest, messages::QuorumDriverResponse};
 
 async fn setup() -> (
@@ -150,6 +155,31 @@ async fn test_quorum_driver_with_given_notify_read() {
     handle.await.unwrap();
 }
 
+#[tokio::test]
+async fn test_quorum_driver_recovers_journaled_transaction() {
+    let (aggregator, tx) = setup().await;
+    let digest = *tx.digest();
+    let notifier = Arc::new(NotifyRead::new());
+
+    // The transaction was accepted before the fullnode restarted, and is not submitted again.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let journal = Arc::new(QuorumDriverJournal::new(temp_dir.path().to_path_buf()));
+    journal.accept(&tx).unwrap();
+    let ticket = notifier.register_one(&digest);
+
+    let _quorum_driver_handler = QuorumDriverHandlerBuilder::new(
+        Arc::new(aggregator),
+        Arc::new(QuorumDriverMetrics::new_for_tests()),
+    )
+    .with_notifier(notifier.clone())
+    .with_reconfig_observer(Arc::new(DummyReconfigObserver {}))
+    .with_journal(journal.clone())
+    .start();
+
+    verify_ticket_response(ticket, &digest).await;
+    assert!(journal.load_in_flight().is_empty());
+}
+
 // TODO: add other cases for mismatched validator/client epoch
 #[tokio::test]
 async fn test_quorum_driver_update_validators_and_max_retry_times() {
@@ -427,3 +457,59 @@ async fn test_quorum_driver_object_locked() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_quorum_driver_resubmission_across_epochs() {
+    telemetry_subscribers::init_for_testing();
+    let (sender, keypair): (_, AccountKeyPair) = get_key_pair();
+    let gas_object = Object::with_owner_for_testing(sender);
+    let (mut aggregator, _, genesis, _) = init_local_authorities(4, vec![gas_object.clone()]).await;
+    let gas_object = genesis
+        .objects()
+        .iter()
+        .find(|o| o.id() == gas_object.id())
+        .unwrap();
+    let mut data = TransactionData::new_transfer_sui(
+        SuiAddress::random_for_testing_only(),
+        sender,
+        None,
+        gas_object.compute_object_reference(),
+        MAX_GAS,
+        DUMMY_GAS_PRICE,
+    );
+    *data.expiration_mut_for_testing() = TransactionExpiration::Epoch(0);
+    let tx = to_sender_signed_transaction(data, &keypair);
+
+    // The validators close the epoch, and drop the transactions submitted to them.
+    for client in aggregator.authority_clients.values() {
+        let epoch_store = client
+            .authority_client()
+            .state
+            .load_epoch_store_one_call_per_task();
+        epoch_store.close_user_certs(epoch_store.get_reconfig_state_write_lock_guard());
+    }
+
+    // Without resubmission, the first failure would exhaust the retries.
+    let quorum_driver_handler = Arc::new(
+        QuorumDriverHandlerBuilder::new(
+            Arc::new(aggregator.clone()),
+            Arc::new(QuorumDriverMetrics::new_for_tests()),
+        )
+        .with_reconfig_observer(Arc::new(DummyReconfigObserver {}))
+        .with_max_retry_times(0)
+        .start(),
+    );
+    let quorum_driver = quorum_driver_handler.clone_quorum_driver();
+    let ticket = quorum_driver.submit_transaction(tx).await.unwrap();
+
+    // The quorum driver waits for the next epoch, where the transaction has expired.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    aggregator.committee.epoch = 1;
+    quorum_driver.update_validators(Arc::new(aggregator)).await;
+    match tokio::time::timeout(Duration::from_secs(20), ticket).await {
+        Ok(Err(QuorumDriverError::TransactionExpiredAtEpochEnd { epoch })) => {
+            assert_eq!(epoch, 0)
+        }
+        other => panic!("the transaction should expire at the end of epoch 0, got {other:?}"),
+    }
+}
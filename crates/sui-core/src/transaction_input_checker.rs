@@ -4,6 +4,7 @@
 use crate::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use crate::authority::AuthorityStore;
 use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
 use sui_adapter::adapter::run_metered_move_bytecode_verifier;
 use sui_macros::checked_arithmetic;
 use sui_protocol_config::ProtocolConfig;
@@ -24,6 +25,36 @@ use sui_types::{
 use sui_types::{SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION};
 use tracing::instrument;
 
+/// Reads the input objects of the transactions checked: the latest versions of the objects, or
+/// their versions as of a past checkpoint to execute a transaction against the past state.
+pub trait InputObjectSource {
+    fn check_input_objects(
+        &self,
+        objects: &[InputObjectKind],
+        protocol_config: &ProtocolConfig,
+    ) -> SuiResult<Vec<Object>>;
+}
+
+impl InputObjectSource for AuthorityStore {
+    fn check_input_objects(
+        &self,
+        objects: &[InputObjectKind],
+        protocol_config: &ProtocolConfig,
+    ) -> SuiResult<Vec<Object>> {
+        AuthorityStore::check_input_objects(self, objects, protocol_config)
+    }
+}
+
+impl<T: InputObjectSource> InputObjectSource for Arc<T> {
+    fn check_input_objects(
+        &self,
+        objects: &[InputObjectKind],
+        protocol_config: &ProtocolConfig,
+    ) -> SuiResult<Vec<Object>> {
+        self.as_ref().check_input_objects(objects, protocol_config)
+    }
+}
+
 checked_arithmetic! {
 
 // Entry point for all checks related to gas.
@@ -56,7 +87,7 @@ async fn get_gas_status(
 
 #[instrument(level = "trace", skip_all)]
 pub async fn check_transaction_input(
-    store: &AuthorityStore,
+    store: &impl InputObjectSource,
     epoch_store: &AuthorityPerEpochStore,
     transaction: &TransactionData,
 ) -> SuiResult<(SuiGasStatus<'static>, InputObjects)> {
@@ -71,7 +102,7 @@ pub async fn check_transaction_input(
 }
 
 pub async fn check_transaction_input_with_given_gas(
-    store: &AuthorityStore,
+    store: &impl InputObjectSource,
     epoch_store: &AuthorityPerEpochStore,
     transaction: &TransactionData,
     gas_object: Object,
@@ -93,7 +124,7 @@ pub async fn check_transaction_input_with_given_gas(
 /// WARNING! This should only be used for the dev-inspect transaction. This transaction type
 /// bypasses many of the normal object checks
 pub(crate) async fn check_dev_inspect_input(
-    store: &AuthorityStore,
+    store: &impl InputObjectSource,
     config: &ProtocolConfig,
     kind: &TransactionKind,
     gas_object: Object,
@@ -23,6 +23,7 @@ use prometheus::{
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
+use sui_storage::quorum_driver_journal::QuorumDriverJournal;
 use sui_storage::write_path_pending_tx_log::WritePathPendingTransactionLog;
 use sui_types::base_types::TransactionDigest;
 use sui_types::error::{SuiError, SuiResult};
@@ -64,6 +65,8 @@ impl TransactiondOrchestrator<NetworkAuthorityClient> {
         reconfig_channel: Receiver<SuiSystemState>,
         parent_path: &Path,
         prometheus_registry: &Registry,
+        resubmit_across_epochs: bool,
+        journal_quorum_driver: bool,
     ) -> anyhow::Result<Self> {
         let safe_client_metrics_base = SafeClientMetricsBase::new(prometheus_registry);
         let auth_agg_metrics = AuthAggMetrics::new(prometheus_registry);
@@ -87,6 +90,8 @@ impl TransactiondOrchestrator<NetworkAuthorityClient> {
             parent_path,
             prometheus_registry,
             observer,
+            resubmit_across_epochs,
+            journal_quorum_driver,
         )
         .await)
     }
@@ -103,17 +108,23 @@ where
         parent_path: &Path,
         prometheus_registry: &Registry,
         reconfig_observer: OnsiteReconfigObserver,
+        resubmit_across_epochs: bool,
+        journal_quorum_driver: bool,
     ) -> Self {
         let notifier = Arc::new(NotifyRead::new());
-        let quorum_driver_handler = Arc::new(
-            QuorumDriverHandlerBuilder::new(
-                validators,
-                Arc::new(QuorumDriverMetrics::new(prometheus_registry)),
-            )
-            .with_notifier(notifier.clone())
-            .with_reconfig_observer(Arc::new(reconfig_observer))
-            .start(),
-        );
+        let mut quorum_driver_builder = QuorumDriverHandlerBuilder::new(
+            validators,
+            Arc::new(QuorumDriverMetrics::new(prometheus_registry)),
+        )
+        .with_notifier(notifier.clone())
+        .with_reconfig_observer(Arc::new(reconfig_observer))
+        .with_resubmit_across_epochs(resubmit_across_epochs);
+        if journal_quorum_driver {
+            quorum_driver_builder = quorum_driver_builder.with_journal(Arc::new(
+                QuorumDriverJournal::new(parent_path.join("fullnode_quorum_driver_journal")),
+            ));
+        }
+        let quorum_driver_handler = Arc::new(quorum_driver_builder.start());
 
         let effects_receiver = quorum_driver_handler.subscribe_to_effects();
         let state_clone = validator_state.clone();
@@ -183,6 +194,16 @@ where
             .transaction
             .verify()
             .map_err(QuorumDriverError::InvalidUserSignature)?;
+        // Spare the validators the transactions doomed to fail.
+        if let Err(e) = self
+            .validator_state
+            .pre_validate_transaction(&transaction)
+            .await
+        {
+            debug!(tx_digest = ?transaction.digest(), "Transaction failed pre-validation: {e}");
+            self.metrics.pre_validation_rejections.inc();
+            return Err(QuorumDriverError::InvalidTransactionInput(e));
+        }
         let (_in_flight_metrics_guards, good_response_metrics) = self.update_metrics(&transaction);
         let tx_digest = *transaction.digest();
         debug!(?tx_digest, "TO Received transaction execution request.");
@@ -429,8 +450,16 @@ where
         quorum_driver: &Arc<QuorumDriverHandler<A>>,
     ) {
         let pending_txes = pending_tx_log.load_all_pending_transactions();
+        let journal = quorum_driver.clone_quorum_driver().journal().cloned();
         for tx in pending_txes {
             let tx_digest = *tx.digest();
+            // The transactions of the journal are driven again by the quorum driver itself,
+            // from where they were.
+            if let Some(journal) = &journal {
+                if matches!(journal.get(&tx_digest), Ok(Some(_))) {
+                    continue;
+                }
+            }
             // It's not impossible we fail to enqueue a task but that's not the end of world.
             if let Err(err) = quorum_driver.submit_transaction_no_ticket(tx).await {
                 error!(
@@ -465,6 +494,8 @@ pub struct TransactionOrchestratorMetrics {
     local_execution_timeout: GenericCounter<AtomicU64>,
     local_execution_failure: GenericCounter<AtomicU64>,
 
+    pre_validation_rejections: GenericCounter<AtomicU64>,
+
     request_latency_single_writer: Histogram,
     request_latency_shared_obj: Histogram,
     wait_for_finality_latency_single_writer: Histogram,
@@ -565,6 +596,12 @@ impl TransactionOrchestratorMetrics {
                 registry,
             )
             .unwrap(),
+            pre_validation_rejections: register_int_counter_with_registry!(
+                "tx_orchestrator_pre_validation_rejections",
+                "Total number of txns Transaction Orchestrator rejects before submitting them",
+                registry,
+            )
+            .unwrap(),
             request_latency_single_writer: request_latency
                 .with_label_values(&[TX_TYPE_SINGLE_WRITER_TX]),
             request_latency_shared_obj: request_latency.with_label_values(&[TX_TYPE_SHARED_OBJ_TX]),
@@ -0,0 +1,131 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use mysten_metrics::{monitored_scope, spawn_monitored_task};
+use sui_types::{
+    base_types::ObjectID,
+    messages::{CertifiedTransaction, InputObjectKind, TransactionDataAPI},
+    storage::ObjectKey,
+};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::authority::{AuthorityMetrics, AuthorityStore};
+
+/// The number of commits whose input objects can be waiting to be prefetched. The input objects
+/// of the commits sequenced while the prefetcher is behind are not prefetched.
+const PREFETCH_QUEUE_CAPACITY: usize = 64;
+
+/// Reads the input objects of the sequenced transactions ahead of their execution, so that they
+/// are in the caches of the object store once the transactions are executed, instead of being
+/// read from disk on the path from the commit to the effects.
+///
+/// Prefetching is best effort: the objects read are discarded, and the version of the shared
+/// objects is not assigned yet, so their latest version is read.
+pub(crate) struct TransactionPrefetcher {
+    sender: mpsc::Sender<Vec<InputObjectKind>>,
+    metrics: Arc<AuthorityMetrics>,
+}
+
+impl TransactionPrefetcher {
+    pub fn start(store: Arc<AuthorityStore>, metrics: Arc<AuthorityMetrics>) -> Self {
+        let (sender, receiver) = mpsc::channel(PREFETCH_QUEUE_CAPACITY);
+        spawn_monitored_task!(Self::run(receiver, store, metrics.clone()));
+        Self { sender, metrics }
+    }
+
+    /// Queues the input objects of the certificates to be prefetched.
+    pub fn prefetch<'a>(&self, certificates: impl Iterator<Item = &'a CertifiedTransaction>) {
+        let objects: Vec<_> = certificates
+            .flat_map(|certificate| {
+                certificate
+                    .data()
+                    .intent_message()
+                    .value
+                    .input_objects()
+                    .unwrap_or_default()
+            })
+            .collect();
+        if objects.is_empty() {
+            return;
+        }
+        if self.sender.try_send(objects).is_err() {
+            self.metrics.consensus_handler_prefetch_dropped.inc();
+        }
+    }
+
+    async fn run(
+        mut receiver: mpsc::Receiver<Vec<InputObjectKind>>,
+        store: Arc<AuthorityStore>,
+        metrics: Arc<AuthorityMetrics>,
+    ) {
+        while let Some(objects) = receiver.recv().await {
+            let store = store.clone();
+            let prefetched = tokio::task::spawn_blocking(move || {
+                let _scope = monitored_scope("TransactionPrefetcher::prefetch");
+                prefetch_objects(&store, &objects)
+            })
+            .await
+            .unwrap_or_default();
+            metrics
+                .consensus_handler_prefetched_objects
+                .inc_by(prefetched as u64);
+        }
+    }
+}
+
+/// Reads the objects, and returns the number of objects found.
+fn prefetch_objects(store: &AuthorityStore, objects: &[InputObjectKind]) -> usize {
+    let mut keys = vec![];
+    let mut latest: Vec<ObjectID> = vec![];
+    for object in objects {
+        match object {
+            InputObjectKind::ImmOrOwnedMoveObject((id, version, _)) => {
+                keys.push(ObjectKey(*id, *version))
+            }
+            InputObjectKind::MovePackage(id) | InputObjectKind::SharedMoveObject { id, .. } => {
+                latest.push(*id)
+            }
+        }
+    }
+    latest.sort_unstable();
+    latest.dedup();
+
+    let found = |objects: Vec<Option<_>>| objects.iter().filter(|o| o.is_some()).count();
+    let owned = store.multi_get_object_by_key(&keys).map_or(0, found);
+    let shared = store.get_objects(&latest).map_or(0, found);
+    debug!("Prefetched {} input objects", owned + shared);
+    owned + shared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prefetch_objects;
+    use crate::authority::authority_tests::init_state_with_objects;
+    use sui_types::{
+        base_types::{ObjectID, SequenceNumber, SuiAddress},
+        messages::InputObjectKind,
+        object::Object,
+    };
+
+    #[tokio::test]
+    async fn test_prefetch_reads_input_objects() {
+        let owned = Object::with_id_owner_for_testing(ObjectID::random(), SuiAddress::default());
+        let owned_ref = owned.compute_object_reference();
+        let state = init_state_with_objects(vec![owned]).await;
+
+        let missing = ObjectID::random();
+        let objects = vec![
+            InputObjectKind::ImmOrOwnedMoveObject(owned_ref),
+            InputObjectKind::ImmOrOwnedMoveObject((
+                missing,
+                SequenceNumber::from_u64(1),
+                owned_ref.2,
+            )),
+            InputObjectKind::MovePackage(missing),
+        ];
+        assert_eq!(prefetch_objects(&state.db(), &objects), 1);
+    }
+}
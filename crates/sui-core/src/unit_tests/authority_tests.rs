@@ -1398,6 +1398,70 @@ async fn test_handle_sponsored_transaction() {
     );
 }
 
+#[tokio::test]
+async fn test_object_version_conflict() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let object_id = ObjectID::random();
+    let gas_object_id = ObjectID::random();
+    let authority_state =
+        init_state_with_ids(vec![(sender, object_id), (sender, gas_object_id)]).await;
+    let object = authority_state
+        .get_object(&object_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let gas_object = authority_state
+        .get_object(&gas_object_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let transaction = init_transfer_transaction(
+        sender,
+        &sender_key,
+        dbg_addr(2),
+        object.compute_object_reference(),
+        gas_object.compute_object_reference(),
+    );
+    let transaction_digest = *transaction.digest();
+    send_and_confirm_transaction(&authority_state, transaction)
+        .await
+        .unwrap();
+    let next_version = SequenceNumber::lamport_increment([object.version(), gas_object.version()]);
+
+    // The version spent by the transfer is reported as consumed by it.
+    let conflict = authority_state
+        .get_object_version_conflict(object_id, object.version())
+        .unwrap();
+    assert_eq!(
+        conflict.status,
+        SuiObjectVersionStatus::Consumed {
+            next_version,
+            deleted: false,
+            transaction: Some(transaction_digest),
+            checkpoint: None,
+        }
+    );
+
+    let conflict = authority_state
+        .get_object_version_conflict(object_id, next_version)
+        .unwrap();
+    assert_eq!(conflict.status, SuiObjectVersionStatus::Live);
+
+    let conflict = authority_state
+        .get_object_version_conflict(
+            object_id,
+            SequenceNumber::from_u64(next_version.value() + 1),
+        )
+        .unwrap();
+    assert_eq!(
+        conflict.status,
+        SuiObjectVersionStatus::NotFound {
+            latest_version: Some(next_version)
+        }
+    );
+}
+
 #[tokio::test]
 async fn test_transfer_package() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
@@ -4210,8 +4274,8 @@ fn init_certified_transaction(
     let vote = VerifiedSignedTransaction::new(
         0,
         transaction.clone(),
-        authority_state.name,
-        &*authority_state.secret,
+        authority_state.name(),
+        &*authority_state.secret(),
     );
     let epoch_store = authority_state.epoch_store_for_testing();
     CertifiedTransaction::new(
@@ -4227,7 +4291,7 @@ fn init_certified_transaction(
 #[cfg(test)]
 pub(crate) async fn send_consensus(authority: &AuthorityState, cert: &VerifiedCertificate) {
     let transaction = SequencedConsensusTransaction::new_test(
-        ConsensusTransaction::new_certificate_message(&authority.name, cert.clone().into_inner()),
+        ConsensusTransaction::new_certificate_message(&authority.name(), cert.clone().into_inner()),
     );
 
     if let Ok(transaction) = authority
@@ -4260,7 +4324,7 @@ pub(crate) async fn send_consensus_no_execution(
     cert: &VerifiedCertificate,
 ) {
     let transaction = SequencedConsensusTransaction::new_test(
-        ConsensusTransaction::new_certificate_message(&authority.name, cert.clone().into_inner()),
+        ConsensusTransaction::new_certificate_message(&authority.name(), cert.clone().into_inner()),
     );
 
     if let Ok(transaction) = authority
@@ -4999,6 +5063,52 @@ fn test_choose_next_system_packages() {
     );
 }
 
+#[test]
+fn test_protocol_upgrade_readiness() {
+    let packages = vec![random_object_ref()];
+    let committee = Committee::new_simple_test_committee().0;
+    let v = &committee.voting_rights;
+    let capabilities: Vec<_> = [2, 2, 2, 1]
+        .into_iter()
+        .zip(v)
+        .map(|(max, (name, _))| {
+            AuthorityCapabilities::new(
+                *name,
+                SupportedProtocolVersions::new_for_testing(1, max),
+                packages.clone(),
+            )
+        })
+        .collect();
+
+    // The committee upgrades to a version the binary does not support.
+    let readiness = ProtocolUpgradeReadiness::new(
+        ProtocolVersion::new(1),
+        SupportedProtocolVersions::new_for_testing(1, 1),
+        &committee,
+        capabilities.clone(),
+        0,
+    );
+    assert_eq!(readiness.next_version, ProtocolVersion::new(2));
+    assert!(!readiness.is_ready());
+    let stake = committee.total_votes() / 4;
+    assert_eq!(
+        readiness.stake_by_version,
+        vec![
+            (ProtocolVersion::new(1), 4 * stake),
+            (ProtocolVersion::new(2), 3 * stake)
+        ]
+    );
+
+    let readiness = ProtocolUpgradeReadiness::new(
+        ProtocolVersion::new(1),
+        SupportedProtocolVersions::new_for_testing(1, 2),
+        &committee,
+        capabilities,
+        0,
+    );
+    assert!(readiness.is_ready());
+}
+
 // skipped because it violates SUI conservation checks
 #[tokio::test]
 async fn test_gas_smashing() {
@@ -5457,3 +5567,65 @@ async fn test_publish_not_a_package_dependency() {
         failure,
     )
 }
+
+#[test]
+fn test_object_versions_index() {
+    use crate::authority::authority_store_tables::AuthorityPerpetualTables;
+
+    let path = tempfile::tempdir().unwrap().into_path();
+    let tables = AuthorityPerpetualTables::open(&path, None);
+    let (id, other_id) = (ObjectID::random(), ObjectID::random());
+    let owner = Owner::AddressOwner(SuiAddress::default());
+    let object_ref = |id, version| (id, SequenceNumber::from(version), ObjectDigest::random());
+    let effects = |created: Vec<ObjectRef>, mutated: Vec<ObjectRef>, deleted: Vec<ObjectRef>| {
+        TransactionEffects::V1(TransactionEffectsV1 {
+            created: created.into_iter().map(|r| (r, owner)).collect(),
+            mutated: mutated.into_iter().map(|r| (r, owner)).collect(),
+            deleted,
+            ..Default::default()
+        })
+    };
+
+    let created = object_ref(id, 1);
+    let mutated = object_ref(id, 3);
+    let other_created = object_ref(other_id, 1);
+    let deleted = (
+        id,
+        SequenceNumber::from(5),
+        ObjectDigest::OBJECT_DIGEST_DELETED,
+    );
+    let checkpoints = [
+        vec![effects(vec![created], vec![], vec![])],
+        // The last version written by the transactions of the checkpoint is indexed.
+        vec![
+            effects(vec![other_created], vec![object_ref(id, 2)], vec![]),
+            effects(vec![], vec![mutated], vec![]),
+        ],
+        vec![],
+        vec![effects(vec![], vec![], vec![deleted])],
+    ];
+    for (checkpoint, effects) in checkpoints.iter().enumerate() {
+        let wb = tables.object_versions_by_checkpoint.batch();
+        let wb = tables
+            .insert_object_versions_at_checkpoint(wb, 1 + checkpoint as u64, effects)
+            .unwrap();
+        wb.write().unwrap();
+    }
+    assert_eq!(tables.get_object_versions_index_start().unwrap(), Some(1));
+
+    let find = |id, checkpoint| {
+        tables
+            .find_object_ref_at_checkpoint(id, checkpoint)
+            .unwrap()
+    };
+    // The checkpoints before the index are not covered.
+    assert_eq!(find(id, 0), None);
+    assert_eq!(find(id, 1), Some(Some(created)));
+    assert_eq!(find(id, 2), Some(Some(mutated)));
+    assert_eq!(find(id, 3), Some(Some(mutated)));
+    assert_eq!(find(id, 4), Some(Some(deleted)));
+    assert_eq!(find(id, 100), Some(Some(deleted)));
+    // An object not changed since the index started may have been changed before.
+    assert_eq!(find(other_id, 1), None);
+    assert_eq!(find(other_id, 2), Some(Some(other_created)));
+}
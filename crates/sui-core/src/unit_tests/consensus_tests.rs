@@ -8,7 +8,7 @@ use crate::consensus_handler::VerifiedSequencedConsensusTransaction;
 use move_core_types::{account_address::AccountAddress, ident_str};
 use narwhal_types::Transactions;
 use narwhal_types::TransactionsServer;
-use narwhal_types::{Empty, TransactionProto};
+use narwhal_types::{Empty, TransactionProto, TransactionStatusProto, TransactionStatusRequest};
 use sui_network::tonic;
 use sui_types::crypto::deterministic_random_account_key;
 use sui_types::multiaddr::Multiaddr;
@@ -124,7 +124,7 @@ async fn submit_transaction_to_consensus_adapter() {
     // Make a new consensus adapter instance.
     let adapter = Arc::new(ConsensusAdapter::new(
         Box::new(SubmitDirectly(state.clone())),
-        state.name,
+        state.name(),
         Box::new(Arc::new(ConnectionMonitorStatusForTests {})),
         100_000,
         100_000,
@@ -133,7 +133,7 @@ async fn submit_transaction_to_consensus_adapter() {
 
     // Submit the transaction and ensure the adapter reports success to the caller. Note
     // that consensus may drop some transactions (so we may need to resubmit them).
-    let transaction = ConsensusTransaction::new_certificate_message(&state.name, certificate);
+    let transaction = ConsensusTransaction::new_certificate_message(&state.name(), certificate);
     let epoch_store = state.epoch_store_for_testing();
     let waiter = adapter
         .submit(
@@ -143,6 +143,8 @@ async fn submit_transaction_to_consensus_adapter() {
         )
         .unwrap();
     waiter.await.unwrap();
+    // The transaction is no longer pending once processed.
+    assert!(adapter.pending_transactions(None, 10).is_empty());
 }
 
 pub struct ConsensusMockServer {
@@ -170,6 +172,10 @@ impl ConsensusMockServer {
 
 #[tonic::async_trait]
 impl Transactions for ConsensusMockServer {
+    type SubscribeTransactionStatusStream = std::pin::Pin<
+        Box<dyn futures::Stream<Item = Result<TransactionStatusProto, tonic::Status>> + Send>,
+    >;
+
     /// Submit a Transactions
     async fn submit_transaction(
         &self,
@@ -185,4 +191,16 @@ impl Transactions for ConsensusMockServer {
     ) -> Result<tonic::Response<Empty>, tonic::Status> {
         unimplemented!()
     }
+    async fn get_transaction_status(
+        &self,
+        _request: tonic::Request<TransactionStatusRequest>,
+    ) -> Result<tonic::Response<TransactionStatusProto>, tonic::Status> {
+        unimplemented!()
+    }
+    async fn subscribe_transaction_status(
+        &self,
+        _request: tonic::Request<TransactionStatusRequest>,
+    ) -> Result<tonic::Response<Self::SubscribeTransactionStatusStream>, tonic::Status> {
+        unimplemented!()
+    }
 }
@@ -52,3 +52,22 @@ async fn test_remove_old_epoch_data() {
     assert_eq!(epochs_left.len(), 1);
     assert_eq!(epochs_left[0], 100);
 }
+
+#[test]
+fn test_epoch_store_sizes() {
+    let base_path = narwhal_test_utils::temp_dir();
+    fs::create_dir_all(base_path.join("7/batches")).unwrap();
+    fs::create_dir_all(base_path.join("8")).unwrap();
+    fs::create_dir_all(base_path.join("other")).unwrap();
+    fs::write(base_path.join("7/CURRENT"), [0; 10]).unwrap();
+    fs::write(base_path.join("7/batches/000001.sst"), [0; 100]).unwrap();
+    fs::write(base_path.join("other/000001.sst"), [0; 1000]).unwrap();
+
+    // Only the epoch stores are measured, with the files of their subdirectories.
+    assert_eq!(
+        data_removal::epoch_store_sizes(&base_path),
+        vec![(7, 110), (8, 0)]
+    );
+
+    _ = fs::remove_dir_all(base_path);
+}
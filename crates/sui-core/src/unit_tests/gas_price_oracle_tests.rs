@@ -0,0 +1,22 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+#[test]
+fn test_gas_price_percentiles() {
+    let prices = RecentGasPrices::new(0..=9, vec![]);
+    assert_eq!(prices.percentile(50), None);
+
+    let prices = RecentGasPrices::new(0..=9, (1..=100).rev().collect());
+    assert_eq!(prices.num_transactions(), 100);
+    assert_eq!(prices.percentile(0), Some(1));
+    assert_eq!(prices.percentile(50), Some(50));
+    assert_eq!(prices.percentile(90), Some(90));
+    assert_eq!(prices.percentile(99), Some(99));
+    assert_eq!(prices.percentile(100), Some(100));
+
+    let prices = RecentGasPrices::new(0..=0, vec![1000, 10]);
+    assert_eq!(prices.percentile(50), Some(10));
+    assert_eq!(prices.percentile(90), Some(1000));
+}
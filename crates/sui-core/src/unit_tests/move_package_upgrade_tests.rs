@@ -4,6 +4,7 @@
 use move_core_types::{account_address::AccountAddress, ident_str};
 use sui_framework::{MoveStdlib, SuiFramework, SystemPackage};
 use sui_framework_build::compiled_package::BuildConfig;
+use sui_json_rpc_types::SuiPackageUpgradeIncompatibility;
 use sui_protocol_config::ProtocolConfig;
 use sui_types::{
     base_types::{ObjectID, ObjectRef, SuiAddress},
@@ -486,6 +487,62 @@ async fn test_upgrade_package_invalid_additive_mode() {
     );
 }
 
+#[tokio::test]
+async fn test_check_package_upgrade() {
+    let runner = UpgradeStateRunner::new("move_upgrade/base").await;
+    let package_id = runner.package.0;
+    let state = &runner.authority_state;
+
+    let (_, modules) = build_upgrade_test_modules("stage1_basic_compatibility_valid");
+    let report = state
+        .check_package_upgrade(package_id, modules, vec![], UpgradePolicy::COMPATIBLE)
+        .unwrap();
+    assert!(report.is_compatible(), "{report:?}");
+
+    // All the incompatibilities are reported, not only the first one.
+    let (_, modules) = build_upgrade_test_modules("additive_upgrade_invalid");
+    let missing_dependency = ObjectID::random();
+    let report = state
+        .check_package_upgrade(
+            package_id,
+            modules,
+            vec![missing_dependency],
+            UpgradePolicy::ADDITIVE,
+        )
+        .unwrap();
+    assert!(report
+        .incompatibilities
+        .iter()
+        .any(|incompatibility| matches!(
+            incompatibility,
+            SuiPackageUpgradeIncompatibility::IncompatibleModule { .. }
+        )));
+    assert!(report.incompatibilities.contains(
+        &SuiPackageUpgradeIncompatibility::MissingDependency {
+            package: missing_dependency
+        }
+    ));
+
+    let (_, modules) = build_upgrade_test_modules("stage1_basic_compatibility_valid");
+    let report = state
+        .check_package_upgrade(package_id, modules, vec![], 255)
+        .unwrap();
+    assert_eq!(
+        report.incompatibilities,
+        vec![SuiPackageUpgradeIncompatibility::UnknownUpgradePolicy]
+    );
+
+    // The package to upgrade must exist.
+    assert!(state
+        .check_package_upgrade(
+            ObjectID::random(),
+            vec![],
+            vec![],
+            UpgradePolicy::COMPATIBLE
+        )
+        .is_err());
+}
+
 #[tokio::test]
 async fn test_upgrade_package_additive_dep_only_mode() {
     let runner = UpgradeStateRunner::new("move_upgrade/base").await;
@@ -2,10 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::authority::AuthorityState;
-use crate::narwhal_manager::{NarwhalConfiguration, NarwhalManager, NarwhalManagerMetrics};
+use crate::narwhal_manager::{
+    NarwhalConfiguration, NarwhalManager, NarwhalManagerMetrics, NARWHAL_PRIMARY, NARWHAL_WORKERS,
+};
 use bytes::Bytes;
 use fastcrypto::bls12381;
 use fastcrypto::traits::KeyPair;
+use mysten_common::lifecycle::{ComponentState, Lifecycle};
 use mysten_metrics::RegistryService;
 use narwhal_config::{Epoch, WorkerCache};
 use narwhal_executor::ExecutionState;
@@ -14,6 +17,7 @@ use narwhal_worker::TrivialTransactionValidator;
 use prometheus::Registry;
 use std::sync::Arc;
 use std::time::Duration;
+use sui_protocol_config::ProtocolConfig;
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemStateTrait;
 use sui_types::sui_system_state::SuiSystemStateTrait;
 use test_utils::authority::test_and_configure_authority_configs;
@@ -61,6 +65,7 @@ async fn send_transactions(
     // Make a transaction to submit forever.
     let tx = TransactionProto {
         transaction: Bytes::from(epoch.to_be_bytes().to_vec()),
+        ..Default::default()
     };
     // Repeatedly send transactions.
     let interval = interval(Duration::from_millis(1));
@@ -115,12 +120,14 @@ async fn test_narwhal_manager() {
         });
 
         let narwhal_config = NarwhalConfiguration {
-            primary_keypair: config.protocol_key_pair().copy(),
+            primary_signer: Arc::new(config.protocol_key_pair().copy()),
             network_keypair: config.network_key_pair().copy(),
             worker_ids_and_keypairs: vec![(0, config.worker_key_pair().copy())],
             storage_base_path: consensus_config.db_path().to_path_buf(),
             parameters: consensus_config.narwhal_config().to_owned(),
             registry_service,
+            lifecycle: Lifecycle::new(),
+            worker_standby: false,
         };
 
         let metrics = NarwhalManagerMetrics::new(&Registry::new());
@@ -134,8 +141,10 @@ async fn test_narwhal_manager() {
                 worker_cache.clone(),
                 Arc::new(execution_state.clone()),
                 TrivialTransactionValidator::default(),
+                &ProtocolConfig::get_for_max_version(),
             )
             .await;
+        assert!(narwhal_manager.lifecycle.is_ready());
 
         let name = config.protocol_key_pair().public().clone();
         narwhal_managers.push((
@@ -176,6 +185,12 @@ async fn test_narwhal_manager() {
             .workers_running()
             .await
             .is_empty());
+        for component in [NARWHAL_PRIMARY, NARWHAL_WORKERS] {
+            assert_eq!(
+                narwhal_manager.lifecycle.state(component),
+                Some(ComponentState::Stopped)
+            );
+        }
 
         let system_state = state
             .get_sui_system_state_object_for_testing()
@@ -195,6 +210,7 @@ async fn test_narwhal_manager() {
                 worker_cache.clone(),
                 Arc::new(execution_state.clone()),
                 TrivialTransactionValidator::default(),
+                &ProtocolConfig::get_for_max_version(),
             )
             .await;
 
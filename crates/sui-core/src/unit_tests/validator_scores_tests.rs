@@ -0,0 +1,49 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+fn record_many(scores: &ValidatorScores, name: AuthorityName, response: Result<(), SuiError>) {
+    for _ in 0..MIN_SAMPLES {
+        scores.record(name, &response, Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_deprioritized_validators() {
+    let (committee, _) = Committee::new_simple_test_committee_of_size(4);
+    let names: Vec<_> = committee.names().copied().collect();
+    let scores = ValidatorScores::default();
+    assert!(scores.deprioritized_validators(&committee).is_empty());
+
+    // The errors caused by the transaction do not count against the validator.
+    record_many(
+        &scores,
+        names[0],
+        Err(SuiError::ExecutionError("failed".to_string())),
+    );
+    assert!(scores.deprioritized_validators(&committee).is_empty());
+
+    record_many(&scores, names[1], Ok(()));
+    record_many(&scores, names[2], Err(SuiError::TimeoutError));
+    assert_eq!(
+        scores.deprioritized_validators(&committee),
+        BTreeSet::from([names[2]])
+    );
+    let score = scores.get(&names[1]).unwrap();
+    assert_eq!(score.success_rate, 1.0);
+    assert!((score.latency.as_secs_f64() - 0.01).abs() < 1e-6);
+
+    // The validators deprioritized never hold the validity threshold, so that a quorum is
+    // reached without them.
+    record_many(
+        &scores,
+        names[3],
+        Err(SuiError::RpcError(
+            "unavailable".to_string(),
+            "".to_string(),
+        )),
+    );
+    assert_eq!(scores.deprioritized_validators(&committee).len(), 1);
+    assert_eq!(scores.responsive_validators(&committee).len(), 3);
+}
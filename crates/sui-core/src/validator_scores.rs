@@ -0,0 +1,152 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scores of the validators, from the outcome and the latency of the transactions and
+//! certificates submitted to them, so that the transactions are driven to finality by the
+//! responsive validators first instead of piling more requests on the ones already failing.
+//!
+//! The success rate and the latency of a validator are exponential moving averages of its
+//! responses. Only the errors telling about the health of the validator count as failures, e.g.
+//! a timeout or an overload, as opposed to the errors caused by the transaction itself.
+
+use parking_lot::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+use sui_types::base_types::AuthorityName;
+use sui_types::committee::{Committee, StakeUnit};
+use sui_types::error::SuiError;
+
+/// The weight of the latest response in the moving averages.
+const SMOOTHING_FACTOR: f64 = 0.1;
+
+/// The number of responses needed before a validator can be deprioritized.
+const MIN_SAMPLES: u64 = 5;
+
+/// The success rate below which a validator is deprioritized.
+const MIN_SUCCESS_RATE: f64 = 0.5;
+
+/// The time after which the score of a validator is forgotten when it was not sent any request,
+/// so that a deprioritized validator is retried once it had time to recover.
+const SCORE_EXPIRY: Duration = Duration::from_secs(60);
+
+#[cfg(test)]
+#[path = "unit_tests/validator_scores_tests.rs"]
+mod validator_scores_tests;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValidatorScore {
+    /// The moving average of the responses which were successes, between 0 and 1.
+    pub success_rate: f64,
+    /// The moving average of the latency of the successful responses.
+    pub latency: Duration,
+    pub samples: u64,
+    last_update: Instant,
+}
+
+impl ValidatorScore {
+    fn new() -> Self {
+        Self {
+            success_rate: 1.0,
+            latency: Duration::ZERO,
+            samples: 0,
+            last_update: Instant::now(),
+        }
+    }
+
+    fn is_unresponsive(&self) -> bool {
+        self.samples >= MIN_SAMPLES && self.success_rate < MIN_SUCCESS_RATE
+    }
+}
+
+#[derive(Default)]
+pub struct ValidatorScores {
+    scores: Mutex<HashMap<AuthorityName, ValidatorScore>>,
+}
+
+impl ValidatorScores {
+    /// Records the response of the validator to a request sent `latency` ago. Returns the new
+    /// score of the validator, unless the response tells nothing about its health.
+    pub fn record<T>(
+        &self,
+        name: AuthorityName,
+        response: &Result<T, SuiError>,
+        latency: Duration,
+    ) -> Option<ValidatorScore> {
+        let success = match response {
+            Ok(_) => true,
+            Err(SuiError::TimeoutError) => false,
+            Err(err) if err.is_overload() || err.is_retryable().0 => false,
+            Err(_) => return None,
+        };
+        let mut scores = self.scores.lock();
+        let score = scores.entry(name).or_insert_with(ValidatorScore::new);
+        if score.last_update.elapsed() >= SCORE_EXPIRY {
+            *score = ValidatorScore::new();
+        }
+        let sample = if success { 1.0 } else { 0.0 };
+        if score.samples == 0 {
+            score.success_rate = sample;
+        } else {
+            score.success_rate += SMOOTHING_FACTOR * (sample - score.success_rate);
+        }
+        if success {
+            score.latency = if score.latency.is_zero() {
+                latency
+            } else {
+                score
+                    .latency
+                    .mul_f64(1.0 - SMOOTHING_FACTOR)
+                    .saturating_add(latency.mul_f64(SMOOTHING_FACTOR))
+            };
+        }
+        score.samples += 1;
+        score.last_update = Instant::now();
+        Some(*score)
+    }
+
+    pub fn get(&self, name: &AuthorityName) -> Option<ValidatorScore> {
+        self.scores
+            .lock()
+            .get(name)
+            .filter(|score| score.last_update.elapsed() < SCORE_EXPIRY)
+            .copied()
+    }
+
+    /// Returns the unresponsive validators of the committee, which are only sent requests when
+    /// the responsive validators are not enough to reach a quorum in time. They are picked from
+    /// the lowest success rate, and hold less stake than the validity threshold so that the
+    /// other validators always hold a quorum.
+    pub fn deprioritized_validators(&self, committee: &Committee) -> BTreeSet<AuthorityName> {
+        let mut unresponsive: Vec<_> = committee
+            .names()
+            .filter_map(|name| {
+                self.get(name)
+                    .filter(|score| score.is_unresponsive())
+                    .map(|score| (*name, score.success_rate))
+            })
+            .collect();
+        unresponsive.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let mut deprioritized = BTreeSet::new();
+        let mut deprioritized_stake: StakeUnit = 0;
+        for (name, _) in unresponsive {
+            let weight = committee.weight(&name);
+            if deprioritized_stake + weight >= committee.validity_threshold() {
+                continue;
+            }
+            deprioritized_stake += weight;
+            deprioritized.insert(name);
+        }
+        deprioritized
+    }
+
+    /// Returns the validators of the committee which are not deprioritized.
+    pub fn responsive_validators(&self, committee: &Committee) -> BTreeSet<AuthorityName> {
+        let deprioritized = self.deprioritized_validators(committee);
+        committee
+            .names()
+            .filter(|name| !deprioritized.contains(name))
+            .copied()
+            .collect()
+    }
+}
@@ -5,8 +5,9 @@
 #[test]
 #[cfg_attr(msim, ignore)]
 fn test_format() {
-    // If this test breaks and you intended a format change, you need to run to get the fresh format:
-    // # cargo -q run --example generate-format -- print > crates/sui-core/tests/staged/sui.yaml
+    // If this test breaks and you intended a format change, you need to bump FORMAT_VERSION in
+    // src/generate_format.rs and run to record the fresh format:
+    // # cargo -q run --example generate-format -- record
 
     let status = std::process::Command::new("cargo")
         .current_dir("..")
@@ -17,8 +18,9 @@ fn test_format() {
     assert!(
         status.success(),
         "\n\
-If this test breaks and you intended a format change, you need to run to get the fresh format:\n\
-cargo -q run --example generate-format -- print > crates/sui-core/tests/staged/sui.yaml\n\
+If this test breaks and you intended a format change, you need to bump FORMAT_VERSION in\n\
+src/generate_format.rs and run to record the fresh format:\n\
+cargo -q run --example generate-format -- record\n\
         "
     );
 }
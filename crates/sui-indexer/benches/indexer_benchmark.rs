@@ -77,10 +77,13 @@ fn create_checkpoint(sequence_number: i64) -> TemporaryCheckpointStore {
             mutated_objects: (1..1000).map(|_| create_object(sequence_number)).collect(),
             deleted_objects: vec![],
         }],
+        owned_objects: vec![],
+        unowned_object_ids: vec![],
         addresses: vec![],
         packages: vec![],
         input_objects: vec![],
         move_calls: vec![],
+        gas_usages: vec![],
         recipients: vec![],
     }
 }
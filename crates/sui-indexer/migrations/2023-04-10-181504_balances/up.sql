@@ -0,0 +1,29 @@
+-- The live objects owned by addresses, maintained from the checkpoints alongside the objects
+-- table, with the balance of the coins so that the balances can be updated incrementally.
+CREATE TABLE owned_objects
+(
+    object_id            address      PRIMARY KEY,
+    version              BIGINT       NOT NULL,
+    object_digest        base58digest NOT NULL,
+    checkpoint           BIGINT       NOT NULL,
+    owner_address        address      NOT NULL,
+    object_type          VARCHAR      NOT NULL,
+    previous_transaction base58digest NOT NULL,
+    -- only non-null for coins
+    coin_type            VARCHAR,
+    coin_balance         BIGINT
+);
+CREATE INDEX owned_objects_owner_type ON owned_objects (owner_address, object_type, object_id);
+CREATE INDEX owned_objects_owner_coin_type ON owned_objects (owner_address, coin_type, object_id);
+
+-- The total balance of each coin type owned by an address, the sum of its owned coins.
+CREATE TABLE balances
+(
+    owner_address     address NOT NULL,
+    coin_type         VARCHAR NOT NULL,
+    balance           BIGINT  NOT NULL,
+    coin_object_count BIGINT  NOT NULL,
+    -- the last checkpoint which changed the balance
+    checkpoint        BIGINT  NOT NULL,
+    CONSTRAINT balances_pk PRIMARY KEY (owner_address, coin_type)
+);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS move_call_gas_usage;
@@ -0,0 +1,18 @@
+-- The gas used by the transactions calling each Move function, by day, added to as the
+-- checkpoints are indexed. The gas of a transaction is split between its Move calls.
+CREATE TABLE move_call_gas_usage
+(
+    -- the day of the checkpoints, in days since the Unix epoch
+    day               BIGINT  NOT NULL,
+    move_package      TEXT    NOT NULL,
+    move_module       TEXT    NOT NULL,
+    move_function     TEXT    NOT NULL,
+    computation_cost  BIGINT  NOT NULL,
+    storage_cost      BIGINT  NOT NULL,
+    storage_rebate    BIGINT  NOT NULL,
+    call_count        BIGINT  NOT NULL,
+    -- the last checkpoint which added to the usage
+    checkpoint        BIGINT  NOT NULL,
+    CONSTRAINT move_call_gas_usage_pk PRIMARY KEY (day, move_package, move_module, move_function)
+);
+CREATE INDEX move_call_gas_usage_package ON move_call_gas_usage (move_package, day);
@@ -6,27 +6,68 @@ use jsonrpsee::core::RpcResult;
 use jsonrpsee::http_client::HttpClient;
 use jsonrpsee::RpcModule;
 use sui_json_rpc::api::CoinReadApiClient;
-use sui_json_rpc::api::CoinReadApiServer;
+use sui_json_rpc::api::{cap_page_limit, CoinReadApiServer};
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::{Balance, CoinPage, SuiCoinMetadata};
 use sui_open_rpc::Module;
 use sui_types::balance::Supply;
 use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::gas_coin::GAS;
+use sui_types::parse_sui_struct_tag;
 
-pub(crate) struct CoinReadApi {
+use crate::errors::IndexerError;
+use crate::store::IndexerStore;
+
+pub(crate) struct CoinReadApi<S> {
+    state: S,
     fullnode: HttpClient,
+    migrated_methods: Vec<String>,
 }
 
-impl CoinReadApi {
-    pub fn new(fullnode_client: HttpClient) -> Self {
+impl<S: IndexerStore> CoinReadApi<S> {
+    pub fn new(state: S, fullnode_client: HttpClient, migrated_methods: Vec<String>) -> Self {
         Self {
+            state,
             fullnode: fullnode_client,
+            migrated_methods,
         }
     }
+
+    fn get_coins_internal(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+        cursor: Option<ObjectID>,
+        limit: Option<usize>,
+    ) -> Result<CoinPage, IndexerError> {
+        let limit = cap_page_limit(limit);
+        // Retrieve 1 extra item for next cursor
+        let mut data = self.state.get_coins(owner, coin_type, cursor, limit + 1)?;
+        let has_next_page = data.len() > limit;
+        data.truncate(limit);
+        let next_cursor = data.last().map_or(cursor, |coin| Some(coin.coin_object_id));
+        Ok(CoinPage {
+            data,
+            next_cursor,
+            has_next_page,
+        })
+    }
+}
+
+/// Returns the coin type in the format the balances are indexed with, SUI if unspecified.
+fn normalize_coin_type(coin_type: Option<String>) -> Result<String, IndexerError> {
+    Ok(match coin_type {
+        Some(coin_type) => parse_sui_struct_tag(&coin_type)?,
+        None => GAS::type_(),
+    }
+    .to_string())
 }
 
 #[async_trait]
-impl CoinReadApiServer for CoinReadApi {
+impl<S> CoinReadApiServer for CoinReadApi<S>
+where
+    S: IndexerStore + Sync + Send + 'static,
+{
     async fn get_coins(
         &self,
         owner: SuiAddress,
@@ -34,9 +75,14 @@ impl CoinReadApiServer for CoinReadApi {
         cursor: Option<ObjectID>,
         limit: Option<usize>,
     ) -> RpcResult<CoinPage> {
-        self.fullnode
-            .get_coins(owner, coin_type, cursor, limit)
-            .await
+        if !self.migrated_methods.contains(&"get_coins".to_string()) {
+            return self
+                .fullnode
+                .get_coins(owner, coin_type, cursor, limit)
+                .await;
+        }
+        let coin_type = normalize_coin_type(coin_type)?;
+        Ok(self.get_coins_internal(owner, Some(coin_type), cursor, limit)?)
     }
 
     async fn get_all_coins(
@@ -45,7 +91,10 @@ impl CoinReadApiServer for CoinReadApi {
         cursor: Option<ObjectID>,
         limit: Option<usize>,
     ) -> RpcResult<CoinPage> {
-        self.fullnode.get_all_coins(owner, cursor, limit).await
+        if !self.migrated_methods.contains(&"get_all_coins".to_string()) {
+            return self.fullnode.get_all_coins(owner, cursor, limit).await;
+        }
+        Ok(self.get_coins_internal(owner, None, cursor, limit)?)
     }
 
     async fn get_balance(
@@ -53,11 +102,21 @@ impl CoinReadApiServer for CoinReadApi {
         owner: SuiAddress,
         coin_type: Option<String>,
     ) -> RpcResult<Balance> {
-        self.fullnode.get_balance(owner, coin_type).await
+        if !self.migrated_methods.contains(&"get_balance".to_string()) {
+            return self.fullnode.get_balance(owner, coin_type).await;
+        }
+        let coin_type = normalize_coin_type(coin_type)?;
+        Ok(self.state.get_balance(owner, coin_type)?)
     }
 
     async fn get_all_balances(&self, owner: SuiAddress) -> RpcResult<Vec<Balance>> {
-        self.fullnode.get_all_balances(owner).await
+        if !self
+            .migrated_methods
+            .contains(&"get_all_balances".to_string())
+        {
+            return self.fullnode.get_all_balances(owner).await;
+        }
+        Ok(self.state.get_all_balances(owner)?)
     }
 
     async fn get_coin_metadata(&self, coin_type: String) -> RpcResult<SuiCoinMetadata> {
@@ -69,7 +128,10 @@ impl CoinReadApiServer for CoinReadApi {
     }
 }
 
-impl SuiRpcModule for CoinReadApi {
+impl<S> SuiRpcModule for CoinReadApi<S>
+where
+    S: IndexerStore + Sync + Send + 'static,
+{
     fn rpc(self) -> RpcModule<Self> {
         self.into_rpc()
     }
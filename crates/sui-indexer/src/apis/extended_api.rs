@@ -1,25 +1,32 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::RpcModule;
 
 use sui_json_rpc::api::{
-    validate_limit, ExtendedApiServer, QUERY_MAX_RESULT_LIMIT_CHECKPOINTS,
+    validate_limit, ExtendedApiServer, QUERY_MAX_RESULT_LIMIT, QUERY_MAX_RESULT_LIMIT_CHECKPOINTS,
     QUERY_MAX_RESULT_LIMIT_OBJECTS,
 };
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::{
-    CheckpointedObjectID, EpochInfo, EpochPage, MoveCallMetrics, NetworkMetrics, ObjectsPage, Page,
-    SuiObjectDataFilter, SuiObjectResponse, SuiObjectResponseQuery,
+    CheckpointedObjectID, EpochInfo, EpochPage, GasUsage, GasUsageGrouping, MoveCallMetrics,
+    NetworkMetrics, ObjectsPage, Page, SuiObjectDataFilter, SuiObjectResponse,
+    SuiObjectResponseQuery,
 };
 use sui_open_rpc::Module;
 use sui_types::base_types::EpochId;
 
 use crate::errors::IndexerError;
+use crate::models::gas_usage::day_of;
 use crate::store::IndexerStore;
 
+/// The time window of the gas usage when its start is not specified.
+const DEFAULT_GAS_USAGE_WINDOW: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
 pub(crate) struct ExtendedApi<S> {
     state: S,
 }
@@ -124,6 +131,31 @@ impl<S: IndexerStore + Sync + Send + 'static> ExtendedApiServer for ExtendedApi<
     async fn get_move_call_metrics(&self) -> RpcResult<MoveCallMetrics> {
         Ok(self.state.get_move_call_metrics()?)
     }
+
+    async fn get_gas_usage_metrics(
+        &self,
+        grouping: GasUsageGrouping,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        limit: Option<usize>,
+    ) -> RpcResult<Vec<GasUsage>> {
+        let limit = validate_limit(limit, QUERY_MAX_RESULT_LIMIT)?;
+        let end_time = end_time.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64
+        });
+        let start_time = start_time.unwrap_or_else(|| {
+            end_time.saturating_sub(DEFAULT_GAS_USAGE_WINDOW.as_millis() as u64)
+        });
+        Ok(self.state.get_gas_usage_metrics(
+            grouping,
+            day_of(start_time),
+            day_of(end_time),
+            limit,
+        )?)
+    }
 }
 
 impl<S> SuiRpcModule for ExtendedApi<S>
@@ -8,7 +8,7 @@ use jsonrpsee::RpcModule;
 use sui_json_rpc::api::{GovernanceReadApiClient, GovernanceReadApiServer};
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::SuiCommittee;
-use sui_json_rpc_types::{BigInt, DelegatedStake};
+use sui_json_rpc_types::{BigInt, DelegatedStake, GasPriceEstimates};
 use sui_open_rpc::Module;
 use sui_types::base_types::{EpochId, ObjectID, SuiAddress};
 use sui_types::sui_system_state::sui_system_state_summary::SuiSystemStateSummary;
@@ -48,6 +48,13 @@ impl GovernanceReadApiServer for GovernanceReadApi {
     async fn get_reference_gas_price(&self) -> RpcResult<BigInt> {
         self.fullnode.get_reference_gas_price().await
     }
+
+    async fn get_gas_price_estimates(
+        &self,
+        num_checkpoints: Option<usize>,
+    ) -> RpcResult<GasPriceEstimates> {
+        self.fullnode.get_gas_price_estimates(num_checkpoints).await
+    }
 }
 
 impl SuiRpcModule for GovernanceReadApi {
@@ -3,10 +3,12 @@
 
 use std::sync::Arc;
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 use futures::future::join_all;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::http_client::HttpClient;
+use jsonrpsee::types::error::CallError;
 use jsonrpsee::types::SubscriptionResult;
 use jsonrpsee::{RpcModule, SubscriptionSink};
 
@@ -16,9 +18,10 @@ use sui_json_rpc::api::{validate_limit, IndexerApiClient, QUERY_MAX_RESULT_LIMIT
 use sui_json_rpc::indexer_api::spawn_subscription;
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::{
-    CheckpointedObjectID, DynamicFieldPage, EventFilter, EventPage, ObjectsPage, Page,
-    SuiObjectDataFilter, SuiObjectResponse, SuiObjectResponseQuery,
-    SuiTransactionBlockResponseQuery, TransactionBlocksPage,
+    CheckpointedObjectID, DynamicFieldPage, EventFilter, EventPage, ObjectChangeFilter,
+    ObjectsPage, Page, SuiCheckpointSequenceNumber, SuiObjectDataFilter, SuiObjectResponse,
+    SuiObjectResponseQuery, SuiTransactionBlockResponseOptions, SuiTransactionBlockResponseQuery,
+    TransactionBlocksPage,
 };
 use sui_open_rpc::Module;
 use sui_types::base_types::{ObjectID, SuiAddress};
@@ -339,6 +342,31 @@ where
         spawn_subscription(sink, self.event_handler.subscribe(filter));
         Ok(())
     }
+
+    fn subscribe_finalized_transaction(
+        &self,
+        mut sink: SubscriptionSink,
+        _from_checkpoint: Option<SuiCheckpointSequenceNumber>,
+        _options: Option<SuiTransactionBlockResponseOptions>,
+    ) -> SubscriptionResult {
+        // The fullnode is only reached over HTTP, which has no subscriptions.
+        let _ = sink.reject(CallError::Failed(anyhow!(
+            "Subscribing to the finalized transactions is only supported by the fullnodes"
+        )));
+        Ok(())
+    }
+
+    fn subscribe_object_change(
+        &self,
+        mut sink: SubscriptionSink,
+        _filter: ObjectChangeFilter,
+        _from_checkpoint: Option<SuiCheckpointSequenceNumber>,
+    ) -> SubscriptionResult {
+        let _ = sink.reject(CallError::Failed(anyhow!(
+            "Subscribing to the object changes is only supported by the fullnodes"
+        )));
+        Ok(())
+    }
 }
 
 impl<S> SuiRpcModule for IndexerApi<S>
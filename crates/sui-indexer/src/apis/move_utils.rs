@@ -4,6 +4,7 @@
 use std::collections::BTreeMap;
 
 use async_trait::async_trait;
+use fastcrypto::encoding::Base64;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::http_client::HttpClient;
 use jsonrpsee::RpcModule;
@@ -13,7 +14,7 @@ use sui_json_rpc::api::MoveUtilsServer;
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::{
     MoveFunctionArgType, SuiMoveNormalizedFunction, SuiMoveNormalizedModule,
-    SuiMoveNormalizedStruct,
+    SuiMoveNormalizedStruct, SuiPackageUpgradeReport,
 };
 use sui_open_rpc::Module;
 use sui_types::base_types::ObjectID;
@@ -93,4 +94,16 @@ impl MoveUtilsServer for MoveUtilsApi {
             .get_move_function_arg_types(package, module, function)
             .await
     }
+
+    async fn check_package_upgrade(
+        &self,
+        package: ObjectID,
+        compiled_modules: Vec<Base64>,
+        dependencies: Vec<ObjectID>,
+        policy: Option<u8>,
+    ) -> RpcResult<SuiPackageUpgradeReport> {
+        self.fullnode
+            .check_package_upgrade(package, compiled_modules, dependencies, policy)
+            .await
+    }
 }
@@ -10,9 +10,10 @@ use jsonrpsee::RpcModule;
 use sui_json_rpc::api::{ReadApiClient, ReadApiServer};
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::{
-    BigInt, Checkpoint, CheckpointId, CheckpointPage, SuiCheckpointSequenceNumber, SuiEvent,
-    SuiGetPastObjectRequest, SuiObjectDataOptions, SuiObjectResponse, SuiPastObjectResponse,
-    SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    BigInt, Checkpoint, CheckpointId, CheckpointObjectChangePage, CheckpointPage, CheckpointProof,
+    FullCheckpoint, SuiCheckpointSequenceNumber, SuiEvent, SuiGetPastObjectRequest,
+    SuiObjectDataOptions, SuiObjectResponse, SuiObjectVersionConflict, SuiObjectsAtCheckpoint,
+    SuiPastObjectResponse, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
 };
 use sui_open_rpc::Module;
 use sui_types::base_types::{ObjectID, SequenceNumber};
@@ -187,6 +188,17 @@ where
             .await?)
     }
 
+    async fn multi_get_objects_at_checkpoint(
+        &self,
+        object_ids: Vec<ObjectID>,
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
+        options: Option<SuiObjectDataOptions>,
+    ) -> RpcResult<SuiObjectsAtCheckpoint> {
+        self.fullnode
+            .multi_get_objects_at_checkpoint(object_ids, checkpoint, options)
+            .await
+    }
+
     async fn try_get_past_object(
         &self,
         object_id: ObjectID,
@@ -208,6 +220,16 @@ where
             .await
     }
 
+    async fn get_object_version_conflict(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> RpcResult<SuiObjectVersionConflict> {
+        self.fullnode
+            .get_object_version_conflict(object_id, version)
+            .await
+    }
+
     async fn get_latest_checkpoint_sequence_number(
         &self,
     ) -> RpcResult<SuiCheckpointSequenceNumber> {
@@ -232,6 +254,14 @@ where
         Ok(self.state.get_checkpoint(id)?)
     }
 
+    async fn get_checkpoint_proof(&self, id: CheckpointId) -> RpcResult<CheckpointProof> {
+        self.fullnode.get_checkpoint_proof(id).await
+    }
+
+    async fn get_full_checkpoint(&self, id: CheckpointId) -> RpcResult<FullCheckpoint> {
+        self.fullnode.get_full_checkpoint(id).await
+    }
+
     async fn get_checkpoints(
         &self,
         cursor: Option<SuiCheckpointSequenceNumber>,
@@ -244,6 +274,18 @@ where
             .await;
     }
 
+    async fn get_object_changes_between_checkpoints(
+        &self,
+        from_checkpoint: SuiCheckpointSequenceNumber,
+        to_checkpoint: SuiCheckpointSequenceNumber,
+        cursor: Option<ObjectID>,
+        limit: Option<usize>,
+    ) -> RpcResult<CheckpointObjectChangePage> {
+        self.fullnode
+            .get_object_changes_between_checkpoints(from_checkpoint, to_checkpoint, cursor, limit)
+            .await
+    }
+
     async fn get_events(&self, transaction_digest: TransactionDigest) -> RpcResult<Vec<SuiEvent>> {
         self.fullnode.get_events(transaction_digest).await
     }
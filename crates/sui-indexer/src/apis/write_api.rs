@@ -9,8 +9,8 @@ use jsonrpsee::RpcModule;
 use sui_json_rpc::api::{WriteApiClient, WriteApiServer};
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::{
-    BigInt, DevInspectResults, DryRunTransactionBlockResponse, SuiTransactionBlockResponse,
-    SuiTransactionBlockResponseOptions,
+    BigInt, DevInspectResults, DryRunTransactionBlockResponse, SuiCheckpointSequenceNumber,
+    SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
 };
 use sui_open_rpc::Module;
 use sui_types::base_types::{EpochId, SuiAddress};
@@ -74,17 +74,21 @@ where
         tx_bytes: Base64,
         gas_price: Option<BigInt>,
         epoch: Option<EpochId>,
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
     ) -> RpcResult<DevInspectResults> {
         self.fullnode
-            .dev_inspect_transaction_block(sender_address, tx_bytes, gas_price, epoch)
+            .dev_inspect_transaction_block(sender_address, tx_bytes, gas_price, epoch, checkpoint)
             .await
     }
 
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
     ) -> RpcResult<DryRunTransactionBlockResponse> {
-        self.fullnode.dry_run_transaction_block(tx_bytes).await
+        self.fullnode
+            .dry_run_transaction_block(tx_bytes, checkpoint)
+            .await
     }
 }
 
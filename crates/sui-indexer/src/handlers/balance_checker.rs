@@ -0,0 +1,85 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use prometheus::Registry;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use mysten_metrics::spawn_monitored_task;
+
+use crate::errors::IndexerError;
+use crate::metrics::IndexerBalanceCheckerMetrics;
+use crate::store::IndexerStore;
+
+/// The maximum number of inconsistencies reported by a check.
+const MAX_REPORTED_INCONSISTENCIES: usize = 100;
+
+/// Periodically checks that the balances match the coins owned by the addresses, and that the
+/// owned objects match the objects, so that a bug in their incremental maintenance is noticed.
+/// The tables can then be rebuilt with the `--backfill-owned-objects` flag.
+pub struct BalanceChecker<S> {
+    state: S,
+    interval: Duration,
+    metrics: IndexerBalanceCheckerMetrics,
+}
+
+impl<S> BalanceChecker<S>
+where
+    S: IndexerStore + Sync + Send + 'static,
+{
+    pub fn new(state: S, interval: Duration, prometheus_registry: &Registry) -> Self {
+        Self {
+            state,
+            interval,
+            metrics: IndexerBalanceCheckerMetrics::new(prometheus_registry),
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        spawn_monitored_task!(async move {
+            info!("Indexer balance checker started...");
+            loop {
+                tokio::time::sleep(self.interval).await;
+                if let Err(e) = self.check() {
+                    error!("Indexer balance check failed with error: {:?}", e);
+                }
+            }
+        })
+    }
+
+    fn check(&self) -> Result<(), IndexerError> {
+        let balances = self
+            .state
+            .get_balance_inconsistencies(MAX_REPORTED_INCONSISTENCIES)?;
+        for balance in &balances {
+            warn!(
+                "Balance mismatch of {} with coin type {}: indexed balance {:?} of {:?} coins, expected balance {:?} of {:?} coins",
+                balance.owner_address,
+                balance.coin_type,
+                balance.indexed_balance,
+                balance.indexed_coin_object_count,
+                balance.expected_balance,
+                balance.expected_coin_object_count
+            );
+        }
+        let owned_objects = self
+            .state
+            .get_owned_object_inconsistencies(MAX_REPORTED_INCONSISTENCIES)?;
+        if !owned_objects.is_empty() {
+            warn!(
+                "Owned objects mismatch with objects: {}",
+                owned_objects.join(", ")
+            );
+        }
+        self.metrics.total_balance_check.inc();
+        self.metrics
+            .inconsistent_balances
+            .set(balances.len() as i64);
+        self.metrics
+            .inconsistent_owned_objects
+            .set(owned_objects.len() as i64);
+        Ok(())
+    }
+}
@@ -21,6 +21,7 @@ use sui_json_rpc_types::{
 };
 use sui_sdk::error::Error;
 use sui_sdk::SuiClient;
+use sui_types::gas::GasCostSummary;
 use sui_types::messages_checkpoint::{CheckpointCommitment, CheckpointSequenceNumber};
 use sui_types::sui_system_state::sui_system_state_summary::SuiSystemStateSummary;
 use sui_types::sui_system_state::{get_sui_system_state, SuiSystemStateTrait};
@@ -31,7 +32,9 @@ use crate::metrics::IndexerCheckpointHandlerMetrics;
 use crate::models;
 use crate::models::checkpoints::Checkpoint;
 use crate::models::epoch::{DBEpochInfo, SystemEpochInfoEvent};
+use crate::models::gas_usage::gas_usage_changes;
 use crate::models::objects::{DeletedObject, Object, ObjectStatus};
+use crate::models::owned_objects::OwnedObject;
 use crate::models::packages::Package;
 use crate::store::{
     CheckpointData, IndexerStore, TemporaryCheckpointStore, TemporaryEpochStore,
@@ -349,6 +352,9 @@ where
             })
             .collect();
 
+        // Index owned objects, from the last change of each object in the checkpoint
+        let (owned_objects, unowned_object_ids) = Self::index_owned_objects(&objects_changes)?;
+
         // Index packages
         let packages = Self::index_packages(transactions, changed_objects)?;
 
@@ -360,10 +366,24 @@ where
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
-        let move_calls = transactions
+        let tx_move_calls = transactions
             .iter()
-            .flat_map(|tx| tx.get_move_calls(checkpoint.epoch, checkpoint.sequence_number.into()))
-            .collect();
+            .map(|tx| tx.get_move_calls(checkpoint.epoch, checkpoint.sequence_number.into()))
+            .collect::<Vec<_>>();
+
+        // Index the gas used by the move calls
+        let gas_usages = gas_usage_changes(
+            tx_move_calls
+                .iter()
+                .zip(transactions)
+                .map(|(move_calls, tx)| {
+                    let gas = GasCostSummary::from(tx.effects.gas_cost_summary().clone());
+                    (move_calls.as_slice(), gas)
+                }),
+            checkpoint.timestamp_ms,
+            <u64>::from(checkpoint.sequence_number) as i64,
+        );
+        let move_calls = tx_move_calls.into_iter().flatten().collect();
         let recipients = transactions
             .iter()
             .flat_map(|tx| tx.get_recipients(checkpoint.epoch, checkpoint.sequence_number.into()))
@@ -490,16 +510,53 @@ where
                 transactions: db_transactions,
                 events,
                 objects_changes,
+                owned_objects,
+                unowned_object_ids,
                 addresses,
                 packages,
                 input_objects,
                 move_calls,
+                gas_usages,
                 recipients,
             },
             epoch_index,
         ))
     }
 
+    fn index_owned_objects(
+        objects_changes: &[TransactionObjectChanges],
+    ) -> Result<(Vec<OwnedObject>, Vec<String>), IndexerError> {
+        let mut last_changes = BTreeMap::<String, (i64, Option<OwnedObject>)>::new();
+        for changes in objects_changes {
+            let mutated = changes.mutated_objects.iter().map(|o| {
+                let owned_object = OwnedObject::try_from_object(o);
+                (o.object_id.clone(), o.version, owned_object)
+            });
+            let deleted = changes
+                .deleted_objects
+                .iter()
+                .map(|o| (o.object_id.clone(), o.version, Ok(None)));
+            for (object_id, version, owned_object) in mutated.chain(deleted) {
+                let owned_object = owned_object?;
+                match last_changes.get(&object_id) {
+                    Some((last_version, _)) if *last_version > version => {}
+                    _ => {
+                        last_changes.insert(object_id, (version, owned_object));
+                    }
+                }
+            }
+        }
+        let mut owned_objects = vec![];
+        let mut unowned_object_ids = vec![];
+        for (object_id, (_, owned_object)) in last_changes {
+            match owned_object {
+                Some(owned_object) => owned_objects.push(owned_object),
+                None => unowned_object_ids.push(object_id),
+            }
+        }
+        Ok((owned_objects, unowned_object_ids))
+    }
+
     fn index_packages(
         transactions: &[CheckpointTransactionBlockResponse],
         changed_objects: &[(ObjectStatus, SuiObjectData)],
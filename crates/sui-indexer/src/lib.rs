@@ -5,6 +5,7 @@
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use backoff::retry;
@@ -23,6 +24,7 @@ use apis::{
     WriteApi,
 };
 use errors::IndexerError;
+use handlers::balance_checker::BalanceChecker;
 use handlers::checkpoint_handler::CheckpointHandler;
 use mysten_metrics::spawn_monitored_task;
 use store::IndexerStore;
@@ -50,8 +52,12 @@ pub type PgPoolConnection = PooledConnection<ConnectionManager<PgConnection>>;
 /// Returns all endpoints for which we have implemented on the indexer,
 /// some of them are not validated yet.
 /// NOTE: we only use this for integration testing
-const IMPLEMENTED_METHODS: [&str; 7] = [
+const IMPLEMENTED_METHODS: [&str; 11] = [
+    "get_all_balances",
+    "get_all_coins",
+    "get_balance",
     "get_checkpoint",
+    "get_coins",
     "get_latest_checkpoint_sequence_number",
     "get_object_with_options",
     "get_total_transaction_number",
@@ -83,6 +89,12 @@ pub struct IndexerConfig {
     pub migrated_methods: Vec<String>,
     #[clap(long)]
     pub reset_db: bool,
+    /// Rebuilds the owned objects and balances from the objects before indexing.
+    #[clap(long)]
+    pub backfill_owned_objects: bool,
+    /// The interval between the consistency checks of the balances, disabled if unspecified.
+    #[clap(long)]
+    pub balance_check_interval_secs: Option<u64>,
 }
 
 impl IndexerConfig {
@@ -115,6 +127,8 @@ impl Default for IndexerConfig {
             rpc_server_port: 9000,
             migrated_methods: vec![],
             reset_db: false,
+            backfill_owned_objects: false,
+            balance_check_interval_secs: None,
         }
     }
 }
@@ -127,6 +141,16 @@ impl Indexer {
         registry: &Registry,
         store: S,
     ) -> Result<(), IndexerError> {
+        if config.backfill_owned_objects {
+            info!("Backfilling owned objects and balances...");
+            let owned_object_count = store.backfill_owned_objects()?;
+            info!("Backfilled {owned_object_count} owned objects and their balances");
+        }
+        if let Some(interval_secs) = config.balance_check_interval_secs {
+            BalanceChecker::new(store.clone(), Duration::from_secs(interval_secs), registry)
+                .spawn();
+        }
+
         let event_handler = Arc::new(EventHandler::default());
         let handle = build_json_rpc_server(registry, store.clone(), event_handler.clone(), config)
             .await
@@ -223,7 +247,11 @@ pub async fn build_json_rpc_server<S: IndexerStore + Sync + Send + 'static + Clo
         http_client.clone(),
         config.migrated_methods.clone(),
     ))?;
-    builder.register_module(CoinReadApi::new(http_client.clone()))?;
+    builder.register_module(CoinReadApi::new(
+        state.clone(),
+        http_client.clone(),
+        config.migrated_methods.clone(),
+    ))?;
     builder.register_module(TransactionBuilderApi::new(http_client.clone()))?;
     builder.register_module(GovernanceReadApi::new(http_client.clone()))?;
     builder.register_module(IndexerApi::new(
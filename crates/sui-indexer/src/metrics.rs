@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use prometheus::{
-    register_histogram_with_registry, register_int_counter_with_registry, Histogram, IntCounter,
-    Registry,
+    register_histogram_with_registry, register_int_counter_with_registry,
+    register_int_gauge_with_registry, Histogram, IntCounter, IntGauge, Registry,
 };
 
 /// Prometheus metrics for sui-indexer.
@@ -125,3 +125,35 @@ impl IndexerObjectProcessorMetrics {
         }
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct IndexerBalanceCheckerMetrics {
+    pub total_balance_check: IntCounter,
+    pub inconsistent_balances: IntGauge,
+    pub inconsistent_owned_objects: IntGauge,
+}
+
+impl IndexerBalanceCheckerMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            total_balance_check: register_int_counter_with_registry!(
+                "total_balance_check",
+                "Total number of consistency checks of the balances and owned objects",
+                registry,
+            )
+            .unwrap(),
+            inconsistent_balances: register_int_gauge_with_registry!(
+                "inconsistent_balances",
+                "Number of balances not matching the owned coins found by the last check",
+                registry,
+            )
+            .unwrap(),
+            inconsistent_owned_objects: register_int_gauge_with_registry!(
+                "inconsistent_owned_objects",
+                "Number of owned objects not matching the objects found by the last check",
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
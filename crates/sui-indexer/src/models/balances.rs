@@ -0,0 +1,196 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, VarChar};
+
+use sui_json_rpc_types::Balance;
+
+use crate::models::owned_objects::OwnedObject;
+use crate::schema::balances;
+
+/// Returns the balances which do not match the coins owned by the addresses.
+pub const BALANCE_INCONSISTENCIES_SQL: &str = r#"
+SELECT COALESCE(b.owner_address, c.owner_address) AS owner_address,
+       COALESCE(b.coin_type, c.coin_type)         AS coin_type,
+       b.balance                                  AS indexed_balance,
+       b.coin_object_count                        AS indexed_coin_object_count,
+       c.balance                                  AS expected_balance,
+       c.coin_object_count                        AS expected_coin_object_count
+FROM balances b
+         FULL OUTER JOIN (SELECT owner_address,
+                                 coin_type,
+                                 SUM(coin_balance)::BIGINT AS balance,
+                                 COUNT(*)                  AS coin_object_count
+                          FROM owned_objects
+                          WHERE coin_type IS NOT NULL
+                          GROUP BY owner_address, coin_type) c
+                         ON b.owner_address = c.owner_address AND b.coin_type = c.coin_type
+WHERE b.balance IS DISTINCT FROM c.balance
+   OR b.coin_object_count IS DISTINCT FROM c.coin_object_count
+LIMIT $1;"#;
+
+/// Returns the ids of the owned objects which do not match the live objects owned by addresses.
+pub const OWNED_OBJECT_INCONSISTENCIES_SQL: &str = r#"
+SELECT COALESCE(o.object_id, w.object_id) AS object_id
+FROM (SELECT object_id, version, owner_address
+      FROM objects
+      WHERE owner_type = 'address_owner'
+        AND object_status NOT IN ('deleted', 'wrapped', 'unwrapped_then_deleted')) o
+         FULL OUTER JOIN owned_objects w ON o.object_id = w.object_id
+WHERE o.version IS DISTINCT FROM w.version
+   OR o.owner_address IS DISTINCT FROM w.owner_address
+LIMIT $1;"#;
+
+/// Rebuilds the balances from the coins owned by the addresses.
+pub const REBUILD_BALANCES_SQL: &str = r#"
+INSERT INTO balances (owner_address, coin_type, balance, coin_object_count, checkpoint)
+SELECT owner_address, coin_type, SUM(coin_balance)::BIGINT, COUNT(*), MAX(checkpoint)
+FROM owned_objects
+WHERE coin_type IS NOT NULL
+GROUP BY owner_address, coin_type;"#;
+
+#[derive(Queryable, Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = balances, primary_key(owner_address, coin_type))]
+pub struct DBBalance {
+    pub owner_address: String,
+    pub coin_type: String,
+    pub balance: i64,
+    pub coin_object_count: i64,
+    // the last checkpoint which changed the balance.
+    pub checkpoint: i64,
+}
+
+impl From<DBBalance> for Balance {
+    fn from(b: DBBalance) -> Self {
+        Balance {
+            coin_type: b.coin_type,
+            coin_object_count: b.coin_object_count as usize,
+            total_balance: b.balance as u128,
+            locked_balance: Default::default(),
+        }
+    }
+}
+
+/// Returns the changes to the balances of a checkpoint, to be added to the balances, from the
+/// previous state of the objects it changed and their state after it.
+pub fn balance_changes(
+    previous: &[OwnedObject],
+    current: &[OwnedObject],
+    checkpoint: i64,
+) -> Vec<DBBalance> {
+    let mut changes = BTreeMap::<(String, String), (i64, i64)>::new();
+    let coins = |objects: &[OwnedObject], sign: i64| {
+        objects
+            .iter()
+            .filter_map(move |o| match (&o.coin_type, o.coin_balance) {
+                (Some(coin_type), Some(balance)) => Some((
+                    (o.owner_address.clone(), coin_type.clone()),
+                    sign * balance,
+                    sign,
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    for (key, balance, coin_object_count) in
+        coins(previous, -1).into_iter().chain(coins(current, 1))
+    {
+        let change = changes.entry(key).or_default();
+        change.0 += balance;
+        change.1 += coin_object_count;
+    }
+    changes
+        .into_iter()
+        .filter(|(_, change)| *change != (0, 0))
+        .map(
+            |((owner_address, coin_type), (balance, coin_object_count))| DBBalance {
+                owner_address,
+                coin_type,
+                balance,
+                coin_object_count,
+                checkpoint,
+            },
+        )
+        .collect()
+}
+
+#[derive(QueryableByName, Debug, Clone)]
+pub struct BalanceInconsistency {
+    #[diesel(sql_type = VarChar)]
+    pub owner_address: String,
+    #[diesel(sql_type = VarChar)]
+    pub coin_type: String,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub indexed_balance: Option<i64>,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub indexed_coin_object_count: Option<i64>,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub expected_balance: Option<i64>,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub expected_coin_object_count: Option<i64>,
+}
+
+#[derive(QueryableByName, Debug, Clone)]
+pub struct OwnedObjectInconsistency {
+    #[diesel(sql_type = VarChar)]
+    pub object_id: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn coin(object_id: &str, owner_address: &str, balance: i64) -> OwnedObject {
+        OwnedObject {
+            object_id: object_id.to_string(),
+            version: 1,
+            object_digest: "digest".to_string(),
+            checkpoint: 1,
+            owner_address: owner_address.to_string(),
+            object_type: "0x2::coin::Coin<0x2::sui::SUI>".to_string(),
+            previous_transaction: "digest".to_string(),
+            coin_type: Some("0x2::sui::SUI".to_string()),
+            coin_balance: Some(balance),
+        }
+    }
+
+    #[test]
+    fn test_balance_changes() {
+        // The first coin is transferred from A to B, the second one is split.
+        let previous = vec![coin("0x1", "A", 100), coin("0x2", "B", 50)];
+        let current = vec![
+            coin("0x1", "B", 100),
+            coin("0x2", "B", 20),
+            coin("0x3", "B", 30),
+        ];
+        let changes = balance_changes(&previous, &current, 2);
+        assert_eq!(
+            changes,
+            vec![
+                DBBalance {
+                    owner_address: "A".to_string(),
+                    coin_type: "0x2::sui::SUI".to_string(),
+                    balance: -100,
+                    coin_object_count: -1,
+                    checkpoint: 2,
+                },
+                DBBalance {
+                    owner_address: "B".to_string(),
+                    coin_type: "0x2::sui::SUI".to_string(),
+                    balance: 100,
+                    coin_object_count: 2,
+                    checkpoint: 2,
+                },
+            ]
+        );
+
+        // The objects which are not coins do not change the balances.
+        let mut object = coin("0x4", "A", 0);
+        object.coin_type = None;
+        object.coin_balance = None;
+        assert!(balance_changes(&[], &[object], 2).is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text};
+
+use sui_json_rpc_types::{GasUsage, GasUsageGrouping};
+use sui_types::base_types::ObjectID;
+use sui_types::gas::GasCostSummary;
+
+use crate::errors::IndexerError;
+use crate::models::transaction_index::MoveCall;
+use crate::schema::move_call_gas_usage;
+
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Queryable, Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(
+    table_name = move_call_gas_usage,
+    primary_key(day, move_package, move_module, move_function)
+)]
+pub struct DBGasUsage {
+    pub day: i64,
+    pub move_package: String,
+    pub move_module: String,
+    pub move_function: String,
+    pub computation_cost: i64,
+    pub storage_cost: i64,
+    pub storage_rebate: i64,
+    pub call_count: i64,
+    // the last checkpoint which added to the usage.
+    pub checkpoint: i64,
+}
+
+/// The day of a timestamp, in days since the Unix epoch, by which the gas usage is aggregated.
+pub fn day_of(timestamp_ms: u64) -> i64 {
+    (timestamp_ms / MS_PER_DAY) as i64
+}
+
+/// Returns the gas used by the Move functions called in a checkpoint, to be added to their usage
+/// on the day of the checkpoint. The gas of a transaction is split evenly between its Move calls,
+/// the remainder going to the first one, so that the usage adds up to the gas of the transactions.
+pub fn gas_usage_changes<'a>(
+    transactions: impl IntoIterator<Item = (&'a [MoveCall], GasCostSummary)>,
+    timestamp_ms: u64,
+    checkpoint: i64,
+) -> Vec<DBGasUsage> {
+    let mut changes = BTreeMap::<(String, String, String), (i64, i64, i64, i64)>::new();
+    for (move_calls, gas) in transactions {
+        let call_count = move_calls.len() as u64;
+        if call_count == 0 {
+            continue;
+        }
+        let share = |cost: u64, index: usize| {
+            let remainder = if index == 0 { cost % call_count } else { 0 };
+            (cost / call_count + remainder) as i64
+        };
+        for (index, call) in move_calls.iter().enumerate() {
+            let key = (
+                call.move_package.clone(),
+                call.move_module.clone(),
+                call.move_function.clone(),
+            );
+            let change = changes.entry(key).or_default();
+            change.0 += share(gas.computation_cost, index);
+            change.1 += share(gas.storage_cost, index);
+            change.2 += share(gas.storage_rebate, index);
+            change.3 += 1;
+        }
+    }
+    let day = day_of(timestamp_ms);
+    changes
+        .into_iter()
+        .map(
+            |(
+                (move_package, move_module, move_function),
+                (computation_cost, storage_cost, storage_rebate, call_count),
+            )| DBGasUsage {
+                day,
+                move_package,
+                move_module,
+                move_function,
+                computation_cost,
+                storage_cost,
+                storage_rebate,
+                call_count,
+                checkpoint,
+            },
+        )
+        .collect()
+}
+
+/// Returns the query of the gas usage between two days included, the most computation-intensive
+/// first, aggregated at the level given.
+pub fn gas_usage_metrics_query(grouping: GasUsageGrouping) -> String {
+    let (move_module, move_function, group_by) = match grouping {
+        GasUsageGrouping::Package => ("NULL", "NULL", "move_package"),
+        GasUsageGrouping::Module => ("move_module", "NULL", "move_package, move_module"),
+        GasUsageGrouping::Function => (
+            "move_module",
+            "move_function",
+            "move_package, move_module, move_function",
+        ),
+    };
+    format!(
+        r#"
+SELECT move_package,
+       {move_module}::TEXT             AS move_module,
+       {move_function}::TEXT           AS move_function,
+       SUM(computation_cost)::BIGINT AS computation_cost,
+       SUM(storage_cost)::BIGINT     AS storage_cost,
+       SUM(storage_rebate)::BIGINT   AS storage_rebate,
+       SUM(call_count)::BIGINT       AS call_count
+FROM move_call_gas_usage
+WHERE day >= $1
+  AND day <= $2
+GROUP BY {group_by}
+ORDER BY computation_cost DESC, {group_by}
+LIMIT $3;"#
+    )
+}
+
+#[derive(QueryableByName, Debug, Clone)]
+pub struct DBGasUsageMetric {
+    #[diesel(sql_type = Text)]
+    pub move_package: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub move_module: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub move_function: Option<String>,
+    #[diesel(sql_type = BigInt)]
+    pub computation_cost: i64,
+    #[diesel(sql_type = BigInt)]
+    pub storage_cost: i64,
+    #[diesel(sql_type = BigInt)]
+    pub storage_rebate: i64,
+    #[diesel(sql_type = BigInt)]
+    pub call_count: i64,
+}
+
+impl TryFrom<DBGasUsageMetric> for GasUsage {
+    type Error = IndexerError;
+
+    fn try_from(m: DBGasUsageMetric) -> Result<Self, Self::Error> {
+        Ok(GasUsage {
+            package: m.move_package.parse::<ObjectID>()?,
+            module: m.move_module,
+            function: m.move_function,
+            computation_cost: (m.computation_cost as u64).into(),
+            storage_cost: (m.storage_cost as u64).into(),
+            storage_rebate: (m.storage_rebate as u64).into(),
+            call_count: m.call_count as usize,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn move_call(function: &str) -> MoveCall {
+        MoveCall {
+            move_package: "0x2".to_string(),
+            move_module: "coin".to_string(),
+            move_function: function.to_string(),
+            ..MoveCall::default()
+        }
+    }
+
+    #[test]
+    fn test_gas_usage_changes() {
+        let gas = GasCostSummary {
+            computation_cost: 1000,
+            storage_cost: 301,
+            storage_rebate: 100,
+            non_refundable_storage_fee: 0,
+        };
+        let split = vec![move_call("split"), move_call("join")];
+        let transfer = vec![move_call("split")];
+        let transactions = [
+            (split.as_slice(), gas.clone()),
+            (transfer.as_slice(), gas.clone()),
+            // Transactions without Move calls are not counted.
+            (&[] as &[MoveCall], gas),
+        ];
+        let changes = gas_usage_changes(transactions, 3 * MS_PER_DAY + 1, 7);
+        assert_eq!(
+            changes,
+            vec![
+                DBGasUsage {
+                    day: 3,
+                    move_package: "0x2".to_string(),
+                    move_module: "coin".to_string(),
+                    move_function: "join".to_string(),
+                    computation_cost: 500,
+                    storage_cost: 150,
+                    storage_rebate: 50,
+                    call_count: 1,
+                    checkpoint: 7,
+                },
+                DBGasUsage {
+                    day: 3,
+                    move_package: "0x2".to_string(),
+                    move_module: "coin".to_string(),
+                    move_function: "split".to_string(),
+                    computation_cost: 1500,
+                    storage_cost: 452,
+                    storage_rebate: 150,
+                    call_count: 2,
+                    checkpoint: 7,
+                },
+            ]
+        );
+    }
+}
@@ -2,11 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod addresses;
+pub mod balances;
 pub mod checkpoints;
 pub mod epoch;
 pub mod events;
+pub mod gas_usage;
 pub mod network_metrics;
 pub mod objects;
+pub mod owned_objects;
 pub mod owners;
 pub mod packages;
 pub mod system_state;
@@ -0,0 +1,101 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use diesel::prelude::*;
+
+use sui_json_rpc_types::Coin;
+use sui_types::base_types::{ObjectID, SequenceNumber};
+use sui_types::coin::Coin as MoveCoin;
+use sui_types::digests::{ObjectDigest, TransactionDigest};
+use sui_types::object::Owner;
+
+use crate::errors::IndexerError;
+use crate::models::objects::{Object, ObjectStatus};
+use crate::schema::owned_objects;
+
+// NOTE: please add updating statement like below in pg_indexer_store.rs,
+// if new columns are added here:
+// owned_objects::version.eq(excluded(owned_objects::version))
+#[derive(Queryable, Insertable, Debug, Identifiable, Clone, PartialEq, Eq)]
+#[diesel(table_name = owned_objects, primary_key(object_id))]
+pub struct OwnedObject {
+    pub object_id: String,
+    pub version: i64,
+    pub object_digest: String,
+    // checkpoint seq number in which this object got update.
+    pub checkpoint: i64,
+    pub owner_address: String,
+    pub object_type: String,
+    pub previous_transaction: String,
+    pub coin_type: Option<String>,
+    pub coin_balance: Option<i64>,
+}
+
+impl OwnedObject {
+    /// Returns the owned object of the live object, or None if the object is not owned by an
+    /// address, e.g. deleted, wrapped or shared.
+    pub fn try_from_object(o: &Object) -> Result<Option<Self>, IndexerError> {
+        if matches!(
+            o.object_status,
+            ObjectStatus::Deleted | ObjectStatus::Wrapped | ObjectStatus::UnwrappedThenDeleted
+        ) {
+            return Ok(None);
+        }
+        let object = sui_types::object::Object::try_from(o.clone())?;
+        let owner_address = match object.owner {
+            Owner::AddressOwner(address) => address.to_string(),
+            _ => return Ok(None),
+        };
+        let (coin_type, coin_balance) = match object.data.try_as_move() {
+            Some(move_object) if move_object.type_().is_coin() => {
+                let coin = MoveCoin::from_bcs_bytes(move_object.contents())?;
+                let coin_type = move_object
+                    .type_()
+                    .type_params()
+                    .first()
+                    .map(|coin_type| coin_type.to_string());
+                (coin_type, Some(coin.value() as i64))
+            }
+            _ => (None, None),
+        };
+        Ok(Some(Self {
+            object_id: o.object_id.clone(),
+            version: o.version,
+            object_digest: o.object_digest.clone(),
+            checkpoint: o.checkpoint,
+            owner_address,
+            object_type: o.object_type.clone(),
+            previous_transaction: o.previous_transaction.clone(),
+            coin_type,
+            coin_balance,
+        }))
+    }
+}
+
+impl TryFrom<OwnedObject> for Coin {
+    type Error = IndexerError;
+
+    fn try_from(o: OwnedObject) -> Result<Self, Self::Error> {
+        let (Some(coin_type), Some(balance)) = (o.coin_type, o.coin_balance) else {
+            return Err(IndexerError::SerdeError(format!(
+                "Object {} is not a coin",
+                o.object_id
+            )));
+        };
+        let digest: ObjectDigest = o.object_digest.parse().map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to parse object digest: {}, error: {}",
+                o.object_digest, e
+            ))
+        })?;
+        Ok(Coin {
+            coin_type,
+            coin_object_id: o.object_id.parse::<ObjectID>()?,
+            version: SequenceNumber::from_u64(o.version as u64),
+            digest,
+            balance: balance as u64,
+            locked_until_epoch: None,
+            previous_transaction: o.previous_transaction.parse::<TransactionDigest>()?,
+        })
+    }
+}
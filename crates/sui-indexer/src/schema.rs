@@ -33,6 +33,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    balances (owner_address, coin_type) {
+        owner_address -> Varchar,
+        coin_type -> Varchar,
+        balance -> Int8,
+        coin_object_count -> Int8,
+        checkpoint -> Int8,
+    }
+}
+
 diesel::table! {
     checkpoints (sequence_number) {
         sequence_number -> Int8,
@@ -104,6 +114,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    move_call_gas_usage (day, move_package, move_module, move_function) {
+        day -> Int8,
+        move_package -> Text,
+        move_module -> Text,
+        move_function -> Text,
+        computation_cost -> Int8,
+        storage_cost -> Int8,
+        storage_rebate -> Int8,
+        call_count -> Int8,
+        checkpoint -> Int8,
+    }
+}
+
 diesel::table! {
     move_calls (id) {
         id -> Int8,
@@ -167,6 +191,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    owned_objects (object_id) {
+        object_id -> Varchar,
+        version -> Int8,
+        object_digest -> Varchar,
+        checkpoint -> Int8,
+        owner_address -> Varchar,
+        object_type -> Varchar,
+        previous_transaction -> Varchar,
+        coin_type -> Nullable<Varchar>,
+        coin_balance -> Nullable<Int8>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::BcsBytes;
@@ -298,13 +336,16 @@ diesel::table! {
 diesel::allow_tables_to_appear_in_same_query!(
     addresses,
     at_risk_validators,
+    balances,
     checkpoints,
     epochs,
     events,
     input_objects,
+    move_call_gas_usage,
     move_calls,
     objects,
     objects_history,
+    owned_objects,
     packages,
     recipients,
     system_states,
@@ -4,11 +4,11 @@
 use async_trait::async_trait;
 
 use sui_json_rpc_types::{
-    Checkpoint as RpcCheckpoint, CheckpointId, EpochInfo, EventFilter, EventPage, MoveCallMetrics,
-    NetworkMetrics, SuiObjectData, SuiObjectDataFilter, SuiTransactionBlockResponse,
-    SuiTransactionBlockResponseOptions,
+    Balance, Checkpoint as RpcCheckpoint, CheckpointId, Coin, EpochInfo, EventFilter, EventPage,
+    GasUsage, GasUsageGrouping, MoveCallMetrics, NetworkMetrics, SuiObjectData,
+    SuiObjectDataFilter, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
 };
-use sui_types::base_types::{EpochId, ObjectID, SequenceNumber};
+use sui_types::base_types::{EpochId, ObjectID, SequenceNumber, SuiAddress};
 use sui_types::digests::CheckpointDigest;
 use sui_types::error::SuiError;
 use sui_types::event::EventID;
@@ -18,10 +18,13 @@ use sui_types::storage::ObjectStore;
 
 use crate::errors::IndexerError;
 use crate::models::addresses::Address;
+use crate::models::balances::BalanceInconsistency;
 use crate::models::checkpoints::Checkpoint;
 use crate::models::epoch::DBEpochInfo;
 use crate::models::events::Event;
+use crate::models::gas_usage::DBGasUsage;
 use crate::models::objects::{DeletedObject, Object, ObjectStatus};
+use crate::models::owned_objects::OwnedObject;
 use crate::models::packages::Package;
 use crate::models::system_state::{DBSystemStateSummary, DBValidatorSummary};
 use crate::models::transaction_index::{InputObject, MoveCall, Recipient};
@@ -62,6 +65,28 @@ pub trait IndexerStore {
         limit: usize,
     ) -> Result<Vec<ObjectRead>, IndexerError>;
 
+    fn get_balance(&self, owner: SuiAddress, coin_type: String) -> Result<Balance, IndexerError>;
+    fn get_all_balances(&self, owner: SuiAddress) -> Result<Vec<Balance>, IndexerError>;
+
+    /// Returns the coins owned by the address ordered by object id, of the coin type if any.
+    fn get_coins(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<Coin>, IndexerError>;
+
+    /// Rebuilds the owned objects and the balances from the objects, e.g. when the tables were
+    /// added to an existing database or found inconsistent. Returns the number of owned objects.
+    fn backfill_owned_objects(&self) -> Result<usize, IndexerError>;
+
+    fn get_balance_inconsistencies(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<BalanceInconsistency>, IndexerError>;
+    fn get_owned_object_inconsistencies(&self, limit: usize) -> Result<Vec<String>, IndexerError>;
+
     fn get_total_transaction_number_from_checkpoints(&self) -> Result<i64, IndexerError>;
 
     // TODO: combine all get_transaction* methods
@@ -170,6 +195,15 @@ pub trait IndexerStore {
 
     fn get_network_metrics(&self) -> Result<NetworkMetrics, IndexerError>;
     fn get_move_call_metrics(&self) -> Result<MoveCallMetrics, IndexerError>;
+    /// Returns the gas used by Move calls from the start day to the end day included, the most
+    /// computation-intensive first.
+    fn get_gas_usage_metrics(
+        &self,
+        grouping: GasUsageGrouping,
+        start_day: i64,
+        end_day: i64,
+        limit: usize,
+    ) -> Result<Vec<GasUsage>, IndexerError>;
 
     fn persist_fast_path(&self, tx: Transaction) -> Result<usize, IndexerError>;
     fn persist_checkpoint(&self, data: &TemporaryCheckpointStore) -> Result<usize, IndexerError>;
@@ -217,10 +251,16 @@ pub struct TemporaryCheckpointStore {
     pub transactions: Vec<Transaction>,
     pub events: Vec<Event>,
     pub objects_changes: Vec<TransactionObjectChanges>,
+    // the objects owned by addresses after the checkpoint, among the ones it changed.
+    pub owned_objects: Vec<OwnedObject>,
+    // the ids of the objects the checkpoint changed which are no longer owned by an address.
+    pub unowned_object_ids: Vec<String>,
     pub addresses: Vec<Address>,
     pub packages: Vec<Package>,
     pub input_objects: Vec<InputObject>,
     pub move_calls: Vec<MoveCall>,
+    // the gas used by the move functions called in the checkpoint.
+    pub gas_usages: Vec<DBGasUsage>,
     pub recipients: Vec<Recipient>,
 }
 
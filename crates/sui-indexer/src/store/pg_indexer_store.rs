@@ -22,8 +22,8 @@ use tracing::info;
 
 use sui_json_rpc::{ObjectProvider, ObjectProviderCache};
 use sui_json_rpc_types::{
-    CheckpointId, EpochInfo, EventFilter, EventPage, MoveCallMetrics, MoveFunctionName,
-    NetworkMetrics, SuiEvent, SuiObjectDataFilter,
+    Balance, CheckpointId, Coin, EpochInfo, EventFilter, EventPage, GasUsage, GasUsageGrouping,
+    MoveCallMetrics, MoveFunctionName, NetworkMetrics, SuiEvent, SuiObjectDataFilter,
 };
 use sui_json_rpc_types::{
     SuiTransactionBlock, SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI,
@@ -41,20 +41,28 @@ use sui_types::messages_checkpoint::{
 use sui_types::object::ObjectRead;
 
 use crate::errors::{Context, IndexerError};
+use crate::models::balances::{
+    balance_changes, BalanceInconsistency, DBBalance, OwnedObjectInconsistency,
+    BALANCE_INCONSISTENCIES_SQL, OWNED_OBJECT_INCONSISTENCIES_SQL, REBUILD_BALANCES_SQL,
+};
 use crate::models::checkpoints::Checkpoint;
 use crate::models::epoch::DBEpochInfo;
 use crate::models::events::Event;
+use crate::models::gas_usage::{gas_usage_metrics_query, DBGasUsageMetric};
 use crate::models::network_metrics::{DBMoveCallMetrics, DBNetworkMetrics};
 use crate::models::objects::{
-    compose_object_bulk_insert_update_query, group_and_sort_objects, Object,
+    compose_object_bulk_insert_update_query, group_and_sort_objects, Object, ObjectStatus,
 };
+use crate::models::owned_objects::OwnedObject;
+use crate::models::owners::OwnerType;
 use crate::models::system_state::DBValidatorSummary;
 use crate::models::transactions::Transaction;
 use crate::schema::{
-    addresses, checkpoints, checkpoints::dsl as checkpoints_dsl, epochs, epochs::dsl as epochs_dsl,
-    events, input_objects, input_objects::dsl as input_objects_dsl, move_calls,
-    move_calls::dsl as move_calls_dsl, objects, objects::dsl as objects_dsl, objects_history,
-    packages, recipients, recipients::dsl as recipients_dsl, system_states, transactions,
+    addresses, balances, checkpoints, checkpoints::dsl as checkpoints_dsl, epochs,
+    epochs::dsl as epochs_dsl, events, input_objects, input_objects::dsl as input_objects_dsl,
+    move_call_gas_usage, move_calls, move_calls::dsl as move_calls_dsl, objects,
+    objects::dsl as objects_dsl, objects_history, owned_objects, packages, recipients,
+    recipients::dsl as recipients_dsl, system_states, transactions,
     transactions::dsl as transactions_dsl, validators,
 };
 use crate::store::diesel_marco::{read_only, transactional};
@@ -483,6 +491,7 @@ impl IndexerStore for PgIndexerStore {
             confirmed_local_execution: tx.confirmed_local_execution,
             timestamp_ms: tx.timestamp_ms.map(|t| t as u64),
             checkpoint: tx.checkpoint_sequence_number.map(|c| c as u64),
+            checkpoint_watermark: None,
             events,
             object_changes,
             balance_changes,
@@ -609,6 +618,130 @@ impl IndexerStore for PgIndexerStore {
             .collect()
     }
 
+    fn get_balance(&self, owner: SuiAddress, coin_type: String) -> Result<Balance, IndexerError> {
+        let balance = read_only!(&self.cp, |conn| {
+            balances::table
+                .filter(balances::owner_address.eq(owner.to_string()))
+                .filter(balances::coin_type.eq(coin_type.clone()))
+                .first::<DBBalance>(conn)
+                .optional()
+        })
+        .context(&format!(
+            "Failed reading balance of {owner} with coin type {coin_type}"
+        ))?;
+        Ok(balance.map(Balance::from).unwrap_or_else(|| Balance {
+            coin_type,
+            coin_object_count: 0,
+            total_balance: 0,
+            locked_balance: Default::default(),
+        }))
+    }
+
+    fn get_all_balances(&self, owner: SuiAddress) -> Result<Vec<Balance>, IndexerError> {
+        let balances = read_only!(&self.cp, |conn| {
+            balances::table
+                .filter(balances::owner_address.eq(owner.to_string()))
+                .order(balances::coin_type.asc())
+                .load::<DBBalance>(conn)
+        })
+        .context(&format!("Failed reading balances of {owner}"))?;
+        Ok(balances.into_iter().map(Balance::from).collect())
+    }
+
+    fn get_coins(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<Coin>, IndexerError> {
+        let coins = read_only!(&self.cp, |conn| {
+            let mut boxed_query = owned_objects::table
+                .filter(owned_objects::owner_address.eq(owner.to_string()))
+                .into_boxed();
+            if let Some(coin_type) = &coin_type {
+                boxed_query = boxed_query.filter(owned_objects::coin_type.eq(coin_type.clone()));
+            } else {
+                boxed_query = boxed_query.filter(owned_objects::coin_type.is_not_null());
+            }
+            if let Some(cursor) = cursor {
+                boxed_query = boxed_query.filter(owned_objects::object_id.gt(cursor.to_string()));
+            }
+            boxed_query
+                .order(owned_objects::object_id.asc())
+                .limit(limit as i64)
+                .load::<OwnedObject>(conn)
+        })
+        .context(&format!(
+            "Failed reading coins of {owner} with coin type {coin_type:?} and cursor {cursor:?}"
+        ))?;
+        coins.into_iter().map(Coin::try_from).collect()
+    }
+
+    fn backfill_owned_objects(&self) -> Result<usize, IndexerError> {
+        transactional!(&self.cp, |conn| {
+            diesel::delete(owned_objects::table).execute(conn)?;
+            diesel::delete(balances::table).execute(conn)?;
+
+            let mut owned_object_count = 0;
+            let mut cursor = String::new();
+            loop {
+                let objects = objects::table
+                    .filter(objects::owner_type.eq(OwnerType::AddressOwner))
+                    .filter(objects::object_status.ne_all(vec![
+                        ObjectStatus::Deleted,
+                        ObjectStatus::Wrapped,
+                        ObjectStatus::UnwrappedThenDeleted,
+                    ]))
+                    .filter(objects::object_id.gt(cursor.clone()))
+                    .order(objects::object_id.asc())
+                    .limit(PG_COMMIT_CHUNK_SIZE as i64)
+                    .load::<Object>(conn)?;
+                let Some(last_object) = objects.last() else {
+                    break;
+                };
+                cursor = last_object.object_id.clone();
+
+                let owned_objects = objects
+                    .iter()
+                    .filter_map(|o| OwnedObject::try_from_object(o).transpose())
+                    .collect::<Result<Vec<_>, _>>()?;
+                if owned_objects.is_empty() {
+                    continue;
+                }
+                owned_object_count += diesel::insert_into(owned_objects::table)
+                    .values(&owned_objects)
+                    .execute(conn)?;
+            }
+
+            diesel::sql_query(REBUILD_BALANCES_SQL).execute(conn)?;
+            Ok::<_, IndexerError>(owned_object_count)
+        })
+        .context("Failed backfilling owned objects and balances")
+    }
+
+    fn get_balance_inconsistencies(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<BalanceInconsistency>, IndexerError> {
+        read_only!(&self.cp, |conn| {
+            diesel::sql_query(BALANCE_INCONSISTENCIES_SQL)
+                .bind::<BigInt, _>(limit as i64)
+                .load::<BalanceInconsistency>(conn)
+        })
+        .context("Failed checking the consistency of the balances")
+    }
+
+    fn get_owned_object_inconsistencies(&self, limit: usize) -> Result<Vec<String>, IndexerError> {
+        let inconsistencies = read_only!(&self.cp, |conn| {
+            diesel::sql_query(OWNED_OBJECT_INCONSISTENCIES_SQL)
+                .bind::<BigInt, _>(limit as i64)
+                .load::<OwnedObjectInconsistency>(conn)
+        })
+        .context("Failed checking the consistency of the owned objects")?;
+        Ok(inconsistencies.into_iter().map(|i| i.object_id).collect())
+    }
+
     fn get_move_call_sequence_by_digest(
         &self,
         tx_digest: Option<String>,
@@ -1023,6 +1156,26 @@ impl IndexerStore for PgIndexerStore {
         })
     }
 
+    fn get_gas_usage_metrics(
+        &self,
+        grouping: GasUsageGrouping,
+        start_day: i64,
+        end_day: i64,
+        limit: usize,
+    ) -> Result<Vec<GasUsage>, IndexerError> {
+        let metrics = read_only!(&self.cp, |conn| {
+            diesel::sql_query(gas_usage_metrics_query(grouping))
+                .bind::<BigInt, _>(start_day)
+                .bind::<BigInt, _>(end_day)
+                .bind::<BigInt, _>(limit as i64)
+                .load::<DBGasUsageMetric>(conn)
+        })
+        .context(&format!(
+            "Failed reading gas usage by {grouping:?} from day {start_day} to day {end_day}"
+        ))?;
+        metrics.into_iter().map(GasUsage::try_from).collect()
+    }
+
     fn persist_fast_path(&self, tx: Transaction) -> Result<usize, IndexerError> {
         transactional!(&self.cp, |conn| {
             diesel::insert_into(transactions::table)
@@ -1040,10 +1193,13 @@ impl IndexerStore for PgIndexerStore {
             transactions,
             events,
             objects_changes,
+            owned_objects,
+            unowned_object_ids,
             addresses,
             packages,
             input_objects,
             move_calls,
+            gas_usages,
             recipients,
         } = data;
 
@@ -1125,6 +1281,81 @@ impl IndexerStore for PgIndexerStore {
                     ))?;
             }
 
+            // Commit indexed owned objects and the changes to the balances of their owners,
+            // the previous state of the changed objects telling what to subtract.
+            let changed_object_ids = owned_objects
+                .iter()
+                .map(|o| o.object_id.clone())
+                .chain(unowned_object_ids.iter().cloned())
+                .collect::<Vec<_>>();
+            let mut previous_owned_objects = vec![];
+            for object_ids_chunk in changed_object_ids.chunks(PG_COMMIT_CHUNK_SIZE) {
+                previous_owned_objects.extend(
+                    owned_objects::table
+                        .filter(owned_objects::object_id.eq_any(object_ids_chunk))
+                        .load::<OwnedObject>(conn)
+                        .map_err(IndexerError::from)
+                        .context("Failed reading owned objects from PostgresDB")?,
+                );
+            }
+            for object_ids_chunk in unowned_object_ids.chunks(PG_COMMIT_CHUNK_SIZE) {
+                diesel::delete(
+                    owned_objects::table.filter(owned_objects::object_id.eq_any(object_ids_chunk)),
+                )
+                .execute(conn)
+                .map_err(IndexerError::from)
+                .context("Failed deleting owned objects from PostgresDB")?;
+            }
+            for owned_objects_chunk in owned_objects.chunks(PG_COMMIT_CHUNK_SIZE) {
+                diesel::insert_into(owned_objects::table)
+                    .values(owned_objects_chunk)
+                    .on_conflict(owned_objects::object_id)
+                    .do_update()
+                    .set((
+                        owned_objects::version.eq(excluded(owned_objects::version)),
+                        owned_objects::object_digest.eq(excluded(owned_objects::object_digest)),
+                        owned_objects::checkpoint.eq(excluded(owned_objects::checkpoint)),
+                        owned_objects::owner_address.eq(excluded(owned_objects::owner_address)),
+                        owned_objects::object_type.eq(excluded(owned_objects::object_type)),
+                        owned_objects::previous_transaction
+                            .eq(excluded(owned_objects::previous_transaction)),
+                        owned_objects::coin_type.eq(excluded(owned_objects::coin_type)),
+                        owned_objects::coin_balance.eq(excluded(owned_objects::coin_balance)),
+                    ))
+                    .execute(conn)
+                    .map_err(IndexerError::from)
+                    .context("Failed writing owned objects to PostgresDB")?;
+            }
+            let changed_balances = balance_changes(
+                &previous_owned_objects,
+                owned_objects,
+                checkpoint.sequence_number,
+            );
+            for balances_chunk in changed_balances.chunks(PG_COMMIT_CHUNK_SIZE) {
+                diesel::insert_into(balances::table)
+                    .values(balances_chunk)
+                    .on_conflict((balances::owner_address, balances::coin_type))
+                    .do_update()
+                    .set(
+                        (
+                            balances::balance.eq(balances::balance + excluded(balances::balance)),
+                            balances::coin_object_count
+                                .eq(balances::coin_object_count
+                                    + excluded(balances::coin_object_count)),
+                            balances::checkpoint.eq(excluded(balances::checkpoint)),
+                        ),
+                    )
+                    .execute(conn)
+                    .map_err(IndexerError::from)
+                    .context("Failed writing balances to PostgresDB")?;
+            }
+            if !changed_balances.is_empty() {
+                diesel::delete(balances::table.filter(balances::coin_object_count.eq(0)))
+                    .execute(conn)
+                    .map_err(IndexerError::from)
+                    .context("Failed deleting empty balances from PostgresDB")?;
+            }
+
             // Commit indexed addresses
             for addresses_chunk in addresses.chunks(PG_COMMIT_CHUNK_SIZE) {
                 diesel::insert_into(addresses::table)
@@ -1156,6 +1387,35 @@ impl IndexerStore for PgIndexerStore {
                     .context("Failed writing move_calls to PostgresDB")?;
             }
 
+            // Commit the gas used by move calls, added to the usage of the day
+            for gas_usages_chunk in gas_usages.chunks(PG_COMMIT_CHUNK_SIZE) {
+                diesel::insert_into(move_call_gas_usage::table)
+                    .values(gas_usages_chunk)
+                    .on_conflict((
+                        move_call_gas_usage::day,
+                        move_call_gas_usage::move_package,
+                        move_call_gas_usage::move_module,
+                        move_call_gas_usage::move_function,
+                    ))
+                    .do_update()
+                    .set((
+                        move_call_gas_usage::computation_cost
+                            .eq(move_call_gas_usage::computation_cost
+                                + excluded(move_call_gas_usage::computation_cost)),
+                        move_call_gas_usage::storage_cost.eq(move_call_gas_usage::storage_cost
+                            + excluded(move_call_gas_usage::storage_cost)),
+                        move_call_gas_usage::storage_rebate.eq(move_call_gas_usage::storage_rebate
+                            + excluded(move_call_gas_usage::storage_rebate)),
+                        move_call_gas_usage::call_count.eq(move_call_gas_usage::call_count
+                            + excluded(move_call_gas_usage::call_count)),
+                        move_call_gas_usage::checkpoint
+                            .eq(excluded(move_call_gas_usage::checkpoint)),
+                    ))
+                    .execute(conn)
+                    .map_err(IndexerError::from)
+                    .context("Failed writing gas usage to PostgresDB")?;
+            }
+
             // Commit indexed input objects
             for input_objects_chunk in input_objects.chunks(PG_COMMIT_CHUNK_SIZE) {
                 diesel::insert_into(input_objects::table)
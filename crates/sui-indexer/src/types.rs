@@ -41,6 +41,7 @@ impl TryFrom<SuiTransactionBlockResponse> for FastPathTransactionBlockResponse {
             timestamp_ms: _,
             confirmed_local_execution,
             checkpoint: _,
+            checkpoint_watermark: _,
             errors,
         } = response;
 
@@ -123,6 +124,7 @@ impl TryFrom<SuiTransactionBlockResponse> for CheckpointTransactionBlockResponse
             timestamp_ms,
             confirmed_local_execution,
             checkpoint,
+            checkpoint_watermark: _,
             errors,
         } = response;
 
@@ -384,6 +386,7 @@ impl From<SuiTransactionBlockResponseWithOptions> for SuiTransactionBlockRespons
             timestamp_ms: response.timestamp_ms,
             confirmed_local_execution: response.confirmed_local_execution,
             checkpoint: response.checkpoint,
+            checkpoint_watermark: None,
             errors: vec![],
         }
     }
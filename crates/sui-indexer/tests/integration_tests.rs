@@ -29,12 +29,14 @@ pub mod pg_integration_test {
     use sui_indexer::store::{IndexerStore, PgIndexerStore};
     use sui_indexer::test_utils::{start_test_indexer, SuiTransactionBlockResponseBuilder};
     use sui_indexer::{get_pg_pool_connection, new_pg_connection_pool, IndexerConfig};
+    use sui_json_rpc::api::CoinReadApiClient;
     use sui_json_rpc::api::ExtendedApiClient;
     use sui_json_rpc::api::IndexerApiClient;
     use sui_json_rpc::api::{ReadApiClient, TransactionBuilderClient, WriteApiClient};
     use sui_json_rpc_types::{
-        BigInt, CheckpointId, EventFilter, SuiMoveObject, SuiObjectData, SuiObjectDataFilter,
-        SuiObjectDataOptions, SuiObjectResponse, SuiObjectResponseQuery, SuiParsedMoveObject,
+        Balance, BigInt, CheckpointId, Coin, EventFilter, GasUsageGrouping, SuiMoveObject,
+        SuiObjectData, SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponse,
+        SuiObjectResponseQuery, SuiParsedMoveObject, SuiTransactionBlockEffectsAPI,
         SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
         SuiTransactionBlockResponseQuery, TransactionBlockBytes,
     };
@@ -442,6 +444,55 @@ pub mod pg_integration_test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_gas_usage_metrics() -> Result<(), anyhow::Error> {
+        let (mut test_cluster, indexer_rpc_client, store, _handle) = start_test_cluster(None).await;
+        wait_until_next_checkpoint(&store).await;
+        let (package_id, publish_digest) =
+            publish_nfts_package(&mut test_cluster.wallet, /* sender */ None).await;
+        wait_until_transaction_synced(&store, publish_digest.base58_encode().as_str()).await;
+        let (_, _, nft_digest) = create_devnet_nft(&mut test_cluster.wallet, package_id)
+            .await
+            .unwrap();
+        wait_until_transaction_synced_in_checkpoint(&store, nft_digest.base58_encode().as_str())
+            .await;
+        let nft_tx = indexer_rpc_client
+            .get_transaction_block(
+                nft_digest,
+                Some(SuiTransactionBlockResponseOptions::new().with_effects()),
+            )
+            .await?;
+        let gas_used = nft_tx.effects.unwrap().gas_cost_summary().clone();
+
+        let usages = indexer_rpc_client
+            .get_gas_usage_metrics(GasUsageGrouping::Function, None, None, None)
+            .await?;
+        let nft_usages: Vec<_> = usages
+            .into_iter()
+            .filter(|usage| usage.package == package_id)
+            .collect();
+        assert_eq!(nft_usages.len(), 1);
+        assert_eq!(nft_usages[0].module.as_deref(), Some("devnet_nft"));
+        assert_eq!(nft_usages[0].function.as_deref(), Some("mint"));
+        assert_eq!(nft_usages[0].computation_cost, gas_used.computation_cost);
+        assert_eq!(nft_usages[0].storage_cost, gas_used.storage_cost);
+        assert_eq!(nft_usages[0].call_count, 1);
+
+        // The usage of the package is the same, and is not found outside the time window.
+        let usages = indexer_rpc_client
+            .get_gas_usage_metrics(GasUsageGrouping::Package, None, None, None)
+            .await?;
+        let package_usage = usages.iter().find(|usage| usage.package == package_id);
+        assert_eq!(package_usage.unwrap().module, None);
+        assert_eq!(package_usage.unwrap().call_count, 1);
+        let usages = indexer_rpc_client
+            .get_gas_usage_metrics(GasUsageGrouping::Package, Some(0), Some(0), None)
+            .await?;
+        assert!(usages.is_empty());
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[timeout(60000)]
     async fn test_event_query_e2e() -> Result<(), anyhow::Error> {
@@ -917,6 +968,58 @@ pub mod pg_integration_test {
         Ok(())
     }
 
+    #[tokio::test]
+    #[timeout(60000)]
+    async fn test_balances_cross_check() -> Result<(), anyhow::Error> {
+        let (mut test_cluster, indexer_rpc_client, store, _handle) = start_test_cluster(None).await;
+        // Allow indexer to sync genesis
+        wait_until_next_checkpoint(&store).await;
+        let (tx_response, sender, recipient, _) =
+            execute_simple_transfer(&mut test_cluster, &indexer_rpc_client).await?;
+        wait_until_transaction_synced_in_checkpoint(
+            &store,
+            tx_response.digest.base58_encode().as_str(),
+        )
+        .await;
+        let fullnode_client = test_cluster.rpc_client();
+
+        for address in [sender, recipient] {
+            let summarize = |balances: Vec<Balance>| {
+                let mut balances = balances
+                    .into_iter()
+                    .map(|b| (b.coin_type, b.coin_object_count, b.total_balance))
+                    .collect::<Vec<_>>();
+                balances.sort();
+                balances
+            };
+            let balances_from_fullnode = fullnode_client.get_all_balances(address).await?;
+            let balances_from_indexer = indexer_rpc_client.get_all_balances(address).await?;
+            assert_eq!(
+                summarize(balances_from_fullnode),
+                summarize(balances_from_indexer)
+            );
+
+            let coins_from_fullnode = fullnode_client.get_coins(address, None, None, None).await?;
+            let coins_from_indexer = indexer_rpc_client
+                .get_coins(address, None, None, None)
+                .await?;
+            let summarize = |coins: Vec<Coin>| {
+                coins
+                    .into_iter()
+                    .map(|c| (c.coin_object_id, c.version, c.balance))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                summarize(coins_from_fullnode.data),
+                summarize(coins_from_indexer.data)
+            );
+        }
+
+        assert!(store.get_balance_inconsistencies(10)?.is_empty());
+        assert!(store.get_owned_object_inconsistencies(10)?.is_empty());
+        Ok(())
+    }
+
     #[tokio::test]
     #[timeout(60000)]
     async fn test_query_objects() -> Result<(), anyhow::Error> {
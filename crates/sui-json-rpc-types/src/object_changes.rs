@@ -6,9 +6,13 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use sui_types::base_types::{ObjectDigest, ObjectID, SequenceNumber, SuiAddress};
+use sui_types::digests::TransactionDigest;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 use sui_types::object::Owner;
 use sui_types::sui_serde::SuiStructTag;
 
+use crate::Filter;
+
 /// ObjectChange are derived from the object mutations in the TransactionEffect to provide richer object information.
 #[serde_as]
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
@@ -80,3 +84,71 @@ pub enum ObjectChange {
         digest: ObjectDigest,
     },
 }
+
+impl ObjectChange {
+    pub fn object_type(&self) -> Option<&StructTag> {
+        match self {
+            ObjectChange::Published { .. } => None,
+            ObjectChange::Transferred { object_type, .. }
+            | ObjectChange::Mutated { object_type, .. }
+            | ObjectChange::Deleted { object_type, .. }
+            | ObjectChange::Wrapped { object_type, .. }
+            | ObjectChange::Created { object_type, .. } => Some(object_type),
+        }
+    }
+}
+
+/// A change of an object, streamed to the subscribers of the object changes at checkpoint
+/// finality.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectChangeNotification {
+    /// The transaction which changed the object.
+    pub transaction_digest: TransactionDigest,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<CheckpointSequenceNumber>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_ms: Option<u64>,
+    pub change: ObjectChange,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub enum ObjectChangeFilter {
+    /// Changes of the objects owned by the address after the change, and of the objects it no
+    /// longer owns after a transaction it sent: the ones sent away, deleted or wrapped.
+    Owner(SuiAddress),
+    /// Changes of the objects of the given Move struct type.
+    ObjectType(
+        #[schemars(with = "String")]
+        #[serde_as(as = "SuiStructTag")]
+        StructTag,
+    ),
+    All(Vec<ObjectChangeFilter>),
+    Any(Vec<ObjectChangeFilter>),
+}
+
+impl Filter<ObjectChange> for ObjectChangeFilter {
+    fn matches(&self, item: &ObjectChange) -> bool {
+        match self {
+            ObjectChangeFilter::Owner(address) => match item {
+                ObjectChange::Published { .. } => false,
+                ObjectChange::Created { owner, .. } => owner == &Owner::AddressOwner(*address),
+                ObjectChange::Transferred {
+                    sender, recipient, ..
+                } => sender == address || recipient == &Owner::AddressOwner(*address),
+                // The owned objects of the sender which are mutated may have been sent away.
+                ObjectChange::Mutated { sender, owner, .. } => {
+                    owner == &Owner::AddressOwner(*address)
+                        || (sender == address && !owner.is_shared())
+                }
+                ObjectChange::Deleted { sender, .. } | ObjectChange::Wrapped { sender, .. } => {
+                    sender == address
+                }
+            },
+            ObjectChangeFilter::ObjectType(object_type) => item.object_type() == Some(object_type),
+            ObjectChangeFilter::All(filters) => filters.iter().all(|f| f.matches(item)),
+            ObjectChangeFilter::Any(filters) => filters.iter().any(|f| f.matches(item)),
+        }
+    }
+}
@@ -1,23 +1,31 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::anyhow;
+use fastcrypto::encoding::Base64;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sui_types::base_types::TransactionDigest;
-use sui_types::committee::EpochId;
+use serde_with::serde_as;
+use sui_types::base_types::{
+    ExecutionDigests, ObjectDigest, ObjectID, SequenceNumber, TransactionDigest,
+};
+use sui_types::committee::{Committee, EpochId};
+use sui_types::crypto::{AuthorityStrongQuorumSignInfo, SuiAuthorityStrongQuorumSignInfo};
 use sui_types::digests::CheckpointDigest;
 use sui_types::gas::GasCostSummary;
 use sui_types::message_envelope::Message;
 use sui_types::messages_checkpoint::{
-    CheckpointCommitment, CheckpointContents, CheckpointSequenceNumber, CheckpointSummary,
-    CheckpointTimestamp, EndOfEpochData,
+    CertifiedCheckpointSummary, CheckpointCommitment, CheckpointContents, CheckpointSequenceNumber,
+    CheckpointSummary, CheckpointTimestamp, EndOfEpochData, FullCheckpointContents,
 };
 
 use crate::BigInt;
 use crate::Page;
+use crate::SuiCommittee;
 
 pub type SuiCheckpointSequenceNumber = BigInt;
 pub type CheckpointPage = Page<Checkpoint, SuiCheckpointSequenceNumber>;
+pub type CheckpointObjectChangePage = Page<CheckpointObjectChange, ObjectID>;
 
 #[derive(Clone, Debug, JsonSchema, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -83,6 +91,143 @@ impl From<(CheckpointSummary, CheckpointContents)> for Checkpoint {
     }
 }
 
+/// How an object changed between two checkpoints, from the first one excluded to the last one
+/// included.
+#[derive(Clone, Copy, Debug, JsonSchema, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CheckpointObjectChangeKind {
+    /// The object did not exist as of the first checkpoint, it was created or unwrapped since.
+    Created,
+    /// The object existed as of both checkpoints, at different versions.
+    Mutated,
+    /// The object does not exist as of the last checkpoint, it was deleted or wrapped since.
+    Deleted,
+}
+
+#[derive(Clone, Debug, JsonSchema, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointObjectChange {
+    pub object_id: ObjectID,
+    pub kind: CheckpointObjectChangeKind,
+    /// The version of the object as of the last checkpoint, or the version it was deleted at.
+    pub version: SequenceNumber,
+    /// The digest of the object as of the last checkpoint, or of its tombstone.
+    pub digest: ObjectDigest,
+}
+
+/// The proof that a checkpoint is final, from which a light client verifies the finality of
+/// its transactions without trusting the fullnode serving it: the aggregate signature of a
+/// quorum of the committee of its epoch over its summary, which commits to its contents.
+#[serde_as]
+#[derive(Clone, Debug, JsonSchema, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointProof {
+    /// Checkpoint sequence number
+    pub sequence_number: SuiCheckpointSequenceNumber,
+    /// Checkpoint digest
+    pub digest: CheckpointDigest,
+    /// BCS serialized checkpoint summary, which the signature is over
+    #[serde_as(as = "Base64")]
+    #[schemars(with = "Base64")]
+    pub summary_bcs: Vec<u8>,
+    /// BCS serialized checkpoint contents, whose digest is committed to by the summary
+    #[serde_as(as = "Base64")]
+    #[schemars(with = "Base64")]
+    pub contents_bcs: Vec<u8>,
+    /// Aggregate signature of a quorum of the committee over the summary
+    pub auth_signature: SuiAuthorityStrongQuorumSignInfo,
+    /// The committee of the epoch of the checkpoint, as known to the fullnode. Light clients
+    /// verify the proof against the committee they trust instead, e.g. the one announced by the
+    /// last checkpoint of the previous epoch.
+    pub committee: SuiCommittee,
+}
+
+impl CheckpointProof {
+    pub fn new(
+        checkpoint: CertifiedCheckpointSummary,
+        contents: &CheckpointContents,
+        committee: Committee,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            sequence_number: checkpoint.sequence_number.into(),
+            digest: *checkpoint.digest(),
+            summary_bcs: bcs::to_bytes(checkpoint.data())?,
+            contents_bcs: bcs::to_bytes(contents)?,
+            auth_signature: checkpoint.auth_sig().into(),
+            committee: committee.into(),
+        })
+    }
+
+    /// Verifies the signature of the checkpoint against the given committee, and returns its
+    /// summary and contents.
+    pub fn verify(
+        &self,
+        committee: &Committee,
+    ) -> anyhow::Result<(CheckpointSummary, CheckpointContents)> {
+        let summary: CheckpointSummary = bcs::from_bytes(&self.summary_bcs)?;
+        let contents: CheckpointContents = bcs::from_bytes(&self.contents_bcs)?;
+        let auth_signature = AuthorityStrongQuorumSignInfo::try_from(&self.auth_signature)
+            .map_err(|e| anyhow!("Invalid checkpoint signature: {e}"))?;
+        let checkpoint = CertifiedCheckpointSummary::new_from_data_and_sig(summary, auth_signature);
+        checkpoint.verify_with_contents(committee, Some(&contents))?;
+        Ok((checkpoint.into_data(), contents))
+    }
+
+    /// Verifies that the given transaction is final, included in the checkpoint, and returns
+    /// the digests of its execution.
+    pub fn verify_transaction(
+        &self,
+        committee: &Committee,
+        transaction: &TransactionDigest,
+    ) -> anyhow::Result<ExecutionDigests> {
+        let (_, contents) = self.verify(committee)?;
+        contents
+            .iter()
+            .find(|digests| digests.transaction == *transaction)
+            .copied()
+            .ok_or_else(|| anyhow!("Transaction {transaction} is not in the checkpoint"))
+    }
+}
+
+/// A checkpoint with the transactions and effects it contains, for fullnodes to ingest it from
+/// the RPC of another fullnode. The summary is verified against the committee of its epoch and
+/// the contents against the summary, so the fullnode serving it is not trusted with its data.
+#[serde_as]
+#[derive(Clone, Debug, JsonSchema, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullCheckpoint {
+    /// Checkpoint sequence number
+    pub sequence_number: SuiCheckpointSequenceNumber,
+    /// BCS serialized certified checkpoint summary
+    #[serde_as(as = "Base64")]
+    #[schemars(with = "Base64")]
+    pub summary_bcs: Vec<u8>,
+    /// BCS serialized transactions, effects and user signatures of the checkpoint
+    #[serde_as(as = "Base64")]
+    #[schemars(with = "Base64")]
+    pub contents_bcs: Vec<u8>,
+}
+
+impl FullCheckpoint {
+    pub fn new(
+        checkpoint: &CertifiedCheckpointSummary,
+        contents: &FullCheckpointContents,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            sequence_number: checkpoint.sequence_number.into(),
+            summary_bcs: bcs::to_bytes(checkpoint)?,
+            contents_bcs: bcs::to_bytes(contents)?,
+        })
+    }
+
+    /// Decodes the summary and contents of the checkpoint, which are yet to be verified.
+    pub fn decode(&self) -> anyhow::Result<(CertifiedCheckpointSummary, FullCheckpointContents)> {
+        Ok((
+            bcs::from_bytes(&self.summary_bcs)?,
+            bcs::from_bytes(&self.contents_bcs)?,
+        ))
+    }
+}
+
 #[derive(Clone, Debug, JsonSchema, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum CheckpointId {
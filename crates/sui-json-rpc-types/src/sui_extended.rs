@@ -12,7 +12,7 @@ use sui_types::base_types::{EpochId, ObjectID};
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 use sui_types::sui_system_state::sui_system_state_summary::SuiValidatorSummary;
 
-use crate::Page;
+use crate::{BigInt, Page};
 
 pub type EpochPage = Page<EpochInfo, EpochId>;
 
@@ -89,3 +89,26 @@ pub struct MoveFunctionName {
     #[serde_as(as = "DisplayFromStr")]
     pub function: Identifier,
 }
+
+/// The level at which the gas usage of Move calls is aggregated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub enum GasUsageGrouping {
+    Package,
+    Module,
+    Function,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GasUsage {
+    pub package: ObjectID,
+    /// None when the usage is aggregated by package
+    pub module: Option<String>,
+    /// None unless the usage is aggregated by function
+    pub function: Option<String>,
+    pub computation_cost: BigInt,
+    pub storage_cost: BigInt,
+    pub storage_rebate: BigInt,
+    /// Number of Move calls the gas was used by
+    pub call_count: usize,
+}
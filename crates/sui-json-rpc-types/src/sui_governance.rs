@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use sui_types::base_types::{AuthorityName, EpochId, ObjectID, SuiAddress};
 use sui_types::committee::{Committee, StakeUnit};
 
-use crate::SuiEpochId;
+use crate::{BigInt, SuiCheckpointSequenceNumber, SuiEpochId};
 
 /// RPC representation of the [Committee] type.
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -58,3 +58,22 @@ pub struct Stake {
     #[serde(flatten)]
     pub status: StakeStatus,
 }
+
+/// Gas price estimates, from the gas prices paid by the transactions of the latest checkpoints.
+/// A percentile is the lowest gas price paid by at least this percentage of the transactions, and
+/// is never lower than the reference gas price.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GasPriceEstimates {
+    pub reference_gas_price: BigInt,
+    pub p50: BigInt,
+    pub p90: BigInt,
+    pub p99: BigInt,
+    /// The number of transactions the gas prices were observed in, which is 0 when the
+    /// percentiles default to the reference gas price.
+    pub num_transactions: BigInt,
+    /// The first checkpoint the gas prices were observed in.
+    pub first_checkpoint: SuiCheckpointSequenceNumber,
+    /// The last checkpoint the gas prices were observed in.
+    pub last_checkpoint: SuiCheckpointSequenceNumber,
+}
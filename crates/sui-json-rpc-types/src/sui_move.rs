@@ -282,6 +282,48 @@ pub enum MoveFunctionArgType {
     Object(ObjectValueKind),
 }
 
+/// The outcome of checking that compiled modules can upgrade an on-chain package, as the
+/// validators check it when executing the upgrade.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, Eq, PartialEq)]
+#[serde(rename = "PackageUpgradeReport", rename_all = "camelCase")]
+pub struct SuiPackageUpgradeReport {
+    /// The on-chain package to upgrade.
+    pub package_id: ObjectID,
+    /// The upgrade policy checked, as in `sui::package`.
+    pub policy: u8,
+    /// All the reasons for the upgrade to fail. Empty if the upgrade is valid.
+    pub incompatibilities: Vec<SuiPackageUpgradeIncompatibility>,
+}
+
+impl SuiPackageUpgradeReport {
+    pub fn is_compatible(&self) -> bool {
+        self.incompatibilities.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, Eq, PartialEq)]
+#[serde(
+    rename = "PackageUpgradeIncompatibility",
+    rename_all = "camelCase",
+    tag = "type"
+)]
+pub enum SuiPackageUpgradeIncompatibility {
+    /// The policy is not one of `sui::package`.
+    UnknownUpgradePolicy,
+    /// The modules cannot be deserialized, or do not form a package.
+    InvalidModules { error: String },
+    /// A module fails the Move or Sui bytecode verifier.
+    VerificationFailure { module: String, error: String },
+    /// A module of the existing package is missing from the upgrade.
+    MissingModule { module: String },
+    /// A module of the upgrade breaks the policy with respect to its existing version.
+    IncompatibleModule { module: String, error: String },
+    /// A dependency is not a package on chain.
+    MissingDependency { package: ObjectID },
+    /// The upgraded package cannot be linked against its dependencies.
+    InvalidLinkage { error: String },
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Eq, PartialEq)]
 #[serde(untagged, rename = "MoveValue")]
 pub enum SuiMoveValue {
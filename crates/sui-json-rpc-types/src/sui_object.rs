@@ -32,7 +32,7 @@ use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 use sui_types::move_package::{MovePackage, TypeOrigin, UpgradeInfo};
 use sui_types::object::{Data, MoveObject, Object, ObjectFormatOptions, ObjectRead, Owner};
 
-use crate::{Page, SuiMoveStruct, SuiMoveValue};
+use crate::{Page, SuiCheckpointSequenceNumber, SuiMoveStruct, SuiMoveValue};
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, PartialEq, Eq)]
 pub struct SuiObjectResponse {
@@ -1037,6 +1037,16 @@ pub struct SuiGetPastObjectRequest {
     pub version: SequenceNumber,
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Eq, PartialEq)]
+#[serde(rename = "ObjectsAtCheckpoint", rename_all = "camelCase")]
+pub struct SuiObjectsAtCheckpoint {
+    /// The checkpoint as of which the objects were read. Reading other objects as of this same
+    /// checkpoint gives results consistent with these ones.
+    pub checkpoint: SuiCheckpointSequenceNumber,
+    /// The objects, in the order of the queried IDs.
+    pub objects: Vec<SuiPastObjectResponse>,
+}
+
 #[serde_as]
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub enum SuiObjectDataFilter {
@@ -1146,3 +1156,41 @@ impl SuiObjectResponseQuery {
         }
     }
 }
+
+/// What happened to a version of an object, to explain why a transaction expecting that version
+/// fails with a version conflict.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, Eq, PartialEq)]
+#[serde(rename_all = "camelCase", rename = "ObjectVersionConflict")]
+pub struct SuiObjectVersionConflict {
+    pub object_id: ObjectID,
+    /// The version expected by the transaction.
+    pub version: SequenceNumber,
+    pub status: SuiObjectVersionStatus,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, Eq, PartialEq)]
+#[serde(rename_all = "camelCase", rename = "ObjectVersionStatus", tag = "type")]
+pub enum SuiObjectVersionStatus {
+    /// The node has no record of the version: it never existed, or was pruned.
+    #[serde(rename_all = "camelCase")]
+    NotFound {
+        /// The latest version of the object known to the node, if any.
+        latest_version: Option<SequenceNumber>,
+    },
+    /// The version is the latest version of the object, so it can still be used as input.
+    Live,
+    /// The version was consumed by a transaction, which wrote the next version of the object, or
+    /// deleted or wrapped it.
+    #[serde(rename_all = "camelCase")]
+    Consumed {
+        /// The version of the object written by the consuming transaction.
+        next_version: SequenceNumber,
+        /// Whether the consuming transaction deleted or wrapped the object.
+        deleted: bool,
+        /// The consuming transaction. Only found for a deleted or wrapped object when the node
+        /// indexes the transactions by input object.
+        transaction: Option<TransactionDigest>,
+        /// The checkpoint including the consuming transaction, unless not checkpointed yet.
+        checkpoint: Option<CheckpointSequenceNumber>,
+    },
+}
@@ -37,7 +37,7 @@ use sui_types::storage::{DeleteKind, WriteKind};
 
 use crate::balance_changes::BalanceChange;
 use crate::object_changes::ObjectChange;
-use crate::{Page, SuiEvent, SuiMovePackage, SuiObjectRef};
+use crate::{Page, SuiEvent, SuiMovePackage, SuiObjectData, SuiObjectRef};
 use sui_types::sui_serde::SuiTypeTag as AsSuiTypeTag;
 
 #[serde_as]
@@ -229,6 +229,12 @@ pub struct SuiTransactionBlockResponse {
     /// This is only returned in the read api, not in the transaction execution api.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub checkpoint: Option<CheckpointSequenceNumber>,
+    /// The highest checkpoint executed by the node when it returned this transaction.
+    /// This is only returned in the transaction execution api. Passing it back in the
+    /// `sui-min-checkpoint-watermark` header of the next reads makes the node serve them only
+    /// once it has caught up to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_watermark: Option<CheckpointSequenceNumber>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub errors: Vec<String>,
 }
@@ -660,6 +666,10 @@ pub struct DryRunTransactionBlockResponse {
     pub events: SuiTransactionBlockEvents,
     pub object_changes: Vec<ObjectChange>,
     pub balance_changes: Vec<BalanceChange>,
+    /// The content of the objects created, mutated or unwrapped by the transaction, as they would
+    /// be written if the transaction was executed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub written_objects: Vec<SuiObjectData>,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
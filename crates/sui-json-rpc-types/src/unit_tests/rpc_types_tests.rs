@@ -9,13 +9,21 @@ use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::{StructTag, TypeTag};
 use move_core_types::value::{MoveStruct, MoveValue};
 
-use sui_types::base_types::{ObjectDigest, SequenceNumber};
+use fastcrypto::traits::KeyPair;
+use sui_types::base_types::{ExecutionDigests, ObjectDigest, SequenceNumber, TransactionDigest};
 use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::committee::Committee;
+use sui_types::gas::GasCostSummary;
 use sui_types::gas_coin::GasCoin;
+use sui_types::messages_checkpoint::{
+    CertifiedCheckpointSummary, CheckpointContents, CheckpointSummary, SignedCheckpointSummary,
+};
 use sui_types::object::{MoveObject, Owner};
 use sui_types::{parse_sui_struct_tag, MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS};
 
-use crate::{ObjectChange, SuiMoveStruct, SuiMoveValue};
+use crate::{
+    CheckpointProof, Filter, ObjectChange, ObjectChangeFilter, SuiMoveStruct, SuiMoveValue,
+};
 
 #[test]
 fn test_move_value_to_sui_coin() {
@@ -169,3 +177,87 @@ fn test_type_tag_struct_tag_devnet_inc_222() {
         assert_eq!(oc, deser);
     }
 }
+
+#[test]
+fn test_checkpoint_proof() {
+    let (committee, keys) = Committee::new_simple_test_committee();
+    let digests = ExecutionDigests::random();
+    let contents =
+        CheckpointContents::new_with_causally_ordered_transactions([digests].into_iter());
+    let summary = CheckpointSummary::new(
+        committee.epoch,
+        1,
+        0,
+        &contents,
+        None,
+        GasCostSummary::default(),
+        None,
+        0,
+    );
+    let sign_infos: Vec<_> = keys
+        .iter()
+        .map(|k| SignedCheckpointSummary::sign(committee.epoch, &summary, k, k.public().into()))
+        .collect();
+    let checkpoint = CertifiedCheckpointSummary::new(summary, sign_infos, &committee).unwrap();
+
+    let proof = CheckpointProof::new(checkpoint, &contents, committee.clone()).unwrap();
+    // The proof survives its JSON encoding.
+    let proof: CheckpointProof =
+        serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+    assert_eq!(
+        proof
+            .verify_transaction(&committee, &digests.transaction)
+            .unwrap(),
+        digests
+    );
+    assert!(proof
+        .verify_transaction(&committee, &TransactionDigest::random())
+        .is_err());
+
+    // The proof does not verify against another committee.
+    let (other_committee, _) = Committee::new_simple_test_committee();
+    assert!(proof.verify(&other_committee).is_err());
+}
+
+#[test]
+fn test_object_change_filter() {
+    let (sender, recipient) = (
+        SuiAddress::random_for_testing_only(),
+        SuiAddress::random_for_testing_only(),
+    );
+    let coin_type = GasCoin::type_();
+    let mutated = |owner| ObjectChange::Mutated {
+        sender,
+        owner,
+        object_type: coin_type.clone(),
+        object_id: ObjectID::random(),
+        version: Default::default(),
+        previous_version: Default::default(),
+        digest: ObjectDigest::random(),
+    };
+    let sent = mutated(Owner::AddressOwner(recipient));
+    let shared = mutated(Owner::Shared {
+        initial_shared_version: Default::default(),
+    });
+    let deleted = ObjectChange::Deleted {
+        sender,
+        object_type: parse_sui_struct_tag("0x2::example::Example").unwrap(),
+        object_id: ObjectID::random(),
+        version: Default::default(),
+    };
+
+    // The sender and the recipient of a transfer are both notified.
+    assert!(ObjectChangeFilter::Owner(sender).matches(&sent));
+    assert!(ObjectChangeFilter::Owner(recipient).matches(&sent));
+    assert!(!ObjectChangeFilter::Owner(recipient).matches(&deleted));
+    assert!(ObjectChangeFilter::Owner(sender).matches(&deleted));
+    assert!(!ObjectChangeFilter::Owner(sender).matches(&shared));
+
+    let coins_of_recipient = ObjectChangeFilter::All(vec![
+        ObjectChangeFilter::Owner(recipient),
+        ObjectChangeFilter::ObjectType(coin_type),
+    ]);
+    assert!(coins_of_recipient.matches(&sent));
+    assert!(!coins_of_recipient.matches(&shared));
+    assert!(!coins_of_recipient.matches(&deleted));
+}
@@ -5,8 +5,8 @@ use jsonrpsee::core::RpcResult;
 use jsonrpsee_proc_macros::rpc;
 
 use sui_json_rpc_types::{
-    CheckpointedObjectID, EpochInfo, EpochPage, MoveCallMetrics, NetworkMetrics, ObjectsPage,
-    SuiObjectResponseQuery,
+    CheckpointedObjectID, EpochInfo, EpochPage, GasUsage, GasUsageGrouping, MoveCallMetrics,
+    NetworkMetrics, ObjectsPage, SuiObjectResponseQuery,
 };
 use sui_open_rpc_macros::open_rpc;
 use sui_types::base_types::EpochId;
@@ -49,4 +49,20 @@ pub trait ExtendedApi {
     /// Return Network metrics
     #[method(name = "getMoveCallMetrics")]
     async fn get_move_call_metrics(&self) -> RpcResult<MoveCallMetrics>;
+
+    /// Return the gas used by Move calls over a time window, aggregated by package, module or
+    /// function, the most computation-intensive first. The gas of a transaction is split evenly
+    /// between its Move calls. Note that this is an enhanced full node only api.
+    #[method(name = "getGasUsageMetrics")]
+    async fn get_gas_usage_metrics(
+        &self,
+        /// the level at which the gas usage is aggregated
+        grouping: GasUsageGrouping,
+        /// start of the time window in milliseconds since the Unix epoch, the usage being aggregated by day. Default to 30 days before the end of the window if not specified.
+        start_time: Option<u64>,
+        /// end of the time window in milliseconds since the Unix epoch, included. Default to now if not specified.
+        end_time: Option<u64>,
+        /// Max number of items returned, default to [QUERY_MAX_RESULT_LIMIT] if not specified.
+        limit: Option<usize>,
+    ) -> RpcResult<Vec<GasUsage>>;
 }
@@ -4,7 +4,7 @@
 use jsonrpsee::core::RpcResult;
 use jsonrpsee_proc_macros::rpc;
 
-use sui_json_rpc_types::{BigInt, DelegatedStake, SuiCommittee};
+use sui_json_rpc_types::{BigInt, DelegatedStake, GasPriceEstimates, SuiCommittee};
 use sui_open_rpc_macros::open_rpc;
 use sui_types::base_types::{ObjectID, SuiAddress};
 use sui_types::committee::EpochId;
@@ -39,4 +39,15 @@ pub trait GovernanceReadApi {
     /// Return the reference gas price for the network
     #[method(name = "getReferenceGasPrice")]
     async fn get_reference_gas_price(&self) -> RpcResult<BigInt>;
+
+    /// Return the gas price percentiles observed in the latest checkpoints, along with the
+    /// reference gas price, to suggest the gas price of a transaction.
+    #[method(name = "getGasPriceEstimates")]
+    async fn get_gas_price_estimates(
+        &self,
+        /// The number of latest checkpoints to observe, default to
+        /// [GAS_PRICE_ESTIMATES_DEFAULT_CHECKPOINTS] and at most
+        /// [GAS_PRICE_ESTIMATES_MAX_CHECKPOINTS].
+        num_checkpoints: Option<usize>,
+    ) -> RpcResult<GasPriceEstimates>;
 }
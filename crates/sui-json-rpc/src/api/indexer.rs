@@ -5,9 +5,10 @@ use jsonrpsee::core::RpcResult;
 use jsonrpsee_proc_macros::rpc;
 
 use sui_json_rpc_types::{
-    CheckpointedObjectID, DynamicFieldPage, EventFilter, EventPage, ObjectsPage, SuiEvent,
-    SuiObjectResponse, SuiObjectResponseQuery, SuiTransactionBlockResponseQuery,
-    TransactionBlocksPage,
+    CheckpointedObjectID, DynamicFieldPage, EventFilter, EventPage, ObjectChangeFilter,
+    ObjectChangeNotification, ObjectsPage, SuiCheckpointSequenceNumber, SuiEvent,
+    SuiObjectResponse, SuiObjectResponseQuery, SuiTransactionBlockResponse,
+    SuiTransactionBlockResponseOptions, SuiTransactionBlockResponseQuery, TransactionBlocksPage,
 };
 use sui_open_rpc_macros::open_rpc;
 use sui_types::base_types::{ObjectID, SuiAddress};
@@ -46,7 +47,8 @@ pub trait IndexerApi {
         descending_order: Option<bool>,
     ) -> RpcResult<TransactionBlocksPage>;
 
-    /// Return list of events for a specified query criteria.
+    /// Return list of events for a specified query criteria. A page may hold less events than the
+    /// limit and still have a next page when many events were scanned without matching the query.
     #[method(name = "queryEvents")]
     async fn query_events(
         &self,
@@ -68,6 +70,29 @@ pub trait IndexerApi {
         filter: EventFilter,
     );
 
+    /// Subscribe to a stream of the transactions at checkpoint finality, in the order of their
+    /// checkpoints. Each transaction comes with the sequence number and the timestamp of its
+    /// checkpoint.
+    #[subscription(name = "subscribeFinalizedTransaction", item = SuiTransactionBlockResponse)]
+    fn subscribe_finalized_transaction(
+        &self,
+        /// the checkpoint from which to stream the transactions, to resume a stream. If None, default to the next checkpoint executed
+        from_checkpoint: Option<SuiCheckpointSequenceNumber>,
+        /// options for specifying the content to be returned
+        options: Option<SuiTransactionBlockResponseOptions>,
+    );
+
+    /// Subscribe to a stream of the changes of objects at checkpoint finality, in the order of
+    /// their checkpoints, for wallets to follow the objects of an address without polling.
+    #[subscription(name = "subscribeObjectChange", item = ObjectChangeNotification)]
+    fn subscribe_object_change(
+        &self,
+        /// the filter criteria of the object changes, by the owner or the type of the objects
+        filter: ObjectChangeFilter,
+        /// the checkpoint from which to stream the changes, to resume a stream. If None, default to the next checkpoint executed
+        from_checkpoint: Option<SuiCheckpointSequenceNumber>,
+    );
+
     /// Return the list of dynamic field objects owned by an object.
     #[method(name = "getDynamicFields")]
     async fn get_dynamic_fields(
@@ -47,6 +47,14 @@ pub const QUERY_MAX_RESULT_LIMIT_CHECKPOINTS: usize = 100;
 
 pub const QUERY_MAX_RESULT_LIMIT_OBJECTS: usize = 256;
 
+/// The maximum number of checkpoints whose object changes are diffed in a single query.
+pub const QUERY_MAX_CHECKPOINTS_OBJECT_CHANGES: u64 = 1000;
+
+/// The number of latest checkpoints the gas price estimates are computed from, by default and at
+/// most.
+pub const GAS_PRICE_ESTIMATES_DEFAULT_CHECKPOINTS: usize = 100;
+pub const GAS_PRICE_ESTIMATES_MAX_CHECKPOINTS: usize = 1000;
+
 pub fn cap_page_limit(limit: Option<usize>) -> usize {
     let limit = limit.unwrap_or_default();
     if limit > QUERY_MAX_RESULT_LIMIT || limit == 0 {
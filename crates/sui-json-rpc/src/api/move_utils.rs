@@ -3,12 +3,13 @@
 
 use std::collections::BTreeMap;
 
+use fastcrypto::encoding::Base64;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee_proc_macros::rpc;
 
 use sui_json_rpc_types::{
     MoveFunctionArgType, SuiMoveNormalizedFunction, SuiMoveNormalizedModule,
-    SuiMoveNormalizedStruct,
+    SuiMoveNormalizedStruct, SuiPackageUpgradeReport,
 };
 use sui_open_rpc_macros::open_rpc;
 use sui_types::base_types::ObjectID;
@@ -58,4 +59,19 @@ pub trait MoveUtils {
         module_name: String,
         function_name: String,
     ) -> RpcResult<SuiMoveNormalizedFunction>;
+
+    /// Check that a compiled package can upgrade an on-chain package, as the validators check
+    /// the upgrade when executing it, and report all the incompatibilities found. No gas is paid.
+    #[method(name = "checkPackageUpgrade")]
+    async fn check_package_upgrade(
+        &self,
+        /// the on-chain package to upgrade
+        package: ObjectID,
+        /// the compiled bytes of the upgraded Move package
+        compiled_modules: Vec<Base64>,
+        /// a list of transitive dependency addresses that this set of modules depends on.
+        dependencies: Vec<ObjectID>,
+        /// the upgrade policy, as in `sui::package`. Compatible if not provided.
+        policy: Option<u8>,
+    ) -> RpcResult<SuiPackageUpgradeReport>;
 }
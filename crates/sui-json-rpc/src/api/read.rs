@@ -5,9 +5,10 @@ use jsonrpsee::core::RpcResult;
 use jsonrpsee_proc_macros::rpc;
 
 use sui_json_rpc_types::{
-    BigInt, Checkpoint, CheckpointId, CheckpointPage, SuiCheckpointSequenceNumber, SuiEvent,
-    SuiGetPastObjectRequest, SuiObjectDataOptions, SuiObjectResponse, SuiPastObjectResponse,
-    SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    BigInt, Checkpoint, CheckpointId, CheckpointObjectChangePage, CheckpointPage, CheckpointProof,
+    FullCheckpoint, SuiCheckpointSequenceNumber, SuiEvent, SuiGetPastObjectRequest,
+    SuiObjectDataOptions, SuiObjectResponse, SuiObjectVersionConflict, SuiObjectsAtCheckpoint,
+    SuiPastObjectResponse, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
 };
 use sui_open_rpc_macros::open_rpc;
 use sui_types::base_types::{ObjectID, SequenceNumber, TransactionDigest};
@@ -57,6 +58,22 @@ pub trait ReadApi {
         options: Option<SuiObjectDataOptions>,
     ) -> RpcResult<Vec<SuiObjectResponse>>;
 
+    /// Return the object information for a list of objects, as of a single checkpoint so that
+    /// they are consistent with each other. The checkpoint used is returned, for the next reads
+    /// to be consistent with this one.
+    /// Note that as for tryGetPastObject, the versions of the objects as of past checkpoints may
+    /// have been pruned.
+    #[method(name = "multiGetObjectsAtCheckpoint")]
+    async fn multi_get_objects_at_checkpoint(
+        &self,
+        /// the IDs of the queried objects
+        object_ids: Vec<ObjectID>,
+        /// the checkpoint as of which the objects are read. If None, default to the latest executed checkpoint
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
+        /// options for specifying the content to be returned
+        options: Option<SuiObjectDataOptions>,
+    ) -> RpcResult<SuiObjectsAtCheckpoint>;
+
     /// Note there is no software-level guarantee/SLA that objects with past versions
     /// can be retrieved by this API, even if the object and version exists/existed.
     /// The result may vary across nodes depending on their pruning policies.
@@ -85,6 +102,18 @@ pub trait ReadApi {
         options: Option<SuiObjectDataOptions>,
     ) -> RpcResult<Vec<SuiPastObjectResponse>>;
 
+    /// Explain why a transaction expecting a version of an object fails with a version conflict:
+    /// return whether the version is still the latest one, or which transaction consumed it and
+    /// in which checkpoint
+    #[method(name = "getObjectVersionConflict")]
+    async fn get_object_version_conflict(
+        &self,
+        /// the ID of the object
+        object_id: ObjectID,
+        /// the version of the object expected by the transaction
+        version: SequenceNumber,
+    ) -> RpcResult<SuiObjectVersionConflict>;
+
     /// Return a checkpoint
     #[method(name = "getCheckpoint")]
     async fn get_checkpoint(
@@ -93,6 +122,24 @@ pub trait ReadApi {
         id: CheckpointId,
     ) -> RpcResult<Checkpoint>;
 
+    /// Return the proof that a checkpoint is final, which light clients verify against the
+    /// committee of its epoch
+    #[method(name = "getCheckpointProof")]
+    async fn get_checkpoint_proof(
+        &self,
+        /// Checkpoint identifier, can use either checkpoint digest, or checkpoint sequence number as input.
+        id: CheckpointId,
+    ) -> RpcResult<CheckpointProof>;
+
+    /// Return a checkpoint with the transactions and effects it contains, for fullnodes to
+    /// ingest it without joining state sync
+    #[method(name = "getFullCheckpoint")]
+    async fn get_full_checkpoint(
+        &self,
+        /// Checkpoint identifier, can use either checkpoint digest, or checkpoint sequence number as input.
+        id: CheckpointId,
+    ) -> RpcResult<FullCheckpoint>;
+
     /// Return paginated list of checkpoints
     #[method(name = "getCheckpoints")]
     async fn get_checkpoints(
@@ -105,6 +152,23 @@ pub trait ReadApi {
         descending_order: bool,
     ) -> RpcResult<CheckpointPage>;
 
+    /// Return paginated list of the objects created, mutated or deleted by the checkpoints after
+    /// `from_checkpoint`, up to `to_checkpoint` included, in the order of their IDs, for services
+    /// syncing the changes incrementally. The objects created then deleted in between are left
+    /// out. At most QUERY_MAX_CHECKPOINTS_OBJECT_CHANGES checkpoints are diffed at once.
+    #[method(name = "getObjectChangesBetweenCheckpoints")]
+    async fn get_object_changes_between_checkpoints(
+        &self,
+        /// the checkpoint the changes are from, excluded
+        from_checkpoint: SuiCheckpointSequenceNumber,
+        /// the checkpoint the changes are up to, included. It must be executed already
+        to_checkpoint: SuiCheckpointSequenceNumber,
+        /// An optional paging cursor. If provided, the query will start from the next item after the specified cursor. Default to start from the first item if not specified.
+        cursor: Option<ObjectID>,
+        /// Maximum item returned per page, default to [QUERY_MAX_RESULT_LIMIT] if not specified.
+        limit: Option<usize>,
+    ) -> RpcResult<CheckpointObjectChangePage>;
+
     /// Return transaction events.
     #[method(name = "getEvents")]
     async fn get_events(
@@ -5,8 +5,8 @@ use fastcrypto::encoding::Base64;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee_proc_macros::rpc;
 use sui_json_rpc_types::{
-    BigInt, DevInspectResults, DryRunTransactionBlockResponse, SuiTransactionBlockResponse,
-    SuiTransactionBlockResponseOptions,
+    BigInt, DevInspectResults, DryRunTransactionBlockResponse, SuiCheckpointSequenceNumber,
+    SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
 };
 
 use sui_open_rpc_macros::open_rpc;
@@ -52,13 +52,20 @@ pub trait WriteApi {
         gas_price: Option<BigInt>,
         /// The epoch to perform the call. Will be set from the system state object if not provided
         epoch: Option<EpochId>,
+        /// The checkpoint as of which the objects are read, for archive nodes only. If None, the transaction is run against the latest state
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
     ) -> RpcResult<DevInspectResults>;
 
     /// Return transaction execution effects including the gas cost summary,
     /// while the effects are not committed to the chain.
+    /// The transaction can be run against the state as of a past checkpoint, on archive nodes,
+    /// to analyse what it would have done then. It is run under the protocol config of the
+    /// current epoch.
     #[method(name = "dryRunTransactionBlock")]
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        /// The checkpoint as of which the objects are read, for archive nodes only. If None, the transaction is run against the latest state
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
     ) -> RpcResult<DryRunTransactionBlockResponse>;
 }
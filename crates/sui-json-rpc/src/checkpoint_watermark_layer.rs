@@ -0,0 +1,163 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MIN_CHECKPOINT_WATERMARK_HEADER;
+use hyper::{Body, Request, Response};
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use sui_core::authority::AuthorityState;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tower::{Layer, Service};
+
+/// The longest a request waits for the node to catch up to its checkpoint watermark.
+pub const MAX_CHECKPOINT_WATERMARK_WAIT: Duration = Duration::from_secs(10);
+
+/// Holds the requests carrying a checkpoint watermark, e.g. the one returned when executing a
+/// transaction, until the node has executed this checkpoint, so that the reads following a write
+/// are not served from an older state. Requests without a watermark are served right away.
+#[derive(Clone)]
+pub struct CheckpointWatermarkLayer {
+    state: Option<Arc<AuthorityState>>,
+}
+
+impl CheckpointWatermarkLayer {
+    pub fn new(state: Option<Arc<AuthorityState>>) -> Self {
+        Self { state }
+    }
+}
+
+impl<S> Layer<S> for CheckpointWatermarkLayer {
+    type Service = CheckpointWatermarkService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CheckpointWatermarkService {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CheckpointWatermarkService<S> {
+    inner: S,
+    state: Option<Arc<AuthorityState>>,
+}
+
+impl<S> Service<Request<Body>> for CheckpointWatermarkService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Response: 'static,
+    S::Error: Into<Box<dyn Error + Send + Sync>> + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Box<dyn Error + Send + Sync + 'static>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        // take the service that was ready
+        // https://docs.rs/tower/latest/tower/trait.Service.html#be-careful-when-cloning-inner-services
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+        let res_fut = async move {
+            if let (Some(state), Some(watermark)) =
+                (state, req.headers().get(MIN_CHECKPOINT_WATERMARK_HEADER))
+            {
+                let Some(watermark) = watermark
+                    .to_str()
+                    .ok()
+                    .and_then(|w| w.parse::<CheckpointSequenceNumber>().ok()) else {
+                    return Ok(response::invalid_watermark());
+                };
+                match tokio::time::timeout(
+                    MAX_CHECKPOINT_WATERMARK_WAIT,
+                    state.notify_read_executed_checkpoint(watermark),
+                )
+                .await
+                {
+                    Ok(Ok(())) => (),
+                    Ok(Err(e)) => {
+                        tracing::error!("Failed to read the highest executed checkpoint: {e}");
+                        return Ok(response::internal_error());
+                    }
+                    Err(_) => return Ok(response::not_caught_up(watermark)),
+                }
+            }
+            inner.call(req).await.map_err(|err| err.into())
+        };
+        Box::pin(res_fut)
+    }
+}
+
+mod response {
+    use jsonrpsee::core::__reexports::serde_json;
+    use jsonrpsee::types::error::{ErrorCode, ErrorObject};
+    use jsonrpsee::types::{ErrorResponse, Id};
+    use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+    const JSON: &str = "application/json; charset=utf-8";
+
+    /// The error code of the requests whose checkpoint watermark the node has not caught up to.
+    pub(crate) const NOT_CAUGHT_UP_CODE: i32 = -32050;
+
+    pub(crate) fn invalid_watermark() -> hyper::Response<hyper::Body> {
+        let error = serde_json::to_string(&ErrorResponse::borrowed(
+            ErrorObject::owned(
+                ErrorCode::InvalidRequest.code(),
+                format!(
+                    "Invalid {} header, expected a checkpoint sequence number",
+                    crate::MIN_CHECKPOINT_WATERMARK_HEADER
+                ),
+                None::<()>,
+            ),
+            Id::Null,
+        ))
+        .expect("built from known-good data; qed");
+        from_template(hyper::StatusCode::BAD_REQUEST, error)
+    }
+
+    pub(crate) fn not_caught_up(
+        watermark: CheckpointSequenceNumber,
+    ) -> hyper::Response<hyper::Body> {
+        let error = serde_json::to_string(&ErrorResponse::borrowed(
+            ErrorObject::owned(
+                NOT_CAUGHT_UP_CODE,
+                format!("The node has not executed checkpoint {watermark} yet, retry later"),
+                None::<()>,
+            ),
+            Id::Null,
+        ))
+        .expect("built from known-good data; qed");
+        from_template(hyper::StatusCode::SERVICE_UNAVAILABLE, error)
+    }
+
+    pub(crate) fn internal_error() -> hyper::Response<hyper::Body> {
+        let error = serde_json::to_string(&ErrorResponse::borrowed(
+            ErrorCode::InternalError.into(),
+            Id::Null,
+        ))
+        .expect("built from known-good data; qed");
+        from_template(hyper::StatusCode::INTERNAL_SERVER_ERROR, error)
+    }
+
+    fn from_template(status: hyper::StatusCode, body: String) -> hyper::Response<hyper::Body> {
+        hyper::Response::builder()
+            .status(status)
+            .header(
+                "content-type",
+                hyper::header::HeaderValue::from_static(JSON),
+            )
+            .body(body.into())
+            .expect("Unable to parse response body for type conversion")
+    }
+}
@@ -47,6 +47,9 @@ pub enum Error {
 
     #[error(transparent)]
     SuiObjectResponseError(#[from] SuiObjectResponseError),
+
+    #[error("The memory budget of the node is exceeded, retry later")]
+    MemoryBudgetExceeded,
 }
 
 impl From<Error> for RpcError {
@@ -10,7 +10,7 @@ use jsonrpsee::core::RpcResult;
 use jsonrpsee::RpcModule;
 
 use sui_core::authority::AuthorityState;
-use sui_json_rpc_types::{BigInt, SuiCommittee};
+use sui_json_rpc_types::{BigInt, GasPriceEstimates, SuiCommittee};
 use sui_json_rpc_types::{DelegatedStake, Stake, StakeStatus};
 use sui_open_rpc::Module;
 use sui_types::base_types::{MoveObjectType, ObjectID, SuiAddress};
@@ -27,7 +27,10 @@ use sui_types::sui_system_state::{
     get_validator_from_table, sui_system_state_summary::get_validator_by_pool_id, SuiSystemState,
 };
 
-use crate::api::GovernanceReadApiServer;
+use crate::api::{
+    GovernanceReadApiServer, GAS_PRICE_ESTIMATES_DEFAULT_CHECKPOINTS,
+    GAS_PRICE_ESTIMATES_MAX_CHECKPOINTS,
+};
 use crate::error::Error;
 use crate::SuiRpcModule;
 
@@ -260,6 +263,40 @@ impl GovernanceReadApiServer for GovernanceReadApi {
         let epoch_store = self.state.load_epoch_store_one_call_per_task();
         Ok(epoch_store.reference_gas_price().into())
     }
+
+    async fn get_gas_price_estimates(
+        &self,
+        num_checkpoints: Option<usize>,
+    ) -> RpcResult<GasPriceEstimates> {
+        let num_checkpoints = num_checkpoints
+            .unwrap_or(GAS_PRICE_ESTIMATES_DEFAULT_CHECKPOINTS)
+            .clamp(1, GAS_PRICE_ESTIMATES_MAX_CHECKPOINTS);
+        let reference_gas_price = self
+            .state
+            .load_epoch_store_one_call_per_task()
+            .reference_gas_price();
+        let prices = self
+            .state
+            .get_recent_gas_prices(num_checkpoints as u64)
+            .map_err(Error::from)?;
+        // The transactions pay at least the reference gas price of their epoch, which may be
+        // lower than the current one.
+        let percentile = |percentile| {
+            prices
+                .percentile(percentile)
+                .map_or(reference_gas_price, |price| price.max(reference_gas_price))
+                .into()
+        };
+        Ok(GasPriceEstimates {
+            reference_gas_price: reference_gas_price.into(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            num_transactions: (prices.num_transactions() as u64).into(),
+            first_checkpoint: (*prices.checkpoints.start()).into(),
+            last_checkpoint: (*prices.checkpoints.end()).into(),
+        })
+    }
 }
 
 impl SuiRpcModule for GovernanceReadApi {
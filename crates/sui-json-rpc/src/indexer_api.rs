@@ -5,7 +5,7 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
-use futures::Stream;
+use futures::{stream, Stream, StreamExt};
 use jsonrpsee::core::error::SubscriptionClosed;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::types::SubscriptionResult;
@@ -14,11 +14,13 @@ use serde::Serialize;
 use tracing::{debug, warn};
 
 use mysten_metrics::spawn_monitored_task;
+use std::time::Duration;
 use sui_core::authority::AuthorityState;
 use sui_json_rpc_types::{
-    CheckpointedObjectID, DynamicFieldPage, EventFilter, EventPage, ObjectsPage, Page,
-    SuiObjectDataOptions, SuiObjectResponse, SuiObjectResponseQuery, SuiTransactionBlockResponse,
-    SuiTransactionBlockResponseQuery, TransactionBlocksPage,
+    CheckpointedObjectID, DynamicFieldPage, EventFilter, EventPage, Filter, ObjectChangeFilter,
+    ObjectChangeNotification, ObjectsPage, Page, SuiCheckpointSequenceNumber, SuiObjectDataOptions,
+    SuiObjectResponse, SuiObjectResponseQuery, SuiTransactionBlockResponse,
+    SuiTransactionBlockResponseOptions, SuiTransactionBlockResponseQuery, TransactionBlocksPage,
 };
 use sui_open_rpc::Module;
 use sui_types::base_types::{ObjectID, SuiAddress};
@@ -26,12 +28,21 @@ use sui_types::digests::TransactionDigest;
 use sui_types::dynamic_field::DynamicFieldName;
 use sui_types::error::UserInputError;
 use sui_types::event::EventID;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tokio::sync::mpsc;
+use tokio::time::sleep;
+use tokio_stream::wrappers::ReceiverStream;
 
 use crate::api::IndexerApiServer;
 use crate::api::ReadApiServer;
-use crate::api::{cap_page_limit, validate_limit, QUERY_MAX_RESULT_LIMIT_OBJECTS};
+use crate::api::{
+    cap_page_limit, validate_limit, QUERY_MAX_RESULT_LIMIT, QUERY_MAX_RESULT_LIMIT_OBJECTS,
+};
 use crate::SuiRpcModule;
 
+/// How often the streams of finalized transactions check for newly executed checkpoints.
+const FINALIZED_TRANSACTIONS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
 pub fn spawn_subscription<S, T>(mut sink: SubscriptionSink, rx: S)
 where
     S: Stream<Item = T> + Unpin + Send + 'static,
@@ -51,19 +62,90 @@ where
     });
 }
 
+/// Sends the transactions of the executed checkpoints, from `next_checkpoint` on, until the
+/// subscriber is gone.
+async fn stream_finalized_transactions<R: ReadApiServer>(
+    state: Arc<AuthorityState>,
+    read_api: R,
+    mut next_checkpoint: Option<CheckpointSequenceNumber>,
+    options: SuiTransactionBlockResponseOptions,
+    tx: mpsc::Sender<SuiTransactionBlockResponse>,
+) {
+    while !tx.is_closed() {
+        let latest_checkpoint = match state.get_latest_checkpoint_sequence_number() {
+            Ok(latest_checkpoint) => latest_checkpoint,
+            Err(e) => {
+                debug!("Latest checkpoint sequence number was not found with error: {e}");
+                sleep(FINALIZED_TRANSACTIONS_POLL_INTERVAL).await;
+                continue;
+            }
+        };
+        let checkpoint = *next_checkpoint.get_or_insert(latest_checkpoint + 1);
+        if checkpoint > latest_checkpoint {
+            sleep(FINALIZED_TRANSACTIONS_POLL_INTERVAL).await;
+            continue;
+        }
+
+        let digests: Vec<_> = match state.get_checkpoint_contents_by_sequence_number(checkpoint) {
+            Ok(contents) => contents.iter().map(|c| c.transaction).collect(),
+            Err(e) => {
+                warn!("Failed to read checkpoint {checkpoint}, closing the subscription: {e}");
+                return;
+            }
+        };
+        for chunk in digests.chunks(QUERY_MAX_RESULT_LIMIT) {
+            let responses = match read_api
+                .multi_get_transaction_blocks(chunk.to_vec(), Some(options.clone()))
+                .await
+            {
+                Ok(responses) => responses,
+                Err(e) => {
+                    warn!("Failed to read the transactions of checkpoint {checkpoint}, closing the subscription: {e}");
+                    return;
+                }
+            };
+            for response in responses {
+                if tx.send(response).await.is_err() {
+                    return;
+                }
+            }
+        }
+        next_checkpoint = Some(checkpoint + 1);
+    }
+}
+
+/// The changes of objects by the transaction which match the filter.
+fn object_change_notifications(
+    response: SuiTransactionBlockResponse,
+    filter: &ObjectChangeFilter,
+) -> Vec<ObjectChangeNotification> {
+    response
+        .object_changes
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|change| filter.matches(change))
+        .map(|change| ObjectChangeNotification {
+            transaction_digest: response.digest,
+            checkpoint: response.checkpoint,
+            timestamp_ms: response.timestamp_ms,
+            change,
+        })
+        .collect()
+}
+
 pub struct IndexerApi<R> {
     state: Arc<AuthorityState>,
     read_api: R,
 }
 
-impl<R: ReadApiServer> IndexerApi<R> {
+impl<R: ReadApiServer + Clone> IndexerApi<R> {
     pub fn new(state: Arc<AuthorityState>, read_api: R) -> Self {
         Self { state, read_api }
     }
 }
 
 #[async_trait]
-impl<R: ReadApiServer> IndexerApiServer for IndexerApi<R> {
+impl<R: ReadApiServer + Clone> IndexerApiServer for IndexerApi<R> {
     async fn get_owned_objects(
         &self,
         address: SuiAddress,
@@ -180,19 +262,10 @@ impl<R: ReadApiServer> IndexerApiServer for IndexerApi<R> {
         );
         let descending = descending_order.unwrap_or_default();
         let limit = cap_page_limit(limit);
-        // Retrieve 1 extra item for next cursor
-        let mut data = self
+        Ok(self
             .state
-            .query_events(query, cursor.clone(), limit + 1, descending)
-            .await?;
-        let has_next_page = data.len() > limit;
-        data.truncate(limit);
-        let next_cursor = data.last().map_or(cursor, |e| Some(e.id.clone()));
-        Ok(EventPage {
-            data,
-            next_cursor,
-            has_next_page,
-        })
+            .query_events_page(query, cursor, limit, descending)
+            .await?)
     }
 
     fn subscribe_event(&self, sink: SubscriptionSink, filter: EventFilter) -> SubscriptionResult {
@@ -200,6 +273,44 @@ impl<R: ReadApiServer> IndexerApiServer for IndexerApi<R> {
         Ok(())
     }
 
+    fn subscribe_finalized_transaction(
+        &self,
+        sink: SubscriptionSink,
+        from_checkpoint: Option<SuiCheckpointSequenceNumber>,
+        options: Option<SuiTransactionBlockResponseOptions>,
+    ) -> SubscriptionResult {
+        let (tx, rx) = mpsc::channel(QUERY_MAX_RESULT_LIMIT);
+        spawn_monitored_task!(stream_finalized_transactions(
+            self.state.clone(),
+            self.read_api.clone(),
+            from_checkpoint.map(<u64>::from),
+            options.unwrap_or_default(),
+            tx,
+        ));
+        spawn_subscription(sink, ReceiverStream::new(rx));
+        Ok(())
+    }
+
+    fn subscribe_object_change(
+        &self,
+        sink: SubscriptionSink,
+        filter: ObjectChangeFilter,
+        from_checkpoint: Option<SuiCheckpointSequenceNumber>,
+    ) -> SubscriptionResult {
+        let (tx, rx) = mpsc::channel(QUERY_MAX_RESULT_LIMIT);
+        spawn_monitored_task!(stream_finalized_transactions(
+            self.state.clone(),
+            self.read_api.clone(),
+            from_checkpoint.map(<u64>::from),
+            SuiTransactionBlockResponseOptions::new().with_object_changes(),
+            tx,
+        ));
+        let changes = ReceiverStream::new(rx)
+            .flat_map(move |response| stream::iter(object_change_notifications(response, &filter)));
+        spawn_subscription(sink, changes);
+        Ok(())
+    }
+
     async fn get_dynamic_fields(
         &self,
         parent_object_id: ObjectID,
@@ -241,7 +352,7 @@ impl<R: ReadApiServer> IndexerApiServer for IndexerApi<R> {
     }
 }
 
-impl<R: ReadApiServer> SuiRpcModule for IndexerApi<R> {
+impl<R: ReadApiServer + Clone> SuiRpcModule for IndexerApi<R> {
     fn rpc(self) -> RpcModule<Self> {
         self.into_rpc()
     }
@@ -4,6 +4,7 @@
 use std::env;
 use std::net::SocketAddr;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use hyper::header::HeaderName;
 use hyper::header::HeaderValue;
@@ -18,14 +19,17 @@ use tracing::{info, warn};
 
 pub use balance_changes::*;
 pub use object_changes::*;
+use sui_core::authority::AuthorityState;
 use sui_open_rpc::{Module, Project};
 
+use crate::checkpoint_watermark_layer::CheckpointWatermarkLayer;
 use crate::error::Error;
 use crate::metrics::MetricsLogger;
 use crate::routing_layer::RoutingLayer;
 
 pub mod api;
 mod balance_changes;
+mod checkpoint_watermark_layer;
 pub mod coin_api;
 pub mod error;
 pub mod governance_api;
@@ -35,6 +39,7 @@ pub mod move_utils;
 mod object_changes;
 pub mod read_api;
 mod routing_layer;
+pub mod simulation_cache;
 pub mod transaction_builder_api;
 pub mod transaction_execution_api;
 
@@ -45,6 +50,9 @@ pub const CLIENT_SDK_VERSION_HEADER: &str = "client-sdk-version";
 /// API version.
 pub const CLIENT_TARGET_API_VERSION_HEADER: &str = "client-target-api-version";
 pub const APP_NAME_HEADER: &str = "app-name";
+/// The checkpoint the node must have executed before serving the request, to read the writes of
+/// a transaction executed before, as returned in its `checkpointWatermark`.
+pub const MIN_CHECKPOINT_WATERMARK_HEADER: &str = "sui-min-checkpoint-watermark";
 
 pub const MAX_REQUEST_SIZE: u32 = 2 << 30;
 
@@ -54,11 +62,15 @@ mod rpc_server_test;
 #[cfg(test)]
 #[path = "unit_tests/transaction_tests.rs"]
 mod transaction_tests;
+#[cfg(test)]
+#[path = "unit_tests/simulation_cache_tests.rs"]
+mod simulation_cache_tests;
 
 pub struct JsonRpcServerBuilder {
     module: RpcModule<()>,
     rpc_doc: Project,
     registry: Registry,
+    checkpoint_watermark_state: Option<Arc<AuthorityState>>,
 }
 
 pub fn sui_rpc_doc(version: &str) -> Project {
@@ -80,9 +92,15 @@ impl JsonRpcServerBuilder {
             module: RpcModule::new(()),
             rpc_doc: sui_rpc_doc(version),
             registry: prometheus_registry.clone(),
+            checkpoint_watermark_state: None,
         }
     }
 
+    /// Serves the requests carrying a checkpoint watermark only once the node has executed it.
+    pub fn enable_checkpoint_watermark(&mut self, state: Arc<AuthorityState>) {
+        self.checkpoint_watermark_state = Some(state);
+    }
+
     pub fn register_module<T: SuiRpcModule>(&mut self, module: T) -> Result<(), Error> {
         self.rpc_doc.add_module(T::rpc_doc_module());
         Ok(self.module.merge(module.rpc())?)
@@ -113,6 +131,7 @@ impl JsonRpcServerBuilder {
                 HeaderName::from_static(CLIENT_SDK_VERSION_HEADER),
                 HeaderName::from_static(CLIENT_TARGET_API_VERSION_HEADER),
                 HeaderName::from_static(APP_NAME_HEADER),
+                HeaderName::from_static(MIN_CHECKPOINT_WATERMARK_HEADER),
             ]);
 
         let routing = self.rpc_doc.method_routing.clone();
@@ -147,9 +166,13 @@ impl JsonRpcServerBuilder {
         // We need to use the routing layer to block access to the old methods when routing is disabled.
         let routing_layer = RoutingLayer::new(routing, disable_routing);
 
+        let checkpoint_watermark_layer =
+            CheckpointWatermarkLayer::new(self.checkpoint_watermark_state.clone());
+
         let middleware = tower::ServiceBuilder::new()
             .layer(cors)
-            .layer(routing_layer);
+            .layer(routing_layer)
+            .layer(checkpoint_watermark_layer);
 
         let server = ServerBuilder::default()
             .batch_requests_supported(false)
@@ -6,6 +6,7 @@ use crate::read_api::{get_move_module, get_move_modules_by_package};
 use crate::SuiRpcModule;
 use anyhow::anyhow;
 use async_trait::async_trait;
+use fastcrypto::encoding::Base64;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::RpcModule;
 use move_binary_format::file_format_common::VERSION_MAX;
@@ -16,11 +17,11 @@ use std::sync::Arc;
 use sui_core::authority::AuthorityState;
 use sui_json_rpc_types::{
     MoveFunctionArgType, ObjectValueKind, SuiMoveNormalizedFunction, SuiMoveNormalizedModule,
-    SuiMoveNormalizedStruct,
+    SuiMoveNormalizedStruct, SuiPackageUpgradeReport,
 };
 use sui_open_rpc::Module;
 use sui_types::base_types::ObjectID;
-use sui_types::move_package::normalize_modules;
+use sui_types::move_package::{normalize_modules, UpgradePolicy};
 use sui_types::object::{Data, ObjectRead};
 
 pub struct MoveUtils {
@@ -158,4 +159,26 @@ impl MoveUtilsServer for MoveUtils {
             None => Err(anyhow!("No parameters found for function {}", function)),
         }?)
     }
+
+    async fn check_package_upgrade(
+        &self,
+        package: ObjectID,
+        compiled_modules: Vec<Base64>,
+        dependencies: Vec<ObjectID>,
+        policy: Option<u8>,
+    ) -> RpcResult<SuiPackageUpgradeReport> {
+        let compiled_modules = compiled_modules
+            .into_iter()
+            .map(|data| data.to_vec().map_err(|e| anyhow!(e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self
+            .state
+            .check_package_upgrade(
+                package,
+                compiled_modules,
+                dependencies,
+                policy.unwrap_or(UpgradePolicy::COMPATIBLE),
+            )
+            .map_err(|e| anyhow!("{e}"))?)
+    }
 }
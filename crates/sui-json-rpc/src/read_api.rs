@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
 use std::sync::Arc;
 
 use anyhow::anyhow;
@@ -16,15 +17,18 @@ use move_binary_format::{file_format_common::VERSION_MAX, normalized::Module as
 use move_bytecode_utils::module_cache::GetModule;
 use move_core_types::language_storage::StructTag;
 use move_core_types::value::{MoveStruct, MoveStructLayout, MoveValue};
+use mysten_common::memory_budget::{self, MemoryAccount, MemoryReservation};
 use tap::TapFallible;
 use tracing::{debug, error, warn};
 
 use shared_crypto::intent::{AppId, Intent, IntentMessage, IntentScope, IntentVersion};
 use sui_core::authority::AuthorityState;
 use sui_json_rpc_types::{
-    BalanceChange, BigInt, Checkpoint, CheckpointId, CheckpointPage, EventFilter, ObjectChange,
-    SuiCheckpointSequenceNumber, SuiEvent, SuiGetPastObjectRequest, SuiMoveStruct, SuiMoveValue,
-    SuiObjectDataOptions, SuiObjectResponse, SuiPastObjectResponse, SuiTransactionBlock,
+    BalanceChange, BigInt, Checkpoint, CheckpointId, CheckpointObjectChange,
+    CheckpointObjectChangeKind, CheckpointObjectChangePage, CheckpointPage, CheckpointProof,
+    EventFilter, FullCheckpoint, ObjectChange, SuiCheckpointSequenceNumber, SuiEvent,
+    SuiGetPastObjectRequest, SuiMoveStruct, SuiMoveValue, SuiObjectDataOptions, SuiObjectResponse,
+    SuiObjectVersionConflict, SuiObjectsAtCheckpoint, SuiPastObjectResponse, SuiTransactionBlock,
     SuiTransactionBlockEvents, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
 };
 use sui_open_rpc::Module;
@@ -44,7 +48,10 @@ use sui_types::move_package::normalize_modules;
 use sui_types::object::{Data, Object, ObjectRead, PastObjectRead};
 
 use crate::api::{validate_limit, ReadApiServer};
-use crate::api::{QUERY_MAX_RESULT_LIMIT, QUERY_MAX_RESULT_LIMIT_CHECKPOINTS};
+use crate::api::{
+    QUERY_MAX_CHECKPOINTS_OBJECT_CHANGES, QUERY_MAX_RESULT_LIMIT,
+    QUERY_MAX_RESULT_LIMIT_CHECKPOINTS,
+};
 use crate::error::Error;
 use crate::{
     get_balance_changes_from_effect, get_object_changes, ObjectProviderCache, SuiRpcModule,
@@ -55,8 +62,13 @@ const MAX_DISPLAY_NESTED_LEVEL: usize = 10;
 // An implementation of the read portion of the JSON-RPC interface intended for use in
 // Fullnodes.
 #[derive(Clone)]
+/// The memory estimated to be held by an item of a response, while the response is built.
+const ESTIMATED_RESPONSE_ITEM_SIZE: usize = 4 * 1024;
+
 pub struct ReadApi {
     pub state: Arc<AuthorityState>,
+    // The account of the responses being built with the memory budget of the node.
+    response_memory: MemoryAccount,
 }
 
 // Internal data structure to make it easy to work with data returned from
@@ -86,7 +98,18 @@ impl IntermediateTransactionResponse {
 
 impl ReadApi {
     pub fn new(state: Arc<AuthorityState>) -> Self {
-        Self { state }
+        Self {
+            state,
+            response_memory: memory_budget::global().register("json-rpc-responses"),
+        }
+    }
+
+    /// Reserves the memory of a response of `items` items while it is built, unless the memory
+    /// budget of the node is exceeded.
+    fn reserve_response(&self, items: usize) -> Result<MemoryReservation, Error> {
+        self.response_memory
+            .try_reserve(items * ESTIMATED_RESPONSE_ITEM_SIZE)
+            .ok_or(Error::MemoryBudgetExceeded)
     }
 
     fn get_checkpoint_internal(&self, id: CheckpointId) -> Result<Checkpoint, Error> {
@@ -105,6 +128,43 @@ impl ReadApi {
             }
         })
     }
+
+    fn get_checkpoint_proof_internal(&self, id: CheckpointId) -> Result<CheckpointProof, Error> {
+        let checkpoint = match id.clone() {
+            CheckpointId::SequenceNumber(seq) => {
+                self.state.get_checkpoint_by_sequence_number(seq.into())?
+            }
+            CheckpointId::Digest(digest) => self.state.get_checkpoint_by_digest(&digest)?,
+        }
+        .ok_or_else(|| anyhow!("Verified checkpoint not found for {id:?}"))?;
+        let contents = self
+            .state
+            .get_checkpoint_contents(checkpoint.content_digest)?;
+        let committee = self
+            .state
+            .committee_store()
+            .get_committee(&checkpoint.epoch)?
+            .ok_or_else(|| anyhow!("Committee not found for epoch {}", checkpoint.epoch))?;
+        Ok(CheckpointProof::new(
+            checkpoint.into_inner(),
+            &contents,
+            committee.as_ref().clone(),
+        )?)
+    }
+
+    fn get_full_checkpoint_internal(&self, id: CheckpointId) -> Result<FullCheckpoint, Error> {
+        let checkpoint = match id.clone() {
+            CheckpointId::SequenceNumber(seq) => {
+                self.state.get_checkpoint_by_sequence_number(seq.into())?
+            }
+            CheckpointId::Digest(digest) => self.state.get_checkpoint_by_digest(&digest)?,
+        }
+        .ok_or_else(|| anyhow!("Verified checkpoint not found for {id:?}"))?;
+        let contents = self
+            .state
+            .get_full_checkpoint_contents(checkpoint.content_digest)?;
+        Ok(FullCheckpoint::new(checkpoint.inner(), &contents)?)
+    }
 }
 
 #[async_trait]
@@ -150,6 +210,7 @@ impl ReadApiServer for ReadApi {
         options: Option<SuiObjectDataOptions>,
     ) -> RpcResult<Vec<SuiObjectResponse>> {
         if object_ids.len() <= QUERY_MAX_RESULT_LIMIT {
+            let _memory = self.reserve_response(object_ids.len())?;
             let mut futures = vec![];
             for object_id in object_ids {
                 futures.push(self.get_object(object_id, options.clone()))
@@ -192,35 +253,7 @@ impl ReadApiServer for ReadApi {
                 error!("Failed to call try_get_past_object for object: {object_id:?} version: {version:?} with error: {e:?}");
                 anyhow!("{e}")
             })?;
-        let options = options.unwrap_or_default();
-        match past_read {
-            PastObjectRead::ObjectNotExists(id) => Ok(SuiPastObjectResponse::ObjectNotExists(id)),
-            PastObjectRead::VersionFound(object_ref, o, layout) => {
-                let display_fields = if options.show_display {
-                    get_display_fields(self, &o, &layout).await?
-                } else {
-                    None
-                };
-                Ok(SuiPastObjectResponse::VersionFound(
-                    (object_ref, o, layout, options, display_fields).try_into()?,
-                ))
-            }
-            PastObjectRead::ObjectDeleted(oref) => {
-                Ok(SuiPastObjectResponse::ObjectDeleted(oref.into()))
-            }
-            PastObjectRead::VersionNotFound(id, seq_num) => {
-                Ok(SuiPastObjectResponse::VersionNotFound(id, seq_num))
-            }
-            PastObjectRead::VersionTooHigh {
-                object_id,
-                asked_version,
-                latest_version,
-            } => Ok(SuiPastObjectResponse::VersionTooHigh {
-                object_id,
-                asked_version,
-                latest_version,
-            }),
-        }
+        to_past_object_response(self, past_read, options.unwrap_or_default()).await
     }
 
     async fn try_multi_get_past_objects(
@@ -229,6 +262,7 @@ impl ReadApiServer for ReadApi {
         options: Option<SuiObjectDataOptions>,
     ) -> RpcResult<Vec<SuiPastObjectResponse>> {
         if past_objects.len() <= QUERY_MAX_RESULT_LIMIT {
+            let _memory = self.reserve_response(past_objects.len())?;
             let mut futures = vec![];
             for past_object in past_objects {
                 futures.push(self.try_get_past_object(
@@ -260,6 +294,66 @@ impl ReadApiServer for ReadApi {
         }
     }
 
+    async fn get_object_version_conflict(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> RpcResult<SuiObjectVersionConflict> {
+        Ok(self
+            .state
+            .get_object_version_conflict(object_id, version)
+            .map_err(|e| anyhow!("{e}"))?)
+    }
+
+    async fn multi_get_objects_at_checkpoint(
+        &self,
+        object_ids: Vec<ObjectID>,
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
+        options: Option<SuiObjectDataOptions>,
+    ) -> RpcResult<SuiObjectsAtCheckpoint> {
+        if object_ids.len() > QUERY_MAX_RESULT_LIMIT {
+            return Err(anyhow!(UserInputError::SizeLimitExceeded {
+                limit: "input limit".to_string(),
+                value: QUERY_MAX_RESULT_LIMIT.to_string()
+            })
+            .into());
+        }
+        let latest_checkpoint =
+            self.state
+                .get_latest_checkpoint_sequence_number()
+                .map_err(|e| {
+                    anyhow!("Latest checkpoint sequence number was not found with error :{e}")
+                })?;
+        let checkpoint = match checkpoint.map(<u64>::from) {
+            Some(checkpoint) if checkpoint > latest_checkpoint => {
+                return Err(anyhow!(
+                    "Checkpoint {checkpoint} is not executed yet, the latest executed checkpoint \
+                    is {latest_checkpoint}"
+                )
+                .into())
+            }
+            Some(checkpoint) => checkpoint,
+            None => latest_checkpoint,
+        };
+
+        let options = options.unwrap_or_default();
+        let mut objects = Vec::with_capacity(object_ids.len());
+        for object_id in object_ids {
+            let past_read = self
+                .state
+                .get_object_read_at_checkpoint(&object_id, checkpoint)
+                .map_err(|e| {
+                    error!("Failed to read object {object_id:?} at checkpoint {checkpoint} with error: {e:?}");
+                    anyhow!("{e}")
+                })?;
+            objects.push(to_past_object_response(self, past_read, options.clone()).await?);
+        }
+        Ok(SuiObjectsAtCheckpoint {
+            checkpoint: checkpoint.into(),
+            objects,
+        })
+    }
+
     async fn get_total_transaction_blocks(&self) -> RpcResult<BigInt> {
         Ok(self.state.get_total_transaction_blocks()?.into())
     }
@@ -375,6 +469,7 @@ impl ReadApiServer for ReadApi {
             })
             .into());
         }
+        let _memory = self.reserve_response(num_digests)?;
 
         let opts = opts.unwrap_or_default();
 
@@ -640,6 +735,14 @@ impl ReadApiServer for ReadApi {
         Ok(self.get_checkpoint_internal(id)?)
     }
 
+    async fn get_checkpoint_proof(&self, id: CheckpointId) -> RpcResult<CheckpointProof> {
+        Ok(self.get_checkpoint_proof_internal(id)?)
+    }
+
+    async fn get_full_checkpoint(&self, id: CheckpointId) -> RpcResult<FullCheckpoint> {
+        Ok(self.get_full_checkpoint_internal(id)?)
+    }
+
     async fn get_checkpoints(
         &self,
         // If `Some`, the query will start from the next item after the specified cursor
@@ -670,6 +773,77 @@ impl ReadApiServer for ReadApi {
             has_next_page,
         })
     }
+
+    async fn get_object_changes_between_checkpoints(
+        &self,
+        from_checkpoint: SuiCheckpointSequenceNumber,
+        to_checkpoint: SuiCheckpointSequenceNumber,
+        // If `Some`, the query will start from the next object after the specified cursor
+        cursor: Option<ObjectID>,
+        limit: Option<usize>,
+    ) -> RpcResult<CheckpointObjectChangePage> {
+        let limit = validate_limit(limit, QUERY_MAX_RESULT_LIMIT)?;
+        let (from, to) = (<u64>::from(from_checkpoint), <u64>::from(to_checkpoint));
+        if from > to {
+            return Err(anyhow!("Checkpoint {from} is after checkpoint {to}").into());
+        }
+        if to - from > QUERY_MAX_CHECKPOINTS_OBJECT_CHANGES {
+            return Err(anyhow!(UserInputError::SizeLimitExceeded {
+                limit: "checkpoints range".to_string(),
+                value: QUERY_MAX_CHECKPOINTS_OBJECT_CHANGES.to_string()
+            })
+            .into());
+        }
+        let latest_checkpoint = self.state.get_latest_checkpoint_sequence_number()?;
+        if to > latest_checkpoint {
+            return Err(anyhow!(
+                "Checkpoint {to} is not executed yet, the latest executed checkpoint is \
+                {latest_checkpoint}"
+            )
+            .into());
+        }
+
+        let changes = self
+            .state
+            .get_object_changes_between_checkpoints(from, to)?;
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(cursor),
+            None => Bound::Unbounded,
+        };
+        let mut data = changes
+            .range((start, Bound::Unbounded))
+            .filter_map(|(object_id, (object_ref, existed))| {
+                let kind = match (*existed, object_ref.2.is_alive()) {
+                    (false, true) => CheckpointObjectChangeKind::Created,
+                    (true, true) => CheckpointObjectChangeKind::Mutated,
+                    (true, false) => CheckpointObjectChangeKind::Deleted,
+                    // The object was created then deleted in between.
+                    (false, false) => return None,
+                };
+                Some(CheckpointObjectChange {
+                    object_id: *object_id,
+                    kind,
+                    version: object_ref.1,
+                    digest: object_ref.2,
+                })
+            })
+            .take(limit + 1)
+            .collect::<Vec<_>>();
+
+        let has_next_page = data.len() > limit;
+        data.truncate(limit);
+        let next_cursor = if has_next_page {
+            data.last().map(|change| change.object_id)
+        } else {
+            None
+        };
+
+        Ok(CheckpointObjectChangePage {
+            data,
+            next_cursor,
+            has_next_page,
+        })
+    }
 }
 
 impl SuiRpcModule for ReadApi {
@@ -705,6 +879,41 @@ fn to_sui_transaction_events(
     .map_err(Error::SuiError)?)
 }
 
+async fn to_past_object_response(
+    fullnode_api: &ReadApi,
+    past_read: PastObjectRead,
+    options: SuiObjectDataOptions,
+) -> RpcResult<SuiPastObjectResponse> {
+    match past_read {
+        PastObjectRead::ObjectNotExists(id) => Ok(SuiPastObjectResponse::ObjectNotExists(id)),
+        PastObjectRead::VersionFound(object_ref, o, layout) => {
+            let display_fields = if options.show_display {
+                get_display_fields(fullnode_api, &o, &layout).await?
+            } else {
+                None
+            };
+            Ok(SuiPastObjectResponse::VersionFound(
+                (object_ref, o, layout, options, display_fields).try_into()?,
+            ))
+        }
+        PastObjectRead::ObjectDeleted(oref) => {
+            Ok(SuiPastObjectResponse::ObjectDeleted(oref.into()))
+        }
+        PastObjectRead::VersionNotFound(id, seq_num) => {
+            Ok(SuiPastObjectResponse::VersionNotFound(id, seq_num))
+        }
+        PastObjectRead::VersionTooHigh {
+            object_id,
+            asked_version,
+            latest_version,
+        } => Ok(SuiPastObjectResponse::VersionTooHigh {
+            object_id,
+            asked_version,
+            latest_version,
+        }),
+    }
+}
+
 async fn get_display_fields(
     fullnode_api: &ReadApi,
     original_object: &Object,
@@ -0,0 +1,73 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use lru::LruCache;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+
+/// The number of simulation results cached by default.
+pub const DEFAULT_SIMULATION_CACHE_CAPACITY: usize = 10_000;
+
+type RequestDigest = [u8; 32];
+
+struct Inner<V> {
+    /// The checkpoint the cached results were computed at.
+    checkpoint: CheckpointSequenceNumber,
+    results: LruCache<RequestDigest, V>,
+}
+
+/// Caches the results of the simulated transactions (dev inspect and dry run) by request, until
+/// a new checkpoint is executed. The dapps poll the same simulations many times, while their
+/// results only change with the objects they read.
+pub struct SimulationCache<V> {
+    inner: Mutex<Inner<V>>,
+}
+
+impl<V: Clone> SimulationCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                checkpoint: 0,
+                results: LruCache::new(NonZeroUsize::new(capacity.max(1)).unwrap()),
+            }),
+        }
+    }
+
+    /// The result of the request computed at the given checkpoint, if cached. The results of
+    /// the older checkpoints are dropped once a newer checkpoint is seen.
+    pub fn get(&self, request: &[u8], checkpoint: CheckpointSequenceNumber) -> Option<V> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.advance(checkpoint);
+        if inner.checkpoint != checkpoint {
+            return None;
+        }
+        inner.results.get(&digest(request)).cloned()
+    }
+
+    /// Caches the result of the request computed at the given checkpoint, unless a newer
+    /// checkpoint was seen meanwhile.
+    pub fn insert(&self, request: &[u8], checkpoint: CheckpointSequenceNumber, result: V) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.advance(checkpoint);
+        if inner.checkpoint == checkpoint {
+            inner.results.put(digest(request), result);
+        }
+    }
+}
+
+/// The digest of the request, as the key of its result.
+fn digest(request: &[u8]) -> RequestDigest {
+    Blake2b256::digest(request).digest
+}
+
+impl<V> Inner<V> {
+    fn advance(&mut self, checkpoint: CheckpointSequenceNumber) {
+        if checkpoint > self.checkpoint {
+            self.checkpoint = checkpoint;
+            self.results.clear();
+        }
+    }
+}
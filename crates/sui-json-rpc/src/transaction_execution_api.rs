@@ -16,8 +16,9 @@ use sui_core::authority::AuthorityState;
 use sui_core::authority_client::NetworkAuthorityClient;
 use sui_core::transaction_orchestrator::TransactiondOrchestrator;
 use sui_json_rpc_types::{
-    BigInt, DevInspectResults, DryRunTransactionBlockResponse, SuiTransactionBlock,
-    SuiTransactionBlockEvents, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    BigInt, DevInspectResults, DryRunTransactionBlockResponse, SuiCheckpointSequenceNumber,
+    SuiTransactionBlock, SuiTransactionBlockEvents, SuiTransactionBlockResponse,
+    SuiTransactionBlockResponseOptions,
 };
 use sui_open_rpc::Module;
 use sui_types::base_types::{EpochId, SuiAddress};
@@ -25,6 +26,7 @@ use sui_types::messages::{
     ExecuteTransactionRequest, ExecuteTransactionRequestType, TransactionEffectsAPI,
     TransactionKind,
 };
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 
 use sui_types::messages::{ExecuteTransactionResponse, Transaction};
 use sui_types::messages::{TransactionData, TransactionDataAPI};
@@ -33,6 +35,7 @@ use sui_types::signature::GenericSignature;
 use crate::api::WriteApiServer;
 use crate::error::Error;
 use crate::read_api::get_transaction_data_and_digest;
+use crate::simulation_cache::{SimulationCache, DEFAULT_SIMULATION_CACHE_CAPACITY};
 use crate::{
     get_balance_changes_from_effect, get_object_changes, ObjectProviderCache, SuiRpcModule,
 };
@@ -40,6 +43,8 @@ use crate::{
 pub struct TransactionExecutionApi {
     state: Arc<AuthorityState>,
     transaction_orchestrator: Arc<TransactiondOrchestrator<NetworkAuthorityClient>>,
+    dev_inspect_cache: SimulationCache<DevInspectResults>,
+    dry_run_cache: SimulationCache<DryRunTransactionBlockResponse>,
 }
 impl TransactionExecutionApi {
     pub fn new(
@@ -49,6 +54,8 @@ impl TransactionExecutionApi {
         Self {
             state,
             transaction_orchestrator,
+            dev_inspect_cache: SimulationCache::new(DEFAULT_SIMULATION_CACHE_CAPACITY),
+            dry_run_cache: SimulationCache::new(DEFAULT_SIMULATION_CACHE_CAPACITY),
         }
     }
 
@@ -147,6 +154,7 @@ impl TransactionExecutionApi {
                     timestamp_ms: None,
                     confirmed_local_execution: Some(is_executed_locally),
                     checkpoint: None,
+                    checkpoint_watermark: self.state.get_latest_checkpoint_sequence_number().ok(),
                     errors: vec![],
                 })
             }
@@ -156,12 +164,33 @@ impl TransactionExecutionApi {
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        at_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> Result<DryRunTransactionBlockResponse, Error> {
+        let request = tx_bytes.to_vec()?;
+        // The runs against a past checkpoint are not cached, the cache is for the latest state.
+        let checkpoint = match at_checkpoint {
+            Some(_) => None,
+            None => self.state.get_latest_checkpoint_sequence_number().ok(),
+        };
+        if let Some(response) =
+            checkpoint.and_then(|checkpoint| self.dry_run_cache.get(&request, checkpoint))
+        {
+            return Ok(response);
+        }
+
         let (txn_data, txn_digest) = get_transaction_data_and_digest(tx_bytes)?;
-        let (resp, written_objects, transaction_effects) = self
-            .state
-            .dry_exec_transaction(txn_data.clone(), txn_digest)
-            .await?;
+        let (resp, written_objects, transaction_effects) = match at_checkpoint {
+            Some(at_checkpoint) => {
+                self.state
+                    .dry_exec_transaction_at_checkpoint(txn_data.clone(), txn_digest, at_checkpoint)
+                    .await?
+            }
+            None => {
+                self.state
+                    .dry_exec_transaction(txn_data.clone(), txn_digest)
+                    .await?
+            }
+        };
         let object_cache = ObjectProviderCache::new_with_cache(self.state.clone(), written_objects);
         let balance_changes =
             get_balance_changes_from_effect(&object_cache, &transaction_effects).await?;
@@ -174,12 +203,62 @@ impl TransactionExecutionApi {
         )
         .await?;
 
-        Ok(DryRunTransactionBlockResponse {
+        let response = DryRunTransactionBlockResponse {
             effects: resp.effects,
             events: resp.events,
             object_changes,
             balance_changes,
-        })
+            written_objects: resp.written_objects,
+        };
+        if let Some(checkpoint) = checkpoint {
+            self.dry_run_cache
+                .insert(&request, checkpoint, response.clone());
+        }
+        Ok(response)
+    }
+
+    async fn dev_inspect_transaction_block(
+        &self,
+        sender_address: SuiAddress,
+        tx_bytes: Base64,
+        gas_price: Option<u64>,
+        at_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> Result<DevInspectResults, Error> {
+        let tx_bytes = tx_bytes.to_vec().map_err(|e| anyhow!(e))?;
+        let request = bcs::to_bytes(&(sender_address, &tx_bytes, gas_price))?;
+        let checkpoint = match at_checkpoint {
+            Some(_) => None,
+            None => self.state.get_latest_checkpoint_sequence_number().ok(),
+        };
+        if let Some(results) =
+            checkpoint.and_then(|checkpoint| self.dev_inspect_cache.get(&request, checkpoint))
+        {
+            return Ok(results);
+        }
+
+        let tx_kind: TransactionKind = bcs::from_bytes(&tx_bytes).map_err(|e| anyhow!(e))?;
+        let results = match at_checkpoint {
+            Some(at_checkpoint) => {
+                self.state
+                    .dev_inspect_transaction_block_at_checkpoint(
+                        sender_address,
+                        tx_kind,
+                        gas_price,
+                        at_checkpoint,
+                    )
+                    .await?
+            }
+            None => {
+                self.state
+                    .dev_inspect_transaction_block(sender_address, tx_kind, gas_price)
+                    .await?
+            }
+        };
+        if let Some(checkpoint) = checkpoint {
+            self.dev_inspect_cache
+                .insert(&request, checkpoint, results.clone());
+        }
+        Ok(results)
     }
 }
 
@@ -203,20 +282,26 @@ impl WriteApiServer for TransactionExecutionApi {
         tx_bytes: Base64,
         gas_price: Option<BigInt>,
         _epoch: Option<EpochId>,
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
     ) -> RpcResult<DevInspectResults> {
-        let tx_kind: TransactionKind =
-            bcs::from_bytes(&tx_bytes.to_vec().map_err(|e| anyhow!(e))?).map_err(|e| anyhow!(e))?;
         Ok(self
-            .state
-            .dev_inspect_transaction_block(sender_address, tx_kind, gas_price.map(<u64>::from))
+            .dev_inspect_transaction_block(
+                sender_address,
+                tx_bytes,
+                gas_price.map(<u64>::from),
+                checkpoint.map(<u64>::from),
+            )
             .await?)
     }
 
     async fn dry_run_transaction_block(
         &self,
         tx_bytes: Base64,
+        checkpoint: Option<SuiCheckpointSequenceNumber>,
     ) -> RpcResult<DryRunTransactionBlockResponse> {
-        Ok(self.dry_run_transaction_block(tx_bytes).await?)
+        Ok(self
+            .dry_run_transaction_block(tx_bytes, checkpoint.map(<u64>::from))
+            .await?)
     }
 }
 
@@ -5,6 +5,8 @@ use crate::api::{
     CoinReadApiClient, GovernanceReadApiClient, IndexerApiClient, ReadApiClient,
     TransactionBuilderClient, WriteApiClient,
 };
+use futures::StreamExt;
+use jsonrpsee::core::client::Subscription;
 use std::path::Path;
 #[cfg(not(msim))]
 use std::str::FromStr;
@@ -17,10 +19,12 @@ use sui_json::{call_args, type_args};
 use sui_json_rpc_types::ObjectChange;
 use sui_json_rpc_types::ObjectsPage;
 use sui_json_rpc_types::{
-    Balance, CoinPage, DelegatedStake, StakeStatus, SuiCoinMetadata, SuiExecutionStatus,
-    SuiObjectDataOptions, SuiObjectResponse, SuiObjectResponseQuery, SuiTransactionBlockEffectsAPI,
+    Balance, CheckpointObjectChange, CheckpointObjectChangeKind, CoinPage, DelegatedStake,
+    StakeStatus, SuiCoinMetadata, SuiExecutionStatus, SuiObjectDataOptions, SuiObjectResponse,
+    SuiObjectResponseQuery, SuiObjectsAtCheckpoint, SuiTransactionBlockEffectsAPI,
     SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions, TransactionBlockBytes,
 };
+use sui_json_rpc_types::{ObjectChangeFilter, ObjectChangeNotification};
 use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
 use sui_macros::sim_test;
 use sui_types::balance::Supply;
@@ -113,7 +117,9 @@ async fn test_public_transfer_object() -> Result<(), anyhow::Error> {
     let tx = to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(address)?);
     let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
     let tx_bytes1 = tx_bytes.clone();
-    let dryrun_response = http_client.dry_run_transaction_block(tx_bytes).await?;
+    let dryrun_response = http_client
+        .dry_run_transaction_block(tx_bytes, None)
+        .await?;
 
     let tx_response: SuiTransactionBlockResponse = http_client
         .execute_transaction_block(
@@ -141,6 +147,250 @@ async fn test_public_transfer_object() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_multi_get_objects_at_checkpoint() -> Result<(), anyhow::Error> {
+    let cluster = TestClusterBuilder::new().build().await?;
+    let http_client = cluster.rpc_client();
+    let address = cluster.accounts.first().unwrap();
+
+    let objects = http_client
+        .get_owned_objects(*address, None, None, None)
+        .await?
+        .data;
+    let object_ids: Vec<_> = objects
+        .iter()
+        .map(|o| o.object().unwrap().object_id)
+        .collect();
+    let before = http_client
+        .multi_get_objects_at_checkpoint(object_ids.clone(), None, None)
+        .await?;
+    assert_eq!(before.objects.len(), object_ids.len());
+
+    // Transfer an object, which writes new versions of it and of the gas.
+    let obj = object_ids[0];
+    let gas = *object_ids.last().unwrap();
+    let transaction_bytes: TransactionBlockBytes = http_client
+        .transfer_object(*address, obj, Some(gas), 1000, *address)
+        .await?;
+    let keystore_path = cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+    let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+    let tx = to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(address)?);
+    let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+    http_client
+        .execute_transaction_block(
+            tx_bytes,
+            signatures,
+            None,
+            Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+        )
+        .await?;
+
+    // The objects are read at their newer versions once the transaction is checkpointed.
+    let after = loop {
+        let after = http_client
+            .multi_get_objects_at_checkpoint(object_ids.clone(), None, None)
+            .await?;
+        if after.objects[0] != before.objects[0] {
+            break after;
+        }
+        sleep(Duration::from_millis(500)).await;
+    };
+    assert!(<u64>::from(after.checkpoint) > <u64>::from(before.checkpoint));
+    let version =
+        |read: &SuiObjectsAtCheckpoint, i: usize| read.objects[i].object().unwrap().version;
+    assert!(version(&after, 0) > version(&before, 0));
+    assert!(version(&after, object_ids.len() - 1) > version(&before, object_ids.len() - 1));
+
+    // Reading as of the first checkpoint still gives the older versions.
+    let again = http_client
+        .multi_get_objects_at_checkpoint(object_ids.clone(), Some(before.checkpoint), None)
+        .await?;
+    assert_eq!(again, before);
+
+    // The checkpoints not executed yet are refused.
+    let future_checkpoint = (<u64>::from(after.checkpoint) + 1_000_000).into();
+    assert!(http_client
+        .multi_get_objects_at_checkpoint(object_ids, Some(future_checkpoint), None)
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[sim_test]
+async fn test_get_object_changes_between_checkpoints() -> Result<(), anyhow::Error> {
+    let cluster = TestClusterBuilder::new().build().await?;
+    let http_client = cluster.rpc_client();
+    let address = cluster.accounts.first().unwrap();
+
+    let objects = http_client
+        .get_owned_objects(*address, None, None, None)
+        .await?
+        .data;
+    let obj = objects.first().unwrap().object().unwrap().object_id;
+    let gas = objects.last().unwrap().object().unwrap().object_id;
+    let transaction_bytes: TransactionBlockBytes = http_client
+        .transfer_object(*address, obj, Some(gas), 1000, *address)
+        .await?;
+    let keystore_path = cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+    let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+    let tx = to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(address)?);
+    let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+    let response = http_client
+        .execute_transaction_block(
+            tx_bytes,
+            signatures,
+            Some(SuiTransactionBlockResponseOptions::new().with_effects()),
+            Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+        )
+        .await?;
+    let effects = response.effects.unwrap();
+
+    let checkpoint = loop {
+        let checkpoint = http_client
+            .get_transaction_block(response.digest, None)
+            .await?
+            .checkpoint;
+        if let Some(checkpoint) = checkpoint {
+            if <u64>::from(http_client.get_latest_checkpoint_sequence_number().await?) >= checkpoint
+            {
+                break checkpoint;
+            }
+        }
+        sleep(Duration::from_millis(500)).await;
+    };
+
+    // Both objects are mutated by the checkpoint, they are returned one page each.
+    let (from, to) = ((checkpoint - 1).into(), checkpoint.into());
+    let mut changes = vec![];
+    let mut cursor = None;
+    loop {
+        let page = http_client
+            .get_object_changes_between_checkpoints(from, to, cursor, Some(1))
+            .await?;
+        assert!(page.data.len() <= 1);
+        changes.extend(page.data);
+        if !page.has_next_page {
+            break;
+        }
+        cursor = page.next_cursor;
+    }
+    let mut expected: Vec<_> = effects
+        .mutated()
+        .iter()
+        .map(|o| CheckpointObjectChange {
+            object_id: o.reference.object_id,
+            kind: CheckpointObjectChangeKind::Mutated,
+            version: o.reference.version,
+            digest: o.reference.digest,
+        })
+        .collect();
+    expected.sort_by_key(|change| change.object_id);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes, expected);
+
+    // The checkpoints not executed yet are refused.
+    let future_checkpoint = (checkpoint + 1_000_000).into();
+    assert!(http_client
+        .get_object_changes_between_checkpoints(from, future_checkpoint, None, None)
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[sim_test]
+async fn test_subscribe_finalized_transaction() -> Result<(), anyhow::Error> {
+    let cluster = TestClusterBuilder::new().build().await?;
+    let http_client = cluster.rpc_client();
+    let address = cluster.accounts.first().unwrap();
+    let from_checkpoint = http_client.get_latest_checkpoint_sequence_number().await?;
+    let mut subscription: Subscription<SuiTransactionBlockResponse> = cluster
+        .fullnode_handle
+        .ws_client
+        .subscribe_finalized_transaction(Some(from_checkpoint), None)
+        .await?;
+
+    let objects = http_client
+        .get_owned_objects(*address, None, None, None)
+        .await?
+        .data;
+    let obj = objects.first().unwrap().object().unwrap().object_id;
+    let gas = objects.last().unwrap().object().unwrap().object_id;
+    let transaction_bytes: TransactionBlockBytes = http_client
+        .transfer_object(*address, obj, Some(gas), 1000, *address)
+        .await?;
+    let keystore_path = cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+    let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+    let tx = to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(address)?);
+    let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+    let response = http_client
+        .execute_transaction_block(
+            tx_bytes,
+            signatures,
+            None,
+            Some(ExecuteTransactionRequestType::WaitForEffectsCert),
+        )
+        .await?;
+
+    // The transaction is streamed once its checkpoint is executed, with the checkpoint.
+    let finalized = loop {
+        let finalized = subscription.next().await.unwrap()?;
+        if finalized.digest == response.digest {
+            break finalized;
+        }
+    };
+    let checkpoint = finalized.checkpoint.unwrap();
+    assert!(finalized.timestamp_ms.is_some());
+    let latest_checkpoint = http_client.get_latest_checkpoint_sequence_number().await?;
+    assert!(<u64>::from(latest_checkpoint) >= checkpoint);
+    Ok(())
+}
+
+#[sim_test]
+async fn test_subscribe_object_change() -> Result<(), anyhow::Error> {
+    let cluster = TestClusterBuilder::new().build().await?;
+    let http_client = cluster.rpc_client();
+    let address = cluster.accounts.first().unwrap();
+    let recipient = cluster.accounts.last().unwrap();
+    let from_checkpoint = http_client.get_latest_checkpoint_sequence_number().await?;
+    let mut subscription: Subscription<ObjectChangeNotification> = cluster
+        .fullnode_handle
+        .ws_client
+        .subscribe_object_change(ObjectChangeFilter::Owner(*recipient), Some(from_checkpoint))
+        .await?;
+
+    let objects = http_client
+        .get_owned_objects(*address, None, None, None)
+        .await?
+        .data;
+    let obj = objects.first().unwrap().object().unwrap().object_id;
+    let gas = objects.last().unwrap().object().unwrap().object_id;
+    let transaction_bytes: TransactionBlockBytes = http_client
+        .transfer_object(*address, obj, Some(gas), 1000, *recipient)
+        .await?;
+    let keystore_path = cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+    let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+    let tx = to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(address)?);
+    let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+    let response = http_client
+        .execute_transaction_block(
+            tx_bytes,
+            signatures,
+            None,
+            Some(ExecuteTransactionRequestType::WaitForEffectsCert),
+        )
+        .await?;
+
+    // Only the object received is streamed to the recipient, not the gas of the sender.
+    let notification = subscription.next().await.unwrap()?;
+    assert_eq!(notification.transaction_digest, response.digest);
+    assert!(notification.checkpoint.is_some());
+    assert!(matches!(
+        notification.change,
+        ObjectChange::Mutated { object_id, .. } if object_id == obj
+    ));
+    Ok(())
+}
+
 #[sim_test]
 async fn test_publish() -> Result<(), anyhow::Error> {
     let cluster = TestClusterBuilder::new().build().await?;
@@ -861,7 +1111,7 @@ async fn test_staking_multiple_coins() -> Result<(), anyhow::Error> {
     let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
 
     let dryrun_response = http_client
-        .dry_run_transaction_block(tx_bytes.clone())
+        .dry_run_transaction_block(tx_bytes.clone(), None)
         .await?;
 
     let executed_response = http_client
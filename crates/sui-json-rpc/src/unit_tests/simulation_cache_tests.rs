@@ -0,0 +1,25 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::simulation_cache::SimulationCache;
+
+#[test]
+fn test_simulation_cache_invalidated_by_checkpoints() {
+    let cache = SimulationCache::new(2);
+    let (first, second) = (b"first".as_slice(), b"second".as_slice());
+
+    cache.insert(first, 1, 10);
+    assert_eq!(cache.get(first, 1), Some(10));
+    assert_eq!(cache.get(second, 1), None);
+
+    // A result computed at an older checkpoint is neither returned nor cached.
+    assert_eq!(cache.get(first, 0), None);
+    cache.insert(second, 0, 20);
+    assert_eq!(cache.get(second, 1), None);
+
+    // A new checkpoint invalidates all the results.
+    assert_eq!(cache.get(first, 2), None);
+    cache.insert(second, 2, 30);
+    assert_eq!(cache.get(second, 2), Some(30));
+    assert_eq!(cache.get(first, 1), None);
+}
@@ -85,11 +85,33 @@ fn main() -> Result<()> {
                 .codec_path(codec_path)
                 .build(),
         )
+        .method(
+            Method::builder()
+                .name("validator_report")
+                .route_name("ValidatorReport")
+                .input_type("sui_types::validator_report::ValidatorReportRequest")
+                .output_type("sui_types::validator_report::SignedValidatorReport")
+                .codec_path(codec_path)
+                .build(),
+        )
         .build();
 
     Builder::new()
         .out_dir(&out_dir)
         .compile(&[validator_service]);
+    write_validator_descriptor(
+        &out_dir,
+        &[
+            "Transaction",
+            "CertifiedTransaction",
+            "SubmitCertificate",
+            "ObjectInfo",
+            "TransactionInfo",
+            "Checkpoint",
+            "GetSystemStateObject",
+            "ValidatorReport",
+        ],
+    )?;
 
     build_anemo_services(&out_dir);
 
@@ -99,6 +121,48 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Writes the descriptor of the Validator service, served by gRPC reflection. Its messages are
+/// BCS encoded rather than protobuf, so they are all described as an opaque `BcsMessage`.
+fn write_validator_descriptor(out_dir: &Path, routes: &[&str]) -> Result<()> {
+    use prost::Message;
+    use prost_types::{
+        DescriptorProto, FileDescriptorProto, FileDescriptorSet, MethodDescriptorProto,
+        ServiceDescriptorProto,
+    };
+
+    let message_type = ".sui.validator.BcsMessage".to_string();
+    let method = routes
+        .iter()
+        .map(|route| MethodDescriptorProto {
+            name: Some(route.to_string()),
+            input_type: Some(message_type.clone()),
+            output_type: Some(message_type.clone()),
+            ..Default::default()
+        })
+        .collect();
+    let file = FileDescriptorProto {
+        name: Some("sui/validator.proto".to_string()),
+        package: Some("sui.validator".to_string()),
+        message_type: vec![DescriptorProto {
+            name: Some("BcsMessage".to_string()),
+            ..Default::default()
+        }],
+        service: vec![ServiceDescriptorProto {
+            name: Some("Validator".to_string()),
+            method,
+            ..Default::default()
+        }],
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    };
+    let descriptor_set = FileDescriptorSet { file: vec![file] };
+    std::fs::write(
+        out_dir.join("sui.validator.descriptor.bin"),
+        descriptor_set.encode_to_vec(),
+    )?;
+    Ok(())
+}
+
 fn build_anemo_services(out_dir: &Path) {
     let codec_path = "mysten_network::codec::anemo::BcsSnappyCodec";
 
@@ -5,6 +5,10 @@ mod validator {
     include!(concat!(env!("OUT_DIR"), "/sui.validator.Validator.rs"));
 }
 
+/// The descriptor of the Validator service, for gRPC reflection.
+pub const VALIDATOR_FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/sui.validator.descriptor.bin"));
+
 pub use validator::{
     validator_client::ValidatorClient,
     validator_server::{Validator, ValidatorServer},
@@ -0,0 +1,265 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Archival fallback of StateSync.
+//!
+//! Peers only keep a limited history of checkpoints, so a new node may be unable to sync from
+//! genesis over the P2P network. An archive is an object store (S3, GCS, ...) where the history
+//! of the chain is uploaded, and which StateSync falls back to for the checkpoints its peers are
+//! unable to serve.
+//!
+//! The archive is made of files of consecutive checkpoints, each holding the BCS-serialized
+//! certified summaries and full contents of its checkpoints, and of a `MANIFEST` listing these
+//! files with the range of their checkpoints and the digest of their bytes. A file is only used
+//! once its bytes match the digest in the manifest, and its checkpoints are then verified like
+//! the ones served by peers: the summaries against the committee of their epoch, and the
+//! contents against the content digest of their summary. The archive is thus not trusted.
+
+use anyhow::{anyhow, Context};
+use bytes::Bytes;
+use fastcrypto::hash::HashFunction;
+use object_store::{path::Path, DynObjectStore};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    ops::Range,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use sui_storage::object_store::{util::put, ObjectStoreConfig};
+use sui_types::{
+    crypto::DefaultHash,
+    digests::CheckpointContentsDigest,
+    messages_checkpoint::{
+        CertifiedCheckpointSummary as Checkpoint, CheckpointSequenceNumber, FullCheckpointContents,
+    },
+};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// The path of the manifest in the archive.
+pub const MANIFEST_FILE: &str = "MANIFEST";
+
+/// The number of checkpoint files kept in memory, a file serving many consecutive checkpoints.
+const MAX_CACHED_FILES: usize = 4;
+
+/// The minimum interval between two fetches of the manifest, when looking for checkpoints which
+/// are not archived yet.
+const MANIFEST_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A checkpoint, as archived.
+pub type ArchivedCheckpoint = (Checkpoint, FullCheckpointContents);
+
+/// The list of the checkpoint files of an archive, ordered by checkpoint.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub files: Vec<ArchiveFileMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveFileMetadata {
+    /// The sequence numbers of the checkpoints in the file.
+    pub checkpoints: Range<CheckpointSequenceNumber>,
+    /// The digest of the bytes of the file.
+    pub digest: [u8; 32],
+}
+
+impl ArchiveFileMetadata {
+    pub fn path(&self) -> Path {
+        Path::from(format!("checkpoints/{}.chk", self.checkpoints.start))
+    }
+}
+
+impl ArchiveManifest {
+    /// Returns the sequence number of the first checkpoint which is not archived yet.
+    pub fn next_checkpoint(&self) -> CheckpointSequenceNumber {
+        self.files.last().map_or(0, |file| file.checkpoints.end)
+    }
+
+    fn file_of(&self, sequence_number: CheckpointSequenceNumber) -> Option<&ArchiveFileMetadata> {
+        let index = self
+            .files
+            .partition_point(|file| file.checkpoints.end <= sequence_number);
+        self.files
+            .get(index)
+            .filter(|file| file.checkpoints.contains(&sequence_number))
+    }
+}
+
+/// Reads the checkpoints of an archive.
+pub struct ArchiveReader {
+    store: Arc<DynObjectStore>,
+    // Held while fetching, so that the concurrent requests for the checkpoints of a file only
+    // fetch it once.
+    state: Mutex<ReaderState>,
+}
+
+#[derive(Default)]
+struct ReaderState {
+    manifest: ArchiveManifest,
+    manifest_fetched_at: Option<Instant>,
+    files: VecDeque<(ArchiveFileMetadata, Arc<Vec<ArchivedCheckpoint>>)>,
+}
+
+impl ArchiveReader {
+    pub fn new(config: &ObjectStoreConfig) -> anyhow::Result<Self> {
+        Ok(Self::from_store(config.make()?))
+    }
+
+    pub fn from_store(store: Arc<DynObjectStore>) -> Self {
+        Self {
+            store,
+            state: Mutex::new(ReaderState::default()),
+        }
+    }
+
+    /// Returns the summary of the checkpoint, if archived. The summary is not verified.
+    pub async fn get_checkpoint_summary(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> anyhow::Result<Option<Checkpoint>> {
+        Ok(self
+            .get_checkpoint(sequence_number)
+            .await?
+            .map(|(summary, _)| summary))
+    }
+
+    /// Returns the summary of the latest archived checkpoint, if any. The summary is not verified.
+    pub async fn get_latest_checkpoint_summary(&self) -> anyhow::Result<Option<Checkpoint>> {
+        let next_checkpoint = {
+            let mut state = self.state.lock().await;
+            if state
+                .manifest_fetched_at
+                .map_or(true, |at| at.elapsed() >= MANIFEST_REFRESH_INTERVAL)
+            {
+                state.manifest = read_manifest(&self.store).await?;
+                state.manifest_fetched_at = Some(Instant::now());
+            }
+            state.manifest.next_checkpoint()
+        };
+        match next_checkpoint.checked_sub(1) {
+            Some(latest) => self.get_checkpoint_summary(latest).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the contents of the checkpoint, if archived, once verified against their digest.
+    pub async fn get_checkpoint_contents(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+        digest: CheckpointContentsDigest,
+    ) -> anyhow::Result<Option<FullCheckpointContents>> {
+        let Some((_, contents)) = self.get_checkpoint(sequence_number).await? else {
+            return Ok(None);
+        };
+        contents.verify_digests(digest).map_err(|e| {
+            anyhow!("archived contents of checkpoint {sequence_number} do not match: {e}")
+        })?;
+        Ok(Some(contents))
+    }
+
+    async fn get_checkpoint(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> anyhow::Result<Option<ArchivedCheckpoint>> {
+        let mut state = self.state.lock().await;
+        if state.manifest.file_of(sequence_number).is_none()
+            && state
+                .manifest_fetched_at
+                .map_or(true, |at| at.elapsed() >= MANIFEST_REFRESH_INTERVAL)
+        {
+            state.manifest = read_manifest(&self.store).await?;
+            state.manifest_fetched_at = Some(Instant::now());
+        }
+        let Some(file) = state.manifest.file_of(sequence_number).cloned() else {
+            return Ok(None);
+        };
+
+        let checkpoints = match state.files.iter().find(|(cached, _)| *cached == file) {
+            Some((_, checkpoints)) => checkpoints.clone(),
+            None => {
+                let checkpoints = Arc::new(self.read_file(&file).await?);
+                if state.files.len() >= MAX_CACHED_FILES {
+                    state.files.pop_front();
+                }
+                state.files.push_back((file.clone(), checkpoints.clone()));
+                checkpoints
+            }
+        };
+        let index = (sequence_number - file.checkpoints.start) as usize;
+        Ok(Some(checkpoints[index].clone()))
+    }
+
+    async fn read_file(
+        &self,
+        file: &ArchiveFileMetadata,
+    ) -> anyhow::Result<Vec<ArchivedCheckpoint>> {
+        debug!(checkpoints = ?file.checkpoints, "fetching checkpoints from the archive");
+        let bytes = self.store.get(&file.path()).await?.bytes().await?;
+        if DefaultHash::digest(&bytes).digest != file.digest {
+            return Err(anyhow!(
+                "digest of archive file {} does not match the manifest",
+                file.path()
+            ));
+        }
+        let checkpoints: Vec<ArchivedCheckpoint> = bcs::from_bytes(&bytes)
+            .with_context(|| format!("invalid archive file {}", file.path()))?;
+        let sequence_numbers = checkpoints
+            .iter()
+            .map(|(summary, _)| *summary.sequence_number());
+        if !sequence_numbers.eq(file.checkpoints.clone()) {
+            return Err(anyhow!(
+                "archive file {} does not hold the checkpoints {:?}",
+                file.path(),
+                file.checkpoints
+            ));
+        }
+        Ok(checkpoints)
+    }
+}
+
+/// Returns the manifest of the archive, which is empty if nothing was archived yet.
+pub async fn read_manifest(store: &Arc<DynObjectStore>) -> anyhow::Result<ArchiveManifest> {
+    match store.get(&Path::from(MANIFEST_FILE)).await {
+        Ok(result) => {
+            let bytes = result.bytes().await?;
+            bcs::from_bytes(&bytes).context("invalid archive manifest")
+        }
+        Err(object_store::Error::NotFound { .. }) => Ok(ArchiveManifest::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Uploads a file of checkpoints to the archive, which must directly follow the checkpoints
+/// already archived, then adds it to the manifest.
+pub async fn append_to_archive(
+    store: &Arc<DynObjectStore>,
+    checkpoints: Vec<ArchivedCheckpoint>,
+) -> anyhow::Result<()> {
+    let mut manifest = read_manifest(store).await?;
+    let start = manifest.next_checkpoint();
+    let end = start + checkpoints.len() as u64;
+    let sequence_numbers = checkpoints
+        .iter()
+        .map(|(summary, _)| *summary.sequence_number());
+    if checkpoints.is_empty() || !sequence_numbers.eq(start..end) {
+        return Err(anyhow!(
+            "the archived checkpoints must be consecutive and start at checkpoint {start}"
+        ));
+    }
+
+    let bytes = Bytes::from(bcs::to_bytes(&checkpoints)?);
+    let file = ArchiveFileMetadata {
+        checkpoints: start..end,
+        digest: DefaultHash::digest(&bytes).digest,
+    };
+    put(&file.path(), bytes, store.clone()).await?;
+    manifest.files.push(file);
+    put(
+        &Path::from(MANIFEST_FILE),
+        Bytes::from(bcs::to_bytes(&manifest)?),
+        store.clone(),
+    )
+    .await?;
+    Ok(())
+}
@@ -9,17 +9,19 @@ use std::{
 };
 use sui_config::p2p::StateSyncConfig;
 use sui_types::{messages_checkpoint::VerifiedCheckpoint, storage::ReadStore};
-use tap::Pipe;
+use tap::{Pipe, TapFallible};
 use tokio::{
     sync::{broadcast, mpsc},
     task::JoinSet,
 };
 
 use super::{
-    metrics::Metrics, server::Server, Handle, PeerHeights, StateSync, StateSyncEventLoop,
-    StateSyncMessage, StateSyncServer,
+    archive::ArchiveReader, follower::FullnodeReader, metrics::Metrics, peer_scores::PeerScores,
+    server::Server, Handle, PeerHeights, StateSync, StateSyncEventLoop, StateSyncMessage,
+    StateSyncServer,
 };
 use sui_types::storage::WriteStore;
+use tracing::warn;
 
 pub struct Builder<S> {
     store: Option<S>,
@@ -111,17 +113,43 @@ where
         let (checkpoint_event_sender, _receiver) =
             broadcast::channel(config.synced_checkpoint_broadcast_channel_capacity());
         let weak_sender = sender.downgrade();
+        let scores = Arc::new(PeerScores::new(
+            config.peer_ban_duration(),
+            config.peer_ban_list_path.clone(),
+        ));
         let handle = Handle {
             sender,
             checkpoint_event_sender: checkpoint_event_sender.clone(),
+            scores: scores.clone(),
         };
         let peer_heights = PeerHeights {
             peers: HashMap::new(),
             unprocessed_checkpoints: HashMap::new(),
             sequence_number_to_digest: HashMap::new(),
+            followed_height: None,
+            scores,
         }
         .pipe(RwLock::new)
         .pipe(Arc::new);
+        let archive = config
+            .archive_object_store
+            .as_ref()
+            .and_then(|config| {
+                ArchiveReader::new(config)
+                    .tap_err(|e| warn!("unable to open the checkpoint archive: {e}"))
+                    .ok()
+            })
+            .map(Arc::new);
+        let followed = config
+            .follower
+            .as_ref()
+            .and_then(|follower| follower.rpc_url.as_deref())
+            .and_then(|rpc_url| {
+                FullnodeReader::new(rpc_url, config.timeout())
+                    .tap_err(|e| warn!("unable to connect to the followed fullnode: {e}"))
+                    .ok()
+            })
+            .map(Arc::new);
 
         let server = Server {
             store: store.clone(),
@@ -138,6 +166,8 @@ where
                 peer_heights,
                 checkpoint_event_sender,
                 metrics,
+                archive,
+                followed,
             },
             server,
         )
@@ -152,6 +182,8 @@ pub struct UnstartedStateSync<S> {
     pub(super) peer_heights: Arc<RwLock<PeerHeights>>,
     pub(super) checkpoint_event_sender: broadcast::Sender<VerifiedCheckpoint>,
     pub(super) metrics: Metrics,
+    pub(super) archive: Option<Arc<ArchiveReader>>,
+    pub(super) followed: Option<Arc<FullnodeReader>>,
 }
 
 impl<S> UnstartedStateSync<S>
@@ -168,6 +200,8 @@ where
             peer_heights,
             checkpoint_event_sender,
             metrics,
+            archive,
+            followed,
         } = self;
 
         (
@@ -183,6 +217,8 @@ where
                 checkpoint_event_sender,
                 network,
                 metrics,
+                archive,
+                followed,
             },
             handle,
         )
@@ -0,0 +1,113 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Follower mode of StateSync.
+//!
+//! Nodes in restricted network environments may be unable to join the P2P network. In follower
+//! mode, a fullnode does not connect to any peer and syncs the checkpoints by polling the JSON-RPC
+//! of a fullnode it is able to reach, and from the archive if configured, see [super::archive].
+//! The checkpoints served by the followed fullnode are verified like the ones served by peers: the
+//! summaries against the committee of their epoch, and the contents against the content digest
+//! of their summary. The followed fullnode is thus only trusted to keep serving checkpoints.
+
+use anyhow::anyhow;
+use jsonrpsee::{
+    core::client::ClientT,
+    http_client::{HttpClient, HttpClientBuilder},
+    rpc_params,
+};
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
+use sui_json_rpc_types::{BigInt, CheckpointId, FullCheckpoint};
+use sui_types::{
+    digests::CheckpointContentsDigest,
+    messages_checkpoint::{
+        CertifiedCheckpointSummary as Checkpoint, CheckpointSequenceNumber, FullCheckpointContents,
+    },
+};
+
+/// The number of checkpoints whose contents are kept in memory once fetched along with their
+/// summary, until they are synced.
+const MAX_CACHED_CONTENTS: usize = 128;
+
+/// Reads the checkpoints of the followed fullnode from its JSON-RPC.
+pub struct FullnodeReader {
+    client: HttpClient,
+    contents: Mutex<BTreeMap<CheckpointSequenceNumber, FullCheckpointContents>>,
+}
+
+impl FullnodeReader {
+    pub fn new(rpc_url: &str, timeout: Duration) -> anyhow::Result<Self> {
+        let client = HttpClientBuilder::default()
+            .request_timeout(timeout)
+            .build(rpc_url)?;
+        Ok(Self {
+            client,
+            contents: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Returns the summary of the latest checkpoint of the fullnode. The summary is not verified.
+    pub async fn get_latest_checkpoint_summary(&self) -> anyhow::Result<Checkpoint> {
+        let latest: BigInt = self
+            .client
+            .request("sui_getLatestCheckpointSequenceNumber", rpc_params![])
+            .await?;
+        self.get_checkpoint_summary(latest.into()).await
+    }
+
+    /// Returns the summary of the checkpoint. The summary is not verified.
+    pub async fn get_checkpoint_summary(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> anyhow::Result<Checkpoint> {
+        let (summary, contents) = self.get_checkpoint(sequence_number).await?;
+        let mut cached = self.contents.lock().unwrap();
+        cached.insert(sequence_number, contents);
+        if cached.len() > MAX_CACHED_CONTENTS {
+            let oldest = *cached.keys().next().unwrap();
+            cached.remove(&oldest);
+        }
+        Ok(summary)
+    }
+
+    /// Returns the contents of the checkpoint, once verified against their digest.
+    pub async fn get_checkpoint_contents(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+        digest: CheckpointContentsDigest,
+    ) -> anyhow::Result<FullCheckpointContents> {
+        let cached = self.contents.lock().unwrap().remove(&sequence_number);
+        let contents = match cached {
+            Some(contents) => contents,
+            None => self.get_checkpoint(sequence_number).await?.1,
+        };
+        contents.verify_digests(digest).map_err(|e| {
+            anyhow!(
+                "contents of checkpoint {sequence_number} served by the followed fullnode do not \
+                match: {e}"
+            )
+        })?;
+        Ok(contents)
+    }
+
+    async fn get_checkpoint(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> anyhow::Result<(Checkpoint, FullCheckpointContents)> {
+        let checkpoint: FullCheckpoint = self
+            .client
+            .request(
+                "sui_getFullCheckpoint",
+                rpc_params![CheckpointId::from(sequence_number)],
+            )
+            .await?;
+        let (summary, contents) = checkpoint.decode()?;
+        if *summary.sequence_number() != sequence_number {
+            return Err(anyhow!(
+                "followed fullnode served checkpoint {} instead of {sequence_number}",
+                summary.sequence_number()
+            ));
+        }
+        Ok((summary, contents))
+    }
+}
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use mysten_metrics::histogram::Histogram;
-use prometheus::{register_int_gauge_with_registry, IntGauge, Registry};
+use prometheus::{
+    register_int_counter_with_registry, register_int_gauge_with_registry, IntCounter, IntGauge,
+    Registry,
+};
 use std::sync::Arc;
 use std::time::Duration;
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
@@ -53,6 +56,30 @@ impl Metrics {
                 .report(age.as_millis() as u64);
         }
     }
+
+    pub fn inc_checkpoint_summaries_synced_from_archive(&self) {
+        if let Some(inner) = &self.0 {
+            inner.checkpoint_summaries_synced_from_archive.inc();
+        }
+    }
+
+    pub fn inc_checkpoint_contents_synced_from_archive(&self) {
+        if let Some(inner) = &self.0 {
+            inner.checkpoint_contents_synced_from_archive.inc();
+        }
+    }
+
+    pub fn inc_checkpoint_summaries_synced_from_followed(&self) {
+        if let Some(inner) = &self.0 {
+            inner.checkpoint_summaries_synced_from_followed.inc();
+        }
+    }
+
+    pub fn inc_checkpoint_contents_synced_from_followed(&self) {
+        if let Some(inner) = &self.0 {
+            inner.checkpoint_contents_synced_from_followed.inc();
+        }
+    }
 }
 
 struct Inner {
@@ -60,6 +87,10 @@ struct Inner {
     highest_verified_checkpoint: IntGauge,
     highest_synced_checkpoint: IntGauge,
     checkpoint_summary_age_ms: Histogram,
+    checkpoint_summaries_synced_from_archive: IntCounter,
+    checkpoint_contents_synced_from_archive: IntCounter,
+    checkpoint_summaries_synced_from_followed: IntCounter,
+    checkpoint_contents_synced_from_followed: IntCounter,
 }
 
 impl Inner {
@@ -91,6 +122,34 @@ impl Inner {
                 "Age of checkpoints summaries when they arrive and are verified.",
                 registry,
             ),
+
+            checkpoint_summaries_synced_from_archive: register_int_counter_with_registry!(
+                "checkpoint_summaries_synced_from_archive",
+                "Number of checkpoint summaries fetched from the archive",
+                registry
+            )
+            .unwrap(),
+
+            checkpoint_contents_synced_from_archive: register_int_counter_with_registry!(
+                "checkpoint_contents_synced_from_archive",
+                "Number of checkpoint contents fetched from the archive",
+                registry
+            )
+            .unwrap(),
+
+            checkpoint_summaries_synced_from_followed: register_int_counter_with_registry!(
+                "checkpoint_summaries_synced_from_followed",
+                "Number of checkpoint summaries fetched from the followed fullnode",
+                registry
+            )
+            .unwrap(),
+
+            checkpoint_contents_synced_from_followed: register_int_counter_with_registry!(
+                "checkpoint_contents_synced_from_followed",
+                "Number of checkpoint contents fetched from the followed fullnode",
+                registry
+            )
+            .unwrap(),
         }
         .pipe(Arc::new)
     }
@@ -46,6 +46,17 @@
 //! indicating that a new checkpoint has been fully downloaded. Notifications on this broadcast
 //! channel will always be made in order. StateSync will also send out a notification to its peers
 //! of the newly synchronized checkpoint so that it can help other peers synchronize.
+//!
+//! Peers only keep a limited history, so StateSync can be configured with an archive of the
+//! checkpoints in an object store, see [archive]. The checkpoints that no peer is able to serve
+//! are then fetched from the archive, and verified like the ones served by peers.
+//!
+//! In restricted network environments, a fullnode can be configured to follow the chain without
+//! connecting to any peer, see [follower]. Its checkpoints are then discovered and synced by
+//! polling the JSON-RPC of a fullnode it trusts to serve them, and from the archive.
+//!
+//! Peers serving invalid or slow data lose score, and are banned for a while once their score is
+//! too low, see [peer_scores].
 
 use anemo::{types::PeerEvent, PeerId, Request, Response, Result};
 use anyhow::anyhow;
@@ -53,7 +64,7 @@ use futures::{FutureExt, StreamExt};
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 use sui_config::p2p::StateSyncConfig;
 use sui_types::{
@@ -75,8 +86,11 @@ use tracing::{debug, info, trace, warn};
 mod generated {
     include!(concat!(env!("OUT_DIR"), "/sui.StateSync.rs"));
 }
+pub mod archive;
 mod builder;
+pub mod follower;
 mod metrics;
+pub mod peer_scores;
 mod server;
 pub mod test_utils;
 #[cfg(test)]
@@ -89,7 +103,12 @@ pub use generated::{
 };
 pub use server::GetCheckpointSummaryRequest;
 
-use self::metrics::Metrics;
+use self::{
+    archive::ArchiveReader,
+    follower::FullnodeReader,
+    metrics::Metrics,
+    peer_scores::{BannedPeer, PeerBehavior, PeerScores},
+};
 
 /// A handle to the StateSync subsystem.
 ///
@@ -99,6 +118,7 @@ use self::metrics::Metrics;
 pub struct Handle {
     sender: mpsc::Sender<StateSyncMessage>,
     checkpoint_event_sender: broadcast::Sender<VerifiedCheckpoint>,
+    scores: Arc<PeerScores>,
 }
 
 impl Handle {
@@ -121,6 +141,16 @@ impl Handle {
     pub fn subscribe_to_synced_checkpoints(&self) -> broadcast::Receiver<VerifiedCheckpoint> {
         self.checkpoint_event_sender.subscribe()
     }
+
+    /// The peers banned for serving invalid or slow data.
+    pub fn banned_peers(&self) -> Vec<BannedPeer> {
+        self.scores.banned_peers()
+    }
+
+    /// Lifts the ban of the peer, and returns whether it was banned.
+    pub fn unban_peer(&self, peer_id: &PeerId) -> bool {
+        self.scores.unban(peer_id)
+    }
 }
 
 struct PeerHeights {
@@ -128,6 +158,11 @@ struct PeerHeights {
     peers: HashMap<PeerId, PeerStateSyncInfo>,
     unprocessed_checkpoints: HashMap<CheckpointDigest, Checkpoint>,
     sequence_number_to_digest: HashMap<CheckpointSequenceNumber, CheckpointDigest>,
+    /// Highest checkpoint sequence number we know of for the followed fullnode and archive, in
+    /// follower mode.
+    followed_height: Option<CheckpointSequenceNumber>,
+    /// Scores of our peers, the banned ones being ignored.
+    scores: Arc<PeerScores>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -148,16 +183,16 @@ impl PeerHeights {
     }
 
     pub fn highest_known_checkpoint_sequence_number(&self) -> Option<CheckpointSequenceNumber> {
-        self.peers
-            .values()
-            .filter_map(|info| info.on_same_chain_as_us.then_some(info.height))
+        self.peers_on_same_chain()
+            .map(|(_peer_id, info)| info.height)
+            .chain(self.followed_height)
             .max()
     }
 
     pub fn peers_on_same_chain(&self) -> impl Iterator<Item = (&PeerId, &PeerStateSyncInfo)> {
         self.peers
             .iter()
-            .filter(|(_peer_id, info)| info.on_same_chain_as_us)
+            .filter(|(peer_id, info)| info.on_same_chain_as_us && !self.scores.is_banned(peer_id))
     }
 
     // Returns a bool that indicates if the update was done successfully.
@@ -165,6 +200,9 @@ impl PeerHeights {
     // This will return false if the given peer doesn't have an entry or is not on the same chain
     // as us
     pub fn update_peer_info(&mut self, peer_id: PeerId, checkpoint: Checkpoint) -> bool {
+        if self.scores.is_banned(&peer_id) {
+            return false;
+        }
         let info = match self.peers.get_mut(&peer_id) {
             Some(info) if info.on_same_chain_as_us => info,
             _ => return false,
@@ -176,6 +214,12 @@ impl PeerHeights {
         true
     }
 
+    pub fn update_followed_height(&mut self, checkpoint: Checkpoint) {
+        self.followed_height =
+            std::cmp::max(Some(*checkpoint.sequence_number()), self.followed_height);
+        self.insert_checkpoint(checkpoint);
+    }
+
     pub fn insert_peer_info(&mut self, peer_id: PeerId, info: PeerStateSyncInfo) {
         use std::collections::hash_map::Entry;
 
@@ -266,6 +310,8 @@ struct StateSyncEventLoop<S> {
     checkpoint_event_sender: broadcast::Sender<VerifiedCheckpoint>,
     network: anemo::Network,
     metrics: Metrics,
+    archive: Option<Arc<ArchiveReader>>,
+    followed: Option<Arc<FullnodeReader>>,
 }
 
 impl<S> StateSyncEventLoop<S>
@@ -288,6 +334,9 @@ where
             }
             subscriber
         };
+        if self.config.is_follower() {
+            info!("State-Synchronizer following the chain without syncing from peers");
+        }
 
         // Initialize checkpoint watermark metrics
         self.metrics.set_highest_verified_checkpoint(
@@ -464,6 +513,14 @@ where
     }
 
     fn spawn_get_latest_from_peer(&mut self, peer_id: PeerId) {
+        // Peers are never synced from in follower mode
+        if self.config.is_follower() {
+            return;
+        }
+        if self.peer_heights.read().unwrap().scores.is_banned(&peer_id) {
+            let _ = self.network.disconnect(peer_id);
+            return;
+        }
         if let Some(peer) = self.network.peer(peer_id) {
             let genesis_checkpoint_digest = *self
                 .store
@@ -482,6 +539,17 @@ where
     }
 
     fn handle_tick(&mut self, _now: std::time::Instant) {
+        if self.config.is_follower() {
+            let task = query_followed_for_latest_checkpoint(
+                self.followed.clone(),
+                self.archive.clone(),
+                self.peer_heights.clone(),
+                self.weak_sender.clone(),
+            );
+            self.tasks.spawn(task);
+            return;
+        }
+
         let task = query_peers_for_their_latest_checkpoint(
             self.network.clone(),
             self.peer_heights.clone(),
@@ -520,6 +588,8 @@ where
                 self.store.clone(),
                 self.peer_heights.clone(),
                 self.metrics.clone(),
+                self.archive.clone(),
+                self.followed.clone(),
                 self.config.checkpoint_header_download_concurrency(),
                 self.config.timeout(),
                 // The if condition should ensure that this is Some
@@ -553,13 +623,16 @@ where
 
         if highest_verified_checkpoint.sequence_number()
             > highest_synced_checkpoint.sequence_number()
-            // skip if we aren't connected to any peers that can help
-            && self
-                .peer_heights
-                .read()
-                .unwrap()
-                .highest_known_checkpoint_sequence_number()
-                > Some(*highest_synced_checkpoint.sequence_number())
+            // skip if we aren't connected to any peers that can help, unless we have an archive
+            // or follow a fullnode
+            && (self.archive.is_some()
+                || self.followed.is_some()
+                || self
+                    .peer_heights
+                    .read()
+                    .unwrap()
+                    .highest_known_checkpoint_sequence_number()
+                    > Some(*highest_synced_checkpoint.sequence_number()))
         {
             let task = sync_checkpoint_contents(
                 self.network.clone(),
@@ -568,6 +641,8 @@ where
                 self.weak_sender.clone(),
                 self.checkpoint_event_sender.clone(),
                 self.metrics.clone(),
+                self.archive.clone(),
+                self.followed.clone(),
                 self.config.checkpoint_content_download_concurrency(),
                 self.config.checkpoint_content_timeout(),
                 highest_verified_checkpoint,
@@ -755,11 +830,62 @@ async fn query_peers_for_their_latest_checkpoint(
     }
 }
 
+// Polls the followed fullnode and the archive for their latest checkpoint, in follower mode.
+async fn query_followed_for_latest_checkpoint(
+    followed: Option<Arc<FullnodeReader>>,
+    archive: Option<Arc<ArchiveReader>>,
+    peer_heights: Arc<RwLock<PeerHeights>>,
+    sender: mpsc::WeakSender<StateSyncMessage>,
+) {
+    let from_followed = async {
+        followed?
+            .get_latest_checkpoint_summary()
+            .await
+            .tap_err(|e| {
+                debug!("unable to get the latest checkpoint of the followed fullnode: {e}")
+            })
+            .ok()
+    };
+    let from_archive = async {
+        archive?
+            .get_latest_checkpoint_summary()
+            .await
+            .tap_err(|e| debug!("unable to get the latest checkpoint of the archive: {e}"))
+            .ok()
+            .flatten()
+    };
+    let (from_followed, from_archive) = futures::join!(from_followed, from_archive);
+    let Some(highest_checkpoint) = from_followed
+        .into_iter()
+        .chain(from_archive)
+        .max_by_key(|checkpoint| *checkpoint.sequence_number()) else {
+        return;
+    };
+
+    let our_highest_checkpoint = peer_heights
+        .read()
+        .unwrap()
+        .highest_known_checkpoint_sequence_number();
+    if our_highest_checkpoint >= Some(*highest_checkpoint.sequence_number()) {
+        return;
+    }
+    peer_heights
+        .write()
+        .unwrap()
+        .update_followed_height(highest_checkpoint);
+
+    if let Some(sender) = sender.upgrade() {
+        let _ = sender.send(StateSyncMessage::StartSyncJob).await;
+    }
+}
+
 async fn sync_to_checkpoint<S>(
     network: anemo::Network,
     store: S,
     peer_heights: Arc<RwLock<PeerHeights>>,
     metrics: Metrics,
+    archive: Option<Arc<ArchiveReader>>,
+    followed: Option<Arc<FullnodeReader>>,
     checkpoint_header_download_concurrency: usize,
     timeout: Duration,
     checkpoint: Checkpoint,
@@ -781,6 +907,7 @@ where
         ));
     }
 
+    let scores = peer_heights.read().unwrap().scores.clone();
     let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::from_entropy();
     // get a list of peers that can help
     let peers = peer_heights
@@ -805,7 +932,12 @@ where
                 .map(StateSyncClient::new)
                 .collect::<Vec<_>>();
             rand::seq::SliceRandom::shuffle(peers.as_mut_slice(), &mut rng);
+            let network = network.clone();
+            let scores = scores.clone();
             let peer_heights = peer_heights.clone();
+            let archive = archive.clone();
+            let followed = followed.clone();
+            let metrics = metrics.clone();
             async move {
                 if let Some(checkpoint) = peer_heights
                     .read()
@@ -818,11 +950,15 @@ where
                 // Iterate through our selected peers trying each one in turn until we're able to
                 // successfully get the target checkpoint
                 for mut peer in peers {
+                    let peer_id = peer.inner().peer_id();
                     let request = Request::new(GetCheckpointSummaryRequest::BySequenceNumber(next))
                         .with_timeout(timeout);
-                    if let Some(checkpoint) = peer
-                        .get_checkpoint_summary(request)
-                        .await
+                    let started = Instant::now();
+                    let response = peer.get_checkpoint_summary(request).await;
+                    if response.is_err() || is_slow(started, timeout) {
+                        report_peer(&network, &scores, peer_id, PeerBehavior::Slow);
+                    }
+                    if let Some(checkpoint) = response
                         .tap_err(|e| trace!("{e:?}"))
                         .ok()
                         .and_then(Response::into_inner)
@@ -830,6 +966,7 @@ where
                     {
                         // peer didn't give us a checkpoint with the height that we requested
                         if *checkpoint.sequence_number() != next {
+                            report_peer(&network, &scores, peer_id, PeerBehavior::Invalid);
                             continue;
                         }
 
@@ -838,10 +975,30 @@ where
                             .write()
                             .unwrap()
                             .insert_checkpoint(checkpoint.clone());
-                        return (Some(checkpoint), next, Some(peer.inner().peer_id()));
+                        return (Some(checkpoint), next, Some(peer_id));
                     }
                 }
 
+                // Fall back to the followed fullnode, then to the archive, for the checkpoints
+                // which no peer could help with
+                if let Some(checkpoint) = get_checkpoint_summary_from_followed(followed, next).await
+                {
+                    peer_heights
+                        .write()
+                        .unwrap()
+                        .insert_checkpoint(checkpoint.clone());
+                    metrics.inc_checkpoint_summaries_synced_from_followed();
+                    return (Some(checkpoint), next, None);
+                }
+                if let Some(checkpoint) = get_checkpoint_summary_from_archive(archive, next).await {
+                    peer_heights
+                        .write()
+                        .unwrap()
+                        .insert_checkpoint(checkpoint.clone());
+                    metrics.inc_checkpoint_summaries_synced_from_archive();
+                    return (Some(checkpoint), next, None);
+                }
+
                 (None, next, None)
             }
         })
@@ -856,7 +1013,12 @@ where
             let checkpoint = maybe_checkpoint
                 .ok_or_else(|| anyhow::anyhow!("no peers were able to help sync"))?;
             match verify_checkpoint(&current, &store, checkpoint) {
-                Ok(verified_checkpoint) => verified_checkpoint,
+                Ok(verified_checkpoint) => {
+                    if let Some(peer_id) = maybe_peer_id {
+                        report_peer(&network, &scores, peer_id, PeerBehavior::Valid);
+                    }
+                    verified_checkpoint
+                }
                 Err(checkpoint) => {
                     let mut peer_heights = peer_heights.write().unwrap();
                     // Remove the checkpoint from our temporary store so that we can try querying
@@ -866,6 +1028,7 @@ where
                     // Mark peer as not on the same chain as us
                     if let Some(peer_id) = maybe_peer_id {
                         peer_heights.mark_peer_as_not_on_same_chain(peer_id);
+                        report_peer(&network, &scores, peer_id, PeerBehavior::Invalid);
                     }
 
                     return Err(anyhow::anyhow!(
@@ -965,6 +1128,8 @@ async fn sync_checkpoint_contents<S>(
     sender: mpsc::WeakSender<StateSyncMessage>,
     checkpoint_event_sender: broadcast::Sender<VerifiedCheckpoint>,
     metrics: Metrics,
+    archive: Option<Arc<ArchiveReader>>,
+    followed: Option<Arc<FullnodeReader>>,
     checkpoint_content_download_concurrency: usize,
     timeout: Duration,
     target_checkpoint: VerifiedCheckpoint,
@@ -992,6 +1157,9 @@ async fn sync_checkpoint_contents<S>(
                 network.clone(),
                 &store,
                 peer_heights.clone(),
+                &metrics,
+                archive.clone(),
+                followed.clone(),
                 timeout,
                 checkpoint,
             )
@@ -1035,6 +1203,9 @@ async fn sync_one_checkpoint_contents<S>(
     network: anemo::Network,
     store: S,
     peer_heights: Arc<RwLock<PeerHeights>>,
+    metrics: &Metrics,
+    archive: Option<Arc<ArchiveReader>>,
+    followed: Option<Arc<FullnodeReader>>,
     timeout: Duration,
     checkpoint: VerifiedCheckpoint,
 ) -> Result<(VerifiedCheckpoint, u64)>
@@ -1056,7 +1227,29 @@ where
         .collect::<Vec<_>>();
     rand::seq::SliceRandom::shuffle(peers.as_mut_slice(), &mut rng);
 
-    let Some(contents) = get_full_checkpoint_contents(&mut peers, &store, checkpoint.content_digest, timeout).await else {
+    let scores = peer_heights.read().unwrap().scores.clone();
+    let mut contents = get_full_checkpoint_contents(
+        &network,
+        &scores,
+        &mut peers,
+        &store,
+        checkpoint.content_digest,
+        timeout,
+    )
+    .await;
+    if contents.is_none() {
+        // Fall back to the followed fullnode when no peer could help
+        contents = get_checkpoint_contents_from_followed(followed, &store, &checkpoint)
+            .await
+            .tap_some(|_| metrics.inc_checkpoint_contents_synced_from_followed());
+    }
+    if contents.is_none() {
+        // Fall back to the archive when no peer could help
+        contents = get_checkpoint_contents_from_archive(archive, &store, &checkpoint)
+            .await
+            .tap_some(|_| metrics.inc_checkpoint_contents_synced_from_archive());
+    }
+    let Some(contents) = contents else {
         return Err(anyhow!("unable to sync checkpoint contents for checkpoint {}", checkpoint.sequence_number()));
     };
 
@@ -1066,6 +1259,8 @@ where
 }
 
 async fn get_full_checkpoint_contents<S>(
+    network: &anemo::Network,
+    scores: &PeerScores,
     peers: &mut [StateSyncClient<anemo::Peer>],
     store: S,
     digest: CheckpointContentsDigest,
@@ -1085,24 +1280,124 @@ where
     // Iterate through our selected peers trying each one in turn until we're able to
     // successfully get the target checkpoint
     for peer in peers.iter_mut() {
+        let peer_id = peer.inner().peer_id();
         let request = Request::new(digest).with_timeout(timeout);
-        if let Some(contents) = peer
-            .get_checkpoint_contents(request)
-            .await
+        let started = Instant::now();
+        let response = peer.get_checkpoint_contents(request).await;
+        if response.is_err() || is_slow(started, timeout) {
+            report_peer(network, scores, peer_id, PeerBehavior::Slow);
+        }
+        if let Some(contents) = response
             .tap_err(|e| trace!("{e:?}"))
             .ok()
             .and_then(Response::into_inner)
             .tap_none(|| trace!("peer unable to help sync"))
         {
             if contents.verify_digests(digest).is_ok() {
+                report_peer(network, scores, peer_id, PeerBehavior::Valid);
                 let verified_contents = VerifiedCheckpointContents::new_unchecked(contents.clone());
                 store
                     .insert_checkpoint_contents(verified_contents)
                     .expect("store operation should not fail");
                 return Some(contents);
             }
+            report_peer(network, scores, peer_id, PeerBehavior::Invalid);
         }
     }
 
     None
 }
+
+// Responses taking more than half of the timeout are slow.
+fn is_slow(started: Instant, timeout: Duration) -> bool {
+    started.elapsed() > timeout / 2
+}
+
+// Records the behavior of the peer, and disconnects it once banned.
+fn report_peer(
+    network: &anemo::Network,
+    scores: &PeerScores,
+    peer_id: PeerId,
+    behavior: PeerBehavior,
+) {
+    if scores.report(peer_id, behavior) {
+        let _ = network.disconnect(peer_id);
+    }
+}
+
+async fn get_checkpoint_summary_from_archive(
+    archive: Option<Arc<ArchiveReader>>,
+    sequence_number: CheckpointSequenceNumber,
+) -> Option<Checkpoint> {
+    archive?
+        .get_checkpoint_summary(sequence_number)
+        .await
+        .tap_err(|e| debug!("unable to get checkpoint {sequence_number} from the archive: {e}"))
+        .ok()
+        .flatten()
+}
+
+async fn get_checkpoint_contents_from_archive<S>(
+    archive: Option<Arc<ArchiveReader>>,
+    store: S,
+    checkpoint: &VerifiedCheckpoint,
+) -> Option<FullCheckpointContents>
+where
+    S: WriteStore,
+    <S as ReadStore>::Error: std::error::Error,
+{
+    let sequence_number = *checkpoint.sequence_number();
+    // The contents are verified against the digest of the checkpoint by the archive
+    let contents = archive?
+        .get_checkpoint_contents(sequence_number, checkpoint.content_digest)
+        .await
+        .tap_err(|e| {
+            debug!("unable to get contents of checkpoint {sequence_number} from the archive: {e}")
+        })
+        .ok()
+        .flatten()?;
+    store
+        .insert_checkpoint_contents(VerifiedCheckpointContents::new_unchecked(contents.clone()))
+        .expect("store operation should not fail");
+    Some(contents)
+}
+
+async fn get_checkpoint_summary_from_followed(
+    followed: Option<Arc<FullnodeReader>>,
+    sequence_number: CheckpointSequenceNumber,
+) -> Option<Checkpoint> {
+    followed?
+        .get_checkpoint_summary(sequence_number)
+        .await
+        .tap_err(|e| {
+            debug!("unable to get checkpoint {sequence_number} from the followed fullnode: {e}")
+        })
+        .ok()
+}
+
+async fn get_checkpoint_contents_from_followed<S>(
+    followed: Option<Arc<FullnodeReader>>,
+    store: S,
+    checkpoint: &VerifiedCheckpoint,
+) -> Option<FullCheckpointContents>
+where
+    S: WriteStore,
+    <S as ReadStore>::Error: std::error::Error,
+{
+    let sequence_number = *checkpoint.sequence_number();
+    // The contents are verified against the digest of the checkpoint by the reader
+    let contents = followed?
+        .get_checkpoint_contents(sequence_number, checkpoint.content_digest)
+        .await
+        .tap_err(|e| {
+            debug!(
+                "unable to get contents of checkpoint {sequence_number} from the followed \
+                fullnode: {e}"
+            )
+        })
+        .ok()?;
+    store
+        .insert_checkpoint_contents(VerifiedCheckpointContents::new_unchecked(contents.clone()))
+        .expect("store operation should not fail");
+    Some(contents)
+}
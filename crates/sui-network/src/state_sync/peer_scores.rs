@@ -0,0 +1,196 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scoring of the peers StateSync syncs from.
+//!
+//! Peers gain score by serving valid data, lose some when they fail to respond or respond slowly,
+//! and lose a lot when they serve data failing verification. A peer whose score drops to the ban
+//! threshold is banned for a while: it is disconnected, never queried again, and its pushes are
+//! ignored. Once the ban is lifted, either when it expires or manually, the peer starts over with
+//! a neutral score. The ban list is persisted so that bans outlive restarts.
+
+use anemo::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tap::TapFallible;
+use tracing::{info, warn};
+
+/// The highest score of a peer, so that a long history of valid data does not protect a peer
+/// which starts misbehaving.
+const MAX_SCORE: i64 = 100;
+
+/// The score at which a peer is banned.
+const BAN_THRESHOLD: i64 = -100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerBehavior {
+    /// The peer served valid data in time.
+    Valid,
+    /// The peer failed to respond, or responded slowly.
+    Slow,
+    /// The peer served data failing verification.
+    Invalid,
+}
+
+impl PeerBehavior {
+    fn score_change(&self) -> i64 {
+        match self {
+            PeerBehavior::Valid => 1,
+            PeerBehavior::Slow => -10,
+            PeerBehavior::Invalid => -50,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BannedPeer {
+    pub peer_id: PeerId,
+    /// When the ban expires, in milliseconds since the unix epoch.
+    pub banned_until_ms: u64,
+}
+
+#[derive(Debug)]
+pub struct PeerScores {
+    ban_duration: Duration,
+    // The file where the ban list is persisted.
+    path: Option<PathBuf>,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    scores: HashMap<PeerId, i64>,
+    // The expiry of the bans, in milliseconds since the unix epoch.
+    bans: HashMap<PeerId, u64>,
+}
+
+impl PeerScores {
+    /// Creates the scores, with the bans persisted in `path` if any.
+    pub fn new(ban_duration: Duration, path: Option<PathBuf>) -> Self {
+        let bans = path
+            .as_deref()
+            .filter(|path| path.exists())
+            .and_then(|path| {
+                read_ban_list(path)
+                    .tap_err(|e| warn!("unable to read the ban list of state sync: {e}"))
+                    .ok()
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .map(|peer| (peer.peer_id, peer.banned_until_ms))
+            .collect();
+        Self {
+            ban_duration,
+            path,
+            inner: Mutex::new(Inner {
+                scores: HashMap::new(),
+                bans,
+            }),
+        }
+    }
+
+    /// Records the behavior of the peer, and returns whether the peer got banned because of it.
+    pub fn report(&self, peer_id: PeerId, behavior: PeerBehavior) -> bool {
+        let now = now_ms();
+        let mut inner = self.inner.lock().unwrap();
+        inner
+            .bans
+            .retain(|_, banned_until_ms| *banned_until_ms > now);
+        if inner.bans.contains_key(&peer_id) {
+            return false;
+        }
+
+        let score = inner.scores.entry(peer_id).or_default();
+        *score = std::cmp::min(*score + behavior.score_change(), MAX_SCORE);
+        if *score > BAN_THRESHOLD {
+            return false;
+        }
+
+        inner.scores.remove(&peer_id);
+        let banned_until_ms = now.saturating_add(self.ban_duration.as_millis() as u64);
+        inner.bans.insert(peer_id, banned_until_ms);
+        warn!(
+            "banning peer {peer_id} from state sync for {}s, as it served invalid or slow data",
+            self.ban_duration.as_secs()
+        );
+        self.persist(&inner);
+        true
+    }
+
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .bans
+            .get(peer_id)
+            .map_or(false, |banned_until_ms| *banned_until_ms > now_ms())
+    }
+
+    /// Returns the peers currently banned, ordered by the expiry of their ban.
+    pub fn banned_peers(&self) -> Vec<BannedPeer> {
+        let now = now_ms();
+        let mut banned: Vec<_> = self
+            .inner
+            .lock()
+            .unwrap()
+            .bans
+            .iter()
+            .filter(|(_, banned_until_ms)| **banned_until_ms > now)
+            .map(|(peer_id, banned_until_ms)| BannedPeer {
+                peer_id: *peer_id,
+                banned_until_ms: *banned_until_ms,
+            })
+            .collect();
+        banned.sort_by_key(|peer| (peer.banned_until_ms, peer.peer_id.0));
+        banned
+    }
+
+    /// Lifts the ban of the peer, and returns whether it was banned.
+    pub fn unban(&self, peer_id: &PeerId) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.scores.remove(peer_id);
+        let banned = inner
+            .bans
+            .remove(peer_id)
+            .map_or(false, |banned_until_ms| banned_until_ms > now_ms());
+        if banned {
+            info!("peer {peer_id} unbanned from state sync");
+        }
+        self.persist(&inner);
+        banned
+    }
+
+    fn persist(&self, inner: &Inner) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let banned: Vec<_> = inner
+            .bans
+            .iter()
+            .map(|(peer_id, banned_until_ms)| BannedPeer {
+                peer_id: *peer_id,
+                banned_until_ms: *banned_until_ms,
+            })
+            .collect();
+        let _ = bcs::to_bytes(&banned)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(std::fs::write(path, bytes)?))
+            .tap_err(|e| warn!("unable to persist the ban list of state sync: {e}"));
+    }
+}
+
+fn read_ban_list(path: &Path) -> anyhow::Result<Vec<BannedPeer>> {
+    Ok(bcs::from_bytes(&std::fs::read(path)?)?)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
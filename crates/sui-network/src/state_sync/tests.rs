@@ -3,6 +3,8 @@
 
 use crate::{
     state_sync::{
+        archive::{append_to_archive, read_manifest, ArchiveReader},
+        peer_scores::{PeerBehavior, PeerScores},
         test_utils::{empty_contents, CommitteeFixture},
         Builder, GetCheckpointSummaryRequest, PeerStateSyncInfo, StateSync, StateSyncMessage,
         UnstartedStateSync,
@@ -11,6 +13,8 @@ use crate::{
 };
 use anemo::{PeerId, Request};
 use std::{collections::HashMap, time::Duration};
+use sui_config::p2p::{FollowerConfig, StateSyncConfig};
+use sui_storage::object_store::{ObjectStoreConfig, ObjectStoreType};
 use sui_types::{
     messages_checkpoint::CheckpointDigest,
     storage::{ReadStore, SharedInMemoryStore, WriteStore},
@@ -380,3 +384,276 @@ async fn sync_with_checkpoints_being_inserted() {
         &sequence_number_to_digest
     );
 }
+
+#[tokio::test]
+async fn sync_from_archive() {
+    let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
+    // build mock data
+    let (ordered_checkpoints, _sequence_number_to_digest, _checkpoints) =
+        committee.make_checkpoints(100, None);
+
+    // Archive all of the checkpoints, in two files
+    let dir = tempfile::tempdir().unwrap();
+    let archive_config = ObjectStoreConfig {
+        object_store: Some(ObjectStoreType::File),
+        directory: Some(dir.path().to_path_buf()),
+        ..Default::default()
+    };
+    let archive_store = archive_config.make().unwrap();
+    for chunk in ordered_checkpoints.chunks(50) {
+        let checkpoints = chunk
+            .iter()
+            .map(|checkpoint| {
+                (
+                    checkpoint.clone().into_inner(),
+                    empty_contents().into_inner(),
+                )
+            })
+            .collect();
+        append_to_archive(&archive_store, checkpoints)
+            .await
+            .unwrap();
+    }
+    assert_eq!(read_manifest(&archive_store).await.unwrap().files.len(), 2);
+
+    // Build two nodes, Node 1 syncing from the archive
+    let config = StateSyncConfig {
+        archive_object_store: Some(archive_config),
+        ..Default::default()
+    };
+    let (builder, server) = Builder::new()
+        .config(config)
+        .store(SharedInMemoryStore::default())
+        .build();
+    let network_1 = build_network(|router| router.add_rpc_service(server));
+    let (mut event_loop_1, _handle_1) = builder.build(network_1.clone());
+    let (builder, server) = Builder::new().store(SharedInMemoryStore::default()).build();
+    let network_2 = build_network(|router| router.add_rpc_service(server));
+    let (event_loop_2, _handle_2) = builder.build(network_2.clone());
+    network_1.connect(network_2.local_addr()).await.unwrap();
+
+    // Init the root committee in both nodes
+    event_loop_1.store.inner_mut().insert_genesis_state(
+        ordered_checkpoints.first().cloned().unwrap(),
+        empty_contents(),
+        committee.committee().to_owned(),
+    );
+    event_loop_2.store.inner_mut().insert_genesis_state(
+        ordered_checkpoints.first().cloned().unwrap(),
+        empty_contents(),
+        committee.committee().to_owned(),
+    );
+
+    // Node 2 claims to be at the latest checkpoint, but pruned all of the history
+    event_loop_1.peer_heights.write().unwrap().peers.insert(
+        network_2.peer_id(),
+        PeerStateSyncInfo {
+            genesis_checkpoint_digest: *ordered_checkpoints[0].digest(),
+            on_same_chain_as_us: true,
+            height: *ordered_checkpoints.last().unwrap().sequence_number(),
+        },
+    );
+    event_loop_1
+        .peer_heights
+        .write()
+        .unwrap()
+        .insert_checkpoint(ordered_checkpoints.last().cloned().unwrap().into_inner());
+
+    // Sync the summaries, then the contents, from the archive
+    event_loop_1.maybe_start_checkpoint_summary_sync_task();
+    event_loop_1.tasks.join_next().await.unwrap().unwrap();
+    assert_eq!(
+        ordered_checkpoints.last().map(|x| x.data()),
+        Some(
+            event_loop_1
+                .store
+                .get_highest_verified_checkpoint()
+                .unwrap()
+                .data()
+        )
+    );
+
+    event_loop_1.maybe_start_checkpoint_contents_sync_task();
+    event_loop_1.tasks.join_next().await.unwrap().unwrap();
+    assert_eq!(
+        ordered_checkpoints.last().map(|x| x.data()),
+        Some(
+            event_loop_1
+                .store
+                .get_highest_synced_checkpoint()
+                .unwrap()
+                .data()
+        )
+    );
+}
+
+#[tokio::test]
+async fn follow_archive_without_peers() {
+    let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
+    let (ordered_checkpoints, _sequence_number_to_digest, _checkpoints) =
+        committee.make_checkpoints(20, None);
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive_config = ObjectStoreConfig {
+        object_store: Some(ObjectStoreType::File),
+        directory: Some(dir.path().to_path_buf()),
+        ..Default::default()
+    };
+    let checkpoints = ordered_checkpoints
+        .iter()
+        .map(|checkpoint| {
+            (
+                checkpoint.clone().into_inner(),
+                empty_contents().into_inner(),
+            )
+        })
+        .collect();
+    append_to_archive(&archive_config.make().unwrap(), checkpoints)
+        .await
+        .unwrap();
+
+    // Build two nodes, Node 1 following the archive
+    let config = StateSyncConfig {
+        archive_object_store: Some(archive_config),
+        follower: Some(FollowerConfig::default()),
+        ..Default::default()
+    };
+    let (builder, server) = Builder::new()
+        .config(config)
+        .store(SharedInMemoryStore::default())
+        .build();
+    let network_1 = build_network(|router| router.add_rpc_service(server));
+    let (mut event_loop_1, _handle_1) = builder.build(network_1.clone());
+    let (builder, server) = Builder::new().store(SharedInMemoryStore::default()).build();
+    let network_2 = build_network(|router| router.add_rpc_service(server));
+    let (_event_loop_2, _handle_2) = builder.build(network_2.clone());
+    network_1.connect(network_2.local_addr()).await.unwrap();
+
+    event_loop_1.store.inner_mut().insert_genesis_state(
+        ordered_checkpoints.first().cloned().unwrap(),
+        empty_contents(),
+        committee.committee().to_owned(),
+    );
+
+    // Peers are not queried
+    event_loop_1.spawn_get_latest_from_peer(network_2.peer_id());
+    assert!(event_loop_1.tasks.is_empty());
+
+    // The latest checkpoint is discovered from the archive
+    event_loop_1.handle_tick(std::time::Instant::now());
+    event_loop_1.tasks.join_next().await.unwrap().unwrap();
+    assert_eq!(
+        event_loop_1
+            .peer_heights
+            .read()
+            .unwrap()
+            .highest_known_checkpoint_sequence_number(),
+        Some(*ordered_checkpoints.last().unwrap().sequence_number())
+    );
+    assert!(matches!(
+        event_loop_1.mailbox.try_recv().unwrap(),
+        StateSyncMessage::StartSyncJob
+    ));
+
+    event_loop_1.maybe_start_checkpoint_summary_sync_task();
+    event_loop_1.tasks.join_next().await.unwrap().unwrap();
+    event_loop_1.maybe_start_checkpoint_contents_sync_task();
+    event_loop_1.tasks.join_next().await.unwrap().unwrap();
+    assert_eq!(
+        ordered_checkpoints.last().map(|x| x.data()),
+        Some(
+            event_loop_1
+                .store
+                .get_highest_synced_checkpoint()
+                .unwrap()
+                .data()
+        )
+    );
+}
+
+#[test]
+fn peer_scores_ban_and_persist() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("banned-peers");
+    let peer_id = PeerId([1; 32]);
+    let scores = PeerScores::new(Duration::from_secs(60), Some(path.clone()));
+
+    // Valid data does not make up for invalid data for long
+    assert!(!scores.report(peer_id, PeerBehavior::Valid));
+    assert!(!scores.report(peer_id, PeerBehavior::Valid));
+    assert!(!scores.report(peer_id, PeerBehavior::Invalid));
+    assert!(!scores.report(peer_id, PeerBehavior::Invalid));
+    assert!(!scores.is_banned(&peer_id));
+    assert!(scores.report(peer_id, PeerBehavior::Slow));
+    assert!(scores.is_banned(&peer_id));
+    assert!(!scores.report(peer_id, PeerBehavior::Invalid));
+
+    // The ban outlives restarts, until lifted
+    let scores = PeerScores::new(Duration::from_secs(60), Some(path.clone()));
+    let banned = scores.banned_peers();
+    assert_eq!(banned.len(), 1);
+    assert_eq!(banned[0].peer_id, peer_id);
+    assert!(scores.unban(&peer_id));
+    assert!(!scores.unban(&peer_id));
+    let scores = PeerScores::new(Duration::from_secs(60), Some(path));
+    assert!(!scores.is_banned(&peer_id));
+
+    // Bans expire
+    let scores = PeerScores::new(Duration::ZERO, None);
+    assert!(!scores.report(peer_id, PeerBehavior::Invalid));
+    assert!(scores.report(peer_id, PeerBehavior::Invalid));
+    assert!(!scores.is_banned(&peer_id));
+    assert!(scores.banned_peers().is_empty());
+}
+
+#[tokio::test]
+async fn archive_file_not_matching_manifest() {
+    let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
+    let (ordered_checkpoints, _, _) = committee.make_checkpoints(10, None);
+
+    let dir = tempfile::tempdir().unwrap();
+    let store = ObjectStoreConfig {
+        object_store: Some(ObjectStoreType::File),
+        directory: Some(dir.path().to_path_buf()),
+        ..Default::default()
+    }
+    .make()
+    .unwrap();
+    let checkpoints: Vec<_> = ordered_checkpoints
+        .iter()
+        .map(|checkpoint| {
+            (
+                checkpoint.clone().into_inner(),
+                empty_contents().into_inner(),
+            )
+        })
+        .collect();
+    append_to_archive(&store, checkpoints.clone())
+        .await
+        .unwrap();
+
+    let reader = ArchiveReader::from_store(store.clone());
+    assert_eq!(
+        reader
+            .get_checkpoint_summary(3)
+            .await
+            .unwrap()
+            .unwrap()
+            .data(),
+        ordered_checkpoints[3].data()
+    );
+    assert!(reader.get_checkpoint_summary(10).await.unwrap().is_none());
+
+    // The checkpoints must follow the archived ones
+    assert!(append_to_archive(&store, checkpoints[..1].to_vec())
+        .await
+        .is_err());
+
+    // Tamper with the archived file
+    let manifest = read_manifest(&store).await.unwrap();
+    let path = manifest.files[0].path();
+    let bytes = bcs::to_bytes(&checkpoints[..9].to_vec()).unwrap();
+    store.put(&path, bytes.into()).await.unwrap();
+    let reader = ArchiveReader::from_store(store);
+    assert!(reader.get_checkpoint_summary(3).await.is_err());
+}
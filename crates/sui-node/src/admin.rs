@@ -1,17 +1,21 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::parameter_overrides::TunableParameter;
 use crate::SuiNode;
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
     routing::{get, post},
     Router,
 };
+use fastcrypto::encoding::{Encoding, Hex};
+use mysten_common::memory_budget;
 use mysten_metrics::spawn_monitored_task;
 use serde::Deserialize;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
+use std::time::Instant;
 use sui_types::error::SuiError;
 use telemetry_subscribers::FilterHandle;
 use tracing::info;
@@ -34,29 +38,101 @@ use tracing::info;
 // View current all capabilities from all authorities that have been received by this node:
 //
 //   $ curl 'http://127.0.0.1:1337/capabilities'
+//
+// View the protocol versions supported by the binary, and the stake of the committee supporting
+// each version, answered with 503 if the committee votes to upgrade to an unsupported version:
+//
+//   $ curl 'http://127.0.0.1:1337/protocol-versions'
+//
+// View the states of the components of the node, answered with 503 until all of them are ready:
+//
+//   $ curl 'http://127.0.0.1:1337/components'
+//
+// View the health score and the outcome of the self-checks of the node, answered with 503 while
+// any check fails:
+//
+//   $ curl 'http://127.0.0.1:1337/health'
+//
+// View the peers banned by state sync for serving invalid or slow data, with the expiry of their
+// ban in milliseconds since the unix epoch:
+//
+//   $ curl 'http://127.0.0.1:1337/banned-peers'
+//
+// View the memory reserved by the components of the node from its memory budget, answered with
+// 503 while the budget is exceeded:
+//
+//   $ curl 'http://127.0.0.1:1337/memory'
+//
+// Lift the ban of a peer:
+//
+//   $ curl -X POST 'http://127.0.0.1:1337/unban-peer?peer_id=<peer-id>'
+//
+// View the effective values of the tunable consensus parameters:
+//
+//   $ curl 'http://127.0.0.1:1337/parameters'
+//
+// Override max-batch-delay-ms, with the admin-interface-token of the node config:
+//
+//   $ curl -X POST -H 'Authorization: Bearer <token>' \
+//       'http://127.0.0.1:1337/set-parameter?name=max-batch-delay-ms&value=150'
+//
+// Clear the override of max-batch-delay-ms, which goes back to its configured value:
+//
+//   $ curl -X POST -H 'Authorization: Bearer <token>' \
+//       'http://127.0.0.1:1337/clear-parameter?name=max-batch-delay-ms'
+//
+// List the oldest transactions pending in the ingress queues of the local worker, or in the
+// consensus adapter with queue=consensus-adapter, then the next ones from the returned cursor:
+//
+//   $ curl -H 'Authorization: Bearer <token>' \
+//       'http://127.0.0.1:1337/pending-transactions?queue=worker&limit=100'
+//   $ curl -H 'Authorization: Bearer <token>' \
+//       'http://127.0.0.1:1337/pending-transactions?queue=worker&limit=100&cursor=<cursor>'
 
 const LOGGING_ROUTE: &str = "/logging";
 const SET_BUFFER_STAKE_ROUTE: &str = "/set-override-buffer-stake";
 const CLEAR_BUFFER_STAKE_ROUTE: &str = "/clear-override-buffer-stake";
 const FORCE_CLOSE_EPOCH: &str = "/force-close-epoch";
 const CAPABILITIES: &str = "/capabilities";
+const PROTOCOL_VERSIONS: &str = "/protocol-versions";
+const COMPONENTS: &str = "/components";
+const HEALTH: &str = "/health";
+const BANNED_PEERS: &str = "/banned-peers";
+const UNBAN_PEER: &str = "/unban-peer";
+const PARAMETERS: &str = "/parameters";
+const MEMORY: &str = "/memory";
+const SET_PARAMETER: &str = "/set-parameter";
+const CLEAR_PARAMETER: &str = "/clear-parameter";
+const PENDING_TRANSACTIONS: &str = "/pending-transactions";
+
+// The number of transactions listed at once by default, and at most.
+const DEFAULT_PENDING_TRANSACTIONS_LIMIT: usize = 100;
+const MAX_PENDING_TRANSACTIONS_LIMIT: usize = 1_000;
 
 struct AppState {
     node: Arc<SuiNode>,
     filter_handle: FilterHandle,
+    // The bearer token of the routes changing the consensus parameters, which are disabled
+    // without one.
+    token: Option<String>,
 }
 
 pub fn start_admin_server(node: Arc<SuiNode>, port: u16, filter_handle: FilterHandle) {
     let filter = filter_handle.get().unwrap();
 
+    let token = node.config.admin_interface_token.clone();
     let app_state = AppState {
         node,
         filter_handle,
+        token,
     };
 
     let app = Router::new()
         .route(LOGGING_ROUTE, get(get_filter))
         .route(CAPABILITIES, get(capabilities))
+        .route(PROTOCOL_VERSIONS, get(protocol_versions))
+        .route(COMPONENTS, get(components))
+        .route(HEALTH, get(health))
         .route(LOGGING_ROUTE, post(set_filter))
         .route(
             SET_BUFFER_STAKE_ROUTE,
@@ -67,6 +143,13 @@ pub fn start_admin_server(node: Arc<SuiNode>, port: u16, filter_handle: FilterHa
             post(clear_override_protocol_upgrade_buffer_stake),
         )
         .route(FORCE_CLOSE_EPOCH, post(force_close_epoch))
+        .route(BANNED_PEERS, get(banned_peers))
+        .route(MEMORY, get(memory))
+        .route(UNBAN_PEER, post(unban_peer))
+        .route(PARAMETERS, get(parameters))
+        .route(SET_PARAMETER, post(set_parameter))
+        .route(CLEAR_PARAMETER, post(clear_parameter))
+        .route(PENDING_TRANSACTIONS, get(pending_transactions))
         .with_state(Arc::new(app_state));
 
     let socket_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
@@ -116,6 +199,106 @@ async fn capabilities(State(state): State<Arc<AppState>>) -> (StatusCode, String
     (StatusCode::OK, output)
 }
 
+async fn protocol_versions(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let epoch_store = state.node.state().load_epoch_store_one_call_per_task();
+    let readiness = state
+        .node
+        .state()
+        .check_protocol_upgrade_readiness(&epoch_store);
+    let supported = readiness.supported_versions;
+
+    let mut output = format!(
+        "current: {}\nsupported: {} to {}\nnext epoch: {} ({})\n",
+        readiness.current_version.as_u64(),
+        supported.min.as_u64(),
+        supported.max.as_u64(),
+        readiness.next_version.as_u64(),
+        if readiness.is_ready() {
+            "supported"
+        } else {
+            "not supported, the binary must be upgraded"
+        },
+    );
+    output.push_str(&format!(
+        "quorum threshold: {} of {}\n",
+        readiness.quorum_threshold, readiness.total_stake
+    ));
+    for (version, stake) in &readiness.stake_by_version {
+        output.push_str(&format!(
+            "version {}: supported by {stake}{}\n",
+            version.as_u64(),
+            if *stake >= readiness.quorum_threshold {
+                " (quorum)"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    let status = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, output)
+}
+
+async fn components(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let lifecycle = state.node.lifecycle();
+
+    let mut output = String::new();
+    for (component, component_state, dependencies) in lifecycle.states() {
+        output.push_str(&format!("{component}: {component_state}"));
+        if !dependencies.is_empty() {
+            output.push_str(&format!(" (depends on {})", dependencies.join(", ")));
+        }
+        output.push('\n');
+    }
+
+    let status = if lifecycle.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, output)
+}
+
+async fn health(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let report = state.node.health();
+
+    let mut output = format!("score: {}\n", report.score());
+    for (check, passing) in &report.checks {
+        let outcome = if *passing { "passing" } else { "failing" };
+        output.push_str(&format!("{check}: {outcome}\n"));
+    }
+
+    let status = if report.is_degraded() {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (status, output)
+}
+
+async fn memory() -> (StatusCode, String) {
+    let budget = memory_budget::global();
+
+    let mut output = match budget.soft_limit() {
+        Some(soft_limit) => format!("used: {} of {soft_limit} bytes\n", budget.used()),
+        None => format!("used: {} bytes, without limit\n", budget.used()),
+    };
+    for (component, used) in budget.usage() {
+        output.push_str(&format!("{component}: {used} bytes\n"));
+    }
+
+    let status = if budget.is_within_budget() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, output)
+}
+
 #[derive(Deserialize)]
 struct Epoch {
     epoch: u64,
@@ -188,3 +371,210 @@ async fn force_close_epoch(
         Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
     }
 }
+
+async fn banned_peers(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let mut output = String::new();
+    for peer in state.node.banned_peers() {
+        output.push_str(&format!(
+            "{}: banned until {}\n",
+            peer.peer_id, peer.banned_until_ms
+        ));
+    }
+
+    (StatusCode::OK, output)
+}
+
+#[derive(Deserialize)]
+struct UnbanPeer {
+    peer_id: anemo::PeerId,
+}
+
+async fn unban_peer(
+    State(state): State<Arc<AppState>>,
+    unban_peer: Query<UnbanPeer>,
+) -> (StatusCode, String) {
+    let Query(UnbanPeer { peer_id }) = unban_peer;
+
+    if state.node.unban_peer(&peer_id) {
+        (StatusCode::OK, format!("peer {peer_id} unbanned\n"))
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            format!("peer {peer_id} is not banned\n"),
+        )
+    }
+}
+
+async fn parameters(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    match state.node.effective_parameters() {
+        Ok(parameters) => {
+            let mut output = String::new();
+            for (parameter, value, overridden) in parameters {
+                output.push_str(&format!(
+                    "{parameter} = {value}{}\n",
+                    if overridden { " (overridden)" } else { "" },
+                ));
+            }
+            (StatusCode::OK, output)
+        }
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let Some(token) = &state.token else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "admin-interface-token is not configured\n".to_string(),
+        ));
+    };
+    let expected = format!("Bearer {token}");
+    match headers.get(AUTHORIZATION) {
+        Some(value) if constant_time_eq(value.as_bytes(), expected.as_bytes()) => Ok(()),
+        _ => Err((
+            StatusCode::UNAUTHORIZED,
+            "invalid admin token\n".to_string(),
+        )),
+    }
+}
+
+// Compares the tokens in a time which does not depend on their contents, so that the expected
+// token cannot be guessed byte by byte. Only their lengths are leaked.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Deserialize)]
+struct SetParameter {
+    name: String,
+    value: u64,
+}
+
+async fn set_parameter(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    set_parameter: Query<SetParameter>,
+) -> (StatusCode, String) {
+    if let Err(err) = authorize(&state, &headers) {
+        return err;
+    }
+    let Query(SetParameter { name, value }) = set_parameter;
+    let parameter: TunableParameter = match name.parse() {
+        Ok(parameter) => parameter,
+        Err(err) => return (StatusCode::BAD_REQUEST, format!("{err}\n")),
+    };
+
+    match state.node.set_parameter_override(parameter, value).await {
+        Ok(()) => {
+            info!(%parameter, value, "Consensus parameter overridden");
+            (StatusCode::OK, format!("{parameter} set to '{value}'\n"))
+        }
+        Err(err) => (StatusCode::BAD_REQUEST, format!("{err}\n")),
+    }
+}
+
+#[derive(Deserialize)]
+struct ClearParameter {
+    name: String,
+}
+
+async fn clear_parameter(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    clear_parameter: Query<ClearParameter>,
+) -> (StatusCode, String) {
+    if let Err(err) = authorize(&state, &headers) {
+        return err;
+    }
+    let Query(ClearParameter { name }) = clear_parameter;
+    let parameter: TunableParameter = match name.parse() {
+        Ok(parameter) => parameter,
+        Err(err) => return (StatusCode::BAD_REQUEST, format!("{err}\n")),
+    };
+
+    match state.node.clear_parameter_override(parameter).await {
+        Ok(()) => {
+            info!(%parameter, "Consensus parameter override cleared");
+            (StatusCode::OK, format!("{parameter} override cleared\n"))
+        }
+        Err(err) => (StatusCode::BAD_REQUEST, format!("{err}\n")),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum PendingQueue {
+    Worker,
+    ConsensusAdapter,
+}
+
+#[derive(Deserialize)]
+struct PendingTransactions {
+    queue: PendingQueue,
+    cursor: Option<u64>,
+    limit: Option<usize>,
+}
+
+async fn pending_transactions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    pending_transactions: Query<PendingTransactions>,
+) -> (StatusCode, String) {
+    if let Err(err) = authorize(&state, &headers) {
+        return err;
+    }
+    let Query(PendingTransactions {
+        queue,
+        cursor,
+        limit,
+    }) = pending_transactions;
+    let limit = limit
+        .unwrap_or(DEFAULT_PENDING_TRANSACTIONS_LIMIT)
+        .min(MAX_PENDING_TRANSACTIONS_LIMIT);
+
+    // The id, digest, size, lane and submission time of each pending transaction.
+    let pending = match queue {
+        PendingQueue::Worker => {
+            state
+                .node
+                .pending_worker_transactions(cursor, limit)
+                .map(|pending| {
+                    pending
+                        .into_iter()
+                        .map(|p| (p.id, Hex::encode(p.digest), p.size, p.lane, p.submitted_at))
+                        .collect::<Vec<_>>()
+                })
+        }
+        PendingQueue::ConsensusAdapter => state
+            .node
+            .pending_consensus_transactions(cursor, limit)
+            .await
+            .map(|pending| {
+                pending
+                    .into_iter()
+                    .map(|p| (p.id, format!("{:?}", p.key), p.size, p.lane, p.submitted_at))
+                    .collect()
+            }),
+    };
+    let pending = match pending {
+        Ok(pending) => pending,
+        Err(err) => return (StatusCode::SERVICE_UNAVAILABLE, format!("{err}\n")),
+    };
+
+    let now = Instant::now();
+    let mut output = String::new();
+    for (_, digest, size, lane, submitted_at) in &pending {
+        output.push_str(&format!(
+            "{digest}: {size} bytes, {lane} lane, pending for {} ms\n",
+            now.saturating_duration_since(*submitted_at).as_millis()
+        ));
+    }
+    // There may be more transactions to list after a full page.
+    if pending.len() == limit {
+        if let Some((id, ..)) = pending.last() {
+            output.push_str(&format!("next cursor: {id}\n"));
+        }
+    }
+
+    (StatusCode::OK, output)
+}
@@ -0,0 +1,281 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use mysten_metrics::RegistryService;
+use parking_lot::Mutex;
+use prometheus::{
+    register_int_counter_with_registry, register_int_gauge_vec_with_registry,
+    register_int_gauge_with_registry, IntCounter, IntGauge, IntGaugeVec, Registry,
+};
+use std::fmt;
+use tracing::{info, warn};
+
+/// The checks run by the node on itself. The Narwhal and peer checks only run on validators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthCheck {
+    /// The round of the primary advances.
+    NarwhalRounds,
+    /// The primary forms certificates.
+    NarwhalCertificates,
+    /// Consensus commits.
+    NarwhalCommits,
+    /// The stores of the node can be read.
+    Storage,
+    /// At least two thirds of the peers of Narwhal are connected.
+    Peers,
+}
+
+impl HealthCheck {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthCheck::NarwhalRounds => "narwhal-rounds",
+            HealthCheck::NarwhalCertificates => "narwhal-certificates",
+            HealthCheck::NarwhalCommits => "narwhal-commits",
+            HealthCheck::Storage => "storage",
+            HealthCheck::Peers => "peers",
+        }
+    }
+}
+
+impl fmt::Display for HealthCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The progress of Narwhal, read from the metrics of the primary and of consensus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NarwhalProgress {
+    pub round: u64,
+    pub certificates_created: u64,
+    pub committed_round: u64,
+}
+
+impl NarwhalProgress {
+    /// Reads the progress from the registries of the node, if Narwhal runs.
+    pub fn from_registry(registry_service: &RegistryService) -> Option<Self> {
+        let metrics = registry_service.gather_all();
+        let value = |name: &str| {
+            let family = metrics.iter().find(|family| family.get_name() == name)?;
+            let metric = family.get_metric().first()?;
+            let value = if metric.has_counter() {
+                metric.get_counter().get_value()
+            } else {
+                metric.get_gauge().get_value()
+            };
+            Some(value as u64)
+        };
+        Some(Self {
+            round: value("current_round")?,
+            certificates_created: value("certificates_created")?,
+            committed_round: value("last_committed_round")?,
+        })
+    }
+}
+
+/// What a run of the checks observed.
+#[derive(Clone, Debug, Default)]
+pub struct HealthObservation {
+    /// None on fullnodes, and while Narwhal is not running on validators.
+    pub narwhal: Option<NarwhalProgress>,
+    pub validator: bool,
+    pub storage_readable: bool,
+    /// The number of peers connected and known, on validators.
+    pub peers: Option<(usize, usize)>,
+}
+
+/// The outcome of the latest run of the checks.
+#[derive(Clone, Debug, Default)]
+pub struct HealthReport {
+    pub checks: Vec<(HealthCheck, bool)>,
+}
+
+impl HealthReport {
+    /// The share of the checks passing, in percent.
+    pub fn score(&self) -> u64 {
+        if self.checks.is_empty() {
+            return 100;
+        }
+        let passing = self.checks.iter().filter(|(_, passing)| *passing).count();
+        (passing * 100 / self.checks.len()) as u64
+    }
+
+    /// Whether any check fails.
+    pub fn is_degraded(&self) -> bool {
+        self.checks.iter().any(|(_, passing)| !passing)
+    }
+}
+
+pub struct HealthMetrics {
+    health_score: IntGauge,
+    health_check_passing: IntGaugeVec,
+    health_degraded: IntCounter,
+}
+
+impl HealthMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            health_score: register_int_gauge_with_registry!(
+                "health_score",
+                "The share of the self-checks of the node passing, in percent",
+                registry
+            )
+            .unwrap(),
+            health_check_passing: register_int_gauge_vec_with_registry!(
+                "health_check_passing",
+                "Whether a self-check of the node passes",
+                &["check"],
+                registry
+            )
+            .unwrap(),
+            health_degraded: register_int_counter_with_registry!(
+                "health_degraded",
+                "Number of times the node entered a degraded state",
+                registry
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// Runs the self-checks of the node: Narwhal is live when its rounds, certificates and commits
+/// all changed since the previous run. A failing check puts the node in a degraded state, which
+/// is logged and counted once until all the checks pass again.
+pub struct HealthMonitor {
+    metrics: HealthMetrics,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    previous_narwhal: Option<NarwhalProgress>,
+    report: HealthReport,
+}
+
+impl HealthMonitor {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            metrics: HealthMetrics::new(registry),
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub fn report(&self) -> HealthReport {
+        self.inner.lock().report.clone()
+    }
+
+    pub fn update(&self, observation: HealthObservation) -> HealthReport {
+        let mut inner = self.inner.lock();
+        let mut checks = vec![(HealthCheck::Storage, observation.storage_readable)];
+        if observation.validator {
+            // Progress cannot be judged without a previous run observing Narwhal.
+            let (rounds, certificates, commits) =
+                match (inner.previous_narwhal, observation.narwhal) {
+                    (Some(previous), Some(current)) => (
+                        current.round != previous.round,
+                        current.certificates_created != previous.certificates_created,
+                        current.committed_round != previous.committed_round,
+                    ),
+                    (None, Some(_)) => (true, true, true),
+                    (_, None) => (false, false, false),
+                };
+            checks.push((HealthCheck::NarwhalRounds, rounds));
+            checks.push((HealthCheck::NarwhalCertificates, certificates));
+            checks.push((HealthCheck::NarwhalCommits, commits));
+            let (connected, known) = observation.peers.unwrap_or_default();
+            checks.push((HealthCheck::Peers, connected * 3 >= known * 2));
+        }
+        inner.previous_narwhal = observation.narwhal;
+
+        let report = HealthReport { checks };
+        for (check, passing) in &report.checks {
+            self.metrics
+                .health_check_passing
+                .with_label_values(&[check.as_str()])
+                .set(*passing as i64);
+        }
+        self.metrics.health_score.set(report.score() as i64);
+        let failing: Vec<_> = report
+            .checks
+            .iter()
+            .filter(|(_, passing)| !passing)
+            .map(|(check, _)| check.as_str())
+            .collect();
+        match (inner.report.is_degraded(), report.is_degraded()) {
+            (false, true) => {
+                warn!(
+                    "Node entered a degraded state, failing {}",
+                    failing.join(", ")
+                );
+                self.metrics.health_degraded.inc();
+            }
+            (true, false) => info!("Node recovered from a degraded state"),
+            _ => (),
+        }
+        inner.report = report.clone();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HealthCheck, HealthMonitor, HealthObservation, NarwhalProgress};
+    use prometheus::Registry;
+
+    #[test]
+    fn test_narwhal_liveness() {
+        let monitor = HealthMonitor::new(&Registry::new());
+        let observe = |round, certificates_created, committed_round| HealthObservation {
+            narwhal: Some(NarwhalProgress {
+                round,
+                certificates_created,
+                committed_round,
+            }),
+            validator: true,
+            storage_readable: true,
+            peers: Some((3, 3)),
+        };
+
+        let report = monitor.update(observe(1, 1, 0));
+        assert_eq!(report.score(), 100);
+        assert!(!monitor.update(observe(2, 2, 1)).is_degraded());
+
+        // No commit since the previous run.
+        let report = monitor.update(observe(3, 3, 1));
+        assert!(report.is_degraded());
+        assert_eq!(report.score(), 80);
+        assert!(report
+            .checks
+            .contains(&(HealthCheck::NarwhalCommits, false)));
+        assert!(monitor.report().is_degraded());
+
+        // Narwhal is restarted at a new epoch, from round 0.
+        assert!(!monitor.update(observe(0, 0, 0)).is_degraded());
+    }
+
+    #[test]
+    fn test_storage_and_peers() {
+        let monitor = HealthMonitor::new(&Registry::new());
+        let fullnode = HealthObservation {
+            storage_readable: true,
+            ..HealthObservation::default()
+        };
+        let report = monitor.update(fullnode.clone());
+        assert_eq!(report.checks, vec![(HealthCheck::Storage, true)]);
+
+        let report = monitor.update(HealthObservation {
+            storage_readable: false,
+            ..fullnode
+        });
+        assert_eq!(report.score(), 0);
+
+        let report = monitor.update(HealthObservation {
+            narwhal: None,
+            validator: true,
+            storage_readable: true,
+            peers: Some((1, 3)),
+        });
+        assert!(report.checks.contains(&(HealthCheck::Peers, false)));
+        assert!(report.checks.contains(&(HealthCheck::NarwhalRounds, false)));
+    }
+}
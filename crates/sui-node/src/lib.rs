@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anemo::Network;
 use anemo_tower::callback::CallbackLayer;
@@ -24,39 +24,49 @@ use tokio::sync::broadcast;
 use tokio::sync::oneshot::Sender;
 use tokio::sync::{watch, Mutex};
 use tokio::task::JoinHandle;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
 use tower::ServiceBuilder;
 use tracing::{debug, warn};
 use tracing::{error_span, info, Instrument};
 
 use checkpoint_executor::CheckpointExecutor;
 pub use handle::SuiNodeHandle;
+use health::{HealthMonitor, HealthObservation, HealthReport, NarwhalProgress};
+use mysten_common::lifecycle::{ComponentState, Lifecycle};
+use mysten_common::memory_budget;
 use mysten_metrics::{spawn_monitored_task, RegistryService};
 use mysten_network::server::ServerBuilder;
+use narwhal_network::connectivity::ConnectionStatus;
 use narwhal_network::metrics::MetricsMakeCallbackHandler;
 use narwhal_network::metrics::{NetworkConnectionMetrics, NetworkMetrics};
+use narwhal_worker::{LocalNarwhalClient, PendingTransaction};
 use sui_config::node::DBCheckpointConfig;
 use sui_config::{ConsensusConfig, NodeConfig};
 use sui_core::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use sui_core::authority::epoch_start_configuration::EpochStartConfiguration;
 use sui_core::authority_aggregator::AuthorityAggregator;
-use sui_core::authority_server::ValidatorService;
+use sui_core::authority_server::{LatestValidatorReport, ValidatorService};
 use sui_core::checkpoints::checkpoint_executor;
 use sui_core::checkpoints::{
-    CheckpointMetrics, CheckpointService, CheckpointStore, SendCheckpointToStateSync,
-    SubmitCheckpointToConsensus,
+    CheckpointMetrics, CheckpointService, CheckpointServiceExit, CheckpointStore,
+    SendCheckpointToStateSync, SubmitCheckpointToConsensus, CHECKPOINT_SERVICE,
 };
 use sui_core::consensus_adapter::{
     CheckConnection, ConnectionMonitorStatus, ConsensusAdapter, ConsensusAdapterMetrics,
+    PendingConsensusTransaction,
 };
 use sui_core::consensus_handler::ConsensusHandler;
 use sui_core::consensus_validator::{SuiTxValidator, SuiTxValidatorMetrics};
 use sui_core::db_checkpoint_handler::DBCheckpointHandler;
 use sui_core::epoch::committee_store::CommitteeStore;
-use sui_core::epoch::data_removal::EpochDataRemover;
+use sui_core::epoch::data_removal::{EpochDataMetrics, EpochDataRemover};
 use sui_core::epoch::epoch_metrics::EpochMetrics;
 use sui_core::epoch::reconfiguration::ReconfigurationInitiator;
 use sui_core::module_cache_metrics::ResolverMetrics;
-use sui_core::narwhal_manager::{NarwhalConfiguration, NarwhalManager, NarwhalManagerMetrics};
+use sui_core::narwhal_manager::{
+    NarwhalConfiguration, NarwhalManager, NarwhalManagerMetrics, NARWHAL_PRIMARY, NARWHAL_WORKERS,
+};
 use sui_core::signature_verifier::VerifiedDigestCacheMetrics;
 use sui_core::state_accumulator::StateAccumulator;
 use sui_core::storage::RocksDbStore;
@@ -78,6 +88,7 @@ use sui_network::api::ValidatorServer;
 use sui_network::discovery;
 use sui_network::discovery::TrustedPeerChangeEvent;
 use sui_network::state_sync;
+use sui_network::state_sync::peer_scores::BannedPeer;
 use sui_protocol_config::{ProtocolConfig, SupportedProtocolVersions};
 use sui_storage::IndexStore;
 use sui_types::base_types::{AuthorityName, EpochId, TransactionDigest};
@@ -89,23 +100,50 @@ use sui_types::quorum_driver_types::QuorumDriverEffectsQueueResult;
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemState;
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemStateTrait;
 use sui_types::sui_system_state::SuiSystemStateTrait;
+use sui_types::validator_report::{SignedValidatorReport, ValidatorReport};
 use typed_store::DBMetrics;
 
 use crate::metrics::GrpcMetrics;
+use crate::parameter_overrides::{ParameterOverrides, TunableParameter};
+use crate::report::{ReportPublisher, ReportSampler};
 
 pub mod admin;
 mod handle;
+pub mod health;
 pub mod metrics;
+pub mod parameter_overrides;
+mod peer_addresses;
+pub mod report;
+pub mod signer;
+
+/// How often the network addresses of the committee are checked for changes during the epoch.
+const COMMITTEE_ADDRESSES_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the node runs its self-checks.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the readiness reported by the health service of the validator is updated.
+const READINESS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often a validator checks that its binary supports the protocol version the committee
+/// votes to upgrade to.
+const PROTOCOL_UPGRADE_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// The names of the components of the node in its lifecycle, besides the ones of Narwhal and of
+/// the checkpoint service.
+const STATE_SYNC: &str = "state-sync";
+const JSON_RPC: &str = "json-rpc";
+const VALIDATOR_SERVER: &str = "validator-server";
 
 pub struct ValidatorComponents {
     validator_server_handle: JoinHandle<Result<()>>,
+    // Reports the readiness of the validator through the gRPC health service of its server.
+    validator_health_reporter: HealthReporter,
     narwhal_manager: NarwhalManager,
     narwhal_epoch_data_remover: EpochDataRemover,
     consensus_adapter: Arc<ConsensusAdapter>,
-    // dropping this will eventually stop checkpoint tasks. The receiver side of this channel
-    // is copied into each checkpoint service task, and they are listening to any change to this
-    // channel. When the sender is dropped, a change is triggered and those tasks will exit.
-    checkpoint_service_exit: watch::Sender<()>,
+    // Stops the checkpoint tasks, which also exit eventually if this is dropped.
+    checkpoint_service_exit: CheckpointServiceExit,
     checkpoint_metrics: Arc<CheckpointMetrics>,
     sui_tx_validator_metrics: Arc<SuiTxValidatorMetrics>,
 }
@@ -132,6 +170,19 @@ pub struct SuiNode {
 
     _db_checkpoint_handle: Option<Sender<()>>,
 
+    /// The overrides of the consensus parameters, set through the admin server. `config` holds
+    /// the parameters without the overrides.
+    parameter_overrides: std::sync::Mutex<ParameterOverrides>,
+
+    /// The states of the components of the node, reported by the admin server.
+    lifecycle: Lifecycle,
+
+    /// The outcome of the self-checks of the node, reported by the admin server.
+    health_monitor: HealthMonitor,
+
+    /// The latest operational report signed by the validator, served by its gRPC interface.
+    validator_report: LatestValidatorReport,
+
     #[cfg(msim)]
     sim_node: sui_simulator::runtime::NodeHandle,
 }
@@ -139,7 +190,7 @@ pub struct SuiNode {
 impl fmt::Debug for SuiNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("SuiNode")
-            .field("name", &self.state.name.concise())
+            .field("name", &self.state.name().concise())
             .finish()
     }
 }
@@ -171,9 +222,16 @@ impl SuiNode {
         DBMetrics::init(&prometheus_registry);
         mysten_metrics::init_metrics(&prometheus_registry);
 
+        // The budget is set before the components holding memory register with it.
+        let memory_budget = memory_budget::init_global(config.memory_budget_bytes);
+        info!(
+            "Memory budget of the node: {:?}",
+            memory_budget.soft_limit()
+        );
+
         let genesis = config.genesis()?;
 
-        let secret = Arc::pin(config.protocol_key_pair().copy());
+        let mut secret = signer::authority_signer(&config).await?;
         let genesis_committee = genesis.committee()?;
         let committee_store = Arc::new(CommitteeStore::new(
             config.db_path().join("epochs"),
@@ -187,6 +245,7 @@ impl SuiNode {
                 genesis,
                 &committee_store,
                 config.indirect_objects_threshold,
+                config.archive_mode,
             )
             .await?,
         );
@@ -194,6 +253,15 @@ impl SuiNode {
         let committee = committee_store
             .get_committee(&cur_epoch)?
             .expect("Committee of the current epoch must exist");
+        // The protocol key may have been rotated before the node restarted.
+        let mut name = config.protocol_public_key();
+        if let Some((next_name, next_secret)) =
+            signer::rotated_protocol_key(&config, name, &committee)
+        {
+            info!(?next_name, "Signing with the next protocol key of the node");
+            name = next_name;
+            secret = next_secret;
+        }
         let epoch_start_configuration = store
             .get_epoch_start_configuration()?
             .expect("EpochStartConfiguration of the current epoch must exist");
@@ -201,7 +269,7 @@ impl SuiNode {
         let batch_verifier_metrics = VerifiedDigestCacheMetrics::new(&prometheus_registry);
 
         let epoch_store = AuthorityPerEpochStore::new(
-            config.protocol_public_key(),
+            name,
             committee.clone(),
             &config.db_path().join("store"),
             None,
@@ -255,12 +323,24 @@ impl SuiNode {
         // We must explicitly send this instead of relying on the initial value to trigger
         // watch value change, so that state-sync is able to process it.
         send_trusted_peer_change(
-            &config,
+            name,
             &trusted_peer_change_tx,
             epoch_store.epoch_start_state(),
         )
         .expect("Initial trusted peers must be set");
 
+        let lifecycle = Lifecycle::new();
+        lifecycle.register(STATE_SYNC, &[]);
+        lifecycle.set_state(STATE_SYNC, ComponentState::Ready);
+        let health_monitor = HealthMonitor::new(&prometheus_registry);
+
+        // Archive nodes keep every version of the objects.
+        let pruning_config = if config.archive_mode {
+            config.authority_store_pruning_config.for_archive_mode()
+        } else {
+            config.authority_store_pruning_config
+        };
+
         let db_checkpoint_config = if config.db_checkpoint_config.checkpoint_path.is_none() {
             DBCheckpointConfig {
                 checkpoint_path: Some(config.db_checkpoint_path()),
@@ -283,7 +363,7 @@ impl SuiNode {
         };
 
         let state = AuthorityState::new(
-            config.protocol_public_key(),
+            name,
             secret,
             config.supported_protocol_versions.unwrap(),
             store.clone(),
@@ -292,7 +372,7 @@ impl SuiNode {
             index_store.clone(),
             checkpoint_store.clone(),
             &prometheus_registry,
-            config.authority_store_pruning_config,
+            pruning_config,
             genesis.objects(),
             &db_checkpoint_config,
         )
@@ -324,6 +404,8 @@ impl SuiNode {
                     end_of_epoch_receiver,
                     &config.db_path(),
                     &prometheus_registry,
+                    config.resubmit_transactions_across_epochs,
+                    config.quorum_driver_journal,
                 )
                 .await?,
             ))
@@ -338,6 +420,10 @@ impl SuiNode {
             &prometheus_registry,
         )
         .await?;
+        if json_rpc_service.is_some() {
+            lifecycle.register(JSON_RPC, &[]);
+            lifecycle.set_state(JSON_RPC, ComponentState::Ready);
+        }
 
         let accumulator = Arc::new(StateAccumulator::new(store));
 
@@ -368,9 +454,11 @@ impl SuiNode {
 
         let connection_monitor_status = Arc::new(connection_monitor_status);
 
+        let parameter_overrides = ParameterOverrides::load(&config.db_path())?;
+        let validator_report = LatestValidatorReport::default();
         let validator_components = if state.is_validator(&epoch_store) {
             let components = Self::construct_validator_components(
-                &config,
+                &parameter_overrides.apply_to_node_config(&config),
                 state.clone(),
                 committee,
                 epoch_store.clone(),
@@ -379,7 +467,9 @@ impl SuiNode {
                 accumulator.clone(),
                 connection_monitor_status.clone(),
                 authority_names_to_hostnames,
+                validator_report.clone(),
                 &registry_service,
+                &lifecycle,
             )
             .await?;
             // This is only needed during cold start.
@@ -407,6 +497,10 @@ impl SuiNode {
             trusted_peer_change_tx,
 
             _db_checkpoint_handle: db_checkpoint_handle,
+            parameter_overrides: std::sync::Mutex::new(parameter_overrides),
+            lifecycle,
+            health_monitor,
+            validator_report,
             #[cfg(msim)]
             sim_node: sui_simulator::runtime::NodeHandle::current(),
         };
@@ -415,6 +509,18 @@ impl SuiNode {
         let node = Arc::new(node);
         let node_copy = node.clone();
         spawn_monitored_task!(async move { Self::monitor_reconfiguration(node_copy).await });
+        let node_copy = node.clone();
+        spawn_monitored_task!(async move { Self::monitor_committee_addresses(node_copy).await });
+        let node_copy = node.clone();
+        spawn_monitored_task!(async move { Self::monitor_health(node_copy).await });
+        let node_copy = node.clone();
+        spawn_monitored_task!(async move { Self::monitor_readiness(node_copy).await });
+        let node_copy = node.clone();
+        spawn_monitored_task!(async move { Self::monitor_protocol_upgrade(node_copy).await });
+        if node.config.validator_report.is_some() {
+            let node_copy = node.clone();
+            spawn_monitored_task!(async move { Self::report_operations(node_copy).await });
+        }
 
         Ok(node)
     }
@@ -458,6 +564,82 @@ impl SuiNode {
             .set_override_protocol_upgrade_buffer_stake(epoch, buffer_stake_bps)
     }
 
+    /// Returns the effective values of the tunable consensus parameters, and whether they are
+    /// overridden.
+    pub fn effective_parameters(&self) -> SuiResult<Vec<(TunableParameter, u64, bool)>> {
+        let overrides = self.parameter_overrides.lock().unwrap().clone();
+        let config = overrides.apply_to_node_config(&self.config);
+        let consensus_config = config
+            .consensus_config()
+            .ok_or_else(|| SuiError::from("Node is not a validator"))?;
+        Ok(TunableParameter::ALL
+            .into_iter()
+            .map(|parameter| {
+                (
+                    parameter,
+                    parameter.get(consensus_config),
+                    overrides.get(parameter).is_some(),
+                )
+            })
+            .collect())
+    }
+
+    /// Overrides a consensus parameter, and persists the override so that it survives restarts.
+    pub async fn set_parameter_override(
+        &self,
+        parameter: TunableParameter,
+        value: u64,
+    ) -> Result<()> {
+        self.update_parameter_overrides(|overrides| overrides.set(parameter, value))
+            .await
+    }
+
+    /// Clears the override of a consensus parameter, which goes back to its configured value.
+    pub async fn clear_parameter_override(&self, parameter: TunableParameter) -> Result<()> {
+        self.update_parameter_overrides(|overrides| {
+            overrides.clear(parameter);
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_parameter_overrides(
+        &self,
+        update: impl FnOnce(&mut ParameterOverrides) -> Result<()>,
+    ) -> Result<()> {
+        if self.config.consensus_config().is_none() {
+            return Err(anyhow!("Node is not a validator"));
+        }
+        {
+            let mut overrides = self.parameter_overrides.lock().unwrap();
+            let mut new_overrides = overrides.clone();
+            update(&mut new_overrides)?;
+            new_overrides.save(&self.config.db_path())?;
+            *overrides = new_overrides;
+        }
+
+        // The overrides are read again once the validator components are locked, so that the
+        // latest overrides are applied when they are updated concurrently.
+        if let Some(components) = &*self.validator_components.lock().await {
+            let config = self
+                .parameter_overrides
+                .lock()
+                .unwrap()
+                .apply_to_node_config(&self.config);
+            let consensus_config = config
+                .consensus_config()
+                .expect("Validator must have a consensus config");
+            components
+                .consensus_adapter
+                .set_max_pending_transactions(consensus_config.max_pending_transactions());
+            components
+                .narwhal_manager
+                .set_parameters(consensus_config.narwhal_config().clone())
+                .await;
+        }
+        Ok(())
+    }
+
     // Testing-only API to start epoch close process.
     // For production code, please use the non-testing version.
     pub async fn close_epoch_for_testing(&self) -> SuiResult {
@@ -480,14 +662,29 @@ impl SuiNode {
         trusted_peer_change_rx: watch::Receiver<TrustedPeerChangeEvent>,
         prometheus_registry: &Registry,
     ) -> Result<(Network, discovery::Handle, state_sync::Handle)> {
+        let mut state_sync_config = config.p2p_config.state_sync.clone().unwrap_or_default();
+        state_sync_config
+            .peer_ban_list_path
+            .get_or_insert_with(|| config.db_path().join("state_sync_banned_peers"));
+        let mut discovery_config = config.p2p_config.clone();
+        if state_sync_config.is_follower() {
+            // Followers sync checkpoints without peers, so they never dial the P2P network.
+            info!("Following the chain without connecting to peers");
+            discovery_config.seed_peers.clear();
+            discovery_config
+                .discovery
+                .get_or_insert_with(Default::default)
+                .target_concurrent_connections = Some(0);
+        }
+
         let (state_sync, state_sync_server) = state_sync::Builder::new()
-            .config(config.p2p_config.state_sync.clone().unwrap_or_default())
+            .config(state_sync_config)
             .store(state_sync_store)
             .with_metrics(prometheus_registry)
             .build();
 
         let (discovery, discovery_server) = discovery::Builder::new(trusted_peer_change_rx)
-            .config(config.p2p_config.clone())
+            .config(discovery_config)
             .build();
 
         let p2p_network = {
@@ -556,7 +753,9 @@ impl SuiNode {
         accumulator: Arc<StateAccumulator>,
         connection_monitor_status: Arc<ConnectionMonitorStatus>,
         authority_names_to_hostnames: HashMap<AuthorityName, String>,
+        validator_report: LatestValidatorReport,
         registry_service: &RegistryService,
+        lifecycle: &Lifecycle,
     ) -> Result<ValidatorComponents> {
         let consensus_config = config
             .consensus_config()
@@ -565,15 +764,22 @@ impl SuiNode {
         let consensus_adapter = Arc::new(Self::construct_consensus_adapter(
             &committee,
             consensus_config,
-            state.name,
+            state.name(),
             connection_monitor_status.clone(),
             &registry_service.default_registry(),
         ));
-        let narwhal_manager =
-            Self::construct_narwhal_manager(config, consensus_config, registry_service)?;
+        let narwhal_manager = Self::construct_narwhal_manager(
+            config,
+            &state,
+            consensus_config,
+            registry_service,
+            lifecycle,
+        )?;
 
-        let mut narwhal_epoch_data_remover =
-            EpochDataRemover::new(narwhal_manager.get_storage_base_path());
+        let mut narwhal_epoch_data_remover = EpochDataRemover::new(
+            narwhal_manager.get_storage_base_path(),
+            EpochDataMetrics::new(&registry_service.default_registry()),
+        );
 
         // This only gets started up once, not on every epoch. (Make call to remove every epoch.)
         narwhal_epoch_data_remover.run().await;
@@ -582,13 +788,17 @@ impl SuiNode {
         let sui_tx_validator_metrics =
             SuiTxValidatorMetrics::new(&registry_service.default_registry());
 
-        let validator_server_handle = Self::start_grpc_validator_service(
-            config,
-            state.clone(),
-            consensus_adapter.clone(),
-            &registry_service.default_registry(),
-        )
-        .await?;
+        let (validator_server_handle, validator_health_reporter) =
+            Self::start_grpc_validator_service(
+                config,
+                state.clone(),
+                consensus_adapter.clone(),
+                validator_report,
+                &registry_service.default_registry(),
+            )
+            .await?;
+        lifecycle.register(VALIDATOR_SERVER, &[]);
+        lifecycle.set_state(VALIDATOR_SERVER, ComponentState::Ready);
 
         Self::start_epoch_specific_validator_components(
             config,
@@ -602,8 +812,10 @@ impl SuiNode {
             accumulator,
             authority_names_to_hostnames,
             validator_server_handle,
+            validator_health_reporter,
             checkpoint_metrics,
             sui_tx_validator_metrics,
+            lifecycle,
         )
         .await
     }
@@ -620,8 +832,10 @@ impl SuiNode {
         accumulator: Arc<StateAccumulator>,
         authority_names_to_hostnames: HashMap<AuthorityName, String>,
         validator_server_handle: JoinHandle<Result<()>>,
+        validator_health_reporter: HealthReporter,
         checkpoint_metrics: Arc<CheckpointMetrics>,
         sui_tx_validator_metrics: Arc<SuiTxValidatorMetrics>,
+        lifecycle: &Lifecycle,
     ) -> Result<ValidatorComponents> {
         let (checkpoint_service, checkpoint_service_exit) = Self::start_checkpoint_service(
             config,
@@ -640,6 +854,9 @@ impl SuiNode {
         let low_scoring_authorities = Arc::new(ArcSwap::new(Arc::new(HashMap::new())));
 
         consensus_adapter.swap_low_scoring_authorities(low_scoring_authorities.clone());
+        // The protocol key of the authority may have been rotated when reconfiguring to this
+        // epoch, while the consensus adapter lives across the epochs.
+        consensus_adapter.swap_authority(state.name());
 
         let new_epoch_start_state = epoch_store.epoch_start_state();
         let committee = new_epoch_start_state.get_narwhal_committee();
@@ -649,6 +866,7 @@ impl SuiNode {
             checkpoint_service.clone(),
             state.transaction_manager().clone(),
             state.db(),
+            state.db(),
             low_scoring_authorities,
             authority_names_to_hostnames,
             committee,
@@ -668,15 +886,21 @@ impl SuiNode {
                 worker_cache,
                 consensus_handler,
                 SuiTxValidator::new(
-                    epoch_store,
+                    epoch_store.clone(),
                     state.transaction_manager().clone(),
                     sui_tx_validator_metrics.clone(),
                 ),
+                epoch_store.protocol_config(),
             )
             .await;
+        // The checkpoint service submits its signatures to consensus: it is registered once
+        // consensus is started, so that it is stopped before it.
+        lifecycle.register(CHECKPOINT_SERVICE, &[NARWHAL_PRIMARY]);
+        lifecycle.set_state(CHECKPOINT_SERVICE, ComponentState::Ready);
 
         Ok(ValidatorComponents {
             validator_server_handle,
+            validator_health_reporter,
             narwhal_manager,
             narwhal_epoch_data_remover,
             consensus_adapter,
@@ -695,7 +919,7 @@ impl SuiNode {
         state_sync_handle: state_sync::Handle,
         accumulator: Arc<StateAccumulator>,
         checkpoint_metrics: Arc<CheckpointMetrics>,
-    ) -> (Arc<CheckpointService>, watch::Sender<()>) {
+    ) -> (Arc<CheckpointService>, CheckpointServiceExit) {
         let epoch_start_timestamp_ms = epoch_store.epoch_start_state().epoch_start_timestamp_ms();
         let epoch_duration_ms = epoch_store.epoch_start_state().epoch_duration_ms();
 
@@ -707,8 +931,8 @@ impl SuiNode {
 
         let checkpoint_output = Box::new(SubmitCheckpointToConsensus {
             sender: consensus_adapter,
-            signer: state.secret.clone(),
-            authority: config.protocol_public_key(),
+            signer: state.secret(),
+            authority: state.name(),
             next_reconfiguration_timestamp_ms: epoch_start_timestamp_ms
                 .checked_add(epoch_duration_ms)
                 .expect("Overflow calculating next_reconfiguration_timestamp_ms"),
@@ -736,16 +960,20 @@ impl SuiNode {
 
     fn construct_narwhal_manager(
         config: &NodeConfig,
+        state: &AuthorityState,
         consensus_config: &ConsensusConfig,
         registry_service: &RegistryService,
+        lifecycle: &Lifecycle,
     ) -> Result<NarwhalManager> {
         let narwhal_config = NarwhalConfiguration {
-            primary_keypair: config.protocol_key_pair().copy(),
+            primary_signer: signer::primary_signer(config, state.name())?,
             network_keypair: config.network_key_pair().copy(),
             worker_ids_and_keypairs: vec![(0, config.worker_key_pair().copy())],
             storage_base_path: consensus_config.db_path().to_path_buf(),
             parameters: consensus_config.narwhal_config().to_owned(),
             registry_service: registry_service.clone(),
+            lifecycle: lifecycle.clone(),
+            worker_standby: consensus_config.narwhal_worker_standby,
         };
 
         let metrics = NarwhalManagerMetrics::new(&registry_service.default_registry());
@@ -779,10 +1007,16 @@ impl SuiNode {
         config: &NodeConfig,
         state: Arc<AuthorityState>,
         consensus_adapter: Arc<ConsensusAdapter>,
+        validator_report: LatestValidatorReport,
         prometheus_registry: &Registry,
-    ) -> Result<tokio::task::JoinHandle<Result<()>>> {
-        let validator_service =
-            ValidatorService::new(state.clone(), consensus_adapter, prometheus_registry).await?;
+    ) -> Result<(tokio::task::JoinHandle<Result<()>>, HealthReporter)> {
+        let validator_service = ValidatorService::new(
+            state.clone(),
+            consensus_adapter,
+            validator_report,
+            prometheus_registry,
+        )
+        .await?;
 
         let mut server_conf = mysten_network::config::Config::new();
         server_conf.global_concurrency_limit = config.grpc_concurrency_limit;
@@ -790,7 +1024,9 @@ impl SuiNode {
         let mut server_builder =
             ServerBuilder::from_config(&server_conf, GrpcMetrics::new(prometheus_registry));
 
-        server_builder = server_builder.add_service(ValidatorServer::new(validator_service));
+        server_builder = server_builder
+            .add_service(ValidatorServer::new(validator_service))
+            .register_file_descriptor_set(sui_network::api::VALIDATOR_FILE_DESCRIPTOR_SET);
 
         let server = server_builder
             .bind(config.network_address())
@@ -798,9 +1034,69 @@ impl SuiNode {
             .map_err(|err| anyhow!(err.to_string()))?;
         let local_addr = server.local_addr();
         info!("Listening to traffic on {local_addr}");
+
+        // The validator is not ready until consensus runs, see `monitor_readiness`.
+        let mut health_reporter = server.health_reporter();
+        health_reporter
+            .set_service_status("", ServingStatus::NotServing)
+            .await;
+        health_reporter
+            .set_not_serving::<ValidatorServer<ValidatorService>>()
+            .await;
         let grpc_server = spawn_monitored_task!(server.serve().map_err(Into::into));
 
-        Ok(grpc_server)
+        Ok((grpc_server, health_reporter))
+    }
+
+    pub fn lifecycle(&self) -> &Lifecycle {
+        &self.lifecycle
+    }
+
+    /// The outcome of the latest run of the self-checks.
+    pub fn health(&self) -> HealthReport {
+        self.health_monitor.report()
+    }
+
+    /// The peers banned by state sync for serving invalid or slow data.
+    pub fn banned_peers(&self) -> Vec<BannedPeer> {
+        self.state_sync.banned_peers()
+    }
+
+    /// Lists the transactions this validator submitted to consensus and are not yet processed,
+    /// the oldest first, starting after the transaction with id `cursor` if set.
+    pub async fn pending_consensus_transactions(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+    ) -> SuiResult<Vec<PendingConsensusTransaction>> {
+        match &*self.validator_components.lock().await {
+            Some(components) => Ok(components
+                .consensus_adapter
+                .pending_transactions(cursor, limit)),
+            None => Err(SuiError::from("Node is not a validator")),
+        }
+    }
+
+    /// Lists the transactions pending in the ingress queues of the local Narwhal worker, the
+    /// oldest first, starting after the transaction with id `cursor` if set.
+    pub fn pending_worker_transactions(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+    ) -> SuiResult<Vec<PendingTransaction>> {
+        let consensus_config = self
+            .config
+            .consensus_config()
+            .ok_or_else(|| SuiError::from("Node is not a validator"))?;
+        let client = LocalNarwhalClient::get_global(consensus_config.address())
+            .ok_or_else(|| SuiError::from("Narwhal worker is not started"))?;
+        let pending = client.load().pending_transactions().list(cursor, limit);
+        Ok(pending)
+    }
+
+    /// Lifts the ban of the peer by state sync, and returns whether it was banned.
+    pub fn unban_peer(&self, peer_id: &anemo::PeerId) -> bool {
+        self.state_sync.unban_peer(peer_id)
     }
 
     pub fn state(&self) -> Arc<AuthorityState> {
@@ -843,8 +1139,239 @@ impl SuiNode {
             .ok_or_else(|| anyhow::anyhow!("Transaction Orchestrator is not enabled in this node."))
     }
 
-    /// This function awaits the completion of checkpoint execution of the current epoch,
-    /// after which it iniitiates reconfiguration of the entire system.
+    /// Watches the network addresses the validators stage for the next epoch in the system
+    /// state, so that Narwhal dials the validators which moved to them during the epoch.
+    async fn monitor_committee_addresses(self: Arc<Self>) {
+        let Some(consensus_config) = self.config.consensus_config.as_ref() else {
+            // Fullnodes do not run Narwhal.
+            return;
+        };
+        let mut last_checkpoint = None;
+        let mut last_committee_addresses = None;
+        loop {
+            tokio::time::sleep(COMMITTEE_ADDRESSES_CHECK_INTERVAL).await;
+
+            // The system state only changes with the executed checkpoints.
+            let checkpoint = match self
+                .checkpoint_store
+                .get_highest_executed_checkpoint_seq_number()
+            {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => {
+                    warn!("Failed to read the highest executed checkpoint: {e}");
+                    continue;
+                }
+            };
+            if checkpoint == last_checkpoint {
+                continue;
+            }
+            let system_state = match self.state.database.get_sui_system_state_object() {
+                Ok(system_state) => system_state,
+                Err(e) => {
+                    warn!("Failed to read the system state for the committee addresses: {e}");
+                    continue;
+                }
+            };
+            last_checkpoint = checkpoint;
+            let committee_addresses =
+                peer_addresses::committee_peer_addresses(system_state, &consensus_config.address);
+            if last_committee_addresses.as_ref() == Some(&committee_addresses) {
+                continue;
+            }
+
+            // The Narwhal nodes are only updated if they run the epoch of the addresses.
+            let components = self.validator_components.lock().await;
+            let Some(components) = &*components else {
+                continue;
+            };
+            let (epoch, addresses) = committee_addresses.clone();
+            if components
+                .narwhal_manager
+                .update_peer_addresses(epoch, addresses)
+                .await
+            {
+                last_committee_addresses = Some(committee_addresses);
+            }
+        }
+    }
+
+    /// Runs the self-checks of the node periodically.
+    async fn monitor_health(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+
+            let validator = self.validator_components.lock().await.is_some();
+            let storage_readable = self
+                .checkpoint_store
+                .get_highest_executed_checkpoint_seq_number()
+                .is_ok()
+                && self.state.database.get_sui_system_state_object().is_ok();
+            let (narwhal, peers) = if validator {
+                let statuses = &self.connection_monitor_status.connection_statuses;
+                let connected = statuses
+                    .iter()
+                    .filter(|status| *status.value() == ConnectionStatus::Connected)
+                    .count();
+                (
+                    NarwhalProgress::from_registry(&self.registry_service),
+                    Some((connected, statuses.len())),
+                )
+            } else {
+                (None, None)
+            };
+            self.health_monitor.update(HealthObservation {
+                narwhal,
+                validator,
+                storage_readable,
+                peers,
+            });
+        }
+    }
+
+    /// Checks periodically, while the node is a validator, that its binary supports the protocol
+    /// version the committee votes to upgrade to, so that the operator is warned before the end of
+    /// the epoch.
+    async fn monitor_protocol_upgrade(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(PROTOCOL_UPGRADE_CHECK_INTERVAL).await;
+
+            if self.validator_components.lock().await.is_none() {
+                continue;
+            }
+            let epoch_store = self.state.load_epoch_store_one_call_per_task();
+            self.state.check_protocol_upgrade_readiness(&epoch_store);
+        }
+    }
+
+    /// Signs a report of the operations of the validator periodically, while it is a member of
+    /// the committee, and publishes it to the configured endpoint, if any.
+    async fn report_operations(self: Arc<Self>) {
+        let Some(report_config) = self.config.validator_report.clone() else {
+            return;
+        };
+        let publisher = match report_config
+            .publish_url
+            .as_deref()
+            .map(ReportPublisher::new)
+            .transpose()
+        {
+            Ok(publisher) => publisher,
+            Err(err) => {
+                warn!("Not publishing the validator reports: {err}");
+                None
+            }
+        };
+        let mut sampler = ReportSampler::new();
+        let mut interval = tokio::time::interval(report_config.report_interval());
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            if self.validator_components.lock().await.is_none() {
+                self.validator_report.store(None);
+                continue;
+            }
+            let (uptime, version) = sampler.uptime_and_version(&self.registry_service);
+            let last_committed_round = NarwhalProgress::from_registry(&self.registry_service)
+                .map_or(0, |progress| progress.committed_round);
+            let statuses = &self.connection_monitor_status.connection_statuses;
+            let connected = statuses
+                .iter()
+                .filter(|status| *status.value() == ConnectionStatus::Connected)
+                .count();
+            let report = ValidatorReport {
+                epoch: self.state.load_epoch_store_one_call_per_task().epoch(),
+                authority: self.state.name(),
+                version,
+                timestamp_ms: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64,
+                uptime_secs: uptime.as_secs(),
+                commit_rate: sampler.commit_rate(Instant::now(), last_committed_round),
+                last_committed_round,
+                highest_executed_checkpoint: self
+                    .checkpoint_store
+                    .get_highest_executed_checkpoint_seq_number()
+                    .ok()
+                    .flatten(),
+                connected_peer_count: connected as u64,
+                known_peer_count: statuses.len() as u64,
+            };
+            let (epoch, authority) = (report.epoch, report.authority);
+            let secret = self.state.secret();
+            let report = Arc::new(SignedValidatorReport::new(
+                epoch, report, &*secret, authority,
+            ));
+            self.validator_report.store(Some(report.clone()));
+
+            if let Some(publisher) = &publisher {
+                if let Err(err) = publisher.publish(&report).await {
+                    warn!(url = %publisher.url(), "Unable to publish the validator report: {err}");
+                }
+            }
+        }
+    }
+
+    /// Reports through the gRPC health service whether the validator is ready to serve: it is
+    /// once consensus runs, its stores can be read, and it has reconfigured to the epoch of the
+    /// checkpoints it synced.
+    async fn monitor_readiness(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(READINESS_CHECK_INTERVAL).await;
+
+            let Some(mut health_reporter) = self
+                .validator_components
+                .lock()
+                .await
+                .as_ref()
+                .map(|components| components.validator_health_reporter.clone())
+            else {
+                continue;
+            };
+            let consensus_running = [NARWHAL_PRIMARY, VALIDATOR_SERVER]
+                .iter()
+                .all(|component| self.lifecycle.state(component) == Some(ComponentState::Ready));
+            let store_open = self
+                .checkpoint_store
+                .get_highest_executed_checkpoint_seq_number()
+                .is_ok()
+                && self.state.database.get_sui_system_state_object().is_ok();
+            let epoch = self.state.load_epoch_store_one_call_per_task().epoch();
+            let epoch_synced = match self.checkpoint_store.get_highest_synced_checkpoint() {
+                // A checkpoint ending the epoch, or of a later epoch, means that the node did
+                // not reconfigure yet.
+                Ok(Some(checkpoint)) => {
+                    checkpoint.epoch() < epoch
+                        || (checkpoint.epoch() == epoch && checkpoint.end_of_epoch_data.is_none())
+                }
+                Ok(None) => true,
+                Err(_) => false,
+            };
+
+            if consensus_running && store_open && epoch_synced {
+                health_reporter
+                    .set_service_status("", ServingStatus::Serving)
+                    .await;
+                health_reporter
+                    .set_serving::<ValidatorServer<ValidatorService>>()
+                    .await;
+            } else {
+                debug!(
+                    consensus_running,
+                    store_open, epoch_synced, "Validator is not ready to serve"
+                );
+                health_reporter
+                    .set_service_status("", ServingStatus::NotServing)
+                    .await;
+                health_reporter
+                    .set_not_serving::<ValidatorServer<ValidatorService>>()
+                    .await;
+            }
+        }
+    }
+
     pub async fn monitor_reconfiguration(self: Arc<Self>) -> Result<()> {
         let mut checkpoint_executor = CheckpointExecutor::new(
             self.state_sync.subscribe_to_synced_checkpoints(),
@@ -868,7 +1395,7 @@ impl SuiNode {
                     .move_binary_format_version();
                 let transaction =
                     ConsensusTransaction::new_capability_notification(AuthorityCapabilities::new(
-                        self.state.name,
+                        self.state.name(),
                         self.config
                             .supported_protocol_versions
                             .expect("Supported versions should be populated"),
@@ -928,7 +1455,7 @@ impl SuiNode {
             cur_epoch_store.record_epoch_reconfig_start_time_metric();
 
             let _ = send_trusted_peer_change(
-                &self.config,
+                signer::name_in_committee(&self.config, self.state.name(), &next_epoch_committee),
                 &self.trusted_peer_change_tx,
                 &new_epoch_start_state,
             );
@@ -938,7 +1465,8 @@ impl SuiNode {
             // in the new epoch.
             let new_validator_components = if let Some(ValidatorComponents {
                 validator_server_handle,
-                narwhal_manager,
+                validator_health_reporter,
+                mut narwhal_manager,
                 narwhal_epoch_data_remover,
                 consensus_adapter,
                 checkpoint_service_exit,
@@ -947,10 +1475,11 @@ impl SuiNode {
             }) = self.validator_components.lock().await.take()
             {
                 info!("Reconfiguring the validator.");
-                // Stop the old checkpoint service.
-                drop(checkpoint_service_exit);
-
-                narwhal_manager.shutdown().await;
+                self.shutdown_epoch_specific_validator_components(
+                    checkpoint_service_exit,
+                    &narwhal_manager,
+                )
+                .await;
 
                 let new_epoch_store = self
                     .reconfigure_state(
@@ -974,7 +1503,12 @@ impl SuiNode {
                     .await;
 
                 if self.state.is_validator(&new_epoch_store) {
-                    // Only restart Narwhal if this node is still a validator in the new epoch.
+                    // Only restart Narwhal if this node is still a validator in the new epoch,
+                    // with its new protocol key if it was rotated.
+                    narwhal_manager.set_primary_signer(signer::primary_signer(
+                        &self.config,
+                        self.state.name(),
+                    )?);
                     Some(
                         Self::start_epoch_specific_validator_components(
                             &self.config,
@@ -988,13 +1522,18 @@ impl SuiNode {
                             self.accumulator.clone(),
                             authority_names_to_hostnames,
                             validator_server_handle,
+                            validator_health_reporter,
                             checkpoint_metrics,
                             sui_tx_validator_metrics,
+                            &self.lifecycle,
                         )
                         .await?,
                     )
                 } else {
                     info!("This node is no longer a validator after reconfiguration");
+                    for component in [CHECKPOINT_SERVICE, NARWHAL_PRIMARY, NARWHAL_WORKERS] {
+                        self.lifecycle.deregister(component);
+                    }
                     None
                 }
             } else {
@@ -1009,9 +1548,14 @@ impl SuiNode {
                 if self.state.is_validator(&new_epoch_store) {
                     info!("Promoting the node from fullnode to validator, starting grpc server");
 
+                    let config = self
+                        .parameter_overrides
+                        .lock()
+                        .unwrap()
+                        .apply_to_node_config(&self.config);
                     Some(
                         Self::construct_validator_components(
-                            &self.config,
+                            &config,
                             self.state.clone(),
                             Arc::new(next_epoch_committee.clone()),
                             new_epoch_store.clone(),
@@ -1020,7 +1564,9 @@ impl SuiNode {
                             self.accumulator.clone(),
                             self.connection_monitor_status.clone(),
                             authority_names_to_hostnames,
+                            self.validator_report.clone(),
                             &self.registry_service,
+                            &self.lifecycle,
                         )
                         .await?,
                     )
@@ -1041,6 +1587,35 @@ impl SuiNode {
         }
     }
 
+    // Stops the components of the validator started for the epoch, each one before the
+    // components it depends on.
+    async fn shutdown_epoch_specific_validator_components(
+        &self,
+        checkpoint_service_exit: CheckpointServiceExit,
+        narwhal_manager: &NarwhalManager,
+    ) {
+        let mut checkpoint_service_exit = Some(checkpoint_service_exit);
+        for component in self
+            .lifecycle
+            .shutdown_order(&[CHECKPOINT_SERVICE, NARWHAL_PRIMARY])
+        {
+            match component {
+                CHECKPOINT_SERVICE => {
+                    self.lifecycle
+                        .set_state(CHECKPOINT_SERVICE, ComponentState::Draining);
+                    if let Some(exit) = checkpoint_service_exit.take() {
+                        exit.shutdown().await;
+                    }
+                    self.lifecycle
+                        .set_state(CHECKPOINT_SERVICE, ComponentState::Stopped);
+                }
+                // Reports the states of the Narwhal components itself.
+                NARWHAL_PRIMARY => narwhal_manager.shutdown().await,
+                _ => unreachable!("Unexpected component {component}"),
+            }
+        }
+    }
+
     fn check_is_consistent_state(
         &self,
         accumulator: Arc<StateAccumulator>,
@@ -1074,6 +1649,12 @@ impl SuiNode {
     ) -> Arc<AuthorityPerEpochStore> {
         let next_epoch = next_epoch_committee.epoch();
 
+        if let Some((name, secret)) =
+            signer::rotated_protocol_key(&self.config, self.state.name(), &next_epoch_committee)
+        {
+            self.state.rotate_protocol_key(secret, name);
+        }
+
         let last_checkpoint = self
             .checkpoint_store
             .get_epoch_last_checkpoint(cur_epoch_store.epoch())
@@ -1102,13 +1683,13 @@ impl SuiNode {
 
 /// Notify state-sync that a new list of trusted peers are now available.
 fn send_trusted_peer_change(
-    config: &NodeConfig,
+    name: AuthorityName,
     sender: &watch::Sender<TrustedPeerChangeEvent>,
     epoch_state_state: &EpochStartSystemState,
 ) -> Result<(), watch::error::SendError<TrustedPeerChangeEvent>> {
     sender
         .send(TrustedPeerChangeEvent {
-            new_peers: epoch_state_state.get_validator_as_p2p_peers(config.protocol_public_key()),
+            new_peers: epoch_state_state.get_validator_as_p2p_peers(name),
         })
         .tap_err(|err| {
             warn!(
@@ -1130,6 +1711,7 @@ pub async fn build_server(
     }
 
     let mut server = JsonRpcServerBuilder::new(env!("CARGO_PKG_VERSION"), prometheus_registry);
+    server.enable_checkpoint_watermark(state.clone());
 
     server.register_module(ReadApi::new(state.clone()))?;
     server.register_module(CoinReadApi::new(state.clone()))?;
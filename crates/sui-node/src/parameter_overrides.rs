@@ -0,0 +1,185 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Overrides of the consensus parameters of a validator, set through the admin server and
+//! persisted next to its database so that they survive the restarts of the node.
+//!
+//! Only a whitelist of parameters can be overridden: the ones which are local to the node, as
+//! opposed to the protocol parameters which all the validators must agree on, and which the
+//! running components read at runtime, so that the overrides apply right away.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use sui_config::{ConsensusConfig, NodeConfig};
+use tracing::warn;
+
+/// The file holding the overrides, in the database directory of the node.
+pub const PARAMETER_OVERRIDES_FILE: &str = "parameter_overrides.yaml";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TunableParameter {
+    /// `narwhal_config.max_batch_delay`, in ms.
+    MaxBatchDelayMs,
+    /// `narwhal_config.max_header_delay`, in ms.
+    MaxHeaderDelayMs,
+    /// `narwhal_config.sync_retry_nodes`, the number of other workers the missing batches are
+    /// requested from, when the worker of the certificate does not provide them.
+    SyncRetryNodes,
+    /// `max_pending_transactions`, the limit to the number of transactions submitted to
+    /// consensus and not sequenced yet.
+    MaxPendingTransactions,
+}
+
+impl TunableParameter {
+    pub const ALL: [TunableParameter; 4] = [
+        TunableParameter::MaxBatchDelayMs,
+        TunableParameter::MaxHeaderDelayMs,
+        TunableParameter::SyncRetryNodes,
+        TunableParameter::MaxPendingTransactions,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TunableParameter::MaxBatchDelayMs => "max-batch-delay-ms",
+            TunableParameter::MaxHeaderDelayMs => "max-header-delay-ms",
+            TunableParameter::SyncRetryNodes => "sync-retry-nodes",
+            TunableParameter::MaxPendingTransactions => "max-pending-transactions",
+        }
+    }
+
+    pub fn get(&self, config: &ConsensusConfig) -> u64 {
+        let parameters = &config.narwhal_config;
+        match self {
+            TunableParameter::MaxBatchDelayMs => parameters.max_batch_delay.as_millis() as u64,
+            TunableParameter::MaxHeaderDelayMs => parameters.max_header_delay.as_millis() as u64,
+            TunableParameter::SyncRetryNodes => parameters.sync_retry_nodes as u64,
+            TunableParameter::MaxPendingTransactions => config.max_pending_transactions() as u64,
+        }
+    }
+
+    fn set(&self, config: &mut ConsensusConfig, value: u64) {
+        let parameters = &mut config.narwhal_config;
+        match self {
+            TunableParameter::MaxBatchDelayMs => {
+                parameters.max_batch_delay = Duration::from_millis(value)
+            }
+            TunableParameter::MaxHeaderDelayMs => {
+                parameters.max_header_delay = Duration::from_millis(value)
+            }
+            TunableParameter::SyncRetryNodes => parameters.sync_retry_nodes = value as usize,
+            TunableParameter::MaxPendingTransactions => {
+                config.max_pending_transactions = Some(value as usize)
+            }
+        }
+    }
+}
+
+impl fmt::Display for TunableParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TunableParameter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|parameter| parameter.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|p| p.name()).collect();
+                anyhow!(
+                    "unknown parameter {s}, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ParameterOverrides {
+    overrides: BTreeMap<TunableParameter, u64>,
+}
+
+/// The overrides as persisted, keyed by the names of the parameters, so that the ones which are
+/// not tunable anymore are skipped instead of failing the start of the node.
+#[derive(Deserialize)]
+struct PersistedOverrides {
+    #[serde(default)]
+    overrides: BTreeMap<String, u64>,
+}
+
+impl ParameterOverrides {
+    /// Loads the overrides persisted in the directory, which are empty if none were set.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = std::fs::File::open(&path)?;
+        let persisted: PersistedOverrides = serde_yaml::from_reader(file)
+            .map_err(|e| anyhow!("invalid parameter overrides {}: {e}", path.display()))?;
+        let mut overrides = BTreeMap::new();
+        for (name, value) in persisted.overrides {
+            match name.parse() {
+                Ok(parameter) => {
+                    overrides.insert(parameter, value);
+                }
+                Err(_) => warn!("Skipping the override of {name}, which is not tunable anymore"),
+            }
+        }
+        Ok(Self { overrides })
+    }
+
+    /// Persists the overrides in the directory, replacing the previous ones at once.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let path = Self::path(dir);
+        let tmp_path = path.with_extension("yaml.tmp");
+        std::fs::write(&tmp_path, serde_yaml::to_string(self)?)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    fn path(dir: &Path) -> PathBuf {
+        dir.join(PARAMETER_OVERRIDES_FILE)
+    }
+
+    pub fn get(&self, parameter: TunableParameter) -> Option<u64> {
+        self.overrides.get(&parameter).copied()
+    }
+
+    pub fn set(&mut self, parameter: TunableParameter, value: u64) -> Result<()> {
+        if value == 0 {
+            return Err(anyhow!("{parameter} must be greater than 0"));
+        }
+        self.overrides.insert(parameter, value);
+        Ok(())
+    }
+
+    pub fn clear(&mut self, parameter: TunableParameter) {
+        self.overrides.remove(&parameter);
+    }
+
+    pub fn apply(&self, config: &mut ConsensusConfig) {
+        for (parameter, value) in &self.overrides {
+            parameter.set(config, *value);
+        }
+    }
+
+    /// Returns the node config with the overrides applied to its consensus config, if any.
+    pub fn apply_to_node_config(&self, config: &NodeConfig) -> NodeConfig {
+        let mut config = config.clone();
+        if let Some(consensus_config) = config.consensus_config.as_mut() {
+            self.apply(consensus_config);
+        }
+        config
+    }
+}
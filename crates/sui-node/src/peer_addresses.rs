@@ -0,0 +1,117 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anemo::{types::Address, PeerId};
+use mysten_network::Multiaddr;
+use narwhal_network::discovery::PeerAddresses;
+use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemStateTrait;
+use sui_types::sui_system_state::sui_system_state_summary::SuiValidatorSummary;
+use sui_types::sui_system_state::{SuiSystemState, SuiSystemStateTrait};
+use tracing::warn;
+
+/// Returns the epoch of the system state and the addresses of the Narwhal peers of its
+/// committee. The addresses of the committee only change at the epoch boundaries, but the
+/// validators stage their new addresses for the next epoch beforehand: those are added as the
+/// fallback addresses of the peers, where they are dialed if they moved during the epoch.
+pub fn committee_peer_addresses(
+    system_state: SuiSystemState,
+    transactions_address: &Multiaddr,
+) -> (u64, PeerAddresses) {
+    let epoch_start_state = system_state.clone().into_epoch_start_state();
+    let summary = system_state.into_sui_system_state_summary();
+    let peer_addresses = PeerAddresses::new(
+        &epoch_start_state.get_narwhal_committee(),
+        &epoch_start_state.get_narwhal_worker_cache(transactions_address),
+    )
+    .with_fallbacks(staged_peer_addresses(&summary.active_validators));
+    (summary.epoch, peer_addresses)
+}
+
+/// The addresses of the primaries and workers which the validators staged for the next epoch,
+/// by the peer id of the current epoch.
+fn staged_peer_addresses(validators: &[SuiValidatorSummary]) -> Vec<(PeerId, Address)> {
+    let mut staged = Vec::new();
+    for validator in validators {
+        let peers = [
+            (
+                &validator.network_pubkey_bytes,
+                &validator.next_epoch_primary_address,
+            ),
+            (
+                &validator.worker_pubkey_bytes,
+                &validator.next_epoch_worker_address,
+            ),
+        ];
+        for (pubkey_bytes, address) in peers {
+            let Some(address) = address else {
+                continue;
+            };
+            let Ok(peer_id) = <[u8; 32]>::try_from(pubkey_bytes.as_slice()) else {
+                continue;
+            };
+            let address = address
+                .parse::<Multiaddr>()
+                .map_err(|e| e.to_string())
+                .and_then(|address| address.to_anemo_address().map_err(str::to_string));
+            match address {
+                Ok(address) => staged.push((PeerId(peer_id), address)),
+                Err(e) => warn!(
+                    "Invalid staged address of validator {}: {e}",
+                    validator.sui_address
+                ),
+            }
+        }
+    }
+    staged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::staged_peer_addresses;
+    use anemo::PeerId;
+    use mysten_network::Multiaddr;
+    use narwhal_network::discovery::PeerAddresses;
+    use std::collections::BTreeMap;
+    use sui_types::sui_system_state::sui_system_state_summary::SuiValidatorSummary;
+
+    #[test]
+    fn test_staged_addresses_are_fallbacks() {
+        let address = |port: u16| {
+            let address: Multiaddr = format!("/ip4/127.0.0.1/udp/{port}").parse().unwrap();
+            address.to_anemo_address().unwrap()
+        };
+        let primary = PeerId([1; 32]);
+        let worker = PeerId([2; 32]);
+        let other = PeerId([3; 32]);
+        let validators = vec![
+            SuiValidatorSummary {
+                network_pubkey_bytes: primary.0.to_vec(),
+                worker_pubkey_bytes: worker.0.to_vec(),
+                next_epoch_primary_address: Some("/ip4/127.0.0.1/udp/11".to_string()),
+                ..SuiValidatorSummary::default()
+            },
+            // Not a member of the committee.
+            SuiValidatorSummary {
+                network_pubkey_bytes: other.0.to_vec(),
+                next_epoch_primary_address: Some("/ip4/127.0.0.1/udp/13".to_string()),
+                ..SuiValidatorSummary::default()
+            },
+        ];
+
+        let peer_addresses = PeerAddresses(BTreeMap::from([
+            (primary, vec![address(1)]),
+            (worker, vec![address(2)]),
+        ]))
+        .with_fallbacks(staged_peer_addresses(&validators));
+
+        // The staged primary address is dialed after the one of the committee, and the worker,
+        // which staged nothing, keeps its address.
+        assert_eq!(
+            peer_addresses.0,
+            BTreeMap::from([
+                (primary, vec![address(1), address(11)]),
+                (worker, vec![address(2)]),
+            ])
+        );
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The operational reports of the validator, signed with its protocol key. The latest report is
+//! served through the gRPC interface of the validator, and posted to the configured endpoint, so
+//! that delegators and monitoring services can gather verifiable data about its performance.
+
+use anyhow::{anyhow, Result};
+use mysten_metrics::RegistryService;
+use std::time::{Duration, Instant};
+use sui_types::validator_report::SignedValidatorReport;
+
+/// Tracks what the consecutive reports of the validator are computed from.
+pub struct ReportSampler {
+    started_at: Instant,
+    // When the committed round was read for the previous report, and its value.
+    previous_commit: Option<(Instant, u64)>,
+}
+
+impl ReportSampler {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            previous_commit: None,
+        }
+    }
+
+    /// The rounds committed per second since the previous report, or 0 for the first report.
+    pub fn commit_rate(&mut self, now: Instant, committed_round: u64) -> f64 {
+        let rate = match self.previous_commit {
+            Some((at, round)) if now > at => {
+                committed_round.saturating_sub(round) as f64 / (now - at).as_secs_f64()
+            }
+            _ => 0.0,
+        };
+        self.previous_commit = Some((now, committed_round));
+        rate
+    }
+
+    /// The uptime and version of the node, as reported by its `uptime` metric, or since the
+    /// sampler was created if the metric is not registered.
+    pub fn uptime_and_version(&self, registry_service: &RegistryService) -> (Duration, String) {
+        let metrics = registry_service.gather_all();
+        metrics
+            .iter()
+            .find(|family| family.get_name() == "uptime")
+            .and_then(|family| family.get_metric().first())
+            .and_then(|metric| {
+                let version = metric
+                    .get_label()
+                    .iter()
+                    .find(|label| label.get_name() == "version")?;
+                Some((
+                    Duration::from_secs(metric.get_counter().get_value() as u64),
+                    version.get_value().to_string(),
+                ))
+            })
+            .unwrap_or_else(|| {
+                (
+                    self.started_at.elapsed(),
+                    env!("CARGO_PKG_VERSION").to_string(),
+                )
+            })
+    }
+}
+
+impl Default for ReportSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Posts the signed reports to the configured endpoint.
+pub struct ReportPublisher {
+    client: reqwest::Client,
+    url: reqwest::Url,
+}
+
+impl ReportPublisher {
+    pub fn new(url: &str) -> Result<Self> {
+        let url = reqwest::Url::parse(url)
+            .map_err(|e| anyhow!("Invalid url to publish the validator reports to: {e}"))?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        Ok(Self { client, url })
+    }
+
+    pub fn url(&self) -> &reqwest::Url {
+        &self.url
+    }
+
+    pub async fn publish(&self, report: &SignedValidatorReport) -> Result<()> {
+        self.client
+            .post(self.url.clone())
+            .json(report)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReportSampler;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_commit_rate() {
+        let mut sampler = ReportSampler::new();
+        let start = Instant::now();
+        assert_eq!(sampler.commit_rate(start, 100), 0.0);
+        assert_eq!(
+            sampler.commit_rate(start + Duration::from_secs(10), 150),
+            5.0
+        );
+        // The rate does not go negative once the rounds restart, at an epoch change.
+        assert_eq!(
+            sampler.commit_rate(start + Duration::from_secs(20), 10),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The signers of the protocol key of the node. The key is either held in memory, or by an
+//! external signer, e.g. a sidecar fronting a KMS or a PKCS#11 HSM, so that it is never stored on
+//! the disk of the node.
+//!
+//! The protocol key of a validator is rotated by submitting its next protocol public key on chain:
+//! the node signs with the next protocol key pair of its config from the epoch whose committee has
+//! the next key instead of the current one.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::traits::{KeyPair, ToFromBytes, VerifyingKey};
+use narwhal_crypto::signer::{AuthoritySigner, SignerError};
+use serde::{Deserialize, Serialize};
+use sui_config::node::RemoteSignerConfig;
+use sui_config::NodeConfig;
+use sui_core::authority::StableSyncAuthoritySigner;
+use sui_types::base_types::AuthorityName;
+use sui_types::committee::Committee;
+use sui_types::crypto::{AuthorityKeyPair, AuthorityPublicKey, AuthoritySignature};
+use tokio::runtime::RuntimeFlavor;
+use tracing::{error, info, warn};
+
+/// The message signed on startup to check that the external signer holds the protocol key.
+const CHECK_MESSAGE: &[u8] = b"sui-node protocol key signer check";
+
+#[derive(Serialize)]
+struct SignRequest<'a> {
+    key_id: &'a str,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+/// Signs with the protocol key held by an external signer, over HTTP.
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    sign_url: String,
+    key_id: String,
+    public_key: AuthorityPublicKey,
+}
+
+impl RemoteSigner {
+    pub fn new(config: &RemoteSignerConfig) -> Result<Self> {
+        let public_key = AuthorityPublicKey::try_from(config.public_key)
+            .map_err(|e| anyhow!("Invalid public key of the protocol key signer: {e}"))?;
+        let client = reqwest::Client::builder()
+            .timeout(config.request_timeout())
+            .build()?;
+        Ok(Self {
+            client,
+            sign_url: format!("{}/sign", config.url.trim_end_matches('/')),
+            key_id: config.key_id.clone(),
+            public_key,
+        })
+    }
+
+    async fn sign_remotely(&self, message: &[u8]) -> Result<AuthoritySignature, SignerError> {
+        let request = SignRequest {
+            key_id: &self.key_id,
+            message: Base64::encode(message),
+        };
+        let response: SignResponse = self
+            .client
+            .post(&self.sign_url)
+            .json(&request)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| SignerError::Unavailable(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| SignerError::InvalidSignature(e.to_string()))?;
+        let signature = Base64::decode(&response.signature)
+            .map_err(|e| SignerError::InvalidSignature(e.to_string()))?;
+        AuthoritySignature::from_bytes(&signature)
+            .map_err(|e| SignerError::InvalidSignature(e.to_string()))
+    }
+
+    /// Checks that the signer holds the protocol key, by verifying a signature it makes.
+    pub async fn check(&self) -> Result<()> {
+        let signature = self.sign_remotely(CHECK_MESSAGE).await?;
+        self.public_key
+            .verify(CHECK_MESSAGE, &signature)
+            .map_err(|_| anyhow!("The protocol key signer does not hold the configured key"))
+    }
+}
+
+#[async_trait]
+impl AuthoritySigner for RemoteSigner {
+    fn public_key(&self) -> AuthorityPublicKey {
+        self.public_key.clone()
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<AuthoritySignature, SignerError> {
+        self.sign_remotely(message).await
+    }
+}
+
+/// The authority state signs synchronously, e.g. its votes on transactions, so the remote
+/// signature is awaited in place, without blocking the other tasks of the multi-threaded runtime
+/// of the node. The worker thread signing is still blocked for up to the request timeout. A
+/// current-thread runtime cannot block in place, so no signature is made on it. A failed
+/// signature is fatal to the authority state, so the signer must be highly available.
+impl signature::Signer<AuthoritySignature> for RemoteSigner {
+    fn try_sign(&self, message: &[u8]) -> Result<AuthoritySignature, signature::Error> {
+        let handle = tokio::runtime::Handle::try_current().map_err(|e| {
+            error!("The protocol key signer needs a runtime: {e}");
+            signature::Error::new()
+        })?;
+        if handle.runtime_flavor() != RuntimeFlavor::MultiThread {
+            error!("The protocol key signer needs a multi-threaded runtime");
+            return Err(signature::Error::new());
+        }
+        tokio::task::block_in_place(|| handle.block_on(self.sign_remotely(message))).map_err(|e| {
+            error!("Failed to sign with the protocol key signer: {e}");
+            signature::Error::new()
+        })
+    }
+}
+
+/// Returns the signer of the authority state, which is checked against the configured public key
+/// when the protocol key is held by an external signer.
+pub async fn authority_signer(config: &NodeConfig) -> Result<StableSyncAuthoritySigner> {
+    match &config.protocol_key_signer {
+        Some(signer_config) => {
+            let signer = RemoteSigner::new(signer_config)?;
+            signer.check().await?;
+            info!(
+                "Signing with the protocol key {} of the signer at {}",
+                signer_config.key_id, signer_config.url
+            );
+            Ok(Arc::pin(signer))
+        }
+        None => Ok(Arc::pin(config.protocol_key_pair().copy())),
+    }
+}
+
+/// Returns the signer of the consensus primary, for the authority of the given name, i.e. with the
+/// next protocol key once it was rotated to it.
+pub fn primary_signer(
+    config: &NodeConfig,
+    name: AuthorityName,
+) -> Result<Arc<dyn AuthoritySigner>> {
+    if let Some(key_pair) = next_protocol_key_pair(config) {
+        if AuthorityName::from(key_pair.public()) == name {
+            return Ok(Arc::new(key_pair.copy()));
+        }
+    }
+    match &config.protocol_key_signer {
+        Some(signer_config) => Ok(Arc::new(RemoteSigner::new(signer_config)?)),
+        None => Ok(Arc::new(config.protocol_key_pair().copy())),
+    }
+}
+
+fn next_protocol_key_pair(config: &NodeConfig) -> Option<&AuthorityKeyPair> {
+    let key_pair = config.next_protocol_key_pair.as_ref()?;
+    key_pair
+        .try_authority_keypair()
+        .map_err(|e| warn!("Failed to load the next protocol key pair: {e}"))
+        .ok()
+}
+
+/// Returns the name of the authority in the epoch of the committee: the public key of its next
+/// protocol key pair if the committee has it instead of the current one, the current name
+/// otherwise.
+pub fn name_in_committee(
+    config: &NodeConfig,
+    name: AuthorityName,
+    committee: &Committee,
+) -> AuthorityName {
+    if committee.authority_exists(&name) {
+        return name;
+    }
+    match next_protocol_key_pair(config).map(|key_pair| AuthorityName::from(key_pair.public())) {
+        Some(next) if committee.authority_exists(&next) => next,
+        _ => name,
+    }
+}
+
+/// Returns the name and the signer of the next protocol key of the authority, if its protocol key
+/// is rotated in the epoch of the committee.
+pub fn rotated_protocol_key(
+    config: &NodeConfig,
+    name: AuthorityName,
+    committee: &Committee,
+) -> Option<(AuthorityName, StableSyncAuthoritySigner)> {
+    let next = name_in_committee(config, name, committee);
+    if next == name {
+        return None;
+    }
+    let key_pair = next_protocol_key_pair(config)?;
+    Some((next, Arc::pin(key_pair.copy())))
+}
@@ -528,6 +528,7 @@ impl RpcExampleProvider {
             raw_transaction,
             confirmed_local_execution: None,
             checkpoint: None,
+            checkpoint_watermark: None,
             errors: vec![],
         };
 
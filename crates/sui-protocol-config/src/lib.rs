@@ -117,6 +117,37 @@ struct FeatureFlags {
     // If true, validators will commit to the root state digest
     // in end of epoch checkpoint proposals
     commit_root_state_digest: bool,
+    // If true, the Narwhal workers create versioned V2 batches, which carry their
+    // priority lane. Every node decodes both versions regardless of this flag.
+    narwhal_batch_v2: bool,
+    // If true, the consensus commit prologue uses the timestamp Narwhal derives for each
+    // committed sub-dag, instead of the creation time of the leader's header.
+    consensus_commit_timestamp: bool,
+    // If true, the Narwhal primaries bound the batch digests of the headers they create and
+    // vote for to the limits below, instead of to the limits of their own parameters.
+    narwhal_header_payload_limits: bool,
+    // If true, the Narwhal workers reject the user transactions whose gas price is below the
+    // reference gas price of the epoch, before batching them and in the batches of their peers.
+    consensus_minimum_gas_price: bool,
+    // If true, the transactions touching a shared object which already has its budget of
+    // transactions in a consensus commit are deferred to the next commit.
+    shared_object_congestion_control: bool,
+    // If true, the transactions of a consensus commit are shuffled, seeded by the digest of the
+    // leader of the commit, before they are executed.
+    consensus_transaction_shuffling: bool,
+    // If true, the batches created and accepted by the Narwhal workers are bounded in number of
+    // transactions and in size.
+    narwhal_batch_limits: bool,
+    // If true, the Narwhal workers create V3 batches, whose digest is a tree over chunks of
+    // their transactions, hashed in parallel for the large batches. It takes precedence over
+    // `narwhal_batch_v2`.
+    narwhal_batch_v3: bool,
+    // If true, the Narwhal primaries swap the leaders with the lowest reputation scores out of
+    // the Bullshark schedule, for the ones with the highest scores.
+    narwhal_leader_swaps: bool,
+    // If true, the transactions of a sender are only accepted by the Narwhal worker assigned to
+    // it, so that they are ordered within that worker.
+    narwhal_worker_sharding_by_sender: bool,
 }
 
 /// Constants that change the behavior of the protocol.
@@ -367,6 +398,29 @@ pub struct ProtocolConfig {
     /// 3f+1 must vote), while 0bps would indicate that 2f+1 is sufficient.
     buffer_stake_for_protocol_upgrade_bps: Option<u64>,
 
+    // === Narwhal ===
+    /// Maximum number of batch digests in a Narwhal header.
+    /// Only enforced when the `narwhal_header_payload_limits` feature flag is set, as the headers
+    /// exceeding it are refused votes.
+    narwhal_max_header_num_of_batches: Option<u64>,
+
+    /// Maximum number of batch digests of a single Narwhal worker in a header.
+    narwhal_max_header_num_of_batches_per_worker: Option<u64>,
+
+    /// Maximum number of transactions in a Narwhal batch.
+    /// Only enforced when the `narwhal_batch_limits` feature flag is set, as the batches
+    /// exceeding it are rejected.
+    narwhal_max_batch_num_of_transactions: Option<u64>,
+
+    /// Maximum size in bytes of the transactions of a Narwhal batch.
+    narwhal_max_batch_size_bytes: Option<u64>,
+
+    // === Consensus ===
+    /// Maximum number of transactions touching a single shared object in a consensus commit.
+    /// Only enforced when the `shared_object_congestion_control` feature flag is set, as the
+    /// transactions past it are deferred to the next commit.
+    max_transactions_per_shared_object_per_commit: Option<u64>,
+
     // === Native Function Costs ===
 
     // `address` module
@@ -554,6 +608,46 @@ impl ProtocolConfig {
     pub fn check_commit_root_state_digest_supported(&self) -> bool {
         self.feature_flags.commit_root_state_digest
     }
+
+    pub fn check_narwhal_batch_v2_supported(&self) -> bool {
+        self.feature_flags.narwhal_batch_v2
+    }
+
+    pub fn check_consensus_commit_timestamp_supported(&self) -> bool {
+        self.feature_flags.consensus_commit_timestamp
+    }
+
+    pub fn check_narwhal_header_payload_limits_supported(&self) -> bool {
+        self.feature_flags.narwhal_header_payload_limits
+    }
+
+    pub fn check_consensus_minimum_gas_price_supported(&self) -> bool {
+        self.feature_flags.consensus_minimum_gas_price
+    }
+
+    pub fn check_shared_object_congestion_control_supported(&self) -> bool {
+        self.feature_flags.shared_object_congestion_control
+    }
+
+    pub fn check_consensus_transaction_shuffling_supported(&self) -> bool {
+        self.feature_flags.consensus_transaction_shuffling
+    }
+
+    pub fn check_narwhal_batch_limits_supported(&self) -> bool {
+        self.feature_flags.narwhal_batch_limits
+    }
+
+    pub fn check_narwhal_batch_v3_supported(&self) -> bool {
+        self.feature_flags.narwhal_batch_v3
+    }
+
+    pub fn check_narwhal_leader_swaps_supported(&self) -> bool {
+        self.feature_flags.narwhal_leader_swaps
+    }
+
+    pub fn check_narwhal_worker_sharding_by_sender_supported(&self) -> bool {
+        self.feature_flags.narwhal_worker_sharding_by_sender
+    }
 }
 
 // getters
@@ -760,6 +854,25 @@ impl ProtocolConfig {
         self.buffer_stake_for_protocol_upgrade_bps
             .expect(CONSTANT_ERR_MSG)
     }
+    pub fn narwhal_max_header_num_of_batches(&self) -> u64 {
+        self.narwhal_max_header_num_of_batches
+            .expect(CONSTANT_ERR_MSG)
+    }
+    pub fn narwhal_max_header_num_of_batches_per_worker(&self) -> u64 {
+        self.narwhal_max_header_num_of_batches_per_worker
+            .expect(CONSTANT_ERR_MSG)
+    }
+    pub fn narwhal_max_batch_num_of_transactions(&self) -> u64 {
+        self.narwhal_max_batch_num_of_transactions
+            .expect(CONSTANT_ERR_MSG)
+    }
+    pub fn narwhal_max_batch_size_bytes(&self) -> u64 {
+        self.narwhal_max_batch_size_bytes.expect(CONSTANT_ERR_MSG)
+    }
+    pub fn max_transactions_per_shared_object_per_commit(&self) -> u64 {
+        self.max_transactions_per_shared_object_per_commit
+            .expect(CONSTANT_ERR_MSG)
+    }
 
     pub fn address_from_bytes_cost_base(&self) -> u64 {
         self.address_from_bytes_cost_base.expect(CONSTANT_ERR_MSG)
@@ -1313,6 +1426,13 @@ impl ProtocolConfig {
                 // MUSTFIX: This number should be increased to at least 2000 (20%) for mainnet.
                 buffer_stake_for_protocol_upgrade_bps: Some(0),
 
+                narwhal_max_header_num_of_batches: Some(1_000),
+                narwhal_max_header_num_of_batches_per_worker: Some(1_000),
+                narwhal_max_batch_num_of_transactions: Some(10_000),
+                narwhal_max_batch_size_bytes: Some(5_000_000),
+
+                max_transactions_per_shared_object_per_commit: Some(100),
+
                 /// === Native Function Costs ===
                 // `address` module
                 // Cost params for the Move native function `address::from_bytes(bytes: vector<u8>)`
@@ -1518,6 +1638,42 @@ impl ProtocolConfig {
     pub fn set_package_upgrades_for_testing(&mut self, val: bool) {
         self.feature_flags.package_upgrades = val
     }
+    pub fn set_narwhal_batch_v2_for_testing(&mut self, val: bool) {
+        self.feature_flags.narwhal_batch_v2 = val
+    }
+    pub fn set_consensus_commit_timestamp_for_testing(&mut self, val: bool) {
+        self.feature_flags.consensus_commit_timestamp = val
+    }
+    pub fn set_narwhal_header_payload_limits_for_testing(&mut self, val: bool) {
+        self.feature_flags.narwhal_header_payload_limits = val
+    }
+    pub fn set_consensus_minimum_gas_price_for_testing(&mut self, val: bool) {
+        self.feature_flags.consensus_minimum_gas_price = val
+    }
+    pub fn set_shared_object_congestion_control_for_testing(&mut self, val: bool) {
+        self.feature_flags.shared_object_congestion_control = val
+    }
+    pub fn set_max_transactions_per_shared_object_per_commit_for_testing(&mut self, val: u64) {
+        self.max_transactions_per_shared_object_per_commit = Some(val)
+    }
+    pub fn set_consensus_transaction_shuffling_for_testing(&mut self, val: bool) {
+        self.feature_flags.consensus_transaction_shuffling = val
+    }
+    pub fn set_narwhal_batch_limits_for_testing(&mut self, val: bool) {
+        self.feature_flags.narwhal_batch_limits = val
+    }
+    pub fn set_narwhal_max_batch_num_of_transactions_for_testing(&mut self, val: u64) {
+        self.narwhal_max_batch_num_of_transactions = Some(val)
+    }
+    pub fn set_narwhal_batch_v3_for_testing(&mut self, val: bool) {
+        self.feature_flags.narwhal_batch_v3 = val
+    }
+    pub fn set_narwhal_leader_swaps_for_testing(&mut self, val: bool) {
+        self.feature_flags.narwhal_leader_swaps = val
+    }
+    pub fn set_narwhal_worker_sharding_by_sender_for_testing(&mut self, val: bool) {
+        self.feature_flags.narwhal_worker_sharding_by_sender = val
+    }
 }
 
 type OverrideFn = dyn Fn(ProtocolVersion, ProtocolConfig) -> ProtocolConfig + Send;
@@ -15,11 +15,14 @@ use sui_json_rpc::api::GovernanceReadApiClient;
 use sui_json_rpc::api::IndexerApiClient;
 use sui_json_rpc::api::MoveUtilsClient;
 use sui_json_rpc_types::{
-    Balance, Checkpoint, CheckpointId, CheckpointedObjectID, Coin, CoinPage, DelegatedStake,
-    DryRunTransactionBlockResponse, DynamicFieldPage, EventFilter, EventPage, ObjectsPage,
-    SuiCoinMetadata, SuiCommittee, SuiEvent, SuiGetPastObjectRequest, SuiMoveNormalizedModule,
-    SuiObjectDataOptions, SuiObjectResponse, SuiObjectResponseQuery, SuiPastObjectResponse,
-    SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    Balance, Checkpoint, CheckpointId, CheckpointObjectChangePage, CheckpointProof,
+    CheckpointedObjectID, Coin, CoinPage, DelegatedStake, DryRunTransactionBlockResponse,
+    DynamicFieldPage, EventFilter, EventPage, FullCheckpoint, GasPriceEstimates,
+    ObjectChangeFilter, ObjectChangeNotification, ObjectsPage, SuiCoinMetadata, SuiCommittee,
+    SuiEvent, SuiGetPastObjectRequest, SuiMoveNormalizedModule, SuiObjectDataOptions,
+    SuiObjectResponse, SuiObjectResponseQuery, SuiObjectVersionConflict, SuiObjectsAtCheckpoint,
+    SuiPackageUpgradeReport, SuiPastObjectResponse, SuiTransactionBlockEffectsAPI,
+    SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
     SuiTransactionBlockResponseQuery, TransactionBlocksPage,
 };
 use sui_types::balance::Supply;
@@ -96,6 +99,20 @@ impl ReadApi {
             .await?)
     }
 
+    /// Explain why a transaction expecting the version of the object fails with a version
+    /// conflict, reporting which transaction consumed the version and in which checkpoint
+    pub async fn get_object_version_conflict(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> SuiRpcResult<SuiObjectVersionConflict> {
+        Ok(self
+            .api
+            .http
+            .get_object_version_conflict(object_id, version)
+            .await?)
+    }
+
     pub async fn get_object_with_options(
         &self,
         object_id: ObjectID,
@@ -116,6 +133,19 @@ impl ReadApi {
             .await?)
     }
 
+    pub async fn multi_get_objects_at_checkpoint(
+        &self,
+        object_ids: Vec<ObjectID>,
+        checkpoint: Option<CheckpointSequenceNumber>,
+        options: SuiObjectDataOptions,
+    ) -> SuiRpcResult<SuiObjectsAtCheckpoint> {
+        Ok(self
+            .api
+            .http
+            .multi_get_objects_at_checkpoint(object_ids, checkpoint.map(Into::into), Some(options))
+            .await?)
+    }
+
     pub async fn get_total_transaction_blocks(&self) -> SuiRpcResult<u64> {
         Ok(self.api.http.get_total_transaction_blocks().await?.into())
     }
@@ -144,6 +174,46 @@ impl ReadApi {
             .await?)
     }
 
+    /// Streams the transactions at checkpoint finality, from the given checkpoint or from the
+    /// next one executed.
+    pub async fn subscribe_finalized_transaction(
+        &self,
+        from_checkpoint: Option<CheckpointSequenceNumber>,
+        options: SuiTransactionBlockResponseOptions,
+    ) -> SuiRpcResult<impl Stream<Item = SuiRpcResult<SuiTransactionBlockResponse>>> {
+        match &self.api.ws {
+            Some(c) => {
+                let subscription: Subscription<SuiTransactionBlockResponse> = c
+                    .subscribe_finalized_transaction(from_checkpoint.map(Into::into), Some(options))
+                    .await?;
+                Ok(subscription.map(|item| Ok(item?)))
+            }
+            _ => Err(Error::Subscription(
+                "Subscription only supported by WebSocket client.".to_string(),
+            )),
+        }
+    }
+
+    /// Streams the changes of the objects matching the filter at checkpoint finality, from the
+    /// given checkpoint or from the next one executed.
+    pub async fn subscribe_object_change(
+        &self,
+        filter: ObjectChangeFilter,
+        from_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> SuiRpcResult<impl Stream<Item = SuiRpcResult<ObjectChangeNotification>>> {
+        match &self.api.ws {
+            Some(c) => {
+                let subscription: Subscription<ObjectChangeNotification> = c
+                    .subscribe_object_change(filter, from_checkpoint.map(Into::into))
+                    .await?;
+                Ok(subscription.map(|item| Ok(item?)))
+            }
+            _ => Err(Error::Subscription(
+                "Subscription only supported by WebSocket client.".to_string(),
+            )),
+        }
+    }
+
     pub async fn get_committee_info(&self, epoch: Option<EpochId>) -> SuiRpcResult<SuiCommittee> {
         Ok(self.api.http.get_committee_info(epoch).await?)
     }
@@ -167,6 +237,32 @@ impl ReadApi {
         Ok(self.api.http.get_checkpoint(id).await?)
     }
 
+    /// Return the proof that a checkpoint is final, to verify against the committee of its epoch
+    pub async fn get_checkpoint_proof(&self, id: CheckpointId) -> SuiRpcResult<CheckpointProof> {
+        Ok(self.api.http.get_checkpoint_proof(id).await?)
+    }
+
+    /// Return a checkpoint with the transactions and effects it contains
+    pub async fn get_full_checkpoint(&self, id: CheckpointId) -> SuiRpcResult<FullCheckpoint> {
+        Ok(self.api.http.get_full_checkpoint(id).await?)
+    }
+
+    /// Return a page of the objects created, mutated or deleted by the checkpoints after `from`,
+    /// up to `to` included
+    pub async fn get_object_changes_between_checkpoints(
+        &self,
+        from: CheckpointSequenceNumber,
+        to: CheckpointSequenceNumber,
+        cursor: Option<ObjectID>,
+        limit: Option<usize>,
+    ) -> SuiRpcResult<CheckpointObjectChangePage> {
+        Ok(self
+            .api
+            .http
+            .get_object_changes_between_checkpoints(from.into(), to.into(), cursor, limit)
+            .await?)
+    }
+
     /// Return the sequence number of the latest checkpoint that has been executed
     pub async fn get_latest_checkpoint_sequence_number(
         &self,
@@ -222,6 +318,25 @@ impl ReadApi {
             .await?)
     }
 
+    pub async fn check_package_upgrade(
+        &self,
+        package: ObjectID,
+        compiled_modules: Vec<Vec<u8>>,
+        dependencies: Vec<ObjectID>,
+        policy: Option<u8>,
+    ) -> SuiRpcResult<SuiPackageUpgradeReport> {
+        Ok(self
+            .api
+            .http
+            .check_package_upgrade(
+                package,
+                compiled_modules.iter().map(Base64::from_bytes).collect(),
+                dependencies,
+                policy,
+            )
+            .await?)
+    }
+
     // TODO(devx): we can probably cache this given an epoch
     pub async fn get_reference_gas_price(&self) -> SuiRpcResult<u64> {
         Ok(self.api.http.get_reference_gas_price().await?.into())
@@ -234,7 +349,23 @@ impl ReadApi {
         Ok(self
             .api
             .http
-            .dry_run_transaction_block(Base64::from_bytes(&bcs::to_bytes(&tx)?))
+            .dry_run_transaction_block(Base64::from_bytes(&bcs::to_bytes(&tx)?), None)
+            .await?)
+    }
+
+    /// Dry runs the transaction against the state as of the checkpoint, on archive nodes.
+    pub async fn dry_run_transaction_block_at_checkpoint(
+        &self,
+        tx: TransactionData,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiRpcResult<DryRunTransactionBlockResponse> {
+        Ok(self
+            .api
+            .http
+            .dry_run_transaction_block(
+                Base64::from_bytes(&bcs::to_bytes(&tx)?),
+                Some(checkpoint.into()),
+            )
             .await?)
     }
 }
@@ -543,4 +674,18 @@ impl GovernanceApi {
     pub async fn get_reference_gas_price(&self) -> SuiRpcResult<u64> {
         Ok(self.api.http.get_reference_gas_price().await?.into())
     }
+
+    /// Return the gas price percentiles observed in the latest checkpoints, along with the
+    /// reference gas price.
+    /// `num_checkpoints`: The number of latest checkpoints to observe, default to 100
+    pub async fn get_gas_price_estimates(
+        &self,
+        num_checkpoints: Option<usize>,
+    ) -> SuiRpcResult<GasPriceEstimates> {
+        Ok(self
+            .api
+            .http
+            .get_gas_price_estimates(num_checkpoints)
+            .await?)
+    }
 }
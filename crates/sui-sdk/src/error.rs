@@ -30,4 +30,6 @@ pub enum Error {
     },
     #[error("Insufficient fund for address [{address}], requested amount: {amount}")]
     InsufficientFund { address: SuiAddress, amount: u128 },
+    #[error("Invalid sponsored transaction: {0}")]
+    SponsorshipError(String),
 }
@@ -0,0 +1,154 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gas sponsorship: a sponsored transaction is sent by its sender, and its gas is paid by its
+//! sponsor, the gas owner. Both of them sign the transaction, one after the other: typically the
+//! sponsor builds the transaction with `TransactionBuilder::sponsored_transaction` and signs it,
+//! then passes the partially signed transaction to the sender which signs and executes it.
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::traits::ToFromBytes;
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_keys::keystore::AccountKeystore;
+use sui_types::base_types::SuiAddress;
+use sui_types::messages::{Transaction, TransactionData, TransactionDataAPI, VerifiedTransaction};
+use sui_types::signature::{AuthenticatorTrait, GenericSignature};
+
+use crate::error::{Error, SuiRpcResult};
+
+/// A sponsored transaction with the signatures of some of its signers, to be passed between the
+/// sender and the sponsor until both of them signed it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PartiallySignedTransaction {
+    data: TransactionData,
+    signatures: Vec<GenericSignature>,
+}
+
+impl PartiallySignedTransaction {
+    /// Returns the unsigned transaction, once checked that it is a valid sponsored transaction.
+    pub fn new(data: TransactionData) -> SuiRpcResult<Self> {
+        if !data.is_sponsored_tx() {
+            return Err(Error::SponsorshipError(format!(
+                "The gas of the transaction is paid by its sender {}",
+                data.sender()
+            )));
+        }
+        data.check_sponsorship()?;
+        Ok(Self {
+            data,
+            signatures: Vec::new(),
+        })
+    }
+
+    /// Decodes the payload passed to the other signer, see `tx_bytes_and_signatures`. The
+    /// signatures are verified.
+    pub fn from_tx_bytes_and_signatures(
+        tx_bytes: &Base64,
+        signatures: &[Base64],
+    ) -> SuiRpcResult<Self> {
+        let mut transaction = Self::new(bcs::from_bytes(&decode_base64(tx_bytes)?)?)?;
+        for signature in signatures {
+            let signature = GenericSignature::from_bytes(&decode_base64(signature)?)
+                .map_err(|e| Error::SponsorshipError(e.to_string()))?;
+            transaction.add_signature(signature)?;
+        }
+        Ok(transaction)
+    }
+
+    /// Returns the Base64 encoded transaction data and signatures, to be passed to the other
+    /// signer.
+    pub fn tx_bytes_and_signatures(&self) -> (Base64, Vec<Base64>) {
+        (
+            Base64::from_bytes(&bcs::to_bytes(&self.data).unwrap()),
+            self.signatures
+                .iter()
+                .map(|s| Base64::from_bytes(s.as_ref()))
+                .collect(),
+        )
+    }
+
+    pub fn data(&self) -> &TransactionData {
+        &self.data
+    }
+
+    pub fn sender(&self) -> SuiAddress {
+        self.data.sender()
+    }
+
+    pub fn sponsor(&self) -> SuiAddress {
+        self.data.gas_owner()
+    }
+
+    /// Signs the transaction as the signer, which must be either its sender or its sponsor.
+    pub fn sign(
+        &mut self,
+        keystore: &impl AccountKeystore,
+        signer: SuiAddress,
+    ) -> SuiRpcResult<()> {
+        let signature = keystore
+            .sign_secure(&signer, &self.data, Intent::default())
+            .map_err(|e| Error::SponsorshipError(e.to_string()))?;
+        self.add_signature(signature.into())?;
+        Ok(())
+    }
+
+    /// Adds the signature of a signer, once verified. Returns the address of the signer.
+    pub fn add_signature(&mut self, signature: GenericSignature) -> SuiRpcResult<SuiAddress> {
+        let signer =
+            SuiAddress::try_from(&signature).map_err(|e| Error::SponsorshipError(e.to_string()))?;
+        if !self.data.signers().contains(&signer) {
+            return Err(Error::SponsorshipError(format!(
+                "{signer} is neither the sender nor the sponsor of the transaction"
+            )));
+        }
+        signature
+            .verify_secure_generic(
+                &IntentMessage::new(Intent::default(), self.data.clone()),
+                signer,
+            )
+            .map_err(|e| Error::SponsorshipError(e.to_string()))?;
+
+        // A signer signing again replaces its signature.
+        self.signatures
+            .retain(|s| SuiAddress::try_from(s).map_or(true, |address| address != signer));
+        self.signatures.push(signature);
+        Ok(signer)
+    }
+
+    /// Returns the signers which did not sign the transaction yet.
+    pub fn missing_signers(&self) -> Vec<SuiAddress> {
+        let signed: Vec<_> = self
+            .signatures
+            .iter()
+            .flat_map(|s| SuiAddress::try_from(s).ok())
+            .collect();
+        self.data
+            .signers()
+            .into_iter()
+            .filter(|signer| !signed.contains(signer))
+            .collect()
+    }
+
+    pub fn is_fully_signed(&self) -> bool {
+        self.missing_signers().is_empty()
+    }
+
+    /// Returns the transaction to execute, once signed by both the sender and the sponsor.
+    pub fn into_transaction(self) -> SuiRpcResult<VerifiedTransaction> {
+        let missing_signers = self.missing_signers();
+        if !missing_signers.is_empty() {
+            return Err(Error::SponsorshipError(format!(
+                "The transaction is missing the signatures of {missing_signers:?}"
+            )));
+        }
+        Transaction::from_generic_sig_data(self.data, Intent::default(), self.signatures)
+            .verify()
+            .map_err(|e| Error::SponsorshipError(e.to_string()))
+    }
+}
+
+fn decode_base64(value: &Base64) -> SuiRpcResult<Vec<u8>> {
+    value
+        .to_vec()
+        .map_err(|e| Error::SponsorshipError(e.to_string()))
+}
@@ -1,10 +1,15 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use shared_crypto::intent::Intent;
 use tempfile::TempDir;
 
-use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
+use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, InMemKeystore, Keystore};
+use sui_sdk::sponsorship::PartiallySignedTransaction;
+use sui_types::base_types::{random_object_ref, SuiAddress};
 use sui_types::crypto::Ed25519SuiSignature;
 use sui_types::crypto::{SignatureScheme, SuiSignatureInner};
+use sui_types::messages::{GasData, TransactionData, TransactionKind};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 #[test]
 fn mnemonic_test() {
     let temp_dir = TempDir::new().unwrap();
@@ -32,3 +37,60 @@ fn keystore_display_test() -> Result<(), anyhow::Error> {
     assert!(!keystore.to_string().contains("keys:"));
     Ok(())
 }
+
+fn sponsored_transaction(sender: SuiAddress, sponsor: SuiAddress) -> TransactionData {
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_sui(sponsor, Some(1));
+    TransactionData::new_with_gas_data(
+        TransactionKind::programmable(builder.finish()),
+        sender,
+        GasData {
+            payment: vec![random_object_ref()],
+            owner: sponsor,
+            price: 1,
+            budget: 10_000,
+        },
+    )
+}
+
+#[test]
+fn sign_sponsored_transaction() {
+    let keystore = Keystore::from(InMemKeystore::new(3));
+    let addresses = keystore.addresses();
+    let (sender, sponsor, other) = (addresses[0], addresses[1], addresses[2]);
+    let data = sponsored_transaction(sender, sponsor);
+
+    // The sponsor signs first, and passes the payload to the sender.
+    let mut transaction = PartiallySignedTransaction::new(data.clone()).unwrap();
+    transaction.sign(&keystore, sponsor).unwrap();
+    assert_eq!(transaction.missing_signers(), vec![sender]);
+    assert!(transaction.sign(&keystore, other).is_err());
+    let (tx_bytes, signatures) = transaction.tx_bytes_and_signatures();
+
+    let mut transaction =
+        PartiallySignedTransaction::from_tx_bytes_and_signatures(&tx_bytes, &signatures).unwrap();
+    assert_eq!(transaction.sponsor(), sponsor);
+    assert!(transaction.clone().into_transaction().is_err());
+    transaction.sign(&keystore, sender).unwrap();
+    assert!(transaction.is_fully_signed());
+    let transaction = transaction.into_transaction().unwrap();
+    assert_eq!(transaction.data().transaction_data(), &data);
+
+    // A signature of another transaction is refused.
+    let mut transaction = PartiallySignedTransaction::new(data).unwrap();
+    let signature = keystore
+        .sign_secure(
+            &sender,
+            &sponsored_transaction(sender, other),
+            Intent::default(),
+        )
+        .unwrap();
+    assert!(transaction.add_signature(signature.into()).is_err());
+}
+
+#[test]
+fn refuse_transaction_without_sponsor() {
+    let keystore = Keystore::from(InMemKeystore::new(1));
+    let sender = keystore.addresses()[0];
+    assert!(PartiallySignedTransaction::new(sponsored_transaction(sender, sender)).is_err());
+}
@@ -6,4 +6,5 @@ pub use indexes::{IndexStore, IndexStoreTables};
 
 pub mod mutex_table;
 pub mod object_store;
+pub mod quorum_driver_journal;
 pub mod write_path_pending_tx_log;
@@ -0,0 +1,158 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! QuorumDriverJournal records the transactions accepted by the quorum driver of a fullnode and
+//! how far they were driven to finality. When the fullnode crashes and restarts, the
+//! transactions still in flight are driven again from where they were: a certified transaction
+//! only has its certificate executed, as long as the epoch of the certificate did not end.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sui_types::base_types::TransactionDigest;
+use sui_types::error::SuiResult;
+use sui_types::messages::{
+    TrustedCertificate, TrustedTransaction, VerifiedCertificate, VerifiedTransaction,
+};
+use typed_store::rocks::MetricConf;
+use typed_store::traits::{TableSummary, TypedStoreDebug};
+use typed_store::{rocks::DBMap, traits::Map};
+use typed_store_derive::DBMapUtils;
+
+/// How far a transaction of the journal was driven to finality.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum JournalStage {
+    /// The transaction is being signed by the validators.
+    Accepted,
+    /// The transaction was certified, only its certificate remains to be executed.
+    Certified(TrustedCertificate),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub transaction: TrustedTransaction,
+    pub stage: JournalStage,
+    /// When the transaction was accepted, in milliseconds since the unix epoch.
+    pub accepted_at_ms: u64,
+}
+
+impl JournalEntry {
+    pub fn transaction(&self) -> VerifiedTransaction {
+        self.transaction.clone().into()
+    }
+
+    /// The certificate of the transaction, once certified.
+    pub fn certificate(&self) -> Option<VerifiedCertificate> {
+        match &self.stage {
+            JournalStage::Accepted => None,
+            JournalStage::Certified(certificate) => Some(certificate.clone().into()),
+        }
+    }
+}
+
+#[derive(DBMapUtils)]
+struct QuorumDriverJournalTables {
+    entries: DBMap<TransactionDigest, JournalEntry>,
+}
+
+pub struct QuorumDriverJournal {
+    tables: QuorumDriverJournalTables,
+}
+
+impl QuorumDriverJournal {
+    pub fn new(path: PathBuf) -> Self {
+        let tables = QuorumDriverJournalTables::open_tables_read_write(
+            path,
+            MetricConf::default(),
+            None,
+            None,
+        );
+        Self { tables }
+    }
+
+    /// Records that the transaction was accepted. A transaction already in flight keeps its
+    /// progress, e.g. when it is submitted again by the client.
+    pub fn accept(&self, transaction: &VerifiedTransaction) -> SuiResult {
+        let tx_digest = transaction.digest();
+        if self.tables.entries.contains_key(tx_digest)? {
+            return Ok(());
+        }
+        let accepted_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_millis() as u64)
+            .unwrap_or_default();
+        self.tables.entries.insert(
+            tx_digest,
+            &JournalEntry {
+                transaction: transaction.serializable_ref().clone(),
+                stage: JournalStage::Accepted,
+                accepted_at_ms,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Records that the transaction was certified. It is a no-op for a transaction which is not
+    /// in flight, e.g. as it was finished concurrently.
+    pub fn certified(&self, certificate: &VerifiedCertificate) -> SuiResult {
+        let tx_digest = certificate.digest();
+        let Some(mut entry) = self.tables.entries.get(tx_digest)? else {
+            return Ok(());
+        };
+        entry.stage = JournalStage::Certified(certificate.serializable_ref().clone());
+        self.tables.entries.insert(tx_digest, &entry)?;
+        Ok(())
+    }
+
+    /// Removes the transaction from the journal once it reached finality, or failed to.
+    pub fn finish(&self, tx_digest: &TransactionDigest) -> SuiResult {
+        self.tables.entries.remove(tx_digest)?;
+        Ok(())
+    }
+
+    pub fn get(&self, tx_digest: &TransactionDigest) -> SuiResult<Option<JournalEntry>> {
+        Ok(self.tables.entries.get(tx_digest)?)
+    }
+
+    /// The transactions in flight, to drive again after a restart.
+    pub fn load_in_flight(&self) -> Vec<JournalEntry> {
+        self.tables
+            .entries
+            .iter()
+            .map(|(_tx_digest, entry)| entry)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::utils::create_fake_transaction;
+
+    #[test]
+    fn test_journal_recovers_in_flight_transactions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let journal = QuorumDriverJournal::new(temp_dir.path().to_path_buf());
+        let tx = create_fake_transaction();
+        let other = create_fake_transaction();
+        journal.accept(&tx).unwrap();
+        journal.accept(&other).unwrap();
+        // Accepting it again keeps the transaction once.
+        journal.accept(&tx).unwrap();
+        journal.finish(other.digest()).unwrap();
+        drop(journal);
+
+        // The transactions in flight are loaded back once reopened.
+        let journal = QuorumDriverJournal::new(temp_dir.path().to_path_buf());
+        let in_flight = journal.load_in_flight();
+        assert_eq!(in_flight.len(), 1);
+        assert_eq!(in_flight[0].transaction(), tx);
+        assert!(in_flight[0].certificate().is_none());
+
+        journal.finish(tx.digest()).unwrap();
+        assert!(journal.load_in_flight().is_empty());
+        assert!(journal.get(tx.digest()).unwrap().is_none());
+        // It's ok to finish an already finished transaction.
+        journal.finish(tx.digest()).unwrap();
+    }
+}
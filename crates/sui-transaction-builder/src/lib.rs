@@ -27,7 +27,8 @@ use sui_types::error::UserInputError;
 use sui_types::gas_coin::GasCoin;
 use sui_types::governance::{ADD_STAKE_MUL_COIN_FUN_NAME, WITHDRAW_STAKE_FUN_NAME};
 use sui_types::messages::{
-    Argument, CallArg, Command, InputObjectKind, ObjectArg, TransactionData, TransactionKind,
+    Argument, CallArg, Command, GasData, InputObjectKind, ObjectArg, TransactionData,
+    TransactionDataAPI, TransactionKind,
 };
 use sui_types::move_package::MovePackage;
 use sui_types::object::{Object, Owner};
@@ -779,6 +780,48 @@ impl<Mode: ExecutionMode> TransactionBuilder<Mode> {
         )
     }
 
+    /// Builds a transaction sent by the sender, whose gas is paid by the sponsor. The gas coin is
+    /// selected among the coins of the sponsor when `sponsor_gas` is None. The transaction must
+    /// then be signed by both the sender and the sponsor.
+    pub async fn sponsored_transaction(
+        &self,
+        kind: TransactionKind,
+        sender: SuiAddress,
+        sponsor: SuiAddress,
+        sponsor_gas: Option<ObjectID>,
+        gas_budget: u64,
+    ) -> anyhow::Result<TransactionData> {
+        ensure!(
+            sender != sponsor,
+            "The sponsor of a transaction must not be its sender"
+        );
+        let inputs = kind
+            .input_objects()?
+            .iter()
+            .flat_map(|obj| match obj {
+                InputObjectKind::ImmOrOwnedMoveObject((id, _, _)) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        let gas_price = self.0.get_reference_gas_price().await?;
+        let gas = self
+            .select_gas(sponsor, sponsor_gas, gas_budget, inputs, gas_price)
+            .await?;
+
+        let data = TransactionData::new_with_gas_data(
+            kind,
+            sender,
+            GasData {
+                payment: vec![gas],
+                owner: sponsor,
+                price: gas_price,
+                budget: gas_budget,
+            },
+        );
+        data.check_sponsorship()?;
+        Ok(data)
+    }
+
     // TODO: we should add retrial to reduce the transaction building error rate
     async fn get_object_ref(&self, object_id: ObjectID) -> anyhow::Result<ObjectRef> {
         self.get_object_ref_and_type(object_id)
@@ -1433,6 +1433,8 @@ mod bcs_signable {
 
     impl BcsSignable for crate::accumulator::Accumulator {}
 
+    impl BcsSignable for crate::validator_report::ValidatorReport {}
+
     impl BcsSignable for super::bcs_signable_test::Foo {}
     #[cfg(test)]
     impl BcsSignable for super::bcs_signable_test::Bar {}
@@ -343,6 +343,13 @@ pub enum SuiError {
     TransactionNotFound { digest: TransactionDigest },
     #[error("{TRANSACTIONS_NOT_FOUND_MSG_PREFIX} [{:?}].", digests)]
     TransactionsNotFound { digests: Vec<TransactionDigest> },
+    #[error("Transaction [{digest:?}] was pruned by this node.")]
+    TransactionPruned { digest: TransactionDigest },
+    #[error("Version {version:?} of object {object_id} was pruned by this node.")]
+    ObjectVersionPruned {
+        object_id: ObjectID,
+        version: SequenceNumber,
+    },
     #[error("Could not find the referenced transaction events [{digest:?}].")]
     TransactionEventsNotFound { digest: TransactionEventsDigest },
     #[error(
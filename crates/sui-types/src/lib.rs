@@ -52,6 +52,7 @@ pub mod storage;
 pub mod sui_serde;
 pub mod sui_system_state;
 pub mod temporary_store;
+pub mod validator_report;
 pub mod versioned;
 
 pub mod epoch_data;
@@ -5,7 +5,7 @@
 use std::collections::BTreeMap;
 
 use crate::base_types::{AuthorityName, ObjectRef, TransactionDigest};
-use crate::committee::StakeUnit;
+use crate::committee::{EpochId, StakeUnit};
 use crate::error::SuiError;
 use crate::messages::{QuorumDriverResponse, VerifiedTransaction};
 use serde::{Deserialize, Serialize};
@@ -25,6 +25,11 @@ pub enum QuorumDriverError {
     QuorumDriverInternalError(SuiError),
     #[error("Invalid user signature: {0:?}.")]
     InvalidUserSignature(SuiError),
+    /// The inputs of the transaction were checked by the node before its submission, and the
+    /// validators would reject it. The client should fix the inputs, e.g. refresh the versions of
+    /// its objects, before retrying.
+    #[error("Invalid transaction input: {0}.")]
+    InvalidTransactionInput(SuiError),
     #[error(
         "Failed to sign transaction by a quorum of validators because of locked objects: {:?}, retried a conflicting transaction {:?}, success: {:?}",
         conflicting_txes,
@@ -49,4 +54,8 @@ pub enum QuorumDriverError {
         overloaded_stake: StakeUnit,
         errors: Vec<(SuiError, Vec<AuthorityName>, StakeUnit)>,
     },
+    /// The validators dropped the transaction at the end of `epoch`, and it expires before the
+    /// next epoch so it could not be resubmitted. The client should submit a new transaction.
+    #[error("Transaction was dropped at the end of epoch {epoch}, and expired before the next one.")]
+    TransactionExpiredAtEpochEnd { epoch: EpochId },
 }
@@ -0,0 +1,180 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::base_types::SuiAddress;
+use crate::collection_types::Bag;
+use crate::sui_system_state::sui_system_state_inner_v1::{
+    metadata_error_message, ValidatorMetadataV1,
+};
+use crate::sui_system_state::sui_system_state_summary::{
+    SuiSystemStateSummary, SuiValidatorSummary,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+#[path = "../unit_tests/epoch_change_simulation_tests.rs"]
+mod epoch_change_simulation_tests;
+
+/// Why a validator would not be in the committee of the next epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidatorDeparture {
+    /// The validator requested to leave the committee.
+    RequestedRemoval,
+    /// The stake of the validator is below the very low stake threshold.
+    VeryLowStake,
+    /// The stake of the validator stayed below the low stake threshold for longer than the
+    /// grace period.
+    LowStake,
+}
+
+/// A validator of the committee of the epoch, or joining it, after the simulated epoch change.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedValidator {
+    pub sui_address: SuiAddress,
+    pub name: String,
+    /// The stake of the validator at the next epoch, without the rewards of this epoch.
+    pub next_epoch_stake: u64,
+    /// Whether the validator joins the committee at the epoch change.
+    pub joining: bool,
+    /// The number of epochs the validator will have been below the low stake threshold, if it
+    /// is below it.
+    pub low_stake_epochs: Option<u64>,
+    pub departure: Option<ValidatorDeparture>,
+    /// Why the metadata of the validator at the next epoch, with its staged changes, fails the
+    /// checks of the system state, if it does.
+    pub metadata_error: Option<String>,
+}
+
+/// The outcome of the transition to the next epoch, simulated from the system state: the
+/// validators are processed like `validator_set::advance_epoch` processes them, except that the
+/// rewards of the epoch are not distributed, which can only raise stakes.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochChangeSimulation {
+    pub next_epoch: u64,
+    pub validators: Vec<SimulatedValidator>,
+}
+
+impl EpochChangeSimulation {
+    /// Simulates the next epoch change. `pending_active_validators` are the validators which
+    /// requested to join the committee, held in the table of the system state.
+    pub fn new(
+        system_state: &SuiSystemStateSummary,
+        pending_active_validators: &[SuiValidatorSummary],
+    ) -> Self {
+        let at_risk: BTreeMap<_, _> = system_state.at_risk_validators.iter().copied().collect();
+        let active = system_state
+            .active_validators
+            .iter()
+            .enumerate()
+            .map(|(index, validator)| {
+                let removal = system_state.pending_removals.contains(&(index as u64));
+                (validator, false, removal)
+            });
+        let pending = pending_active_validators
+            .iter()
+            .map(|validator| (validator, true, false));
+        let validators = active
+            .chain(pending)
+            .map(|(validator, joining, removal)| {
+                let stake = validator.next_epoch_stake;
+                let low_stake_epochs = (stake < system_state.validator_low_stake_threshold)
+                    .then(|| at_risk.get(&validator.sui_address).copied().unwrap_or(0) + 1);
+                let departure = if removal {
+                    Some(ValidatorDeparture::RequestedRemoval)
+                } else if stake < system_state.validator_very_low_stake_threshold {
+                    Some(ValidatorDeparture::VeryLowStake)
+                } else if low_stake_epochs.map_or(false, |epochs| {
+                    epochs > system_state.validator_low_stake_grace_period
+                }) {
+                    Some(ValidatorDeparture::LowStake)
+                } else {
+                    None
+                };
+                SimulatedValidator {
+                    sui_address: validator.sui_address,
+                    name: validator.name.clone(),
+                    next_epoch_stake: stake,
+                    joining,
+                    low_stake_epochs,
+                    departure,
+                    metadata_error: next_epoch_metadata(validator)
+                        .verify()
+                        .err()
+                        .map(|code| metadata_error_message(code).to_string()),
+                }
+            })
+            .collect();
+        Self {
+            next_epoch: system_state.epoch + 1,
+            validators,
+        }
+    }
+
+    /// The validators in the committee of the next epoch.
+    pub fn committee(&self) -> impl Iterator<Item = &SimulatedValidator> {
+        self.validators
+            .iter()
+            .filter(|validator| validator.departure.is_none())
+    }
+
+    pub fn total_stake(&self) -> u64 {
+        self.committee()
+            .map(|validator| validator.next_epoch_stake)
+            .sum()
+    }
+
+    /// Whether any validator of the next committee fails the checks of its metadata.
+    pub fn has_failures(&self) -> bool {
+        self.committee()
+            .any(|validator| validator.metadata_error.is_some())
+    }
+}
+
+/// The metadata of the validator once its staged changes take effect at the epoch change.
+fn next_epoch_metadata(validator: &SuiValidatorSummary) -> ValidatorMetadataV1 {
+    let validator = validator.clone();
+    ValidatorMetadataV1 {
+        sui_address: validator.sui_address,
+        protocol_pubkey_bytes: validator
+            .next_epoch_protocol_pubkey_bytes
+            .unwrap_or(validator.protocol_pubkey_bytes),
+        network_pubkey_bytes: validator
+            .next_epoch_network_pubkey_bytes
+            .unwrap_or(validator.network_pubkey_bytes),
+        worker_pubkey_bytes: validator
+            .next_epoch_worker_pubkey_bytes
+            .unwrap_or(validator.worker_pubkey_bytes),
+        proof_of_possession_bytes: validator
+            .next_epoch_proof_of_possession
+            .unwrap_or(validator.proof_of_possession_bytes),
+        name: validator.name,
+        description: validator.description,
+        image_url: validator.image_url,
+        project_url: validator.project_url,
+        net_address: validator
+            .next_epoch_net_address
+            .unwrap_or(validator.net_address),
+        p2p_address: validator
+            .next_epoch_p2p_address
+            .unwrap_or(validator.p2p_address),
+        primary_address: validator
+            .next_epoch_primary_address
+            .unwrap_or(validator.primary_address),
+        worker_address: validator
+            .next_epoch_worker_address
+            .unwrap_or(validator.worker_address),
+        next_epoch_protocol_pubkey_bytes: None,
+        next_epoch_proof_of_possession: None,
+        next_epoch_network_pubkey_bytes: None,
+        next_epoch_worker_pubkey_bytes: None,
+        next_epoch_net_address: None,
+        next_epoch_p2p_address: None,
+        next_epoch_primary_address: None,
+        next_epoch_worker_address: None,
+        extra_fields: Bag::default(),
+    }
+}
@@ -9,7 +9,9 @@ use crate::committee::{Committee, StakeUnit};
 use crate::multiaddr::Multiaddr;
 use anemo::types::{PeerAffinity, PeerInfo};
 use anemo::PeerId;
-use narwhal_config::{Committee as NarwhalCommittee, CommitteeBuilder, WorkerCache, WorkerIndex};
+use narwhal_config::{
+    Committee as NarwhalCommittee, CommitteeBuilder, WorkerCache, WorkerIndex, WorkerSharding,
+};
 use serde::{Deserialize, Serialize};
 use sui_protocol_config::ProtocolVersion;
 use tracing::warn;
@@ -223,9 +225,12 @@ impl EpochStartSystemStateTrait for EpochStartSystemStateV1 {
                 (validator.protocol_pubkey.clone(), worker_index)
             })
             .collect();
+        // A single worker per validator, so all the transactions go to it. The sharding is
+        // decided by the protocol config when Narwhal is started.
         WorkerCache {
             workers,
             epoch: self.epoch,
+            sharding: WorkerSharding::Any,
         }
     }
 }
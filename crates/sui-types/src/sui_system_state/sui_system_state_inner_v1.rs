@@ -29,6 +29,21 @@ const E_METADATA_INVALID_P2P_ADDR: u64 = 5;
 const E_METADATA_INVALID_PRIMARY_ADDR: u64 = 6;
 const E_METADATA_INVALID_WORKER_ADDR: u64 = 7;
 
+/// Describes an error code returned by `ValidatorMetadataV1::verify`.
+pub fn metadata_error_message(code: u64) -> &'static str {
+    match code {
+        E_METADATA_INVALID_POP => "invalid proof of possession",
+        E_METADATA_INVALID_PUBKEY => "invalid protocol public key",
+        E_METADATA_INVALID_NET_PUBKEY => "invalid network public key",
+        E_METADATA_INVALID_WORKER_PUBKEY => "invalid worker public key",
+        E_METADATA_INVALID_NET_ADDR => "invalid network address",
+        E_METADATA_INVALID_P2P_ADDR => "invalid p2p address",
+        E_METADATA_INVALID_PRIMARY_ADDR => "invalid primary address",
+        E_METADATA_INVALID_WORKER_ADDR => "invalid worker address",
+        _ => "unknown error",
+    }
+}
+
 /// Rust version of the Move sui::sui_system::SystemParameters type
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct SystemParametersV1 {
@@ -0,0 +1,103 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::crypto::{
+    generate_proof_of_possession, get_authority_key_pair, get_key_pair, NetworkKeyPair,
+};
+use fastcrypto::traits::{KeyPair, ToFromBytes};
+
+fn validator(name: &str, next_epoch_stake: u64) -> SuiValidatorSummary {
+    let (sui_address, protocol_key) = get_authority_key_pair();
+    let (_, network_key): (_, NetworkKeyPair) = get_key_pair();
+    let (_, worker_key): (_, NetworkKeyPair) = get_key_pair();
+    SuiValidatorSummary {
+        sui_address,
+        protocol_pubkey_bytes: protocol_key.public().as_bytes().to_vec(),
+        network_pubkey_bytes: network_key.public().as_bytes().to_vec(),
+        worker_pubkey_bytes: worker_key.public().as_bytes().to_vec(),
+        proof_of_possession_bytes: generate_proof_of_possession(&protocol_key, sui_address)
+            .as_bytes()
+            .to_vec(),
+        name: name.to_string(),
+        net_address: "/ip4/127.0.0.1/tcp/8080/http".to_string(),
+        p2p_address: "/ip4/127.0.0.1/udp/8084".to_string(),
+        primary_address: "/ip4/127.0.0.1/udp/8081".to_string(),
+        worker_address: "/ip4/127.0.0.1/udp/8082".to_string(),
+        next_epoch_stake,
+        ..SuiValidatorSummary::default()
+    }
+}
+
+#[test]
+fn test_epoch_change_simulation() {
+    let mut leaving = validator("leaving", 100);
+    let mut bad_address = validator("bad-address", 100);
+    bad_address.next_epoch_p2p_address = Some("/ip4/127.0.0.1/tcp/8084/http".to_string());
+    leaving.next_epoch_worker_pubkey_bytes = Some(vec![0; 3]);
+    let at_risk = validator("at-risk", 40);
+    let out_of_grace = validator("out-of-grace", 40);
+    let system_state = SuiSystemStateSummary {
+        epoch: 7,
+        validator_low_stake_threshold: 50,
+        validator_very_low_stake_threshold: 20,
+        validator_low_stake_grace_period: 2,
+        at_risk_validators: vec![(at_risk.sui_address, 1), (out_of_grace.sui_address, 2)],
+        pending_removals: vec![1],
+        active_validators: vec![
+            validator("safe", 100),
+            leaving,
+            at_risk,
+            out_of_grace,
+            validator("very-low", 10),
+        ],
+        ..SuiSystemStateSummary::default()
+    };
+
+    let simulation = EpochChangeSimulation::new(&system_state, &[bad_address]);
+    assert_eq!(simulation.next_epoch, 8);
+    let outcomes: Vec<_> = simulation
+        .validators
+        .iter()
+        .map(|v| (v.name.as_str(), v.departure, v.low_stake_epochs, v.joining))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("safe", None, None, false),
+            (
+                "leaving",
+                Some(ValidatorDeparture::RequestedRemoval),
+                None,
+                false
+            ),
+            ("at-risk", None, Some(2), false),
+            (
+                "out-of-grace",
+                Some(ValidatorDeparture::LowStake),
+                Some(3),
+                false
+            ),
+            (
+                "very-low",
+                Some(ValidatorDeparture::VeryLowStake),
+                Some(1),
+                false
+            ),
+            ("bad-address", None, None, true),
+        ]
+    );
+    assert_eq!(simulation.total_stake(), 240);
+
+    // The staged metadata is checked, and only matters for the validators staying.
+    assert_eq!(
+        simulation.validators[1].metadata_error.as_deref(),
+        Some("invalid worker public key")
+    );
+    assert_eq!(
+        simulation.validators[5].metadata_error.as_deref(),
+        Some("invalid p2p address")
+    );
+    assert!(simulation.validators[0].metadata_error.is_none());
+    assert!(simulation.has_failures());
+}
@@ -0,0 +1,128 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The operational reports of the validators, signed with their protocol key so that delegators
+//! and monitoring services can verify them against the committee of their epoch.
+
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::IntentScope;
+
+use crate::base_types::AuthorityName;
+use crate::committee::{Committee, EpochId};
+use crate::crypto::{default_hash, AuthoritySignInfo};
+use crate::digests::Digest;
+use crate::error::{SuiError, SuiResult};
+use crate::message_envelope::{Envelope, Message};
+use crate::messages_checkpoint::CheckpointSequenceNumber;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorReport {
+    pub epoch: EpochId,
+    pub authority: AuthorityName,
+    /// The version of the binary of the validator.
+    pub version: String,
+    /// When the report was made, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub uptime_secs: u64,
+    /// The rounds committed by consensus per second, since the previous report of the validator.
+    pub commit_rate: f64,
+    pub last_committed_round: u64,
+    pub highest_executed_checkpoint: Option<CheckpointSequenceNumber>,
+    /// The peers of the validator in consensus it is connected to, out of `known_peer_count`.
+    pub connected_peer_count: u64,
+    pub known_peer_count: u64,
+}
+
+impl Message for ValidatorReport {
+    type DigestType = Digest;
+    const SCOPE: IntentScope = IntentScope::ValidatorReport;
+
+    fn digest(&self) -> Self::DigestType {
+        Digest::new(default_hash(self))
+    }
+
+    fn verify(&self, sig_epoch: Option<EpochId>) -> SuiResult {
+        // Reports signed with the key of another epoch are not valid.
+        if let Some(sig_epoch) = sig_epoch {
+            fp_ensure!(
+                self.epoch == sig_epoch,
+                SuiError::from("Epoch in the report doesn't match with the signature")
+            );
+        }
+        Ok(())
+    }
+}
+
+pub type SignedValidatorReport = Envelope<ValidatorReport, AuthoritySignInfo>;
+
+impl SignedValidatorReport {
+    /// Verifies that the report is signed by the validator it is about, as a member of the
+    /// committee.
+    pub fn verify_report(&self, committee: &Committee) -> SuiResult {
+        fp_ensure!(
+            self.auth_sig().authority == self.data().authority,
+            SuiError::from("The report is not signed by the validator it is about")
+        );
+        self.verify_signature(committee)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValidatorReportRequest {
+    // This is needed to make gRPC happy.
+    pub _unused: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::traits::KeyPair;
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::utils::make_committee_key;
+
+    fn report(epoch: EpochId, authority: AuthorityName) -> ValidatorReport {
+        ValidatorReport {
+            epoch,
+            authority,
+            version: "0.1.0".to_string(),
+            timestamp_ms: 1_000,
+            uptime_secs: 60,
+            commit_rate: 1.5,
+            last_committed_round: 90,
+            highest_executed_checkpoint: Some(10),
+            connected_peer_count: 3,
+            known_peer_count: 3,
+        }
+    }
+
+    #[test]
+    fn test_signed_report() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let (keys, committee) = make_committee_key(&mut rng);
+        let (_, committee2) = make_committee_key(&mut rng);
+
+        let name = keys[0].public().into();
+        let signed = SignedValidatorReport::new(
+            committee.epoch,
+            report(committee.epoch, name),
+            &keys[0],
+            name,
+        );
+        signed.verify_report(&committee).expect("signature ok");
+
+        // Fails when not signed by a member of the committee.
+        assert!(signed.verify_report(&committee2).is_err());
+
+        // Fails when signed by another validator than the one reported on.
+        let other = keys[1].public().into();
+        let signed = SignedValidatorReport::new(
+            committee.epoch,
+            report(committee.epoch, name),
+            &keys[1],
+            other,
+        );
+        assert!(signed.verify_report(&committee).is_err());
+    }
+}
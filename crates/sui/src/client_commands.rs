@@ -4,7 +4,7 @@
 use core::fmt;
 use std::sync::Arc;
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     fmt::{Debug, Display, Formatter, Write},
     path::{Path, PathBuf},
     time::Instant,
@@ -37,9 +37,9 @@ use sui_framework_build::compiled_package::{
 };
 use sui_json::SuiJsonValue;
 use sui_json_rpc_types::{
-    DynamicFieldPage, SuiData, SuiObjectData, SuiObjectResponse, SuiObjectResponseQuery,
-    SuiRawData, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
-    SuiTransactionBlockResponseOptions,
+    DryRunTransactionBlockResponse, DynamicFieldPage, SuiData, SuiObjectData, SuiObjectResponse,
+    SuiObjectResponseQuery, SuiParsedData, SuiRawData, SuiTransactionBlockEffectsAPI,
+    SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
 };
 use sui_json_rpc_types::{SuiExecutionStatus, SuiObjectDataOptions};
 use sui_keys::keystore::AccountKeystore;
@@ -51,7 +51,7 @@ use sui_types::signature::GenericSignature;
 use sui_types::{
     base_types::{ObjectID, ObjectRef, SuiAddress},
     gas_coin::GasCoin,
-    messages::{Transaction, VerifiedTransaction},
+    messages::{Transaction, TransactionData, VerifiedTransaction},
     object::Owner,
     parse_sui_type_tag,
 };
@@ -465,6 +465,16 @@ pub enum SuiClientCommands {
         #[clap(long)]
         signatures: Vec<String>,
     },
+
+    /// Dry run a transaction without executing it, printing the content of the objects it would
+    /// create, mutate or delete before and after it, and its gas cost. This is useful to check a
+    /// transaction before signing it.
+    #[clap(name = "dry-run")]
+    DryRun {
+        /// BCS serialized transaction data bytes without its type tag, as base-64 encoded string.
+        #[clap(long)]
+        tx_bytes: String,
+    },
 }
 
 impl SuiClientCommands {
@@ -1039,6 +1049,38 @@ impl SuiClientCommands {
                 let response = context.execute_transaction_block(verified).await?;
                 SuiClientCommandResult::ExecuteSignedTx(response)
             }
+            SuiClientCommands::DryRun { tx_bytes } => {
+                let data: TransactionData = bcs::from_bytes(
+                    &Base64::try_from(tx_bytes)
+                        .map_err(|e| anyhow!(e))?
+                        .to_vec()
+                        .map_err(|e| anyhow!(e))?,
+                )?;
+
+                let client = context.get_client().await?;
+                let response = client.read_api().dry_run_transaction_block(data).await?;
+
+                // The objects modified by the transaction, as of the version it would modify. The
+                // gas coin made up by the full node when none is given has no previous version.
+                let mut objects_before = Vec::new();
+                for (object_id, version) in response.effects.modified_at_versions() {
+                    let object = client
+                        .read_api()
+                        .try_get_parsed_past_object(
+                            object_id,
+                            version,
+                            SuiObjectDataOptions::full_content(),
+                        )
+                        .await?;
+                    if let Ok(object) = object.into_object() {
+                        objects_before.push(object);
+                    }
+                }
+                SuiClientCommandResult::DryRun(DryRunResponse {
+                    response,
+                    objects_before,
+                })
+            }
             SuiClientCommands::NewEnv { alias, rpc, ws } => {
                 if context.config.envs.iter().any(|env| env.alias == alias) {
                     return Err(anyhow!(
@@ -1527,6 +1569,9 @@ impl Display for SuiClientCommandResult {
             SuiClientCommandResult::ExecuteSignedTx(response) => {
                 write!(writer, "{}", write_transaction_response(response)?)?;
             }
+            SuiClientCommandResult::DryRun(response) => {
+                write!(writer, "{}", response)?;
+            }
             SuiClientCommandResult::SerializeTransferSui(data) => {
                 writeln!(writer, "Raw tx_bytes to execute: {}", data)?;
             }
@@ -1729,9 +1774,167 @@ pub enum SuiClientCommandResult {
     SerializeTransferSui(String),
     SerializePublish(String),
     ExecuteSignedTx(SuiTransactionBlockResponse),
+    DryRun(DryRunResponse),
     NewEnv(SuiEnv),
 }
 
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunResponse {
+    pub response: DryRunTransactionBlockResponse,
+    /// The content of the objects modified by the transaction, before it.
+    pub objects_before: Vec<SuiObjectData>,
+}
+
+impl Display for DryRunResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut writer = String::new();
+        let effects = &self.response.effects;
+        writeln!(writer, "{}", "----- Dry Run Status ----".bold())?;
+        writeln!(writer, "{:?}", effects.status())?;
+
+        let before: BTreeMap<_, _> = self
+            .objects_before
+            .iter()
+            .map(|object| (object.object_id, object))
+            .collect();
+        let after: BTreeMap<_, _> = self
+            .response
+            .written_objects
+            .iter()
+            .map(|object| (object.object_id, object))
+            .collect();
+
+        writeln!(writer, "{}", "----- Created Objects ----".bold())?;
+        for (object_id, object) in &after {
+            if !before.contains_key(object_id) {
+                write_object_header(&mut writer, object, None)?;
+                write_fields_diff(&mut writer, &BTreeMap::new(), &object_fields(object))?;
+            }
+        }
+
+        writeln!(writer, "{}", "----- Mutated Objects ----".bold())?;
+        for (object_id, object) in &after {
+            if let Some(previous) = before.get(object_id) {
+                write_object_header(&mut writer, object, Some(previous))?;
+                write_fields_diff(
+                    &mut writer,
+                    &object_fields(previous),
+                    &object_fields(object),
+                )?;
+            }
+        }
+
+        // Wrapped objects are no longer in the store either, but can be unwrapped later.
+        writeln!(writer, "{}", "----- Deleted or Wrapped Objects ----".bold())?;
+        for (object_id, object) in &before {
+            if !after.contains_key(object_id) {
+                write_object_header(&mut writer, object, None)?;
+                write_fields_diff(&mut writer, &object_fields(object), &BTreeMap::new())?;
+            }
+        }
+
+        let gas = effects.gas_cost_summary();
+        let computation_cost = <u64>::from(gas.computation_cost);
+        let storage_cost = <u64>::from(gas.storage_cost);
+        let storage_rebate = <u64>::from(gas.storage_rebate);
+        writeln!(writer, "{}", "----- Gas ----".bold())?;
+        writeln!(writer, "Computation cost: {computation_cost}")?;
+        writeln!(writer, "Storage cost: {storage_cost}")?;
+        writeln!(writer, "Storage rebate: {storage_rebate}")?;
+        writeln!(
+            writer,
+            "Non-refundable storage fee: {}",
+            <u64>::from(gas.non_refundable_storage_fee)
+        )?;
+        writeln!(
+            writer,
+            "Net gas cost: {}",
+            (computation_cost as i128) + (storage_cost as i128) - (storage_rebate as i128)
+        )?;
+        write!(f, "{}", writer)
+    }
+}
+
+fn write_object_header(
+    writer: &mut String,
+    object: &SuiObjectData,
+    previous: Option<&SuiObjectData>,
+) -> std::fmt::Result {
+    let type_ = object
+        .type_
+        .as_ref()
+        .map_or_else(|| "Unknown Type".to_string(), |type_| type_.to_string());
+    match previous {
+        Some(previous) => writeln!(
+            writer,
+            "{type_} ({}[{} -> {}])",
+            object.object_id, previous.version, object.version
+        )?,
+        None => writeln!(writer, "{type_} ({}[{}])", object.object_id, object.version)?,
+    }
+    match (previous.and_then(|previous| previous.owner), object.owner) {
+        (Some(previous), Some(owner)) if previous != owner => {
+            writeln!(writer, "  Owner: {previous} -> {owner}")
+        }
+        (_, Some(owner)) => writeln!(writer, "  Owner: {owner}"),
+        (_, None) => Ok(()),
+    }
+}
+
+/// Writes the fields that differ between the two versions of an object, an object without
+/// version being the empty map.
+fn write_fields_diff(
+    writer: &mut String,
+    before: &BTreeMap<String, Value>,
+    after: &BTreeMap<String, Value>,
+) -> std::fmt::Result {
+    let paths: BTreeSet<_> = before.keys().chain(after.keys()).collect();
+    for path in paths {
+        match (before.get(path), after.get(path)) {
+            (Some(old), Some(new)) if old == new => {}
+            (old, new) => {
+                if let Some(old) = old {
+                    writeln!(writer, "{}", format!("  - {path}: {old}").red())?;
+                }
+                if let Some(new) = new {
+                    writeln!(writer, "{}", format!("  + {path}: {new}").green())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the decoded fields of a Move object by their path, e.g. `balance.value`.
+fn object_fields(object: &SuiObjectData) -> BTreeMap<String, Value> {
+    let mut fields = BTreeMap::new();
+    if let Some(SuiParsedData::MoveObject(move_object)) = &object.content {
+        if let Ok(value) = serde_json::to_value(&move_object.fields) {
+            flatten_fields(String::new(), value, &mut fields);
+        }
+    }
+    fields
+}
+
+fn flatten_fields(path: String, value: Value, fields: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let path = if path.is_empty() {
+                    key
+                } else {
+                    format!("{path}.{key}")
+                };
+                flatten_fields(path, value, fields);
+            }
+        }
+        value => {
+            fields.insert(path, value);
+        }
+    }
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct SwitchResponse {
     /// Active address
@@ -6,6 +6,7 @@ use clap::*;
 use fastcrypto::encoding::{decode_bytes_hex, Base64, Encoding};
 use fastcrypto::hash::HashFunction;
 use fastcrypto::traits::KeyPair;
+use serde::{Deserialize, Serialize};
 use shared_crypto::intent::{Intent, IntentMessage};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -17,8 +18,8 @@ use sui_keys::keypair_file::{
 use sui_keys::keystore::{AccountKeystore, Keystore};
 use sui_types::base_types::SuiAddress;
 use sui_types::crypto::{get_authority_key_pair, EncodeDecodeBase64, SignatureScheme, SuiKeyPair};
-use sui_types::crypto::{DefaultHash, PublicKey, Signature};
-use sui_types::messages::TransactionData;
+use sui_types::crypto::{DefaultHash, PublicKey, Signature, SuiSignature};
+use sui_types::messages::{TransactionData, TransactionDataAPI};
 use sui_types::multisig::{MultiSig, MultiSigPublicKey, ThresholdUnit, WeightUnit};
 use sui_types::signature::GenericSignature;
 use tracing::info;
@@ -104,6 +105,149 @@ pub enum KeyToolCommand {
         #[clap(long)]
         threshold: ThresholdUnit,
     },
+
+    /// Writes a transaction sent from a MultiSig address to a file, for the parties of the
+    /// MultiSig to sign it offline with `keytool multi-sig-sign-tx`. The transaction is the
+    /// Base64 encoded BCS bytes of its TransactionData, and its sender must be the MultiSig
+    /// address of the public keys, weights and threshold.
+    MultiSigNewTx {
+        #[clap(long)]
+        tx_bytes: String,
+        #[clap(long, multiple_occurrences = false, multiple_values = true)]
+        pks: Vec<PublicKey>,
+        #[clap(long, multiple_occurrences = false, multiple_values = true)]
+        weights: Vec<WeightUnit>,
+        #[clap(long)]
+        threshold: ThresholdUnit,
+        #[clap(long)]
+        file: PathBuf,
+    },
+
+    /// Adds signatures to the partially signed MultiSig transaction of the file, with the key of
+    /// the given address in sui.keystore, or with every key of sui.keystore which is a party of
+    /// the MultiSig and has not signed yet.
+    MultiSigSignTx {
+        #[clap(long)]
+        file: PathBuf,
+        #[clap(long, parse(try_from_str = decode_bytes_hex))]
+        address: Option<SuiAddress>,
+    },
+
+    /// Prints the parties of the partially signed MultiSig transaction of the file, which of
+    /// them signed, and whether the signatures reach the threshold.
+    MultiSigInspectTx {
+        #[clap(long)]
+        file: PathBuf,
+    },
+
+    /// Combines the signatures of the partially signed MultiSig transaction of the file into a
+    /// MultiSig, once they reach the threshold. The transaction bytes and the MultiSig can be
+    /// passed to `sui client execute-signed-tx`.
+    MultiSigCombineTx {
+        #[clap(long)]
+        file: PathBuf,
+    },
+}
+
+/// A transaction sent from a MultiSig address, with the signatures of its parties collected so
+/// far. It is written to a file for the parties to sign it without sharing their keys.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartiallySignedMultiSigTx {
+    /// Base64 encoded BCS bytes of the TransactionData.
+    pub tx_bytes: String,
+    pub multisig_pk: MultiSigPublicKey,
+    /// The signatures `flag || sig || pk` of the parties, in the order they signed.
+    pub sigs: Vec<Signature>,
+}
+
+impl PartiallySignedMultiSigTx {
+    pub fn new(tx_bytes: String, multisig_pk: MultiSigPublicKey) -> Result<Self, anyhow::Error> {
+        let tx = Self {
+            tx_bytes,
+            multisig_pk,
+            sigs: vec![],
+        };
+        let address: SuiAddress = tx.multisig_pk.clone().into();
+        let sender = tx.tx_data()?.sender();
+        if sender != address {
+            return Err(anyhow!(
+                "The transaction is sent from {sender}, not from the MultiSig address {address}"
+            ));
+        }
+        Ok(tx)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Cannot read the transaction at {:?}: {e}", path))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), anyhow::Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| anyhow!("Cannot write the transaction to {:?}: {e}", path))
+    }
+
+    pub fn tx_data(&self) -> Result<TransactionData, anyhow::Error> {
+        bcs::from_bytes(
+            &Base64::decode(&self.tx_bytes)
+                .map_err(|e| anyhow!("Cannot deserialize tx_bytes as TransactionData {:?}", e))?,
+        )
+        .map_err(|e| anyhow!("Cannot deserialize tx_bytes as TransactionData {:?}", e))
+    }
+
+    /// The public keys of the parties which signed.
+    pub fn signers(&self) -> Result<Vec<PublicKey>, anyhow::Error> {
+        self.sigs
+            .iter()
+            .map(|sig| Ok(sig.to_public_key()?))
+            .collect()
+    }
+
+    /// The total weight of the parties which signed.
+    pub fn signed_weight(&self) -> Result<ThresholdUnit, anyhow::Error> {
+        let signers = self.signers()?;
+        Ok(self
+            .multisig_pk
+            .pubkeys()
+            .iter()
+            .filter(|(pk, _)| signers.contains(pk))
+            .map(|(_, weight)| *weight as ThresholdUnit)
+            .sum())
+    }
+
+    /// Adds the signature of a party of the MultiSig which has not signed yet, once verified
+    /// against the transaction.
+    pub fn add_signature(&mut self, sig: Signature) -> Result<(), anyhow::Error> {
+        let pk = sig.to_public_key()?;
+        let signer: SuiAddress = (&pk).into();
+        if self.multisig_pk.get_index(pk.clone()).is_none() {
+            return Err(anyhow!("{signer} is not a party of the MultiSig"));
+        }
+        if self.signers()?.contains(&pk) {
+            return Err(anyhow!("{signer} already signed the transaction"));
+        }
+        let intent_msg = IntentMessage::new(Intent::default(), self.tx_data()?);
+        sig.verify_secure(&intent_msg, signer)?;
+        self.sigs.push(sig);
+        Ok(())
+    }
+
+    /// Combines the signatures into a MultiSig, if they reach the threshold.
+    pub fn combine(&self) -> Result<MultiSig, anyhow::Error> {
+        let weight = self.signed_weight()?;
+        let threshold = *self.multisig_pk.threshold();
+        if weight < threshold {
+            return Err(anyhow!(
+                "The signatures weigh {weight}, below the threshold of {threshold}"
+            ));
+        }
+        Ok(MultiSig::combine(
+            self.sigs.clone(),
+            self.multisig_pk.clone(),
+        )?)
+    }
 }
 
 impl KeyToolCommand {
@@ -272,6 +416,84 @@ impl KeyToolCommand {
                 println!("MultiSig parsed: {:?}", generic_sig);
                 println!("MultiSig serialized: {:?}", generic_sig.encode_base64());
             }
+            KeyToolCommand::MultiSigNewTx {
+                tx_bytes,
+                pks,
+                weights,
+                threshold,
+                file,
+            } => {
+                let multisig_pk = MultiSigPublicKey::new(pks, weights, threshold)?;
+                let tx = PartiallySignedMultiSigTx::new(tx_bytes, multisig_pk)?;
+                tx.write(&file)?;
+                println!(
+                    "Partially signed MultiSig transaction written to {:?}",
+                    file
+                );
+            }
+            KeyToolCommand::MultiSigSignTx { file, address } => {
+                let mut tx = PartiallySignedMultiSigTx::read(&file)?;
+                let tx_data = tx.tx_data()?;
+                let signers = tx.signers()?;
+                let addresses: Vec<SuiAddress> = match address {
+                    Some(address) => vec![address],
+                    None => tx
+                        .multisig_pk
+                        .pubkeys()
+                        .iter()
+                        .filter(|(pk, _)| !signers.contains(pk))
+                        .map(|(pk, _)| SuiAddress::from(pk))
+                        .filter(|address| keystore.addresses().contains(address))
+                        .collect(),
+                };
+                if addresses.is_empty() {
+                    return Err(anyhow!("No key of the keystore can sign for the MultiSig"));
+                }
+                for address in addresses {
+                    let sig = keystore.sign_secure(&address, &tx_data, Intent::default())?;
+                    tx.add_signature(sig)?;
+                    println!("Signed by {address}");
+                }
+                tx.write(&file)?;
+                println!(
+                    "Signed weight: {} of threshold {}",
+                    tx.signed_weight()?,
+                    tx.multisig_pk.threshold()
+                );
+            }
+            KeyToolCommand::MultiSigInspectTx { file } => {
+                let tx = PartiallySignedMultiSigTx::read(&file)?;
+                let address: SuiAddress = tx.multisig_pk.clone().into();
+                let signers = tx.signers()?;
+                println!("MultiSig address: {address}");
+                println!("Raw tx_bytes to execute: {}", tx.tx_bytes);
+                println!(
+                    " {0: ^42} | {1: ^50} | {2: ^6} | {3: ^6}",
+                    "Sui Address", "Public Key (Base64)", "Weight", "Signed"
+                );
+                println!("{}", ["-"; 100].join(""));
+                for (pk, w) in tx.multisig_pk.pubkeys() {
+                    println!(
+                        " {0: ^42} | {1: ^45} | {2: ^6} | {3: ^6}",
+                        Into::<SuiAddress>::into(pk),
+                        pk.encode_base64(),
+                        w,
+                        signers.contains(pk)
+                    );
+                }
+                let weight = tx.signed_weight()?;
+                let threshold = *tx.multisig_pk.threshold();
+                println!("Signed weight: {weight} of threshold {threshold}");
+                println!("Ready to combine: {}", weight >= threshold);
+            }
+            KeyToolCommand::MultiSigCombineTx { file } => {
+                let tx = PartiallySignedMultiSigTx::read(&file)?;
+                let address: SuiAddress = tx.multisig_pk.clone().into();
+                let generic_sig: GenericSignature = tx.combine()?.into();
+                println!("MultiSig address: {address}");
+                println!("Raw tx_bytes to execute: {}", tx.tx_bytes);
+                println!("MultiSig serialized: {:?}", generic_sig.encode_base64());
+            }
         }
 
         Ok(())
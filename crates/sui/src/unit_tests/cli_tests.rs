@@ -1831,6 +1831,70 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_dry_run() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await?;
+    let address = test_cluster.get_address_0();
+    let address1 = test_cluster.get_address_1();
+    let context = &mut test_cluster.wallet;
+    let client = context.get_client().await?;
+    let coin = client
+        .coin_read_api()
+        .get_coins(address, None, None, None)
+        .await?
+        .data[0]
+        .coin_object_id;
+
+    let result = SuiClientCommands::SerializeTransferSui {
+        to: address1,
+        sui_coin_object_id: coin,
+        gas_budget: 1000,
+        amount: Some(1),
+    }
+    .execute(context)
+    .await?;
+    let SuiClientCommandResult::SerializeTransferSui(tx_bytes) = result else {
+        panic!()
+    };
+
+    let result = SuiClientCommands::DryRun { tx_bytes }
+        .execute(context)
+        .await?;
+    let SuiClientCommandResult::DryRun(response) = result else {
+        panic!()
+    };
+    assert!(response.response.effects.status().is_ok());
+
+    // The coin is mutated, and a new coin is created for the recipient.
+    let before = response
+        .objects_before
+        .iter()
+        .find(|o| o.object_id == coin)
+        .unwrap();
+    let after = response
+        .response
+        .written_objects
+        .iter()
+        .find(|o| o.object_id == coin)
+        .unwrap();
+    assert!(after.version > before.version);
+    assert!(response
+        .response
+        .written_objects
+        .iter()
+        .any(|o| o.owner == Some(Owner::AddressOwner(address1))));
+
+    // Nothing was executed.
+    let object = client
+        .read_api()
+        .get_object_with_options(coin, SuiObjectDataOptions::new())
+        .await?
+        .into_object()?;
+    assert_eq!(object.version, before.version);
+    assert!(format!("{response}").contains("Mutated Objects"));
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_stake_with_none_amount() -> Result<(), anyhow::Error> {
     let mut test_cluster = TestClusterBuilder::new().build().await?;
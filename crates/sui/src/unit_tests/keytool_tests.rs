@@ -8,6 +8,7 @@ use crate::keytool::read_keypair_from_file;
 
 use super::write_keypair_to_file;
 use super::KeyToolCommand;
+use super::PartiallySignedMultiSigTx;
 use fastcrypto::encoding::Base64;
 use fastcrypto::encoding::Encoding;
 use rand::rngs::StdRng;
@@ -30,7 +31,10 @@ use sui_types::crypto::Signature;
 use sui_types::crypto::SignatureScheme;
 use sui_types::crypto::SuiKeyPair;
 use sui_types::crypto::SuiSignatureInner;
+use sui_types::messages::Transaction;
 use sui_types::messages::TransactionData;
+use sui_types::multisig::MultiSigPublicKey;
+use sui_types::signature::GenericSignature;
 use tempfile::TempDir;
 
 const TEST_MNEMONIC: &str = "result crisp session latin must fruit genuine question prevent start coconut brave speak student dismiss";
@@ -380,3 +384,86 @@ fn test_sign_command() -> Result<(), anyhow::Error> {
     .execute(&mut keystore)?;
     Ok(())
 }
+
+#[test]
+fn test_multisig_tx_offline_signing() -> Result<(), anyhow::Error> {
+    let mut keystore = Keystore::from(InMemKeystore::new(2));
+    keystore.add_key(SuiKeyPair::Secp256k1(get_key_pair().1))?;
+    let pks = keystore.keys();
+    let multisig_pk = MultiSigPublicKey::new(pks.clone(), vec![1, 1, 2], 3)?;
+    let multisig_address: SuiAddress = multisig_pk.into();
+    let addresses: Vec<SuiAddress> = pks.iter().map(SuiAddress::from).collect();
+
+    let gas = (
+        ObjectID::random(),
+        SequenceNumber::new(),
+        ObjectDigest::random(),
+    );
+    let tx_data = TransactionData::new_pay_sui_with_dummy_gas_price(
+        multisig_address,
+        vec![gas],
+        vec![SuiAddress::random_for_testing_only()],
+        vec![10000],
+        gas,
+        1000,
+    )
+    .unwrap();
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("multisig_tx.json");
+
+    // The transaction must be sent from the MultiSig address.
+    assert!(KeyToolCommand::MultiSigNewTx {
+        tx_bytes: Base64::encode(bcs::to_bytes(&tx_data)?),
+        pks: pks[1..].to_vec(),
+        weights: vec![1, 2],
+        threshold: 3,
+        file: file.clone(),
+    }
+    .execute(&mut keystore)
+    .is_err());
+    KeyToolCommand::MultiSigNewTx {
+        tx_bytes: Base64::encode(bcs::to_bytes(&tx_data)?),
+        pks,
+        weights: vec![1, 1, 2],
+        threshold: 3,
+        file: file.clone(),
+    }
+    .execute(&mut keystore)?;
+
+    // Below the threshold, the signatures cannot be combined.
+    KeyToolCommand::MultiSigSignTx {
+        file: file.clone(),
+        address: Some(addresses[0]),
+    }
+    .execute(&mut keystore)?;
+    assert!(KeyToolCommand::MultiSigSignTx {
+        file: file.clone(),
+        address: Some(addresses[0]),
+    }
+    .execute(&mut keystore)
+    .is_err());
+    assert_eq!(PartiallySignedMultiSigTx::read(&file)?.signed_weight()?, 1);
+    assert!(KeyToolCommand::MultiSigCombineTx { file: file.clone() }
+        .execute(&mut keystore)
+        .is_err());
+
+    // The remaining parties sign, with keys of mixed schemes.
+    KeyToolCommand::MultiSigSignTx {
+        file: file.clone(),
+        address: None,
+    }
+    .execute(&mut keystore)?;
+    let tx = PartiallySignedMultiSigTx::read(&file)?;
+    assert_eq!(tx.sigs.len(), 3);
+    assert_eq!(tx.signed_weight()?, 4);
+    KeyToolCommand::MultiSigInspectTx { file: file.clone() }.execute(&mut keystore)?;
+    KeyToolCommand::MultiSigCombineTx { file }.execute(&mut keystore)?;
+
+    let multisig: GenericSignature = tx.combine()?.into();
+    assert!(
+        Transaction::from_generic_sig_data(tx_data, Intent::default(), vec![multisig])
+            .verify()
+            .is_ok()
+    );
+    Ok(())
+}
@@ -18,6 +18,7 @@ use sui_types::{
     multiaddr::Multiaddr,
     object::Owner,
     sui_system_state::{
+        epoch_change_simulation::{EpochChangeSimulation, ValidatorDeparture},
         sui_system_state_inner_v1::{UnverifiedValidatorOperationCapV1, ValidatorV1},
         sui_system_state_summary::{SuiSystemStateSummary, SuiValidatorSummary},
     },
@@ -135,6 +136,10 @@ pub enum SuiValidatorCommand {
         #[clap(name = "gas-budget", long)]
         gas_budget: Option<u64>,
     },
+    /// Simulate the next epoch change from the latest system state: the committee of the next
+    /// epoch, the validators which would leave it and the metadata failing its checks.
+    #[clap(name = "simulate-epoch-change")]
+    SimulateEpochChange,
 }
 
 #[derive(Serialize)]
@@ -148,6 +153,7 @@ pub enum SuiValidatorCommandResponse {
     UpdateMetadata(SuiTransactionBlockResponse),
     UpdateGasPrice(SuiTransactionBlockResponse),
     ReportValidator(SuiTransactionBlockResponse),
+    SimulateEpochChange(EpochChangeSimulation),
 }
 
 fn make_key_files(
@@ -376,6 +382,24 @@ impl SuiValidatorCommand {
                 .await?;
                 SuiValidatorCommandResponse::ReportValidator(resp)
             }
+
+            SuiValidatorCommand::SimulateEpochChange => {
+                let sui_client = context.get_client().await?;
+                let system_state = sui_client
+                    .governance_api()
+                    .get_latest_sui_system_state()
+                    .await?;
+                let pending_active_validators: Vec<_> =
+                    get_pending_candidates(&sui_client, system_state.pending_active_validators_id)
+                        .await?
+                        .into_iter()
+                        .map(|validator| validator.into_sui_validator_summary())
+                        .collect();
+                SuiValidatorCommandResponse::SimulateEpochChange(EpochChangeSimulation::new(
+                    &system_state,
+                    &pending_active_validators,
+                ))
+            }
         });
         ret
     }
@@ -585,6 +609,9 @@ impl Display for SuiValidatorCommandResponse {
             SuiValidatorCommandResponse::ReportValidator(response) => {
                 write!(writer, "{}", write_transaction_response(response)?)?;
             }
+            SuiValidatorCommandResponse::SimulateEpochChange(simulation) => {
+                write!(writer, "{}", write_epoch_change_simulation(simulation)?)?;
+            }
         }
         write!(f, "{}", writer.trim_end_matches('\n'))
     }
@@ -611,6 +638,56 @@ pub fn write_transaction_response(
     Ok(writer)
 }
 
+fn write_epoch_change_simulation(simulation: &EpochChangeSimulation) -> Result<String, fmt::Error> {
+    let mut writer = String::new();
+    writeln!(
+        writer,
+        "----- Committee of epoch {} ----",
+        simulation.next_epoch
+    )?;
+    for validator in simulation.committee() {
+        write!(
+            writer,
+            "{} ({}): stake {}",
+            validator.name, validator.sui_address, validator.next_epoch_stake
+        )?;
+        if validator.joining {
+            write!(writer, ", joining")?;
+        }
+        if let Some(epochs) = validator.low_stake_epochs {
+            write!(
+                writer,
+                "{}",
+                format!(", at risk for {epochs} epochs").yellow()
+            )?;
+        }
+        if let Some(error) = &validator.metadata_error {
+            write!(writer, "{}", format!(", {error}").red())?;
+        }
+        writeln!(writer)?;
+    }
+    writeln!(writer, "Total stake: {}", simulation.total_stake())?;
+
+    writeln!(writer, "\n----- Leaving the committee ----")?;
+    for validator in &simulation.validators {
+        if let Some(departure) = validator.departure {
+            let reason = match departure {
+                ValidatorDeparture::RequestedRemoval => "requested removal",
+                ValidatorDeparture::VeryLowStake => "stake below the very low stake threshold",
+                ValidatorDeparture::LowStake => "stake below the low stake threshold for too long",
+            };
+            writeln!(
+                writer,
+                "{} ({}): {}",
+                validator.name,
+                validator.sui_address,
+                reason.red()
+            )?;
+        }
+    }
+    Ok(writer)
+}
+
 impl Debug for SuiValidatorCommandResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let string = serde_json::to_string_pretty(self);
@@ -713,6 +790,19 @@ async fn get_pending_candidate_summary(
     sui_client: &SuiClient,
     pending_active_validators_id: ObjectID,
 ) -> anyhow::Result<Option<ValidatorV1>> {
+    Ok(
+        get_pending_candidates(sui_client, pending_active_validators_id)
+            .await?
+            .into_iter()
+            .find(|val| val.verified_metadata().sui_address == validator_address),
+    )
+}
+
+/// The validators which requested to join the committee at the next epoch.
+async fn get_pending_candidates(
+    sui_client: &SuiClient,
+    pending_active_validators_id: ObjectID,
+) -> anyhow::Result<Vec<ValidatorV1>> {
     let pending_validators = sui_client
         .read_api()
         .get_dynamic_fields(pending_active_validators_id, None, None)
@@ -728,6 +818,7 @@ async fn get_pending_candidate_summary(
             SuiObjectDataOptions::default().with_bcs(),
         )
         .await?;
+    let mut candidates = vec![];
     for resp in resps {
         // We always expect an objectId from the response as one of data/error should be included.
         let object_id = resp.object_id()?;
@@ -744,11 +835,9 @@ async fn get_pending_candidate_summary(
                 e,
             )
         })?;
-        if val.verified_metadata().sui_address == validator_address {
-            return Ok(Some(val));
-        }
+        candidates.push(val);
     }
-    Ok(None)
+    Ok(candidates)
 }
 
 #[derive(Subcommand)]
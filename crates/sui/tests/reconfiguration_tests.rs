@@ -3,6 +3,7 @@
 
 use futures::future::join_all;
 use move_core_types::ident_str;
+use move_core_types::identifier::Identifier;
 use mysten_metrics::RegistryService;
 use prometheus::Registry;
 use rand::{rngs::StdRng, SeedableRng};
@@ -10,6 +11,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use sui_config::builder::ConfigBuilder;
+use sui_config::node::AuthorityKeyPairWithPath;
 use sui_config::NodeConfig;
 use sui_core::authority_aggregator::{AuthAggMetrics, AuthorityAggregator};
 use sui_core::consensus_adapter::position_submit_certificate;
@@ -21,7 +23,7 @@ use sui_node::SuiNodeHandle;
 use sui_types::base_types::{AuthorityName, ObjectRef, SuiAddress};
 use sui_types::crypto::{
     generate_proof_of_possession, get_account_key_pair, get_key_pair_from_rng, AccountKeyPair,
-    KeypairTraits, ToFromBytes,
+    AuthorityKeyPair, KeypairTraits, ToFromBytes,
 };
 use sui_types::gas::GasCostSummary;
 use sui_types::message_envelope::Message;
@@ -32,7 +34,7 @@ use sui_types::messages::{
 use sui_types::object::{generate_test_gas_objects_with_owner, Object};
 use sui_types::sui_system_state::sui_system_state_inner_v1::VerifiedValidatorMetadataV1;
 use sui_types::sui_system_state::{
-    get_validator_from_table, sui_system_state_summary::get_validator_by_pool_id,
+    get_validator_from_table, sui_system_state_summary::get_validator_by_pool_id, SuiSystemState,
     SuiSystemStateTrait,
 };
 use sui_types::utils::to_sender_signed_transaction;
@@ -152,7 +154,7 @@ async fn reconfig_with_revert_end_to_end_test() {
     for (i, handle) in authorities.iter().enumerate() {
         handle
             .with_async(|node| async {
-                if position_submit_certificate(&net.committee, &node.state().name, tx.digest())
+                if position_submit_certificate(&net.committee, &node.state().name(), tx.digest())
                     < (authorities.len() - 1)
                 {
                     node.close_epoch_for_testing().await.unwrap();
@@ -166,7 +168,7 @@ async fn reconfig_with_revert_end_to_end_test() {
 
     let reverting_authority_idx = reverting_authority_idx.unwrap();
     let client = net
-        .get_client(&authorities[reverting_authority_idx].with(|node| node.state().name))
+        .get_client(&authorities[reverting_authority_idx].with(|node| node.state().name()))
         .unwrap();
     client
         .handle_certificate(cert.clone().into_inner())
@@ -384,6 +386,53 @@ async fn test_reconfig_with_failing_validator() {
     test_cluster.wait_for_epoch(Some(target_epoch)).await;
 }
 
+#[sim_test]
+async fn test_force_new_epoch_with_crashed_validator_and_stake_change() {
+    telemetry_subscribers::init_for_testing();
+
+    // The epochs never end by themselves during the test.
+    let test_cluster = TestClusterBuilder::new()
+        .with_epoch_duration_ms(3_600_000)
+        .build()
+        .await
+        .unwrap();
+
+    let staker = test_cluster.get_address_0();
+    let validator = test_cluster.get_validator_addresses()[0];
+    let validator_address = test_cluster
+        .swarm
+        .validator(validator)
+        .unwrap()
+        .config
+        .sui_address();
+    let stake_of = |system_state: SuiSystemState| {
+        system_state
+            .into_sui_system_state_summary()
+            .active_validators
+            .into_iter()
+            .find(|v| v.sui_address == validator_address)
+            .unwrap()
+            .staking_pool_sui_balance
+    };
+    let initial_stake = stake_of(test_cluster.force_new_epoch().await);
+
+    test_cluster
+        .add_stake(staker, validator, 1_000_000_000)
+        .await
+        .unwrap();
+
+    // The epoch still changes with a validator down, and the stake added takes effect.
+    let crashed = test_cluster.crash_validators(1);
+    let system_state = test_cluster.force_new_epoch().await;
+    assert_eq!(system_state.epoch(), 2);
+    // The pool may also have earned rewards at the epoch change.
+    assert!(stake_of(system_state) >= initial_stake + 1_000_000_000);
+
+    test_cluster.start_validator(crashed[0]).await;
+    let system_state = test_cluster.force_new_epoch().await;
+    assert_eq!(system_state.epoch(), 3);
+}
+
 #[sim_test]
 async fn test_validator_resign_effects() {
     // This test checks that validators are able to re-sign transaction effects that were finalized
@@ -747,6 +796,82 @@ async fn test_reconfig_with_committee_change_basic() {
     })
 }
 
+#[sim_test]
+async fn test_reconfig_with_rotated_protocol_key() {
+    let gas_objects: Vec<_> = gen_keys(4)
+        .iter()
+        .map(|key| Object::with_owner_for_testing(SuiAddress::from(key.public())))
+        .collect();
+    let mut init_configs = ConfigBuilder::new_with_temp_dir()
+        .rng(StdRng::from_seed([0; 32]))
+        .with_validator_account_keys(gen_keys(4))
+        .with_objects(gas_objects.clone())
+        .build();
+
+    // The first validator rotates to a new protocol key.
+    let next_key_pair: AuthorityKeyPair = get_key_pair_from_rng(&mut StdRng::from_seed([1; 32])).1;
+    let next_name = AuthorityName::from(next_key_pair.public());
+    let proof_of_possession = generate_proof_of_possession(
+        &next_key_pair,
+        init_configs.validator_configs[0].sui_address(),
+    );
+    init_configs.validator_configs[0].next_protocol_key_pair =
+        Some(AuthorityKeyPairWithPath::new(next_key_pair));
+    let node_config = init_configs.validator_configs[0].clone();
+    let mut gas = gas_objects
+        .iter()
+        .map(|o| init_configs.genesis.object(o.id()).unwrap())
+        .find(|o| o.owner == node_config.sui_address())
+        .unwrap()
+        .compute_object_reference();
+
+    let authorities = spawn_test_authorities(&init_configs).await;
+    let rotated = authorities
+        .iter()
+        .find(|handle| handle.with(|node| node.state().name()) == node_config.protocol_public_key())
+        .unwrap();
+
+    let effects = execute_system_call_tx(
+        &authorities,
+        gas,
+        &node_config,
+        "update_validator_next_epoch_protocol_pubkey",
+        vec![
+            CallArg::Pure(bcs::to_bytes(next_name.as_ref()).unwrap()),
+            CallArg::Pure(bcs::to_bytes(proof_of_possession.as_ref()).unwrap()),
+        ],
+    )
+    .await;
+    gas = effects.gas_object().0;
+
+    trigger_reconfiguration(&authorities).await;
+    rotated.with(|node| {
+        assert_eq!(node.state().name(), next_name);
+        assert!(node
+            .state()
+            .epoch_store_for_testing()
+            .committee()
+            .authority_exists(&next_name));
+    });
+
+    // The validator keeps submitting the certificates of shared object transactions to consensus,
+    // and its end of publish, under its new name.
+    execute_system_call_tx(
+        &authorities,
+        gas,
+        &node_config,
+        "update_validator_name",
+        vec![CallArg::Pure(bcs::to_bytes("rotated".as_bytes()).unwrap())],
+    )
+    .await;
+    trigger_reconfiguration(&authorities).await;
+    rotated.with(|node| {
+        assert!(node
+            .state()
+            .is_validator(&node.state().epoch_store_for_testing()));
+    });
+}
+
 #[sim_test]
 async fn test_reconfig_with_committee_change_stress() {
     // This needs to be written to genesis for all validators, present and future
@@ -913,7 +1038,9 @@ async fn test_reconfig_with_committee_change_stress() {
             let node_config = validator_superset
                 .validator_configs()
                 .iter()
-                .find(|config| config.protocol_public_key() == auth.with(|node| node.state().name))
+                .find(|config| {
+                    config.protocol_public_key() == auth.with(|node| node.state().name())
+                })
                 .unwrap();
 
             let sender = node_config.sui_address();
@@ -950,7 +1077,7 @@ async fn test_reconfig_with_committee_change_stress() {
         for name in joined_auths.into_iter() {
             let pos = fullnode_handles
                 .iter()
-                .position(|handle| handle.with(|node| node.state().name == name))
+                .position(|handle| handle.with(|node| node.state().name() == name))
                 .unwrap();
             let handle = fullnode_handles.remove(pos);
             handle
@@ -986,7 +1113,7 @@ async fn test_reconfig_with_committee_change_stress() {
         // Check that new validators have joined the committee.
         let valdator_pubkeys: Vec<_> = validator_handles
             .iter()
-            .map(|auth| auth.with(|node| node.state().name))
+            .map(|auth| auth.with(|node| node.state().name()))
             .collect();
         validator_handles.last().unwrap().with(|node| {
             assert_eq!(
@@ -1158,6 +1285,41 @@ async fn execute_leave_committee_tx(
     effects
 }
 
+async fn execute_system_call_tx(
+    authorities: &[SuiNodeHandle],
+    gas: ObjectRef,
+    node_config: &NodeConfig,
+    function: &'static str,
+    mut args: Vec<CallArg>,
+) -> CertifiedTransactionEffects {
+    args.insert(
+        0,
+        CallArg::Object(ObjectArg::SharedObject {
+            id: SUI_SYSTEM_STATE_OBJECT_ID,
+            initial_shared_version: SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+            mutable: true,
+        }),
+    );
+    let tx_data = TransactionData::new_move_call_with_dummy_gas_price(
+        node_config.sui_address(),
+        SuiSystem::ID,
+        ident_str!("sui_system").to_owned(),
+        Identifier::new(function).unwrap(),
+        vec![],
+        gas,
+        args,
+        10000,
+    )
+    .unwrap();
+
+    let transaction = to_sender_signed_transaction(tx_data, node_config.account_key_pair());
+    let effects = execute_transaction_block(authorities, transaction)
+        .await
+        .unwrap();
+    assert!(effects.status().is_ok());
+    effects
+}
+
 async fn trigger_reconfiguration(authorities: &[SuiNodeHandle]) {
     info!("Starting reconfiguration");
     let start = Instant::now();
@@ -1170,7 +1332,7 @@ async fn trigger_reconfiguration(authorities: &[SuiNodeHandle]) {
         handle
             .with_async(|node| async {
                 node.close_epoch_for_testing().await.unwrap();
-                cur_stake += cur_committee.weight(&node.state().name);
+                cur_stake += cur_committee.weight(&node.state().name());
             })
             .await;
         if cur_stake >= cur_committee.quorum_threshold() {
@@ -1192,7 +1354,7 @@ async fn trigger_reconfiguration(authorities: &[SuiNodeHandle]) {
                     tokio::time::sleep(Duration::from_secs(1)).await;
                     retries += 1;
                     if retries % 5 == 0 {
-                        warn!(validator=?node.state().name.concise(), "Waiting for {:?} seconds for epoch change", retries);
+                        warn!(validator=?node.state().name().concise(), "Waiting for {:?} seconds for epoch change", retries);
                     }
                 }
             })
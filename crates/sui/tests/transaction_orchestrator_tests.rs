@@ -36,6 +36,8 @@ async fn test_blocking_execution() -> Result<(), anyhow::Error> {
         reconfig_channel,
         temp_dir.path(),
         &Registry::new(),
+        true,
+        false,
     )
     .await
     .unwrap();
@@ -102,6 +104,8 @@ async fn test_fullnode_wal_log() -> Result<(), anyhow::Error> {
         reconfig_channel,
         temp_dir.path(),
         &Registry::new(),
+        true,
+        false,
     )
     .await
     .unwrap();
@@ -291,6 +295,64 @@ async fn test_tx_across_epoch_boundaries() {
     info!("test completed in {:?}", start.elapsed());
 }
 
+#[sim_test]
+async fn test_pre_validation() {
+    telemetry_subscribers::init_for_testing();
+    let (sender, keypair) = get_key_pair::<AccountKeyPair>();
+    let gas_objects = generate_test_gas_objects_with_owner(1, sender);
+    let (config, mut gas_objects) = test_authority_configs_with_objects(gas_objects);
+    let _authorities = spawn_test_authorities(&config).await;
+    let fullnode = spawn_fullnode(&config, None).await;
+    let gas_object_ref = gas_objects.swap_remove(0).compute_object_reference();
+    let transfer = |recipient| {
+        let data = TransactionData::new_transfer_sui_with_dummy_gas_price(
+            recipient,
+            sender,
+            None,
+            gas_object_ref,
+            5000,
+        );
+        to_sender_signed_transaction(data, &keypair)
+    };
+    let tx = transfer(get_key_pair::<AccountKeyPair>().0);
+    let conflicting_tx = transfer(get_key_pair::<AccountKeyPair>().0);
+
+    fullnode
+        .with_async(|node| async {
+            let orchestrator = node.transaction_orchestrator().unwrap();
+            execute_with_orchestrator(
+                &orchestrator,
+                tx.clone(),
+                ExecuteTransactionRequestType::WaitForLocalExecution,
+            )
+            .await
+            .unwrap();
+
+            // The gas object is consumed: the transaction is rejected before its submission.
+            let err = execute_with_orchestrator(
+                &orchestrator,
+                conflicting_tx,
+                ExecuteTransactionRequestType::WaitForEffectsCert,
+            )
+            .await
+            .unwrap_err();
+            assert!(
+                matches!(err, QuorumDriverError::InvalidTransactionInput(_)),
+                "{err:?}"
+            );
+
+            // An executed transaction can still be submitted again, for its effects.
+            execute_with_orchestrator(
+                &orchestrator,
+                tx,
+                ExecuteTransactionRequestType::WaitForEffectsCert,
+            )
+            .await
+            .unwrap();
+        })
+        .await;
+}
+
 async fn execute_with_orchestrator(
     orchestrator: &TransactiondOrchestrator<NetworkAuthorityClient>,
     txn: VerifiedTransaction,
@@ -49,6 +49,13 @@
 //!
 //! NOTE: JSON output requires the `json` crate feature to be enabled.
 //!
+//! ### Correlation logs
+//!
+//! Setting the `correlation_log_output` config variable, or the `RUST_LOG_CORRELATION` environment
+//! variable, outputs JSON logs including the debug logs of the [`CORRELATION_TARGET`] target.
+//! These trace every transaction, batch and certificate through the components of a node by
+//! their digests, so that the path of a single transaction can be reconstructed from the logs.
+//!
 //! ### Automatic Prometheus span latencies
 //!
 //! Included in this library is a tracing-subscriber layer named `PrometheusSpanLatencyLayer`.  It will create
@@ -105,6 +112,15 @@ pub mod span_latency_prom;
 /// Alias for a type-erased error type.
 pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
+/// The target of the correlation logs. Each of them records the `stage` a transaction, batch or
+/// certificate reached, and links its digest to the digests of the previous and next stages:
+/// - `batched`: the `transaction` is sealed in the `batch` by the worker.
+/// - `certified`: the `batch` is included in the `certificate` formed by the primary.
+/// - `committed`: the `certificate` is committed by consensus in the `sub_dag`.
+/// - `fetched`: the `batch` of the `certificate` is fetched by the executor for the `sub_dag`.
+/// - `sequenced`: the `transaction` of the `certificate` is sequenced by the node as `digest`.
+pub const CORRELATION_TARGET: &str = "correlation";
+
 /// Configuration for different logging/tracing options
 /// ===
 /// - json_log_output: Output JSON logs to stdout only.
@@ -116,6 +132,8 @@ pub struct TelemetryConfig {
     pub tokio_console: bool,
     /// Output JSON logs.
     pub json_log_output: bool,
+    /// Output JSON logs, including the correlation logs.
+    pub correlation_log_output: bool,
     /// If defined, write output to a file starting with this name, ex app.log
     pub log_file: Option<String>,
     /// Log level to set, defaults to info
@@ -208,6 +226,7 @@ impl TelemetryConfig {
         Self {
             tokio_console: false,
             json_log_output: false,
+            correlation_log_output: false,
             log_file: None,
             log_string: None,
             span_level: None,
@@ -222,6 +241,11 @@ impl TelemetryConfig {
         self
     }
 
+    pub fn with_correlation_logs(mut self) -> Self {
+        self.correlation_log_output = true;
+        self
+    }
+
     pub fn with_log_level(mut self, log_string: &str) -> Self {
         self.log_string = Some(log_string.to_owned());
         self
@@ -251,6 +275,10 @@ impl TelemetryConfig {
             self.json_log_output = true;
         }
 
+        if env::var("RUST_LOG_CORRELATION").is_ok() {
+            self.correlation_log_output = true;
+        }
+
         if env::var("TOKIO_CONSOLE").is_ok() {
             self.tokio_console = true;
         }
@@ -275,8 +303,15 @@ impl TelemetryConfig {
         // different filtering needs, including tokio-console/console-subscriber, and it also doesn't
         // fit with the span creation needs for distributed tracing and other span-based tools.
         let log_level = config.log_string.unwrap_or_else(|| "info".into());
-        let env_filter =
+        let mut env_filter =
             EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(log_level));
+        if config.correlation_log_output {
+            env_filter = env_filter.add_directive(
+                format!("{CORRELATION_TARGET}=debug")
+                    .parse()
+                    .expect("The correlation directive is valid"),
+            );
+        }
         let (log_filter, reload_handle) = reload::Layer::new(env_filter);
         let filter_handle = FilterHandle(reload_handle);
 
@@ -305,7 +340,7 @@ impl TelemetryConfig {
         }
 
         let (nb_output, worker_guard) = get_output(config.log_file.clone());
-        if config.json_log_output {
+        if config.json_log_output || config.correlation_log_output {
             // Output to file or to stderr in a newline-delimited JSON format
             let json_layer = fmt::layer()
                 .with_file(true)
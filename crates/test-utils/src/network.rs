@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::anyhow;
 use futures::future::join_all;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
@@ -24,22 +25,26 @@ use sui_config::genesis_config::GenesisConfig;
 use sui_config::node::DBCheckpointConfig;
 use sui_config::{Config, SUI_CLIENT_CONFIG, SUI_NETWORK_CONFIG};
 use sui_config::{FullnodeConfigBuilder, NodeConfig, PersistedConfig, SUI_KEYSTORE_FILENAME};
+use sui_json_rpc_types::{
+    SuiExecutionStatus, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
+};
 use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
 use sui_node::SuiNode;
 use sui_node::SuiNodeHandle;
 use sui_protocol_config::{ProtocolVersion, SupportedProtocolVersions};
 use sui_sdk::{SuiClient, SuiClientBuilder};
 use sui_swarm::memory::{Swarm, SwarmBuilder};
-use sui_types::base_types::{AuthorityName, SuiAddress};
+use sui_types::base_types::{AuthorityName, ObjectID, SuiAddress};
 use sui_types::committee::EpochId;
 use sui_types::crypto::KeypairTraits;
 use sui_types::crypto::SuiKeyPair;
-use sui_types::messages::TransactionData;
+use sui_types::messages::{Transaction, TransactionData};
 use sui_types::object::Object;
 use sui_types::sui_system_state::SuiSystemState;
 use sui_types::sui_system_state::SuiSystemStateTrait;
 
 const NUM_VALIDAOTR: usize = 4;
+const STAKING_GAS_BUDGET: u64 = 15_000;
 
 pub struct FullNodeHandle {
     pub sui_node: Arc<SuiNode>,
@@ -146,6 +151,135 @@ impl TestCluster {
         node.start().await.unwrap();
     }
 
+    /// Stop `count` randomly chosen running validators, simulating their crash, and return
+    /// their names so that they can be restarted with `start_validator`.
+    pub fn crash_validators(&self, count: usize) -> Vec<AuthorityName> {
+        let running: Vec<_> = self
+            .swarm
+            .validators()
+            .filter(|v| v.is_running())
+            .map(|v| v.name())
+            .collect();
+        assert!(
+            count <= running.len(),
+            "Cannot crash {count} validators, only {} are running",
+            running.len()
+        );
+        let crashed: Vec<_> = running
+            .choose_multiple(&mut OsRng, count)
+            .copied()
+            .collect();
+        for name in &crashed {
+            info!("Crashing validator {:?}", name.concise());
+            self.stop_validator(*name);
+        }
+        crashed
+    }
+
+    /// Force the cluster into the next epoch without waiting for the epoch duration, by
+    /// closing the epoch on a quorum of the running validators. Panics if the running
+    /// validators do not hold a quorum of the stake.
+    pub async fn force_new_epoch(&self) -> SuiSystemState {
+        let handles: Vec<_> = self
+            .swarm
+            .validators()
+            .filter_map(|v| v.get_node_handle())
+            .collect();
+        let committee = handles
+            .first()
+            .expect("No validator is running")
+            .with(|node| node.state().epoch_store_for_testing().committee().clone());
+        let mut stake = 0;
+        for handle in &handles {
+            if stake >= committee.quorum_threshold() {
+                break;
+            }
+            handle
+                .with_async(|node| async {
+                    node.close_epoch_for_testing().await.unwrap();
+                    stake += committee.weight(&node.state().name());
+                })
+                .await;
+        }
+        assert!(
+            stake >= committee.quorum_threshold(),
+            "The running validators do not hold a quorum of the stake"
+        );
+        self.wait_for_epoch(Some(committee.epoch + 1)).await
+    }
+
+    /// Stake `amount` of the SUI owned by `staker` with the validator, taking effect from the
+    /// next epoch, e.g. after `force_new_epoch`.
+    pub async fn add_stake(
+        &self,
+        staker: SuiAddress,
+        validator: AuthorityName,
+        amount: u64,
+    ) -> anyhow::Result<SuiTransactionBlockResponse> {
+        let validator_address = self
+            .swarm
+            .validator(validator)
+            .ok_or_else(|| anyhow!("Unknown validator {:?}", validator.concise()))?
+            .config
+            .sui_address();
+        let (_, coin) = self
+            .wallet
+            .gas_objects(staker)
+            .await?
+            .into_iter()
+            .max_by_key(|(value, _)| *value)
+            .ok_or_else(|| anyhow!("{staker} owns no gas coin"))?;
+        let data = self
+            .sui_client()
+            .transaction_builder()
+            .request_add_stake(
+                staker,
+                vec![coin.object_id],
+                Some(amount),
+                validator_address,
+                None,
+                STAKING_GAS_BUDGET,
+            )
+            .await?;
+        self.execute_signed(staker, data).await
+    }
+
+    /// Withdraw the stake of the `StakedSui` object owned by `staker`, taking effect from the
+    /// next epoch, e.g. after `force_new_epoch`.
+    pub async fn withdraw_stake(
+        &self,
+        staker: SuiAddress,
+        staked_sui: ObjectID,
+    ) -> anyhow::Result<SuiTransactionBlockResponse> {
+        let data = self
+            .sui_client()
+            .transaction_builder()
+            .request_withdraw_stake(staker, staked_sui, None, STAKING_GAS_BUDGET)
+            .await?;
+        self.execute_signed(staker, data).await
+    }
+
+    async fn execute_signed(
+        &self,
+        signer: SuiAddress,
+        data: TransactionData,
+    ) -> anyhow::Result<SuiTransactionBlockResponse> {
+        let signature = self.sign_transaction(&signer, &data);
+        let tx = Transaction::from_data(data, Intent::default(), vec![signature]).verify()?;
+        let response = self.wallet.execute_transaction_block(tx).await?;
+        let status = response
+            .effects
+            .as_ref()
+            .map(|effects| effects.status().clone());
+        match status {
+            Some(SuiExecutionStatus::Success) => Ok(response),
+            status => Err(anyhow!(
+                "Transaction {} did not succeed: {status:?}",
+                response.digest
+            )),
+        }
+    }
+
     pub fn random_node_restarter(self: &Arc<Self>) -> RandomNodeRestarter {
         RandomNodeRestarter::new(self.clone())
     }
@@ -157,7 +291,16 @@ impl TestCluster {
     /// If target_epoch is specified, wait until the cluster reaches that epoch.
     /// If target_epoch is None, wait until the cluster reaches the next epoch.
     pub async fn wait_for_epoch(&self, target_epoch: Option<EpochId>) -> SuiSystemState {
-        let mut epoch_rx = self.fullnode_handle.sui_node.subscribe_to_epoch_change();
+        let sui_node = &self.fullnode_handle.sui_node;
+        let mut epoch_rx = sui_node.subscribe_to_epoch_change();
+        // The target epoch may have been reached before subscribing, e.g. when it was forced.
+        let current_epoch = sui_node.current_epoch_for_testing();
+        if matches!(target_epoch, Some(target_epoch) if current_epoch >= target_epoch) {
+            return sui_node
+                .state()
+                .get_sui_system_state_object_for_testing()
+                .unwrap();
+        }
         timeout(Duration::from_secs(60), async move {
             while let Ok(system_state) = epoch_rx.recv().await {
                 info!("received epoch {}", system_state.epoch());
@@ -7,7 +7,9 @@ use rocksdb::Direction;
 
 use crate::metrics::{DBMetrics, SamplingInterval};
 
-use super::{be_fix_int_ser, errors::TypedStoreError, RocksDBRawIter};
+use super::{
+    be_fix_int_ser, deserialize_value, errors::TypedStoreError, RocksDBRawIter, ValueCipher,
+};
 use serde::{de::DeserializeOwned, Serialize};
 
 /// An iterator over all key-value pairs in a data map.
@@ -18,6 +20,7 @@ pub struct Iter<'a, K, V> {
     cf: String,
     db_metrics: Arc<DBMetrics>,
     iter_bytes_sample_interval: SamplingInterval,
+    value_cipher: Option<Arc<dyn ValueCipher>>,
 }
 
 impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iter<'a, K, V> {
@@ -26,6 +29,7 @@ impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iter<'a, K, V> {
         cf: String,
         db_metrics: &Arc<DBMetrics>,
         iter_bytes_sample_interval: &SamplingInterval,
+        value_cipher: Option<Arc<dyn ValueCipher>>,
     ) -> Self {
         Self {
             db_iter,
@@ -34,6 +38,7 @@ impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iter<'a, K, V> {
             cf,
             db_metrics: db_metrics.clone(),
             iter_bytes_sample_interval: iter_bytes_sample_interval.clone(),
+            value_cipher,
         }
     }
 }
@@ -55,7 +60,7 @@ impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for Iter<'a, K, V> {
                 .value()
                 .expect("Valid iterator failed to get value");
             let key = config.deserialize(raw_key).ok();
-            let value = bcs::from_bytes(raw_value).ok();
+            let value = deserialize_value(raw_value, self.value_cipher.as_ref()).ok();
             if self.iter_bytes_sample_interval.sample() {
                 let total_bytes_read = (raw_key.len() + raw_value.len()) as f64;
                 self.db_metrics
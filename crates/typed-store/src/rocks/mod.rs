@@ -225,6 +225,14 @@ impl RocksDB {
         delegate_call!(self.property_int_value_cf(cf, name))
     }
 
+    pub fn property_value_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        name: impl CStrLike,
+    ) -> Result<Option<String>, rocksdb::Error> {
+        delegate_call!(self.property_value_cf(cf, name))
+    }
+
     pub fn get_pinned_cf<K: AsRef<[u8]>>(
         &self,
         cf: &impl AsColumnFamilyRef,
@@ -292,14 +300,22 @@ impl RocksDB {
     }
 
     pub fn write(&self, batch: RocksDBBatch) -> Result<(), TypedStoreError> {
+        self.write_opt(batch, &WriteOptions::default())
+    }
+
+    pub fn write_opt(
+        &self,
+        batch: RocksDBBatch,
+        writeopts: &WriteOptions,
+    ) -> Result<(), TypedStoreError> {
         fail_point!("batch-write");
         match (self, batch) {
             (RocksDB::DBWithThreadMode(db), RocksDBBatch::Regular(batch)) => {
-                db.underlying.write(batch)?;
+                db.underlying.write_opt(batch, writeopts)?;
                 Ok(())
             }
             (RocksDB::OptimisticTransactionDB(db), RocksDBBatch::Transactional(batch)) => {
-                db.underlying.write(batch)?;
+                db.underlying.write_opt(batch, writeopts)?;
                 Ok(())
             }
             _ => Err(TypedStoreError::RocksDBError(
@@ -377,6 +393,49 @@ impl RocksDB {
         delegate_call!(self.compact_range_cf(cf, start, end))
     }
 
+    /// Returns the statistics of the column family, or None if there is no such column family.
+    pub fn column_family_stats(
+        &self,
+        cf_name: &str,
+    ) -> Result<Option<ColumnFamilyStats>, TypedStoreError> {
+        let Some(cf) = self.cf_handle(cf_name) else {
+            return Ok(None);
+        };
+        let int_property = |name: &'static std::ffi::CStr| {
+            self.property_int_value_cf(&cf, name)
+                .map(Option::unwrap_or_default)
+                .map_err(|e| TypedStoreError::RocksDBError(e.into_string()))
+        };
+        let sst_tombstones = self
+            .property_value_cf(&cf, properties::AGGREGATED_TABLE_PROPERTIES)
+            .map_err(|e| TypedStoreError::RocksDBError(e.into_string()))?
+            .as_deref()
+            .and_then(parse_num_deleted_keys)
+            .unwrap_or_default();
+
+        Ok(Some(ColumnFamilyStats {
+            total_sst_files_size: int_property(properties::TOTAL_SST_FILES_SIZE)?,
+            live_data_size: int_property(properties::ESTIMATE_LIVE_DATA_SIZE)?,
+            size_all_mem_tables: int_property(properties::SIZE_ALL_MEM_TABLES)?,
+            estimated_num_keys: int_property(properties::ESTIMATE_NUM_KEYS)?,
+            num_tombstones: sst_tombstones
+                + int_property(properties::NUM_DELETES_ACTIVE_MEM_TABLE)?
+                + int_property(properties::NUM_DELETES_IMM_MEM_TABLES)?,
+            pending_compaction_bytes: int_property(properties::ESTIMATE_PENDING_COMPACTION_BYTES)?,
+            num_running_compactions: int_property(properties::NUM_RUNNING_COMPACTIONS)?,
+        }))
+    }
+
+    /// Compacts the whole column family, blocking until the compaction is done. Returns false if
+    /// there is no such column family.
+    pub fn compact_column_family(&self, cf_name: &str) -> bool {
+        let Some(cf) = self.cf_handle(cf_name) else {
+            return false;
+        };
+        self.compact_range_cf(&cf, None::<&[u8]>, None::<&[u8]>);
+        true
+    }
+
     pub fn flush(&self) -> Result<(), rocksdb::Error> {
         delegate_call!(self.flush())
     }
@@ -545,6 +604,36 @@ impl MetricConf {
 const CF_METRICS_REPORT_PERIOD_MILLIS: u64 = 1000;
 const METRICS_ERROR: i64 = -1;
 
+/// Encrypts the values of a map before they are written, and decrypts them when they are read.
+/// The keys are left in the clear, so that the map remains ordered.
+pub trait ValueCipher: std::fmt::Debug + Send + Sync {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, TypedStoreError>;
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, TypedStoreError>;
+}
+
+/// Serializes a value, encrypting it with the cipher if any.
+pub(crate) fn serialize_value<V: Serialize + ?Sized>(
+    value: &V,
+    cipher: Option<&Arc<dyn ValueCipher>>,
+) -> Result<Vec<u8>, TypedStoreError> {
+    let bytes = bcs::to_bytes(value)?;
+    match cipher {
+        Some(cipher) => cipher.encrypt(&bytes),
+        None => Ok(bytes),
+    }
+}
+
+/// Deserializes a value, decrypting it with the cipher if any.
+pub(crate) fn deserialize_value<V: DeserializeOwned>(
+    bytes: &[u8],
+    cipher: Option<&Arc<dyn ValueCipher>>,
+) -> Result<V, TypedStoreError> {
+    match cipher {
+        Some(cipher) => Ok(bcs::from_bytes(&cipher.decrypt(bytes)?)?),
+        None => Ok(bcs::from_bytes(bytes)?),
+    }
+}
+
 /// An interface to a rocksDB database, keyed by a columnfamily
 #[derive(Clone, Debug)]
 pub struct DBMap<K, V> {
@@ -559,6 +648,8 @@ pub struct DBMap<K, V> {
     iter_latency_sample_interval: SamplingInterval,
     iter_bytes_sample_interval: SamplingInterval,
     _metrics_task_cancel_handle: Arc<oneshot::Sender<()>>,
+    // Encrypts the values of the map, if set.
+    value_cipher: Option<Arc<dyn ValueCipher>>,
 }
 
 unsafe impl<K: Send, V: Send> Send for DBMap<K, V> {}
@@ -601,9 +692,21 @@ impl<K, V> DBMap<K, V> {
             write_sample_interval: db.write_sampling_interval(),
             iter_bytes_sample_interval: db.iter_bytes_sampling_interval(),
             iter_latency_sample_interval: db.iter_latency_sampling_interval(),
+            value_cipher: None,
         }
     }
 
+    /// Encrypts the values of the map with the given cipher. The values already written in the
+    /// clear, or with another cipher, can no longer be read.
+    pub fn with_value_cipher(mut self, cipher: Arc<dyn ValueCipher>) -> Self {
+        self.value_cipher = Some(cipher);
+        self
+    }
+
+    fn value_cipher(&self) -> Option<&Arc<dyn ValueCipher>> {
+        self.value_cipher.as_ref()
+    }
+
     /// Opens a database from a path, with specific options and an optional column family.
     ///
     /// This database is used to perform operations on single column family, and parametrizes
@@ -1001,6 +1104,12 @@ impl DBBatch {
     /// Consume the batch and write its operations to the database
     #[instrument(level = "trace", skip_all, err)]
     pub fn write(self) -> Result<(), TypedStoreError> {
+        self.write_opt(&WriteOptions::default())
+    }
+
+    /// Consume the batch and write its operations to the database with the given options
+    #[instrument(level = "trace", skip_all, err)]
+    pub fn write_opt(self, writeopts: &WriteOptions) -> Result<(), TypedStoreError> {
         let report_metrics = if self.write_sample_interval.sample() {
             let db_name = self.rocksdb.db_name();
             let timer = self
@@ -1014,7 +1123,7 @@ impl DBBatch {
         } else {
             None
         };
-        self.rocksdb.write(self.batch)?;
+        self.rocksdb.write_opt(self.batch, writeopts)?;
         if let Some((db_name, batch_size, _timer, _perf_ctx)) = report_metrics {
             self.db_metrics
                 .op_metrics
@@ -1079,7 +1188,7 @@ impl DBBatch {
             .into_iter()
             .try_for_each::<_, Result<_, TypedStoreError>>(|(k, v)| {
                 let k_buf = be_fix_int_ser(k.borrow())?;
-                let v_buf = bcs::to_bytes(v.borrow())?;
+                let v_buf = serialize_value(v.borrow(), db.value_cipher())?;
                 self.batch.put_cf(&db.cf(), k_buf, v_buf);
                 Ok(())
             })?;
@@ -1141,7 +1250,7 @@ impl DBBatch {
             .into_iter()
             .try_for_each::<_, Result<_, TypedStoreError>>(|(k, v)| {
                 let k_buf = be_fix_int_ser(k.borrow())?;
-                let v_buf = bcs::to_bytes(v.borrow())?;
+                let v_buf = serialize_value(v.borrow(), db.value_cipher())?;
                 self.batch.put_cf(&db.cf(), k_buf, v_buf);
                 Ok(())
             })?;
@@ -1162,7 +1271,7 @@ impl DBBatch {
             .into_iter()
             .try_for_each::<_, Result<_, TypedStoreError>>(|(k, v)| {
                 let k_buf = be_fix_int_ser(k.borrow())?;
-                let v_buf = bcs::to_bytes(v.borrow())?;
+                let v_buf = serialize_value(v.borrow(), db.value_cipher())?;
                 self.batch.merge_cf(&db.cf(), k_buf, v_buf);
                 Ok(())
             })?;
@@ -1222,7 +1331,7 @@ impl<'a> DBTransaction<'a> {
             .into_iter()
             .try_for_each::<_, Result<_, TypedStoreError>>(|(k, v)| {
                 let k_buf = be_fix_int_ser(k.borrow())?;
-                let v_buf = bcs::to_bytes(v.borrow())?;
+                let v_buf = serialize_value(v.borrow(), db.value_cipher())?;
                 self.transaction.put_cf(&db.cf(), k_buf, v_buf)?;
                 Ok(())
             })?;
@@ -1268,7 +1377,7 @@ impl<'a> DBTransaction<'a> {
             .transaction
             .get_for_update_cf_opt(&db.cf(), k_buf, true, &db.opts.readopts())?
         {
-            Some(data) => Ok(Some(bcs::from_bytes(&data)?)),
+            Some(data) => Ok(Some(deserialize_value(&data, db.value_cipher())?)),
             None => Ok(None),
         }
     }
@@ -1282,7 +1391,7 @@ impl<'a> DBTransaction<'a> {
         self.transaction
             .get_cf_opt(&db.cf(), key_buf, &db.opts.readopts())
             .map_err(|e| TypedStoreError::RocksDBError(e.to_string()))
-            .map(|res| res.and_then(|bytes| bcs::from_bytes::<V>(&bytes).ok()))
+            .map(|res| res.and_then(|bytes| deserialize_value::<V>(&bytes, db.value_cipher()).ok()))
     }
 
     pub fn multi_get<J: Borrow<K>, K: Serialize + DeserializeOwned, V: DeserializeOwned>(
@@ -1303,7 +1412,7 @@ impl<'a> DBTransaction<'a> {
         let values_parsed: Result<Vec<_>, TypedStoreError> = results
             .into_iter()
             .map(|value_byte| match value_byte? {
-                Some(data) => Ok(Some(bcs::from_bytes(&data)?)),
+                Some(data) => Ok(Some(deserialize_value(&data, db.value_cipher())?)),
                 None => Ok(None),
             })
             .collect();
@@ -1325,6 +1434,7 @@ impl<'a> DBTransaction<'a> {
             db.cf.clone(),
             &db.db_metrics,
             &db.iter_latency_sample_interval,
+            db.value_cipher.clone(),
         )
     }
 
@@ -1351,7 +1461,7 @@ impl<'a> DBTransaction<'a> {
         );
         db_iter.seek_to_first();
 
-        Values::new(db_iter)
+        Values::new(db_iter, db.value_cipher.clone())
     }
 
     pub fn commit(self) -> Result<(), TypedStoreError> {
@@ -1489,7 +1599,7 @@ where
                 .report_metrics(&self.cf);
         }
         match res {
-            Some(data) => Ok(Some(bcs::from_bytes(&data)?)),
+            Some(data) => Ok(Some(deserialize_value(&data, self.value_cipher())?)),
             None => Ok(None),
         }
     }
@@ -1541,7 +1651,7 @@ where
             None
         };
         let key_buf = be_fix_int_ser(key)?;
-        let value_buf = bcs::to_bytes(value)?;
+        let value_buf = serialize_value(value, self.value_cipher())?;
         if report_metrics.is_some() {
             self.db_metrics
                 .op_metrics
@@ -1625,6 +1735,7 @@ where
             self.cf.clone(),
             &self.db_metrics,
             &self.iter_bytes_sample_interval,
+            self.value_cipher.clone(),
         )
     }
 
@@ -1643,7 +1754,7 @@ where
             .raw_iterator_cf(&self.cf(), self.opts.readopts());
         db_iter.seek_to_first();
 
-        Values::new(db_iter)
+        Values::new(db_iter, self.value_cipher.clone())
     }
 
     /// Returns a vector of values corresponding to the keys provided.
@@ -1694,7 +1805,7 @@ where
         let values_parsed: Result<Vec<_>, TypedStoreError> = results
             .into_iter()
             .map(|value_byte| match value_byte? {
-                Some(data) => Ok(Some(bcs::from_bytes(&data)?)),
+                Some(data) => Ok(Some(deserialize_value(&data, self.value_cipher())?)),
                 None => Ok(None),
             })
             .collect();
@@ -1769,9 +1880,32 @@ fn read_size_from_env(var_name: &str) -> Option<usize> {
         .ok()
 }
 
+/// The statistics of a column family, to understand its disk usage.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ColumnFamilyStats {
+    pub total_sst_files_size: u64,
+    pub live_data_size: u64,
+    pub size_all_mem_tables: u64,
+    pub estimated_num_keys: u64,
+    /// The deletions that are not compacted away yet, in the memtables and in the SST files.
+    pub num_tombstones: u64,
+    pub pending_compaction_bytes: u64,
+    pub num_running_compactions: u64,
+}
+
+/// Parses the number of deleted keys out of the aggregated table properties of a column family.
+fn parse_num_deleted_keys(table_properties: &str) -> Option<u64> {
+    table_properties
+        .split(';')
+        .find_map(|property| property.trim().strip_prefix("# deleted keys="))
+        .and_then(|value| value.trim().parse().ok())
+}
+
 #[derive(Default, Clone, Debug)]
 pub struct ReadWriteOptions {
     pub ignore_range_deletions: bool,
+    /// Whether the writes wait for the RocksDB WAL to be synced to disk.
+    pub sync_to_disk: bool,
 }
 
 impl ReadWriteOptions {
@@ -1781,7 +1915,9 @@ impl ReadWriteOptions {
         readopts
     }
     pub fn writeopts(&self) -> WriteOptions {
-        WriteOptions::default()
+        let mut writeopts = WriteOptions::default();
+        writeopts.set_sync(self.sync_to_disk);
+        writeopts
     }
 }
 
@@ -263,6 +263,43 @@ async fn test_remove(#[values(true, false)] is_transactional: bool) {
     assert!(db.get(&123456789).expect("Failed to get").is_none());
 }
 
+#[rstest]
+#[tokio::test]
+async fn test_column_family_stats(#[values(true, false)] is_transactional: bool) {
+    let db = open_map(temp_dir(), Some("table"), is_transactional);
+    for i in 0..10 {
+        db.insert(&i, &i.to_string()).expect("Failed to insert");
+    }
+    db.remove(&0).expect("Failed to remove");
+
+    let stats = db
+        .rocksdb
+        .column_family_stats("table")
+        .expect("Failed to read stats")
+        .expect("Missing column family");
+    assert_eq!(stats.num_tombstones, 1);
+    assert!(stats.size_all_mem_tables > 0);
+
+    // The tombstone is compacted away.
+    assert!(db.rocksdb.compact_column_family("table"));
+    let stats = db.rocksdb.column_family_stats("table").unwrap().unwrap();
+    assert_eq!(stats.num_tombstones, 0);
+
+    assert!(db.rocksdb.column_family_stats("missing").unwrap().is_none());
+    assert!(!db.rocksdb.compact_column_family("missing"));
+}
+
+#[test]
+fn test_parse_num_deleted_keys() {
+    assert_eq!(
+        parse_num_deleted_keys(
+            "# data blocks=1; # entries=12; # deleted keys=3; # merge operands=0"
+        ),
+        Some(3)
+    );
+    assert_eq!(parse_num_deleted_keys("# data blocks=1"), None);
+}
+
 #[rstest]
 #[tokio::test]
 async fn test_iter(#[values(true, false)] is_transactional: bool) {
@@ -322,6 +359,69 @@ async fn test_values(#[values(true, false)] is_transactional: bool) {
     assert_eq!(None, values.next());
 }
 
+#[derive(Debug)]
+struct XorCipher(u8);
+
+impl ValueCipher for XorCipher {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, TypedStoreError> {
+        let mut ciphertext: Vec<u8> = plaintext.iter().map(|b| b ^ self.0).collect();
+        ciphertext.push(self.0);
+        Ok(ciphertext)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, TypedStoreError> {
+        match ciphertext.split_last() {
+            Some((tag, ciphertext)) if *tag == self.0 => {
+                Ok(ciphertext.iter().map(|b| b ^ self.0).collect())
+            }
+            _ => Err(TypedStoreError::EncryptionError("wrong key".to_string())),
+        }
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_value_cipher(#[values(true, false)] is_transactional: bool) {
+    let db = open_map::<_, u32, String>(temp_dir(), None, is_transactional)
+        .with_value_cipher(Arc::new(XorCipher(0x5a)));
+
+    db.insert(&1, &"1".to_string()).expect("Failed to insert");
+    db.multi_insert([(2, "2".to_string()), (3, "3".to_string())])
+        .expect("Failed to multi-insert");
+
+    assert_eq!(Some("1".to_string()), db.get(&1).unwrap());
+    assert_eq!(
+        vec![Some("2".to_string()), None],
+        db.multi_get([2, 4]).unwrap()
+    );
+    assert_eq!(
+        vec![
+            (1, "1".to_string()),
+            (2, "2".to_string()),
+            (3, "3".to_string())
+        ],
+        db.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!["1".to_string(), "2".to_string(), "3".to_string()],
+        db.values().collect::<Vec<_>>()
+    );
+
+    // The values are not stored in the clear.
+    let raw = db.get_raw_bytes(&1).unwrap().unwrap();
+    assert_ne!(bcs::to_bytes(&"1".to_string()).unwrap(), raw);
+
+    // They cannot be read without the key, or with another one.
+    let plain = DBMap::<u32, String>::reopen(&db.rocksdb, None, &ReadWriteOptions::default())
+        .expect("Failed to re-open storage");
+    assert!(plain.get(&1).is_err());
+    let other = plain.with_value_cipher(Arc::new(XorCipher(0x33)));
+    assert!(matches!(
+        other.get(&1),
+        Err(TypedStoreError::EncryptionError(_))
+    ));
+}
+
 #[rstest]
 #[tokio::test]
 async fn test_try_extend(#[values(true, false)] is_transactional: bool) {
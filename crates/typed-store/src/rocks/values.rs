@@ -1,22 +1,27 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
 use serde::de::DeserializeOwned;
 
-use super::RocksDBRawIter;
+use super::{deserialize_value, RocksDBRawIter, ValueCipher};
 
 /// An iterator over the values of a prefix.
 pub struct Values<'a, V> {
     db_iter: RocksDBRawIter<'a>,
     _phantom: PhantomData<V>,
+    value_cipher: Option<Arc<dyn ValueCipher>>,
 }
 
 impl<'a, V: DeserializeOwned> Values<'a, V> {
-    pub(crate) fn new(db_iter: RocksDBRawIter<'a>) -> Self {
+    pub(crate) fn new(
+        db_iter: RocksDBRawIter<'a>,
+        value_cipher: Option<Arc<dyn ValueCipher>>,
+    ) -> Self {
         Self {
             db_iter,
             _phantom: PhantomData,
+            value_cipher,
         }
     }
 }
@@ -26,10 +31,11 @@ impl<'a, V: DeserializeOwned> Iterator for Values<'a, V> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.db_iter.valid() {
-            let value = self
-                .db_iter
-                .key()
-                .and_then(|_| self.db_iter.value().and_then(|v| bcs::from_bytes(v).ok()));
+            let value = self.db_iter.key().and_then(|_| {
+                self.db_iter
+                    .value()
+                    .and_then(|v| deserialize_value(v, self.value_cipher.as_ref()).ok())
+            });
 
             self.db_iter.next();
             value
@@ -10,7 +10,10 @@
 #![allow(clippy::mutable_key_type)]
 
 use crypto::{NetworkPublicKey, PublicKey};
-use fastcrypto::traits::EncodeDecodeBase64;
+use fastcrypto::{
+    hash::{Blake2b256, HashFunction},
+    traits::EncodeDecodeBase64,
+};
 use mysten_network::Multiaddr;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
@@ -18,6 +21,7 @@ use std::{
     fs::{self, OpenOptions},
     io::{BufWriter, Write as _},
     num::NonZeroU32,
+    path::PathBuf,
     time::Duration,
 };
 use thiserror::Error;
@@ -118,6 +122,10 @@ pub struct Parameters {
     #[serde(default = "Parameters::default_max_header_num_of_batches")]
     pub max_header_num_of_batches: usize,
 
+    /// The maximum number of batch digests of a single worker included in a header.
+    #[serde(default = "Parameters::default_max_header_num_of_batches_per_worker")]
+    pub max_header_num_of_batches_per_worker: usize,
+
     /// The maximum delay that the primary should wait between generating two headers, even if
     /// other conditions are not satisfied besides having enough parent stakes.
     #[serde(
@@ -132,6 +140,14 @@ pub struct Parameters {
         default = "Parameters::default_min_header_delay"
     )]
     pub min_header_delay: Duration,
+    /// Whether the proposer waits for a minimum payload before proposing a header.
+    #[serde(default = "HeaderProposalPolicy::default")]
+    pub header_proposal_policy: HeaderProposalPolicy,
+    /// Whether the leaders with a low reputation are swapped out of the schedule, when decided
+    /// by the protocol for the epoch. It is not configurable, so that every primary elects the
+    /// same leaders.
+    #[serde(skip)]
+    pub leader_swaps: bool,
 
     /// The depth of the garbage collection (Denominated in number of rounds).
     #[serde(default = "Parameters::default_gc_depth")]
@@ -146,6 +162,18 @@ pub struct Parameters {
     /// are picked at random from the committee.
     #[serde(default = "Parameters::default_sync_retry_nodes")]
     pub sync_retry_nodes: usize,
+    /// The delay after which a worker synchronizing missing batches also requests them from the
+    /// next peer, racing all the requests in flight. Denominated in ms.
+    #[serde(
+        with = "duration_format",
+        default = "Parameters::default_sync_hedge_delay"
+    )]
+    pub sync_hedge_delay: Duration,
+    /// The maximum size of the batches returned in a response to a request for batches, or in a
+    /// chunk of a stream of batches. The batches left out are reported for the requester to
+    /// request them again. Denominated in bytes.
+    #[serde(default = "Parameters::default_max_request_batches_response_size")]
+    pub max_request_batches_response_size: usize,
     /// The preferred batch size. The workers seal a batch of transactions when it reaches this size.
     /// Denominated in bytes.
     #[serde(default = "Parameters::default_batch_size")]
@@ -157,6 +185,91 @@ pub struct Parameters {
         default = "Parameters::default_max_batch_delay"
     )]
     pub max_batch_delay: Duration,
+    /// How the workers adapt `batch_size` and `max_batch_delay` to their load.
+    #[serde(default = "AdaptiveSealingParameters::default")]
+    pub adaptive_sealing: AdaptiveSealingParameters,
+    /// The time window during which the workers reject transactions identical to one they
+    /// have already received.
+    #[serde(
+        with = "duration_format",
+        default = "Parameters::default_tx_dedup_window"
+    )]
+    pub tx_dedup_window: Duration,
+    /// The maximum number of transaction digests the workers remember for deduplication.
+    #[serde(default = "Parameters::default_tx_dedup_cache_size")]
+    pub tx_dedup_cache_size: usize,
+    /// The maximum number of batch verdicts of the transaction validator the workers remember.
+    #[serde(default = "Parameters::default_batch_verdict_cache_size")]
+    pub batch_verdict_cache_size: usize,
+    /// How the workers schedule the transactions of the different priority lanes into batches.
+    #[serde(default = "LaneSchedulingPolicy::default")]
+    pub lane_scheduling_policy: LaneSchedulingPolicy,
+    /// The version of the batches created by the workers. Batches of every version are
+    /// accepted regardless, so V2 and V3 can only be enabled once all the nodes decode them.
+    #[serde(default = "BatchVersion::default")]
+    pub batch_version: BatchVersion,
+    /// The limits on the transactions of the batches created by the workers, when decided by
+    /// the protocol for the epoch. They are not configurable, so that every worker creates
+    /// batches the others accept.
+    #[serde(skip)]
+    pub batch_limits: Option<BatchLimits>,
+    /// Which batches are removed from the batch store once they are no longer needed.
+    #[serde(default = "BatchRetentionPolicy::default")]
+    pub batch_retention_policy: BatchRetentionPolicy,
+    /// How often the batch store is pruned according to `batch_retention_policy`. Zero
+    /// disables the pruning.
+    #[serde(
+        with = "duration_format",
+        default = "Parameters::default_batch_pruning_interval"
+    )]
+    pub batch_pruning_interval: Duration,
+    /// How long the workers wait at the end of an epoch for their pending batches to be
+    /// acknowledged, before handing off their transactions. The handed off transactions are only
+    /// batched again by a worker of the same epoch, and rejected by the workers of the next one.
+    #[serde(
+        with = "duration_format",
+        default = "Parameters::default_worker_drain_timeout"
+    )]
+    pub worker_drain_timeout: Duration,
+    /// How durably the workers log the batches they seal, until they are included.
+    #[serde(default = "TransactionDurability::default")]
+    pub transaction_durability: TransactionDurability,
+    /// The parameters for the admission control of the workers' transaction endpoint
+    #[serde(default = "AdmissionControlParameters::default")]
+    pub admission_control: AdmissionControlParameters,
+    /// The per-client quotas of the workers' transaction endpoint
+    #[serde(default = "ClientQuotaParameters::default")]
+    pub client_quotas: ClientQuotaParameters,
+    /// The parameters for the channels multiplexed on the connections between workers
+    #[serde(default = "WorkerChannelParameters::default")]
+    pub worker_channels: WorkerChannelParameters,
+    /// The parameters for the anti-entropy synchronization of batches between workers
+    #[serde(default = "BatchDiffSyncParameters::default")]
+    pub batch_diff_sync: BatchDiffSyncParameters,
+    /// The parameters for the erasure coded broadcast of large batches between workers
+    #[serde(default = "ErasureCodingParameters::default")]
+    pub erasure_coding: ErasureCodingParameters,
+    /// The hints on the network topology, used to broadcast the batches to the nearby workers first
+    #[serde(default = "TopologyHintsParameters::default")]
+    pub topology_hints: TopologyHintsParameters,
+    /// The parameters for the replication of the batches of a worker to its standby
+    #[serde(default = "WorkerStandbyParameters::default")]
+    pub worker_standby: WorkerStandbyParameters,
+    /// The parameters for the maintenance of the primary's stores
+    #[serde(default = "StoreMaintenanceParameters::default")]
+    pub store_maintenance: StoreMaintenanceParameters,
+    /// The parameters for the batching of the writes to the primary's stores
+    #[serde(default = "WriteBatchingParameters::default")]
+    pub write_batching: WriteBatchingParameters,
+    /// The parameters of the integrity check of the primary's stores on startup
+    #[serde(default = "IntegrityCheckParameters::default")]
+    pub integrity_check: IntegrityCheckParameters,
+    /// The encryption at rest of the batch and certificate stores.
+    #[serde(default = "StorageEncryptionParameters::default")]
+    pub storage_encryption: StorageEncryptionParameters,
+    /// The parameters of the DAG snapshots, served to and fetched by the joining primaries
+    #[serde(default = "DagSnapshotParameters::default")]
+    pub dag_snapshot: DagSnapshotParameters,
     /// The parameters for the block synchronizer
     #[serde(default = "BlockSynchronizerParameters::default")]
     pub block_synchronizer: BlockSynchronizerParameters,
@@ -186,6 +299,10 @@ impl Parameters {
         1_000
     }
 
+    fn default_max_header_num_of_batches_per_worker() -> usize {
+        1_000
+    }
+
     fn default_max_header_delay() -> Duration {
         Duration::from_secs(2)
     }
@@ -206,6 +323,14 @@ impl Parameters {
         3
     }
 
+    fn default_sync_hedge_delay() -> Duration {
+        Duration::from_millis(200)
+    }
+
+    fn default_max_request_batches_response_size() -> usize {
+        6_000_000
+    }
+
     fn default_batch_size() -> usize {
         500_000
     }
@@ -217,6 +342,132 @@ impl Parameters {
     fn default_max_concurrent_requests() -> usize {
         500_000
     }
+
+    fn default_tx_dedup_window() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    fn default_tx_dedup_cache_size() -> usize {
+        100_000
+    }
+
+    fn default_batch_verdict_cache_size() -> usize {
+        10_000
+    }
+
+    fn default_batch_pruning_interval() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    fn default_worker_drain_timeout() -> Duration {
+        Duration::from_secs(5)
+    }
+}
+
+/// When the proposer proposes a header, once it has a quorum of parents. Whatever the policy, a
+/// header is proposed at the latest after `max_header_delay`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderProposalPolicy {
+    /// Propose a header once `header_num_of_batches_threshold` batch digests are available, or
+    /// after `min_header_delay`, possibly with an empty payload.
+    #[default]
+    Eager,
+    /// Additionally wait until at least `min_num_of_batches` batch digests, or batches of at
+    /// least `min_payload_size` bytes in total, are available, to avoid proposing near empty
+    /// headers during low traffic. The leaders of the next round do not wait, so that the
+    /// commits are not delayed.
+    Delayed {
+        min_num_of_batches: usize,
+        min_payload_size: usize,
+    },
+}
+
+/// How the batch maker picks the next transaction to batch when several priority lanes have
+/// pending transactions. Lanes are always visited from the highest (system) to the lowest
+/// (normal) priority.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LaneSchedulingPolicy {
+    /// Always take the next transaction from the highest priority lane that has one. Lower
+    /// priority lanes only make progress when the higher ones are empty.
+    #[default]
+    StrictPriority,
+    /// Take up to the given number of transactions from each lane in turn, so that lower
+    /// priority lanes keep a share of the batching capacity under sustained high priority
+    /// traffic. A weight of zero is treated as one.
+    WeightedRoundRobin { system: u32, high: u32, normal: u32 },
+}
+
+/// How durably the workers log the batches they seal. The transactions of a logged batch are
+/// batched again when the worker restarts, if the batch was not included before it stopped.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionDurability {
+    /// The batches are not logged, and their transactions are lost if the worker stops before
+    /// they are included.
+    #[default]
+    None,
+    /// The batches are logged without waiting for the log to reach the disk. They survive a
+    /// crash of the process, but not of the machine.
+    Async,
+    /// The log is synced to disk before a batch leaves the worker. The batches survive a crash
+    /// of the machine, at the cost of a sync per sealed batch.
+    Fsync,
+}
+
+/// The version of the batches created by the workers.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchVersion {
+    #[default]
+    V1,
+    /// Also records the priority lane of the batch.
+    V2,
+    /// Like V2, with a digest computed as a tree over chunks of the transactions, hashed in
+    /// parallel for the large batches.
+    V3,
+}
+
+/// The limits on the number of batch digests a header references, enforced when creating
+/// headers and when voting for the headers of the other primaries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeaderPayloadLimits {
+    /// The maximum number of batch digests of a header.
+    pub max_num_of_batches: usize,
+    /// The maximum number of batch digests of a single worker in a header.
+    pub max_num_of_batches_per_worker: usize,
+}
+
+/// The limits on the transactions of a batch, enforced when the workers create batches and
+/// when they validate the batches of the other workers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchLimits {
+    /// The maximum number of transactions of a batch.
+    pub max_num_of_transactions: usize,
+    /// The maximum total size (in bytes) of the transactions of a batch.
+    pub max_size: usize,
+}
+
+/// Decides which batches are pruned from the batch store. Pruning is off by default.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchRetentionPolicy {
+    /// Never prune batches.
+    KeepAll,
+    /// Prune the batches referenced by the certificates that are at least this many rounds
+    /// below the garbage collection round. Batches not referenced by any certificate are kept.
+    Rounds(u64),
+    /// Prune the batches referenced by the certificates below the garbage collection round,
+    /// once they were created longer than the given duration ago. Batches not referenced by any
+    /// certificate are kept.
+    Ttl(#[serde(with = "duration_format")] Duration),
+}
+
+impl Default for BatchRetentionPolicy {
+    fn default() -> Self {
+        BatchRetentionPolicy::KeepAll
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -259,12 +510,50 @@ pub struct AnemoParameters {
     pub report_batch_rate_limit: Option<NonZeroU32>,
     pub request_batch_rate_limit: Option<NonZeroU32>,
 
+    /// Budgets (in requests/sec) of the most expensive RPCs. Unlike the rate-limits above, the
+    /// requests over budget are refused as rate limited instead of delayed, so that the peers
+    /// back off.
+    #[serde(default)]
+    pub send_certificate_budget: RateLimitBudget,
+    #[serde(default)]
+    pub fetch_certificates_budget: RateLimitBudget,
+    #[serde(default)]
+    pub request_batches_budget: RateLimitBudget,
+
     /// Size in bytes above which network messages are considered excessively large. Excessively
     /// large messages will still be handled, but logged and reported in metrics for debugging.
     ///
     /// If unspecified, this will default to 8 MiB.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub excessive_message_size: Option<usize>,
+
+    /// Faults injected into the inbound requests, to test the network under adverse conditions.
+    /// Must never be set in production.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fault_injection: Option<FaultInjectionParameters>,
+}
+
+/// The faults injected into the inbound requests, drawn from an RNG seeded with `seed` so that a
+/// run under the simulator is reproducible. The requests delayed by different amounts of time
+/// are reordered.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FaultInjectionParameters {
+    /// The seed of the RNG.
+    pub seed: u64,
+    /// The probability (from 0.0 to 1.0) with which a request is dropped.
+    pub drop_probability: f64,
+    /// The delay of each request is drawn uniformly up to this delay.
+    #[serde(with = "duration_format")]
+    pub max_delay: Duration,
+}
+
+/// The budget of an RPC, in requests/sec. No budget means no limit.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RateLimitBudget {
+    /// The budget of each peer.
+    pub per_peer: Option<NonZeroU32>,
+    /// The budget shared by all the peers.
+    pub per_endpoint: Option<NonZeroU32>,
 }
 
 impl AnemoParameters {
@@ -336,6 +625,522 @@ impl ConsensusAPIGrpcParameters {
     }
 }
 
+/// The thresholds of the adaptive sealing of batches. The workers track a moving average of the
+/// bytes of transactions they receive: under low load the batches are sealed after
+/// `min_batch_delay` instead of `max_batch_delay`, to cut their latency, and under high load
+/// they are sealed at `max_batch_size` instead of `batch_size`, to cut the overhead per
+/// transaction. The thresholds can be changed at runtime through the worker's admin server.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AdaptiveSealingParameters {
+    /// Whether the batch delay and size adapt to the load.
+    #[serde(default = "AdaptiveSealingParameters::default_enabled")]
+    pub enabled: bool,
+    /// The ingress rate below which the worker is under low load. Denominated in bytes/s.
+    #[serde(default = "AdaptiveSealingParameters::default_low_load_bytes_per_second")]
+    pub low_load_bytes_per_second: usize,
+    /// The ingress rate above which the worker is under high load. Denominated in bytes/s.
+    #[serde(default = "AdaptiveSealingParameters::default_high_load_bytes_per_second")]
+    pub high_load_bytes_per_second: usize,
+    /// The delay after which the batches are sealed under low load.
+    #[serde(
+        with = "duration_format",
+        default = "AdaptiveSealingParameters::default_min_batch_delay"
+    )]
+    pub min_batch_delay: Duration,
+    /// The size at which the batches are sealed under high load. Denominated in bytes.
+    #[serde(default = "AdaptiveSealingParameters::default_max_batch_size")]
+    pub max_batch_size: usize,
+    /// The half life of the moving average of the ingress rate.
+    #[serde(
+        with = "duration_format",
+        default = "AdaptiveSealingParameters::default_rate_half_life"
+    )]
+    pub rate_half_life: Duration,
+}
+
+impl AdaptiveSealingParameters {
+    fn default_enabled() -> bool {
+        false
+    }
+    fn default_low_load_bytes_per_second() -> usize {
+        1_000_000
+    }
+    fn default_high_load_bytes_per_second() -> usize {
+        50_000_000
+    }
+    fn default_min_batch_delay() -> Duration {
+        Duration::from_millis(10)
+    }
+    fn default_max_batch_size() -> usize {
+        2_000_000
+    }
+    fn default_rate_half_life() -> Duration {
+        Duration::from_secs(1)
+    }
+}
+
+impl Default for AdaptiveSealingParameters {
+    fn default() -> Self {
+        Self {
+            enabled: AdaptiveSealingParameters::default_enabled(),
+            low_load_bytes_per_second: AdaptiveSealingParameters::default_low_load_bytes_per_second(
+            ),
+            high_load_bytes_per_second:
+                AdaptiveSealingParameters::default_high_load_bytes_per_second(),
+            min_batch_delay: AdaptiveSealingParameters::default_min_batch_delay(),
+            max_batch_size: AdaptiveSealingParameters::default_max_batch_size(),
+            rate_half_life: AdaptiveSealingParameters::default_rate_half_life(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AdmissionControlParameters {
+    /// The maximum number of transaction bytes accepted by a worker but not yet included in a
+    /// batch acknowledged by a quorum. Transactions beyond it are rejected as overloaded.
+    #[serde(default = "AdmissionControlParameters::default_max_pending_batch_bytes")]
+    pub max_pending_batch_bytes: usize,
+    /// The maximum number of sealed batches waiting for a quorum of workers to store them.
+    /// Transactions are rejected as overloaded while it is reached.
+    #[serde(default = "AdmissionControlParameters::default_max_inflight_quorum_waits")]
+    pub max_inflight_quorum_waits: usize,
+    /// The delay after which the clients of an overloaded worker are told to retry.
+    #[serde(
+        with = "duration_format",
+        default = "AdmissionControlParameters::default_retry_after"
+    )]
+    pub retry_after: Duration,
+    /// The maximum number of transaction bytes spilled to disk while the worker is overloaded.
+    /// The spilled transactions are accepted instead of being rejected, and are batched in the
+    /// order they were spilled once the load subsides. Transactions are rejected as overloaded
+    /// once the bound is reached. Spilling is disabled when zero.
+    #[serde(default = "AdmissionControlParameters::default_max_spilled_bytes")]
+    pub max_spilled_bytes: usize,
+}
+
+impl AdmissionControlParameters {
+    fn default_max_pending_batch_bytes() -> usize {
+        64 * 1024 * 1024
+    }
+    fn default_max_inflight_quorum_waits() -> usize {
+        100
+    }
+    fn default_retry_after() -> Duration {
+        Duration::from_millis(500)
+    }
+    fn default_max_spilled_bytes() -> usize {
+        0
+    }
+}
+
+impl Default for AdmissionControlParameters {
+    fn default() -> Self {
+        Self {
+            max_pending_batch_bytes: AdmissionControlParameters::default_max_pending_batch_bytes(),
+            max_inflight_quorum_waits:
+                AdmissionControlParameters::default_max_inflight_quorum_waits(),
+            retry_after: AdmissionControlParameters::default_retry_after(),
+            max_spilled_bytes: AdmissionControlParameters::default_max_spilled_bytes(),
+        }
+    }
+}
+
+/// The quotas of each client of the workers' transaction endpoint, so that a single client
+/// cannot monopolize the batch bandwidth. Clients are identified by their IP address, and each
+/// quota is a token bucket refilled at the quota rate.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClientQuotaParameters {
+    /// The number of transactions per second a client may submit. Unlimited if not set.
+    pub max_transactions_per_second: Option<NonZeroU32>,
+    /// The number of transaction bytes per second a client may submit. Unlimited if not set.
+    pub max_bytes_per_second: Option<NonZeroU32>,
+    /// How long a client may submit at the quota rates in a single burst, after being idle.
+    #[serde(
+        with = "duration_format",
+        default = "ClientQuotaParameters::default_burst_duration"
+    )]
+    pub burst_duration: Duration,
+    /// The maximum number of clients whose quotas are tracked. The least recently seen
+    /// clients are forgotten beyond it.
+    #[serde(default = "ClientQuotaParameters::default_max_tracked_clients")]
+    pub max_tracked_clients: usize,
+}
+
+impl ClientQuotaParameters {
+    fn default_burst_duration() -> Duration {
+        Duration::from_secs(1)
+    }
+    fn default_max_tracked_clients() -> usize {
+        10_000
+    }
+}
+
+impl Default for ClientQuotaParameters {
+    fn default() -> Self {
+        Self {
+            max_transactions_per_second: None,
+            max_bytes_per_second: None,
+            burst_duration: ClientQuotaParameters::default_burst_duration(),
+            max_tracked_clients: ClientQuotaParameters::default_max_tracked_clients(),
+        }
+    }
+}
+
+/// The capacity of the channels multiplexed on the connection between two workers. The
+/// broadcasts of batches are bounded, so that they do not delay the batch requests and their
+/// responses.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WorkerChannelParameters {
+    /// The maximum number of bytes of batch broadcasts in flight to a single peer.
+    #[serde(default = "WorkerChannelParameters::default_max_inflight_broadcast_bytes_per_peer")]
+    pub max_inflight_broadcast_bytes_per_peer: usize,
+    /// The maximum number of batch requests in flight to a single peer.
+    #[serde(default = "WorkerChannelParameters::default_max_concurrent_requests_per_peer")]
+    pub max_concurrent_requests_per_peer: usize,
+}
+
+impl WorkerChannelParameters {
+    fn default_max_inflight_broadcast_bytes_per_peer() -> usize {
+        100 << 20
+    }
+    fn default_max_concurrent_requests_per_peer() -> usize {
+        1_000
+    }
+}
+
+impl Default for WorkerChannelParameters {
+    fn default() -> Self {
+        Self {
+            max_inflight_broadcast_bytes_per_peer:
+                WorkerChannelParameters::default_max_inflight_broadcast_bytes_per_peer(),
+            max_concurrent_requests_per_peer:
+                WorkerChannelParameters::default_max_concurrent_requests_per_peer(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BatchDiffSyncParameters {
+    /// How often a worker compares its recent batches with those of a random peer worker, and
+    /// fetches the ones it is missing. Zero disables the sync.
+    #[serde(
+        with = "duration_format",
+        default = "BatchDiffSyncParameters::default_interval"
+    )]
+    pub interval: Duration,
+    /// How far back (by the time they were stored) the batches are compared.
+    #[serde(
+        with = "duration_format",
+        default = "BatchDiffSyncParameters::default_window"
+    )]
+    pub window: Duration,
+    /// The maximum number of missing batches fetched from a peer in a single round.
+    #[serde(default = "BatchDiffSyncParameters::default_max_missing_batches")]
+    pub max_missing_batches: usize,
+    /// The diffs each peer may request per second. The requests over it are refused as rate
+    /// limited, a peer only needing one per interval.
+    #[serde(default = "BatchDiffSyncParameters::default_max_requests_per_peer")]
+    pub max_requests_per_peer: NonZeroU32,
+}
+
+impl BatchDiffSyncParameters {
+    fn default_interval() -> Duration {
+        Duration::from_secs(10)
+    }
+    fn default_window() -> Duration {
+        Duration::from_secs(60)
+    }
+    fn default_max_missing_batches() -> usize {
+        1_000
+    }
+    fn default_max_requests_per_peer() -> NonZeroU32 {
+        NonZeroU32::new(1).unwrap()
+    }
+}
+
+impl Default for BatchDiffSyncParameters {
+    fn default() -> Self {
+        Self {
+            interval: BatchDiffSyncParameters::default_interval(),
+            window: BatchDiffSyncParameters::default_window(),
+            max_missing_batches: BatchDiffSyncParameters::default_max_missing_batches(),
+            max_requests_per_peer: BatchDiffSyncParameters::default_max_requests_per_peer(),
+        }
+    }
+}
+
+/// The erasure coded broadcast of large batches: instead of sending the whole batch to every
+/// other worker, the worker sends each of them a different coded chunk, which they forward to
+/// each other to reconstruct the batch. Any large enough subset of the chunks reconstructs the
+/// batch, so the egress of the originating worker is a small multiple of the batch size rather
+/// than a multiple of the number of workers.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ErasureCodingParameters {
+    /// Whether the large batches are broadcast as erasure coded chunks. All the workers must
+    /// support receiving chunks before it is enabled on any of them.
+    #[serde(default = "ErasureCodingParameters::default_enabled")]
+    pub enabled: bool,
+    /// The size in bytes from which a batch is broadcast as erasure coded chunks.
+    #[serde(default = "ErasureCodingParameters::default_min_batch_size")]
+    pub min_batch_size: usize,
+    /// How long a worker waits for the chunks forwarded by the others to reconstruct a batch,
+    /// before failing the broadcast of its chunk, which the originator then retries.
+    #[serde(
+        with = "duration_format",
+        default = "ErasureCodingParameters::default_reconstruction_timeout"
+    )]
+    pub reconstruction_timeout: Duration,
+}
+
+impl ErasureCodingParameters {
+    fn default_enabled() -> bool {
+        false
+    }
+    fn default_min_batch_size() -> usize {
+        1_000_000
+    }
+    fn default_reconstruction_timeout() -> Duration {
+        Duration::from_secs(5)
+    }
+}
+
+impl Default for ErasureCodingParameters {
+    fn default() -> Self {
+        Self {
+            enabled: ErasureCodingParameters::default_enabled(),
+            min_batch_size: ErasureCodingParameters::default_min_batch_size(),
+            reconstruction_timeout: ErasureCodingParameters::default_reconstruction_timeout(),
+        }
+    }
+}
+
+/// Hints on the network topology of the committee, for large validators to save on the egress
+/// across regions. The authorities are grouped by region: a worker broadcasts its batches directly
+/// to the workers of its own group and of no group, but sends each batch to a single worker of
+/// each of the other groups, which relays it to the rest of its group and reports back the
+/// workers which acknowledged it. The workers not reported are then sent the batch directly.
+///
+/// The acknowledgements relayed are trusted for the quorum of the broadcast, which only decides
+/// when the batch is proposed: a relay misreporting them can only delay the other primaries,
+/// which then fetch the batch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TopologyHintsParameters {
+    /// The authorities in each region, by region name. Empty by default, broadcasting each batch
+    /// directly to all the workers.
+    pub peer_groups: BTreeMap<String, Vec<PublicKey>>,
+    /// How long a relay waits for the workers of its group to acknowledge a batch, before
+    /// reporting back the ones which did.
+    #[serde(
+        with = "duration_format",
+        default = "TopologyHintsParameters::default_relay_ack_timeout"
+    )]
+    pub relay_ack_timeout: Duration,
+}
+
+impl TopologyHintsParameters {
+    fn default_relay_ack_timeout() -> Duration {
+        Duration::from_secs(5)
+    }
+
+    /// The name of the group of the authority, if any.
+    pub fn group_of(&self, authority: &PublicKey) -> Option<&str> {
+        self.peer_groups
+            .iter()
+            .find(|(_, members)| members.contains(authority))
+            .map(|(group, _)| group.as_str())
+    }
+}
+
+impl Default for TopologyHintsParameters {
+    fn default() -> Self {
+        Self {
+            peer_groups: BTreeMap::new(),
+            relay_ack_timeout: TopologyHintsParameters::default_relay_ack_timeout(),
+        }
+    }
+}
+
+/// The maintenance of the header, certificate and batch stores of the primary.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StoreMaintenanceParameters {
+    /// How often the statistics of the column families are reported as metrics. Zero disables
+    /// the reports.
+    #[serde(
+        with = "duration_format",
+        default = "StoreMaintenanceParameters::default_stats_interval"
+    )]
+    pub stats_interval: Duration,
+    /// How often the column families are compacted. Zero disables the scheduled compactions,
+    /// which can still be triggered through the admin interface.
+    #[serde(
+        with = "duration_format",
+        default = "StoreMaintenanceParameters::default_compaction_interval"
+    )]
+    pub compaction_interval: Duration,
+}
+
+impl StoreMaintenanceParameters {
+    fn default_stats_interval() -> Duration {
+        Duration::from_secs(30)
+    }
+    fn default_compaction_interval() -> Duration {
+        Duration::ZERO
+    }
+}
+
+impl Default for StoreMaintenanceParameters {
+    fn default() -> Self {
+        Self {
+            stats_interval: StoreMaintenanceParameters::default_stats_interval(),
+            compaction_interval: StoreMaintenanceParameters::default_compaction_interval(),
+        }
+    }
+}
+
+/// The batching of the writes of the payload availability entries of the primary, which are
+/// otherwise written one key at a time: the entries written concurrently are committed together
+/// in a single batch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WriteBatchingParameters {
+    /// Whether the writes are batched.
+    pub enabled: bool,
+    /// The maximum number of entries committed in a single batch.
+    pub max_batch_size: usize,
+    /// Whether a write returns once queued, instead of once committed. An entry may then not be
+    /// readable yet when its write returns, and is lost if the node crashes before it is
+    /// committed.
+    pub async_flush: bool,
+}
+
+impl Default for WriteBatchingParameters {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_batch_size: 1_000,
+            async_flush: false,
+        }
+    }
+}
+
+/// The cross-check of the certificate, payload and batch stores run by the primary on startup.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IntegrityCheckParameters {
+    /// Whether the stores are checked before the primary starts.
+    pub enabled: bool,
+    /// Whether the payload entries are checked against the batch store. Only meaningful when the
+    /// workers share the storage of the primary, as in a Sui validator.
+    pub verify_batch_store: bool,
+}
+
+impl Default for IntegrityCheckParameters {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            verify_batch_store: false,
+        }
+    }
+}
+
+/// The replication of the batch store of a worker to a standby worker process, which holds the
+/// same network key and takes over the network identity of the worker when it stops responding.
+/// The standby replicates under a key derived from it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WorkerStandbyParameters {
+    /// The number of recently stored batches the worker keeps track of, for its standby to
+    /// replicate. Zero, the default, disables the replication.
+    pub replication_log_size: usize,
+    /// The maximum number of batches replicated by the standby in a single request.
+    pub max_batches_per_request: usize,
+    /// How often the standby requests the batches stored by the worker since its last request.
+    #[serde(
+        with = "duration_format",
+        default = "WorkerStandbyParameters::default_replication_interval"
+    )]
+    pub replication_interval: Duration,
+    /// How long the worker must stop responding to its standby before the standby takes over.
+    #[serde(
+        with = "duration_format",
+        default = "WorkerStandbyParameters::default_failover_timeout"
+    )]
+    pub failover_timeout: Duration,
+}
+
+impl WorkerStandbyParameters {
+    fn default_replication_interval() -> Duration {
+        Duration::from_millis(200)
+    }
+    fn default_failover_timeout() -> Duration {
+        Duration::from_secs(3)
+    }
+}
+
+impl Default for WorkerStandbyParameters {
+    fn default() -> Self {
+        Self {
+            replication_log_size: 0,
+            max_batches_per_request: 100,
+            replication_interval: WorkerStandbyParameters::default_replication_interval(),
+            failover_timeout: WorkerStandbyParameters::default_failover_timeout(),
+        }
+    }
+}
+
+/// The encryption at rest of the values of the batch and certificate stores, for the nodes which
+/// cannot rely on the encryption of their disks.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StorageEncryptionParameters {
+    /// The file holding the hex encoded 256-bit AES-GCM key, provisioned by the KMS of the node.
+    /// The values are stored in the clear when unset. The stores of a node must always be opened
+    /// with the same key.
+    pub key_file: Option<PathBuf>,
+}
+
+/// The snapshots of the DAG, from which a primary joining with empty stores can bootstrap
+/// instead of replaying the DAG from genesis.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DagSnapshotParameters {
+    /// Whether a primary starting with empty stores bootstraps from a snapshot of its peers.
+    pub bootstrap: bool,
+    /// The maximum number of certificates above the last committed round in the snapshots
+    /// served to the peers.
+    pub max_certificates: usize,
+    /// The timeout when requesting a snapshot from a peer.
+    #[serde(
+        with = "duration_format",
+        default = "DagSnapshotParameters::default_request_timeout"
+    )]
+    pub request_timeout: Duration,
+}
+
+impl DagSnapshotParameters {
+    fn default_request_timeout() -> Duration {
+        Duration::from_secs(30)
+    }
+}
+
+impl Default for DagSnapshotParameters {
+    fn default() -> Self {
+        Self {
+            bootstrap: false,
+            max_certificates: 10_000,
+            request_timeout: DagSnapshotParameters::default_request_timeout(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct BlockSynchronizerParameters {
@@ -418,13 +1223,43 @@ impl Default for Parameters {
         Self {
             header_num_of_batches_threshold: Parameters::default_header_num_of_batches_threshold(),
             max_header_num_of_batches: Parameters::default_max_header_num_of_batches(),
+            max_header_num_of_batches_per_worker:
+                Parameters::default_max_header_num_of_batches_per_worker(),
             max_header_delay: Parameters::default_max_header_delay(),
             min_header_delay: Parameters::default_min_header_delay(),
+            header_proposal_policy: HeaderProposalPolicy::default(),
+            leader_swaps: false,
             gc_depth: Parameters::default_gc_depth(),
             sync_retry_delay: Parameters::default_sync_retry_delay(),
             sync_retry_nodes: Parameters::default_sync_retry_nodes(),
+            sync_hedge_delay: Parameters::default_sync_hedge_delay(),
+            max_request_batches_response_size:
+                Parameters::default_max_request_batches_response_size(),
             batch_size: Parameters::default_batch_size(),
             max_batch_delay: Parameters::default_max_batch_delay(),
+            adaptive_sealing: AdaptiveSealingParameters::default(),
+            tx_dedup_window: Parameters::default_tx_dedup_window(),
+            tx_dedup_cache_size: Parameters::default_tx_dedup_cache_size(),
+            batch_verdict_cache_size: Parameters::default_batch_verdict_cache_size(),
+            lane_scheduling_policy: LaneSchedulingPolicy::default(),
+            batch_version: BatchVersion::default(),
+            batch_limits: None,
+            batch_retention_policy: BatchRetentionPolicy::default(),
+            batch_pruning_interval: Parameters::default_batch_pruning_interval(),
+            worker_drain_timeout: Parameters::default_worker_drain_timeout(),
+            transaction_durability: TransactionDurability::default(),
+            admission_control: AdmissionControlParameters::default(),
+            client_quotas: ClientQuotaParameters::default(),
+            worker_channels: WorkerChannelParameters::default(),
+            batch_diff_sync: BatchDiffSyncParameters::default(),
+            erasure_coding: ErasureCodingParameters::default(),
+            topology_hints: TopologyHintsParameters::default(),
+            worker_standby: WorkerStandbyParameters::default(),
+            store_maintenance: StoreMaintenanceParameters::default(),
+            write_batching: WriteBatchingParameters::default(),
+            integrity_check: IntegrityCheckParameters::default(),
+            storage_encryption: StorageEncryptionParameters::default(),
+            dag_snapshot: DagSnapshotParameters::default(),
             block_synchronizer: BlockSynchronizerParameters::default(),
             consensus_api_grpc: ConsensusAPIGrpcParameters::default(),
             max_concurrent_requests: Parameters::default_max_concurrent_requests(),
@@ -444,6 +1279,28 @@ impl Parameters {
         params
     }
 
+    pub fn header_payload_limits(&self) -> HeaderPayloadLimits {
+        HeaderPayloadLimits {
+            max_num_of_batches: self.max_header_num_of_batches,
+            max_num_of_batches_per_worker: self.max_header_num_of_batches_per_worker,
+        }
+    }
+
+    /// Sets the limits on the batch digests of the headers, e.g. from the protocol.
+    pub fn set_header_payload_limits(&mut self, limits: HeaderPayloadLimits) {
+        self.max_header_num_of_batches = limits.max_num_of_batches;
+        self.max_header_num_of_batches_per_worker = limits.max_num_of_batches_per_worker;
+    }
+
+    /// Sets the parameters which the running components read at runtime, from the given ones:
+    /// the maximum delays of the batches and the headers, and the number of nodes to retry the
+    /// batch requests with. The other parameters only apply when the node is started.
+    pub fn set_runtime_parameters(&mut self, parameters: &Parameters) {
+        self.max_batch_delay = parameters.max_batch_delay;
+        self.max_header_delay = parameters.max_header_delay;
+        self.sync_retry_nodes = parameters.sync_retry_nodes;
+    }
+
     pub fn tracing(&self) {
         info!(
             "Header number of batches threshold set to {}",
@@ -453,6 +1310,10 @@ impl Parameters {
             "Header max number of batches set to {}",
             self.max_header_num_of_batches
         );
+        info!(
+            "Header max number of batches per worker set to {}",
+            self.max_header_num_of_batches_per_worker
+        );
         info!(
             "Max header delay set to {} ms",
             self.max_header_delay.as_millis()
@@ -461,17 +1322,190 @@ impl Parameters {
             "Min header delay set to {} ms",
             self.min_header_delay.as_millis()
         );
+        info!(
+            "Header proposal policy set to {:?}",
+            self.header_proposal_policy
+        );
+        info!("Leader swaps set to {}", self.leader_swaps);
         info!("Garbage collection depth set to {} rounds", self.gc_depth);
         info!(
             "Sync retry delay set to {} ms",
             self.sync_retry_delay.as_millis()
         );
         info!("Sync retry nodes set to {} nodes", self.sync_retry_nodes);
+        info!(
+            "Sync hedge delay set to {} ms",
+            self.sync_hedge_delay.as_millis()
+        );
+        info!(
+            "Max request batches response size set to {} B",
+            self.max_request_batches_response_size
+        );
         info!("Batch size set to {} B", self.batch_size);
         info!(
             "Max batch delay set to {} ms",
             self.max_batch_delay.as_millis()
         );
+        info!(
+            "Adaptive sealing enabled set to {}",
+            self.adaptive_sealing.enabled
+        );
+        info!(
+            "Adaptive sealing low load threshold set to {} B/s",
+            self.adaptive_sealing.low_load_bytes_per_second
+        );
+        info!(
+            "Adaptive sealing high load threshold set to {} B/s",
+            self.adaptive_sealing.high_load_bytes_per_second
+        );
+        info!(
+            "Adaptive sealing min batch delay set to {} ms",
+            self.adaptive_sealing.min_batch_delay.as_millis()
+        );
+        info!(
+            "Adaptive sealing max batch size set to {} B",
+            self.adaptive_sealing.max_batch_size
+        );
+        info!(
+            "Adaptive sealing ingress rate half life set to {} ms",
+            self.adaptive_sealing.rate_half_life.as_millis()
+        );
+        info!(
+            "Transaction dedup window set to {} ms",
+            self.tx_dedup_window.as_millis()
+        );
+        info!(
+            "Transaction dedup cache size set to {} digests",
+            self.tx_dedup_cache_size
+        );
+        info!(
+            "Batch verdict cache size set to {} batches",
+            self.batch_verdict_cache_size
+        );
+        info!(
+            "Lane scheduling policy set to {:?}",
+            self.lane_scheduling_policy
+        );
+        info!("Batch version set to {:?}", self.batch_version);
+        info!("Batch limits set to {:?}", self.batch_limits);
+        info!(
+            "Batch retention policy set to {:?}",
+            self.batch_retention_policy
+        );
+        info!(
+            "Batch pruning interval set to {} ms",
+            self.batch_pruning_interval.as_millis()
+        );
+        info!(
+            "Worker drain timeout set to {} ms",
+            self.worker_drain_timeout.as_millis()
+        );
+        info!(
+            "Transaction durability set to {:?}",
+            self.transaction_durability
+        );
+        info!(
+            "Admission control max pending batch bytes set to {} B",
+            self.admission_control.max_pending_batch_bytes
+        );
+        info!(
+            "Admission control max in-flight quorum waits set to {}",
+            self.admission_control.max_inflight_quorum_waits
+        );
+        info!(
+            "Admission control retry after set to {} ms",
+            self.admission_control.retry_after.as_millis()
+        );
+        info!(
+            "Admission control max spilled bytes set to {} B",
+            self.admission_control.max_spilled_bytes
+        );
+        info!(
+            "Client quota max transactions per second set to {:?}",
+            self.client_quotas.max_transactions_per_second
+        );
+        info!(
+            "Client quota max bytes per second set to {:?}",
+            self.client_quotas.max_bytes_per_second
+        );
+        info!(
+            "Client quota burst duration set to {} ms",
+            self.client_quotas.burst_duration.as_millis()
+        );
+        info!(
+            "Client quota max tracked clients set to {}",
+            self.client_quotas.max_tracked_clients
+        );
+        info!(
+            "Worker channels max in-flight broadcast bytes per peer set to {} B",
+            self.worker_channels.max_inflight_broadcast_bytes_per_peer
+        );
+        info!(
+            "Worker channels max concurrent requests per peer set to {}",
+            self.worker_channels.max_concurrent_requests_per_peer
+        );
+        info!(
+            "Batch diff sync interval set to {} ms",
+            self.batch_diff_sync.interval.as_millis()
+        );
+        info!(
+            "Batch diff sync window set to {} ms",
+            self.batch_diff_sync.window.as_millis()
+        );
+        info!(
+            "Batch diff sync max missing batches set to {}",
+            self.batch_diff_sync.max_missing_batches
+        );
+        info!(
+            "Batch diff sync max requests per peer set to {}/s",
+            self.batch_diff_sync.max_requests_per_peer
+        );
+        info!(
+            "Erasure coded broadcast of batches {}, from {} B, reconstruction timeout {} ms",
+            if self.erasure_coding.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            },
+            self.erasure_coding.min_batch_size,
+            self.erasure_coding.reconstruction_timeout.as_millis()
+        );
+        info!(
+            "Topology hints set to {} peer groups, relay ack timeout {} ms",
+            self.topology_hints.peer_groups.len(),
+            self.topology_hints.relay_ack_timeout.as_millis()
+        );
+        info!(
+            "Worker standby replication log size set to {}, failover timeout {} ms",
+            self.worker_standby.replication_log_size,
+            self.worker_standby.failover_timeout.as_millis()
+        );
+        info!(
+            "Store maintenance stats interval set to {} ms",
+            self.store_maintenance.stats_interval.as_millis()
+        );
+        info!(
+            "Store maintenance compaction interval set to {} ms",
+            self.store_maintenance.compaction_interval.as_millis()
+        );
+        info!(
+            "Write batching enabled: {}, max batch size {}, async flush: {}",
+            self.write_batching.enabled,
+            self.write_batching.max_batch_size,
+            self.write_batching.async_flush
+        );
+        info!(
+            "Startup integrity check enabled: {}, verifying the batch store: {}",
+            self.integrity_check.enabled, self.integrity_check.verify_batch_store
+        );
+        info!(
+            "Storage encryption enabled: {}",
+            self.storage_encryption.key_file.is_some()
+        );
+        info!(
+            "DAG snapshot bootstrap enabled: {}, serving up to {} certificates",
+            self.dag_snapshot.bootstrap, self.dag_snapshot.max_certificates
+        );
         info!(
             "Synchronize range timeout set to {} s",
             self.block_synchronizer.range_synchronize_timeout.as_secs()
@@ -547,12 +1581,41 @@ pub struct WorkerInfo {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct WorkerIndex(pub BTreeMap<WorkerId, WorkerInfo>);
 
+impl WorkerIndex {
+    /// Returns the worker assigned to the transactions of the sender, by hashing its address
+    /// over the workers in the order of their ids.
+    pub fn worker_for_sender(&self, sender: &[u8]) -> Option<(WorkerId, &WorkerInfo)> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let digest = Blake2b256::digest(sender).digest;
+        let hash = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        let index = (hash % self.0.len() as u64) as usize;
+        self.0.iter().nth(index).map(|(id, worker)| (*id, worker))
+    }
+}
+
+/// How the transactions are assigned to the workers of an authority.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
+pub enum WorkerSharding {
+    /// The clients submit their transactions to any worker.
+    #[default]
+    Any,
+    /// The transactions of a sender are submitted to the worker assigned to it by
+    /// `WorkerIndex::worker_for_sender`, so that they are ordered within that worker. The
+    /// workers reject the transactions of the senders assigned to other workers.
+    BySender,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct WorkerCache {
     /// The authority to worker index.
     pub workers: BTreeMap<PublicKey, WorkerIndex>,
     /// The epoch number for workers
     pub epoch: Epoch,
+    /// How the transactions are assigned to the workers.
+    #[serde(default)]
+    pub sharding: WorkerSharding,
 }
 
 impl std::fmt::Display for WorkerIndex {
@@ -609,6 +1672,23 @@ impl WorkerCache {
             .ok_or_else(|| ConfigError::NotInWorkerCache((*to).encode_base64()))
     }
 
+    /// Returns the worker of the authority (`to`) the transactions of the sender should be
+    /// submitted to. Any worker accepts them unless the transactions are sharded by sender.
+    pub fn worker_for_sender(
+        &self,
+        to: &PublicKey,
+        sender: &[u8],
+    ) -> Result<(WorkerId, WorkerInfo), ConfigError> {
+        let index = self
+            .workers
+            .get(to)
+            .ok_or_else(|| ConfigError::NotInWorkerCache((*to).encode_base64()))?;
+        index
+            .worker_for_sender(sender)
+            .map(|(id, worker)| (id, worker.clone()))
+            .ok_or_else(|| ConfigError::NotInWorkerCache((*to).encode_base64()))
+    }
+
     /// Returns the addresses of all our workers.
     pub fn our_workers(&self, myself: &PublicKey) -> Result<Vec<WorkerInfo>, ConfigError> {
         let res = self
@@ -28,7 +28,7 @@ use mysten_network::Multiaddr;
 use narwhal_config as config;
 use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fs::File,
     io::Write,
 };
@@ -261,3 +261,30 @@ fn workers_snapshot_matches() {
     settings.set_sort_maps(true);
     settings.bind(|| assert_json_snapshot!("worker_cache", worker_cache));
 }
+
+#[test]
+fn senders_are_sharded_across_workers() {
+    let fixture = CommitteeFixture::builder().build();
+    let worker_cache = fixture.worker_cache();
+    let authorities: Vec<_> = fixture.authorities().map(|a| a.public_key()).collect();
+
+    let mut assigned = HashSet::new();
+    for sender in 0u32..100 {
+        let sender = sender.to_le_bytes();
+        let (id, worker) = worker_cache
+            .worker_for_sender(&authorities[0], &sender)
+            .unwrap();
+        assert_eq!(worker, worker_cache.worker(&authorities[0], &id).unwrap());
+
+        // The assignment is stable, and the same for every authority.
+        for authority in &authorities {
+            let (other_id, _) = worker_cache.worker_for_sender(authority, &sender).unwrap();
+            assert_eq!(other_id, id);
+        }
+        assigned.insert(id);
+    }
+    assert_eq!(
+        assigned.len(),
+        worker_cache.workers[&authorities[0]].0.len()
+    );
+}
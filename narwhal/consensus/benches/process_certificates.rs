@@ -4,6 +4,7 @@
 use consensus::{
     bullshark::Bullshark,
     consensus::{ConsensusProtocol, ConsensusState},
+    leader_schedule::{LeaderSchedule, LeaderSwapTable},
     metrics::ConsensusMetrics,
 };
 use criterion::{
@@ -60,6 +61,7 @@ pub fn process_certificates(c: &mut Criterion) {
             last_leader_election: Default::default(),
             max_inserted_certificate_round: 0,
             num_sub_dags_per_schedule: 100,
+            leader_schedule: LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         };
         consensus_group.bench_with_input(
             BenchmarkId::new("batched", certificates.len()),
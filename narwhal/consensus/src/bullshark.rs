@@ -3,7 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 use crate::metrics::ConsensusMetrics;
 use crate::{
-    consensus::{ConsensusProtocol, ConsensusState, Dag},
+    consensus::{ConsensusProtocol, ConsensusState},
+    leader_schedule::{LeaderSchedule, LeaderSwapTable, DEFAULT_BAD_NODES_STAKE_THRESHOLD},
     utils, ConsensusError, Outcome,
 };
 use config::{AuthorityIdentifier, Committee, Stake};
@@ -12,8 +13,8 @@ use std::sync::Arc;
 use tokio::time::Instant;
 use tracing::{debug, error_span};
 use types::{
-    Certificate, CertificateAPI, CertificateDigest, CommittedSubDag, ConsensusStore, HeaderAPI,
-    ReputationScores, Round,
+    Certificate, CertificateAPI, CommittedSubDag, ConsensusStore, HeaderAPI, ReputationScores,
+    Round,
 };
 
 #[cfg(test)]
@@ -55,6 +56,9 @@ pub struct Bullshark {
     /// The number of committed subdags that will trigger the schedule change and reputation
     /// score reset.
     pub num_sub_dags_per_schedule: u64,
+    /// The leader schedule, which swaps out the leaders with a low reputation. It is shared with
+    /// the proposer so it can anticipate the leaders elected here.
+    pub leader_schedule: LeaderSchedule,
 }
 
 impl ConsensusProtocol for Bullshark {
@@ -107,7 +111,9 @@ impl ConsensusProtocol for Bullshark {
         if leader_round <= state.last_round.committed_round {
             return Ok((Outcome::LeaderBelowCommitRound, Vec::new()));
         }
-        let (leader_digest, leader) = match Self::leader(&self.committee, leader_round, &state.dag)
+        let (leader_digest, leader) = match self
+            .leader_schedule
+            .leader_certificate(leader_round, &state.dag)
         {
             Some(x) => x,
             None => {
@@ -151,10 +157,12 @@ impl ConsensusProtocol for Bullshark {
         let mut total_committed_certificates = 0;
 
         // TODO: duplicated in tusk.rs
-        for leader in utils::order_leaders(&self.committee, leader, state, Self::leader)
-            .iter()
-            .rev()
-        {
+        let leader_schedule = &self.leader_schedule;
+        let leaders_to_commit =
+            utils::order_leaders(&self.committee, leader, state, |_, round, dag| {
+                leader_schedule.leader_certificate(round, dag)
+            });
+        for leader in leaders_to_commit.iter().rev() {
             let sub_dag_index = state.latest_sub_dag_index + 1;
             let _span = error_span!("bullshark_process_sub_dag", sub_dag_index);
 
@@ -181,11 +189,17 @@ impl ConsensusProtocol for Bullshark {
             // We update the reputation score stored in state
             let reputation_score = self.update_reputation_score(state, &sequence, sub_dag_index);
 
+            let commit_timestamp = CommittedSubDag::compute_commit_timestamp(
+                leader,
+                &sequence,
+                state.last_commit_timestamp,
+            );
             let sub_dag = CommittedSubDag {
                 certificates: sequence,
                 leader: leader.clone(),
                 sub_dag_index,
                 reputation_score,
+                commit_timestamp,
             };
 
             // Persist the update.
@@ -194,9 +208,28 @@ impl ConsensusProtocol for Bullshark {
 
             // Increase the global consensus index.
             state.latest_sub_dag_index = sub_dag_index;
+            state.last_commit_timestamp = commit_timestamp;
             state.last_committed_leader = Some(sub_dag.leader.digest());
 
+            // When the reputation scores of the schedule are final, we switch to the new leader
+            // schedule and stop committing. The remaining leaders were elected with the previous
+            // schedule, so they are re-evaluated against the new one on the next certificates.
+            let final_of_schedule = sub_dag.reputation_score.final_of_schedule;
+            if final_of_schedule {
+                self.leader_schedule
+                    .update_leader_schedule(LeaderSwapTable::new(
+                        &self.committee,
+                        sub_dag.sub_dag_index,
+                        &sub_dag.reputation_score,
+                        DEFAULT_BAD_NODES_STAKE_THRESHOLD,
+                    ));
+            }
+
             committed_sub_dags.push(sub_dag);
+
+            if final_of_schedule {
+                break;
+            }
         }
 
         // record the last time we got a successful leader election
@@ -246,6 +279,7 @@ impl Bullshark {
         store: Arc<ConsensusStore>,
         metrics: Arc<ConsensusMetrics>,
         num_sub_dags_per_schedule: u64,
+        leader_schedule: LeaderSchedule,
     ) -> Self {
         Self {
             committee,
@@ -255,6 +289,7 @@ impl Bullshark {
             max_inserted_certificate_round: 0,
             metrics,
             num_sub_dags_per_schedule,
+            leader_schedule,
         }
     }
 
@@ -286,22 +321,6 @@ impl Bullshark {
         }
     }
 
-    // TODO: duplicated in tusk.rs
-    /// Returns the certificate (and the certificate's digest) originated by the leader of the
-    /// specified round (if any).
-    fn leader<'a>(
-        committee: &Committee,
-        round: Round,
-        dag: &'a Dag,
-    ) -> Option<&'a (CertificateDigest, Certificate)> {
-        // Note: this function is often called with even rounds only. While we do not aim at random selection
-        // yet (see issue #10), repeated calls to this function should still pick from the whole roster of leaders.
-        let leader = Self::leader_authority(committee, round);
-
-        // Return its certificate and the certificate's digest.
-        dag.get(&round).and_then(|x| x.get(&leader))
-    }
-
     /// Updates and calculates the reputation score for the current commit managing any internal state.
     /// It returns the updated reputation score.
     fn update_reputation_score(
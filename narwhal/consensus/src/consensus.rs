@@ -8,6 +8,7 @@ use crate::utils::gc_round;
 use crate::{metrics::ConsensusMetrics, ConsensusError, Outcome, SequenceNumber};
 use config::{AuthorityIdentifier, Committee};
 use fastcrypto::hash::Hash;
+use mysten_common::memory_budget::{self, MemoryAccount, MemoryReservation};
 use mysten_metrics::spawn_logged_monitored_task;
 use std::{
     cmp::{max, Ordering},
@@ -15,21 +16,34 @@ use std::{
     sync::Arc,
 };
 use storage::CertificateStore;
+use telemetry_subscribers::CORRELATION_TARGET;
 use tokio::{sync::watch, task::JoinHandle};
 use tracing::{debug, info, instrument};
 use types::{
     metered_channel, Certificate, CertificateAPI, CertificateDigest, CommittedSubDag,
     CommittedSubDagShell, ConditionalBroadcastReceiver, ConsensusStore, HeaderAPI,
-    ReputationScores, Round, Timestamp,
+    ReputationScores, Round, Timestamp, TimestampMs,
 };
 
 #[cfg(test)]
 #[path = "tests/consensus_tests.rs"]
 pub mod consensus_tests;
 
+/// The stage of the transactions' pipeline from the certificate being formed to it being
+/// committed.
+const COMMITTED_STAGE: &str = "committed";
+
+/// The account of the memory budget holding the certificates of the DAG.
+const MEMORY_ACCOUNT: &str = "consensus-dag";
+
 /// The representation of the DAG in memory.
 pub type Dag = BTreeMap<Round, HashMap<AuthorityIdentifier, (CertificateDigest, Certificate)>>;
 
+/// The memory held by a certificate, estimated by its serialized size.
+fn certificate_size(certificate: &Certificate) -> usize {
+    bcs::serialized_size(certificate).unwrap_or_default()
+}
+
 /// The state that needs to be persisted for crash-recovery.
 pub struct ConsensusState {
     /// The information about the last committed round and corresponding GC round.
@@ -46,11 +60,19 @@ pub struct ConsensusState {
     /// The last committed sub dag leader. This allow us to calculate the reputation score of the nodes
     /// that vote for the last leader.
     pub last_committed_leader: Option<CertificateDigest>,
+    /// The timestamp of the last committed sub dag, which the next commit timestamps can't go
+    /// below.
+    pub last_commit_timestamp: TimestampMs,
     /// Keeps the latest committed certificate (and its parents) for every authority. Anything older
     /// must be regularly cleaned up through the function `update`.
     pub dag: Dag,
     /// Metrics handler
     pub metrics: Arc<ConsensusMetrics>,
+    /// The memory of the certificates of each round of the DAG, reserved from the budget of the
+    /// node. The DAG cannot refuse certificates, so it is only accounted for, for the other
+    /// components to hold back while the budget is exceeded.
+    dag_memory: BTreeMap<Round, MemoryReservation>,
+    memory: MemoryAccount,
 }
 
 impl ConsensusState {
@@ -63,7 +85,10 @@ impl ConsensusState {
             dag: Default::default(),
             last_consensus_reputation_score: ReputationScores::new(committee),
             last_committed_leader: None,
+            last_commit_timestamp: 0,
             metrics,
+            dag_memory: BTreeMap::new(),
+            memory: memory_budget::global().register(MEMORY_ACCOUNT),
         }
     }
 
@@ -86,10 +111,31 @@ impl ConsensusState {
         .expect("error when recovering DAG from store");
         metrics.recovered_consensus_state.inc();
 
-        let (latest_sub_dag_index, last_consensus_reputation_score, last_committed_leader) =
-            latest_sub_dag
-                .map(|s| (s.sub_dag_index, s.reputation_score, Some(s.leader)))
-                .unwrap_or((0, ReputationScores::new(committee), None));
+        let (
+            latest_sub_dag_index,
+            last_consensus_reputation_score,
+            last_committed_leader,
+            last_commit_timestamp,
+        ) = latest_sub_dag
+            .map(|s| {
+                (
+                    s.sub_dag_index,
+                    s.reputation_score,
+                    Some(s.leader),
+                    s.commit_timestamp,
+                )
+            })
+            .unwrap_or((0, ReputationScores::new(committee), None, 0));
+
+        let memory = memory_budget::global().register(MEMORY_ACCOUNT);
+        let mut dag_memory = BTreeMap::new();
+        for (round, certificates) in &dag {
+            let size = certificates
+                .values()
+                .map(|(_, certificate)| certificate_size(certificate))
+                .sum();
+            dag_memory.insert(*round, memory.reserve(size));
+        }
 
         Self {
             gc_depth,
@@ -98,8 +144,11 @@ impl ConsensusState {
             last_consensus_reputation_score,
             latest_sub_dag_index,
             last_committed_leader,
+            last_commit_timestamp,
             dag,
             metrics,
+            dag_memory,
+            memory,
         }
     }
 
@@ -134,12 +183,26 @@ impl ConsensusState {
 
     /// Returns true if certificate is inserted in the dag.
     pub fn try_insert(&mut self, certificate: &Certificate) -> Result<bool, ConsensusError> {
-        Self::try_insert_in_dag(
+        let known = self
+            .dag
+            .get(&certificate.round())
+            .map_or(false, |certificates| {
+                certificates.contains_key(&certificate.origin())
+            });
+        let inserted = Self::try_insert_in_dag(
             &mut self.dag,
             &self.last_committed,
             self.last_round.gc_round,
             certificate,
-        )
+        )?;
+        if !known && certificate.round() > self.last_round.gc_round {
+            let memory = &self.memory;
+            self.dag_memory
+                .entry(certificate.round())
+                .or_insert_with(|| memory.reserve(0))
+                .grow(certificate_size(certificate));
+        }
+        Ok(inserted)
     }
 
     /// Returns true if certificate is inserted in the dag.
@@ -196,6 +259,10 @@ impl ConsensusState {
         self.metrics
             .certificate_commit_latency
             .observe(certificate.metadata().created_at.elapsed().as_secs_f64());
+        self.metrics
+            .consensus_pipeline_stage_latency
+            .with_label_values(&[COMMITTED_STAGE])
+            .observe(elapsed);
 
         // NOTE: This log entry is used to compute performance.
         tracing::debug!(
@@ -207,6 +274,7 @@ impl ConsensusState {
 
         // Purge all certificates past the gc depth.
         self.dag.retain(|r, _| *r > self.last_round.gc_round);
+        self.dag_memory.retain(|r, _| *r > self.last_round.gc_round);
     }
 
     // Checks that the provided certificate's parents exist and crashes if not.
@@ -428,6 +496,13 @@ where
                                 tracing::info!("Committed {} -> {:?}", certificate.header(), digest);
                             }
 
+                            tracing::debug!(
+                                target: CORRELATION_TARGET,
+                                stage = "committed",
+                                certificate = %certificate.digest(),
+                                sub_dag = committed_sub_dag.sub_dag_index,
+                            );
+
                             commited_certificates.push(certificate.clone());
                         }
 
@@ -0,0 +1,236 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::{bullshark::Bullshark, consensus::Dag};
+use arc_swap::ArcSwap;
+use config::{AuthorityIdentifier, Committee, Stake};
+use std::{collections::HashSet, fmt::Debug, sync::Arc};
+use tracing::{debug, info};
+use types::{Certificate, CertificateDigest, ConsensusStore, ReputationScores, Round};
+
+#[cfg(test)]
+#[path = "tests/leader_schedule_tests.rs"]
+pub mod leader_schedule_tests;
+
+/// The maximum percentage of the total stake that can be swapped out of the leader schedule.
+pub const DEFAULT_BAD_NODES_STAKE_THRESHOLD: u64 = 20;
+
+/// Holds the authorities that should be swapped out of the leader schedule (the bad nodes) and
+/// the authorities that should take their place (the good nodes). The table is derived only from
+/// the final reputation scores of a schedule, which are part of the committed sub dag, so every
+/// node derives the same table for the same commit.
+#[derive(Default, Clone, Eq, PartialEq)]
+pub struct LeaderSwapTable {
+    /// The sub dag index of the commit whose reputation scores produced this table.
+    pub sub_dag_index: u64,
+    /// The authorities with the lowest reputation scores.
+    pub bad_nodes: HashSet<AuthorityIdentifier>,
+    /// The authorities with the highest reputation scores, ordered by descending score.
+    pub good_nodes: Vec<AuthorityIdentifier>,
+}
+
+impl Debug for LeaderSwapTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bad_nodes: Vec<_> = self.bad_nodes.iter().collect();
+        bad_nodes.sort();
+        f.write_str(&format!(
+            "LeaderSwapTable sub_dag_index: {}, bad_nodes: {:?}, good_nodes: {:?}",
+            self.sub_dag_index, bad_nodes, self.good_nodes
+        ))
+    }
+}
+
+impl LeaderSwapTable {
+    /// Builds the table from the final reputation scores of a schedule. Up to
+    /// `bad_nodes_stake_threshold` percent of the total stake is picked from each end of the
+    /// scores. A node is only considered bad when its score is lower than the score of every
+    /// good node, so no swaps happen when all the scores are equal.
+    pub fn new(
+        committee: &Committee,
+        sub_dag_index: u64,
+        reputation_scores: &ReputationScores,
+        bad_nodes_stake_threshold: u64,
+    ) -> Self {
+        assert!(
+            (0..=33).contains(&bad_nodes_stake_threshold),
+            "The bad_nodes_stake_threshold should be in range [0 - 33], out of bounds parameter detected"
+        );
+        assert!(
+            reputation_scores.final_of_schedule,
+            "Only reputation scores that have been calculated on the end of a schedule are accepted"
+        );
+
+        // Authorities missing from the scores are considered to have a zero score. Ties are
+        // broken by the authority id so every node ends up with the same ordering.
+        let mut scores: Vec<(AuthorityIdentifier, u64)> = committee
+            .authorities()
+            .map(|authority| {
+                let score = reputation_scores
+                    .scores_per_authority
+                    .get(&authority.id())
+                    .copied()
+                    .unwrap_or_default();
+                (authority.id(), score)
+            })
+            .collect();
+        scores.sort_by(|(id1, score1), (id2, score2)| score2.cmp(score1).then(id1.cmp(id2)));
+
+        let good_nodes = Self::retrieve_first_nodes(
+            committee,
+            scores.iter().copied(),
+            bad_nodes_stake_threshold,
+        );
+        let min_good_score = good_nodes
+            .last()
+            .map(|(_, score)| *score)
+            .unwrap_or_default();
+        let bad_nodes: HashSet<_> = Self::retrieve_first_nodes(
+            committee,
+            scores.iter().rev().copied(),
+            bad_nodes_stake_threshold,
+        )
+        .into_iter()
+        .filter(|(_, score)| *score < min_good_score)
+        .map(|(id, _)| id)
+        .collect();
+
+        let good_nodes = if bad_nodes.is_empty() {
+            Vec::new()
+        } else {
+            good_nodes.into_iter().map(|(id, _)| id).collect()
+        };
+
+        Self {
+            sub_dag_index,
+            bad_nodes,
+            good_nodes,
+        }
+    }
+
+    /// Returns the authority that should lead the given round instead of the `leader`, if the
+    /// `leader` is one of the bad nodes.
+    pub fn swap(&self, leader: &AuthorityIdentifier, round: Round) -> Option<AuthorityIdentifier> {
+        if !self.bad_nodes.contains(leader) || self.good_nodes.is_empty() {
+            return None;
+        }
+        // Rotate over the good nodes so no single one of them leads all the swapped rounds.
+        let index = (round / 2) as usize % self.good_nodes.len();
+        Some(self.good_nodes[index])
+    }
+
+    // Retrieves the first nodes provided by the iterator `authorities` until their total stake
+    // reaches the `stake_threshold` percent of the total stake.
+    fn retrieve_first_nodes(
+        committee: &Committee,
+        authorities: impl Iterator<Item = (AuthorityIdentifier, u64)>,
+        stake_threshold: u64,
+    ) -> Vec<(AuthorityIdentifier, u64)> {
+        let mut filtered_authorities = Vec::new();
+        let mut stake: Stake = 0;
+        for (authority_id, score) in authorities {
+            stake += committee.stake_by_id(authority_id);
+
+            // If the total accumulated stake has surpassed the stake threshold then we omit this
+            // last authority and we exit the loop.
+            if stake > (stake_threshold * committee.total_stake()) / 100 {
+                break;
+            }
+            filtered_authorities.push((authority_id, score));
+        }
+        filtered_authorities
+    }
+}
+
+/// The leader schedule shared between consensus and the proposer. It elects the leader of each
+/// round as Bullshark does, except that the leaders with a low reputation are swapped out as
+/// dictated by the current `LeaderSwapTable`. As the leaders elected are consensus critical, the
+/// swaps are only enabled once decided by the protocol, for every primary at once.
+#[derive(Clone)]
+pub struct LeaderSchedule {
+    pub committee: Committee,
+    pub leader_swap_table: Arc<ArcSwap<LeaderSwapTable>>,
+    // Whether the table is updated with the final reputation scores of the schedules.
+    leader_swaps: bool,
+}
+
+impl LeaderSchedule {
+    /// Creates a schedule with the leader swaps enabled, starting from the given table.
+    pub fn new(committee: Committee, table: LeaderSwapTable) -> Self {
+        Self {
+            committee,
+            leader_swap_table: Arc::new(ArcSwap::from_pointee(table)),
+            leader_swaps: true,
+        }
+    }
+
+    /// Creates the Bullshark schedule, never swapping its leaders.
+    pub fn without_leader_swaps(committee: Committee) -> Self {
+        Self {
+            leader_swaps: false,
+            ..Self::new(committee, LeaderSwapTable::default())
+        }
+    }
+
+    pub fn leader_swaps(&self) -> bool {
+        self.leader_swaps
+    }
+
+    /// Restores the leader schedule from the latest commit with final reputation scores. If
+    /// there is none, the schedule starts with an empty `LeaderSwapTable`. Without the leader
+    /// swaps, the scores are ignored.
+    pub fn from_store(
+        committee: Committee,
+        store: Arc<ConsensusStore>,
+        leader_swaps: bool,
+    ) -> Self {
+        if !leader_swaps {
+            return Self::without_leader_swaps(committee);
+        }
+        let table = store
+            .read_latest_commit_with_final_reputation_scores()
+            .map_or(LeaderSwapTable::default(), |sub_dag| {
+                LeaderSwapTable::new(
+                    &committee,
+                    sub_dag.sub_dag_index,
+                    &sub_dag.reputation_score,
+                    DEFAULT_BAD_NODES_STAKE_THRESHOLD,
+                )
+            });
+        info!("Restored leader swap table: {table:?}");
+
+        Self::new(committee, table)
+    }
+
+    /// Returns the leader of the given `round`.
+    pub fn leader(&self, round: Round) -> AuthorityIdentifier {
+        let leader = Bullshark::leader_authority(&self.committee, round);
+        match self.leader_swap_table.load().swap(&leader, round) {
+            Some(swapped) => {
+                debug!("Swapped leader {leader} of round {round} for {swapped}");
+                swapped
+            }
+            None => leader,
+        }
+    }
+
+    /// Returns the certificate (and the certificate's digest) originated by the leader of the
+    /// specified round (if any).
+    pub fn leader_certificate<'a>(
+        &self,
+        round: Round,
+        dag: &'a Dag,
+    ) -> Option<&'a (CertificateDigest, Certificate)> {
+        let leader = self.leader(round);
+        dag.get(&round).and_then(|x| x.get(&leader))
+    }
+
+    /// Replaces the `LeaderSwapTable` used to elect the leaders from now on. It is a no-op
+    /// without the leader swaps.
+    pub fn update_leader_schedule(&self, table: LeaderSwapTable) {
+        if !self.leader_swaps {
+            debug!("Leader swaps are disabled, ignoring leader swap table: {table:?}");
+            return;
+        }
+        info!("Updating leader swap table: {table:?}");
+        self.leader_swap_table.store(Arc::new(table));
+    }
+}
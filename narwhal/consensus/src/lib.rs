@@ -13,6 +13,7 @@ pub mod consensus;
 #[path = "tests/consensus_utils.rs"]
 pub mod consensus_utils;
 pub mod dag;
+pub mod leader_schedule;
 pub mod metrics;
 pub mod tusk;
 pub mod utils;
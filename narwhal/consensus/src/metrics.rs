@@ -1,10 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use prometheus::{
-    default_registry, register_histogram_with_registry, register_int_counter_vec_with_registry,
-    register_int_counter_with_registry, register_int_gauge_vec_with_registry,
-    register_int_gauge_with_registry, Histogram, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
-    Registry,
+    default_registry, register_histogram_vec_with_registry, register_histogram_with_registry,
+    register_int_counter_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_vec_with_registry, register_int_gauge_with_registry, Histogram,
+    HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry,
 };
 
 const LATENCY_SEC_BUCKETS: &[f64] = &[
@@ -43,6 +43,8 @@ pub struct ConsensusMetrics {
     pub leader_election: IntCounterVec,
     /// Count leader certificates committed, and whether the leader has strong support.
     pub leader_commits: IntCounterVec,
+    /// The latency of the stages of the transactions' pipeline within consensus, per stage
+    pub consensus_pipeline_stage_latency: HistogramVec,
 }
 
 impl ConsensusMetrics {
@@ -117,6 +119,13 @@ impl ConsensusMetrics {
                 &["type"],
                 registry
             ).unwrap(),
+            consensus_pipeline_stage_latency: register_histogram_vec_with_registry!(
+                "consensus_pipeline_stage_latency",
+                "The latency of the stages of the transactions' pipeline, since the previous stage",
+                &["stage"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry
+            ).unwrap(),
         }
     }
 }
@@ -61,6 +61,7 @@ async fn commit_one() {
         store.clone(),
         metrics.clone(),
         NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     let _consensus_handle = Consensus::spawn(
@@ -141,6 +142,7 @@ async fn dead_node() {
         store.clone(),
         metrics.clone(),
         NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     let _consensus_handle = Consensus::spawn(
@@ -292,6 +294,7 @@ async fn not_enough_support() {
         store.clone(),
         metrics.clone(),
         NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     let _consensus_handle = Consensus::spawn(
@@ -410,6 +413,7 @@ async fn missing_leader() {
         store.clone(),
         metrics.clone(),
         NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     let _consensus_handle = Consensus::spawn(
@@ -491,6 +495,7 @@ async fn committed_round_after_restart() {
             store.clone(),
             metrics.clone(),
             NUM_SUB_DAGS_PER_SCHEDULE,
+            LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         );
 
         let handle = Consensus::spawn(
@@ -573,7 +578,13 @@ async fn delayed_certificates_are_rejected() {
 
     let store = make_consensus_store(&test_utils::temp_dir());
     let mut state = ConsensusState::new(metrics.clone(), &committee, gc_depth);
-    let mut bullshark = Bullshark::new(committee, store, metrics, NUM_SUB_DAGS_PER_SCHEDULE);
+    let mut bullshark = Bullshark::new(
+        committee.clone(),
+        store,
+        metrics,
+        NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee, LeaderSwapTable::default()),
+    );
 
     // Populate DAG with the rounds up to round 5 so we trigger commits
     let mut all_subdags = Vec::new();
@@ -619,8 +630,13 @@ async fn submitting_equivocating_certificate_should_error() {
 
     let store = make_consensus_store(&test_utils::temp_dir());
     let mut state = ConsensusState::new(metrics.clone(), &committee, gc_depth);
-    let mut bullshark =
-        Bullshark::new(committee.clone(), store, metrics, NUM_SUB_DAGS_PER_SCHEDULE);
+    let mut bullshark = Bullshark::new(
+        committee.clone(),
+        store,
+        metrics,
+        NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
+    );
 
     // Populate DAG with all the certificates
     for certificate in certificates.clone() {
@@ -677,7 +693,13 @@ async fn reset_consensus_scores_on_every_schedule_change() {
 
     let store = make_consensus_store(&test_utils::temp_dir());
     let mut state = ConsensusState::new(metrics.clone(), &committee, gc_depth);
-    let mut bullshark = Bullshark::new(committee, store, metrics, NUM_SUB_DAGS_PER_SCHEDULE);
+    let mut bullshark = Bullshark::new(
+        committee.clone(),
+        store,
+        metrics,
+        NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee, LeaderSwapTable::default()),
+    );
 
     // Populate DAG with the rounds up to round 50 so we trigger commits
     let mut all_subdags = Vec::new();
@@ -748,6 +770,7 @@ async fn restart_with_new_committee() {
             store.clone(),
             metrics.clone(),
             NUM_SUB_DAGS_PER_SCHEDULE,
+            LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         );
 
         let handle = Consensus::spawn(
@@ -854,7 +877,13 @@ async fn garbage_collection_basic() {
 
     let metrics = Arc::new(ConsensusMetrics::new(&Registry::new()));
     let mut state = ConsensusState::new(metrics.clone(), &committee, GC_DEPTH);
-    let mut bullshark = Bullshark::new(committee, store, metrics, NUM_SUB_DAGS_PER_SCHEDULE);
+    let mut bullshark = Bullshark::new(
+        committee.clone(),
+        store,
+        metrics,
+        NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee, LeaderSwapTable::default()),
+    );
 
     // Now start feeding the certificates per round
     for c in certificates {
@@ -954,8 +983,13 @@ async fn slow_node() {
     let store = make_consensus_store(&test_utils::temp_dir());
     let metrics = Arc::new(ConsensusMetrics::new(&Registry::new()));
     let mut state = ConsensusState::new(metrics.clone(), &committee, GC_DEPTH);
-    let mut bullshark =
-        Bullshark::new(committee.clone(), store, metrics, NUM_SUB_DAGS_PER_SCHEDULE);
+    let mut bullshark = Bullshark::new(
+        committee.clone(),
+        store,
+        metrics,
+        NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
+    );
 
     // Now start feeding the certificates per round up to 8. We expect to have
     // triggered a commit up to round 6 and gc round 1 & 2.
@@ -1127,7 +1161,13 @@ async fn not_enough_support_and_missing_leaders_and_gc() {
     let store = make_consensus_store(&test_utils::temp_dir());
     let metrics = Arc::new(ConsensusMetrics::new(&Registry::new()));
     let mut state = ConsensusState::new(metrics.clone(), &committee, GC_DEPTH);
-    let mut bullshark = Bullshark::new(committee, store, metrics, NUM_SUB_DAGS_PER_SCHEDULE);
+    let mut bullshark = Bullshark::new(
+        committee.clone(),
+        store,
+        metrics,
+        NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee, LeaderSwapTable::default()),
+    );
 
     let mut committed = false;
     for c in &certificates {
@@ -1196,3 +1236,59 @@ async fn not_enough_support_and_missing_leaders_and_gc() {
 
     assert!(committed);
 }
+
+#[test]
+fn test_commit_timestamp() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+
+    // One certificate of round 1 per authority, created at 10, 40, 20 and 30.
+    let parents: Vec<Certificate> = fixture
+        .authorities()
+        .zip([10, 40, 20, 30])
+        .map(|(authority, created_at)| {
+            let header = authority
+                .header_builder(&committee)
+                .round(1)
+                .created_at(created_at)
+                .payload(Default::default())
+                .build()
+                .unwrap();
+            fixture.certificate(&types::Header::V1(header))
+        })
+        .collect();
+
+    // The leader of round 2 links to the certificates created at 10, 40 and 20.
+    let leader = types::Header::V1(
+        fixture
+            .authorities()
+            .next()
+            .unwrap()
+            .header_builder(&committee)
+            .round(2)
+            .created_at(1_000)
+            .payload(Default::default())
+            .parents(parents[..3].iter().map(|c| c.digest()).collect())
+            .build()
+            .unwrap(),
+    );
+    let leader = fixture.certificate(&leader);
+    let mut certificates = parents;
+    certificates.push(leader.clone());
+
+    // The timestamp is the median of the parents' timestamps.
+    assert_eq!(
+        types::CommittedSubDag::compute_commit_timestamp(&leader, &certificates, 0),
+        20
+    );
+    // It never goes below the previous commit timestamp.
+    assert_eq!(
+        types::CommittedSubDag::compute_commit_timestamp(&leader, &certificates, 25),
+        25
+    );
+    // Without the parents, the timestamp of the leader is used.
+    assert_eq!(
+        types::CommittedSubDag::compute_commit_timestamp(&leader, &[leader.clone()], 0),
+        1_000
+    );
+}
@@ -15,6 +15,7 @@ use tokio::sync::watch;
 use crate::bullshark::Bullshark;
 use crate::consensus::ConsensusRound;
 use crate::consensus_utils::NUM_SUB_DAGS_PER_SCHEDULE;
+use crate::leader_schedule::{LeaderSchedule, LeaderSwapTable};
 use crate::metrics::ConsensusMetrics;
 use crate::Consensus;
 use crate::NUM_SHUTDOWN_RECEIVERS;
@@ -71,6 +72,7 @@ async fn test_consensus_recovery_with_bullshark() {
         consensus_store.clone(),
         metrics.clone(),
         NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     let consensus_handle = Consensus::spawn(
@@ -166,6 +168,7 @@ async fn test_consensus_recovery_with_bullshark() {
         consensus_store.clone(),
         metrics.clone(),
         NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     let consensus_handle = Consensus::spawn(
@@ -235,6 +238,7 @@ async fn test_consensus_recovery_with_bullshark() {
         consensus_store.clone(),
         metrics.clone(),
         NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     let _consensus_handle = Consensus::spawn(
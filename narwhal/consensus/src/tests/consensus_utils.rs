@@ -7,7 +7,8 @@ use storage::{CertificateStore, CertificateStoreCache};
 use store::rocks::MetricConf;
 use store::{reopen, rocks, rocks::DBMap, rocks::ReadWriteOptions};
 use types::{
-    Certificate, CertificateDigest, CommittedSubDagShell, ConsensusStore, Round, SequenceNumber,
+    Certificate, CertificateDigest, CommittedSubDagShellV1, ConsensusStore, Round, SequenceNumber,
+    VersionedCommittedSubDagShell,
 };
 
 pub(crate) const NUM_SUB_DAGS_PER_SCHEDULE: u64 = 100;
@@ -15,21 +16,27 @@ pub(crate) const NUM_SUB_DAGS_PER_SCHEDULE: u64 = 100;
 pub fn make_consensus_store(store_path: &std::path::Path) -> Arc<ConsensusStore> {
     const LAST_COMMITTED_CF: &str = "last_committed";
     const SEQUENCE_CF: &str = "sequence";
+    const SEQUENCE_V2_CF: &str = "sequence_v2";
 
     let rocksdb = rocks::open_cf(
         store_path,
         None,
         MetricConf::default(),
-        &[LAST_COMMITTED_CF, SEQUENCE_CF],
+        &[LAST_COMMITTED_CF, SEQUENCE_CF, SEQUENCE_V2_CF],
     )
     .expect("Failed to create database");
 
-    let (last_committed_map, sequence_map) = reopen!(&rocksdb,
+    let (last_committed_map, sequence_map, sequence_v2_map) = reopen!(&rocksdb,
         LAST_COMMITTED_CF;<AuthorityIdentifier, Round>,
-        SEQUENCE_CF;<SequenceNumber, CommittedSubDagShell>
+        SEQUENCE_CF;<SequenceNumber, CommittedSubDagShellV1>,
+        SEQUENCE_V2_CF;<SequenceNumber, VersionedCommittedSubDagShell>
     );
 
-    Arc::new(ConsensusStore::new(last_committed_map, sequence_map))
+    Arc::new(ConsensusStore::new(
+        last_committed_map,
+        sequence_map,
+        sequence_v2_map,
+    ))
 }
 
 pub fn make_certificate_store(store_path: &std::path::Path) -> CertificateStore {
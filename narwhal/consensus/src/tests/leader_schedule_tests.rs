@@ -0,0 +1,167 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::consensus_utils::make_consensus_store;
+use std::{
+    collections::{BTreeSet, HashMap},
+    num::NonZeroUsize,
+};
+use test_utils::{mock_certificate, CommitteeFixture};
+use types::CommittedSubDag;
+
+fn final_scores(committee: &Committee, scores: &[u64]) -> ReputationScores {
+    let mut reputation_scores = ReputationScores::new(committee);
+    for (authority, score) in committee.authorities().zip(scores) {
+        reputation_scores.add_score(authority.id(), *score);
+    }
+    reputation_scores.final_of_schedule = true;
+    reputation_scores
+}
+
+#[test]
+fn leader_swap_table() {
+    // GIVEN a committee of 10 equally staked authorities
+    let fixture = CommitteeFixture::builder()
+        .committee_size(NonZeroUsize::new(10).unwrap())
+        .build();
+    let committee = fixture.committee();
+    let ids: Vec<_> = committee.authorities().map(|a| a.id()).collect();
+
+    // WHEN two authorities have the lowest scores
+    let scores = final_scores(&committee, &[1, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    let table = LeaderSwapTable::new(&committee, 10, &scores, DEFAULT_BAD_NODES_STAKE_THRESHOLD);
+
+    // THEN they are swapped for the two authorities with the highest scores
+    assert_eq!(table.sub_dag_index, 10);
+    assert_eq!(table.bad_nodes, HashSet::from([ids[0], ids[9]]));
+    assert_eq!(table.good_nodes, vec![ids[1], ids[2]]);
+
+    assert_eq!(table.swap(&ids[0], 2), Some(ids[2]));
+    assert_eq!(table.swap(&ids[9], 4), Some(ids[1]));
+    assert_eq!(table.swap(&ids[5], 2), None);
+}
+
+#[test]
+fn leader_swap_table_equal_scores() {
+    let fixture = CommitteeFixture::builder()
+        .committee_size(NonZeroUsize::new(10).unwrap())
+        .build();
+    let committee = fixture.committee();
+
+    // When all the scores are equal no authority is considered bad.
+    let scores = final_scores(&committee, &[5; 10]);
+    let table = LeaderSwapTable::new(&committee, 10, &scores, DEFAULT_BAD_NODES_STAKE_THRESHOLD);
+
+    assert!(table.bad_nodes.is_empty());
+    assert!(table.good_nodes.is_empty());
+}
+
+#[test]
+fn leader_schedule() {
+    let fixture = CommitteeFixture::builder()
+        .committee_size(NonZeroUsize::new(10).unwrap())
+        .build();
+    let committee = fixture.committee();
+    let schedule = LeaderSchedule::new(committee.clone(), LeaderSwapTable::default());
+
+    // Without a swap table the schedule is the Bullshark one.
+    for round in (2..=40).step_by(2) {
+        assert_eq!(
+            schedule.leader(round),
+            Bullshark::leader_authority(&committee, round)
+        );
+    }
+
+    // Once updated, the bad nodes never lead a round.
+    let scores = final_scores(&committee, &[1, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    let table = LeaderSwapTable::new(&committee, 10, &scores, DEFAULT_BAD_NODES_STAKE_THRESHOLD);
+    schedule.update_leader_schedule(table.clone());
+
+    for round in (2..=40).step_by(2) {
+        let leader = schedule.leader(round);
+        assert!(!table.bad_nodes.contains(&leader));
+        if !table
+            .bad_nodes
+            .contains(&Bullshark::leader_authority(&committee, round))
+        {
+            assert_eq!(leader, Bullshark::leader_authority(&committee, round));
+        }
+    }
+}
+
+#[test]
+fn leader_schedule_from_store() {
+    let fixture = CommitteeFixture::builder()
+        .committee_size(NonZeroUsize::new(10).unwrap())
+        .build();
+    let committee = fixture.committee();
+    let store = make_consensus_store(&test_utils::temp_dir());
+
+    // Without any commit there is nothing to swap.
+    let schedule = LeaderSchedule::from_store(committee.clone(), store.clone(), true);
+    assert_eq!(
+        *schedule.leader_swap_table.load_full(),
+        LeaderSwapTable::default()
+    );
+
+    // Commit a sub dag with the final scores of the schedule, followed by one that starts the
+    // next schedule.
+    let authority = fixture.authorities().next().unwrap();
+    let (_, leader) = mock_certificate(&committee, authority.id(), 2, BTreeSet::new());
+    let scores = final_scores(&committee, &[1, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    for (sub_dag_index, reputation_score) in
+        [(9, scores.clone()), (10, ReputationScores::new(&committee))]
+    {
+        let sub_dag = CommittedSubDag {
+            certificates: vec![leader.clone()],
+            leader: leader.clone(),
+            sub_dag_index,
+            reputation_score,
+            commit_timestamp: 0,
+        };
+        store
+            .write_consensus_state(&HashMap::new(), &sub_dag)
+            .unwrap();
+    }
+
+    // The table is restored from the latest final scores.
+    let schedule = LeaderSchedule::from_store(committee.clone(), store.clone(), true);
+    assert_eq!(
+        *schedule.leader_swap_table.load_full(),
+        LeaderSwapTable::new(&committee, 9, &scores, DEFAULT_BAD_NODES_STAKE_THRESHOLD)
+    );
+
+    // Unless the leader swaps are disabled.
+    let schedule = LeaderSchedule::from_store(committee.clone(), store, false);
+    assert_eq!(
+        *schedule.leader_swap_table.load_full(),
+        LeaderSwapTable::default()
+    );
+}
+
+#[test]
+fn leader_schedule_without_leader_swaps() {
+    let fixture = CommitteeFixture::builder()
+        .committee_size(NonZeroUsize::new(10).unwrap())
+        .build();
+    let committee = fixture.committee();
+    let schedule = LeaderSchedule::without_leader_swaps(committee.clone());
+    assert!(!schedule.leader_swaps());
+
+    // The final scores of a schedule are ignored, so the schedule stays the Bullshark one.
+    let scores = final_scores(&committee, &[1, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    let table = LeaderSwapTable::new(&committee, 10, &scores, DEFAULT_BAD_NODES_STAKE_THRESHOLD);
+    assert!(!table.bad_nodes.is_empty());
+    schedule.update_leader_schedule(table);
+
+    assert_eq!(
+        *schedule.leader_swap_table.load_full(),
+        LeaderSwapTable::default()
+    );
+    for round in (2..=40).step_by(2) {
+        assert_eq!(
+            schedule.leader(round),
+            Bullshark::leader_authority(&committee, round)
+        );
+    }
+}
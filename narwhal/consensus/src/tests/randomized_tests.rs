@@ -5,6 +5,7 @@ use crate::consensus::ConsensusProtocol;
 use crate::consensus::ConsensusState;
 use crate::consensus_utils::make_consensus_store;
 use crate::consensus_utils::NUM_SUB_DAGS_PER_SCHEDULE;
+use crate::leader_schedule::{LeaderSchedule, LeaderSwapTable};
 use crate::metrics::ConsensusMetrics;
 use config::{Authority, AuthorityIdentifier, Committee, Stake};
 use fastcrypto::hash::Hash;
@@ -431,6 +432,7 @@ fn generate_and_run_execution_plans(
             store.clone(),
             metrics.clone(),
             NUM_SUB_DAGS_PER_SCHEDULE,
+            LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         );
 
         let mut inserted_certificates = HashSet::new();
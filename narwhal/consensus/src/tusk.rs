@@ -105,11 +105,17 @@ impl ConsensusProtocol for Tusk {
             }
             debug!("Subdag has {} certificates", sequence.len());
 
+            let commit_timestamp = CommittedSubDag::compute_commit_timestamp(
+                leader,
+                &sequence,
+                state.last_commit_timestamp,
+            );
             let sub_dag = CommittedSubDag {
                 certificates: sequence,
                 leader: leader.clone(),
                 sub_dag_index,
                 reputation_score: ReputationScores::default(), // TODO compute the scores for Tusk as well
+                commit_timestamp,
             };
 
             // Persist the update.
@@ -118,6 +124,7 @@ impl ConsensusProtocol for Tusk {
 
             // Increase the global consensus index.
             state.latest_sub_dag_index = sub_dag_index;
+            state.last_commit_timestamp = commit_timestamp;
 
             committed_sub_dags.push(sub_dag);
         }
@@ -20,6 +20,8 @@ pub use fastcrypto::traits;
 use serde::Serialize;
 use shared_crypto::intent::{AppId, Intent, IntentMessage, IntentScope, INTENT_PREFIX_LENGTH};
 
+pub mod signer;
+
 ////////////////////////////////////////////////////////////////////////
 /// Type aliases selecting the signature algorithm for the code base.
 ////////////////////////////////////////////////////////////////////////
@@ -98,6 +100,16 @@ pub trait NarwhalAuthorityAggregateSignature {
     ) -> Result<(), FastCryptoError>
     where
         T: Serialize;
+
+    /// Verify the aggregate signatures on their intent messages against the public keys of their
+    /// signers, at once. Fails if any of the signatures is invalid, without telling which.
+    fn batch_verify_secure<T>(
+        signatures: &[&Self],
+        pks: &[&[PublicKey]],
+        values: &[IntentMessage<T>],
+    ) -> Result<(), FastCryptoError>
+    where
+        T: Serialize;
 }
 
 impl NarwhalAuthorityAggregateSignature for AggregateSignature {
@@ -112,6 +124,25 @@ impl NarwhalAuthorityAggregateSignature for AggregateSignature {
         let message = bcs::to_bytes(&value).expect("Message serialization should not fail");
         self.verify(pks, &message)
     }
+
+    fn batch_verify_secure<T>(
+        signatures: &[&Self],
+        pks: &[&[PublicKey]],
+        values: &[IntentMessage<T>],
+    ) -> Result<(), FastCryptoError>
+    where
+        T: Serialize,
+    {
+        let messages: Vec<_> = values
+            .iter()
+            .map(|value| bcs::to_bytes(value).expect("Message serialization should not fail"))
+            .collect();
+        AggregateSignature::batch_verify(
+            signatures,
+            pks.iter().map(|pks| pks.iter()).collect(),
+            &messages.iter().map(|m| &m[..]).collect::<Vec<_>>(),
+        )
+    }
 }
 
 /// Wrap a message in an intent message. Currently in Narwhal, the scope is always IntentScope::HeaderDigest and the app id is AppId::Narwhal.
@@ -0,0 +1,97 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The signers of the protocol messages of an authority. The protocol key may be held by the node,
+//! or outside of it, e.g. by a KMS or a PKCS#11 HSM, in which case signing is a remote call. The
+//! network and worker keys are always held by the node, as they authenticate its TLS connections.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use fastcrypto::hash::Digest;
+use fastcrypto::traits::{KeyPair as _, Signer};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::{KeyPair, PublicKey, Signature, INTENT_MESSAGE_LENGTH};
+
+/// The initial delay before retrying a failed signature, doubled after every failure.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(50);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, Error)]
+pub enum SignerError {
+    #[error("The signer is unavailable: {0}")]
+    Unavailable(String),
+    #[error("The signer returned an invalid signature: {0}")]
+    InvalidSignature(String),
+}
+
+/// Signs messages with the protocol key of an authority.
+#[async_trait]
+pub trait AuthoritySigner: Send + Sync + 'static {
+    /// The public key of the protocol key.
+    fn public_key(&self) -> PublicKey;
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError>;
+}
+
+/// Signs with a protocol key held in memory, e.g. loaded from a key file.
+#[async_trait]
+impl AuthoritySigner for KeyPair {
+    fn public_key(&self) -> PublicKey {
+        self.public().clone()
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        Ok(self.sign(message))
+    }
+}
+
+#[async_trait]
+impl<S: AuthoritySigner + ?Sized> AuthoritySigner for Arc<S> {
+    fn public_key(&self) -> PublicKey {
+        self.as_ref().public_key()
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.as_ref().sign_message(message).await
+    }
+}
+
+/// Signs the digests of the messages of the primary, e.g. its votes, with the authority signer.
+/// Cloning the service shares the signer.
+#[derive(Clone)]
+pub struct SignatureService {
+    signer: Arc<dyn AuthoritySigner>,
+}
+
+impl SignatureService {
+    pub fn new(signer: impl AuthoritySigner) -> Self {
+        Self {
+            signer: Arc::new(signer),
+        }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.signer.public_key()
+    }
+
+    /// Returns the signature of the digest. As the authority cannot make progress without
+    /// signing, a failed signature is retried with a backoff until the signer succeeds, e.g. when
+    /// a remote signer is temporarily unreachable.
+    pub async fn request_signature(&self, digest: Digest<INTENT_MESSAGE_LENGTH>) -> Signature {
+        let mut delay = INITIAL_RETRY_DELAY;
+        loop {
+            match self.signer.sign_message(&digest.digest).await {
+                Ok(signature) => return signature,
+                Err(e) => {
+                    warn!("Failed to sign digest, retrying in {delay:?}: {e}");
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs the execution state in its own process: the primary streams the consensus output over a
+//! local unix socket to an external executor, and waits for the executor to ack each sub dag.
+//!
+//! The protocol is language independent. Each message is a BCS-serialized
+//! `ExternalExecutorRequest` or `ExternalExecutorResponse`, prefixed by its length as a big
+//! endian u32. The primary sends a request at a time and waits for its response before sending
+//! the next one.
+use crate::ExecutionState;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{io, path::PathBuf, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+    sync::Mutex,
+    time::sleep,
+};
+use tracing::{info, warn};
+use types::{Batch, Certificate, CommittedSubDag, ConsensusOutput};
+
+/// The delay before reconnecting to the external executor after a failure.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// The requests of the primary to the external executor.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ExternalExecutorRequest {
+    /// Asks for the index of the last sub dag executed, see
+    /// `ExecutionState::last_executed_sub_dag_index`.
+    LastExecutedSubDagIndex,
+    /// Executes the consensus output, which is acked by the index of its sub dag once executed.
+    Execute(ExternalConsensusOutput),
+}
+
+/// The response of the external executor to a request: the index of the last sub dag executed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExternalExecutorResponse {
+    pub sub_dag_index: u64,
+}
+
+/// The serializable form of the `ConsensusOutput`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExternalConsensusOutput {
+    pub sub_dag: CommittedSubDag,
+    pub batches: Vec<(Certificate, Vec<Batch>)>,
+}
+
+impl From<ConsensusOutput> for ExternalConsensusOutput {
+    fn from(output: ConsensusOutput) -> Self {
+        Self {
+            sub_dag: output.sub_dag.as_ref().clone(),
+            batches: output.batches,
+        }
+    }
+}
+
+impl From<ExternalConsensusOutput> for ConsensusOutput {
+    fn from(output: ExternalConsensusOutput) -> Self {
+        Self {
+            sub_dag: Arc::new(output.sub_dag),
+            batches: output.batches,
+        }
+    }
+}
+
+/// An execution state forwarding the consensus output to an external executor listening on a
+/// unix socket. The executor is reconnected to until it acks, so that no output is skipped: like
+/// any execution state, it must ignore the sub dags it has already executed.
+pub struct ExternalExecutionState {
+    path: PathBuf,
+    connection: Mutex<Option<UnixStream>>,
+}
+
+impl ExternalExecutionState {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            connection: Mutex::new(None),
+        }
+    }
+
+    async fn call(&self, request: ExternalExecutorRequest) -> ExternalExecutorResponse {
+        let message = bcs::to_bytes(&request).expect("Serializing the request should not fail");
+        let mut connection = self.connection.lock().await;
+        loop {
+            match self.try_call(&mut connection, &message).await {
+                Ok(response) => return response,
+                Err(e) => {
+                    warn!(
+                        "Failed to call the external executor at {}: {e}, reconnecting",
+                        self.path.display()
+                    );
+                    *connection = None;
+                    sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    async fn try_call(
+        &self,
+        connection: &mut Option<UnixStream>,
+        message: &[u8],
+    ) -> io::Result<ExternalExecutorResponse> {
+        if connection.is_none() {
+            *connection = Some(UnixStream::connect(&self.path).await?);
+            info!(
+                "Connected to the external executor at {}",
+                self.path.display()
+            );
+        }
+        let stream = connection.as_mut().unwrap();
+        write_message(stream, message).await?;
+        let response = read_message(stream).await?;
+        bcs::from_bytes(&response).map_err(invalid_data)
+    }
+}
+
+#[async_trait]
+impl ExecutionState for ExternalExecutionState {
+    async fn handle_consensus_output(&self, consensus_output: ConsensusOutput) {
+        let sub_dag_index = consensus_output.sub_dag.sub_dag_index;
+        let request = ExternalExecutorRequest::Execute(consensus_output.into());
+        loop {
+            let response = self.call(request.clone()).await;
+            if response.sub_dag_index == sub_dag_index {
+                return;
+            }
+            // Only a misbehaving executor acks another sub dag, the output is resent.
+            warn!(
+                "The external executor acked sub dag {} instead of {sub_dag_index}",
+                response.sub_dag_index
+            );
+            sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn last_executed_sub_dag_index(&self) -> u64 {
+        self.call(ExternalExecutorRequest::LastExecutedSubDagIndex)
+            .await
+            .sub_dag_index
+    }
+}
+
+/// Serves the execution state to the primary over the listener, so that an executor written in
+/// Rust can run in its own process. The connections are served one at a time, as the primary
+/// only reconnects after a failure.
+pub async fn serve_execution_state<State: ExecutionState + Send + Sync>(
+    listener: UnixListener,
+    state: State,
+) -> io::Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        if let Err(e) = serve_connection(&mut stream, &state).await {
+            warn!("Connection with the primary failed: {e}");
+        }
+    }
+}
+
+async fn serve_connection<State: ExecutionState + Send + Sync>(
+    stream: &mut UnixStream,
+    state: &State,
+) -> io::Result<()> {
+    loop {
+        let message = match read_message(stream).await {
+            Ok(message) => message,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let sub_dag_index = match bcs::from_bytes(&message).map_err(invalid_data)? {
+            ExternalExecutorRequest::LastExecutedSubDagIndex => {
+                state.last_executed_sub_dag_index().await
+            }
+            ExternalExecutorRequest::Execute(output) => {
+                let sub_dag_index = output.sub_dag.sub_dag_index;
+                state.handle_consensus_output(output.into()).await;
+                sub_dag_index
+            }
+        };
+        let response = bcs::to_bytes(&ExternalExecutorResponse { sub_dag_index })
+            .expect("Serializing the response should not fail");
+        write_message(stream, &response).await?;
+    }
+}
+
+async fn write_message(stream: &mut UnixStream, message: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(message.len()).map_err(invalid_data)?;
+    stream.write_all(&len.to_be_bytes()).await?;
+    stream.write_all(message).await?;
+    stream.flush().await
+}
+
+async fn read_message(stream: &mut UnixStream) -> io::Result<Vec<u8>> {
+    let len = stream.read_u32().await?;
+    let mut message = vec![0; len as usize];
+    stream.read_exact(&mut message).await?;
+    Ok(message)
+}
+
+fn invalid_data<E: std::error::Error + Send + Sync + 'static>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+
+    #[derive(Default)]
+    struct TestExecutionState {
+        executed: StdMutex<Vec<u64>>,
+    }
+
+    #[async_trait]
+    impl ExecutionState for TestExecutionState {
+        async fn handle_consensus_output(&self, consensus_output: ConsensusOutput) {
+            let mut executed = self.executed.lock().unwrap();
+            executed.push(consensus_output.sub_dag.sub_dag_index);
+        }
+
+        async fn last_executed_sub_dag_index(&self) -> u64 {
+            self.executed.lock().unwrap().last().copied().unwrap_or(0)
+        }
+    }
+
+    fn output(sub_dag_index: u64) -> ConsensusOutput {
+        ConsensusOutput {
+            sub_dag: Arc::new(CommittedSubDag {
+                sub_dag_index,
+                ..Default::default()
+            }),
+            batches: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("executor.sock");
+        let state = Arc::new(TestExecutionState::default());
+        let server = tokio::spawn(serve_execution_state(
+            UnixListener::bind(&path).unwrap(),
+            state.clone(),
+        ));
+
+        let external = ExternalExecutionState::new(&path);
+        assert_eq!(external.last_executed_sub_dag_index().await, 0);
+        external.handle_consensus_output(output(1)).await;
+        external.handle_consensus_output(output(2)).await;
+        assert_eq!(external.last_executed_sub_dag_index().await, 2);
+
+        // The executor restarts, and is reconnected to.
+        server.abort();
+        let _ = server.await;
+        std::fs::remove_file(&path).unwrap();
+        tokio::spawn(serve_execution_state(
+            UnixListener::bind(&path).unwrap(),
+            state.clone(),
+        ));
+        external.handle_consensus_output(output(3)).await;
+        assert_eq!(*state.executed.lock().unwrap(), vec![1, 2, 3]);
+    }
+}
@@ -1,12 +1,17 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 mod errors;
+mod external;
 mod state;
 mod subscriber;
 
 mod metrics;
 
 pub use errors::{SubscriberError, SubscriberResult};
+pub use external::{
+    serve_execution_state, ExternalConsensusOutput, ExternalExecutionState,
+    ExternalExecutorRequest, ExternalExecutorResponse,
+};
 pub use state::ExecutionIndices;
 use tracing::info;
 
@@ -121,6 +126,7 @@ pub async fn get_restored_consensus_output<State: ExecutionState>(
             leader,
             sub_dag_index,
             reputation_score: compressed_sub_dag.reputation_score,
+            commit_timestamp: compressed_sub_dag.commit_timestamp,
         });
     }
 
@@ -1,9 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use prometheus::{
-    default_registry, register_histogram_with_registry, register_int_counter_vec_with_registry,
-    register_int_counter_with_registry, register_int_gauge_with_registry, Histogram, IntCounter,
-    IntCounterVec, IntGauge, Registry,
+    default_registry, register_histogram_vec_with_registry, register_histogram_with_registry,
+    register_int_counter_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_with_registry, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    Registry,
 };
 
 // buckets defined in seconds
@@ -46,8 +47,13 @@ pub struct ExecutorMetrics {
     /// Latency for time taken to fetch all batches for committed subdag
     /// either from local or remote worker.
     pub batch_fetch_for_committed_subdag_total_latency: Histogram,
+    /// The time the delivery of the next committed sub dag to the executor waited for its
+    /// payload, since the previous sub dag was delivered or it was committed.
+    pub subscriber_blocked_on_payload_latency: Histogram,
     /// Counter of remote/local batch fetch statuses.
     pub subscriber_batch_fetch: IntCounterVec,
+    /// The latency of the stages of the transactions' pipeline within the executor, per stage
+    pub executor_pipeline_stage_latency: HistogramVec,
 }
 
 impl ExecutorMetrics {
@@ -91,6 +97,13 @@ impl ExecutorMetrics {
                 registry
             )
             .unwrap(),
+            subscriber_blocked_on_payload_latency: register_histogram_with_registry!(
+                "subscriber_blocked_on_payload_latency",
+                "The time the delivery of the next committed sub dag waited for its payload",
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry
+            )
+            .unwrap(),
             subscriber_processed_batches: register_int_counter_with_registry!(
                 "subscriber_processed_batches",
                 "Number of batches processed by subscriber",
@@ -129,6 +142,13 @@ impl ExecutorMetrics {
                 &["source", "status"],
                 registry
             ).unwrap(),
+            executor_pipeline_stage_latency: register_histogram_vec_with_registry!(
+                "executor_pipeline_stage_latency",
+                "The latency of the stages of the transactions' pipeline, since the previous stage",
+                &["stage"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry
+            ).unwrap(),
         }
     }
 }
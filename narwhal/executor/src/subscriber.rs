@@ -6,29 +6,38 @@ use config::{AuthorityIdentifier, Committee, WorkerCache, WorkerId};
 use crypto::NetworkPublicKey;
 
 use futures::stream::{FuturesOrdered, FuturesUnordered};
-use futures::{FutureExt, StreamExt};
+use futures::{Future, FutureExt, StreamExt};
 
 use network::WorkerRpc;
 
-use anyhow::bail;
 use prometheus::IntGauge;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::{sync::Arc, time::Duration, vec};
-use types::RequestBatchesRequest;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+    vec,
+};
+use types::{OpenBatchesStreamRequest, RequestBatchesChunkRequest, RequestBatchesV2Request};
 
 use async_trait::async_trait;
 use fastcrypto::hash::Hash;
 use mysten_metrics::spawn_logged_monitored_task;
+use telemetry_subscribers::CORRELATION_TARGET;
 use tokio::time::Instant;
 use tokio::{sync::oneshot, task::JoinHandle};
 use tracing::{debug, error, trace, warn};
 use tracing::{info, instrument};
 use types::{
     metered_channel, Batch, BatchAPI, BatchDigest, Certificate, CertificateAPI, CommittedSubDag,
-    ConditionalBroadcastReceiver, ConsensusOutput, HeaderAPI, RequestBatchesResponse, Timestamp,
+    ConditionalBroadcastReceiver, ConsensusOutput, FetchPriority, HeaderAPI,
+    OpenBatchesStreamResponse, RequestBatchesV2Response, Timestamp,
 };
 
+/// The stage of the transactions' pipeline from the sub dag being committed to it being
+/// executed, including the time to fetch its batches.
+const EXECUTED_STAGE: &str = "executed";
+
 /// The `Subscriber` receives certificates sequenced by the consensus and waits until the
 /// downloaded all the transactions references by the certificates; it then
 /// forward the certificates to the Executor.
@@ -46,6 +55,45 @@ pub struct Subscriber<Network> {
 struct Fetcher<Network> {
     network: Network,
     metrics: Arc<ExecutorMetrics>,
+    worker_scores: WorkerScores,
+}
+
+/// The reputation of the workers the batches are fetched from. A worker loses points every time
+/// it responds with batches that were not requested, and the workers are requested in order of
+/// decreasing score.
+#[derive(Default)]
+struct WorkerScores {
+    scores: Mutex<HashMap<NetworkPublicKey, i64>>,
+}
+
+impl WorkerScores {
+    /// The points lost by a worker per batch not requested in its responses.
+    const MISMATCHED_BATCH_PENALTY: i64 = 10;
+
+    fn penalize(&self, worker: &NetworkPublicKey, mismatched_batches: usize) {
+        *self
+            .scores
+            .lock()
+            .unwrap()
+            .entry(worker.clone())
+            .or_default() -= Self::MISMATCHED_BATCH_PENALTY * mismatched_batches as i64;
+    }
+
+    fn score(&self, worker: &NetworkPublicKey) -> i64 {
+        self.scores
+            .lock()
+            .unwrap()
+            .get(worker)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Orders the workers by decreasing score.
+    fn order(&self, workers: &HashSet<NetworkPublicKey>) -> Vec<NetworkPublicKey> {
+        let mut workers: Vec<_> = workers.iter().cloned().collect();
+        workers.sort_by_key(|worker| std::cmp::Reverse(self.score(worker)));
+        workers
+    }
 }
 
 pub fn spawn_subscriber<State: ExecutionState + Send + Sync + 'static>(
@@ -76,7 +124,7 @@ pub fn spawn_subscriber<State: ExecutionState + Send + Sync + 'static>(
 
     vec![
         spawn_logged_monitored_task!(
-            run_notify(state, rx_notifier, rx_shutdown_notify),
+            run_notify(state, rx_notifier, rx_shutdown_notify, metrics.clone()),
             "SubscriberNotifyTask"
         ),
         spawn_logged_monitored_task!(
@@ -98,13 +146,18 @@ pub fn spawn_subscriber<State: ExecutionState + Send + Sync + 'static>(
 
 async fn run_notify<State: ExecutionState + Send + Sync + 'static>(
     state: State,
-    mut tr_notify: metered_channel::Receiver<ConsensusOutput>,
+    mut tr_notify: metered_channel::Receiver<(ConsensusOutput, Instant)>,
     mut rx_shutdown: ConditionalBroadcastReceiver,
+    metrics: Arc<ExecutorMetrics>,
 ) {
+    let executed = metrics
+        .executor_pipeline_stage_latency
+        .with_label_values(&[EXECUTED_STAGE]);
     loop {
         tokio::select! {
-            Some(message) = tr_notify.recv() => {
+            Some((message, committed_at)) = tr_notify.recv() => {
                 state.handle_consensus_output(message).await;
+                executed.observe(committed_at.elapsed().as_secs_f64());
             }
 
             _ = rx_shutdown.receiver.recv() => {
@@ -124,7 +177,7 @@ async fn create_and_run_subscriber(
     rx_sequence: metered_channel::Receiver<CommittedSubDag>,
     metrics: Arc<ExecutorMetrics>,
     restored_consensus_output: Vec<CommittedSubDag>,
-    tx_notifier: metered_channel::Sender<ConsensusOutput>,
+    tx_notifier: metered_channel::Sender<(ConsensusOutput, Instant)>,
 ) {
     let network = network.await.expect("Failed to receive network");
     info!("Starting subscriber");
@@ -137,6 +190,7 @@ async fn create_and_run_subscriber(
     let fetcher = Fetcher {
         network,
         metrics: metrics.clone(),
+        worker_scores: WorkerScores::default(),
     };
     let subscriber = Subscriber {
         rx_shutdown,
@@ -158,7 +212,7 @@ impl<Network: SubscriberNetwork> Subscriber<Network> {
     async fn run(
         mut self,
         restored_consensus_output: Vec<CommittedSubDag>,
-        tx_notifier: metered_channel::Sender<ConsensusOutput>,
+        tx_notifier: metered_channel::Sender<(ConsensusOutput, Instant)>,
     ) -> SubscriberResult<()> {
         // It's important to have the futures in ordered fashion as we want
         // to guarantee that will deliver to the executor the certificates
@@ -167,15 +221,21 @@ impl<Network: SubscriberNetwork> Subscriber<Network> {
         // certificate. Unless the earlier certificate's payload has been
         // fetched, no later certificate will be delivered.
         let mut waiting = FuturesOrdered::new();
+        // Since when the next sub dag to deliver has been waiting for its payload, i.e. when the
+        // executor runs out of work.
+        let mut blocked_since = None;
 
         // First handle any consensus output messages that were restored due to a restart.
         // This needs to happen before we start listening on rx_sequence and receive messages sequenced after these.
         for message in restored_consensus_output {
-            let future = self.fetcher.fetch_batches(message);
+            let future = self.fetcher.fetch_committed_batches(message);
             waiting.push_back(future);
 
             self.metrics.subscriber_recovered_certificates_count.inc();
         }
+        if !waiting.is_empty() {
+            blocked_since = Some(Instant::now());
+        }
 
         // Listen to sequenced consensus message and process them.
         loop {
@@ -185,11 +245,20 @@ impl<Network: SubscriberNetwork> Subscriber<Network> {
                     // We can schedule more then MAX_PENDING_PAYLOADS payloads but
                     // don't process more consensus messages when more
                     // then MAX_PENDING_PAYLOADS is pending
-                    waiting.push_back(self.fetcher.fetch_batches(sub_dag));
+                    waiting.push_back(self.fetcher.fetch_committed_batches(sub_dag));
+                    blocked_since.get_or_insert_with(Instant::now);
                 },
 
                 // Receive here consensus messages for which we have downloaded all transactions data.
                 Some(message) = waiting.next() => {
+                    if let Some(since) = blocked_since.take() {
+                        self.metrics
+                            .subscriber_blocked_on_payload_latency
+                            .observe(since.elapsed().as_secs_f64());
+                    }
+                    if !waiting.is_empty() {
+                        blocked_since = Some(Instant::now());
+                    }
                     if let Err(e) = tx_notifier.send(message).await {
                         error!("tx_notifier closed: {}", e);
                         return Ok(());
@@ -210,6 +279,17 @@ impl<Network: SubscriberNetwork> Subscriber<Network> {
 }
 
 impl<Network: SubscriberNetwork> Fetcher<Network> {
+    /// Fetches the batches of the committed sub dag, along with the time it was received at
+    /// from consensus.
+    fn fetch_committed_batches(
+        &self,
+        deliver: CommittedSubDag,
+    ) -> impl Future<Output = (ConsensusOutput, Instant)> + '_ {
+        let committed_at = Instant::now();
+        self.fetch_batches(deliver)
+            .map(move |output| (output, committed_at))
+    }
+
     /// Returns ordered vector of futures for downloading batches for certificates
     /// Order of futures returned follows order of batches in the certificate
     /// See fetch_batches_from_worker for more details
@@ -291,6 +371,13 @@ impl<Network: SubscriberNetwork> Fetcher<Network> {
                     "Adding fetched batch {digest} from certificate {} to consensus output",
                     cert.digest()
                 );
+                debug!(
+                    target: CORRELATION_TARGET,
+                    stage = "fetched",
+                    batch = %digest,
+                    certificate = %cert.digest(),
+                    sub_dag = sub_dag.sub_dag_index,
+                );
                 output_batches.push(batch.clone());
             }
             subscriber_output
@@ -335,7 +422,9 @@ impl<Network: SubscriberNetwork> Fetcher<Network> {
                         .batch_execution_latency
                         .observe(batch_fetch_duration);
                     trace!(
-                        "Batch {local_batch_digest:?} took {batch_fetch_duration} seconds to be fetched for execution since creation",
+                        "Batch {local_batch_digest:?} took {batch_fetch_duration} seconds to be fetched for execution since creation by worker {:?} in epoch {:?}",
+                        local_batch.worker_id(),
+                        local_batch.epoch(),
                     );
                     fetched_batches.insert(local_batch_digest, local_batch);
                 }
@@ -348,8 +437,9 @@ impl<Network: SubscriberNetwork> Fetcher<Network> {
             let _timer = self.metrics.subscriber_remote_fetch_latency.start_timer();
             let mut stagger = Duration::from_secs(0);
             let mut futures = FuturesUnordered::new();
-            for worker in &workers {
-                let future = self.fetch_remote(stagger, worker.clone(), remaining_digests.clone());
+            // The workers which responded with batches not requested are tried last.
+            for worker in self.worker_scores.order(&workers) {
+                let future = self.fetch_remote(stagger, worker, remaining_digests.clone());
                 futures.push(future.boxed());
                 // TODO: Make this a parameter, and also record workers / authorities that are down
                 // to request from them batches later.
@@ -365,7 +455,9 @@ impl<Network: SubscriberNetwork> Fetcher<Network> {
                             .batch_execution_latency
                             .observe(batch_fetch_duration);
                         trace!(
-                            "Batch {remote_batch_digest:?} took {batch_fetch_duration} seconds to be fetched for execution since creation"
+                            "Batch {remote_batch_digest:?} took {batch_fetch_duration} seconds to be fetched for execution since creation by worker {:?} in epoch {:?}",
+                            remote_batch.worker_id(),
+                            remote_batch.epoch(),
                         );
 
                         fetched_batches.insert(remote_batch_digest, remote_batch);
@@ -389,66 +481,81 @@ impl<Network: SubscriberNetwork> Fetcher<Network> {
         let _timer = self.metrics.subscriber_local_fetch_latency.start_timer();
         let mut fetched_batches: HashMap<BatchDigest, Batch> = HashMap::new();
         let worker = self.network.my_worker(&worker_id);
-        let mut digests_to_fetch = digests;
-
-        // Continue to bulk request from local worker until no remaining digests
-        // are available.
-        loop {
-            if digests_to_fetch.is_empty() {
-                break;
-            }
-            debug!("Local attempt to fetch {} digests", digests_to_fetch.len());
-            let timeout = Duration::from_secs(10);
-            let rpc_response = self
-                .network
-                .request_batches(
-                    digests_to_fetch.clone().into_iter().collect(),
-                    worker.clone(),
-                    timeout,
-                )
-                .await;
-
-            match rpc_response {
-                Ok(request_batches_response) => {
-                    let RequestBatchesResponse {
-                        batches,
-                        is_size_limit_reached,
-                    } = request_batches_response;
-                    debug!("Locally found {} batches", batches.len());
-                    for local_batch in batches {
-                        self.metrics
-                            .subscriber_batch_fetch
-                            .with_label_values(&["local", "success"])
-                            .inc();
-                        digests_to_fetch.remove(&local_batch.digest());
-                        fetched_batches.insert(local_batch.digest(), local_batch);
-                    }
 
-                    if !is_size_limit_reached {
-                        break;
-                    }
+        if digests.is_empty() {
+            return fetched_batches;
+        }
+        debug!("Local attempt to fetch {} digests", digests.len());
+        let timeout = Duration::from_secs(10);
+        match self
+            .stream_batches(digests.into_iter().collect(), worker, timeout)
+            .await
+        {
+            Ok(batches) => {
+                debug!("Locally found {} batches", batches.len());
+                for local_batch in batches {
+                    self.metrics
+                        .subscriber_batch_fetch
+                        .with_label_values(&["local", "success"])
+                        .inc();
+                    fetched_batches.insert(local_batch.digest(), local_batch);
                 }
-                Err(err) => {
-                    if err.to_string().contains("Timeout") {
-                        self.metrics
-                            .subscriber_batch_fetch
-                            .with_label_values(&["local", "timeout"])
-                            .inc();
-                    } else {
-                        self.metrics
-                            .subscriber_batch_fetch
-                            .with_label_values(&["local", "fail"])
-                            .inc();
-                    }
-                    warn!("Error communicating with our own worker: {err}");
-                    break;
+            }
+            Err(err) => {
+                if err.to_string().contains("Timeout") {
+                    self.metrics
+                        .subscriber_batch_fetch
+                        .with_label_values(&["local", "timeout"])
+                        .inc();
+                } else {
+                    self.metrics
+                        .subscriber_batch_fetch
+                        .with_label_values(&["local", "fail"])
+                        .inc();
                 }
+                warn!("Error communicating with our own worker: {err}");
             }
         }
 
         fetched_batches
     }
 
+    /// Streams the requested batches from the given worker. The worker splits them into chunks
+    /// that each fit in a single response, so all the available batches are received without
+    /// resending the digests that were already served. Chunks are pulled concurrently and
+    /// reassembled in order.
+    #[instrument(level = "debug", skip_all, fields(worker = % worker, timeout = ? timeout))]
+    async fn stream_batches(
+        &self,
+        digests: Vec<BatchDigest>,
+        worker: NetworkPublicKey,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<Batch>> {
+        const MAX_CONCURRENT_CHUNK_REQUESTS: usize = 4;
+
+        let OpenBatchesStreamResponse {
+            stream_id,
+            num_chunks,
+            first_chunk,
+        } = self
+            .network
+            .open_batches_stream(digests, worker.clone(), timeout)
+            .await?;
+        debug!("Opened batches stream {stream_id} with {num_chunks} chunks");
+
+        let mut batches = first_chunk;
+        let mut chunks = futures::stream::iter(1..num_chunks)
+            .map(|chunk_index| {
+                self.network
+                    .request_batches_chunk(stream_id, chunk_index, worker.clone(), timeout)
+            })
+            .buffered(MAX_CONCURRENT_CHUNK_REQUESTS);
+        while let Some(chunk) = chunks.next().await {
+            batches.extend(chunk?);
+        }
+        Ok(batches)
+    }
+
     /// This future performs a fetch from a given remote worker
     /// This future performs infinite retries with exponential backoff
     /// You can specify stagger_delay before request is issued
@@ -532,9 +639,10 @@ impl<Network: SubscriberNetwork> Fetcher<Network> {
             if digests_to_fetch.is_empty() {
                 break;
             }
-            let RequestBatchesResponse {
+            let RequestBatchesV2Response {
                 batches,
-                is_size_limit_reached,
+                remaining_digests,
+                missing_digests,
             } = self
                 .network
                 .request_batches(
@@ -548,19 +656,48 @@ impl<Network: SubscriberNetwork> Fetcher<Network> {
             // and no batches leading us to retry this worker forever or until
             // we get batches from other workers.
             let mut is_digest_received = false;
+            let mut mismatched_batches = 0;
             for batch in batches {
+                // The digest is recomputed, so that a batch not matching any requested digest is
+                // dropped instead of being attributed to one of them.
                 let batch_digest = batch.digest();
                 if !digests_to_fetch.contains(&batch_digest) {
-                    bail!("[Protocol violation] Worker {worker} returned batch with digest {batch_digest} which is not part of the requested digests: {digests:?}");
+                    warn!("[Protocol violation] Worker {worker} returned batch with digest {batch_digest} which is not part of the requested digests");
+                    mismatched_batches += 1;
                 } else {
                     is_digest_received = true;
                     verified_batches.insert(batch_digest, batch);
                     digests_to_fetch.remove(&batch_digest);
                 }
             }
-            if !is_size_limit_reached || !is_digest_received {
+            if mismatched_batches > 0 {
+                self.metrics
+                    .subscriber_batch_fetch
+                    .with_label_values(&["remote", "mismatch"])
+                    .inc();
+                self.worker_scores.penalize(&worker, mismatched_batches);
+            }
+            // The batches the worker does not have are left to be fetched from other workers.
+            let missing_batches = missing_digests
+                .iter()
+                .filter(|digest| digests_to_fetch.contains(digest))
+                .count();
+            if missing_batches > 0 {
+                debug!("Worker {worker} is missing {missing_batches} of the requested batches");
+                self.metrics
+                    .subscriber_batch_fetch
+                    .with_label_values(&["remote", "missing"])
+                    .inc_by(missing_batches as u64);
+            }
+            if remaining_digests.is_empty() || !is_digest_received {
                 break;
             }
+            // Resume with the digests the worker left out. The ones it does not have are not
+            // requested again, and neither are the ones it claims remain but were not requested.
+            digests_to_fetch = remaining_digests
+                .into_iter()
+                .filter(|digest| digests_to_fetch.contains(digest))
+                .collect();
         }
 
         Ok(verified_batches)
@@ -600,7 +737,22 @@ pub trait SubscriberNetwork: Send + Sync {
         batch_digests: Vec<BatchDigest>,
         worker: NetworkPublicKey,
         timeout: Duration,
-    ) -> anyhow::Result<RequestBatchesResponse>;
+    ) -> anyhow::Result<RequestBatchesV2Response>;
+
+    async fn open_batches_stream(
+        &self,
+        batch_digests: Vec<BatchDigest>,
+        worker: NetworkPublicKey,
+        timeout: Duration,
+    ) -> anyhow::Result<OpenBatchesStreamResponse>;
+
+    async fn request_batches_chunk(
+        &self,
+        stream_id: u64,
+        chunk_index: u32,
+        worker: NetworkPublicKey,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<Batch>>;
 }
 
 struct SubscriberNetworkImpl {
@@ -654,10 +806,41 @@ impl SubscriberNetwork for SubscriberNetworkImpl {
         batch_digests: Vec<BatchDigest>,
         worker: NetworkPublicKey,
         timeout: Duration,
-    ) -> anyhow::Result<RequestBatchesResponse> {
+    ) -> anyhow::Result<RequestBatchesV2Response> {
+        let request = RequestBatchesV2Request {
+            batch_digests,
+            priority: FetchPriority::BlocksExecution,
+        };
+        self.network
+            .request_batches_v2(worker, request, timeout)
+            .await
+    }
+
+    async fn open_batches_stream(
+        &self,
+        batch_digests: Vec<BatchDigest>,
+        worker: NetworkPublicKey,
+        timeout: Duration,
+    ) -> anyhow::Result<OpenBatchesStreamResponse> {
         let request =
-            anemo::Request::new(RequestBatchesRequest { batch_digests }).with_timeout(timeout);
-        self.network.request_batches(worker, request).await
+            anemo::Request::new(OpenBatchesStreamRequest { batch_digests }).with_timeout(timeout);
+        self.network.open_batches_stream(worker, request).await
+    }
+
+    async fn request_batches_chunk(
+        &self,
+        stream_id: u64,
+        chunk_index: u32,
+        worker: NetworkPublicKey,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<Batch>> {
+        let request = anemo::Request::new(RequestBatchesChunkRequest {
+            stream_id,
+            chunk_index,
+        })
+        .with_timeout(timeout);
+        let response = self.network.request_batches_chunk(worker, request).await?;
+        Ok(response.batches)
     }
 }
 
@@ -691,6 +874,7 @@ mod tests {
         let fetcher = Fetcher {
             network,
             metrics: Arc::new(ExecutorMetrics::default()),
+            worker_scores: WorkerScores::default(),
         };
         let expected_batches = HashMap::from_iter(vec![
             (batch1.digest(), batch1.clone()),
@@ -733,6 +917,7 @@ mod tests {
         let fetcher = Fetcher {
             network,
             metrics: Arc::new(ExecutorMetrics::default()),
+            worker_scores: WorkerScores::default(),
         };
         let expected_batches = HashMap::from_iter(vec![
             (batch1.digest(), batch1.clone()),
@@ -746,8 +931,8 @@ mod tests {
 
     #[tokio::test]
     pub async fn test_fetcher_locally_with_remaining() {
-        // Limit is set to two batches in test request_batches(). Request 3 batches
-        // and ensure another request is sent to get the remaining batches.
+        // Chunks are limited to two batches in test open_batches_stream(). Request 3 batches
+        // and ensure the remaining chunk is pulled to get the remaining batches.
         let mut network = TestSubscriberNetwork::new(1);
         let batch1 = Batch::new(vec![vec![1]]);
         let batch2 = Batch::new(vec![vec![2]]);
@@ -768,6 +953,7 @@ mod tests {
         let fetcher = Fetcher {
             network,
             metrics: Arc::new(ExecutorMetrics::default()),
+            worker_scores: WorkerScores::default(),
         };
         let expected_batches = HashMap::from_iter(vec![
             (batch1.digest(), batch1.clone()),
@@ -804,6 +990,7 @@ mod tests {
         let fetcher = Fetcher {
             network,
             metrics: Arc::new(ExecutorMetrics::default()),
+            worker_scores: WorkerScores::default(),
         };
         let expected_batches = HashMap::from_iter(vec![
             (batch1.digest(), batch1.clone()),
@@ -838,6 +1025,7 @@ mod tests {
         let fetcher = Fetcher {
             network,
             metrics: Arc::new(ExecutorMetrics::default()),
+            worker_scores: WorkerScores::default(),
         };
         let expected_batches = HashMap::from_iter(vec![
             (batch1.digest(), batch1.clone()),
@@ -886,6 +1074,7 @@ mod tests {
         let fetcher = Fetcher {
             network,
             metrics: Arc::new(ExecutorMetrics::default()),
+            worker_scores: WorkerScores::default(),
         };
         let fetched_batches = fetcher
             .fetch_batches_from_worker(batch_digests_and_workers)
@@ -893,10 +1082,103 @@ mod tests {
         assert_eq!(fetched_batches, expected_batches);
     }
 
+    #[tokio::test]
+    pub async fn test_safe_request_batches_resumes_from_remaining() {
+        // Limit is set to two batches in test request_batches(). Request 5 batches and one
+        // the worker does not have, and ensure every batch is only downloaded once.
+        let mut network = TestSubscriberNetwork::new(1);
+        let batches: Vec<_> = (1..=5).map(|i| Batch::new(vec![vec![i]])).collect();
+        for batch in &batches {
+            network.put(0, &[2], batch.clone());
+        }
+        let unknown = Batch::new(vec![vec![6]]);
+        let mut digests: HashSet<_> = batches.iter().map(|batch| batch.digest()).collect();
+        digests.insert(unknown.digest());
+        let fetcher = Fetcher {
+            network,
+            metrics: Arc::new(ExecutorMetrics::default()),
+            worker_scores: WorkerScores::default(),
+        };
+
+        let fetched_batches = fetcher
+            .safe_request_batches(digests, test_pk(2), Duration::from_secs(1))
+            .await
+            .unwrap();
+        let expected_batches: HashMap<_, _> = batches
+            .iter()
+            .map(|batch| (batch.digest(), batch.clone()))
+            .collect();
+        assert_eq!(fetched_batches, expected_batches);
+        let served = fetcher.network.served.lock().unwrap().clone();
+        assert_eq!(served.len(), 5);
+        assert_eq!(served.iter().unique().count(), 5);
+        // The batch the worker does not have is reported missing, and not requested again.
+        assert_eq!(
+            fetcher
+                .metrics
+                .subscriber_batch_fetch
+                .with_label_values(&["remote", "missing"])
+                .get(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    pub async fn test_safe_request_batches_drops_mismatched_batches() {
+        let mut network = TestSubscriberNetwork::new(1);
+        let batch1 = Batch::new(vec![vec![1]]);
+        let batch2 = Batch::new(vec![vec![2]]);
+        network.put(0, &[2, 3], batch1.clone());
+        // Worker 2 also responds with a batch that was not requested.
+        network.forged.insert(test_pk(2), batch2);
+        let fetcher = Fetcher {
+            network,
+            metrics: Arc::new(ExecutorMetrics::default()),
+            worker_scores: WorkerScores::default(),
+        };
+
+        let fetched_batches = fetcher
+            .safe_request_batches(
+                HashSet::from_iter(vec![batch1.digest()]),
+                test_pk(2),
+                Duration::from_secs(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            fetched_batches,
+            HashMap::from_iter(vec![(batch1.digest(), batch1.clone())])
+        );
+        assert!(fetcher.worker_scores.score(&test_pk(2)) < 0);
+        assert_eq!(fetcher.worker_scores.score(&test_pk(3)), 0);
+        assert_eq!(
+            fetcher
+                .metrics
+                .subscriber_batch_fetch
+                .with_label_values(&["remote", "mismatch"])
+                .get(),
+            1
+        );
+
+        // The malicious worker is now requested last.
+        assert_eq!(
+            fetcher
+                .worker_scores
+                .order(&HashSet::from_iter(test_pks(&[2, 3]))),
+            test_pks(&[3, 2])
+        );
+    }
+
     struct TestSubscriberNetwork {
         data: HashMap<WorkerId, HashMap<BatchDigest, HashMap<NetworkPublicKey, Batch>>>,
         worker_cache: HashMap<NetworkPublicKey, WorkerId>,
         my: HashMap<WorkerId, NetworkPublicKey>,
+        // The chunks of the opened batches streams, indexed by stream id.
+        streams: Mutex<Vec<Vec<Vec<Batch>>>>,
+        // The digests of the batches served by request_batches(), in order.
+        served: Mutex<Vec<BatchDigest>>,
+        // The batches the malicious workers add to their responses to request_batches().
+        forged: HashMap<NetworkPublicKey, Batch>,
     }
 
     impl TestSubscriberNetwork {
@@ -914,6 +1196,9 @@ mod tests {
                 data,
                 worker_cache,
                 my,
+                streams: Default::default(),
+                served: Default::default(),
+                forged: Default::default(),
             }
         }
 
@@ -966,12 +1251,13 @@ mod tests {
             digests: Vec<BatchDigest>,
             worker: NetworkPublicKey,
             _timeout: Duration,
-        ) -> anyhow::Result<RequestBatchesResponse> {
+        ) -> anyhow::Result<RequestBatchesV2Response> {
             // Use this to simulate server side response size limit in RequestBatches
             const MAX_REQUEST_BATCHES_RESPONSE_SIZE: usize = 2;
             const MAX_READ_BATCH_DIGESTS: usize = 5;
 
-            let mut is_size_limit_reached = false;
+            let mut remaining_digests = Vec::new();
+            let mut missing_digests = Vec::new();
             let mut batches = Vec::new();
             let mut total_size = 0;
 
@@ -982,30 +1268,100 @@ mod tests {
             let worker_id = self.worker_cache.get(&worker).unwrap();
             for digests_chunk in digests_chunks {
                 for digest in digests_chunk {
+                    if !remaining_digests.is_empty() {
+                        remaining_digests.push(digest);
+                        continue;
+                    }
                     if let Some(batch) = self
                         .data
                         .get(worker_id)
                         .unwrap()
                         .get(&digest)
-                        .unwrap()
-                        .get(&worker)
+                        .and_then(|batches| batches.get(&worker))
                     {
                         if total_size < MAX_REQUEST_BATCHES_RESPONSE_SIZE {
                             batches.push(batch.clone());
                             total_size += batch.size();
                         } else {
-                            is_size_limit_reached = true;
-                            break;
+                            remaining_digests.push(digest);
                         }
+                    } else {
+                        missing_digests.push(digest);
                     }
                 }
             }
+            self.served
+                .lock()
+                .unwrap()
+                .extend(batches.iter().map(|batch| batch.digest()));
+            if let Some(forged) = self.forged.get(&worker) {
+                batches.push(forged.clone());
+            }
 
-            Ok(RequestBatchesResponse {
+            Ok(RequestBatchesV2Response {
                 batches,
-                is_size_limit_reached,
+                remaining_digests,
+                missing_digests,
             })
         }
+
+        async fn open_batches_stream(
+            &self,
+            digests: Vec<BatchDigest>,
+            worker: NetworkPublicKey,
+            _timeout: Duration,
+        ) -> anyhow::Result<OpenBatchesStreamResponse> {
+            // Use this to simulate server side chunk size limit in OpenBatchesStream
+            const MAX_BATCHES_CHUNK_SIZE: usize = 2;
+
+            let worker_id = self.worker_cache.get(&worker).unwrap();
+            let mut chunks: Vec<Vec<Batch>> = vec![vec![]];
+            let mut chunk_size = 0;
+            for digest in digests {
+                if let Some(batch) = self
+                    .data
+                    .get(worker_id)
+                    .unwrap()
+                    .get(&digest)
+                    .unwrap()
+                    .get(&worker)
+                {
+                    if chunk_size >= MAX_BATCHES_CHUNK_SIZE {
+                        chunks.push(vec![]);
+                        chunk_size = 0;
+                    }
+                    chunks.last_mut().unwrap().push(batch.clone());
+                    chunk_size += batch.size();
+                }
+            }
+
+            let num_chunks = chunks.len() as u32;
+            let first_chunk = chunks[0].clone();
+            let mut streams = self.streams.lock().unwrap();
+            streams.push(chunks);
+            Ok(OpenBatchesStreamResponse {
+                stream_id: (streams.len() - 1) as u64,
+                num_chunks,
+                first_chunk,
+            })
+        }
+
+        async fn request_batches_chunk(
+            &self,
+            stream_id: u64,
+            chunk_index: u32,
+            _worker: NetworkPublicKey,
+            _timeout: Duration,
+        ) -> anyhow::Result<Vec<Batch>> {
+            let streams = self.streams.lock().unwrap();
+            let chunk = streams
+                .get(stream_id as usize)
+                .and_then(|chunks| chunks.get(chunk_index as usize))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Unknown chunk {chunk_index} of stream {stream_id}")
+                })?;
+            Ok(chunk.clone())
+        }
     }
 
     fn test_pk(i: u8) -> NetworkPublicKey {
@@ -3,6 +3,7 @@
 use bytes::Bytes;
 use consensus::bullshark::Bullshark;
 use consensus::consensus::ConsensusRound;
+use consensus::leader_schedule::{LeaderSchedule, LeaderSwapTable};
 use consensus::metrics::ConsensusMetrics;
 use consensus::Consensus;
 use fastcrypto::hash::Hash;
@@ -64,6 +65,7 @@ async fn test_recovery() {
         consensus_store.clone(),
         metrics.clone(),
         NUM_SUB_DAGS_PER_SCHEDULE,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     let _consensus_handle = Consensus::spawn(
@@ -170,6 +172,7 @@ async fn test_internal_consensus_output() {
         let tr = bcs::to_bytes(&tx).unwrap();
         let txn = TransactionProto {
             transaction: Bytes::from(tr),
+            ..Default::default()
         };
         client.submit_transaction(txn).await.unwrap();
 
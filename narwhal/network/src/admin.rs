@@ -1,9 +1,12 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::metrics::{PeerMetrics, PeerStats};
 use axum::{extract::Extension, http::StatusCode, routing::get, Json, Router};
 use mysten_metrics::{spawn_logged_monitored_task, spawn_monitored_task};
+use std::collections::BTreeMap;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
@@ -13,12 +16,24 @@ use types::ConditionalBroadcastReceiver;
 pub fn start_admin_server(
     port: u16,
     network: anemo::Network,
+    tr_shutdown: ConditionalBroadcastReceiver,
+) -> Vec<JoinHandle<()>> {
+    start_admin_server_with_routes(port, network, Router::new(), tr_shutdown)
+}
+
+/// Starts the admin server, serving the provided routes along with the network ones.
+pub fn start_admin_server_with_routes(
+    port: u16,
+    network: anemo::Network,
+    routes: Router,
     mut tr_shutdown: ConditionalBroadcastReceiver,
 ) -> Vec<JoinHandle<()>> {
     let mut router = Router::new()
         .route("/peers", get(get_peers))
         .route("/known_peers", get(get_known_peers));
 
+    router = router.merge(routes);
+
     router = router.layer(Extension(network));
 
     let socket_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
@@ -80,6 +95,19 @@ pub fn start_admin_server(
     handles
 }
 
+/// The routes dumping the per-peer network health, to be served by the admin server.
+pub fn peer_metrics_routes(peer_metrics: Arc<PeerMetrics>) -> Router {
+    Router::new()
+        .route("/peer_metrics", get(get_peer_metrics))
+        .layer(Extension(peer_metrics))
+}
+
+async fn get_peer_metrics(
+    Extension(peer_metrics): Extension<Arc<PeerMetrics>>,
+) -> (StatusCode, Json<BTreeMap<String, PeerStats>>) {
+    (StatusCode::OK, Json(peer_metrics.snapshot()))
+}
+
 async fn get_peers(
     Extension(network): Extension<anemo::Network>,
 ) -> (StatusCode, Json<Vec<String>>) {
@@ -0,0 +1,310 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::{
+    metrics::{ConnectionManagerMetrics, PeerMetrics},
+    p2p::send,
+    CancelOnDropHandler,
+};
+use anemo::PeerId;
+use crypto::NetworkPublicKey;
+use dashmap::DashMap;
+use std::{fmt, sync::Arc, time::Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+use types::{
+    WorkerBatchChunkMessage, WorkerBatchMessage, WorkerRelayBatchMessage, WorkerRelayBatchResponse,
+    WorkerToWorkerClient,
+};
+
+/// The logical channels multiplexed on the connection with a peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    /// The bulk broadcast of our batches.
+    Broadcast,
+    /// The requests for batches, and their responses.
+    Request,
+}
+
+impl Channel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Broadcast => "broadcast",
+            Channel::Request => "request",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The capacity of the channels with a single peer.
+struct PeerChannels {
+    /// The bytes of our broadcasts which may be in flight to the peer.
+    broadcast_bytes: Arc<Semaphore>,
+    /// The number of requests which may be in flight to the peer.
+    requests: Arc<Semaphore>,
+}
+
+/// Held while a message is in flight on a channel, releasing its capacity when dropped.
+pub struct ChannelPermit {
+    _permit: OwnedSemaphorePermit,
+    channel: Channel,
+    metrics: Option<Arc<ConnectionManagerMetrics>>,
+}
+
+impl Drop for ChannelPermit {
+    fn drop(&mut self) {
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .channel_inflight
+                .with_label_values(&[self.channel.as_str()])
+                .dec();
+        }
+    }
+}
+
+/// Manages the connections of a worker with its peers. The connection with a peer is reused
+/// by all the messages, and multiplexes a broadcast and a request channel. The bytes of the
+/// broadcasts in flight to a peer are bounded, so that bulk broadcasts cannot fill the flow
+/// control windows of the connection: the batch requests and the responses served to the peer
+/// always find room on the connection, instead of being head-of-line blocked behind the
+/// broadcasts.
+#[derive(Clone)]
+pub struct ConnectionManager {
+    peers: Arc<DashMap<PeerId, Arc<PeerChannels>>>,
+    max_inflight_broadcast_bytes: usize,
+    max_concurrent_requests: usize,
+    metrics: Option<Arc<ConnectionManagerMetrics>>,
+    peer_metrics: Option<Arc<PeerMetrics>>,
+}
+
+impl ConnectionManager {
+    /// The default bound of the broadcast bytes in flight to a peer, half of the send window
+    /// of the worker connections.
+    pub const DEFAULT_MAX_INFLIGHT_BROADCAST_BYTES: usize = 100 << 20;
+    /// The default bound of the requests in flight to a peer.
+    pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 1_000;
+
+    pub fn new(
+        max_inflight_broadcast_bytes: usize,
+        max_concurrent_requests: usize,
+        metrics: Option<Arc<ConnectionManagerMetrics>>,
+    ) -> Self {
+        Self {
+            peers: Arc::new(DashMap::new()),
+            max_inflight_broadcast_bytes: max_inflight_broadcast_bytes.clamp(1, u32::MAX as usize),
+            max_concurrent_requests: max_concurrent_requests.max(1),
+            metrics,
+            peer_metrics: None,
+        }
+    }
+
+    /// Records the failed dials in the per-peer metrics.
+    pub fn with_peer_metrics(mut self, peer_metrics: Arc<PeerMetrics>) -> Self {
+        self.peer_metrics = Some(peer_metrics);
+        self
+    }
+
+    fn channels(&self, peer_id: PeerId) -> Arc<PeerChannels> {
+        self.peers
+            .entry(peer_id)
+            .or_insert_with(|| {
+                Arc::new(PeerChannels {
+                    broadcast_bytes: Arc::new(Semaphore::new(self.max_inflight_broadcast_bytes)),
+                    requests: Arc::new(Semaphore::new(self.max_concurrent_requests)),
+                })
+            })
+            .clone()
+    }
+
+    /// Returns the connection with the peer. If the peer is not connected but known to the
+    /// network, it is dialed instead of waiting for the connectivity check to reconnect it.
+    pub async fn peer(&self, network: &anemo::Network, peer_id: PeerId) -> Option<anemo::Peer> {
+        if let Some(peer) = network.peer(peer_id) {
+            return Some(peer);
+        }
+        let addresses = network.known_peers().get(&peer_id)?.address;
+        for address in addresses {
+            let result = network.connect_with_peer_id(address, peer_id).await;
+            if let Some(metrics) = &self.metrics {
+                let label = if result.is_ok() { "success" } else { "failure" };
+                metrics.dials.with_label_values(&[label]).inc();
+            }
+            match result {
+                Ok(_) => return network.peer(peer_id),
+                Err(e) => {
+                    debug!("Failed to dial peer {peer_id}: {e:?}");
+                    if let Some(peer_metrics) = &self.peer_metrics {
+                        peer_metrics.record_failed_dial(peer_id);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Waits for room on the channel with the peer, for a message of the given size. The size
+    /// only matters for the broadcast channel.
+    pub async fn acquire(&self, peer_id: PeerId, channel: Channel, size: usize) -> ChannelPermit {
+        let channels = self.channels(peer_id);
+        let start = Instant::now();
+        let permit = match channel {
+            Channel::Broadcast => {
+                // A message larger than the whole budget waits for the channel to be empty.
+                let bytes = size.clamp(1, self.max_inflight_broadcast_bytes) as u32;
+                channels.broadcast_bytes.acquire_many_owned(bytes).await
+            }
+            Channel::Request => channels.requests.acquire_owned().await,
+        }
+        .expect("The channel semaphores are never closed");
+
+        if let Some(metrics) = &self.metrics {
+            let label = [channel.as_str()];
+            metrics
+                .channel_wait_latency
+                .with_label_values(&label)
+                .observe(start.elapsed().as_secs_f64());
+            metrics.channel_inflight.with_label_values(&label).inc();
+        }
+        ChannelPermit {
+            _permit: permit,
+            channel,
+            metrics: self.metrics.clone(),
+        }
+    }
+
+    /// Reliably broadcasts the batch to the peer on the broadcast channel, retrying until the
+    /// returned handle is dropped.
+    pub fn broadcast(
+        &self,
+        network: &anemo::Network,
+        peer: NetworkPublicKey,
+        message: &WorkerBatchMessage,
+    ) -> CancelOnDropHandler<anemo::Result<anemo::Response<()>>> {
+        let manager = self.clone();
+        let network = network.clone();
+        let message = message.to_owned();
+        CancelOnDropHandler(tokio::spawn(async move {
+            let peer_id = PeerId(peer.0.to_bytes());
+            let _permit = manager
+                .acquire(peer_id, Channel::Broadcast, message.batch.size())
+                .await;
+            let f = move |peer| {
+                let message = message.clone();
+                async move { WorkerToWorkerClient::new(peer).report_batch(message).await }
+            };
+            send(network, peer, f).await
+        }))
+    }
+
+    /// Reliably sends the erasure coded chunk of a batch to the peer on the broadcast channel,
+    /// retrying until the returned handle is dropped.
+    pub fn broadcast_chunk(
+        &self,
+        network: &anemo::Network,
+        peer: NetworkPublicKey,
+        message: WorkerBatchChunkMessage,
+    ) -> CancelOnDropHandler<anemo::Result<anemo::Response<()>>> {
+        let manager = self.clone();
+        let network = network.clone();
+        CancelOnDropHandler(tokio::spawn(async move {
+            let peer_id = PeerId(peer.0.to_bytes());
+            let _permit = manager
+                .acquire(peer_id, Channel::Broadcast, message.chunk.len())
+                .await;
+            let f = move |peer| {
+                let message = message.clone();
+                async move {
+                    WorkerToWorkerClient::new(peer)
+                        .report_batch_chunk(message)
+                        .await
+                }
+            };
+            send(network, peer, f).await
+        }))
+    }
+
+    /// Reliably sends the batch to the peer on the broadcast channel, for the peer to relay it
+    /// to the workers of its region, retrying until the returned handle is dropped.
+    pub fn relay_batch(
+        &self,
+        network: &anemo::Network,
+        peer: NetworkPublicKey,
+        message: WorkerRelayBatchMessage,
+    ) -> CancelOnDropHandler<anemo::Result<anemo::Response<WorkerRelayBatchResponse>>> {
+        let manager = self.clone();
+        let network = network.clone();
+        CancelOnDropHandler(tokio::spawn(async move {
+            let peer_id = PeerId(peer.0.to_bytes());
+            let _permit = manager
+                .acquire(peer_id, Channel::Broadcast, message.message.batch.size())
+                .await;
+            let f = move |peer| {
+                let message = message.clone();
+                async move { WorkerToWorkerClient::new(peer).relay_batch(message).await }
+            };
+            send(network, peer, f).await
+        }))
+    }
+}
+
+impl Default for ConnectionManager {
+    fn default() -> Self {
+        Self::new(
+            Self::DEFAULT_MAX_INFLIGHT_BROADCAST_BYTES,
+            Self::DEFAULT_MAX_CONCURRENT_REQUESTS,
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Channel, ConnectionManager};
+    use crate::metrics::ConnectionManagerMetrics;
+    use anemo::PeerId;
+    use prometheus::Registry;
+    use std::{sync::Arc, time::Duration};
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn broadcasts_do_not_block_requests() {
+        let metrics = Arc::new(ConnectionManagerMetrics::new("test", &Registry::new()));
+        let manager = ConnectionManager::new(1_000, 2, Some(metrics.clone()));
+        let peer = PeerId([1; 32]);
+        let other_peer = PeerId([2; 32]);
+
+        // The broadcast channel with the peer is full.
+        let broadcast = manager.acquire(peer, Channel::Broadcast, 600).await;
+        assert!(timeout(
+            Duration::from_millis(100),
+            manager.acquire(peer, Channel::Broadcast, 600)
+        )
+        .await
+        .is_err());
+
+        // But requests to the peer, and broadcasts to other peers, still go through.
+        let _request = manager.acquire(peer, Channel::Request, 0).await;
+        let _other_broadcast = manager.acquire(other_peer, Channel::Broadcast, 600).await;
+        assert_eq!(
+            metrics
+                .channel_inflight
+                .with_label_values(&["broadcast"])
+                .get(),
+            2
+        );
+
+        // A broadcast larger than the whole budget waits for the channel to be empty.
+        drop(broadcast);
+        let _large = manager.acquire(peer, Channel::Broadcast, 10_000).await;
+        assert!(timeout(
+            Duration::from_millis(100),
+            manager.acquire(peer, Channel::Broadcast, 1)
+        )
+        .await
+        .is_err());
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anemo::{
+    types::{Address, PeerInfo},
+    PeerId,
+};
+use config::{Committee, WorkerCache};
+use mysten_metrics::spawn_logged_monitored_task;
+use std::{collections::BTreeMap, time::Duration};
+use tokio::{
+    sync::watch,
+    task::JoinHandle,
+    time::{interval_at, Instant, MissedTickBehavior},
+};
+use tracing::{info, warn};
+use types::ConditionalBroadcastReceiver;
+
+/// The interval at which the peers not connected are dialed at their next address, if they have
+/// several.
+pub const UNREACHABLE_PEER_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The network addresses of the primaries and workers of a committee, by peer id. The first
+/// address of a peer is the one of the committee, the next ones are the addresses it may have
+/// moved to during the epoch, e.g. the ones it staged for the next epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerAddresses(pub BTreeMap<PeerId, Vec<Address>>);
+
+impl PeerAddresses {
+    pub fn new(committee: &Committee, worker_cache: &WorkerCache) -> Self {
+        let primaries = committee
+            .authorities()
+            .map(|authority| (authority.network_key(), authority.primary_address()));
+        let addresses = primaries
+            .chain(worker_cache.all_workers())
+            .filter_map(|(network_key, address)| {
+                let peer_id = PeerId(network_key.0.to_bytes());
+                match address.to_anemo_address() {
+                    Ok(address) => Some((peer_id, vec![address])),
+                    Err(e) => {
+                        warn!("Invalid address {address} of peer {peer_id}: {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self(addresses)
+    }
+
+    /// Adds the addresses the peers may have moved to, after their address in the committee.
+    /// The addresses of the peers not in the committee are ignored.
+    pub fn with_fallbacks(
+        mut self,
+        fallbacks: impl IntoIterator<Item = (PeerId, Address)>,
+    ) -> Self {
+        for (peer_id, address) in fallbacks {
+            if let Some(addresses) = self.0.get_mut(&peer_id) {
+                if !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+            }
+        }
+        self
+    }
+}
+
+/// Keeps the addresses of the known peers of the network up to date with the committee, whose
+/// members may move to other network addresses during the epoch. A peer whose address changed is
+/// disconnected, so that it is dialed again at its new address, and a peer which cannot be
+/// connected to is dialed at its next address in turn, if it has several.
+///
+/// Only the peers already known are updated: the set of peers is fixed for the epoch.
+pub struct PeerDiscovery {
+    network: anemo::NetworkRef,
+    rx_peer_addresses: watch::Receiver<PeerAddresses>,
+    retry_interval: Duration,
+    rx_shutdown: ConditionalBroadcastReceiver,
+}
+
+impl PeerDiscovery {
+    #[must_use]
+    pub fn spawn(
+        network: anemo::NetworkRef,
+        rx_peer_addresses: watch::Receiver<PeerAddresses>,
+        retry_interval: Duration,
+        rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        spawn_logged_monitored_task!(
+            Self {
+                network,
+                rx_peer_addresses,
+                retry_interval,
+                rx_shutdown,
+            }
+            .run(),
+            "PeerDiscovery"
+        )
+    }
+
+    async fn run(mut self) {
+        // The peers are given the time to connect at their address in the committee first.
+        let mut retry = interval_at(Instant::now() + self.retry_interval, self.retry_interval);
+        retry.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut watching = true;
+        loop {
+            tokio::select! {
+                result = self.rx_peer_addresses.changed(), if watching => {
+                    // The committee is no longer watched, the addresses are not updated anymore.
+                    if result.is_err() {
+                        watching = false;
+                        continue;
+                    }
+                    let Some(network) = self.network.upgrade() else {
+                        return;
+                    };
+                    let peer_addresses = self.rx_peer_addresses.borrow().clone();
+                    Self::update_known_peers(&network, &peer_addresses);
+                },
+
+                _ = retry.tick() => {
+                    let Some(network) = self.network.upgrade() else {
+                        return;
+                    };
+                    let peer_addresses = self.rx_peer_addresses.borrow().clone();
+                    Self::dial_unreachable_peers(&network, &peer_addresses);
+                },
+
+                _ = self.rx_shutdown.receiver.recv() => {
+                    return
+                }
+            }
+        }
+    }
+
+    fn update_known_peers(network: &anemo::Network, peer_addresses: &PeerAddresses) {
+        for (peer_id, addresses) in &peer_addresses.0 {
+            let Some(peer_info) = network.known_peers().get(peer_id) else {
+                continue;
+            };
+            let Some(address) = addresses.first() else {
+                continue;
+            };
+            // The peer may be known at one of its fallback addresses already.
+            if matches!(&peer_info.address[..], [known] if addresses.contains(known)) {
+                continue;
+            }
+            info!("Peer {peer_id} moved to address {address}");
+            network.known_peers().insert(PeerInfo {
+                peer_id: *peer_id,
+                affinity: peer_info.affinity,
+                address: vec![address.clone()],
+            });
+            // Not connected to the peer if it fails, in which case it is dialed at the new
+            // address anyway.
+            let _ = network.disconnect(*peer_id);
+        }
+    }
+
+    fn dial_unreachable_peers(network: &anemo::Network, peer_addresses: &PeerAddresses) {
+        for (peer_id, addresses) in &peer_addresses.0 {
+            if addresses.len() < 2 || network.peer(*peer_id).is_some() {
+                continue;
+            }
+            let Some(peer_info) = network.known_peers().get(peer_id) else {
+                continue;
+            };
+            let next = peer_info
+                .address
+                .first()
+                .and_then(|known| addresses.iter().position(|address| address == known))
+                .map_or(0, |index| (index + 1) % addresses.len());
+            let address = &addresses[next];
+            info!("Peer {peer_id} is unreachable, dialing it at address {address}");
+            network.known_peers().insert(PeerInfo {
+                peer_id: *peer_id,
+                affinity: peer_info.affinity,
+                address: vec![address.clone()],
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::{PeerAddresses, PeerDiscovery, UNREACHABLE_PEER_RETRY_INTERVAL};
+    use anemo::{
+        types::{Address, PeerAffinity, PeerInfo},
+        Network, PeerId, Request, Response,
+    };
+    use bytes::Bytes;
+    use mysten_network::Multiaddr;
+    use std::collections::BTreeMap;
+    use std::convert::Infallible;
+    use std::time::Duration;
+    use tokio::sync::watch;
+    use tokio::time::{sleep, timeout};
+    use tower::util::BoxCloneService;
+    use types::PreSubscribedBroadcastSender;
+
+    #[tokio::test]
+    async fn test_update_known_peers() {
+        // GIVEN a peer known at a wrong address
+        let network_1 = build_network().unwrap();
+        let network_2 = build_network().unwrap();
+        let peer_2 = network_2.peer_id();
+        let unknown_peer = PeerId([7; 32]);
+        network_1.known_peers().insert(PeerInfo {
+            peer_id: peer_2,
+            affinity: PeerAffinity::High,
+            address: vec![anemo_address(1)],
+        });
+
+        let (tx_peer_addresses, rx_peer_addresses) = watch::channel(PeerAddresses::default());
+        let mut tx_shutdown = PreSubscribedBroadcastSender::new(1);
+        let _h = PeerDiscovery::spawn(
+            network_1.downgrade(),
+            rx_peer_addresses,
+            UNREACHABLE_PEER_RETRY_INTERVAL,
+            tx_shutdown.subscribe(),
+        );
+
+        // WHEN the committee advertises its actual address
+        let address = anemo_address(network_2.local_addr().port());
+        tx_peer_addresses
+            .send(PeerAddresses(BTreeMap::from([
+                (peer_2, vec![address.clone()]),
+                (unknown_peer, vec![anemo_address(2)]),
+            ])))
+            .unwrap();
+
+        // THEN the known peer is updated, and only it
+        timeout(Duration::from_secs(5), async {
+            while network_1.known_peers().get(&peer_2).unwrap().address != [address.clone()] {
+                sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let peer_info = network_1.known_peers().get(&peer_2).unwrap();
+        assert_eq!(peer_info.affinity, PeerAffinity::High);
+        assert!(network_1.known_peers().get(&unknown_peer).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dial_unreachable_peer_at_fallback_address() {
+        // GIVEN a peer of the committee which moved to its fallback address
+        let network_1 = build_network().unwrap();
+        let network_2 = build_network().unwrap();
+        let peer_2 = network_2.peer_id();
+        let address = anemo_address(network_2.local_addr().port());
+        network_1.known_peers().insert(PeerInfo {
+            peer_id: peer_2,
+            affinity: PeerAffinity::High,
+            address: vec![anemo_address(1)],
+        });
+        let peer_addresses = PeerAddresses(BTreeMap::from([(peer_2, vec![anemo_address(1)])]))
+            .with_fallbacks([
+                (peer_2, address.clone()),
+                (PeerId([7; 32]), anemo_address(2)),
+            ]);
+        assert_eq!(
+            peer_addresses.0,
+            BTreeMap::from([(peer_2, vec![anemo_address(1), address.clone()])])
+        );
+
+        // WHEN the peer cannot be connected to at its address in the committee
+        let (_tx_peer_addresses, rx_peer_addresses) = watch::channel(peer_addresses);
+        let mut tx_shutdown = PreSubscribedBroadcastSender::new(1);
+        let _h = PeerDiscovery::spawn(
+            network_1.downgrade(),
+            rx_peer_addresses,
+            Duration::from_millis(200),
+            tx_shutdown.subscribe(),
+        );
+
+        // THEN it is dialed at its fallback address
+        timeout(Duration::from_secs(5), async {
+            while network_1.known_peers().get(&peer_2).unwrap().address != [address.clone()] {
+                sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    fn anemo_address(port: u16) -> Address {
+        let address: Multiaddr = format!("/ip4/127.0.0.1/udp/{port}").parse().unwrap();
+        address.to_anemo_address().unwrap()
+    }
+
+    fn build_network() -> anyhow::Result<Network> {
+        let network = Network::bind("localhost:0")
+            .private_key(random_private_key())
+            .server_name("test")
+            .start(echo_service())?;
+        Ok(network)
+    }
+
+    fn echo_service() -> BoxCloneService<Request<Bytes>, Response<Bytes>, Infallible> {
+        let handle = move |request: Request<Bytes>| async move {
+            let response = Response::new(request.into_body());
+            Result::<Response<Bytes>, Infallible>::Ok(response)
+        };
+
+        tower::ServiceExt::boxed_clone(tower::service_fn(handle))
+    }
+
+    fn random_private_key() -> [u8; 32] {
+        let mut rng = rand::thread_rng();
+        let mut bytes = [0u8; 32];
+        rand::RngCore::fill_bytes(&mut rng, &mut bytes[..]);
+
+        bytes
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anemo::rpc::Status;
+use anemo::types::response::IntoResponse;
+use anemo::{Request, Response};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower::{Layer, Service};
+
+/// Injects faults into the inbound requests: each request is either dropped, or delayed by a
+/// random duration, which reorders the requests. The faults are drawn from a seeded RNG, so that
+/// a run under the simulator is reproducible.
+#[derive(Clone, Default)]
+pub struct FaultInjectionLayer {
+    faults: Option<Arc<Faults>>,
+}
+
+struct Faults {
+    rng: Mutex<StdRng>,
+    drop_probability: f64,
+    max_delay: Duration,
+}
+
+impl FaultInjectionLayer {
+    pub fn new(seed: u64, drop_probability: f64, max_delay: Duration) -> Self {
+        Self {
+            faults: Some(Arc::new(Faults {
+                rng: Mutex::new(StdRng::seed_from_u64(seed)),
+                drop_probability: drop_probability.clamp(0.0, 1.0),
+                max_delay,
+            })),
+        }
+    }
+}
+
+impl Faults {
+    /// Draws whether the next request is dropped, and its delay.
+    fn sample(&self) -> (bool, Duration) {
+        let mut rng = self.rng.lock().unwrap();
+        let dropped = rng.gen_bool(self.drop_probability);
+        let delay = self.max_delay.mul_f64(rng.gen_range(0.0..=1.0));
+        (dropped, delay)
+    }
+}
+
+impl<S> Layer<S> for FaultInjectionLayer {
+    type Service = FaultInjection<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FaultInjection {
+            inner,
+            faults: self.faults.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FaultInjection<S> {
+    inner: S,
+    faults: Option<Arc<Faults>>,
+}
+
+impl<S> Service<Request<Bytes>> for FaultInjection<S>
+where
+    S: Service<Request<Bytes>, Response = Response<Bytes>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Bytes>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Bytes>) -> Self::Future {
+        let Some(faults) = &self.faults else {
+            return self.inner.call(request).boxed();
+        };
+        let (dropped, delay) = faults.sample();
+
+        // The service that was polled ready is the one called once the delay has elapsed.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        async move {
+            tokio::time::sleep(delay).await;
+            if dropped {
+                return Ok(Status::internal("Request dropped by fault injection").into_response());
+            }
+            inner.call(request).await
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anemo::types::response::StatusCode;
+    use tower::{BoxError, ServiceBuilder, ServiceExt};
+
+    async fn call(layer: FaultInjectionLayer) -> StatusCode {
+        ServiceBuilder::new()
+            .layer(layer)
+            .service_fn(echo)
+            .oneshot(Request::new(Bytes::from("foobar")))
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn inject_faults() {
+        assert_eq!(
+            call(FaultInjectionLayer::default()).await,
+            StatusCode::Success
+        );
+        assert_eq!(
+            call(FaultInjectionLayer::new(1, 1.0, Duration::ZERO)).await,
+            StatusCode::InternalServerError
+        );
+
+        let max_delay = Duration::from_secs(10);
+        let started_at = tokio::time::Instant::now();
+        assert_eq!(
+            call(FaultInjectionLayer::new(1, 0.0, max_delay)).await,
+            StatusCode::Success
+        );
+        assert!(started_at.elapsed() <= max_delay);
+    }
+
+    #[test]
+    fn reproducible_faults() {
+        let samples = |seed| {
+            let layer = FaultInjectionLayer::new(seed, 0.5, Duration::from_secs(1));
+            let faults = layer.faults.unwrap();
+            (0..100).map(|_| faults.sample()).collect::<Vec<_>>()
+        };
+        assert_eq!(samples(7), samples(7));
+        assert_ne!(samples(7), samples(8));
+    }
+
+    async fn echo(req: Request<Bytes>) -> Result<Response<Bytes>, BoxError> {
+        Ok(Response::new(req.into_body()))
+    }
+}
@@ -11,11 +11,15 @@
 
 pub mod admin;
 pub mod anemo_ext;
+pub mod connection_manager;
 pub mod connectivity;
+pub mod discovery;
 pub mod epoch_filter;
 pub mod failpoints;
+pub mod fault_injection;
 pub mod metrics;
 mod p2p;
+pub mod request_budget;
 mod retry;
 mod traits;
 
@@ -1,12 +1,18 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use anemo::PeerId;
 use anemo_tower::callback::{MakeCallbackHandler, ResponseHandler};
+use dashmap::DashMap;
 use prometheus::{
-    register_histogram_vec_with_registry, register_int_counter_vec_with_registry,
-    register_int_gauge_vec_with_registry, register_int_gauge_with_registry, HistogramTimer,
-    HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Registry,
+    register_gauge_vec_with_registry, register_histogram_vec_with_registry,
+    register_int_counter_vec_with_registry, register_int_gauge_vec_with_registry,
+    register_int_gauge_with_registry, GaugeVec, HistogramTimer, HistogramVec, IntCounterVec,
+    IntGauge, IntGaugeVec, Registry,
 };
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tracing::warn;
 
 #[derive(Clone, Debug)]
@@ -37,6 +43,45 @@ impl NetworkConnectionMetrics {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct ConnectionManagerMetrics {
+    /// The number of messages in flight by channel
+    pub channel_inflight: IntGaugeVec,
+    /// The time spent waiting for room on a channel
+    pub channel_wait_latency: HistogramVec,
+    /// The number of dials of disconnected peers by result
+    pub dials: IntCounterVec,
+}
+
+impl ConnectionManagerMetrics {
+    pub fn new(node: &'static str, registry: &Registry) -> Self {
+        Self {
+            channel_inflight: register_int_gauge_vec_with_registry!(
+                format!("{node}_channel_inflight"),
+                "The number of messages in flight on the channels with the peers",
+                &["channel"],
+                registry
+            )
+            .unwrap(),
+            channel_wait_latency: register_histogram_vec_with_registry!(
+                format!("{node}_channel_wait_latency"),
+                "The time spent waiting for room on the channels with the peers",
+                &["channel"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry
+            )
+            .unwrap(),
+            dials: register_int_counter_vec_with_registry!(
+                format!("{node}_peer_dials"),
+                "The number of dials of disconnected peers",
+                &["result"],
+                registry
+            )
+            .unwrap(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct NetworkMetrics {
     /// Counter of requests by route
@@ -264,3 +309,272 @@ impl Drop for MetricsResponseHandler {
             .dec();
     }
 }
+
+/// The network health of a single peer, as dumped by the admin server.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PeerStats {
+    /// The smoothed round trip time of our requests to the peer, in milliseconds.
+    pub rtt_ms: Option<f64>,
+    /// The number of failed dials of the peer.
+    pub failed_dials: u64,
+    /// The bytes of the requests and responses sent to the peer.
+    pub bytes_sent: u64,
+    /// The bytes of the requests and responses received from the peer.
+    pub bytes_received: u64,
+    /// The number of our requests to the peer waiting for a response.
+    pub outstanding_requests: u64,
+    /// When we last heard from the peer, in milliseconds since the Unix epoch.
+    pub last_seen_timestamp_ms: Option<u64>,
+}
+
+/// Per-peer network health: exported as metrics labelled by peer, and kept in memory so that the
+/// admin server can dump it, e.g. to find the peer stalling the quorum waits.
+#[derive(Clone)]
+pub struct PeerMetrics {
+    /// The smoothed round trip time of our requests by peer
+    peer_rtt: GaugeVec,
+    /// The number of failed dials by peer
+    peer_failed_dials: IntCounterVec,
+    /// The bytes sent by peer
+    peer_bytes_sent: IntCounterVec,
+    /// The bytes received by peer
+    peer_bytes_received: IntCounterVec,
+    /// The number of our requests waiting for a response by peer
+    peer_outstanding_requests: IntGaugeVec,
+    /// When we last heard from each peer
+    peer_last_seen: IntGaugeVec,
+    stats: Arc<DashMap<PeerId, PeerStats>>,
+}
+
+impl PeerMetrics {
+    // The weight of a new sample in the smoothed round trip time, as for TCP.
+    const RTT_ALPHA: f64 = 0.125;
+
+    pub fn new(node: &'static str, registry: &Registry) -> Self {
+        Self {
+            peer_rtt: register_gauge_vec_with_registry!(
+                format!("{node}_peer_rtt"),
+                "The smoothed round trip time of the requests to a peer, in seconds",
+                &["peer_id"],
+                registry
+            )
+            .unwrap(),
+            peer_failed_dials: register_int_counter_vec_with_registry!(
+                format!("{node}_peer_failed_dials"),
+                "The number of failed dials of a peer",
+                &["peer_id"],
+                registry
+            )
+            .unwrap(),
+            peer_bytes_sent: register_int_counter_vec_with_registry!(
+                format!("{node}_peer_bytes_sent"),
+                "The bytes of the requests and responses sent to a peer",
+                &["peer_id"],
+                registry
+            )
+            .unwrap(),
+            peer_bytes_received: register_int_counter_vec_with_registry!(
+                format!("{node}_peer_bytes_received"),
+                "The bytes of the requests and responses received from a peer",
+                &["peer_id"],
+                registry
+            )
+            .unwrap(),
+            peer_outstanding_requests: register_int_gauge_vec_with_registry!(
+                format!("{node}_peer_outstanding_requests"),
+                "The number of requests to a peer waiting for a response",
+                &["peer_id"],
+                registry
+            )
+            .unwrap(),
+            peer_last_seen: register_int_gauge_vec_with_registry!(
+                format!("{node}_peer_last_seen"),
+                "When a peer was last heard from, in seconds since the Unix epoch",
+                &["peer_id"],
+                registry
+            )
+            .unwrap(),
+            stats: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Returns the network health of the peers, by peer id.
+    pub fn snapshot(&self) -> BTreeMap<String, PeerStats> {
+        self.stats
+            .iter()
+            .map(|entry| (entry.key().to_string(), entry.value().clone()))
+            .collect()
+    }
+
+    pub fn record_failed_dial(&self, peer_id: PeerId) {
+        self.stats.entry(peer_id).or_default().failed_dials += 1;
+        self.peer_failed_dials
+            .with_label_values(&[&peer_id.to_string()])
+            .inc();
+    }
+
+    fn record_sent(&self, peer_id: PeerId, bytes: usize, request: bool) {
+        let mut stats = self.stats.entry(peer_id).or_default();
+        stats.bytes_sent += bytes as u64;
+        let peer = peer_id.to_string();
+        self.peer_bytes_sent
+            .with_label_values(&[&peer])
+            .inc_by(bytes as u64);
+        if request {
+            stats.outstanding_requests += 1;
+            self.peer_outstanding_requests
+                .with_label_values(&[&peer])
+                .inc();
+        }
+    }
+
+    fn record_received(&self, peer_id: PeerId, bytes: usize) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut stats = self.stats.entry(peer_id).or_default();
+        stats.bytes_received += bytes as u64;
+        stats.last_seen_timestamp_ms = Some(now.as_millis() as u64);
+        let peer = peer_id.to_string();
+        self.peer_bytes_received
+            .with_label_values(&[&peer])
+            .inc_by(bytes as u64);
+        self.peer_last_seen
+            .with_label_values(&[&peer])
+            .set(now.as_secs() as i64);
+    }
+
+    fn record_rtt(&self, peer_id: PeerId, rtt: f64) {
+        let mut stats = self.stats.entry(peer_id).or_default();
+        let rtt_ms = match stats.rtt_ms {
+            Some(smoothed) => smoothed + Self::RTT_ALPHA * (rtt * 1000.0 - smoothed),
+            None => rtt * 1000.0,
+        };
+        stats.rtt_ms = Some(rtt_ms);
+        self.peer_rtt
+            .with_label_values(&[&peer_id.to_string()])
+            .set(rtt_ms / 1000.0);
+    }
+
+    fn record_request_done(&self, peer_id: PeerId) {
+        let mut stats = self.stats.entry(peer_id).or_default();
+        stats.outstanding_requests = stats.outstanding_requests.saturating_sub(1);
+        self.peer_outstanding_requests
+            .with_label_values(&[&peer_id.to_string()])
+            .dec();
+    }
+}
+
+/// Records the per-peer network health of the requests, either those we serve (inbound) or
+/// those we make (outbound).
+#[derive(Clone)]
+pub struct PeerMetricsMakeCallbackHandler {
+    metrics: Arc<PeerMetrics>,
+    outbound: bool,
+}
+
+impl PeerMetricsMakeCallbackHandler {
+    pub fn inbound(metrics: Arc<PeerMetrics>) -> Self {
+        Self {
+            metrics,
+            outbound: false,
+        }
+    }
+
+    pub fn outbound(metrics: Arc<PeerMetrics>) -> Self {
+        Self {
+            metrics,
+            outbound: true,
+        }
+    }
+}
+
+impl MakeCallbackHandler for PeerMetricsMakeCallbackHandler {
+    type Handler = PeerMetricsResponseHandler;
+
+    fn make_handler(&self, request: &anemo::Request<bytes::Bytes>) -> Self::Handler {
+        let peer_id = request.peer_id().copied();
+        if let Some(peer_id) = peer_id {
+            let body_len = request.body().len();
+            if self.outbound {
+                self.metrics.record_sent(peer_id, body_len, true);
+            } else {
+                self.metrics.record_received(peer_id, body_len);
+            }
+        }
+        PeerMetricsResponseHandler {
+            metrics: self.metrics.clone(),
+            peer_id,
+            outbound: self.outbound,
+            start: Instant::now(),
+        }
+    }
+}
+
+pub struct PeerMetricsResponseHandler {
+    metrics: Arc<PeerMetrics>,
+    peer_id: Option<PeerId>,
+    outbound: bool,
+    start: Instant,
+}
+
+impl ResponseHandler for PeerMetricsResponseHandler {
+    fn on_response(self, response: &anemo::Response<bytes::Bytes>) {
+        let Some(peer_id) = self.peer_id else {
+            return;
+        };
+        let body_len = response.body().len();
+        if self.outbound {
+            self.metrics.record_received(peer_id, body_len);
+            self.metrics
+                .record_rtt(peer_id, self.start.elapsed().as_secs_f64());
+        } else {
+            self.metrics.record_sent(peer_id, body_len, false);
+        }
+    }
+
+    fn on_error<E>(self, _error: &E) {}
+}
+
+impl Drop for PeerMetricsResponseHandler {
+    fn drop(&mut self) {
+        if let (true, Some(peer_id)) = (self.outbound, self.peer_id) {
+            self.metrics.record_request_done(peer_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PeerMetrics;
+    use anemo::PeerId;
+    use prometheus::Registry;
+
+    #[test]
+    fn peer_stats_are_recorded_per_peer() {
+        let metrics = PeerMetrics::new("test", &Registry::new());
+        let peer = PeerId([1; 32]);
+        let other_peer = PeerId([2; 32]);
+
+        metrics.record_sent(peer, 100, true);
+        metrics.record_sent(peer, 50, true);
+        metrics.record_received(peer, 10);
+        metrics.record_rtt(peer, 0.1);
+        metrics.record_rtt(peer, 0.9);
+        metrics.record_request_done(peer);
+        metrics.record_failed_dial(other_peer);
+
+        let snapshot = metrics.snapshot();
+        let stats = &snapshot[&peer.to_string()];
+        assert_eq!(stats.bytes_sent, 150);
+        assert_eq!(stats.bytes_received, 10);
+        assert_eq!(stats.outstanding_requests, 1);
+        assert_eq!(stats.rtt_ms, Some(200.0));
+        assert!(stats.last_seen_timestamp_ms.is_some());
+        assert_eq!(stats.failed_dials, 0);
+
+        let stats = &snapshot[&other_peer.to_string()];
+        assert_eq!(stats.failed_dials, 1);
+        assert_eq!(stats.last_seen_timestamp_ms, None);
+    }
+}
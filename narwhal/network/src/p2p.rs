@@ -1,11 +1,13 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::request_budget::{is_rate_limited, RateLimited};
 use crate::traits::{PrimaryToPrimaryRpc, PrimaryToWorkerRpc, WorkerRpc};
 use crate::{
     traits::{ReliableNetwork, UnreliableNetwork},
     CancelOnDropHandler, RetryConfig,
 };
+use anemo::types::response::StatusCode;
 use anemo::PeerId;
 use anyhow::format_err;
 use anyhow::Result;
@@ -15,12 +17,26 @@ use std::time::Duration;
 use tokio::task::JoinHandle;
 use types::{
     Batch, BatchDigest, FetchCertificatesRequest, FetchCertificatesResponse,
-    GetCertificatesRequest, GetCertificatesResponse, PrimaryToPrimaryClient, PrimaryToWorkerClient,
-    RequestBatchRequest, RequestBatchesRequest, RequestBatchesResponse, WorkerBatchMessage,
-    WorkerDeleteBatchesMessage, WorkerOthersBatchMessage, WorkerOurBatchMessage,
-    WorkerSynchronizeMessage, WorkerToPrimaryClient, WorkerToWorkerClient,
+    FetchCertificatesV2Request, FetchCertificatesV2Response, GetCertificatesRequest,
+    GetCertificatesResponse, GetDagSnapshotRequest, GetDagSnapshotResponse,
+    OpenBatchesStreamRequest, OpenBatchesStreamResponse, PrimaryToPrimaryClient,
+    PrimaryToWorkerClient, RequestBatchRequest, RequestBatchesChunkRequest,
+    RequestBatchesChunkResponse, RequestBatchesRequest, RequestBatchesResponse,
+    RequestBatchesV2Request, RequestBatchesV2Response, WorkerBatchMessage,
+    WorkerBatchStatusMessage, WorkerDeleteBatchesMessage, WorkerOthersBatchMessage,
+    WorkerOurBatchMessage, WorkerRoundsMessage, WorkerSynchronizeMessage, WorkerToPrimaryClient,
+    WorkerToWorkerClient,
 };
 
+/// Keeps the requests refused for being over budget distinguishable, as `RateLimited` errors.
+fn network_error(peer_id: PeerId, status: anemo::rpc::Status) -> anyhow::Error {
+    if is_rate_limited(&status) {
+        RateLimited(peer_id).into()
+    } else {
+        format_err!("Network error {:?}", status)
+    }
+}
+
 fn unreliable_send<F, R, Fut>(
     network: &anemo::Network,
     peer: NetworkPublicKey,
@@ -43,7 +59,7 @@ where
     }))
 }
 
-fn send<F, R, Fut>(
+pub(crate) fn send<F, R, Fut>(
     network: anemo::Network,
     peer: NetworkPublicKey,
     f: F,
@@ -119,6 +135,53 @@ impl PrimaryToPrimaryRpc for anemo::Network {
         let response = PrimaryToPrimaryClient::new(peer)
             .fetch_certificates(request)
             .await
+            .map_err(|e| network_error(peer_id, e))?;
+        Ok(response.into_body())
+    }
+
+    async fn fetch_certificates_v2(
+        &self,
+        peer: &NetworkPublicKey,
+        request: FetchCertificatesV2Request,
+        timeout: Duration,
+    ) -> Result<FetchCertificatesV2Response> {
+        let peer_id = PeerId(peer.0.to_bytes());
+        let peer = self
+            .peer(peer_id)
+            .ok_or_else(|| format_err!("Network has no connection with peer {peer_id}"))?;
+        let mut client = PrimaryToPrimaryClient::new(peer);
+        match client
+            .fetch_certificates_v2(anemo::Request::new(request.clone()).with_timeout(timeout))
+            .await
+        {
+            Ok(response) => Ok(response.into_body()),
+            // The peer does not serve the route yet.
+            Err(status) if status.status() == StatusCode::NotFound => {
+                let response = client
+                    .fetch_certificates(anemo::Request::new(request.request).with_timeout(timeout))
+                    .await
+                    .map_err(|e| network_error(peer_id, e))?;
+                Ok(FetchCertificatesV2Response {
+                    certificates: response.into_body().certificates,
+                    summary: Vec::new(),
+                })
+            }
+            Err(status) => Err(network_error(peer_id, status)),
+        }
+    }
+
+    async fn get_dag_snapshot(
+        &self,
+        peer: &NetworkPublicKey,
+        request: impl anemo::types::request::IntoRequest<GetDagSnapshotRequest> + Send,
+    ) -> Result<GetDagSnapshotResponse> {
+        let peer_id = PeerId(peer.0.to_bytes());
+        let peer = self
+            .peer(peer_id)
+            .ok_or_else(|| format_err!("Network has no connection with peer {peer_id}"))?;
+        let response = PrimaryToPrimaryClient::new(peer)
+            .get_dag_snapshot(request)
+            .await
             .map_err(|e| format_err!("Network error {:?}", e))?;
         Ok(response.into_body())
     }
@@ -250,6 +313,44 @@ impl PrimaryToWorkerRpc for anemo::Network {
             .map(|_| ())
             .map_err(|e| format_err!("DeleteBatches error: {e:?}"))
     }
+
+    async fn report_batch_status(
+        &self,
+        peer: NetworkPublicKey,
+        message: WorkerBatchStatusMessage,
+    ) -> Result<()> {
+        const BATCH_STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+
+        let peer_id = PeerId(peer.0.to_bytes());
+        let peer = self
+            .peer(peer_id)
+            .ok_or_else(|| format_err!("Network has no connection with peer {peer_id}"))?;
+        let request = anemo::Request::new(message).with_timeout(BATCH_STATUS_TIMEOUT);
+        PrimaryToWorkerClient::new(peer)
+            .report_batch_status(request)
+            .await
+            .map(|_| ())
+            .map_err(|e| format_err!("ReportBatchStatus error: {e:?}"))
+    }
+
+    async fn report_rounds(
+        &self,
+        peer: NetworkPublicKey,
+        message: WorkerRoundsMessage,
+    ) -> Result<()> {
+        const ROUNDS_TIMEOUT: Duration = Duration::from_secs(2);
+
+        let peer_id = PeerId(peer.0.to_bytes());
+        let peer = self
+            .peer(peer_id)
+            .ok_or_else(|| format_err!("Network has no connection with peer {peer_id}"))?;
+        let request = anemo::Request::new(message).with_timeout(ROUNDS_TIMEOUT);
+        PrimaryToWorkerClient::new(peer)
+            .report_rounds(request)
+            .await
+            .map(|_| ())
+            .map_err(|e| format_err!("ReportRounds error: {e:?}"))
+    }
 }
 
 #[async_trait]
@@ -293,6 +394,73 @@ impl WorkerRpc for anemo::Network {
         let response = WorkerToWorkerClient::new(peer)
             .request_batches(request)
             .await
+            .map_err(|e| network_error(peer_id, e))?;
+        Ok(response.into_body())
+    }
+
+    async fn request_batches_v2(
+        &self,
+        peer: NetworkPublicKey,
+        request: RequestBatchesV2Request,
+        timeout: Duration,
+    ) -> Result<RequestBatchesV2Response> {
+        let peer_id = PeerId(peer.0.to_bytes());
+        let peer = self
+            .peer(peer_id)
+            .ok_or_else(|| format_err!("Network has no connection with peer {peer_id}"))?;
+        let mut client = WorkerToWorkerClient::new(peer);
+        match client
+            .request_batches_v2(anemo::Request::new(request.clone()).with_timeout(timeout))
+            .await
+        {
+            Ok(response) => Ok(response.into_body()),
+            // The peer does not serve the route yet.
+            Err(status) if status.status() == StatusCode::NotFound => {
+                let requested = request.batch_digests.clone();
+                let request = RequestBatchesRequest {
+                    batch_digests: request.batch_digests,
+                };
+                let response = client
+                    .request_batches(anemo::Request::new(request).with_timeout(timeout))
+                    .await
+                    .map_err(|e| network_error(peer_id, e))?;
+                Ok(RequestBatchesV2Response::from_legacy(
+                    response.into_body(),
+                    &requested,
+                ))
+            }
+            Err(status) => Err(network_error(peer_id, status)),
+        }
+    }
+
+    async fn open_batches_stream(
+        &self,
+        peer: NetworkPublicKey,
+        request: impl anemo::types::request::IntoRequest<OpenBatchesStreamRequest> + Send,
+    ) -> Result<OpenBatchesStreamResponse> {
+        let peer_id = PeerId(peer.0.to_bytes());
+        let peer = self
+            .peer(peer_id)
+            .ok_or_else(|| format_err!("Network has no connection with peer {peer_id}"))?;
+        let response = WorkerToWorkerClient::new(peer)
+            .open_batches_stream(request)
+            .await
+            .map_err(|e| format_err!("Network error {:?}", e))?;
+        Ok(response.into_body())
+    }
+
+    async fn request_batches_chunk(
+        &self,
+        peer: NetworkPublicKey,
+        request: impl anemo::types::request::IntoRequest<RequestBatchesChunkRequest> + Send,
+    ) -> Result<RequestBatchesChunkResponse> {
+        let peer_id = PeerId(peer.0.to_bytes());
+        let peer = self
+            .peer(peer_id)
+            .ok_or_else(|| format_err!("Network has no connection with peer {peer_id}"))?;
+        let response = WorkerToWorkerClient::new(peer)
+            .request_batches_chunk(request)
+            .await
             .map_err(|e| format_err!("Network error {:?}", e))?;
         Ok(response.into_body())
     }
@@ -0,0 +1,137 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anemo::rpc::Status;
+use anemo::types::response::{IntoResponse, StatusCode};
+use anemo::{PeerId, Request, Response};
+use anemo_tower::auth::AuthorizeRequest;
+use bytes::Bytes;
+use governor::{DefaultDirectRateLimiter, DefaultKeyedRateLimiter, Quota, RateLimiter};
+use std::{fmt, num::NonZeroU32, sync::Arc};
+
+/// The status of the requests refused because they are over budget.
+pub const RATE_LIMITED: StatusCode = StatusCode::TooManyRequests;
+
+/// Refuses the requests of an endpoint that are over the budget of their peer, or over the
+/// budget shared by all the peers. Unlike rate limiting layers delaying the requests, the
+/// requests are refused right away with the `RATE_LIMITED` status so the peers back off.
+#[derive(Clone)]
+pub struct RequestBudget {
+    per_peer: Option<Arc<DefaultKeyedRateLimiter<PeerId>>>,
+    per_endpoint: Option<Arc<DefaultDirectRateLimiter>>,
+}
+
+impl RequestBudget {
+    /// Creates the budget, in requests/sec. Returns None when there is no budget at all.
+    pub fn new(per_peer: Option<NonZeroU32>, per_endpoint: Option<NonZeroU32>) -> Option<Self> {
+        if per_peer.is_none() && per_endpoint.is_none() {
+            return None;
+        }
+        Some(Self {
+            per_peer: per_peer.map(|limit| Arc::new(RateLimiter::keyed(Quota::per_second(limit)))),
+            per_endpoint: per_endpoint
+                .map(|limit| Arc::new(RateLimiter::direct(Quota::per_second(limit)))),
+        })
+    }
+}
+
+impl AuthorizeRequest for RequestBudget {
+    fn authorize(&self, request: &mut Request<Bytes>) -> Result<(), Response<Bytes>> {
+        if let (Some(per_peer), Some(peer_id)) = (&self.per_peer, request.peer_id()) {
+            if per_peer.check_key(peer_id).is_err() {
+                return Err(Status::new_with_message(
+                    RATE_LIMITED,
+                    format!("peer {peer_id} is over its request budget"),
+                )
+                .into_response());
+            }
+        }
+        if let Some(per_endpoint) = &self.per_endpoint {
+            if per_endpoint.check().is_err() {
+                return Err(Status::new_with_message(
+                    RATE_LIMITED,
+                    "endpoint is over its request budget",
+                )
+                .into_response());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the request was refused for being over budget, in which case it should only be
+/// retried after backing off.
+pub fn is_rate_limited(status: &Status) -> bool {
+    status.status() == RATE_LIMITED
+}
+
+/// The error of a request refused for being over the budget of the peer.
+#[derive(Debug)]
+pub struct RateLimited(pub PeerId);
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rate limited by peer {}", self.0)
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anemo_tower::auth::RequireAuthorizationLayer;
+    use tower::{BoxError, Service, ServiceBuilder, ServiceExt};
+
+    fn request_from(peer_id: PeerId) -> Request<Bytes> {
+        let mut request = Request::new(Bytes::from("foobar"));
+        request.extensions_mut().insert(peer_id);
+        request
+    }
+
+    #[tokio::test]
+    async fn refuse_requests_over_budget() {
+        let budget = RequestBudget::new(NonZeroU32::new(1), NonZeroU32::new(2)).unwrap();
+        let mut svc = ServiceBuilder::new()
+            .layer(RequireAuthorizationLayer::new(budget))
+            .service_fn(echo);
+        let call = |request| {
+            let svc = svc.clone();
+            async move { svc.oneshot(request).await.unwrap().status() }
+        };
+
+        // Each peer has a single request per second.
+        assert_eq!(
+            call(request_from(PeerId([1; 32]))).await,
+            StatusCode::Success
+        );
+        let status = call(request_from(PeerId([1; 32]))).await;
+        assert_eq!(status, RATE_LIMITED);
+        assert!(is_rate_limited(&Status::new_with_message(status, "")));
+
+        // The budget of the endpoint is shared by the peers.
+        assert_eq!(
+            call(request_from(PeerId([2; 32]))).await,
+            StatusCode::Success
+        );
+        assert_eq!(call(request_from(PeerId([3; 32]))).await, RATE_LIMITED);
+
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::new(Bytes::from("foobar")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), RATE_LIMITED);
+    }
+
+    #[test]
+    fn no_budget() {
+        assert!(RequestBudget::new(None, None).is_none());
+    }
+
+    async fn echo(req: Request<Bytes>) -> Result<Response<Bytes>, BoxError> {
+        Ok(Response::new(req.into_body()))
+    }
+}
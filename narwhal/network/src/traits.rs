@@ -4,10 +4,16 @@ use crate::CancelOnDropHandler;
 use anyhow::Result;
 use async_trait::async_trait;
 use crypto::NetworkPublicKey;
+use std::time::Duration;
 use tokio::task::JoinHandle;
 use types::{
     Batch, BatchDigest, FetchCertificatesRequest, FetchCertificatesResponse,
-    GetCertificatesRequest, GetCertificatesResponse, RequestBatchesRequest, RequestBatchesResponse,
+    FetchCertificatesV2Request, FetchCertificatesV2Response, GetCertificatesRequest,
+    GetCertificatesResponse, GetDagSnapshotRequest, GetDagSnapshotResponse,
+    OpenBatchesStreamRequest, OpenBatchesStreamResponse, RequestBatchesChunkRequest,
+    RequestBatchesChunkResponse, RequestBatchesRequest, RequestBatchesResponse,
+    RequestBatchesV2Request, RequestBatchesV2Response, WorkerBatchStatusMessage,
+    WorkerRoundsMessage,
 };
 
 pub trait UnreliableNetwork<Request: Clone + Send + Sync> {
@@ -70,12 +76,36 @@ pub trait PrimaryToPrimaryRpc {
         peer: &NetworkPublicKey,
         request: impl anemo::types::request::IntoRequest<FetchCertificatesRequest> + Send,
     ) -> Result<FetchCertificatesResponse>;
+    /// Fetches certificates within the round ranges of the request, along with a summary of the
+    /// certificates of the peer. Falls back to `fetch_certificates`, ignoring the upper bounds
+    /// and without a summary, if the peer does not serve this version yet.
+    async fn fetch_certificates_v2(
+        &self,
+        peer: &NetworkPublicKey,
+        request: FetchCertificatesV2Request,
+        timeout: Duration,
+    ) -> Result<FetchCertificatesV2Response>;
+    async fn get_dag_snapshot(
+        &self,
+        peer: &NetworkPublicKey,
+        request: impl anemo::types::request::IntoRequest<GetDagSnapshotRequest> + Send,
+    ) -> Result<GetDagSnapshotResponse>;
 }
 
 #[async_trait]
 pub trait PrimaryToWorkerRpc {
     async fn delete_batches(&self, peer: NetworkPublicKey, digests: Vec<BatchDigest>)
         -> Result<()>;
+    async fn report_batch_status(
+        &self,
+        peer: NetworkPublicKey,
+        message: WorkerBatchStatusMessage,
+    ) -> Result<()>;
+    async fn report_rounds(
+        &self,
+        peer: NetworkPublicKey,
+        message: WorkerRoundsMessage,
+    ) -> Result<()>;
 }
 
 #[async_trait]
@@ -91,4 +121,26 @@ pub trait WorkerRpc {
         peer: NetworkPublicKey,
         request: impl anemo::types::request::IntoRequest<RequestBatchesRequest> + Send,
     ) -> Result<RequestBatchesResponse>;
+
+    /// Requests batches, along with the digests of the requested batches the peer does not have.
+    /// Falls back to `request_batches`, without the missing digests, if the peer does not serve
+    /// this version yet.
+    async fn request_batches_v2(
+        &self,
+        peer: NetworkPublicKey,
+        request: RequestBatchesV2Request,
+        timeout: Duration,
+    ) -> Result<RequestBatchesV2Response>;
+
+    async fn open_batches_stream(
+        &self,
+        peer: NetworkPublicKey,
+        request: impl anemo::types::request::IntoRequest<OpenBatchesStreamRequest> + Send,
+    ) -> Result<OpenBatchesStreamResponse>;
+
+    async fn request_batches_chunk(
+        &self,
+        peer: NetworkPublicKey,
+        request: impl anemo::types::request::IntoRequest<RequestBatchesChunkRequest> + Send,
+    ) -> Result<RequestBatchesChunkResponse>;
 }
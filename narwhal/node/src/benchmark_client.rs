@@ -167,7 +167,10 @@ impl Client {
 
                 tx.resize(size, 0u8);
                 let bytes = tx.split().freeze();
-                TransactionProto { transaction: bytes }
+                TransactionProto {
+                    transaction: bytes,
+                    ..Default::default()
+                }
             });
 
             if let Err(e) = client.submit_transaction_stream(stream).await {
@@ -1,6 +1,6 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use config::{CommitteeBuilder, Epoch, WorkerIndex, WorkerInfo};
+use config::{BatchVersion, CommitteeBuilder, Epoch, WorkerIndex, WorkerInfo};
 use crypto::{KeyPair, NetworkKeyPair};
 use fastcrypto::{
     hash::Hash,
@@ -12,8 +12,9 @@ use serde_reflection::{Registry, Result, Samples, Tracer, TracerConfig};
 use std::{fs::File, io::Write};
 use structopt::{clap::arg_enum, StructOpt};
 use types::{
-    Batch, BatchDigest, Certificate, CertificateDigest, Header, HeaderDigest, HeaderV1Builder,
-    Metadata, WorkerOthersBatchMessage, WorkerOurBatchMessage, WorkerSynchronizeMessage,
+    Batch, BatchCompression, BatchDigest, Certificate, CertificateDigest, Header, HeaderDigest,
+    HeaderV1Builder, Metadata, PriorityLane, WorkerBatchMessage, WorkerOthersBatchMessage,
+    WorkerOurBatchMessage, WorkerSynchronizeMessage,
 };
 
 #[allow(clippy::mutable_key_type)]
@@ -114,6 +115,7 @@ fn get_registry() -> Result<Registry> {
         digest: BatchDigest([0u8; 32]),
         worker_id: 0,
         metadata: Metadata { created_at: 0 },
+        size: 0,
     };
     let others_batch = WorkerOthersBatchMessage {
         digest: BatchDigest([0u8; 32]),
@@ -123,17 +125,34 @@ fn get_registry() -> Result<Registry> {
         digests: vec![BatchDigest([0u8; 32])],
         target: authority.id(),
         is_certified: true,
+        round: Some(1),
     };
 
     tracer.trace_value(&mut samples, &our_batch)?;
     tracer.trace_value(&mut samples, &others_batch)?;
     tracer.trace_value(&mut samples, &sync)?;
 
+    // The batches are compressed on the wire, so the message can only be traced from values:
+    // one for each version of the uncompressed batches, and a compressed one.
+    for (version, compression) in [
+        (BatchVersion::V1, BatchCompression::None),
+        (BatchVersion::V2, BatchCompression::None),
+        (BatchVersion::V3, BatchCompression::None),
+        (BatchVersion::V1, BatchCompression::Zstd),
+    ] {
+        let batch_message = WorkerBatchMessage {
+            batch: Batch::new_versioned(vec![vec![0u8]], PriorityLane::Normal, 0, 0, version),
+            compression,
+        };
+        tracer.trace_value(&mut samples, &batch_message)?;
+    }
+
     // 2. Trace the main entry point(s) + every enum separately.
     tracer.trace_type::<Batch>(&samples)?;
     tracer.trace_type::<BatchDigest>(&samples)?;
     tracer.trace_type::<HeaderDigest>(&samples)?;
     tracer.trace_type::<CertificateDigest>(&samples)?;
+    tracer.trace_type::<BatchCompression>(&samples)?;
 
     tracer.registry()
 }
@@ -157,27 +176,49 @@ struct Options {
     action: Action,
 }
 
-const FILE_PATH: &str = "node/tests/staged/narwhal.yaml";
+/// The version of the formats. The formats of each version are recorded once and for all, for
+/// the clients implementing them: any change to the formats must bump the version.
+const FORMAT_VERSION: u64 = 4;
+
+fn file_path(version: u64) -> String {
+    format!("node/tests/staged/narwhal_v{version}.yaml")
+}
 
 fn main() {
     let options = Options::from_args();
     let registry = get_registry().unwrap();
+    let file_path = file_path(FORMAT_VERSION);
     match options.action {
         Action::Print => {
             let content = serde_yaml::to_string(&registry).unwrap();
             println!("{content}");
         }
         Action::Record => {
+            if let Ok(reference) = std::fs::read_to_string(&file_path) {
+                let reference: Registry = serde_yaml::from_str(&reference).unwrap();
+                assert!(
+                    reference == registry,
+                    "The formats of version {FORMAT_VERSION} are already recorded in {file_path}: \
+                     bump FORMAT_VERSION to record the new formats"
+                );
+            }
             let content = serde_yaml::to_string(&registry).unwrap();
-            let mut f = File::create(FILE_PATH).unwrap();
+            let mut f = File::create(&file_path).unwrap();
             writeln!(f, "{}", content).unwrap();
         }
         Action::Test => {
-            // If this test fails, run the following command from the folder `node`:
-            // cargo -q run --example narwhal-generate-format -- print > tests/staged/narwhal.yaml
-            let reference = std::fs::read_to_string(FILE_PATH).unwrap();
+            // If this test fails, bump FORMAT_VERSION and run the following command from the
+            // folder `narwhal`:
+            // cargo -q run --example narwhal-generate-format -- record
+            let reference = std::fs::read_to_string(&file_path).unwrap_or_else(|_| {
+                panic!("The formats of version {FORMAT_VERSION} are not recorded in {file_path}")
+            });
             let reference: Registry = serde_yaml::from_str(&reference).unwrap();
-            pretty_assertions::assert_eq!(reference, registry);
+            pretty_assertions::assert_eq!(
+                reference,
+                registry,
+                "The formats changed without bumping FORMAT_VERSION"
+            );
         }
     }
 }
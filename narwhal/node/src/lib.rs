@@ -1,15 +1,19 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use config::WorkerId;
+pub use consensus::consensus::ConsensusRound;
 use executor::SubscriberError;
 use futures::future::try_join_all;
 use futures::stream::FuturesUnordered;
-pub use storage::{CertificateStoreCacheMetrics, NodeStorage};
+pub use network::discovery::PeerAddresses;
+pub use storage::{CertificateStoreCacheMetrics, NodeStorage, StoreCipher};
 use thiserror::Error;
 
 pub mod execution_state;
 pub mod metrics;
 pub mod primary_node;
+pub mod profiler;
+pub mod replay;
 pub mod worker_node;
 
 #[derive(Debug, Error, Clone)]
@@ -11,8 +11,12 @@
 use clap::{crate_name, crate_version, App, AppSettings, ArgMatches, SubCommand};
 use config::{Committee, Import, Parameters, WorkerCache, WorkerId};
 use crypto::{KeyPair, NetworkKeyPair};
+use executor::ExternalExecutionState;
 use eyre::Context;
-use fastcrypto::traits::KeyPair as _;
+use fastcrypto::{
+    encoding::{Encoding, Hex},
+    traits::KeyPair as _,
+};
 use mysten_metrics::RegistryService;
 use narwhal_node as node;
 use narwhal_node::primary_node::PrimaryNode;
@@ -20,10 +24,13 @@ use narwhal_node::worker_node::WorkerNode;
 use node::{
     execution_state::SimpleExecutionState,
     metrics::{primary_metrics_registry, start_prometheus_server, worker_metrics_registry},
+    profiler::{LoadProfile, LocalCommittee, ThroughputProfiler},
+    replay::ConsensusReplay,
 };
 use prometheus::Registry;
 use std::sync::Arc;
-use storage::{CertificateStoreCacheMetrics, NodeStorage};
+use std::time::Duration;
+use storage::{CertificateStoreCacheMetrics, NodeStorage, StoreCipher};
 use sui_keys::keypair_file::{
     read_authority_keypair_from_file, read_network_keypair_from_file,
     write_authority_keypair_to_file, write_keypair_to_file,
@@ -36,7 +43,7 @@ use tracing::subscriber::set_global_default;
 use tracing::{info, warn};
 #[cfg(feature = "benchmark")]
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
-use worker::TrivialTransactionValidator;
+use worker::{TransactionHandoff, TrivialTransactionValidator};
 
 #[tokio::main]
 async fn main() -> Result<(), eyre::Report> {
@@ -72,14 +79,35 @@ async fn main() -> Result<(), eyre::Report> {
                 .subcommand(SubCommand::with_name("primary")
                     .about("Run a single primary")
                     .args_from_usage("-d, --consensus-disabled 'Provide this flag to run a primary node without Tusk'")
+                    .args_from_usage("--executor-socket=[PATH] 'The unix socket of an external executor, to which the consensus output is streamed'")
                 )
                 .subcommand(
                     SubCommand::with_name("worker")
                         .about("Run a single worker")
-                        .args_from_usage("--id=<INT> 'The worker id'"),
+                        .args_from_usage("--id=<INT> 'The worker id'")
+                        .args_from_usage("--standby 'Stand by for the worker running with the same keys, and take over once it stops responding'"),
                 )
                 .setting(AppSettings::SubcommandRequiredElseHelp),
         )
+        .subcommand(
+            SubCommand::with_name("replay")
+                .about("Replay the stored certificates through consensus and print the commit sequence. The node must be stopped")
+                .args_from_usage("--committee=<FILE> 'The file containing committee information'")
+                .args_from_usage("--parameters=[FILE] 'The file containing the node parameters'")
+                .args_from_usage("--store=<PATH> 'The path of the data store to replay'")
+                .args_from_usage("--verify 'Provide this flag to compare the commit sequence with the one persisted by the node'"),
+        )
+        .subcommand(
+            SubCommand::with_name("profile")
+                .about("Run a local committee under a synthetic load and report its commit throughput and latency")
+                .args_from_usage("--committee-size=[INT] 'The number of authorities of the committee (default 4)'")
+                .args_from_usage("--workers=[INT] 'The number of workers of each authority (default 1)'")
+                .args_from_usage("--parameters=[FILE] 'The file containing the node parameters'")
+                .args_from_usage("--size=[INT] 'The size of each transaction in bytes (default 512)'")
+                .args_from_usage("--rate=[INT] 'The rate (txs/s) at which to submit the transactions (default 1000)'")
+                .args_from_usage("--bursts=[INT] 'The number of bursts the transactions of each second are submitted in (default 20)'")
+                .args_from_usage("--duration=[SECS] 'How long to submit the transactions for (default 30)'"),
+        )
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .get_matches();
 
@@ -186,6 +214,14 @@ async fn main() -> Result<(), eyre::Report> {
             )
             .await?
         }
+        ("replay", Some(sub_matches)) => {
+            let _guard = setup_telemetry(tracing_level, network_tracing_level, None);
+            replay(sub_matches)?
+        }
+        ("profile", Some(sub_matches)) => {
+            let _guard = setup_telemetry(tracing_level, network_tracing_level, None);
+            profile(sub_matches).await?
+        }
         _ => unreachable!(),
     }
     Ok(())
@@ -238,6 +274,86 @@ fn setup_benchmark_telemetry(
     Ok(())
 }
 
+// Replays the stored certificates of a node through consensus, and prints the commit sequence.
+fn replay(matches: &ArgMatches<'_>) -> Result<(), eyre::Report> {
+    let committee_file = matches.value_of("committee").unwrap();
+    let mut committee =
+        Committee::import(committee_file).context("Failed to load the committee information")?;
+    committee.load();
+    let parameters = match matches.value_of("parameters") {
+        Some(filename) => {
+            Parameters::import(filename).context("Failed to load the node's parameters")?
+        }
+        None => Parameters::default(),
+    };
+    let store = NodeStorage::reopen(matches.value_of("store").unwrap(), None);
+
+    let scratch_dir = tempfile::tempdir().context("Failed to create the replay directory")?;
+    let replay = ConsensusReplay::run(&committee, &store, parameters.gc_depth, scratch_dir.path())
+        .context("Failed to replay the certificates")?;
+    for sub_dag in &replay.sub_dags {
+        println!("{sub_dag}");
+    }
+    println!(
+        "Replayed {} sub dags, commit sequence digest {}",
+        replay.sub_dags.len(),
+        Hex::encode(replay.digest)
+    );
+
+    if matches.is_present("verify") {
+        match replay
+            .first_divergence(&store)
+            .context("Failed to read the persisted commit sequence")?
+        {
+            Some(index) => eyre::bail!("The commit sequence diverges at sub dag {index}"),
+            None => println!("The commit sequence matches the one persisted by the node"),
+        }
+    }
+    Ok(())
+}
+
+// Profiles a local committee under a synthetic load, and prints the report.
+async fn profile(matches: &ArgMatches<'_>) -> Result<(), eyre::Report> {
+    fn value<T: std::str::FromStr>(
+        matches: &ArgMatches<'_>,
+        name: &str,
+        default: T,
+    ) -> Result<T, eyre::Report> {
+        match matches.value_of(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| eyre::eyre!("The {name} must be a non-negative integer")),
+            None => Ok(default),
+        }
+    }
+
+    let parameters = match matches.value_of("parameters") {
+        Some(filename) => {
+            Parameters::import(filename).context("Failed to load the node's parameters")?
+        }
+        None => Parameters::default(),
+    };
+    let defaults = LoadProfile::default();
+    let load = LoadProfile {
+        transaction_size: value(matches, "size", defaults.transaction_size)?,
+        rate: value(matches, "rate", defaults.rate)?,
+        bursts_per_second: value(matches, "bursts", defaults.bursts_per_second)?,
+        duration: Duration::from_secs(value(matches, "duration", defaults.duration.as_secs())?),
+        drain_timeout: defaults.drain_timeout,
+    };
+    let committee = LocalCommittee::new(
+        value(matches, "committee-size", 4)?,
+        value(matches, "workers", 1)?,
+    );
+
+    let store_dir = tempfile::tempdir().context("Failed to create the profile directory")?;
+    let report = ThroughputProfiler::new(committee, parameters)
+        .run(&load, store_dir.path())
+        .await?;
+    println!("{report}");
+    Ok(())
+}
+
 // Runs either a worker or a primary.
 async fn run(
     matches: &ArgMatches<'_>,
@@ -277,7 +393,10 @@ async fn run(
     let certificate_store_cache_metrics =
         CertificateStoreCacheMetrics::new(&registry_service.default_registry());
 
-    let store = NodeStorage::reopen(store_path, Some(certificate_store_cache_metrics));
+    let cipher = StoreCipher::from_parameters(&parameters.storage_encryption)
+        .context("Failed to load the storage encryption key")?;
+    let store =
+        NodeStorage::reopen_with_cipher(store_path, Some(certificate_store_cache_metrics), cipher);
 
     // The channel returning the result for each transaction's execution.
     let (_tx_transaction_confirmation, _rx_transaction_confirmation) = channel(100);
@@ -292,16 +411,36 @@ async fn run(
                 registry_service,
             );
 
-            primary
-                .start(
-                    primary_keypair,
-                    primary_network_keypair,
-                    committee,
-                    worker_cache,
-                    &store,
-                    Arc::new(SimpleExecutionState::new(_tx_transaction_confirmation)),
-                )
-                .await?;
+            match sub_matches.value_of("executor-socket") {
+                Some(path) => {
+                    primary
+                        .start(
+                            primary_keypair,
+                            primary_network_keypair,
+                            committee,
+                            worker_cache,
+                            &store,
+                            Arc::new(ExternalExecutionState::new(path)),
+                            None,
+                            /* leader_swaps */ false,
+                        )
+                        .await?
+                }
+                None => {
+                    primary
+                        .start(
+                            primary_keypair,
+                            primary_network_keypair,
+                            committee,
+                            worker_cache,
+                            &store,
+                            Arc::new(SimpleExecutionState::new(_tx_transaction_confirmation)),
+                            None,
+                            /* leader_swaps */ false,
+                        )
+                        .await?
+                }
+            }
 
             (Some(primary), None)
         }
@@ -314,19 +453,38 @@ async fn run(
                 .parse::<WorkerId>()
                 .context("The worker id must be a positive integer")?;
 
-            let worker = WorkerNode::new(id, parameters.clone(), registry_service);
-
-            worker
-                .start(
-                    primary_keypair.public().clone(),
-                    worker_keypair,
-                    committee,
-                    worker_cache,
-                    &store,
-                    TrivialTransactionValidator::default(),
-                    None,
-                )
-                .await?;
+            let worker = WorkerNode::new(
+                id,
+                parameters.clone(),
+                registry_service,
+                TransactionHandoff::default(),
+            );
+
+            if sub_matches.is_present("standby") {
+                worker
+                    .start_standby(
+                        primary_keypair.public().clone(),
+                        worker_keypair,
+                        committee,
+                        worker_cache,
+                        &store,
+                        TrivialTransactionValidator::default(),
+                        None,
+                    )
+                    .await?;
+            } else {
+                worker
+                    .start(
+                        primary_keypair.public().clone(),
+                        worker_keypair,
+                        committee,
+                        worker_cache,
+                        &store,
+                        TrivialTransactionValidator::default(),
+                        None,
+                    )
+                    .await?;
+            }
 
             (None, Some(worker))
         }
@@ -2,24 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 use crate::metrics::new_registry;
 use crate::{try_join_all, FuturesUnordered, NodeError};
-use config::{AuthorityIdentifier, Committee, Parameters, WorkerCache};
+use config::{
+    AuthorityIdentifier, BatchRetentionPolicy, Committee, HeaderPayloadLimits, Parameters,
+    WorkerCache,
+};
 use consensus::bullshark::Bullshark;
 use consensus::consensus::ConsensusRound;
 use consensus::dag::Dag;
+use consensus::leader_schedule::LeaderSchedule;
 use consensus::metrics::{ChannelMetrics, ConsensusMetrics};
 use consensus::Consensus;
-use crypto::{KeyPair, NetworkKeyPair, PublicKey};
+use crypto::signer::AuthoritySigner;
+use crypto::{NetworkKeyPair, PublicKey};
 use executor::{get_restored_consensus_output, ExecutionState, Executor, SubscriberResult};
-use fastcrypto::traits::{KeyPair as _, VerifyingKey};
-use mysten_metrics::{RegistryID, RegistryService};
-use primary::{NetworkModel, Primary, PrimaryChannelMetrics, NUM_SHUTDOWN_RECEIVERS};
+use fastcrypto::traits::VerifyingKey;
+use mysten_metrics::{spawn_logged_monitored_task, RegistryID, RegistryService};
+use network::discovery::PeerAddresses;
+use primary::{
+    bootstrap_from_snapshot, NetworkModel, Primary, PrimaryChannelMetrics, NUM_SHUTDOWN_RECEIVERS,
+};
 use prometheus::{IntGauge, Registry};
 use std::sync::Arc;
-use std::time::Instant;
-use storage::NodeStorage;
+use std::time::{Duration, Instant};
+use storage::{BatchStorePruner, BatchStorePrunerMetrics, NodeStorage};
 use tokio::sync::{oneshot, watch, RwLock};
 use tokio::task::JoinHandle;
-use tracing::{debug, info, instrument};
+use tracing::{debug, error, info, instrument};
 use types::{
     metered_channel, Certificate, ConditionalBroadcastReceiver, PreSubscribedBroadcastSender, Round,
 };
@@ -41,22 +49,24 @@ struct PrimaryNodeInner {
     handles: FuturesUnordered<JoinHandle<()>>,
     // The shutdown signal channel
     tx_shutdown: Option<PreSubscribedBroadcastSender>,
+    // The channel to update the addresses of the peers while running
+    tx_peer_addresses: Option<watch::Sender<PeerAddresses>>,
+    // The channel to update the parameters tunable at runtime while running
+    tx_parameters: Option<watch::Sender<Parameters>>,
+    // The last committed and GC rounds of consensus while running
+    rx_consensus_round_updates: Option<watch::Receiver<ConsensusRound>>,
 }
 
 impl PrimaryNodeInner {
     /// The default channel capacity.
     pub const CHANNEL_CAPACITY: usize = 1_000;
-    /// The window where the schedule change takes place in consensus. It represents number
-    /// of committed sub dags.
-    /// TODO: move this to node properties
-    const CONSENSUS_SCHEDULE_CHANGE_SUB_DAGS: u64 = 300;
 
     // Starts the primary node with the provided info. If the node is already running then this
     // method will return an error instead.
     #[instrument(level = "info", skip_all)]
     async fn start<State>(
-        &mut self, // The private-public key pair of this authority.
-        keypair: KeyPair,
+        &mut self, // The signer of the protocol key of this authority.
+        keypair: impl AuthoritySigner,
         // The private-public network key pair of this authority.
         network_keypair: NetworkKeyPair,
         // The committee information.
@@ -67,6 +77,12 @@ impl PrimaryNodeInner {
         store: &NodeStorage,
         // The state used by the client to execute transactions.
         execution_state: Arc<State>,
+        // The limits on the batch digests of the headers, when decided by the protocol for this
+        // epoch. They override the ones of the parameters.
+        header_payload_limits: Option<HeaderPayloadLimits>,
+        // Whether the leaders with a low reputation are swapped out, as decided by the protocol
+        // for this epoch.
+        leader_swaps: bool,
     ) -> Result<(), NodeError>
     where
         State: ExecutionState + Send + Sync + 'static,
@@ -81,14 +97,28 @@ impl PrimaryNodeInner {
         // create the channel to send the shutdown signal
         let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
 
+        let mut parameters = self.parameters.clone();
+        if let Some(limits) = header_payload_limits {
+            parameters.set_header_payload_limits(limits);
+        }
+        parameters.leader_swaps = leader_swaps;
+
+        let (tx_peer_addresses, rx_peer_addresses) =
+            watch::channel(PeerAddresses::new(&committee, &worker_cache));
+        let (tx_consensus_round_updates, rx_consensus_round_updates) =
+            watch::channel(ConsensusRound::new(0, 0));
+        let (tx_parameters, rx_parameters) = watch::channel(parameters);
+
         // spawn primary if not already running
         let handles = Self::spawn_primary(
             keypair,
             network_keypair,
             committee,
             worker_cache,
+            rx_peer_addresses,
+            tx_consensus_round_updates,
             store,
-            self.parameters.clone(),
+            rx_parameters,
             self.internal_consensus,
             execution_state,
             &registry,
@@ -103,10 +133,25 @@ impl PrimaryNodeInner {
         self.handles.clear();
         self.handles.extend(handles);
         self.tx_shutdown = Some(tx_shutdown);
+        self.tx_peer_addresses = Some(tx_peer_addresses);
+        self.tx_parameters = Some(tx_parameters);
+        self.rx_consensus_round_updates = Some(rx_consensus_round_updates);
 
         Ok(())
     }
 
+    // Updates the addresses of the peers of the running primary, when the committee members
+    // change their network addresses during the epoch.
+    fn update_peer_addresses(&self, new_peer_addresses: &PeerAddresses) {
+        if let Some(tx_peer_addresses) = self.tx_peer_addresses.as_ref() {
+            tx_peer_addresses.send_if_modified(|peer_addresses| {
+                let modified = peer_addresses != new_peer_addresses;
+                *peer_addresses = new_peer_addresses.clone();
+                modified
+            });
+        }
+    }
+
     // Will shutdown the primary node and wait until the node has shutdown by waiting on the
     // underlying components handles. If the node was not already running then the
     // method will return immediately.
@@ -126,6 +171,9 @@ impl PrimaryNodeInner {
                 .expect("Couldn't send the shutdown signal to downstream components");
             self.tx_shutdown = None
         }
+        self.tx_peer_addresses = None;
+        self.tx_parameters = None;
+        self.rx_consensus_round_updates = None;
 
         // Now wait until handles have been completed
         try_join_all(&mut self.handles).await.unwrap();
@@ -167,18 +215,23 @@ impl PrimaryNodeInner {
 
     /// Spawn a new primary. Optionally also spawn the consensus and a client executing transactions.
     pub async fn spawn_primary<State>(
-        // The private-public key pair of this authority.
-        keypair: KeyPair,
+        // The signer of the protocol key of this authority.
+        keypair: impl AuthoritySigner,
         // The private-public network key pair of this authority.
         network_keypair: NetworkKeyPair,
         // The committee information.
         committee: Committee,
         // The worker information cache.
         worker_cache: WorkerCache,
+        // The updates of the addresses of the peers.
+        rx_peer_addresses: watch::Receiver<PeerAddresses>,
+        // The updates of the last committed and GC rounds of consensus.
+        tx_consensus_round_updates: watch::Sender<ConsensusRound>,
         // The node's storage.
         store: &NodeStorage,
-        // The configuration parameters.
-        parameters: Parameters,
+        // The configuration parameters, the ones tunable at runtime being updated through the
+        // channel.
+        rx_parameters: watch::Receiver<Parameters>,
         // Whether to run consensus (and an executor client) or not.
         // If true, an internal consensus will be used, else an external consensus will be used.
         // If an external consensus will be used, then this bool will also ensure that the
@@ -195,6 +248,7 @@ impl PrimaryNodeInner {
     where
         State: ExecutionState + Send + Sync + 'static,
     {
+        let parameters = rx_parameters.borrow().clone();
         // These gauge is porcelain: do not modify it without also modifying `primary::metrics::PrimaryChannelMetrics::replace_registered_new_certificates_metric`
         // This hack avoids a cyclic dependency in the initialization of consensus and primary
         let new_certificates_counter = IntGauge::new(
@@ -214,17 +268,38 @@ impl PrimaryNodeInner {
             metered_channel::channel(Self::CHANNEL_CAPACITY, &committed_certificates_counter);
 
         // Compute the public key of this authority.
-        let name = keypair.public().clone();
+        let name = keypair.public_key();
 
         // Figure out the id for this authority
         let authority = committee
             .authority_by_key(&name)
             .unwrap_or_else(|| panic!("Our node with key {:?} should be in committee", name));
 
+        // A primary joining with empty stores starts from the DAG snapshot of a peer, before
+        // consensus recovers its state from the stores.
+        if parameters.dag_snapshot.bootstrap {
+            bootstrap_from_snapshot(
+                authority.id(),
+                &network_keypair,
+                &committee,
+                &worker_cache,
+                &store.certificate_store,
+                &store.consensus_store,
+                &parameters.dag_snapshot,
+            )
+            .await;
+        }
+
         let mut handles = Vec::new();
         let (tx_executor_network, rx_executor_network) = oneshot::channel();
-        let (tx_consensus_round_updates, rx_consensus_round_updates) =
-            watch::channel(ConsensusRound::new(0, 0));
+        let rx_consensus_round_updates = tx_consensus_round_updates.subscribe();
+        // The leader schedule is shared between consensus and the proposer, and is restored from
+        // the latest reputation scores committed, when the leader swaps are enabled.
+        let leader_schedule = LeaderSchedule::from_store(
+            committee.clone(),
+            store.consensus_store.clone(),
+            parameters.leader_swaps,
+        );
         let (dag, network_model) = if !internal_consensus {
             debug!("Consensus is disabled: the primary will run w/o Bullshark");
             let consensus_metrics = Arc::new(ConsensusMetrics::new(registry));
@@ -251,6 +326,7 @@ impl PrimaryNodeInner {
                 rx_new_certificates,
                 tx_committed_certificates.clone(),
                 tx_consensus_round_updates,
+                leader_schedule.clone(),
                 registry,
             )
             .await?;
@@ -260,6 +336,25 @@ impl PrimaryNodeInner {
             (None, NetworkModel::PartiallySynchronous)
         };
 
+        // Prune the batch store as the garbage collection round advances, unless every batch
+        // is kept. A zero interval disables the pruning, and tokio panics on it.
+        if parameters.batch_retention_policy != BatchRetentionPolicy::KeepAll
+            && !parameters.batch_pruning_interval.is_zero()
+        {
+            let batch_store_pruner = BatchStorePruner::new(
+                store.batch_store.clone(),
+                store.certificate_store.clone(),
+                parameters.batch_retention_policy.clone(),
+                BatchStorePrunerMetrics::new(registry),
+            );
+            handles.push(Self::spawn_batch_store_pruner(
+                batch_store_pruner,
+                parameters.batch_pruning_interval,
+                rx_consensus_round_updates.clone(),
+                tx_shutdown.subscribe(),
+            ));
+        }
+
         // Spawn the primary.
         let primary_handles = Primary::spawn(
             authority.clone(),
@@ -267,17 +362,21 @@ impl PrimaryNodeInner {
             network_keypair,
             committee.clone(),
             worker_cache.clone(),
-            parameters.clone(),
+            rx_peer_addresses,
+            rx_parameters,
             store.header_store.clone(),
             store.certificate_store.clone(),
             store.proposer_store.clone(),
             store.payload_store.clone(),
             store.vote_digest_store.clone(),
+            store.consensus_store.clone(),
+            store.batch_store.clone(),
             tx_new_certificates,
             rx_committed_certificates,
             rx_consensus_round_updates,
             dag,
             network_model,
+            leader_schedule,
             tx_shutdown,
             tx_committed_certificates,
             registry,
@@ -288,6 +387,35 @@ impl PrimaryNodeInner {
         Ok(handles)
     }
 
+    /// Periodically prunes the batch store, given the latest garbage collection round.
+    fn spawn_batch_store_pruner(
+        mut pruner: BatchStorePruner,
+        interval: Duration,
+        rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+        mut rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        spawn_logged_monitored_task!(
+            async move {
+                let mut ticker = tokio::time::interval(interval);
+                loop {
+                    tokio::select! {
+                        _ = ticker.tick() => {
+                            let gc_round = rx_consensus_round_updates.borrow().gc_round;
+                            if let Err(e) = pruner.prune(gc_round) {
+                                error!("Failed to prune the batch store: {e:?}");
+                            }
+                        }
+
+                        _ = rx_shutdown.receiver.recv() => {
+                            return
+                        }
+                    }
+                }
+            },
+            "BatchStorePrunerTask"
+        )
+    }
+
     /// Spawn the consensus core and the client executing transactions.
     async fn spawn_consensus<State>(
         authority_id: AuthorityIdentifier,
@@ -301,6 +429,7 @@ impl PrimaryNodeInner {
         rx_new_certificates: metered_channel::Receiver<Certificate>,
         tx_committed_certificates: metered_channel::Sender<(Round, Vec<Certificate>)>,
         tx_consensus_round_updates: watch::Sender<ConsensusRound>,
+        leader_schedule: LeaderSchedule,
         registry: &Registry,
     ) -> SubscriberResult<Vec<JoinHandle<()>>>
     where
@@ -336,7 +465,8 @@ impl PrimaryNodeInner {
             committee.clone(),
             store.consensus_store.clone(),
             consensus_metrics.clone(),
-            Self::CONSENSUS_SCHEDULE_CHANGE_SUB_DAGS,
+            PrimaryNode::CONSENSUS_SCHEDULE_CHANGE_SUB_DAGS,
+            leader_schedule,
         );
         let consensus_handles = Consensus::spawn(
             committee.clone(),
@@ -379,6 +509,11 @@ pub struct PrimaryNode {
 }
 
 impl PrimaryNode {
+    /// The window where the schedule change takes place in consensus. It represents number
+    /// of committed sub dags.
+    /// TODO: move this to node properties
+    pub const CONSENSUS_SCHEDULE_CHANGE_SUB_DAGS: u64 = 300;
+
     pub fn new(
         parameters: Parameters,
         internal_consensus: bool,
@@ -391,6 +526,9 @@ impl PrimaryNode {
             registry: None,
             handles: FuturesUnordered::new(),
             tx_shutdown: None,
+            tx_peer_addresses: None,
+            tx_parameters: None,
+            rx_consensus_round_updates: None,
         };
 
         Self {
@@ -399,8 +537,8 @@ impl PrimaryNode {
     }
 
     pub async fn start<State>(
-        &self, // The private-public key pair of this authority.
-        keypair: KeyPair,
+        &self, // The signer of the protocol key of this authority, e.g. its key pair.
+        keypair: impl AuthoritySigner,
         // The private-public network key pair of this authority.
         network_keypair: NetworkKeyPair,
         // The committee information.
@@ -411,6 +549,12 @@ impl PrimaryNode {
         store: &NodeStorage,
         // The state used by the client to execute transactions.
         execution_state: Arc<State>,
+        // The limits on the batch digests of the headers, when decided by the protocol for this
+        // epoch. They override the ones of the parameters.
+        header_payload_limits: Option<HeaderPayloadLimits>,
+        // Whether the leaders with a low reputation are swapped out, as decided by the protocol
+        // for this epoch.
+        leader_swaps: bool,
     ) -> Result<(), NodeError>
     where
         State: ExecutionState + Send + Sync + 'static,
@@ -424,6 +568,8 @@ impl PrimaryNode {
                 worker_cache,
                 store,
                 execution_state,
+                header_payload_limits,
+                leader_swaps,
             )
             .await
     }
@@ -433,6 +579,30 @@ impl PrimaryNode {
         guard.shutdown().await
     }
 
+    /// Updates the addresses of the peers of the primary, if running, e.g. when a member of the
+    /// committee moves during the epoch.
+    pub async fn update_peer_addresses(&self, peer_addresses: &PeerAddresses) {
+        let guard = self.internal.read().await;
+        guard.update_peer_addresses(peer_addresses)
+    }
+
+    /// Subscribes to the last committed round and GC round of consensus, if the primary is
+    /// running. The certificates of the rounds up to the GC round can never be committed anymore.
+    pub async fn subscribe_consensus_round(&self) -> Option<watch::Receiver<ConsensusRound>> {
+        let guard = self.internal.read().await;
+        guard.rx_consensus_round_updates.clone()
+    }
+
+    /// Sets the parameters of the primary, which apply the next time it is started. The ones
+    /// tunable at runtime also apply to the running primary, if any.
+    pub async fn set_parameters(&self, parameters: Parameters) {
+        let mut guard = self.internal.write().await;
+        if let Some(tx_parameters) = guard.tx_parameters.as_ref() {
+            tx_parameters.send_modify(|running| running.set_runtime_parameters(&parameters));
+        }
+        guard.parameters = parameters;
+    }
+
     pub async fn is_running(&self) -> bool {
         let guard = self.internal.read().await;
         guard.is_running().await
@@ -0,0 +1,557 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::{primary_node::PrimaryNode, worker_node::WorkerNodes, NodeError};
+use async_trait::async_trait;
+use bytes::{BufMut as _, BytesMut};
+use config::{
+    utils::get_available_port, Committee, CommitteeBuilder, Parameters, WorkerCache, WorkerId,
+    WorkerIndex, WorkerInfo, WorkerSharding,
+};
+use crypto::{KeyPair, NetworkKeyPair};
+use executor::ExecutionState;
+use fastcrypto::traits::KeyPair as _;
+use futures::StreamExt;
+use mysten_metrics::RegistryService;
+use mysten_network::{multiaddr::to_socket_addr, Multiaddr};
+use prometheus::Registry;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use storage::NodeStorage;
+use tokio::time::{interval, sleep, MissedTickBehavior};
+use tracing::{info, warn};
+use types::{BatchAPI, ConsensusOutput, TransactionProto, TransactionsClient};
+use worker::TrivialTransactionValidator;
+
+/// The size of the header of the profiling transactions: a counter making them unique, and the
+/// time they are submitted at.
+pub const TRANSACTION_HEADER_SIZE: usize = 16;
+
+/// The synthetic load submitted to the committee.
+#[derive(Clone, Debug)]
+pub struct LoadProfile {
+    /// The size of each transaction, in bytes.
+    pub transaction_size: usize,
+    /// The number of transactions submitted per second, over all the workers.
+    pub rate: u64,
+    /// The number of bursts the transactions of each second are submitted in: the fewer, the
+    /// burstier the load.
+    pub bursts_per_second: u64,
+    /// How long the load is submitted for.
+    pub duration: Duration,
+    /// How long to wait for the last transactions to be committed, once the load stops.
+    pub drain_timeout: Duration,
+}
+
+impl Default for LoadProfile {
+    fn default() -> Self {
+        Self {
+            transaction_size: 512,
+            rate: 1_000,
+            bursts_per_second: 20,
+            duration: Duration::from_secs(30),
+            drain_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl LoadProfile {
+    fn burst_size(&self) -> Result<u64, eyre::Report> {
+        eyre::ensure!(
+            self.transaction_size >= TRANSACTION_HEADER_SIZE,
+            "The transactions must be at least {TRANSACTION_HEADER_SIZE} bytes"
+        );
+        eyre::ensure!(
+            self.bursts_per_second > 0 && self.rate >= self.bursts_per_second,
+            "The rate must be at least the number of bursts per second"
+        );
+        Ok(self.rate / self.bursts_per_second)
+    }
+}
+
+#[derive(Default)]
+struct CommitRecord {
+    submitted_at: Option<Instant>,
+    // The latency of each committed transaction, in microseconds.
+    latencies: Vec<u64>,
+    // The number of transactions committed during each second of the profile.
+    committed_per_second: BTreeMap<u64, u64>,
+    committed_bytes: u64,
+    sub_dags: u64,
+    last_commit: Option<Instant>,
+}
+
+/// Records the commit latency of the transactions submitted by the profiler, when the consensus
+/// output of one of the authorities is executed.
+#[derive(Clone, Default)]
+struct CommitRecorder {
+    record: Arc<Mutex<CommitRecord>>,
+}
+
+impl CommitRecorder {
+    fn start(&self) -> Instant {
+        let now = Instant::now();
+        self.record.lock().unwrap().submitted_at = Some(now);
+        now
+    }
+
+    fn committed(record: &mut CommitRecord, transaction: &[u8], now: Instant) {
+        let Some(start) = record.submitted_at else {
+            return;
+        };
+        let Some(sent) = transaction
+            .get(8..TRANSACTION_HEADER_SIZE)
+            .map(|sent| u64::from_be_bytes(sent.try_into().unwrap()))
+        else {
+            return;
+        };
+        let elapsed = now.duration_since(start).as_micros() as u64;
+        record.latencies.push(elapsed.saturating_sub(sent));
+        *record
+            .committed_per_second
+            .entry(elapsed / 1_000_000)
+            .or_default() += 1;
+        record.committed_bytes += transaction.len() as u64;
+        record.last_commit = Some(now);
+    }
+}
+
+#[async_trait]
+impl ExecutionState for CommitRecorder {
+    async fn handle_consensus_output(&self, consensus_output: ConsensusOutput) {
+        let now = Instant::now();
+        let mut record = self.record.lock().unwrap();
+        for (_, batches) in &consensus_output.batches {
+            for batch in batches {
+                for transaction in batch.transactions() {
+                    Self::committed(&mut record, transaction, now);
+                }
+            }
+        }
+        record.sub_dags += 1;
+    }
+
+    async fn last_executed_sub_dag_index(&self) -> u64 {
+        0
+    }
+}
+
+/// Ignores the consensus output of the other authorities.
+struct NoopExecutionState;
+
+#[async_trait]
+impl ExecutionState for NoopExecutionState {
+    async fn handle_consensus_output(&self, _consensus_output: ConsensusOutput) {}
+
+    async fn last_executed_sub_dag_index(&self) -> u64 {
+        0
+    }
+}
+
+/// The distribution of a sample of values.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Distribution {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    pub fn new(mut values: Vec<f64>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: usize| values[((values.len() - 1) * p + 50) / 100];
+        Self {
+            min: values[0],
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min={:.1} mean={:.1} p50={:.1} p90={:.1} p99={:.1} max={:.1}",
+            self.min, self.mean, self.p50, self.p90, self.p99, self.max
+        )
+    }
+}
+
+/// The results of a profile.
+#[derive(Clone, Debug)]
+pub struct ProfileReport {
+    pub submitted_transactions: u64,
+    pub committed_transactions: u64,
+    pub committed_sub_dags: u64,
+    /// The committed transactions per second, from the start of the load to the last commit.
+    pub throughput: f64,
+    pub throughput_bytes: f64,
+    /// The transactions committed during each second of the profile.
+    pub throughput_per_second: Distribution,
+    /// The latency from the submission of the transactions to their commit, in milliseconds.
+    pub latency_ms: Distribution,
+}
+
+impl ProfileReport {
+    fn new(submitted_transactions: u64, record: &CommitRecord) -> Self {
+        let elapsed = match (record.submitted_at, record.last_commit) {
+            (Some(start), Some(end)) => end.duration_since(start).as_secs_f64(),
+            _ => 0.0,
+        };
+        let committed_transactions = record.latencies.len() as u64;
+        let (throughput, throughput_bytes) = if elapsed > 0.0 {
+            (
+                committed_transactions as f64 / elapsed,
+                record.committed_bytes as f64 / elapsed,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        Self {
+            submitted_transactions,
+            committed_transactions,
+            committed_sub_dags: record.sub_dags,
+            throughput,
+            throughput_bytes,
+            throughput_per_second: Distribution::new(
+                record
+                    .committed_per_second
+                    .values()
+                    .map(|count| *count as f64)
+                    .collect(),
+            ),
+            latency_ms: Distribution::new(
+                record
+                    .latencies
+                    .iter()
+                    .map(|latency| *latency as f64 / 1_000.0)
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Committed {} of {} transactions in {} sub dags",
+            self.committed_transactions, self.submitted_transactions, self.committed_sub_dags
+        )?;
+        writeln!(
+            f,
+            "Throughput: {:.0} tx/s, {:.0} B/s",
+            self.throughput, self.throughput_bytes
+        )?;
+        writeln!(
+            f,
+            "Throughput per second (tx): {}",
+            self.throughput_per_second
+        )?;
+        write!(f, "Commit latency (ms): {}", self.latency_ms)
+    }
+}
+
+struct LocalAuthority {
+    keypair: KeyPair,
+    network_keypair: NetworkKeyPair,
+    worker_keypairs: Vec<(WorkerId, NetworkKeyPair)>,
+}
+
+/// A committee of primaries and workers running in this process, on local ports.
+pub struct LocalCommittee {
+    authorities: Vec<LocalAuthority>,
+    committee: Committee,
+    worker_cache: WorkerCache,
+}
+
+impl LocalCommittee {
+    pub fn new(committee_size: usize, workers_per_authority: usize) -> Self {
+        let mut rng = StdRng::from_entropy();
+        let host = "127.0.0.1";
+        let mut address = |protocol: &str| -> Multiaddr {
+            format!("/ip4/{host}/{protocol}/{}", get_available_port(host))
+                .parse()
+                .unwrap()
+        };
+
+        let mut committee_builder = CommitteeBuilder::new(0);
+        let mut workers = BTreeMap::new();
+        let mut authorities = Vec::new();
+        for _ in 0..committee_size {
+            let keypair = KeyPair::generate(&mut rng);
+            let network_keypair = NetworkKeyPair::generate(&mut rng);
+            committee_builder = committee_builder.add_authority(
+                keypair.public().clone(),
+                1,
+                address("udp"),
+                network_keypair.public().clone(),
+            );
+
+            let mut worker_index = BTreeMap::new();
+            let mut worker_keypairs = Vec::new();
+            for id in 0..workers_per_authority as WorkerId {
+                let worker_keypair = NetworkKeyPair::generate(&mut rng);
+                worker_index.insert(
+                    id,
+                    WorkerInfo {
+                        name: worker_keypair.public().clone(),
+                        worker_address: address("udp"),
+                        transactions: format!("{}/http", address("tcp")).parse().unwrap(),
+                    },
+                );
+                worker_keypairs.push((id, worker_keypair));
+            }
+            workers.insert(keypair.public().clone(), WorkerIndex(worker_index));
+            authorities.push(LocalAuthority {
+                keypair,
+                network_keypair,
+                worker_keypairs,
+            });
+        }
+
+        Self {
+            authorities,
+            committee: committee_builder.build(),
+            worker_cache: WorkerCache {
+                workers,
+                epoch: 0,
+                sharding: WorkerSharding::default(),
+            },
+        }
+    }
+
+    // The urls the workers receive the transactions on.
+    fn transactions_urls(&self) -> Vec<String> {
+        self.worker_cache
+            .workers
+            .values()
+            .flat_map(|index| index.0.values())
+            .map(|info| {
+                let address = to_socket_addr(&info.transactions).unwrap();
+                format!("http://{address}")
+            })
+            .collect()
+    }
+}
+
+struct RunningAuthority {
+    primary: PrimaryNode,
+    workers: WorkerNodes,
+}
+
+/// Profiles the throughput and commit latency of a local committee, under a synthetic load. This
+/// measures the protocol alone, without the execution of the transactions nor a Sui deployment.
+pub struct ThroughputProfiler {
+    committee: LocalCommittee,
+    parameters: Parameters,
+}
+
+impl ThroughputProfiler {
+    pub fn new(committee: LocalCommittee, parameters: Parameters) -> Self {
+        Self {
+            committee,
+            parameters,
+        }
+    }
+
+    /// Starts the committee, with its stores in `store_path`, submits the load to its workers and
+    /// reports the commits observed by the first authority.
+    pub async fn run(
+        &self,
+        load: &LoadProfile,
+        store_path: &Path,
+    ) -> Result<ProfileReport, eyre::Report> {
+        let burst_size = load.burst_size()?;
+        let recorder = CommitRecorder::default();
+        let authorities = self.start_committee(&recorder, store_path).await?;
+
+        let mut clients = Vec::new();
+        for url in self.committee.transactions_urls() {
+            let client = loop {
+                match TransactionsClient::connect(url.clone()).await {
+                    Ok(client) => break client,
+                    Err(_) => sleep(Duration::from_millis(100)).await,
+                }
+            };
+            clients.push(client);
+        }
+        info!(
+            "Started {} authorities, submitting {} tx/s of {} B in {} bursts per second",
+            authorities.len(),
+            load.rate,
+            load.transaction_size,
+            load.bursts_per_second
+        );
+
+        let start = recorder.start();
+        let mut ticks = interval(Duration::from_secs(1) / load.bursts_per_second as u32);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Burst);
+        let mut counter = 0u64;
+        'load: while start.elapsed() < load.duration {
+            ticks.tick().await;
+            let sent = start.elapsed().as_micros() as u64;
+            let mut transactions = Vec::new();
+            for _ in 0..burst_size {
+                let mut transaction = BytesMut::with_capacity(load.transaction_size);
+                transaction.put_u64(counter);
+                transaction.put_u64(sent);
+                transaction.resize(load.transaction_size, 0u8);
+                transactions.push(transaction.freeze());
+                counter += 1;
+            }
+            // The burst is spread over all the workers.
+            let per_client = (transactions.len() + clients.len() - 1) / clients.len();
+            for (client, chunk) in clients
+                .iter_mut()
+                .zip(transactions.chunks(per_client.max(1)))
+            {
+                let stream =
+                    futures::stream::iter(chunk.to_vec()).map(|transaction| TransactionProto {
+                        transaction,
+                        ..Default::default()
+                    });
+                if let Err(e) = client.submit_transaction_stream(stream).await {
+                    warn!("Failed to submit transactions: {e}");
+                    break 'load;
+                }
+            }
+        }
+
+        // Wait for the transactions still in flight.
+        let drain_start = Instant::now();
+        while drain_start.elapsed() < load.drain_timeout
+            && (recorder.record.lock().unwrap().latencies.len() as u64) < counter
+        {
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        for authority in &authorities {
+            authority.workers.shutdown().await;
+            authority.primary.shutdown().await;
+        }
+
+        let record = recorder.record.lock().unwrap();
+        Ok(ProfileReport::new(counter, &record))
+    }
+
+    async fn start_committee(
+        &self,
+        recorder: &CommitRecorder,
+        store_path: &Path,
+    ) -> Result<Vec<RunningAuthority>, NodeError> {
+        let committee = &self.committee;
+        let mut running = Vec::new();
+        for (i, authority) in committee.authorities.iter().enumerate() {
+            let registry_service = RegistryService::new(Registry::new());
+            let primary = PrimaryNode::new(self.parameters.clone(), true, registry_service.clone());
+            let primary_store = NodeStorage::reopen(store_path.join(format!("primary-{i}")), None);
+            // Only the commits of the first authority are recorded.
+            if i == 0 {
+                primary
+                    .start(
+                        authority.keypair.copy(),
+                        authority.network_keypair.copy(),
+                        committee.committee.clone(),
+                        committee.worker_cache.clone(),
+                        &primary_store,
+                        Arc::new(recorder.clone()),
+                        None,
+                        /* leader_swaps */ false,
+                    )
+                    .await?;
+            } else {
+                primary
+                    .start(
+                        authority.keypair.copy(),
+                        authority.network_keypair.copy(),
+                        committee.committee.clone(),
+                        committee.worker_cache.clone(),
+                        &primary_store,
+                        Arc::new(NoopExecutionState),
+                        None,
+                        /* leader_swaps */ false,
+                    )
+                    .await?;
+            }
+
+            let workers = WorkerNodes::new(registry_service, self.parameters.clone());
+            let worker_store = NodeStorage::reopen(store_path.join(format!("workers-{i}")), None);
+            workers
+                .start(
+                    authority.keypair.public().clone(),
+                    authority
+                        .worker_keypairs
+                        .iter()
+                        .map(|(id, keypair)| (*id, keypair.copy()))
+                        .collect(),
+                    committee.committee.clone(),
+                    committee.worker_cache.clone(),
+                    &worker_store,
+                    TrivialTransactionValidator::default(),
+                    self.parameters.batch_version,
+                    None,
+                )
+                .await?;
+
+            running.push(RunningAuthority { primary, workers });
+        }
+        Ok(running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommitRecord, Distribution, ProfileReport};
+    use std::collections::BTreeMap;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn distribution_percentiles() {
+        let distribution = Distribution::new((1..=100).rev().map(f64::from).collect());
+        assert_eq!(distribution.min, 1.0);
+        assert_eq!(distribution.max, 100.0);
+        assert_eq!(distribution.mean, 50.5);
+        assert_eq!(distribution.p50, 51.0);
+        assert_eq!(distribution.p90, 90.0);
+        assert_eq!(distribution.p99, 99.0);
+
+        assert_eq!(Distribution::new(Vec::new()), Distribution::default());
+    }
+
+    #[test]
+    fn report_throughput() {
+        let start = Instant::now();
+        let record = CommitRecord {
+            submitted_at: Some(start),
+            latencies: vec![1_000, 2_000, 3_000, 4_000],
+            committed_per_second: BTreeMap::from([(0, 1), (1, 3)]),
+            committed_bytes: 400,
+            sub_dags: 2,
+            last_commit: Some(start + Duration::from_secs(2)),
+        };
+        let report = ProfileReport::new(5, &record);
+        assert_eq!(report.submitted_transactions, 5);
+        assert_eq!(report.committed_transactions, 4);
+        assert_eq!(report.committed_sub_dags, 2);
+        assert_eq!(report.throughput, 2.0);
+        assert_eq!(report.throughput_bytes, 200.0);
+        assert_eq!(report.throughput_per_second.max, 3.0);
+        assert_eq!(report.latency_ms.max, 4.0);
+    }
+}
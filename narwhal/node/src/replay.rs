@@ -0,0 +1,208 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::primary_node::PrimaryNode;
+use config::Committee;
+use consensus::{
+    bullshark::Bullshark,
+    consensus::{ConsensusProtocol, ConsensusState},
+    leader_schedule::LeaderSchedule,
+    metrics::ConsensusMetrics,
+    ConsensusError,
+};
+use fastcrypto::hash::{Hash, HashFunction};
+use prometheus::Registry;
+use std::{fmt, path::Path, sync::Arc};
+use storage::NodeStorage;
+use store::Map;
+use types::{
+    BatchDigest, CertificateAPI, CertificateDigest, CommittedSubDag, CommittedSubDagShell,
+    HeaderAPI, Round, SequenceNumber, TimestampMs,
+};
+
+/// A sub dag committed by the replay of the consensus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayedSubDag {
+    pub sub_dag_index: SequenceNumber,
+    pub leader_round: Round,
+    pub leader: CertificateDigest,
+    /// The certificates of the sub dag, in commit order.
+    pub certificates: Vec<CertificateDigest>,
+    pub commit_timestamp: TimestampMs,
+    /// The batches of the sub dag, in commit order.
+    pub batches: Vec<BatchDigest>,
+    /// The batches of the sub dag that are not in the batch store.
+    pub num_missing_batches: usize,
+}
+
+impl ReplayedSubDag {
+    fn new(sub_dag: &CommittedSubDag, store: &NodeStorage) -> Result<Self, ConsensusError> {
+        let batches: Vec<BatchDigest> = sub_dag
+            .certificates
+            .iter()
+            .flat_map(|certificate| certificate.header().payload().keys().copied())
+            .collect();
+        let num_missing_batches = store
+            .batch_store
+            .multi_get(&batches)?
+            .iter()
+            .filter(|batch| batch.is_none())
+            .count();
+        Ok(Self {
+            sub_dag_index: sub_dag.sub_dag_index,
+            leader_round: sub_dag.leader_round(),
+            leader: sub_dag.leader.digest(),
+            certificates: sub_dag.certificates.iter().map(|x| x.digest()).collect(),
+            commit_timestamp: sub_dag.commit_timestamp,
+            batches,
+            num_missing_batches,
+        })
+    }
+
+    /// Whether the sub dag was committed as persisted by the consensus of the node. The sub
+    /// dags persisted before their timestamp was recorded have a timestamp of 0, which is not
+    /// compared.
+    pub fn matches(&self, persisted: &CommittedSubDagShell) -> bool {
+        self.sub_dag_index == persisted.sub_dag_index
+            && self.leader == persisted.leader
+            && self.certificates == persisted.certificates
+            && (persisted.commit_timestamp == 0
+                || self.commit_timestamp == persisted.commit_timestamp)
+    }
+}
+
+impl fmt::Display for ReplayedSubDag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} certificates={} batches={} missing_batches={}",
+            self.sub_dag_index,
+            self.leader_round,
+            self.leader,
+            self.commit_timestamp,
+            self.certificates
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            self.batches.len(),
+            self.num_missing_batches,
+        )
+    }
+}
+
+/// The commit sequence replayed from the certificates of a node.
+pub struct ConsensusReplay {
+    pub sub_dags: Vec<ReplayedSubDag>,
+    /// The digest of the whole commit sequence, identical on the nodes that committed the same
+    /// sub dags in the same order.
+    pub digest: [u8; crypto::DIGEST_LENGTH],
+}
+
+impl ConsensusReplay {
+    /// Replays the certificates of the node storage through Bullshark, from genesis. The storage
+    /// of the node is not modified: the consensus state of the replay is persisted in
+    /// `scratch_path`, which should be an empty directory.
+    pub fn run(
+        committee: &Committee,
+        store: &NodeStorage,
+        gc_depth: Round,
+        scratch_path: impl AsRef<Path> + Send,
+    ) -> Result<Self, ConsensusError> {
+        let metrics = Arc::new(ConsensusMetrics::new(&Registry::new()));
+        let scratch_store = NodeStorage::reopen(scratch_path, None);
+        let mut state = ConsensusState::new(metrics.clone(), committee, gc_depth);
+        let mut bullshark = Bullshark::new(
+            committee.clone(),
+            scratch_store.consensus_store,
+            metrics,
+            PrimaryNode::CONSENSUS_SCHEDULE_CHANGE_SUB_DAGS,
+            // The standalone primaries never swap their leaders, there is no protocol to decide it.
+            LeaderSchedule::without_leader_swaps(committee.clone()),
+        );
+
+        // The certificates are sorted by round, then by origin, so that they are processed in
+        // the same order regardless of the order in which the node received them.
+        let mut sub_dags = Vec::new();
+        for certificate in store.certificate_store.after_round(1)? {
+            let (_, committed) = bullshark.process_certificate(&mut state, certificate)?;
+            for sub_dag in &committed {
+                sub_dags.push(ReplayedSubDag::new(sub_dag, store)?);
+            }
+        }
+
+        let mut hasher = crypto::DefaultHashFunction::new();
+        for sub_dag in &sub_dags {
+            hasher.update(sub_dag.sub_dag_index.to_le_bytes());
+            hasher.update(sub_dag.leader);
+            for certificate in &sub_dag.certificates {
+                hasher.update(certificate);
+            }
+        }
+        Ok(Self {
+            sub_dags,
+            digest: hasher.finalize().into(),
+        })
+    }
+
+    /// Compares the replayed commit sequence with the one persisted by the consensus of the
+    /// node. Returns the index of the first sub dag that differs, if any.
+    pub fn first_divergence(
+        &self,
+        store: &NodeStorage,
+    ) -> Result<Option<SequenceNumber>, ConsensusError> {
+        let persisted = store.consensus_store.read_committed_sub_dags_from(&0)?;
+        let divergence = self
+            .sub_dags
+            .iter()
+            .zip(&persisted)
+            .find(|(replayed, persisted)| !replayed.matches(persisted))
+            .map(|(replayed, _)| replayed.sub_dag_index);
+        Ok(divergence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConsensusReplay;
+    use fastcrypto::hash::Hash;
+    use std::collections::BTreeSet;
+    use storage::NodeStorage;
+    use test_utils::{make_optimal_certificates, temp_dir, CommitteeFixture};
+    use types::Certificate;
+
+    #[test]
+    fn replay_is_deterministic() {
+        let fixture = CommitteeFixture::builder().build();
+        let committee = fixture.committee();
+        let genesis = Certificate::genesis(&committee)
+            .iter()
+            .map(|x| x.digest())
+            .collect::<BTreeSet<_>>();
+        let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+        let (certificates, _) = make_optimal_certificates(&committee, 1..=10, &genesis, &ids);
+
+        // Two nodes storing the same certificates in a different order.
+        let store = NodeStorage::reopen(temp_dir(), None);
+        let other_store = NodeStorage::reopen(temp_dir(), None);
+        for certificate in &certificates {
+            store.certificate_store.write(certificate.clone()).unwrap();
+        }
+        for certificate in certificates.iter().rev() {
+            other_store
+                .certificate_store
+                .write(certificate.clone())
+                .unwrap();
+        }
+
+        let replay = ConsensusReplay::run(&committee, &store, 50, temp_dir()).unwrap();
+        let other_replay = ConsensusReplay::run(&committee, &other_store, 50, temp_dir()).unwrap();
+
+        // The leaders of the rounds 2 to 8 are committed.
+        assert_eq!(replay.sub_dags.len(), 4);
+        assert_eq!(replay.sub_dags, other_replay.sub_dags);
+        assert_eq!(replay.digest, other_replay.digest);
+
+        // Nothing was persisted by the consensus of the node, so nothing diverges.
+        assert_eq!(replay.first_divergence(&store).unwrap(), None);
+    }
+}
@@ -4,20 +4,24 @@
 use crate::metrics::new_registry;
 use crate::{try_join_all, FuturesUnordered, NodeError};
 use arc_swap::{ArcSwap, ArcSwapOption};
-use config::{Committee, Parameters, WorkerCache, WorkerId};
-use crypto::{NetworkKeyPair, PublicKey};
+use config::{BatchLimits, BatchVersion, Committee, Parameters, WorkerCache, WorkerId};
+use crypto::{traits::KeyPair as _, NetworkKeyPair, PublicKey};
 use mysten_metrics::{RegistryID, RegistryService};
+use network::discovery::PeerAddresses;
 use prometheus::Registry;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use storage::NodeStorage;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tokio::task::JoinHandle;
-use tracing::{info, instrument};
+use tracing::{error, info, instrument};
 use types::PreSubscribedBroadcastSender;
 use worker::metrics::{initialise_metrics, Metrics};
-use worker::{TransactionValidator, Worker, NUM_SHUTDOWN_RECEIVERS};
+use worker::{
+    drain_channel, DrainHandle, TransactionHandoff, TransactionValidator, Worker, WorkerStandby,
+    NUM_SHUTDOWN_RECEIVERS,
+};
 
 pub struct WorkerNodeInner {
     // The worker's id
@@ -32,6 +36,15 @@ pub struct WorkerNodeInner {
     handles: FuturesUnordered<JoinHandle<()>>,
     // The shutdown signal channel
     tx_shutdown: Option<PreSubscribedBroadcastSender>,
+    // The channel to update the addresses of the peers while running
+    tx_peer_addresses: Option<watch::Sender<PeerAddresses>>,
+    // The channel to update the parameters tunable at runtime while running
+    tx_parameters: Option<watch::Sender<Parameters>>,
+    // The handle to drain the worker before shutting it down
+    drain_handle: Option<DrainHandle>,
+    // Where the transactions not included when draining are handed off to the next worker
+    // started with the same id
+    handoff: TransactionHandoff,
 }
 
 impl WorkerNodeInner {
@@ -70,6 +83,7 @@ impl WorkerNodeInner {
         };
 
         let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+        let (drain_handle, drain) = drain_channel(self.handoff.clone());
 
         let authority = committee
             .authority_by_key(&primary_name)
@@ -80,17 +94,23 @@ impl WorkerNodeInner {
                 )
             });
 
+        let (tx_peer_addresses, rx_peer_addresses) =
+            watch::channel(PeerAddresses::new(&committee, &worker_cache));
+        let (tx_parameters, rx_parameters) = watch::channel(self.parameters.clone());
+
         let handles = Worker::spawn(
             authority.clone(),
             network_keypair,
             self.id,
             committee.clone(),
             worker_cache.clone(),
-            self.parameters.clone(),
+            rx_peer_addresses,
+            rx_parameters,
             tx_validator.clone(),
             store.batch_store.clone(),
             metrics,
             &mut tx_shutdown,
+            drain,
         );
 
         // store the registry
@@ -102,10 +122,54 @@ impl WorkerNodeInner {
         self.handles.clear();
         self.handles.extend(handles);
         self.tx_shutdown = Some(tx_shutdown);
+        self.tx_peer_addresses = Some(tx_peer_addresses);
+        self.tx_parameters = Some(tx_parameters);
+        self.drain_handle = Some(drain_handle);
 
         Ok(())
     }
 
+    // Updates the addresses of the peers of the running worker, when the committee members
+    // change their network addresses during the epoch.
+    fn update_peer_addresses(&self, new_peer_addresses: &PeerAddresses) {
+        if let Some(tx_peer_addresses) = self.tx_peer_addresses.as_ref() {
+            tx_peer_addresses.send_if_modified(|peer_addresses| {
+                let modified = peer_addresses != new_peer_addresses;
+                *peer_addresses = new_peer_addresses.clone();
+                modified
+            });
+        }
+    }
+
+    // Sets the parameters tunable at runtime, applying them to the running worker if any.
+    fn set_runtime_parameters(&mut self, parameters: &Parameters) {
+        self.parameters.set_runtime_parameters(parameters);
+        if let Some(tx_parameters) = self.tx_parameters.as_ref() {
+            tx_parameters.send_modify(|running| running.set_runtime_parameters(parameters));
+        }
+    }
+
+    // Puts the worker node in drain mode: it stops accepting new transactions, flushes its
+    // pending batches and waits for them to be acknowledged or for the drain timeout. The
+    // transactions that could not be included are handed off to the next worker node started
+    // with the same id. The node still has to be shutdown afterwards.
+    #[instrument(level = "info", skip_all)]
+    async fn drain(&mut self) {
+        let Some(drain_handle) = self.drain_handle.take() else {
+            return;
+        };
+
+        let now = Instant::now();
+        drain_handle.drain().await;
+
+        info!(
+            "Narwhal worker {} drain is complete - took {} seconds, {} transactions handed off",
+            self.id,
+            now.elapsed().as_secs_f64(),
+            self.handoff.len()
+        );
+    }
+
     // Will shutdown the worker node and wait until the node has shutdown by waiting on the
     // underlying components handles. If the node was not already running then the
     // method will return immediately.
@@ -122,6 +186,9 @@ impl WorkerNodeInner {
                 .expect("Couldn't send the shutdown signal to downstream components");
             self.tx_shutdown = None;
         }
+        self.tx_peer_addresses = None;
+        self.tx_parameters = None;
+        self.drain_handle = None;
 
         // Now wait until handles have been completed
         try_join_all(&mut self.handles).await.unwrap();
@@ -173,6 +240,7 @@ impl WorkerNode {
         id: WorkerId,
         parameters: Parameters,
         registry_service: RegistryService,
+        handoff: TransactionHandoff,
     ) -> WorkerNode {
         let inner = WorkerNodeInner {
             id,
@@ -181,6 +249,10 @@ impl WorkerNode {
             registry: None,
             handles: FuturesUnordered::new(),
             tx_shutdown: None,
+            tx_peer_addresses: None,
+            tx_parameters: None,
+            drain_handle: None,
+            handoff,
         };
 
         Self {
@@ -219,11 +291,79 @@ impl WorkerNode {
             .await
     }
 
+    /// Runs as the standby of the worker holding the same network key: replicates the batches
+    /// of the worker into our store until it stops responding, then starts the worker in its
+    /// place, listening on its address.
+    pub async fn start_standby(
+        &self,
+        // The primary's public key of this authority.
+        primary_key: PublicKey,
+        // The private-public network key pair of this authority.
+        network_keypair: NetworkKeyPair,
+        // The committee information.
+        committee: Committee,
+        // The worker information cache.
+        worker_cache: WorkerCache,
+        // The node's store, distinct from the store of the worker replicated
+        store: &NodeStorage,
+        // The transaction validator defining Tx acceptance,
+        tx_validator: impl TransactionValidator,
+        // An optional metrics struct
+        metrics: Option<Metrics>,
+    ) -> Result<(), NodeError> {
+        let (id, parameters) = {
+            let guard = self.internal.read().await;
+            (guard.id, guard.parameters.worker_standby.clone())
+        };
+        let address = worker_cache
+            .worker(&primary_key, &id)
+            .expect("Our public key or worker id is not in the worker cache")
+            .worker_address;
+        WorkerStandby::new(
+            id,
+            network_keypair.copy(),
+            address,
+            committee.epoch(),
+            store.batch_store.clone(),
+            parameters,
+        )
+        .run()
+        .await;
+
+        self.start(
+            primary_key,
+            network_keypair,
+            committee,
+            worker_cache,
+            store,
+            tx_validator,
+            metrics,
+        )
+        .await
+    }
+
+    pub async fn drain(&self) {
+        let mut guard = self.internal.write().await;
+        guard.drain().await
+    }
+
     pub async fn shutdown(&self) {
         let mut guard = self.internal.write().await;
         guard.shutdown().await
     }
 
+    pub async fn update_peer_addresses(&self, peer_addresses: &PeerAddresses) {
+        let guard = self.internal.read().await;
+        guard.update_peer_addresses(peer_addresses)
+    }
+
+    /// Sets the parameters tunable at runtime of the worker, e.g. its maximum batch delay,
+    /// applying them to the worker if running.
+    pub async fn set_runtime_parameters(&self, parameters: &Parameters) {
+        let mut guard = self.internal.write().await;
+        guard.set_runtime_parameters(parameters)
+    }
+
     pub async fn is_running(&self) -> bool {
         let guard = self.internal.read().await;
         guard.is_running().await
@@ -239,7 +379,11 @@ pub struct WorkerNodes {
     workers: ArcSwap<HashMap<WorkerId, WorkerNode>>,
     registry_service: RegistryService,
     registry_id: ArcSwapOption<RegistryID>,
-    parameters: Parameters,
+    parameters: ArcSwap<Parameters>,
+    // The transactions handed off by the workers drained before, per worker id
+    handoffs: Mutex<HashMap<WorkerId, TransactionHandoff>>,
+    // The workers standing by, until they start in place of the workers they replicate
+    standbys: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl WorkerNodes {
@@ -248,10 +392,13 @@ impl WorkerNodes {
             workers: ArcSwap::from(Arc::new(HashMap::default())),
             registry_service,
             registry_id: ArcSwapOption::empty(),
-            parameters,
+            parameters: ArcSwap::from_pointee(parameters),
+            handoffs: Mutex::new(HashMap::default()),
+            standbys: Mutex::new(Vec::new()),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[instrument(level = "info", skip_all)]
     pub async fn start(
         &self,
@@ -267,6 +414,78 @@ impl WorkerNodes {
         store: &NodeStorage,
         // The transaction validator defining Tx acceptance,
         tx_validator: impl TransactionValidator,
+        // The version of the batches to create, as decided by the protocol for this epoch. It
+        // overrides the one of the parameters.
+        batch_version: BatchVersion,
+        // The limits on the transactions of the batches to create, when decided by the protocol
+        // for this epoch.
+        batch_limits: Option<BatchLimits>,
+    ) -> Result<(), NodeError> {
+        self.start_workers(
+            primary_key,
+            ids_and_keypairs,
+            committee,
+            worker_cache,
+            store,
+            tx_validator,
+            batch_version,
+            batch_limits,
+            false,
+        )
+        .await
+    }
+
+    /// Starts the workers as the standbys of the workers holding the same network keys, run by
+    /// another node. Each replicates the batches of its worker until it stops responding, then
+    /// starts in its place. Returns once the standbys are running.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(level = "info", skip_all)]
+    pub async fn start_standby(
+        &self,
+        // The primary's public key of this authority.
+        primary_key: PublicKey,
+        // The ids & keypairs of the workers to stand by for.
+        ids_and_keypairs: Vec<(WorkerId, NetworkKeyPair)>,
+        // The committee information.
+        committee: Committee,
+        // The worker information cache.
+        worker_cache: WorkerCache,
+        // The node's store, distinct from the stores of the workers replicated
+        store: &NodeStorage,
+        // The transaction validator defining Tx acceptance,
+        tx_validator: impl TransactionValidator,
+        // The version of the batches to create, as decided by the protocol for this epoch.
+        batch_version: BatchVersion,
+        // The limits on the transactions of the batches to create, when decided by the protocol
+        // for this epoch.
+        batch_limits: Option<BatchLimits>,
+    ) -> Result<(), NodeError> {
+        self.start_workers(
+            primary_key,
+            ids_and_keypairs,
+            committee,
+            worker_cache,
+            store,
+            tx_validator,
+            batch_version,
+            batch_limits,
+            true,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn start_workers(
+        &self,
+        primary_key: PublicKey,
+        ids_and_keypairs: Vec<(WorkerId, NetworkKeyPair)>,
+        committee: Committee,
+        worker_cache: WorkerCache,
+        store: &NodeStorage,
+        tx_validator: impl TransactionValidator,
+        batch_version: BatchVersion,
+        batch_limits: Option<BatchLimits>,
+        standby: bool,
     ) -> Result<(), NodeError> {
         let worker_ids_running = self.workers_running().await;
         if !worker_ids_running.is_empty() {
@@ -282,26 +501,65 @@ impl WorkerNodes {
         // as it's not guaranteed that shutdown has been called
         self.workers.store(Arc::new(HashMap::default()));
 
+        let mut parameters = self.parameters.load().as_ref().clone();
+        parameters.batch_version = batch_version;
+        parameters.batch_limits = batch_limits;
+
         let mut workers = HashMap::<WorkerId, WorkerNode>::new();
         // start all the workers one by one
         for (worker_id, key_pair) in ids_and_keypairs {
+            let handoff = self
+                .handoffs
+                .lock()
+                .unwrap()
+                .entry(worker_id)
+                .or_default()
+                .clone();
             let worker = WorkerNode::new(
                 worker_id,
-                self.parameters.clone(),
+                parameters.clone(),
                 self.registry_service.clone(),
+                handoff,
             );
 
-            worker
-                .start(
-                    primary_key.clone(),
-                    key_pair,
-                    committee.clone(),
-                    worker_cache.clone(),
-                    store,
-                    tx_validator.clone(),
-                    Some(metrics.clone()),
-                )
-                .await?;
+            if standby {
+                let worker = worker.clone();
+                let primary_key = primary_key.clone();
+                let committee = committee.clone();
+                let worker_cache = worker_cache.clone();
+                let store = store.clone();
+                let tx_validator = tx_validator.clone();
+                let metrics = metrics.clone();
+                let handle = tokio::spawn(async move {
+                    if let Err(e) = worker
+                        .start_standby(
+                            primary_key,
+                            key_pair,
+                            committee,
+                            worker_cache,
+                            &store,
+                            tx_validator,
+                            Some(metrics),
+                        )
+                        .await
+                    {
+                        error!("Unable to start worker {worker_id} in place of its worker: {e}");
+                    }
+                });
+                self.standbys.lock().unwrap().push(handle);
+            } else {
+                worker
+                    .start(
+                        primary_key.clone(),
+                        key_pair,
+                        committee.clone(),
+                        worker_cache.clone(),
+                        store,
+                        tx_validator.clone(),
+                        Some(metrics.clone()),
+                    )
+                    .await?;
+            }
 
             workers.insert(worker_id, worker);
         }
@@ -320,9 +578,23 @@ impl WorkerNodes {
         Ok(())
     }
 
-    // Shuts down all the workers
+    // Drains all the workers, so that the transactions they could not include are handed off
+    // to the next workers. Those of the next epoch fail them back to their submitters. Should be
+    // called before shutting them down at the end of an epoch.
+    #[instrument(level = "info", skip_all)]
+    pub async fn drain(&self) {
+        for (key, worker) in self.workers.load_full().as_ref() {
+            info!("Draining worker {}", key);
+            worker.drain().await;
+        }
+    }
+
+    // Shuts down all the workers, and the standbys yet to start in place of theirs
     #[instrument(level = "info", skip_all)]
     pub async fn shutdown(&self) {
+        for standby in self.standbys.lock().unwrap().drain(..) {
+            standby.abort();
+        }
         for (key, worker) in self.workers.load_full().as_ref() {
             info!("Shutting down worker {}", key);
             worker.shutdown().await;
@@ -338,6 +610,23 @@ impl WorkerNodes {
         self.workers.store(Arc::new(HashMap::default()));
     }
 
+    // Updates the addresses of the peers of the running workers, e.g. when a member of the
+    // committee moves during the epoch.
+    pub async fn update_peer_addresses(&self, peer_addresses: &PeerAddresses) {
+        for worker in self.workers.load_full().values() {
+            worker.update_peer_addresses(peer_addresses).await;
+        }
+    }
+
+    // Sets the parameters of the workers, which apply the next time they are started. The ones
+    // tunable at runtime also apply to the running workers.
+    pub async fn set_parameters(&self, parameters: Parameters) {
+        for worker in self.workers.load_full().values() {
+            worker.set_runtime_parameters(&parameters).await;
+        }
+        self.parameters.store(Arc::new(parameters));
+    }
+
     // returns the worker ids that are currently running
     pub async fn workers_running(&self) -> Vec<WorkerId> {
         let mut worker_ids = Vec::new();
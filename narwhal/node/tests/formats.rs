@@ -3,8 +3,9 @@
 
 #[test]
 fn test_format() {
-    // If this test breaks and you intended a format change, you need to run to get the fresh format:
-    // # cargo -q run --example narwhal-generate-format -- print > narwhal/node/tests/staged/narwhal.yaml
+    // If this test breaks and you intended a format change, you need to bump FORMAT_VERSION in
+    // src/generate_format.rs and run to record the fresh format:
+    // # cargo -q run --example narwhal-generate-format -- record
 
     let status = std::process::Command::new("cargo")
         .current_dir("..")
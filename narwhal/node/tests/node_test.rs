@@ -50,6 +50,8 @@ async fn simple_primary_worker_node_start_stop() {
             worker_cache.clone(),
             &store,
             execution_state,
+            None,
+            /* leader_swaps */ false,
         )
         .await
         .unwrap();
@@ -65,6 +67,8 @@ async fn simple_primary_worker_node_start_stop() {
             worker_cache,
             &store,
             TrivialTransactionValidator::default(),
+            parameters.batch_version,
+            None,
         )
         .await
         .unwrap();
@@ -129,6 +133,8 @@ async fn primary_node_restart() {
             worker_cache.clone(),
             &store,
             execution_state.clone(),
+            None,
+            /* leader_swaps */ false,
         )
         .await
         .unwrap();
@@ -149,6 +155,8 @@ async fn primary_node_restart() {
             worker_cache.clone(),
             &store,
             execution_state,
+            None,
+            /* leader_swaps */ false,
         )
         .await
         .unwrap();
@@ -18,7 +18,9 @@ use types::{
     Certificate, CertificateAPI, Header, Vote, VoteAPI,
 };
 
-/// Aggregates votes for a particular header into a certificate.
+/// Aggregates votes for a particular header into a certificate. The votes are appended without
+/// verifying their signatures, which are verified at once as an aggregate signature when the
+/// votes reach a quorum.
 pub struct VotesAggregator {
     weight: Stake,
     votes: Vec<(AuthorityIdentifier, Signature)>,
@@ -38,12 +40,8 @@ impl VotesAggregator {
         }
     }
 
-    pub fn append(
-        &mut self,
-        vote: Vote,
-        committee: &Committee,
-        header: &Header,
-    ) -> DagResult<Option<Certificate>> {
+    /// Appends a vote, and returns whether the votes appended reach a quorum.
+    pub fn append(&mut self, vote: Vote, committee: &Committee) -> DagResult<bool> {
         let author = vote.author();
 
         // Ensure it is the first time this authority votes.
@@ -58,41 +56,56 @@ impl VotesAggregator {
         self.metrics
             .votes_received_last_round
             .set(self.votes.len() as i64);
-        if self.weight >= committee.quorum_threshold() {
-            let cert = Certificate::new_unverified(committee, header.clone(), self.votes.clone())?;
-            let (_, pks) = cert.signed_by(committee);
+        Ok(self.weight >= committee.quorum_threshold())
+    }
 
-            let certificate_digest: Digest<{ crypto::DIGEST_LENGTH }> = Digest::from(cert.digest());
-            match AggregateSignature::try_from(cert.aggregated_signature())
-                .map_err(|_| DagError::InvalidSignature)?
-                .verify_secure(&to_intent_message(certificate_digest), &pks[..])
+    /// Forms the certificate of the header once the votes reach a quorum. The votes with invalid
+    /// signatures are removed, a certificate is still formed if the valid ones reach a quorum.
+    ///
+    /// This verifies signatures, so it is best called off the async runtime.
+    pub fn try_certify(
+        &mut self,
+        committee: &Committee,
+        header: &Header,
+    ) -> DagResult<Option<Certificate>> {
+        if self.weight < committee.quorum_threshold() {
+            return Ok(None);
+        }
+        let cert = Certificate::new_unverified(committee, header.clone(), self.votes.clone())?;
+        let (_, pks) = cert.signed_by(committee);
+
+        let certificate_digest: Digest<{ crypto::DIGEST_LENGTH }> = Digest::from(cert.digest());
+        let Err(err) = AggregateSignature::try_from(cert.aggregated_signature())
+            .map_err(|_| DagError::InvalidSignature)?
+            .verify_secure(&to_intent_message(certificate_digest), &pks[..]) else {
+            return Ok(Some(cert));
+        };
+        warn!(
+            "Failed to verify aggregated sig on certificate: {} error: {}",
+            certificate_digest, err
+        );
+        self.metrics.vote_verification_failures.inc();
+        let num_votes = self.votes.len();
+        let mut i = 0;
+        while i < self.votes.len() {
+            let (id, sig) = &self.votes[i];
+            let pk = committee.authority_safe(id).protocol_key();
+            if sig
+                .verify_secure(&to_intent_message(certificate_digest), pk)
+                .is_err()
             {
-                Err(err) => {
-                    warn!(
-                        "Failed to verify aggregated sig on certificate: {} error: {}",
-                        certificate_digest, err
-                    );
-                    let mut i = 0;
-                    while i < self.votes.len() {
-                        let (id, sig) = &self.votes[i];
-                        let pk = committee.authority_safe(id).protocol_key();
-                        if sig
-                            .verify_secure(&to_intent_message(certificate_digest), pk)
-                            .is_err()
-                        {
-                            warn!("Invalid signature on header from authority: {}", id);
-                            self.weight -= committee.stake(pk);
-                            self.votes.remove(i);
-                        } else {
-                            i += 1;
-                        }
-                    }
-                    return Ok(None);
-                }
-                Ok(_) => return Ok(Some(cert)),
+                warn!("Invalid signature on header from authority: {}", id);
+                self.weight -= committee.stake(pk);
+                self.votes.remove(i);
+            } else {
+                i += 1;
             }
         }
-        Ok(None)
+        if self.votes.len() == num_votes {
+            return Ok(None);
+        }
+        // The remaining votes are valid, they may still reach a quorum.
+        self.try_certify(committee, header)
     }
 }
 
@@ -0,0 +1,111 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use config::{AuthorityIdentifier, Committee, WorkerCache, WorkerId};
+use fastcrypto::hash::Hash as _;
+use mysten_metrics::spawn_monitored_task;
+use network::PrimaryToWorkerRpc;
+use std::collections::BTreeMap;
+use tracing::debug;
+use types::{
+    BatchDigest, BatchStatus, Certificate, CertificateAPI, HeaderAPI, Round,
+    WorkerBatchStatusMessage,
+};
+
+#[cfg(test)]
+#[path = "tests/batch_status_tests.rs"]
+pub mod batch_status_tests;
+
+/// Reports to our workers the progress of their batches through consensus, so that they can
+/// inform the clients that submitted the transactions. The reports are best effort: a worker
+/// that misses one only reports a stale status until the next one.
+#[derive(Clone)]
+pub struct BatchStatusNotifier {
+    /// The id of this primary.
+    authority_id: AuthorityIdentifier,
+    /// The committee information.
+    committee: Committee,
+    /// The worker information cache.
+    worker_cache: WorkerCache,
+    /// The network to reach our workers.
+    network: anemo::Network,
+}
+
+impl BatchStatusNotifier {
+    pub fn new(
+        authority_id: AuthorityIdentifier,
+        committee: Committee,
+        worker_cache: WorkerCache,
+        network: anemo::Network,
+    ) -> Self {
+        Self {
+            authority_id,
+            committee,
+            worker_cache,
+            network,
+        }
+    }
+
+    /// Reports that our batches are included in the certificate.
+    pub fn certified(&self, certificate: &Certificate) {
+        self.notify(
+            certificate,
+            BatchStatus::Certified {
+                certificate: certificate.digest(),
+                round: certificate.round(),
+            },
+        );
+    }
+
+    /// Reports that the certificate including our batches is committed.
+    pub fn committed(&self, certificate: &Certificate, commit_round: Round) {
+        self.notify(
+            certificate,
+            BatchStatus::Committed {
+                certificate: certificate.digest(),
+                round: certificate.round(),
+                commit_round,
+            },
+        );
+    }
+
+    fn notify(&self, certificate: &Certificate, status: BatchStatus) {
+        // Only our own batches are tracked by our workers.
+        if certificate.origin() != self.authority_id {
+            return;
+        }
+        let Some(authority) = self.committee.authority(&self.authority_id) else {
+            return;
+        };
+
+        let mut digests_by_worker: BTreeMap<WorkerId, Vec<BatchDigest>> = BTreeMap::new();
+        for (digest, (worker_id, _)) in certificate.header().payload() {
+            digests_by_worker
+                .entry(*worker_id)
+                .or_default()
+                .push(*digest);
+        }
+
+        for (worker_id, digests) in digests_by_worker {
+            let worker_name = match self
+                .worker_cache
+                .worker(authority.protocol_key(), &worker_id)
+            {
+                Ok(worker) => worker.name,
+                Err(e) => {
+                    debug!("Unable to report batch status to worker {worker_id}: {e}");
+                    continue;
+                }
+            };
+            let network = self.network.clone();
+            let message = WorkerBatchStatusMessage {
+                digests,
+                status: status.clone(),
+            };
+            spawn_monitored_task!(async move {
+                if let Err(e) = network.report_batch_status(worker_name, message).await {
+                    debug!("Failed to report batch status to worker {worker_id}: {e}");
+                }
+            });
+        }
+    }
+}
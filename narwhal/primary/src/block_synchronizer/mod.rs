@@ -655,6 +655,9 @@ impl BlockSynchronizer {
                 digests: batch_ids,
                 target: primary_peer_name,
                 is_certified: true,
+                // The payloads of committed certificates are needed by the execution, even once
+                // their rounds are garbage collected.
+                round: None,
             };
             let _ = self.network.unreliable_send(worker_name, &message);
 
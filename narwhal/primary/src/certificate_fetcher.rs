@@ -2,17 +2,19 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::{metrics::PrimaryMetrics, synchronizer::Synchronizer};
-use anemo::{Network, Request};
+use anemo::{Network, PeerId};
 use config::{AuthorityIdentifier, Committee};
 use consensus::consensus::ConsensusRound;
 use crypto::NetworkPublicKey;
 use futures::{stream::FuturesUnordered, StreamExt};
 use itertools::Itertools;
 use mysten_metrics::{monitored_future, monitored_scope, spawn_logged_monitored_task};
-use network::PrimaryToPrimaryRpc;
+use network::{request_budget::RateLimited, PrimaryToPrimaryRpc};
+use parking_lot::Mutex;
 use rand::{rngs::ThreadRng, seq::SliceRandom};
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap},
     sync::Arc,
     time::Duration,
 };
@@ -27,8 +29,9 @@ use tracing::{debug, error, instrument, trace, warn};
 use types::{
     error::{DagError, DagResult},
     metered_channel::Receiver,
-    Certificate, CertificateAPI, ConditionalBroadcastReceiver, FetchCertificatesRequest,
-    FetchCertificatesResponse, HeaderAPI, Round,
+    AuthorityCertificatesSummary, Certificate, CertificateAPI, ConditionalBroadcastReceiver,
+    FetchCertificatesRequest, FetchCertificatesV2Request, FetchCertificatesV2Response, HeaderAPI,
+    Round,
 };
 
 #[cfg(test)]
@@ -87,6 +90,9 @@ struct CertificateFetcherState {
     synchronizer: Arc<Synchronizer>,
     /// The metrics handler
     metrics: Arc<PrimaryMetrics>,
+    /// The certificates the peers reported having in their last responses, used to request the
+    /// peers most likely to have the missing certificates first.
+    peer_summaries: Mutex<HashMap<PeerId, Vec<AuthorityCertificatesSummary>>>,
 }
 
 impl CertificateFetcher {
@@ -107,6 +113,7 @@ impl CertificateFetcher {
             network,
             synchronizer,
             metrics,
+            peer_summaries: Mutex::new(HashMap::new()),
         });
 
         spawn_logged_monitored_task!(
@@ -250,6 +257,7 @@ impl CertificateFetcher {
 
         let state = self.state.clone();
         let committee = self.committee.clone();
+        let targets = self.targets.clone();
 
         debug!(
             "Starting task to fetch missing certificates: max target {}, gc round {:?}",
@@ -262,7 +270,9 @@ impl CertificateFetcher {
                 state.metrics.certificate_fetcher_inflight_fetch.inc();
 
                 let now = Instant::now();
-                match run_fetch_task(state.clone(), committee, gc_round, written_rounds).await {
+                match run_fetch_task(state.clone(), committee, gc_round, written_rounds, targets)
+                    .await
+                {
                     Ok(_) => {
                         debug!(
                             "Finished task to fetch certificates successfully, elapsed = {}s",
@@ -290,15 +300,26 @@ async fn run_fetch_task(
     committee: Committee,
     gc_round: Round,
     written_rounds: BTreeMap<AuthorityIdentifier, BTreeSet<Round>>,
+    targets: BTreeMap<AuthorityIdentifier, Round>,
 ) -> DagResult<()> {
     // Send request to fetch certificates.
-    let request = FetchCertificatesRequest::default()
-        .set_bounds(gc_round, written_rounds)
-        .set_max_items(MAX_CERTIFICATES_TO_FETCH);
-    let Some(response) =
-        fetch_certificates_helper(state.authority_id, &state.network, &committee, request).await else {
-            return Err(DagError::NoCertificateFetched);
-        };
+    let request = FetchCertificatesV2Request::new(
+        FetchCertificatesRequest::default()
+            .set_bounds(gc_round, written_rounds)
+            .set_max_items(MAX_CERTIFICATES_TO_FETCH),
+    )
+    .set_include_summary(true);
+    let Some(response) = fetch_certificates_helper(
+        state.authority_id,
+        &state.network,
+        &committee,
+        request,
+        &targets,
+        &state.peer_summaries,
+    )
+    .await else {
+        return Err(DagError::NoCertificateFetched);
+    };
 
     // Process and store fetched certificates.
     let num_certs_fetched = response.certificates.len();
@@ -312,6 +333,34 @@ async fn run_fetch_task(
     Ok(())
 }
 
+/// Orders the peers in the order they are requested: the ones that reported having the
+/// certificates of the most targets first. The peers without a summary yet are requested after
+/// the ones that have all the targets, and before the ones that miss some.
+pub(crate) fn order_peers(
+    mut peers: Vec<NetworkPublicKey>,
+    targets: &BTreeMap<AuthorityIdentifier, Round>,
+    peer_summaries: &HashMap<PeerId, Vec<AuthorityCertificatesSummary>>,
+) -> Vec<NetworkPublicKey> {
+    // The sort is stable, so the peers with the same score stay shuffled.
+    peers.sort_by_key(|peer| {
+        Reverse(match peer_summaries.get(&PeerId(peer.0.to_bytes())) {
+            Some(summary) => {
+                let covered = targets
+                    .iter()
+                    .filter(|(origin, target)| {
+                        summary
+                            .iter()
+                            .any(|s| s.origin == **origin && s.highest_round >= **target)
+                    })
+                    .count();
+                2 * covered
+            }
+            None => (2 * targets.len()).saturating_sub(1),
+        })
+    });
+    peers
+}
+
 /// Fetches certificates from other primaries concurrently, with ~5 sec interval between each request.
 /// Terminates after the 1st successful response is received.
 #[instrument(level = "debug", skip_all)]
@@ -319,8 +368,10 @@ async fn fetch_certificates_helper(
     name: AuthorityIdentifier,
     network: &anemo::Network,
     committee: &Committee,
-    request: FetchCertificatesRequest,
-) -> Option<FetchCertificatesResponse> {
+    request: FetchCertificatesV2Request,
+    targets: &BTreeMap<AuthorityIdentifier, Round>,
+    peer_summaries: &Mutex<HashMap<PeerId, Vec<AuthorityCertificatesSummary>>>,
+) -> Option<FetchCertificatesV2Response> {
     let _scope = monitored_scope("FetchingCertificatesFromPeers");
     trace!("Start sending fetch certificates requests");
     // TODO: make this a config parameter.
@@ -331,6 +382,7 @@ async fn fetch_certificates_helper(
         .map(|(_, _, network_key)| network_key)
         .collect();
     peers.shuffle(&mut ThreadRng::default());
+    let mut peers = order_peers(peers, targets, &peer_summaries.lock()).into_iter();
     let fetch_timeout = PARALLEL_FETCH_REQUEST_INTERVAL_SECS * peers.len().try_into().unwrap()
         + PARALLEL_FETCH_REQUEST_ADDITIONAL_TIMEOUT;
     let fetch_callback = async move {
@@ -339,33 +391,50 @@ async fn fetch_certificates_helper(
         let mut fut = FuturesUnordered::new();
         // Loop until one peer returns with certificates, or no peer does.
         loop {
-            if let Some(peer) = peers.pop() {
-                let request = Request::new(request.clone())
-                    .with_timeout(PARALLEL_FETCH_REQUEST_INTERVAL_SECS * 2);
+            if let Some(peer) = peers.next() {
+                let request = request.clone();
                 fut.push(monitored_future!(async move {
                     debug!("Sending out fetch request in parallel to {peer}");
-                    let result = network.fetch_certificates(&peer, request).await;
+                    let result = network
+                        .fetch_certificates_v2(
+                            &peer,
+                            request,
+                            PARALLEL_FETCH_REQUEST_INTERVAL_SECS * 2,
+                        )
+                        .await;
                     if let Ok(resp) = &result {
                         debug!(
                             "Fetched {} certificates from peer {peer}",
                             resp.certificates.len()
                         );
                     }
-                    result
+                    (peer, result)
                 }));
             }
             let mut interval = Box::pin(sleep(request_interval));
             tokio::select! {
                 res = fut.next() => match res {
-                    Some(Ok(resp)) => {
+                    Some((peer, Ok(resp))) => {
+                        // The peers which do not serve summaries yet are left unknown.
+                        let peer_id = PeerId(peer.0.to_bytes());
+                        if resp.summary.is_empty() {
+                            peer_summaries.lock().remove(&peer_id);
+                        } else {
+                            peer_summaries.lock().insert(peer_id, resp.summary.clone());
+                        }
                         if resp.certificates.is_empty() {
                             // Issue request to another primary immediately.
                             continue;
                         }
                         return Some(resp);
                     }
-                    Some(Err(e)) => {
-                        debug!("Failed to fetch certificates: {e}");
+                    Some((_, Err(e))) => {
+                        if e.is::<RateLimited>() {
+                            // Not retried until the next fetch, so the peer can recover.
+                            debug!("Fetching certificates is over budget: {e}");
+                        } else {
+                            debug!("Failed to fetch certificates: {e}");
+                        }
                         // Issue request to another primary immediately.
                         continue;
                     }
@@ -395,7 +464,7 @@ async fn fetch_certificates_helper(
 
 #[instrument(level = "debug", skip_all)]
 async fn process_certificates_helper(
-    response: FetchCertificatesResponse,
+    response: FetchCertificatesV2Response,
     synchronizer: &Synchronizer,
     network: &Network,
 ) -> DagResult<()> {
@@ -419,9 +488,7 @@ async fn process_certificates_helper(
             let sync = synchronizer.clone();
             // Use threads dedicated to computation heavy work.
             spawn_blocking(move || {
-                for c in &certs {
-                    sync.sanitize_certificate(c)?;
-                }
+                sync.sanitize_certificates(&certs)?;
                 Ok::<Vec<Certificate>, DagError>(certs)
             })
         })
@@ -0,0 +1,107 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::metrics::PrimaryMetrics;
+use config::{Committee, WorkerCache};
+use mysten_metrics::{monitored_scope, spawn_monitored_task};
+use std::sync::Arc;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::spawn_blocking,
+    time::{sleep, Duration},
+};
+use types::{
+    error::{DagError, DagResult},
+    Certificate,
+};
+
+#[cfg(test)]
+#[path = "tests/certificate_verifier_tests.rs"]
+pub mod certificate_verifier_tests;
+
+/// The maximum number of certificates verified in a batch. The certificates of a round arrive at
+/// about the same time, so a batch is typically filled by the certificates of a round.
+const MAX_BATCH_SIZE: usize = 16;
+
+/// The maximum time to wait for a batch to fill up, before verifying a partial batch.
+const BATCH_TIMEOUT: Duration = Duration::from_millis(5);
+
+type VerifyRequest = (Certificate, oneshot::Sender<DagResult<()>>);
+
+/// Verifies the certificates received one at a time, in batches: the aggregate signatures of a
+/// batch are verified at once, which is cheaper than verifying them one by one. The batches are
+/// verified in parallel on the threads dedicated to blocking work.
+pub(crate) struct CertificateVerifier {
+    tx_verify: mpsc::Sender<VerifyRequest>,
+}
+
+impl CertificateVerifier {
+    pub fn new(
+        committee: Committee,
+        worker_cache: WorkerCache,
+        metrics: Arc<PrimaryMetrics>,
+    ) -> Self {
+        let (tx_verify, rx_verify) = mpsc::channel(MAX_BATCH_SIZE * 16);
+        // The task exits once the verifier is dropped.
+        spawn_monitored_task!(Self::run(rx_verify, committee, worker_cache, metrics));
+        Self { tx_verify }
+    }
+
+    /// Verifies the validity of the certificate, in a batch with the certificates received
+    /// around the same time.
+    pub async fn verify(&self, certificate: Certificate) -> DagResult<()> {
+        let (tx_result, rx_result) = oneshot::channel();
+        self.tx_verify
+            .send((certificate, tx_result))
+            .await
+            .map_err(|_| DagError::ShuttingDown)?;
+        rx_result.await.map_err(|_| DagError::ShuttingDown)?
+    }
+
+    async fn run(
+        mut rx_verify: mpsc::Receiver<VerifyRequest>,
+        committee: Committee,
+        worker_cache: WorkerCache,
+        metrics: Arc<PrimaryMetrics>,
+    ) {
+        while let Some(request) = rx_verify.recv().await {
+            let mut batch = vec![request];
+            let timeout = sleep(BATCH_TIMEOUT);
+            tokio::pin!(timeout);
+            while batch.len() < MAX_BATCH_SIZE {
+                tokio::select! {
+                    Some(request) = rx_verify.recv() => batch.push(request),
+                    () = &mut timeout => break,
+                    else => break,
+                }
+            }
+
+            let committee = committee.clone();
+            let worker_cache = worker_cache.clone();
+            let metrics = metrics.clone();
+            // Not awaited, so that the next batch is gathered and verified meanwhile.
+            spawn_blocking(move || Self::verify_batch(batch, &committee, &worker_cache, &metrics));
+        }
+    }
+
+    fn verify_batch(
+        batch: Vec<VerifyRequest>,
+        committee: &Committee,
+        worker_cache: &WorkerCache,
+        metrics: &PrimaryMetrics,
+    ) {
+        let _scope = monitored_scope("CertificateVerifier::verify_batch");
+        metrics
+            .certificate_verification_batch_size
+            .observe(batch.len() as f64);
+
+        let certificates: Vec<_> = batch.iter().map(|(certificate, _)| certificate).collect();
+        let results = Certificate::verify_batch(&certificates, committee, worker_cache);
+        if results.iter().any(|result| result.is_err()) {
+            metrics.certificate_verification_batch_failures.inc();
+        }
+        for ((_, tx_result), result) in batch.into_iter().zip(results) {
+            // The caller may have stopped waiting.
+            let _ = tx_result.send(result);
+        }
+    }
+}
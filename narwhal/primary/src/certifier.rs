@@ -1,21 +1,26 @@
 // Copyright (c) 2021, Facebook, Inc. and its affiliates
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use crate::{aggregators::VotesAggregator, metrics::PrimaryMetrics, synchronizer::Synchronizer};
+use crate::{
+    aggregators::VotesAggregator, batch_status::BatchStatusNotifier, metrics::PrimaryMetrics,
+    synchronizer::Synchronizer,
+};
 
 use config::{AuthorityIdentifier, Committee};
-use crypto::{NetworkPublicKey, Signature};
-use fastcrypto::signature_service::SignatureService;
+use crypto::signer::SignatureService;
+use crypto::NetworkPublicKey;
+use fastcrypto::hash::Hash;
 use futures::stream::FuturesUnordered;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use mysten_metrics::{monitored_future, spawn_logged_monitored_task};
 use network::anemo_ext::NetworkExt;
 use std::sync::Arc;
 use std::time::Duration;
 use storage::{CertificateStore, HeaderStore};
+use telemetry_subscribers::CORRELATION_TARGET;
 use tokio::{
     sync::oneshot,
-    task::{JoinHandle, JoinSet},
+    task::{spawn_blocking, JoinHandle, JoinSet},
 };
 use tracing::{debug, enabled, error, info, instrument, warn};
 use types::{
@@ -47,7 +52,7 @@ pub struct Certifier {
     /// Handles synchronization with other nodes and our workers.
     synchronizer: Arc<Synchronizer>,
     /// Service to sign headers.
-    signature_service: SignatureService<Signature, { crypto::INTENT_MESSAGE_LENGTH }>,
+    signature_service: SignatureService,
     /// Receiver for shutdown.
     rx_shutdown: ConditionalBroadcastReceiver,
     /// Receives our newly created headers from the `Proposer`.
@@ -64,6 +69,8 @@ pub struct Certifier {
     network: anemo::Network,
     /// Metrics handler
     metrics: Arc<PrimaryMetrics>,
+    /// Reports the certification of our batches to our workers.
+    batch_status_notifier: BatchStatusNotifier,
 }
 
 impl Certifier {
@@ -75,11 +82,12 @@ impl Certifier {
         header_store: HeaderStore,
         certificate_store: CertificateStore,
         synchronizer: Arc<Synchronizer>,
-        signature_service: SignatureService<Signature, { crypto::INTENT_MESSAGE_LENGTH }>,
+        signature_service: SignatureService,
         rx_shutdown: ConditionalBroadcastReceiver,
         rx_headers: Receiver<Header>,
         metrics: Arc<PrimaryMetrics>,
         primary_network: anemo::Network,
+        batch_status_notifier: BatchStatusNotifier,
     ) -> JoinHandle<()> {
         spawn_logged_monitored_task!(
             async move {
@@ -96,6 +104,7 @@ impl Certifier {
                     propose_header_tasks: JoinSet::new(),
                     network: primary_network,
                     metrics,
+                    batch_status_notifier,
                 }
                 .run_inner()
                 .await
@@ -250,7 +259,7 @@ impl Certifier {
         committee: Committee,
         header_store: HeaderStore,
         certificate_store: CertificateStore,
-        signature_service: SignatureService<Signature, { crypto::INTENT_MESSAGE_LENGTH }>,
+        signature_service: SignatureService,
         metrics: Arc<PrimaryMetrics>,
         network: anemo::Network,
         header: Header,
@@ -275,7 +284,7 @@ impl Certifier {
         // Reset the votes aggregator and sign our own header.
         let mut votes_aggregator = VotesAggregator::new(metrics.clone());
         let vote = Vote::new(&header, &authority_id, &signature_service).await;
-        let mut certificate = votes_aggregator.append(vote, &committee, &header)?;
+        let mut quorum = votes_aggregator.append(vote, &committee)?;
 
         // Trigger vote requests.
         let peers = committee
@@ -295,30 +304,65 @@ impl Certifier {
                 )
             })
             .collect();
-        loop {
-            if certificate.is_some() {
-                break;
+        let mut exhausted = false;
+        let certificate = loop {
+            if quorum {
+                // Verifying the votes is CPU intensive, so it runs on the threads dedicated to
+                // blocking work. The pending vote requests are dropped once a certificate forms.
+                let committee = committee.clone();
+                let header = header.clone();
+                let (aggregator, result) = spawn_blocking(move || {
+                    let result = votes_aggregator.try_certify(&committee, &header);
+                    (votes_aggregator, result)
+                })
+                .await
+                .map_err(|_| DagError::ShuttingDown)?;
+                votes_aggregator = aggregator;
+                if let Some(certificate) = result? {
+                    break Some(certificate);
+                }
+                quorum = false;
             }
+            if exhausted {
+                break None;
+            }
+
+            // Wait for a vote, then take the votes received meanwhile to aggregate them at once.
+            let mut results = Vec::new();
             tokio::select! {
-                result = &mut requests.next() => {
-                    match result {
-                        Some(Ok(vote)) => {
-                            certificate = votes_aggregator.append(
-                                vote,
-                                &committee,
-                                &header,
-                            )?;
-                        },
-                        Some(Err(e)) => debug!("failed to get vote for header {header:?}: {e:?}"),
-                        None => break,
-                    }
+                result = &mut requests.next() => match result {
+                    Some(result) => results.push(result),
+                    None => exhausted = true,
                 },
                 _ = &mut cancel => {
                     debug!("canceling Header proposal {header} for round {}", header.round());
                     return Err(DagError::Canceled)
                 },
             }
-        }
+            while !exhausted {
+                match requests.next().now_or_never() {
+                    Some(Some(result)) => results.push(result),
+                    Some(None) => exhausted = true,
+                    None => break,
+                }
+            }
+
+            let mut batch_size = 0;
+            for result in results {
+                match result {
+                    Ok(vote) => {
+                        quorum = votes_aggregator.append(vote, &committee)?;
+                        batch_size += 1;
+                    }
+                    Err(e) => debug!("failed to get vote for header {header:?}: {e:?}"),
+                }
+            }
+            if batch_size > 0 {
+                metrics
+                    .vote_aggregation_batch_size
+                    .observe(batch_size as f64);
+            }
+        };
 
         let certificate = certificate.ok_or_else(|| {
             // Log detailed header info if we failed to form a certificate.
@@ -343,6 +387,15 @@ impl Certifier {
             DagError::CouldNotFormCertificate(header.digest())
         })?;
         debug!("Assembled {certificate:?}");
+        for batch in certificate.header().payload().keys() {
+            debug!(
+                target: CORRELATION_TARGET,
+                stage = "certified",
+                batch = %batch,
+                certificate = %certificate.digest(),
+                round = certificate.round(),
+            );
+        }
 
         Ok(certificate)
     }
@@ -407,7 +460,14 @@ impl Certifier {
                 Some(result) = self.propose_header_tasks.join_next() => {
                     match result {
                         Ok(Ok(certificate)) => {
-                            self.synchronizer.accept_own_certificate(certificate, &self.network).await
+                            let result = self
+                                .synchronizer
+                                .accept_own_certificate(certificate.clone(), &self.network)
+                                .await;
+                            if result.is_ok() {
+                                self.batch_status_notifier.certified(&certificate);
+                            }
+                            result
                         },
                         Ok(Err(e)) => Err(e),
                         Err(_) => Err(DagError::ShuttingDown),
@@ -0,0 +1,371 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::synchronizer::Synchronizer;
+use axum::{
+    extract::{Extension, Path, Query},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use config::{AuthorityIdentifier, Committee, Stake};
+use consensus::consensus::ConsensusRound;
+use fastcrypto::hash::Hash as _;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write as _, sync::Arc};
+use storage::CertificateStore;
+use tokio::sync::watch;
+use types::{Certificate, CertificateAPI, CommittedSubDagShell, ConsensusStore, HeaderAPI, Round};
+
+/// The maximum number of rounds exported by the `/dag/export` admin endpoint at once.
+const MAX_EXPORTED_ROUNDS: Round = 500;
+
+#[cfg(test)]
+#[path = "tests/dag_admin_tests.rs"]
+pub mod dag_admin_tests;
+
+/// The state of the DAG as reported by the `/dag` admin endpoint.
+#[derive(Debug, Serialize)]
+pub struct DagStatus {
+    pub gc_round: Round,
+    pub committed_round: Round,
+    pub highest_processed_round: Round,
+    pub highest_received_round: Round,
+    pub last_committed_leader: Option<CommittedLeader>,
+    /// The rounds of the certificates stored above the gc round.
+    pub rounds: Vec<RoundStatus>,
+    /// The certificates waiting for some of their parents before being accepted.
+    pub suspended_certificates: Vec<SuspendedCertificateStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommittedLeader {
+    pub sub_dag_index: u64,
+    pub round: Round,
+    pub digest: String,
+    /// None if the leader certificate has been garbage collected.
+    pub origin: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct RoundStatus {
+    pub round: Round,
+    pub num_certificates: usize,
+    pub stake: Stake,
+    /// Whether the certificates of the round gather a quorum of stake.
+    pub has_quorum: bool,
+    pub origins: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuspendedCertificateStatus {
+    pub digest: String,
+    pub round: Round,
+    pub origin: String,
+    pub missing_parents: Vec<String>,
+}
+
+/// A certificate as reported by the `/dag/rounds/:round` admin endpoint.
+#[derive(Debug, Serialize)]
+pub struct CertificateStatus {
+    pub digest: String,
+    pub origin: String,
+    pub parents: Vec<String>,
+    pub num_batches: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportParams {
+    /// The first round exported, the round above the gc round if not set.
+    from_round: Option<Round>,
+    /// The last round exported, the highest round stored if not set.
+    to_round: Option<Round>,
+    /// Either `json` (the default) or `dot`, for graphviz.
+    format: Option<String>,
+}
+
+/// The certificates of a range of rounds of the DAG, as exported by the `/dag/export` admin
+/// endpoint to be visualized.
+#[derive(Debug, Serialize)]
+pub struct DagExport {
+    pub from_round: Round,
+    pub to_round: Round,
+    pub nodes: Vec<DagNode>,
+}
+
+/// A certificate of the DAG, with its header, parent links and commit.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DagNode {
+    pub digest: String,
+    pub round: Round,
+    pub origin: String,
+    pub parents: Vec<String>,
+    pub num_batches: usize,
+    /// The index of the sub dag committing the certificate, None if not committed yet.
+    pub sub_dag_index: Option<u64>,
+    /// Whether the certificate is the leader committing its sub dag.
+    pub is_leader: bool,
+}
+
+impl DagExport {
+    /// Exports the certificates, marked with the sub dags committing them.
+    pub fn new(
+        from_round: Round,
+        to_round: Round,
+        certificates: &[Certificate],
+        sub_dags: &[CommittedSubDagShell],
+    ) -> Self {
+        let mut commits = BTreeMap::new();
+        for sub_dag in sub_dags {
+            for digest in &sub_dag.certificates {
+                commits.insert(*digest, (sub_dag.sub_dag_index, *digest == sub_dag.leader));
+            }
+        }
+        let nodes = certificates
+            .iter()
+            .map(|certificate| {
+                let digest = certificate.digest();
+                let commit = commits.get(&digest);
+                DagNode {
+                    digest: digest.to_string(),
+                    round: certificate.round(),
+                    origin: certificate.origin().to_string(),
+                    parents: certificate
+                        .header()
+                        .parents()
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect(),
+                    num_batches: certificate.header().payload().len(),
+                    sub_dag_index: commit.map(|(index, _)| *index),
+                    is_leader: commit.map_or(false, |(_, is_leader)| *is_leader),
+                }
+            })
+            .collect();
+        Self {
+            from_round,
+            to_round,
+            nodes,
+        }
+    }
+
+    /// Renders the DAG in the graphviz format, with the rounds bottom up. The committed leaders
+    /// are filled, the other committed certificates are bold, and the edges go from a
+    /// certificate to its parents.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph dag {{").unwrap();
+        writeln!(dot, "  rankdir=BT;").unwrap();
+        writeln!(dot, "  node [shape=box];").unwrap();
+        let mut rounds: BTreeMap<Round, Vec<&DagNode>> = BTreeMap::new();
+        for node in &self.nodes {
+            rounds.entry(node.round).or_default().push(node);
+        }
+        for (round, nodes) in rounds {
+            writeln!(dot, "  {{ rank=same;").unwrap();
+            for node in nodes {
+                let style = match (node.is_leader, node.sub_dag_index) {
+                    (true, _) => ", style=filled",
+                    (false, Some(_)) => ", style=bold",
+                    (false, None) => "",
+                };
+                let commit = node
+                    .sub_dag_index
+                    .map(|index| format!("\\ncommit {index}"))
+                    .unwrap_or_default();
+                writeln!(
+                    dot,
+                    "    \"{}\" [label=\"{round} / {}{commit}\"{style}];",
+                    node.digest, node.origin
+                )
+                .unwrap();
+            }
+            writeln!(dot, "  }}").unwrap();
+        }
+        for node in &self.nodes {
+            for parent in &node.parents {
+                writeln!(dot, "  \"{}\" -> \"{parent}\";", node.digest).unwrap();
+            }
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+/// Serves the DAG introspection endpoints of the primary admin server.
+#[derive(Clone)]
+struct DagAdmin {
+    committee: Committee,
+    certificate_store: CertificateStore,
+    consensus_store: Arc<ConsensusStore>,
+    synchronizer: Arc<Synchronizer>,
+    rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+}
+
+/// Returns the routes of the DAG introspection endpoints, to be served by the admin server.
+pub fn routes(
+    committee: Committee,
+    certificate_store: CertificateStore,
+    consensus_store: Arc<ConsensusStore>,
+    synchronizer: Arc<Synchronizer>,
+    rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+) -> Router {
+    Router::new()
+        .route("/dag", get(get_dag))
+        .route("/dag/rounds/:round", get(get_round))
+        .route("/dag/export", get(export_dag))
+        .layer(Extension(DagAdmin {
+            committee,
+            certificate_store,
+            consensus_store,
+            synchronizer,
+            rx_consensus_round_updates,
+        }))
+}
+
+async fn get_dag(
+    Extension(admin): Extension<DagAdmin>,
+) -> Result<Json<DagStatus>, (StatusCode, String)> {
+    let ConsensusRound {
+        committed_round,
+        gc_round,
+    } = *admin.rx_consensus_round_updates.borrow();
+
+    let last_committed_leader = match admin.consensus_store.get_latest_sub_dag() {
+        Some(sub_dag) => {
+            let leader = admin
+                .certificate_store
+                .read(sub_dag.leader)
+                .map_err(internal_error)?;
+            Some(CommittedLeader {
+                sub_dag_index: sub_dag.sub_dag_index,
+                round: sub_dag.leader_round,
+                digest: sub_dag.leader.to_string(),
+                origin: leader.map(|certificate| certificate.origin().to_string()),
+            })
+        }
+        None => None,
+    };
+
+    let origins = admin
+        .certificate_store
+        .origins_after_round(gc_round + 1)
+        .map_err(internal_error)?;
+
+    let suspended_certificates = admin
+        .synchronizer
+        .suspended_certificates()
+        .await
+        .into_iter()
+        .map(|info| SuspendedCertificateStatus {
+            digest: info.digest.to_string(),
+            round: info.round,
+            origin: info.origin.to_string(),
+            missing_parents: info
+                .missing_parents
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        })
+        .collect();
+
+    Ok(Json(DagStatus {
+        gc_round,
+        committed_round,
+        highest_processed_round: admin.synchronizer.highest_processed_round(),
+        highest_received_round: admin.synchronizer.highest_received_round(),
+        last_committed_leader,
+        rounds: round_statuses(&admin.committee, origins),
+        suspended_certificates,
+    }))
+}
+
+async fn get_round(
+    Extension(admin): Extension<DagAdmin>,
+    Path(round): Path<Round>,
+) -> Result<Json<Vec<CertificateStatus>>, (StatusCode, String)> {
+    let certificates = admin
+        .certificate_store
+        .between_rounds(round, round)
+        .map_err(internal_error)?;
+
+    Ok(Json(
+        certificates
+            .iter()
+            .map(|certificate| CertificateStatus {
+                digest: certificate.digest().to_string(),
+                origin: certificate.origin().to_string(),
+                parents: certificate
+                    .header()
+                    .parents()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                num_batches: certificate.header().payload().len(),
+            })
+            .collect(),
+    ))
+}
+
+async fn export_dag(
+    Extension(admin): Extension<DagAdmin>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, (StatusCode, String)> {
+    let gc_round = admin.rx_consensus_round_updates.borrow().gc_round;
+    let to_round = params
+        .to_round
+        .unwrap_or_else(|| admin.certificate_store.highest_round_number());
+    let from_round = params
+        .from_round
+        .unwrap_or(gc_round + 1)
+        .max(to_round.saturating_sub(MAX_EXPORTED_ROUNDS - 1));
+    let certificates = admin
+        .certificate_store
+        .between_rounds(from_round, to_round)
+        .map_err(internal_error)?;
+    let sub_dags = admin
+        .consensus_store
+        .read_committed_sub_dags_since_round(from_round);
+    let export = DagExport::new(from_round, to_round, &certificates, &sub_dags);
+
+    match params.format.as_deref() {
+        None | Some("json") => Ok(Json(export).into_response()),
+        Some("dot") => Ok((
+            [(header::CONTENT_TYPE, "text/vnd.graphviz")],
+            export.to_dot(),
+        )
+            .into_response()),
+        Some(format) => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown format {format}, expected json or dot"),
+        )),
+    }
+}
+
+/// Summarizes the stored certificates of each round, as returned by
+/// `CertificateStore::origins_after_round`.
+fn round_statuses(
+    committee: &Committee,
+    origins: BTreeMap<Round, Vec<AuthorityIdentifier>>,
+) -> Vec<RoundStatus> {
+    origins
+        .into_iter()
+        .map(|(round, origins)| {
+            let stake: Stake = origins
+                .iter()
+                .map(|origin| committee.stake_by_id(*origin))
+                .sum();
+            RoundStatus {
+                round,
+                num_certificates: origins.len(),
+                stake,
+                has_quorum: stake >= committee.quorum_threshold(),
+                origins: origins.iter().map(ToString::to_string).collect(),
+            }
+        })
+        .collect()
+}
+
+fn internal_error(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
@@ -0,0 +1,339 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use anemo::PeerId;
+use anemo_tower::set_header::SetRequestHeaderLayer;
+use config::{AuthorityIdentifier, Committee, DagSnapshotParameters, Stake, WorkerCache};
+use crypto::signer::SignatureService;
+use crypto::{to_intent_message, NarwhalAuthoritySignature, NetworkKeyPair};
+use fastcrypto::{
+    hash::{Digest, Hash as _},
+    traits::KeyPair as _,
+};
+use futures::future::join_all;
+use network::{epoch_filter::EPOCH_HEADER_KEY, PrimaryToPrimaryRpc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    iter,
+};
+use storage::CertificateStore;
+use tokio::time::timeout;
+use tower::ServiceBuilder;
+use tracing::{info, warn};
+use types::{
+    ensure,
+    error::{DagError, DagResult},
+    CertificateAPI, ConsensusStore, DagSnapshotManifest, GetDagSnapshotRequest,
+    GetDagSnapshotResponse, Round,
+};
+
+#[cfg(test)]
+#[path = "tests/dag_snapshot_tests.rs"]
+pub mod dag_snapshot_tests;
+
+// The number of times the snapshots are fetched from the peers, until enough of them agree.
+const BOOTSTRAP_ATTEMPTS: usize = 3;
+
+/// Builds the snapshot of our DAG signed by us, with at most `max_items` certificates.
+pub(crate) async fn make_snapshot(
+    authority_id: AuthorityIdentifier,
+    committee: &Committee,
+    certificate_store: &CertificateStore,
+    consensus_store: &ConsensusStore,
+    signature_service: &SignatureService,
+    gc_depth: Round,
+    max_items: usize,
+) -> DagResult<GetDagSnapshotResponse> {
+    let last_committed: BTreeMap<_, _> =
+        consensus_store.read_last_committed().into_iter().collect();
+    let last_committed_round = last_committed.values().max().copied().unwrap_or_default();
+    let gc_round = last_committed_round.saturating_sub(gc_depth);
+    let latest_sub_dag = consensus_store.get_latest_sub_dag();
+    let latest_final_sub_dag = consensus_store.read_latest_commit_with_final_reputation_scores();
+
+    let mut certificates = certificate_store.after_round(gc_round + 1)?;
+    // The certificates of the latest sub dag are needed to restore the consensus output, even
+    // when below the garbage collection round.
+    if let Some(sub_dag) = &latest_sub_dag {
+        let known: HashSet<_> = certificates.iter().map(|c| c.digest()).collect();
+        let missing: Vec<_> = sub_dag
+            .certificates
+            .iter()
+            .chain(iter::once(&sub_dag.leader))
+            .filter(|digest| !known.contains(digest))
+            .copied()
+            .collect();
+        certificates.extend(certificate_store.read_all(missing)?.into_iter().flatten());
+    }
+    // The leader and the certificates of the latest sub dag come first, as consensus requires
+    // them to resume, followed by the others by round.
+    let (leader, sub_dag): (Option<_>, HashSet<_>) = match &latest_sub_dag {
+        Some(sub_dag) => (
+            Some(sub_dag.leader),
+            sub_dag.certificates.iter().copied().collect(),
+        ),
+        None => (None, HashSet::new()),
+    };
+    certificates.sort_by_cached_key(|c| {
+        let digest = c.digest();
+        (
+            Some(digest) != leader,
+            !sub_dag.contains(&digest),
+            c.round(),
+        )
+    });
+    certificates.truncate(max_items);
+
+    let manifest = DagSnapshotManifest {
+        epoch: committee.epoch(),
+        author: authority_id,
+        last_committed,
+        gc_round,
+        latest_sub_dag,
+        latest_final_sub_dag,
+        certificates: certificates.iter().map(|c| c.digest()).collect(),
+    };
+    let signature = signature_service
+        .request_signature(manifest.digest().into())
+        .await;
+    Ok(GetDagSnapshotResponse {
+        manifest,
+        signature,
+        certificates,
+    })
+}
+
+/// Verifies that the snapshot is signed by an authority of the committee, and that its
+/// certificates are the valid ones listed by its manifest. The commit rounds of the manifest
+/// cannot be verified, so the snapshot is trusted as much as its author: see `select_snapshot`.
+pub fn verify_snapshot(
+    snapshot: &GetDagSnapshotResponse,
+    committee: &Committee,
+    worker_cache: &WorkerCache,
+) -> DagResult<()> {
+    let manifest = &snapshot.manifest;
+    ensure!(
+        manifest.epoch == committee.epoch(),
+        DagError::InvalidEpoch {
+            expected: committee.epoch(),
+            received: manifest.epoch
+        }
+    );
+    let author = committee
+        .authority(&manifest.author)
+        .ok_or_else(|| DagError::UnknownAuthority(manifest.author.to_string()))?;
+    let digest: Digest<{ crypto::DIGEST_LENGTH }> = Digest::from(manifest.digest());
+    snapshot
+        .signature
+        .verify_secure(&to_intent_message(digest), author.protocol_key())
+        .map_err(|_| DagError::InvalidSignature)?;
+
+    if let Some(unknown) = manifest
+        .last_committed
+        .keys()
+        .find(|id| committee.authority(id).is_none())
+    {
+        return Err(DagError::UnknownAuthority(unknown.to_string()));
+    }
+    ensure!(
+        manifest.gc_round <= manifest.last_committed_round(),
+        DagError::InvalidDagSnapshot(format!(
+            "GC round {} is above the last committed round {}",
+            manifest.gc_round,
+            manifest.last_committed_round()
+        ))
+    );
+
+    let digests: Vec<_> = snapshot.certificates.iter().map(|c| c.digest()).collect();
+    ensure!(
+        digests == manifest.certificates,
+        DagError::InvalidDagSnapshot("The certificates do not match the manifest".to_string())
+    );
+    if let Some(sub_dag) = &manifest.latest_sub_dag {
+        ensure!(
+            digests.contains(&sub_dag.leader),
+            DagError::InvalidDagSnapshot(format!(
+                "The leader {} of the latest sub dag is missing",
+                sub_dag.leader
+            ))
+        );
+    }
+    if let Some(final_sub_dag) = &manifest.latest_final_sub_dag {
+        ensure!(
+            final_sub_dag.reputation_score.final_of_schedule,
+            DagError::InvalidDagSnapshot(format!(
+                "The scores of the sub dag {} are not final",
+                final_sub_dag.sub_dag_index
+            ))
+        );
+        ensure!(
+            manifest
+                .latest_sub_dag
+                .as_ref()
+                .map(|sub_dag| sub_dag.sub_dag_index)
+                >= Some(final_sub_dag.sub_dag_index),
+            DagError::InvalidDagSnapshot(format!(
+                "The sub dag {} with final scores is after the latest sub dag",
+                final_sub_dag.sub_dag_index
+            ))
+        );
+    }
+    for certificate in &snapshot.certificates {
+        certificate.verify(committee, worker_cache)?;
+    }
+    Ok(())
+}
+
+/// Picks a snapshot among the verified ones, whose committed state is the same in the snapshots
+/// of authorities with at least the validity threshold of stake, so that at least one honest
+/// authority vouches for it.
+pub fn select_snapshot(
+    snapshots: Vec<GetDagSnapshotResponse>,
+    committee: &Committee,
+) -> Option<GetDagSnapshotResponse> {
+    let mut candidates: Vec<(GetDagSnapshotResponse, HashSet<AuthorityIdentifier>, Stake)> =
+        Vec::new();
+    for snapshot in snapshots {
+        let author = snapshot.manifest.author;
+        let stake = committee.stake_by_id(author);
+        let candidate = candidates.iter_mut().find(|(candidate, _, _)| {
+            candidate.manifest.last_committed == snapshot.manifest.last_committed
+                && candidate.manifest.latest_sub_dag == snapshot.manifest.latest_sub_dag
+                && candidate.manifest.latest_final_sub_dag == snapshot.manifest.latest_final_sub_dag
+        });
+        match candidate {
+            Some((_, authors, total)) => {
+                if authors.insert(author) {
+                    *total += stake;
+                }
+            }
+            None => candidates.push((snapshot, HashSet::from([author]), stake)),
+        }
+    }
+    candidates
+        .into_iter()
+        .filter(|(_, _, stake)| *stake >= committee.validity_threshold())
+        .max_by_key(|(snapshot, _, _)| snapshot.manifest.last_committed_round())
+        .map(|(snapshot, _, _)| snapshot)
+}
+
+/// Writes the verified snapshot to our stores, from which consensus and the primary resume.
+pub fn install_snapshot(
+    snapshot: GetDagSnapshotResponse,
+    certificate_store: &CertificateStore,
+    consensus_store: &ConsensusStore,
+) -> DagResult<()> {
+    certificate_store.write_all(snapshot.certificates)?;
+    consensus_store.write_snapshot_state(
+        &snapshot.manifest.last_committed,
+        snapshot.manifest.latest_sub_dag.as_ref(),
+        snapshot.manifest.latest_final_sub_dag.as_ref(),
+    )?;
+    Ok(())
+}
+
+/// Bootstraps the stores of a primary joining with empty stores from the DAG snapshot of one
+/// of its peers, whose committed state is agreed upon by enough of them. Returns whether a
+/// snapshot was installed: a primary that cannot get one replays the DAG from genesis instead.
+///
+/// Runs before the consensus and the primary are started, by binding a temporary network.
+pub async fn bootstrap_from_snapshot(
+    authority_id: AuthorityIdentifier,
+    network_keypair: &NetworkKeyPair,
+    committee: &Committee,
+    worker_cache: &WorkerCache,
+    certificate_store: &CertificateStore,
+    consensus_store: &ConsensusStore,
+    parameters: &DagSnapshotParameters,
+) -> bool {
+    if !certificate_store.is_empty() || !consensus_store.read_last_committed().is_empty() {
+        return false;
+    }
+
+    let outbound_layer = ServiceBuilder::new()
+        .layer(SetRequestHeaderLayer::overriding(
+            EPOCH_HEADER_KEY.parse().unwrap(),
+            committee.epoch().to_string(),
+        ))
+        .into_inner();
+    let network = match anemo::Network::bind("0.0.0.0:0")
+        .server_name("narwhal")
+        .private_key(network_keypair.copy().private().0.to_bytes())
+        .outbound_request_layer(outbound_layer)
+        .start(anemo::Router::new())
+    {
+        Ok(network) => network,
+        Err(e) => {
+            warn!("Failed to start the network to fetch a DAG snapshot: {e}");
+            return false;
+        }
+    };
+
+    let peers = committee.others_primaries_by_id(authority_id);
+    for attempt in 1..=BOOTSTRAP_ATTEMPTS {
+        // The snapshots are fetched from all the peers at once, so that the honest ones are
+        // likely to serve the same committed state.
+        let snapshots = join_all(peers.iter().map(|(peer_id, address, network_key)| {
+            let network = &network;
+            let request = GetDagSnapshotRequest {
+                max_items: parameters.max_certificates,
+            };
+            async move {
+                let fetch = async {
+                    network
+                        .connect_with_peer_id(
+                            address.to_anemo_address().map_err(|_| {
+                                DagError::NetworkError(format!("Invalid address {address}"))
+                            })?,
+                            PeerId(network_key.0.to_bytes()),
+                        )
+                        .await
+                        .map_err(|e| DagError::NetworkError(e.to_string()))?;
+                    network
+                        .get_dag_snapshot(network_key, request)
+                        .await
+                        .map_err(|e| DagError::NetworkError(e.to_string()))
+                };
+                let snapshot = match timeout(parameters.request_timeout, fetch).await {
+                    Ok(Ok(snapshot)) => snapshot,
+                    Ok(Err(e)) => {
+                        warn!("Failed to fetch a DAG snapshot from {peer_id}: {e}");
+                        return None;
+                    }
+                    Err(_) => {
+                        warn!("Timed out fetching a DAG snapshot from {peer_id}");
+                        return None;
+                    }
+                };
+                if let Err(e) = verify_snapshot(&snapshot, committee, worker_cache) {
+                    warn!("Rejected the DAG snapshot of {peer_id}: {e}");
+                    return None;
+                }
+                Some(snapshot)
+            }
+        }))
+        .await;
+
+        let Some(snapshot) = select_snapshot(snapshots.into_iter().flatten().collect(), committee)
+        else {
+            warn!(
+                "No committed state agreed upon by enough peers in the DAG snapshots \
+                (attempt {attempt} of {BOOTSTRAP_ATTEMPTS})"
+            );
+            continue;
+        };
+        let author = snapshot.manifest.author;
+        let last_committed_round = snapshot.manifest.last_committed_round();
+        let num_certificates = snapshot.certificates.len();
+        if let Err(e) = install_snapshot(snapshot, certificate_store, consensus_store) {
+            // The stores are left in an unknown state, which the primary cannot start from.
+            panic!("Failed to install the DAG snapshot of {author}: {e}");
+        }
+        info!(
+            "Bootstrapped from the DAG snapshot of {author}: {num_certificates} certificates, \
+            last committed round {last_committed_round}"
+        );
+        return true;
+    }
+    warn!("No DAG snapshot could be fetched, replaying the DAG from genesis");
+    false
+}
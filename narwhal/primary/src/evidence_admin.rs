@@ -0,0 +1,92 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use axum::{
+    extract::{Extension, Query},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use config::AuthorityIdentifier;
+use fastcrypto::{
+    encoding::{Encoding, Hex},
+    hash::Hash as _,
+};
+use serde::{Deserialize, Serialize};
+use storage::EvidenceStore;
+use types::{Evidence, Misbehavior, Round, TimestampMs};
+
+#[derive(Debug, Deserialize)]
+struct EvidenceParams {
+    /// Only report the evidences of this authority, all of them if not set.
+    offender: Option<u16>,
+}
+
+/// An evidence as reported by the `/evidence` admin endpoint.
+#[derive(Debug, Serialize)]
+pub struct EvidenceStatus {
+    pub digest: String,
+    pub kind: &'static str,
+    pub offender: String,
+    pub round: Round,
+    pub recorded_at: TimestampMs,
+    /// The digests of the offending headers or certificates.
+    pub messages: Vec<String>,
+    /// Why the certificate failed verification, for invalid certificates.
+    pub reason: Option<String>,
+    /// The BCS encoding of the whole evidence, in hex, to be submitted to third parties.
+    pub bcs: String,
+}
+
+impl From<&Evidence> for EvidenceStatus {
+    fn from(evidence: &Evidence) -> Self {
+        let (messages, reason) = match &evidence.misbehavior {
+            Misbehavior::EquivocatingHeaders { first, second } => (
+                vec![first.digest().to_string(), second.digest().to_string()],
+                None,
+            ),
+            Misbehavior::EquivocatingCertificates { first, second } => (
+                vec![first.digest().to_string(), second.digest().to_string()],
+                None,
+            ),
+            Misbehavior::InvalidCertificate {
+                certificate,
+                reason,
+            } => (vec![certificate.to_string()], Some(reason.clone())),
+        };
+        Self {
+            digest: Hex::encode(evidence.digest()),
+            kind: evidence.misbehavior.kind(),
+            offender: evidence.offender.to_string(),
+            round: evidence.round,
+            recorded_at: evidence.recorded_at,
+            messages,
+            reason,
+            bcs: Hex::encode(
+                bcs::to_bytes(evidence).expect("Serialization of the evidence cannot fail"),
+            ),
+        }
+    }
+}
+
+/// Returns the routes of the evidence endpoint, to be served by the admin server.
+pub fn routes(evidence_store: EvidenceStore) -> Router {
+    Router::new()
+        .route("/evidence", get(get_evidence))
+        .layer(Extension(evidence_store))
+}
+
+async fn get_evidence(
+    Extension(evidence_store): Extension<EvidenceStore>,
+    Query(params): Query<EvidenceParams>,
+) -> Result<Json<Vec<EvidenceStatus>>, (StatusCode, String)> {
+    let evidences = match params.offender {
+        Some(offender) => evidence_store
+            .read_by_offender(AuthorityIdentifier(offender))
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        None => evidence_store.read_all(),
+    };
+    // Sort by detection time, the store is ordered by offender.
+    let mut statuses: Vec<EvidenceStatus> = evidences.iter().map(EvidenceStatus::from).collect();
+    statuses.sort_by_key(|status| status.recorded_at);
+    Ok(Json(statuses))
+}
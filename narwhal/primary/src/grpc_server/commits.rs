@@ -0,0 +1,179 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use consensus::consensus::ConsensusRound;
+use futures::Stream;
+use std::{collections::VecDeque, pin::Pin, sync::Arc};
+use storage::{CertificateStore, CertificateStoreCache};
+use store::{rocks::DBMap, Map};
+use tokio::sync::watch;
+use tonic::{Request, Response, Status};
+use types::{
+    Batch, BatchDigest, CertificateAPI, Commits, CommittedBatchProto, CommittedCertificateProto,
+    CommittedSubDagProto, CommittedSubDagShell, ConsensusStore, HeaderAPI, SubscribeCommitsRequest,
+};
+
+#[cfg(test)]
+#[path = "tests/commits_tests.rs"]
+pub mod commits_tests;
+
+/// The maximum number of sub dags read from storage at once.
+const MAX_SUB_DAGS_PER_READ: usize = 100;
+
+pub struct NarwhalCommits {
+    consensus_store: Arc<ConsensusStore>,
+    certificate_store: CertificateStore<CertificateStoreCache>,
+    batch_store: DBMap<BatchDigest, Batch>,
+    /// Gets notified when consensus commits.
+    rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+}
+
+impl NarwhalCommits {
+    pub fn new(
+        consensus_store: Arc<ConsensusStore>,
+        certificate_store: CertificateStore<CertificateStoreCache>,
+        batch_store: DBMap<BatchDigest, Batch>,
+        rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+    ) -> Self {
+        Self {
+            consensus_store,
+            certificate_store,
+            batch_store,
+            rx_consensus_round_updates,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Commits for NarwhalCommits {
+    type SubscribeCommitsStream =
+        Pin<Box<dyn Stream<Item = Result<CommittedSubDagProto, Status>> + Send>>;
+
+    /// Streams the committed sub dags from the requested commit index onwards. The sub dags
+    /// already committed are read from storage, then the stream waits for new commits.
+    async fn subscribe_commits(
+        &self,
+        request: Request<SubscribeCommitsRequest>,
+    ) -> Result<Response<Self::SubscribeCommitsStream>, Status> {
+        let subscription = CommitSubscription {
+            next_commit_index: request.into_inner().from_commit_index,
+            pending: VecDeque::new(),
+            consensus_store: self.consensus_store.clone(),
+            certificate_store: self.certificate_store.clone(),
+            batch_store: self.batch_store.clone(),
+            rx_consensus_round_updates: self.rx_consensus_round_updates.clone(),
+            failed: false,
+        };
+
+        let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+            let item = subscription.next().await?;
+            Some((item, subscription))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// The state of a single commits subscription.
+struct CommitSubscription {
+    // The index of the next sub dag to read from storage.
+    next_commit_index: u64,
+    // The sub dags read from storage that are not sent yet.
+    pending: VecDeque<CommittedSubDagShell>,
+    consensus_store: Arc<ConsensusStore>,
+    certificate_store: CertificateStore<CertificateStoreCache>,
+    batch_store: DBMap<BatchDigest, Batch>,
+    rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+    // Whether an error has been returned, after which the stream ends.
+    failed: bool,
+}
+
+impl CommitSubscription {
+    /// Returns the next committed sub dag, waiting for it to be committed if needed. Returns
+    /// None once the stream has ended.
+    async fn next(&mut self) -> Option<Result<CommittedSubDagProto, Status>> {
+        if self.failed {
+            return None;
+        }
+        loop {
+            if let Some(sub_dag) = self.pending.pop_front() {
+                let result = self.resolve(sub_dag);
+                self.failed = result.is_err();
+                return Some(result);
+            }
+
+            let sub_dags = match self
+                .consensus_store
+                .read_committed_sub_dags_from_with_limit(
+                    &self.next_commit_index,
+                    MAX_SUB_DAGS_PER_READ,
+                ) {
+                Ok(sub_dags) => sub_dags,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(Status::internal(format!(
+                        "Couldn't read committed sub dags: {e}"
+                    ))));
+                }
+            };
+            if let Some(last) = sub_dags.last() {
+                self.next_commit_index = last.sub_dag_index + 1;
+                self.pending.extend(sub_dags);
+                continue;
+            }
+
+            // Nothing left to read, wait for consensus to commit. The stream ends when
+            // consensus shuts down.
+            self.rx_consensus_round_updates.changed().await.ok()?;
+        }
+    }
+
+    /// Reads the certificates and the batches of the provided committed sub dag.
+    fn resolve(&self, sub_dag: CommittedSubDagShell) -> Result<CommittedSubDagProto, Status> {
+        let certificates = self
+            .certificate_store
+            .read_all(sub_dag.certificates.clone())
+            .map_err(|e| Status::internal(format!("Couldn't read certificates: {e}")))?;
+
+        let mut committed_certificates = Vec::with_capacity(certificates.len());
+        for (digest, certificate) in sub_dag.certificates.iter().zip(certificates) {
+            let certificate = certificate.ok_or_else(|| {
+                Status::not_found(format!(
+                    "Certificate {digest} of committed sub dag {} not found",
+                    sub_dag.sub_dag_index
+                ))
+            })?;
+
+            let batch_digests: Vec<BatchDigest> =
+                certificate.header().payload().keys().copied().collect();
+            let stored_batches = self
+                .batch_store
+                .multi_get(&batch_digests)
+                .map_err(|e| Status::internal(format!("Couldn't read batches: {e}")))?;
+
+            let mut batches = Vec::new();
+            let mut missing_batches = Vec::new();
+            for (batch_digest, batch) in batch_digests.into_iter().zip(stored_batches) {
+                match batch {
+                    Some(batch) => batches.push(CommittedBatchProto::from(batch)),
+                    None => missing_batches.push(batch_digest.0.to_vec().into()),
+                }
+            }
+
+            let serialized = bcs::to_bytes(&certificate)
+                .map_err(|e| Status::internal(format!("Couldn't serialize certificate: {e}")))?;
+            committed_certificates.push(CommittedCertificateProto {
+                digest: Some((*digest).into()),
+                certificate: serialized.into(),
+                batches,
+                missing_batches,
+            });
+        }
+
+        Ok(CommittedSubDagProto {
+            commit_index: sub_dag.sub_dag_index,
+            leader: Some(sub_dag.leader.into()),
+            leader_round: sub_dag.leader_round,
+            commit_timestamp: sub_dag.commit_timestamp,
+            certificates: committed_certificates,
+        })
+    }
+}
@@ -1,23 +1,32 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use self::{configuration::NarwhalConfiguration, validator::NarwhalValidator};
+use self::{
+    commits::NarwhalCommits, configuration::NarwhalConfiguration, validator::NarwhalValidator,
+};
 use crate::{
     block_synchronizer::handler::Handler,
     grpc_server::{metrics::EndpointMetrics, proposer::NarwhalProposer},
     BlockRemover, BlockWaiter,
 };
 use config::{AuthorityIdentifier, Committee};
-use consensus::dag::Dag;
+use consensus::{consensus::ConsensusRound, dag::Dag};
 
 use mysten_metrics::spawn_logged_monitored_task;
 use mysten_network::Multiaddr;
 use std::{sync::Arc, time::Duration};
+use storage::{CertificateStore, CertificateStoreCache};
+use store::rocks::DBMap;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tokio::time::timeout;
 use tracing::{error, info, warn};
-use types::{ConditionalBroadcastReceiver, ConfigurationServer, ProposerServer, ValidatorServer};
+use types::{
+    Batch, BatchDigest, CommitsServer, ConditionalBroadcastReceiver, ConfigurationServer,
+    ConsensusStore, ProposerServer, ValidatorServer,
+};
 
+mod commits;
 mod configuration;
 pub mod metrics;
 mod proposer;
@@ -133,3 +142,92 @@ impl<SynchronizerHandler: Handler + Send + Sync + 'static> ConsensusAPIGrpc<Sync
         Ok(())
     }
 }
+
+/// A gRPC server that streams the consensus output to external processes, e.g. indexers or
+/// external executors. It is spawned when the internal consensus is used.
+pub struct CommitStreamGrpc {
+    // Multiaddr of gRPC server
+    socket_address: Multiaddr,
+    consensus_store: Arc<ConsensusStore>,
+    certificate_store: CertificateStore<CertificateStoreCache>,
+    batch_store: DBMap<BatchDigest, Batch>,
+    rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+    endpoints_metrics: EndpointMetrics,
+    rx_shutdown: ConditionalBroadcastReceiver,
+}
+
+impl CommitStreamGrpc {
+    #[must_use]
+    pub fn spawn(
+        socket_address: Multiaddr,
+        consensus_store: Arc<ConsensusStore>,
+        certificate_store: CertificateStore<CertificateStoreCache>,
+        batch_store: DBMap<BatchDigest, Batch>,
+        rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+        endpoints_metrics: EndpointMetrics,
+        rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        spawn_logged_monitored_task!(
+            async move {
+                let _ = Self {
+                    socket_address,
+                    consensus_store,
+                    certificate_store,
+                    batch_store,
+                    rx_consensus_round_updates,
+                    endpoints_metrics,
+                    rx_shutdown,
+                }
+                .run()
+                .await
+                .map_err(|e| error!("{:?}", e));
+            },
+            "CommitStreamGrpcTask"
+        )
+    }
+
+    async fn run(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        const GRACEFUL_SHUTDOWN_DURATION: Duration = Duration::from_millis(2_000);
+
+        let narwhal_commits = NarwhalCommits::new(
+            self.consensus_store,
+            self.certificate_store,
+            self.batch_store,
+            self.rx_consensus_round_updates,
+        );
+
+        let config = mysten_network::config::Config::default();
+        let mut server = config
+            .server_builder_with_metrics(self.endpoints_metrics.clone())
+            .add_service(CommitsServer::new(narwhal_commits))
+            .bind(&self.socket_address)
+            .await?;
+        let local_addr = server.local_addr();
+        info!("Commit stream gRPC Server listening on {local_addr}");
+
+        let shutdown_handle = server.take_cancel_handle().unwrap();
+
+        let server_handle = spawn_logged_monitored_task!(server.serve());
+
+        // wait to receive a shutdown signal
+        let _ = self.rx_shutdown.receiver.recv().await;
+
+        // once do just gracefully shutdown the node
+        shutdown_handle.send(()).unwrap();
+
+        // now wait until the handle completes or timeout if it takes long time
+        match timeout(GRACEFUL_SHUTDOWN_DURATION, server_handle).await {
+            Ok(_) => {
+                info!("Successfully shutting down gracefully commit stream grpc server");
+            }
+            Err(err) => {
+                warn!(
+                    "Time out while waiting to gracefully shutdown commit stream grpc server: {}",
+                    err
+                )
+            }
+        }
+
+        Ok(())
+    }
+}
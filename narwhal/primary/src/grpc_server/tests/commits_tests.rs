@@ -0,0 +1,112 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use fastcrypto::hash::Hash;
+use futures::StreamExt;
+use std::collections::HashMap;
+use storage::NodeStorage;
+use test_utils::{temp_dir, CommitteeFixture};
+use types::{BatchAPI, Certificate, CertificateDigestProto, CommittedSubDag, Header};
+
+fn committed_sub_dag(certificate: &Certificate, sub_dag_index: u64) -> CommittedSubDag {
+    CommittedSubDag {
+        certificates: vec![certificate.clone()],
+        leader: certificate.clone(),
+        sub_dag_index,
+        reputation_score: Default::default(),
+        commit_timestamp: sub_dag_index * 1_000,
+    }
+}
+
+#[tokio::test]
+async fn subscribe_commits() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let author = fixture.authorities().next().unwrap();
+    let store = NodeStorage::reopen(temp_dir(), None);
+
+    // A committed certificate with one batch available locally and one missing.
+    let stored_batch = test_utils::fixture_batch_with_transactions(2);
+    let missing_batch = test_utils::fixture_batch_with_transactions(3);
+    store
+        .batch_store
+        .insert(&stored_batch.digest(), &stored_batch)
+        .unwrap();
+    let header = Header::V1(
+        author
+            .header_builder(&committee)
+            .with_payload_batch(stored_batch.clone(), 0, 0)
+            .with_payload_batch(missing_batch.clone(), 0, 0)
+            .build()
+            .unwrap(),
+    );
+    let certificate = fixture.certificate(&header);
+    store.certificate_store.write(certificate.clone()).unwrap();
+    for sub_dag_index in 1..=2 {
+        store
+            .consensus_store
+            .write_consensus_state(
+                &HashMap::new(),
+                &committed_sub_dag(&certificate, sub_dag_index),
+            )
+            .unwrap();
+    }
+
+    let (tx_consensus_round_updates, rx_consensus_round_updates) =
+        watch::channel(ConsensusRound::default());
+    let commits = NarwhalCommits::new(
+        store.consensus_store.clone(),
+        store.certificate_store.clone(),
+        store.batch_store.clone(),
+        rx_consensus_round_updates,
+    );
+
+    // The sub dags committed before the subscription are replayed from the cursor.
+    let mut stream = commits
+        .subscribe_commits(Request::new(SubscribeCommitsRequest {
+            from_commit_index: 2,
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    let sub_dag = stream.next().await.unwrap().unwrap();
+    assert_eq!(sub_dag.commit_index, 2);
+    assert_eq!(sub_dag.commit_timestamp, 2_000);
+    assert_eq!(
+        sub_dag.leader,
+        Some(CertificateDigestProto::from(certificate.digest()))
+    );
+    assert_eq!(sub_dag.certificates.len(), 1);
+
+    let committed_certificate = &sub_dag.certificates[0];
+    let decoded: Certificate = bcs::from_bytes(&committed_certificate.certificate).unwrap();
+    assert_eq!(decoded.digest(), certificate.digest());
+    assert_eq!(committed_certificate.batches.len(), 1);
+    assert_eq!(
+        committed_certificate.batches[0].transactions.len(),
+        stored_batch.transactions().len()
+    );
+    assert_eq!(
+        committed_certificate.batches[0].digest.to_vec(),
+        stored_batch.digest().0.to_vec()
+    );
+    assert_eq!(
+        committed_certificate.missing_batches,
+        vec![missing_batch.digest().0.to_vec()]
+    );
+
+    // New commits are streamed once consensus notifies about them.
+    store
+        .consensus_store
+        .write_consensus_state(&HashMap::new(), &committed_sub_dag(&certificate, 3))
+        .unwrap();
+    tx_consensus_round_updates
+        .send(ConsensusRound::new(2, 0))
+        .unwrap();
+    let sub_dag = stream.next().await.unwrap().unwrap();
+    assert_eq!(sub_dag.commit_index, 3);
+
+    // The stream ends when consensus shuts down.
+    drop(tx_consensus_round_updates);
+    assert!(stream.next().await.is_none());
+}
@@ -0,0 +1,153 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::synchronizer::Synchronizer;
+use config::AuthorityIdentifier;
+use fastcrypto::hash::Hash as _;
+use mysten_metrics::spawn_monitored_task;
+use std::{collections::HashSet, sync::Arc};
+use storage::{CertificateStore, PayloadStore};
+use store::{rocks::DBMap, rocks::TypedStoreError, Map};
+use thiserror::Error;
+use tracing::{info, warn};
+use types::{Batch, BatchDigest, Certificate, CertificateAPI, CertificateDigest, HeaderAPI, Round};
+
+#[cfg(test)]
+#[path = "tests/integrity_check_tests.rs"]
+pub mod integrity_check_tests;
+
+/// A corruption of the stores that cannot be repaired.
+#[derive(Debug, Error)]
+pub enum IntegrityError {
+    #[error("Failed to read the stores: {0}")]
+    Store(#[from] TypedStoreError),
+
+    #[error("Certificate {certificate} references the parent {parent}, missing from the store")]
+    MissingParent {
+        certificate: CertificateDigest,
+        parent: CertificateDigest,
+    },
+
+    #[error("Certificate {certificate} of {origin} at round {round} is not indexed by its origin")]
+    MissingOriginIndex {
+        certificate: CertificateDigest,
+        origin: AuthorityIdentifier,
+        round: Round,
+    },
+}
+
+/// The inconsistencies found, and partly repaired, by the integrity check.
+#[derive(Debug, Default)]
+pub struct IntegrityReport {
+    /// The number of certificates checked.
+    pub checked_certificates: usize,
+    /// The certificates whose batches are not all available from our workers.
+    pub missing_payload: Vec<Certificate>,
+    /// The number of payload entries removed because their batch is not in the batch store.
+    pub removed_payload_entries: usize,
+}
+
+/// Cross-checks the certificates above the garbage collection round with the payload store,
+/// and optionally the batch store. The payload entries whose batch is missing are removed so
+/// that the batch is synchronized again.
+pub fn check(
+    certificate_store: &CertificateStore,
+    payload_store: &PayloadStore,
+    batch_store: Option<&DBMap<BatchDigest, Batch>>,
+    gc_round: Round,
+) -> Result<IntegrityReport, IntegrityError> {
+    // Fails if the round index references missing certificates.
+    let certificates = certificate_store.after_round(gc_round + 1)?;
+    let digests: HashSet<_> = certificates.iter().map(|c| c.digest()).collect();
+
+    let mut report = IntegrityReport {
+        checked_certificates: certificates.len(),
+        ..Default::default()
+    };
+    for certificate in certificates {
+        let digest = certificate.digest();
+
+        // The certificates are only stored once their parents are, so the parents above the
+        // garbage collection round must be there.
+        if certificate.round() > gc_round + 1 {
+            if let Some(parent) = certificate
+                .header()
+                .parents()
+                .iter()
+                .find(|parent| !digests.contains(parent))
+            {
+                return Err(IntegrityError::MissingParent {
+                    certificate: digest,
+                    parent: *parent,
+                });
+            }
+        }
+
+        let indexed = certificate_store.read_by_index(certificate.origin(), certificate.round())?;
+        if indexed.map(|c| c.digest()) != Some(digest) {
+            return Err(IntegrityError::MissingOriginIndex {
+                certificate: digest,
+                origin: certificate.origin(),
+                round: certificate.round(),
+            });
+        }
+
+        let mut missing = false;
+        for (batch, (worker_id, _)) in certificate.header().payload() {
+            if !payload_store.contains(*batch, *worker_id)? {
+                missing = true;
+                continue;
+            }
+            let Some(batch_store) = batch_store else {
+                continue;
+            };
+            if !batch_store.contains_key(batch)? {
+                payload_store.remove_all([(*batch, *worker_id)])?;
+                report.removed_payload_entries += 1;
+                missing = true;
+            }
+        }
+        if missing {
+            report.missing_payload.push(certificate);
+        }
+    }
+    Ok(report)
+}
+
+/// Synchronizes the batches missing from the certificates of the report from our workers,
+/// which fetch them from their peers.
+pub fn spawn_repair(
+    report: IntegrityReport,
+    authority_id: AuthorityIdentifier,
+    synchronizer: Arc<Synchronizer>,
+    network: anemo::Network,
+    gc_depth: Round,
+) {
+    if report.missing_payload.is_empty() {
+        return;
+    }
+    spawn_monitored_task!(async move {
+        let total = report.missing_payload.len();
+        let mut repaired = 0;
+        for certificate in report.missing_payload {
+            // Our workers are the only source of the batches of our own certificates.
+            if certificate.origin() == authority_id {
+                warn!(
+                    "The batches of our certificate {} are missing and cannot be fetched",
+                    certificate.digest()
+                );
+                continue;
+            }
+            match synchronizer
+                .sync_certificate_batches(certificate.header(), network.clone(), gc_depth)
+                .await
+            {
+                Ok(()) => repaired += 1,
+                Err(e) => warn!(
+                    "Failed to fetch the batches of certificate {}: {e}",
+                    certificate.digest()
+                ),
+            }
+        }
+        info!("Fetched the missing batches of {repaired} certificates out of {total}");
+    });
+}
@@ -9,17 +9,26 @@
 )]
 
 mod aggregators;
+mod batch_status;
 mod block_remover;
 pub mod block_synchronizer;
 mod block_waiter;
 mod certificate_fetcher;
+mod certificate_verifier;
 mod certifier;
+mod dag_admin;
+mod dag_snapshot;
+mod evidence_admin;
 mod grpc_server;
+mod integrity_check;
 mod primary;
 mod proposer;
+mod round_reporter;
 mod state_handler;
+mod store_admin;
 mod synchronizer;
 mod utils;
+mod validation;
 
 #[cfg(test)]
 #[path = "tests/common.rs"]
@@ -34,6 +43,7 @@ pub use crate::{
     block_remover::BlockRemover,
     block_synchronizer::{mock::MockBlockSynchronizer, BlockHeader},
     block_waiter::{BlockWaiter, GetBlockResponse},
+    dag_snapshot::bootstrap_from_snapshot,
     grpc_server::metrics::EndpointMetrics,
     metrics::PrimaryChannelMetrics,
     primary::{NetworkModel, Primary, CHANNEL_CAPACITY, NUM_SHUTDOWN_RECEIVERS},
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 use crate::EndpointMetrics;
 use mysten_network::metrics::MetricsCallbackProvider;
-use network::metrics::{NetworkConnectionMetrics, NetworkMetrics};
+use network::metrics::{NetworkConnectionMetrics, NetworkMetrics, PeerMetrics};
 use prometheus::{
     core::{AtomicI64, GenericGauge},
     default_registry, linear_buckets, register_histogram_vec_with_registry,
@@ -28,6 +28,7 @@ pub(crate) struct Metrics {
     pub(crate) primary_channel_metrics: Option<PrimaryChannelMetrics>,
     pub(crate) node_metrics: Option<PrimaryMetrics>,
     pub(crate) network_connection_metrics: Option<NetworkConnectionMetrics>,
+    pub(crate) peer_metrics: Option<PeerMetrics>,
 }
 
 /// Initialises the metrics
@@ -48,6 +49,9 @@ pub(crate) fn initialise_metrics(metrics_registry: &Registry) -> Metrics {
     // Network metrics for the primary connection
     let network_connection_metrics = NetworkConnectionMetrics::new("primary", metrics_registry);
 
+    // The network health of the connections with each peer
+    let peer_metrics = PeerMetrics::new("primary", metrics_registry);
+
     Metrics {
         node_metrics: Some(node_metrics),
         endpoint_metrics: Some(endpoint_metrics),
@@ -55,6 +59,7 @@ pub(crate) fn initialise_metrics(metrics_registry: &Registry) -> Metrics {
         inbound_network_metrics: Some(inbound_network_metrics),
         outbound_network_metrics: Some(outbound_network_metrics),
         network_connection_metrics: Some(network_connection_metrics),
+        peer_metrics: Some(peer_metrics),
     }
 }
 
@@ -297,6 +302,10 @@ pub struct PrimaryMetrics {
     pub certificate_fetcher_num_certificates_processed: IntGauge,
     /// Number of votes that were requested but not sent due to previously having voted differently
     pub votes_dropped_equivocation_protection: IntCounter,
+    /// Number of evidences of misbehaviors recorded, by kind of misbehavior
+    pub evidences_recorded: IntCounterVec,
+    /// Number of headers and certificates rejected by validation, by kind of message and reason
+    pub validation_rejections: IntCounterVec,
     /// Number of pending batches in proposer
     pub num_of_pending_batches_in_proposer: IntGauge,
     /// A histogram to track the number of batches included
@@ -314,6 +323,18 @@ pub struct PrimaryMetrics {
     pub proposer_resend_batches: IntCounter,
     /// Time it takes for a header to be materialised to a certificate
     pub header_to_certificate_latency: Histogram,
+    /// The latency of the stages of the transactions' pipeline within the primary, per stage
+    pub primary_pipeline_stage_latency: HistogramVec,
+    /// The number of certificates received from peers verified in a batch
+    pub certificate_verification_batch_size: Histogram,
+    /// The number of batches of certificates that failed verification, which are then verified
+    /// one at a time
+    pub certificate_verification_batch_failures: IntCounter,
+    /// The number of votes received for our headers which are aggregated at once
+    pub vote_aggregation_batch_size: Histogram,
+    /// The number of aggregate signatures of votes that failed verification, whose votes are
+    /// then verified one at a time
+    pub vote_verification_failures: IntCounter,
 }
 
 impl PrimaryMetrics {
@@ -436,6 +457,20 @@ impl PrimaryMetrics {
                 registry
             )
             .unwrap(),
+            evidences_recorded: register_int_counter_vec_with_registry!(
+                "evidences_recorded",
+                "Number of evidences of misbehaviors recorded, by kind of misbehavior",
+                &["kind"],
+                registry
+            )
+            .unwrap(),
+            validation_rejections: register_int_counter_vec_with_registry!(
+                "validation_rejections",
+                "Number of headers and certificates rejected by validation, by kind of message and reason",
+                &["kind", "reason"],
+                registry
+            )
+            .unwrap(),
             num_of_pending_batches_in_proposer: register_int_gauge_with_registry!(
                 "num_of_pending_batches_in_proposer",
                 "Number of batch digests pending in proposer for next header proposal",
@@ -476,6 +511,35 @@ impl PrimaryMetrics {
                 "Time it takes for a header to be materialised to a certificate",
                 LATENCY_SEC_BUCKETS.to_vec(),
                 registry
+            ).unwrap(),
+            primary_pipeline_stage_latency: register_histogram_vec_with_registry!(
+                "primary_pipeline_stage_latency",
+                "The latency of the stages of the transactions' pipeline, since the previous stage",
+                &["stage"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry
+            ).unwrap(),
+            certificate_verification_batch_size: register_histogram_with_registry!(
+                "certificate_verification_batch_size",
+                "The number of certificates received from peers verified in a batch",
+                linear_buckets(1.0, 1.0, 16).unwrap(),
+                registry
+            ).unwrap(),
+            certificate_verification_batch_failures: register_int_counter_with_registry!(
+                "certificate_verification_batch_failures",
+                "The number of batches of certificates that failed verification",
+                registry
+            ).unwrap(),
+            vote_aggregation_batch_size: register_histogram_with_registry!(
+                "vote_aggregation_batch_size",
+                "The number of votes received for our headers which are aggregated at once",
+                linear_buckets(1.0, 1.0, 20).unwrap(),
+                registry
+            ).unwrap(),
+            vote_verification_failures: register_int_counter_with_registry!(
+                "vote_verification_failures",
+                "The number of aggregate signatures of votes that failed verification",
+                registry
             ).unwrap()
         }
     }
@@ -2,15 +2,21 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
+    batch_status::BatchStatusNotifier,
     block_synchronizer::{handler::BlockSynchronizerHandler, BlockSynchronizer},
     block_waiter::BlockWaiter,
     certificate_fetcher::CertificateFetcher,
     certifier::Certifier,
-    grpc_server::ConsensusAPIGrpc,
+    dag_admin, dag_snapshot, evidence_admin,
+    grpc_server::{CommitStreamGrpc, ConsensusAPIGrpc},
+    integrity_check,
     metrics::{initialise_metrics, PrimaryMetrics},
     proposer::{OurDigestMessage, Proposer},
+    round_reporter::RoundReporter,
     state_handler::StateHandler,
+    store_admin,
     synchronizer::Synchronizer,
+    validation::{report_rejection, HeaderValidator, Validated},
     BlockRemover,
 };
 
@@ -27,22 +33,30 @@ use anemo_tower::{
 };
 use async_trait::async_trait;
 use config::{
-    Authority, AuthorityIdentifier, Committee, Parameters, WorkerCache, WorkerId, WorkerInfo,
+    Authority, AuthorityIdentifier, Committee, DagSnapshotParameters, HeaderPayloadLimits,
+    Parameters, WorkerCache, WorkerId, WorkerInfo,
 };
 use consensus::consensus::ConsensusRound;
 use consensus::dag::Dag;
+use consensus::leader_schedule::LeaderSchedule;
+use crypto::signer::{AuthoritySigner, SignatureService};
 use crypto::traits::EncodeDecodeBase64;
-use crypto::{KeyPair, NetworkKeyPair, NetworkPublicKey, Signature};
+use crypto::{NetworkKeyPair, NetworkPublicKey};
 use fastcrypto::{
     hash::Hash,
-    signature_service::SignatureService,
     traits::{KeyPair as _, ToFromBytes},
 };
 use futures::{stream::FuturesUnordered, StreamExt};
 use mysten_metrics::spawn_monitored_task;
 use mysten_network::{multiaddr::Protocol, Multiaddr};
+use network::discovery::{PeerAddresses, PeerDiscovery, UNREACHABLE_PEER_RETRY_INTERVAL};
 use network::epoch_filter::{AllowedEpoch, EPOCH_HEADER_KEY};
-use network::{failpoints::FailpointsMakeCallbackHandler, metrics::MetricsMakeCallbackHandler};
+use network::request_budget::RequestBudget;
+use network::{
+    failpoints::FailpointsMakeCallbackHandler,
+    fault_injection::FaultInjectionLayer,
+    metrics::{MetricsMakeCallbackHandler, PeerMetricsMakeCallbackHandler},
+};
 use prometheus::Registry;
 use std::collections::HashMap;
 use std::{
@@ -53,7 +67,11 @@ use std::{
     thread::sleep,
     time::Duration,
 };
-use storage::{CertificateStore, HeaderStore, PayloadStore, ProposerStore, VoteDigestStore};
+use storage::{
+    CertificateStore, EvidenceStore, HeaderStore, PayloadStore, ProposerStore, StoreMaintenance,
+    StoreMaintenanceMetrics, VoteDigestStore,
+};
+use store::rocks::DBMap;
 use tokio::{sync::watch, task::JoinHandle};
 use tokio::{
     sync::{mpsc, oneshot},
@@ -66,12 +84,15 @@ use types::{
     ensure,
     error::{DagError, DagResult},
     metered_channel::{channel_with_total, Receiver, Sender},
-    now, Certificate, CertificateAPI, CertificateDigest, FetchCertificatesRequest,
-    FetchCertificatesResponse, GetCertificatesRequest, GetCertificatesResponse, HeaderAPI,
-    PayloadAvailabilityRequest, PayloadAvailabilityResponse, PreSubscribedBroadcastSender,
-    PrimaryToPrimary, PrimaryToPrimaryServer, RequestVoteRequest, RequestVoteResponse, Round,
-    SendCertificateRequest, SendCertificateResponse, Vote, VoteInfoAPI, WorkerInfoResponse,
-    WorkerOthersBatchMessage, WorkerOurBatchMessage, WorkerToPrimary, WorkerToPrimaryServer,
+    now, AuthorityCertificatesSummary, Batch, BatchDigest, Certificate, CertificateAPI,
+    CertificateDigest, ConsensusStore, Evidence, FetchCertificatesRequest,
+    FetchCertificatesResponse, FetchCertificatesV2Request, FetchCertificatesV2Response,
+    GetCertificatesRequest, GetCertificatesResponse, GetDagSnapshotRequest, GetDagSnapshotResponse,
+    HeaderAPI, PayloadAvailabilityRequest, PayloadAvailabilityResponse,
+    PreSubscribedBroadcastSender, PrimaryToPrimary, PrimaryToPrimaryServer, RequestVoteRequest,
+    RequestVoteResponse, Round, SendCertificateRequest, SendCertificateResponse, Vote, VoteInfoAPI,
+    WorkerInfoResponse, WorkerOthersBatchMessage, WorkerOurBatchMessage, WorkerToPrimary,
+    WorkerToPrimaryServer,
 };
 
 #[cfg(any(test))]
@@ -82,7 +103,7 @@ pub mod primary_tests;
 pub const CHANNEL_CAPACITY: usize = 1_000;
 
 /// The number of shutdown receivers to create on startup. We need one per component loop.
-pub const NUM_SHUTDOWN_RECEIVERS: u64 = 26;
+pub const NUM_SHUTDOWN_RECEIVERS: u64 = 30;
 
 /// Maximum duration to fetch certificates from local storage.
 const FETCH_CERTIFICATES_MAX_HANDLER_TIME: Duration = Duration::from_secs(10);
@@ -100,27 +121,34 @@ impl Primary {
     #[allow(clippy::too_many_arguments)]
     pub fn spawn(
         authority: Authority,
-        signer: KeyPair,
+        signer: impl AuthoritySigner,
         network_signer: NetworkKeyPair,
         committee: Committee,
         worker_cache: WorkerCache,
-        parameters: Parameters,
+        rx_peer_addresses: watch::Receiver<PeerAddresses>,
+        rx_parameters: watch::Receiver<Parameters>,
         header_store: HeaderStore,
         certificate_store: CertificateStore,
         proposer_store: ProposerStore,
         payload_store: PayloadStore,
         vote_digest_store: VoteDigestStore,
+        consensus_store: Arc<ConsensusStore>,
+        batch_store: DBMap<BatchDigest, Batch>,
         tx_new_certificates: Sender<Certificate>,
         rx_committed_certificates: Receiver<(Round, Vec<Certificate>)>,
         rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
         dag: Option<Arc<Dag>>,
         network_model: NetworkModel,
+        leader_schedule: LeaderSchedule,
         tx_shutdown: &mut PreSubscribedBroadcastSender,
         tx_committed_certificates: Sender<(Round, Vec<Certificate>)>,
         registry: &Registry,
         // See comments in Subscriber::spawn
         tx_executor_network: Option<oneshot::Sender<anemo::Network>>,
     ) -> Vec<JoinHandle<()>> {
+        // Most parameters are read once at startup, the ones tunable at runtime are read from the
+        // watch channel by the components using them.
+        let parameters = rx_parameters.borrow().clone();
         // Write the parameters to the logs.
         parameters.tracing();
 
@@ -139,6 +167,41 @@ impl Primary {
         let outbound_network_metrics = Arc::new(metrics.outbound_network_metrics.unwrap());
         let node_metrics = Arc::new(metrics.node_metrics.unwrap());
         let network_connection_metrics = metrics.network_connection_metrics.unwrap();
+        let peer_metrics = Arc::new(metrics.peer_metrics.unwrap());
+
+        // Commit the payload entries reported concurrently by the workers in batches.
+        let payload_store = payload_store.with_write_batching(&parameters.write_batching);
+
+        // Cross-check the stores left by the previous run before starting from them.
+        let integrity_report = parameters.integrity_check.enabled.then(|| {
+            let last_committed_round = consensus_store
+                .read_last_committed()
+                .values()
+                .max()
+                .copied()
+                .unwrap_or_default();
+            let gc_round = last_committed_round.saturating_sub(parameters.gc_depth);
+            let report = integrity_check::check(
+                &certificate_store,
+                &payload_store,
+                parameters
+                    .integrity_check
+                    .verify_batch_store
+                    .then_some(&batch_store),
+                gc_round,
+            )
+            .unwrap_or_else(|e| {
+                panic!("Refusing to start the primary, its storage is corrupted: {e}")
+            });
+            info!(
+                "Checked {} certificates above round {gc_round}, {} with missing batches, \
+                {} payload entries removed",
+                report.checked_certificates,
+                report.missing_payload.len(),
+                report.removed_payload_entries
+            );
+            report
+        });
 
         let (tx_our_digests, rx_our_digests) = channel_with_total(
             CHANNEL_CAPACITY,
@@ -204,6 +267,9 @@ impl Primary {
 
         let signature_service = SignatureService::new(signer);
 
+        // All the stores of the node share the database of the batch store.
+        let evidence_store = EvidenceStore::reopen(&batch_store.rocksdb);
+
         let our_workers = worker_cache
             .workers
             .get(authority.protocol_key())
@@ -226,6 +292,11 @@ impl Primary {
             certificate_store: certificate_store.clone(),
             payload_store: payload_store.clone(),
             vote_digest_store,
+            evidence_store: evidence_store.clone(),
+            consensus_store: consensus_store.clone(),
+            gc_depth: parameters.gc_depth,
+            dag_snapshot: parameters.dag_snapshot.clone(),
+            header_payload_limits: parameters.header_payload_limits(),
             rx_narwhal_round_updates,
             metrics: node_metrics.clone(),
         })
@@ -238,6 +309,13 @@ impl Primary {
         // These are already a batch request; an individual peer should never need more than one.
         .add_layer_for_fetch_certificates(InboundRequestLayer::new(
             inflight_limit::InflightLimitLayer::new(1, inflight_limit::WaitMode::ReturnError),
+        ))
+        .add_layer_for_fetch_certificates_v2(InboundRequestLayer::new(
+            inflight_limit::InflightLimitLayer::new(1, inflight_limit::WaitMode::ReturnError),
+        ))
+        // Snapshots are only requested once by the joining primaries.
+        .add_layer_for_get_dag_snapshot(InboundRequestLayer::new(
+            inflight_limit::InflightLimitLayer::new(1, inflight_limit::WaitMode::ReturnError),
         ));
 
         // Apply other rate limits from configuration as needed.
@@ -265,6 +343,24 @@ impl Primary {
                 )),
             );
         }
+        // Refuse the requests over budget, so that the peers back off.
+        let budget = &parameters.anemo.send_certificate_budget;
+        if let Some(budget) = RequestBudget::new(budget.per_peer, budget.per_endpoint) {
+            primary_service = primary_service.add_layer_for_send_certificate(
+                InboundRequestLayer::new(RequireAuthorizationLayer::new(budget)),
+            );
+        }
+        let budget = &parameters.anemo.fetch_certificates_budget;
+        if let Some(budget) = RequestBudget::new(budget.per_peer, budget.per_endpoint) {
+            // Both versions of the RPC share the budget.
+            primary_service = primary_service
+                .add_layer_for_fetch_certificates(InboundRequestLayer::new(
+                    RequireAuthorizationLayer::new(budget.clone()),
+                ))
+                .add_layer_for_fetch_certificates_v2(InboundRequestLayer::new(
+                    RequireAuthorizationLayer::new(budget),
+                ));
+        }
 
         let worker_service = WorkerToPrimaryServer::new(WorkerReceiverHandler {
             tx_our_digests,
@@ -298,6 +394,13 @@ impl Primary {
             )))
             .merge(worker_to_primary_router);
 
+        let fault_injection = match &parameters.anemo.fault_injection {
+            Some(faults) => {
+                warn!("Injecting faults into the inbound requests: {faults:?}");
+                FaultInjectionLayer::new(faults.seed, faults.drop_probability, faults.max_delay)
+            }
+            None => FaultInjectionLayer::default(),
+        };
         let service = ServiceBuilder::new()
             .layer(
                 TraceLayer::new_for_server_errors()
@@ -308,7 +411,11 @@ impl Primary {
                 inbound_network_metrics,
                 parameters.anemo.excessive_message_size(),
             )))
+            .layer(CallbackLayer::new(PeerMetricsMakeCallbackHandler::inbound(
+                peer_metrics.clone(),
+            )))
             .layer(CallbackLayer::new(FailpointsMakeCallbackHandler::new()))
+            .layer(fault_injection)
             .layer(SetResponseHeaderLayer::overriding(
                 EPOCH_HEADER_KEY.parse().unwrap(),
                 epoch_string.clone(),
@@ -325,6 +432,9 @@ impl Primary {
                 outbound_network_metrics,
                 parameters.anemo.excessive_message_size(),
             )))
+            .layer(CallbackLayer::new(
+                PeerMetricsMakeCallbackHandler::outbound(peer_metrics.clone()),
+            ))
             .layer(CallbackLayer::new(FailpointsMakeCallbackHandler::new()))
             .layer(SetRequestHeaderLayer::overriding(
                 EPOCH_HEADER_KEY.parse().unwrap(),
@@ -393,6 +503,16 @@ impl Primary {
             panic!("Failed to send Network to Synchronizer!");
         }
 
+        if let Some(report) = integrity_report {
+            integrity_check::spawn_repair(
+                report,
+                authority.id(),
+                synchronizer.clone(),
+                network.clone(),
+                parameters.gc_depth,
+            );
+        }
+
         info!("Primary {} listening on {}", authority.id(), address);
 
         let mut peer_types = HashMap::new();
@@ -440,6 +560,14 @@ impl Primary {
             peer_types,
         );
 
+        // The other primaries and the workers may move to new addresses during the epoch.
+        let peer_discovery_handle = PeerDiscovery::spawn(
+            network.downgrade(),
+            rx_peer_addresses,
+            UNREACHABLE_PEER_RETRY_INTERVAL,
+            tx_shutdown.subscribe(),
+        );
+
         info!(
             "Primary {} listening to network admin messages on 127.0.0.1:{}",
             authority.id(),
@@ -448,11 +576,31 @@ impl Primary {
                 .primary_network_admin_server_port
         );
 
-        let admin_handles = network::admin::start_admin_server(
+        let store_maintenance = StoreMaintenance::new(
+            batch_store.rocksdb.clone(),
+            StoreMaintenanceMetrics::new(registry),
+        );
+        let store_maintenance_handle = store_admin::spawn_scheduled_maintenance(
+            store_maintenance.clone(),
+            parameters.store_maintenance.clone(),
+            tx_shutdown.subscribe(),
+        );
+
+        let admin_handles = network::admin::start_admin_server_with_routes(
             parameters
                 .network_admin_server
                 .primary_network_admin_server_port,
             network.clone(),
+            dag_admin::routes(
+                committee.clone(),
+                certificate_store.clone(),
+                consensus_store.clone(),
+                synchronizer.clone(),
+                rx_consensus_round_updates.clone(),
+            )
+            .merge(store_admin::routes(store_maintenance))
+            .merge(evidence_admin::routes(evidence_store))
+            .merge(network::admin::peer_metrics_routes(peer_metrics)),
             tx_shutdown.subscribe(),
         );
 
@@ -462,6 +610,22 @@ impl Primary {
             }
         }
 
+        // Our workers are told when their batches are certified and committed.
+        let batch_status_notifier = BatchStatusNotifier::new(
+            authority.id(),
+            committee.clone(),
+            worker_cache.clone(),
+            network.clone(),
+        );
+        // Our workers are told up to which round their pending synchronizations matter.
+        let round_reporter_handle = RoundReporter::spawn(
+            authority.id(),
+            committee.clone(),
+            worker_cache.clone(),
+            network.clone(),
+            rx_consensus_round_updates.clone(),
+            tx_shutdown.subscribe(),
+        );
         let core_handle = Certifier::spawn(
             authority.id(),
             committee.clone(),
@@ -473,6 +637,7 @@ impl Primary {
             rx_headers,
             node_metrics.clone(),
             network.clone(),
+            batch_status_notifier.clone(),
         );
 
         // The `CertificateFetcher` waits to receive all the ancestors of a certificate before looping it back to the
@@ -482,7 +647,7 @@ impl Primary {
             committee.clone(),
             network.clone(),
             certificate_store.clone(),
-            rx_consensus_round_updates,
+            rx_consensus_round_updates.clone(),
             tx_shutdown.subscribe(),
             rx_certificate_fetcher,
             synchronizer.clone(),
@@ -496,9 +661,10 @@ impl Primary {
             committee.clone(),
             proposer_store,
             parameters.header_num_of_batches_threshold,
-            parameters.max_header_num_of_batches,
-            parameters.max_header_delay,
+            parameters.header_payload_limits(),
+            rx_parameters.clone(),
             parameters.min_header_delay,
+            parameters.header_proposal_policy,
             None,
             network_model,
             tx_shutdown.subscribe(),
@@ -508,6 +674,7 @@ impl Primary {
             tx_narwhal_round_updates,
             rx_committed_own_headers,
             node_metrics,
+            leader_schedule,
         );
 
         let mut handles = vec![
@@ -515,6 +682,9 @@ impl Primary {
             certificate_fetcher_handle,
             proposer_handle,
             connection_monitor_handle,
+            peer_discovery_handle,
+            store_maintenance_handle,
+            round_reporter_handle,
         ];
         handles.extend(admin_handles);
 
@@ -600,6 +770,20 @@ impl Primary {
             );
 
             handles.extend(vec![block_synchronizer_handle, consensus_api_handle]);
+        } else {
+            // With the internal consensus the consensus API gRPC server is not needed, so its
+            // address is used to stream the committed sub dags to external processes instead.
+            let commit_stream_handle = CommitStreamGrpc::spawn(
+                parameters.consensus_api_grpc.socket_addr,
+                consensus_store,
+                certificate_store,
+                batch_store,
+                rx_consensus_round_updates,
+                endpoint_metrics,
+                tx_shutdown.subscribe(),
+            );
+
+            handles.push(commit_stream_handle);
         }
 
         // Keeps track of the latest consensus round and allows other tasks to clean up their their internal state
@@ -608,6 +792,7 @@ impl Primary {
             rx_committed_certificates,
             tx_shutdown.subscribe(),
             Some(tx_committed_own_headers),
+            batch_status_notifier,
             network,
         );
         handles.push(state_handler_handle);
@@ -649,12 +834,22 @@ struct PrimaryReceiverHandler {
     worker_cache: WorkerCache,
     synchronizer: Arc<Synchronizer>,
     /// Service to sign headers.
-    signature_service: SignatureService<Signature, { crypto::INTENT_MESSAGE_LENGTH }>,
+    signature_service: SignatureService,
     header_store: HeaderStore,
     certificate_store: CertificateStore,
     payload_store: PayloadStore,
     /// The store to persist the last voted round per authority, used to ensure idempotence.
     vote_digest_store: VoteDigestStore,
+    /// The store of the evidences of the misbehaviors of the other authorities.
+    evidence_store: EvidenceStore,
+    /// The consensus state, from which the DAG snapshots are served.
+    consensus_store: Arc<ConsensusStore>,
+    /// The depth of the garbage collection.
+    gc_depth: Round,
+    /// The parameters of the DAG snapshots served to the peers.
+    dag_snapshot: DagSnapshotParameters,
+    /// The limits on the batch digests of the headers we vote for.
+    header_payload_limits: HeaderPayloadLimits,
     /// Get a signal when the round changes.
     rx_narwhal_round_updates: watch::Receiver<Round>,
     metrics: Arc<PrimaryMetrics>,
@@ -662,6 +857,32 @@ struct PrimaryReceiverHandler {
 
 #[allow(clippy::result_large_err)]
 impl PrimaryReceiverHandler {
+    /// Records the evidence of a misbehavior, unless it was already recorded or enough evidences
+    /// of its offender were.
+    fn record_evidence(&self, evidence: Evidence) {
+        match self.evidence_store.write(&evidence) {
+            Ok(true) => {
+                warn!("Recorded evidence of misbehavior: {evidence}");
+                self.metrics
+                    .evidences_recorded
+                    .with_label_values(&[evidence.misbehavior.kind()])
+                    .inc();
+            }
+            Ok(false) => debug!("Evidence of misbehavior not recorded: {evidence}"),
+            Err(e) => error!("Failed to record evidence of misbehavior {evidence}: {e:?}"),
+        }
+    }
+
+    /// Returns the certificate already stored for the round and origin of the provided one, if
+    /// it is a different certificate.
+    fn conflicting_certificate(&self, certificate: &Certificate) -> Option<Certificate> {
+        self.certificate_store
+            .read_by_index(certificate.origin(), certificate.round())
+            .ok()
+            .flatten()
+            .filter(|stored| stored.digest() != certificate.digest())
+    }
+
     fn find_next_round(
         &self,
         origin: AuthorityIdentifier,
@@ -682,6 +903,133 @@ impl PrimaryReceiverHandler {
         Ok(None)
     }
 
+    /// Summarizes the certificates of each authority above the provided round. Only seeks the
+    /// lowest and the highest rounds of each authority in the store, so the summary does not
+    /// depend on the number of certificates.
+    fn summarize_certificates(
+        &self,
+        exclusive_lower_bound: Round,
+    ) -> Result<Vec<AuthorityCertificatesSummary>, anemo::rpc::Status> {
+        let mut summaries = Vec::new();
+        for origin in self.committee.authorities().map(|authority| authority.id()) {
+            let lowest_round = self
+                .certificate_store
+                .next_round_number(origin, exclusive_lower_bound)
+                .map_err(|e| anemo::rpc::Status::from_error(Box::new(e)))?;
+            let Some(lowest_round) = lowest_round else {
+                continue;
+            };
+            let highest_round = self
+                .certificate_store
+                .last_round_number(origin)
+                .map_err(|e| anemo::rpc::Status::from_error(Box::new(e)))?
+                .unwrap_or(lowest_round);
+            summaries.push(AuthorityCertificatesSummary {
+                origin,
+                lowest_round,
+                highest_round,
+            });
+        }
+        Ok(summaries)
+    }
+
+    /// Reads the certificates requested by a peer, from lower to higher rounds, within the
+    /// per authority upper bounds of the rounds.
+    async fn read_certificates(
+        &self,
+        peer: String,
+        request: &FetchCertificatesRequest,
+        upper_bounds: BTreeMap<AuthorityIdentifier, Round>,
+    ) -> Result<Vec<Certificate>, anemo::rpc::Status> {
+        let time_start = Instant::now();
+        let mut certificates = Vec::new();
+        if request.max_items == 0 {
+            return Ok(certificates);
+        }
+
+        // Use a min-queue for (round, authority) to keep track of the next certificate to fetch.
+        //
+        // Compared to fetching certificates iteratatively round by round, using a heap is simpler,
+        // and avoids the pathological case of iterating through many missing rounds of a downed authority.
+        let (lower_bound, skip_rounds) = request.get_bounds();
+        let within_upper_bound = |origin: &AuthorityIdentifier, round: Round| {
+            upper_bounds
+                .get(origin)
+                .map_or(true, |upper_bound| round <= *upper_bound)
+        };
+        debug!(
+            "Fetching certificates after round {lower_bound} for peer {:?}, elapsed = {}ms",
+            peer,
+            time_start.elapsed().as_millis(),
+        );
+
+        let mut fetch_queue = BinaryHeap::new();
+        const MAX_SKIP_ROUNDS: usize = 1000;
+        for (origin, rounds) in &skip_rounds {
+            if rounds.len() > MAX_SKIP_ROUNDS {
+                warn!(
+                    "Peer has sent {} rounds to skip on origin {}, indicating peer's problem with \
+                    committing or keeping track of GC rounds. elapsed = {}ms",
+                    rounds.len(),
+                    origin,
+                    time_start.elapsed().as_millis(),
+                );
+            }
+            let next_round = self.find_next_round(*origin, lower_bound, rounds)?;
+            if let Some(r) = next_round.filter(|r| within_upper_bound(origin, *r)) {
+                fetch_queue.push(Reverse((r, origin)));
+            }
+        }
+        debug!(
+            "Initialized origins and rounds to fetch, elapsed = {}ms",
+            time_start.elapsed().as_millis(),
+        );
+
+        // Iteratively pop the next smallest (Round, Authority) pair, and push to min-heap the next
+        // higher round of the same authority that should not be skipped.
+        // The process ends when there are no more pairs in the min-heap.
+        while let Some(Reverse((round, origin))) = fetch_queue.pop() {
+            // Allow the request handler to be stopped after timeout.
+            tokio::task::yield_now().await;
+            match self
+                .certificate_store
+                .read_by_index(*origin, round)
+                .map_err(|e| anemo::rpc::Status::from_error(Box::new(e)))?
+            {
+                Some(cert) => {
+                    certificates.push(cert);
+                    let next_round =
+                        self.find_next_round(*origin, round, skip_rounds.get(origin).unwrap())?;
+                    if let Some(r) = next_round.filter(|r| within_upper_bound(origin, *r)) {
+                        fetch_queue.push(Reverse((r, origin)));
+                    }
+                }
+                None => continue,
+            };
+            if certificates.len() == request.max_items {
+                debug!(
+                    "Collected enough certificates (num={}, elapsed={}ms), returning.",
+                    certificates.len(),
+                    time_start.elapsed().as_millis(),
+                );
+                break;
+            }
+            if time_start.elapsed() >= FETCH_CERTIFICATES_MAX_HANDLER_TIME {
+                debug!(
+                    "Spent enough time reading certificates (num={}, elapsed={}ms), returning.",
+                    certificates.len(),
+                    time_start.elapsed().as_millis(),
+                );
+                break;
+            }
+            assert!(certificates.len() < request.max_items);
+        }
+
+        // The requestor should be able to process certificates returned in this order without
+        // any missing parents.
+        Ok(certificates)
+    }
+
     #[allow(clippy::mutable_key_type)]
     async fn process_request_vote(
         &self,
@@ -697,7 +1045,12 @@ impl PrimaryReceiverHandler {
 
         let header = &request.body().header;
         let committee = self.committee.clone();
-        header.validate(&committee, &self.worker_cache)?;
+        HeaderValidator {
+            committee: &committee,
+            worker_cache: &self.worker_cache,
+            limits: &self.header_payload_limits,
+        }
+        .validate(header)?;
 
         // Vote request must come from the Header's author.
         let peer_id = request
@@ -891,6 +1244,13 @@ impl PrimaryReceiverHandler {
                         header.round()
                     );
                     self.metrics.votes_dropped_equivocation_protection.inc();
+                    if let Some(first) = self
+                        .header_store
+                        .read_by_author_round(header.author(), header.round())?
+                        .filter(|first| first.digest() != header.digest())
+                    {
+                        self.record_evidence(Evidence::equivocating_headers(first, header.clone()));
+                    }
                     return Err(DagError::AlreadyVoted(
                         vote_info.vote_digest(),
                         header.round(),
@@ -932,18 +1292,45 @@ impl PrimaryToPrimary for PrimaryReceiverHandler {
                     "Unable to access network to send child RPCs".to_owned(),
                 )
             })?;
+        let sender = request.peer_id().and_then(|peer_id| {
+            let network_key = NetworkPublicKey::from_bytes(&peer_id.0).ok()?;
+            self.committee
+                .authority_by_network_key(&network_key)
+                .map(|authority| authority.id())
+        });
         let certificate = request.into_body().certificate;
+        let conflicting = self.conflicting_certificate(&certificate);
         match self
             .synchronizer
-            .try_accept_certificate(certificate, &network)
+            .try_accept_certificate(certificate.clone(), &network)
             .await
         {
-            Ok(()) => Ok(anemo::Response::new(SendCertificateResponse {
-                accepted: true,
-            })),
+            Ok(()) => {
+                if let Some(first) = conflicting {
+                    self.record_evidence(Evidence::equivocating_certificates(first, certificate));
+                }
+                Ok(anemo::Response::new(SendCertificateResponse {
+                    accepted: true,
+                }))
+            }
             Err(DagError::Suspended(_)) => Ok(anemo::Response::new(SendCertificateResponse {
                 accepted: false,
             })),
+            Err(
+                e @ (DagError::InvalidSignature
+                | DagError::CertificateRequiresQuorum
+                | DagError::InvalidHeaderDigest
+                | DagError::HeaderHasBadWorkerIds(_)),
+            ) => {
+                if let Some(sender) = sender {
+                    self.record_evidence(Evidence::invalid_certificate(
+                        sender,
+                        &certificate,
+                        e.to_string(),
+                    ));
+                }
+                Err(anemo::rpc::Status::internal(e.to_string()))
+            }
             Err(e) => Err(anemo::rpc::Status::internal(e.to_string())),
         }
     }
@@ -952,10 +1339,12 @@ impl PrimaryToPrimary for PrimaryReceiverHandler {
         &self,
         request: anemo::Request<RequestVoteRequest>,
     ) -> Result<anemo::Response<RequestVoteResponse>, anemo::rpc::Status> {
+        let header = request.body().header.digest();
         self.process_request_vote(request)
             .await
             .map(anemo::Response::new)
             .map_err(|e| {
+                let e = report_rejection(&self.metrics, Validated::Header, header, e);
                 anemo::rpc::Status::new_with_message(
                     match e {
                         // Report unretriable errors as 400 Bad Request.
@@ -963,6 +1352,8 @@ impl PrimaryToPrimary for PrimaryReceiverHandler {
                         | DagError::InvalidEpoch { .. }
                         | DagError::InvalidHeaderDigest
                         | DagError::HeaderHasBadWorkerIds(_)
+                        | DagError::HeaderHasTooManyBatches(..)
+                        | DagError::HeaderHasTooManyWorkerBatches(..)
                         | DagError::HeaderHasInvalidParentRoundNumbers(_)
                         | DagError::HeaderHasDuplicateParentAuthorities(_)
                         | DagError::AlreadyVoted(_, _)
@@ -1003,94 +1394,39 @@ impl PrimaryToPrimary for PrimaryReceiverHandler {
         &self,
         request: anemo::Request<FetchCertificatesRequest>,
     ) -> Result<anemo::Response<FetchCertificatesResponse>, anemo::rpc::Status> {
-        let time_start = Instant::now();
         let peer = request
             .peer_id()
             .map_or_else(|| "None".to_string(), |peer_id| format!("{}", peer_id));
         let request = request.into_body();
-        let mut response = FetchCertificatesResponse {
-            certificates: Vec::new(),
-        };
-        if request.max_items == 0 {
-            return Ok(anemo::Response::new(response));
-        }
-
-        // Use a min-queue for (round, authority) to keep track of the next certificate to fetch.
-        //
-        // Compared to fetching certificates iteratatively round by round, using a heap is simpler,
-        // and avoids the pathological case of iterating through many missing rounds of a downed authority.
-        let (lower_bound, skip_rounds) = request.get_bounds();
-        debug!(
-            "Fetching certificates after round {lower_bound} for peer {:?}, elapsed = {}ms",
-            peer,
-            time_start.elapsed().as_millis(),
-        );
-
-        let mut fetch_queue = BinaryHeap::new();
-        const MAX_SKIP_ROUNDS: usize = 1000;
-        for (origin, rounds) in &skip_rounds {
-            if rounds.len() > MAX_SKIP_ROUNDS {
-                warn!(
-                    "Peer has sent {} rounds to skip on origin {}, indicating peer's problem with \
-                    committing or keeping track of GC rounds. elapsed = {}ms",
-                    rounds.len(),
-                    origin,
-                    time_start.elapsed().as_millis(),
-                );
-            }
-            let next_round = self.find_next_round(*origin, lower_bound, rounds)?;
-            if let Some(r) = next_round {
-                fetch_queue.push(Reverse((r, origin)));
-            }
-        }
-        debug!(
-            "Initialized origins and rounds to fetch, elapsed = {}ms",
-            time_start.elapsed().as_millis(),
-        );
-
-        // Iteratively pop the next smallest (Round, Authority) pair, and push to min-heap the next
-        // higher round of the same authority that should not be skipped.
-        // The process ends when there are no more pairs in the min-heap.
-        while let Some(Reverse((round, origin))) = fetch_queue.pop() {
-            // Allow the request handler to be stopped after timeout.
-            tokio::task::yield_now().await;
-            match self
-                .certificate_store
-                .read_by_index(*origin, round)
-                .map_err(|e| anemo::rpc::Status::from_error(Box::new(e)))?
-            {
-                Some(cert) => {
-                    response.certificates.push(cert);
-                    let next_round =
-                        self.find_next_round(*origin, round, skip_rounds.get(origin).unwrap())?;
-                    if let Some(r) = next_round {
-                        fetch_queue.push(Reverse((r, origin)));
-                    }
-                }
-                None => continue,
-            };
-            if response.certificates.len() == request.max_items {
-                debug!(
-                    "Collected enough certificates (num={}, elapsed={}ms), returning.",
-                    response.certificates.len(),
-                    time_start.elapsed().as_millis(),
-                );
-                break;
-            }
-            if time_start.elapsed() >= FETCH_CERTIFICATES_MAX_HANDLER_TIME {
-                debug!(
-                    "Spent enough time reading certificates (num={}, elapsed={}ms), returning.",
-                    response.certificates.len(),
-                    time_start.elapsed().as_millis(),
-                );
-                break;
-            }
-            assert!(response.certificates.len() < request.max_items);
-        }
+        let certificates = self
+            .read_certificates(peer, &request, BTreeMap::new())
+            .await?;
+        Ok(anemo::Response::new(FetchCertificatesResponse {
+            certificates,
+        }))
+    }
 
-        // The requestor should be able to process certificates returned in this order without
-        // any missing parents.
-        Ok(anemo::Response::new(response))
+    #[instrument(level = "debug", skip_all, peer = ?request.peer_id())]
+    async fn fetch_certificates_v2(
+        &self,
+        request: anemo::Request<FetchCertificatesV2Request>,
+    ) -> Result<anemo::Response<FetchCertificatesV2Response>, anemo::rpc::Status> {
+        let peer = request
+            .peer_id()
+            .map_or_else(|| "None".to_string(), |peer_id| format!("{}", peer_id));
+        let request = request.into_body();
+        let summary = if request.include_summary {
+            self.summarize_certificates(request.request.exclusive_lower_bound)?
+        } else {
+            Vec::new()
+        };
+        let certificates = self
+            .read_certificates(peer, &request.request, request.get_upper_bounds())
+            .await?;
+        Ok(anemo::Response::new(FetchCertificatesV2Response {
+            certificates,
+            summary,
+        }))
     }
 
     async fn get_payload_availability(
@@ -1136,6 +1472,28 @@ impl PrimaryToPrimary for PrimaryReceiverHandler {
             payload_availability: result,
         }))
     }
+
+    async fn get_dag_snapshot(
+        &self,
+        request: anemo::Request<GetDagSnapshotRequest>,
+    ) -> Result<anemo::Response<GetDagSnapshotResponse>, anemo::rpc::Status> {
+        let max_items = request
+            .into_body()
+            .max_items
+            .min(self.dag_snapshot.max_certificates);
+        let snapshot = dag_snapshot::make_snapshot(
+            self.authority_id,
+            &self.committee,
+            &self.certificate_store,
+            &self.consensus_store,
+            &self.signature_service,
+            self.gc_depth,
+            max_items,
+        )
+        .await
+        .map_err(|e| anemo::rpc::Status::internal(format!("error making DAG snapshot: {e:?}")))?;
+        Ok(anemo::Response::new(snapshot))
+    }
 }
 
 /// Defines how the network receiver handles incoming workers messages.
@@ -1168,6 +1526,8 @@ impl WorkerToPrimary for WorkerReceiverHandler {
                 digest: message.digest,
                 worker_id: message.worker_id,
                 timestamp: message.metadata.created_at,
+                size: message.size as usize,
+                received_at: Instant::now(),
                 ack_channel: Some(tx_ack),
             })
             .await
@@ -1188,7 +1548,8 @@ impl WorkerToPrimary for WorkerReceiverHandler {
     ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
         let message = request.into_body();
         self.payload_store
-            .write(&message.digest, &message.worker_id)
+            .write_batched(&message.digest, &message.worker_id)
+            .await
             .map_err(|e| anemo::rpc::Status::internal(e.to_string()))?;
         Ok(anemo::Response::new(()))
     }
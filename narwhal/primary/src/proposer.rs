@@ -2,10 +2,14 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::{metrics::PrimaryMetrics, NetworkModel};
-use config::{AuthorityIdentifier, Committee, Epoch, WorkerId};
+use config::{
+    AuthorityIdentifier, Committee, Epoch, HeaderPayloadLimits, HeaderProposalPolicy, Parameters,
+    WorkerId,
+};
+use consensus::leader_schedule::LeaderSchedule;
 use fastcrypto::hash::Hash as _;
 use mysten_metrics::spawn_logged_monitored_task;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::{cmp::Ordering, sync::Arc};
 use storage::ProposerStore;
 use tokio::time::{sleep_until, Instant};
@@ -28,6 +32,10 @@ pub struct OurDigestMessage {
     pub digest: BatchDigest,
     pub worker_id: WorkerId,
     pub timestamp: TimestampMs,
+    /// The total size in bytes of the transactions of the batch.
+    pub size: usize,
+    /// The time the digest was received at, once the batch was acknowledged by a quorum.
+    pub received_at: Instant,
     /// A channel to send an () as an ack after this digest is processed by the primary.
     pub ack_channel: Option<oneshot::Sender<()>>,
 }
@@ -38,6 +46,10 @@ pub mod proposer_tests;
 
 const DEFAULT_HEADER_RESEND_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// The stage of the transactions' pipeline from the batch being acknowledged by a quorum to it
+/// being referenced in one of our headers.
+pub(crate) const REFERENCED_IN_HEADER_STAGE: &str = "referenced_in_header";
+
 /// The proposer creates new headers and send them to the core for broadcasting and further processing.
 pub struct Proposer {
     /// The id of this primary.
@@ -49,12 +61,15 @@ pub struct Proposer {
     /// `header_num_of_batches_threshold` batches we are ok
     /// to try and propose a header
     header_num_of_batches_threshold: usize,
-    /// The maximum number of batches in header.
-    max_header_num_of_batches: usize,
-    /// The maximum delay to wait for conditions like having leader in parents.
-    max_header_delay: Duration,
+    /// The maximum number of batches in header, in total and per worker.
+    header_payload_limits: HeaderPayloadLimits,
+    /// The parameters of the node, from which the maximum delay to wait for conditions like
+    /// having leader in parents is read, so that it can be tuned at runtime.
+    rx_parameters: watch::Receiver<Parameters>,
     /// The minimum delay between generating headers.
     min_header_delay: Duration,
+    /// Whether to wait for a minimum payload before proposing a header.
+    header_proposal_policy: HeaderProposalPolicy,
     /// The delay to wait until resending the last proposed header if proposer
     /// hasn't proposed anything new since then. If None is provided then the
     /// default value will be used instead.
@@ -96,6 +111,8 @@ pub struct Proposer {
 
     /// Metrics handler
     metrics: Arc<PrimaryMetrics>,
+    /// The leader schedule used by consensus, to know the leader of each round.
+    leader_schedule: LeaderSchedule,
 }
 
 impl Proposer {
@@ -106,9 +123,10 @@ impl Proposer {
         committee: Committee,
         proposer_store: ProposerStore,
         header_num_of_batches_threshold: usize,
-        max_header_num_of_batches: usize,
-        max_header_delay: Duration,
+        header_payload_limits: HeaderPayloadLimits,
+        rx_parameters: watch::Receiver<Parameters>,
         min_header_delay: Duration,
+        header_proposal_policy: HeaderProposalPolicy,
         header_resend_timeout: Option<Duration>,
         network_model: NetworkModel,
         rx_shutdown: ConditionalBroadcastReceiver,
@@ -118,6 +136,7 @@ impl Proposer {
         tx_narwhal_round_updates: watch::Sender<Round>,
         rx_committed_own_headers: Receiver<(Round, Vec<Round>)>,
         metrics: Arc<PrimaryMetrics>,
+        leader_schedule: LeaderSchedule,
     ) -> JoinHandle<()> {
         let genesis = Certificate::genesis(&committee);
         spawn_logged_monitored_task!(
@@ -126,9 +145,10 @@ impl Proposer {
                     authority_id,
                     committee,
                     header_num_of_batches_threshold,
-                    max_header_num_of_batches,
-                    max_header_delay,
+                    header_payload_limits,
+                    rx_parameters,
                     min_header_delay,
+                    header_proposal_policy,
                     header_resend_timeout,
                     network_model,
                     rx_shutdown,
@@ -141,10 +161,11 @@ impl Proposer {
                     last_round_timestamp: None,
                     last_parents: genesis,
                     last_leader: None,
-                    digests: VecDeque::with_capacity(2 * max_header_num_of_batches),
+                    digests: VecDeque::with_capacity(2 * header_payload_limits.max_num_of_batches),
                     proposed_headers: BTreeMap::new(),
                     rx_committed_own_headers,
                     metrics,
+                    leader_schedule,
                 }
                 .run()
                 .await;
@@ -180,6 +201,32 @@ impl Proposer {
         Ok((header, num_of_included_digests))
     }
 
+    // Takes the oldest digests within the limits of a header. The digests of the workers that
+    // reached their limit stay in order for the next headers.
+    fn take_header_digests(&mut self) -> VecDeque<OurDigestMessage> {
+        let limits = self.header_payload_limits;
+        let mut header_digests = VecDeque::new();
+        let mut skipped_digests = VecDeque::new();
+        let mut num_of_batches_per_worker = HashMap::<WorkerId, usize>::new();
+        while header_digests.len() < limits.max_num_of_batches {
+            let Some(digest) = self.digests.pop_front() else {
+                break;
+            };
+            let num_of_batches = num_of_batches_per_worker
+                .entry(digest.worker_id)
+                .or_default();
+            if *num_of_batches < limits.max_num_of_batches_per_worker {
+                *num_of_batches += 1;
+                header_digests.push_back(digest);
+            } else {
+                skipped_digests.push_back(digest);
+            }
+        }
+        skipped_digests.append(&mut self.digests);
+        self.digests = skipped_digests;
+        header_digests
+    }
+
     // Creates a new header. Also the method ensures we are protected against equivocation.
     // If we detect that a different header has been already produced for the same round, then
     // this method returns the earlier header. Otherwise the newly created header will be returned.
@@ -198,8 +245,7 @@ impl Proposer {
         }
 
         // Make a new header.
-        let num_of_digests = self.digests.len().min(self.max_header_num_of_batches);
-        let header_digests: VecDeque<_> = self.digests.drain(..num_of_digests).collect();
+        let header_digests = self.take_header_digests();
         let parents: Vec<_> = self.last_parents.drain(..).collect();
 
         // Here we check that the timestamp we will include in the header is consistent with the
@@ -231,15 +277,15 @@ impl Proposer {
         .await;
 
         let leader_and_support = if this_round % 2 == 0 {
-            let authority = self.committee.leader(this_round);
-            if self.authority_id == authority.id() {
+            let authority = self.leader_schedule.leader(this_round);
+            if self.authority_id == authority {
                 "even_round_is_leader"
             } else {
                 "even_round_not_leader"
             }
         } else {
-            let authority = self.committee.leader(this_round - 1);
-            if parents.iter().any(|c| c.origin() == authority.id()) {
+            let authority = self.leader_schedule.leader(this_round - 1);
+            if parents.iter().any(|c| c.origin() == authority) {
                 "odd_round_gives_support"
             } else {
                 "odd_round_no_support"
@@ -282,6 +328,10 @@ impl Proposer {
             self.metrics
                 .proposer_batch_latency
                 .observe(batch_inclusion_secs);
+            self.metrics
+                .primary_pipeline_stage_latency
+                .with_label_values(&[REFERENCED_IN_HEADER_STAGE])
+                .observe(digest.received_at.elapsed().as_secs_f64());
         }
 
         // NOTE: This log entry is used to compute performance.
@@ -292,7 +342,7 @@ impl Proposer {
                     total_inclusion_secs / header_digests.len() as f64,
                 )
             } else {
-                (self.max_header_delay.as_secs_f64(), 0.0)
+                (self.max_header_delay().as_secs_f64(), 0.0)
             };
         debug!(
             "Header {:?} was created in {} seconds. Contains {} batches, with average delay {} seconds.",
@@ -310,19 +360,21 @@ impl Proposer {
         Ok(header)
     }
 
+    fn max_header_delay(&self) -> Duration {
+        self.rx_parameters.borrow().max_header_delay
+    }
+
     fn max_delay(&self) -> Duration {
         match self.network_model {
             // In partial synchrony, if this node is going to be the leader of the next
             // round, we set a lower max timeout value to increase its chance of committing
             // the leader.
-            NetworkModel::PartiallySynchronous
-                if self.committee.leader(self.round + 1).id() == self.authority_id =>
-            {
-                self.max_header_delay / 2
+            NetworkModel::PartiallySynchronous if self.is_next_round_leader() => {
+                self.max_header_delay() / 2
             }
 
             // Otherwise we keep the default timeout value.
-            _ => self.max_header_delay,
+            _ => self.max_header_delay(),
         }
     }
 
@@ -332,8 +384,7 @@ impl Proposer {
             // round and there are more than 1 primary in the committee, we use a lower
             // min delay value to increase the chance of committing the leader.
             NetworkModel::PartiallySynchronous
-                if self.committee.size() > 1
-                    && self.committee.leader(self.round + 1).id() == self.authority_id =>
+                if self.committee.size() > 1 && self.is_next_round_leader() =>
             {
                 Duration::ZERO
             }
@@ -343,13 +394,42 @@ impl Proposer {
         }
     }
 
+    /// Whether the pending digests are enough of a payload for the next header, according to the
+    /// proposal policy.
+    fn enough_payload(&self) -> bool {
+        match self.header_proposal_policy {
+            HeaderProposalPolicy::Eager => true,
+            // Delaying the leader would delay the commit of the payloads of every primary.
+            HeaderProposalPolicy::Delayed { .. }
+                if matches!(self.network_model, NetworkModel::PartiallySynchronous)
+                    && self.is_next_round_leader() =>
+            {
+                true
+            }
+            HeaderProposalPolicy::Delayed {
+                min_num_of_batches,
+                min_payload_size,
+            } => {
+                self.digests.len() >= min_num_of_batches
+                    || self.digests.iter().map(|digest| digest.size).sum::<usize>()
+                        >= min_payload_size
+            }
+        }
+    }
+
+    /// Whether this node is the leader of the next round. Leaders are only elected for even rounds.
+    fn is_next_round_leader(&self) -> bool {
+        (self.round + 1) % 2 == 0
+            && self.leader_schedule.leader(self.round + 1) == self.authority_id
+    }
+
     /// Update the last leader certificate. This is only relevant in partial synchrony.
     fn update_leader(&mut self) -> bool {
-        let leader = self.committee.leader(self.round);
+        let leader = self.leader_schedule.leader(self.round);
         self.last_leader = self
             .last_parents
             .iter()
-            .find(|x| x.origin() == leader.id())
+            .find(|x| x.origin() == leader)
             .cloned();
 
         if let Some(leader) = self.last_leader.as_ref() {
@@ -363,7 +443,7 @@ impl Proposer {
     /// (i) f+1 votes for the leader, (ii) 2f+1 nodes not voting for the leader,
     /// (iii) there is no leader to vote for. This is only relevant in partial synchrony.
     fn enough_votes(&self) -> bool {
-        if self.committee.leader(self.round + 1).id() == self.authority_id {
+        if self.is_next_round_leader() {
             return true;
         }
 
@@ -418,7 +498,7 @@ impl Proposer {
         let mut advance = true;
 
         let timer_start = Instant::now();
-        let max_delay_timer = sleep_until(timer_start + self.max_header_delay);
+        let max_delay_timer = sleep_until(timer_start + self.max_header_delay());
         let min_delay_timer = sleep_until(timer_start + self.min_header_delay);
 
         let header_resend_timeout = self
@@ -441,12 +521,14 @@ impl Proposer {
             // (ii) we have enough digests (header_num_of_batches_threshold) and we are on the happy path (we can vote for
             // the leader or the leader has enough votes to enable a commit). The latter condition only matters
             // in partially synchrony. We guarantee that no more than max_header_num_of_batches are included in
+            // Unless the timer expired, the proposal policy may also require a minimum payload.
             let enough_parents = !self.last_parents.is_empty();
             let enough_digests = self.digests.len() >= self.header_num_of_batches_threshold;
             let max_delay_timed_out = max_delay_timer.is_elapsed();
             let min_delay_timed_out = min_delay_timer.is_elapsed();
 
-            if (max_delay_timed_out || ((enough_digests || min_delay_timed_out) && advance))
+            if (max_delay_timed_out
+                || ((enough_digests || min_delay_timed_out) && advance && self.enough_payload()))
                 && enough_parents
             {
                 if max_delay_timed_out
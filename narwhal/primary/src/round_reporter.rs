@@ -0,0 +1,97 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use config::{AuthorityIdentifier, Committee, WorkerCache};
+use consensus::consensus::ConsensusRound;
+use mysten_metrics::{spawn_logged_monitored_task, spawn_monitored_task};
+use network::PrimaryToWorkerRpc;
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::debug;
+use types::{ConditionalBroadcastReceiver, WorkerRoundsMessage};
+
+#[cfg(test)]
+#[path = "tests/round_reporter_tests.rs"]
+pub mod round_reporter_tests;
+
+/// Reports to our workers the last committed round and the GC round of consensus, whenever they
+/// advance. The workers stop synchronizing the batches of the headers that are garbage collected,
+/// since these can never be committed anymore. The reports are best effort: a worker that misses
+/// one catches up on the next one.
+pub struct RoundReporter {
+    /// The id of this primary.
+    authority_id: AuthorityIdentifier,
+    /// The committee information.
+    committee: Committee,
+    /// The worker information cache.
+    worker_cache: WorkerCache,
+    /// The network to reach our workers.
+    network: anemo::Network,
+    /// The rounds of consensus.
+    rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+    /// Receiver for shutdown.
+    rx_shutdown: ConditionalBroadcastReceiver,
+}
+
+impl RoundReporter {
+    #[must_use]
+    pub fn spawn(
+        authority_id: AuthorityIdentifier,
+        committee: Committee,
+        worker_cache: WorkerCache,
+        network: anemo::Network,
+        rx_consensus_round_updates: watch::Receiver<ConsensusRound>,
+        rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        spawn_logged_monitored_task!(
+            Self {
+                authority_id,
+                committee,
+                worker_cache,
+                network,
+                rx_consensus_round_updates,
+                rx_shutdown,
+            }
+            .run(),
+            "RoundReporter"
+        )
+    }
+
+    async fn run(mut self) {
+        loop {
+            tokio::select! {
+                result = self.rx_consensus_round_updates.changed() => {
+                    // Consensus shut down.
+                    if result.is_err() {
+                        break;
+                    }
+                    let rounds = *self.rx_consensus_round_updates.borrow();
+                    self.report(WorkerRoundsMessage {
+                        committed_round: rounds.committed_round,
+                        gc_round: rounds.gc_round,
+                    });
+                },
+
+                _ = self.rx_shutdown.receiver.recv() => {
+                    return
+                }
+            }
+        }
+        let _ = self.rx_shutdown.receiver.recv().await;
+    }
+
+    fn report(&self, message: WorkerRoundsMessage) {
+        let Some(authority) = self.committee.authority(&self.authority_id) else {
+            return;
+        };
+        let Ok(workers) = self.worker_cache.our_workers(authority.protocol_key()) else {
+            return;
+        };
+        for worker in workers {
+            let network = self.network.clone();
+            spawn_monitored_task!(async move {
+                if let Err(e) = network.report_rounds(worker.name, message).await {
+                    debug!("Failed to report rounds {message:?} to our worker: {e}");
+                }
+            });
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2021, Facebook, Inc. and its affiliates
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use crate::batch_status::BatchStatusNotifier;
 use config::AuthorityIdentifier;
 use mysten_metrics::spawn_logged_monitored_task;
 use tap::TapFallible;
@@ -21,6 +22,8 @@ pub struct StateHandler {
     rx_shutdown: ConditionalBroadcastReceiver,
     /// A channel to update the committed rounds
     tx_committed_own_headers: Option<Sender<(Round, Vec<Round>)>>,
+    /// Reports the commit of our batches to our workers.
+    batch_status_notifier: BatchStatusNotifier,
 
     network: anemo::Network,
 }
@@ -32,6 +35,7 @@ impl StateHandler {
         rx_committed_certificates: Receiver<(Round, Vec<Certificate>)>,
         rx_shutdown: ConditionalBroadcastReceiver,
         tx_committed_own_headers: Option<Sender<(Round, Vec<Round>)>>,
+        batch_status_notifier: BatchStatusNotifier,
         network: anemo::Network,
     ) -> JoinHandle<()> {
         spawn_logged_monitored_task!(
@@ -41,6 +45,7 @@ impl StateHandler {
                     rx_committed_certificates,
                     rx_shutdown,
                     tx_committed_own_headers,
+                    batch_status_notifier,
                     network,
                 }
                 .run()
@@ -56,6 +61,7 @@ impl StateHandler {
             .iter()
             .filter_map(|cert| {
                 if cert.header().author() == self.authority_id {
+                    self.batch_status_notifier.committed(cert, commit_round);
                     Some(cert.header().round())
                 } else {
                     None
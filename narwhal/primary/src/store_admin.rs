@@ -0,0 +1,100 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use axum::{
+    extract::{Extension, Query},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use config::StoreMaintenanceParameters;
+use mysten_metrics::spawn_logged_monitored_task;
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+use storage::{CompactionTrigger, StoreMaintenance};
+use store::rocks::{ColumnFamilyStats, TypedStoreError};
+use tokio::task::JoinHandle;
+use tracing::error;
+use types::ConditionalBroadcastReceiver;
+
+#[derive(Debug, Deserialize)]
+struct CompactParams {
+    /// The column family to compact, all the maintained column families if not set.
+    cf: Option<String>,
+}
+
+/// Returns the routes of the store maintenance endpoints, to be served by the admin server.
+pub fn routes(maintenance: StoreMaintenance) -> Router {
+    Router::new()
+        .route("/store/stats", get(get_stats))
+        .route("/store/compact", post(compact))
+        .layer(Extension(maintenance))
+}
+
+async fn get_stats(
+    Extension(maintenance): Extension<StoreMaintenance>,
+) -> Result<Json<BTreeMap<&'static str, ColumnFamilyStats>>, (StatusCode, String)> {
+    maintenance
+        .stats()
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn compact(
+    Extension(maintenance): Extension<StoreMaintenance>,
+    Query(params): Query<CompactParams>,
+) -> Result<Json<Vec<&'static str>>, (StatusCode, String)> {
+    match maintenance
+        .compact(params.cf.as_deref(), CompactionTrigger::Manual)
+        .await
+    {
+        Ok(compacted) => Ok(Json(compacted)),
+        Err(TypedStoreError::UnregisteredColumn(cf)) => Err((
+            StatusCode::NOT_FOUND,
+            format!("Column family {cf} is not maintained"),
+        )),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// Periodically reports the statistics of the stores, and compacts them if configured.
+pub fn spawn_scheduled_maintenance(
+    maintenance: StoreMaintenance,
+    parameters: StoreMaintenanceParameters,
+    mut rx_shutdown: ConditionalBroadcastReceiver,
+) -> JoinHandle<()> {
+    spawn_logged_monitored_task!(
+        async move {
+            // A zero interval disables the reports of the statistics, and tokio panics on it.
+            let stats_enabled = !parameters.stats_interval.is_zero();
+            let mut stats_ticker =
+                tokio::time::interval(parameters.stats_interval.max(Duration::from_millis(1)));
+            // A zero interval disables the scheduled compactions.
+            let compactions_enabled = !parameters.compaction_interval.is_zero();
+            let mut compaction_ticker = tokio::time::interval_at(
+                tokio::time::Instant::now() + parameters.compaction_interval,
+                parameters.compaction_interval.max(Duration::from_millis(1)),
+            );
+            loop {
+                tokio::select! {
+                    _ = stats_ticker.tick(), if stats_enabled => {
+                        if let Err(e) = maintenance.stats() {
+                            error!("Failed to read the store statistics: {e:?}");
+                        }
+                    }
+
+                    _ = compaction_ticker.tick(), if compactions_enabled => {
+                        let result = maintenance.compact(None, CompactionTrigger::Scheduled).await;
+                        if let Err(e) = result {
+                            error!("Failed to compact the stores: {e:?}");
+                        }
+                    }
+
+                    _ = rx_shutdown.receiver.recv() => {
+                        return
+                    }
+                }
+            }
+        },
+        "StoreMaintenanceTask"
+    )
+}
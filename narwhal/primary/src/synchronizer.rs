@@ -8,10 +8,12 @@ use consensus::dag::Dag;
 use crypto::NetworkPublicKey;
 use fastcrypto::hash::Hash as _;
 use futures::{stream::FuturesOrdered, StreamExt};
+use mysten_common::memory_budget::{self, MemoryAccount, MemoryReservation};
 use mysten_common::sync::notify_once::NotifyOnce;
 use mysten_metrics::spawn_monitored_task;
 use network::{
     anemo_ext::{NetworkExt, WaitingPeer},
+    request_budget::is_rate_limited,
     RetryConfig,
 };
 use parking_lot::Mutex;
@@ -41,7 +43,13 @@ use types::{
     WorkerSynchronizeMessage,
 };
 
-use crate::{aggregators::CertificatesAggregator, metrics::PrimaryMetrics, CHANNEL_CAPACITY};
+use crate::{
+    aggregators::CertificatesAggregator,
+    certificate_verifier::CertificateVerifier,
+    metrics::PrimaryMetrics,
+    validation::{report_rejection, Validated},
+    CHANNEL_CAPACITY,
+};
 
 #[cfg(test)]
 #[path = "tests/synchronizer_tests.rs"]
@@ -51,6 +59,10 @@ pub mod synchronizer_tests;
 /// locally highest processed round.
 const NEW_CERTIFICATE_ROUND_LIMIT: Round = 100;
 
+/// The stage of the transactions' pipeline from our header being proposed to its certificate
+/// being formed.
+const CERTIFICATE_FORMED_STAGE: &str = "certificate_formed";
+
 struct Inner {
     /// The id of this primary.
     authority_id: AuthorityIdentifier,
@@ -91,6 +103,8 @@ struct Inner {
     dag: Option<Arc<Dag>>,
     /// Contains Synchronizer specific metrics among other Primary metrics.
     metrics: Arc<PrimaryMetrics>,
+    /// Verifies the certificates received from peers in batches.
+    certificate_verifier: CertificateVerifier,
     /// Background tasks synchronizing worker batches for processed certificates.
     batch_tasks: Mutex<JoinSet<DagResult<()>>>,
     /// Background tasks broadcasting newly formed certificates.
@@ -99,6 +113,8 @@ struct Inner {
     certificates_aggregators: Mutex<BTreeMap<Round, Box<CertificatesAggregator>>>,
     /// State for tracking suspended certificates and when they can be accepted.
     state: tokio::sync::Mutex<State>,
+    /// The account of the suspended certificates with the memory budget of the node.
+    suspended_memory: MemoryAccount,
 }
 
 impl Inner {
@@ -119,24 +135,62 @@ impl Inner {
             .map_err(|_| DagError::ShuttingDown)
     }
 
-    async fn accept_suspended_certificate(
+    /// Accepts the certificate, then the suspended certificates it unblocked, in causal order.
+    /// All of them are written to storage in a single batch, instead of one write each.
+    // State lock must be held when calling this function.
+    async fn accept_certificates(
         &self,
         lock: &MutexGuard<'_, State>,
-        suspended: SuspendedCertificate,
+        certificate: Option<Certificate>,
+        suspended: Vec<SuspendedCertificate>,
     ) -> DagResult<()> {
-        self.accept_certificate_internal(lock, suspended.certificate.clone())
-            .await?;
-        // Notify waiters that the certificate is no longer suspended.
-        // Must be after certificate acceptance.
-        // It is ok if there is no longer any waiter.
-        suspended
-            .notify
-            .notify()
-            .expect("Suspended certificate should be notified once.");
+        let certificates: Vec<_> = certificate
+            .into_iter()
+            .chain(suspended.iter().map(|s| s.certificate.clone()))
+            .collect();
+
+        // TODO: remove this validation later to reduce rocksdb access.
+        let gc_round = self.gc_round.load(Ordering::Acquire);
+        let mut batched = HashSet::with_capacity(certificates.len());
+        for certificate in &certificates {
+            if certificate.round() > gc_round + 1 {
+                for digest in certificate.header().parents() {
+                    // A parent may be one of the certificates before it in the batch.
+                    if !batched.contains(digest)
+                        && !self.certificate_store.contains(digest).unwrap()
+                    {
+                        panic!("Parent {digest:?} not found for {certificate:?}!");
+                    }
+                }
+            }
+            batched.insert(certificate.digest());
+        }
+
+        // Store the certificates and make them available as parents to other certificates.
+        self.certificate_store
+            .write_all(certificates.clone())
+            .expect("Writing certificates to storage cannot fail!");
+
+        // From this point, the certificates must be sent to consensus or Narwhal needs to
+        // shutdown, to avoid inconsistencies in certificate store and consensus dag.
+        for certificate in certificates {
+            self.accept_certificate_internal(lock, certificate).await?;
+        }
+
+        for suspended in suspended {
+            // Notify waiters that the certificate is no longer suspended.
+            // Must be after certificate acceptance.
+            // It is ok if there is no longer any waiter.
+            suspended
+                .notify
+                .notify()
+                .expect("Suspended certificate should be notified once.");
+        }
         Ok(())
     }
 
-    // State lock must be held when calling this function.
+    // State lock must be held when calling this function, and the certificate must have been
+    // stored.
     async fn accept_certificate_internal(
         &self,
         _lock: &MutexGuard<'_, State>,
@@ -144,23 +198,6 @@ impl Inner {
     ) -> DagResult<()> {
         let digest = certificate.digest();
 
-        // TODO: remove this validation later to reduce rocksdb access.
-        if certificate.round() > self.gc_round.load(Ordering::Acquire) + 1 {
-            for digest in certificate.header().parents() {
-                if !self.certificate_store.contains(digest).unwrap() {
-                    panic!("Parent {digest:?} not found for {certificate:?}!");
-                }
-            }
-        }
-
-        // Store the certificate and make it available as parent to other certificates.
-        self.certificate_store
-            .write(certificate.clone())
-            .expect("Writing certificate to storage cannot fail!");
-
-        // From this point, the certificate must be sent to consensus or Narwhal needs to shutdown,
-        // to avoid inconsistencies in certificate store and consensus dag.
-
         // Update metrics for accepted certificates.
         let highest_processed_round = self
             .highest_processed_round
@@ -286,6 +323,8 @@ impl Synchronizer {
             broadcast::channel(CHANNEL_CAPACITY);
         let (tx_certificate_acceptor, mut rx_certificate_acceptor) =
             mpsc::channel(CHANNEL_CAPACITY);
+        let certificate_verifier =
+            CertificateVerifier::new(committee.clone(), worker_cache.clone(), metrics.clone());
         let inner = Arc::new(Inner {
             authority_id,
             committee: committee.clone(),
@@ -305,10 +344,12 @@ impl Synchronizer {
             genesis,
             dag,
             metrics,
+            certificate_verifier,
             batch_tasks: Mutex::new(JoinSet::new()),
             certificate_senders: Mutex::new(JoinSet::new()),
             certificates_aggregators: Mutex::new(BTreeMap::new()),
             state: tokio::sync::Mutex::new(State::default()),
+            suspended_memory: memory_budget::global().register("primary-suspended-certificates"),
         });
 
         // Start a task to recover parent certificates for proposer.
@@ -358,13 +399,14 @@ impl Synchronizer {
                         suspended_cert.certificate.digest(),
                     );
                     // Iteration must be in causal order.
-                    for suspended in iter::once(suspended_cert).chain(suspended_certs.into_iter()) {
-                        match inner.accept_suspended_certificate(&state, suspended).await {
-                            Ok(()) => {}
-                            Err(DagError::ShuttingDown) => return,
-                            Err(e) => {
-                                panic!("Unexpected error accepting certificate during GC! {e}")
-                            }
+                    let suspended = iter::once(suspended_cert)
+                        .chain(suspended_certs.into_iter())
+                        .collect();
+                    match inner.accept_certificates(&state, None, suspended).await {
+                        Ok(()) => {}
+                        Err(DagError::ShuttingDown) => return,
+                        Err(e) => {
+                            panic!("Unexpected error accepting certificate during GC! {e}")
                         }
                     }
                 }
@@ -514,6 +556,11 @@ impl Synchronizer {
             .metrics
             .header_to_certificate_latency
             .observe(header_to_certificate_duration);
+        self.inner
+            .metrics
+            .primary_pipeline_stage_latency
+            .with_label_values(&[CERTIFICATE_FORMED_STAGE])
+            .observe(header_to_certificate_duration);
 
         // NOTE: This log entry is used to compute performance.
         debug!(
@@ -538,6 +585,33 @@ impl Synchronizer {
     /// Checks if the certificate is valid and can potentially be accepted into the DAG.
     // TODO: produce a different type after sanitize, e.g. VerifiedCertificate.
     pub fn sanitize_certificate(&self, certificate: &Certificate) -> DagResult<()> {
+        self.check_certificate_round(certificate)?;
+        // Verify the certificate (and the embedded header).
+        certificate
+            .verify(&self.inner.committee, &self.inner.worker_cache)
+            .map_err(DagError::from)
+    }
+
+    /// Checks if the certificates are all valid, like `sanitize_certificate()`, with a single
+    /// verification of their signatures.
+    pub fn sanitize_certificates(&self, certificates: &[Certificate]) -> DagResult<()> {
+        for certificate in certificates {
+            self.check_certificate_round(certificate)?;
+        }
+        let certificates: Vec<_> = certificates.iter().collect();
+        Certificate::verify_batch(
+            &certificates,
+            &self.inner.committee,
+            &self.inner.worker_cache,
+        )
+        .into_iter()
+        .collect()
+    }
+
+    /// Checks that the certificate is of the current epoch and above the garbage collection
+    /// round, which filters out the certificates that are harmless to drop before verifying
+    /// their signatures.
+    fn check_certificate_round(&self, certificate: &Certificate) -> DagResult<()> {
         ensure!(
             self.inner.committee.epoch() == certificate.epoch(),
             DagError::InvalidEpoch {
@@ -551,10 +625,7 @@ impl Synchronizer {
             gc_round < certificate.round(),
             DagError::TooOld(certificate.digest().into(), certificate.round(), gc_round)
         );
-        // Verify the certificate (and the embedded header).
-        certificate
-            .verify(&self.inner.committee, &self.inner.worker_cache)
-            .map_err(DagError::from)
+        Ok(())
     }
 
     async fn process_certificate_internal(
@@ -584,7 +655,15 @@ impl Synchronizer {
             }
         }
         if sanitize {
-            self.sanitize_certificate(&certificate)?;
+            let report = |e: DagError| {
+                report_rejection(&self.inner.metrics, Validated::Certificate, digest, e)
+            };
+            self.check_certificate_round(&certificate).map_err(report)?;
+            self.inner
+                .certificate_verifier
+                .verify(certificate.clone())
+                .await
+                .map_err(report)?;
         }
 
         debug!(
@@ -706,14 +785,23 @@ impl Synchronizer {
                     "Processing certificate {:?} suspended: missing ancestors",
                     certificate
                 );
+                // There is no upper round limit to suspended certificates, which speeds up
+                // catching up, but no certificate is suspended while the memory budget of the
+                // node is exceeded. The refused certificates are fetched again later.
+                let size = bcs::serialized_size(&certificate).unwrap_or_default();
+                let Some(memory) = inner.suspended_memory.try_reserve(size) else {
+                    debug!(
+                        "Refusing to suspend certificate {:?}: memory budget exceeded",
+                        certificate.digest()
+                    );
+                    return Err(DagError::MemoryBudgetExceeded);
+                };
                 inner
                     .metrics
                     .certificates_suspended
                     .with_label_values(&["missing_parents"])
                     .inc();
-                // There is no upper round limit to suspended certificates. Currently there is no
-                // memory usage issue and this will speed up catching up. But we can revisit later.
-                let notify = state.insert(certificate, missing_parents, !early_suspend);
+                let notify = state.insert(certificate, missing_parents, !early_suspend, memory);
                 inner
                     .metrics
                     .certificates_currently_suspended
@@ -725,13 +813,8 @@ impl Synchronizer {
         let suspended_certs = state.accept_children(certificate.round(), certificate.digest());
         // Accept in causal order.
         inner
-            .accept_certificate_internal(&state, certificate)
+            .accept_certificates(&state, Some(certificate), suspended_certs)
             .await?;
-        for suspended in suspended_certs {
-            inner
-                .accept_suspended_certificate(&state, suspended)
-                .await?;
-        }
 
         inner
             .metrics
@@ -798,12 +881,15 @@ impl Synchronizer {
                         Ok(_) => {
                             0
                         },
-                        Err(_) => {
+                        Err(status) => {
                             if requests.is_empty() {
                                 // Retry broadcasting the latest certificate, to help the network stay alive.
                                 let request = Request::new(SendCertificateRequest { certificate: cert.clone() }).with_timeout(PUSH_TIMEOUT);
                                 requests.push_back(send_certificate(client.clone(), request, cert));
                                 min(backoff_multiplier * 2 + 1, MAX_BACKOFF_MULTIPLIER)
+                            } else if is_rate_limited(&status) {
+                                // The peer is over budget, so the next certificates wait as well.
+                                min(backoff_multiplier * 2 + 1, MAX_BACKOFF_MULTIPLIER)
                             } else {
                                 // TODO: add backoff and retries for transient & retriable errors.
                                 0
@@ -832,16 +918,17 @@ impl Synchronizer {
             .await
     }
 
+    /// Synchronizes the batches of a certified header, which only need their digest verified.
     // TODO: Add batching support to synchronizer and use this call from executor.
-    // pub async fn sync_certificate_batches(
-    //     &self,
-    //     header: &Header,
-    //     network: anemo::Network,
-    //     max_age: Round,
-    // ) -> DagResult<()> {
-    //     Synchronizer::sync_batches_internal(self.inner.clone(), header, network, max_age, true)
-    //         .await
-    // }
+    pub async fn sync_certificate_batches(
+        &self,
+        header: &Header,
+        network: anemo::Network,
+        max_age: Round,
+    ) -> DagResult<()> {
+        Synchronizer::sync_batches_internal(self.inner.clone(), header, network, max_age, true)
+            .await
+    }
 
     async fn sync_batches_internal(
         inner: Arc<Inner>,
@@ -917,6 +1004,7 @@ impl Synchronizer {
                     digests: digests.clone(),
                     target: header.author(),
                     is_certified,
+                    round: Some(header.round()),
                 };
                 let peer = network.waiting_peer(anemo::PeerId(worker_name.0.to_bytes()));
                 let mut client = PrimaryToWorkerClient::new(peer);
@@ -926,12 +1014,10 @@ impl Synchronizer {
                         backoff::Error::transient(DagError::NetworkError(format!("{e:?}")))
                     });
                     if result.is_ok() {
-                        for digest in &digests {
-                            inner
-                                .payload_store
-                                .write(digest, &worker_id)
-                                .map_err(|e| backoff::Error::permanent(DagError::StoreError(e)))?
-                        }
+                        inner
+                            .payload_store
+                            .write_all(digests.iter().map(|digest| (*digest, worker_id)))
+                            .map_err(|e| backoff::Error::permanent(DagError::StoreError(e)))?
                     }
                     result
                 }
@@ -994,6 +1080,34 @@ impl Synchronizer {
         Ok((parents, missing))
     }
 
+    /// Highest round of certificate accepted into the certificate store.
+    pub fn highest_processed_round(&self) -> Round {
+        self.inner.highest_processed_round.load(Ordering::Acquire)
+    }
+
+    /// Highest round of verified certificate that has been received.
+    pub fn highest_received_round(&self) -> Round {
+        self.inner.highest_received_round.load(Ordering::Acquire)
+    }
+
+    /// Returns the certificates suspended until their missing parents are accepted, ordered by
+    /// round.
+    pub async fn suspended_certificates(&self) -> Vec<SuspendedCertificateInfo> {
+        let state = self.inner.state.lock().await;
+        let mut suspended: Vec<_> = state
+            .suspended
+            .iter()
+            .map(|(digest, suspended)| SuspendedCertificateInfo {
+                digest: *digest,
+                round: suspended.certificate.round(),
+                origin: suspended.certificate.origin(),
+                missing_parents: suspended.missing_parents.iter().cloned().collect(),
+            })
+            .collect();
+        suspended.sort_by_key(|info| (info.round, info.origin));
+        suspended
+    }
+
     /// Tries to get all missing parents of the certificate. If there is any, sends the
     /// certificate to `CertificateFetcher` which will trigger range fetching of missing
     /// certificates.
@@ -1006,12 +1120,23 @@ impl Synchronizer {
     }
 }
 
+/// Describes a suspended certificate, for introspection.
+#[derive(Clone, Debug)]
+pub struct SuspendedCertificateInfo {
+    pub digest: CertificateDigest,
+    pub round: Round,
+    pub origin: AuthorityIdentifier,
+    pub missing_parents: Vec<CertificateDigest>,
+}
+
 /// Holds information for a suspended certificate. The certificate can be accepted into the DAG
 /// once `missing_parents` become empty.
 struct SuspendedCertificate {
     certificate: Certificate,
     missing_parents: HashSet<CertificateDigest>,
     notify: AcceptNotification,
+    // The memory of the certificate, reserved from the budget while it is suspended.
+    _memory: MemoryReservation,
 }
 
 impl Drop for SuspendedCertificate {
@@ -1061,6 +1186,7 @@ impl State {
         certificate: Certificate,
         missing_parents: Vec<CertificateDigest>,
         allow_reinsert: bool,
+        memory: MemoryReservation,
     ) -> AcceptNotification {
         let digest = certificate.digest();
         let missing_round = certificate.round() - 1;
@@ -1084,6 +1210,7 @@ impl State {
                     certificate,
                     missing_parents: missing_parents_map,
                     notify: notify.clone(),
+                    _memory: memory,
                 }
             )
             .is_none());
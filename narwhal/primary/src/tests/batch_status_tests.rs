@@ -0,0 +1,89 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use anemo::PeerId;
+use crypto::traits::KeyPair;
+use test_utils::CommitteeFixture;
+use tokio::sync::mpsc;
+use types::{Header, MockPrimaryToWorker, PrimaryToWorkerServer};
+
+#[tokio::test]
+async fn test_report_own_batches_to_their_workers() {
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let committee = fixture.committee();
+    let primary = fixture.authorities().next().unwrap();
+    let other = fixture.authorities().nth(1).unwrap();
+    let network = test_utils::test_network(primary.network_keypair(), primary.address());
+
+    // A mock of our worker 0, forwarding the reports it receives.
+    let (tx_reports, mut rx_reports) = mpsc::unbounded_channel();
+    let worker = primary.worker(0);
+    let mut mock_server = MockPrimaryToWorker::new();
+    mock_server
+        .expect_report_batch_status()
+        .returning(move |request| {
+            tx_reports.send(request.into_body()).unwrap();
+            Ok(anemo::Response::new(()))
+        });
+    let routes = anemo::Router::new().add_rpc_service(PrimaryToWorkerServer::new(mock_server));
+    let _worker_network = worker.new_network(routes);
+    network
+        .connect_with_peer_id(
+            worker.info().worker_address.to_anemo_address().unwrap(),
+            PeerId(worker.keypair().public().0.to_bytes()),
+        )
+        .await
+        .unwrap();
+
+    let notifier = BatchStatusNotifier::new(
+        primary.id(),
+        committee.clone(),
+        fixture.worker_cache(),
+        network,
+    );
+
+    let batch = test_utils::fixture_batch_with_transactions(10);
+    let header = Header::V1(
+        primary
+            .header_builder(&committee)
+            .with_payload_batch(batch.clone(), 0, 0)
+            .build()
+            .unwrap(),
+    );
+    let certificate = fixture.certificate(&header);
+
+    notifier.certified(&certificate);
+    let report = rx_reports.recv().await.unwrap();
+    assert_eq!(
+        report,
+        WorkerBatchStatusMessage {
+            digests: vec![batch.digest()],
+            status: BatchStatus::Certified {
+                certificate: certificate.digest(),
+                round: certificate.round(),
+            },
+        }
+    );
+
+    // The certificates of the other authorities are not reported.
+    let other_header = Header::V1(
+        other
+            .header_builder(&committee)
+            .with_payload_batch(test_utils::fixture_batch_with_transactions(10), 0, 0)
+            .build()
+            .unwrap(),
+    );
+    notifier.committed(&fixture.certificate(&other_header), 2);
+
+    notifier.committed(&certificate, 2);
+    let report = rx_reports.recv().await.unwrap();
+    assert_eq!(
+        report.status,
+        BatchStatus::Committed {
+            certificate: certificate.digest(),
+            round: certificate.round(),
+            commit_round: 2,
+        }
+    );
+    assert!(rx_reports.try_recv().is_err());
+}
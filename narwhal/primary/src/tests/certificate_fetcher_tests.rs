@@ -2,9 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 use crate::primary::NUM_SHUTDOWN_RECEIVERS;
 use crate::{
-    certificate_fetcher::CertificateFetcher, metrics::PrimaryMetrics, synchronizer::Synchronizer,
+    certificate_fetcher::{order_peers, CertificateFetcher},
+    metrics::PrimaryMetrics,
+    synchronizer::Synchronizer,
 };
 use anemo::async_trait;
+use anemo::types::response::StatusCode;
 use anyhow::Result;
 use config::{AuthorityIdentifier, Epoch, WorkerId};
 use fastcrypto::{hash::Hash, traits::KeyPair};
@@ -12,7 +15,11 @@ use indexmap::IndexMap;
 use itertools::Itertools;
 use once_cell::sync::OnceCell;
 use prometheus::Registry;
-use std::{collections::BTreeSet, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+    time::Duration,
+};
 use storage::CertificateStore;
 use storage::NodeStorage;
 use tokio::sync::oneshot;
@@ -27,11 +34,13 @@ use tokio::{
     time::sleep,
 };
 use types::{
-    BatchDigest, Certificate, CertificateAPI, CertificateDigest, FetchCertificatesRequest,
-    FetchCertificatesResponse, GetCertificatesRequest, GetCertificatesResponse, Header, HeaderAPI,
-    HeaderDigest, Metadata, PayloadAvailabilityRequest, PayloadAvailabilityResponse,
-    PreSubscribedBroadcastSender, PrimaryToPrimary, PrimaryToPrimaryServer, RequestVoteRequest,
-    RequestVoteResponse, Round, SendCertificateRequest, SendCertificateResponse,
+    AuthorityCertificatesSummary, BatchDigest, Certificate, CertificateAPI, CertificateDigest,
+    FetchCertificatesRequest, FetchCertificatesResponse, FetchCertificatesV2Request,
+    FetchCertificatesV2Response, GetCertificatesRequest, GetCertificatesResponse,
+    GetDagSnapshotRequest, GetDagSnapshotResponse, Header, HeaderAPI, HeaderDigest, Metadata,
+    PayloadAvailabilityRequest, PayloadAvailabilityResponse, PreSubscribedBroadcastSender,
+    PrimaryToPrimary, PrimaryToPrimaryServer, RequestVoteRequest, RequestVoteResponse, Round,
+    SendCertificateRequest, SendCertificateResponse,
 };
 
 pub struct NetworkProxy {
@@ -75,12 +84,28 @@ impl PrimaryToPrimary for NetworkProxy {
         ))
     }
 
+    // The proxy stands for a peer which does not serve the route yet, so that the fetcher falls
+    // back to fetch_certificates.
+    async fn fetch_certificates_v2(
+        &self,
+        _request: anemo::Request<FetchCertificatesV2Request>,
+    ) -> Result<anemo::Response<FetchCertificatesV2Response>, anemo::rpc::Status> {
+        Err(anemo::rpc::Status::new(StatusCode::NotFound))
+    }
+
     async fn get_payload_availability(
         &self,
         _request: anemo::Request<PayloadAvailabilityRequest>,
     ) -> Result<anemo::Response<PayloadAvailabilityResponse>, anemo::rpc::Status> {
         unimplemented!()
     }
+
+    async fn get_dag_snapshot(
+        &self,
+        _request: anemo::Request<GetDagSnapshotRequest>,
+    ) -> Result<anemo::Response<GetDagSnapshotResponse>, anemo::rpc::Status> {
+        unimplemented!()
+    }
 }
 
 async fn verify_certificates_in_store(
@@ -383,3 +408,44 @@ async fn fetch_certificates_basic() {
     sleep(Duration::from_secs(5)).await;
     verify_certificates_not_in_store(&certificate_store, &certificates[num_written..]);
 }
+
+#[test]
+fn order_peers_by_summary() {
+    let fixture = CommitteeFixture::builder().build();
+    let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+    let peers: Vec<_> = fixture
+        .authorities()
+        .map(|a| a.network_keypair().public().clone())
+        .collect();
+    let targets = BTreeMap::from([(ids[0], 10), (ids[1], 10)]);
+    let summary = |highest_rounds: [Round; 2]| {
+        ids[..2]
+            .iter()
+            .zip(highest_rounds)
+            .map(|(origin, highest_round)| AuthorityCertificatesSummary {
+                origin: *origin,
+                lowest_round: 1,
+                highest_round,
+                num_certificates: highest_round,
+            })
+            .collect_vec()
+    };
+
+    // Peer 0 has all the targets, peer 1 some of them and peer 2 none. Peer 3 is unknown.
+    let peer_summaries = HashMap::from([
+        (anemo::PeerId(peers[0].0.to_bytes()), summary([10, 12])),
+        (anemo::PeerId(peers[1].0.to_bytes()), summary([10, 5])),
+        (anemo::PeerId(peers[2].0.to_bytes()), summary([3, 5])),
+    ]);
+
+    let ordered = order_peers(peers.clone(), &targets, &peer_summaries);
+    assert_eq!(
+        ordered,
+        vec![
+            peers[0].clone(),
+            peers[3].clone(),
+            peers[1].clone(),
+            peers[2].clone()
+        ]
+    );
+}
@@ -0,0 +1,75 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use futures::future::join_all;
+use prometheus::Registry;
+use test_utils::CommitteeFixture;
+use types::{Header, VoteAPI};
+
+/// Certifies the header with the votes cast for `signed`, which are only valid if it is the
+/// header itself.
+fn certificate(fixture: &CommitteeFixture, header: Header, signed: &Header) -> Certificate {
+    let signatures = fixture
+        .authorities()
+        .take(3)
+        .map(|authority| {
+            let vote = authority.vote(signed);
+            (vote.author(), vote.signature().clone())
+        })
+        .collect();
+    Certificate::new_unverified(&fixture.committee(), header, signatures).unwrap()
+}
+
+#[tokio::test]
+async fn verify_certificates_in_batches() {
+    let fixture = CommitteeFixture::builder().build();
+    let metrics = Arc::new(PrimaryMetrics::new(&Registry::new()));
+    let verifier =
+        CertificateVerifier::new(fixture.committee(), fixture.worker_cache(), metrics.clone());
+
+    let headers = fixture.headers();
+    let mut certificates: Vec<_> = headers
+        .iter()
+        .map(|header| certificate(&fixture, header.clone(), header))
+        .collect();
+    // One of the certificates is signed over another header.
+    certificates[1] = certificate(&fixture, headers[1].clone(), &headers[0]);
+
+    let results = join_all(
+        certificates
+            .into_iter()
+            .map(|certificate| verifier.verify(certificate)),
+    )
+    .await;
+    for (i, result) in results.into_iter().enumerate() {
+        if i == 1 {
+            assert!(matches!(result, Err(DagError::InvalidSignature)));
+        } else {
+            assert!(result.is_ok());
+        }
+    }
+    assert_eq!(metrics.certificate_verification_batch_failures.get(), 1);
+}
+
+#[test]
+fn verify_batch_identifies_invalid_certificates() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let worker_cache = fixture.worker_cache();
+    let headers = fixture.headers();
+
+    let valid = certificate(&fixture, headers[0].clone(), &headers[0]);
+    let invalid = certificate(&fixture, headers[1].clone(), &headers[0]);
+    let genesis = Certificate::genesis(&committee).pop().unwrap();
+
+    let results = Certificate::verify_batch(&[&valid, &genesis], &committee, &worker_cache);
+    assert!(results.iter().all(|result| result.is_ok()));
+
+    let results =
+        Certificate::verify_batch(&[&valid, &invalid, &genesis], &committee, &worker_cache);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(DagError::InvalidSignature)));
+    assert!(results[2].is_ok());
+
+    assert!(Certificate::verify_batch(&[], &committee, &worker_cache).is_empty());
+}
@@ -124,7 +124,8 @@ async fn propose_header() {
         tx_shutdown.subscribe(),
         rx_headers,
         metrics.clone(),
-        network,
+        network.clone(),
+        BatchStatusNotifier::new(id, committee.clone(), worker_cache.clone(), network.clone()),
     );
 
     // Propose header and ensure that a certificate is formed by pulling it out of the
@@ -222,7 +223,13 @@ async fn propose_header_failure() {
         tx_shutdown.subscribe(),
         rx_headers,
         metrics.clone(),
-        network,
+        network.clone(),
+        BatchStatusNotifier::new(
+            authority_id,
+            committee.clone(),
+            worker_cache.clone(),
+            network.clone(),
+        ),
     );
 
     // Propose header and verify we get no certificate back.
@@ -352,7 +359,8 @@ async fn run_vote_aggregator_with_param(
         tx_shutdown.subscribe(),
         rx_headers,
         metrics.clone(),
-        network,
+        network.clone(),
+        BatchStatusNotifier::new(id, committee.clone(), worker_cache.clone(), network.clone()),
     );
 
     // Send a proposed header.
@@ -372,6 +380,45 @@ async fn run_vote_aggregator_with_param(
         );
     }
 }
+#[test]
+fn votes_aggregator_removes_invalid_votes() {
+    let fixture = CommitteeFixture::builder()
+        .committee_size(NonZeroUsize::new(4).unwrap())
+        .build();
+    let committee = fixture.committee();
+    let header = fixture.authorities().next().unwrap().header(&committee);
+    let metrics = Arc::new(PrimaryMetrics::new(&Registry::new()));
+    let mut aggregator = VotesAggregator::new(metrics.clone());
+
+    // The votes are only verified once they reach a quorum.
+    let mut authorities = fixture.authorities();
+    let bad_key: DefinedKeyPair = DefinedKeyPair::generate(&mut StdRng::from_seed([0; 32]));
+    let bad_vote = Vote::new_with_signer(&header, &authorities.next().unwrap().id(), &bad_key);
+    assert!(!aggregator.append(bad_vote, &committee).unwrap());
+    for authority in authorities.by_ref().take(2) {
+        let vote = Vote::new_with_signer(&header, &authority.id(), authority.keypair());
+        assert!(aggregator.append(vote, &committee).unwrap());
+    }
+    assert!(aggregator
+        .try_certify(&committee, &header)
+        .unwrap()
+        .is_none());
+    assert_eq!(metrics.vote_verification_failures.get(), 1);
+
+    // The remaining valid votes form a certificate with the next one.
+    let authority = authorities.next().unwrap();
+    let vote = Vote::new_with_signer(&header, &authority.id(), authority.keypair());
+    assert!(aggregator.append(vote, &committee).unwrap());
+    let certificate = aggregator
+        .try_certify(&committee, &header)
+        .unwrap()
+        .unwrap();
+    assert_eq!(certificate.header().digest(), header.digest());
+    assert!(certificate
+        .verify(&committee, &fixture.worker_cache())
+        .is_ok());
+}
+
 #[tokio::test]
 async fn shutdown_core() {
     let fixture = CommitteeFixture::builder().build();
@@ -437,6 +484,7 @@ async fn shutdown_core() {
         rx_headers,
         metrics.clone(),
         network.clone(),
+        BatchStatusNotifier::new(id, committee.clone(), worker_cache.clone(), network.clone()),
     );
 
     // Shutdown the core.
@@ -8,7 +8,7 @@ use storage::{CertificateStore, CertificateStoreCache, HeaderStore, PayloadStore
 use store::{reopen, rocks, rocks::DBMap, rocks::ReadWriteOptions};
 use test_utils::{
     temp_dir, PrimaryToWorkerMockServer, CERTIFICATES_CF, CERTIFICATE_DIGEST_BY_ORIGIN_CF,
-    CERTIFICATE_DIGEST_BY_ROUND_CF, HEADERS_CF, PAYLOAD_CF,
+    CERTIFICATE_DIGEST_BY_ROUND_CF, HEADERS_CF, HEADER_DIGEST_BY_AUTHOR_CF, PAYLOAD_CF,
 };
 use types::{
     BatchDigest, Certificate, CertificateDigest, Header, HeaderDigest, Round,
@@ -27,6 +27,7 @@ pub fn create_db_stores() -> (HeaderStore, CertificateStore, PayloadStore) {
         MetricConf::default(),
         &[
             HEADERS_CF,
+            HEADER_DIGEST_BY_AUTHOR_CF,
             CERTIFICATES_CF,
             CERTIFICATE_DIGEST_BY_ROUND_CF,
             CERTIFICATE_DIGEST_BY_ORIGIN_CF,
@@ -37,19 +38,21 @@ pub fn create_db_stores() -> (HeaderStore, CertificateStore, PayloadStore) {
 
     let (
         header_map,
+        header_digest_by_author_map,
         certificate_map,
         certificate_digest_by_round_map,
         certificate_digest_by_origin_map,
         payload_map,
     ) = reopen!(&rocksdb,
         HEADERS_CF;<HeaderDigest, Header>,
+        HEADER_DIGEST_BY_AUTHOR_CF;<(AuthorityIdentifier, Round), HeaderDigest>,
         CERTIFICATES_CF;<CertificateDigest, Certificate>,
         CERTIFICATE_DIGEST_BY_ROUND_CF;<(Round, AuthorityIdentifier), CertificateDigest>,
         CERTIFICATE_DIGEST_BY_ORIGIN_CF;<(AuthorityIdentifier, Round), CertificateDigest>,
         PAYLOAD_CF;<(BatchDigest, WorkerId), PayloadToken>);
 
     (
-        HeaderStore::new(header_map),
+        HeaderStore::new(header_map, header_digest_by_author_map),
         CertificateStore::new(
             certificate_map,
             certificate_digest_by_round_map,
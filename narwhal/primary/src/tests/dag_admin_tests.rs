@@ -0,0 +1,93 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::common::create_db_stores;
+use fastcrypto::hash::Hash;
+use std::collections::BTreeSet;
+use test_utils::{make_optimal_certificates, CommitteeFixture};
+use types::Certificate;
+
+#[test]
+fn round_statuses_report_quorums() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+    let (_, certificate_store, _) = create_db_stores();
+
+    // All the authorities certify round 1, only two of them certify round 2.
+    let genesis = Certificate::genesis(&committee)
+        .iter()
+        .map(|c| c.digest())
+        .collect::<BTreeSet<_>>();
+    let (round_1, parents) = make_optimal_certificates(&committee, 1..=1, &genesis, &ids);
+    let (round_2, _) = make_optimal_certificates(&committee, 2..=2, &parents, &ids[..2]);
+    certificate_store
+        .write_all(round_1.into_iter().chain(round_2))
+        .unwrap();
+
+    let origins = certificate_store.origins_after_round(1).unwrap();
+    let statuses = round_statuses(&committee, origins);
+
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[0].round, 1);
+    assert_eq!(statuses[0].num_certificates, 4);
+    assert_eq!(statuses[0].stake, committee.total_stake());
+    assert!(statuses[0].has_quorum);
+    assert_eq!(statuses[1].round, 2);
+    assert_eq!(statuses[1].num_certificates, 2);
+    assert!(!statuses[1].has_quorum);
+    let mut expected: Vec<_> = ids[..2].iter().map(ToString::to_string).collect();
+    expected.sort();
+    let mut origins = statuses[1].origins.clone();
+    origins.sort();
+    assert_eq!(origins, expected);
+}
+
+#[test]
+fn dag_export_marks_commits() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+
+    let genesis = Certificate::genesis(&committee)
+        .iter()
+        .map(|c| c.digest())
+        .collect::<BTreeSet<_>>();
+    let (certificates, _) = make_optimal_certificates(&committee, 1..=2, &genesis, &ids);
+    let certificates: Vec<_> = certificates.into_iter().collect();
+
+    // The first certificate of round 2 commits the certificates of round 1 and itself.
+    let leader = certificates
+        .iter()
+        .find(|c| c.round() == 2)
+        .unwrap()
+        .digest();
+    let sub_dag = CommittedSubDagShell {
+        certificates: certificates
+            .iter()
+            .filter(|c| c.round() == 1)
+            .map(|c| c.digest())
+            .chain(std::iter::once(leader))
+            .collect(),
+        leader,
+        leader_round: 2,
+        sub_dag_index: 1,
+        reputation_score: Default::default(),
+        commit_timestamp: 0,
+    };
+    let export = DagExport::new(1, 2, &certificates, &[sub_dag]);
+
+    assert_eq!(export.nodes.len(), 8);
+    for node in &export.nodes {
+        let committed = node.round == 1 || node.digest == leader.to_string();
+        assert_eq!(node.sub_dag_index.is_some(), committed);
+        assert_eq!(node.is_leader, node.digest == leader.to_string());
+        assert_eq!(node.parents.len(), 4);
+    }
+
+    let dot = export.to_dot();
+    assert!(dot.starts_with("digraph dag {"));
+    assert_eq!(dot.matches(" -> ").count(), 8 * 4);
+    assert_eq!(dot.matches("style=filled").count(), 1);
+    assert_eq!(dot.matches("style=bold").count(), 4);
+}
@@ -0,0 +1,278 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::common::create_db_stores;
+use consensus::leader_schedule::LeaderSchedule;
+use crypto::signer::SignatureService;
+use crypto::traits::KeyPair as _;
+use fastcrypto::hash::Hash;
+use std::{collections::BTreeSet, num::NonZeroUsize};
+use test_utils::{
+    make_consensus_store, make_optimal_signed_certificates, temp_dir, CommitteeFixture,
+};
+use types::{Certificate, CommittedSubDagShell, ReputationScores};
+
+const GC_DEPTH: Round = 2;
+
+/// Stores the certificates of rounds 1 to 6, of which round 4 is committed.
+fn populate(
+    fixture: &CommitteeFixture,
+    certificate_store: &CertificateStore,
+    consensus_store: &ConsensusStore,
+) -> Vec<Certificate> {
+    let committee = fixture.committee();
+    let genesis: BTreeSet<_> = Certificate::genesis(&committee)
+        .iter()
+        .map(|c| c.digest())
+        .collect();
+    let keys: Vec<_> = fixture
+        .authorities()
+        .map(|a| (a.id(), a.keypair().copy()))
+        .collect();
+    let (certificates, _) = make_optimal_signed_certificates(1..=6, &genesis, &committee, &keys);
+    let certificates: Vec<_> = certificates.into_iter().collect();
+    certificate_store.write_all(certificates.clone()).unwrap();
+
+    let leader = certificates.iter().find(|c| c.round() == 4).unwrap();
+    let sub_dag = CommittedSubDagShell {
+        certificates: certificates
+            .iter()
+            .filter(|c| c.round() == 3)
+            .map(|c| c.digest())
+            .chain(iter::once(leader.digest()))
+            .collect(),
+        leader: leader.digest(),
+        leader_round: 4,
+        sub_dag_index: 1,
+        reputation_score: ReputationScores::new(&committee),
+        commit_timestamp: 0,
+    };
+    let last_committed = fixture.authorities().map(|a| (a.id(), 4)).collect();
+    consensus_store
+        .write_snapshot_state(&last_committed, Some(&sub_dag), None)
+        .unwrap();
+    certificates
+}
+
+async fn snapshot_of(
+    fixture: &CommitteeFixture,
+    author: usize,
+    certificate_store: &CertificateStore,
+    consensus_store: &ConsensusStore,
+    max_items: usize,
+) -> GetDagSnapshotResponse {
+    let author = fixture.authorities().nth(author).unwrap();
+    make_snapshot(
+        author.id(),
+        &fixture.committee(),
+        certificate_store,
+        consensus_store,
+        &SignatureService::new(author.keypair().copy()),
+        GC_DEPTH,
+        max_items,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_install_verified_snapshot() {
+    let fixture = CommitteeFixture::builder().build();
+    let (_, certificate_store, _) = create_db_stores();
+    let consensus_store = make_consensus_store(&temp_dir());
+    let certificates = populate(&fixture, &certificate_store, &consensus_store);
+
+    let snapshot = snapshot_of(&fixture, 0, &certificate_store, &consensus_store, 100).await;
+    assert_eq!(snapshot.manifest.gc_round, 2);
+    assert_eq!(snapshot.manifest.last_committed_round(), 4);
+    let expected: HashSet<_> = certificates
+        .iter()
+        .filter(|c| c.round() > 2)
+        .map(|c| c.digest())
+        .collect();
+    assert_eq!(
+        snapshot
+            .manifest
+            .certificates
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>(),
+        expected
+    );
+    verify_snapshot(&snapshot, &fixture.committee(), &fixture.worker_cache()).unwrap();
+
+    // A joining primary resumes from the snapshot.
+    let (_, new_certificate_store, _) = create_db_stores();
+    let new_consensus_store = make_consensus_store(&temp_dir());
+    install_snapshot(snapshot, &new_certificate_store, &new_consensus_store).unwrap();
+    assert_eq!(
+        new_consensus_store.read_last_committed(),
+        consensus_store.read_last_committed()
+    );
+    assert_eq!(new_consensus_store.get_latest_sub_dag_index(), 1);
+    for digest in expected {
+        assert!(new_certificate_store.contains(&digest).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_bootstrap_mid_schedule_with_leader_swaps() {
+    let fixture = CommitteeFixture::builder()
+        .committee_size(NonZeroUsize::new(5).unwrap())
+        .build();
+    let committee = fixture.committee();
+    let (_, certificate_store, _) = create_db_stores();
+    let consensus_store = make_consensus_store(&temp_dir());
+    let certificates = populate(&fixture, &certificate_store, &consensus_store);
+
+    // The sub dag of round 4 ends a schedule in which the first authority scored the lowest,
+    // and is followed by the sub dag of round 6 in the next schedule.
+    let mut final_sub_dag = consensus_store.get_latest_sub_dag().unwrap();
+    final_sub_dag.reputation_score = ReputationScores {
+        scores_per_authority: fixture
+            .authorities()
+            .enumerate()
+            .map(|(i, a)| (a.id(), i as u64 + 1))
+            .collect(),
+        final_of_schedule: true,
+    };
+    let leader = certificates.iter().find(|c| c.round() == 6).unwrap();
+    let latest_sub_dag = CommittedSubDagShell {
+        certificates: vec![leader.digest()],
+        leader: leader.digest(),
+        leader_round: 6,
+        sub_dag_index: 2,
+        reputation_score: ReputationScores::new(&committee),
+        commit_timestamp: 0,
+    };
+    let last_committed = fixture.authorities().map(|a| (a.id(), 6)).collect();
+    consensus_store
+        .write_snapshot_state(&last_committed, Some(&latest_sub_dag), Some(&final_sub_dag))
+        .unwrap();
+
+    let snapshot = snapshot_of(&fixture, 0, &certificate_store, &consensus_store, 100).await;
+    assert_eq!(snapshot.manifest.latest_sub_dag, Some(latest_sub_dag));
+    assert_eq!(snapshot.manifest.latest_final_sub_dag, Some(final_sub_dag));
+    verify_snapshot(&snapshot, &committee, &fixture.worker_cache()).unwrap();
+
+    // The joining primary swaps the same leaders as its peers.
+    let (_, new_certificate_store, _) = create_db_stores();
+    let new_consensus_store = make_consensus_store(&temp_dir());
+    install_snapshot(snapshot, &new_certificate_store, &new_consensus_store).unwrap();
+    assert_eq!(new_consensus_store.get_latest_sub_dag_index(), 2);
+    let expected = LeaderSchedule::from_store(committee.clone(), consensus_store, true);
+    let restored = LeaderSchedule::from_store(committee, new_consensus_store, true);
+    assert!(!expected.leader_swap_table.load().bad_nodes.is_empty());
+    assert_eq!(
+        **restored.leader_swap_table.load(),
+        **expected.leader_swap_table.load()
+    );
+    for round in (2..=20).step_by(2) {
+        assert_eq!(restored.leader(round), expected.leader(round));
+    }
+}
+
+#[tokio::test]
+async fn test_cap_snapshot_certificates() {
+    let fixture = CommitteeFixture::builder().build();
+    let (_, certificate_store, _) = create_db_stores();
+    let consensus_store = make_consensus_store(&temp_dir());
+    populate(&fixture, &certificate_store, &consensus_store);
+    let sub_dag = consensus_store.get_latest_sub_dag().unwrap();
+
+    // The certificates of the latest sub dag are kept first, starting with its leader.
+    let committee_size = fixture.authorities().count();
+    let snapshot = snapshot_of(
+        &fixture,
+        0,
+        &certificate_store,
+        &consensus_store,
+        committee_size + 1,
+    )
+    .await;
+    assert_eq!(snapshot.manifest.certificates[0], sub_dag.leader);
+    assert_eq!(
+        snapshot
+            .manifest
+            .certificates
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>(),
+        sub_dag.certificates.iter().copied().collect::<HashSet<_>>()
+    );
+    verify_snapshot(&snapshot, &fixture.committee(), &fixture.worker_cache()).unwrap();
+
+    let snapshot = snapshot_of(&fixture, 0, &certificate_store, &consensus_store, 1).await;
+    assert_eq!(snapshot.manifest.certificates, vec![sub_dag.leader]);
+    verify_snapshot(&snapshot, &fixture.committee(), &fixture.worker_cache()).unwrap();
+}
+
+#[tokio::test]
+async fn test_select_snapshot_vouched_for_by_enough_authorities() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let (_, certificate_store, _) = create_db_stores();
+    let consensus_store = make_consensus_store(&temp_dir());
+    populate(&fixture, &certificate_store, &consensus_store);
+    let snapshot = snapshot_of(&fixture, 0, &certificate_store, &consensus_store, 100).await;
+
+    // A single authority is not trusted, however many times it serves its snapshot.
+    assert!(select_snapshot(vec![snapshot.clone(), snapshot.clone()], &committee).is_none());
+
+    // Nor are authorities disagreeing on the committed state.
+    let mut other = snapshot_of(&fixture, 1, &certificate_store, &consensus_store, 100).await;
+    other
+        .manifest
+        .last_committed
+        .values_mut()
+        .for_each(|r| *r = 6);
+    assert!(select_snapshot(vec![snapshot.clone(), other.clone()], &committee).is_none());
+
+    // The committed state of enough authorities is.
+    let third = snapshot_of(&fixture, 2, &certificate_store, &consensus_store, 100).await;
+    let selected = select_snapshot(vec![snapshot, other, third], &committee).unwrap();
+    assert_eq!(selected.manifest.last_committed_round(), 4);
+    assert_eq!(
+        selected.manifest.author,
+        fixture.authorities().next().unwrap().id()
+    );
+}
+
+#[tokio::test]
+async fn test_reject_tampered_snapshots() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let worker_cache = fixture.worker_cache();
+    let (_, certificate_store, _) = create_db_stores();
+    let consensus_store = make_consensus_store(&temp_dir());
+    populate(&fixture, &certificate_store, &consensus_store);
+    let snapshot = snapshot_of(&fixture, 0, &certificate_store, &consensus_store, 100).await;
+
+    // The commit rounds are covered by the signature.
+    let mut tampered = snapshot.clone();
+    tampered
+        .manifest
+        .last_committed
+        .values_mut()
+        .for_each(|r| *r = 6);
+    assert!(matches!(
+        verify_snapshot(&tampered, &committee, &worker_cache),
+        Err(DagError::InvalidSignature)
+    ));
+
+    // So is the author.
+    let mut tampered = snapshot.clone();
+    tampered.manifest.author = fixture.authorities().nth(1).unwrap().id();
+    assert!(matches!(
+        verify_snapshot(&tampered, &committee, &worker_cache),
+        Err(DagError::InvalidSignature)
+    ));
+
+    // The certificates must be the ones of the manifest.
+    let mut tampered = snapshot;
+    tampered.certificates.pop();
+    assert!(matches!(
+        verify_snapshot(&tampered, &committee, &worker_cache),
+        Err(DagError::InvalidDagSnapshot(_))
+    ));
+}
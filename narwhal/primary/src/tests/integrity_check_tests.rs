@@ -0,0 +1,96 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::common::create_db_stores;
+use std::collections::BTreeSet;
+use test_utils::{make_optimal_certificates, open_batch_store, CommitteeFixture};
+
+/// Stores the certificates of the rounds, with their payload unless it is skipped.
+fn store_certificates(
+    fixture: &CommitteeFixture,
+    rounds: std::ops::RangeInclusive<Round>,
+    certificate_store: &CertificateStore,
+    payload_store: &PayloadStore,
+    skip_payload: usize,
+) -> Vec<Certificate> {
+    let committee = fixture.committee();
+    let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+    let genesis: BTreeSet<_> = Certificate::genesis(&committee)
+        .iter()
+        .map(|c| c.digest())
+        .collect();
+    let (certificates, _) =
+        make_optimal_certificates(&committee, 1..=*rounds.end(), &genesis, &ids);
+    let certificates: Vec<_> = certificates
+        .into_iter()
+        .filter(|c| rounds.contains(&c.round()))
+        .collect();
+
+    certificate_store.write_all(certificates.clone()).unwrap();
+    for certificate in certificates.iter().skip(skip_payload) {
+        for (batch, (worker_id, _)) in certificate.header().payload() {
+            payload_store.write(batch, worker_id).unwrap();
+        }
+    }
+    certificates
+}
+
+#[tokio::test]
+async fn test_consistent_stores() {
+    let fixture = CommitteeFixture::builder().build();
+    let (_, certificate_store, payload_store) = create_db_stores();
+    let certificates = store_certificates(&fixture, 1..=3, &certificate_store, &payload_store, 0);
+
+    let report = check(&certificate_store, &payload_store, None, 0).unwrap();
+    assert_eq!(report.checked_certificates, certificates.len());
+    assert!(report.missing_payload.is_empty());
+    assert_eq!(report.removed_payload_entries, 0);
+}
+
+#[tokio::test]
+async fn test_report_missing_payload() {
+    let fixture = CommitteeFixture::builder().build();
+    let (_, certificate_store, payload_store) = create_db_stores();
+    let certificates = store_certificates(&fixture, 1..=3, &certificate_store, &payload_store, 2);
+
+    let report = check(&certificate_store, &payload_store, None, 0).unwrap();
+    let missing: HashSet<_> = report.missing_payload.iter().map(|c| c.digest()).collect();
+    let expected: HashSet<_> = certificates.iter().take(2).map(|c| c.digest()).collect();
+    assert_eq!(missing, expected);
+}
+
+#[tokio::test]
+async fn test_remove_payload_of_missing_batches() {
+    let fixture = CommitteeFixture::builder().build();
+    let (_, certificate_store, payload_store) = create_db_stores();
+    let certificates = store_certificates(&fixture, 1..=2, &certificate_store, &payload_store, 0);
+
+    // None of the batches made it to the batch store before the crash.
+    let batch_store = open_batch_store();
+    let report = check(&certificate_store, &payload_store, Some(&batch_store), 0).unwrap();
+    assert_eq!(report.missing_payload.len(), certificates.len());
+    assert_eq!(report.removed_payload_entries, certificates.len());
+    for certificate in &certificates {
+        for (batch, (worker_id, _)) in certificate.header().payload() {
+            assert!(!payload_store.contains(*batch, *worker_id).unwrap());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_detect_missing_parents() {
+    let fixture = CommitteeFixture::builder().build();
+    let (_, certificate_store, payload_store) = create_db_stores();
+    store_certificates(&fixture, 2..=3, &certificate_store, &payload_store, 0);
+
+    // The round 1 parents of the round 2 certificates are lost.
+    let result = check(&certificate_store, &payload_store, None, 0);
+    assert!(
+        matches!(result, Err(IntegrityError::MissingParent { .. })),
+        "{result:?}"
+    );
+
+    // But they are not needed anymore once garbage collected.
+    let report = check(&certificate_store, &payload_store, None, 1).unwrap();
+    assert!(report.missing_payload.is_empty());
+}
@@ -8,29 +8,36 @@ use crate::{
     NUM_SHUTDOWN_RECEIVERS,
 };
 use bincode::Options;
-use config::{AuthorityIdentifier, Committee, Parameters, WorkerId};
+use config::{
+    AuthorityIdentifier, Committee, DagSnapshotParameters, HeaderPayloadLimits, Parameters,
+    WorkerId,
+};
 use consensus::consensus::ConsensusRound;
+use consensus::leader_schedule::{LeaderSchedule, LeaderSwapTable};
 use consensus::{dag::Dag, metrics::ConsensusMetrics};
+use crypto::signer::SignatureService;
 use fastcrypto::{
     encoding::{Encoding, Hex},
     hash::Hash,
-    signature_service::SignatureService,
     traits::KeyPair,
 };
 use itertools::Itertools;
+use network::discovery::PeerAddresses;
 use prometheus::Registry;
 use std::{
     borrow::Borrow,
-    collections::{BTreeSet, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     num::NonZeroUsize,
     sync::Arc,
     time::Duration,
 };
-use storage::{CertificateStore, VoteDigestStore};
+use storage::{CertificateStore, EvidenceStore, VoteDigestStore};
 use storage::{CertificateStoreCache, PayloadToken};
 use storage::{NodeStorage, PayloadStore};
 use store::rocks::{DBMap, MetricConf, ReadWriteOptions};
-use test_utils::{make_optimal_signed_certificates, temp_dir, CommitteeFixture};
+use test_utils::{
+    make_consensus_store, make_optimal_signed_certificates, temp_dir, CommitteeFixture,
+};
 use tokio::{
     sync::{oneshot, watch},
     time::timeout,
@@ -38,11 +45,14 @@ use tokio::{
 
 use types::{
     now, BatchDigest, Certificate, CertificateAPI, CertificateDigest, FetchCertificatesRequest,
-    Header, HeaderAPI, MockPrimaryToWorker, PayloadAvailabilityRequest,
-    PreSubscribedBroadcastSender, PrimaryToPrimary, PrimaryToWorkerServer, RequestVoteRequest,
-    Round,
+    FetchCertificatesV2Request, Header, HeaderAPI, Misbehavior, MockPrimaryToWorker,
+    PayloadAvailabilityRequest, PreSubscribedBroadcastSender, PrimaryToPrimary,
+    PrimaryToWorkerServer, RequestVoteRequest, Round,
+};
+use worker::{
+    drain_channel, metrics::initialise_metrics, TransactionHandoff, TrivialTransactionValidator,
+    Worker,
 };
-use worker::{metrics::initialise_metrics, TrivialTransactionValidator, Worker};
 
 #[tokio::test]
 async fn get_network_peers_from_admin_server() {
@@ -92,12 +102,15 @@ async fn get_network_peers_from_admin_server() {
         authority_1.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_1_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_1_parameters.clone()).1,
         store.header_store.clone(),
         store.certificate_store.clone(),
         store.proposer_store.clone(),
         store.payload_store.clone(),
         store.vote_digest_store.clone(),
+        store.consensus_store.clone(),
+        store.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
@@ -112,6 +125,7 @@ async fn get_network_peers_from_admin_server() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -138,11 +152,13 @@ async fn get_network_peers_from_admin_server() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        worker_1_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(worker_1_parameters.clone()).1,
         TrivialTransactionValidator::default(),
         store.batch_store,
         metrics_1,
         &mut tx_shutdown_worker,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Test getting all known peers for primary 1
@@ -214,12 +230,15 @@ async fn get_network_peers_from_admin_server() {
         authority_2.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_2_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_2_parameters.clone()).1,
         store.header_store.clone(),
         store.certificate_store.clone(),
         store.proposer_store.clone(),
         store.payload_store.clone(),
         store.vote_digest_store.clone(),
+        store.consensus_store.clone(),
+        store.batch_store.clone(),
         /* tx_consensus */ tx_new_certificates_2,
         /* rx_consensus */ rx_feedback_2,
         rx_consensus_round_updates,
@@ -234,6 +253,7 @@ async fn get_network_peers_from_admin_server() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown_2,
         tx_feedback_2,
         &Registry::new(),
@@ -339,6 +359,11 @@ async fn test_request_vote_send_missing_parents() {
         certificate_store: certificate_store.clone(),
         payload_store: payload_store.clone(),
         vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: EvidenceStore::new_for_tests(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: Parameters::default().header_payload_limits(),
         rx_narwhal_round_updates,
         metrics: metrics.clone(),
     };
@@ -488,6 +513,11 @@ async fn test_request_vote_accept_missing_parents() {
         certificate_store: certificate_store.clone(),
         payload_store: payload_store.clone(),
         vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: EvidenceStore::new_for_tests(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: Parameters::default().header_payload_limits(),
         rx_narwhal_round_updates,
         metrics: metrics.clone(),
     };
@@ -629,6 +659,11 @@ async fn test_request_vote_missing_batches() {
         certificate_store: certificate_store.clone(),
         payload_store: payload_store.clone(),
         vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: EvidenceStore::new_for_tests(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: Parameters::default().header_payload_limits(),
         rx_narwhal_round_updates,
         metrics: metrics.clone(),
     };
@@ -748,6 +783,7 @@ async fn test_request_vote_already_voted() {
         None,
         metrics.clone(),
     ));
+    let evidence_store = EvidenceStore::new_for_tests();
     let handler = PrimaryReceiverHandler {
         authority_id: id,
         committee: fixture.committee(),
@@ -758,6 +794,11 @@ async fn test_request_vote_already_voted() {
         certificate_store: certificate_store.clone(),
         payload_store: payload_store.clone(),
         vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: evidence_store.clone(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: Parameters::default().header_payload_limits(),
         rx_narwhal_round_updates,
         metrics: metrics.clone(),
     };
@@ -846,6 +887,7 @@ async fn test_request_vote_already_voted() {
     assert_eq!(vote.digest(), response.into_body().vote.unwrap().digest());
 
     // Verify a different request for the same round receives an error.
+    let first_header = test_header;
     let test_header = Header::V1(
         author
             .header_builder(&fixture.committee())
@@ -874,6 +916,25 @@ async fn test_request_vote_already_voted() {
         anemo::types::response::StatusCode::BadRequest,
         response.err().unwrap().status()
     );
+
+    // And the equivocation is recorded, with both headers as evidence.
+    let evidences = evidence_store.read_by_offender(author.id()).unwrap();
+    assert_eq!(evidences.len(), 1);
+    assert_eq!(evidences[0].round, 2);
+    match &evidences[0].misbehavior {
+        Misbehavior::EquivocatingHeaders { first, second } => {
+            assert_eq!(first.digest(), first_header.digest());
+            assert_eq!(second.digest(), test_header.digest());
+        }
+        misbehavior => panic!("Unexpected misbehavior {misbehavior:?}"),
+    }
+    assert_eq!(
+        metrics
+            .evidences_recorded
+            .with_label_values(&["equivocating_headers"])
+            .get(),
+        1
+    );
 }
 
 #[tokio::test]
@@ -922,6 +983,11 @@ async fn test_fetch_certificates_handler() {
         certificate_store: certificate_store.clone(),
         payload_store: payload_store.clone(),
         vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: EvidenceStore::new_for_tests(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: Parameters::default().header_payload_limits(),
         rx_narwhal_round_updates,
         metrics: metrics.clone(),
     };
@@ -1041,6 +1107,64 @@ async fn test_fetch_certificates_handler() {
             expected_rounds
         );
     }
+
+    // The rounds of the last two authorities can be capped, e.g. to rounds 2 and 3.
+    let req = FetchCertificatesV2Request::new(
+        FetchCertificatesRequest::default()
+            .set_bounds(
+                0,
+                authorities
+                    .iter()
+                    .map(|authority| (*authority, BTreeSet::new()))
+                    .collect(),
+            )
+            .set_max_items(20),
+    )
+    .set_upper_bounds(
+        [(authorities[2], 2), (authorities[3], 3)]
+            .into_iter()
+            .collect(),
+    );
+    let resp = handler
+        .fetch_certificates_v2(anemo::Request::new(req))
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(
+        resp.certificates
+            .iter()
+            .map(|cert| cert.round())
+            .collect_vec(),
+        vec![1, 1, 1, 1, 2, 2, 2, 3]
+    );
+    assert!(resp.summary.is_empty());
+
+    // The summary can be requested alone.
+    let req = FetchCertificatesV2Request::new(
+        FetchCertificatesRequest::default().set_bounds(1, BTreeMap::new()),
+    )
+    .set_include_summary(true);
+    let resp = handler
+        .fetch_certificates_v2(anemo::Request::new(req))
+        .await
+        .unwrap()
+        .into_body();
+    assert!(resp.certificates.is_empty());
+    assert_eq!(
+        resp.summary
+            .iter()
+            .map(|s| (s.origin, s.lowest_round, s.highest_round))
+            .sorted()
+            .collect_vec(),
+        vec![
+            (authorities[1], 2, 2),
+            (authorities[2], 2, 3),
+            (authorities[3], 2, 4),
+        ]
+        .into_iter()
+        .sorted()
+        .collect_vec()
+    );
 }
 
 #[tokio::test]
@@ -1090,6 +1214,11 @@ async fn test_process_payload_availability_success() {
         certificate_store: certificate_store.clone(),
         payload_store: payload_store.clone(),
         vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: EvidenceStore::new_for_tests(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: Parameters::default().header_payload_limits(),
         rx_narwhal_round_updates,
         metrics: metrics.clone(),
     };
@@ -1244,6 +1373,11 @@ async fn test_process_payload_availability_when_failures() {
         certificate_store: certificate_store.clone(),
         payload_store: payload_store.clone(),
         vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: EvidenceStore::new_for_tests(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: Parameters::default().header_payload_limits(),
         rx_narwhal_round_updates,
         metrics: metrics.clone(),
     };
@@ -1345,6 +1479,11 @@ async fn test_request_vote_created_at_in_future() {
         certificate_store: certificate_store.clone(),
         payload_store: payload_store.clone(),
         vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: EvidenceStore::new_for_tests(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: Parameters::default().header_payload_limits(),
         rx_narwhal_round_updates,
         metrics: metrics.clone(),
     };
@@ -1452,3 +1591,94 @@ async fn test_request_vote_created_at_in_future() {
     // We are now later
     assert!(created_at < now());
 }
+
+#[tokio::test]
+async fn test_request_vote_too_many_batches() {
+    let fixture = CommitteeFixture::builder()
+        .randomize_ports(true)
+        .committee_size(NonZeroUsize::new(4).unwrap())
+        .build();
+    let worker_cache = fixture.worker_cache();
+    let primary = fixture.authorities().next().unwrap();
+    let id = primary.id();
+    let author = fixture.authorities().nth(2).unwrap();
+    let signature_service = SignatureService::new(primary.keypair().copy());
+    let metrics = Arc::new(PrimaryMetrics::new(&Registry::new()));
+    let network = test_utils::test_network(primary.network_keypair(), primary.address());
+
+    let (header_store, certificate_store, payload_store) = create_db_stores();
+    let (tx_certificate_fetcher, _rx_certificate_fetcher) = test_utils::test_channel!(1);
+    let (tx_new_certificates, _rx_new_certificates) = test_utils::test_channel!(100);
+    let (tx_parents, _rx_parents) = test_utils::test_channel!(100);
+    let (_tx_consensus_round_updates, rx_consensus_round_updates) =
+        watch::channel(ConsensusRound::new(1, 0));
+    let (_tx_narwhal_round_updates, rx_narwhal_round_updates) = watch::channel(1u64);
+    let (_tx_synchronizer_network, rx_synchronizer_network) = oneshot::channel();
+
+    let synchronizer = Arc::new(Synchronizer::new(
+        id,
+        fixture.committee(),
+        worker_cache.clone(),
+        /* gc_depth */ 50,
+        certificate_store.clone(),
+        payload_store.clone(),
+        tx_certificate_fetcher,
+        tx_new_certificates,
+        tx_parents,
+        rx_consensus_round_updates,
+        rx_synchronizer_network,
+        None,
+        metrics.clone(),
+    ));
+    let handler = PrimaryReceiverHandler {
+        authority_id: id,
+        committee: fixture.committee(),
+        worker_cache,
+        synchronizer,
+        signature_service,
+        header_store,
+        certificate_store,
+        payload_store,
+        vote_digest_store: VoteDigestStore::new_for_tests(),
+        evidence_store: EvidenceStore::new_for_tests(),
+        consensus_store: make_consensus_store(&temp_dir()),
+        gc_depth: 50,
+        dag_snapshot: DagSnapshotParameters::default(),
+        header_payload_limits: HeaderPayloadLimits {
+            max_num_of_batches: 2,
+            max_num_of_batches_per_worker: 1,
+        },
+        rx_narwhal_round_updates,
+        metrics,
+    };
+
+    // Two batches are within the total limit, but not within the limit of worker 0.
+    let test_header = Header::V1(
+        author
+            .header_builder(&fixture.committee())
+            .round(2)
+            .with_payload_batch(test_utils::fixture_batch_with_transactions(10), 0, 0)
+            .with_payload_batch(test_utils::fixture_batch_with_transactions(10), 0, 0)
+            .build()
+            .unwrap(),
+    );
+    let mut request = anemo::Request::new(RequestVoteRequest {
+        header: test_header,
+        parents: Vec::new(),
+    });
+    assert!(request
+        .extensions_mut()
+        .insert(network.downgrade())
+        .is_none());
+    assert!(request
+        .extensions_mut()
+        .insert(anemo::PeerId(author.network_public_key().0.to_bytes()))
+        .is_none());
+
+    // The header is refused a vote, which is not worth retrying.
+    let result = handler.request_vote(request).await;
+    assert_eq!(
+        anemo::types::response::StatusCode::BadRequest,
+        result.err().unwrap().status()
+    );
+}
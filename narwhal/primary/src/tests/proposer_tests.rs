@@ -3,11 +3,21 @@
 // SPDX-License-Identifier: Apache-2.0
 use super::*;
 use crate::NUM_SHUTDOWN_RECEIVERS;
+use config::{HeaderPayloadLimits, HeaderProposalPolicy};
+use consensus::leader_schedule::LeaderSwapTable;
 use indexmap::IndexMap;
 use prometheus::Registry;
 use test_utils::{fixture_payload, CommitteeFixture};
 use types::PreSubscribedBroadcastSender;
 
+fn parameters(max_header_delay: Duration) -> watch::Receiver<Parameters> {
+    watch::channel(Parameters {
+        max_header_delay,
+        ..Parameters::default()
+    })
+    .1
+}
+
 #[tokio::test]
 async fn propose_empty() {
     let fixture = CommitteeFixture::builder().build();
@@ -31,9 +41,13 @@ async fn propose_empty() {
         committee.clone(),
         ProposerStore::new_for_tests(),
         /* header_num_of_batches_threshold */ 32,
-        /* max_header_num_of_batches */ 100,
-        /* max_header_delay */ Duration::from_millis(20),
+        HeaderPayloadLimits {
+            max_num_of_batches: 100,
+            max_num_of_batches_per_worker: 100,
+        },
+        parameters(/* max_header_delay */ Duration::from_millis(20)),
         /* min_header_delay */ Duration::from_millis(20),
+        HeaderProposalPolicy::Eager,
         None,
         NetworkModel::PartiallySynchronous,
         tx_shutdown.subscribe(),
@@ -43,6 +57,7 @@ async fn propose_empty() {
         tx_narwhal_round_updates,
         rx_committed_own_headers,
         metrics,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     // Ensure the proposer makes a correct empty header.
@@ -52,6 +67,86 @@ async fn propose_empty() {
     assert!(header.validate(&committee, &worker_cache).is_ok());
 }
 
+#[tokio::test]
+async fn propose_delayed_until_enough_payload() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let primary = fixture.authorities().next().unwrap();
+    let name = primary.id();
+
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (_tx_parents, rx_parents) = test_utils::test_channel!(1);
+    let (_tx_committed_own_headers, rx_committed_own_headers) = test_utils::test_channel!(1);
+    let (tx_our_digests, rx_our_digests) = test_utils::test_channel!(1);
+    let (tx_headers, mut rx_headers) = test_utils::test_channel!(1);
+    let (tx_narwhal_round_updates, _rx_narwhal_round_updates) = watch::channel(0u64);
+
+    let metrics = Arc::new(PrimaryMetrics::new(&Registry::new()));
+
+    // Spawn the proposer.
+    let _proposer_handle = Proposer::spawn(
+        name,
+        committee.clone(),
+        ProposerStore::new_for_tests(),
+        /* header_num_of_batches_threshold */ 32,
+        HeaderPayloadLimits {
+            max_num_of_batches: 100,
+            max_num_of_batches_per_worker: 100,
+        },
+        // Ensure the max header delay is not triggered.
+        parameters(/* max_header_delay */ Duration::from_millis(1_000_000)),
+        /* min_header_delay */ Duration::from_millis(20),
+        HeaderProposalPolicy::Delayed {
+            min_num_of_batches: 3,
+            min_payload_size: 1_000,
+        },
+        None,
+        NetworkModel::PartiallySynchronous,
+        tx_shutdown.subscribe(),
+        /* rx_core */ rx_parents,
+        /* rx_workers */ rx_our_digests,
+        /* tx_core */ tx_headers,
+        tx_narwhal_round_updates,
+        rx_committed_own_headers,
+        metrics,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
+    );
+
+    let batches: Vec<_> = fixture_payload(2).into_iter().collect();
+    let send = |(digest, (worker_id, timestamp)), size| {
+        let tx_our_digests = tx_our_digests.clone();
+        async move {
+            let (tx_ack, rx_ack) = tokio::sync::oneshot::channel();
+            tx_our_digests
+                .send(OurDigestMessage {
+                    digest,
+                    worker_id,
+                    timestamp,
+                    size,
+                    received_at: Instant::now(),
+                    ack_channel: Some(tx_ack),
+                })
+                .await
+                .unwrap();
+            rx_ack.await.unwrap();
+        }
+    };
+
+    // A small batch is not enough of a payload, even after the min header delay.
+    send(batches[0], 10).await;
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), rx_headers.recv())
+            .await
+            .is_err()
+    );
+
+    // Once the payload is large enough, the header is proposed with all the pending batches.
+    send(batches[1], 2_000).await;
+    let header = rx_headers.recv().await.unwrap();
+    assert_eq!(header.round(), 1);
+    assert_eq!(header.payload().len(), 2);
+}
+
 #[tokio::test]
 async fn propose_payload_and_repropose_after_n_seconds() {
     let fixture = CommitteeFixture::builder().build();
@@ -78,11 +173,15 @@ async fn propose_payload_and_repropose_after_n_seconds() {
         committee.clone(),
         ProposerStore::new_for_tests(),
         /* header_num_of_batches_threshold */ 1,
-        /* max_header_num_of_batches */ max_num_of_batches,
-        /* max_header_delay */
-        Duration::from_millis(1_000_000), // Ensure it is not triggered.
+        HeaderPayloadLimits {
+            max_num_of_batches,
+            max_num_of_batches_per_worker: max_num_of_batches,
+        },
+        // Ensure the max header delay is not triggered.
+        parameters(/* max_header_delay */ Duration::from_millis(1_000_000)),
         /* min_header_delay */
         Duration::from_millis(1_000_000), // Ensure it is not triggered.
+        HeaderProposalPolicy::Eager,
         Some(header_resend_delay),
         NetworkModel::PartiallySynchronous,
         tx_shutdown.subscribe(),
@@ -92,6 +191,7 @@ async fn propose_payload_and_repropose_after_n_seconds() {
         tx_narwhal_round_updates,
         rx_committed_own_headers,
         metrics,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     // Send enough digests for the header payload.
@@ -108,6 +208,8 @@ async fn propose_payload_and_repropose_after_n_seconds() {
             digest,
             worker_id,
             timestamp: created_at_ts,
+            size: 0,
+            received_at: Instant::now(),
             ack_channel: Some(tx_ack),
         })
         .await
@@ -134,6 +236,8 @@ async fn propose_payload_and_repropose_after_n_seconds() {
                 digest: batch_id,
                 worker_id,
                 timestamp: created_at,
+                size: 0,
+                received_at: Instant::now(),
                 ack_channel: Some(tx_ack),
             })
             .await
@@ -177,6 +281,108 @@ async fn propose_payload_and_repropose_after_n_seconds() {
     }
 }
 
+#[tokio::test]
+async fn propose_within_payload_limits() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let primary = fixture.authorities().next().unwrap();
+    let name = primary.id();
+
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_parents, rx_parents) = test_utils::test_channel!(1);
+    let (tx_our_digests, rx_our_digests) = test_utils::test_channel!(1);
+    let (_tx_committed_own_headers, rx_committed_own_headers) = test_utils::test_channel!(1);
+    let (tx_headers, mut rx_headers) = test_utils::test_channel!(1);
+    let (tx_narwhal_round_updates, _rx_narwhal_round_updates) = watch::channel(0u64);
+
+    let metrics = Arc::new(PrimaryMetrics::new(&Registry::new()));
+
+    // Spawn the proposer.
+    let _proposer_handle = Proposer::spawn(
+        name,
+        committee.clone(),
+        ProposerStore::new_for_tests(),
+        /* header_num_of_batches_threshold */ 4,
+        HeaderPayloadLimits {
+            max_num_of_batches: 3,
+            max_num_of_batches_per_worker: 2,
+        },
+        // Ensure the max header delay is not triggered.
+        parameters(/* max_header_delay */ Duration::from_millis(1_000_000)),
+        /* min_header_delay */
+        Duration::from_millis(1_000_000), // Ensure it is not triggered.
+        HeaderProposalPolicy::Eager,
+        None,
+        NetworkModel::PartiallySynchronous,
+        tx_shutdown.subscribe(),
+        /* rx_core */ rx_parents,
+        /* rx_workers */ rx_our_digests,
+        /* tx_core */ tx_headers,
+        tx_narwhal_round_updates,
+        rx_committed_own_headers,
+        metrics,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
+    );
+
+    let digests: Vec<_> = (0..7u8).map(|i| BatchDigest::new([i; 32])).collect();
+    let send_digests = |batches: Vec<(BatchDigest, WorkerId)>| {
+        let tx_our_digests = tx_our_digests.clone();
+        async move {
+            for (digest, worker_id) in batches {
+                tx_our_digests
+                    .send(OurDigestMessage {
+                        digest,
+                        worker_id,
+                        timestamp: 0,
+                        size: 0,
+                        received_at: Instant::now(),
+                        ack_channel: None,
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+    };
+
+    // The third batch of worker 0 is over the limit of the worker, so it waits for the next
+    // header while the batch of worker 1 is included.
+    send_digests(vec![
+        (digests[0], 0),
+        (digests[1], 0),
+        (digests[2], 0),
+        (digests[3], 1),
+    ])
+    .await;
+    let header = rx_headers.recv().await.unwrap();
+    assert_eq!(header.round(), 1);
+    assert_eq!(
+        header.payload().keys().copied().collect::<Vec<_>>(),
+        vec![digests[0], digests[1], digests[3]]
+    );
+
+    // The next header is full once it has 3 batches, the last one waiting again.
+    send_digests(vec![(digests[4], 1), (digests[5], 1), (digests[6], 1)]).await;
+    let parents: Vec<_> = fixture
+        .headers()
+        .iter()
+        .take(4)
+        .map(|h| fixture.certificate(h))
+        .collect();
+    tx_parents.send((parents, 1, 0)).await.unwrap();
+    let header = rx_headers.recv().await.unwrap();
+    assert_eq!(header.round(), 2);
+    assert_eq!(
+        header.payload().keys().copied().collect::<Vec<_>>(),
+        vec![digests[2], digests[4], digests[5]]
+    );
+    assert!(header
+        .validate_payload_limits(&HeaderPayloadLimits {
+            max_num_of_batches: 3,
+            max_num_of_batches_per_worker: 2,
+        })
+        .is_ok());
+}
+
 #[tokio::test]
 async fn equivocation_protection() {
     let fixture = CommitteeFixture::builder().build();
@@ -200,11 +406,15 @@ async fn equivocation_protection() {
         committee.clone(),
         proposer_store.clone(),
         /* header_num_of_batches_threshold */ 1,
-        /* max_header_num_of_batches */ 10,
-        /* max_header_delay */
-        Duration::from_millis(1_000_000), // Ensure it is not triggered.
+        HeaderPayloadLimits {
+            max_num_of_batches: 10,
+            max_num_of_batches_per_worker: 10,
+        },
+        // Ensure the max header delay is not triggered.
+        parameters(/* max_header_delay */ Duration::from_millis(1_000_000)),
         /* min_header_delay */
         Duration::from_millis(1_000_000), // Ensure it is not triggered.
+        HeaderProposalPolicy::Eager,
         None,
         NetworkModel::PartiallySynchronous,
         tx_shutdown.subscribe(),
@@ -214,6 +424,7 @@ async fn equivocation_protection() {
         tx_narwhal_round_updates,
         rx_committed_own_headers,
         metrics,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     // Send enough digests for the header payload.
@@ -230,6 +441,8 @@ async fn equivocation_protection() {
             digest,
             worker_id,
             timestamp: created_at_ts,
+            size: 0,
+            received_at: Instant::now(),
             ack_channel: Some(tx_ack),
         })
         .await
@@ -272,11 +485,15 @@ async fn equivocation_protection() {
         committee.clone(),
         proposer_store,
         /* header_num_of_batches_threshold */ 1,
-        /* max_header_num_of_batches */ 10,
-        /* max_header_delay */
-        Duration::from_millis(1_000_000), // Ensure it is not triggered.
+        HeaderPayloadLimits {
+            max_num_of_batches: 10,
+            max_num_of_batches_per_worker: 10,
+        },
+        // Ensure the max header delay is not triggered.
+        parameters(/* max_header_delay */ Duration::from_millis(1_000_000)),
         /* min_header_delay */
         Duration::from_millis(1_000_000), // Ensure it is not triggered.
+        HeaderProposalPolicy::Eager,
         None,
         NetworkModel::PartiallySynchronous,
         tx_shutdown.subscribe(),
@@ -286,6 +503,7 @@ async fn equivocation_protection() {
         tx_narwhal_round_updates,
         rx_committed_own_headers,
         metrics,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
     );
 
     // Send enough digests for the header payload.
@@ -301,6 +519,8 @@ async fn equivocation_protection() {
             digest,
             worker_id,
             timestamp: 0,
+            size: 0,
+            received_at: Instant::now(),
             ack_channel: Some(tx_ack),
         })
         .await
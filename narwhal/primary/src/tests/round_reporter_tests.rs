@@ -0,0 +1,59 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use anemo::PeerId;
+use crypto::traits::KeyPair;
+use test_utils::CommitteeFixture;
+use tokio::sync::mpsc;
+use types::{MockPrimaryToWorker, PreSubscribedBroadcastSender, PrimaryToWorkerServer};
+
+#[tokio::test]
+async fn test_report_rounds_to_our_workers() {
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let primary = fixture.authorities().next().unwrap();
+    let network = test_utils::test_network(primary.network_keypair(), primary.address());
+
+    // A mock of our worker 0, forwarding the reports it receives.
+    let (tx_reports, mut rx_reports) = mpsc::unbounded_channel();
+    let worker = primary.worker(0);
+    let mut mock_server = MockPrimaryToWorker::new();
+    mock_server
+        .expect_report_rounds()
+        .returning(move |request| {
+            tx_reports.send(request.into_body()).unwrap();
+            Ok(anemo::Response::new(()))
+        });
+    let routes = anemo::Router::new().add_rpc_service(PrimaryToWorkerServer::new(mock_server));
+    let _worker_network = worker.new_network(routes);
+    network
+        .connect_with_peer_id(
+            worker.info().worker_address.to_anemo_address().unwrap(),
+            PeerId(worker.keypair().public().0.to_bytes()),
+        )
+        .await
+        .unwrap();
+
+    let (tx_consensus_round_updates, rx_consensus_round_updates) =
+        watch::channel(ConsensusRound::default());
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(1);
+    let _handle = RoundReporter::spawn(
+        primary.id(),
+        fixture.committee(),
+        fixture.worker_cache(),
+        network,
+        rx_consensus_round_updates,
+        tx_shutdown.subscribe(),
+    );
+
+    tx_consensus_round_updates
+        .send(ConsensusRound::new(60, 10))
+        .unwrap();
+    let report = rx_reports.recv().await.unwrap();
+    assert_eq!(
+        report,
+        WorkerRoundsMessage {
+            committed_round: 60,
+            gc_round: 10,
+        }
+    );
+}
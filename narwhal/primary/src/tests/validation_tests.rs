@@ -0,0 +1,71 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::{
+    metrics::PrimaryMetrics,
+    validation::{report_rejection, HeaderValidator, Validated},
+};
+use config::Parameters;
+use prometheus::Registry;
+use test_utils::CommitteeFixture;
+use types::{error::DagError, Header};
+
+#[test]
+fn header_validator_rejects_wrong_epoch() {
+    let fixture = CommitteeFixture::builder().build();
+    let committee = fixture.committee();
+    let worker_cache = fixture.worker_cache();
+    let limits = Parameters::default().header_payload_limits();
+    let validator = HeaderValidator {
+        committee: &committee,
+        worker_cache: &worker_cache,
+        limits: &limits,
+    };
+    let author = fixture.authorities().next().unwrap();
+
+    let header = Header::V1(author.header_builder(&committee).build().unwrap());
+    validator.validate(&header).unwrap();
+
+    let header = Header::V1(
+        author
+            .header_builder(&committee)
+            .epoch(committee.epoch() + 1)
+            .build()
+            .unwrap(),
+    );
+    let error = validator.validate(&header).unwrap_err();
+    assert!(matches!(error, DagError::InvalidEpoch { .. }));
+}
+
+#[test]
+fn rejections_are_counted_by_reason() {
+    let metrics = PrimaryMetrics::new(&Registry::new());
+
+    let error = report_rejection(
+        &metrics,
+        Validated::Header,
+        "test",
+        DagError::InvalidEpoch {
+            expected: 1,
+            received: 2,
+        },
+    );
+    assert!(matches!(error, DagError::InvalidEpoch { .. }));
+    report_rejection(
+        &metrics,
+        Validated::Certificate,
+        "test",
+        DagError::InvalidSignature,
+    );
+    // Errors not caused by the message are not rejections.
+    report_rejection(&metrics, Validated::Header, "test", DagError::ShuttingDown);
+
+    let count = |kind: &str, reason: &str| {
+        metrics
+            .validation_rejections
+            .with_label_values(&[kind, reason])
+            .get()
+    };
+    assert_eq!(count("header", "wrong_epoch"), 1);
+    assert_eq!(count("certificate", "bad_signature"), 1);
+    assert_eq!(count("certificate", "wrong_epoch"), 0);
+}
@@ -0,0 +1,63 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::metrics::PrimaryMetrics;
+use config::{Committee, HeaderPayloadLimits, WorkerCache};
+use std::fmt::Display;
+use tracing::warn;
+use types::{
+    error::{DagError, DagResult},
+    Header,
+};
+
+#[cfg(test)]
+#[path = "tests/validation_tests.rs"]
+pub mod validation_tests;
+
+/// The kinds of messages validated by the primary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Validated {
+    Header,
+    Certificate,
+}
+
+impl Validated {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Validated::Header => "header",
+            Validated::Certificate => "certificate",
+        }
+    }
+}
+
+/// Runs the checks of the headers received from the other primaries, in order, stopping at the
+/// first failing one.
+pub struct HeaderValidator<'a> {
+    pub committee: &'a Committee,
+    pub worker_cache: &'a WorkerCache,
+    pub limits: &'a HeaderPayloadLimits,
+}
+
+impl HeaderValidator<'_> {
+    pub fn validate(&self, header: &Header) -> DagResult<()> {
+        header.validate(self.committee, self.worker_cache)?;
+        header.validate_payload_limits(self.limits)
+    }
+}
+
+/// Logs and counts the rejection of a message by reason, when the error is caused by the message
+/// itself. Returns the error, to be propagated.
+pub fn report_rejection(
+    metrics: &PrimaryMetrics,
+    kind: Validated,
+    message: impl Display,
+    error: DagError,
+) -> DagError {
+    if let Some(reason) = error.rejection_reason() {
+        warn!("Rejected {} {message} ({reason}): {error}", kind.as_str());
+        metrics
+            .validation_rejections
+            .with_label_values(&[kind.as_str(), reason.as_str()])
+            .inc();
+    }
+    error
+}
@@ -43,6 +43,7 @@ async fn test_restore_from_disk() {
             let tr = bcs::to_bytes(&tx).unwrap();
             let txn = TransactionProto {
                 transaction: Bytes::from(tr),
+                ..Default::default()
             };
 
             c.submit_transaction(txn).await.unwrap();
@@ -4,6 +4,7 @@
 use bytes::Bytes;
 use config::{AuthorityIdentifier, CommitteeBuilder, Epoch, Parameters};
 use consensus::consensus::ConsensusRound;
+use consensus::leader_schedule::{LeaderSchedule, LeaderSwapTable};
 use consensus::{dag::Dag, metrics::ConsensusMetrics};
 use crypto::KeyPair;
 use fastcrypto::{
@@ -12,6 +13,7 @@ use fastcrypto::{
 };
 use narwhal_primary as primary;
 use narwhal_primary::NUM_SHUTDOWN_RECEIVERS;
+use network::discovery::PeerAddresses;
 use primary::{NetworkModel, Primary, CHANNEL_CAPACITY};
 use prometheus::Registry;
 use rand::thread_rng;
@@ -111,12 +113,15 @@ async fn test_rounds_errors() {
         network_keypair,
         committee.clone(),
         worker_cache,
-        parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters.clone()).1,
         store_primary.header_store,
         store_primary.certificate_store,
         store_primary.proposer_store,
         store_primary.payload_store,
         store_primary.vote_digest_store,
+        store_primary.consensus_store.clone(),
+        store_primary.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
@@ -131,6 +136,7 @@ async fn test_rounds_errors() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -213,17 +219,21 @@ async fn test_rounds_return_successful_response() {
         author.network_keypair().copy(),
         committee.clone(),
         worker_cache,
-        parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters.clone()).1,
         store_primary.header_store,
         store_primary.certificate_store,
         store_primary.proposer_store,
         store_primary.payload_store,
         store_primary.vote_digest_store,
+        store_primary.consensus_store.clone(),
+        store_primary.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
         /* external_consensus */ Some(dag.clone()),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -374,17 +384,21 @@ async fn test_node_read_causal_signed_certificates() {
         authority_1.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_1_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_1_parameters.clone()).1,
         primary_store_1.header_store.clone(),
         primary_store_1.certificate_store.clone(),
         primary_store_1.proposer_store.clone(),
         primary_store_1.payload_store.clone(),
         primary_store_1.vote_digest_store.clone(),
+        primary_store_1.consensus_store.clone(),
+        primary_store_1.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
         /* dag */ Some(dag.clone()),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -413,12 +427,15 @@ async fn test_node_read_causal_signed_certificates() {
         authority_2.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_2_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_2_parameters.clone()).1,
         primary_store_2.header_store,
         primary_store_2.certificate_store,
         primary_store_2.proposer_store,
         primary_store_2.payload_store,
         primary_store_2.vote_digest_store,
+        primary_store_2.consensus_store.clone(),
+        primary_store_2.batch_store.clone(),
         tx_new_certificates_2,
         rx_feedback_2,
         rx_consensus_round_updates_2,
@@ -433,6 +450,7 @@ async fn test_node_read_causal_signed_certificates() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown_2,
         tx_feedback_2,
         &Registry::new(),
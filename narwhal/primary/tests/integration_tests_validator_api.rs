@@ -3,11 +3,13 @@
 
 use config::{AuthorityIdentifier, BlockSynchronizerParameters, Committee, Parameters};
 use consensus::consensus::ConsensusRound;
+use consensus::leader_schedule::{LeaderSchedule, LeaderSwapTable};
 use consensus::{dag::Dag, metrics::ConsensusMetrics};
 use fastcrypto::{hash::Hash, traits::KeyPair as _};
 use indexmap::IndexMap;
 use narwhal_primary as primary;
 use narwhal_primary::NUM_SHUTDOWN_RECEIVERS;
+use network::discovery::PeerAddresses;
 use primary::{NetworkModel, Primary, CHANNEL_CAPACITY};
 use prometheus::Registry;
 use std::{
@@ -29,7 +31,10 @@ use types::{
     CollectionRetrievalResult, Empty, GetCollectionsRequest, Header, PreSubscribedBroadcastSender,
     ReadCausalRequest, RemoveCollectionsRequest, RetrievalResult, Transaction, ValidatorClient,
 };
-use worker::{metrics::initialise_metrics, TrivialTransactionValidator, Worker};
+use worker::{
+    drain_channel, metrics::initialise_metrics, TransactionHandoff, TrivialTransactionValidator,
+    Worker,
+};
 
 #[tokio::test(flavor = "current_thread", start_paused = true)]
 async fn test_get_collections() {
@@ -108,12 +113,15 @@ async fn test_get_collections() {
         author.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters.clone()).1,
         store.header_store.clone(),
         store.certificate_store.clone(),
         store.proposer_store.clone(),
         store.payload_store.clone(),
         store.vote_digest_store,
+        store.consensus_store.clone(),
+        store.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
@@ -128,6 +136,7 @@ async fn test_get_collections() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -145,11 +154,13 @@ async fn test_get_collections() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters.clone()).1,
         TrivialTransactionValidator::default(),
         store.batch_store.clone(),
         metrics,
         &mut tx_shutdown_worker,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Wait for tasks to start
@@ -314,17 +325,21 @@ async fn test_remove_collections() {
         author.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters.clone()).1,
         store.header_store.clone(),
         store.certificate_store.clone(),
         store.proposer_store.clone(),
         store.payload_store.clone(),
         store.vote_digest_store.clone(),
+        store.consensus_store.clone(),
+        store.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
         /* dag */ Some(dag.clone()),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -373,11 +388,13 @@ async fn test_remove_collections() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters.clone()).1,
         TrivialTransactionValidator::default(),
         store.batch_store.clone(),
         metrics,
         &mut tx_shutdown_worker,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Test remove no collections
@@ -563,17 +580,21 @@ async fn test_read_causal_signed_certificates() {
         authority_1.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_1_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_1_parameters.clone()).1,
         primary_store_1.header_store.clone(),
         primary_store_1.certificate_store.clone(),
         primary_store_1.proposer_store.clone(),
         primary_store_1.payload_store.clone(),
         primary_store_1.vote_digest_store.clone(),
+        primary_store_1.consensus_store.clone(),
+        primary_store_1.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
         /* dag */ Some(dag.clone()),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -603,12 +624,15 @@ async fn test_read_causal_signed_certificates() {
         authority_2.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_2_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_2_parameters.clone()).1,
         primary_store_2.header_store,
         primary_store_2.certificate_store,
         primary_store_2.proposer_store,
         primary_store_2.payload_store,
         primary_store_2.vote_digest_store,
+        primary_store_2.consensus_store.clone(),
+        primary_store_2.batch_store.clone(),
         tx_new_certificates_2,
         rx_feedback_2,
         rx_consensus_round_updates_2,
@@ -623,6 +647,7 @@ async fn test_read_causal_signed_certificates() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown_2,
         tx_feedback_2,
         &Registry::new(),
@@ -788,17 +813,21 @@ async fn test_read_causal_unsigned_certificates() {
         authority_1.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_1_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_1_parameters.clone()).1,
         primary_store_1.header_store.clone(),
         primary_store_1.certificate_store.clone(),
         primary_store_1.proposer_store.clone(),
         primary_store_1.payload_store.clone(),
         primary_store_1.vote_digest_store.clone(),
+        primary_store_1.consensus_store.clone(),
+        primary_store_1.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
         /* dag */ Some(dag.clone()),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -822,12 +851,15 @@ async fn test_read_causal_unsigned_certificates() {
         network_keypair_2,
         committee.clone(),
         worker_cache.clone(),
-        primary_2_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_2_parameters.clone()).1,
         primary_store_2.header_store,
         primary_store_2.certificate_store,
         primary_store_2.proposer_store,
         primary_store_2.payload_store,
         primary_store_2.vote_digest_store,
+        primary_store_2.consensus_store.clone(),
+        primary_store_2.batch_store.clone(),
         tx_new_certificates_2,
         rx_feedback_2,
         rx_consensus_round_updates_2,
@@ -842,6 +874,7 @@ async fn test_read_causal_unsigned_certificates() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown_2,
         tx_feedback_2,
         &Registry::new(),
@@ -995,12 +1028,15 @@ async fn test_get_collections_with_missing_certificates() {
         authority_1.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        parameters_1.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters_1.clone()).1,
         store_primary_1.header_store,
         store_primary_1.certificate_store,
         store_primary_1.proposer_store,
         store_primary_1.payload_store,
         store_primary_1.vote_digest_store,
+        store_primary_1.consensus_store.clone(),
+        store_primary_1.batch_store.clone(),
         tx_new_certificates_1,
         rx_feedback_1,
         rx_consensus_round_updates,
@@ -1015,6 +1051,7 @@ async fn test_get_collections_with_missing_certificates() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback_1,
         &Registry::new(),
@@ -1032,11 +1069,13 @@ async fn test_get_collections_with_missing_certificates() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        parameters_1.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters_1.clone()).1,
         TrivialTransactionValidator::default(),
         store_primary_1.batch_store,
         metrics_1,
         &mut tx_shutdown_worker_1,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Spawn the primary 2 - a peer to fetch missing certificates from
@@ -1066,18 +1105,22 @@ async fn test_get_collections_with_missing_certificates() {
         authority_2.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        parameters_2.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters_2.clone()).1,
         store_primary_2.header_store,
         store_primary_2.certificate_store,
         store_primary_2.proposer_store,
         store_primary_2.payload_store,
         store_primary_2.vote_digest_store,
+        store_primary_2.consensus_store.clone(),
+        store_primary_2.batch_store.clone(),
         tx_new_certificates_2,
         rx_feedback_2,
         rx_consensus_round_updates,
         /* external_consensus */
         None,
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown_2,
         tx_feedback_2,
         &Registry::new(),
@@ -1095,11 +1138,13 @@ async fn test_get_collections_with_missing_certificates() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        parameters_2.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters_2.clone()).1,
         TrivialTransactionValidator::default(),
         store_primary_2.batch_store,
         metrics_2,
         &mut tx_shutdown_worker_2,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Wait for tasks to start
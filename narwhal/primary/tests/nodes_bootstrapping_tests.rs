@@ -70,6 +70,7 @@ async fn test_response_error_after_shutdown_internal_consensus() {
     let tx = bcs::to_bytes(&tx_str).unwrap();
     let txn = TransactionProto {
         transaction: Bytes::from(tx),
+        ..Default::default()
     };
 
     // Should fail submitting to consensus.
@@ -0,0 +1,105 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use config::WriteBatchingParameters;
+use criterion::{
+    criterion_group, criterion_main, BenchmarkId, Criterion, SamplingMode, Throughput,
+};
+use fastcrypto::hash::Hash;
+use futures::future::join_all;
+use narwhal_storage::NodeStorage;
+use std::collections::BTreeSet;
+use test_utils::{make_optimal_certificates, temp_dir, CommitteeFixture};
+use types::{BatchDigest, Certificate};
+
+/// Writes the certificates of a few rounds, one by one as the primary used to write them, and in
+/// a single batch as it writes a certificate along with the certificates it unblocks.
+pub fn write_certificates(c: &mut Criterion) {
+    let mut bench_group = c.benchmark_group("write_certificates");
+    bench_group.sampling_mode(SamplingMode::Flat);
+
+    static COMMITTEE_SIZES: [usize; 4] = [4, 10, 50, 100];
+    for committee_size in COMMITTEE_SIZES {
+        let fixture = CommitteeFixture::builder()
+            .committee_size(committee_size.try_into().unwrap())
+            .build();
+        let committee = fixture.committee();
+        let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+        let genesis = Certificate::genesis(&committee)
+            .iter()
+            .map(|x| x.digest())
+            .collect::<BTreeSet<_>>();
+        let (certificates, _next_parents) =
+            make_optimal_certificates(&committee, 1..=5, &genesis, &ids);
+        let certificates: Vec<_> = certificates.into_iter().collect();
+        bench_group.throughput(Throughput::Elements(certificates.len() as u64));
+
+        let store = NodeStorage::reopen(temp_dir(), None).certificate_store;
+        bench_group.bench_with_input(
+            BenchmarkId::new("one_by_one", committee_size),
+            &certificates,
+            |b, certificates| {
+                b.iter(|| {
+                    for certificate in certificates {
+                        store.write(certificate.clone()).unwrap();
+                    }
+                })
+            },
+        );
+
+        let store = NodeStorage::reopen(temp_dir(), None).certificate_store;
+        bench_group.bench_with_input(
+            BenchmarkId::new("batched", committee_size),
+            &certificates,
+            |b, certificates| b.iter(|| store.write_all(certificates.clone()).unwrap()),
+        );
+    }
+}
+
+/// Writes the payload entries reported concurrently by the workers of a committee, one by one
+/// and with the write batching of the primary.
+pub fn write_payloads(c: &mut Criterion) {
+    let mut bench_group = c.benchmark_group("write_payloads");
+    bench_group.sampling_mode(SamplingMode::Flat);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let _guard = runtime.enter();
+
+    static COMMITTEE_SIZES: [usize; 4] = [4, 10, 50, 100];
+    for committee_size in COMMITTEE_SIZES {
+        // One batch reported by a worker of every authority.
+        let digests: Vec<_> = (0..committee_size)
+            .map(|i| BatchDigest::new([i as u8; 32]))
+            .collect();
+        bench_group.throughput(Throughput::Elements(digests.len() as u64));
+
+        for (name, enabled) in [("one_by_one", false), ("batched", true)] {
+            let store = NodeStorage::reopen(temp_dir(), None)
+                .payload_store
+                .with_write_batching(&WriteBatchingParameters {
+                    enabled,
+                    ..WriteBatchingParameters::default()
+                });
+            bench_group.bench_with_input(
+                BenchmarkId::new(name, committee_size),
+                &digests,
+                |b, digests| {
+                    b.iter(|| {
+                        runtime.block_on(join_all(
+                            digests.iter().map(|digest| store.write_batched(digest, &0)),
+                        ))
+                    })
+                },
+            );
+        }
+    }
+}
+
+criterion_group! {
+    name = batched_writes;
+    config = Criterion::default().sample_size(100).noise_threshold(0.1);
+    targets = write_certificates, write_payloads
+}
+criterion_main!(batched_writes);
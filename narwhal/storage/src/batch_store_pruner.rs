@@ -0,0 +1,306 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::CertificateStore;
+use config::BatchRetentionPolicy;
+use prometheus::{
+    register_int_counter_with_registry, register_int_gauge_with_registry, IntCounter, IntGauge,
+    Registry,
+};
+use std::{collections::HashSet, time::Duration};
+use store::{rocks::DBMap, Map};
+use tracing::debug;
+use types::{Batch, BatchDigest, HeaderAPI, Round, StoreResult, Timestamp};
+
+#[derive(Clone)]
+pub struct BatchStorePrunerMetrics {
+    /// The number of batches removed from the batch store
+    pruned_batches: IntCounter,
+    /// The number of transaction bytes reclaimed from the batch store
+    pruned_bytes: IntCounter,
+    /// The highest round whose certificates had their batches pruned
+    last_pruned_round: IntGauge,
+}
+
+impl BatchStorePrunerMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            pruned_batches: register_int_counter_with_registry!(
+                "batch_store_pruned_batches",
+                "The number of batches removed from the batch store",
+                registry
+            )
+            .unwrap(),
+            pruned_bytes: register_int_counter_with_registry!(
+                "batch_store_pruned_bytes",
+                "The number of transaction bytes reclaimed from the batch store",
+                registry
+            )
+            .unwrap(),
+            last_pruned_round: register_int_gauge_with_registry!(
+                "batch_store_last_pruned_round",
+                "The highest round whose certificates had their batches pruned",
+                registry
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// Removes the batches that are no longer needed from the batch store, according to the
+/// configured retention policy.
+pub struct BatchStorePruner {
+    batch_store: DBMap<BatchDigest, Batch>,
+    certificate_store: CertificateStore,
+    policy: BatchRetentionPolicy,
+    metrics: BatchStorePrunerMetrics,
+    /// The highest round whose certificates had their batches pruned.
+    last_pruned_round: Round,
+}
+
+impl BatchStorePruner {
+    pub fn new(
+        batch_store: DBMap<BatchDigest, Batch>,
+        certificate_store: CertificateStore,
+        policy: BatchRetentionPolicy,
+        metrics: BatchStorePrunerMetrics,
+    ) -> Self {
+        Self {
+            batch_store,
+            certificate_store,
+            policy,
+            metrics,
+            last_pruned_round: 0,
+        }
+    }
+
+    /// Prunes the batch store given the current garbage collection round. Returns the number
+    /// of removed batches.
+    pub fn prune(&mut self, gc_round: Round) -> StoreResult<usize> {
+        match self.policy {
+            BatchRetentionPolicy::KeepAll => Ok(0),
+            BatchRetentionPolicy::Rounds(rounds) => {
+                self.prune_below(gc_round.saturating_sub(rounds), None)
+            }
+            BatchRetentionPolicy::Ttl(ttl) => self.prune_below(gc_round, Some(ttl)),
+        }
+    }
+
+    /// Prunes the batches of the certificates up to the given round, which were not pruned
+    /// yet. Given a ttl, stops at the first certificate with a batch created more recently,
+    /// whose round is pruned later. Only the payload of the headers is read, not the batches.
+    fn prune_below(&mut self, prune_round: Round, ttl: Option<Duration>) -> StoreResult<usize> {
+        if prune_round <= self.last_pruned_round {
+            return Ok(0);
+        }
+        let certificates = self
+            .certificate_store
+            .between_rounds(self.last_pruned_round + 1, prune_round)?;
+        // The certificates are sorted by round.
+        let mut pruned_round = prune_round;
+        if let Some(ttl) = ttl {
+            let fresh = certificates.iter().find(|certificate| {
+                certificate
+                    .header()
+                    .payload()
+                    .values()
+                    .any(|(_, created_at)| created_at.elapsed() < ttl)
+            });
+            if let Some(fresh) = fresh {
+                pruned_round = fresh.round() - 1;
+            }
+        }
+        if pruned_round <= self.last_pruned_round {
+            return Ok(0);
+        }
+        let digests = certificates
+            .iter()
+            .take_while(|certificate| certificate.round() <= pruned_round)
+            .flat_map(|certificate| certificate.header().payload().keys().copied())
+            .collect::<HashSet<_>>();
+        self.last_pruned_round = pruned_round;
+        self.metrics.last_pruned_round.set(pruned_round as i64);
+        self.remove(digests)
+    }
+
+    fn remove(&self, digests: HashSet<BatchDigest>) -> StoreResult<usize> {
+        let digests: Vec<_> = digests.into_iter().collect();
+        let mut removed = Vec::new();
+        let mut bytes = 0;
+        for (digest, batch) in digests.iter().zip(self.batch_store.multi_get(&digests)?) {
+            if let Some(batch) = batch {
+                bytes += batch.size();
+                removed.push(*digest);
+            }
+        }
+        self.batch_store.multi_remove(&removed)?;
+
+        self.metrics.pruned_batches.inc_by(removed.len() as u64);
+        self.metrics.pruned_bytes.inc_by(bytes as u64);
+        debug!(
+            "Pruned {} batches ({} B) from the batch store",
+            removed.len(),
+            bytes
+        );
+        Ok(removed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BatchStorePruner, BatchStorePrunerMetrics, NodeStorage};
+    use config::BatchRetentionPolicy;
+    use fastcrypto::hash::Hash;
+    use prometheus::Registry;
+    use std::time::Duration;
+    use store::Map;
+    use test_utils::{temp_dir, transaction, CommitteeFixture};
+    use types::{now, Batch, BatchAPI, Header};
+
+    #[tokio::test]
+    async fn test_prune_rounds() {
+        let fixture = CommitteeFixture::builder().build();
+        let committee = fixture.committee();
+        let author = fixture.authorities().next().unwrap();
+        let store = NodeStorage::reopen(temp_dir(), None);
+
+        // One certificate with its own batch for each of the rounds 1 to 10.
+        let mut batches = Vec::new();
+        for round in 1..=10 {
+            let batch = Batch::new(vec![transaction()]);
+            let header = Header::V1(
+                author
+                    .header_builder(&committee)
+                    .round(round)
+                    .with_payload_batch(batch.clone(), 0, 0)
+                    .build()
+                    .unwrap(),
+            );
+            store
+                .certificate_store
+                .write(fixture.certificate(&header))
+                .unwrap();
+            store.batch_store.insert(&batch.digest(), &batch).unwrap();
+            batches.push(batch);
+        }
+        // And a batch not referenced by any certificate.
+        let unreferenced = Batch::new(vec![transaction()]);
+        store
+            .batch_store
+            .insert(&unreferenced.digest(), &unreferenced)
+            .unwrap();
+
+        let metrics = BatchStorePrunerMetrics::new(&Registry::new());
+        let mut pruner = BatchStorePruner::new(
+            store.batch_store.clone(),
+            store.certificate_store.clone(),
+            BatchRetentionPolicy::Rounds(3),
+            metrics.clone(),
+        );
+
+        // The batches of the rounds 1 to 5 are pruned.
+        assert_eq!(pruner.prune(8).unwrap(), 5);
+        for (i, batch) in batches.iter().enumerate() {
+            let stored = store.batch_store.contains_key(&batch.digest()).unwrap();
+            assert_eq!(stored, i >= 5);
+        }
+        assert!(store
+            .batch_store
+            .contains_key(&unreferenced.digest())
+            .unwrap());
+        assert_eq!(metrics.pruned_batches.get(), 5);
+        assert_eq!(
+            metrics.pruned_bytes.get() as usize,
+            batches[..5].iter().map(|b| b.size()).sum::<usize>()
+        );
+        assert_eq!(metrics.last_pruned_round.get(), 5);
+
+        // Rounds are only pruned once.
+        assert_eq!(pruner.prune(8).unwrap(), 0);
+        assert_eq!(pruner.prune(9).unwrap(), 1);
+        assert!(!store
+            .batch_store
+            .contains_key(&batches[5].digest())
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_prune_ttl() {
+        let fixture = CommitteeFixture::builder().build();
+        let committee = fixture.committee();
+        let author = fixture.authorities().next().unwrap();
+        let store = NodeStorage::reopen(temp_dir(), None);
+
+        // One certificate with its own batch for each of the rounds 1 to 4, where only the
+        // batch of the round 3 is fresh.
+        let mut batches = Vec::new();
+        for round in 1..=4 {
+            let mut batch = Batch::new(vec![transaction()]);
+            if round != 3 {
+                batch.metadata_mut().created_at = now() - 10_000;
+            }
+            let header = Header::V1(
+                author
+                    .header_builder(&committee)
+                    .round(round)
+                    .with_payload_batch(batch.clone(), 0, batch.metadata().created_at)
+                    .build()
+                    .unwrap(),
+            );
+            store
+                .certificate_store
+                .write(fixture.certificate(&header))
+                .unwrap();
+            store.batch_store.insert(&batch.digest(), &batch).unwrap();
+            batches.push(batch);
+        }
+        // And an expired batch not referenced by any certificate.
+        let mut unreferenced = Batch::new(vec![transaction()]);
+        unreferenced.metadata_mut().created_at = now() - 10_000;
+        store
+            .batch_store
+            .insert(&unreferenced.digest(), &unreferenced)
+            .unwrap();
+
+        let metrics = BatchStorePrunerMetrics::new(&Registry::new());
+        let mut pruner = BatchStorePruner::new(
+            store.batch_store.clone(),
+            store.certificate_store.clone(),
+            BatchRetentionPolicy::Ttl(Duration::from_secs(5)),
+            metrics.clone(),
+        );
+
+        // The expired batches above the garbage collection round are kept.
+        assert_eq!(pruner.prune(1).unwrap(), 1);
+        assert_eq!(metrics.pruned_bytes.get() as usize, batches[0].size());
+
+        // The rounds after the fresh batch are kept until it expires.
+        assert_eq!(pruner.prune(10).unwrap(), 1);
+        for (i, batch) in batches.iter().enumerate() {
+            let stored = store.batch_store.contains_key(&batch.digest()).unwrap();
+            assert_eq!(stored, i >= 2);
+        }
+        assert_eq!(metrics.last_pruned_round.get(), 2);
+        assert!(store
+            .batch_store
+            .contains_key(&unreferenced.digest())
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_keep_all() {
+        let store = NodeStorage::reopen(temp_dir(), None);
+        let mut batch = Batch::new(vec![transaction()]);
+        batch.metadata_mut().created_at = 0;
+        store.batch_store.insert(&batch.digest(), &batch).unwrap();
+
+        let mut pruner = BatchStorePruner::new(
+            store.batch_store.clone(),
+            store.certificate_store.clone(),
+            BatchRetentionPolicy::KeepAll,
+            BatchStorePrunerMetrics::new(&Registry::new()),
+        );
+
+        assert_eq!(pruner.prune(1_000).unwrap(), 0);
+        assert!(store.batch_store.contains_key(&batch.digest()).unwrap());
+    }
+}
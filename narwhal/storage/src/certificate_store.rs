@@ -545,6 +545,36 @@ impl<T: Cache> CertificateStore<T> {
             .collect()
     }
 
+    /// Retrieves all the certificates with `from` <= round <= `to`.
+    /// The result is returned with certificates sorted in round asc order
+    pub fn between_rounds(&self, from: Round, to: Round) -> StoreResult<Vec<Certificate>> {
+        // Skip to a row at or before the requested round.
+        let mut iter = self.certificate_id_by_round.iter();
+        if from > 0 {
+            iter = iter.skip_to(&(from - 1, AuthorityIdentifier::default()))?;
+        }
+
+        let digests: Vec<_> = iter
+            .skip_while(|((r, _), _)| *r < from)
+            .take_while(|((r, _), _)| *r <= to)
+            .map(|(_, d)| d)
+            .collect();
+
+        // Fetch all those certificates from main storage, return an error if any one is missing.
+        self.certificates_by_id
+            .multi_get(digests.clone())?
+            .into_iter()
+            .map(|opt_cert| {
+                opt_cert.ok_or_else(|| {
+                    RocksDBError(format!(
+                        "Certificate with some digests not found, CertificateStore invariant violation: {:?}",
+                        digests
+                    ))
+                })
+            })
+            .collect()
+    }
+
     /// Retrieves origins with certificates in each round >= the provided round.
     pub fn origins_after_round(
         &self,
@@ -1011,6 +1041,39 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn test_between_rounds() {
+        // GIVEN
+        let store = new_store(temp_dir());
+        let certs = certificates(20);
+        store.write_all(certs.clone()).unwrap();
+
+        // WHEN
+        let result = store
+            .between_rounds(5, 10)
+            .expect("Error returned while reading between_rounds");
+
+        // THEN only the certificates of the requested rounds are returned, in round order.
+        let mut expected: Vec<_> = certs
+            .into_iter()
+            .filter(|c| (5..=10).contains(&c.round()))
+            .collect();
+        expected.sort_by_key(|c| c.round());
+        assert_eq!(result.len(), expected.len());
+        assert_eq!(result.len(), 6 * 4);
+
+        let mut last_round = 0;
+        for certificate in &result {
+            assert!(certificate.round() >= last_round);
+            last_round = certificate.round();
+        }
+        let result: HashSet<_> = result.iter().map(|c| c.digest()).collect();
+        assert!(expected.iter().all(|c| result.contains(&c.digest())));
+
+        // AND an empty range returns nothing.
+        assert!(store.between_rounds(10, 5).unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_notify_read() {
         let store = new_store(temp_dir());
@@ -0,0 +1,145 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use config::StorageEncryptionParameters;
+use fastcrypto::encoding::{Encoding, Hex};
+use rand::RngCore;
+use std::{fmt, io, path::Path, sync::Arc};
+use store::rocks::{TypedStoreError, ValueCipher};
+
+/// Encrypts the values of the stores with AES-256-GCM, under a key local to the node. Each value
+/// is sealed under a random nonce, which is prepended to its ciphertext.
+#[derive(Clone)]
+pub struct StoreCipher {
+    cipher: Aes256Gcm,
+}
+
+impl StoreCipher {
+    /// The length of the keys, in bytes.
+    pub const KEY_LENGTH: usize = 32;
+    const NONCE_LENGTH: usize = 12;
+
+    pub fn new(key: &[u8; Self::KEY_LENGTH]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new_from_slice(key).expect("The key has the length of AES-256"),
+        }
+    }
+
+    /// Loads the key hex encoded in the given file, as provisioned by the KMS of the node.
+    pub fn from_key_file(path: &Path) -> io::Result<Self> {
+        let encoded = std::fs::read_to_string(path)?;
+        let key: [u8; Self::KEY_LENGTH] = Hex::decode(encoded.trim())
+            .ok()
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{} does not hold a hex encoded key of {} bytes",
+                        path.display(),
+                        Self::KEY_LENGTH
+                    ),
+                )
+            })?;
+        Ok(Self::new(&key))
+    }
+
+    /// Loads the cipher of the stores set by the parameters, if any.
+    pub fn from_parameters(
+        parameters: &StorageEncryptionParameters,
+    ) -> io::Result<Option<Arc<dyn ValueCipher>>> {
+        let Some(key_file) = &parameters.key_file else {
+            return Ok(None);
+        };
+        Ok(Some(Arc::new(Self::from_key_file(key_file)?)))
+    }
+}
+
+impl fmt::Debug for StoreCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the key.
+        f.write_str("StoreCipher")
+    }
+}
+
+impl ValueCipher for StoreCipher {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, TypedStoreError> {
+        let mut nonce = [0u8; Self::NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|e| TypedStoreError::EncryptionError(e.to_string()))?;
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, TypedStoreError> {
+        if ciphertext.len() < Self::NONCE_LENGTH {
+            return Err(TypedStoreError::EncryptionError(
+                "The value is too short to be encrypted".to_string(),
+            ));
+        }
+        let (nonce, ciphertext) = ciphertext.split_at(Self::NONCE_LENGTH);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|e| TypedStoreError::EncryptionError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{NodeStorage, StoreCipher};
+    use fastcrypto::encoding::{Encoding, Hex};
+    use fastcrypto::hash::Hash;
+    use std::sync::Arc;
+    use store::rocks::{DBMap, ReadWriteOptions, ValueCipher};
+    use store::Map;
+    use test_utils::{fixture_batch_with_transactions, temp_dir};
+    use types::{Batch, BatchDigest};
+
+    #[test]
+    fn encrypt_decrypt() {
+        let cipher = StoreCipher::new(&[7; StoreCipher::KEY_LENGTH]);
+        let ciphertext = cipher.encrypt(b"value").unwrap();
+        assert_ne!(&ciphertext[..], b"value");
+        assert_eq!(cipher.decrypt(&ciphertext).unwrap(), b"value");
+
+        let other = StoreCipher::new(&[8; StoreCipher::KEY_LENGTH]);
+        assert!(other.decrypt(&ciphertext).is_err());
+        assert!(cipher.decrypt(&ciphertext[..4]).is_err());
+    }
+
+    #[test]
+    fn key_file() {
+        let dir = temp_dir();
+        let path = dir.join("key");
+        std::fs::write(&path, format!("{}\n", Hex::encode([7; 32]))).unwrap();
+        assert!(StoreCipher::from_key_file(&path).is_ok());
+
+        std::fs::write(&path, Hex::encode([7; 16])).unwrap();
+        assert!(StoreCipher::from_key_file(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn encrypted_batch_store() {
+        let cipher: Arc<dyn ValueCipher> =
+            Arc::new(StoreCipher::new(&[7; StoreCipher::KEY_LENGTH]));
+        let store = NodeStorage::reopen_with_cipher(temp_dir(), None, Some(cipher));
+        let batch = fixture_batch_with_transactions(10);
+        let digest = batch.digest();
+        store.batch_store.insert(&digest, &batch).unwrap();
+        assert_eq!(store.batch_store.get(&digest).unwrap(), Some(batch));
+
+        // The batch is not readable without the key.
+        let plain = DBMap::<BatchDigest, Batch>::reopen(
+            &store.batch_store.rocksdb,
+            Some(NodeStorage::BATCHES_CF),
+            &ReadWriteOptions::default(),
+        )
+        .unwrap();
+        assert!(plain.get(&digest).is_err());
+    }
+}
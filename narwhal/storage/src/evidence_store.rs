@@ -0,0 +1,177 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::NodeStorage;
+use config::AuthorityIdentifier;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use store::rocks::{open_cf, DBMap, MetricConf, ReadWriteOptions, RocksDB};
+use store::{reopen, Map};
+use types::{Evidence, EvidenceDigest, StoreResult};
+
+/// The maximum number of evidences recorded for an offender. The first ones are kept, as a
+/// sample of its misbehaviors.
+pub const MAX_EVIDENCES_PER_OFFENDER: usize = 100;
+
+/// The evidences of the misbehaviors detected by the primary, by offender.
+#[derive(Clone)]
+pub struct EvidenceStore {
+    store: DBMap<(AuthorityIdentifier, EvidenceDigest), Evidence>,
+    // The number of evidences recorded for each offender.
+    counts: Arc<Mutex<HashMap<AuthorityIdentifier, usize>>>,
+}
+
+impl EvidenceStore {
+    pub fn new(evidence_store: DBMap<(AuthorityIdentifier, EvidenceDigest), Evidence>) -> Self {
+        let mut counts = HashMap::new();
+        for ((offender, _), _) in evidence_store.iter() {
+            *counts.entry(offender).or_default() += 1;
+        }
+        Self {
+            store: evidence_store,
+            counts: Arc::new(Mutex::new(counts)),
+        }
+    }
+
+    /// Opens the store in the provided database of the node storage.
+    pub fn reopen(rocksdb: &Arc<RocksDB>) -> Self {
+        let map = DBMap::reopen(
+            rocksdb,
+            Some(NodeStorage::EVIDENCE_CF),
+            &ReadWriteOptions::default(),
+        )
+        .expect("Cannot open the evidence store");
+        Self::new(map)
+    }
+
+    pub fn new_for_tests() -> Self {
+        let rocksdb = open_cf(
+            tempfile::tempdir().unwrap(),
+            None,
+            MetricConf::default(),
+            &[NodeStorage::EVIDENCE_CF],
+        )
+        .expect("Cannot open database");
+        let map = reopen!(&rocksdb,
+            NodeStorage::EVIDENCE_CF;<(AuthorityIdentifier, EvidenceDigest), Evidence>);
+        Self::new(map)
+    }
+
+    /// Records the evidence. Returns false if the same misbehavior was already recorded, or if
+    /// `MAX_EVIDENCES_PER_OFFENDER` evidences of its offender were.
+    pub fn write(&self, evidence: &Evidence) -> StoreResult<bool> {
+        let mut counts = self.counts.lock().unwrap();
+        let count = counts.entry(evidence.offender).or_default();
+        if *count >= MAX_EVIDENCES_PER_OFFENDER {
+            return Ok(false);
+        }
+        let key = (evidence.offender, evidence.digest());
+        if self.store.contains_key(&key)? {
+            return Ok(false);
+        }
+        self.store.insert(&key, evidence)?;
+        *count += 1;
+        Ok(true)
+    }
+
+    /// Returns all the recorded evidences, by offender.
+    pub fn read_all(&self) -> Vec<Evidence> {
+        self.store.iter().map(|(_, evidence)| evidence).collect()
+    }
+
+    /// Returns the recorded evidences of the misbehaviors of the authority.
+    pub fn read_by_offender(&self, offender: AuthorityIdentifier) -> StoreResult<Vec<Evidence>> {
+        Ok(self
+            .store
+            .iter()
+            .skip_to(&(offender, EvidenceDigest::default()))?
+            .take_while(|((authority, _), _)| *authority == offender)
+            .map(|(_, evidence)| evidence)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvidenceStore, MAX_EVIDENCES_PER_OFFENDER};
+    use config::AuthorityIdentifier;
+    use test_utils::{fixture_batch_with_transactions, CommitteeFixture};
+    use types::{Evidence, Header, HeaderV1Builder};
+
+    #[test]
+    fn test_write_and_read_by_offender() {
+        let store = EvidenceStore::new_for_tests();
+        let fixture = CommitteeFixture::builder().build();
+        let authorities: Vec<_> = fixture.authorities().collect();
+
+        let header = |author: AuthorityIdentifier, transactions| {
+            let header = HeaderV1Builder::default()
+                .author(author)
+                .round(1)
+                .epoch(0)
+                .parents(Default::default())
+                .with_payload_batch(fixture_batch_with_transactions(transactions), 0, 0)
+                .build()
+                .unwrap();
+            Header::V1(header)
+        };
+        let offender = authorities[0].id();
+        let other = authorities[1].id();
+        let evidence = Evidence::equivocating_headers(header(offender, 1), header(offender, 2));
+        assert!(store.write(&evidence).unwrap());
+        assert!(store
+            .write(&Evidence::equivocating_headers(
+                header(other, 1),
+                header(other, 2)
+            ))
+            .unwrap());
+
+        // The same misbehavior is recorded once.
+        assert!(!store.write(&evidence).unwrap());
+
+        assert_eq!(store.read_all().len(), 2);
+        let recorded = store.read_by_offender(offender).unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].offender, offender);
+        assert_eq!(recorded[0].digest(), evidence.digest());
+        assert!(store
+            .read_by_offender(authorities[2].id())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_cap_evidences_per_offender() {
+        let store = EvidenceStore::new_for_tests();
+        let fixture = CommitteeFixture::builder().build();
+        let offender = fixture.authorities().next().unwrap().id();
+
+        let header = |transactions| {
+            let header = HeaderV1Builder::default()
+                .author(offender)
+                .round(1)
+                .epoch(0)
+                .parents(Default::default())
+                .with_payload_batch(fixture_batch_with_transactions(transactions), 0, 0)
+                .build()
+                .unwrap();
+            Header::V1(header)
+        };
+        for i in 0..MAX_EVIDENCES_PER_OFFENDER {
+            let evidence = Evidence::equivocating_headers(header(i), header(i + 1));
+            assert!(store.write(&evidence).unwrap());
+        }
+
+        // The evidences past the cap are not recorded, even after reopening the store.
+        let evidence =
+            Evidence::equivocating_headers(header(0), header(MAX_EVIDENCES_PER_OFFENDER));
+        assert!(!store.write(&evidence).unwrap());
+        let reopened = EvidenceStore::new(store.store.clone());
+        assert!(!reopened.write(&evidence).unwrap());
+        assert_eq!(
+            store.read_by_offender(offender).unwrap().len(),
+            MAX_EVIDENCES_PER_OFFENDER
+        );
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use store::TypedStoreError;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error};
+
+/// The number of writes which can be queued before the writers wait for the queued ones to be
+/// committed.
+const QUEUE_CAPACITY: usize = 10_000;
+
+struct PendingWrite<T> {
+    entries: Vec<T>,
+    ack: Option<oneshot::Sender<Result<(), TypedStoreError>>>,
+}
+
+/// Commits the writes of concurrent writers in groups: the writes queued while a group is being
+/// committed are committed together, with a single call to `commit` writing them in one batch,
+/// instead of one write each.
+///
+/// By default, a writer waits until its write is committed. With `async_flush`, it only waits
+/// until its write is queued, so the write may not be visible yet when it returns, and a failure
+/// to commit it is only logged.
+///
+/// The commit task stops once all the handles are dropped, after committing the queued writes.
+pub struct GroupCommit<T> {
+    sender: mpsc::Sender<PendingWrite<T>>,
+    async_flush: bool,
+}
+
+impl<T> Clone for GroupCommit<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            async_flush: self.async_flush,
+        }
+    }
+}
+
+impl<T: Send + 'static> GroupCommit<T> {
+    /// Spawns the task committing the writes, in groups of at most `max_batch_size` entries.
+    /// Must be called within a tokio runtime.
+    pub fn spawn(
+        max_batch_size: usize,
+        async_flush: bool,
+        commit: impl Fn(&[T]) -> Result<(), TypedStoreError> + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(Self::run(max_batch_size.max(1), receiver, commit));
+        Self {
+            sender,
+            async_flush,
+        }
+    }
+
+    /// Writes the entries, along with the other writes queued meanwhile.
+    pub async fn write(&self, entries: Vec<T>) -> Result<(), TypedStoreError> {
+        let shut_down = || TypedStoreError::RocksDBError("group commit is shut down".to_string());
+        let (ack, committed) = if self.async_flush {
+            (None, None)
+        } else {
+            let (ack, committed) = oneshot::channel();
+            (Some(ack), Some(committed))
+        };
+        self.sender
+            .send(PendingWrite { entries, ack })
+            .await
+            .map_err(|_| shut_down())?;
+        match committed {
+            Some(committed) => committed.await.map_err(|_| shut_down())?,
+            None => Ok(()),
+        }
+    }
+
+    async fn run(
+        max_batch_size: usize,
+        mut receiver: mpsc::Receiver<PendingWrite<T>>,
+        commit: impl Fn(&[T]) -> Result<(), TypedStoreError>,
+    ) {
+        while let Some(first) = receiver.recv().await {
+            let mut entries = first.entries;
+            let mut acks: Vec<_> = first.ack.into_iter().collect();
+            while entries.len() < max_batch_size {
+                let Ok(write) = receiver.try_recv() else {
+                    break;
+                };
+                entries.extend(write.entries);
+                acks.extend(write.ack);
+            }
+
+            let result = commit(&entries);
+            match &result {
+                Ok(()) => debug!("Committed a group of {} writes", entries.len()),
+                Err(e) => error!("Failed to commit a group of {} writes: {e}", entries.len()),
+            }
+            for ack in acks {
+                let _ = ack.send(result.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GroupCommit;
+    use futures::future::join_all;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use store::{
+        reopen,
+        rocks::{open_cf, DBMap, MetricConf, ReadWriteOptions},
+        Map,
+    };
+
+    const CF: &str = "group_commit";
+
+    fn new_store() -> DBMap<u64, u64> {
+        let rocksdb = open_cf(
+            tempfile::tempdir().unwrap(),
+            None,
+            MetricConf::default(),
+            &[CF],
+        )
+        .unwrap();
+        reopen!(&rocksdb, CF;<u64, u64>)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writes_are_committed() {
+        let store = new_store();
+        let committed = Arc::new(AtomicUsize::new(0));
+        let group_commit = GroupCommit::spawn(16, false, {
+            let (store, committed) = (store.clone(), committed.clone());
+            move |entries: &[(u64, u64)]| {
+                store.multi_insert(entries.iter().copied())?;
+                committed.fetch_add(entries.len(), Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        let writes = (0..100u64).map(|i| {
+            let group_commit = group_commit.clone();
+            tokio::spawn(async move { group_commit.write(vec![(i, i * 2)]).await })
+        });
+        for result in join_all(writes).await {
+            result.unwrap().unwrap();
+        }
+
+        // Every write is visible once acknowledged.
+        assert_eq!(committed.load(Ordering::SeqCst), 100);
+        for i in 0..100u64 {
+            assert_eq!(store.get(&i).unwrap(), Some(i * 2));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_flush_commits_queued_writes() {
+        let store = new_store();
+        let (tx_committed, mut rx_committed) = tokio::sync::mpsc::unbounded_channel();
+        let group_commit = GroupCommit::spawn(4, true, {
+            let store = store.clone();
+            move |entries: &[(u64, u64)]| {
+                store.multi_insert(entries.iter().copied())?;
+                tx_committed.send(entries.len()).unwrap();
+                Ok(())
+            }
+        });
+
+        for i in 0..10u64 {
+            group_commit.write(vec![(i, i)]).await.unwrap();
+        }
+        let mut committed = 0;
+        while committed < 10 {
+            let count = rx_committed.recv().await.unwrap();
+            assert!(count <= 4, "a group is larger than the max batch size");
+            committed += count;
+        }
+        for i in 0..10u64 {
+            assert_eq!(store.get(&i).unwrap(), Some(i));
+        }
+    }
+}
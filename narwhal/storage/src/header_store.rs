@@ -2,20 +2,29 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::NodeStorage;
+use config::AuthorityIdentifier;
+use std::iter;
 use store::rocks::ReadWriteOptions;
 use store::rocks::{open_cf, DBMap, MetricConf};
 use store::{reopen, Map, TypedStoreError};
-use types::{Header, HeaderDigest};
+use types::{Header, HeaderAPI, HeaderDigest, Round};
 
 #[derive(Clone)]
 pub struct HeaderStore {
     store: DBMap<HeaderDigest, Header>,
+    /// The digest of the first header stored for each author and round, kept to detect and
+    /// prove equivocations.
+    header_digest_by_author: DBMap<(AuthorityIdentifier, Round), HeaderDigest>,
 }
 
 impl HeaderStore {
-    pub fn new(header_store: DBMap<HeaderDigest, Header>) -> Self {
+    pub fn new(
+        header_store: DBMap<HeaderDigest, Header>,
+        header_digest_by_author: DBMap<(AuthorityIdentifier, Round), HeaderDigest>,
+    ) -> Self {
         Self {
             store: header_store,
+            header_digest_by_author,
         }
     }
 
@@ -24,19 +33,45 @@ impl HeaderStore {
             tempfile::tempdir().unwrap(),
             None,
             MetricConf::default(),
-            &[NodeStorage::HEADERS_CF],
+            &[
+                NodeStorage::HEADERS_CF,
+                NodeStorage::HEADER_DIGEST_BY_AUTHOR_CF,
+            ],
         )
         .expect("Cannot open database");
-        let map = reopen!(&rocksdb, NodeStorage::HEADERS_CF;<HeaderDigest, Header>);
-        Self::new(map)
+        let (header_map, header_digest_by_author_map) = reopen!(&rocksdb,
+            NodeStorage::HEADERS_CF;<HeaderDigest, Header>,
+            NodeStorage::HEADER_DIGEST_BY_AUTHOR_CF;<(AuthorityIdentifier, Round), HeaderDigest>);
+        Self::new(header_map, header_digest_by_author_map)
     }
 
     pub fn read(&self, id: &HeaderDigest) -> Result<Option<Header>, TypedStoreError> {
         self.store.get(id)
     }
 
+    /// Returns the first header stored for the author and round.
+    pub fn read_by_author_round(
+        &self,
+        author: AuthorityIdentifier,
+        round: Round,
+    ) -> Result<Option<Header>, TypedStoreError> {
+        match self.header_digest_by_author.get(&(author, round))? {
+            Some(digest) => self.read(&digest),
+            None => Ok(None),
+        }
+    }
+
     pub fn write(&self, header: &Header) -> Result<(), TypedStoreError> {
-        self.store.insert(&header.digest(), header)
+        let digest = header.digest();
+        let mut batch = self.store.batch();
+        batch = batch.insert_batch(&self.store, iter::once((digest, header.clone())))?;
+
+        // Only the first header of the author for the round is indexed.
+        let key = (header.author(), header.round());
+        if !self.header_digest_by_author.contains_key(&key)? {
+            batch = batch.insert_batch(&self.header_digest_by_author, iter::once((key, digest)))?;
+        }
+        batch.write()
     }
 
     pub fn remove_all(
@@ -1,16 +1,26 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod batch_store_pruner;
 mod certificate_store;
+mod encryption;
+mod evidence_store;
+mod group_commit;
 mod header_store;
 mod node_store;
 mod payload_store;
 mod proposer_store;
+mod store_maintenance;
 mod vote_digest_store;
 
+pub use batch_store_pruner::*;
 pub use certificate_store::*;
+pub use encryption::*;
+pub use evidence_store::*;
+pub use group_commit::*;
 pub use header_store::*;
 pub use node_store::*;
 pub use payload_store::*;
 pub use proposer_store::*;
+pub use store_maintenance::*;
 pub use vote_digest_store::*;
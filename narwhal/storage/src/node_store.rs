@@ -14,10 +14,10 @@ use std::time::Duration;
 use store::metrics::SamplingInterval;
 use store::reopen;
 use store::rocks::DBMap;
-use store::rocks::{open_cf, MetricConf, ReadWriteOptions};
+use store::rocks::{open_cf, MetricConf, ReadWriteOptions, ValueCipher};
 use types::{
-    Batch, BatchDigest, Certificate, CertificateDigest, CommittedSubDagShell, ConsensusStore,
-    Header, HeaderDigest, Round, SequenceNumber, VoteInfo,
+    Batch, BatchDigest, Certificate, CertificateDigest, CommittedSubDagShellV1, ConsensusStore,
+    Header, HeaderDigest, Round, SequenceNumber, VersionedCommittedSubDagShell, VoteInfo,
 };
 
 // A type alias marking the "payload" tokens sent by workers to their primary as batch acknowledgements
@@ -40,6 +40,7 @@ impl NodeStorage {
     pub(crate) const LAST_PROPOSED_CF: &'static str = "last_proposed";
     pub(crate) const VOTES_CF: &'static str = "votes";
     pub(crate) const HEADERS_CF: &'static str = "headers";
+    pub(crate) const HEADER_DIGEST_BY_AUTHOR_CF: &'static str = "header_digest_by_author";
     pub(crate) const CERTIFICATES_CF: &'static str = "certificates";
     pub(crate) const CERTIFICATE_DIGEST_BY_ROUND_CF: &'static str = "certificate_digest_by_round";
     pub(crate) const CERTIFICATE_DIGEST_BY_ORIGIN_CF: &'static str = "certificate_digest_by_origin";
@@ -47,6 +48,13 @@ impl NodeStorage {
     pub(crate) const BATCHES_CF: &'static str = "batches";
     pub(crate) const LAST_COMMITTED_CF: &'static str = "last_committed";
     pub(crate) const SUB_DAG_INDEX_CF: &'static str = "sub_dag";
+    pub(crate) const SUB_DAG_INDEX_V2_CF: &'static str = "sub_dag_v2";
+    /// The transactions accepted by the workers, until they are included in a batch.
+    pub const TRANSACTION_LOG_CF: &'static str = "transaction_log";
+    /// The transactions spilled to disk by the workers while they are overloaded.
+    pub const SPILLED_TRANSACTIONS_CF: &'static str = "spilled_transactions";
+    /// The evidences of the misbehaviors detected by the primary.
+    pub const EVIDENCE_CF: &'static str = "evidence";
 
     // 100 nodes * 60 rounds (assuming 1 round/sec this will hold data for about the last 1 minute
     // which should be more than enough for advancing the protocol and also help other nodes)
@@ -57,6 +65,16 @@ impl NodeStorage {
     pub fn reopen<Path: AsRef<std::path::Path> + Send>(
         store_path: Path,
         certificate_store_cache_metrics: Option<CertificateStoreCacheMetrics>,
+    ) -> Self {
+        Self::reopen_with_cipher(store_path, certificate_store_cache_metrics, None)
+    }
+
+    /// Open or reopen all the storage of the node, encrypting the batches and the certificates
+    /// with the given cipher if any. The stores must always be reopened with the same cipher.
+    pub fn reopen_with_cipher<Path: AsRef<std::path::Path> + Send>(
+        store_path: Path,
+        certificate_store_cache_metrics: Option<CertificateStoreCacheMetrics>,
+        cipher: Option<Arc<dyn ValueCipher>>,
     ) -> Self {
         let mut metrics_conf = MetricConf::with_db_name("consensus_epoch");
         metrics_conf.read_sample_interval = SamplingInterval::new(Duration::from_secs(60), 0);
@@ -68,6 +86,7 @@ impl NodeStorage {
                 Self::LAST_PROPOSED_CF,
                 Self::VOTES_CF,
                 Self::HEADERS_CF,
+                Self::HEADER_DIGEST_BY_AUTHOR_CF,
                 Self::CERTIFICATES_CF,
                 Self::CERTIFICATE_DIGEST_BY_ROUND_CF,
                 Self::CERTIFICATE_DIGEST_BY_ORIGIN_CF,
@@ -75,6 +94,10 @@ impl NodeStorage {
                 Self::BATCHES_CF,
                 Self::LAST_COMMITTED_CF,
                 Self::SUB_DAG_INDEX_CF,
+                Self::SUB_DAG_INDEX_V2_CF,
+                Self::TRANSACTION_LOG_CF,
+                Self::SPILLED_TRANSACTIONS_CF,
+                Self::EVIDENCE_CF,
             ],
         )
         .expect("Cannot open database");
@@ -83,6 +106,7 @@ impl NodeStorage {
             last_proposed_map,
             votes_map,
             header_map,
+            header_digest_by_author_map,
             certificate_map,
             certificate_digest_by_round_map,
             certificate_digest_by_origin_map,
@@ -90,22 +114,33 @@ impl NodeStorage {
             batch_map,
             last_committed_map,
             sub_dag_index_map,
+            sub_dag_index_v2_map,
         ) = reopen!(&rocksdb,
             Self::LAST_PROPOSED_CF;<ProposerKey, Header>,
             Self::VOTES_CF;<AuthorityIdentifier, VoteInfo>,
             Self::HEADERS_CF;<HeaderDigest, Header>,
+            Self::HEADER_DIGEST_BY_AUTHOR_CF;<(AuthorityIdentifier, Round), HeaderDigest>,
             Self::CERTIFICATES_CF;<CertificateDigest, Certificate>,
             Self::CERTIFICATE_DIGEST_BY_ROUND_CF;<(Round, AuthorityIdentifier), CertificateDigest>,
             Self::CERTIFICATE_DIGEST_BY_ORIGIN_CF;<(AuthorityIdentifier, Round), CertificateDigest>,
             Self::PAYLOAD_CF;<(BatchDigest, WorkerId), PayloadToken>,
             Self::BATCHES_CF;<BatchDigest, Batch>,
             Self::LAST_COMMITTED_CF;<AuthorityIdentifier, Round>,
-            Self::SUB_DAG_INDEX_CF;<SequenceNumber, CommittedSubDagShell>
+            Self::SUB_DAG_INDEX_CF;<SequenceNumber, CommittedSubDagShellV1>,
+            Self::SUB_DAG_INDEX_V2_CF;<SequenceNumber, VersionedCommittedSubDagShell>
         );
 
+        let (certificate_map, batch_map) = match cipher {
+            Some(cipher) => (
+                certificate_map.with_value_cipher(cipher.clone()),
+                batch_map.with_value_cipher(cipher),
+            ),
+            None => (certificate_map, batch_map),
+        };
+
         let proposer_store = ProposerStore::new(last_proposed_map);
         let vote_digest_store = VoteDigestStore::new(votes_map);
-        let header_store = HeaderStore::new(header_map);
+        let header_store = HeaderStore::new(header_map, header_digest_by_author_map);
 
         let certificate_store_cache = CertificateStoreCache::new(
             NonZeroUsize::new(Self::CERTIFICATE_STORE_CACHE_SIZE).unwrap(),
@@ -119,7 +154,11 @@ impl NodeStorage {
         );
         let payload_store = PayloadStore::new(payload_map);
         let batch_store = batch_map;
-        let consensus_store = Arc::new(ConsensusStore::new(last_committed_map, sub_dag_index_map));
+        let consensus_store = Arc::new(ConsensusStore::new(
+            last_committed_map,
+            sub_dag_index_map,
+            sub_dag_index_v2_map,
+        ));
 
         Self {
             proposer_store,
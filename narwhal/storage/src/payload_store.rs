@@ -1,8 +1,8 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{NodeStorage, PayloadToken};
-use config::WorkerId;
+use crate::{GroupCommit, NodeStorage, PayloadToken};
+use config::{WorkerId, WriteBatchingParameters};
 use mysten_common::sync::notify_read::NotifyRead;
 use std::sync::Arc;
 use store::reopen;
@@ -17,6 +17,9 @@ pub struct PayloadStore {
 
     /// Senders to notify for a write that happened for the specified batch digest and worker id
     notify_subscribers: Arc<NotifyRead<(BatchDigest, WorkerId), ()>>,
+
+    /// Commits the writes of `write_batched` in groups, if the writes are batched.
+    group_commit: Option<GroupCommit<(BatchDigest, WorkerId)>>,
 }
 
 impl PayloadStore {
@@ -24,6 +27,32 @@ impl PayloadStore {
         Self {
             store: payload_store,
             notify_subscribers: Arc::new(NotifyRead::new()),
+            group_commit: None,
+        }
+    }
+
+    /// Returns the store batching the writes of `write_batched` as configured. Must be called
+    /// within a tokio runtime when the batching is enabled.
+    pub fn with_write_batching(&self, parameters: &WriteBatchingParameters) -> Self {
+        if !parameters.enabled {
+            return self.clone();
+        }
+        let store = self.store.clone();
+        let notify_subscribers = self.notify_subscribers.clone();
+        let group_commit = GroupCommit::spawn(
+            parameters.max_batch_size,
+            parameters.async_flush,
+            move |keys: &[(BatchDigest, WorkerId)]| {
+                store.multi_insert(keys.iter().map(|key| (key, 0u8)))?;
+                for key in keys {
+                    notify_subscribers.notify(key, &());
+                }
+                Ok(())
+            },
+        );
+        Self {
+            group_commit: Some(group_commit),
+            ..self.clone()
         }
     }
 
@@ -46,6 +75,19 @@ impl PayloadStore {
         Ok(())
     }
 
+    /// Writes the entry along with the other entries written concurrently, in a single batch, if
+    /// the writes are batched. Otherwise, same as `write`.
+    pub async fn write_batched(
+        &self,
+        digest: &BatchDigest,
+        worker_id: &WorkerId,
+    ) -> Result<(), TypedStoreError> {
+        match &self.group_commit {
+            Some(group_commit) => group_commit.write(vec![(*digest, *worker_id)]).await,
+            None => self.write(digest, worker_id),
+        }
+    }
+
     /// Writes all the provided values atomically in store - either all will succeed or nothing will
     /// be stored.
     pub fn write_all(
@@ -116,6 +158,7 @@ impl PayloadStore {
 #[cfg(test)]
 mod tests {
     use crate::PayloadStore;
+    use config::WriteBatchingParameters;
     use fastcrypto::hash::Hash;
     use futures::future::join_all;
     use types::Batch;
@@ -155,4 +198,33 @@ mod tests {
             assert!(token.is_ok());
         }
     }
+
+    #[tokio::test]
+    async fn test_write_batched() {
+        let store =
+            PayloadStore::new_for_tests().with_write_batching(&WriteBatchingParameters::default());
+        let digests: Vec<_> = (0..10)
+            .map(|i| test_utils::fixture_batch_with_transactions(i).digest())
+            .collect();
+
+        let waiters: Vec<_> = digests
+            .iter()
+            .map(|digest| {
+                let store = store.clone();
+                let digest = *digest;
+                tokio::spawn(async move { store.notify_contains(digest, 1).await })
+            })
+            .collect();
+        let writes = digests.iter().map(|digest| store.write_batched(digest, &1));
+        for result in join_all(writes).await {
+            result.unwrap();
+        }
+
+        for digest in &digests {
+            assert!(store.contains(*digest, 1).unwrap());
+        }
+        for result in join_all(waiters).await {
+            result.unwrap().unwrap();
+        }
+    }
 }
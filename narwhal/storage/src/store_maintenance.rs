@@ -0,0 +1,248 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::NodeStorage;
+use prometheus::{
+    register_int_counter_vec_with_registry, register_int_gauge_vec_with_registry, IntCounterVec,
+    IntGaugeVec, Registry,
+};
+use std::{collections::BTreeMap, sync::Arc};
+use store::rocks::{ColumnFamilyStats, RocksDB, TypedStoreError};
+use tracing::info;
+use types::StoreResult;
+
+#[derive(Clone)]
+pub struct StoreMaintenanceMetrics {
+    /// The estimated size of the live data of each column family
+    live_data_size: IntGaugeVec,
+    /// The size of the SST files of each column family
+    total_sst_files_size: IntGaugeVec,
+    /// The number of deletions not compacted away yet in each column family
+    num_tombstones: IntGaugeVec,
+    /// The estimated number of bytes compaction needs to rewrite in each column family
+    pending_compaction_bytes: IntGaugeVec,
+    /// The number of compactions of each column family, by trigger
+    compactions: IntCounterVec,
+}
+
+impl StoreMaintenanceMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            live_data_size: register_int_gauge_vec_with_registry!(
+                "store_cf_live_data_size",
+                "The estimated size of the live data of each column family",
+                &["cf"],
+                registry
+            )
+            .unwrap(),
+            total_sst_files_size: register_int_gauge_vec_with_registry!(
+                "store_cf_total_sst_files_size",
+                "The size of the SST files of each column family",
+                &["cf"],
+                registry
+            )
+            .unwrap(),
+            num_tombstones: register_int_gauge_vec_with_registry!(
+                "store_cf_num_tombstones",
+                "The number of deletions not compacted away yet in each column family",
+                &["cf"],
+                registry
+            )
+            .unwrap(),
+            pending_compaction_bytes: register_int_gauge_vec_with_registry!(
+                "store_cf_pending_compaction_bytes",
+                "The estimated number of bytes compaction needs to rewrite in each column family",
+                &["cf"],
+                registry
+            )
+            .unwrap(),
+            compactions: register_int_counter_vec_with_registry!(
+                "store_cf_compactions",
+                "The number of compactions of each column family, by trigger",
+                &["cf", "trigger"],
+                registry
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// What triggered a compaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompactionTrigger {
+    /// Requested through the admin interface.
+    Manual,
+    /// Run periodically, as configured.
+    Scheduled,
+}
+
+impl CompactionTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompactionTrigger::Manual => "manual",
+            CompactionTrigger::Scheduled => "scheduled",
+        }
+    }
+}
+
+/// Reports the statistics of the column families of the header, certificate and batch stores,
+/// and compacts them on demand.
+#[derive(Clone)]
+pub struct StoreMaintenance {
+    rocksdb: Arc<RocksDB>,
+    column_families: Vec<&'static str>,
+    metrics: StoreMaintenanceMetrics,
+    /// Held while compacting, so that a single compaction runs at a time.
+    compaction_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl StoreMaintenance {
+    /// The maintained column families, those of the stores that grow with the DAG.
+    const COLUMN_FAMILIES: [&'static str; 6] = [
+        NodeStorage::HEADERS_CF,
+        NodeStorage::CERTIFICATES_CF,
+        NodeStorage::CERTIFICATE_DIGEST_BY_ROUND_CF,
+        NodeStorage::CERTIFICATE_DIGEST_BY_ORIGIN_CF,
+        NodeStorage::PAYLOAD_CF,
+        NodeStorage::BATCHES_CF,
+    ];
+
+    /// Maintains the column families of the node storage opened in the provided database.
+    pub fn new(rocksdb: Arc<RocksDB>, metrics: StoreMaintenanceMetrics) -> Self {
+        let column_families = Self::COLUMN_FAMILIES
+            .into_iter()
+            .filter(|cf_name| rocksdb.cf_handle(cf_name).is_some())
+            .collect();
+        Self {
+            rocksdb,
+            column_families,
+            metrics,
+            compaction_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    pub fn column_families(&self) -> &[&'static str] {
+        &self.column_families
+    }
+
+    /// Returns the statistics of the maintained column families, and reports them as metrics.
+    pub fn stats(&self) -> StoreResult<BTreeMap<&'static str, ColumnFamilyStats>> {
+        let mut all_stats = BTreeMap::new();
+        for cf_name in &self.column_families {
+            let Some(stats) = self.rocksdb.column_family_stats(cf_name)? else {
+                continue;
+            };
+            let labels = [*cf_name];
+            self.metrics
+                .live_data_size
+                .with_label_values(&labels)
+                .set(stats.live_data_size as i64);
+            self.metrics
+                .total_sst_files_size
+                .with_label_values(&labels)
+                .set(stats.total_sst_files_size as i64);
+            self.metrics
+                .num_tombstones
+                .with_label_values(&labels)
+                .set(stats.num_tombstones as i64);
+            self.metrics
+                .pending_compaction_bytes
+                .with_label_values(&labels)
+                .set(stats.pending_compaction_bytes as i64);
+            all_stats.insert(*cf_name, stats);
+        }
+        Ok(all_stats)
+    }
+
+    /// Compacts the provided column family, or all the maintained column families if None.
+    /// Returns the compacted column families.
+    pub async fn compact(
+        &self,
+        cf_name: Option<&str>,
+        trigger: CompactionTrigger,
+    ) -> StoreResult<Vec<&'static str>> {
+        let column_families = match cf_name {
+            Some(cf_name) => vec![*self
+                .column_families
+                .iter()
+                .find(|maintained| **maintained == cf_name)
+                .ok_or_else(|| TypedStoreError::UnregisteredColumn(cf_name.to_string()))?],
+            None => self.column_families.clone(),
+        };
+
+        let _guard = self.compaction_lock.lock().await;
+        for cf_name in &column_families {
+            let rocksdb = self.rocksdb.clone();
+            let cf = *cf_name;
+            tokio::task::spawn_blocking(move || rocksdb.compact_column_family(cf))
+                .await
+                .map_err(|e| TypedStoreError::RocksDBError(e.to_string()))?;
+            self.metrics
+                .compactions
+                .with_label_values(&[cf_name, trigger.as_str()])
+                .inc();
+            info!("Compacted column family {cf_name} ({})", trigger.as_str());
+        }
+        Ok(column_families)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CompactionTrigger, NodeStorage, StoreMaintenance, StoreMaintenanceMetrics};
+    use fastcrypto::hash::Hash;
+    use prometheus::Registry;
+    use store::{rocks::TypedStoreError, Map};
+    use test_utils::{temp_dir, transaction};
+    use types::Batch;
+
+    #[tokio::test]
+    async fn test_compact_batches() {
+        let store = NodeStorage::reopen(temp_dir(), None);
+        let maintenance = StoreMaintenance::new(
+            store.batch_store.rocksdb.clone(),
+            StoreMaintenanceMetrics::new(&Registry::new()),
+        );
+        assert_eq!(
+            maintenance.column_families(),
+            StoreMaintenance::COLUMN_FAMILIES
+        );
+
+        let batches: Vec<_> = (0..10).map(|_| Batch::new(vec![transaction()])).collect();
+        for batch in &batches {
+            store.batch_store.insert(&batch.digest(), batch).unwrap();
+        }
+        store
+            .batch_store
+            .multi_remove(batches.iter().map(|batch| batch.digest()))
+            .unwrap();
+        let stats = maintenance.stats().unwrap();
+        assert_eq!(stats[NodeStorage::BATCHES_CF].num_tombstones, 10);
+
+        // Compacting the batches removes their tombstones.
+        assert_eq!(
+            maintenance
+                .compact(Some(NodeStorage::BATCHES_CF), CompactionTrigger::Manual)
+                .await
+                .unwrap(),
+            vec![NodeStorage::BATCHES_CF]
+        );
+        let stats = maintenance.stats().unwrap();
+        assert_eq!(stats[NodeStorage::BATCHES_CF].num_tombstones, 0);
+        assert_eq!(
+            maintenance
+                .metrics
+                .compactions
+                .with_label_values(&[NodeStorage::BATCHES_CF, "manual"])
+                .get(),
+            1
+        );
+
+        // Only the maintained column families can be compacted.
+        assert!(matches!(
+            maintenance
+                .compact(Some(NodeStorage::VOTES_CF), CompactionTrigger::Manual)
+                .await,
+            Err(TypedStoreError::UnregisteredColumn(_))
+        ));
+    }
+}
@@ -22,7 +22,7 @@ use tokio::{
 use tonic::transport::Channel;
 use tracing::info;
 use types::{ConfigurationClient, ProposerClient, TransactionsClient};
-use worker::TrivialTransactionValidator;
+use worker::{TransactionHandoff, TrivialTransactionValidator};
 
 #[cfg(test)]
 #[path = "tests/cluster_tests.rs"]
@@ -366,6 +366,8 @@ impl PrimaryNodeDetails {
                 self.worker_cache.clone(),
                 &primary_store,
                 Arc::new(SimpleExecutionState::new(tx_transaction_confirmation)),
+                None,
+                /* leader_swaps */ false,
             )
             .await
             .unwrap();
@@ -427,7 +429,12 @@ impl WorkerNodeDetails {
         worker_cache: WorkerCache,
     ) -> Self {
         let registry_service = RegistryService::new(Registry::new());
-        let node = WorkerNode::new(id, parameters, registry_service);
+        let node = WorkerNode::new(
+            id,
+            parameters,
+            registry_service,
+            TransactionHandoff::default(),
+        );
 
         Self {
             id,
@@ -5,7 +5,7 @@
 use anemo::async_trait;
 use config::{
     utils::get_available_port, Authority, AuthorityIdentifier, Committee, CommitteeBuilder, Epoch,
-    Stake, WorkerCache, WorkerId, WorkerIndex, WorkerInfo,
+    Stake, WorkerCache, WorkerId, WorkerIndex, WorkerInfo, WorkerSharding,
 };
 use crypto::{
     to_intent_message, KeyPair, NarwhalAuthoritySignature, NetworkKeyPair, NetworkPublicKey,
@@ -36,14 +36,22 @@ use store::{reopen, rocks, rocks::DBMap};
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tracing::info;
 use types::{
-    Batch, BatchDigest, Certificate, CertificateAPI, CertificateDigest, CommittedSubDagShell,
-    ConsensusStore, FetchCertificatesRequest, FetchCertificatesResponse, GetCertificatesRequest,
-    GetCertificatesResponse, Header, HeaderAPI, HeaderV1Builder, PayloadAvailabilityRequest,
+    Batch, BatchDigest, Certificate, CertificateAPI, CertificateDigest, CommittedSubDagShellV1,
+    ConsensusStore, FetchCertificatesRequest, FetchCertificatesResponse,
+    FetchCertificatesV2Request, FetchCertificatesV2Response, GetCertificatesRequest,
+    GetCertificatesResponse, GetDagSnapshotRequest, GetDagSnapshotResponse, Header, HeaderAPI,
+    HeaderV1Builder, NegotiateBatchCompressionRequest, NegotiateBatchCompressionResponse,
+    OpenBatchesStreamRequest, OpenBatchesStreamResponse, PayloadAvailabilityRequest,
     PayloadAvailabilityResponse, PrimaryToPrimary, PrimaryToPrimaryServer, PrimaryToWorker,
-    PrimaryToWorkerServer, RequestBatchRequest, RequestBatchResponse, RequestBatchesRequest,
-    RequestBatchesResponse, RequestVoteRequest, RequestVoteResponse, Round, SendCertificateRequest,
-    SendCertificateResponse, SequenceNumber, TimestampMs, Transaction, Vote, VoteAPI,
-    WorkerBatchMessage, WorkerDeleteBatchesMessage, WorkerSynchronizeMessage, WorkerToWorker,
+    PrimaryToWorkerServer, RequestBatchRequest, RequestBatchResponse, RequestBatchesChunkRequest,
+    RequestBatchesChunkResponse, RequestBatchesDiffRequest, RequestBatchesDiffResponse,
+    RequestBatchesRequest, RequestBatchesResponse, RequestBatchesV2Request,
+    RequestBatchesV2Response, RequestReplicatedBatchesRequest, RequestReplicatedBatchesResponse,
+    RequestVoteRequest, RequestVoteResponse, Round, SendCertificateRequest,
+    SendCertificateResponse, SequenceNumber, TimestampMs, Transaction,
+    VersionedCommittedSubDagShell, Vote, VoteAPI, WorkerBatchChunkMessage, WorkerBatchMessage,
+    WorkerBatchStatusMessage, WorkerDeleteBatchesMessage, WorkerRelayBatchMessage,
+    WorkerRelayBatchResponse, WorkerRoundsMessage, WorkerSynchronizeMessage, WorkerToWorker,
     WorkerToWorkerServer,
 };
 
@@ -51,6 +59,7 @@ pub mod cluster;
 
 pub const VOTES_CF: &str = "votes";
 pub const HEADERS_CF: &str = "headers";
+pub const HEADER_DIGEST_BY_AUTHOR_CF: &str = "header_digest_by_author";
 pub const CERTIFICATES_CF: &str = "certificates";
 pub const CERTIFICATE_DIGEST_BY_ROUND_CF: &str = "certificate_digest_by_round";
 pub const CERTIFICATE_DIGEST_BY_ORIGIN_CF: &str = "certificate_digest_by_origin";
@@ -133,21 +142,27 @@ pub fn random_key() -> KeyPair {
 pub fn make_consensus_store(store_path: &std::path::Path) -> Arc<ConsensusStore> {
     const LAST_COMMITTED_CF: &str = "last_committed";
     const SEQUENCE_CF: &str = "sequence";
+    const SEQUENCE_V2_CF: &str = "sequence_v2";
 
     let rocksdb = rocks::open_cf(
         store_path,
         None,
         MetricConf::default(),
-        &[LAST_COMMITTED_CF, SEQUENCE_CF],
+        &[LAST_COMMITTED_CF, SEQUENCE_CF, SEQUENCE_V2_CF],
     )
     .expect("Failed creating database");
 
-    let (last_committed_map, sequence_map) = reopen!(&rocksdb,
+    let (last_committed_map, sequence_map, sequence_v2_map) = reopen!(&rocksdb,
         LAST_COMMITTED_CF;<AuthorityIdentifier, Round>,
-        SEQUENCE_CF;<SequenceNumber, CommittedSubDagShell>
+        SEQUENCE_CF;<SequenceNumber, CommittedSubDagShellV1>,
+        SEQUENCE_V2_CF;<SequenceNumber, VersionedCommittedSubDagShell>
     );
 
-    Arc::new(ConsensusStore::new(last_committed_map, sequence_map))
+    Arc::new(ConsensusStore::new(
+        last_committed_map,
+        sequence_map,
+        sequence_v2_map,
+    ))
 }
 
 pub fn fixture_payload(number_of_batches: u8) -> IndexMap<BatchDigest, (WorkerId, TimestampMs)> {
@@ -265,6 +280,12 @@ impl PrimaryToPrimary for PrimaryToPrimaryMockServer {
     ) -> Result<anemo::Response<FetchCertificatesResponse>, anemo::rpc::Status> {
         unimplemented!()
     }
+    async fn fetch_certificates_v2(
+        &self,
+        _request: anemo::Request<FetchCertificatesV2Request>,
+    ) -> Result<anemo::Response<FetchCertificatesV2Response>, anemo::rpc::Status> {
+        unimplemented!()
+    }
 
     async fn get_payload_availability(
         &self,
@@ -272,6 +293,13 @@ impl PrimaryToPrimary for PrimaryToPrimaryMockServer {
     ) -> Result<anemo::Response<PayloadAvailabilityResponse>, anemo::rpc::Status> {
         unimplemented!()
     }
+
+    async fn get_dag_snapshot(
+        &self,
+        _request: anemo::Request<GetDagSnapshotRequest>,
+    ) -> Result<anemo::Response<GetDagSnapshotResponse>, anemo::rpc::Status> {
+        unimplemented!()
+    }
 }
 
 pub struct PrimaryToWorkerMockServer {
@@ -317,6 +345,20 @@ impl PrimaryToWorker for PrimaryToWorkerMockServer {
         tracing::error!("Not implemented PrimaryToWorkerMockServer::delete_batches");
         Err(anemo::rpc::Status::internal("Unimplemented"))
     }
+
+    async fn report_batch_status(
+        &self,
+        _request: anemo::Request<WorkerBatchStatusMessage>,
+    ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
+        Ok(anemo::Response::new(()))
+    }
+
+    async fn report_rounds(
+        &self,
+        _request: anemo::Request<WorkerRoundsMessage>,
+    ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
+        Ok(anemo::Response::new(()))
+    }
 }
 
 pub struct WorkerToWorkerMockServer {
@@ -355,6 +397,23 @@ impl WorkerToWorker for WorkerToWorkerMockServer {
 
         Ok(anemo::Response::new(()))
     }
+
+    async fn report_batch_chunk(
+        &self,
+        _request: anemo::Request<WorkerBatchChunkMessage>,
+    ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
+        tracing::error!("Not implemented WorkerToWorkerMockServer::report_batch_chunk");
+        Err(anemo::rpc::Status::internal("Unimplemented"))
+    }
+
+    async fn relay_batch(
+        &self,
+        _request: anemo::Request<WorkerRelayBatchMessage>,
+    ) -> Result<anemo::Response<WorkerRelayBatchResponse>, anemo::rpc::Status> {
+        tracing::error!("Not implemented WorkerToWorkerMockServer::relay_batch");
+        Err(anemo::rpc::Status::internal("Unimplemented"))
+    }
+
     async fn request_batch(
         &self,
         _request: anemo::Request<RequestBatchRequest>,
@@ -370,6 +429,54 @@ impl WorkerToWorker for WorkerToWorkerMockServer {
         tracing::error!("Not implemented WorkerToWorkerMockServer::request_batches");
         Err(anemo::rpc::Status::internal("Unimplemented"))
     }
+
+    async fn request_batches_v2(
+        &self,
+        _request: anemo::Request<RequestBatchesV2Request>,
+    ) -> Result<anemo::Response<RequestBatchesV2Response>, anemo::rpc::Status> {
+        tracing::error!("Not implemented WorkerToWorkerMockServer::request_batches_v2");
+        Err(anemo::rpc::Status::internal("Unimplemented"))
+    }
+
+    async fn open_batches_stream(
+        &self,
+        _request: anemo::Request<OpenBatchesStreamRequest>,
+    ) -> Result<anemo::Response<OpenBatchesStreamResponse>, anemo::rpc::Status> {
+        tracing::error!("Not implemented WorkerToWorkerMockServer::open_batches_stream");
+        Err(anemo::rpc::Status::internal("Unimplemented"))
+    }
+
+    async fn request_batches_chunk(
+        &self,
+        _request: anemo::Request<RequestBatchesChunkRequest>,
+    ) -> Result<anemo::Response<RequestBatchesChunkResponse>, anemo::rpc::Status> {
+        tracing::error!("Not implemented WorkerToWorkerMockServer::request_batches_chunk");
+        Err(anemo::rpc::Status::internal("Unimplemented"))
+    }
+
+    async fn request_batches_diff(
+        &self,
+        _request: anemo::Request<RequestBatchesDiffRequest>,
+    ) -> Result<anemo::Response<RequestBatchesDiffResponse>, anemo::rpc::Status> {
+        tracing::error!("Not implemented WorkerToWorkerMockServer::request_batches_diff");
+        Err(anemo::rpc::Status::internal("Unimplemented"))
+    }
+
+    async fn negotiate_batch_compression(
+        &self,
+        _request: anemo::Request<NegotiateBatchCompressionRequest>,
+    ) -> Result<anemo::Response<NegotiateBatchCompressionResponse>, anemo::rpc::Status> {
+        tracing::error!("Not implemented WorkerToWorkerMockServer::negotiate_batch_compression");
+        Err(anemo::rpc::Status::internal("Unimplemented"))
+    }
+
+    async fn request_replicated_batches(
+        &self,
+        _request: anemo::Request<RequestReplicatedBatchesRequest>,
+    ) -> Result<anemo::Response<RequestReplicatedBatchesResponse>, anemo::rpc::Status> {
+        tracing::error!("Not implemented WorkerToWorkerMockServer::request_replicated_batches");
+        Err(anemo::rpc::Status::internal("Unimplemented"))
+    }
 }
 
 ////////////////////////////////////////////////////////////////
@@ -843,6 +950,7 @@ impl CommitteeFixture {
                 .iter()
                 .map(|a| (a.public_key(), a.worker_index()))
                 .collect(),
+            sharding: WorkerSharding::default(),
         }
     }
 
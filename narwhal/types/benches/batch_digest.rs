@@ -6,13 +6,13 @@ use criterion::{
 use fastcrypto::hash::Hash;
 use narwhal_types as types;
 use rand::Rng;
-use types::Batch;
+use types::{Batch, BatchV3, PriorityLane};
 
 pub fn batch_digest(c: &mut Criterion) {
     let mut digest_group = c.benchmark_group("Batch digests");
     digest_group.sampling_mode(SamplingMode::Flat);
 
-    static BATCH_SIZES: [usize; 4] = [100, 500, 1000, 5000];
+    static BATCH_SIZES: [usize; 5] = [100, 500, 1000, 5000, 10000];
 
     for size in BATCH_SIZES {
         let tx_gen = || {
@@ -20,11 +20,19 @@ pub fn batch_digest(c: &mut Criterion) {
                 .map(|_| rand::thread_rng().gen())
                 .collect::<Vec<u8>>()
         };
-        let batch = Batch::new((0..size).map(|_| tx_gen()).collect::<Vec<_>>());
+        let transactions = (0..size).map(|_| tx_gen()).collect::<Vec<_>>();
+        let batch = Batch::new(transactions.clone());
         digest_group.throughput(Throughput::Bytes(512 * size as u64));
         digest_group.bench_with_input(BenchmarkId::new("batch digest", size), &batch, |b, i| {
             b.iter(|| i.digest())
         });
+        // The digest tree of V3, whose chunks are hashed in parallel from 1024 transactions.
+        let batch = Batch::V3(BatchV3::new(transactions, PriorityLane::Normal, 0, 0));
+        digest_group.bench_with_input(
+            BenchmarkId::new("batch digest tree", size),
+            &batch,
+            |b, i| b.iter(|| i.digest()),
+        );
     }
 }
 
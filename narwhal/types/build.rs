@@ -97,6 +97,24 @@ fn build_anemo_services(out_dir: &Path) {
                 .codec_path(codec_path)
                 .build(),
         )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("fetch_certificates_v2")
+                .route_name("FetchCertificatesV2")
+                .request_type("crate::FetchCertificatesV2Request")
+                .response_type("crate::FetchCertificatesV2Response")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("get_dag_snapshot")
+                .route_name("GetDagSnapshot")
+                .request_type("crate::GetDagSnapshotRequest")
+                .response_type("crate::GetDagSnapshotResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
         .build();
 
     let primary_to_worker = anemo_build::manual::Service::builder()
@@ -121,6 +139,24 @@ fn build_anemo_services(out_dir: &Path) {
                 .codec_path(codec_path)
                 .build(),
         )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("report_batch_status")
+                .route_name("ReportBatchStatus")
+                .request_type("crate::WorkerBatchStatusMessage")
+                .response_type("()")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("report_rounds")
+                .route_name("ReportRounds")
+                .request_type("crate::WorkerRoundsMessage")
+                .response_type("()")
+                .codec_path(codec_path)
+                .build(),
+        )
         .build();
 
     let worker_to_primary = anemo_build::manual::Service::builder()
@@ -169,6 +205,24 @@ fn build_anemo_services(out_dir: &Path) {
                 .codec_path(codec_path)
                 .build(),
         )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("report_batch_chunk")
+                .route_name("ReportBatchChunk")
+                .request_type("crate::WorkerBatchChunkMessage")
+                .response_type("()")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("relay_batch")
+                .route_name("RelayBatch")
+                .request_type("crate::WorkerRelayBatchMessage")
+                .response_type("crate::WorkerRelayBatchResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
         .method(
             anemo_build::manual::Method::builder()
                 .name("request_batch")
@@ -187,6 +241,60 @@ fn build_anemo_services(out_dir: &Path) {
                 .codec_path(codec_path)
                 .build(),
         )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("request_batches_v2")
+                .route_name("RequestBatchesV2")
+                .request_type("crate::RequestBatchesV2Request")
+                .response_type("crate::RequestBatchesV2Response")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("open_batches_stream")
+                .route_name("OpenBatchesStream")
+                .request_type("crate::OpenBatchesStreamRequest")
+                .response_type("crate::OpenBatchesStreamResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("request_batches_chunk")
+                .route_name("RequestBatchesChunk")
+                .request_type("crate::RequestBatchesChunkRequest")
+                .response_type("crate::RequestBatchesChunkResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("request_batches_diff")
+                .route_name("RequestBatchesDiff")
+                .request_type("crate::RequestBatchesDiffRequest")
+                .response_type("crate::RequestBatchesDiffResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("negotiate_batch_compression")
+                .route_name("NegotiateBatchCompression")
+                .request_type("crate::NegotiateBatchCompressionRequest")
+                .response_type("crate::NegotiateBatchCompressionResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("request_replicated_batches")
+                .route_name("RequestReplicatedBatches")
+                .request_type("crate::RequestReplicatedBatchesRequest")
+                .response_type("crate::RequestReplicatedBatchesResponse")
+                .codec_path(codec_path)
+                .build(),
+        )
         .build();
 
     anemo_build::manual::Builder::new()
@@ -2,11 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 #![allow(clippy::mutable_key_type)]
 
-use crate::{Batch, Certificate, CertificateAPI, CertificateDigest, HeaderAPI, Round};
+use crate::{Batch, Certificate, CertificateAPI, CertificateDigest, HeaderAPI, Round, TimestampMs};
 use config::{AuthorityIdentifier, Committee};
 use fastcrypto::hash::Hash;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use store::{
     rocks::{DBMap, TypedStoreError},
@@ -14,6 +14,10 @@ use store::{
 };
 use tokio::sync::mpsc;
 
+#[cfg(test)]
+#[path = "tests/consensus_store_tests.rs"]
+mod consensus_store_tests;
+
 /// A global sequence number assigned to every CommittedSubDag.
 pub type SequenceNumber = u64;
 
@@ -25,6 +29,13 @@ pub struct ConsensusOutput {
     pub batches: Vec<(Certificate, Vec<Batch>)>,
 }
 
+impl ConsensusOutput {
+    /// The consensus timestamp of the committed sub-dag, see `CommittedSubDag::commit_timestamp`.
+    pub fn commit_timestamp(&self) -> TimestampMs {
+        self.sub_dag.commit_timestamp
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct CommittedSubDag {
     /// The sequence of committed certificates.
@@ -35,9 +46,37 @@ pub struct CommittedSubDag {
     pub sub_dag_index: SequenceNumber,
     /// The so far calculated reputation score for nodes
     pub reputation_score: ReputationScores,
+    /// The timestamp of the commit, derived from the DAG so that every node agrees on it. It
+    /// never decreases from one sub-dag to the next.
+    pub commit_timestamp: TimestampMs,
 }
 
 impl CommittedSubDag {
+    /// Computes the timestamp of the sub-dag committed by the given leader: the median of the
+    /// creation times of the leader's parents, which form a quorum so that a minority of the
+    /// authorities cannot skew it. The timestamp is clamped to never go below the previous one.
+    pub fn compute_commit_timestamp(
+        leader: &Certificate,
+        certificates: &[Certificate],
+        previous_commit_timestamp: TimestampMs,
+    ) -> TimestampMs {
+        let parents = leader.header().parents();
+        let mut timestamps: Vec<_> = certificates
+            .iter()
+            .filter(|certificate| {
+                certificate.round() + 1 == leader.round() && parents.contains(&certificate.digest())
+            })
+            .map(|certificate| *certificate.header().created_at())
+            .collect();
+        // The parents may have been garbage collected, in which case the leader is used alone.
+        if timestamps.is_empty() {
+            timestamps.push(*leader.header().created_at());
+        }
+        timestamps.sort_unstable();
+        let median = timestamps[(timestamps.len() - 1) / 2];
+        median.max(previous_commit_timestamp)
+    }
+
     pub fn len(&self) -> usize {
         self.certificates.len()
     }
@@ -105,7 +144,7 @@ impl ReputationScores {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct CommittedSubDagShell {
     /// The sequence of committed certificates' digests.
     pub certificates: Vec<CertificateDigest>,
@@ -117,6 +156,49 @@ pub struct CommittedSubDagShell {
     pub sub_dag_index: SequenceNumber,
     /// The so far calculated reputation score for nodes
     pub reputation_score: ReputationScores,
+    /// The timestamp of the commit
+    pub commit_timestamp: TimestampMs,
+}
+
+/// The layout of the sub dags stored before their commit timestamp was recorded.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CommittedSubDagShellV1 {
+    pub certificates: Vec<CertificateDigest>,
+    pub leader: CertificateDigest,
+    pub leader_round: Round,
+    pub sub_dag_index: SequenceNumber,
+    pub reputation_score: ReputationScores,
+}
+
+/// The sub dags as stored, versioned so that the stored ones can still be read once fields
+/// are added.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum VersionedCommittedSubDagShell {
+    V1(CommittedSubDagShellV1),
+    V2(CommittedSubDagShell),
+}
+
+impl From<CommittedSubDagShellV1> for CommittedSubDagShell {
+    /// The sub dags committed before their timestamp was recorded have a timestamp of 0.
+    fn from(sub_dag: CommittedSubDagShellV1) -> Self {
+        Self {
+            certificates: sub_dag.certificates,
+            leader: sub_dag.leader,
+            leader_round: sub_dag.leader_round,
+            sub_dag_index: sub_dag.sub_dag_index,
+            reputation_score: sub_dag.reputation_score,
+            commit_timestamp: 0,
+        }
+    }
+}
+
+impl From<VersionedCommittedSubDagShell> for CommittedSubDagShell {
+    fn from(sub_dag: VersionedCommittedSubDagShell) -> Self {
+        match sub_dag {
+            VersionedCommittedSubDagShell::V1(sub_dag) => sub_dag.into(),
+            VersionedCommittedSubDagShell::V2(sub_dag) => sub_dag,
+        }
+    }
 }
 
 impl CommittedSubDagShell {
@@ -127,6 +209,7 @@ impl CommittedSubDagShell {
             leader_round: sub_dag.leader.round(),
             sub_dag_index: sub_dag.sub_dag_index,
             reputation_score: sub_dag.reputation_score.clone(),
+            commit_timestamp: sub_dag.commit_timestamp,
         }
     }
 }
@@ -141,19 +224,23 @@ pub type StoreResult<T> = Result<T, TypedStoreError>;
 pub struct ConsensusStore {
     /// The latest committed round of each validator.
     last_committed: DBMap<AuthorityIdentifier, Round>,
-    /// The global consensus sequence.
-    committed_sub_dags_by_index: DBMap<SequenceNumber, CommittedSubDagShell>,
+    /// The global consensus sequence committed before the sub dags were versioned, only read.
+    committed_sub_dags_by_index: DBMap<SequenceNumber, CommittedSubDagShellV1>,
+    /// The global consensus sequence, following the one above.
+    committed_sub_dags_by_index_v2: DBMap<SequenceNumber, VersionedCommittedSubDagShell>,
 }
 
 impl ConsensusStore {
     /// Create a new consensus store structure by using already loaded maps.
     pub fn new(
         last_committed: DBMap<AuthorityIdentifier, Round>,
-        sequence: DBMap<SequenceNumber, CommittedSubDagShell>,
+        sequence: DBMap<SequenceNumber, CommittedSubDagShellV1>,
+        sequence_v2: DBMap<SequenceNumber, VersionedCommittedSubDagShell>,
     ) -> Self {
         Self {
             last_committed,
             committed_sub_dags_by_index: sequence,
+            committed_sub_dags_by_index_v2: sequence_v2,
         }
     }
 
@@ -161,6 +248,7 @@ impl ConsensusStore {
     pub fn clear(&self) -> StoreResult<()> {
         self.last_committed.clear()?;
         self.committed_sub_dags_by_index.clear()?;
+        self.committed_sub_dags_by_index_v2.clear()?;
         Ok(())
     }
 
@@ -175,8 +263,36 @@ impl ConsensusStore {
         let mut write_batch = self.last_committed.batch();
         write_batch = write_batch.insert_batch(&self.last_committed, last_committed.iter())?;
         write_batch = write_batch.insert_batch(
-            &self.committed_sub_dags_by_index,
-            std::iter::once((sub_dag.sub_dag_index, shell)),
+            &self.committed_sub_dags_by_index_v2,
+            std::iter::once((
+                sub_dag.sub_dag_index,
+                VersionedCommittedSubDagShell::V2(shell),
+            )),
+        )?;
+        write_batch.write()
+    }
+
+    /// Persist the consensus state of a DAG snapshot, from which consensus resumes. The latest
+    /// sub dag with final reputation scores restores the leader schedule.
+    pub fn write_snapshot_state(
+        &self,
+        last_committed: &BTreeMap<AuthorityIdentifier, Round>,
+        latest_sub_dag: Option<&CommittedSubDagShell>,
+        latest_final_sub_dag: Option<&CommittedSubDagShell>,
+    ) -> Result<(), TypedStoreError> {
+        let mut write_batch = self.last_committed.batch();
+        write_batch = write_batch.insert_batch(&self.last_committed, last_committed.iter())?;
+        write_batch = write_batch.insert_batch(
+            &self.committed_sub_dags_by_index_v2,
+            latest_final_sub_dag
+                .into_iter()
+                .chain(latest_sub_dag)
+                .map(|sub_dag| {
+                    (
+                        sub_dag.sub_dag_index,
+                        VersionedCommittedSubDagShell::V2(sub_dag.clone()),
+                    )
+                }),
         )?;
         write_batch.write()
     }
@@ -186,26 +302,81 @@ impl ConsensusStore {
         self.last_committed.iter().collect()
     }
 
-    /// Gets the latest sub dag index from the store
-    pub fn get_latest_sub_dag_index(&self) -> SequenceNumber {
-        let s = self
+    // The sub dags committed with sequence number of at least `from`, in the order of their
+    // commit. The legacy sub dags all come before the versioned ones.
+    fn sub_dags_from(
+        &self,
+        from: &SequenceNumber,
+    ) -> StoreResult<impl Iterator<Item = CommittedSubDagShell> + '_> {
+        let legacy = self
+            .committed_sub_dags_by_index
+            .iter()
+            .skip_to(from)?
+            .map(|(_, sub_dag)| sub_dag.into());
+        let versioned = self
+            .committed_sub_dags_by_index_v2
+            .iter()
+            .skip_to(from)?
+            .map(|(_, sub_dag)| sub_dag.into());
+        Ok(legacy.chain(versioned))
+    }
+
+    // The sub dags committed, from the latest one.
+    fn sub_dags_from_latest(&self) -> impl Iterator<Item = CommittedSubDagShell> + '_ {
+        let versioned = self
+            .committed_sub_dags_by_index_v2
+            .iter()
+            .skip_to_last()
+            .reverse()
+            .map(|(_, sub_dag)| sub_dag.into());
+        let legacy = self
             .committed_sub_dags_by_index
             .iter()
             .skip_to_last()
+            .reverse()
+            .map(|(_, sub_dag)| sub_dag.into());
+        versioned.chain(legacy)
+    }
+
+    /// Gets the latest sub dag index from the store
+    pub fn get_latest_sub_dag_index(&self) -> SequenceNumber {
+        self.sub_dags_from_latest()
             .next()
-            .map(|(seq, _)| seq)
-            .unwrap_or_default();
-        s
+            .map(|sub_dag| sub_dag.sub_dag_index)
+            .unwrap_or_default()
     }
 
     /// Returns thet latest subdag committed. If none is committed yet, then
     /// None is returned instead.
     pub fn get_latest_sub_dag(&self) -> Option<CommittedSubDagShell> {
-        self.committed_sub_dags_by_index
-            .iter()
-            .skip_to_last()
-            .next()
-            .map(|(_, subdag)| subdag)
+        self.sub_dags_from_latest().next()
+    }
+
+    /// Returns the latest committed sub dag whose reputation scores are the final ones of their
+    /// schedule. If there is none, then None is returned instead.
+    pub fn read_latest_commit_with_final_reputation_scores(&self) -> Option<CommittedSubDagShell> {
+        self.sub_dags_from_latest()
+            .find(|sub_dag| sub_dag.reputation_score.final_of_schedule)
+    }
+
+    /// Load the sub dags committed with a leader of at least the given round, in the order of
+    /// their commit.
+    pub fn read_committed_sub_dags_since_round(&self, round: Round) -> Vec<CommittedSubDagShell> {
+        let mut sub_dags: Vec<_> = self
+            .sub_dags_from_latest()
+            .take_while(|sub_dag| sub_dag.leader_round >= round)
+            .collect();
+        sub_dags.reverse();
+        sub_dags
+    }
+
+    /// Load up to `limit` sub dags committed with sequence number of at least `from`.
+    pub fn read_committed_sub_dags_from_with_limit(
+        &self,
+        from: &SequenceNumber,
+        limit: usize,
+    ) -> StoreResult<Vec<CommittedSubDagShell>> {
+        Ok(self.sub_dags_from(from)?.take(limit).collect())
     }
 
     /// Load all the sub dags committed with sequence number of at least `from`.
@@ -213,11 +384,6 @@ impl ConsensusStore {
         &self,
         from: &SequenceNumber,
     ) -> StoreResult<Vec<CommittedSubDagShell>> {
-        Ok(self
-            .committed_sub_dags_by_index
-            .iter()
-            .skip_to(from)?
-            .map(|(_, sub_dag)| sub_dag)
-            .collect())
+        Ok(self.sub_dags_from(from)?.collect())
     }
 }
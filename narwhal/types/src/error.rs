@@ -1,8 +1,8 @@
 // Copyright (c) 2021, Facebook, Inc. and its affiliates
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use crate::{CertificateDigest, HeaderDigest, Round, TimestampMs, VoteDigest};
-use config::Epoch;
+use crate::{BatchDigest, CertificateDigest, HeaderDigest, Round, TimestampMs, VoteDigest};
+use config::{Epoch, WorkerId};
 use fastcrypto::hash::Digest;
 use mysten_common::sync::notify_once::NotifyOnce;
 use std::sync::Arc;
@@ -54,6 +54,18 @@ pub enum DagError {
     #[error("Header {0} has bad worker IDs")]
     HeaderHasBadWorkerIds(HeaderDigest),
 
+    #[error("Header {0} has {1} batches, more than the maximum of {2}")]
+    HeaderHasTooManyBatches(HeaderDigest, usize, usize),
+
+    #[error("Header {0} has {2} batches of worker {1}, more than the maximum of {3}")]
+    HeaderHasTooManyWorkerBatches(HeaderDigest, WorkerId, usize, usize),
+
+    #[error("Batch {0} has {1} transactions, more than the maximum of {2}")]
+    BatchHasTooManyTransactions(BatchDigest, usize, usize),
+
+    #[error("Batch {0} has {1} bytes of transactions, more than the maximum of {2}")]
+    BatchTooLarge(BatchDigest, usize, usize),
+
     #[error("Header {0} has parents with invalid round numbers")]
     HeaderHasInvalidParentRoundNumbers(HeaderDigest),
 
@@ -117,6 +129,9 @@ pub enum DagError {
     #[error("Network error: {0}")]
     NetworkError(String),
 
+    #[error("Invalid DAG snapshot: {0}")]
+    InvalidDagSnapshot(String),
+
     #[error("Processing was suspended to retrieve parent certificates")]
     Suspended(AcceptNotification),
 
@@ -126,10 +141,87 @@ pub enum DagError {
     #[error("Channel full")]
     ChannelFull,
 
+    #[error("Memory budget of the node exceeded")]
+    MemoryBudgetExceeded,
+
     #[error("Operation was canceled")]
     Canceled,
 }
 
+/// The reason a header or a certificate is rejected by its validation, reported in the logs and
+/// the metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RejectionReason {
+    BadSignature,
+    WrongEpoch,
+    BadDigest,
+    UnknownAuthority,
+    BadWorkerIds,
+    TooManyDigests,
+    UnknownParent,
+    InvalidParents,
+    StaleRound,
+    FutureRound,
+    FutureTimestamp,
+    Equivocation,
+}
+
+impl RejectionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectionReason::BadSignature => "bad_signature",
+            RejectionReason::WrongEpoch => "wrong_epoch",
+            RejectionReason::BadDigest => "bad_digest",
+            RejectionReason::UnknownAuthority => "unknown_authority",
+            RejectionReason::BadWorkerIds => "bad_worker_ids",
+            RejectionReason::TooManyDigests => "too_many_digests",
+            RejectionReason::UnknownParent => "unknown_parent",
+            RejectionReason::InvalidParents => "invalid_parents",
+            RejectionReason::StaleRound => "stale_round",
+            RejectionReason::FutureRound => "future_round",
+            RejectionReason::FutureTimestamp => "future_timestamp",
+            RejectionReason::Equivocation => "equivocation",
+        }
+    }
+}
+
+impl std::fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl DagError {
+    /// The reason of the rejection of the header or certificate this error is returned for, or
+    /// None if the error does not reject it, e.g. a storage failure or a shutdown.
+    pub fn rejection_reason(&self) -> Option<RejectionReason> {
+        let reason = match self {
+            DagError::InvalidSignature
+            | DagError::InvalidBitmap(_)
+            | DagError::CertificateRequiresQuorum => RejectionReason::BadSignature,
+            DagError::InvalidEpoch { .. } => RejectionReason::WrongEpoch,
+            DagError::InvalidHeaderDigest => RejectionReason::BadDigest,
+            DagError::UnknownAuthority(_) | DagError::AuthorityReuse(_) => {
+                RejectionReason::UnknownAuthority
+            }
+            DagError::HeaderHasBadWorkerIds(_) => RejectionReason::BadWorkerIds,
+            DagError::HeaderHasTooManyBatches(..) | DagError::HeaderHasTooManyWorkerBatches(..) => {
+                RejectionReason::TooManyDigests
+            }
+            DagError::InvalidGenesisParent(_) => RejectionReason::UnknownParent,
+            DagError::HeaderHasInvalidParentRoundNumbers(_)
+            | DagError::HeaderHasDuplicateParentAuthorities(_)
+            | DagError::HeaderRequiresQuorum(_) => RejectionReason::InvalidParents,
+            DagError::TooOld(..) | DagError::VoteTooOld(..) => RejectionReason::StaleRound,
+            DagError::TooNew(..) | DagError::InvalidRound { .. } => RejectionReason::FutureRound,
+            DagError::InvalidTimestamp { .. } => RejectionReason::FutureTimestamp,
+            DagError::AlreadyVoted(..) => RejectionReason::Equivocation,
+            _ => return None,
+        };
+        Some(reason)
+    }
+}
+
 impl<T> From<tokio::sync::mpsc::error::TrySendError<T>> for DagError {
     fn from(err: tokio::sync::mpsc::error::TrySendError<T>) -> Self {
         match err {
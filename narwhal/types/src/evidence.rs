@@ -0,0 +1,120 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::{
+    now, Certificate, CertificateAPI, CertificateDigest, Header, HeaderAPI, Round, TimestampMs,
+};
+use config::AuthorityIdentifier;
+use fastcrypto::hash::{Hash, HashFunction};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The digest keying an evidence in the store.
+pub type EvidenceDigest = [u8; crypto::DIGEST_LENGTH];
+
+/// A misbehavior detected by the primary, along with the messages proving it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Misbehavior {
+    /// The authority proposed two different headers for the same round.
+    EquivocatingHeaders { first: Header, second: Header },
+    /// Two different certificates were formed for the same round and authority.
+    EquivocatingCertificates {
+        first: Certificate,
+        second: Certificate,
+    },
+    /// The authority sent us a certificate failing verification. Only its digest is kept, as
+    /// the certificate proves nothing about its sender, and an authority may send any number
+    /// of them.
+    InvalidCertificate {
+        certificate: CertificateDigest,
+        reason: String,
+    },
+}
+
+impl Misbehavior {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Misbehavior::EquivocatingHeaders { .. } => "equivocating_headers",
+            Misbehavior::EquivocatingCertificates { .. } => "equivocating_certificates",
+            Misbehavior::InvalidCertificate { .. } => "invalid_certificate",
+        }
+    }
+}
+
+/// The evidence of a misbehavior of an authority, as recorded by the primary.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Evidence {
+    /// The misbehaving authority.
+    pub offender: AuthorityIdentifier,
+    /// The round of the offending messages.
+    pub round: Round,
+    /// When the misbehavior was detected.
+    pub recorded_at: TimestampMs,
+    pub misbehavior: Misbehavior,
+}
+
+impl Evidence {
+    pub fn equivocating_headers(first: Header, second: Header) -> Self {
+        Self::new(
+            second.author(),
+            second.round(),
+            Misbehavior::EquivocatingHeaders { first, second },
+        )
+    }
+
+    pub fn equivocating_certificates(first: Certificate, second: Certificate) -> Self {
+        Self::new(
+            second.origin(),
+            second.round(),
+            Misbehavior::EquivocatingCertificates { first, second },
+        )
+    }
+
+    /// The offender is the authority that sent the certificate, which is not necessarily its
+    /// origin: anyone can forge an invalid certificate of another authority.
+    pub fn invalid_certificate(
+        offender: AuthorityIdentifier,
+        certificate: &Certificate,
+        reason: String,
+    ) -> Self {
+        Self::new(
+            offender,
+            certificate.round(),
+            Misbehavior::InvalidCertificate {
+                certificate: certificate.digest(),
+                reason,
+            },
+        )
+    }
+
+    fn new(offender: AuthorityIdentifier, round: Round, misbehavior: Misbehavior) -> Self {
+        Self {
+            offender,
+            round,
+            recorded_at: now(),
+            misbehavior,
+        }
+    }
+
+    /// The digest of the offending messages, identical for the evidences of the same misbehavior
+    /// regardless of when they were recorded.
+    pub fn digest(&self) -> EvidenceDigest {
+        let mut hasher = crypto::DefaultHashFunction::new();
+        hasher.update(self.misbehavior.kind());
+        hasher.update(
+            bcs::to_bytes(&self.misbehavior).expect("Serialization of the evidence cannot fail"),
+        );
+        hasher.finalize().into()
+    }
+}
+
+impl fmt::Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} by {} at round {}",
+            self.misbehavior.kind(),
+            self.offender,
+            self.round
+        )
+    }
+}
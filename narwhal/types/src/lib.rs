@@ -10,6 +10,9 @@ pub mod error;
 mod consensus;
 pub use consensus::*;
 
+mod evidence;
+pub use evidence::*;
+
 mod primary;
 pub use primary::*;
 
@@ -4,10 +4,14 @@
 use crate::{
     error::{DagError, DagResult},
     serde::NarwhalBitmap,
-    CertificateDigestProto,
+    CertificateDigestProto, CommittedSubDagShell,
 };
 use bytes::Bytes;
-use config::{AuthorityIdentifier, Committee, Epoch, Stake, WorkerCache, WorkerId, WorkerInfo};
+use config::{
+    AuthorityIdentifier, BatchLimits, BatchVersion, Committee, Epoch, HeaderPayloadLimits, Stake,
+    WorkerCache, WorkerId, WorkerInfo,
+};
+use crypto::signer::SignatureService;
 use crypto::{
     to_intent_message, AggregateSignature, AggregateSignatureBytes,
     NarwhalAuthorityAggregateSignature, NarwhalAuthoritySignature, PublicKey, Signature,
@@ -17,16 +21,17 @@ use derive_builder::Builder;
 use enum_dispatch::enum_dispatch;
 use fastcrypto::{
     hash::{Digest, Hash, HashFunction},
-    signature_service::SignatureService,
     traits::{AggregateAuthenticator, Signer, VerifyingKey},
 };
 use indexmap::IndexMap;
 use mysten_util_mem::MallocSizeOf;
 use once_cell::sync::OnceCell;
 use proptest_derive::Arbitrary;
+use rayon::prelude::*;
 use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use shared_crypto::intent::IntentMessage;
 use std::time::{Duration, SystemTime};
 use std::{
     collections::{BTreeMap, BTreeSet, VecDeque},
@@ -81,10 +86,15 @@ impl Default for Metadata {
     }
 }
 
+/// The versioned batch exchanged by the workers. New versions are only ever appended, so that
+/// nodes still creating older versions can decode the batches of the nodes that upgraded,
+/// while the version they create is gated by the protocol config.
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Arbitrary)]
 #[enum_dispatch(BatchAPI)]
 pub enum Batch {
     V1(BatchV1),
+    V2(BatchV2),
+    V3(BatchV3),
 }
 
 // TODO: Revisit if we should not impl Default for batch
@@ -99,19 +109,70 @@ impl Batch {
         Self::V1(BatchV1::new(transactions))
     }
 
+    /// Creates a batch of the given version. Only V2 and V3 record the lane and the origin of
+    /// the batch, a V1 batch is always considered part of the normal lane.
+    pub fn new_versioned(
+        transactions: Vec<Transaction>,
+        lane: PriorityLane,
+        worker_id: WorkerId,
+        epoch: Epoch,
+        version: BatchVersion,
+    ) -> Self {
+        match version {
+            BatchVersion::V1 => Self::V1(BatchV1::new(transactions)),
+            BatchVersion::V2 => Self::V2(BatchV2::new(transactions, lane, worker_id, epoch)),
+            BatchVersion::V3 => Self::V3(BatchV3::new(transactions, lane, worker_id, epoch)),
+        }
+    }
+
     pub fn size(&self) -> usize {
         match self {
             Batch::V1(data) => data.size(),
+            Batch::V2(data) => data.size(),
+            Batch::V3(data) => data.size(),
+        }
+    }
+
+    /// Ensures the batch does not have more transactions, or more bytes of transactions, than
+    /// allowed.
+    pub fn validate_limits(&self, limits: &BatchLimits) -> DagResult<()> {
+        let num_of_transactions = self.transactions().len();
+        ensure!(
+            num_of_transactions <= limits.max_num_of_transactions,
+            DagError::BatchHasTooManyTransactions(
+                self.digest(),
+                num_of_transactions,
+                limits.max_num_of_transactions
+            )
+        );
+        let size = self.size();
+        ensure!(
+            size <= limits.max_size,
+            DagError::BatchTooLarge(self.digest(), size, limits.max_size)
+        );
+        Ok(())
+    }
+
+    pub fn version(&self) -> BatchVersion {
+        match self {
+            Batch::V1(_) => BatchVersion::V1,
+            Batch::V2(_) => BatchVersion::V2,
+            Batch::V3(_) => BatchVersion::V3,
         }
     }
 }
 
+/// The digest of a batch only covers its transactions. The metadata, lane, worker id and epoch
+/// are deliberately kept out of it: they are informational only, and the same transactions
+/// must have the same digest whoever batched them.
 impl Hash<{ crypto::DIGEST_LENGTH }> for Batch {
     type TypedDigest = BatchDigest;
 
     fn digest(&self) -> BatchDigest {
         match self {
             Batch::V1(data) => data.digest(),
+            Batch::V2(data) => data.digest(),
+            Batch::V3(data) => data.digest(),
         }
     }
 }
@@ -122,6 +183,60 @@ pub trait BatchAPI {
     fn transactions_mut(&mut self) -> &mut Vec<Transaction>;
     fn metadata(&self) -> &Metadata;
     fn metadata_mut(&mut self) -> &mut Metadata;
+    fn lane(&self) -> PriorityLane;
+    /// The id of the worker that created the batch, if recorded.
+    fn worker_id(&self) -> Option<WorkerId>;
+    /// The epoch during which the batch was created, if recorded.
+    fn epoch(&self) -> Option<Epoch>;
+}
+
+/// The priority lane a transaction is submitted to. The workers batch the transactions of each
+/// lane separately, so that system transactions and prioritized user transactions are not
+/// queued behind bulk traffic.
+#[derive(
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Arbitrary,
+)]
+pub enum PriorityLane {
+    /// Transactions submitted by the validator itself, e.g. checkpoint signatures.
+    System,
+    /// User transactions prioritized by the submitter, e.g. because of their gas price.
+    High,
+    #[default]
+    Normal,
+}
+
+impl PriorityLane {
+    /// All the lanes, from the highest to the lowest priority.
+    pub const ALL: [PriorityLane; 3] = [
+        PriorityLane::System,
+        PriorityLane::High,
+        PriorityLane::Normal,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriorityLane::System => "system",
+            PriorityLane::High => "high",
+            PriorityLane::Normal => "normal",
+        }
+    }
+}
+
+impl fmt::Display for PriorityLane {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 pub type Transaction = Vec<u8>;
@@ -147,6 +262,18 @@ impl BatchAPI for BatchV1 {
     fn metadata_mut(&mut self) -> &mut Metadata {
         &mut self.metadata
     }
+
+    fn lane(&self) -> PriorityLane {
+        PriorityLane::Normal
+    }
+
+    fn worker_id(&self) -> Option<WorkerId> {
+        None
+    }
+
+    fn epoch(&self) -> Option<Epoch> {
+        None
+    }
 }
 
 impl BatchV1 {
@@ -162,6 +289,160 @@ impl BatchV1 {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Arbitrary)]
+pub struct BatchV2 {
+    pub transactions: Vec<Transaction>,
+    pub metadata: Metadata,
+    // The lane all the transactions of the batch were submitted to.
+    pub lane: PriorityLane,
+    // The worker that created the batch, and the epoch it was created in. Together with the
+    // creation timestamp of the metadata, they let the consumers of the batch compute its
+    // end-to-end latency. Like the metadata and the lane, they are not part of the digest.
+    pub worker_id: WorkerId,
+    pub epoch: Epoch,
+}
+
+impl BatchAPI for BatchV2 {
+    fn transactions(&self) -> &Vec<Transaction> {
+        &self.transactions
+    }
+
+    fn transactions_mut(&mut self) -> &mut Vec<Transaction> {
+        &mut self.transactions
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    fn lane(&self) -> PriorityLane {
+        self.lane
+    }
+
+    fn worker_id(&self) -> Option<WorkerId> {
+        Some(self.worker_id)
+    }
+
+    fn epoch(&self) -> Option<Epoch> {
+        Some(self.epoch)
+    }
+}
+
+impl BatchV2 {
+    pub fn new(
+        transactions: Vec<Transaction>,
+        lane: PriorityLane,
+        worker_id: WorkerId,
+        epoch: Epoch,
+    ) -> Self {
+        Self {
+            transactions,
+            metadata: Metadata::default(),
+            lane,
+            worker_id,
+            epoch,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.transactions.iter().map(|t| t.len()).sum()
+    }
+}
+
+/// Has the same content as V2, but a digest computed as a tree over chunks of its transactions,
+/// so that the chunks of the large batches are hashed in parallel.
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Arbitrary)]
+pub struct BatchV3 {
+    pub transactions: Vec<Transaction>,
+    pub metadata: Metadata,
+    pub lane: PriorityLane,
+    pub worker_id: WorkerId,
+    pub epoch: Epoch,
+}
+
+impl BatchAPI for BatchV3 {
+    fn transactions(&self) -> &Vec<Transaction> {
+        &self.transactions
+    }
+
+    fn transactions_mut(&mut self) -> &mut Vec<Transaction> {
+        &mut self.transactions
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    fn lane(&self) -> PriorityLane {
+        self.lane
+    }
+
+    fn worker_id(&self) -> Option<WorkerId> {
+        Some(self.worker_id)
+    }
+
+    fn epoch(&self) -> Option<Epoch> {
+        Some(self.epoch)
+    }
+}
+
+impl BatchV3 {
+    pub fn new(
+        transactions: Vec<Transaction>,
+        lane: PriorityLane,
+        worker_id: WorkerId,
+        epoch: Epoch,
+    ) -> Self {
+        Self {
+            transactions,
+            metadata: Metadata::default(),
+            lane,
+            worker_id,
+            epoch,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.transactions.iter().map(|t| t.len()).sum()
+    }
+}
+
+/// The number of transactions hashed together into a leaf of the digest tree of a V3 batch.
+pub const BATCH_DIGEST_CHUNK_SIZE: usize = 256;
+
+/// The size of the transactions from which the chunks of a V3 batch are hashed in parallel.
+/// Below it, hashing them in sequence is faster than dispatching them to the thread pool.
+pub const PARALLEL_BATCH_DIGEST_MIN_SIZE: usize = 512 * 1024;
+
+/// The root of the digest tree of the transactions of a V3 batch: the digest of the digests of
+/// their consecutive chunks of `BATCH_DIGEST_CHUNK_SIZE` transactions. The chunks do not depend
+/// on how they are hashed, so the digest is the same whether they are hashed in parallel or not.
+fn batch_digest_tree(transactions: &[Transaction], size: usize) -> BatchDigest {
+    let hash_chunk = |chunk: &[Transaction]| -> [u8; crypto::DIGEST_LENGTH] {
+        crypto::DefaultHashFunction::digest_iterator(chunk.iter()).into()
+    };
+    let leaves: Vec<_> = if size >= PARALLEL_BATCH_DIGEST_MIN_SIZE {
+        transactions
+            .par_chunks(BATCH_DIGEST_CHUNK_SIZE)
+            .map(hash_chunk)
+            .collect()
+    } else {
+        transactions
+            .chunks(BATCH_DIGEST_CHUNK_SIZE)
+            .map(hash_chunk)
+            .collect()
+    };
+    BatchDigest::new(crypto::DefaultHashFunction::digest_iterator(leaves.iter()).into())
+}
+
 #[derive(
     Clone,
     Copy,
@@ -216,6 +497,28 @@ impl Hash<{ crypto::DIGEST_LENGTH }> for BatchV1 {
     }
 }
 
+// Like for V1, the digest only covers the transactions, so the same transactions have the
+// same digest whatever the version of their batch.
+impl Hash<{ crypto::DIGEST_LENGTH }> for BatchV2 {
+    type TypedDigest = BatchDigest;
+
+    fn digest(&self) -> Self::TypedDigest {
+        BatchDigest::new(
+            crypto::DefaultHashFunction::digest_iterator(self.transactions.iter()).into(),
+        )
+    }
+}
+
+// Unlike for V1 and V2, the digest is a tree over the transactions, which is why V3 batches
+// have to be enabled by the protocol config.
+impl Hash<{ crypto::DIGEST_LENGTH }> for BatchV3 {
+    type TypedDigest = BatchDigest;
+
+    fn digest(&self) -> Self::TypedDigest {
+        batch_digest_tree(&self.transactions, self.size())
+    }
+}
+
 #[derive(Clone, Deserialize, MallocSizeOf, Serialize)]
 #[enum_dispatch(HeaderAPI)]
 pub enum Header {
@@ -253,6 +556,12 @@ impl Header {
             Header::V1(data) => data.validate(committee, worker_cache),
         }
     }
+
+    pub fn validate_payload_limits(&self, limits: &HeaderPayloadLimits) -> DagResult<()> {
+        match self {
+            Header::V1(data) => data.validate_payload_limits(limits),
+        }
+    }
 }
 
 impl Hash<{ crypto::DIGEST_LENGTH }> for Header {
@@ -423,6 +732,38 @@ impl HeaderV1 {
 
         Ok(())
     }
+
+    /// Ensures the header does not reference more batch digests than allowed, in total and for
+    /// any of the workers. Unlike `validate`, the limits are not checked when verifying
+    /// certificates, as a quorum already voted for their header.
+    pub fn validate_payload_limits(&self, limits: &HeaderPayloadLimits) -> DagResult<()> {
+        ensure!(
+            self.payload.len() <= limits.max_num_of_batches,
+            DagError::HeaderHasTooManyBatches(
+                self.digest(),
+                self.payload.len(),
+                limits.max_num_of_batches
+            )
+        );
+
+        let mut num_of_batches_per_worker = BTreeMap::<WorkerId, usize>::new();
+        for (worker_id, _) in self.payload.values() {
+            *num_of_batches_per_worker.entry(*worker_id).or_default() += 1;
+        }
+        for (worker_id, num_of_batches) in num_of_batches_per_worker {
+            ensure!(
+                num_of_batches <= limits.max_num_of_batches_per_worker,
+                DagError::HeaderHasTooManyWorkerBatches(
+                    self.digest(),
+                    worker_id,
+                    num_of_batches,
+                    limits.max_num_of_batches_per_worker
+                )
+            );
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(
@@ -525,7 +866,7 @@ impl Vote {
     pub async fn new(
         header: &Header,
         author: &AuthorityIdentifier,
-        signature_service: &SignatureService<Signature, { crypto::INTENT_MESSAGE_LENGTH }>,
+        signature_service: &SignatureService,
     ) -> Self {
         Vote::V1(VoteV1::new(header, author, signature_service).await)
     }
@@ -596,7 +937,7 @@ impl VoteV1 {
     pub async fn new(
         header: &Header,
         author: &AuthorityIdentifier,
-        signature_service: &SignatureService<Signature, { crypto::INTENT_MESSAGE_LENGTH }>,
+        signature_service: &SignatureService,
     ) -> Self {
         let vote = Self {
             header_digest: header.digest(),
@@ -759,6 +1100,21 @@ impl Certificate {
         }
     }
 
+    /// Verifies the validity of the certificates, see `CertificateV1::verify_batch`.
+    pub fn verify_batch(
+        certificates: &[&Self],
+        committee: &Committee,
+        worker_cache: &WorkerCache,
+    ) -> Vec<DagResult<()>> {
+        let certificates: Vec<_> = certificates
+            .iter()
+            .map(|certificate| match certificate {
+                Certificate::V1(certificate) => certificate,
+            })
+            .collect();
+        CertificateV1::verify_batch(&certificates, committee, worker_cache)
+    }
+
     pub fn round(&self) -> Round {
         match self {
             Certificate::V1(certificate) => certificate.round(),
@@ -973,6 +1329,20 @@ impl CertificateV1 {
     /// Verifies the validity of the certificate.
     /// TODO: Output a different type, similar to Sui VerifiedCertificate.
     pub fn verify(&self, committee: &Committee, worker_cache: &WorkerCache) -> DagResult<()> {
+        let Some(pks) = self.verify_quorum(committee, worker_cache)? else {
+            return Ok(());
+        };
+        self.verify_signature(&pks)
+    }
+
+    /// Verifies everything but the aggregate signature of the certificate, and returns the
+    /// public keys of its signers. Returns None for the genesis certificates, which are not
+    /// signed.
+    fn verify_quorum(
+        &self,
+        committee: &Committee,
+        worker_cache: &WorkerCache,
+    ) -> DagResult<Option<Vec<PublicKey>>> {
         // Ensure the header is from the correct epoch.
         ensure!(
             self.epoch() == committee.epoch(),
@@ -984,7 +1354,7 @@ impl CertificateV1 {
 
         // Genesis certificates are always valid.
         if self.round() == 0 && Self::genesis(committee).contains(self) {
-            return Ok(());
+            return Ok(None);
         }
 
         // Save signature verifications when the header is invalid.
@@ -996,15 +1366,69 @@ impl CertificateV1 {
             weight >= committee.quorum_threshold(),
             DagError::CertificateRequiresQuorum
         );
+        Ok(Some(pks))
+    }
 
-        // Verify the signatures
-        let certificate_digest: Digest<{ crypto::DIGEST_LENGTH }> = Digest::from(self.digest());
+    fn verify_signature(&self, pks: &[PublicKey]) -> DagResult<()> {
         AggregateSignature::try_from(&self.aggregated_signature)
             .map_err(|_| DagError::InvalidSignature)?
-            .verify_secure(&to_intent_message(certificate_digest), &pks[..])
-            .map_err(|_| DagError::InvalidSignature)?;
+            .verify_secure(&self.signed_message(), pks)
+            .map_err(|_| DagError::InvalidSignature)
+    }
 
-        Ok(())
+    fn signed_message(&self) -> IntentMessage<Digest<{ crypto::DIGEST_LENGTH }>> {
+        to_intent_message(Digest::from(self.digest()))
+    }
+
+    /// Verifies the validity of the certificates, with a single verification of their aggregate
+    /// signatures. When it fails, the signatures are verified one at a time to tell the invalid
+    /// certificates apart.
+    pub fn verify_batch(
+        certificates: &[&Self],
+        committee: &Committee,
+        worker_cache: &WorkerCache,
+    ) -> Vec<DagResult<()>> {
+        let results: Vec<_> = certificates
+            .iter()
+            .map(|certificate| certificate.verify_quorum(committee, worker_cache))
+            .collect();
+
+        // The certificates whose aggregate signature can be parsed are verified in a batch.
+        let mut in_batch = vec![false; certificates.len()];
+        let mut signatures = Vec::new();
+        let mut pks = Vec::new();
+        let mut messages = Vec::new();
+        for (i, (certificate, result)) in certificates.iter().zip(&results).enumerate() {
+            let Ok(Some(signers)) = result else {
+                continue;
+            };
+            let signature = AggregateSignature::try_from(&certificate.aggregated_signature);
+            let Ok(signature) = signature else {
+                continue;
+            };
+            in_batch[i] = true;
+            signatures.push(signature);
+            pks.push(&signers[..]);
+            messages.push(certificate.signed_message());
+        }
+        let batch_verified = !signatures.is_empty()
+            && AggregateSignature::batch_verify_secure(
+                &signatures.iter().collect::<Vec<_>>(),
+                &pks,
+                &messages,
+            )
+            .is_ok();
+
+        results
+            .into_iter()
+            .zip(in_batch)
+            .zip(certificates)
+            .map(|((result, in_batch), certificate)| match result? {
+                None => Ok(()),
+                Some(_) if batch_verified && in_batch => Ok(()),
+                Some(signers) => certificate.verify_signature(&signers),
+            })
+            .collect()
     }
 
     pub fn round(&self) -> Round {
@@ -1256,6 +1680,157 @@ pub struct FetchCertificatesResponse {
     pub certificates: Vec<Certificate>,
 }
 
+/// Used by the primary to fetch certificates from other primaries, within per authority round
+/// ranges and along with a summary of the certificates the peer has. The primaries which do not
+/// serve it yet are sent the `FetchCertificatesRequest` instead.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchCertificatesV2Request {
+    pub request: FetchCertificatesRequest,
+    /// The inclusive upper bound of the rounds to return, per authority. The certificates of
+    /// the authorities without an upper bound are returned up to their highest round.
+    pub upper_bounds: Vec<(AuthorityIdentifier, Round)>,
+    /// Whether the response should summarize the certificates the peer has above the lower
+    /// bound, so the requestor can plan its catch-up.
+    pub include_summary: bool,
+}
+
+impl FetchCertificatesV2Request {
+    pub fn new(request: FetchCertificatesRequest) -> Self {
+        Self {
+            request,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_upper_bounds(&self) -> BTreeMap<AuthorityIdentifier, Round> {
+        self.upper_bounds.iter().copied().collect()
+    }
+
+    pub fn set_upper_bounds(mut self, upper_bounds: BTreeMap<AuthorityIdentifier, Round>) -> Self {
+        self.upper_bounds = upper_bounds.into_iter().collect();
+        self
+    }
+
+    pub fn set_include_summary(mut self, include_summary: bool) -> Self {
+        self.include_summary = include_summary;
+        self
+    }
+}
+
+/// Used by the primary to reply to FetchCertificatesV2Request.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FetchCertificatesV2Response {
+    /// Certificates sorted from lower to higher rounds.
+    pub certificates: Vec<Certificate>,
+    /// The rounds of the certificates the peer has above the lower bound of the request, per
+    /// authority. Empty unless requested with `include_summary`.
+    pub summary: Vec<AuthorityCertificatesSummary>,
+}
+
+/// Summarizes the certificates a primary has for an authority, above a lower bound round.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorityCertificatesSummary {
+    pub origin: AuthorityIdentifier,
+    pub lowest_round: Round,
+    pub highest_round: Round,
+}
+
+/// Used by a primary joining the committee to get a snapshot of the DAG of another primary.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct GetDagSnapshotRequest {
+    /// Maximum number of certificates above the last committed round that should be returned.
+    pub max_items: usize,
+}
+
+/// Used by the primary to reply to GetDagSnapshotRequest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetDagSnapshotResponse {
+    pub manifest: DagSnapshotManifest,
+    /// The signature of the manifest digest by its author.
+    pub signature: Signature,
+    /// The certificates listed by the manifest, sorted from lower to higher rounds.
+    pub certificates: Vec<Certificate>,
+}
+
+/// Describes the state of the DAG of a primary, from which another primary can start instead
+/// of replaying the DAG from genesis.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DagSnapshotManifest {
+    pub epoch: Epoch,
+    /// The authority serving the snapshot.
+    pub author: AuthorityIdentifier,
+    /// The last committed round of each authority.
+    pub last_committed: BTreeMap<AuthorityIdentifier, Round>,
+    /// The garbage collection round of the snapshot.
+    pub gc_round: Round,
+    /// The latest committed sub dag.
+    pub latest_sub_dag: Option<CommittedSubDagShell>,
+    /// The latest committed sub dag whose reputation scores are the final ones of their
+    /// schedule, from which the leader schedule is restored. It is the latest sub dag itself
+    /// when that one ends a schedule.
+    pub latest_final_sub_dag: Option<CommittedSubDagShell>,
+    /// The digests of the certificates of the snapshot, above the garbage collection round.
+    pub certificates: Vec<CertificateDigest>,
+}
+
+impl DagSnapshotManifest {
+    /// The highest round committed in the snapshot.
+    pub fn last_committed_round(&self) -> Round {
+        self.last_committed
+            .values()
+            .max()
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DagSnapshotDigest([u8; crypto::DIGEST_LENGTH]);
+
+impl From<DagSnapshotDigest> for Digest<{ crypto::DIGEST_LENGTH }> {
+    fn from(digest: DagSnapshotDigest) -> Self {
+        Digest::new(digest.0)
+    }
+}
+
+impl From<DagSnapshotDigest> for Digest<{ crypto::INTENT_MESSAGE_LENGTH }> {
+    fn from(digest: DagSnapshotDigest) -> Self {
+        let intent_message = to_intent_message(HeaderDigest(digest.0));
+        Digest {
+            digest: bcs::to_bytes(&intent_message)
+                .expect("Serialization message should not fail")
+                .try_into()
+                .expect("INTENT_MESSAGE_LENGTH is correct"),
+        }
+    }
+}
+
+impl fmt::Debug for DagSnapshotDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}", base64::encode(self.0))
+    }
+}
+
+impl fmt::Display for DagSnapshotDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "{}",
+            base64::encode(self.0).get(0..16).ok_or(fmt::Error)?
+        )
+    }
+}
+
+impl Hash<{ crypto::DIGEST_LENGTH }> for DagSnapshotManifest {
+    type TypedDigest = DagSnapshotDigest;
+
+    fn digest(&self) -> DagSnapshotDigest {
+        let mut hasher = crypto::DefaultHashFunction::new();
+        hasher.update(bcs::to_bytes(&self).expect("Serialization should not fail"));
+        DagSnapshotDigest(hasher.finalize().into())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct PayloadAvailabilityRequest {
     pub certificate_digests: Vec<CertificateDigest>,
@@ -1284,6 +1859,9 @@ pub struct WorkerSynchronizeMessage {
     // the batch it receives because it is part of a certificate. Only digest
     // verification is required.
     pub is_certified: bool,
+    // The round of the header whose batches are synchronized, if the synchronization is no longer
+    // needed once the round is garbage collected.
+    pub round: Option<Round>,
 }
 
 /// Used by the primary to request that the worker delete the specified batches.
@@ -1292,6 +1870,37 @@ pub struct WorkerDeleteBatchesMessage {
     pub digests: Vec<BatchDigest>,
 }
 
+/// The progress of the batches of a worker through consensus, reported by its primary.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BatchStatus {
+    /// The batches are included in a certificate of the primary.
+    Certified {
+        certificate: CertificateDigest,
+        round: Round,
+    },
+    /// The certificate including the batches is committed by the leader of `commit_round`.
+    Committed {
+        certificate: CertificateDigest,
+        round: Round,
+        commit_round: Round,
+    },
+}
+
+/// Used by the primary to report the progress of the batches of the worker.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WorkerBatchStatusMessage {
+    pub digests: Vec<BatchDigest>,
+    pub status: BatchStatus,
+}
+
+/// Used by the primary to report to the worker its last committed round, and the round up to
+/// which the DAG is garbage collected, i.e. below which nothing can be committed anymore.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WorkerRoundsMessage {
+    pub committed_round: Round,
+    pub gc_round: Round,
+}
+
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
 pub struct BatchMessage {
     // TODO: revisit including the digest here [see #188]
@@ -1342,6 +1951,8 @@ pub struct WorkerOurBatchMessage {
     pub digest: BatchDigest,
     pub worker_id: WorkerId,
     pub metadata: Metadata,
+    /// The total size in bytes of the transactions of the batch.
+    pub size: u64,
 }
 
 /// Used by worker to inform primary it received a batch from another authority.
@@ -1414,7 +2025,11 @@ impl From<&Vote> for VoteInfo {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Batch, BatchAPI, BatchV1, Metadata, Timestamp};
+    use crate::{
+        error::DagError, Batch, BatchAPI, BatchDigest, BatchV1, Header, HeaderV1, Metadata,
+        Timestamp,
+    };
+    use config::{BatchLimits, HeaderPayloadLimits};
     use std::time::Duration;
     use tokio::time::sleep;
 
@@ -1439,4 +2054,54 @@ mod tests {
 
         assert_eq!(batch.metadata().created_at.elapsed().as_secs_f64(), 0.0);
     }
+
+    #[test]
+    fn test_header_payload_limits() {
+        // Two batches of each of the workers 0 and 1.
+        let header = Header::V1(HeaderV1 {
+            payload: (0..4u8)
+                .map(|i| (BatchDigest::new([i; 32]), (u32::from(i % 2), 0)))
+                .collect(),
+            ..Default::default()
+        });
+        let limits = |max_num_of_batches, max_num_of_batches_per_worker| HeaderPayloadLimits {
+            max_num_of_batches,
+            max_num_of_batches_per_worker,
+        };
+
+        // The limits are inclusive.
+        assert!(header.validate_payload_limits(&limits(4, 2)).is_ok());
+        assert!(matches!(
+            header.validate_payload_limits(&limits(3, 2)),
+            Err(DagError::HeaderHasTooManyBatches(_, 4, 3))
+        ));
+        assert!(matches!(
+            header.validate_payload_limits(&limits(4, 1)),
+            Err(DagError::HeaderHasTooManyWorkerBatches(_, 0, 2, 1))
+        ));
+
+        // An empty header is always within the limits.
+        let empty = Header::V1(HeaderV1::default());
+        assert!(empty.validate_payload_limits(&limits(0, 0)).is_ok());
+    }
+
+    #[test]
+    fn test_batch_limits() {
+        let batch = Batch::new(vec![vec![0; 10], vec![0; 20]]);
+        let limits = |max_num_of_transactions, max_size| BatchLimits {
+            max_num_of_transactions,
+            max_size,
+        };
+
+        // The limits are inclusive.
+        assert!(batch.validate_limits(&limits(2, 30)).is_ok());
+        assert!(matches!(
+            batch.validate_limits(&limits(1, 30)),
+            Err(DagError::BatchHasTooManyTransactions(_, 2, 1))
+        ));
+        assert!(matches!(
+            batch.validate_limits(&limits(2, 29)),
+            Err(DagError::BatchTooLarge(_, 30, 29))
+        ));
+    }
 }
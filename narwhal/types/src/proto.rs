@@ -12,13 +12,18 @@ mod narwhal {
 
 use std::{array::TryFromSliceError, ops::Deref};
 
-use crate::{BlockError, BlockErrorKind, CertificateDigest, Transaction};
+use crate::{
+    Batch, BatchAPI, BlockError, BlockErrorKind, CertificateDigest, PriorityLane, Transaction,
+};
 use bytes::Bytes;
 use crypto::PublicKey;
+use fastcrypto::hash::Hash;
 
 pub use narwhal::{
     collection_error::CollectionErrorType,
     collection_retrieval_result::RetrievalResult,
+    commits_client::CommitsClient,
+    commits_server::{Commits, CommitsServer},
     configuration_client::ConfigurationClient,
     configuration_server::{Configuration, ConfigurationServer},
     primary_to_primary_client::PrimaryToPrimaryClient,
@@ -27,6 +32,8 @@ pub use narwhal::{
     primary_to_worker_server::{MockPrimaryToWorker, PrimaryToWorker, PrimaryToWorkerServer},
     proposer_client::ProposerClient,
     proposer_server::{Proposer, ProposerServer},
+    transaction::Priority as TransactionPriorityProto,
+    transaction_status::Stage as TransactionStageProto,
     transactions_client::TransactionsClient,
     transactions_server::{Transactions, TransactionsServer},
     validator_client::ValidatorClient,
@@ -36,11 +43,14 @@ pub use narwhal::{
     worker_to_worker_client::WorkerToWorkerClient,
     worker_to_worker_server::{MockWorkerToWorker, WorkerToWorker, WorkerToWorkerServer},
     CertificateDigest as CertificateDigestProto, Collection, CollectionError,
-    CollectionRetrievalResult, Empty, GetCollectionsRequest, GetCollectionsResponse,
-    GetPrimaryAddressResponse, MultiAddr as MultiAddrProto, NewEpochRequest, NewNetworkInfoRequest,
-    NodeReadCausalRequest, NodeReadCausalResponse, PublicKey as PublicKeyProto, ReadCausalRequest,
-    ReadCausalResponse, RemoveCollectionsRequest, RoundsRequest, RoundsResponse,
-    Transaction as TransactionProto, ValidatorData,
+    CollectionRetrievalResult, CommittedBatch as CommittedBatchProto,
+    CommittedCertificate as CommittedCertificateProto, CommittedSubDag as CommittedSubDagProto,
+    Empty, GetCollectionsRequest, GetCollectionsResponse, GetPrimaryAddressResponse,
+    MultiAddr as MultiAddrProto, NewEpochRequest, NewNetworkInfoRequest, NodeReadCausalRequest,
+    NodeReadCausalResponse, PublicKey as PublicKeyProto, ReadCausalRequest, ReadCausalResponse,
+    RemoveCollectionsRequest, RoundsRequest, RoundsResponse, SubscribeCommitsRequest,
+    Transaction as TransactionProto, TransactionStatus as TransactionStatusProto,
+    TransactionStatusRequest, ValidatorData,
 };
 
 impl From<PublicKey> for PublicKeyProto {
@@ -55,6 +65,27 @@ impl From<Transaction> for TransactionProto {
     fn from(transaction: Transaction) -> Self {
         TransactionProto {
             transaction: Bytes::from(transaction),
+            priority: TransactionPriorityProto::Normal.into(),
+        }
+    }
+}
+
+impl From<PriorityLane> for TransactionPriorityProto {
+    fn from(lane: PriorityLane) -> Self {
+        match lane {
+            PriorityLane::System => TransactionPriorityProto::System,
+            PriorityLane::High => TransactionPriorityProto::High,
+            PriorityLane::Normal => TransactionPriorityProto::Normal,
+        }
+    }
+}
+
+impl From<TransactionPriorityProto> for PriorityLane {
+    fn from(priority: TransactionPriorityProto) -> Self {
+        match priority {
+            TransactionPriorityProto::System => PriorityLane::System,
+            TransactionPriorityProto::High => PriorityLane::High,
+            TransactionPriorityProto::Normal => PriorityLane::Normal,
         }
     }
 }
@@ -65,6 +96,20 @@ impl From<TransactionProto> for Transaction {
     }
 }
 
+impl From<Batch> for CommittedBatchProto {
+    fn from(batch: Batch) -> Self {
+        CommittedBatchProto {
+            digest: Bytes::from(batch.digest().0.to_vec()),
+            transactions: batch
+                .transactions()
+                .iter()
+                .cloned()
+                .map(Bytes::from)
+                .collect(),
+        }
+    }
+}
+
 impl From<BlockError> for CollectionError {
     fn from(error: BlockError) -> Self {
         CollectionError {
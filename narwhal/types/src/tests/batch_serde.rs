@@ -2,7 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::worker::batch_serde::Token::NewtypeVariant;
-use crate::{Batch, BatchV1, Metadata};
+use crate::{
+    Batch, BatchAPI, BatchCompression, BatchDigestFilter, BatchV1, BatchV2, BatchV3, Metadata,
+    PriorityLane, WorkerBatchMessage, BATCH_DIGEST_CHUNK_SIZE, PARALLEL_BATCH_DIGEST_MIN_SIZE,
+};
+use config::BatchVersion;
+use fastcrypto::hash::{Hash, HashFunction};
+use serde::{Deserialize, Serialize};
 use serde_test::{assert_tokens, Token};
 #[test]
 fn test_serde_batch() {
@@ -55,3 +61,260 @@ fn test_serde_batch() {
         ],
     );
 }
+
+#[test]
+fn test_serde_batch_v2() {
+    let batch = Batch::V2(BatchV2 {
+        transactions: vec![vec![1; 2]],
+        metadata: Metadata {
+            created_at: 1666205365890,
+        },
+        lane: PriorityLane::High,
+        worker_id: 3,
+        epoch: 7,
+    });
+
+    assert_tokens(
+        &batch,
+        &[
+            NewtypeVariant {
+                name: "Batch",
+                variant: "V2",
+            },
+            Token::Struct {
+                name: "BatchV2",
+                len: 5,
+            },
+            Token::Str("transactions"),
+            Token::Seq { len: Some(1) },
+            Token::Seq { len: Some(2) },
+            Token::U8(1),
+            Token::U8(1),
+            Token::SeqEnd,
+            Token::SeqEnd,
+            Token::Str("metadata"),
+            Token::Struct {
+                name: "Metadata",
+                len: 1,
+            },
+            Token::Str("created_at"),
+            Token::U64(1666205365890),
+            Token::StructEnd,
+            Token::Str("lane"),
+            Token::UnitVariant {
+                name: "PriorityLane",
+                variant: "High",
+            },
+            Token::Str("worker_id"),
+            Token::U32(3),
+            Token::Str("epoch"),
+            Token::U64(7),
+            Token::StructEnd,
+            Token::StructEnd,
+        ],
+    );
+}
+
+// The batch type of the nodes that predate the versioned batches.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum UnversionedBatch {
+    V1(BatchV1),
+}
+
+#[test]
+fn test_batch_v1_wire_compatibility() {
+    let batch = Batch::new(vec![vec![1; 5], vec![2; 5]]);
+    let Batch::V1(inner) = batch.clone() else {
+        panic!("Batch::new should create a V1 batch");
+    };
+
+    // Old nodes decode the V1 batches of upgraded nodes, and the other way round.
+    let bytes = bcs::to_bytes(&batch).unwrap();
+    let decoded: UnversionedBatch = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, UnversionedBatch::V1(inner.clone()));
+    let bytes = bcs::to_bytes(&UnversionedBatch::V1(inner)).unwrap();
+    let decoded: Batch = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, batch);
+    assert_eq!(decoded.lane(), PriorityLane::Normal);
+    assert_eq!(decoded.worker_id(), None);
+    assert_eq!(decoded.epoch(), None);
+
+    // V2 batches cannot be decoded by old nodes, which is why they have to be enabled by the
+    // protocol config.
+    let batch_v2 = Batch::V2(BatchV2::new(vec![vec![1; 5]], PriorityLane::System, 0, 0));
+    let bytes = bcs::to_bytes(&batch_v2).unwrap();
+    assert!(bcs::from_bytes::<UnversionedBatch>(&bytes).is_err());
+    let decoded: Batch = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, batch_v2);
+}
+
+#[test]
+fn test_batch_digest_does_not_depend_on_version() {
+    let transactions = vec![vec![1; 5], vec![2; 5]];
+    let batch_v1 = Batch::V1(BatchV1::new(transactions.clone()));
+    let batch_v2 = Batch::V2(BatchV2::new(transactions, PriorityLane::High, 1, 2));
+
+    assert_eq!(batch_v1.digest(), batch_v2.digest());
+}
+
+#[test]
+fn test_batch_digest_does_not_cover_metadata() {
+    let transactions = vec![vec![1; 5], vec![2; 5]];
+    let batch = Batch::V2(BatchV2::new(
+        transactions.clone(),
+        PriorityLane::Normal,
+        0,
+        0,
+    ));
+    let other = Batch::V2(BatchV2 {
+        transactions,
+        metadata: Metadata { created_at: 1 },
+        lane: PriorityLane::System,
+        worker_id: 1,
+        epoch: 1,
+    });
+    assert_eq!(batch.digest(), other.digest());
+
+    // While the transactions are.
+    let mut changed = batch.clone();
+    changed.transactions_mut().push(vec![3; 5]);
+    assert_ne!(batch.digest(), changed.digest());
+}
+
+#[test]
+fn test_batch_v3_digest_tree() {
+    let transactions: Vec<_> = (0..3 * BATCH_DIGEST_CHUNK_SIZE)
+        .map(|i| vec![i as u8; 1_024])
+        .collect();
+    // Large enough to be hashed in parallel.
+    assert!(transactions.len() * 1_024 >= PARALLEL_BATCH_DIGEST_MIN_SIZE);
+    let batch = Batch::V3(BatchV3::new(
+        transactions.clone(),
+        PriorityLane::Normal,
+        0,
+        0,
+    ));
+
+    // The digest is the root of the tree over the chunks, not the digest of the transactions.
+    let leaves: Vec<[u8; 32]> = transactions
+        .chunks(BATCH_DIGEST_CHUNK_SIZE)
+        .map(|chunk| crypto::DefaultHashFunction::digest_iterator(chunk.iter()).into())
+        .collect();
+    let root: [u8; 32] = crypto::DefaultHashFunction::digest_iterator(leaves.iter()).into();
+    assert_eq!(batch.digest().0, root);
+    assert_ne!(
+        batch.digest(),
+        Batch::V1(BatchV1::new(transactions.clone())).digest()
+    );
+
+    // Like for the other versions, only the transactions are covered.
+    let other = Batch::V3(BatchV3 {
+        transactions: transactions.clone(),
+        metadata: Metadata { created_at: 1 },
+        lane: PriorityLane::System,
+        worker_id: 1,
+        epoch: 1,
+    });
+    assert_eq!(batch.digest(), other.digest());
+
+    // The chunks of a small batch are hashed in sequence, into the same tree.
+    let small = Batch::V3(BatchV3::new(
+        transactions[..BATCH_DIGEST_CHUNK_SIZE + 1]
+            .iter()
+            .map(|transaction| transaction[..1].to_vec())
+            .collect(),
+        PriorityLane::Normal,
+        0,
+        0,
+    ));
+    let leaves: Vec<[u8; 32]> = small
+        .transactions()
+        .chunks(BATCH_DIGEST_CHUNK_SIZE)
+        .map(|chunk| crypto::DefaultHashFunction::digest_iterator(chunk.iter()).into())
+        .collect();
+    let root: [u8; 32] = crypto::DefaultHashFunction::digest_iterator(leaves.iter()).into();
+    assert_eq!(small.digest().0, root);
+}
+
+#[test]
+fn test_worker_batch_message_compression_roundtrip() {
+    let batch = Batch::new((0..10).map(|i| vec![i; 100]).collect());
+
+    for compression in BatchCompression::SUPPORTED {
+        let message = WorkerBatchMessage {
+            batch: batch.clone(),
+            compression,
+        };
+        let bytes = bcs::to_bytes(&message).unwrap();
+        let decoded: WorkerBatchMessage = bcs::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded, message);
+        // The digest does not depend on the codec used on the wire.
+        assert_eq!(decoded.batch.digest(), batch.digest());
+    }
+}
+
+#[test]
+fn test_worker_batch_message_legacy_encoding() {
+    // The encoding of the messages sent by the workers which do not compress batches.
+    #[derive(Serialize)]
+    struct LegacyWorkerBatchMessage {
+        batch: Batch,
+    }
+
+    for batch in [
+        Batch::new(vec![vec![1; 5]]),
+        Batch::new_versioned(vec![vec![1; 5]], PriorityLane::High, 3, 7, BatchVersion::V2),
+        Batch::new_versioned(vec![vec![1; 5]], PriorityLane::High, 3, 7, BatchVersion::V3),
+    ] {
+        let legacy = bcs::to_bytes(&LegacyWorkerBatchMessage {
+            batch: batch.clone(),
+        })
+        .unwrap();
+        let decoded: WorkerBatchMessage = bcs::from_bytes(&legacy).unwrap();
+        assert_eq!(decoded.batch, batch);
+        assert_eq!(decoded.compression, BatchCompression::None);
+
+        // Uncompressed messages are still sent in the legacy encoding.
+        assert_eq!(bcs::to_bytes(&decoded).unwrap(), legacy);
+    }
+}
+
+#[test]
+fn test_negotiate_batch_compression() {
+    assert_eq!(
+        BatchCompression::negotiate(
+            &BatchCompression::SUPPORTED,
+            &[BatchCompression::Lz4, BatchCompression::Zstd]
+        ),
+        BatchCompression::Zstd
+    );
+    assert_eq!(
+        BatchCompression::negotiate(&BatchCompression::SUPPORTED, &[BatchCompression::Lz4]),
+        BatchCompression::Lz4
+    );
+    assert_eq!(
+        BatchCompression::negotiate(&BatchCompression::SUPPORTED, &[]),
+        BatchCompression::None
+    );
+}
+
+#[test]
+fn test_batch_digest_filter() {
+    let digest = |i: u32| Batch::new(vec![i.to_le_bytes().to_vec()]).digest();
+    let mut filter = BatchDigestFilter::new(1_000, 42);
+    for i in 0..1_000 {
+        filter.insert(&digest(i));
+    }
+
+    // The filter survives the trip to another worker, and never misses an inserted digest.
+    let filter: BatchDigestFilter = bcs::from_bytes(&bcs::to_bytes(&filter).unwrap()).unwrap();
+    assert!(filter.is_well_formed());
+    assert!((0..1_000).all(|i| filter.contains(&digest(i))));
+
+    // The false positive rate is about 1%.
+    let false_positives = (1_000..11_000)
+        .filter(|i| filter.contains(&digest(*i)))
+        .count();
+    assert!(false_positives < 300, "{false_positives} false positives");
+}
@@ -0,0 +1,84 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use store::rocks::{MetricConf, ReadWriteOptions};
+use store::{reopen, rocks};
+
+fn open_consensus_store(store_path: &std::path::Path) -> ConsensusStore {
+    const LAST_COMMITTED_CF: &str = "last_committed";
+    const SEQUENCE_CF: &str = "sequence";
+    const SEQUENCE_V2_CF: &str = "sequence_v2";
+
+    let rocksdb = rocks::open_cf(
+        store_path,
+        None,
+        MetricConf::default(),
+        &[LAST_COMMITTED_CF, SEQUENCE_CF, SEQUENCE_V2_CF],
+    )
+    .expect("Failed to create database");
+    let (last_committed_map, sequence_map, sequence_v2_map) = reopen!(&rocksdb,
+        LAST_COMMITTED_CF;<AuthorityIdentifier, Round>,
+        SEQUENCE_CF;<SequenceNumber, CommittedSubDagShellV1>,
+        SEQUENCE_V2_CF;<SequenceNumber, VersionedCommittedSubDagShell>
+    );
+    ConsensusStore::new(last_committed_map, sequence_map, sequence_v2_map)
+}
+
+#[test]
+fn read_sub_dags_stored_before_versioning() {
+    let store = open_consensus_store(&test_utils::temp_dir());
+
+    // The bytes of a sub dag as stored before its commit timestamp was recorded.
+    let mut bytes = vec![1];
+    bytes.extend([1; crypto::DIGEST_LENGTH]);
+    bytes.extend([2; crypto::DIGEST_LENGTH]);
+    bytes.extend(4u64.to_le_bytes());
+    bytes.extend(1u64.to_le_bytes());
+    bytes.extend([0, 1]);
+    let legacy: CommittedSubDagShellV1 = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(bcs::to_bytes(&legacy).unwrap(), bytes);
+    store
+        .committed_sub_dags_by_index
+        .insert(&1, &legacy)
+        .unwrap();
+
+    let sub_dag = store.get_latest_sub_dag().unwrap();
+    assert_eq!(sub_dag.certificates, vec![CertificateDigest::new([1; 32])]);
+    assert_eq!(sub_dag.leader, CertificateDigest::new([2; 32]));
+    assert_eq!((sub_dag.leader_round, sub_dag.sub_dag_index), (4, 1));
+    assert!(sub_dag.reputation_score.final_of_schedule);
+    assert_eq!(sub_dag.commit_timestamp, 0);
+
+    // The sub dags committed next are versioned, and read after the legacy ones.
+    let next = CommittedSubDagShell {
+        certificates: vec![CertificateDigest::new([3; 32])],
+        leader: CertificateDigest::new([3; 32]),
+        leader_round: 6,
+        sub_dag_index: 2,
+        reputation_score: ReputationScores::default(),
+        commit_timestamp: 1_000,
+    };
+    store
+        .write_snapshot_state(&BTreeMap::new(), Some(&next), None)
+        .unwrap();
+    assert_eq!(store.get_latest_sub_dag_index(), 2);
+    assert_eq!(store.get_latest_sub_dag(), Some(next.clone()));
+    assert_eq!(
+        store.read_latest_commit_with_final_reputation_scores(),
+        Some(sub_dag.clone())
+    );
+    assert_eq!(
+        store.read_committed_sub_dags_from(&0).unwrap(),
+        vec![sub_dag.clone(), next.clone()]
+    );
+    assert_eq!(
+        store
+            .read_committed_sub_dags_from_with_limit(&2, 10)
+            .unwrap(),
+        vec![next.clone()]
+    );
+    assert_eq!(
+        store.read_committed_sub_dags_since_round(4),
+        vec![sub_dag, next]
+    );
+}
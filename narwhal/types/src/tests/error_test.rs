@@ -74,3 +74,35 @@ async fn with_permit_unhappy_case() {
 
     assert_eq!(recvd, (0..100).collect::<Vec<usize>>());
 }
+
+#[test]
+fn rejection_reasons() {
+    use super::RejectionReason;
+    use crate::HeaderDigest;
+
+    assert_eq!(
+        DagError::InvalidSignature.rejection_reason(),
+        Some(RejectionReason::BadSignature)
+    );
+    assert_eq!(
+        DagError::InvalidEpoch {
+            expected: 1,
+            received: 0
+        }
+        .rejection_reason(),
+        Some(RejectionReason::WrongEpoch)
+    );
+    assert_eq!(
+        DagError::HeaderHasTooManyBatches(HeaderDigest::default(), 10, 5).rejection_reason(),
+        Some(RejectionReason::TooManyDigests)
+    );
+    assert_eq!(
+        DagError::TooOld(HeaderDigest::default().into(), 1, 10).rejection_reason(),
+        Some(RejectionReason::StaleRound)
+    );
+    assert_eq!(RejectionReason::UnknownParent.to_string(), "unknown_parent");
+
+    // Failures of the node do not reject what it validates.
+    assert_eq!(DagError::ShuttingDown.rejection_reason(), None);
+    assert_eq!(DagError::ChannelFull.rejection_reason(), None);
+}
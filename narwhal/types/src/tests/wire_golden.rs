@@ -0,0 +1,228 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Golden encodings of the messages exchanged by the workers. The encodings of the messages built
+//! below are compared to the ones recorded in the golden file, so that a change to any of them,
+//! which would break the wire compatibility with the nodes already deployed, fails the tests.
+//! The compressed payloads of `WorkerBatchMessage` depend on the version of the codecs, so only
+//! the uncompressed one is recorded, which is the encoding of the batch itself, the compressed
+//! ones being checked to round trip.
+
+use crate::{
+    Batch, BatchCompression, BatchDigest, BatchV1, BatchV2, BatchV3, FetchPriority, Metadata,
+    PriorityLane, RequestBatchRequest, RequestBatchResponse, RequestBatchesRequest,
+    RequestBatchesResponse, RequestBatchesV2Request, RequestBatchesV2Response, WorkerBatchMessage,
+};
+use fastcrypto::{
+    encoding::{Encoding, Hex},
+    hash::Hash,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::BTreeMap, fmt::Debug, path::PathBuf};
+
+const GOLDEN_FILE: &str = "src/tests/golden/worker_messages.txt";
+
+const GOLDEN_HEADER: &str = "\
+# Golden BCS encodings of the worker wire messages, and digests of their batches, in hex.
+# A change to an encoding breaks the wire compatibility with the nodes already deployed.
+# Only for an intended change, regenerate with:
+#   UPDATE_GOLDEN=1 cargo test -p narwhal-types golden
+";
+
+fn batch_v1() -> Batch {
+    Batch::V1(BatchV1 {
+        transactions: vec![vec![1, 2, 3], vec![4]],
+        metadata: Metadata {
+            created_at: 1666205365890,
+        },
+    })
+}
+
+fn batch_v2() -> Batch {
+    Batch::V2(BatchV2 {
+        transactions: vec![vec![5, 5]],
+        metadata: Metadata {
+            created_at: 1666205365890,
+        },
+        lane: PriorityLane::High,
+        worker_id: 3,
+        epoch: 7,
+    })
+}
+
+fn batch_v3() -> Batch {
+    Batch::V3(BatchV3 {
+        transactions: vec![vec![5, 5], vec![6]],
+        metadata: Metadata {
+            created_at: 1666205365890,
+        },
+        lane: PriorityLane::System,
+        worker_id: 3,
+        epoch: 7,
+    })
+}
+
+/// An encoding recorded in the golden file, along with a check that the recorded bytes decode
+/// to the expected value.
+struct Vector {
+    name: &'static str,
+    bytes: Vec<u8>,
+    check_decode: Box<dyn Fn(&[u8])>,
+}
+
+fn message<T>(name: &'static str, message: T) -> Vector
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug + 'static,
+{
+    let bytes = bcs::to_bytes(&message).unwrap();
+    let decoded: T = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, message, "{name} does not round trip");
+    Vector {
+        name,
+        bytes,
+        check_decode: Box::new(move |bytes| {
+            let decoded: T = bcs::from_bytes(bytes).unwrap();
+            assert_eq!(
+                decoded, message,
+                "the golden {name} decodes to another message"
+            );
+        }),
+    }
+}
+
+fn digest(name: &'static str, batch: Batch) -> Vector {
+    Vector {
+        name,
+        bytes: batch.digest().0.to_vec(),
+        check_decode: Box::new(|_| ()),
+    }
+}
+
+fn vectors() -> Vec<Vector> {
+    vec![
+        message("batch_v1", batch_v1()),
+        message("batch_v2", batch_v2()),
+        message("batch_v3", batch_v3()),
+        message(
+            "worker_batch_message",
+            WorkerBatchMessage {
+                batch: batch_v1(),
+                compression: BatchCompression::None,
+            },
+        ),
+        message(
+            "request_batch_request",
+            RequestBatchRequest {
+                batch: BatchDigest([7; 32]),
+            },
+        ),
+        message(
+            "request_batch_response",
+            RequestBatchResponse {
+                batch: Some(batch_v2()),
+            },
+        ),
+        message(
+            "request_batch_response_missing",
+            RequestBatchResponse { batch: None },
+        ),
+        message(
+            "request_batches_request",
+            RequestBatchesRequest {
+                batch_digests: vec![BatchDigest([7; 32]), BatchDigest([8; 32])],
+            },
+        ),
+        message(
+            "request_batches_v2_request",
+            RequestBatchesV2Request {
+                batch_digests: vec![BatchDigest([7; 32]), BatchDigest([8; 32])],
+                priority: FetchPriority::BlocksExecution,
+            },
+        ),
+        message(
+            "request_batches_response",
+            RequestBatchesResponse {
+                batches: vec![batch_v1(), batch_v2()],
+                is_size_limit_reached: true,
+            },
+        ),
+        message(
+            "request_batches_v2_response",
+            RequestBatchesV2Response {
+                batches: vec![batch_v1(), batch_v2()],
+                remaining_digests: vec![BatchDigest([9; 32])],
+                missing_digests: vec![BatchDigest([10; 32])],
+            },
+        ),
+        digest("batch_v1_digest", batch_v1()),
+        digest("batch_v2_digest", batch_v2()),
+        digest("batch_v3_digest", batch_v3()),
+    ]
+}
+
+fn golden_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_FILE)
+}
+
+fn read_golden() -> BTreeMap<String, Vec<u8>> {
+    std::fs::read_to_string(golden_path())
+        .unwrap()
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, hex) = line
+                .split_once(" = ")
+                .unwrap_or_else(|| panic!("malformed golden line: {line}"));
+            (name.to_string(), Hex::decode(hex).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn test_golden_worker_messages() {
+    let vectors = vectors();
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        let mut golden = GOLDEN_HEADER.to_string();
+        for vector in &vectors {
+            golden.push_str(&format!(
+                "{} = {}\n",
+                vector.name,
+                Hex::encode(&vector.bytes)
+            ));
+        }
+        std::fs::write(golden_path(), golden).unwrap();
+        return;
+    }
+
+    let golden = read_golden();
+    let mut names: Vec<_> = vectors.iter().map(|vector| vector.name).collect();
+    names.sort_unstable();
+    assert_eq!(
+        golden.keys().map(String::as_str).collect::<Vec<_>>(),
+        names,
+        "the golden file does not record the expected messages"
+    );
+    for vector in &vectors {
+        let expected = &golden[vector.name];
+        assert_eq!(
+            Hex::encode(&vector.bytes),
+            Hex::encode(expected),
+            "the encoding of {} changed, which breaks the wire compatibility",
+            vector.name
+        );
+        (vector.check_decode)(expected);
+    }
+}
+
+#[test]
+fn test_compressed_worker_batch_messages_round_trip() {
+    for compression in [BatchCompression::Zstd, BatchCompression::Lz4] {
+        let message = WorkerBatchMessage {
+            batch: batch_v2(),
+            compression,
+        };
+        let bytes = bcs::to_bytes(&message).unwrap();
+        let decoded: WorkerBatchMessage = bcs::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, message);
+    }
+}
@@ -1,19 +1,235 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Batch, BatchDigest};
+use crate::{Batch, BatchDigest, BatchV1, BatchV2, BatchV3};
+use crypto::NetworkPublicKey;
+use fastcrypto::hash::Hash;
 
-use serde::{Deserialize, Serialize};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    io::{Read, Write},
+};
 use thiserror::Error;
 
 #[cfg(test)]
 #[path = "tests/batch_serde.rs"]
 mod batch_serde;
 
-/// Used by workers to send a new batch.
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg(test)]
+#[path = "tests/wire_golden.rs"]
+mod wire_golden;
+
+/// The maximum size (in bytes) a compressed batch is allowed to expand to when decompressed.
+pub const MAX_DECOMPRESSED_BATCH_SIZE: usize = 256 << 20;
+
+/// Compression codecs that can be applied to a batch before it is sent to other workers.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum BatchCompression {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+}
+
+impl BatchCompression {
+    /// The codecs supported by this node, in order of preference.
+    pub const SUPPORTED: [BatchCompression; 3] = [Self::Zstd, Self::Lz4, Self::None];
+
+    const ZSTD_COMPRESSION_LEVEL: i32 = 1;
+
+    /// Picks the most preferred of our codecs that is also supported by the peer. Falls back to
+    /// no compression, which every peer supports.
+    pub fn negotiate(ours: &[BatchCompression], theirs: &[BatchCompression]) -> Self {
+        ours.iter()
+            .find(|codec| theirs.contains(codec))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, BatchCompressionError> {
+        match self {
+            BatchCompression::None => Ok(bytes.to_vec()),
+            BatchCompression::Zstd => zstd::stream::encode_all(bytes, Self::ZSTD_COMPRESSION_LEVEL)
+                .map_err(|e| BatchCompressionError::Compression(e.to_string())),
+            BatchCompression::Lz4 => {
+                let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+                encoder
+                    .write_all(bytes)
+                    .map_err(|e| BatchCompressionError::Compression(e.to_string()))?;
+                encoder
+                    .finish()
+                    .map_err(|e| BatchCompressionError::Compression(e.to_string()))
+            }
+        }
+    }
+
+    pub fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, BatchCompressionError> {
+        match self {
+            BatchCompression::None => Ok(bytes.to_vec()),
+            BatchCompression::Zstd => {
+                let decoder = zstd::stream::read::Decoder::new(bytes)
+                    .map_err(|e| BatchCompressionError::Decompression(e.to_string()))?;
+                Self::read_bounded(decoder)
+            }
+            BatchCompression::Lz4 => Self::read_bounded(lz4_flex::frame::FrameDecoder::new(bytes)),
+        }
+    }
+
+    // Reads the decompressed bytes, refusing payloads that expand beyond
+    // MAX_DECOMPRESSED_BATCH_SIZE.
+    fn read_bounded(reader: impl Read) -> Result<Vec<u8>, BatchCompressionError> {
+        let mut decompressed = Vec::new();
+        reader
+            .take(MAX_DECOMPRESSED_BATCH_SIZE as u64 + 1)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| BatchCompressionError::Decompression(e.to_string()))?;
+        if decompressed.len() > MAX_DECOMPRESSED_BATCH_SIZE {
+            return Err(BatchCompressionError::TooLarge);
+        }
+        Ok(decompressed)
+    }
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BatchCompressionError {
+    #[error("Failed to compress batch: {0}")]
+    Compression(String),
+    #[error("Failed to decompress batch: {0}")]
+    Decompression(String),
+    #[error("Decompressed batch exceeds {MAX_DECOMPRESSED_BATCH_SIZE} bytes")]
+    TooLarge,
+}
+
+/// Used by workers to send a new batch. Uncompressed batches keep the encoding of the batch
+/// itself, which every deployed worker decodes; the others are serialized and then compressed
+/// with the `compression` negotiated with the peer. The batch digest is computed over the
+/// uncompressed contents, so it does not depend on the codec used to send it.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct WorkerBatchMessage {
     pub batch: Batch,
+    pub compression: BatchCompression,
+}
+
+// The first variants mirror `Batch`, so that an uncompressed message is encoded exactly as the
+// batch it carries. A new version of `Batch` must be added after `Compressed` here, and decoded as
+// an uncompressed batch.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "WorkerBatchMessage")]
+enum WireWorkerBatchMessage<'a> {
+    V1(Cow<'a, BatchV1>),
+    V2(Cow<'a, BatchV2>),
+    V3(Cow<'a, BatchV3>),
+    Compressed {
+        compression: BatchCompression,
+        payload: Vec<u8>,
+    },
+}
+
+impl Serialize for WorkerBatchMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let message = match (&self.batch, self.compression) {
+            (Batch::V1(batch), BatchCompression::None) => {
+                WireWorkerBatchMessage::V1(Cow::Borrowed(batch))
+            }
+            (Batch::V2(batch), BatchCompression::None) => {
+                WireWorkerBatchMessage::V2(Cow::Borrowed(batch))
+            }
+            (Batch::V3(batch), BatchCompression::None) => {
+                WireWorkerBatchMessage::V3(Cow::Borrowed(batch))
+            }
+            (batch, compression) => {
+                let bytes = bcs::to_bytes(batch).map_err(ser::Error::custom)?;
+                let payload = compression.compress(&bytes).map_err(ser::Error::custom)?;
+                WireWorkerBatchMessage::Compressed {
+                    compression,
+                    payload,
+                }
+            }
+        };
+        message.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WorkerBatchMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (batch, compression) = match WireWorkerBatchMessage::deserialize(deserializer)? {
+            WireWorkerBatchMessage::V1(batch) => {
+                (Batch::V1(batch.into_owned()), BatchCompression::None)
+            }
+            WireWorkerBatchMessage::V2(batch) => {
+                (Batch::V2(batch.into_owned()), BatchCompression::None)
+            }
+            WireWorkerBatchMessage::V3(batch) => {
+                (Batch::V3(batch.into_owned()), BatchCompression::None)
+            }
+            WireWorkerBatchMessage::Compressed {
+                compression,
+                payload,
+            } => {
+                let bytes = compression
+                    .decompress(&payload)
+                    .map_err(de::Error::custom)?;
+                (
+                    bcs::from_bytes(&bytes).map_err(de::Error::custom)?,
+                    compression,
+                )
+            }
+        };
+        Ok(Self { batch, compression })
+    }
+}
+
+/// Used by workers to broadcast a large batch as erasure coded chunks. The originator sends a
+/// different chunk to each of the other workers, which forward it to each other. Any
+/// `data_chunks` of the chunks reconstruct the batch. The originator commits to the chunks with
+/// the root of a Merkle tree over them, so that each chunk is checked before it is used.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkerBatchChunkMessage {
+    pub digest: BatchDigest,
+    // The number of chunks reconstructing the batch, and of parity chunks.
+    pub data_chunks: u32,
+    pub parity_chunks: u32,
+    // The size of the serialized batch, before it was padded to be split in chunks.
+    pub batch_size: u64,
+    pub index: u32,
+    pub chunk: Vec<u8>,
+    // The root of the Merkle tree over the chunks of the batch and their encoding, and the
+    // siblings of the chunk on its path to the root, from the leaves up.
+    pub chunks_root: [u8; crypto::DIGEST_LENGTH],
+    pub proof: Vec<[u8; crypto::DIGEST_LENGTH]>,
+    // Whether the chunk was forwarded by another worker, rather than sent by the originator.
+    pub forwarded: bool,
+}
+
+/// Used by workers to send a new batch to a worker in another region, following the topology
+/// hints, which relays it to the workers of its region in `relay_to`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkerRelayBatchMessage {
+    pub message: WorkerBatchMessage,
+    pub relay_to: Vec<NetworkPublicKey>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkerRelayBatchResponse {
+    // The workers which acknowledged the batch relayed to them, in time.
+    pub acknowledged: Vec<NetworkPublicKey>,
+}
+
+/// Used by workers to agree on the codec used to compress the batches they send each other.
+/// Sent when a connection with another worker is established.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NegotiateBatchCompressionRequest {
+    // The codecs supported by the requesting worker, in order of preference.
+    pub supported: Vec<BatchCompression>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NegotiateBatchCompressionResponse {
+    // The codec both workers will use to compress the batches they send each other.
+    pub selected: BatchCompression,
 }
 
 /// Used by primary to ask worker for the request.
@@ -27,12 +243,41 @@ pub struct RequestBatchResponse {
     pub batch: Option<Batch>,
 }
 
+/// The priority of a request for batches. The workers serve the requests for the batches that
+/// block the execution of committed sub dags before the others, e.g. the requests for the batches
+/// of uncommitted certificates.
+#[derive(
+    Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum FetchPriority {
+    #[default]
+    Uncommitted,
+    BlocksExecution,
+}
+
+impl FetchPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FetchPriority::Uncommitted => "uncommitted",
+            FetchPriority::BlocksExecution => "blocks_execution",
+        }
+    }
+}
+
 /// Used by primary to bulk request batches from workers local store.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RequestBatchesRequest {
     pub batch_digests: Vec<BatchDigest>,
 }
 
+/// Used to bulk request batches from workers local store through the `request_batches_v2`
+/// route, which serves the requests in the order of their priority.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestBatchesV2Request {
+    pub batch_digests: Vec<BatchDigest>,
+    pub priority: FetchPriority,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RequestBatchesResponse {
     pub batches: Vec<Batch>,
@@ -41,7 +286,200 @@ pub struct RequestBatchesResponse {
     pub is_size_limit_reached: bool,
 }
 
-pub type TxResponse = tokio::sync::oneshot::Sender<BatchDigest>;
+/// The response of the workers to a `RequestBatchesV2Request`, which reports the batches they
+/// left out and the ones they do not have.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestBatchesV2Response {
+    pub batches: Vec<Batch>,
+    // The requested digests that were left out because the response size limit was reached,
+    // in the order they were requested. The requester should request them again, but this
+    // may not be something that can be trusted from a remote worker.
+    pub remaining_digests: Vec<BatchDigest>,
+    // The requested digests of batches missing from the worker's store, in the order they were
+    // requested. Unlike the remaining ones, they should be requested from other workers. The
+    // digests left once the size limit is reached are not read, so they are all remaining.
+    pub missing_digests: Vec<BatchDigest>,
+}
+
+impl RequestBatchesV2Response {
+    pub fn is_size_limit_reached(&self) -> bool {
+        !self.remaining_digests.is_empty()
+    }
+
+    /// Converts the response of the workers serving only `request_batches`, to the request of
+    /// the given digests. The digests of the batches not returned are all remaining once the
+    /// size limit is reached, as these workers do not report the batches they do not have.
+    pub fn from_legacy(response: RequestBatchesResponse, requested: &[BatchDigest]) -> Self {
+        let remaining_digests = if response.is_size_limit_reached {
+            let returned: HashSet<_> = response.batches.iter().map(|b| b.digest()).collect();
+            requested
+                .iter()
+                .filter(|digest| !returned.contains(digest))
+                .copied()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            batches: response.batches,
+            remaining_digests,
+            missing_digests: Vec::new(),
+        }
+    }
+}
+
+/// Used by primary to open a chunked stream of batches from a worker's local store. The worker
+/// splits the requested digests into chunks that each fit in a single response, returns the
+/// first chunk right away and serves the remaining ones via `RequestBatchesChunkRequest`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OpenBatchesStreamRequest {
+    pub batch_digests: Vec<BatchDigest>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OpenBatchesStreamResponse {
+    // Identifies the stream on the worker. Only meaningful when num_chunks > 1.
+    pub stream_id: u64,
+    // The total number of chunks in the stream, including the first one.
+    pub num_chunks: u32,
+    // The batches of the first chunk of the stream.
+    pub first_chunk: Vec<Batch>,
+}
+
+/// Used by primary to pull a chunk of a stream opened with `OpenBatchesStreamRequest`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestBatchesChunkRequest {
+    pub stream_id: u64,
+    pub chunk_index: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestBatchesChunkResponse {
+    pub batches: Vec<Batch>,
+}
+
+/// A bloom filter of batch digests, used by the workers to summarize the batches they hold
+/// without exchanging the full list of digests. The filter never misses an inserted digest,
+/// but may report digests that were never inserted. The seed changes which digests are
+/// falsely reported, so that successive filters of the same digests miss different batches.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BatchDigestFilter {
+    seed: u64,
+    num_hashes: u32,
+    bits: Vec<u64>,
+}
+
+impl BatchDigestFilter {
+    /// The number of bits per expected digest, for a false positive rate of about 1%.
+    const BITS_PER_DIGEST: usize = 10;
+    const NUM_HASHES: u32 = 7;
+    /// Bounds the filters accepted from other workers.
+    pub const MAX_NUM_HASHES: u32 = 32;
+    pub const MAX_NUM_WORDS: usize = 1 << 20;
+
+    /// Creates an empty filter sized for the given number of digests.
+    pub fn new(expected_digests: usize, seed: u64) -> Self {
+        let num_words =
+            (expected_digests * Self::BITS_PER_DIGEST / 64 + 1).min(Self::MAX_NUM_WORDS);
+        Self {
+            seed,
+            num_hashes: Self::NUM_HASHES,
+            bits: vec![0; num_words],
+        }
+    }
+
+    pub fn insert(&mut self, digest: &BatchDigest) {
+        for index in self.bit_indices(digest) {
+            self.bits[index / 64] |= 1 << (index % 64);
+        }
+    }
+
+    /// Returns true if the digest may have been inserted, and false if it was definitely not.
+    pub fn contains(&self, digest: &BatchDigest) -> bool {
+        self.bit_indices(digest)
+            .all(|index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+    }
+
+    /// Checks that a filter received from another worker is within bounds.
+    pub fn is_well_formed(&self) -> bool {
+        !self.bits.is_empty()
+            && self.bits.len() <= Self::MAX_NUM_WORDS
+            && (1..=Self::MAX_NUM_HASHES).contains(&self.num_hashes)
+    }
+
+    // The digests are uniformly distributed already, so the bit indices are derived from two
+    // of their words by double hashing, after mixing in the seed.
+    fn bit_indices(&self, digest: &BatchDigest) -> impl Iterator<Item = usize> {
+        let word = |i: usize| u64::from_le_bytes(digest.0[i * 8..(i + 1) * 8].try_into().unwrap());
+        let h1 = mix(word(0) ^ self.seed);
+        let h2 = mix(word(1) ^ self.seed) | 1;
+        let num_bits = (self.bits.len() * 64) as u64;
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+}
+
+// The splitmix64 finalizer.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Used by a worker to find out which of the batches recently received by another worker it
+/// is missing. The filter summarizes the batches the requesting worker already holds.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestBatchesDiffRequest {
+    pub filter: BatchDigestFilter,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestBatchesDiffResponse {
+    /// The digests of the recent batches of the responding worker that are not in the filter.
+    pub missing_digests: Vec<BatchDigest>,
+}
+
+/// Used by a standby worker to replicate the batches stored by the worker it stands by for,
+/// since the given position of the worker's replication log.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestReplicatedBatchesRequest {
+    // Identifies the replication log of the worker, which starts anew when the worker restarts.
+    pub log_id: u64,
+    // The position of the first batch to replicate.
+    pub position: u64,
+    pub max_batches: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestReplicatedBatchesResponse {
+    pub log_id: u64,
+    // The position to request next.
+    pub next_position: u64,
+    pub batches: Vec<Batch>,
+    // Whether batches stored since the requested position were left out, because the log
+    // started anew or no longer holds them.
+    pub gap: bool,
+}
+
+/// Reasons for the worker to reject a transaction instead of including it in a batch.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransactionRejection {
+    #[error("Transaction is a duplicate of one submitted within the last {0:?}")]
+    Duplicate(std::time::Duration),
+    #[error("Transaction is part of a batch rejected by the validator: {0}")]
+    InvalidBatch(String),
+    #[error("Transaction could not be durably logged: {0}")]
+    LogFailed(String),
+    #[error("Transaction expired before it was batched")]
+    Expired,
+    #[error("Transaction of {0} bytes does not fit in a batch of at most {1} bytes")]
+    TooLarge(usize, usize),
+    #[error("Transaction was not included before the end of epoch {0}")]
+    EpochEnded(u64),
+}
+
+pub type TxResponse = tokio::sync::oneshot::Sender<Result<BatchDigest, TransactionRejection>>;
 pub type PrimaryResponse = Option<tokio::sync::oneshot::Sender<()>>;
 
 #[derive(Debug, Error)]
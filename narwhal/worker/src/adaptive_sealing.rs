@@ -0,0 +1,139 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use axum::{extract::Extension, http::StatusCode, routing::get, Json, Router};
+use config::AdaptiveSealingParameters;
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::watch, time::Instant};
+
+#[cfg(test)]
+#[path = "tests/adaptive_sealing_tests.rs"]
+pub mod adaptive_sealing_tests;
+
+/// The size and delay at which the batch maker seals its batches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SealingThresholds {
+    pub batch_size_limit: usize,
+    pub max_batch_delay: Duration,
+}
+
+/// Derives the sealing thresholds of the batch maker from a moving average of its ingress rate.
+/// The parameters are read from a watch channel, so that they can be tuned at runtime.
+pub struct AdaptiveSealing {
+    /// The thresholds under normal load, or at all times if the adaptive sealing is disabled.
+    base: SealingThresholds,
+    rx_parameters: watch::Receiver<AdaptiveSealingParameters>,
+    /// The bytes received, exponentially decayed by their age.
+    decayed_bytes: f64,
+    last_update: Instant,
+}
+
+impl AdaptiveSealing {
+    pub fn new(
+        batch_size_limit: usize,
+        max_batch_delay: Duration,
+        rx_parameters: watch::Receiver<AdaptiveSealingParameters>,
+    ) -> Self {
+        Self {
+            base: SealingThresholds {
+                batch_size_limit,
+                max_batch_delay,
+            },
+            rx_parameters,
+            decayed_bytes: 0.0,
+            last_update: Instant::now(),
+        }
+    }
+
+    /// Sets the maximum delay of the batches under normal load.
+    pub fn set_max_batch_delay(&mut self, max_batch_delay: Duration) {
+        self.base.max_batch_delay = max_batch_delay;
+    }
+
+    fn half_life(&self) -> f64 {
+        self.rx_parameters
+            .borrow()
+            .rate_half_life
+            .as_secs_f64()
+            .max(f64::EPSILON)
+    }
+
+    fn decay(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_update)
+            .as_secs_f64();
+        self.decayed_bytes *= (-elapsed / self.half_life()).exp2();
+        self.last_update = now;
+    }
+
+    /// Records the bytes of a transaction received by the batch maker.
+    pub fn record(&mut self, bytes: usize) {
+        self.decay(Instant::now());
+        self.decayed_bytes += bytes as f64;
+    }
+
+    /// The moving average of the ingress rate. Denominated in bytes/s.
+    pub fn ingress_rate(&mut self) -> f64 {
+        self.decay(Instant::now());
+        // The decayed sum of a constant rate r converges to r * half_life / ln(2).
+        self.decayed_bytes * std::f64::consts::LN_2 / self.half_life()
+    }
+
+    /// The thresholds to seal the batches at, given the current load.
+    pub fn thresholds(&mut self) -> SealingThresholds {
+        let parameters = self.rx_parameters.borrow().clone();
+        if !parameters.enabled {
+            return self.base;
+        }
+        let rate = self.ingress_rate();
+        if rate < parameters.low_load_bytes_per_second as f64 {
+            return SealingThresholds {
+                batch_size_limit: self.base.batch_size_limit,
+                max_batch_delay: parameters.min_batch_delay.min(self.base.max_batch_delay),
+            };
+        }
+        if rate > parameters.high_load_bytes_per_second as f64 {
+            return SealingThresholds {
+                batch_size_limit: parameters.max_batch_size.max(self.base.batch_size_limit),
+                max_batch_delay: self.base.max_batch_delay,
+            };
+        }
+        self.base
+    }
+}
+
+/// Returns the routes reporting and tuning the adaptive sealing parameters, to be served by the
+/// admin server.
+pub fn routes(tx_parameters: Arc<watch::Sender<AdaptiveSealingParameters>>) -> Router {
+    Router::new()
+        .route(
+            "/adaptive_sealing",
+            get(get_parameters).post(set_parameters),
+        )
+        .layer(Extension(tx_parameters))
+}
+
+async fn get_parameters(
+    Extension(tx_parameters): Extension<Arc<watch::Sender<AdaptiveSealingParameters>>>,
+) -> Json<AdaptiveSealingParameters> {
+    Json(tx_parameters.borrow().clone())
+}
+
+async fn set_parameters(
+    Extension(tx_parameters): Extension<Arc<watch::Sender<AdaptiveSealingParameters>>>,
+    Json(parameters): Json<AdaptiveSealingParameters>,
+) -> Result<Json<AdaptiveSealingParameters>, (StatusCode, String)> {
+    if parameters.low_load_bytes_per_second > parameters.high_load_bytes_per_second {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The low load threshold must not exceed the high load threshold".to_string(),
+        ));
+    }
+    if parameters.rate_half_life.is_zero() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The half life of the ingress rate must not be zero".to_string(),
+        ));
+    }
+    tx_parameters.send_replace(parameters.clone());
+    Ok(Json(parameters))
+}
@@ -0,0 +1,93 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::metrics::WorkerMetrics;
+use config::AdmissionControlParameters;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[cfg(test)]
+#[path = "tests/admission_control_tests.rs"]
+pub mod admission_control_tests;
+
+/// Tracks the load of the worker, so that the transactions submitted while it is overloaded are
+/// rejected with a retry delay instead of being queued without bound.
+pub struct AdmissionController {
+    parameters: AdmissionControlParameters,
+    /// The bytes of the admitted transactions not yet included in a batch acknowledged by a
+    /// quorum.
+    pending_batch_bytes: AtomicUsize,
+    /// The number of sealed batches waiting for a quorum, as reported by the batch maker.
+    inflight_quorum_waits: AtomicUsize,
+    metrics: Arc<WorkerMetrics>,
+}
+
+impl AdmissionController {
+    pub fn new(parameters: AdmissionControlParameters, metrics: Arc<WorkerMetrics>) -> Arc<Self> {
+        Arc::new(Self {
+            parameters,
+            pending_batch_bytes: AtomicUsize::new(0),
+            inflight_quorum_waits: AtomicUsize::new(0),
+            metrics,
+        })
+    }
+
+    /// Admits a transaction of the given size, or returns the delay after which the client
+    /// should retry if the worker is overloaded. The transaction is accounted for until the
+    /// returned permit is dropped.
+    pub fn try_admit(self: &Arc<Self>, size: usize) -> Result<AdmissionPermit, Duration> {
+        self.admit(size).map_err(|retry_after| {
+            self.metrics.overloaded_transactions_rejected.inc();
+            retry_after
+        })
+    }
+
+    /// Like `try_admit`, but a transaction which is not admitted is not counted as rejected,
+    /// e.g. when it is spilled to disk instead.
+    pub fn admit(self: &Arc<Self>, size: usize) -> Result<AdmissionPermit, Duration> {
+        if self.inflight_quorum_waits.load(Ordering::Relaxed)
+            >= self.parameters.max_inflight_quorum_waits
+        {
+            return Err(self.parameters.retry_after);
+        }
+        let pending = self.pending_batch_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        // A single transaction is always admitted when nothing else is pending, so that
+        // transactions larger than the limit are not rejected forever.
+        if pending > self.parameters.max_pending_batch_bytes && pending != size {
+            self.pending_batch_bytes.fetch_sub(size, Ordering::Relaxed);
+            return Err(self.parameters.retry_after);
+        }
+        self.metrics.pending_batch_bytes.set(pending as i64);
+        Ok(AdmissionPermit {
+            controller: self.clone(),
+            size,
+        })
+    }
+
+    /// Records the number of sealed batches currently waiting for a quorum.
+    pub fn set_inflight_quorum_waits(&self, inflight: usize) {
+        self.inflight_quorum_waits
+            .store(inflight, Ordering::Relaxed);
+    }
+
+    fn release(&self, size: usize) {
+        let pending = self.pending_batch_bytes.fetch_sub(size, Ordering::Relaxed) - size;
+        self.metrics.pending_batch_bytes.set(pending as i64);
+    }
+}
+
+/// Accounts for an admitted transaction until it is dropped.
+pub struct AdmissionPermit {
+    controller: Arc<AdmissionController>,
+    size: usize,
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        self.controller.release(self.size);
+    }
+}
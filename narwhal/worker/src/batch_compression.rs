@@ -0,0 +1,131 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use anemo::{types::PeerEvent, PeerId};
+use mysten_metrics::{spawn_logged_monitored_task, spawn_monitored_task};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use types::{
+    BatchCompression, ConditionalBroadcastReceiver, NegotiateBatchCompressionRequest,
+    WorkerToWorkerClient,
+};
+
+/// The codec negotiated with each of the other workers. Peers we have not negotiated with yet
+/// are sent uncompressed batches.
+#[derive(Clone, Default)]
+pub struct PeerBatchCompressions {
+    inner: Arc<RwLock<HashMap<PeerId, BatchCompression>>>,
+}
+
+impl PeerBatchCompressions {
+    pub fn get(&self, peer: &PeerId) -> BatchCompression {
+        self.inner
+            .read()
+            .unwrap()
+            .get(peer)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set(&self, peer: PeerId, compression: BatchCompression) {
+        self.inner.write().unwrap().insert(peer, compression);
+    }
+
+    pub fn remove(&self, peer: &PeerId) {
+        self.inner.write().unwrap().remove(peer);
+    }
+}
+
+/// Negotiates the batch compression codec with the other workers every time a connection with
+/// one of them is established.
+pub struct BatchCompressionNegotiator {
+    network: anemo::NetworkRef,
+    // The peer ids of the other workers. Other peers (e.g. our primary) are ignored.
+    other_workers: HashSet<PeerId>,
+    compressions: PeerBatchCompressions,
+    rx_shutdown: ConditionalBroadcastReceiver,
+}
+
+impl BatchCompressionNegotiator {
+    #[must_use]
+    pub fn spawn(
+        network: anemo::NetworkRef,
+        other_workers: HashSet<PeerId>,
+        compressions: PeerBatchCompressions,
+        rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        spawn_logged_monitored_task!(
+            Self {
+                network,
+                other_workers,
+                compressions,
+                rx_shutdown,
+            }
+            .run(),
+            "BatchCompressionNegotiatorTask"
+        )
+    }
+
+    async fn run(mut self) {
+        let (mut subscriber, connected_peers) = {
+            if let Some(network) = self.network.upgrade() {
+                let Ok((subscriber, connected_peers)) = network.subscribe() else {
+                    return;
+                };
+
+                (subscriber, connected_peers)
+            } else {
+                return;
+            }
+        };
+
+        for peer_id in connected_peers {
+            self.negotiate(peer_id);
+        }
+
+        loop {
+            tokio::select! {
+                event = subscriber.recv() => match event {
+                    Ok(PeerEvent::NewPeer(peer_id)) => self.negotiate(peer_id),
+                    Ok(PeerEvent::LostPeer(peer_id, _)) => self.compressions.remove(&peer_id),
+                    Err(_) => return,
+                },
+
+                _ = self.rx_shutdown.receiver.recv() => {
+                    return
+                }
+            }
+        }
+    }
+
+    fn negotiate(&self, peer_id: PeerId) {
+        if !self.other_workers.contains(&peer_id) {
+            return;
+        }
+        let Some(peer) = self.network.upgrade().and_then(|network| network.peer(peer_id)) else {
+            return;
+        };
+        let compressions = self.compressions.clone();
+        spawn_monitored_task!(async move {
+            let request = NegotiateBatchCompressionRequest {
+                supported: BatchCompression::SUPPORTED.to_vec(),
+            };
+            match WorkerToWorkerClient::new(peer)
+                .negotiate_batch_compression(request)
+                .await
+            {
+                Ok(response) => {
+                    let selected = response.into_body().selected;
+                    debug!("Negotiated batch compression {selected:?} with worker {peer_id}");
+                    compressions.set(peer_id, selected);
+                }
+                Err(e) => {
+                    warn!("Failed to negotiate batch compression with worker {peer_id}: {e:?}");
+                }
+            }
+        });
+    }
+}
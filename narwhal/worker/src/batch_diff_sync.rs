@@ -0,0 +1,235 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::{metrics::WorkerMetrics, TransactionValidator};
+use anemo::PeerId;
+use config::{BatchDiffSyncParameters, WorkerId};
+use fastcrypto::hash::Hash;
+use mysten_metrics::spawn_logged_monitored_task;
+use network::request_budget::is_rate_limited;
+use rand::seq::SliceRandom;
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use store::{rocks::DBMap, Map};
+use tokio::{
+    task::JoinHandle,
+    time::{interval, Instant, MissedTickBehavior},
+};
+use tracing::{debug, warn};
+use types::{
+    metered_channel::Sender, Batch, BatchDigest, BatchDigestFilter, ConditionalBroadcastReceiver,
+    FetchPriority, RequestBatchesDiffRequest, RequestBatchesV2Request, WorkerOthersBatchMessage,
+    WorkerToWorkerClient,
+};
+
+#[cfg(test)]
+#[path = "tests/batch_diff_sync_tests.rs"]
+pub mod batch_diff_sync_tests;
+
+/// Indexes the digests of the batches stored by the worker within the window, in the order
+/// they were stored, so that the batch diffs do not scan the batch store. The index starts
+/// anew with the worker.
+#[derive(Clone)]
+pub struct RecentBatches {
+    window: Duration,
+    digests: Arc<Mutex<VecDeque<(Instant, BatchDigest)>>>,
+}
+
+impl RecentBatches {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            digests: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    fn expire(&self, digests: &mut VecDeque<(Instant, BatchDigest)>) {
+        while let Some((stored_at, _)) = digests.front() {
+            if stored_at.elapsed() < self.window {
+                break;
+            }
+            digests.pop_front();
+        }
+    }
+
+    pub fn append(&self, digest: BatchDigest) {
+        let mut digests = self.digests.lock().unwrap();
+        digests.push_back((Instant::now(), digest));
+        self.expire(&mut digests);
+    }
+
+    /// Returns the digests of the batches stored within the window.
+    pub fn digests(&self) -> Vec<BatchDigest> {
+        let mut digests = self.digests.lock().unwrap();
+        self.expire(&mut digests);
+        digests.iter().map(|(_, digest)| *digest).collect()
+    }
+}
+
+/// Periodically compares the recent batches of this worker with those of a random peer worker,
+/// and fetches the batches it is missing. This lets a worker that was briefly partitioned catch
+/// up without waiting for its primary to request each missing batch.
+pub struct BatchDiffSynchronizer<V> {
+    // Our worker's id.
+    id: WorkerId,
+    network: anemo::NetworkRef,
+    // The peer ids of the other workers with our id.
+    other_workers: Vec<PeerId>,
+    store: DBMap<BatchDigest, Batch>,
+    recent_batches: RecentBatches,
+    validator: V,
+    // Notifies our primary of the fetched batches.
+    tx_others_batch: Sender<WorkerOthersBatchMessage>,
+    parameters: BatchDiffSyncParameters,
+    metrics: Arc<WorkerMetrics>,
+}
+
+impl<V: TransactionValidator> BatchDiffSynchronizer<V> {
+    #[must_use]
+    pub fn spawn(
+        id: WorkerId,
+        network: anemo::NetworkRef,
+        other_workers: HashSet<PeerId>,
+        store: DBMap<BatchDigest, Batch>,
+        recent_batches: RecentBatches,
+        validator: V,
+        tx_others_batch: Sender<WorkerOthersBatchMessage>,
+        parameters: BatchDiffSyncParameters,
+        metrics: Arc<WorkerMetrics>,
+        rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        spawn_logged_monitored_task!(
+            Self {
+                id,
+                network,
+                other_workers: other_workers.into_iter().collect(),
+                store,
+                recent_batches,
+                validator,
+                tx_others_batch,
+                parameters,
+                metrics,
+            }
+            .run(rx_shutdown),
+            "BatchDiffSynchronizerTask"
+        )
+    }
+
+    async fn run(self, mut rx_shutdown: ConditionalBroadcastReceiver) {
+        // A zero interval disables the sync, and tokio panics on it.
+        let enabled = !self.parameters.interval.is_zero();
+        let mut ticker = interval(self.parameters.interval.max(Duration::from_millis(1)));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick(), if enabled => {
+                    tokio::select! {
+                        result = self.sync_with_random_peer() => {
+                            match result {
+                                // The peer is over budget, the next round is soon enough.
+                                Err(e) if is_rate_limited(&e) => {
+                                    debug!("Batch diff sync round is over budget: {e:?}")
+                                }
+                                Err(e) => warn!("Batch diff sync round failed: {e:?}"),
+                                Ok(_) => {}
+                            }
+                        }
+                        _ = rx_shutdown.receiver.recv() => return,
+                    }
+                }
+
+                _ = rx_shutdown.receiver.recv() => {
+                    return
+                }
+            }
+        }
+    }
+
+    /// Fetches the recent batches of a random connected peer that are missing from our store.
+    /// Returns the number of fetched batches.
+    async fn sync_with_random_peer(&self) -> Result<usize, anemo::rpc::Status> {
+        let Some(network) = self.network.upgrade() else {
+            return Ok(0);
+        };
+        let peers: Vec<_> = self
+            .other_workers
+            .iter()
+            .filter_map(|peer_id| network.peer(*peer_id))
+            .collect();
+        let Some(peer) = peers.choose(&mut rand::thread_rng()).cloned() else {
+            return Ok(0);
+        };
+        let peer_id = peer.peer_id();
+        let mut client = WorkerToWorkerClient::new(peer);
+
+        let ours = self.recent_batches.digests();
+        let mut filter = BatchDigestFilter::new(ours.len(), rand::random());
+        for digest in &ours {
+            filter.insert(digest);
+        }
+        let missing_digests = client
+            .request_batches_diff(RequestBatchesDiffRequest { filter })
+            .await?
+            .into_body()
+            .missing_digests;
+
+        // The peer may report batches we already have, e.g. received since the filter was built.
+        let mut missing = HashSet::new();
+        for digest in missing_digests
+            .into_iter()
+            .take(self.parameters.max_missing_batches)
+        {
+            let stored = self.store.contains_key(&digest).map_err(|e| {
+                anemo::rpc::Status::internal(format!("failed to read from batch store: {e:?}"))
+            })?;
+            if !stored {
+                missing.insert(digest);
+            }
+        }
+        if missing.is_empty() {
+            return Ok(0);
+        }
+
+        // The response is size limited, the batches left out are fetched in the next rounds.
+        let batches = client
+            .request_batches_v2(RequestBatchesV2Request {
+                batch_digests: missing.iter().copied().collect(),
+                priority: FetchPriority::Uncommitted,
+            })
+            .await?
+            .into_body()
+            .batches;
+        let mut fetched = 0;
+        for batch in batches {
+            let digest = batch.digest();
+            if !missing.remove(&digest) {
+                warn!("Worker {peer_id} sent batch {digest} that was not requested");
+                continue;
+            }
+            if let Err(err) = self.validator.validate_batch(&batch).await {
+                warn!("Worker {peer_id} sent invalid batch {digest}: {err}");
+                continue;
+            }
+            self.store.insert(&digest, &batch).map_err(|e| {
+                anemo::rpc::Status::internal(format!("failed to write to batch store: {e:?}"))
+            })?;
+            self.tx_others_batch
+                .send(WorkerOthersBatchMessage {
+                    digest,
+                    worker_id: self.id,
+                })
+                .await
+                .map_err(|e| anemo::rpc::Status::internal(e.to_string()))?;
+            fetched += 1;
+        }
+
+        self.metrics
+            .batch_diff_sync_fetched_batches
+            .inc_by(fetched as u64);
+        debug!("Fetched {fetched} missing batches from worker {peer_id}");
+        Ok(fetched)
+    }
+}
@@ -1,92 +1,247 @@
 // Copyright (c) 2021, Facebook, Inc. and its affiliates
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use crate::metrics::WorkerMetrics;
+use crate::{
+    adaptive_sealing::{AdaptiveSealing, SealingThresholds},
+    admission_control::AdmissionController,
+    drain::{DrainReceiver, HandedOffTransaction, TransactionHandoff, WorkerDrain},
+    lanes::{LaneReceivers, LaneScheduler, Lanes},
+    metrics::WorkerMetrics,
+    transaction_log::TransactionLog,
+    transaction_status::{TransactionStage, TransactionStatusTracker},
+    TransactionValidator,
+};
 #[cfg(feature = "trace_transaction")]
 use byteorder::{BigEndian, ReadBytesExt};
-use fastcrypto::hash::Hash;
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::{Digest, Hash, HashFunction};
 use futures::stream::FuturesUnordered;
+use lru::LruCache;
 use store::{rocks::DBMap, Map};
 
-use config::WorkerId;
-use tracing::{debug, error};
+use config::{
+    AdaptiveSealingParameters, BatchLimits, BatchVersion, Epoch, LaneSchedulingPolicy, Parameters,
+    WorkerId,
+};
+use telemetry_subscribers::CORRELATION_TARGET;
+use tracing::{debug, error, info};
 
 #[cfg(feature = "benchmark")]
 use std::convert::TryInto;
 
 use futures::{Future, StreamExt};
 
+use mysten_common::memory_budget::{self, MemoryAccount, MemoryReservation};
 use mysten_metrics::spawn_logged_monitored_task;
-use std::sync::Arc;
+use std::{num::NonZeroUsize, sync::Arc};
 use tokio::{
+    sync::{oneshot, watch},
     task::JoinHandle,
     time::{sleep, Duration, Instant},
 };
 use types::{
-    error::DagError,
-    metered_channel::{Receiver, Sender},
-    now, Batch, BatchAPI, BatchDigest, ConditionalBroadcastReceiver, PrimaryResponse, Transaction,
+    error::DagError, metered_channel::Sender, now, Batch, BatchAPI, BatchDigest,
+    ConditionalBroadcastReceiver, PrimaryResponse, PriorityLane, Transaction, TransactionRejection,
     TxResponse, WorkerOurBatchMessage,
 };
 
 // The number of batches to store / transmit in parallel.
 pub const MAX_PARALLEL_BATCH: usize = 100;
 
+// The stages of the transactions' pipeline timed by the batch maker: from the transaction being
+// received to its batch being sealed, and from then to the batch being acked by a quorum.
+const BATCH_SEALED_STAGE: &str = "batch_sealed";
+const QUORUM_REACHED_STAGE: &str = "quorum_reached";
+
+/// The account of the memory budget holding the transactions of the batches, from their
+/// reception until their batch reaches a quorum.
+const MEMORY_ACCOUNT: &str = "worker-batches";
+
 #[cfg(test)]
 #[path = "tests/batch_maker_tests.rs"]
 pub mod batch_maker_tests;
 
-/// Assemble clients transactions into batches.
-pub struct BatchMaker {
+/// Remembers the digests of the recently received transactions, to reject the ones that are
+/// submitted again within the dedup window.
+struct TransactionDedupCache {
+    window: Duration,
+    received_at: LruCache<Digest<{ crypto::DIGEST_LENGTH }>, Instant>,
+}
+
+impl TransactionDedupCache {
+    fn new(window: Duration, capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity.max(1)).unwrap();
+        Self {
+            window,
+            received_at: LruCache::new(capacity),
+        }
+    }
+
+    /// Records the transaction as received and returns true if it was already received within
+    /// the dedup window.
+    fn check_and_insert(&mut self, transaction: &Transaction) -> bool {
+        let digest = crypto::DefaultHashFunction::digest(transaction);
+        let now = Instant::now();
+        match self.received_at.get(&digest) {
+            Some(received_at) if now.duration_since(*received_at) < self.window => true,
+            _ => {
+                self.received_at.put(digest, now);
+                false
+            }
+        }
+    }
+}
+
+/// The batch being assembled for a priority lane.
+struct LaneBatch {
+    lane: PriorityLane,
+    batch: Batch,
+    responses: Vec<TxResponse>,
+    /// The time each transaction of the batch was received at.
+    received_at: Vec<Instant>,
+    /// The total size (in bytes) of the transactions of the batch.
+    size: usize,
+    /// The timestamp of the batch creation.
+    /// Average resident time in the batch would be ~ (batch seal time - creation time) / 2
+    started_at: Instant,
+    /// The memory of the transactions of the batch, reserved from the budget until the batch
+    /// reaches a quorum.
+    memory: MemoryReservation,
+}
+
+impl LaneBatch {
+    fn new(
+        lane: PriorityLane,
+        worker_id: WorkerId,
+        epoch: Epoch,
+        version: BatchVersion,
+        memory: MemoryReservation,
+    ) -> Self {
+        Self {
+            lane,
+            batch: Batch::new_versioned(Vec::new(), lane, worker_id, epoch, version),
+            responses: Vec::new(),
+            received_at: Vec::new(),
+            size: 0,
+            started_at: Instant::now(),
+            memory,
+        }
+    }
+}
+
+/// Assemble clients transactions into batches. The transactions of each priority lane are
+/// assembled into separate batches.
+pub struct BatchMaker<V> {
     // Our worker's id.
     id: WorkerId,
-    /// The preferred batch size (in bytes).
-    batch_size_limit: usize,
-    /// The maximum delay after which to seal the batch.
-    max_batch_delay: Duration,
+    // The current epoch.
+    epoch: Epoch,
+    /// Decides the size (in bytes) and the delay at which to seal the batches.
+    sealing: AdaptiveSealing,
+    /// The parameters of the worker, from which the maximum batch delay is read, so that it can
+    /// be tuned at runtime.
+    rx_parameters: watch::Receiver<Parameters>,
     /// Receiver for shutdown.
     rx_shutdown: ConditionalBroadcastReceiver,
-    /// Channel to receive transactions from the network.
-    rx_batch_maker: Receiver<(Transaction, TxResponse)>,
+    /// Channels to receive transactions from the network, one per priority lane.
+    rx_batch_maker: LaneReceivers,
+    /// Decides which lane the next transaction is taken from.
+    scheduler: LaneScheduler,
     /// Output channel to deliver sealed batches to the `QuorumWaiter`.
     tx_quorum_waiter: Sender<(Batch, tokio::sync::oneshot::Sender<()>)>,
     /// Metrics handler
     node_metrics: Arc<WorkerMetrics>,
-    /// The timestamp of the batch creation.
-    /// Average resident time in the batch would be ~ (batch seal time - creation time) / 2
-    batch_start_timestamp: Instant,
     /// The batch store to store our own batches.
     store: DBMap<BatchDigest, Batch>,
     // Output channel to send out batches' digests.
     tx_our_batch: Sender<(WorkerOurBatchMessage, PrimaryResponse)>,
+    /// The digests of the recently received transactions.
+    dedup_cache: TransactionDedupCache,
+    /// The version of the batches to create.
+    batch_version: BatchVersion,
+    /// The limits on the transactions of the batches, when decided by the protocol.
+    batch_limits: Option<BatchLimits>,
+    /// Informed of the number of batches waiting for a quorum.
+    admission_controller: Arc<AdmissionController>,
+    /// Validates the batches before they are sealed.
+    validator: V,
+    /// Notified when the worker should drain at the end of the epoch.
+    rx_draining: DrainReceiver,
+    /// Notified once the batch maker has drained.
+    tx_drained: Option<oneshot::Sender<()>>,
+    /// Receives the transactions not included when draining.
+    handoff: TransactionHandoff,
+    /// How long the batches in flight are waited for when draining.
+    drain_timeout: Duration,
+    /// Set once the drain timeout has expired, so the batches in flight give up on being
+    /// included.
+    tx_drain_expired: watch::Sender<bool>,
+    /// Logs the transactions until they are included in a batch.
+    transaction_log: TransactionLog,
+    /// Tracks the progress of the batched transactions for their clients.
+    transaction_status: TransactionStatusTracker,
+    /// The account of the batches with the memory budget of the node.
+    memory: MemoryAccount,
 }
 
-impl BatchMaker {
+impl<V: TransactionValidator> BatchMaker<V> {
     #[must_use]
     pub fn spawn(
         id: WorkerId,
+        epoch: Epoch,
         batch_size_limit: usize,
-        max_batch_delay: Duration,
+        rx_parameters: watch::Receiver<Parameters>,
         rx_shutdown: ConditionalBroadcastReceiver,
-        rx_batch_maker: Receiver<(Transaction, TxResponse)>,
+        rx_batch_maker: LaneReceivers,
         tx_quorum_waiter: Sender<(Batch, tokio::sync::oneshot::Sender<()>)>,
         node_metrics: Arc<WorkerMetrics>,
         store: DBMap<BatchDigest, Batch>,
         tx_our_batch: Sender<(WorkerOurBatchMessage, PrimaryResponse)>,
+        tx_dedup_window: Duration,
+        tx_dedup_cache_size: usize,
+        lane_scheduling_policy: LaneSchedulingPolicy,
+        batch_version: BatchVersion,
+        batch_limits: Option<BatchLimits>,
+        admission_controller: Arc<AdmissionController>,
+        validator: V,
+        drain: WorkerDrain,
+        drain_timeout: Duration,
+        transaction_log: TransactionLog,
+        rx_sealing_parameters: watch::Receiver<AdaptiveSealingParameters>,
+        transaction_status: TransactionStatusTracker,
     ) -> JoinHandle<()> {
+        let max_batch_delay = rx_parameters.borrow().max_batch_delay;
         spawn_logged_monitored_task!(
             async move {
                 Self {
                     id,
-                    batch_size_limit,
-                    max_batch_delay,
+                    epoch,
+                    sealing: AdaptiveSealing::new(
+                        batch_size_limit,
+                        max_batch_delay,
+                        rx_sealing_parameters,
+                    ),
+                    rx_parameters,
                     rx_shutdown,
                     rx_batch_maker,
+                    scheduler: LaneScheduler::new(lane_scheduling_policy),
                     tx_quorum_waiter,
-                    batch_start_timestamp: Instant::now(),
                     node_metrics,
                     store,
                     tx_our_batch,
+                    dedup_cache: TransactionDedupCache::new(tx_dedup_window, tx_dedup_cache_size),
+                    batch_version,
+                    batch_limits,
+                    admission_controller,
+                    validator,
+                    rx_draining: drain.rx_draining,
+                    tx_drained: Some(drain.tx_drained),
+                    handoff: drain.handoff,
+                    drain_timeout,
+                    tx_drain_expired: watch::channel(false).0,
+                    transaction_log,
+                    transaction_status,
+                    memory: memory_budget::global().register(MEMORY_ACCOUNT),
                 }
                 .run()
                 .await;
@@ -95,67 +250,137 @@ impl BatchMaker {
         )
     }
 
+    fn new_lane_batch(&self, lane: PriorityLane) -> LaneBatch {
+        LaneBatch::new(
+            lane,
+            self.id,
+            self.epoch,
+            self.batch_version,
+            self.memory.reserve(0),
+        )
+    }
+
     /// Main loop receiving incoming transactions and creating batches.
     async fn run(&mut self) {
-        let timer = sleep(self.max_batch_delay);
+        let timer = sleep(self.thresholds().max_batch_delay);
         tokio::pin!(timer);
 
-        let mut current_batch = Batch::default();
-        let mut current_responses = Vec::new();
-        let mut current_batch_size = 0;
+        let mut current_batches = Lanes::new(|lane| self.new_lane_batch(lane));
 
         let mut batch_pipeline = FuturesUnordered::new();
 
+        // The transactions logged but not included before the worker stopped are batched
+        // first. Their submitters are gone, so nobody waits for their responses.
+        let recovered = self.transaction_log.recover();
+        if !recovered.is_empty() {
+            info!(
+                "Worker {} recovered {} logged transactions",
+                self.id,
+                recovered.len()
+            );
+            self.node_metrics
+                .recovered_transactions
+                .inc_by(recovered.len() as u64);
+        }
+        for (lane, transaction) in recovered {
+            let (response_sender, _) = oneshot::channel();
+            if let Some(sealed) =
+                self.add_transaction(&mut current_batches, lane, transaction, response_sender)
+            {
+                if let Some(seal) = self.seal(false, sealed).await {
+                    batch_pipeline.push(seal);
+                }
+            }
+        }
+
+        // The transactions handed off by the previous worker of the epoch are batched first,
+        // the ones of an earlier epoch being failed back to their submitters.
+        for (lane, transaction, response_sender) in self.handoff.take(self.epoch) {
+            if let Some(sealed) =
+                self.add_transaction(&mut current_batches, lane, transaction, response_sender)
+            {
+                if let Some(seal) = self.seal(false, sealed).await {
+                    batch_pipeline.push(seal);
+                }
+            }
+        }
+        self.record_pipeline_len(batch_pipeline.len());
+
         loop {
             tokio::select! {
                 // Assemble client transactions into batches of preset size.
                 // Note that transactions are only consumed when the number of batches
                 // 'in-flight' are below a certain number (MAX_PARALLEL_BATCH). This
                 // condition will be met eventually if the store and network are functioning.
-                Some((transaction, response_sender)) = self.rx_batch_maker.recv(), if batch_pipeline.len() < MAX_PARALLEL_BATCH => {
-                    current_batch_size += transaction.len();
-                    current_batch.transactions_mut().push(transaction);
-                    current_responses.push(response_sender);
-                    if current_batch_size >= self.batch_size_limit {
-                        if let Some(seal) = self.seal(false, current_batch, current_batch_size, current_responses).await{
+                // They are not consumed either while the memory budget of the node is exceeded,
+                // so that the clients are pushed back.
+                Some((lane, transaction, response_sender)) = self.scheduler.next_transaction(&mut self.rx_batch_maker), if batch_pipeline.len() < MAX_PARALLEL_BATCH && self.memory.is_within_budget() => {
+                    if let Some(sealed) = self.add_transaction(&mut current_batches, lane, transaction, response_sender) {
+                        if let Some(seal) = self.seal(false, sealed).await {
                             batch_pipeline.push(seal);
                         }
-                        self.node_metrics.parallel_worker_batches.set(batch_pipeline.len() as i64);
-
-                        current_batch = Batch::default();
-                        current_responses = Vec::new();
-                        current_batch_size = 0;
+                        self.record_pipeline_len(batch_pipeline.len());
+                    }
 
-                        timer.as_mut().reset(Instant::now() + self.max_batch_delay);
-                        self.batch_start_timestamp = Instant::now();
+                    // The batch delay may have changed along with the load.
+                    let deadline = self.next_deadline(&current_batches);
+                    if deadline != timer.deadline() {
+                        timer.as_mut().reset(deadline);
                     }
                 },
 
-                // If the timer triggers, seal the batch even if it contains few transactions.
+                // If the timer triggers, seal the batches that have been open for too long,
+                // even if they contain few transactions.
                 () = &mut timer => {
-                    if !current_batch.transactions().is_empty() {
-                        if let Some(seal) = self.seal(true, current_batch, current_batch_size, current_responses).await {
-                            batch_pipeline.push(seal);
+                    let now = Instant::now();
+                    let max_batch_delay = self.thresholds().max_batch_delay;
+                    for lane in PriorityLane::ALL {
+                        let current_batch = current_batches.get_mut(lane);
+                        if current_batch.started_at + max_batch_delay > now {
+                            continue;
+                        }
+                        let sealed = std::mem::replace(current_batch, self.new_lane_batch(lane));
+                        if !sealed.batch.transactions().is_empty() {
+                            if let Some(seal) = self.seal(true, sealed).await {
+                                batch_pipeline.push(seal);
+                            }
+                            self.record_pipeline_len(batch_pipeline.len());
                         }
-                        self.node_metrics.parallel_worker_batches.set(batch_pipeline.len() as i64);
-
-                        current_batch = Batch::default();
-                        current_responses = Vec::new();
-                        current_batch_size = 0;
                     }
-                    timer.as_mut().reset(Instant::now() + self.max_batch_delay);
-                    self.batch_start_timestamp = Instant::now();
+                    timer.as_mut().reset(self.next_deadline(&current_batches));
                 }
 
                 _ = self.rx_shutdown.receiver.recv() => {
                     return
                 }
 
+                () = self.rx_draining.draining() => {
+                    for lane in PriorityLane::ALL {
+                        let current_batch = current_batches.get_mut(lane);
+                        let sealed = std::mem::replace(current_batch, self.new_lane_batch(lane));
+                        if !sealed.batch.transactions().is_empty() {
+                            if let Some(seal) = self.seal(true, sealed).await {
+                                batch_pipeline.push(seal);
+                            }
+                        }
+                    }
+                    self.record_pipeline_len(batch_pipeline.len());
+                    self.drain(batch_pipeline).await;
+                    return
+                }
+
                 // Process the pipeline of batches, this consumes items in the `batch_pipeline`
                 // list, and ensures the main loop in run will always be able to make progress
                 // by lowering it until condition batch_pipeline.len() < MAX_PARALLEL_BATCH is met.
-                _ = batch_pipeline.next(), if !batch_pipeline.is_empty() => {
-                    self.node_metrics.parallel_worker_batches.set(batch_pipeline.len() as i64);
+                // Resume consuming the transactions once the memory budget is no longer exceeded.
+                () = self.memory.budget().wait_for_budget(), if !self.memory.is_within_budget() => {}
+
+                Some(handed_off) = batch_pipeline.next(), if !batch_pipeline.is_empty() => {
+                    // A batch may give up right as the drain starts.
+                    if let Some(handed_off) = handed_off {
+                        self.handoff.extend(self.epoch, handed_off);
+                    }
+                    self.record_pipeline_len(batch_pipeline.len());
                 }
 
             }
@@ -165,14 +390,237 @@ impl BatchMaker {
         }
     }
 
+    /// Adds the transaction to the current batch of its lane, unless it is a duplicate. Returns
+    /// the batch to seal if it is full, or if it cannot take the transaction within the batch
+    /// limits, in which case the transaction starts the next batch.
+    fn add_transaction(
+        &mut self,
+        current_batches: &mut Lanes<LaneBatch>,
+        lane: PriorityLane,
+        transaction: Transaction,
+        response_sender: TxResponse,
+    ) -> Option<LaneBatch> {
+        if self.dedup_cache.check_and_insert(&transaction) {
+            self.node_metrics.duplicate_transactions_rejected.inc();
+            let rejection = TransactionRejection::Duplicate(self.dedup_cache.window);
+            let _ = response_sender.send(Err(rejection));
+            return None;
+        }
+        if let Some(limits) = self.batch_limits {
+            if transaction.len() > limits.max_size {
+                let rejection = TransactionRejection::TooLarge(transaction.len(), limits.max_size);
+                let _ = response_sender.send(Err(rejection));
+                return None;
+            }
+        }
+        self.node_metrics
+            .batched_transactions
+            .with_label_values(&[lane.as_str()])
+            .inc();
+
+        self.sealing.record(transaction.len());
+        let batch_size_limit = self.thresholds().batch_size_limit;
+        let current_batch = current_batches.get_mut(lane);
+        let overflowing = self.batch_limits.map_or(false, |limits| {
+            !current_batch.batch.transactions().is_empty()
+                && current_batch.size + transaction.len() > limits.max_size
+        });
+        let overflowed =
+            overflowing.then(|| std::mem::replace(current_batch, self.new_lane_batch(lane)));
+        current_batch.size += transaction.len();
+        current_batch.memory.grow(transaction.len());
+        current_batch.batch.transactions_mut().push(transaction);
+        current_batch.responses.push(response_sender);
+        current_batch.received_at.push(Instant::now());
+        if overflowed.is_some() {
+            // The transaction left alone is sealed with the next ones, or by the timer.
+            return overflowed;
+        }
+        let full = self.batch_limits.map_or(false, |limits| {
+            current_batch.batch.transactions().len() >= limits.max_num_of_transactions
+        });
+        if full || current_batch.size >= batch_size_limit {
+            return Some(std::mem::replace(current_batch, self.new_lane_batch(lane)));
+        }
+        None
+    }
+
+    /// Drains the batch maker at the end of the epoch, once the current batches are sealed. The
+    /// batches in flight are waited for until the drain timeout. The transactions that are not
+    /// included by then, or not batched yet, are handed off to the next worker with the same id,
+    /// which fails them back to their submitters if it is of another epoch.
+    async fn drain<F: Future<Output = Option<Vec<HandedOffTransaction>>>>(
+        &mut self,
+        mut batch_pipeline: FuturesUnordered<F>,
+    ) {
+        let started_at = Instant::now();
+        info!("Draining the batch maker of worker {}", self.id);
+
+        // The clients have stopped submitting, so whatever is queued is all there is.
+        let mut handed_off = Vec::new();
+        for lane in PriorityLane::ALL {
+            while let Ok((transaction, response)) = self.rx_batch_maker.get_mut(lane).try_recv() {
+                handed_off.push((lane, transaction, response));
+            }
+        }
+
+        let deadline = sleep(self.drain_timeout);
+        tokio::pin!(deadline);
+        let mut expired = false;
+        loop {
+            tokio::select! {
+                result = batch_pipeline.next() => match result {
+                    Some(transactions) => {
+                        handed_off.extend(transactions.into_iter().flatten());
+                        self.record_pipeline_len(batch_pipeline.len());
+                    }
+                    None => break,
+                },
+
+                // The batches still in flight give up on being included, and return their
+                // transactions.
+                () = &mut deadline, if !expired => {
+                    expired = true;
+                    let _ = self.tx_drain_expired.send(true);
+                }
+
+                _ = self.rx_shutdown.receiver.recv(), if !expired => {
+                    expired = true;
+                    let _ = self.tx_drain_expired.send(true);
+                }
+            }
+        }
+
+        self.node_metrics
+            .handed_off_transactions
+            .inc_by(handed_off.len() as u64);
+        info!(
+            "Batch maker of worker {} drained in {} seconds, handing off {} transactions",
+            self.id,
+            started_at.elapsed().as_secs_f64(),
+            handed_off.len()
+        );
+        self.handoff.extend(self.epoch, handed_off);
+
+        if let Some(tx_drained) = self.tx_drained.take() {
+            let _ = tx_drained.send(());
+        }
+    }
+
+    /// Records the number of sealed batches waiting for a quorum.
+    fn record_pipeline_len(&self, len: usize) {
+        self.node_metrics.parallel_worker_batches.set(len as i64);
+        self.admission_controller.set_inflight_quorum_waits(len);
+    }
+
+    /// The current sealing thresholds, reported as metrics.
+    fn thresholds(&mut self) -> SealingThresholds {
+        let max_batch_delay = self.rx_parameters.borrow().max_batch_delay;
+        self.sealing.set_max_batch_delay(max_batch_delay);
+        let thresholds = self.sealing.thresholds();
+        self.node_metrics
+            .batch_ingress_rate
+            .set(self.sealing.ingress_rate() as i64);
+        self.node_metrics
+            .batch_size_threshold
+            .set(thresholds.batch_size_limit as i64);
+        self.node_metrics
+            .batch_delay_threshold
+            .set(thresholds.max_batch_delay.as_millis() as i64);
+        thresholds
+    }
+
+    /// The time at which the oldest of the current batches should be sealed.
+    fn next_deadline(&mut self, current_batches: &Lanes<LaneBatch>) -> Instant {
+        let max_batch_delay = self.thresholds().max_batch_delay;
+        PriorityLane::ALL
+            .iter()
+            .map(|lane| current_batches.get(*lane).started_at + max_batch_delay)
+            .min()
+            .unwrap()
+    }
+
     /// Seal and broadcast the current batch.
     async fn seal(
         &self,
         timeout: bool,
-        mut batch: Batch,
-        size: usize,
-        responses: Vec<TxResponse>,
-    ) -> Option<impl Future<Output = ()>> {
+        current_batch: LaneBatch,
+    ) -> Option<impl Future<Output = Option<Vec<HandedOffTransaction>>>> {
+        let LaneBatch {
+            lane,
+            mut batch,
+            mut responses,
+            mut received_at,
+            mut size,
+            started_at,
+            mut memory,
+        } = current_batch;
+
+        // The transactions which expired while queued are dropped, their clients having given
+        // up on them.
+        if batch
+            .transactions()
+            .iter()
+            .any(|transaction| self.validator.is_expired(transaction))
+        {
+            let transactions = std::mem::take(batch.transactions_mut());
+            let mut expired = Vec::new();
+            let mut kept_responses = Vec::new();
+            let mut kept_received_at = Vec::new();
+            for ((transaction, response), received) in
+                transactions.into_iter().zip(responses).zip(received_at)
+            {
+                if self.validator.is_expired(&transaction) {
+                    size -= transaction.len();
+                    memory.shrink(transaction.len());
+                    let _ = response.send(Err(TransactionRejection::Expired));
+                    expired.push(transaction);
+                } else {
+                    batch.transactions_mut().push(transaction);
+                    kept_responses.push(response);
+                    kept_received_at.push(received);
+                }
+            }
+            responses = kept_responses;
+            received_at = kept_received_at;
+            self.node_metrics
+                .expired_transactions_dropped
+                .inc_by(expired.len() as u64);
+            if let Err(e) = self.transaction_log.remove(&expired) {
+                error!("Failed to remove expired transactions from the log: {e}");
+            }
+            if batch.transactions().is_empty() {
+                return None;
+            }
+        }
+
+        if let Err(err) = self.validator.validate_local_batch(&batch).await {
+            self.node_metrics.invalid_batches_rejected.inc();
+            debug!("Rejected batch {:?}: {err}", batch.digest());
+            let rejection = TransactionRejection::InvalidBatch(err.to_string());
+            if let Err(e) = self.transaction_log.remove(batch.transactions()) {
+                error!("Failed to remove rejected transactions from the log: {e}");
+            }
+            for response in responses {
+                let _ = response.send(Err(rejection.clone()));
+            }
+            return None;
+        }
+
+        // The whole batch is logged at once, before leaving the worker.
+        let (mut batch, logged) = self.transaction_log.append(lane, batch).await;
+        if let Err(e) = logged {
+            error!(
+                "Failed to log the transactions of batch {:?}: {e}",
+                batch.digest()
+            );
+            let rejection = TransactionRejection::LogFailed(e.to_string());
+            for response in responses {
+                let _ = response.send(Err(rejection.clone()));
+            }
+            return None;
+        }
+
         #[cfg(feature = "benchmark")]
         {
             let digest = batch.digest();
@@ -241,7 +689,7 @@ impl BatchMaker {
             return None;
         }
 
-        let batch_creation_duration = self.batch_start_timestamp.elapsed().as_secs_f64();
+        let batch_creation_duration = started_at.elapsed().as_secs_f64();
 
         tracing::debug!(
             "Batch {:?} took {} seconds to create due to {}",
@@ -257,64 +705,128 @@ impl BatchMaker {
             .created_batch_latency
             .with_label_values(&[reason])
             .observe(batch_creation_duration);
+        let sealed_at = Instant::now();
+        let pipeline_stage_latency = &self.node_metrics.worker_pipeline_stage_latency;
+        let batch_sealed = pipeline_stage_latency.with_label_values(&[BATCH_SEALED_STAGE]);
+        for received_at in received_at {
+            batch_sealed.observe(sealed_at.duration_since(received_at).as_secs_f64());
+        }
+        let quorum_reached = pipeline_stage_latency.with_label_values(&[QUORUM_REACHED_STAGE]);
 
         // Clone things to not capture self
         let store = self.store.clone();
         let worker_id = self.id;
         let tx_our_batch = self.tx_our_batch.clone();
+        let rx_draining = self.rx_draining.clone();
+        let rx_drain_expired = self.tx_drain_expired.subscribe();
+        let transaction_log = self.transaction_log.clone();
+        let transaction_status = self.transaction_status.clone();
 
         // The batch has been sealed so we can officially set its creation time
         // for latency calculations.
         batch.metadata_mut().created_at = now();
         let metadata = batch.metadata().clone();
+        let size = batch.size() as u64;
 
         Some(async move {
-            // Now save it to disk
+            // Released once the batch reaches a quorum, or gives up on it.
+            let _memory = memory;
             let digest = batch.digest();
-
-            if let Err(e) = store.insert(&digest, &batch) {
-                error!("Store failed with error: {:?}", e);
-                return;
+            transaction_status.batched(digest, batch.transactions());
+            if tracing::enabled!(target: CORRELATION_TARGET, tracing::Level::DEBUG) {
+                for transaction in batch.transactions() {
+                    let transaction_digest = TransactionStatusTracker::digest(transaction);
+                    debug!(
+                        target: CORRELATION_TARGET,
+                        stage = "batched",
+                        transaction = Hex::encode(transaction_digest.digest),
+                        batch = %digest,
+                    );
+                }
             }
 
-            // Also wait for sending to be done here
-            //
-            // TODO: Here if we get back Err it means that potentially this was not send
-            //       to a quorum. However, if that happens we can still proceed on the basis
-            //       that an other authority will request the batch from us, and we will deliver
-            //       it since it is now stored. So ignore the error for the moment.
-            let _ = done_sending.await;
-
-            // Finally send to primary
-            let (primary_response, batch_done) = tokio::sync::oneshot::channel();
-            let message = WorkerOurBatchMessage {
-                digest,
-                worker_id,
-                metadata,
+            let include = async {
+                // Now save it to disk
+                if let Err(e) = store.insert(&digest, &batch) {
+                    error!("Store failed with error: {:?}", e);
+                    return false;
+                }
+
+                // Also wait for sending to be done here
+                //
+                // TODO: Here if we get back Err it means that potentially this was not send
+                //       to a quorum. However, if that happens we can still proceed on the basis
+                //       that an other authority will request the batch from us, and we will
+                //       deliver it since it is now stored. So ignore the error for the moment.
+                if done_sending.await.is_ok() {
+                    transaction_status.update(&digest, TransactionStage::QuorumAcked);
+                    quorum_reached.observe(sealed_at.elapsed().as_secs_f64());
+                }
+
+                // Finally send to primary
+                let (primary_response, batch_done) = tokio::sync::oneshot::channel();
+                let message = WorkerOurBatchMessage {
+                    digest,
+                    worker_id,
+                    metadata,
+                    size,
+                };
+                if tx_our_batch
+                    .send((message, Some(primary_response)))
+                    .await
+                    .is_err()
+                {
+                    debug!("Failed to send created batch to primary. Shutting down.");
+                    return false;
+                };
+
+                // Wait for a primary response
+                //
+                // If there is an error it means the channel closed, and therefore we drop all
+                // response handers since we cannot ensure the primary has actually signaled the
+                // batch will eventually be sent.
+                batch_done.await.is_ok()
             };
-            if tx_our_batch
-                .send((message, Some(primary_response)))
-                .await
-                .is_err()
-            {
-                debug!("Failed to send created batch to primary. Shutting down.");
-                return;
+
+            let included = tokio::select! {
+                included = include => included,
+                () = drain_expired(rx_drain_expired) => false,
             };
 
-            // Wait for a primary response
-            if batch_done.await.is_err() {
-                // If there is an error it means the channel closed,
-                // and therefore we drop all response handers since we
-                // cannot ensure the primary has actually signaled the
-                // batch will eventually be sent.
-                return;
+            if included {
+                // A failure only means the transactions may be batched again after a restart.
+                if let Err(e) = transaction_log.remove(batch.transactions()) {
+                    error!("Failed to remove the transactions of {digest} from the log: {e}");
+                }
+                // We now signal back to the transaction sender that the transaction is in a
+                // batch and also the digest of the batch.
+                for response in responses {
+                    let _ = response.send(Ok(digest));
+                }
+                return None;
             }
 
-            // We now signal back to the transaction sender that the transaction is in a
-            // batch and also the digest of the batch.
-            for response in responses {
-                let _ = response.send(digest);
+            // When draining, the transactions are handed off instead of being dropped.
+            if !rx_draining.is_draining() {
+                return None;
             }
+            let transactions = std::mem::take(batch.transactions_mut());
+            Some(
+                transactions
+                    .into_iter()
+                    .zip(responses)
+                    .map(|(transaction, response)| (lane, transaction, response))
+                    .collect(),
+            )
         })
     }
 }
+
+/// Completes once the drain timeout has expired.
+async fn drain_expired(mut rx_drain_expired: watch::Receiver<bool>) {
+    while !*rx_drain_expired.borrow() {
+        if rx_drain_expired.changed().await.is_err() {
+            futures::future::pending::<()>().await;
+        }
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use anemo::PeerId;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use types::BatchDigest;
+
+/// How long a batches stream can stay open before it is discarded.
+pub const BATCHES_STREAM_TTL: Duration = Duration::from_secs(120);
+
+/// The maximum number of batches streams a single peer can have open at the same time. When a
+/// peer opens more, its oldest stream is discarded.
+pub const MAX_OPEN_BATCHES_STREAMS_PER_PEER: usize = 16;
+
+struct BatchesStream {
+    // The digests of the batches in each chunk of the stream, or None if the
+    // chunk has already been served.
+    chunks: Vec<Option<Vec<BatchDigest>>>,
+    opened_at: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_stream_id: u64,
+    streams: HashMap<PeerId, BTreeMap<u64, BatchesStream>>,
+}
+
+/// Keeps track of the batches streams opened by peers on this worker. A stream only records
+/// which digests belong to each of its chunks; the batches themselves are read from the store
+/// when a chunk is requested.
+#[derive(Clone)]
+pub struct BatchesStreams {
+    // The maximum total size (in bytes) of the batches in a single chunk.
+    max_chunk_size: usize,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl BatchesStreams {
+    pub fn new(max_chunk_size: usize) -> Self {
+        Self {
+            max_chunk_size,
+            inner: Arc::new(Mutex::new(Inner::default())),
+        }
+    }
+
+    pub fn max_chunk_size(&self) -> usize {
+        self.max_chunk_size
+    }
+
+    /// Registers a new stream for `peer` and returns its id. The first chunk is considered
+    /// served, as it is returned along with the stream id.
+    pub fn open(&self, peer: PeerId, chunks: Vec<Vec<BatchDigest>>) -> u64 {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+
+        // Drop the streams that were never fully consumed.
+        inner.streams.retain(|_, peer_streams| {
+            peer_streams.retain(|_, stream| stream.opened_at.elapsed() < BATCHES_STREAM_TTL);
+            !peer_streams.is_empty()
+        });
+
+        let stream_id = inner.next_stream_id;
+        inner.next_stream_id += 1;
+
+        let peer_streams = inner.streams.entry(peer).or_default();
+        while peer_streams.len() >= MAX_OPEN_BATCHES_STREAMS_PER_PEER {
+            // Stream ids are increasing, so the first one is the oldest.
+            let oldest = *peer_streams.keys().next().unwrap();
+            peer_streams.remove(&oldest);
+        }
+
+        let chunks = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| (index != 0).then_some(chunk))
+            .collect();
+        peer_streams.insert(
+            stream_id,
+            BatchesStream {
+                chunks,
+                opened_at: Instant::now(),
+            },
+        );
+        stream_id
+    }
+
+    /// Returns the digests of the requested chunk, or None if the stream does not exist, the
+    /// chunk index is out of range or the chunk has already been served. The stream is closed
+    /// once all its chunks have been served.
+    pub fn take_chunk(
+        &self,
+        peer: &PeerId,
+        stream_id: u64,
+        chunk_index: u32,
+    ) -> Option<Vec<BatchDigest>> {
+        let mut inner = self.inner.lock().unwrap();
+        let peer_streams = inner.streams.get_mut(peer)?;
+        let stream = peer_streams.get_mut(&stream_id)?;
+        let chunk = stream.chunks.get_mut(chunk_index as usize)?.take();
+
+        if stream.chunks.iter().all(Option::is_none) {
+            peer_streams.remove(&stream_id);
+            if peer_streams.is_empty() {
+                inner.streams.remove(peer);
+            }
+        }
+        chunk
+    }
+}
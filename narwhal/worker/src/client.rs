@@ -7,10 +7,17 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use crate::{
+    admission_control::AdmissionController, client_quotas::QuotaExceeded, drain::DrainReceiver,
+    lanes::LaneSenders, pending_transactions::PendingTransactions, spill_queue::SpillQueue,
+    transaction_status::TransactionStatusTracker, transactions_server::SenderShard,
+};
 use arc_swap::ArcSwap;
+use config::WorkerId;
 use mysten_network::{multiaddr::Protocol, Multiaddr};
+use std::time::Duration;
 use thiserror::Error;
-use types::{metered_channel::Sender, Transaction, TxResponse};
+use types::{PriorityLane, Transaction, TransactionRejection};
 
 /// Uses a map to allow running multiple Narwhal instances in the same process.
 /// TODO: after Rust 1.66, use BTreeMap::new() instead of wrapping it in an Option.
@@ -32,20 +39,68 @@ pub enum NarwhalError {
 
     #[error("Transaction is too large: size={0} limit={1}")]
     TransactionTooLarge(usize, usize),
+
+    #[error("Transaction rejected: {0}")]
+    TransactionRejected(TransactionRejection),
+
+    #[error("Narwhal is overloaded, retry after {} ms", .0.as_millis())]
+    Overloaded(Duration),
+
+    #[error(transparent)]
+    QuotaExceeded(QuotaExceeded),
+
+    #[error("The transactions of this sender are handled by worker {0} at {1}")]
+    WrongWorker(WorkerId, Multiaddr),
 }
 
+/// Returns the sender of a transaction, if known.
+pub type SenderOf = Arc<dyn Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync>;
+
 /// TODO: add NarwhalClient trait and implement RemoteNarwhalClient with grpc.
 
 /// A client that connects to Narwhal locally.
 #[derive(Clone)]
 pub struct LocalNarwhalClient {
-    /// TODO: maybe use tx_batch_maker for load schedding.
-    tx_batch_maker: Sender<(Transaction, TxResponse)>,
+    tx_batch_maker: LaneSenders,
+    /// Sheds the load when the worker is overloaded.
+    admission_controller: Arc<AdmissionController>,
+    /// Spills the transactions to disk instead of shedding them, if enabled.
+    spill_queue: Arc<SpillQueue>,
+    /// No new transactions are accepted once the worker drains at the end of the epoch.
+    rx_draining: DrainReceiver,
+    /// The transactions submitted through this client, until their batch is acknowledged.
+    pending_transactions: Arc<PendingTransactions>,
+    /// Rejects the transactions of the senders assigned to the other workers, when the
+    /// transactions are sharded by sender.
+    sender_shard: Option<(SenderShard, SenderOf)>,
+    /// Acknowledges the resubmitted transactions which are already batched, so that the retries
+    /// of the clients are idempotent.
+    transaction_status: TransactionStatusTracker,
 }
 
 impl LocalNarwhalClient {
-    pub fn new(tx_batch_maker: Sender<(Transaction, TxResponse)>) -> Arc<Self> {
-        Arc::new(Self { tx_batch_maker })
+    pub fn new(
+        tx_batch_maker: LaneSenders,
+        admission_controller: Arc<AdmissionController>,
+        spill_queue: Arc<SpillQueue>,
+        rx_draining: DrainReceiver,
+        sender_shard: Option<(SenderShard, SenderOf)>,
+        transaction_status: TransactionStatusTracker,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            tx_batch_maker,
+            admission_controller,
+            spill_queue,
+            rx_draining,
+            pending_transactions: PendingTransactions::new(),
+            sender_shard,
+            transaction_status,
+        })
+    }
+
+    /// The transactions submitted to the local Narwhal worker and not yet acknowledged.
+    pub fn pending_transactions(&self) -> &Arc<PendingTransactions> {
+        &self.pending_transactions
     }
 
     /// Sets the instance of LocalNarwhalClient for the local address.
@@ -74,26 +129,74 @@ impl LocalNarwhalClient {
         clients.as_ref()?.get(&addr).cloned()
     }
 
-    /// Submits a transaction to the local Narwhal worker.
+    /// Submits a transaction to the normal priority lane of the local Narwhal worker.
     pub async fn submit_transaction(&self, transaction: Transaction) -> Result<(), NarwhalError> {
+        self.submit_transaction_in_lane(transaction, PriorityLane::Normal)
+            .await
+    }
+
+    /// Submits a transaction to the given priority lane of the local Narwhal worker.
+    pub async fn submit_transaction_in_lane(
+        &self,
+        transaction: Transaction,
+        lane: PriorityLane,
+    ) -> Result<(), NarwhalError> {
         if transaction.len() > MAX_ALLOWED_TRANSACTION_SIZE {
             return Err(NarwhalError::TransactionTooLarge(
                 transaction.len(),
                 MAX_ALLOWED_TRANSACTION_SIZE,
             ));
         }
+        if self.rx_draining.is_draining() {
+            return Err(NarwhalError::ShuttingDown);
+        }
+        if let Some((sender_shard, sender_of)) = &self.sender_shard {
+            if let Some(sender) = sender_of(&transaction) {
+                sender_shard.check(&sender)?;
+            }
+        }
+        let digest = TransactionStatusTracker::digest(&transaction);
+        if self.transaction_status.status(&digest).is_some() {
+            return Ok(());
+        }
+        // The permit is held until the batch of the transaction is acknowledged by a quorum.
+        // While the spilled transactions are not all restored, the new ones are spilled after
+        // them. A spilled transaction is accepted without waiting for its batch.
+        let admitted = if self.spill_queue.is_empty() {
+            self.admission_controller.admit(transaction.len()).ok()
+        } else {
+            None
+        };
+        let _permit = match admitted {
+            Some(permit) => permit,
+            None if self.spill_queue.try_spill(lane, &transaction) => return Ok(()),
+            None => self
+                .admission_controller
+                .try_admit(transaction.len())
+                .map_err(NarwhalError::Overloaded)?,
+        };
+        let _pending = self.pending_transactions.track(&transaction, lane);
         // Send the transaction to the batch maker.
         let (notifier, when_done) = tokio::sync::oneshot::channel();
         self.tx_batch_maker
+            .get(lane)
             .send((transaction, notifier))
             .await
             .map_err(|_| NarwhalError::ShuttingDown)?;
 
-        let _digest = when_done
+        match when_done
             .await
-            .map_err(|_| NarwhalError::TransactionNotIncludedInHeader)?;
-
-        Ok(())
+            .map_err(|_| NarwhalError::TransactionNotIncludedInHeader)?
+        {
+            Ok(_) => Ok(()),
+            // The first submission may have been batched in the meantime.
+            Err(TransactionRejection::Duplicate(_))
+                if self.transaction_status.status(&digest).is_some() =>
+            {
+                Ok(())
+            }
+            Err(rejection) => Err(NarwhalError::TransactionRejected(rejection)),
+        }
     }
 
     /// Ensures getter and setter use the same key for the same network address.
@@ -0,0 +1,192 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::metrics::WorkerMetrics;
+use config::ClientQuotaParameters;
+use lru::LruCache;
+use std::{
+    fmt,
+    net::IpAddr,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+#[cfg(test)]
+#[path = "tests/client_quotas_tests.rs"]
+pub mod client_quotas_tests;
+
+/// The quotas of a client of the transaction endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quota {
+    Transactions,
+    Bytes,
+}
+
+impl Quota {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Quota::Transactions => "transactions",
+            Quota::Bytes => "bytes",
+        }
+    }
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returned when a client submits a transaction beyond one of its quotas.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("Client {client} exceeded its {quota} quota, retry after {} ms", .retry_after.as_millis())]
+pub struct QuotaExceeded {
+    pub client: IpAddr,
+    pub quota: Quota,
+    /// The delay after which the quota will allow the transaction.
+    pub retry_after: Duration,
+}
+
+/// A token bucket holding up to `capacity` tokens, refilled at `rate` tokens per second.
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    rate: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u32, burst_duration: Duration, now: Instant) -> Self {
+        let rate = rate as f64;
+        let capacity = (rate * burst_duration.as_secs_f64()).max(1.0);
+        Self {
+            tokens: capacity,
+            capacity,
+            rate,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Returns the delay until `amount` tokens can be taken. An amount larger than the capacity
+    /// can be taken once the bucket is full, leaving it in debt, so that it is not rejected
+    /// forever.
+    fn delay(&self, amount: f64) -> Option<Duration> {
+        let needed = amount.min(self.capacity) - self.tokens;
+        if needed <= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(needed / self.rate))
+    }
+
+    fn take(&mut self, amount: f64) {
+        self.tokens -= amount;
+    }
+}
+
+/// The token buckets of a client, one per enabled quota.
+struct ClientBuckets {
+    transactions: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+}
+
+/// Enforces the per-client quotas of the transaction endpoint, so that a single client cannot
+/// monopolize the batch bandwidth of the worker.
+pub struct ClientQuotas {
+    parameters: ClientQuotaParameters,
+    /// The buckets of the most recently seen clients.
+    buckets: Mutex<LruCache<IpAddr, ClientBuckets>>,
+    metrics: Arc<WorkerMetrics>,
+}
+
+impl ClientQuotas {
+    pub fn new(parameters: ClientQuotaParameters, metrics: Arc<WorkerMetrics>) -> Arc<Self> {
+        let capacity = NonZeroUsize::new(parameters.max_tracked_clients.max(1)).unwrap();
+        Arc::new(Self {
+            parameters,
+            buckets: Mutex::new(LruCache::new(capacity)),
+            metrics,
+        })
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.parameters.max_transactions_per_second.is_some()
+            || self.parameters.max_bytes_per_second.is_some()
+    }
+
+    /// Charges a transaction of the given size to the quotas of the client. If the transaction
+    /// exceeds any of them, nothing is charged and the exceeded quota is returned.
+    pub fn try_charge(&self, client: IpAddr, size: usize) -> Result<(), QuotaExceeded> {
+        self.try_charge_at(client, size, Instant::now())
+    }
+
+    fn try_charge_at(
+        &self,
+        client: IpAddr,
+        size: usize,
+        now: Instant,
+    ) -> Result<(), QuotaExceeded> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let client_buckets = buckets.get_or_insert_mut(client, || {
+            let bucket = |rate: u32| TokenBucket::new(rate, self.parameters.burst_duration, now);
+            ClientBuckets {
+                transactions: self
+                    .parameters
+                    .max_transactions_per_second
+                    .map(|rate| bucket(rate.get())),
+                bytes: self
+                    .parameters
+                    .max_bytes_per_second
+                    .map(|rate| bucket(rate.get())),
+            }
+        });
+
+        let charges = [
+            (
+                Quota::Transactions,
+                client_buckets.transactions.as_mut(),
+                1.0,
+            ),
+            (Quota::Bytes, client_buckets.bytes.as_mut(), size as f64),
+        ];
+        let mut charged = Vec::with_capacity(charges.len());
+        for (quota, bucket, amount) in charges {
+            let Some(bucket) = bucket else {
+                continue;
+            };
+            bucket.refill(now);
+            if let Some(retry_after) = bucket.delay(amount) {
+                self.metrics
+                    .client_quota_rejected_transactions
+                    .with_label_values(&[quota.as_str()])
+                    .inc();
+                return Err(QuotaExceeded {
+                    client,
+                    quota,
+                    retry_after,
+                });
+            }
+            charged.push((bucket, amount));
+        }
+        for (bucket, amount) in charged {
+            bucket.take(amount);
+        }
+
+        self.metrics
+            .client_quota_tracked_clients
+            .set(buckets.len() as i64);
+        Ok(())
+    }
+}
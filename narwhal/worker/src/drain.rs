@@ -0,0 +1,125 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use config::Epoch;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, watch};
+use types::{PriorityLane, Transaction, TransactionRejection, TxResponse};
+
+/// A transaction that a worker could not get included before the end of its epoch, along with
+/// the channel to respond to its submitter.
+pub type HandedOffTransaction = (PriorityLane, Transaction, TxResponse);
+
+/// The transactions handed off by a drained worker to the next worker started with the same id.
+/// When the next worker is of the same epoch, the submitters keep waiting for its response. The
+/// transactions are not carried over to another epoch, as they may no longer be valid against
+/// its committee: their submitters are told the epoch ended instead, for them to resubmit.
+#[derive(Clone, Default)]
+pub struct TransactionHandoff {
+    transactions: Arc<Mutex<Vec<(Epoch, HandedOffTransaction)>>>,
+}
+
+impl TransactionHandoff {
+    /// Hands off the given transactions, not included by the worker of the given epoch.
+    pub fn extend(
+        &self,
+        epoch: Epoch,
+        transactions: impl IntoIterator<Item = HandedOffTransaction>,
+    ) {
+        self.transactions
+            .lock()
+            .unwrap()
+            .extend(transactions.into_iter().map(|t| (epoch, t)));
+    }
+
+    /// Takes the transactions handed off by a worker of the given epoch. The ones handed off in
+    /// an earlier epoch are rejected.
+    pub fn take(&self, epoch: Epoch) -> Vec<HandedOffTransaction> {
+        let transactions = std::mem::take(&mut *self.transactions.lock().unwrap());
+        let mut taken = Vec::new();
+        for (handed_off_epoch, (lane, transaction, response_sender)) in transactions {
+            if handed_off_epoch == epoch {
+                taken.push((lane, transaction, response_sender));
+            } else {
+                let _ =
+                    response_sender.send(Err(TransactionRejection::EpochEnded(handed_off_epoch)));
+            }
+        }
+        taken
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Creates the handle to drain a worker, and the drain given to the worker on spawn.
+pub fn drain_channel(handoff: TransactionHandoff) -> (DrainHandle, WorkerDrain) {
+    let (tx_draining, rx_draining) = watch::channel(false);
+    let (tx_drained, rx_drained) = oneshot::channel();
+    (
+        DrainHandle {
+            tx_draining,
+            rx_drained,
+        },
+        WorkerDrain {
+            rx_draining: DrainReceiver { rx_draining },
+            tx_drained,
+            handoff,
+        },
+    )
+}
+
+/// Puts a worker in drain mode at the end of an epoch: the worker stops accepting new
+/// transactions, flushes its pending batches and hands off the transactions it could not get
+/// included.
+pub struct DrainHandle {
+    tx_draining: watch::Sender<bool>,
+    rx_drained: oneshot::Receiver<()>,
+}
+
+impl DrainHandle {
+    /// Drains the worker, and waits until it has handed off its un-included transactions.
+    /// Returns immediately if the worker is not running.
+    pub async fn drain(self) {
+        if self.tx_draining.send(true).is_err() {
+            return;
+        }
+        let _ = self.rx_drained.await;
+    }
+}
+
+/// Notified when the worker should drain.
+#[derive(Clone)]
+pub struct DrainReceiver {
+    rx_draining: watch::Receiver<bool>,
+}
+
+impl DrainReceiver {
+    pub fn is_draining(&self) -> bool {
+        *self.rx_draining.borrow()
+    }
+
+    /// Waits until the worker should drain. Never returns if the drain handle is dropped
+    /// without requesting a drain.
+    pub async fn draining(&mut self) {
+        while !self.is_draining() {
+            if self.rx_draining.changed().await.is_err() {
+                futures::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+/// The side of the drain given to the worker.
+pub struct WorkerDrain {
+    pub rx_draining: DrainReceiver,
+    /// Notified once the worker has drained.
+    pub tx_drained: oneshot::Sender<()>,
+    /// Where the un-included transactions are handed off to, and where the transactions handed
+    /// off by the previous worker with the same id are taken from.
+    pub handoff: TransactionHandoff,
+}
@@ -0,0 +1,431 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use anemo::PeerId;
+use config::{Authority, WorkerCache, WorkerId};
+use fastcrypto::hash::HashFunction;
+use mysten_metrics::{spawn_logged_monitored_task, spawn_monitored_task};
+use network::connection_manager::ConnectionManager;
+use reed_solomon_erasure::galois_8::ReedSolomon;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::timeout,
+};
+use types::{Batch, BatchDigest, ConditionalBroadcastReceiver, WorkerBatchChunkMessage};
+
+#[cfg(test)]
+#[path = "tests/erasure_coding_tests.rs"]
+pub mod erasure_coding_tests;
+
+/// The maximum number of batches being reconstructed at once. Beyond it, the oldest ones are
+/// dropped, and left to the batch synchronization.
+const MAX_PENDING_BATCHES: usize = 1_000;
+
+/// Returns the numbers of data and parity chunks a batch is split in, when each of the given
+/// number of other workers is sent one of them, or None if the batch should be sent whole. The
+/// chunks held by the other workers which are not faulty, up to a third of all the workers,
+/// reconstruct the batch.
+pub fn chunk_counts(other_workers: usize) -> Option<(usize, usize)> {
+    let faulty = other_workers / 3;
+    if faulty == 0 {
+        return None;
+    }
+    Some((other_workers - faulty, faulty))
+}
+
+/// Splits the batch in the given numbers of data and parity chunks.
+pub fn encode_batch(
+    batch: &Batch,
+    digest: BatchDigest,
+    data_chunks: usize,
+    parity_chunks: usize,
+) -> anyhow::Result<Vec<WorkerBatchChunkMessage>> {
+    let bytes = bcs::to_bytes(batch)?;
+    let chunk_size = (bytes.len() + data_chunks - 1) / data_chunks;
+    let mut chunks: Vec<_> = bytes
+        .chunks(chunk_size.max(1))
+        .map(|chunk| {
+            let mut chunk = chunk.to_vec();
+            chunk.resize(chunk_size, 0);
+            chunk
+        })
+        .collect();
+    chunks.resize(data_chunks + parity_chunks, vec![0; chunk_size]);
+    ReedSolomon::new(data_chunks, parity_chunks)?.encode(&mut chunks)?;
+
+    Ok(commit_chunks(
+        digest,
+        data_chunks as u32,
+        parity_chunks as u32,
+        bytes.len() as u64,
+        chunks,
+    ))
+}
+
+// Builds the messages of the chunks, committed to by the root of a Merkle tree over them.
+fn commit_chunks(
+    digest: BatchDigest,
+    data_chunks: u32,
+    parity_chunks: u32,
+    batch_size: u64,
+    chunks: Vec<Vec<u8>>,
+) -> Vec<WorkerBatchChunkMessage> {
+    let leaves = chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            chunk_leaf(data_chunks, parity_chunks, batch_size, index as u32, chunk)
+        })
+        .collect();
+    let levels = merkle_levels(leaves);
+    let chunks_root = levels.last().expect("There is at least one chunk")[0];
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| WorkerBatchChunkMessage {
+            digest,
+            data_chunks,
+            parity_chunks,
+            batch_size,
+            index: index as u32,
+            chunk,
+            chunks_root,
+            proof: merkle_proof(&levels, index),
+            forwarded: false,
+        })
+        .collect()
+}
+
+pub type ChunkHash = [u8; crypto::DIGEST_LENGTH];
+
+// The leaves commit to the encoding of the batch along with the chunks, so that the root commits
+// to both.
+fn chunk_leaf(
+    data_chunks: u32,
+    parity_chunks: u32,
+    batch_size: u64,
+    index: u32,
+    chunk: &[u8],
+) -> ChunkHash {
+    let mut hasher = crypto::DefaultHashFunction::new();
+    hasher.update(data_chunks.to_le_bytes());
+    hasher.update(parity_chunks.to_le_bytes());
+    hasher.update(batch_size.to_le_bytes());
+    hasher.update(index.to_le_bytes());
+    hasher.update(chunk);
+    hasher.finalize().into()
+}
+
+fn hash_pair(left: &ChunkHash, right: &ChunkHash) -> ChunkHash {
+    crypto::DefaultHashFunction::digest_iterator([left, right].into_iter()).into()
+}
+
+// The levels of the Merkle tree, from the leaves up to the root. The last node of a level with
+// an odd number of nodes is promoted to the level above.
+fn merkle_levels(leaves: Vec<ChunkHash>) -> Vec<Vec<ChunkHash>> {
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let level = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                [promoted] => *promoted,
+                _ => unreachable!("chunks of at most 2 nodes"),
+            })
+            .collect();
+        levels.push(level);
+    }
+    levels
+}
+
+fn merkle_proof(levels: &[Vec<ChunkHash>], mut index: usize) -> Vec<ChunkHash> {
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    proof
+}
+
+/// Checks that the chunk is the one the originator committed to with the root of the chunks.
+pub fn verify_chunk(message: &WorkerBatchChunkMessage) -> bool {
+    let mut node = chunk_leaf(
+        message.data_chunks,
+        message.parity_chunks,
+        message.batch_size,
+        message.index,
+        &message.chunk,
+    );
+    let mut index = message.index as usize;
+    let mut width = message.data_chunks as usize + message.parity_chunks as usize;
+    let mut proof = message.proof.iter();
+    while width > 1 {
+        if index ^ 1 < width {
+            let Some(sibling) = proof.next() else {
+                return false;
+            };
+            node = if index % 2 == 0 {
+                hash_pair(&node, sibling)
+            } else {
+                hash_pair(sibling, &node)
+            };
+        }
+        index /= 2;
+        width = (width + 1) / 2;
+    }
+    proof.next().is_none() && node == message.chunks_root
+}
+
+/// Reconstructs a batch from at least `data_chunks` of its chunks.
+pub fn decode_batch(
+    mut chunks: Vec<Option<Vec<u8>>>,
+    data_chunks: usize,
+    parity_chunks: usize,
+    batch_size: usize,
+) -> anyhow::Result<Batch> {
+    ReedSolomon::new(data_chunks, parity_chunks)?.reconstruct_data(&mut chunks)?;
+    let mut bytes: Vec<u8> = chunks
+        .into_iter()
+        .take(data_chunks)
+        .flat_map(|chunk| chunk.expect("The data chunks are reconstructed"))
+        .collect();
+    anyhow::ensure!(
+        bytes.len() >= batch_size,
+        "The chunks are too small for a batch of {batch_size} bytes"
+    );
+    bytes.truncate(batch_size);
+    Ok(bcs::from_bytes(&bytes)?)
+}
+
+struct PendingBatch {
+    data_chunks: u32,
+    chunks: Vec<Option<Vec<u8>>>,
+    // The indexes of the chunks received, in the order they were received.
+    received: Vec<usize>,
+    // Whether reconstructing the batch from the chunks received so far failed.
+    failed: bool,
+    created: Instant,
+    // Set once the batch is reconstructed and stored.
+    tx_reconstructed: watch::Sender<bool>,
+}
+
+impl PendingBatch {
+    // The last `data_chunks` chunks received, so that each new chunk tries another subset of
+    // the chunks after a failed reconstruction.
+    fn latest_chunks(&self) -> Vec<Option<Vec<u8>>> {
+        let mut chunks = vec![None; self.chunks.len()];
+        for index in self.received.iter().rev().take(self.data_chunks as usize) {
+            chunks[*index] = self.chunks[*index].clone();
+        }
+        chunks
+    }
+}
+
+/// The chunk received is either new, or one the worker already has.
+pub struct InsertedChunk {
+    pub is_new: bool,
+    /// The chunks to reconstruct the batch from, returned once enough of them are received, and
+    /// again with each new chunk if the reconstruction failed.
+    pub ready: Option<Vec<Option<Vec<u8>>>>,
+    /// Notified once the batch is reconstructed and stored.
+    pub rx_reconstructed: watch::Receiver<bool>,
+}
+
+/// The chunks received of the batches being reconstructed. The chunks of a batch are collected
+/// per root they are committed to, so that the chunks forged with another root cannot prevent
+/// the batch from being reconstructed from the chunks of its originator.
+#[derive(Clone, Default)]
+pub struct BatchChunks {
+    pending: Arc<Mutex<HashMap<(BatchDigest, ChunkHash), PendingBatch>>>,
+}
+
+impl BatchChunks {
+    pub fn insert(&self, message: &WorkerBatchChunkMessage) -> anyhow::Result<InsertedChunk> {
+        let total = message.data_chunks as usize + message.parity_chunks as usize;
+        anyhow::ensure!(
+            message.data_chunks > 0 && total <= 256 && (message.index as usize) < total,
+            "Invalid chunk {} of {total}",
+            message.index
+        );
+        anyhow::ensure!(
+            verify_chunk(message),
+            "Chunk {} not committed to by the root of the chunks of batch {}",
+            message.index,
+            message.digest
+        );
+
+        let key = (message.digest, message.chunks_root);
+        let mut pending = self.pending.lock().unwrap();
+        if !pending.contains_key(&key) && pending.len() >= MAX_PENDING_BATCHES {
+            let oldest = pending
+                .iter()
+                .min_by_key(|(_, batch)| batch.created)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                pending.remove(&oldest);
+            }
+        }
+        // The root commits to the encoding, so the chunks of the same root share it.
+        let batch = pending.entry(key).or_insert_with(|| PendingBatch {
+            data_chunks: message.data_chunks,
+            chunks: vec![None; total],
+            received: Vec::new(),
+            failed: false,
+            created: Instant::now(),
+            tx_reconstructed: watch::channel(false).0,
+        });
+
+        let slot = &mut batch.chunks[message.index as usize];
+        let is_new = slot.is_none();
+        if is_new {
+            *slot = Some(message.chunk.clone());
+            batch.received.push(message.index as usize);
+        }
+        let enough = if batch.failed {
+            batch.received.len() > batch.data_chunks as usize
+        } else {
+            batch.received.len() == batch.data_chunks as usize
+        };
+        let ready = (is_new && enough).then(|| batch.latest_chunks());
+        Ok(InsertedChunk {
+            is_new,
+            ready,
+            rx_reconstructed: batch.tx_reconstructed.subscribe(),
+        })
+    }
+
+    /// Marks the batch as reconstructed, waking up the requests waiting for it, whatever the
+    /// root of their chunks.
+    pub fn reconstructed(&self, digest: &BatchDigest) {
+        self.pending.lock().unwrap().retain(|(pending, _), batch| {
+            if pending == digest {
+                batch.tx_reconstructed.send_replace(true);
+            }
+            pending != digest
+        });
+    }
+
+    /// Records that the batch could not be reconstructed from the latest chunks received, so
+    /// that it is retried with the next chunk. The chunks are dropped once all of them were
+    /// received.
+    pub fn reconstruction_failed(&self, digest: &BatchDigest, chunks_root: &ChunkHash) {
+        let mut pending = self.pending.lock().unwrap();
+        let key = (*digest, *chunks_root);
+        if let Some(batch) = pending.get_mut(&key) {
+            batch.failed = true;
+            if batch.received.len() == batch.chunks.len() {
+                pending.remove(&key);
+            }
+        }
+    }
+
+    /// Drops the chunks of a batch committed to by the given root.
+    pub fn discard(&self, digest: &BatchDigest, chunks_root: &ChunkHash) {
+        self.pending
+            .lock()
+            .unwrap()
+            .remove(&(*digest, *chunks_root));
+    }
+
+    /// Waits until the batch is reconstructed, returning false on timeout or if its chunks
+    /// are discarded.
+    pub async fn wait_reconstructed(
+        mut rx_reconstructed: watch::Receiver<bool>,
+        wait: Duration,
+    ) -> bool {
+        timeout(wait, async move {
+            while !*rx_reconstructed.borrow() {
+                if rx_reconstructed.changed().await.is_err() {
+                    return *rx_reconstructed.borrow();
+                }
+            }
+            true
+        })
+        .await
+        .unwrap_or(false)
+    }
+}
+
+/// Forwards the chunks received from the originator of a batch to the other workers, so that
+/// each of them receives enough chunks to reconstruct the batch.
+pub struct BatchChunkForwarder {
+    authority: Authority,
+    id: WorkerId,
+    worker_cache: WorkerCache,
+    network: anemo::Network,
+    connection_manager: ConnectionManager,
+    // How long a chunk is retried for.
+    forward_timeout: Duration,
+    rx_forward: mpsc::Receiver<(PeerId, WorkerBatchChunkMessage)>,
+    rx_shutdown: ConditionalBroadcastReceiver,
+}
+
+impl BatchChunkForwarder {
+    #[must_use]
+    pub fn spawn(
+        authority: Authority,
+        id: WorkerId,
+        worker_cache: WorkerCache,
+        network: anemo::Network,
+        connection_manager: ConnectionManager,
+        forward_timeout: Duration,
+        rx_forward: mpsc::Receiver<(PeerId, WorkerBatchChunkMessage)>,
+        rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        spawn_logged_monitored_task!(
+            Self {
+                authority,
+                id,
+                worker_cache,
+                network,
+                connection_manager,
+                forward_timeout,
+                rx_forward,
+                rx_shutdown,
+            }
+            .run(),
+            "BatchChunkForwarderTask"
+        )
+    }
+
+    async fn run(mut self) {
+        loop {
+            tokio::select! {
+                Some((origin, mut message)) = self.rx_forward.recv() => {
+                    message.forwarded = true;
+                    let workers = self
+                        .worker_cache
+                        .others_workers_by_id(self.authority.protocol_key(), &self.id);
+                    for (_, info) in workers {
+                        if PeerId(info.name.0.to_bytes()) == origin {
+                            continue;
+                        }
+                        let handler = self.connection_manager.broadcast_chunk(
+                            &self.network,
+                            info.name,
+                            message.clone(),
+                        );
+                        let forward_timeout = self.forward_timeout;
+                        spawn_monitored_task!(async move {
+                            let _ = timeout(forward_timeout, handler).await;
+                        });
+                    }
+                },
+
+                _ = self.rx_shutdown.receiver.recv() => {
+                    return
+                }
+            }
+        }
+    }
+}
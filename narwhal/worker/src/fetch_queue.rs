@@ -0,0 +1,115 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::metrics::WorkerMetrics;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use tokio::sync::oneshot;
+use types::FetchPriority;
+
+#[cfg(test)]
+#[path = "tests/fetch_queue_tests.rs"]
+pub mod fetch_queue_tests;
+
+/// The default number of requests for batches served concurrently by a worker.
+pub const DEFAULT_MAX_CONCURRENT_FETCHES: usize = 64;
+
+struct Inner {
+    // The number of requests which can be served right away.
+    available: usize,
+    // The requests waiting to be served, by priority.
+    waiters: [VecDeque<oneshot::Sender<FetchPermit>>; 2],
+}
+
+impl Inner {
+    // Returns the oldest waiter of the highest priority.
+    fn next_waiter(&mut self) -> Option<oneshot::Sender<FetchPermit>> {
+        self.waiters
+            .iter_mut()
+            .rev()
+            .find_map(|waiters| waiters.pop_front())
+    }
+}
+
+/// Bounds the number of requests for batches served concurrently by the worker. When the bound
+/// is reached, the requests wait in a priority queue, so the requests for the batches that block
+/// the execution of committed sub dags are served before the requests for the batches of
+/// uncommitted certificates, whatever their order of arrival.
+#[derive(Clone)]
+pub struct FetchQueue {
+    inner: Arc<Mutex<Inner>>,
+    metrics: Option<Arc<WorkerMetrics>>,
+}
+
+impl FetchQueue {
+    pub fn new(max_concurrent_fetches: usize, metrics: Option<Arc<WorkerMetrics>>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                available: max_concurrent_fetches.max(1),
+                waiters: Default::default(),
+            })),
+            metrics,
+        }
+    }
+
+    /// Waits for the request to be served, i.e. until there is room for it and no request of a
+    /// higher priority is waiting.
+    pub async fn acquire(&self, priority: FetchPriority) -> FetchPermit {
+        let start = Instant::now();
+        let receiver = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.available > 0 {
+                inner.available -= 1;
+                None
+            } else {
+                let (sender, receiver) = oneshot::channel();
+                inner.waiters[priority as usize].push_back(sender);
+                Some(receiver)
+            }
+        };
+        let permit = match receiver {
+            None => FetchPermit {
+                queue: Some(self.inner.clone()),
+            },
+            Some(receiver) => receiver
+                .await
+                .expect("The waiters are only dropped once handed a permit"),
+        };
+
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .fetch_queue_wait_latency
+                .with_label_values(&[priority.as_str()])
+                .observe(start.elapsed().as_secs_f64());
+        }
+        permit
+    }
+}
+
+/// Serving a request for batches. Dropping the permit hands it over to the next request waiting.
+pub struct FetchPermit {
+    queue: Option<Arc<Mutex<Inner>>>,
+}
+
+impl Drop for FetchPermit {
+    fn drop(&mut self) {
+        let Some(queue) = self.queue.take() else {
+            return;
+        };
+        let mut inner = queue.lock().unwrap();
+        while let Some(waiter) = inner.next_waiter() {
+            let permit = FetchPermit {
+                queue: Some(queue.clone()),
+            };
+            match waiter.send(permit) {
+                Ok(()) => return,
+                // The request was abandoned while waiting. The permit must not release itself
+                // while the queue is locked.
+                Err(mut permit) => permit.queue = None,
+            }
+        }
+        inner.available += 1;
+    }
+}
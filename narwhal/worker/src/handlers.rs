@@ -4,30 +4,59 @@
 use anemo::types::response::StatusCode;
 use anyhow::Result;
 use async_trait::async_trait;
-use config::{AuthorityIdentifier, Committee, WorkerCache, WorkerId};
+use config::{
+    AuthorityIdentifier, BatchDiffSyncParameters, Committee, ErasureCodingParameters, Parameters,
+    WorkerCache, WorkerId,
+};
 use fastcrypto::hash::Hash;
 use futures::{stream::FuturesUnordered, StreamExt};
 use itertools::Itertools;
+use network::connection_manager::{Channel, ConnectionManager};
 use rand::seq::SliceRandom;
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
 use store::{rocks::DBMap, Map};
-use tokio::time::sleep;
+use tokio::{
+    sync::watch,
+    time::{sleep, Instant},
+};
 use tracing::{debug, info, trace, warn};
 use types::{
-    metered_channel::Sender, Batch, BatchDigest, PrimaryToWorker, RequestBatchRequest,
-    RequestBatchResponse, RequestBatchesRequest, RequestBatchesResponse, WorkerBatchMessage,
-    WorkerDeleteBatchesMessage, WorkerOthersBatchMessage, WorkerSynchronizeMessage, WorkerToWorker,
-    WorkerToWorkerClient,
+    metered_channel::Sender, Batch, BatchCompression, BatchDigest, FetchPriority,
+    NegotiateBatchCompressionRequest, NegotiateBatchCompressionResponse, OpenBatchesStreamRequest,
+    OpenBatchesStreamResponse, PrimaryToWorker, RequestBatchRequest, RequestBatchResponse,
+    RequestBatchesChunkRequest, RequestBatchesChunkResponse, RequestBatchesDiffRequest,
+    RequestBatchesDiffResponse, RequestBatchesRequest, RequestBatchesResponse,
+    RequestBatchesV2Request, RequestBatchesV2Response, RequestReplicatedBatchesRequest,
+    RequestReplicatedBatchesResponse, Round, WorkerBatchChunkMessage, WorkerBatchMessage,
+    WorkerBatchStatusMessage, WorkerDeleteBatchesMessage, WorkerOthersBatchMessage,
+    WorkerRelayBatchMessage, WorkerRelayBatchResponse, WorkerRoundsMessage,
+    WorkerSynchronizeMessage, WorkerToWorker, WorkerToWorkerClient,
 };
 
 use mysten_metrics::monitored_future;
 
-use crate::TransactionValidator;
+use crate::{
+    batch_compression::PeerBatchCompressions,
+    batch_diff_sync::RecentBatches,
+    batches_streams::BatchesStreams,
+    erasure_coding::{decode_batch, BatchChunks},
+    fetch_queue::FetchQueue,
+    primary_rounds::PrimaryRounds,
+    standby::ReplicationLog,
+    topology::RelayRequest,
+    transaction_status::{TransactionStage, TransactionStatusTracker},
+    TransactionValidator,
+};
 
 #[cfg(test)]
 #[path = "tests/handlers_tests.rs"]
 pub mod handlers_tests;
 
+const BATCH_DIGESTS_READ_CHUNK_SIZE: usize = 200;
+
 /// Defines how the network receiver handles incoming workers messages.
 #[derive(Clone)]
 pub struct WorkerReceiverHandler<V> {
@@ -35,24 +64,142 @@ pub struct WorkerReceiverHandler<V> {
     pub tx_others_batch: Sender<WorkerOthersBatchMessage>,
     pub store: DBMap<BatchDigest, Batch>,
     pub validator: V,
+    // The maximum total size (in bytes) of the batches returned in a single response.
+    pub max_request_batches_response_size: usize,
+    pub batches_streams: BatchesStreams,
+    pub batch_compressions: PeerBatchCompressions,
+    pub batch_diff_sync: BatchDiffSyncParameters,
+    // The batches stored recently, compared in the batch diffs.
+    pub recent_batches: RecentBatches,
+    // Serves the requests for the batches blocking the execution first.
+    pub fetch_queue: FetchQueue,
+    pub erasure_coding: ErasureCodingParameters,
+    // The chunks of the erasure coded batches being reconstructed.
+    pub batch_chunks: BatchChunks,
+    pub tx_forward_chunks: tokio::sync::mpsc::Sender<(anemo::PeerId, WorkerBatchChunkMessage)>,
+    // The batches to relay to the workers of our region, sent by the workers of other regions.
+    pub tx_relay_batches: tokio::sync::mpsc::Sender<RelayRequest>,
+    // The batches stored, replicated by our standby.
+    pub replication_log: ReplicationLog,
 }
 
-#[async_trait]
-impl<V: TransactionValidator> WorkerToWorker for WorkerReceiverHandler<V> {
-    async fn report_batch(
+impl<V> WorkerReceiverHandler<V> {
+    // Splits the given digests into chunks whose batches fit in a single response. Digests
+    // of batches missing from the store are skipped, and a batch larger than the chunk size
+    // gets a chunk of its own.
+    fn plan_batches_chunks(
         &self,
-        request: anemo::Request<WorkerBatchMessage>,
-    ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
-        let message = request.into_body();
-        if let Err(err) = self.validator.validate_batch(&message.batch).await {
+        digests: Vec<BatchDigest>,
+    ) -> Result<Vec<Vec<BatchDigest>>, anemo::rpc::Status> {
+        let max_chunk_size = self.batches_streams.max_chunk_size();
+        let mut chunks = Vec::new();
+        let mut current_chunk = Vec::new();
+        let mut current_size = 0;
+
+        for digests_chunk in digests.chunks(BATCH_DIGESTS_READ_CHUNK_SIZE) {
+            let stored_batches = self.store.multi_get(digests_chunk).map_err(|e| {
+                anemo::rpc::Status::internal(format!("failed to read from batch store: {e:?}"))
+            })?;
+
+            for (digest, stored_batch) in digests_chunk.iter().zip(stored_batches) {
+                let Some(batch) = stored_batch else {
+                    continue;
+                };
+                let batch_size = batch.size();
+                if !current_chunk.is_empty() && current_size + batch_size > max_chunk_size {
+                    chunks.push(std::mem::take(&mut current_chunk));
+                    current_size = 0;
+                }
+                current_chunk.push(*digest);
+                current_size += batch_size;
+            }
+        }
+        if !current_chunk.is_empty() || chunks.is_empty() {
+            chunks.push(current_chunk);
+        }
+        Ok(chunks)
+    }
+
+    fn read_batches(&self, digests: Vec<BatchDigest>) -> Result<Vec<Batch>, anemo::rpc::Status> {
+        let stored_batches = self.store.multi_get(digests).map_err(|e| {
+            anemo::rpc::Status::internal(format!("failed to read from batch store: {e:?}"))
+        })?;
+        // Batches removed from the store since the stream was opened are skipped.
+        Ok(stored_batches.into_iter().flatten().collect())
+    }
+
+    // Reads the requested batches up to the response size limit.
+    async fn serve_batches(
+        &self,
+        request: RequestBatchesV2Request,
+    ) -> Result<RequestBatchesV2Response, anemo::rpc::Status> {
+        let RequestBatchesV2Request {
+            batch_digests: digests_to_fetch,
+            priority,
+        } = request;
+        let _permit = self.fetch_queue.acquire(priority).await;
+        let digests_chunks = digests_to_fetch
+            .chunks(BATCH_DIGESTS_READ_CHUNK_SIZE)
+            .map(|chunk| chunk.to_vec())
+            .collect_vec();
+        let mut batches = Vec::new();
+        let mut total_size = 0;
+        let mut remaining_digests = Vec::new();
+        let mut missing_digests = Vec::new();
+
+        for digests_chunk in digests_chunks {
+            // Once the size limit is reached, the digests left are not even read from the store.
+            if !remaining_digests.is_empty() {
+                remaining_digests.extend(digests_chunk);
+                continue;
+            }
+            let stored_batches = self.store.multi_get(&digests_chunk).map_err(|e| {
+                anemo::rpc::Status::internal(format!("failed to read from batch store: {e:?}"))
+            })?;
+
+            for (digest, stored_batch) in digests_chunk.iter().zip(stored_batches) {
+                if !remaining_digests.is_empty() {
+                    remaining_digests.push(*digest);
+                    continue;
+                }
+                let Some(stored_batch) = stored_batch else {
+                    missing_digests.push(*digest);
+                    continue;
+                };
+                let batch_size = stored_batch.size();
+                // A batch larger than the limit is returned on its own, for the requester to
+                // make progress.
+                if batches.is_empty()
+                    || total_size + batch_size <= self.max_request_batches_response_size
+                {
+                    batches.push(stored_batch);
+                    total_size += batch_size;
+                } else {
+                    remaining_digests.push(*digest);
+                }
+            }
+        }
+
+        Ok(RequestBatchesV2Response {
+            batches,
+            remaining_digests,
+            missing_digests,
+        })
+    }
+}
+
+impl<V: TransactionValidator> WorkerReceiverHandler<V> {
+    // Validates and stores a batch received from another worker, and reports it to the primary.
+    async fn process_batch(&self, batch: Batch) -> Result<(), anemo::rpc::Status> {
+        if let Err(err) = self.validator.validate_batch(&batch).await {
             // The batch is invalid, we don't want to process it.
             return Err(anemo::rpc::Status::new_with_message(
                 StatusCode::BadRequest,
                 format!("Invalid batch: {err}"),
             ));
         }
-        let digest = message.batch.digest();
-        self.store.insert(&digest, &message.batch).map_err(|e| {
+        let digest = batch.digest();
+        self.store.insert(&digest, &batch).map_err(|e| {
             anemo::rpc::Status::internal(format!("failed to write to batch store: {e:?}"))
         })?;
         self.tx_others_batch
@@ -61,15 +208,126 @@ impl<V: TransactionValidator> WorkerToWorker for WorkerReceiverHandler<V> {
                 worker_id: self.id,
             })
             .await
-            .map(|_| anemo::Response::new(()))
             .map_err(|e| anemo::rpc::Status::internal(e.to_string()))
     }
+}
+
+#[async_trait]
+impl<V: TransactionValidator> WorkerToWorker for WorkerReceiverHandler<V> {
+    async fn report_batch(
+        &self,
+        request: anemo::Request<WorkerBatchMessage>,
+    ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
+        let message = request.into_body();
+        self.process_batch(message.batch)
+            .await
+            .map(|_| anemo::Response::new(()))
+    }
+
+    async fn report_batch_chunk(
+        &self,
+        request: anemo::Request<WorkerBatchChunkMessage>,
+    ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
+        let origin = request.peer_id().copied();
+        let message = request.into_body();
+        let digest = message.digest;
+        if self.store.contains_key(&digest).map_err(|e| {
+            anemo::rpc::Status::internal(format!("failed to read batch store: {e:?}"))
+        })? {
+            return Ok(anemo::Response::new(()));
+        }
+
+        let inserted = self.batch_chunks.insert(&message).map_err(|e| {
+            anemo::rpc::Status::new_with_message(StatusCode::BadRequest, e.to_string())
+        })?;
+        // The chunks sent by the originator of the batch are forwarded to the other workers,
+        // each of them receiving a different one.
+        if inserted.is_new && !message.forwarded {
+            if let Some(origin) = origin {
+                let _ = self.tx_forward_chunks.try_send((origin, message.clone()));
+            }
+        }
+        if let Some(chunks) = inserted.ready {
+            let batch = decode_batch(
+                chunks,
+                message.data_chunks as usize,
+                message.parity_chunks as usize,
+                message.batch_size as usize,
+            )
+            .and_then(|batch| {
+                anyhow::ensure!(
+                    batch.digest() == digest,
+                    "Reconstructed batch digest mismatch"
+                );
+                Ok(batch)
+            });
+            let batch = match batch {
+                Ok(batch) => batch,
+                Err(e) => {
+                    // The next chunk received retries with another subset of the chunks.
+                    self.batch_chunks
+                        .reconstruction_failed(&digest, &message.chunks_root);
+                    return Err(anemo::rpc::Status::new_with_message(
+                        StatusCode::BadRequest,
+                        format!("Cannot reconstruct batch {digest}: {e}"),
+                    ));
+                }
+            };
+            let result = self.process_batch(batch).await;
+            match &result {
+                Ok(()) => self.batch_chunks.reconstructed(&digest),
+                Err(_) => self.batch_chunks.discard(&digest, &message.chunks_root),
+            }
+            return result.map(|_| anemo::Response::new(()));
+        }
+
+        // The originator of the batch is acknowledged once the batch is reconstructed, the
+        // workers forwarding a chunk right away.
+        if message.forwarded {
+            return Ok(anemo::Response::new(()));
+        }
+        if BatchChunks::wait_reconstructed(
+            inserted.rx_reconstructed,
+            self.erasure_coding.reconstruction_timeout,
+        )
+        .await
+        {
+            Ok(anemo::Response::new(()))
+        } else {
+            Err(anemo::rpc::Status::new_with_message(
+                StatusCode::ServiceUnavailable,
+                format!("Batch {digest} not reconstructed yet"),
+            ))
+        }
+    }
+
+    async fn relay_batch(
+        &self,
+        request: anemo::Request<WorkerRelayBatchMessage>,
+    ) -> Result<anemo::Response<WorkerRelayBatchResponse>, anemo::rpc::Status> {
+        let message = request.into_body();
+        self.process_batch(message.message.batch.clone()).await?;
+
+        // The batch is relayed once stored, the acknowledgements of the workers relayed to being
+        // reported back to the originator.
+        let (tx_acknowledged, rx_acknowledged) = tokio::sync::oneshot::channel();
+        self.tx_relay_batches
+            .send((message, tx_acknowledged))
+            .await
+            .map_err(|e| anemo::rpc::Status::internal(e.to_string()))?;
+        let acknowledged = rx_acknowledged.await.unwrap_or_default();
+        Ok(anemo::Response::new(WorkerRelayBatchResponse {
+            acknowledged,
+        }))
+    }
 
     async fn request_batch(
         &self,
         request: anemo::Request<RequestBatchRequest>,
     ) -> Result<anemo::Response<RequestBatchResponse>, anemo::rpc::Status> {
         // TODO [issue #7]: Do some accounting to prevent bad actors from monopolizing our resources
+        // Single batches are requested to vote for or to certify headers, not to execute them.
+        let _permit = self.fetch_queue.acquire(FetchPriority::Uncommitted).await;
         let batch = request.into_body().batch;
         let batch = self.store.get(&batch).map_err(|e| {
             anemo::rpc::Status::internal(format!("failed to read from batch store: {e:?}"))
@@ -82,38 +340,168 @@ impl<V: TransactionValidator> WorkerToWorker for WorkerReceiverHandler<V> {
         &self,
         request: anemo::Request<RequestBatchesRequest>,
     ) -> Result<anemo::Response<RequestBatchesResponse>, anemo::rpc::Status> {
-        const MAX_REQUEST_BATCHES_RESPONSE_SIZE: usize = 6_000_000;
-        const BATCH_DIGESTS_READ_CHUNK_SIZE: usize = 200;
+        // The nodes requesting through the first version of the route only fetch the batches
+        // blocking execution.
+        let request = RequestBatchesV2Request {
+            batch_digests: request.into_body().batch_digests,
+            priority: FetchPriority::BlocksExecution,
+        };
+        let response = self.serve_batches(request).await?;
+        Ok(anemo::Response::new(RequestBatchesResponse {
+            is_size_limit_reached: response.is_size_limit_reached(),
+            batches: response.batches,
+        }))
+    }
 
-        let digests_to_fetch = request.into_body().batch_digests;
-        let digests_chunks = digests_to_fetch
-            .chunks(BATCH_DIGESTS_READ_CHUNK_SIZE)
-            .map(|chunk| chunk.to_vec())
-            .collect_vec();
-        let mut batches = Vec::new();
-        let mut total_size = 0;
-        let mut is_size_limit_reached = false;
+    async fn request_batches_v2(
+        &self,
+        request: anemo::Request<RequestBatchesV2Request>,
+    ) -> Result<anemo::Response<RequestBatchesV2Response>, anemo::rpc::Status> {
+        self.serve_batches(request.into_body())
+            .await
+            .map(anemo::Response::new)
+    }
 
-        for digests_chunks in digests_chunks {
-            let stored_batches = self.store.multi_get(digests_chunks).map_err(|e| {
-                anemo::rpc::Status::internal(format!("failed to read from batch store: {e:?}"))
+    async fn open_batches_stream(
+        &self,
+        request: anemo::Request<OpenBatchesStreamRequest>,
+    ) -> Result<anemo::Response<OpenBatchesStreamResponse>, anemo::rpc::Status> {
+        let peer = *request.peer_id().ok_or_else(|| {
+            anemo::rpc::Status::internal("Unable to identify the peer opening the stream")
+        })?;
+        // The batches are streamed to the executor of our primary.
+        let _permit = self
+            .fetch_queue
+            .acquire(FetchPriority::BlocksExecution)
+            .await;
+        let chunks = self.plan_batches_chunks(request.into_body().batch_digests)?;
+        let num_chunks = chunks.len() as u32;
+        let first_chunk = self.read_batches(chunks[0].clone())?;
+
+        // A stream is only registered when there is more to serve than the first chunk.
+        let stream_id = if num_chunks > 1 {
+            self.batches_streams.open(peer, chunks)
+        } else {
+            0
+        };
+
+        Ok(anemo::Response::new(OpenBatchesStreamResponse {
+            stream_id,
+            num_chunks,
+            first_chunk,
+        }))
+    }
+
+    async fn request_batches_chunk(
+        &self,
+        request: anemo::Request<RequestBatchesChunkRequest>,
+    ) -> Result<anemo::Response<RequestBatchesChunkResponse>, anemo::rpc::Status> {
+        let peer = *request.peer_id().ok_or_else(|| {
+            anemo::rpc::Status::internal("Unable to identify the peer requesting the chunk")
+        })?;
+        let RequestBatchesChunkRequest {
+            stream_id,
+            chunk_index,
+        } = request.into_body();
+        let digests = self
+            .batches_streams
+            .take_chunk(&peer, stream_id, chunk_index)
+            .ok_or_else(|| {
+                anemo::rpc::Status::new_with_message(
+                    StatusCode::NotFound,
+                    format!("Unknown chunk {chunk_index} of batches stream {stream_id}"),
+                )
             })?;
+        let _permit = self
+            .fetch_queue
+            .acquire(FetchPriority::BlocksExecution)
+            .await;
+        let batches = self.read_batches(digests)?;
 
-            for stored_batch in stored_batches.into_iter().flatten() {
-                let batch_size = stored_batch.size();
-                if total_size + batch_size <= MAX_REQUEST_BATCHES_RESPONSE_SIZE {
-                    batches.push(stored_batch);
-                    total_size += batch_size;
-                } else {
-                    is_size_limit_reached = true;
-                    break;
-                }
+        Ok(anemo::Response::new(RequestBatchesChunkResponse {
+            batches,
+        }))
+    }
+
+    async fn request_batches_diff(
+        &self,
+        request: anemo::Request<RequestBatchesDiffRequest>,
+    ) -> Result<anemo::Response<RequestBatchesDiffResponse>, anemo::rpc::Status> {
+        let filter = request.into_body().filter;
+        if !filter.is_well_formed() {
+            return Err(anemo::rpc::Status::new_with_message(
+                StatusCode::BadRequest,
+                "Malformed batch digest filter",
+            ));
+        }
+        let missing_digests = self
+            .recent_batches
+            .digests()
+            .into_iter()
+            .filter(|digest| !filter.contains(digest))
+            .take(self.batch_diff_sync.max_missing_batches)
+            .collect();
+
+        Ok(anemo::Response::new(RequestBatchesDiffResponse {
+            missing_digests,
+        }))
+    }
+
+    async fn negotiate_batch_compression(
+        &self,
+        request: anemo::Request<NegotiateBatchCompressionRequest>,
+    ) -> Result<anemo::Response<NegotiateBatchCompressionResponse>, anemo::rpc::Status> {
+        let peer = *request.peer_id().ok_or_else(|| {
+            anemo::rpc::Status::internal("Unable to identify the peer negotiating compression")
+        })?;
+        let selected =
+            BatchCompression::negotiate(&BatchCompression::SUPPORTED, &request.body().supported);
+        self.batch_compressions.set(peer, selected);
+
+        Ok(anemo::Response::new(NegotiateBatchCompressionResponse {
+            selected,
+        }))
+    }
+
+    async fn request_replicated_batches(
+        &self,
+        request: anemo::Request<RequestReplicatedBatchesRequest>,
+    ) -> Result<anemo::Response<RequestReplicatedBatchesResponse>, anemo::rpc::Status> {
+        let RequestReplicatedBatchesRequest {
+            log_id,
+            position,
+            max_batches,
+        } = request.into_body();
+        let (start, digests, gap) =
+            self.replication_log
+                .read(log_id, position, max_batches as usize);
+        let mut next_position = start + digests.len() as u64;
+        let stored_batches = self.store.multi_get(&digests).map_err(|e| {
+            anemo::rpc::Status::internal(format!("failed to read from batch store: {e:?}"))
+        })?;
+
+        let mut batches = Vec::new();
+        let mut total_size = 0;
+        for (i, stored_batch) in stored_batches.into_iter().enumerate() {
+            // Batches removed from the store since are not replicated.
+            let Some(batch) = stored_batch else {
+                continue;
+            };
+            if !batches.is_empty()
+                && total_size + batch.size() > self.max_request_batches_response_size
+            {
+                next_position = start + i as u64;
+                break;
             }
+            total_size += batch.size();
+            batches.push(batch);
         }
 
-        Ok(anemo::Response::new(RequestBatchesResponse {
+        Ok(anemo::Response::new(RequestReplicatedBatchesResponse {
+            log_id: self.replication_log.log_id(),
+            next_position,
             batches,
-            is_size_limit_reached,
+            gap,
         }))
     }
 }
@@ -132,10 +520,95 @@ pub struct PrimaryReceiverHandler<V> {
     pub store: DBMap<BatchDigest, Batch>,
     // Timeout on RequestBatch RPC.
     pub request_batch_timeout: Duration,
-    // Number of random nodes to query when retrying batch requests.
-    pub request_batch_retry_nodes: usize,
+    // The parameters of the worker, from which the number of random nodes to query when retrying
+    // batch requests is read, so that it can be tuned at runtime.
+    pub rx_parameters: watch::Receiver<Parameters>,
+    // Delay after which the batch requests are also sent to the next peer.
+    pub request_batch_hedge_delay: Duration,
     // Validate incoming batches
     pub validator: V,
+    // The connections with the other workers, on which the batches are requested.
+    pub connection_manager: ConnectionManager,
+    // Tracks the progress of our transactions, as reported by the primary.
+    pub transaction_status: TransactionStatusTracker,
+    // The committed and garbage collected rounds, as reported by the primary.
+    pub primary_rounds: PrimaryRounds,
+}
+
+impl<V> PrimaryReceiverHandler<V> {
+    fn collected_round(round: Round) -> anemo::rpc::Status {
+        anemo::rpc::Status::new_with_message(
+            StatusCode::BadRequest,
+            format!("Round {round} is garbage collected"),
+        )
+    }
+
+    /// The peers to request the missing batches of `message` from, in order: the worker of the
+    /// target first, then up to `sync_retry_nodes` random other workers. The peers
+    /// that were demoted because they failed to provide the batches are skipped, unless all of
+    /// them were, in which case they are given another chance.
+    async fn sync_peers(
+        &self,
+        message: &WorkerSynchronizeMessage,
+        network: &anemo::Network,
+        demoted: &mut HashSet<anemo::PeerId>,
+    ) -> Result<Vec<anemo::Peer>, anemo::rpc::Status> {
+        let target = match self.worker_cache.worker(
+            self.committee
+                .authority(&message.target)
+                .unwrap()
+                .protocol_key(),
+            &self.id,
+        ) {
+            Ok(worker_info) => worker_info.name,
+            Err(e) => {
+                return Err(anemo::rpc::Status::internal(format!(
+                    "The primary asked us to sync with an unknown node: {e}"
+                )));
+            }
+        };
+        let others: Vec<_> = self
+            .worker_cache
+            .others_workers_by_id(
+                self.committee
+                    .authority(&self.authority_id)
+                    .unwrap()
+                    .protocol_key(),
+                &self.id,
+            )
+            .into_iter()
+            .map(|(_, info)| info.name)
+            .filter(|name| *name != target)
+            .collect();
+
+        let candidates: Vec<_> = std::iter::once(target.clone())
+            .chain(others.iter().cloned())
+            .map(|name| anemo::PeerId(name.0.to_bytes()))
+            .collect();
+        if candidates.iter().all(|peer_id| demoted.contains(peer_id)) {
+            demoted.clear();
+        }
+
+        let target = anemo::PeerId(target.0.to_bytes());
+        let retry_nodes = self.rx_parameters.borrow().sync_retry_nodes;
+        let mut peers: Vec<_> = others
+            .choose_multiple(&mut rand::thread_rng(), others.len())
+            .map(|name| anemo::PeerId(name.0.to_bytes()))
+            .filter(|peer_id| !demoted.contains(peer_id))
+            .take(retry_nodes)
+            .collect();
+        if !demoted.contains(&target) {
+            peers.insert(0, target);
+        }
+        let mut reachable = Vec::with_capacity(peers.len());
+        for peer_id in peers {
+            match self.connection_manager.peer(network, peer_id).await {
+                Some(peer) => reachable.push(peer),
+                None => warn!("Unable to reach worker {peer_id} on the network"),
+            }
+        }
+        Ok(reachable)
+    }
 }
 
 #[async_trait]
@@ -165,19 +638,27 @@ impl<V: TransactionValidator> PrimaryToWorker for PrimaryReceiverHandler<V> {
             };
         }
 
-        // Keep attempting to retrieve missing batches until we get them all or the client
-        // abandons the RPC.
-        let mut first_attempt = true;
+        // Keep attempting to retrieve missing batches until we get them all, the client
+        // abandons the RPC, or the round of the header is garbage collected. The peers that
+        // failed to provide the batches are demoted for the next attempts.
+        let collected = async {
+            match message.round {
+                Some(round) => self.primary_rounds.collected(round).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(collected);
+        let mut demoted = HashSet::new();
         loop {
             if missing.is_empty() {
                 return Ok(anemo::Response::new(()));
             }
+            if let Some(round) = message.round {
+                if self.primary_rounds.is_collected(round) {
+                    return Err(Self::collected_round(round));
+                }
+            }
 
-            let batch_requests: Vec<_> = missing
-                .iter()
-                .cloned()
-                .map(|batch| RequestBatchRequest { batch })
-                .collect();
             let network = request
                 .extensions()
                 .get::<anemo::NetworkRef>()
@@ -185,123 +666,104 @@ impl<V: TransactionValidator> PrimaryToWorker for PrimaryReceiverHandler<V> {
                 .ok_or_else(|| {
                     anemo::rpc::Status::internal("Unable to access network to send child RPCs")
                 })?;
+            let mut peers: VecDeque<_> = self
+                .sync_peers(message, &network, &mut demoted)
+                .await?
+                .into();
+
+            let request_batch_fn = |peer: anemo::Peer, batch_request, timeout| {
+                let connection_manager = self.connection_manager.clone();
+                // Wrapper function enables us to move `peer` into the future.
+                monitored_future!(async move {
+                    let peer_id = peer.peer_id();
+                    let _permit = connection_manager
+                        .acquire(peer_id, Channel::Request, 0)
+                        .await;
+                    let result = WorkerToWorkerClient::new(peer)
+                        .request_batch(anemo::Request::new(batch_request).with_timeout(timeout))
+                        .await;
+                    (peer_id, result)
+                })
+            };
 
+            // Request the missing batches from the first peer, then hedge the requests to the
+            // next peer every `request_batch_hedge_delay` until all the batches are received.
+            // Returning drops the requests still in flight.
             let mut handles = FuturesUnordered::new();
-            let request_batch_fn =
-                |mut client: WorkerToWorkerClient<anemo::Peer>, batch_request, timeout| {
-                    // Wrapper function enables us to move `client` into the future.
-                    monitored_future!(async move {
-                        client
-                            .request_batch(anemo::Request::new(batch_request).with_timeout(timeout))
-                            .await
-                    })
-                };
-            if first_attempt {
-                // Send first sync request to a single node.
-                let worker_name = match self.worker_cache.worker(
-                    self.committee
-                        .authority(&message.target)
-                        .unwrap()
-                        .protocol_key(),
-                    &self.id,
-                ) {
-                    Ok(worker_info) => worker_info.name,
-                    Err(e) => {
-                        return Err(anemo::rpc::Status::internal(format!(
-                            "The primary asked us to sync with an unknown node: {e}"
-                        )));
+            let hedge_timer = sleep(Duration::ZERO);
+            tokio::pin!(hedge_timer);
+            loop {
+                tokio::select! {
+                    () = &mut hedge_timer, if !peers.is_empty() => {
+                        let peer = peers.pop_front().unwrap();
+                        let batch_requests: Vec<_> = missing
+                            .iter()
+                            .cloned()
+                            .map(|batch| RequestBatchRequest { batch })
+                            .collect();
+                        debug!(
+                            "Sending BatchRequests to worker {}: {:?}",
+                            peer.peer_id(),
+                            batch_requests
+                        );
+                        handles.extend(batch_requests.into_iter().map(|request| {
+                            request_batch_fn(peer.clone(), request, self.request_batch_timeout)
+                        }));
+                        hedge_timer
+                            .as_mut()
+                            .reset(Instant::now() + self.request_batch_hedge_delay);
                     }
-                };
-                let peer_id = anemo::PeerId(worker_name.0.to_bytes());
-                if let Some(peer) = network.peer(peer_id) {
-                    debug!(
-                        "Sending BatchRequests to {worker_name}: {:?}",
-                        batch_requests
-                    );
-                    handles.extend(batch_requests.into_iter().map(|request| {
-                        request_batch_fn(
-                            WorkerToWorkerClient::new(peer.clone()),
-                            request,
-                            self.request_batch_timeout,
-                        )
-                    }));
-                } else {
-                    warn!("Unable to reach primary peer {worker_name} on the network");
-                }
-            } else {
-                // If first request timed out or was missing batches, try broadcasting to some others.
-                let names: Vec<_> = self
-                    .worker_cache
-                    .others_workers_by_id(
-                        self.committee
-                            .authority(&self.authority_id)
-                            .unwrap()
-                            .protocol_key(),
-                        &self.id,
-                    )
-                    .into_iter()
-                    .map(|(_, info)| info.name)
-                    .collect();
-                handles.extend(
-                    names
-                        .choose_multiple(&mut rand::thread_rng(), self.request_batch_retry_nodes)
-                        .filter_map(|name| network.peer(anemo::PeerId(name.0.to_bytes())))
-                        .flat_map(|peer| {
-                            batch_requests.iter().cloned().map(move |request| {
-                                let peer = peer.clone();
-                                request_batch_fn(
-                                    WorkerToWorkerClient::new(peer),
-                                    request,
-                                    self.request_batch_timeout,
-                                )
-                            })
-                        }),
-                );
-                debug!(
-                    "Sending BatchRequest retries to workers {names:?}: {:?}",
-                    batch_requests
-                );
-            }
 
-            // Fire off batch request(s) and process results. Stop as soon as we have all the
-            // missing batches.
-            while let Some(result) = handles.next().await {
-                match result {
-                    Ok(response) => {
-                        if let Some(batch) = response.into_body().batch {
-                            if !message.is_certified {
-                                // This batch is not part of a certificate, so we need to validate it.
-                                if let Err(err) = self.validator.validate_batch(&batch).await {
-                                    // The batch is invalid, we don't want to process it.
-                                    return Err(anemo::rpc::Status::new_with_message(
-                                        StatusCode::BadRequest,
-                                        format!("Invalid batch: {err}"),
-                                    ));
+                    Some((peer_id, result)) = handles.next() => {
+                        match result {
+                            Ok(response) => match response.into_body().batch {
+                                Some(batch) => {
+                                    if !message.is_certified {
+                                        // This batch is not part of a certificate, so we need to validate it.
+                                        if let Err(err) = self.validator.validate_batch(&batch).await {
+                                            // The batch is invalid, we don't want to process it.
+                                            return Err(anemo::rpc::Status::new_with_message(
+                                                StatusCode::BadRequest,
+                                                format!("Invalid batch: {err}"),
+                                            ));
+                                        }
+                                    }
+                                    let digest = batch.digest();
+                                    if missing.remove(&digest) {
+                                        self.store.insert(&digest, &batch).map_err(|e| {
+                                            anemo::rpc::Status::internal(format!(
+                                                "failed to write to batch store: {e:?}"
+                                            ))
+                                        })?;
+                                    }
+                                    if missing.is_empty() {
+                                        return Ok(anemo::Response::new(()));
+                                    }
                                 }
-                            }
-                            let digest = batch.digest();
-                            if missing.remove(&digest) {
-                                self.store.insert(&digest, &batch).map_err(|e| {
-                                    anemo::rpc::Status::internal(format!(
-                                        "failed to write to batch store: {e:?}"
-                                    ))
-                                })?;
+                                None => {
+                                    demoted.insert(peer_id);
+                                }
+                            },
+                            Err(e) => {
+                                info!("RequestBatchRequest to worker {peer_id:?} failed: {e:?}");
+                                demoted.insert(peer_id);
                             }
                         }
-                        if missing.is_empty() {
-                            return Ok(anemo::Response::new(()));
+                        // Do not wait for the hedge delay if no request is in flight anymore.
+                        if handles.is_empty() {
+                            hedge_timer.as_mut().reset(Instant::now());
                         }
                     }
-                    Err(e) => {
-                        info!(
-                            "RequestBatchRequest to worker {:?} failed: {e:?}",
-                            e.peer_id()
-                        )
+
+                    // Only while requests are pending, so that the loop still ends otherwise.
+                    () = &mut collected, if !peers.is_empty() || !handles.is_empty() => {
+                        return Err(Self::collected_round(message.round.unwrap_or_default()));
                     }
+
+                    else => break,
                 }
             }
 
-            first_attempt = false;
             // Add a delay before retrying.
             sleep(Duration::from_secs(1)).await;
         }
@@ -318,4 +780,24 @@ impl<V: TransactionValidator> PrimaryToWorker for PrimaryReceiverHandler<V> {
         }
         Ok(anemo::Response::new(()))
     }
+
+    async fn report_batch_status(
+        &self,
+        request: anemo::Request<WorkerBatchStatusMessage>,
+    ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
+        let message = request.into_body();
+        let stage = TransactionStage::from(message.status);
+        for digest in &message.digests {
+            self.transaction_status.update(digest, stage);
+        }
+        Ok(anemo::Response::new(()))
+    }
+
+    async fn report_rounds(
+        &self,
+        request: anemo::Request<WorkerRoundsMessage>,
+    ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
+        self.primary_rounds.update(request.into_body());
+        Ok(anemo::Response::new(()))
+    }
 }
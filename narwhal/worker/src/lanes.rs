@@ -0,0 +1,164 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use config::LaneSchedulingPolicy;
+use futures::future::poll_fn;
+use prometheus::{IntCounter, IntGauge};
+use std::task::Poll;
+use types::{
+    metered_channel::{channel_with_total, Receiver, Sender},
+    PriorityLane, Transaction, TxResponse,
+};
+
+#[cfg(test)]
+#[path = "tests/lanes_tests.rs"]
+pub mod lanes_tests;
+
+/// Holds one value per priority lane.
+#[derive(Clone, Debug, Default)]
+pub struct Lanes<T> {
+    system: T,
+    high: T,
+    normal: T,
+}
+
+impl<T> Lanes<T> {
+    pub fn new(mut f: impl FnMut(PriorityLane) -> T) -> Self {
+        Self {
+            system: f(PriorityLane::System),
+            high: f(PriorityLane::High),
+            normal: f(PriorityLane::Normal),
+        }
+    }
+
+    pub fn get(&self, lane: PriorityLane) -> &T {
+        match lane {
+            PriorityLane::System => &self.system,
+            PriorityLane::High => &self.high,
+            PriorityLane::Normal => &self.normal,
+        }
+    }
+
+    pub fn get_mut(&mut self, lane: PriorityLane) -> &mut T {
+        match lane {
+            PriorityLane::System => &mut self.system,
+            PriorityLane::High => &mut self.high,
+            PriorityLane::Normal => &mut self.normal,
+        }
+    }
+}
+
+pub type LaneSenders = Lanes<Sender<(Transaction, TxResponse)>>;
+pub type LaneReceivers = Lanes<Receiver<(Transaction, TxResponse)>>;
+
+/// Creates the channels feeding the batch maker, one per priority lane. All the channels
+/// report to the same metrics.
+pub fn lane_channels(
+    size: usize,
+    gauge: &IntGauge,
+    total_gauge: &IntCounter,
+) -> (LaneSenders, LaneReceivers) {
+    let mut receivers = Vec::new();
+    let senders = Lanes::new(|_| {
+        let (sender, receiver) = channel_with_total(size, gauge, total_gauge);
+        receivers.push(receiver);
+        sender
+    });
+    let mut receivers = receivers.into_iter();
+    let receivers = Lanes::new(|_| receivers.next().unwrap());
+    (senders, receivers)
+}
+
+/// Decides which lane the batch maker takes its next transaction from.
+pub struct LaneScheduler {
+    policy: LaneSchedulingPolicy,
+    // The lane served by the weighted round robin, and the number of transactions it can
+    // still take before its turn ends. Unused with strict priority.
+    current: usize,
+    credits: u32,
+}
+
+impl LaneScheduler {
+    pub fn new(policy: LaneSchedulingPolicy) -> Self {
+        let mut scheduler = Self {
+            policy,
+            current: 0,
+            credits: 0,
+        };
+        scheduler.credits = scheduler.weight(PriorityLane::ALL[0]);
+        scheduler
+    }
+
+    fn weight(&self, lane: PriorityLane) -> u32 {
+        match self.policy {
+            LaneSchedulingPolicy::StrictPriority => 1,
+            LaneSchedulingPolicy::WeightedRoundRobin {
+                system,
+                high,
+                normal,
+            } => match lane {
+                PriorityLane::System => system,
+                PriorityLane::High => high,
+                PriorityLane::Normal => normal,
+            }
+            .max(1),
+        }
+    }
+
+    /// The order in which the lanes should be checked for the next transaction.
+    pub fn order(&self) -> [PriorityLane; 3] {
+        match self.policy {
+            LaneSchedulingPolicy::StrictPriority => PriorityLane::ALL,
+            LaneSchedulingPolicy::WeightedRoundRobin { .. } => {
+                let lanes = PriorityLane::ALL;
+                [0, 1, 2].map(|offset| lanes[(self.current + offset) % lanes.len()])
+            }
+        }
+    }
+
+    /// Records that a transaction was taken from `lane`.
+    pub fn record(&mut self, lane: PriorityLane) {
+        if self.policy == LaneSchedulingPolicy::StrictPriority {
+            return;
+        }
+        let lanes = PriorityLane::ALL;
+        let index = lanes.iter().position(|l| *l == lane).unwrap();
+        // The lanes ahead in the rotation were empty, so their turn is over.
+        if index != self.current {
+            self.current = index;
+            self.credits = self.weight(lane);
+        }
+        self.credits = self.credits.saturating_sub(1);
+        if self.credits == 0 {
+            self.current = (index + 1) % lanes.len();
+            self.credits = self.weight(lanes[self.current]);
+        }
+    }
+
+    /// Waits for the next transaction of any lane. When several lanes have pending
+    /// transactions, the one coming first in `order()` is served. Returns None once all the
+    /// lanes are closed.
+    pub async fn next_transaction(
+        &mut self,
+        receivers: &mut LaneReceivers,
+    ) -> Option<(PriorityLane, Transaction, TxResponse)> {
+        let order = self.order();
+        let (lane, (transaction, response)) = poll_fn(|cx| {
+            let mut closed = 0;
+            for lane in order {
+                match receivers.get_mut(lane).poll_recv(cx) {
+                    Poll::Ready(Some(message)) => return Poll::Ready(Some((lane, message))),
+                    Poll::Ready(None) => closed += 1,
+                    Poll::Pending => {}
+                }
+            }
+            if closed == order.len() {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            }
+        })
+        .await?;
+        self.record(lane);
+        Some((lane, transaction, response))
+    }
+}
@@ -8,11 +8,28 @@
     rust_2021_compatibility
 )]
 
+mod adaptive_sealing;
+mod admission_control;
+mod batch_compression;
+mod batch_diff_sync;
 mod batch_maker;
+mod batches_streams;
 mod client;
+mod client_quotas;
+mod drain;
+mod erasure_coding;
+mod fetch_queue;
 mod handlers;
+mod lanes;
+mod pending_transactions;
 mod primary_connector;
+mod primary_rounds;
 mod quorum_waiter;
+mod spill_queue;
+mod standby;
+mod topology;
+mod transaction_log;
+mod transaction_status;
 mod transactions_server;
 mod tx_validator;
 mod worker;
@@ -20,8 +37,14 @@ mod worker;
 pub mod metrics;
 
 pub use crate::client::LocalNarwhalClient;
-pub use crate::tx_validator::{TransactionValidator, TrivialTransactionValidator};
+pub use crate::drain::{drain_channel, DrainHandle, TransactionHandoff, WorkerDrain};
+pub use crate::pending_transactions::{PendingTransaction, PendingTransactions};
+pub use crate::standby::WorkerStandby;
+pub use crate::transaction_status::TransactionStatusTracker;
+pub use crate::tx_validator::{
+    BatchVerdictCache, TransactionValidator, TrivialTransactionValidator,
+};
 pub use crate::worker::Worker;
 
 /// The number of shutdown receivers to create on startup. We need one per component loop.
-pub const NUM_SHUTDOWN_RECEIVERS: u64 = 25;
+pub const NUM_SHUTDOWN_RECEIVERS: u64 = 30;
@@ -1,7 +1,9 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use mysten_network::metrics::MetricsCallbackProvider;
-use network::metrics::{NetworkConnectionMetrics, NetworkMetrics};
+use network::metrics::{
+    ConnectionManagerMetrics, NetworkConnectionMetrics, NetworkMetrics, PeerMetrics,
+};
 use prometheus::{
     default_registry, register_histogram_vec_with_registry, register_int_counter_vec_with_registry,
     register_int_counter_with_registry, register_int_gauge_with_registry, HistogramVec, IntCounter,
@@ -24,6 +26,8 @@ pub struct Metrics {
     pub inbound_network_metrics: Option<NetworkMetrics>,
     pub outbound_network_metrics: Option<NetworkMetrics>,
     pub network_connection_metrics: Option<NetworkConnectionMetrics>,
+    pub connection_manager_metrics: Option<ConnectionManagerMetrics>,
+    pub peer_metrics: Option<PeerMetrics>,
 }
 
 /// Initialises the metrics
@@ -44,6 +48,12 @@ pub fn initialise_metrics(metrics_registry: &Registry) -> Metrics {
     // Network metrics for the worker connection
     let network_connection_metrics = NetworkConnectionMetrics::new("worker", metrics_registry);
 
+    // Metrics for the channels multiplexed on the connections with the other workers
+    let connection_manager_metrics = ConnectionManagerMetrics::new("worker", metrics_registry);
+
+    // The network health of the connections with each peer
+    let peer_metrics = PeerMetrics::new("worker", metrics_registry);
+
     Metrics {
         worker_metrics: Some(node_metrics),
         channel_metrics: Some(channel_metrics),
@@ -51,6 +61,8 @@ pub fn initialise_metrics(metrics_registry: &Registry) -> Metrics {
         inbound_network_metrics: Some(inbound_network_metrics),
         outbound_network_metrics: Some(outbound_network_metrics),
         network_connection_metrics: Some(network_connection_metrics),
+        connection_manager_metrics: Some(connection_manager_metrics),
+        peer_metrics: Some(peer_metrics),
     }
 }
 
@@ -62,6 +74,44 @@ pub struct WorkerMetrics {
     pub created_batch_latency: HistogramVec,
     /// The number of parallel worker batches currently processed by the worker
     pub parallel_worker_batches: IntGauge,
+    /// The number of transactions rejected as duplicates by the batch_maker
+    pub duplicate_transactions_rejected: IntCounter,
+    /// The number of transactions batched by the batch_maker, per priority lane
+    pub batched_transactions: IntCounterVec,
+    /// The bytes of the admitted transactions not yet included in a batch acknowledged by a quorum
+    pub pending_batch_bytes: IntGauge,
+    /// The number of transactions rejected because the worker is overloaded
+    pub overloaded_transactions_rejected: IntCounter,
+    /// The number of locally assembled batches rejected by the transaction validator
+    pub invalid_batches_rejected: IntCounter,
+    /// The number of expired transactions dropped instead of batched
+    pub expired_transactions_dropped: IntCounter,
+    /// The number of missing batches fetched from other workers by the batch diff sync
+    pub batch_diff_sync_fetched_batches: IntCounter,
+    /// The number of transactions handed off to the worker of the next epoch when draining
+    pub handed_off_transactions: IntCounter,
+    /// The number of logged transactions batched again when the worker restarted
+    pub recovered_transactions: IntCounter,
+    /// The number of transactions spilled to disk while the worker was overloaded
+    pub spilled_transactions: IntCounter,
+    /// The number of spilled transactions restored to the batch maker
+    pub restored_spilled_transactions: IntCounter,
+    /// The bytes of the spilled transactions not restored yet
+    pub spilled_transaction_bytes: IntGauge,
+    /// The number of transactions rejected because their client exceeded a quota, per quota
+    pub client_quota_rejected_transactions: IntCounterVec,
+    /// The number of clients whose quotas are tracked
+    pub client_quota_tracked_clients: IntGauge,
+    /// The moving average of the bytes of transactions received by the batch maker, per second
+    pub batch_ingress_rate: IntGauge,
+    /// The size (in bytes) at which the batch maker currently seals its batches
+    pub batch_size_threshold: IntGauge,
+    /// The delay (in ms) after which the batch maker currently seals its batches
+    pub batch_delay_threshold: IntGauge,
+    /// The latency of the stages of the transactions' pipeline within the worker, per stage
+    pub worker_pipeline_stage_latency: HistogramVec,
+    /// The time the requests for batches waited to be served, per fetch priority
+    pub fetch_queue_wait_latency: HistogramVec,
 }
 
 impl WorkerMetrics {
@@ -103,6 +153,126 @@ impl WorkerMetrics {
                 registry
             )
             .unwrap(),
+            duplicate_transactions_rejected: register_int_counter_with_registry!(
+                "duplicate_transactions_rejected",
+                "The number of transactions rejected as duplicates of recently received ones",
+                registry
+            )
+            .unwrap(),
+            batched_transactions: register_int_counter_vec_with_registry!(
+                "batched_transactions",
+                "The number of transactions added to a batch, per priority lane",
+                &["lane"],
+                registry
+            )
+            .unwrap(),
+            pending_batch_bytes: register_int_gauge_with_registry!(
+                "pending_batch_bytes",
+                "The bytes of the admitted transactions not yet included in a batch acknowledged by a quorum",
+                registry
+            )
+            .unwrap(),
+            overloaded_transactions_rejected: register_int_counter_with_registry!(
+                "overloaded_transactions_rejected",
+                "The number of transactions rejected because the worker is overloaded",
+                registry
+            )
+            .unwrap(),
+            invalid_batches_rejected: register_int_counter_with_registry!(
+                "invalid_batches_rejected",
+                "The number of locally assembled batches rejected by the transaction validator",
+                registry
+            )
+            .unwrap(),
+            expired_transactions_dropped: register_int_counter_with_registry!(
+                "expired_transactions_dropped",
+                "The number of expired transactions dropped instead of batched",
+                registry
+            )
+            .unwrap(),
+            batch_diff_sync_fetched_batches: register_int_counter_with_registry!(
+                "batch_diff_sync_fetched_batches",
+                "The number of missing batches fetched from other workers by the batch diff sync",
+                registry
+            )
+            .unwrap(),
+            handed_off_transactions: register_int_counter_with_registry!(
+                "handed_off_transactions",
+                "The number of transactions handed off to the next epoch worker when draining",
+                registry
+            )
+            .unwrap(),
+            recovered_transactions: register_int_counter_with_registry!(
+                "recovered_transactions",
+                "The number of logged transactions batched again when the worker restarted",
+                registry
+            )
+            .unwrap(),
+            spilled_transactions: register_int_counter_with_registry!(
+                "spilled_transactions",
+                "The number of transactions spilled to disk while the worker was overloaded",
+                registry
+            )
+            .unwrap(),
+            restored_spilled_transactions: register_int_counter_with_registry!(
+                "restored_spilled_transactions",
+                "The number of spilled transactions restored to the batch maker",
+                registry
+            )
+            .unwrap(),
+            spilled_transaction_bytes: register_int_gauge_with_registry!(
+                "spilled_transaction_bytes",
+                "The bytes of the spilled transactions not restored yet",
+                registry
+            )
+            .unwrap(),
+            client_quota_rejected_transactions: register_int_counter_vec_with_registry!(
+                "client_quota_rejected_transactions",
+                "The number of transactions rejected because their client exceeded a quota",
+                &["quota"],
+                registry
+            )
+            .unwrap(),
+            client_quota_tracked_clients: register_int_gauge_with_registry!(
+                "client_quota_tracked_clients",
+                "The number of clients whose quotas are tracked",
+                registry
+            )
+            .unwrap(),
+            batch_ingress_rate: register_int_gauge_with_registry!(
+                "batch_ingress_rate",
+                "The moving average of the bytes of transactions received by the batch maker, per second",
+                registry
+            )
+            .unwrap(),
+            batch_size_threshold: register_int_gauge_with_registry!(
+                "batch_size_threshold",
+                "The size (in bytes) at which the batch maker currently seals its batches",
+                registry
+            )
+            .unwrap(),
+            batch_delay_threshold: register_int_gauge_with_registry!(
+                "batch_delay_threshold",
+                "The delay (in ms) after which the batch maker currently seals its batches",
+                registry
+            )
+            .unwrap(),
+            worker_pipeline_stage_latency: register_histogram_vec_with_registry!(
+                "worker_pipeline_stage_latency",
+                "The latency of the stages of the transactions' pipeline, since the previous stage",
+                &["stage"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry
+            )
+            .unwrap(),
+            fetch_queue_wait_latency: register_histogram_vec_with_registry!(
+                "fetch_queue_wait_latency",
+                "The time the requests for batches waited to be served, per fetch priority",
+                &["priority"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry
+            )
+            .unwrap(),
         }
     }
 }
@@ -0,0 +1,92 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::transaction_status::{TransactionDigest, TransactionStatusTracker};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+use types::PriorityLane;
+
+#[cfg(test)]
+#[path = "tests/pending_transactions_tests.rs"]
+pub mod pending_transactions_tests;
+
+/// A transaction submitted to the worker, and not yet in a batch acknowledged by a quorum.
+#[derive(Clone, Debug)]
+pub struct PendingTransaction {
+    /// Increases with the order the transactions were submitted in, to paginate over them.
+    pub id: u64,
+    pub digest: TransactionDigest,
+    pub size: usize,
+    pub lane: PriorityLane,
+    pub submitted_at: Instant,
+}
+
+/// The transactions pending in the ingress queues of the worker, so that operators can inspect
+/// what is queued when the pipeline is clogged. The spilled transactions are not tracked, the
+/// spill queue being inspected through its metrics.
+#[derive(Default)]
+pub struct PendingTransactions {
+    next_id: AtomicU64,
+    pending: Mutex<BTreeMap<u64, PendingTransaction>>,
+}
+
+impl PendingTransactions {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Tracks the transaction until the returned guard is dropped.
+    pub fn track(self: &Arc<Self>, transaction: &[u8], lane: PriorityLane) -> PendingGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let pending = PendingTransaction {
+            id,
+            digest: TransactionStatusTracker::digest(transaction),
+            size: transaction.len(),
+            lane,
+            submitted_at: Instant::now(),
+        };
+        self.pending.lock().unwrap().insert(id, pending);
+        PendingGuard {
+            pending: self.clone(),
+            id,
+        }
+    }
+
+    /// Lists up to `limit` of the pending transactions, the oldest first, starting after the
+    /// transaction with id `cursor` if set.
+    pub fn list(&self, cursor: Option<u64>, limit: usize) -> Vec<PendingTransaction> {
+        let start = cursor.map_or(0, |cursor| cursor.saturating_add(1));
+        self.pending
+            .lock()
+            .unwrap()
+            .range(start..)
+            .take(limit)
+            .map(|(_, pending)| pending.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Stops tracking the transaction when dropped.
+pub struct PendingGuard {
+    pending: Arc<PendingTransactions>,
+    id: u64,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.pending.pending.lock().unwrap().remove(&self.id);
+    }
+}
@@ -2,6 +2,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::{batch_diff_sync::RecentBatches, standby::ReplicationLog};
 use crypto::NetworkPublicKey;
 use futures::{stream::FuturesUnordered, StreamExt};
 use mysten_metrics::{monitored_future, spawn_logged_monitored_task};
@@ -26,6 +27,10 @@ pub struct PrimaryConnector {
     rx_others_batch: Receiver<WorkerOthersBatchMessage>,
     /// A network sender to send the batches' digests to the primary.
     primary_client: anemo::Network,
+    /// Logs the batches stored, for our standby to replicate them.
+    replication_log: ReplicationLog,
+    /// Indexes the batches stored, for the batch diffs with the other workers.
+    recent_batches: RecentBatches,
 }
 
 impl PrimaryConnector {
@@ -36,6 +41,8 @@ impl PrimaryConnector {
         rx_our_batch: Receiver<(WorkerOurBatchMessage, PrimaryResponse)>,
         rx_others_batch: Receiver<WorkerOthersBatchMessage>,
         primary_client: anemo::Network,
+        replication_log: ReplicationLog,
+        recent_batches: RecentBatches,
     ) -> JoinHandle<()> {
         spawn_logged_monitored_task!(
             async move {
@@ -45,6 +52,8 @@ impl PrimaryConnector {
                     rx_our_batch,
                     rx_others_batch,
                     primary_client,
+                    replication_log,
+                    recent_batches,
                 }
                 .run()
                 .await;
@@ -64,6 +73,8 @@ impl PrimaryConnector {
             tokio::select! {
                 // Send the digest through the network.
                 Some((batch, response)) = self.rx_our_batch.recv() => {
+                    self.replication_log.append(batch.digest);
+                    self.recent_batches.append(batch.digest);
                     if futures.len() >= MAX_PENDING_DIGESTS {
                         tracing::warn!("Primary unreachable: dropping {batch:?}");
                         continue;
@@ -73,6 +84,8 @@ impl PrimaryConnector {
                     futures.push( monitor(handle_future(handle, response)) );
                 },
                 Some(batch) = self.rx_others_batch.recv() => {
+                    self.replication_log.append(batch.digest);
+                    self.recent_batches.append(batch.digest);
                     if futures.len() >= MAX_PENDING_DIGESTS {
                         tracing::warn!("Primary unreachable: dropping {batch:?}");
                         continue;
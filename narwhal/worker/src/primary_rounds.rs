@@ -0,0 +1,65 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use std::sync::Arc;
+use tokio::sync::watch;
+use types::{Round, WorkerRoundsMessage};
+
+#[cfg(test)]
+#[path = "tests/primary_rounds_tests.rs"]
+pub mod primary_rounds_tests;
+
+/// The last committed and garbage collected rounds of our primary, as it reports them. The work
+/// on behalf of the headers at garbage collected rounds is abandoned, since they can never be
+/// committed anymore.
+#[derive(Clone)]
+pub struct PrimaryRounds {
+    tx_rounds: Arc<watch::Sender<WorkerRoundsMessage>>,
+}
+
+impl Default for PrimaryRounds {
+    fn default() -> Self {
+        Self {
+            tx_rounds: Arc::new(watch::channel(WorkerRoundsMessage::default()).0),
+        }
+    }
+}
+
+impl PrimaryRounds {
+    /// Records the rounds reported by the primary. The rounds never go back, in case the reports
+    /// are received out of order.
+    pub fn update(&self, rounds: WorkerRoundsMessage) {
+        self.tx_rounds.send_if_modified(|current| {
+            let updated = WorkerRoundsMessage {
+                committed_round: current.committed_round.max(rounds.committed_round),
+                gc_round: current.gc_round.max(rounds.gc_round),
+            };
+            let modified = updated != *current;
+            *current = updated;
+            modified
+        });
+    }
+
+    pub fn get(&self) -> WorkerRoundsMessage {
+        *self.tx_rounds.borrow()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<WorkerRoundsMessage> {
+        self.tx_rounds.subscribe()
+    }
+
+    /// Whether the round is garbage collected.
+    pub fn is_collected(&self, round: Round) -> bool {
+        round <= self.get().gc_round
+    }
+
+    /// Resolves once the round is garbage collected.
+    pub async fn collected(&self, round: Round) {
+        let mut rx_rounds = self.subscribe();
+        while rx_rounds.borrow_and_update().gc_round < round {
+            if rx_rounds.changed().await.is_err() {
+                // The rounds are no longer updated.
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
@@ -2,16 +2,33 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::batch_maker::MAX_PARALLEL_BATCH;
-use config::{Authority, Committee, Stake, WorkerCache, WorkerId};
+use crate::{
+    batch_compression::PeerBatchCompressions,
+    batch_maker::MAX_PARALLEL_BATCH,
+    erasure_coding::{chunk_counts, encode_batch},
+    topology::{plan_broadcast, Peer},
+};
+use anemo::PeerId;
+use config::{
+    Authority, Committee, ErasureCodingParameters, Stake, TopologyHintsParameters, WorkerCache,
+    WorkerId,
+};
 use fastcrypto::hash::Hash;
-use futures::stream::{futures_unordered::FuturesUnordered, StreamExt as _};
+use futures::{
+    future::BoxFuture,
+    stream::{futures_unordered::FuturesUnordered, StreamExt as _},
+    FutureExt as _,
+};
 use mysten_metrics::{monitored_future, spawn_logged_monitored_task};
-use network::{CancelOnDropHandler, ReliableNetwork};
-use std::time::Duration;
+use network::{connection_manager::ConnectionManager, CancelOnDropHandler};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tap::TapFallible;
 use tokio::{task::JoinHandle, time::timeout};
 use tracing::{trace, warn};
-use types::{metered_channel::Receiver, Batch, ConditionalBroadcastReceiver, WorkerBatchMessage};
+use types::{
+    metered_channel::Receiver, Batch, ConditionalBroadcastReceiver, WorkerBatchChunkMessage,
+    WorkerBatchMessage, WorkerRelayBatchMessage,
+};
 
 #[cfg(test)]
 #[path = "tests/quorum_waiter_tests.rs"]
@@ -33,6 +50,14 @@ pub struct QuorumWaiter {
     rx_quorum_waiter: Receiver<(Batch, tokio::sync::oneshot::Sender<()>)>,
     /// A network sender to broadcast the batches to the other workers.
     network: anemo::Network,
+    /// The compression codec negotiated with each of the other workers.
+    batch_compressions: PeerBatchCompressions,
+    /// The connections with the other workers, on which the batches are broadcast.
+    connection_manager: ConnectionManager,
+    /// Whether the large batches are broadcast as erasure coded chunks.
+    erasure_coding: ErasureCodingParameters,
+    /// The regions of the other workers, to relay the batches through.
+    topology_hints: TopologyHintsParameters,
 }
 
 impl QuorumWaiter {
@@ -46,6 +71,10 @@ impl QuorumWaiter {
         rx_shutdown: ConditionalBroadcastReceiver,
         rx_quorum_waiter: Receiver<(Batch, tokio::sync::oneshot::Sender<()>)>,
         network: anemo::Network,
+        batch_compressions: PeerBatchCompressions,
+        connection_manager: ConnectionManager,
+        erasure_coding: ErasureCodingParameters,
+        topology_hints: TopologyHintsParameters,
     ) -> JoinHandle<()> {
         spawn_logged_monitored_task!(
             async move {
@@ -57,6 +86,10 @@ impl QuorumWaiter {
                     rx_shutdown,
                     rx_quorum_waiter,
                     network,
+                    batch_compressions,
+                    connection_manager,
+                    erasure_coding,
+                    topology_hints,
                 }
                 .run()
                 .await;
@@ -74,6 +107,113 @@ impl QuorumWaiter {
         deliver
     }
 
+    // Sends the whole batch to each worker, compressed with the codec negotiated with it. The
+    // workers of the other regions are sent the batch through a relay, following the topology
+    // hints. Returns a future per worker, resolving to its stake once it acknowledged the batch.
+    fn broadcast_batch(&self, batch: &Batch, workers: Vec<Peer>) -> Vec<BoxFuture<'static, Stake>> {
+        let (direct, relayed) =
+            plan_broadcast(&self.topology_hints, self.authority.protocol_key(), workers);
+        let mut messages = HashMap::new();
+        let mut acknowledged: Vec<_> = direct
+            .into_iter()
+            .map(|(authority, name)| {
+                let compression = self.batch_compressions.get(&PeerId(name.0.to_bytes()));
+                let message = messages
+                    .entry(compression)
+                    .or_insert_with(|| WorkerBatchMessage {
+                        batch: batch.clone(),
+                        compression,
+                    });
+                let handler = self
+                    .connection_manager
+                    .broadcast(&self.network, name, message);
+                let stake = self.committee.stake(&authority);
+                monitored_future!(Self::waiter(handler, stake)).boxed()
+            })
+            .collect();
+        if !relayed.is_empty() {
+            let batch = Arc::new(batch.clone());
+            for group in relayed {
+                acknowledged.extend(self.relay_batch(&batch, group));
+            }
+        }
+        acknowledged
+    }
+
+    // Sends the batch to a worker of the group, which relays it to the others. The workers the
+    // relay does not report to have acknowledged the batch are then sent the batch directly.
+    fn relay_batch(&self, batch: &Arc<Batch>, group: Vec<Peer>) -> Vec<BoxFuture<'static, Stake>> {
+        // The relay changes with the batches, to spread the load of relaying over the group.
+        let relay = batch.digest().0[0] as usize % group.len();
+        let relay_name = group[relay].1.clone();
+        let relay_to: Vec<_> = group
+            .iter()
+            .filter(|(_, name)| *name != relay_name)
+            .map(|(_, name)| name.clone())
+            .collect();
+        let message = WorkerRelayBatchMessage {
+            message: WorkerBatchMessage {
+                batch: batch.as_ref().clone(),
+                compression: self
+                    .batch_compressions
+                    .get(&PeerId(relay_name.0.to_bytes())),
+            },
+            relay_to: relay_to.clone(),
+        };
+        // None if the relay failed, in which case the whole group is sent the batch directly.
+        let relayed = self
+            .connection_manager
+            .relay_batch(&self.network, relay_name.clone(), message)
+            .map(move |result| {
+                result.ok().map(|response| {
+                    let mut acknowledged = response.into_body().acknowledged;
+                    // Only the workers relayed to are trusted to be acknowledged.
+                    acknowledged.retain(|name| relay_to.contains(name));
+                    acknowledged.push(relay_name);
+                    acknowledged
+                })
+            })
+            .shared();
+
+        group
+            .into_iter()
+            .map(|(authority, name)| {
+                let stake = self.committee.stake(&authority);
+                let relayed = relayed.clone();
+                let batch = batch.clone();
+                let network = self.network.clone();
+                let connection_manager = self.connection_manager.clone();
+                let compression = self.batch_compressions.get(&PeerId(name.0.to_bytes()));
+                async move {
+                    if relayed.await.map_or(true, |acked| !acked.contains(&name)) {
+                        let message = WorkerBatchMessage {
+                            batch: batch.as_ref().clone(),
+                            compression,
+                        };
+                        let _ = connection_manager.broadcast(&network, name, &message).await;
+                    }
+                    stake
+                }
+                .boxed()
+            })
+            .collect()
+    }
+
+    // Splits the batch in a chunk per worker, if it is large enough to be erasure coded.
+    fn encode_chunks(
+        &self,
+        batch: &Batch,
+        num_workers: usize,
+    ) -> Option<Vec<WorkerBatchChunkMessage>> {
+        if !self.erasure_coding.enabled || batch.size() < self.erasure_coding.min_batch_size {
+            return None;
+        }
+        let (data_chunks, parity_chunks) = chunk_counts(num_workers)?;
+        encode_batch(batch, batch.digest(), data_chunks, parity_chunks)
+            .tap_err(|e| warn!("Failed to erasure code batch {}: {e}", batch.digest()))
+            .ok()
+    }
+
     /// Main loop.
     async fn run(&mut self) {
         let mut pipeline = FuturesUnordered::new();
@@ -94,19 +234,24 @@ impl QuorumWaiter {
                         .into_iter()
                         .map(|(name, info)| (name, info.name))
                         .collect();
-                    let (primary_names, worker_names): (Vec<_>, _) = workers.into_iter().unzip();
-                    let message  = WorkerBatchMessage{batch: batch.clone()};
-                    let handlers = self.network.broadcast(worker_names, &message);
-
                     // Collect all the handlers to receive acknowledgements.
-                    let mut wait_for_quorum: FuturesUnordered<_> = primary_names
-                        .into_iter()
-                        .zip(handlers.into_iter())
-                        .map(|(name, handler)| {
-                            let stake = self.committee.stake(&name);
-                            monitored_future!(Self::waiter(handler, stake))
-                        })
-                        .collect();
+                    let chunks = self.encode_chunks(&batch, workers.len());
+                    let mut wait_for_quorum: FuturesUnordered<_> = match chunks {
+                        // Each worker is sent a different chunk of the batch, and forwards it to
+                        // the others.
+                        Some(chunks) => workers
+                            .into_iter()
+                            .zip(chunks)
+                            .map(|((authority, name), chunk)| {
+                                let handler = self
+                                    .connection_manager
+                                    .broadcast_chunk(&self.network, name, chunk);
+                                let stake = self.committee.stake(&authority);
+                                monitored_future!(Self::waiter(handler, stake)).boxed()
+                            })
+                            .collect(),
+                        None => self.broadcast_batch(&batch, workers).into_iter().collect(),
+                    };
 
                     // Wait for the first 2f nodes to send back an Ack. Then we consider the batch
                     // delivered and we send its digest to the primary (that will include it into
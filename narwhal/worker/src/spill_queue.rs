@@ -0,0 +1,218 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::{admission_control::AdmissionController, lanes::LaneSenders, metrics::WorkerMetrics};
+use mysten_metrics::{spawn_logged_monitored_task, spawn_monitored_task};
+use std::sync::{Arc, Mutex};
+use storage::NodeStorage;
+use store::{
+    rocks::{DBMap, ReadWriteOptions},
+    Map,
+};
+use tokio::{
+    sync::{oneshot, Notify},
+    task::JoinHandle,
+    time::sleep,
+};
+use tracing::{debug, warn};
+use types::{Batch, BatchDigest, ConditionalBroadcastReceiver, PriorityLane, Transaction};
+
+#[cfg(test)]
+#[path = "tests/spill_queue_tests.rs"]
+pub mod spill_queue_tests;
+
+#[derive(Default)]
+struct SpillState {
+    // The key of the next spilled transaction.
+    next_key: u64,
+    // The number of spilled transactions, and their total size.
+    len: usize,
+    bytes: usize,
+}
+
+/// Spills to disk the transactions submitted while the worker is overloaded, instead of
+/// rejecting them, and restores them in the order they were spilled once the admission control
+/// admits them again. The disk usage is bounded: transactions are rejected once it is reached.
+///
+/// The spilled transactions are kept in the store of the epoch, so those not restored before
+/// the end of the epoch are dropped, like the other transactions not included in a batch.
+pub struct SpillQueue {
+    /// None if spilling is disabled.
+    store: Option<DBMap<u64, (PriorityLane, Transaction)>>,
+    max_spilled_bytes: usize,
+    state: Mutex<SpillState>,
+    notify: Notify,
+    metrics: Arc<WorkerMetrics>,
+}
+
+impl SpillQueue {
+    pub fn new(
+        store: DBMap<u64, (PriorityLane, Transaction)>,
+        max_spilled_bytes: usize,
+        metrics: Arc<WorkerMetrics>,
+    ) -> Arc<Self> {
+        if max_spilled_bytes == 0 {
+            return Self::disabled(metrics);
+        }
+        // Resume with the transactions spilled before the worker restarted.
+        let mut state = SpillState::default();
+        for (key, (_, transaction)) in store.iter() {
+            state.next_key = key + 1;
+            state.len += 1;
+            state.bytes += transaction.len();
+        }
+        metrics.spilled_transaction_bytes.set(state.bytes as i64);
+        Arc::new(Self {
+            store: Some(store),
+            max_spilled_bytes,
+            state: Mutex::new(state),
+            notify: Notify::new(),
+            metrics,
+        })
+    }
+
+    pub fn disabled(metrics: Arc<WorkerMetrics>) -> Arc<Self> {
+        Arc::new(Self {
+            store: None,
+            max_spilled_bytes: 0,
+            state: Mutex::new(SpillState::default()),
+            notify: Notify::new(),
+            metrics,
+        })
+    }
+
+    /// Opens the queue in the database of the batch store.
+    pub fn reopen(
+        batch_store: &DBMap<BatchDigest, Batch>,
+        max_spilled_bytes: usize,
+        metrics: Arc<WorkerMetrics>,
+    ) -> Arc<Self> {
+        if max_spilled_bytes == 0 {
+            return Self::disabled(metrics);
+        }
+        let store = DBMap::reopen(
+            &batch_store.rocksdb,
+            Some(NodeStorage::SPILLED_TRANSACTIONS_CF),
+            &ReadWriteOptions::default(),
+        )
+        .expect("Cannot open the spilled transactions");
+        Self::new(store, max_spilled_bytes, metrics)
+    }
+
+    /// Whether no transaction waits to be restored. The transactions submitted while some do
+    /// are spilled too, so that they are not batched before them.
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().len == 0
+    }
+
+    /// Spills the transaction, or returns false if spilling is disabled or the disk usage bound
+    /// would be exceeded.
+    pub fn try_spill(&self, lane: PriorityLane, transaction: &Transaction) -> bool {
+        let Some(store) = &self.store else {
+            return false;
+        };
+        let mut state = self.state.lock().unwrap();
+        if state.bytes + transaction.len() > self.max_spilled_bytes {
+            return false;
+        }
+        if let Err(e) = store.insert(&state.next_key, &(lane, transaction.clone())) {
+            warn!("Failed to spill a transaction: {e}");
+            return false;
+        }
+        state.next_key += 1;
+        state.len += 1;
+        state.bytes += transaction.len();
+        self.metrics.spilled_transactions.inc();
+        self.metrics
+            .spilled_transaction_bytes
+            .set(state.bytes as i64);
+        self.notify.notify_one();
+        true
+    }
+
+    // Returns the oldest spilled transaction, from the given key on.
+    fn front(&self, from: u64) -> Option<(u64, (PriorityLane, Transaction))> {
+        let store = self.store.as_ref()?;
+        if self.is_empty() {
+            return None;
+        }
+        match store.iter().skip_to(&from) {
+            Ok(mut iter) => iter.next(),
+            Err(e) => {
+                warn!("Failed to read the spilled transactions: {e}");
+                None
+            }
+        }
+    }
+
+    fn remove(&self, key: u64, size: usize) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store.remove(&key) {
+            warn!("Failed to remove a restored transaction: {e}");
+        }
+        let mut state = self.state.lock().unwrap();
+        state.len -= 1;
+        state.bytes -= size;
+        self.metrics.restored_spilled_transactions.inc();
+        self.metrics
+            .spilled_transaction_bytes
+            .set(state.bytes as i64);
+    }
+
+    /// Spawns the task restoring the spilled transactions to the batch maker. A restored
+    /// transaction is accounted for by the admission control until its batch is acknowledged by
+    /// a quorum, like a submitted one.
+    #[must_use]
+    pub fn spawn_restorer(
+        self: &Arc<Self>,
+        admission_controller: Arc<AdmissionController>,
+        tx_batch_maker: LaneSenders,
+        mut rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        let queue = self.clone();
+        spawn_logged_monitored_task!(
+            async move {
+                let mut cursor = 0;
+                loop {
+                    let Some((key, (lane, transaction))) = queue.front(cursor) else {
+                        tokio::select! {
+                            () = queue.notify.notified() => continue,
+                            _ = rx_shutdown.receiver.recv() => return,
+                        }
+                    };
+                    let size = transaction.len();
+                    let permit = match admission_controller.admit(size) {
+                        Ok(permit) => permit,
+                        // Wait for the load to subside.
+                        Err(retry_after) => {
+                            tokio::select! {
+                                () = sleep(retry_after) => continue,
+                                _ = rx_shutdown.receiver.recv() => return,
+                            }
+                        }
+                    };
+                    let (notifier, when_done) = oneshot::channel();
+                    if tx_batch_maker
+                        .get(lane)
+                        .send((transaction, notifier))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                    // The transaction is batched again if the worker stops right before it is
+                    // removed, and then rejected as a duplicate if it was already batched.
+                    queue.remove(key, size);
+                    cursor = key + 1;
+                    debug!("Restored spilled transaction {key}");
+                    spawn_monitored_task!(async move {
+                        let _permit = permit;
+                        let _ = when_done.await;
+                    });
+                }
+            },
+            "SpillQueueRestorerTask"
+        )
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use anemo::{types::PeerInfo, Network, PeerId};
+use anemo_tower::set_header::SetRequestHeaderLayer;
+use anyhow::anyhow;
+use config::{Epoch, WorkerId, WorkerStandbyParameters};
+use crypto::{traits::KeyPair as _, NetworkKeyPair};
+use fastcrypto::hash::{Hash, HashFunction};
+use mysten_network::Multiaddr;
+use network::epoch_filter::EPOCH_HEADER_KEY;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::VecDeque,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+use store::{rocks::DBMap, Map};
+use tokio::time::{sleep, Instant};
+use tower::ServiceBuilder;
+use tracing::{debug, info, warn};
+use types::{
+    Batch, BatchDigest, RequestReplicatedBatchesRequest, RequestReplicatedBatchesResponse,
+    WorkerToWorkerClient,
+};
+
+#[cfg(test)]
+#[path = "tests/standby_tests.rs"]
+pub mod standby_tests;
+
+/// The network key of the standby of the worker holding the given network key. The standby
+/// holds the key of the worker, to take over its network identity, but replicates it under a
+/// key of its own, as a network does not connect to a peer with its own identity.
+pub fn standby_keypair(keypair: &NetworkKeyPair) -> NetworkKeyPair {
+    let mut hasher = crypto::DefaultHashFunction::new();
+    hasher.update(b"worker-standby");
+    hasher.update(keypair.copy().private().0.to_bytes());
+    NetworkKeyPair::generate(&mut StdRng::from_seed(hasher.finalize().into()))
+}
+
+#[derive(Default)]
+struct Inner {
+    digests: VecDeque<BatchDigest>,
+    // The position of the first digest held.
+    first_position: u64,
+}
+
+/// Logs the digests of the batches stored by the worker, in order, for its standby to replicate
+/// them. Only the most recent digests are held, and the log starts anew with the worker.
+#[derive(Clone)]
+pub struct ReplicationLog {
+    capacity: usize,
+    log_id: u64,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Default for ReplicationLog {
+    /// A disabled log.
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl ReplicationLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            log_id: rand::random(),
+            inner: Arc::new(Mutex::new(Inner::default())),
+        }
+    }
+
+    pub fn log_id(&self) -> u64 {
+        self.log_id
+    }
+
+    pub fn append(&self, digest: BatchDigest) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.digests.push_back(digest);
+        if inner.digests.len() > self.capacity {
+            inner.digests.pop_front();
+            inner.first_position += 1;
+        }
+    }
+
+    /// Returns the position of the first digest read and up to `max_digests` digests logged
+    /// from `position`. When `position` does not belong to this log, or is no longer held, the
+    /// digests are read from the first one held and the gap is reported.
+    pub fn read(
+        &self,
+        log_id: u64,
+        position: u64,
+        max_digests: usize,
+    ) -> (u64, Vec<BatchDigest>, bool) {
+        let inner = self.inner.lock().unwrap();
+        let end = inner.first_position + inner.digests.len() as u64;
+        let (start, gap) = if log_id != self.log_id || position < inner.first_position {
+            (inner.first_position, true)
+        } else {
+            (position.min(end), false)
+        };
+        let digests = inner
+            .digests
+            .iter()
+            .skip((start - inner.first_position) as usize)
+            .take(max_digests)
+            .copied()
+            .collect();
+        (start, digests, gap)
+    }
+}
+
+/// A standby of the worker holding the same network key. It replicates the batches stored by
+/// the worker into its own store, and returns once the worker has stopped responding for the
+/// failover timeout, so that a worker is started on the replicated store in its place.
+pub struct WorkerStandby {
+    id: WorkerId,
+    keypair: NetworkKeyPair,
+    // The address of the worker replicated.
+    address: Multiaddr,
+    epoch: Epoch,
+    store: DBMap<BatchDigest, Batch>,
+    parameters: WorkerStandbyParameters,
+}
+
+impl WorkerStandby {
+    pub fn new(
+        id: WorkerId,
+        keypair: NetworkKeyPair,
+        address: Multiaddr,
+        epoch: Epoch,
+        store: DBMap<BatchDigest, Batch>,
+        parameters: WorkerStandbyParameters,
+    ) -> Self {
+        Self {
+            id,
+            keypair,
+            address,
+            epoch,
+            store,
+            parameters,
+        }
+    }
+
+    pub async fn run(self) {
+        let peer_id = PeerId(self.keypair.public().0.to_bytes());
+        let network = self.start_network();
+        network.known_peers().insert(PeerInfo {
+            peer_id,
+            affinity: anemo::types::PeerAffinity::High,
+            address: vec![self.address.to_anemo_address().unwrap()],
+        });
+        info!(
+            "Worker {} standing by for the worker on {}",
+            self.id, self.address
+        );
+
+        let mut log_id = 0;
+        let mut position = 0;
+        let mut replicated = 0;
+        let mut last_response = Instant::now();
+        let mut catching_up = false;
+        loop {
+            if !catching_up {
+                sleep(self.parameters.replication_interval).await;
+            }
+            catching_up = false;
+            match self.replicate(&network, peer_id, log_id, position).await {
+                Ok(response) => {
+                    last_response = Instant::now();
+                    if response.gap && position > 0 {
+                        // The batches missed are fetched from the peers once taken over.
+                        warn!("Worker {} standby missed batches of the worker", self.id);
+                    }
+                    replicated += response.batches.len();
+                    debug!(
+                        "Worker {} standby replicated {} batches, {replicated} in total",
+                        self.id,
+                        response.batches.len()
+                    );
+                    // Catch up right away when the response was full.
+                    catching_up = response.batches.len() >= self.parameters.max_batches_per_request;
+                    log_id = response.log_id;
+                    position = response.next_position;
+                }
+                Err(e) if last_response.elapsed() >= self.parameters.failover_timeout => {
+                    warn!(
+                        "Worker {} did not respond to its standby for {} ms, taking over: {e}",
+                        self.id,
+                        last_response.elapsed().as_millis()
+                    );
+                    break;
+                }
+                Err(e) => debug!("Worker {} standby failed to replicate: {e}", self.id),
+            }
+        }
+
+        let _ = network.shutdown().await;
+    }
+
+    fn start_network(&self) -> Network {
+        let outbound_layer = ServiceBuilder::new()
+            .layer(SetRequestHeaderLayer::overriding(
+                EPOCH_HEADER_KEY.parse().unwrap(),
+                self.epoch.to_string(),
+            ))
+            .into_inner();
+        let mut config = anemo::Config::default();
+        config.max_frame_size = Some(2 << 30);
+        // The standby only sends requests, on any port.
+        Network::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+            .server_name("narwhal")
+            .private_key(standby_keypair(&self.keypair).private().0.to_bytes())
+            .config(config)
+            .outbound_request_layer(outbound_layer)
+            .start(anemo::Router::new())
+            .expect("The standby network cannot be started")
+    }
+
+    async fn replicate(
+        &self,
+        network: &Network,
+        peer_id: PeerId,
+        log_id: u64,
+        position: u64,
+    ) -> anyhow::Result<RequestReplicatedBatchesResponse> {
+        let peer = network
+            .peer(peer_id)
+            .ok_or_else(|| anyhow!("Not connected to the worker"))?;
+        let request = anemo::Request::new(RequestReplicatedBatchesRequest {
+            log_id,
+            position,
+            max_batches: self.parameters.max_batches_per_request as u32,
+        })
+        .with_timeout(self.parameters.failover_timeout);
+        let response = WorkerToWorkerClient::new(peer)
+            .request_replicated_batches(request)
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?
+            .into_body();
+        self.store
+            .multi_insert(response.batches.iter().map(|batch| (batch.digest(), batch)))?;
+        Ok(response)
+    }
+}
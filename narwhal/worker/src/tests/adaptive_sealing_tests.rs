@@ -0,0 +1,77 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+
+const BATCH_SIZE: usize = 1_000;
+const MAX_BATCH_DELAY: Duration = Duration::from_millis(100);
+
+fn parameters() -> AdaptiveSealingParameters {
+    AdaptiveSealingParameters {
+        enabled: true,
+        low_load_bytes_per_second: 1_000,
+        high_load_bytes_per_second: 100_000,
+        min_batch_delay: Duration::from_millis(10),
+        max_batch_size: 10_000,
+        rate_half_life: Duration::from_secs(1),
+    }
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn thresholds_follow_the_load() {
+    let (tx_parameters, rx_parameters) = watch::channel(parameters());
+    let mut sealing = AdaptiveSealing::new(BATCH_SIZE, MAX_BATCH_DELAY, rx_parameters);
+
+    // Nothing was received, the batches are sealed early.
+    assert_eq!(
+        sealing.thresholds(),
+        SealingThresholds {
+            batch_size_limit: BATCH_SIZE,
+            max_batch_delay: Duration::from_millis(10),
+        }
+    );
+
+    // At 10 KB/s the load is normal.
+    for _ in 0..100 {
+        tokio::time::advance(Duration::from_millis(100)).await;
+        sealing.record(1_000);
+    }
+    let rate = sealing.ingress_rate();
+    assert!((9_000.0..11_000.0).contains(&rate), "rate={rate}");
+    assert_eq!(
+        sealing.thresholds(),
+        SealingThresholds {
+            batch_size_limit: BATCH_SIZE,
+            max_batch_delay: MAX_BATCH_DELAY,
+        }
+    );
+
+    // At 1 MB/s the load is high, the batches are larger.
+    for _ in 0..100 {
+        tokio::time::advance(Duration::from_millis(100)).await;
+        sealing.record(100_000);
+    }
+    assert_eq!(
+        sealing.thresholds(),
+        SealingThresholds {
+            batch_size_limit: 10_000,
+            max_batch_delay: MAX_BATCH_DELAY,
+        }
+    );
+
+    // Once idle, the moving average decays back to low load.
+    tokio::time::advance(Duration::from_secs(30)).await;
+    assert_eq!(
+        sealing.thresholds().max_batch_delay,
+        Duration::from_millis(10)
+    );
+
+    // The parameters are tuned at runtime.
+    tx_parameters.send_modify(|parameters| parameters.enabled = false);
+    assert_eq!(
+        sealing.thresholds(),
+        SealingThresholds {
+            batch_size_limit: BATCH_SIZE,
+            max_batch_delay: MAX_BATCH_DELAY,
+        }
+    );
+}
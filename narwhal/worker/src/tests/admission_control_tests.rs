@@ -0,0 +1,65 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use prometheus::Registry;
+
+fn test_controller(
+    max_pending_batch_bytes: usize,
+    max_inflight_quorum_waits: usize,
+) -> Arc<AdmissionController> {
+    AdmissionController::new(
+        AdmissionControlParameters {
+            max_pending_batch_bytes,
+            max_inflight_quorum_waits,
+            retry_after: Duration::from_millis(200),
+            max_spilled_bytes: 0,
+        },
+        Arc::new(WorkerMetrics::new(&Registry::new())),
+    )
+}
+
+#[test]
+fn rejects_when_pending_bytes_exceed_limit() {
+    let controller = test_controller(100, 10);
+
+    let first = controller.try_admit(60).unwrap();
+    let second = controller.try_admit(40).unwrap();
+    assert_eq!(controller.metrics.pending_batch_bytes.get(), 100);
+
+    assert_eq!(
+        controller.try_admit(1).err(),
+        Some(Duration::from_millis(200))
+    );
+    assert_eq!(controller.metrics.pending_batch_bytes.get(), 100);
+    assert_eq!(controller.metrics.overloaded_transactions_rejected.get(), 1);
+
+    // Releasing a permit makes room for new transactions.
+    drop(first);
+    assert_eq!(controller.metrics.pending_batch_bytes.get(), 40);
+    let _third = controller.try_admit(60).unwrap();
+    drop(second);
+}
+
+#[test]
+fn admits_oversized_transaction_when_idle() {
+    let controller = test_controller(100, 10);
+
+    let permit = controller.try_admit(150).unwrap();
+    assert!(controller.try_admit(1).is_err());
+    drop(permit);
+    assert_eq!(controller.metrics.pending_batch_bytes.get(), 0);
+}
+
+#[test]
+fn rejects_while_quorum_waits_are_saturated() {
+    let controller = test_controller(100, 2);
+
+    controller.set_inflight_quorum_waits(2);
+    assert_eq!(
+        controller.try_admit(1).err(),
+        Some(Duration::from_millis(200))
+    );
+
+    controller.set_inflight_quorum_waits(1);
+    assert!(controller.try_admit(1).is_ok());
+}
@@ -0,0 +1,102 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::TrivialTransactionValidator;
+use prometheus::Registry;
+use test_utils::CommitteeFixture;
+use types::{
+    MockWorkerToWorker, RequestBatchesDiffResponse, RequestBatchesV2Response, WorkerToWorkerServer,
+};
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn recent_batches_expire() {
+    let recent_batches = RecentBatches::new(Duration::from_secs(60));
+    let old = test_utils::batch_with_transactions(1).digest();
+    let recent = test_utils::batch_with_transactions(2).digest();
+    recent_batches.append(old);
+    tokio::time::advance(Duration::from_secs(30)).await;
+    recent_batches.append(recent);
+    assert_eq!(recent_batches.digests(), vec![old, recent]);
+
+    tokio::time::advance(Duration::from_secs(30)).await;
+    assert_eq!(recent_batches.digests(), vec![recent]);
+}
+
+#[tokio::test]
+async fn fetch_missing_batches() {
+    telemetry_subscribers::init_for_testing();
+
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let id = 0;
+    let store = test_utils::open_batch_store();
+
+    // We already have one of the batches reported missing by the peer.
+    let stored = test_utils::batch_with_transactions(1);
+    store.insert(&stored.digest(), &stored).unwrap();
+    let missing = test_utils::batch_with_transactions(2);
+    let unrequested = test_utils::batch_with_transactions(3);
+
+    let mut peer_server = MockWorkerToWorker::new();
+    let missing_digests = vec![stored.digest(), missing.digest()];
+    peer_server
+        .expect_request_batches_diff()
+        .returning(move |_| {
+            Ok(anemo::Response::new(RequestBatchesDiffResponse {
+                missing_digests: missing_digests.clone(),
+            }))
+        });
+    let expected_digests = vec![missing.digest()];
+    let batches = vec![missing.clone(), unrequested.clone()];
+    peer_server
+        .expect_request_batches_v2()
+        .withf(move |request| request.body().batch_digests == expected_digests)
+        .returning(move |_| {
+            Ok(anemo::Response::new(RequestBatchesV2Response {
+                batches: batches.clone(),
+                remaining_digests: vec![],
+                missing_digests: vec![],
+            }))
+        });
+    let peer_worker = fixture.authorities().nth(1).unwrap().worker(id);
+    let _peer_network = peer_worker
+        .new_network(anemo::Router::new().add_rpc_service(WorkerToWorkerServer::new(peer_server)));
+
+    let network = test_utils::random_network();
+    let peer_id = anemo::PeerId(peer_worker.info().name.0.to_bytes());
+    network
+        .connect_with_peer_id(
+            peer_worker
+                .info()
+                .worker_address
+                .to_anemo_address()
+                .unwrap(),
+            peer_id,
+        )
+        .await
+        .unwrap();
+
+    let (tx_others_batch, mut rx_others_batch) = test_utils::test_channel!(10);
+    let metrics = Arc::new(WorkerMetrics::new(&Registry::new()));
+    let synchronizer = BatchDiffSynchronizer {
+        id,
+        network: network.downgrade(),
+        other_workers: vec![peer_id],
+        store: store.clone(),
+        recent_batches: RecentBatches::new(Duration::from_secs(60)),
+        validator: TrivialTransactionValidator,
+        tx_others_batch,
+        parameters: BatchDiffSyncParameters::default(),
+        metrics: metrics.clone(),
+    };
+
+    // Only the requested batch we were missing is stored, and our primary is notified of it.
+    assert_eq!(synchronizer.sync_with_random_peer().await.unwrap(), 1);
+    assert!(store.contains_key(&missing.digest()).unwrap());
+    assert!(!store.contains_key(&unrequested.digest()).unwrap());
+    assert_eq!(
+        rx_others_batch.recv().await.unwrap().digest,
+        missing.digest()
+    );
+    assert!(rx_others_batch.try_recv().is_err());
+    assert_eq!(metrics.batch_diff_sync_fetched_batches.get(), 1);
+}
@@ -3,14 +3,41 @@
 // SPDX-License-Identifier: Apache-2.0
 use super::*;
 
-use crate::NUM_SHUTDOWN_RECEIVERS;
-use prometheus::Registry;
+use crate::drain::drain_channel;
+use crate::lanes::{lane_channels, LaneSenders};
+use crate::transaction_status::TransactionStatus;
+use crate::{TrivialTransactionValidator, NUM_SHUTDOWN_RECEIVERS};
+use async_trait::async_trait;
+use config::{AdaptiveSealingParameters, AdmissionControlParameters, TransactionDurability};
+use prometheus::{IntCounter, IntGauge, Registry};
+use std::collections::HashSet;
+use storage::NodeStorage;
 use store::rocks;
 use store::rocks::MetricConf;
 use store::rocks::ReadWriteOptions;
 use test_utils::{temp_dir, transaction};
+use tokio::time::timeout;
 use types::PreSubscribedBroadcastSender;
 
+fn parameters(max_batch_delay: Duration) -> watch::Receiver<Parameters> {
+    watch::channel(Parameters {
+        max_batch_delay,
+        ..Parameters::default()
+    })
+    .1
+}
+
+fn test_admission_controller() -> Arc<AdmissionController> {
+    AdmissionController::new(
+        AdmissionControlParameters::default(),
+        Arc::new(WorkerMetrics::new(&Registry::new())),
+    )
+}
+
+fn default_sealing_parameters() -> watch::Receiver<AdaptiveSealingParameters> {
+    watch::channel(AdaptiveSealingParameters::default()).1
+}
+
 fn create_batches_store() -> DBMap<BatchDigest, Batch> {
     rocks::DBMap::<BatchDigest, Batch>::open(
         temp_dir(),
@@ -22,39 +49,71 @@ fn create_batches_store() -> DBMap<BatchDigest, Batch> {
     .unwrap()
 }
 
+fn test_lane_channels(size: usize) -> (LaneSenders, LaneReceivers) {
+    lane_channels(
+        size,
+        &IntGauge::new("TEST_GAUGE", "test gauge").unwrap(),
+        &IntCounter::new("TEST_COUNTER", "test counter").unwrap(),
+    )
+}
+
 #[tokio::test]
 async fn make_batch() {
     let store = create_batches_store();
     let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
-    let (tx_batch_maker, rx_batch_maker) = test_utils::test_channel!(1);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
     let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
     let (tx_our_batch, mut rx_our_batch) = test_utils::test_channel!(1);
     let node_metrics = WorkerMetrics::new(&Registry::new());
 
+    let transaction_status = TransactionStatusTracker::default();
+
     // Spawn a `BatchMaker` instance.
     let id = 0;
     let _batch_maker_handle = BatchMaker::spawn(
         id,
+        /* epoch */ 0,
         /* max_batch_size */ 200,
-        /* max_batch_delay */
-        Duration::from_millis(1_000_000), // Ensure the timer is not triggered.
+        // Ensure the timer is not triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(1_000_000)),
         tx_shutdown.subscribe(),
         rx_batch_maker,
         tx_quorum_waiter,
         Arc::new(node_metrics),
         store.clone(),
         tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        transaction_status.clone(),
     );
 
     // Send enough transactions to seal a batch.
-    let tx = transaction();
+    let tx0 = transaction();
+    let tx1 = transaction();
     let (s0, r0) = tokio::sync::oneshot::channel();
     let (s1, r1) = tokio::sync::oneshot::channel();
-    tx_batch_maker.send((tx.clone(), s0)).await.unwrap();
-    tx_batch_maker.send((tx.clone(), s1)).await.unwrap();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx0.clone(), s0))
+        .await
+        .unwrap();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx1.clone(), s1))
+        .await
+        .unwrap();
 
     // Ensure the batch is as expected.
-    let expected_batch = Batch::new(vec![tx.clone(), tx.clone()]);
+    let expected_batch = Batch::new(vec![tx0.clone(), tx1.clone()]);
     let (batch, resp) = rx_quorum_waiter.recv().await.unwrap();
 
     assert_eq!(batch.transactions(), expected_batch.transactions());
@@ -63,11 +122,20 @@ async fn make_batch() {
     assert!(resp.send(()).is_ok());
 
     // Now we send to primary
-    let (_message, respond) = rx_our_batch.recv().await.unwrap();
+    let (message, respond) = rx_our_batch.recv().await.unwrap();
     assert!(respond.unwrap().send(()).is_ok());
 
-    assert!(r0.await.is_ok());
-    assert!(r1.await.is_ok());
+    assert!(r0.await.unwrap().is_ok());
+    assert!(r1.await.unwrap().is_ok());
+
+    // The transactions are tracked until their batch is acknowledged by a quorum.
+    assert_eq!(
+        transaction_status.status(&TransactionStatusTracker::digest(&tx1)),
+        Some(TransactionStatus {
+            batch: message.digest,
+            stage: TransactionStage::QuorumAcked,
+        })
+    );
 
     // Ensure the batch is stored
     assert!(store.get(&expected_batch.digest()).unwrap().is_some());
@@ -77,7 +145,7 @@ async fn make_batch() {
 async fn batch_timeout() {
     let store = create_batches_store();
     let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
-    let (tx_batch_maker, rx_batch_maker) = test_utils::test_channel!(1);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
     let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
     let node_metrics = WorkerMetrics::new(&Registry::new());
     let (tx_our_batch, mut rx_our_batch) = test_utils::test_channel!(1);
@@ -86,21 +154,38 @@ async fn batch_timeout() {
     let id = 0;
     let _batch_maker_handle = BatchMaker::spawn(
         id,
+        /* epoch */ 0,
         /* max_batch_size */ 200,
-        /* max_batch_delay */
-        Duration::from_millis(50), // Ensure the timer is triggered.
+        // Ensure the timer is triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(50)),
         tx_shutdown.subscribe(),
         rx_batch_maker,
         tx_quorum_waiter,
         Arc::new(node_metrics),
         store.clone(),
         tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
     );
 
     // Do not send enough transactions to seal a batch.
     let tx = transaction();
     let (s0, r0) = tokio::sync::oneshot::channel();
-    tx_batch_maker.send((tx.clone(), s0)).await.unwrap();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx.clone(), s0))
+        .await
+        .unwrap();
 
     // Ensure the batch is as expected.
     let (batch, resp) = rx_quorum_waiter.recv().await.unwrap();
@@ -114,8 +199,828 @@ async fn batch_timeout() {
     let (_message, respond) = rx_our_batch.recv().await.unwrap();
     assert!(respond.unwrap().send(()).is_ok());
 
-    assert!(r0.await.is_ok());
+    assert!(r0.await.unwrap().is_ok());
 
     // Ensure the batch is stored
     assert!(store.get(&batch.digest()).unwrap().is_some());
 }
+
+#[tokio::test]
+async fn batch_sealed_early_under_low_load() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let node_metrics = WorkerMetrics::new(&Registry::new());
+    let (tx_our_batch, mut rx_our_batch) = test_utils::test_channel!(1);
+
+    let (_tx_sealing_parameters, rx_sealing_parameters) =
+        watch::channel(AdaptiveSealingParameters {
+            enabled: true,
+            min_batch_delay: Duration::from_millis(50),
+            ..AdaptiveSealingParameters::default()
+        });
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 200,
+        // Only the adaptive delay can trigger the timer.
+        parameters(/* max_batch_delay */ Duration::from_millis(1_000_000)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(node_metrics),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        rx_sealing_parameters,
+        TransactionStatusTracker::default(),
+    );
+
+    // Do not send enough transactions to seal a batch.
+    let tx = transaction();
+    let (s0, r0) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx.clone(), s0))
+        .await
+        .unwrap();
+
+    // The load is low, so the batch is sealed after the min batch delay.
+    let (batch, resp) = timeout(Duration::from_secs(10), rx_quorum_waiter.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let expected_batch = Batch::new(vec![tx.clone()]);
+    assert_eq!(batch.transactions(), expected_batch.transactions());
+
+    // Eventually deliver message
+    assert!(resp.send(()).is_ok());
+
+    // Now we send to primary
+    let (_message, respond) = rx_our_batch.recv().await.unwrap();
+    assert!(respond.unwrap().send(()).is_ok());
+
+    assert!(r0.await.unwrap().is_ok());
+
+    // Ensure the batch is stored
+    assert!(store.get(&batch.digest()).unwrap().is_some());
+}
+
+#[tokio::test]
+async fn batch_delay_tuned_at_runtime() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let node_metrics = WorkerMetrics::new(&Registry::new());
+    let (tx_our_batch, _rx_our_batch) = test_utils::test_channel!(1);
+    let (tx_parameters, rx_parameters) = watch::channel(Parameters {
+        max_batch_delay: Duration::from_millis(1_000_000),
+        ..Parameters::default()
+    });
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 1_000,
+        rx_parameters,
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(node_metrics),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        watch::channel(AdaptiveSealingParameters::default()).1,
+        TransactionStatusTracker::default(),
+    );
+
+    // Do not send enough transactions to seal a batch: the timer is not triggered.
+    let tx_0 = transaction();
+    let (s0, _r0) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx_0.clone(), s0))
+        .await
+        .unwrap();
+    assert!(timeout(Duration::from_millis(200), rx_quorum_waiter.recv())
+        .await
+        .is_err());
+
+    // Once the delay is lowered, the batch is sealed by the timer.
+    tx_parameters.send_modify(|parameters| parameters.max_batch_delay = Duration::from_millis(50));
+    let tx_1 = transaction();
+    let (s1, _r1) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx_1.clone(), s1))
+        .await
+        .unwrap();
+    let (batch, _resp) = timeout(Duration::from_secs(10), rx_quorum_waiter.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let expected_batch = Batch::new(vec![tx_0, tx_1]);
+    assert_eq!(batch.transactions(), expected_batch.transactions());
+}
+
+#[tokio::test]
+async fn reject_duplicate_transactions() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let node_metrics = WorkerMetrics::new(&Registry::new());
+    let (tx_our_batch, mut rx_our_batch) = test_utils::test_channel!(1);
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 200,
+        // Ensure the timer is triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(50)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(node_metrics),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    // Send the same transaction twice.
+    let tx = transaction();
+    let (s0, r0) = tokio::sync::oneshot::channel();
+    let (s1, r1) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx.clone(), s0))
+        .await
+        .unwrap();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx.clone(), s1))
+        .await
+        .unwrap();
+
+    // The duplicate is rejected right away.
+    assert_eq!(
+        r1.await.unwrap(),
+        Err(TransactionRejection::Duplicate(Duration::from_secs(60)))
+    );
+
+    // Ensure the batch only contains the transaction once.
+    let (batch, resp) = rx_quorum_waiter.recv().await.unwrap();
+    assert_eq!(batch.transactions(), &vec![tx.clone()]);
+
+    // Eventually deliver message
+    assert!(resp.send(()).is_ok());
+
+    // Now we send to primary
+    let (_message, respond) = rx_our_batch.recv().await.unwrap();
+    assert!(respond.unwrap().send(()).is_ok());
+
+    assert_eq!(r0.await.unwrap(), Ok(batch.digest()));
+}
+
+// A test validator that rejects the batches assembled locally.
+#[derive(Clone)]
+struct RejectLocalBatchValidator;
+#[async_trait]
+impl TransactionValidator for RejectLocalBatchValidator {
+    type Error = eyre::Report;
+
+    async fn validate(&self, _tx: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    async fn validate_batch(&self, _b: &Batch) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    async fn validate_local_batch(&self, _b: &Batch) -> Result<(), Self::Error> {
+        eyre::bail!("Invalid batch");
+    }
+}
+
+#[tokio::test]
+async fn reject_invalid_local_batch() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let node_metrics = Arc::new(WorkerMetrics::new(&Registry::new()));
+    let (tx_our_batch, _rx_our_batch) = test_utils::test_channel!(1);
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 200,
+        // Ensure the timer is triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(50)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        node_metrics.clone(),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        RejectLocalBatchValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    let (s0, r0) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((transaction(), s0))
+        .await
+        .unwrap();
+
+    // The transactions of the rejected batch are rejected, and the batch is not broadcast.
+    assert_eq!(
+        r0.await.unwrap(),
+        Err(TransactionRejection::InvalidBatch(
+            "Invalid batch".to_string()
+        ))
+    );
+    assert!(rx_quorum_waiter.try_recv().is_err());
+    assert_eq!(node_metrics.invalid_batches_rejected.get(), 1);
+}
+
+// A test validator whose transactions starting with a zero byte are expired.
+#[derive(Clone)]
+struct ExpiringValidator;
+#[async_trait]
+impl TransactionValidator for ExpiringValidator {
+    type Error = eyre::Report;
+
+    async fn validate(&self, _tx: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    async fn validate_batch(&self, _b: &Batch) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn is_expired(&self, tx: &[u8]) -> bool {
+        tx.first() == Some(&0)
+    }
+}
+
+#[tokio::test]
+async fn drop_expired_transactions() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let node_metrics = Arc::new(WorkerMetrics::new(&Registry::new()));
+    let (tx_our_batch, _rx_our_batch) = test_utils::test_channel!(1);
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 200,
+        // Ensure the timer is triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(50)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        node_metrics.clone(),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        ExpiringValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    let expired = vec![0; 10];
+    let valid = vec![1; 10];
+    let (s0, r0) = tokio::sync::oneshot::channel();
+    let (s1, _r1) = tokio::sync::oneshot::channel();
+    let lane = tx_batch_maker.get(PriorityLane::Normal);
+    lane.send((expired, s0)).await.unwrap();
+    lane.send((valid.clone(), s1)).await.unwrap();
+
+    // The expired transaction is rejected, and the batch only contains the valid one.
+    assert_eq!(r0.await.unwrap(), Err(TransactionRejection::Expired));
+    let (batch, _) = rx_quorum_waiter.recv().await.unwrap();
+    assert_eq!(batch.transactions(), &vec![valid]);
+    assert_eq!(node_metrics.expired_transactions_dropped.get(), 1);
+}
+
+#[tokio::test]
+async fn batch_lanes_separately() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let node_metrics = Arc::new(WorkerMetrics::new(&Registry::new()));
+    let (tx_our_batch, mut rx_our_batch) = test_utils::test_channel!(1);
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 200,
+        // Ensure the timer is triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(50)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        node_metrics.clone(),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V2,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    // Send a transaction to the normal and the system lanes.
+    let normal_tx = transaction();
+    let system_tx = transaction();
+    let (s0, r0) = tokio::sync::oneshot::channel();
+    let (s1, r1) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((normal_tx.clone(), s0))
+        .await
+        .unwrap();
+    tx_batch_maker
+        .get(PriorityLane::System)
+        .send((system_tx.clone(), s1))
+        .await
+        .unwrap();
+
+    // Each lane is sealed in its own V2 batch, which records the lane.
+    let mut batches = Vec::new();
+    for _ in 0..2 {
+        let (batch, resp) = rx_quorum_waiter.recv().await.unwrap();
+        assert!(resp.send(()).is_ok());
+        let (_message, respond) = rx_our_batch.recv().await.unwrap();
+        assert!(respond.unwrap().send(()).is_ok());
+        batches.push(batch);
+    }
+    // V2 batches also record their origin.
+    assert!(batches
+        .iter()
+        .all(|batch| batch.version() == BatchVersion::V2
+            && batch.worker_id() == Some(id)
+            && batch.epoch() == Some(0)));
+    batches.sort_by_key(|batch| batch.lane());
+    assert_eq!(batches[0].lane(), PriorityLane::System);
+    assert_eq!(batches[0].transactions(), &vec![system_tx]);
+    assert_eq!(batches[1].lane(), PriorityLane::Normal);
+    assert_eq!(batches[1].transactions(), &vec![normal_tx]);
+
+    assert_eq!(r0.await.unwrap(), Ok(batches[1].digest()));
+    assert_eq!(r1.await.unwrap(), Ok(batches[0].digest()));
+    for lane in [PriorityLane::System, PriorityLane::Normal] {
+        assert_eq!(
+            node_metrics
+                .batched_transactions
+                .with_label_values(&[lane.as_str()])
+                .get(),
+            1
+        );
+    }
+}
+
+#[tokio::test]
+async fn drain_hands_off_transactions() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let (tx_our_batch, _rx_our_batch) = test_utils::test_channel!(1);
+    let node_metrics = WorkerMetrics::new(&Registry::new());
+    let handoff = TransactionHandoff::default();
+    let (drain_handle, drain) = drain_channel(handoff.clone());
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 200,
+        // Ensure the timer is not triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(1_000_000)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(node_metrics),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain,
+        /* drain_timeout */ Duration::from_millis(500),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    // Send a transaction that is not enough to seal a batch.
+    let tx = transaction();
+    let (s0, mut r0) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::High)
+        .send((tx.clone(), s0))
+        .await
+        .unwrap();
+    tokio::task::yield_now().await;
+
+    // Drain while the quorum waiter never acknowledges the batch.
+    let drain = tokio::spawn(drain_handle.drain());
+
+    // The current batch is sealed right away.
+    let (batch, _resp) = rx_quorum_waiter.recv().await.unwrap();
+    assert_eq!(batch.transactions(), &vec![tx.clone()]);
+
+    // Once the drain timeout expires the transaction is handed off, and its submitter is still
+    // waiting for a response.
+    drain.await.unwrap();
+    let handed_off = handoff.take(0);
+    assert_eq!(handed_off.len(), 1);
+    let (lane, transaction, _response) = &handed_off[0];
+    assert_eq!(*lane, PriorityLane::High);
+    assert_eq!(*transaction, tx);
+    assert!(r0.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn batch_handed_off_transactions() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (_tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let (tx_our_batch, mut rx_our_batch) = test_utils::test_channel!(1);
+    let node_metrics = WorkerMetrics::new(&Registry::new());
+
+    // The previous worker of the epoch handed off two transactions.
+    let tx0 = transaction();
+    let tx1 = transaction();
+    let (s0, r0) = tokio::sync::oneshot::channel();
+    let (s1, r1) = tokio::sync::oneshot::channel();
+    let handoff = TransactionHandoff::default();
+    handoff.extend(
+        1,
+        [
+            (PriorityLane::Normal, tx0.clone(), s0),
+            (PriorityLane::Normal, tx1.clone(), s1),
+        ],
+    );
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 1,
+        /* max_batch_size */ 200,
+        // Ensure the timer is not triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(1_000_000)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(node_metrics),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(handoff.clone()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    // The handed off transactions are batched by the new batch maker.
+    let (batch, resp) = rx_quorum_waiter.recv().await.unwrap();
+    assert_eq!(batch.transactions(), &vec![tx0, tx1]);
+    assert!(handoff.is_empty());
+    assert!(resp.send(()).is_ok());
+
+    // And their submitters are notified once the batch is included.
+    let (_message, respond) = rx_our_batch.recv().await.unwrap();
+    assert!(respond.unwrap().send(()).is_ok());
+    assert!(r0.await.unwrap().is_ok());
+    assert!(r1.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn drain_across_epoch_change_fails_back_transactions() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let (tx_our_batch, _rx_our_batch) = test_utils::test_channel!(1);
+    let handoff = TransactionHandoff::default();
+    let (drain_handle, drain) = drain_channel(handoff.clone());
+
+    // Spawn the `BatchMaker` of epoch 0.
+    let _batch_maker_handle = BatchMaker::spawn(
+        0,
+        /* epoch */ 0,
+        /* max_batch_size */ 200,
+        // Ensure the timer is not triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(1_000_000)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(WorkerMetrics::new(&Registry::new())),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain,
+        /* drain_timeout */ Duration::from_millis(500),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    // A transaction is not included before the end of epoch 0.
+    let tx = transaction();
+    let (s0, r0) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx.clone(), s0))
+        .await
+        .unwrap();
+    tokio::task::yield_now().await;
+    let drain = tokio::spawn(drain_handle.drain());
+    let (batch, _resp) = rx_quorum_waiter.recv().await.unwrap();
+    assert_eq!(batch.transactions(), &vec![tx.clone()]);
+    drain.await.unwrap();
+    assert_eq!(handoff.len(), 1);
+
+    // The worker of epoch 1, with a new committee, starts with the same handoff.
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let (tx_our_batch, _rx_our_batch) = test_utils::test_channel!(1);
+    let (drain_handle, drain) = drain_channel(handoff.clone());
+    let _batch_maker_handle = BatchMaker::spawn(
+        0,
+        /* epoch */ 1,
+        /* max_batch_size */ 200,
+        // Ensure the timer is not triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(1_000_000)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(WorkerMetrics::new(&Registry::new())),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain,
+        /* drain_timeout */ Duration::from_millis(500),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    // The transaction of epoch 0 is failed back to its submitter, for it to resubmit.
+    assert_eq!(r0.await.unwrap(), Err(TransactionRejection::EpochEnded(0)));
+    assert!(handoff.is_empty());
+
+    // And it is not batched in epoch 1, only the transactions submitted in epoch 1 are.
+    let tx1 = transaction();
+    let (s1, _r1) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((tx1.clone(), s1))
+        .await
+        .unwrap();
+    tokio::task::yield_now().await;
+    let drain = tokio::spawn(drain_handle.drain());
+    let (batch, _resp) = rx_quorum_waiter.recv().await.unwrap();
+    assert_eq!(batch.transactions(), &vec![tx1]);
+    drain.await.unwrap();
+}
+
+#[tokio::test]
+async fn batch_logged_transactions() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (_tx_batch_maker, rx_batch_maker) = test_lane_channels(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let (tx_our_batch, mut rx_our_batch) = test_utils::test_channel!(1);
+    let node_metrics = WorkerMetrics::new(&Registry::new());
+
+    // The worker logged a batch of two transactions before it stopped.
+    let transaction_log = TransactionLog::new(
+        rocks::DBMap::open(
+            temp_dir(),
+            MetricConf::default(),
+            None,
+            Some(NodeStorage::TRANSACTION_LOG_CF),
+            &ReadWriteOptions::default(),
+        )
+        .unwrap(),
+        TransactionDurability::Async,
+    );
+    let tx0 = transaction();
+    let tx1 = transaction();
+    let (_, logged) = transaction_log
+        .append(
+            PriorityLane::Normal,
+            Batch::new(vec![tx0.clone(), tx1.clone()]),
+        )
+        .await;
+    logged.unwrap();
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 200,
+        // Ensure the timer is not triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(1_000_000)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(node_metrics),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        /* batch_limits */ None,
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        transaction_log.clone(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    // The logged transactions are batched again.
+    let (batch, resp) = rx_quorum_waiter.recv().await.unwrap();
+    let batched: HashSet<_> = batch.transactions().iter().cloned().collect();
+    assert_eq!(batched, HashSet::from([tx0, tx1]));
+    assert!(resp.send(()).is_ok());
+
+    // They are removed from the log once the batch is included.
+    let (_message, respond) = rx_our_batch.recv().await.unwrap();
+    assert!(respond.unwrap().send(()).is_ok());
+    while !transaction_log.recover().is_empty() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn make_batches_within_limits() {
+    let store = create_batches_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_lane_channels(10);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(10);
+    let (tx_our_batch, _rx_our_batch) = test_utils::test_channel!(10);
+    let node_metrics = WorkerMetrics::new(&Registry::new());
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* epoch */ 0,
+        /* max_batch_size */ 100_000,
+        // Ensure the timer is not triggered.
+        parameters(/* max_batch_delay */ Duration::from_millis(1_000_000)),
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        Arc::new(node_metrics),
+        store.clone(),
+        tx_our_batch,
+        /* tx_dedup_window */ Duration::from_secs(60),
+        /* tx_dedup_cache_size */ 1_000,
+        LaneSchedulingPolicy::StrictPriority,
+        BatchVersion::V1,
+        Some(BatchLimits {
+            max_num_of_transactions: 2,
+            max_size: 250,
+        }),
+        test_admission_controller(),
+        TrivialTransactionValidator,
+        drain_channel(TransactionHandoff::default()).1,
+        /* drain_timeout */ Duration::from_secs(5),
+        TransactionLog::default(),
+        default_sealing_parameters(),
+        TransactionStatusTracker::default(),
+    );
+
+    // A transaction larger than a batch is rejected.
+    let (s, r) = tokio::sync::oneshot::channel();
+    tx_batch_maker
+        .get(PriorityLane::Normal)
+        .send((vec![0; 300], s))
+        .await
+        .unwrap();
+    assert_eq!(
+        r.await.unwrap(),
+        Err(TransactionRejection::TooLarge(300, 250))
+    );
+
+    // The first batch is sealed before it exceeds the maximum size, the second one once it has
+    // the maximum number of transactions.
+    let transactions = vec![vec![1; 150], vec![2; 150], vec![3; 100]];
+    for transaction in &transactions {
+        let (s, _r) = tokio::sync::oneshot::channel();
+        tx_batch_maker
+            .get(PriorityLane::Normal)
+            .send((transaction.clone(), s))
+            .await
+            .unwrap();
+    }
+    let (batch, _resp) = rx_quorum_waiter.recv().await.unwrap();
+    assert_eq!(batch.transactions(), &transactions[..1]);
+    let (batch, _resp) = rx_quorum_waiter.recv().await.unwrap();
+    assert_eq!(batch.transactions(), &transactions[1..]);
+}
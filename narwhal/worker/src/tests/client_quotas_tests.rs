@@ -0,0 +1,86 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use prometheus::Registry;
+use std::{net::Ipv4Addr, num::NonZeroU32};
+
+fn test_quotas(
+    max_transactions_per_second: Option<u32>,
+    max_bytes_per_second: Option<u32>,
+) -> Arc<ClientQuotas> {
+    ClientQuotas::new(
+        ClientQuotaParameters {
+            max_transactions_per_second: max_transactions_per_second.and_then(NonZeroU32::new),
+            max_bytes_per_second: max_bytes_per_second.and_then(NonZeroU32::new),
+            burst_duration: Duration::from_secs(1),
+            max_tracked_clients: 10,
+        },
+        Arc::new(WorkerMetrics::new(&Registry::new())),
+    )
+}
+
+fn client(i: u8) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(10, 0, 0, i))
+}
+
+#[test]
+fn transactions_quota() {
+    let quotas = test_quotas(Some(10), None);
+    let now = Instant::now();
+
+    // A client can burst up to its quota.
+    for _ in 0..10 {
+        quotas.try_charge_at(client(1), 100, now).unwrap();
+    }
+    let exceeded = quotas.try_charge_at(client(1), 100, now).unwrap_err();
+    assert_eq!(exceeded.quota, Quota::Transactions);
+    assert_eq!(exceeded.client, client(1));
+    assert_eq!(exceeded.retry_after, Duration::from_millis(100));
+    assert_eq!(
+        quotas
+            .metrics
+            .client_quota_rejected_transactions
+            .with_label_values(&["transactions"])
+            .get(),
+        1
+    );
+
+    // Other clients are not affected.
+    quotas.try_charge_at(client(2), 100, now).unwrap();
+
+    // The quota is refilled over time.
+    let later = now + Duration::from_millis(200);
+    quotas.try_charge_at(client(1), 100, later).unwrap();
+    quotas.try_charge_at(client(1), 100, later).unwrap();
+    assert!(quotas.try_charge_at(client(1), 100, later).is_err());
+}
+
+#[test]
+fn bytes_quota() {
+    let quotas = test_quotas(Some(100), Some(1_000));
+    let now = Instant::now();
+
+    quotas.try_charge_at(client(1), 600, now).unwrap();
+    let exceeded = quotas.try_charge_at(client(1), 600, now).unwrap_err();
+    assert_eq!(exceeded.quota, Quota::Bytes);
+    assert_eq!(exceeded.retry_after, Duration::from_millis(200));
+
+    // A rejected transaction is not charged to any quota.
+    quotas.try_charge_at(client(1), 400, now).unwrap();
+
+    // A transaction larger than the burst is accepted once the bucket is full again.
+    let later = now + Duration::from_secs(1);
+    quotas.try_charge_at(client(1), 5_000, later).unwrap();
+    assert!(quotas.try_charge_at(client(1), 1, later).is_err());
+}
+
+#[test]
+fn disabled_quotas() {
+    let quotas = test_quotas(None, None);
+    let now = Instant::now();
+
+    for _ in 0..1_000 {
+        quotas.try_charge_at(client(1), 1_000_000, now).unwrap();
+    }
+    assert_eq!(quotas.metrics.client_quota_tracked_clients.get(), 0);
+}
@@ -0,0 +1,140 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use fastcrypto::hash::Hash;
+use test_utils::batch;
+
+#[test]
+fn chunk_counts_tolerate_a_third_of_faulty_workers() {
+    // Too few workers to tolerate any fault: the batch is sent whole.
+    assert_eq!(chunk_counts(2), None);
+    assert_eq!(chunk_counts(3), Some((2, 1)));
+    assert_eq!(chunk_counts(9), Some((6, 3)));
+}
+
+#[test]
+fn decode_from_any_data_chunks() {
+    let batch = batch();
+    let digest = batch.digest();
+    let messages = encode_batch(&batch, digest, 4, 2).unwrap();
+    assert_eq!(messages.len(), 6);
+
+    // Any two chunks may be missing.
+    let mut chunks: Vec<_> = messages.into_iter().map(|m| Some(m.chunk)).collect();
+    chunks[0] = None;
+    chunks[3] = None;
+    let batch_size = bcs::to_bytes(&batch).unwrap().len();
+    let decoded = decode_batch(chunks.clone(), 4, 2, batch_size).unwrap();
+    assert_eq!(decoded.digest(), digest);
+
+    chunks[1] = None;
+    assert!(decode_batch(chunks, 4, 2, batch_size).is_err());
+}
+
+#[tokio::test]
+async fn collect_chunks_until_reconstructed() {
+    let batch = batch();
+    let digest = batch.digest();
+    let messages = encode_batch(&batch, digest, 2, 1).unwrap();
+    let batch_chunks = BatchChunks::default();
+
+    let first = batch_chunks.insert(&messages[2]).unwrap();
+    assert!(first.is_new && first.ready.is_none());
+    assert!(!batch_chunks.insert(&messages[2]).unwrap().is_new);
+
+    // The chunks are returned once enough of them are received.
+    let second = batch_chunks.insert(&messages[0]).unwrap();
+    let chunks = second.ready.unwrap();
+    let decoded = decode_batch(chunks, 2, 1, messages[0].batch_size as usize).unwrap();
+    assert_eq!(decoded, batch);
+    assert!(batch_chunks.insert(&messages[1]).unwrap().ready.is_none());
+
+    // The requests waiting for the batch are notified once it is reconstructed.
+    let waiting = tokio::spawn(BatchChunks::wait_reconstructed(
+        first.rx_reconstructed,
+        Duration::from_secs(5),
+    ));
+    batch_chunks.reconstructed(&digest);
+    assert!(waiting.await.unwrap());
+
+    // Inconsistent chunks are rejected.
+    let mut invalid = messages[0].clone();
+    invalid.index = 3;
+    assert!(batch_chunks.insert(&invalid).is_err());
+}
+
+#[test]
+fn chunks_are_checked_against_their_root() {
+    let batch = batch();
+    let digest = batch.digest();
+    let messages = encode_batch(&batch, digest, 4, 3).unwrap();
+    assert!(messages.iter().all(verify_chunk));
+    let batch_chunks = BatchChunks::default();
+
+    // A tampered chunk does not match the root.
+    let mut tampered = messages[1].clone();
+    tampered.chunk[0] ^= 1;
+    assert!(batch_chunks.insert(&tampered).is_err());
+
+    // Neither do chunks with the proof or the encoding of another.
+    let mut forged = messages[1].clone();
+    forged.proof = messages[0].proof.clone();
+    assert!(batch_chunks.insert(&forged).is_err());
+    let mut forged = messages[6].clone();
+    forged.batch_size += 1;
+    assert!(batch_chunks.insert(&forged).is_err());
+    let mut forged = messages[6].clone();
+    forged.proof.push(messages[6].chunks_root);
+    assert!(batch_chunks.insert(&forged).is_err());
+}
+
+#[test]
+fn forged_root_does_not_block_reconstruction() {
+    let batch = batch();
+    let digest = batch.digest();
+    let messages = encode_batch(&batch, digest, 2, 1).unwrap();
+    let batch_chunks = BatchChunks::default();
+
+    // Chunks committed to by another root are collected apart from those of the originator.
+    let forged = commit_chunks(digest, 2, 1, 10, vec![vec![0; 5]; 3]);
+    assert!(batch_chunks.insert(&forged[0]).unwrap().ready.is_none());
+    assert!(batch_chunks.insert(&messages[0]).unwrap().ready.is_none());
+    let chunks = batch_chunks.insert(&messages[1]).unwrap().ready.unwrap();
+    let decoded = decode_batch(chunks, 2, 1, messages[0].batch_size as usize).unwrap();
+    assert_eq!(decoded, batch);
+}
+
+#[test]
+fn retry_with_other_chunks_after_failed_reconstruction() {
+    let batch = batch();
+    let digest = batch.digest();
+    let messages = encode_batch(&batch, digest, 2, 1).unwrap();
+    let batch_size = messages[0].batch_size;
+
+    // The originator commits to a corrupted data chunk.
+    let mut chunks: Vec<_> = messages.into_iter().map(|m| m.chunk).collect();
+    chunks[0][0] ^= 1;
+    let messages = commit_chunks(digest, 2, 1, batch_size, chunks);
+    let batch_chunks = BatchChunks::default();
+
+    assert!(batch_chunks.insert(&messages[0]).unwrap().ready.is_none());
+    let chunks = batch_chunks.insert(&messages[1]).unwrap().ready.unwrap();
+    assert!(!matches!(
+        decode_batch(chunks, 2, 1, batch_size as usize),
+        Ok(decoded) if decoded.digest() == digest
+    ));
+    let root = messages[0].chunks_root;
+    batch_chunks.reconstruction_failed(&digest, &root);
+
+    // The next chunk is tried along with the latest chunks received.
+    let retry = batch_chunks.insert(&messages[2]).unwrap();
+    let chunks = retry.ready.unwrap();
+    assert!(chunks[0].is_none());
+    let decoded = decode_batch(chunks, 2, 1, batch_size as usize).unwrap();
+    assert_eq!(decoded.digest(), digest);
+
+    // All the chunks were received, so they are dropped on another failure.
+    batch_chunks.reconstruction_failed(&digest, &root);
+    let again = batch_chunks.insert(&messages[2]).unwrap();
+    assert!(again.is_new && again.ready.is_none());
+}
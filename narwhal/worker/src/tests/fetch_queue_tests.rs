@@ -0,0 +1,50 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use futures::FutureExt;
+use std::time::Duration;
+
+#[tokio::test]
+async fn blocking_fetches_are_served_first() {
+    let queue = FetchQueue::new(1, None);
+    let permit = queue.acquire(FetchPriority::Uncommitted).await;
+
+    // The queue is full: an uncommitted fetch arrives before a fetch blocking the execution.
+    let (tx_served, mut rx_served) = tokio::sync::mpsc::unbounded_channel();
+    for priority in [FetchPriority::Uncommitted, FetchPriority::BlocksExecution] {
+        let queue = queue.clone();
+        let tx_served = tx_served.clone();
+        tokio::spawn(async move {
+            let _permit = queue.acquire(priority).await;
+            tx_served.send(priority).unwrap();
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(rx_served.try_recv().is_err());
+
+    drop(permit);
+    assert_eq!(
+        rx_served.recv().await.unwrap(),
+        FetchPriority::BlocksExecution
+    );
+    assert_eq!(rx_served.recv().await.unwrap(), FetchPriority::Uncommitted);
+}
+
+#[tokio::test]
+async fn abandoned_fetches_release_their_turn() {
+    let queue = FetchQueue::new(1, None);
+    let permit = queue.acquire(FetchPriority::Uncommitted).await;
+
+    // A fetch gives up while waiting: its turn goes to the next one.
+    assert!(queue
+        .acquire(FetchPriority::BlocksExecution)
+        .now_or_never()
+        .is_none());
+    drop(permit);
+    let permit = queue.acquire(FetchPriority::Uncommitted).now_or_never();
+    assert!(permit.is_some());
+    assert!(queue
+        .acquire(FetchPriority::Uncommitted)
+        .now_or_never()
+        .is_none());
+}
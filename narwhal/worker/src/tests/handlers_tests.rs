@@ -3,10 +3,24 @@
 // SPDX-License-Identifier: Apache-2.0
 use super::*;
 
+use crate::fetch_queue::DEFAULT_MAX_CONCURRENT_FETCHES;
 use crate::TrivialTransactionValidator;
 use fastcrypto::hash::Hash;
 use test_utils::CommitteeFixture;
-use types::{MockWorkerToWorker, WorkerToWorkerServer};
+use types::{
+    BatchAPI, BatchDigestFilter, BatchStatus, CertificateDigest, MockWorkerToWorker,
+    WorkerToWorkerServer,
+};
+
+const RESPONSE_SIZE_LIMIT: usize = 1_000;
+
+fn parameters(sync_retry_nodes: usize) -> watch::Receiver<Parameters> {
+    watch::channel(Parameters {
+        sync_retry_nodes,
+        ..Parameters::default()
+    })
+    .1
+}
 
 #[tokio::test]
 async fn synchronize() {
@@ -28,8 +42,12 @@ async fn synchronize() {
         worker_cache,
         store: store.clone(),
         request_batch_timeout: Duration::from_secs(999),
-        request_batch_retry_nodes: 3, // Not used in this test.
+        rx_parameters: parameters(/* sync_retry_nodes */ 3), // Not used in this test.
+        request_batch_hedge_delay: Duration::from_millis(200),
         validator: TrivialTransactionValidator,
+        connection_manager: ConnectionManager::default(),
+        transaction_status: TransactionStatusTracker::default(),
+        primary_rounds: PrimaryRounds::default(),
     };
 
     // Set up mock behavior for child RequestBatches RPC.
@@ -40,6 +58,7 @@ async fn synchronize() {
         digests: vec![digest],
         target: target_primary.id(),
         is_certified: false,
+        round: None,
     };
 
     let mut mock_server = MockWorkerToWorker::new();
@@ -83,6 +102,95 @@ async fn synchronize() {
     assert!(store.get(&digest).unwrap().is_some())
 }
 
+#[tokio::test]
+async fn synchronize_from_other_workers() {
+    telemetry_subscribers::init_for_testing();
+
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let committee = fixture.committee();
+    let worker_cache = fixture.worker_cache();
+    let authority_id = fixture.authorities().next().unwrap().id();
+    let id = 0;
+
+    // Create a new test store.
+    let store = test_utils::open_batch_store();
+
+    let handler = PrimaryReceiverHandler {
+        authority_id,
+        id,
+        committee,
+        worker_cache,
+        store: store.clone(),
+        request_batch_timeout: Duration::from_secs(999),
+        rx_parameters: parameters(/* sync_retry_nodes */ 3),
+        request_batch_hedge_delay: Duration::from_millis(200),
+        validator: TrivialTransactionValidator,
+        connection_manager: ConnectionManager::default(),
+        transaction_status: TransactionStatusTracker::default(),
+        primary_rounds: PrimaryRounds::default(),
+    };
+
+    let batch = test_utils::batch();
+    let digest = batch.digest();
+    let target_primary = fixture.authorities().nth(1).unwrap();
+    let message = WorkerSynchronizeMessage {
+        digests: vec![digest],
+        target: target_primary.id(),
+        is_certified: false,
+        round: None,
+    };
+
+    // The worker of the target does not have the batch, while the worker of another authority
+    // does.
+    let mut target_server = MockWorkerToWorker::new();
+    target_server
+        .expect_request_batch()
+        .returning(|_| Ok(anemo::Response::new(RequestBatchResponse { batch: None })));
+    let target_worker = target_primary.worker(id);
+    let _target_network = target_worker.new_network(
+        anemo::Router::new().add_rpc_service(WorkerToWorkerServer::new(target_server)),
+    );
+
+    let other_primary = fixture.authorities().nth(2).unwrap();
+    let mut other_server = MockWorkerToWorker::new();
+    let mock_batch_response = batch.clone();
+    other_server
+        .expect_request_batch()
+        .withf(move |request| request.body().batch == digest)
+        .returning(move |_| {
+            Ok(anemo::Response::new(RequestBatchResponse {
+                batch: Some(mock_batch_response.clone()),
+            }))
+        });
+    let other_worker = other_primary.worker(id);
+    let _other_network = other_worker
+        .new_network(anemo::Router::new().add_rpc_service(WorkerToWorkerServer::new(other_server)));
+
+    // Send a sync request.
+    let mut request = anemo::Request::new(message);
+    let send_network = test_utils::random_network();
+    for worker in [&target_worker, &other_worker] {
+        send_network
+            .connect_with_peer_id(
+                worker.info().worker_address.to_anemo_address().unwrap(),
+                anemo::PeerId(worker.info().name.0.to_bytes()),
+            )
+            .await
+            .unwrap();
+    }
+    assert!(request
+        .extensions_mut()
+        .insert(send_network.downgrade())
+        .is_none());
+
+    // The batch is fetched from the other worker within the first attempt.
+    tokio::time::timeout(Duration::from_millis(900), handler.synchronize(request))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(store.get(&digest).unwrap().is_some())
+}
+
 #[tokio::test]
 async fn synchronize_when_batch_exists() {
     telemetry_subscribers::init_for_testing();
@@ -103,8 +211,12 @@ async fn synchronize_when_batch_exists() {
         worker_cache,
         store: store.clone(),
         request_batch_timeout: Duration::from_secs(999),
-        request_batch_retry_nodes: 3, // Not used in this test.
+        rx_parameters: parameters(/* sync_retry_nodes */ 3), // Not used in this test.
+        request_batch_hedge_delay: Duration::from_millis(200),
         validator: TrivialTransactionValidator,
+        connection_manager: ConnectionManager::default(),
+        transaction_status: TransactionStatusTracker::default(),
+        primary_rounds: PrimaryRounds::default(),
     };
 
     // Store the batch.
@@ -119,6 +231,7 @@ async fn synchronize_when_batch_exists() {
         digests: missing.clone(),
         target: target_primary.id(),
         is_certified: false,
+        round: None,
     };
 
     // Send a sync request.
@@ -129,6 +242,91 @@ async fn synchronize_when_batch_exists() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn synchronize_abandoned_when_round_collected() {
+    telemetry_subscribers::init_for_testing();
+
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let committee = fixture.committee();
+    let worker_cache = fixture.worker_cache();
+    let authority_id = fixture.authorities().next().unwrap().id();
+    let id = 0;
+
+    let primary_rounds = PrimaryRounds::default();
+    let handler = PrimaryReceiverHandler {
+        authority_id,
+        id,
+        committee,
+        worker_cache,
+        store: test_utils::open_batch_store(),
+        request_batch_timeout: Duration::from_secs(999),
+        rx_parameters: parameters(/* sync_retry_nodes */ 3), // Not used in this test.
+        request_batch_hedge_delay: Duration::from_millis(200),
+        validator: TrivialTransactionValidator,
+        connection_manager: ConnectionManager::default(),
+        transaction_status: TransactionStatusTracker::default(),
+        primary_rounds: primary_rounds.clone(),
+    };
+
+    // The target worker never has the batch.
+    let target_primary = fixture.authorities().nth(1).unwrap();
+    let mut mock_server = MockWorkerToWorker::new();
+    mock_server
+        .expect_request_batch()
+        .returning(|_| Ok(anemo::Response::new(RequestBatchResponse { batch: None })));
+    let routes = anemo::Router::new().add_rpc_service(WorkerToWorkerServer::new(mock_server));
+    let target_worker = target_primary.worker(id);
+    let _recv_network = target_worker.new_network(routes);
+    let send_network = test_utils::random_network();
+    send_network
+        .connect_with_peer_id(
+            target_worker
+                .info()
+                .worker_address
+                .to_anemo_address()
+                .unwrap(),
+            anemo::PeerId(target_worker.info().name.0.to_bytes()),
+        )
+        .await
+        .unwrap();
+    let request = |round| {
+        let mut request = anemo::Request::new(WorkerSynchronizeMessage {
+            digests: vec![test_utils::batch().digest()],
+            target: target_primary.id(),
+            is_certified: false,
+            round: Some(round),
+        });
+        request.extensions_mut().insert(send_network.downgrade());
+        request
+    };
+
+    // The synchronization keeps retrying until the primary reports the round collected.
+    let synchronize = handler.synchronize(request(3));
+    tokio::pin!(synchronize);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), &mut synchronize)
+            .await
+            .is_err()
+    );
+    handler
+        .report_rounds(anemo::Request::new(WorkerRoundsMessage {
+            committed_round: 53,
+            gc_round: 3,
+        }))
+        .await
+        .unwrap();
+    let status = tokio::time::timeout(Duration::from_secs(5), synchronize)
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(status.status(), StatusCode::BadRequest);
+
+    // The headers at collected rounds are not synchronized anymore.
+    let status = handler.synchronize(request(2)).await.unwrap_err();
+    assert_eq!(status.status(), StatusCode::BadRequest);
+    assert_eq!(primary_rounds.get().gc_round, 3);
+}
+
 #[tokio::test]
 async fn delete_batches() {
     telemetry_subscribers::init_for_testing();
@@ -153,8 +351,12 @@ async fn delete_batches() {
         worker_cache,
         store: store.clone(),
         request_batch_timeout: Duration::from_secs(999),
-        request_batch_retry_nodes: 3, // Not used in this test.
+        rx_parameters: parameters(/* sync_retry_nodes */ 3), // Not used in this test.
+        request_batch_hedge_delay: Duration::from_millis(200),
         validator: TrivialTransactionValidator,
+        connection_manager: ConnectionManager::default(),
+        transaction_status: TransactionStatusTracker::default(),
+        primary_rounds: PrimaryRounds::default(),
     };
     let message = WorkerDeleteBatchesMessage {
         digests: vec![digest],
@@ -166,3 +368,415 @@ async fn delete_batches() {
 
     assert!(store.get(&digest).unwrap().is_none());
 }
+
+#[tokio::test]
+async fn report_batch_status() {
+    telemetry_subscribers::init_for_testing();
+
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let committee = fixture.committee();
+    let worker_cache = fixture.worker_cache();
+    let authority_id = fixture.authorities().next().unwrap().id();
+    let id = 0;
+
+    // A batch of ours, acknowledged by a quorum.
+    let transaction_status = TransactionStatusTracker::default();
+    let batch = test_utils::batch();
+    let digest = batch.digest();
+    transaction_status.batched(digest, batch.transactions());
+    transaction_status.update(&digest, TransactionStage::QuorumAcked);
+    let transaction = TransactionStatusTracker::digest(&batch.transactions()[0]);
+    let (_, mut rx_stage) = transaction_status.subscribe(&transaction).unwrap();
+
+    let handler = PrimaryReceiverHandler {
+        authority_id,
+        id,
+        committee,
+        worker_cache,
+        store: test_utils::open_batch_store(),
+        request_batch_timeout: Duration::from_secs(999),
+        rx_parameters: parameters(/* sync_retry_nodes */ 3), // Not used in this test.
+        request_batch_hedge_delay: Duration::from_millis(200),
+        validator: TrivialTransactionValidator,
+        connection_manager: ConnectionManager::default(),
+        transaction_status: transaction_status.clone(),
+        primary_rounds: PrimaryRounds::default(),
+    };
+
+    let certificate = CertificateDigest::default();
+    let committed = BatchStatus::Committed {
+        certificate,
+        round: 1,
+        commit_round: 2,
+    };
+    handler
+        .report_batch_status(anemo::Request::new(WorkerBatchStatusMessage {
+            digests: vec![digest],
+            status: committed.clone(),
+        }))
+        .await
+        .unwrap();
+    rx_stage.changed().await.unwrap();
+    assert_eq!(*rx_stage.borrow(), TransactionStage::from(committed));
+
+    // A late report of the certification does not move the batch back.
+    handler
+        .report_batch_status(anemo::Request::new(WorkerBatchStatusMessage {
+            digests: vec![digest],
+            status: BatchStatus::Certified {
+                certificate,
+                round: 1,
+            },
+        }))
+        .await
+        .unwrap();
+    assert!(transaction_status
+        .status(&transaction)
+        .unwrap()
+        .stage
+        .is_final());
+}
+
+#[tokio::test]
+async fn stream_batches() {
+    telemetry_subscribers::init_for_testing();
+
+    // Create a new test store with a few batches.
+    let store = test_utils::open_batch_store();
+    let batches = test_utils::batches(5);
+    for batch in &batches {
+        store.insert(&batch.digest(), batch).unwrap();
+    }
+    let digests: Vec<_> = batches.iter().map(|batch| batch.digest()).collect();
+
+    // Limit the chunk size so that each batch is served in its own chunk.
+    let (tx_others_batch, _rx_others_batch) = test_utils::test_channel!(1);
+    let handler = WorkerReceiverHandler {
+        id: 0,
+        tx_others_batch,
+        store: store.clone(),
+        validator: TrivialTransactionValidator,
+        max_request_batches_response_size: 1,
+        batches_streams: BatchesStreams::new(1),
+        batch_compressions: PeerBatchCompressions::default(),
+        batch_diff_sync: BatchDiffSyncParameters::default(),
+        recent_batches: RecentBatches::new(Duration::from_secs(60)),
+        fetch_queue: FetchQueue::new(DEFAULT_MAX_CONCURRENT_FETCHES, None),
+        erasure_coding: ErasureCodingParameters::default(),
+        batch_chunks: BatchChunks::default(),
+        tx_forward_chunks: tokio::sync::mpsc::channel(1).0,
+        tx_relay_batches: tokio::sync::mpsc::channel(1).0,
+        replication_log: ReplicationLog::default(),
+    };
+    let peer = anemo::PeerId([1; 32]);
+
+    // Open the stream.
+    let mut request = anemo::Request::new(OpenBatchesStreamRequest {
+        batch_digests: digests.clone(),
+    });
+    assert!(request.extensions_mut().insert(peer).is_none());
+    let response = handler
+        .open_batches_stream(request)
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(response.num_chunks, 5);
+    let mut received = response.first_chunk;
+
+    // Pull the remaining chunks.
+    for chunk_index in 1..response.num_chunks {
+        let mut request = anemo::Request::new(RequestBatchesChunkRequest {
+            stream_id: response.stream_id,
+            chunk_index,
+        });
+        assert!(request.extensions_mut().insert(peer).is_none());
+        let chunk = handler
+            .request_batches_chunk(request)
+            .await
+            .unwrap()
+            .into_body();
+        assert_eq!(chunk.batches.len(), 1);
+        received.extend(chunk.batches);
+    }
+    assert_eq!(received, batches);
+
+    // The stream is closed once all its chunks have been served.
+    let mut request = anemo::Request::new(RequestBatchesChunkRequest {
+        stream_id: response.stream_id,
+        chunk_index: 1,
+    });
+    assert!(request.extensions_mut().insert(peer).is_none());
+    assert!(handler.request_batches_chunk(request).await.is_err());
+}
+
+#[tokio::test]
+async fn request_batches_diff() {
+    telemetry_subscribers::init_for_testing();
+
+    // Create a new test store with a few recent batches, and an old one no longer indexed.
+    let store = test_utils::open_batch_store();
+    let recent_batches = RecentBatches::new(Duration::from_secs(60));
+    let batches = test_utils::batches(4);
+    for batch in &batches {
+        store.insert(&batch.digest(), batch).unwrap();
+        recent_batches.append(batch.digest());
+    }
+    let old_batch = test_utils::batch();
+    store.insert(&old_batch.digest(), &old_batch).unwrap();
+
+    let (tx_others_batch, _rx_others_batch) = test_utils::test_channel!(1);
+    let handler = WorkerReceiverHandler {
+        id: 0,
+        tx_others_batch,
+        store: store.clone(),
+        validator: TrivialTransactionValidator,
+        max_request_batches_response_size: RESPONSE_SIZE_LIMIT,
+        batches_streams: BatchesStreams::new(RESPONSE_SIZE_LIMIT),
+        batch_compressions: PeerBatchCompressions::default(),
+        batch_diff_sync: BatchDiffSyncParameters::default(),
+        recent_batches,
+        fetch_queue: FetchQueue::new(DEFAULT_MAX_CONCURRENT_FETCHES, None),
+        erasure_coding: ErasureCodingParameters::default(),
+        batch_chunks: BatchChunks::default(),
+        tx_forward_chunks: tokio::sync::mpsc::channel(1).0,
+        tx_relay_batches: tokio::sync::mpsc::channel(1).0,
+        replication_log: ReplicationLog::default(),
+    };
+
+    // The requester already has the first two batches.
+    let mut filter = BatchDigestFilter::new(2, 0);
+    filter.insert(&batches[0].digest());
+    filter.insert(&batches[1].digest());
+    let request = anemo::Request::new(RequestBatchesDiffRequest { filter });
+    let mut missing = handler
+        .request_batches_diff(request)
+        .await
+        .unwrap()
+        .into_body()
+        .missing_digests;
+    missing.sort();
+    let mut expected = vec![batches[2].digest(), batches[3].digest()];
+    expected.sort();
+    assert_eq!(missing, expected);
+}
+
+#[tokio::test]
+async fn request_batches_with_remaining_digests() {
+    telemetry_subscribers::init_for_testing();
+
+    // Create a new test store with batches that do not all fit in a single response.
+    let store = test_utils::open_batch_store();
+    let batches: Vec<_> = (0..3u8)
+        .map(|i| Batch::new(vec![vec![i; RESPONSE_SIZE_LIMIT * 2 / 5]]))
+        .collect();
+    for batch in &batches {
+        store.insert(&batch.digest(), batch).unwrap();
+    }
+    let unknown = test_utils::batch();
+
+    let (tx_others_batch, _rx_others_batch) = test_utils::test_channel!(1);
+    let handler = WorkerReceiverHandler {
+        id: 0,
+        tx_others_batch,
+        store: store.clone(),
+        validator: TrivialTransactionValidator,
+        max_request_batches_response_size: RESPONSE_SIZE_LIMIT,
+        batches_streams: BatchesStreams::new(RESPONSE_SIZE_LIMIT),
+        batch_compressions: PeerBatchCompressions::default(),
+        batch_diff_sync: BatchDiffSyncParameters::default(),
+        recent_batches: RecentBatches::new(Duration::from_secs(60)),
+        fetch_queue: FetchQueue::new(DEFAULT_MAX_CONCURRENT_FETCHES, None),
+        erasure_coding: ErasureCodingParameters::default(),
+        batch_chunks: BatchChunks::default(),
+        tx_forward_chunks: tokio::sync::mpsc::channel(1).0,
+        tx_relay_batches: tokio::sync::mpsc::channel(1).0,
+        replication_log: ReplicationLog::default(),
+    };
+
+    // The unknown batch is reported missing, and the batch that does not fit remaining.
+    let request = anemo::Request::new(RequestBatchesV2Request {
+        batch_digests: vec![
+            batches[0].digest(),
+            unknown.digest(),
+            batches[1].digest(),
+            batches[2].digest(),
+        ],
+        priority: FetchPriority::BlocksExecution,
+    });
+    let response = handler
+        .request_batches_v2(request)
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(response.batches, batches[..2].to_vec());
+    assert_eq!(response.remaining_digests, vec![batches[2].digest()]);
+    assert_eq!(response.missing_digests, vec![unknown.digest()]);
+    assert!(response.is_size_limit_reached());
+
+    // Resuming from the remaining digests serves the rest.
+    let request = anemo::Request::new(RequestBatchesV2Request {
+        batch_digests: response.remaining_digests,
+        priority: FetchPriority::BlocksExecution,
+    });
+    let response = handler
+        .request_batches_v2(request)
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(response.batches, batches[2..].to_vec());
+    assert!(response.remaining_digests.is_empty());
+    assert!(response.missing_digests.is_empty());
+
+    // The first version of the route serves the same batches, only reporting that some are left
+    // out. The requester then considers all the others remaining.
+    let requested = vec![
+        batches[0].digest(),
+        unknown.digest(),
+        batches[1].digest(),
+        batches[2].digest(),
+    ];
+    let request = anemo::Request::new(RequestBatchesRequest {
+        batch_digests: requested.clone(),
+    });
+    let response = handler.request_batches(request).await.unwrap().into_body();
+    assert_eq!(response.batches, batches[..2].to_vec());
+    assert!(response.is_size_limit_reached);
+    let response = RequestBatchesV2Response::from_legacy(response, &requested);
+    assert_eq!(
+        response.remaining_digests,
+        vec![unknown.digest(), batches[2].digest()]
+    );
+    assert!(response.missing_digests.is_empty());
+}
+
+#[tokio::test]
+async fn request_batches_with_missing_digests_after_size_limit() {
+    telemetry_subscribers::init_for_testing();
+
+    // Create a new test store with a batch larger than the response size limit.
+    let store = test_utils::open_batch_store();
+    let large = Batch::new(vec![vec![1; RESPONSE_SIZE_LIMIT * 2]]);
+    let small = Batch::new(vec![vec![2; RESPONSE_SIZE_LIMIT / 2]]);
+    store.insert(&large.digest(), &large).unwrap();
+    store.insert(&small.digest(), &small).unwrap();
+    let unknown = test_utils::batch();
+
+    let (tx_others_batch, _rx_others_batch) = test_utils::test_channel!(1);
+    let handler = WorkerReceiverHandler {
+        id: 0,
+        tx_others_batch,
+        store: store.clone(),
+        validator: TrivialTransactionValidator,
+        max_request_batches_response_size: RESPONSE_SIZE_LIMIT,
+        batches_streams: BatchesStreams::new(RESPONSE_SIZE_LIMIT),
+        batch_compressions: PeerBatchCompressions::default(),
+        batch_diff_sync: BatchDiffSyncParameters::default(),
+        recent_batches: RecentBatches::new(Duration::from_secs(60)),
+        fetch_queue: FetchQueue::new(DEFAULT_MAX_CONCURRENT_FETCHES, None),
+        erasure_coding: ErasureCodingParameters::default(),
+        batch_chunks: BatchChunks::default(),
+        tx_forward_chunks: tokio::sync::mpsc::channel(1).0,
+        tx_relay_batches: tokio::sync::mpsc::channel(1).0,
+        replication_log: ReplicationLog::default(),
+    };
+
+    // The large batch is returned on its own. The digests after it are not read, so the unknown
+    // one is reported remaining rather than missing.
+    let request = anemo::Request::new(RequestBatchesV2Request {
+        batch_digests: vec![large.digest(), small.digest(), unknown.digest()],
+        priority: FetchPriority::BlocksExecution,
+    });
+    let response = handler
+        .request_batches_v2(request)
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(response.batches, vec![large]);
+    assert_eq!(
+        response.remaining_digests,
+        vec![small.digest(), unknown.digest()]
+    );
+    assert!(response.missing_digests.is_empty());
+
+    // Once requested again, the unknown batch is reported missing.
+    let request = anemo::Request::new(RequestBatchesV2Request {
+        batch_digests: response.remaining_digests,
+        priority: FetchPriority::BlocksExecution,
+    });
+    let response = handler
+        .request_batches_v2(request)
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(response.batches, vec![small]);
+    assert!(!response.is_size_limit_reached());
+    assert_eq!(response.missing_digests, vec![unknown.digest()]);
+}
+
+#[tokio::test]
+async fn request_replicated_batches() {
+    telemetry_subscribers::init_for_testing();
+
+    // Create a new test store with logged batches, one of which is then removed.
+    let store = test_utils::open_batch_store();
+    let replication_log = ReplicationLog::new(10);
+    let batches = test_utils::batches(4);
+    for batch in &batches {
+        store.insert(&batch.digest(), batch).unwrap();
+        replication_log.append(batch.digest());
+    }
+    store.remove(&batches[1].digest()).unwrap();
+
+    let (tx_others_batch, _rx_others_batch) = test_utils::test_channel!(1);
+    let handler = WorkerReceiverHandler {
+        id: 0,
+        tx_others_batch,
+        store: store.clone(),
+        validator: TrivialTransactionValidator,
+        max_request_batches_response_size: RESPONSE_SIZE_LIMIT,
+        batches_streams: BatchesStreams::new(RESPONSE_SIZE_LIMIT),
+        batch_compressions: PeerBatchCompressions::default(),
+        batch_diff_sync: BatchDiffSyncParameters::default(),
+        recent_batches: RecentBatches::new(Duration::from_secs(60)),
+        fetch_queue: FetchQueue::new(DEFAULT_MAX_CONCURRENT_FETCHES, None),
+        erasure_coding: ErasureCodingParameters::default(),
+        batch_chunks: BatchChunks::default(),
+        tx_forward_chunks: tokio::sync::mpsc::channel(1).0,
+        tx_relay_batches: tokio::sync::mpsc::channel(1).0,
+        replication_log: replication_log.clone(),
+    };
+
+    // A new standby replicates from the start of the log.
+    let request = anemo::Request::new(RequestReplicatedBatchesRequest {
+        log_id: 0,
+        position: 0,
+        max_batches: 3,
+    });
+    let response = handler
+        .request_replicated_batches(request)
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(response.log_id, replication_log.log_id());
+    assert_eq!(
+        response.batches,
+        vec![batches[0].clone(), batches[2].clone()]
+    );
+    assert_eq!(response.next_position, 3);
+    assert!(response.gap);
+
+    // Then resumes from where it stopped.
+    let request = anemo::Request::new(RequestReplicatedBatchesRequest {
+        log_id: response.log_id,
+        position: response.next_position,
+        max_batches: 3,
+    });
+    let response = handler
+        .request_replicated_batches(request)
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(response.batches, vec![batches[3].clone()]);
+    assert_eq!(response.next_position, 4);
+    assert!(!response.gap);
+}
@@ -0,0 +1,119 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+
+fn test_lane_channels(size: usize) -> (LaneSenders, LaneReceivers) {
+    lane_channels(
+        size,
+        &IntGauge::new("TEST_GAUGE", "test gauge").unwrap(),
+        &IntCounter::new("TEST_COUNTER", "test counter").unwrap(),
+    )
+}
+
+async fn send(senders: &LaneSenders, lane: PriorityLane, transaction: Transaction) {
+    let (notifier, _when_done) = tokio::sync::oneshot::channel();
+    senders
+        .get(lane)
+        .send((transaction, notifier))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn strict_priority_serves_highest_lane_first() {
+    let (senders, mut receivers) = test_lane_channels(10);
+    let mut scheduler = LaneScheduler::new(LaneSchedulingPolicy::StrictPriority);
+
+    send(&senders, PriorityLane::Normal, vec![0]).await;
+    send(&senders, PriorityLane::Normal, vec![1]).await;
+    send(&senders, PriorityLane::High, vec![2]).await;
+    send(&senders, PriorityLane::System, vec![3]).await;
+
+    let mut received = Vec::new();
+    for _ in 0..4 {
+        let (lane, transaction, _) = scheduler.next_transaction(&mut receivers).await.unwrap();
+        received.push((lane, transaction));
+    }
+    assert_eq!(
+        received,
+        vec![
+            (PriorityLane::System, vec![3]),
+            (PriorityLane::High, vec![2]),
+            (PriorityLane::Normal, vec![0]),
+            (PriorityLane::Normal, vec![1]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn weighted_round_robin_does_not_starve_lower_lanes() {
+    let (senders, mut receivers) = test_lane_channels(10);
+    let mut scheduler = LaneScheduler::new(LaneSchedulingPolicy::WeightedRoundRobin {
+        system: 2,
+        high: 0,
+        normal: 1,
+    });
+
+    for i in 0..4 {
+        send(&senders, PriorityLane::System, vec![i]).await;
+        send(&senders, PriorityLane::High, vec![i]).await;
+        send(&senders, PriorityLane::Normal, vec![i]).await;
+    }
+
+    let mut lanes = Vec::new();
+    for _ in 0..8 {
+        let (lane, _, _) = scheduler.next_transaction(&mut receivers).await.unwrap();
+        lanes.push(lane);
+    }
+    // A weight of zero is served as one.
+    assert_eq!(
+        lanes,
+        vec![
+            PriorityLane::System,
+            PriorityLane::System,
+            PriorityLane::High,
+            PriorityLane::Normal,
+            PriorityLane::System,
+            PriorityLane::System,
+            PriorityLane::High,
+            PriorityLane::Normal,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn weighted_round_robin_skips_empty_lanes() {
+    let (senders, mut receivers) = test_lane_channels(10);
+    let mut scheduler = LaneScheduler::new(LaneSchedulingPolicy::WeightedRoundRobin {
+        system: 3,
+        high: 1,
+        normal: 1,
+    });
+
+    send(&senders, PriorityLane::Normal, vec![0]).await;
+    send(&senders, PriorityLane::Normal, vec![1]).await;
+
+    let (lane, transaction, _) = scheduler.next_transaction(&mut receivers).await.unwrap();
+    assert_eq!((lane, transaction), (PriorityLane::Normal, vec![0]));
+
+    // The normal lane used its turn, so the system lane is served first again.
+    send(&senders, PriorityLane::System, vec![2]).await;
+    let (lane, transaction, _) = scheduler.next_transaction(&mut receivers).await.unwrap();
+    assert_eq!((lane, transaction), (PriorityLane::System, vec![2]));
+
+    let (lane, transaction, _) = scheduler.next_transaction(&mut receivers).await.unwrap();
+    assert_eq!((lane, transaction), (PriorityLane::Normal, vec![1]));
+}
+
+#[tokio::test]
+async fn next_transaction_returns_none_when_closed() {
+    let (senders, mut receivers) = test_lane_channels(10);
+    let mut scheduler = LaneScheduler::new(LaneSchedulingPolicy::StrictPriority);
+
+    send(&senders, PriorityLane::High, vec![0]).await;
+    drop(senders);
+
+    let (lane, _, _) = scheduler.next_transaction(&mut receivers).await.unwrap();
+    assert_eq!(lane, PriorityLane::High);
+    assert!(scheduler.next_transaction(&mut receivers).await.is_none());
+}
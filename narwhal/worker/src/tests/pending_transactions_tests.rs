@@ -0,0 +1,29 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+
+#[test]
+fn pending_transactions_are_paginated_in_order() {
+    let pending = PendingTransactions::new();
+    let first = pending.track(&[1; 10], PriorityLane::Normal);
+    let second = pending.track(&[2; 20], PriorityLane::System);
+    let _third = pending.track(&[3; 30], PriorityLane::High);
+    assert_eq!(pending.len(), 3);
+
+    let page = pending.list(None, 2);
+    assert_eq!(
+        page.iter().map(|p| (p.size, p.lane)).collect::<Vec<_>>(),
+        vec![(10, PriorityLane::Normal), (20, PriorityLane::System)]
+    );
+    assert_eq!(page[0].digest, TransactionStatusTracker::digest(&[1; 10]));
+
+    let next = pending.list(Some(page[1].id), 2);
+    assert_eq!(next.len(), 1);
+    assert_eq!(next[0].size, 30);
+
+    // The transactions are no longer listed once their guard is dropped.
+    drop(first);
+    drop(second);
+    let page = pending.list(None, 10);
+    assert_eq!(page.iter().map(|p| p.size).collect::<Vec<_>>(), vec![30]);
+}
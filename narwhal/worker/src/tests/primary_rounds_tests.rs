@@ -0,0 +1,54 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[tokio::test]
+async fn rounds_never_go_back() {
+    let rounds = PrimaryRounds::default();
+    assert!(!rounds.is_collected(1));
+
+    rounds.update(WorkerRoundsMessage {
+        committed_round: 10,
+        gc_round: 5,
+    });
+    rounds.update(WorkerRoundsMessage {
+        committed_round: 8,
+        gc_round: 3,
+    });
+    assert_eq!(
+        rounds.get(),
+        WorkerRoundsMessage {
+            committed_round: 10,
+            gc_round: 5,
+        }
+    );
+    assert!(rounds.is_collected(5));
+    assert!(!rounds.is_collected(6));
+}
+
+#[tokio::test]
+async fn collected_resolves_once_the_round_is_collected() {
+    let rounds = PrimaryRounds::default();
+    let waiting = {
+        let rounds = rounds.clone();
+        tokio::spawn(async move { rounds.collected(4).await })
+    };
+
+    rounds.update(WorkerRoundsMessage {
+        committed_round: 6,
+        gc_round: 3,
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!waiting.is_finished());
+
+    rounds.update(WorkerRoundsMessage {
+        committed_round: 7,
+        gc_round: 4,
+    });
+    timeout(Duration::from_secs(5), waiting)
+        .await
+        .unwrap()
+        .unwrap();
+}
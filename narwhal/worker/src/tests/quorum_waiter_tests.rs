@@ -4,7 +4,7 @@
 use super::*;
 use crate::NUM_SHUTDOWN_RECEIVERS;
 use test_utils::{batch, test_network, CommitteeFixture, WorkerToWorkerMockServer};
-use types::PreSubscribedBroadcastSender;
+use types::{BatchCompression, PreSubscribedBroadcastSender};
 
 #[tokio::test]
 async fn wait_for_quorum() {
@@ -28,12 +28,17 @@ async fn wait_for_quorum() {
         tx_shutdown.subscribe(),
         rx_quorum_waiter,
         network.clone(),
+        PeerBatchCompressions::default(),
+        ConnectionManager::default(),
+        ErasureCodingParameters::default(),
+        TopologyHintsParameters::default(),
     );
 
     // Make a batch.
     let batch = batch();
     let message = WorkerBatchMessage {
         batch: batch.clone(),
+        compression: BatchCompression::None,
     };
 
     // Spawn enough listeners to acknowledge our batches.
@@ -85,12 +90,17 @@ async fn pipeline_for_quorum() {
         tx_shutdown.subscribe(),
         rx_quorum_waiter,
         network.clone(),
+        PeerBatchCompressions::default(),
+        ConnectionManager::default(),
+        ErasureCodingParameters::default(),
+        TopologyHintsParameters::default(),
     );
 
     // Make a batch.
     let batch = batch();
     let message = WorkerBatchMessage {
         batch: batch.clone(),
+        compression: BatchCompression::None,
     };
 
     // Spawn enough listeners to acknowledge our batches.
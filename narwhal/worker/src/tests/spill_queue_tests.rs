@@ -0,0 +1,118 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::{lanes::lane_channels, NUM_SHUTDOWN_RECEIVERS};
+use config::AdmissionControlParameters;
+use prometheus::{IntCounter, IntGauge, Registry};
+use std::time::Duration;
+use store::rocks::MetricConf;
+use test_utils::temp_dir;
+use types::PreSubscribedBroadcastSender;
+
+fn open_store() -> DBMap<u64, (PriorityLane, Transaction)> {
+    DBMap::open(
+        temp_dir(),
+        MetricConf::default(),
+        None,
+        Some(NodeStorage::SPILLED_TRANSACTIONS_CF),
+        &ReadWriteOptions::default(),
+    )
+    .unwrap()
+}
+
+fn test_metrics() -> Arc<WorkerMetrics> {
+    Arc::new(WorkerMetrics::new(&Registry::new()))
+}
+
+#[test]
+fn spilling_is_bounded() {
+    let queue = SpillQueue::new(open_store(), 10, test_metrics());
+    assert!(queue.is_empty());
+
+    assert!(queue.try_spill(PriorityLane::Normal, &vec![0; 6]));
+    assert!(!queue.is_empty());
+    assert!(!queue.try_spill(PriorityLane::Normal, &vec![1; 6]));
+    assert!(queue.try_spill(PriorityLane::High, &vec![2; 4]));
+    assert_eq!(queue.metrics.spilled_transactions.get(), 2);
+    assert_eq!(queue.metrics.spilled_transaction_bytes.get(), 10);
+
+    let disabled = SpillQueue::new(open_store(), 0, test_metrics());
+    assert!(!disabled.try_spill(PriorityLane::Normal, &vec![0]));
+    assert!(disabled.is_empty());
+}
+
+#[test]
+fn reopened_queue_resumes_spilled_transactions() {
+    let store = open_store();
+    let queue = SpillQueue::new(store.clone(), 10, test_metrics());
+    assert!(queue.try_spill(PriorityLane::Normal, &vec![0; 4]));
+    assert!(queue.try_spill(PriorityLane::Normal, &vec![1; 4]));
+    drop(queue);
+
+    let queue = SpillQueue::new(store, 10, test_metrics());
+    assert!(!queue.is_empty());
+    assert_eq!(queue.metrics.spilled_transaction_bytes.get(), 8);
+    assert!(!queue.try_spill(PriorityLane::Normal, &vec![2; 4]));
+    assert!(queue.try_spill(PriorityLane::Normal, &vec![3; 2]));
+    // The new transaction is queued after the resumed ones.
+    assert_eq!(queue.front(0).unwrap().0, 0);
+    assert_eq!(
+        queue.front(2).unwrap(),
+        (2, (PriorityLane::Normal, vec![3; 2]))
+    );
+}
+
+#[tokio::test]
+async fn restores_spilled_transactions_in_order() {
+    let metrics = test_metrics();
+    let admission_controller = AdmissionController::new(
+        AdmissionControlParameters {
+            max_pending_batch_bytes: 100,
+            max_inflight_quorum_waits: 10,
+            retry_after: Duration::from_millis(10),
+            max_spilled_bytes: 100,
+        },
+        metrics.clone(),
+    );
+    // The worker is overloaded until the pending transaction is acknowledged.
+    let pending = admission_controller.try_admit(100).unwrap();
+
+    let queue = SpillQueue::new(open_store(), 100, metrics.clone());
+    assert!(queue.try_spill(PriorityLane::Normal, &vec![0]));
+    assert!(queue.try_spill(PriorityLane::High, &vec![1]));
+    assert!(queue.try_spill(PriorityLane::Normal, &vec![2]));
+
+    let (senders, mut receivers) = lane_channels(
+        10,
+        &IntGauge::new("TEST_GAUGE", "test gauge").unwrap(),
+        &IntCounter::new("TEST_COUNTER", "test counter").unwrap(),
+    );
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let _restorer = queue.spawn_restorer(
+        admission_controller.clone(),
+        senders,
+        tx_shutdown.subscribe(),
+    );
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(receivers.get_mut(PriorityLane::Normal).try_recv().is_err());
+    drop(pending);
+
+    let mut notifiers = Vec::new();
+    for (lane, expected) in [
+        (PriorityLane::Normal, vec![0]),
+        (PriorityLane::High, vec![1]),
+        (PriorityLane::Normal, vec![2]),
+    ] {
+        let (transaction, notifier) = receivers.get_mut(lane).recv().await.unwrap();
+        assert_eq!(transaction, expected);
+        notifiers.push(notifier);
+    }
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    // The restored transactions are accounted for until their batch is acknowledged.
+    assert_eq!(metrics.pending_batch_bytes.get(), 3);
+    assert!(queue.is_empty());
+    assert_eq!(metrics.restored_spilled_transactions.get(), 3);
+    drop(notifiers);
+}
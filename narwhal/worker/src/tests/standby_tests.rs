@@ -0,0 +1,132 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use anemo::codegen::InboundRequestLayer;
+use anemo_tower::auth::{AllowedPeers, RequireAuthorizationLayer};
+use std::time::Duration;
+use test_utils::CommitteeFixture;
+use types::{MockWorkerToWorker, WorkerToWorkerServer};
+
+#[test]
+fn replication_log_holds_the_most_recent_digests() {
+    let log = ReplicationLog::new(3);
+    let batches = test_utils::batches(5);
+    for batch in &batches {
+        log.append(batch.digest());
+    }
+    let digests: Vec<_> = batches.iter().map(|batch| batch.digest()).collect();
+
+    // The first two digests were evicted.
+    assert_eq!(
+        log.read(log.log_id(), 0, 10),
+        (2, digests[2..].to_vec(), true)
+    );
+    assert_eq!(
+        log.read(log.log_id(), 3, 1),
+        (3, digests[3..4].to_vec(), false)
+    );
+    assert_eq!(log.read(log.log_id(), 5, 10), (5, vec![], false));
+    // Positions of another log are not meaningful.
+    assert_eq!(
+        log.read(log.log_id().wrapping_add(1), 3, 10),
+        (2, digests[2..].to_vec(), true)
+    );
+
+    let disabled = ReplicationLog::default();
+    disabled.append(digests[0]);
+    assert_eq!(disabled.read(disabled.log_id(), 0, 10), (0, vec![], false));
+}
+
+#[tokio::test]
+async fn standby_takes_over_unresponsive_worker() {
+    telemetry_subscribers::init_for_testing();
+
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let worker = fixture.authorities().next().unwrap().worker(0);
+    let parameters = WorkerStandbyParameters {
+        replication_interval: Duration::from_millis(50),
+        failover_timeout: Duration::from_millis(500),
+        ..WorkerStandbyParameters::default()
+    };
+    let standby = WorkerStandby::new(
+        0,
+        worker.keypair(),
+        worker.info().worker_address.clone(),
+        fixture.committee().epoch(),
+        test_utils::open_batch_store(),
+        parameters,
+    );
+
+    // No worker is running, so the standby takes over after the failover timeout.
+    tokio::time::timeout(Duration::from_secs(10), standby.run())
+        .await
+        .expect("The standby did not take over");
+}
+
+#[tokio::test]
+async fn standby_replicates_running_worker_then_takes_over() {
+    telemetry_subscribers::init_for_testing();
+
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let worker = fixture.authorities().next().unwrap().worker(0);
+    let batches = test_utils::batches(3);
+
+    // The worker serves its batches to its standby only, as the worker does.
+    let mut worker_server = MockWorkerToWorker::new();
+    let logged = batches.clone();
+    worker_server
+        .expect_request_replicated_batches()
+        .returning(move |request| {
+            let position = request.body().position as usize;
+            Ok(anemo::Response::new(RequestReplicatedBatchesResponse {
+                log_id: 1,
+                next_position: logged.len() as u64,
+                batches: logged[position.min(logged.len())..].to_vec(),
+                gap: false,
+            }))
+        });
+    let standby_peer_id = PeerId(standby_keypair(&worker.keypair()).public().0.to_bytes());
+    let worker_network = worker.new_network(anemo::Router::new().add_rpc_service(
+        WorkerToWorkerServer::new(worker_server).add_layer_for_request_replicated_batches(
+            InboundRequestLayer::new(RequireAuthorizationLayer::new(AllowedPeers::new([
+                standby_peer_id,
+            ]))),
+        ),
+    ));
+
+    let store = test_utils::open_batch_store();
+    let parameters = WorkerStandbyParameters {
+        replication_interval: Duration::from_millis(50),
+        failover_timeout: Duration::from_millis(500),
+        ..WorkerStandbyParameters::default()
+    };
+    let standby = WorkerStandby::new(
+        0,
+        worker.keypair(),
+        worker.info().worker_address.clone(),
+        fixture.committee().epoch(),
+        store.clone(),
+        parameters,
+    );
+    let standby = tokio::spawn(standby.run());
+
+    // The standby replicates the batches of the running worker, holding the same key.
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !batches
+            .iter()
+            .all(|batch| store.contains_key(&batch.digest()).unwrap())
+        {
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("The standby did not replicate the batches");
+    assert!(!standby.is_finished());
+
+    // And takes over once the worker stops.
+    worker_network.shutdown().await.unwrap();
+    tokio::time::timeout(Duration::from_secs(10), standby)
+        .await
+        .expect("The standby did not take over")
+        .unwrap();
+}
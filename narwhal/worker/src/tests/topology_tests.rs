@@ -0,0 +1,58 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use std::num::NonZeroUsize;
+use test_utils::CommitteeFixture;
+
+fn peers() -> Vec<Peer> {
+    let fixture = CommitteeFixture::builder()
+        .committee_size(NonZeroUsize::new(6).unwrap())
+        .build();
+    fixture
+        .authorities()
+        .map(|authority| {
+            (
+                authority.public_key(),
+                authority.worker(0).info().name.clone(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn without_hints_all_workers_are_sent_directly() {
+    let mut peers = peers();
+    let myself = peers.remove(0);
+    let (direct, relayed) = plan_broadcast(
+        &TopologyHintsParameters::default(),
+        &myself.0,
+        peers.clone(),
+    );
+    assert_eq!(direct, peers);
+    assert!(relayed.is_empty());
+}
+
+#[test]
+fn remote_groups_are_relayed() {
+    let mut peers = peers();
+    let myself = peers.remove(0);
+    let hints = TopologyHintsParameters {
+        peer_groups: BTreeMap::from([
+            ("a".to_string(), vec![myself.0.clone(), peers[0].0.clone()]),
+            (
+                "b".to_string(),
+                vec![peers[1].0.clone(), peers[2].0.clone()],
+            ),
+            ("c".to_string(), vec![peers[3].0.clone()]),
+        ]),
+        ..Default::default()
+    };
+    let (direct, relayed) = plan_broadcast(&hints, &myself.0, peers.clone());
+
+    // Our group, the workers of no group, and the groups of a single worker are sent directly.
+    assert_eq!(
+        direct,
+        vec![peers[0].clone(), peers[4].clone(), peers[3].clone()]
+    );
+    assert_eq!(relayed, vec![vec![peers[1].clone(), peers[2].clone()]]);
+}
@@ -0,0 +1,62 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use store::rocks::MetricConf;
+use test_utils::{temp_dir, transaction};
+
+fn open_log(durability: TransactionDurability) -> TransactionLog {
+    TransactionLog::new(
+        DBMap::open(
+            temp_dir(),
+            MetricConf::default(),
+            None,
+            Some(NodeStorage::TRANSACTION_LOG_CF),
+            &ReadWriteOptions::default(),
+        )
+        .unwrap(),
+        durability,
+    )
+}
+
+#[tokio::test]
+async fn append_recover_remove() {
+    let log = open_log(TransactionDurability::Fsync);
+    let tx0 = transaction();
+    let tx1 = transaction();
+    let (batch, logged) = log
+        .append(PriorityLane::High, Batch::new(vec![tx0.clone()]))
+        .await;
+    logged.unwrap();
+    assert_eq!(batch.transactions(), &vec![tx0.clone()]);
+    // Logging a transaction again does not duplicate it.
+    let (_, logged) = log
+        .append(
+            PriorityLane::Normal,
+            Batch::new(vec![tx1.clone(), tx1.clone()]),
+        )
+        .await;
+    logged.unwrap();
+
+    let mut recovered = log.recover();
+    recovered.sort();
+    let mut expected = vec![
+        (PriorityLane::High, tx0.clone()),
+        (PriorityLane::Normal, tx1),
+    ];
+    expected.sort();
+    assert_eq!(recovered, expected);
+
+    log.remove(&[tx0.clone()]).unwrap();
+    assert_eq!(log.recover().len(), 1);
+    assert!(!log.recover().iter().any(|(_, tx)| *tx == tx0));
+}
+
+#[tokio::test]
+async fn disabled_log() {
+    let log = open_log(TransactionDurability::None);
+    let (_, logged) = log
+        .append(PriorityLane::Normal, Batch::new(vec![transaction()]))
+        .await;
+    logged.unwrap();
+    assert!(log.recover().is_empty());
+}
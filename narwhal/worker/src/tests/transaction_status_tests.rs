@@ -0,0 +1,73 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use test_utils::transaction;
+
+fn certified() -> TransactionStage {
+    TransactionStage::Certified {
+        certificate: CertificateDigest::default(),
+        round: 1,
+    }
+}
+
+#[tokio::test]
+async fn track_the_stages_of_a_batch() {
+    let tracker = TransactionStatusTracker::default();
+    let transactions = vec![transaction(), transaction()];
+    let digest = TransactionStatusTracker::digest(&transactions[1]);
+    let batch = BatchDigest::new([1; crypto::DIGEST_LENGTH]);
+
+    // Unknown until batched.
+    assert_eq!(tracker.status(&digest), None);
+    let waiter = {
+        let tracker = tracker.clone();
+        tokio::spawn(async move { tracker.wait_for(&digest).await })
+    };
+    tokio::task::yield_now().await;
+    tracker.batched(batch, &transactions);
+    let (waited_batch, mut rx_stage) = waiter.await.unwrap();
+    assert_eq!(waited_batch, batch);
+    assert_eq!(*rx_stage.borrow_and_update(), TransactionStage::Batched);
+
+    tracker.update(&batch, TransactionStage::QuorumAcked);
+    tracker.update(&batch, certified());
+    rx_stage.changed().await.unwrap();
+    assert_eq!(*rx_stage.borrow_and_update(), certified());
+
+    // The stages do not go backwards.
+    tracker.update(&batch, TransactionStage::QuorumAcked);
+    assert!(!rx_stage.has_changed().unwrap());
+    assert_eq!(
+        tracker.status(&digest),
+        Some(TransactionStatus {
+            batch,
+            stage: certified(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn forget_the_oldest_batches() {
+    let tracker = TransactionStatusTracker::new(10, 1);
+    let first = transaction();
+    let first_batch = BatchDigest::new([1; crypto::DIGEST_LENGTH]);
+    tracker.batched(first_batch, &[first.clone()]);
+    let (_, mut rx_stage) = tracker
+        .subscribe(&TransactionStatusTracker::digest(&first))
+        .unwrap();
+
+    // Tracking a new batch evicts the first one, closing its subscriptions.
+    let second = transaction();
+    tracker.batched(
+        BatchDigest::new([2; crypto::DIGEST_LENGTH]),
+        &[second.clone()],
+    );
+    assert!(rx_stage.changed().await.is_err());
+    assert_eq!(
+        tracker.status(&TransactionStatusTracker::digest(&first)),
+        None
+    );
+    assert!(tracker
+        .status(&TransactionStatusTracker::digest(&second))
+        .is_some());
+}
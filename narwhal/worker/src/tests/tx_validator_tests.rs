@@ -0,0 +1,61 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use test_utils::transaction;
+use types::BatchAPI;
+
+// Rejects the batches with more than one transaction, and counts the validated batches.
+#[derive(Clone, Default)]
+struct CountingValidator {
+    validated: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl TransactionValidator for CountingValidator {
+    type Error = eyre::Report;
+
+    async fn validate(&self, _t: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn validate_batch(&self, b: &Batch) -> Result<(), Self::Error> {
+        self.validated.fetch_add(1, Ordering::Relaxed);
+        if b.transactions().len() > 1 {
+            eyre::bail!("Too many transactions");
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn batch_verdicts_are_cached() {
+    let validator = CountingValidator::default();
+    let cache = BatchVerdictCache::new(validator.clone(), 10);
+
+    let valid = Batch::new(vec![transaction()]);
+    let invalid = Batch::new(vec![transaction(), transaction()]);
+
+    for _ in 0..3 {
+        assert!(cache.validate_batch(&valid).await.is_ok());
+        assert!(cache.validate_batch(&invalid).await.is_err());
+    }
+    // Each batch was only validated once, and the clones share the verdicts.
+    assert_eq!(validator.validated.load(Ordering::Relaxed), 2);
+    assert!(cache.clone().validate_batch(&valid).await.is_ok());
+    assert_eq!(validator.validated.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn batch_verdicts_are_evicted() {
+    let validator = CountingValidator::default();
+    let cache = BatchVerdictCache::new(validator.clone(), 1);
+
+    let first = Batch::new(vec![transaction()]);
+    let second = Batch::new(vec![transaction()]);
+
+    assert!(cache.validate_batch(&first).await.is_ok());
+    assert!(cache.validate_batch(&second).await.is_ok());
+    assert!(cache.validate_batch(&first).await.is_ok());
+    assert_eq!(validator.validated.load(Ordering::Relaxed), 3);
+}
@@ -2,11 +2,12 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use super::*;
-use crate::LocalNarwhalClient;
+use crate::{client::NarwhalError, drain_channel, LocalNarwhalClient, TransactionHandoff};
 use crate::{metrics::initialise_metrics, TrivialTransactionValidator};
 use async_trait::async_trait;
 use bytes::Bytes;
 use consensus::consensus::ConsensusRound;
+use consensus::leader_schedule::{LeaderSchedule, LeaderSwapTable};
 use consensus::{dag::Dag, metrics::ConsensusMetrics};
 use fastcrypto::{
     encoding::{Encoding, Hex},
@@ -14,8 +15,10 @@ use fastcrypto::{
 };
 use futures::stream::FuturesOrdered;
 use futures::StreamExt;
+use network::request_budget::RATE_LIMITED;
 use primary::{NetworkModel, Primary, CHANNEL_CAPACITY, NUM_SHUTDOWN_RECEIVERS};
 use prometheus::Registry;
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::time::Duration;
 use storage::NodeStorage;
 use store::rocks;
@@ -24,9 +27,9 @@ use store::rocks::ReadWriteOptions;
 use test_utils::{batch, temp_dir, test_network, transaction, CommitteeFixture};
 use tokio::sync::watch;
 use types::{
-    BatchAPI, MockWorkerToPrimary, MockWorkerToWorker, PreSubscribedBroadcastSender,
-    TransactionProto, TransactionsClient, WorkerBatchMessage, WorkerToPrimaryServer,
-    WorkerToWorkerClient,
+    BatchAPI, BatchCompression, MockWorkerToPrimary, MockWorkerToWorker, OpenBatchesStreamRequest,
+    PreSubscribedBroadcastSender, RequestBatchesChunkRequest, TransactionProto, TransactionsClient,
+    WorkerBatchMessage, WorkerToPrimaryServer, WorkerToWorkerClient,
 };
 
 // A test validator that rejects every transaction / batch
@@ -36,7 +39,7 @@ struct NilTxValidator;
 impl TransactionValidator for NilTxValidator {
     type Error = eyre::Report;
 
-    fn validate(&self, _tx: &[u8]) -> Result<(), Self::Error> {
+    async fn validate(&self, _tx: &[u8]) -> Result<(), Self::Error> {
         eyre::bail!("Invalid transaction");
     }
     async fn validate_batch(&self, _txs: &Batch) -> Result<(), Self::Error> {
@@ -82,11 +85,13 @@ async fn reject_invalid_clients_transactions() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        parameters,
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters).1,
         NilTxValidator,
         batch_store,
         metrics,
         &mut tx_shutdown,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Wait till other services have been able to start up
@@ -102,6 +107,7 @@ async fn reject_invalid_clients_transactions() {
     let tx = transaction();
     let txn = TransactionProto {
         transaction: Bytes::from(tx.clone()),
+        ..Default::default()
     };
 
     // Check invalid transactions are rejected
@@ -113,6 +119,7 @@ async fn reject_invalid_clients_transactions() {
     let batch = batch();
     let batch_message = WorkerBatchMessage {
         batch: batch.clone(),
+        compression: BatchCompression::None,
     };
 
     // setup network : impersonate a send from another worker
@@ -136,6 +143,83 @@ async fn reject_invalid_clients_transactions() {
     assert!(res.is_err());
 }
 
+#[tokio::test]
+async fn refuse_batches_chunk_requests_over_budget() {
+    let fixture = CommitteeFixture::builder().randomize_ports(true).build();
+    let committee = fixture.committee();
+    let worker_cache = fixture.worker_cache();
+
+    let worker_id = 0;
+    let my_primary = fixture.authorities().next().unwrap();
+    let myself = my_primary.worker(worker_id);
+    let public_key = my_primary.public_key();
+
+    // A single request per second for each peer.
+    let mut parameters = Parameters::default();
+    parameters.anemo.request_batches_budget.per_peer = NonZeroU32::new(1);
+
+    let batch_store = rocks::DBMap::<BatchDigest, Batch>::open(
+        temp_dir(),
+        MetricConf::default(),
+        None,
+        Some("batches"),
+        &ReadWriteOptions::default(),
+    )
+    .unwrap();
+
+    let registry = Registry::new();
+    let metrics = initialise_metrics(&registry);
+
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+
+    Worker::spawn(
+        my_primary.authority().clone(),
+        myself.keypair(),
+        worker_id,
+        committee.clone(),
+        worker_cache.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters).1,
+        TrivialTransactionValidator::default(),
+        batch_store,
+        metrics,
+        &mut tx_shutdown,
+        drain_channel(TransactionHandoff::default()).1,
+    );
+
+    // Wait till other services have been able to start up
+    tokio::task::yield_now().await;
+
+    let worker_pk = worker_cache.worker(&public_key, &worker_id).unwrap().name;
+    let another_worker = fixture.authorities().nth(2).unwrap().worker(worker_id);
+    let network = test_network(
+        another_worker.keypair(),
+        &another_worker.info().worker_address,
+    );
+    network
+        .connect(myself.info().worker_address.to_anemo_address().unwrap())
+        .await
+        .unwrap();
+    let peer = network.peer(PeerId(worker_pk.0.to_bytes())).unwrap();
+    let mut client = WorkerToWorkerClient::new(peer);
+
+    // Opening a stream spends the budget of the peer, which its chunk requests share.
+    let response = client
+        .open_batches_stream(OpenBatchesStreamRequest {
+            batch_digests: vec![],
+        })
+        .await;
+    assert!(response.is_ok());
+    let status = client
+        .request_batches_chunk(RequestBatchesChunkRequest {
+            stream_id: 0,
+            chunk_index: 1,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.status(), RATE_LIMITED);
+}
+
 /// TODO: test both RemoteNarwhalClient and LocalNarwhalClient in the same test case.
 #[tokio::test]
 async fn handle_remote_clients_transactions() {
@@ -175,11 +259,13 @@ async fn handle_remote_clients_transactions() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        parameters,
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters).1,
         TrivialTransactionValidator::default(),
         batch_store,
         metrics,
         &mut tx_shutdown,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Spawn a network listener to receive our batch's digest.
@@ -227,12 +313,15 @@ async fn handle_remote_clients_transactions() {
     let config = mysten_network::config::Config::new();
     let channel = config.connect_lazy(&address).unwrap();
     let client = TransactionsClient::new(channel);
+    let mut resubmitting_client = client.clone();
+    let resubmitted = batch.transactions()[0].clone();
 
     let join_handle = tokio::task::spawn(async move {
         let mut fut_list = FuturesOrdered::new();
         for tx in batch.transactions() {
             let txn = TransactionProto {
                 transaction: Bytes::from(tx.clone()),
+                ..Default::default()
             };
 
             // Calls to submit_transaction are now blocking, so we need to drive them
@@ -252,6 +341,21 @@ async fn handle_remote_clients_transactions() {
 
     // Ensure sending ended.
     assert!(join_handle.await.is_ok());
+
+    // A resubmitted transaction is acknowledged with its batch, without being batched again.
+    let response = resubmitting_client
+        .submit_transaction(TransactionProto {
+            transaction: Bytes::from(resubmitted),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let metadata = response.metadata();
+    assert_eq!(
+        metadata.get("batch-digest").unwrap(),
+        Hex::encode(batch_digest.0).as_str()
+    );
+    assert!(metadata.get("transaction-stage").is_some());
 }
 
 /// TODO: test both RemoteNarwhalClient and LocalNarwhalClient in the same test case.
@@ -293,11 +397,13 @@ async fn handle_local_clients_transactions() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        parameters,
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(parameters).1,
         TrivialTransactionValidator::default(),
         batch_store,
         metrics,
         &mut tx_shutdown,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Spawn a network listener to receive our batch's digest.
@@ -342,6 +448,8 @@ async fn handle_local_clients_transactions() {
         .unwrap()
         .transactions;
     let client = LocalNarwhalClient::get_global(&address).unwrap().load();
+    let resubmitting_client = client.clone();
+    let resubmitted = batch.transactions()[0].clone();
 
     let join_handle = tokio::task::spawn(async move {
         let mut fut_list = FuturesOrdered::new();
@@ -363,6 +471,106 @@ async fn handle_local_clients_transactions() {
 
     // Ensure sending ended.
     assert!(join_handle.await.is_ok());
+
+    // A resubmitted transaction is acknowledged, without being batched again.
+    resubmitting_client
+        .submit_transaction(resubmitted)
+        .await
+        .unwrap();
+}
+
+// A test validator whose transactions are their own sender.
+#[derive(Clone)]
+struct SenderIsTransactionValidator;
+#[async_trait]
+impl TransactionValidator for SenderIsTransactionValidator {
+    type Error = eyre::Report;
+
+    async fn validate(&self, _tx: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn validate_batch(&self, _batch: &Batch) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn sender(&self, t: &[u8]) -> Option<Vec<u8>> {
+        Some(t.to_vec())
+    }
+}
+
+#[tokio::test]
+async fn reject_local_transactions_of_other_workers_senders() {
+    let fixture = CommitteeFixture::builder()
+        .randomize_ports(true)
+        .number_of_workers(NonZeroUsize::new(2).unwrap())
+        .build();
+    let committee = fixture.committee();
+    let mut worker_cache = fixture.worker_cache();
+    worker_cache.sharding = WorkerSharding::BySender;
+
+    let worker_id = 0;
+    let my_primary = fixture.authorities().next().unwrap();
+    let myself = my_primary.worker(worker_id);
+    let authority_public_key = my_primary.public_key();
+
+    let batch_store = rocks::DBMap::<BatchDigest, Batch>::open(
+        temp_dir(),
+        MetricConf::default(),
+        None,
+        Some("batches"),
+        &ReadWriteOptions::default(),
+    )
+    .unwrap();
+
+    let registry = Registry::new();
+    let metrics = initialise_metrics(&registry);
+
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+
+    // Spawn a `Worker` instance.
+    Worker::spawn(
+        my_primary.authority().clone(),
+        myself.keypair(),
+        worker_id,
+        committee.clone(),
+        worker_cache.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(Parameters::default()).1,
+        SenderIsTransactionValidator,
+        batch_store,
+        metrics,
+        &mut tx_shutdown,
+        drain_channel(TransactionHandoff::default()).1,
+    );
+
+    // Wait till other services have been able to start up
+    tokio::task::yield_now().await;
+
+    // Find a sender assigned to the other worker.
+    let transaction = (0u8..)
+        .map(|i| vec![i])
+        .find(|sender| {
+            worker_cache
+                .worker_for_sender(&authority_public_key, sender)
+                .unwrap()
+                .0
+                != worker_id
+        })
+        .unwrap();
+
+    let address = worker_cache
+        .worker(&authority_public_key, &worker_id)
+        .unwrap()
+        .transactions;
+    let client = LocalNarwhalClient::get_global(&address).unwrap().load();
+
+    // The transaction is rejected as it would be through gRPC, with the worker of its sender.
+    let result = client.submit_transaction(transaction).await;
+    assert!(
+        matches!(result, Err(NarwhalError::WrongWorker(id, _)) if id != worker_id),
+        "{result:?}"
+    );
 }
 
 #[tokio::test]
@@ -399,12 +607,15 @@ async fn get_network_peers_from_admin_server() {
         authority_1.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_1_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_1_parameters.clone()).1,
         store.header_store.clone(),
         store.certificate_store.clone(),
         store.proposer_store.clone(),
         store.payload_store.clone(),
         store.vote_digest_store.clone(),
+        store.consensus_store.clone(),
+        store.batch_store.clone(),
         tx_new_certificates,
         rx_feedback,
         rx_consensus_round_updates,
@@ -419,6 +630,7 @@ async fn get_network_peers_from_admin_server() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown,
         tx_feedback,
         &Registry::new(),
@@ -444,11 +656,13 @@ async fn get_network_peers_from_admin_server() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        worker_1_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(worker_1_parameters.clone()).1,
         TrivialTransactionValidator::default(),
         store.batch_store.clone(),
         metrics_1.clone(),
         &mut tx_shutdown,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     let primary_1_peer_id = Hex::encode(authority_1.network_keypair().copy().public().0.as_bytes());
@@ -521,12 +735,15 @@ async fn get_network_peers_from_admin_server() {
         authority_2.network_keypair().copy(),
         committee.clone(),
         worker_cache.clone(),
-        primary_2_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(primary_2_parameters.clone()).1,
         store.header_store.clone(),
         store.certificate_store.clone(),
         store.proposer_store.clone(),
         store.payload_store.clone(),
         store.vote_digest_store.clone(),
+        store.consensus_store.clone(),
+        store.batch_store.clone(),
         tx_new_certificates_2,
         rx_feedback_2,
         rx_consensus_round_updates,
@@ -541,6 +758,7 @@ async fn get_network_peers_from_admin_server() {
             .1,
         )),
         NetworkModel::Asynchronous,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
         &mut tx_shutdown_2,
         tx_feedback_2,
         &Registry::new(),
@@ -567,11 +785,13 @@ async fn get_network_peers_from_admin_server() {
         worker_id,
         committee.clone(),
         worker_cache.clone(),
-        worker_2_parameters.clone(),
+        watch::channel(PeerAddresses::default()).1,
+        watch::channel(worker_2_parameters.clone()).1,
         TrivialTransactionValidator::default(),
         store.batch_store,
         metrics_2.clone(),
         &mut tx_shutdown_worker,
+        drain_channel(TransactionHandoff::default()).1,
     );
 
     // Wait for tasks to start. Sleeping longer here to ensure all primaries and workers
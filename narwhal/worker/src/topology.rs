@@ -0,0 +1,165 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::batch_compression::PeerBatchCompressions;
+use anemo::PeerId;
+use config::{Authority, TopologyHintsParameters, WorkerCache, WorkerId};
+use crypto::{NetworkPublicKey, PublicKey};
+use fastcrypto::hash::Hash;
+use futures::{stream::FuturesUnordered, FutureExt as _, StreamExt as _};
+use mysten_metrics::{spawn_logged_monitored_task, spawn_monitored_task};
+use network::connection_manager::ConnectionManager;
+use std::{collections::BTreeMap, time::Duration};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::{timeout_at, Instant},
+};
+use tracing::debug;
+use types::{ConditionalBroadcastReceiver, WorkerBatchMessage, WorkerRelayBatchMessage};
+
+#[cfg(test)]
+#[path = "tests/topology_tests.rs"]
+pub mod topology_tests;
+
+/// A worker of another authority, along with its authority.
+pub type Peer = (PublicKey, NetworkPublicKey);
+
+/// A batch to relay, and the channel to report back the workers which acknowledged it.
+pub type RelayRequest = (
+    WorkerRelayBatchMessage,
+    oneshot::Sender<Vec<NetworkPublicKey>>,
+);
+
+/// Splits the workers to broadcast a batch to, following the topology hints, between the ones
+/// sent the batch directly, and the groups of the other regions, each sent the batch through
+/// one of its workers. A group with a single worker is sent the batch directly.
+pub fn plan_broadcast(
+    hints: &TopologyHintsParameters,
+    authority: &PublicKey,
+    workers: Vec<Peer>,
+) -> (Vec<Peer>, Vec<Vec<Peer>>) {
+    let own_group = hints.group_of(authority);
+    let mut direct = Vec::new();
+    let mut groups: BTreeMap<&str, Vec<Peer>> = BTreeMap::new();
+    for worker in workers {
+        match hints.group_of(&worker.0) {
+            Some(group) if Some(group) != own_group => {
+                groups.entry(group).or_default().push(worker)
+            }
+            _ => direct.push(worker),
+        }
+    }
+    let mut relayed = Vec::new();
+    for group in groups.into_values() {
+        if group.len() == 1 {
+            direct.extend(group);
+        } else {
+            relayed.push(group);
+        }
+    }
+    (direct, relayed)
+}
+
+/// Relays the batches received from the workers of the other regions to the workers of our
+/// region, and reports back the ones which acknowledged them in time.
+pub struct BatchRelay {
+    authority: Authority,
+    id: WorkerId,
+    worker_cache: WorkerCache,
+    network: anemo::Network,
+    connection_manager: ConnectionManager,
+    batch_compressions: PeerBatchCompressions,
+    // How long the workers relayed to are waited for.
+    ack_timeout: Duration,
+    rx_relay: mpsc::Receiver<RelayRequest>,
+    rx_shutdown: ConditionalBroadcastReceiver,
+}
+
+impl BatchRelay {
+    #[must_use]
+    pub fn spawn(
+        authority: Authority,
+        id: WorkerId,
+        worker_cache: WorkerCache,
+        network: anemo::Network,
+        connection_manager: ConnectionManager,
+        batch_compressions: PeerBatchCompressions,
+        ack_timeout: Duration,
+        rx_relay: mpsc::Receiver<RelayRequest>,
+        rx_shutdown: ConditionalBroadcastReceiver,
+    ) -> JoinHandle<()> {
+        spawn_logged_monitored_task!(
+            Self {
+                authority,
+                id,
+                worker_cache,
+                network,
+                connection_manager,
+                batch_compressions,
+                ack_timeout,
+                rx_relay,
+                rx_shutdown,
+            }
+            .run(),
+            "BatchRelayTask"
+        )
+    }
+
+    async fn run(mut self) {
+        loop {
+            tokio::select! {
+                Some((message, tx_acknowledged)) = self.rx_relay.recv() => {
+                    self.relay(message, tx_acknowledged);
+                },
+
+                _ = self.rx_shutdown.receiver.recv() => {
+                    return
+                }
+            }
+        }
+    }
+
+    fn relay(
+        &self,
+        message: WorkerRelayBatchMessage,
+        tx_acknowledged: oneshot::Sender<Vec<NetworkPublicKey>>,
+    ) {
+        let WorkerRelayBatchMessage { message, relay_to } = message;
+        // Only the other workers of the committee with our id are relayed to.
+        let mut handlers: FuturesUnordered<_> = self
+            .worker_cache
+            .others_workers_by_id(self.authority.protocol_key(), &self.id)
+            .into_iter()
+            .map(|(_, info)| info.name)
+            .filter(|name| relay_to.contains(name))
+            .map(|name| {
+                let compression = self.batch_compressions.get(&PeerId(name.0.to_bytes()));
+                let message = WorkerBatchMessage {
+                    batch: message.batch.clone(),
+                    compression,
+                };
+                let handler =
+                    self.connection_manager
+                        .broadcast(&self.network, name.clone(), &message);
+                handler.map(move |result| result.ok().map(|_| name))
+            })
+            .collect();
+
+        let deadline = Instant::now() + self.ack_timeout;
+        spawn_monitored_task!(async move {
+            // The workers which did not acknowledge in time are not reported, the originator
+            // sending them the batch directly.
+            let mut acknowledged = Vec::new();
+            while let Ok(Some(name)) = timeout_at(deadline, handlers.next()).await {
+                acknowledged.extend(name);
+            }
+            debug!(
+                "Relayed batch {} to {} workers",
+                message.batch.digest(),
+                acknowledged.len()
+            );
+            let _ = tx_acknowledged.send(acknowledged);
+        });
+    }
+}
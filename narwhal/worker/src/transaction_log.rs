@@ -0,0 +1,100 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use config::TransactionDurability;
+use fastcrypto::hash::HashFunction;
+use storage::NodeStorage;
+use store::{
+    rocks::{DBMap, ReadWriteOptions},
+    Map,
+};
+use types::{Batch, BatchAPI, BatchDigest, PriorityLane, StoreResult, Transaction};
+
+#[cfg(test)]
+#[path = "tests/transaction_log_tests.rs"]
+pub mod transaction_log_tests;
+
+/// The digest keying a transaction in the log.
+pub type TransactionLogKey = [u8; crypto::DIGEST_LENGTH];
+
+/// Logs the batches sealed by the batch maker until they are included, so that their
+/// transactions are batched again if the worker restarts before. A batch is logged with a
+/// single write, so that at most one sync is paid per batch. A transaction may be batched
+/// twice if the worker stops right between its inclusion and its removal from the log.
+#[derive(Clone, Default)]
+pub struct TransactionLog {
+    /// None if the log is disabled.
+    store: Option<DBMap<TransactionLogKey, (PriorityLane, Transaction)>>,
+}
+
+impl TransactionLog {
+    pub fn new(
+        mut store: DBMap<TransactionLogKey, (PriorityLane, Transaction)>,
+        durability: TransactionDurability,
+    ) -> Self {
+        if durability == TransactionDurability::None {
+            return Self::default();
+        }
+        store.opts.sync_to_disk = durability == TransactionDurability::Fsync;
+        Self { store: Some(store) }
+    }
+
+    /// Opens the log in the database of the batch store.
+    pub fn reopen(
+        batch_store: &DBMap<BatchDigest, Batch>,
+        durability: TransactionDurability,
+    ) -> Self {
+        if durability == TransactionDurability::None {
+            return Self::default();
+        }
+        let store = DBMap::reopen(
+            &batch_store.rocksdb,
+            Some(NodeStorage::TRANSACTION_LOG_CF),
+            &ReadWriteOptions::default(),
+        )
+        .expect("Cannot open the transaction log");
+        Self::new(store, durability)
+    }
+
+    fn key(transaction: &Transaction) -> TransactionLogKey {
+        crypto::DefaultHashFunction::digest(transaction).into()
+    }
+
+    /// Logs the transactions of a sealed batch, returning the batch once they are as durable as
+    /// configured. The write runs on a blocking thread, not to stall the runtime on a sync.
+    pub async fn append(&self, lane: PriorityLane, batch: Batch) -> (Batch, StoreResult<()>) {
+        let Some(store) = self.store.clone() else {
+            return (batch, Ok(()));
+        };
+        tokio::task::spawn_blocking(move || {
+            let logged = batch
+                .transactions()
+                .iter()
+                .map(|transaction| (Self::key(transaction), (lane, transaction.clone())));
+            let result = store
+                .batch()
+                .insert_batch(&store, logged)
+                .and_then(|write_batch| write_batch.write_opt(&store.opts.writeopts()));
+            (batch, result)
+        })
+        .await
+        .expect("Failed to join the transaction log write")
+    }
+
+    /// Removes the transactions of a batch that no longer needs to be recovered. The removal
+    /// is not synced, as losing it only means the transactions may be batched again.
+    pub fn remove(&self, transactions: &[Transaction]) -> StoreResult<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        store.multi_remove(transactions.iter().map(Self::key))
+    }
+
+    /// Returns the logged transactions, that were not included in a batch before the worker
+    /// stopped.
+    pub fn recover(&self) -> Vec<(PriorityLane, Transaction)> {
+        let Some(store) = &self.store else {
+            return Vec::new();
+        };
+        store.iter().map(|(_, logged)| logged).collect()
+    }
+}
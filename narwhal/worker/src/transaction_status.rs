@@ -0,0 +1,197 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use fastcrypto::hash::{Digest, HashFunction};
+use lru::LruCache;
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{watch, Notify};
+use types::{BatchDigest, BatchStatus, CertificateDigest, Round, Transaction};
+
+#[cfg(test)]
+#[path = "tests/transaction_status_tests.rs"]
+pub mod transaction_status_tests;
+
+/// The number of transactions whose status is remembered.
+const MAX_TRACKED_TRANSACTIONS: usize = 500_000;
+
+/// The number of batches whose status is remembered.
+const MAX_TRACKED_BATCHES: usize = 10_000;
+
+/// The digest identifying a transaction to the status API.
+pub type TransactionDigest = Digest<{ crypto::DIGEST_LENGTH }>;
+
+/// The progress of a transaction of this worker, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionStage {
+    /// The transaction is in a sealed batch.
+    Batched,
+    /// A quorum of workers acknowledged the batch.
+    QuorumAcked,
+    /// The batch is included in a certificate of our primary.
+    Certified {
+        certificate: CertificateDigest,
+        round: Round,
+    },
+    /// The certificate including the batch is committed.
+    Committed {
+        certificate: CertificateDigest,
+        round: Round,
+        commit_round: Round,
+    },
+}
+
+impl TransactionStage {
+    fn rank(&self) -> u8 {
+        match self {
+            TransactionStage::Batched => 0,
+            TransactionStage::QuorumAcked => 1,
+            TransactionStage::Certified { .. } => 2,
+            TransactionStage::Committed { .. } => 3,
+        }
+    }
+
+    pub fn is_final(&self) -> bool {
+        matches!(self, TransactionStage::Committed { .. })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionStage::Batched => "batched",
+            TransactionStage::QuorumAcked => "quorum_acked",
+            TransactionStage::Certified { .. } => "certified",
+            TransactionStage::Committed { .. } => "committed",
+        }
+    }
+}
+
+impl From<BatchStatus> for TransactionStage {
+    fn from(status: BatchStatus) -> Self {
+        match status {
+            BatchStatus::Certified { certificate, round } => {
+                TransactionStage::Certified { certificate, round }
+            }
+            BatchStatus::Committed {
+                certificate,
+                round,
+                commit_round,
+            } => TransactionStage::Committed {
+                certificate,
+                round,
+                commit_round,
+            },
+        }
+    }
+}
+
+/// The status of a transaction, as reported to its client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactionStatus {
+    pub batch: BatchDigest,
+    pub stage: TransactionStage,
+}
+
+#[derive(Debug)]
+struct Inner {
+    /// The batch of each recently batched transaction.
+    transactions: LruCache<TransactionDigest, BatchDigest>,
+    /// The stage of each recent batch.
+    batches: LruCache<BatchDigest, watch::Sender<TransactionStage>>,
+}
+
+/// Tracks the progress of the transactions of this worker from their batch to their commit,
+/// so that the clients can poll or subscribe to it. Only the recent transactions are tracked.
+#[derive(Clone, Debug)]
+pub struct TransactionStatusTracker {
+    inner: Arc<Mutex<Inner>>,
+    /// Notified whenever transactions are batched.
+    batched: Arc<Notify>,
+}
+
+impl Default for TransactionStatusTracker {
+    fn default() -> Self {
+        Self::new(MAX_TRACKED_TRANSACTIONS, MAX_TRACKED_BATCHES)
+    }
+}
+
+impl TransactionStatusTracker {
+    pub fn new(max_transactions: usize, max_batches: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                transactions: LruCache::new(NonZeroUsize::new(max_transactions.max(1)).unwrap()),
+                batches: LruCache::new(NonZeroUsize::new(max_batches.max(1)).unwrap()),
+            })),
+            batched: Arc::new(Notify::new()),
+        }
+    }
+
+    /// The digest of the transaction, as computed by the clients.
+    pub fn digest(transaction: &[u8]) -> TransactionDigest {
+        crypto::DefaultHashFunction::digest(transaction)
+    }
+
+    /// Records that the transactions are sealed in the batch.
+    pub fn batched(&self, batch: BatchDigest, transactions: &[Transaction]) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            for transaction in transactions {
+                inner.transactions.put(Self::digest(transaction), batch);
+            }
+            inner
+                .batches
+                .put(batch, watch::channel(TransactionStage::Batched).0);
+        }
+        self.batched.notify_waiters();
+    }
+
+    /// Advances the stage of the batch. The stages that do not advance it are ignored, since
+    /// the reports of the primary may be reordered.
+    pub fn update(&self, batch: &BatchDigest, stage: TransactionStage) {
+        let inner = self.inner.lock().unwrap();
+        if let Some(tx_stage) = inner.batches.peek(batch) {
+            tx_stage.send_if_modified(|current| {
+                if stage.rank() > current.rank() {
+                    *current = stage;
+                    return true;
+                }
+                false
+            });
+        }
+    }
+
+    /// The current status of the transaction, if it is tracked.
+    pub fn status(&self, transaction: &TransactionDigest) -> Option<TransactionStatus> {
+        let mut inner = self.inner.lock().unwrap();
+        let batch = *inner.transactions.get(transaction)?;
+        let stage = *inner.batches.get(&batch)?.borrow();
+        Some(TransactionStatus { batch, stage })
+    }
+
+    /// Subscribes to the stages of the batch of the transaction, if it is tracked. The channel
+    /// closes once the batch is not tracked anymore.
+    pub fn subscribe(
+        &self,
+        transaction: &TransactionDigest,
+    ) -> Option<(BatchDigest, watch::Receiver<TransactionStage>)> {
+        let mut inner = self.inner.lock().unwrap();
+        let batch = *inner.transactions.get(transaction)?;
+        let rx_stage = inner.batches.get(&batch)?.subscribe();
+        Some((batch, rx_stage))
+    }
+
+    /// Waits until the transaction is tracked, then subscribes to it.
+    pub async fn wait_for(
+        &self,
+        transaction: &TransactionDigest,
+    ) -> (BatchDigest, watch::Receiver<TransactionStage>) {
+        loop {
+            // Register for the notification before checking, not to miss a batch in between.
+            let notified = self.batched.notified();
+            if let Some(subscription) = self.subscribe(transaction) {
+                return subscription;
+            }
+            notified.await;
+        }
+    }
+}
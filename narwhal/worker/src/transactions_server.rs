@@ -1,33 +1,54 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::client::LocalNarwhalClient;
+use crate::admission_control::AdmissionController;
+use crate::client::{LocalNarwhalClient, NarwhalError, SenderOf};
+use crate::client_quotas::ClientQuotas;
+use crate::drain::DrainReceiver;
+use crate::lanes::LaneSenders;
 use crate::metrics::WorkerEndpointMetrics;
+use crate::spill_queue::SpillQueue;
+use crate::transaction_status::{
+    TransactionDigest, TransactionStage, TransactionStatus, TransactionStatusTracker,
+};
 use crate::TransactionValidator;
 use async_trait::async_trait;
+use bytes::Bytes;
+use config::{WorkerId, WorkerIndex};
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::Digest;
 use futures::stream::FuturesUnordered;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use mysten_metrics::spawn_logged_monitored_task;
 use mysten_network::server::Server;
 use mysten_network::Multiaddr;
+use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tokio::time::{sleep, timeout};
 use tonic::{Request, Response, Status};
 use tracing::{error, info, warn};
-use types::metered_channel::Sender;
 use types::{
-    ConditionalBroadcastReceiver, Empty, Transaction, TransactionProto, Transactions,
-    TransactionsServer, TxResponse,
+    BatchDigest, CertificateDigestProto, ConditionalBroadcastReceiver, Empty, PriorityLane,
+    TransactionProto, TransactionRejection, TransactionStageProto, TransactionStatusProto,
+    TransactionStatusRequest, Transactions, TransactionsServer,
 };
 
 pub struct TxServer<V: TransactionValidator> {
     address: Multiaddr,
     rx_shutdown: ConditionalBroadcastReceiver,
     endpoint_metrics: WorkerEndpointMetrics,
-    tx_batch_maker: Sender<(Transaction, TxResponse)>,
+    tx_batch_maker: LaneSenders,
+    admission_controller: Arc<AdmissionController>,
+    spill_queue: Arc<SpillQueue>,
+    rx_draining: DrainReceiver,
+    client_quotas: Arc<ClientQuotas>,
     validator: V,
+    transaction_status: TransactionStatusTracker,
+    sender_shard: Option<SenderShard>,
 }
 
 impl<V: TransactionValidator> TxServer<V> {
@@ -36,15 +57,27 @@ impl<V: TransactionValidator> TxServer<V> {
         address: Multiaddr,
         rx_shutdown: ConditionalBroadcastReceiver,
         endpoint_metrics: WorkerEndpointMetrics,
-        tx_batch_maker: Sender<(Transaction, TxResponse)>,
+        tx_batch_maker: LaneSenders,
+        admission_controller: Arc<AdmissionController>,
+        spill_queue: Arc<SpillQueue>,
+        rx_draining: DrainReceiver,
+        client_quotas: Arc<ClientQuotas>,
         validator: V,
+        transaction_status: TransactionStatusTracker,
+        sender_shard: Option<SenderShard>,
     ) -> JoinHandle<()> {
         spawn_logged_monitored_task!(
             Self {
                 address,
                 tx_batch_maker,
+                admission_controller,
+                spill_queue,
+                rx_draining,
+                client_quotas,
                 endpoint_metrics,
                 validator,
+                transaction_status,
+                sender_shard,
                 rx_shutdown
             }
             .run(),
@@ -57,14 +90,29 @@ impl<V: TransactionValidator> TxServer<V> {
         const RETRY_BACKOFF: Duration = Duration::from_millis(1_000);
         const GRACEFUL_SHUTDOWN_DURATION: Duration = Duration::from_millis(2_000);
 
-        // create and initialize local Narwhal client
-        let local_client = LocalNarwhalClient::new(self.tx_batch_maker.clone());
+        // create and initialize local Narwhal client, which checks the senders of the
+        // transactions submitted through it, locally or through the server
+        let sender_shard = self.sender_shard.map(|sender_shard| {
+            let validator = self.validator.clone();
+            let sender_of: SenderOf = Arc::new(move |transaction| validator.sender(transaction));
+            (sender_shard, sender_of)
+        });
+        let local_client = LocalNarwhalClient::new(
+            self.tx_batch_maker.clone(),
+            self.admission_controller.clone(),
+            self.spill_queue.clone(),
+            self.rx_draining.clone(),
+            sender_shard,
+            self.transaction_status.clone(),
+        );
         LocalNarwhalClient::set_global(self.address.clone(), local_client.clone());
 
         // create the handler
         let tx_handler = TxReceiverHandler {
             local_client,
+            client_quotas: self.client_quotas,
             validator: self.validator,
+            transaction_status: self.transaction_status,
         };
 
         // now create the server
@@ -128,61 +176,308 @@ impl<V: TransactionValidator> TxServer<V> {
     }
 }
 
+/// The workers of our authority, among which the transactions are sharded by sender.
+#[derive(Clone, Debug)]
+pub struct SenderShard {
+    /// The id of this worker.
+    pub id: WorkerId,
+    /// The workers of our authority.
+    pub workers: WorkerIndex,
+}
+
+impl SenderShard {
+    /// Checks that the transactions of the sender are assigned to this worker.
+    pub(crate) fn check(&self, sender: &[u8]) -> Result<(), NarwhalError> {
+        match self.workers.worker_for_sender(sender) {
+            Some((id, worker)) if id != self.id => {
+                Err(NarwhalError::WrongWorker(id, worker.transactions.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 /// Defines how the network receiver handles incoming transactions.
 #[derive(Clone)]
 pub(crate) struct TxReceiverHandler<V> {
     pub(crate) local_client: Arc<LocalNarwhalClient>,
+    pub(crate) client_quotas: Arc<ClientQuotas>,
     pub(crate) validator: V,
+    pub(crate) transaction_status: TransactionStatusTracker,
+}
+
+impl<V> TxReceiverHandler<V> {
+    /// Acknowledges a resubmitted transaction which is already batched, with the status of its
+    /// batch, instead of submitting it again. This makes the retries of the clients idempotent.
+    fn resubmission_ack(&self, transaction: &[u8]) -> Option<Response<Empty>> {
+        let status = self
+            .transaction_status
+            .status(&TransactionStatusTracker::digest(transaction))?;
+        let mut response = Response::new(Empty {});
+        response.metadata_mut().insert(
+            BATCH_DIGEST_METADATA_KEY,
+            Hex::encode(status.batch.0).parse().unwrap(),
+        );
+        response.metadata_mut().insert(
+            TRANSACTION_STAGE_METADATA_KEY,
+            status.stage.as_str().parse().unwrap(),
+        );
+        Some(response)
+    }
+
+    /// Charges the transaction to the quotas of the client, identified by its IP address.
+    fn charge_client(&self, client: Option<SocketAddr>, size: usize) -> Result<(), Status> {
+        match client {
+            Some(client) => self
+                .client_quotas
+                .try_charge(client.ip(), size)
+                .map_err(|e| to_status(NarwhalError::QuotaExceeded(e))),
+            None => Ok(()),
+        }
+    }
 }
 
 #[async_trait]
 impl<V: TransactionValidator> Transactions for TxReceiverHandler<V> {
+    type SubscribeTransactionStatusStream =
+        Pin<Box<dyn Stream<Item = Result<TransactionStatusProto, Status>> + Send>>;
+
     async fn submit_transaction(
         &self,
         request: Request<TransactionProto>,
     ) -> Result<Response<Empty>, Status> {
-        let transaction = request.into_inner().transaction;
-        if self.validator.validate(transaction.as_ref()).is_err() {
+        let client = request.remote_addr();
+        let request = request.into_inner();
+        self.charge_client(client, request.transaction.len())?;
+        let lane = PriorityLane::from(request.priority());
+        let transaction = request.transaction;
+        if self.validator.validate(transaction.as_ref()).await.is_err() {
             return Err(Status::invalid_argument("Invalid transaction"));
         }
+        if let Some(response) = self.resubmission_ack(transaction.as_ref()) {
+            return Ok(response);
+        }
         // Send the transaction to Narwhal via the local client.
-        self.local_client
-            .submit_transaction(transaction.to_vec())
+        match self
+            .local_client
+            .submit_transaction_in_lane(transaction.to_vec(), lane)
             .await
-            .map_err(|e| Status::internal(e.to_string()))?;
-        Ok(Response::new(Empty {}))
+        {
+            Ok(()) => Ok(Response::new(Empty {})),
+            // The first submission may have been batched in the meantime.
+            Err(e @ NarwhalError::TransactionRejected(TransactionRejection::Duplicate(_))) => self
+                .resubmission_ack(transaction.as_ref())
+                .ok_or_else(|| to_status(e)),
+            Err(e) => Err(to_status(e)),
+        }
     }
 
     async fn submit_transaction_stream(
         &self,
         request: Request<tonic::Streaming<types::TransactionProto>>,
     ) -> Result<Response<types::Empty>, Status> {
+        let client = request.remote_addr();
         let mut transactions = request.into_inner();
         let mut reqeusts = FuturesUnordered::new();
 
         while let Some(Ok(txn)) = transactions.next().await {
-            if let Err(err) = self.validator.validate(txn.transaction.as_ref()) {
+            self.charge_client(client, txn.transaction.len())?;
+            if let Err(err) = self.validator.validate(txn.transaction.as_ref()).await {
                 // If the transaction is invalid (often cryptographically), better to drop the client
                 return Err(Status::invalid_argument(format!(
                     "Stream contains an invalid transaction {err}"
                 )));
             }
+            if self.resubmission_ack(txn.transaction.as_ref()).is_some() {
+                continue;
+            }
             // Send the transaction to Narwhal via the local client.
             // Note that here we do not wait for a response because this would
             // mean that we process only a single message from this stream at a
             // time. Instead we gather them and resolve them once the stream is over.
+            let lane = PriorityLane::from(txn.priority());
             reqeusts.push(
                 self.local_client
-                    .submit_transaction(txn.transaction.to_vec()),
+                    .submit_transaction_in_lane(txn.transaction.to_vec(), lane),
             );
         }
 
         while let Some(result) = reqeusts.next().await {
             if let Err(e) = result {
-                return Err(Status::internal(e.to_string()));
+                return Err(to_status(e));
             }
         }
 
         Ok(Response::new(Empty {}))
     }
+
+    async fn get_transaction_status(
+        &self,
+        request: Request<TransactionStatusRequest>,
+    ) -> Result<Response<TransactionStatusProto>, Status> {
+        let digest = parse_transaction_digest(&request.into_inner().digest)?;
+        Ok(Response::new(status_proto(
+            self.transaction_status.status(&digest),
+        )))
+    }
+
+    /// Streams the status of the transaction every time it progresses, starting with its
+    /// current status. The stream ends once the transaction is committed, or not tracked anymore.
+    async fn subscribe_transaction_status(
+        &self,
+        request: Request<TransactionStatusRequest>,
+    ) -> Result<Response<Self::SubscribeTransactionStatusStream>, Status> {
+        let subscription = TransactionStatusSubscription {
+            digest: parse_transaction_digest(&request.into_inner().digest)?,
+            tracker: self.transaction_status.clone(),
+            state: SubscriptionState::Started,
+        };
+        let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+            let item = subscription.next().await?;
+            Some((Ok(item), subscription))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// The progress of a single transaction status subscription.
+enum SubscriptionState {
+    Started,
+    /// The transaction was unknown, waiting for it to be batched.
+    Waiting,
+    Tracking(BatchDigest, watch::Receiver<TransactionStage>),
+    Done,
+}
+
+struct TransactionStatusSubscription {
+    digest: TransactionDigest,
+    tracker: TransactionStatusTracker,
+    state: SubscriptionState,
+}
+
+impl TransactionStatusSubscription {
+    async fn next(&mut self) -> Option<TransactionStatusProto> {
+        let (batch, mut rx_stage) =
+            match std::mem::replace(&mut self.state, SubscriptionState::Done) {
+                SubscriptionState::Started => match self.tracker.subscribe(&self.digest) {
+                    Some(subscription) => subscription,
+                    None => {
+                        self.state = SubscriptionState::Waiting;
+                        return Some(status_proto(None));
+                    }
+                },
+                SubscriptionState::Waiting => self.tracker.wait_for(&self.digest).await,
+                SubscriptionState::Tracking(batch, mut rx_stage) => {
+                    // The batch is not tracked anymore once the sender is dropped.
+                    rx_stage.changed().await.ok()?;
+                    (batch, rx_stage)
+                }
+                SubscriptionState::Done => return None,
+            };
+        let stage = *rx_stage.borrow_and_update();
+        if !stage.is_final() {
+            self.state = SubscriptionState::Tracking(batch, rx_stage);
+        }
+        Some(status_proto(Some(TransactionStatus { batch, stage })))
+    }
+}
+
+fn parse_transaction_digest(digest: &[u8]) -> Result<TransactionDigest, Status> {
+    let digest = digest
+        .try_into()
+        .map_err(|_| Status::invalid_argument("Invalid transaction digest"))?;
+    Ok(Digest::new(digest))
+}
+
+fn status_proto(status: Option<TransactionStatus>) -> TransactionStatusProto {
+    let Some(TransactionStatus { batch, stage }) = status else {
+        return TransactionStatusProto {
+            stage: TransactionStageProto::Unknown.into(),
+            ..Default::default()
+        };
+    };
+    let (stage, certificate, round, commit_round) = match stage {
+        TransactionStage::Batched => (TransactionStageProto::Batched, None, 0, 0),
+        TransactionStage::QuorumAcked => (TransactionStageProto::QuorumAcked, None, 0, 0),
+        TransactionStage::Certified { certificate, round } => (
+            TransactionStageProto::Certified,
+            Some(certificate),
+            round,
+            0,
+        ),
+        TransactionStage::Committed {
+            certificate,
+            round,
+            commit_round,
+        } => (
+            TransactionStageProto::Committed,
+            Some(certificate),
+            round,
+            commit_round,
+        ),
+    };
+    TransactionStatusProto {
+        stage: stage.into(),
+        batch_digest: Bytes::from(batch.0.to_vec()),
+        certificate: certificate.map(CertificateDigestProto::from),
+        round,
+        commit_round,
+    }
+}
+
+/// The metadata key of the delay, in milliseconds, after which the client of an overloaded
+/// worker should retry.
+pub const RETRY_AFTER_MS_METADATA_KEY: &str = "retry-after-ms";
+
+/// The metadata key of the quota exceeded by a client.
+pub const EXCEEDED_QUOTA_METADATA_KEY: &str = "exceeded-quota";
+
+/// The metadata key of the hex encoded digest of the batch of a resubmitted transaction.
+pub const BATCH_DIGEST_METADATA_KEY: &str = "batch-digest";
+
+/// The metadata key of the stage of a resubmitted transaction.
+pub const TRANSACTION_STAGE_METADATA_KEY: &str = "transaction-stage";
+
+fn to_status(error: NarwhalError) -> Status {
+    match error {
+        NarwhalError::TransactionRejected(TransactionRejection::Duplicate(_)) => {
+            Status::already_exists(error.to_string())
+        }
+        NarwhalError::TransactionRejected(
+            TransactionRejection::InvalidBatch(_) | TransactionRejection::TooLarge(..),
+        ) => Status::invalid_argument(error.to_string()),
+        NarwhalError::TransactionRejected(
+            TransactionRejection::LogFailed(_) | TransactionRejection::EpochEnded(_),
+        ) => Status::unavailable(error.to_string()),
+        NarwhalError::TransactionRejected(TransactionRejection::Expired) => {
+            Status::deadline_exceeded(error.to_string())
+        }
+        NarwhalError::Overloaded(retry_after) => {
+            let mut status = Status::resource_exhausted(error.to_string());
+            status.metadata_mut().insert(
+                RETRY_AFTER_MS_METADATA_KEY,
+                retry_after.as_millis().to_string().parse().unwrap(),
+            );
+            status
+        }
+        NarwhalError::QuotaExceeded(ref exceeded) => {
+            let mut status = Status::resource_exhausted(error.to_string());
+            status.metadata_mut().insert(
+                RETRY_AFTER_MS_METADATA_KEY,
+                exceeded
+                    .retry_after
+                    .as_millis()
+                    .to_string()
+                    .parse()
+                    .unwrap(),
+            );
+            status.metadata_mut().insert(
+                EXCEEDED_QUOTA_METADATA_KEY,
+                exceeded.quota.as_str().parse().unwrap(),
+            );
+            status
+        }
+        NarwhalError::WrongWorker(..) => Status::failed_precondition(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
 }
@@ -3,7 +3,17 @@ use std::fmt::{Debug, Display};
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use async_trait::async_trait;
-use types::Batch;
+use fastcrypto::hash::Hash;
+use lru::LruCache;
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+use types::{Batch, BatchDigest};
+
+#[cfg(test)]
+#[path = "tests/tx_validator_tests.rs"]
+pub mod tx_validator_tests;
 
 /// Defines the validation procedure for receiving either a new single transaction (from a client)
 /// of a batch of transactions (from another validator). Invalid transactions will not receive
@@ -12,9 +22,25 @@ use types::Batch;
 pub trait TransactionValidator: Clone + Send + Sync + 'static {
     type Error: Display + Debug + Send + Sync + 'static;
     /// Determines if a transaction valid for the worker to consider putting in a batch
-    fn validate(&self, t: &[u8]) -> Result<(), Self::Error>;
+    async fn validate(&self, t: &[u8]) -> Result<(), Self::Error>;
     /// Determines if this batch can be voted on
     async fn validate_batch(&self, b: &Batch) -> Result<(), Self::Error>;
+    /// Determines if a batch assembled by this worker can be sealed and broadcast. All the
+    /// transactions of a rejected batch are rejected. Every batch is accepted by default.
+    async fn validate_local_batch(&self, _b: &Batch) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    /// Returns the address of the sender of a transaction, used to check that it was submitted
+    /// to the worker of its sender when the transactions are sharded by sender. The senders are
+    /// unknown by default, and their transactions accepted by any worker.
+    fn sender(&self, _t: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+    /// Determines if a transaction expired, e.g. its client gave up on it, in which case it is
+    /// dropped instead of batched. Transactions never expire by default.
+    fn is_expired(&self, _t: &[u8]) -> bool {
+        false
+    }
 }
 
 /// Simple validator that accepts all transactions and batches.
@@ -24,7 +50,7 @@ pub struct TrivialTransactionValidator;
 impl TransactionValidator for TrivialTransactionValidator {
     type Error = eyre::Report;
 
-    fn validate(&self, _t: &[u8]) -> Result<(), Self::Error> {
+    async fn validate(&self, _t: &[u8]) -> Result<(), Self::Error> {
         Ok(())
     }
 
@@ -32,3 +58,65 @@ impl TransactionValidator for TrivialTransactionValidator {
         Ok(())
     }
 }
+
+/// Remembers the verdict of the wrapped validator for the recently validated batches, so that
+/// a batch received several times (e.g. reported, then synchronized) is only validated once.
+pub struct BatchVerdictCache<V: TransactionValidator> {
+    validator: V,
+    verdicts: Arc<Mutex<LruCache<BatchDigest, Result<(), Arc<V::Error>>>>>,
+}
+
+impl<V: TransactionValidator> BatchVerdictCache<V> {
+    pub fn new(validator: V, capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity.max(1)).unwrap();
+        Self {
+            validator,
+            verdicts: Arc::new(Mutex::new(LruCache::new(capacity))),
+        }
+    }
+}
+
+impl<V: TransactionValidator> Clone for BatchVerdictCache<V> {
+    fn clone(&self) -> Self {
+        Self {
+            validator: self.validator.clone(),
+            verdicts: self.verdicts.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<V: TransactionValidator> TransactionValidator for BatchVerdictCache<V> {
+    type Error = Arc<V::Error>;
+
+    async fn validate(&self, t: &[u8]) -> Result<(), Self::Error> {
+        self.validator.validate(t).await.map_err(Arc::new)
+    }
+
+    async fn validate_batch(&self, b: &Batch) -> Result<(), Self::Error> {
+        let digest = b.digest();
+        if let Some(verdict) = self.verdicts.lock().unwrap().get(&digest) {
+            return verdict.clone();
+        }
+        // The lock is not held while validating, so concurrent receptions of the same batch
+        // may validate it more than once.
+        let verdict = self.validator.validate_batch(b).await.map_err(Arc::new);
+        self.verdicts.lock().unwrap().put(digest, verdict.clone());
+        verdict
+    }
+
+    async fn validate_local_batch(&self, b: &Batch) -> Result<(), Self::Error> {
+        self.validator
+            .validate_local_batch(b)
+            .await
+            .map_err(Arc::new)
+    }
+
+    fn sender(&self, t: &[u8]) -> Option<Vec<u8>> {
+        self.validator.sender(t)
+    }
+
+    fn is_expired(&self, t: &[u8]) -> bool {
+        self.validator.is_expired(t)
+    }
+}
@@ -2,11 +2,28 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
+    adaptive_sealing,
+    admission_control::AdmissionController,
+    batch_compression::{BatchCompressionNegotiator, PeerBatchCompressions},
+    batch_diff_sync::{BatchDiffSynchronizer, RecentBatches},
     batch_maker::BatchMaker,
+    batches_streams::BatchesStreams,
+    client_quotas::ClientQuotas,
+    drain::WorkerDrain,
+    erasure_coding::{BatchChunkForwarder, BatchChunks},
+    fetch_queue::{FetchQueue, DEFAULT_MAX_CONCURRENT_FETCHES},
     handlers::{PrimaryReceiverHandler, WorkerReceiverHandler},
+    lanes::lane_channels,
     metrics::WorkerChannelMetrics,
     primary_connector::PrimaryConnector,
+    primary_rounds::PrimaryRounds,
     quorum_waiter::QuorumWaiter,
+    spill_queue::SpillQueue,
+    standby::{standby_keypair, ReplicationLog},
+    topology::BatchRelay,
+    transaction_log::TransactionLog,
+    transaction_status::TransactionStatusTracker,
+    tx_validator::BatchVerdictCache,
     TransactionValidator, NUM_SHUTDOWN_RECEIVERS,
 };
 use anemo::{codegen::InboundRequestLayer, types::Address};
@@ -18,21 +35,28 @@ use anemo_tower::{
     trace::{DefaultMakeSpan, DefaultOnFailure, TraceLayer},
 };
 use anemo_tower::{rate_limit, set_header::SetResponseHeaderLayer};
-use config::{Authority, AuthorityIdentifier, Committee, Parameters, WorkerCache, WorkerId};
+use config::{
+    AdaptiveSealingParameters, Authority, AuthorityIdentifier, Committee, Parameters, WorkerCache,
+    WorkerId, WorkerSharding,
+};
 use crypto::{traits::KeyPair as _, NetworkKeyPair, NetworkPublicKey};
 use mysten_metrics::spawn_logged_monitored_task;
 use mysten_network::{multiaddr::Protocol, Multiaddr};
+use network::connection_manager::ConnectionManager;
+use network::discovery::{PeerAddresses, PeerDiscovery, UNREACHABLE_PEER_RETRY_INTERVAL};
 use network::epoch_filter::{AllowedEpoch, EPOCH_HEADER_KEY};
 use network::failpoints::FailpointsMakeCallbackHandler;
-use network::metrics::MetricsMakeCallbackHandler;
-use std::collections::HashMap;
+use network::fault_injection::FaultInjectionLayer;
+use network::metrics::{MetricsMakeCallbackHandler, PeerMetricsMakeCallbackHandler};
+use network::request_budget::RequestBudget;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use std::{net::Ipv4Addr, sync::Arc, thread::sleep};
 use store::rocks::DBMap;
 use tap::TapFallible;
-use tokio::task::JoinHandle;
+use tokio::{sync::watch, task::JoinHandle};
 use tower::ServiceBuilder;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use types::{
     metered_channel::{channel_with_total, Sender},
     Batch, BatchDigest, ConditionalBroadcastReceiver, PreSubscribedBroadcastSender,
@@ -47,7 +71,7 @@ pub mod worker_tests;
 pub const CHANNEL_CAPACITY: usize = 1_000;
 
 use crate::metrics::{Metrics, WorkerEndpointMetrics, WorkerMetrics};
-use crate::transactions_server::TxServer;
+use crate::transactions_server::{SenderShard, TxServer};
 
 pub struct Worker {
     /// This authority.
@@ -62,6 +86,8 @@ pub struct Worker {
     worker_cache: WorkerCache,
     /// The configuration parameters
     parameters: Parameters,
+    /// The configuration parameters tuned at runtime.
+    rx_parameters: watch::Receiver<Parameters>,
     /// The persistent storage.
     store: DBMap<BatchDigest, Batch>,
 }
@@ -73,12 +99,17 @@ impl Worker {
         id: WorkerId,
         committee: Committee,
         worker_cache: WorkerCache,
-        parameters: Parameters,
+        rx_peer_addresses: watch::Receiver<PeerAddresses>,
+        rx_parameters: watch::Receiver<Parameters>,
         validator: impl TransactionValidator,
         store: DBMap<BatchDigest, Batch>,
         metrics: Metrics,
         tx_shutdown: &mut PreSubscribedBroadcastSender,
+        drain: WorkerDrain,
     ) -> Vec<JoinHandle<()>> {
+        // Most parameters are read once at startup, the ones tunable at runtime are read from the
+        // watch channel by the components using them.
+        let parameters = rx_parameters.borrow().clone();
         info!(
             "Boot worker node with id {} peer id {}",
             id,
@@ -93,6 +124,7 @@ impl Worker {
             committee: committee.clone(),
             worker_cache,
             parameters: parameters.clone(),
+            rx_parameters,
             store,
         };
 
@@ -102,6 +134,15 @@ impl Worker {
         let inbound_network_metrics = Arc::new(metrics.inbound_network_metrics.unwrap());
         let outbound_network_metrics = Arc::new(metrics.outbound_network_metrics.unwrap());
         let network_connection_metrics = metrics.network_connection_metrics.unwrap();
+        let peer_metrics = Arc::new(metrics.peer_metrics.unwrap());
+        let connection_manager = ConnectionManager::new(
+            parameters
+                .worker_channels
+                .max_inflight_broadcast_bytes_per_peer,
+            parameters.worker_channels.max_concurrent_requests_per_peer,
+            metrics.connection_manager_metrics.map(Arc::new),
+        )
+        .with_peer_metrics(peer_metrics.clone());
 
         // Spawn all worker tasks.
         let (tx_our_batch, rx_our_batch) = channel_with_total(
@@ -117,11 +158,38 @@ impl Worker {
 
         let mut shutdown_receivers = tx_shutdown.subscribe_n(NUM_SHUTDOWN_RECEIVERS);
 
+        // Batches are often received more than once (e.g. reported, then synchronized), so
+        // remember the verdicts of the validator.
+        let validator = BatchVerdictCache::new(validator, parameters.batch_verdict_cache_size);
+
+        let batch_compressions = PeerBatchCompressions::default();
+        // The chunks of the erasure coded batches are forwarded to the other workers.
+        let (tx_forward_chunks, rx_forward_chunks) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+        // The batches of the workers of other regions are relayed to the workers of our region.
+        let (tx_relay_batches, rx_relay_batches) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+        // The batches stored are sent to the primary then logged, for our standby to replicate.
+        let replication_log = ReplicationLog::new(parameters.worker_standby.replication_log_size);
+        // They are also indexed for the batch diffs with the other workers.
+        let recent_batches = RecentBatches::new(parameters.batch_diff_sync.window);
         let mut worker_service = WorkerToWorkerServer::new(WorkerReceiverHandler {
             id: worker.id,
-            tx_others_batch,
+            tx_others_batch: tx_others_batch.clone(),
             store: worker.store.clone(),
             validator: validator.clone(),
+            max_request_batches_response_size: parameters.max_request_batches_response_size,
+            batches_streams: BatchesStreams::new(parameters.max_request_batches_response_size),
+            batch_compressions: batch_compressions.clone(),
+            batch_diff_sync: parameters.batch_diff_sync.clone(),
+            recent_batches: recent_batches.clone(),
+            fetch_queue: FetchQueue::new(
+                DEFAULT_MAX_CONCURRENT_FETCHES,
+                Some(node_metrics.clone()),
+            ),
+            erasure_coding: parameters.erasure_coding.clone(),
+            batch_chunks: BatchChunks::default(),
+            tx_forward_chunks,
+            tx_relay_batches,
+            replication_log: replication_log.clone(),
         });
         // Apply rate limits from configuration as needed.
         if let Some(limit) = parameters.anemo.report_batch_rate_limit {
@@ -140,6 +208,39 @@ impl Worker {
                 ),
             ));
         }
+        // Only our standby, whose key is derived from ours, replicates our batches.
+        worker_service = worker_service.add_layer_for_request_replicated_batches(
+            InboundRequestLayer::new(RequireAuthorizationLayer::new(AllowedPeers::new([PeerId(
+                standby_keypair(&worker.keypair).public().0.to_bytes(),
+            )]))),
+        );
+        // Refuse the requests over budget, so that the peers back off. Both versions of the
+        // route and the batch streams share the budget.
+        let budget = &parameters.anemo.request_batches_budget;
+        if let Some(budget) = RequestBudget::new(budget.per_peer, budget.per_endpoint) {
+            worker_service = worker_service.add_layer_for_request_batches(
+                InboundRequestLayer::new(RequireAuthorizationLayer::new(budget.clone())),
+            );
+            worker_service = worker_service.add_layer_for_request_batches_v2(
+                InboundRequestLayer::new(RequireAuthorizationLayer::new(budget.clone())),
+            );
+            worker_service = worker_service.add_layer_for_open_batches_stream(
+                InboundRequestLayer::new(RequireAuthorizationLayer::new(budget.clone())),
+            );
+            worker_service = worker_service.add_layer_for_request_batches_chunk(
+                InboundRequestLayer::new(RequireAuthorizationLayer::new(budget)),
+            );
+        }
+        // A peer only needs a batch diff per interval.
+        let budget =
+            RequestBudget::new(Some(parameters.batch_diff_sync.max_requests_per_peer), None)
+                .expect("The batch diff budget is set");
+        worker_service = worker_service.add_layer_for_request_batches_diff(
+            InboundRequestLayer::new(RequireAuthorizationLayer::new(budget)),
+        );
+
+        // The progress of our transactions is reported by the batch maker then the primary.
+        let transaction_status = TransactionStatusTracker::default();
 
         let primary_service = PrimaryToWorkerServer::new(PrimaryReceiverHandler {
             authority_id: worker.authority.id(),
@@ -148,8 +249,12 @@ impl Worker {
             worker_cache: worker.worker_cache.clone(),
             store: worker.store.clone(),
             request_batch_timeout: worker.parameters.sync_retry_delay,
-            request_batch_retry_nodes: worker.parameters.sync_retry_nodes,
+            rx_parameters: worker.rx_parameters.clone(),
+            request_batch_hedge_delay: worker.parameters.sync_hedge_delay,
             validator: validator.clone(),
+            connection_manager: connection_manager.clone(),
+            transaction_status: transaction_status.clone(),
+            primary_rounds: PrimaryRounds::default(),
         });
 
         // Receive incoming messages from other workers.
@@ -161,6 +266,20 @@ impl Worker {
         let address = address
             .replace(0, |_protocol| Some(Protocol::Ip4(Ipv4Addr::UNSPECIFIED)))
             .unwrap();
+
+        // Only accept the transactions of the senders assigned to us, when sharded by sender.
+        let sender_shard = match self.worker_cache.sharding {
+            WorkerSharding::Any => None,
+            WorkerSharding::BySender => Some(SenderShard {
+                id: self.id,
+                workers: self
+                    .worker_cache
+                    .workers
+                    .get(self.authority.protocol_key())
+                    .expect("Our public key is not in the worker cache")
+                    .clone(),
+            }),
+        };
         let addr = address.to_anemo_address().unwrap();
 
         let epoch_string: String = committee.epoch().to_string();
@@ -184,6 +303,13 @@ impl Worker {
             )))
             .merge(primary_to_worker_router);
 
+        let fault_injection = match &parameters.anemo.fault_injection {
+            Some(faults) => {
+                warn!("Injecting faults into the inbound requests: {faults:?}");
+                FaultInjectionLayer::new(faults.seed, faults.drop_probability, faults.max_delay)
+            }
+            None => FaultInjectionLayer::default(),
+        };
         let service = ServiceBuilder::new()
             .layer(
                 TraceLayer::new_for_server_errors()
@@ -194,7 +320,11 @@ impl Worker {
                 inbound_network_metrics,
                 parameters.anemo.excessive_message_size(),
             )))
+            .layer(CallbackLayer::new(PeerMetricsMakeCallbackHandler::inbound(
+                peer_metrics.clone(),
+            )))
             .layer(CallbackLayer::new(FailpointsMakeCallbackHandler::new()))
+            .layer(fault_injection)
             .layer(SetResponseHeaderLayer::overriding(
                 EPOCH_HEADER_KEY.parse().unwrap(),
                 epoch_string.clone(),
@@ -211,6 +341,9 @@ impl Worker {
                 outbound_network_metrics,
                 parameters.anemo.excessive_message_size(),
             )))
+            .layer(CallbackLayer::new(
+                PeerMetricsMakeCallbackHandler::outbound(peer_metrics.clone()),
+            ))
             .layer(CallbackLayer::new(FailpointsMakeCallbackHandler::new()))
             .layer(SetRequestHeaderLayer::overriding(
                 EPOCH_HEADER_KEY.parse().unwrap(),
@@ -287,8 +420,10 @@ impl Worker {
             .map(|(_, info)| (info.name, info.worker_address));
 
         // Add other workers we want to talk with to the known peers set.
+        let mut other_worker_peer_ids = HashSet::new();
         for (public_key, address) in other_workers {
             let (peer_id, address) = Self::add_peer_in_network(&network, public_key, &address);
+            other_worker_peer_ids.insert(peer_id);
             peer_types.insert(peer_id, "other_worker".to_string());
             info!(
                 "Adding others workers with peer id {} and address {}",
@@ -325,6 +460,57 @@ impl Worker {
             peer_types,
         );
 
+        // The other workers and our primary may move to new addresses during the epoch.
+        let peer_discovery_handle = PeerDiscovery::spawn(
+            network.downgrade(),
+            rx_peer_addresses,
+            UNREACHABLE_PEER_RETRY_INTERVAL,
+            shutdown_receivers.pop().unwrap(),
+        );
+
+        let batch_compression_negotiator_handle = BatchCompressionNegotiator::spawn(
+            network.downgrade(),
+            other_worker_peer_ids.clone(),
+            batch_compressions.clone(),
+            shutdown_receivers.pop().unwrap(),
+        );
+
+        let batch_diff_synchronizer_handle = BatchDiffSynchronizer::spawn(
+            id,
+            network.downgrade(),
+            other_worker_peer_ids,
+            worker.store.clone(),
+            recent_batches.clone(),
+            validator.clone(),
+            tx_others_batch,
+            parameters.batch_diff_sync.clone(),
+            node_metrics.clone(),
+            shutdown_receivers.pop().unwrap(),
+        );
+
+        let batch_chunk_forwarder_handle = BatchChunkForwarder::spawn(
+            authority.clone(),
+            id,
+            worker.worker_cache.clone(),
+            network.clone(),
+            connection_manager.clone(),
+            parameters.erasure_coding.reconstruction_timeout,
+            rx_forward_chunks,
+            shutdown_receivers.pop().unwrap(),
+        );
+
+        let batch_relay_handle = BatchRelay::spawn(
+            authority.clone(),
+            id,
+            worker.worker_cache.clone(),
+            network.clone(),
+            connection_manager.clone(),
+            batch_compressions.clone(),
+            parameters.topology_hints.relay_ack_timeout,
+            rx_relay_batches,
+            shutdown_receivers.pop().unwrap(),
+        );
+
         let network_admin_server_base_port = parameters
             .network_admin_server
             .worker_network_admin_server_base_port
@@ -335,9 +521,14 @@ impl Worker {
             id, network_admin_server_base_port
         );
 
-        let admin_handles = network::admin::start_admin_server(
+        // The adaptive sealing parameters can be tuned through the admin server.
+        let (tx_sealing_parameters, rx_sealing_parameters) =
+            watch::channel(parameters.adaptive_sealing.clone());
+        let admin_handles = network::admin::start_admin_server_with_routes(
             network_admin_server_base_port,
             network.clone(),
+            adaptive_sealing::routes(Arc::new(tx_sealing_parameters))
+                .merge(network::admin::peer_metrics_routes(peer_metrics)),
             shutdown_receivers.pop().unwrap(),
         );
 
@@ -347,6 +538,8 @@ impl Worker {
             rx_our_batch,
             rx_others_batch,
             network.clone(),
+            replication_log,
+            recent_batches,
         );
         let client_flow_handles = worker.handle_clients_transactions(
             vec![
@@ -360,6 +553,11 @@ impl Worker {
             endpoint_metrics,
             validator,
             network.clone(),
+            batch_compressions,
+            connection_manager,
+            drain,
+            rx_sealing_parameters,
+            transaction_status,
         );
 
         let network_shutdown_handle =
@@ -379,6 +577,11 @@ impl Worker {
         let mut handles = vec![
             primary_connector_handle,
             connection_monitor_handle,
+            peer_discovery_handle,
+            batch_compression_negotiator_handle,
+            batch_diff_synchronizer_handle,
+            batch_chunk_forwarder_handle,
+            batch_relay_handle,
             network_shutdown_handle,
         ];
         handles.extend(admin_handles);
@@ -438,8 +641,24 @@ impl Worker {
         endpoint_metrics: WorkerEndpointMetrics,
         validator: impl TransactionValidator,
         network: anemo::Network,
+        batch_compressions: PeerBatchCompressions,
+        connection_manager: ConnectionManager,
+        drain: WorkerDrain,
+        rx_sealing_parameters: watch::Receiver<AdaptiveSealingParameters>,
+        transaction_status: TransactionStatusTracker,
     ) -> Vec<JoinHandle<()>> {
-        let (tx_batch_maker, rx_batch_maker) = channel_with_total(
+        let admission_controller = AdmissionController::new(
+            self.parameters.admission_control.clone(),
+            node_metrics.clone(),
+        );
+        let spill_queue = SpillQueue::reopen(
+            &self.store,
+            self.parameters.admission_control.max_spilled_bytes,
+            node_metrics.clone(),
+        );
+        let client_quotas =
+            ClientQuotas::new(self.parameters.client_quotas.clone(), node_metrics.clone());
+        let (tx_batch_maker, rx_batch_maker) = lane_channels(
             CHANNEL_CAPACITY,
             &channel_metrics.tx_batch_maker,
             &channel_metrics.tx_batch_maker_total,
@@ -464,8 +683,14 @@ impl Worker {
             address.clone(),
             shutdown_receivers.pop().unwrap(),
             endpoint_metrics,
-            tx_batch_maker,
-            validator,
+            tx_batch_maker.clone(),
+            admission_controller.clone(),
+            spill_queue.clone(),
+            drain.rx_draining.clone(),
+            client_quotas,
+            validator.clone(),
+            transaction_status.clone(),
+            sender_shard,
         );
 
         // The transactions are sent to the `BatchMaker` that assembles them into batches. It then broadcasts
@@ -473,14 +698,27 @@ impl Worker {
         // gathers the 'cancel handlers' of the messages and send them to the `QuorumWaiter`.
         let batch_maker_handle = BatchMaker::spawn(
             self.id,
+            self.committee.epoch(),
             self.parameters.batch_size,
-            self.parameters.max_batch_delay,
+            self.rx_parameters.clone(),
             shutdown_receivers.pop().unwrap(),
             rx_batch_maker,
             tx_quorum_waiter,
             node_metrics,
             self.store.clone(),
             tx_our_batch,
+            self.parameters.tx_dedup_window,
+            self.parameters.tx_dedup_cache_size,
+            self.parameters.lane_scheduling_policy.clone(),
+            self.parameters.batch_version,
+            self.parameters.batch_limits,
+            admission_controller,
+            validator,
+            drain,
+            self.parameters.worker_drain_timeout,
+            TransactionLog::reopen(&self.store, self.parameters.transaction_durability),
+            rx_sealing_parameters,
+            transaction_status,
         );
 
         // The `QuorumWaiter` waits for 2f authorities to acknowledge reception of the batch. It then forwards
@@ -493,6 +731,10 @@ impl Worker {
             shutdown_receivers.pop().unwrap(),
             rx_quorum_waiter,
             network,
+            batch_compressions,
+            connection_manager,
+            self.parameters.erasure_coding.clone(),
+            self.parameters.topology_hints.clone(),
         );
 
         info!(
@@ -500,6 +742,17 @@ impl Worker {
             self.id, address
         );
 
-        vec![batch_maker_handle, quorum_waiter_handle, tx_server_handle]
+        let spill_restorer_handle = spill_queue.spawn_restorer(
+            admission_controller.clone(),
+            tx_batch_maker,
+            shutdown_receivers.pop().unwrap(),
+        );
+
+        vec![
+            batch_maker_handle,
+            quorum_waiter_handle,
+            tx_server_handle,
+            spill_restorer_handle,
+        ]
     }
 }
@@ -0,0 +1,135 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs a cluster whose workers and primaries drop, delay and reorder each other's requests, and
+//! checks that no acked transaction is lost and that all the nodes execute the same sequence.
+//! The faults are seeded by the simulator seed, so that a failure is reproduced by running
+//! `MSIM_TEST_SEED=<seed> cargo simtest network_faults`.
+use bytes::Bytes;
+use config::{AnemoParameters, FaultInjectionParameters, Parameters};
+use futures::future::join_all;
+use rand::Rng;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use sui_macros::sim_test;
+use test_utils::cluster::Cluster;
+use tokio::time::{sleep, timeout};
+use tracing::info;
+use types::TransactionProto;
+
+const NUM_AUTHORITIES: usize = 4;
+const NUM_TRANSACTIONS: usize = 100;
+
+fn parameters(seed: u64, drop_probability: f64) -> Parameters {
+    Parameters {
+        batch_size: 200,
+        max_header_delay: Duration::from_secs(2),
+        anemo: AnemoParameters {
+            fault_injection: Some(FaultInjectionParameters {
+                seed,
+                drop_probability,
+                max_delay: Duration::from_millis(500),
+            }),
+            ..Default::default()
+        },
+        ..Parameters::default()
+    }
+}
+
+/// Submits the transactions round robin to the workers of the authorities, and returns the ones
+/// that were acked.
+async fn submit_transactions(cluster: &Cluster) -> HashSet<Vec<u8>> {
+    let mut submissions = Vec::new();
+    for i in 0..NUM_TRANSACTIONS {
+        let authority = cluster.authority(i % NUM_AUTHORITIES);
+        let mut client = authority.new_transactions_client(&0).await;
+        let transaction = format!("transaction {i}").into_bytes();
+        submissions.push(async move {
+            let request = TransactionProto {
+                transaction: Bytes::from(transaction.clone()),
+                ..Default::default()
+            };
+            client
+                .submit_transaction(request)
+                .await
+                .ok()
+                .map(|_| transaction)
+        });
+    }
+    join_all(submissions).await.into_iter().flatten().collect()
+}
+
+async fn check_invariants(drop_probability: f64) {
+    telemetry_subscribers::init_for_testing();
+    let seed = rand::thread_rng().gen();
+    info!("Injecting faults seeded with {seed}");
+
+    let mut cluster = Cluster::new(Some(parameters(seed, drop_probability)), true);
+    cluster.start(Some(NUM_AUTHORITIES), Some(1), None).await;
+
+    // Record the transactions executed by each node, in order.
+    let mut executed = Vec::new();
+    for authority in cluster.authorities().await {
+        let mut receiver = authority
+            .primary()
+            .await
+            .tx_transaction_confirmation
+            .subscribe();
+        let transactions = Arc::new(Mutex::new(Vec::new()));
+        let output = transactions.clone();
+        tokio::spawn(async move {
+            while let Ok(transaction) = receiver.recv().await {
+                output.lock().unwrap().push(transaction);
+            }
+        });
+        executed.push(transactions);
+    }
+
+    let acked = submit_transactions(&cluster).await;
+    assert!(!acked.is_empty(), "No transaction was acked");
+
+    // No acked transaction is lost: all of them are eventually executed by every node.
+    let all_executed = async {
+        loop {
+            let done = executed.iter().all(|transactions| {
+                let transactions = transactions.lock().unwrap();
+                acked.iter().all(|t| transactions.contains(t))
+            });
+            if done {
+                break;
+            }
+            sleep(Duration::from_secs(1)).await;
+        }
+    };
+    timeout(Duration::from_secs(300), all_executed)
+        .await
+        .unwrap_or_else(|_| panic!("Acked transactions were lost with seed {seed}"));
+
+    // The output is stable: every node executes the same sequence, which some nodes may be ahead
+    // of.
+    let sequences: Vec<_> = executed
+        .iter()
+        .map(|transactions| transactions.lock().unwrap().clone())
+        .collect();
+    for sequence in &sequences {
+        let len = sequence.len().min(sequences[0].len());
+        assert_eq!(
+            sequence[..len],
+            sequences[0][..len],
+            "The nodes executed different sequences with seed {seed}"
+        );
+    }
+}
+
+#[sim_test]
+async fn network_faults_delays_only() {
+    check_invariants(0.0).await;
+}
+
+#[sim_test]
+async fn network_faults_drops_and_delays() {
+    check_invariants(0.05).await;
+}